@@ -1,5 +1,5 @@
-# Hash: dd0bd2e84f938b4a
-# Generated: 2026-01-24T10:14:14.530639275
+# Hash: 605ae97f21e83e3e
+# Generated: 2026-10-18T16:04:12.263651517
 # Rust type: impact::command::UserCommand
 # Type category: Inline
 module [
@@ -25,36 +25,36 @@ write_bytes = |bytes, value|
     when value is
         Scene(val) ->
             bytes
-            |> List.reserve(34)
+            |> List.reserve(67)
             |> List.append(0)
             |> Command.SceneCommand.write_bytes(val)
-            |> List.concat(List.repeat(0, 16))
 
         Control(val) ->
             bytes
-            |> List.reserve(34)
+            |> List.reserve(67)
             |> List.append(1)
             |> Command.ControlCommand.write_bytes(val)
-            |> List.concat(List.repeat(0, 28))
+            |> List.concat(List.repeat(0, 61))
 
         Physics(val) ->
             bytes
-            |> List.reserve(34)
+            |> List.reserve(67)
             |> List.append(2)
             |> Command.PhysicsCommand.write_bytes(val)
+            |> List.concat(List.repeat(0, 33))
 
 ## Deserializes a value of [EngineCommand] from its bytes in the
 ## representation used by the engine.
 from_bytes : List U8 -> Result EngineCommand _
 from_bytes = |bytes|
-    if List.len(bytes) != 34 then
+    if List.len(bytes) != 67 then
         Err(InvalidNumberOfBytes)
     else
         when bytes is
             [0, .. as data_bytes] ->
                 Ok(
                     Scene(
-                        data_bytes |> List.sublist({ start: 0, len: 17 }) |> Command.SceneCommand.from_bytes?,
+                        data_bytes |> List.sublist({ start: 0, len: 66 }) |> Command.SceneCommand.from_bytes?,
                     ),
                 )
 
@@ -59,7 +59,7 @@ entity_ids = {
 
 setup! : {} => Result {} Str
 setup! = |_|
-    Command.execute!(Engine(Scene(SetSkybox(Skybox.new(skybox, 2e3)))))?
+    Command.execute!(Engine(Scene(SetSkybox(Skybox.cubemap(skybox, 2e3)))))?
 
     Entity.create_with_id!(player, entity_ids.player)?
     Entity.create_with_id!(camera, entity_ids.camera)?
@@ -78,7 +78,7 @@ setup! = |_|
 
     Ok({})
 
-skybox = Skybox.new(Texture.TextureID.from_name("ocean_skybox"), 1e5)
+skybox = Skybox.cubemap(Texture.TextureID.from_name("ocean_skybox"), 1e5)
 
 player =
     Entity.new_component_data
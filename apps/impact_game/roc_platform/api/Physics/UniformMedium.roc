@@ -1,5 +1,5 @@
-# Hash: 7bb801e13c4e7bb1
-# Generated: 2026-01-16T10:02:50.675953324
+# Hash: feb5051dbc479bc9
+# Generated: 2026-10-18T15:44:14.385225935
 # Rust type: impact_physics::medium::UniformMedium
 # Type category: Inline
 module [
@@ -16,6 +16,7 @@ module [
     from_bytes,
 ]
 
+import Physics.MediumOpticalProperties
 import core.Builtin
 import core.Vector3
 
@@ -25,6 +26,8 @@ UniformMedium : {
     mass_density : F32,
     ## The velocity of the medium.
     velocity : Vector3.Vector3,
+    ## How the medium absorbs and scatters light.
+    optical_properties : Physics.MediumOpticalProperties.MediumOpticalProperties,
 }
 
 ## Earth air mass density at sea level and room temperature [kg/m^3].
@@ -35,15 +38,17 @@ sea_level_air_mass_density = 1.2
 water_mass_density : F32
 water_mass_density = 1e3
 
-## Creates a new uniform medium with the given mass density and velocity.
-new : F32, Vector3.Vector3 -> UniformMedium
-new = |mass_density, velocity|
-    { mass_density, velocity }
+## Creates a new uniform medium with the given mass density, velocity and
+## optical properties.
+new : F32, Vector3.Vector3, Physics.MediumOpticalProperties.MediumOpticalProperties -> UniformMedium
+new = |mass_density, velocity, optical_properties|
+    { mass_density, velocity, optical_properties }
 
-## Creates a new vacuum medium (zero mass density and velocity).
+## Creates a new vacuum medium (zero mass density and velocity, fully
+## transparent).
 vacuum : {} -> UniformMedium
 vacuum = |{}|
-    new(0.0, Vector3.zeros)
+    new(0.0, Vector3.zeros, Physics.MediumOpticalProperties.transparent({}))
 
 ## Creates a new medium of Earth air at sea level and room temperature with
 ## no wind.
@@ -55,7 +60,7 @@ still_air = |{}|
 ## the given wind velocity.
 moving_air : Vector3.Vector3 -> UniformMedium
 moving_air = |velocity|
-    new(sea_level_air_mass_density, velocity)
+    new(sea_level_air_mass_density, velocity, Physics.MediumOpticalProperties.hazy_air({}))
 
 ## Creates a new medium of water with no flow.
 still_water : {} -> UniformMedium
@@ -65,16 +70,17 @@ still_water = |{}|
 ## Creates a new medium of water with the given flow velocity.
 moving_water : Vector3.Vector3 -> UniformMedium
 moving_water = |velocity|
-    new(water_mass_density, velocity)
+    new(water_mass_density, velocity, Physics.MediumOpticalProperties.murky_water({}))
 
 ## Serializes a value of [UniformMedium] into the binary representation
 ## expected by the engine and appends the bytes to the list.
 write_bytes : List U8, UniformMedium -> List U8
 write_bytes = |bytes, value|
     bytes
-    |> List.reserve(16)
+    |> List.reserve(36)
     |> Builtin.write_bytes_f32(value.mass_density)
     |> Vector3.write_bytes(value.velocity)
+    |> Physics.MediumOpticalProperties.write_bytes(value.optical_properties)
 
 ## Deserializes a value of [UniformMedium] from its bytes in the
 ## representation used by the engine.
@@ -84,12 +90,13 @@ from_bytes = |bytes|
         {
             mass_density: bytes |> List.sublist({ start: 0, len: 4 }) |> Builtin.from_bytes_f32?,
             velocity: bytes |> List.sublist({ start: 4, len: 12 }) |> Vector3.from_bytes?,
+            optical_properties: bytes |> List.sublist({ start: 16, len: 20 }) |> Physics.MediumOpticalProperties.from_bytes?,
         },
     )
 
 test_roundtrip : {} -> Result {} _
 test_roundtrip = |{}|
-    bytes = List.range({ start: At 0, end: Length 16 }) |> List.map(|b| Num.to_u8(b))
+    bytes = List.range({ start: At 0, end: Length 36 }) |> List.map(|b| Num.to_u8(b))
     decoded = from_bytes(bytes)?
     encoded = write_bytes([], decoded)
     if List.len(bytes) == List.len(encoded) and List.map2(bytes, encoded, |a, b| a == b) |> List.all(|eq| eq) then
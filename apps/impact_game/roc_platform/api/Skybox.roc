@@ -1,60 +1,88 @@
-# Hash: 619962c1f8f157bc
-# Generated: 2026-01-01T09:41:16.584947407
+# Hash: 823082ba524d4737
+# Generated: 2026-10-18T16:04:12.283206943
 # Rust type: impact_scene::skybox::Skybox
-# Type category: POD
+# Type category: Inline
 module [
     Skybox,
-    new,
+    cubemap,
+    atmospheric,
     write_bytes,
     from_bytes,
 ]
 
+import Atmosphere
 import Texture.TextureID
 import core.Builtin
 
-## A skybox specified by a cubemap texture and a maximum luminance (the
-## luminance that a texel value of unity should be mapped to).
-Skybox : {
-    cubemap_texture_id : Texture.TextureID.TextureID,
-    max_luminance : F64,
-}
+## A skybox, which is either specified by a cubemap texture or computed
+## procedurally from scattering of sunlight in an atmosphere.
+Skybox : [
+    ## A skybox specified by a cubemap texture and a maximum luminance (the
+    ## luminance that a texel value of unity should be mapped to).
+    Cubemap {
+            cubemap_texture_id : Texture.TextureID.TextureID,
+            max_luminance : F64,
+        },
+    ## A sky computed from the scattering of light from the scene's sun (its
+    ## brightest unidirectional light) in the given atmosphere. The sky is
+    ## recomputed whenever the sun moves, and its illuminance is added to the
+    ## scene's ambient lighting.
+    Atmospheric Atmosphere.Atmosphere,
+]
 
 ## Creates a new skybox with the given cubemap texture and maximum
 ## luminance.
-new : Texture.TextureID.TextureID, F64 -> Skybox
-new = |cubemap_texture_id, max_luminance|
-    { cubemap_texture_id, max_luminance }
+cubemap : Texture.TextureID.TextureID, F64 -> Skybox
+cubemap = |cubemap_texture_id, max_luminance|
+    Cubemap { cubemap_texture_id, max_luminance }
+
+## Creates a new skybox computed from the scattering of sunlight in the
+## given atmosphere.
+atmospheric : Atmosphere.Atmosphere -> Skybox
+atmospheric = |atmosphere|
+    Atmospheric(atmosphere)
 
 ## Serializes a value of [Skybox] into the binary representation
 ## expected by the engine and appends the bytes to the list.
 write_bytes : List U8, Skybox -> List U8
 write_bytes = |bytes, value|
-    bytes
-    |> List.reserve(16)
-    |> Texture.TextureID.write_bytes(value.cubemap_texture_id)
-    |> Builtin.write_bytes_f64(value.max_luminance)
+    when value is
+        Cubemap { cubemap_texture_id, max_luminance } ->
+            bytes
+            |> List.reserve(65)
+            |> List.append(0)
+            |> Texture.TextureID.write_bytes(cubemap_texture_id)
+            |> Builtin.write_bytes_f64(max_luminance)
+            |> List.concat(List.repeat(0, 48))
+
+        Atmospheric(val) ->
+            bytes
+            |> List.reserve(65)
+            |> List.append(1)
+            |> Atmosphere.write_bytes(val)
 
 ## Deserializes a value of [Skybox] from its bytes in the
 ## representation used by the engine.
 from_bytes : List U8 -> Result Skybox _
 from_bytes = |bytes|
-    Ok(
-        {
-            cubemap_texture_id: bytes |> List.sublist({ start: 0, len: 8 }) |> Texture.TextureID.from_bytes?,
-            max_luminance: bytes |> List.sublist({ start: 8, len: 8 }) |> Builtin.from_bytes_f64?,
-        },
-    )
-
-test_roundtrip : {} -> Result {} _
-test_roundtrip = |{}|
-    bytes = List.range({ start: At 0, end: Length 16 }) |> List.map(|b| Num.to_u8(b))
-    decoded = from_bytes(bytes)?
-    encoded = write_bytes([], decoded)
-    if List.len(bytes) == List.len(encoded) and List.map2(bytes, encoded, |a, b| a == b) |> List.all(|eq| eq) then
-        Ok({})
+    if List.len(bytes) != 65 then
+        Err(InvalidNumberOfBytes)
     else
-        Err(NotEqual(encoded, bytes))
+        when bytes is
+            [0, .. as data_bytes] ->
+                Ok(
+                    Cubemap     {
+                        cubemap_texture_id: data_bytes |> List.sublist({ start: 0, len: 8 }) |> Texture.TextureID.from_bytes?,
+                        max_luminance: data_bytes |> List.sublist({ start: 8, len: 8 }) |> Builtin.from_bytes_f64?,
+                    },
+                )
+
+            [1, .. as data_bytes] ->
+                Ok(
+                    Atmospheric(
+                        data_bytes |> List.sublist({ start: 0, len: 64 }) |> Atmosphere.from_bytes?,
+                    ),
+                )
 
-expect
-    result = test_roundtrip({})
-    result |> Result.is_ok
+            [] -> Err(MissingDiscriminant)
+            [discr, ..] -> Err(InvalidDiscriminant(discr))
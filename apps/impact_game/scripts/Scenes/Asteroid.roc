@@ -57,7 +57,7 @@ star = {
 setup! : SetupContext => Result {} Str
 setup! = |ctx|
     skybox_texture_id = Texture.TextureID.from_name(skybox.texture)
-    Command.execute!(Engine(Scene(SetSkybox(Skybox.new(skybox_texture_id, skybox.max_luminance)))))?
+    Command.execute!(Engine(Scene(SetSkybox(Skybox.cubemap(skybox_texture_id, skybox.max_luminance)))))?
 
     Command.execute!(Engine(Physics(SetGravitationalConstant(1e-4))))?
 
@@ -42,7 +42,7 @@ ambient_light = {
 setup! : SetupContext, Generation.SolarSystem.System => Result {} Str
 setup! = |ctx, system|
     skybox_texture_id = Texture.TextureID.from_name(skybox.texture)
-    Command.execute!(Engine(Scene(SetSkybox(Skybox.new(skybox_texture_id, skybox.max_luminance)))))?
+    Command.execute!(Engine(Scene(SetSkybox(Skybox.cubemap(skybox_texture_id, skybox.max_luminance)))))?
 
     Command.execute!(Engine(Physics(SetGravitationalConstant(system.properties.grav_const))))?
 
@@ -0,0 +1,128 @@
+# Hash: 041b2bddfabce9b6
+# Generated: 2026-10-18T16:04:12.299333237
+# Rust type: impact_scene::skybox::atmosphere::Atmosphere
+# Type category: Inline
+module [
+    Atmosphere,
+    earth,
+    write_bytes,
+    from_bytes,
+]
+
+import core.Builtin
+import core.Vector3
+
+## A planetary atmosphere that scatters and absorbs light from the sun. The
+## atmosphere is modeled as a spherical shell around the planet containing air
+## molecules (causing Rayleigh scattering) and aerosols (causing Mie
+## scattering) whose densities decrease exponentially with altitude, as well
+## as a layer of ozone (causing absorption).
+##
+## The local vertical direction at the observer is taken to be the world
+## y-axis.
+Atmosphere : {
+    ## The radius of the planet's surface [m].
+    planet_radius : F32,
+    ## The thickness of the atmosphere above the surface [m].
+    atmosphere_height : F32,
+    ## The altitude of the observer above the surface [m].
+    observer_altitude : F32,
+    ## The Rayleigh scattering coefficient at the surface for each RGB
+    ## channel [1/m].
+    rayleigh_scattering_coefficients : Vector3.Vector3,
+    ## The increase in altitude over which the density of Rayleigh scatterers
+    ## decreases by a factor of e [m].
+    rayleigh_scale_height : F32,
+    ## The Mie scattering coefficient at the surface [1/m].
+    mie_scattering_coefficient : F32,
+    ## The Mie extinction (scattering plus absorption) coefficient at the
+    ## surface [1/m].
+    mie_extinction_coefficient : F32,
+    ## The increase in altitude over which the density of Mie scatterers
+    ## decreases by a factor of e [m].
+    mie_scale_height : F32,
+    ## The asymmetry parameter of the Mie phase function. Ranges from -1 (all
+    ## light is scattered backward) to 1 (all light is scattered forward).
+    mie_anisotropy : F32,
+    ## The ozone absorption coefficient at the peak of the ozone layer for
+    ## each RGB channel [1/m].
+    ozone_absorption_coefficients : Vector3.Vector3,
+    ## The altitude of the peak of the ozone layer [m].
+    ozone_peak_altitude : F32,
+    ## The distance above and below the peak of the ozone layer at which the
+    ## ozone density reaches zero [m].
+    ozone_half_thickness : F32,
+}
+
+## Creates the atmosphere of the Earth, observed from the given altitude
+## above the surface.
+earth : F32 -> Atmosphere
+earth = |observer_altitude|
+    {
+        planet_radius: 6.36e6,
+        atmosphere_height: 1e5,
+        observer_altitude,
+        rayleigh_scattering_coefficients: (5.802e-6, 13.558e-6, 33.1e-6),
+        rayleigh_scale_height: 8e3,
+        mie_scattering_coefficient: 3.996e-6,
+        mie_extinction_coefficient: 4.4e-6,
+        mie_scale_height: 1.2e3,
+        mie_anisotropy: 0.8,
+        ozone_absorption_coefficients: (0.65e-6, 1.881e-6, 0.085e-6),
+        ozone_peak_altitude: 2.5e4,
+        ozone_half_thickness: 1.5e4,
+    }
+
+## Serializes a value of [Atmosphere] into the binary representation
+## expected by the engine and appends the bytes to the list.
+write_bytes : List U8, Atmosphere -> List U8
+write_bytes = |bytes, value|
+    bytes
+    |> List.reserve(64)
+    |> Builtin.write_bytes_f32(value.planet_radius)
+    |> Builtin.write_bytes_f32(value.atmosphere_height)
+    |> Builtin.write_bytes_f32(value.observer_altitude)
+    |> Vector3.write_bytes(value.rayleigh_scattering_coefficients)
+    |> Builtin.write_bytes_f32(value.rayleigh_scale_height)
+    |> Builtin.write_bytes_f32(value.mie_scattering_coefficient)
+    |> Builtin.write_bytes_f32(value.mie_extinction_coefficient)
+    |> Builtin.write_bytes_f32(value.mie_scale_height)
+    |> Builtin.write_bytes_f32(value.mie_anisotropy)
+    |> Vector3.write_bytes(value.ozone_absorption_coefficients)
+    |> Builtin.write_bytes_f32(value.ozone_peak_altitude)
+    |> Builtin.write_bytes_f32(value.ozone_half_thickness)
+
+## Deserializes a value of [Atmosphere] from its bytes in the
+## representation used by the engine.
+from_bytes : List U8 -> Result Atmosphere _
+from_bytes = |bytes|
+    Ok(
+        {
+            planet_radius: bytes |> List.sublist({ start: 0, len: 4 }) |> Builtin.from_bytes_f32?,
+            atmosphere_height: bytes |> List.sublist({ start: 4, len: 4 }) |> Builtin.from_bytes_f32?,
+            observer_altitude: bytes |> List.sublist({ start: 8, len: 4 }) |> Builtin.from_bytes_f32?,
+            rayleigh_scattering_coefficients: bytes |> List.sublist({ start: 12, len: 12 }) |> Vector3.from_bytes?,
+            rayleigh_scale_height: bytes |> List.sublist({ start: 24, len: 4 }) |> Builtin.from_bytes_f32?,
+            mie_scattering_coefficient: bytes |> List.sublist({ start: 28, len: 4 }) |> Builtin.from_bytes_f32?,
+            mie_extinction_coefficient: bytes |> List.sublist({ start: 32, len: 4 }) |> Builtin.from_bytes_f32?,
+            mie_scale_height: bytes |> List.sublist({ start: 36, len: 4 }) |> Builtin.from_bytes_f32?,
+            mie_anisotropy: bytes |> List.sublist({ start: 40, len: 4 }) |> Builtin.from_bytes_f32?,
+            ozone_absorption_coefficients: bytes |> List.sublist({ start: 44, len: 12 }) |> Vector3.from_bytes?,
+            ozone_peak_altitude: bytes |> List.sublist({ start: 56, len: 4 }) |> Builtin.from_bytes_f32?,
+            ozone_half_thickness: bytes |> List.sublist({ start: 60, len: 4 }) |> Builtin.from_bytes_f32?,
+        },
+    )
+
+test_roundtrip : {} -> Result {} _
+test_roundtrip = |{}|
+    bytes = List.range({ start: At 0, end: Length 64 }) |> List.map(|b| Num.to_u8(b))
+    decoded = from_bytes(bytes)?
+    encoded = write_bytes([], decoded)
+    if List.len(bytes) == List.len(encoded) and List.map2(bytes, encoded, |a, b| a == b) |> List.all(|eq| eq) then
+        Ok({})
+    else
+        Err(NotEqual(encoded, bytes))
+
+expect
+    result = test_roundtrip({})
+    result |> Result.is_ok
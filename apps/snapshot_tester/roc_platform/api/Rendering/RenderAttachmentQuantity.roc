@@ -1,5 +1,5 @@
-# Hash: e3d03d06c286987b
-# Generated: 2025-12-29T23:55:22.755341756
+# Hash: f349f9d00128070c
+# Generated: 2026-10-18T21:30:01.702831000
 # Rust type: impact_rendering::attachment::RenderAttachmentQuantity
 # Type category: Inline
 module [
@@ -21,6 +21,10 @@ RenderAttachmentQuantity : [
     LuminanceHistory,
     PreviousLuminanceHistory,
     Occlusion,
+    EntityID,
+    HierarchicalDepth,
+    ReflectedLuminanceHistory,
+    PreviousReflectedLuminanceHistory,
 ]
 
 ## Serializes a value of [RenderAttachmentQuantity] into the binary representation
@@ -83,6 +87,26 @@ write_bytes = |bytes, value|
             |> List.reserve(1)
             |> List.append(10)
 
+        EntityID ->
+            bytes
+            |> List.reserve(1)
+            |> List.append(11)
+
+        HierarchicalDepth ->
+            bytes
+            |> List.reserve(1)
+            |> List.append(12)
+
+        ReflectedLuminanceHistory ->
+            bytes
+            |> List.reserve(1)
+            |> List.append(13)
+
+        PreviousReflectedLuminanceHistory ->
+            bytes
+            |> List.reserve(1)
+            |> List.append(14)
+
 ## Deserializes a value of [RenderAttachmentQuantity] from its bytes in the
 ## representation used by the engine.
 from_bytes : List U8 -> Result RenderAttachmentQuantity _
@@ -102,5 +126,9 @@ from_bytes = |bytes|
             [8, ..] -> Ok(LuminanceHistory)
             [9, ..] -> Ok(PreviousLuminanceHistory)
             [10, ..] -> Ok(Occlusion)
+            [11, ..] -> Ok(EntityID)
+            [12, ..] -> Ok(HierarchicalDepth)
+            [13, ..] -> Ok(ReflectedLuminanceHistory)
+            [14, ..] -> Ok(PreviousReflectedLuminanceHistory)
             [] -> Err(MissingDiscriminant)
             [discr, ..] -> Err(InvalidDiscriminant(discr))
@@ -1,5 +1,5 @@
-# Hash: ec736be43d48522d
-# Generated: 2026-01-21T20:16:42.986729253
+# Hash: b809113794391fd3
+# Generated: 2026-10-18T16:04:12.316620676
 # Rust type: impact::command::scene::SceneCommand
 # Type category: Inline
 module [
@@ -32,36 +32,37 @@ write_bytes = |bytes, value|
     when value is
         SetActiveCamera { entity_id } ->
             bytes
-            |> List.reserve(17)
+            |> List.reserve(66)
             |> List.append(0)
             |> Entity.write_bytes_id(entity_id)
-            |> List.concat(List.repeat(0, 8))
+            |> List.concat(List.repeat(0, 57))
 
         SetSkybox(val) ->
             bytes
-            |> List.reserve(17)
+            |> List.reserve(66)
             |> List.append(1)
             |> Skybox.write_bytes(val)
 
         SetMedium(val) ->
             bytes
-            |> List.reserve(17)
+            |> List.reserve(66)
             |> List.append(2)
             |> Physics.UniformMedium.write_bytes(val)
+            |> List.concat(List.repeat(0, 29))
 
         SetSceneEntityActiveState { entity_id, state } ->
             bytes
-            |> List.reserve(17)
+            |> List.reserve(66)
             |> List.append(3)
             |> Entity.write_bytes_id(entity_id)
             |> Command.ActiveState.write_bytes(state)
-            |> List.concat(List.repeat(0, 7))
+            |> List.concat(List.repeat(0, 56))
 
 ## Deserializes a value of [SceneCommand] from its bytes in the
 ## representation used by the engine.
 from_bytes : List U8 -> Result SceneCommand _
 from_bytes = |bytes|
-    if List.len(bytes) != 17 then
+    if List.len(bytes) != 66 then
         Err(InvalidNumberOfBytes)
     else
         when bytes is
@@ -75,14 +76,14 @@ from_bytes = |bytes|
             [1, .. as data_bytes] ->
                 Ok(
                     SetSkybox(
-                        data_bytes |> List.sublist({ start: 0, len: 16 }) |> Skybox.from_bytes?,
+                        data_bytes |> List.sublist({ start: 0, len: 65 }) |> Skybox.from_bytes?,
                     ),
                 )
 
             [2, .. as data_bytes] ->
                 Ok(
                     SetMedium(
-                        data_bytes |> List.sublist({ start: 0, len: 16 }) |> Physics.UniformMedium.from_bytes?,
+                        data_bytes |> List.sublist({ start: 0, len: 36 }) |> Physics.UniformMedium.from_bytes?,
                     ),
                 )
 
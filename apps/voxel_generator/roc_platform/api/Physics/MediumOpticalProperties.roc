@@ -0,0 +1,93 @@
+# Hash: 972fbca3e9dbfeed
+# Generated: 2026-10-18T15:44:14.426542847
+# Rust type: impact_physics::medium::MediumOpticalProperties
+# Type category: Inline
+module [
+    MediumOpticalProperties,
+    new,
+    transparent,
+    hazy_air,
+    murky_water,
+    write_bytes,
+    from_bytes,
+]
+
+import core.Builtin
+import core.Vector3
+
+## Properties determining how light is absorbed and scattered when travelling
+## through a medium.
+MediumOpticalProperties : {
+    ## The fraction of the light in a beam that is absorbed or scattered out
+    ## of the beam per distance travelled through the medium [1/m].
+    extinction_coefficient : F32,
+    ## The fraction of the extinguished light that is scattered rather than
+    ## absorbed, for each RGB channel. This determines the color of the
+    ## medium.
+    scattering_albedo : Vector3.Vector3,
+    ## The asymmetry parameter of the Henyey-Greenstein phase function
+    ## describing the directional distribution of scattered light. Ranges
+    ## from -1 (all light is scattered backward) to 1 (all light is scattered
+    ## forward), with 0 corresponding to isotropic scattering.
+    anisotropy : F32,
+}
+
+## Creates a new set of optical properties with the given extinction
+## coefficient, scattering albedo and scattering anisotropy.
+new : F32, Vector3.Vector3, F32 -> MediumOpticalProperties
+new = |extinction_coefficient, scattering_albedo, anisotropy|
+    { extinction_coefficient, scattering_albedo, anisotropy }
+
+## Creates optical properties for a medium that does not interact with
+## light.
+transparent : {} -> MediumOpticalProperties
+transparent = |{}|
+    new(0.0, (1.0, 1.0, 1.0), 0.0)
+
+## Creates optical properties for slightly hazy Earth air, which scatters
+## light mostly forward without absorbing it.
+hazy_air : {} -> MediumOpticalProperties
+hazy_air = |{}|
+    new(1e-4, (1.0, 1.0, 1.0), 0.7)
+
+## Creates optical properties for murky water, which absorbs red light
+## more strongly than green and blue light and scatters strongly forward.
+murky_water : {} -> MediumOpticalProperties
+murky_water = |{}|
+    new(0.1, (0.3, 0.7, 0.8), 0.8)
+
+## Serializes a value of [MediumOpticalProperties] into the binary representation
+## expected by the engine and appends the bytes to the list.
+write_bytes : List U8, MediumOpticalProperties -> List U8
+write_bytes = |bytes, value|
+    bytes
+    |> List.reserve(20)
+    |> Builtin.write_bytes_f32(value.extinction_coefficient)
+    |> Vector3.write_bytes(value.scattering_albedo)
+    |> Builtin.write_bytes_f32(value.anisotropy)
+
+## Deserializes a value of [MediumOpticalProperties] from its bytes in the
+## representation used by the engine.
+from_bytes : List U8 -> Result MediumOpticalProperties _
+from_bytes = |bytes|
+    Ok(
+        {
+            extinction_coefficient: bytes |> List.sublist({ start: 0, len: 4 }) |> Builtin.from_bytes_f32?,
+            scattering_albedo: bytes |> List.sublist({ start: 4, len: 12 }) |> Vector3.from_bytes?,
+            anisotropy: bytes |> List.sublist({ start: 16, len: 4 }) |> Builtin.from_bytes_f32?,
+        },
+    )
+
+test_roundtrip : {} -> Result {} _
+test_roundtrip = |{}|
+    bytes = List.range({ start: At 0, end: Length 20 }) |> List.map(|b| Num.to_u8(b))
+    decoded = from_bytes(bytes)?
+    encoded = write_bytes([], decoded)
+    if List.len(bytes) == List.len(encoded) and List.map2(bytes, encoded, |a, b| a == b) |> List.all(|eq| eq) then
+        Ok({})
+    else
+        Err(NotEqual(encoded, bytes))
+
+expect
+    result = test_roundtrip({})
+    result |> Result.is_ok
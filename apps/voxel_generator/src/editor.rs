@@ -22,7 +22,7 @@ use impact_voxel::{
         SDFVoxelGenerator, VoxelGeneratorRef,
         voxel_type::{SameVoxelTypeGenerator, VoxelTypeGenerator},
     },
-    voxel_types::VoxelType,
+    voxel_types::{VoxelType, VoxelTypeRegistry},
 };
 use meta::{
     MetaNodeData, MetaNodeID, build,
@@ -62,6 +62,7 @@ pub struct Editor {
     rebuild_generator: bool,
     graph_status: MetaGraphStatus,
     last_graph_path: Option<GraphPath>,
+    pending_mesh_export_path: Option<PathBuf>,
     voxel_type: VoxelType,
     config: EditorConfig,
 }
@@ -106,6 +107,7 @@ impl Editor {
             rebuild_generator: false,
             graph_status: MetaGraphStatus::Incomplete,
             last_graph_path: None,
+            pending_mesh_export_path: None,
             voxel_type: VoxelType::from_idx(0),
             config,
         }
@@ -114,6 +116,7 @@ impl Editor {
     pub fn build_next_voxel_sdf_generator<A: Allocator>(
         &mut self,
         alloc: A,
+        voxel_type_registry: &VoxelTypeRegistry,
     ) -> Option<SDFVoxelGenerator<A>> {
         if !(self.graph_needs_compilation || self.rebuild_generator) {
             return None;
@@ -153,8 +156,20 @@ impl Editor {
         let voxel_type_generator =
             VoxelTypeGenerator::Same(SameVoxelTypeGenerator::new(self.voxel_type));
 
-        let generator =
-            build::build_sdf_voxel_generator(alloc, &compiled_graph, voxel_type_generator);
+        let generator = match build::build_sdf_voxel_generator(
+            alloc,
+            &compiled_graph,
+            voxel_type_generator,
+            voxel_type_registry,
+        ) {
+            Ok(generator) => generator,
+            Err(error) => {
+                self.graph_status = MetaGraphStatus::Invalid {
+                    error: format!("The graph is invalid: {error:#}"),
+                };
+                return None;
+            }
+        };
 
         self.graph_status = MetaGraphStatus::InSync;
 
@@ -164,11 +179,19 @@ impl Editor {
     pub fn build_next_voxel_sdf_generator_or_default<A: Allocator>(
         &mut self,
         alloc: A,
+        voxel_type_registry: &VoxelTypeRegistry,
     ) -> SDFVoxelGenerator<A> {
-        self.build_next_voxel_sdf_generator(alloc)
+        self.build_next_voxel_sdf_generator(alloc, voxel_type_registry)
             .unwrap_or_else(|| build::default_sdf_voxel_generator(alloc))
     }
 
+    /// Returns the path the user has chosen for exporting the mesh of the
+    /// current voxel object to, if an export has been requested since the last
+    /// call.
+    pub fn take_pending_mesh_export_path(&mut self) -> Option<PathBuf> {
+        self.pending_mesh_export_path.take()
+    }
+
     fn reset_canvas(&mut self) {
         self.meta_graph_canvas.reset();
         self.last_graph_path = None;
@@ -297,6 +320,17 @@ impl Editor {
             }
         }
     }
+
+    fn request_mesh_export(&mut self) {
+        if let Some(path_str) = tinyfiledialogs::save_file_dialog_with_filter(
+            "Export mesh as",
+            "",
+            &["*.obj", "*.ply", "*.gltf", "*.glb"],
+            "Mesh (*.obj, *.ply, *.gltf, *.glb)",
+        ) {
+            self.pending_mesh_export_path = Some(PathBuf::from(path_str));
+        }
+    }
 }
 
 impl CustomElements for Editor {
@@ -358,6 +392,17 @@ impl CustomElements for Editor {
                     self.export_graph_as_resource();
                 };
 
+                if ui
+                    .add_enabled(
+                        matches!(self.graph_status, MetaGraphStatus::InSync),
+                        Button::new("Export mesh..."),
+                    )
+                    .on_disabled_hover_text("Generate a valid object to enable exporting its mesh")
+                    .clicked()
+                {
+                    self.request_mesh_export();
+                };
+
                 ui.horizontal(|ui| {
                     if ui.button("Clear").clicked() {
                         self.reset_canvas();
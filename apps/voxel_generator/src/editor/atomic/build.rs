@@ -2,7 +2,7 @@ use super::AtomicNode;
 use impact::impact_alloc::Allocator;
 use impact_voxel::generation::sdf::{
     MultifractalNoiseSDFModifier, SDFGraph, SDFIntersection, SDFNode, SDFNodeID, SDFRotation,
-    SDFScaling, SDFSubtraction, SDFTranslation, SDFUnion,
+    SDFScaling, SDFSubtraction, SDFTranslation, SDFUnion, SDFVoxelTypeTag,
 };
 
 pub fn update_viewer_nodes<A: Allocator>(graph: &SDFGraph<A>, viewer_nodes: &mut Vec<AtomicNode>) {
@@ -18,6 +18,7 @@ pub fn update_viewer_nodes<A: Allocator>(graph: &SDFGraph<A>, viewer_nodes: &mut
         SDFNode::Union(node) => AtomicNode::for_union(node),
         SDFNode::Subtraction(node) => AtomicNode::for_subtraction(node),
         SDFNode::Intersection(node) => AtomicNode::for_intersection(node),
+        SDFNode::VoxelTypeTag(node) => AtomicNode::for_voxel_type_tag(node),
     }));
 
     for (idx, node) in graph.nodes().iter().enumerate() {
@@ -26,7 +27,8 @@ pub fn update_viewer_nodes<A: Allocator>(graph: &SDFGraph<A>, viewer_nodes: &mut
             SDFNode::Translation(SDFTranslation { child_id, .. })
             | SDFNode::Rotation(SDFRotation { child_id, .. })
             | SDFNode::Scaling(SDFScaling { child_id, .. })
-            | SDFNode::MultifractalNoise(MultifractalNoiseSDFModifier { child_id, .. }) => {
+            | SDFNode::MultifractalNoise(MultifractalNoiseSDFModifier { child_id, .. })
+            | SDFNode::VoxelTypeTag(SDFVoxelTypeTag { child_id, .. }) => {
                 let child_idx = *child_id as usize;
                 viewer_nodes[child_idx].parents.push(idx as SDFNodeID);
             }
@@ -1,7 +1,7 @@
 use super::{AtomicFloatParam, AtomicNode, AtomicNodeParams, AtomicPortConfig, AtomicUIntParam};
 use impact_voxel::generation::sdf::{
     BoxSDF, CapsuleSDF, MultifractalNoiseSDFModifier, SDFIntersection, SDFRotation, SDFScaling,
-    SDFSubtraction, SDFTranslation, SDFUnion, SphereSDF,
+    SDFSubtraction, SDFTranslation, SDFUnion, SDFVoxelTypeTag, SphereSDF,
 };
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -17,6 +17,7 @@ pub enum AtomicNodeKind {
     Union,
     Subtraction,
     Intersection,
+    VoxelTypeTag,
 }
 
 impl AtomicNode {
@@ -108,6 +109,12 @@ impl AtomicNode {
             node.child_2_id,
         )
     }
+
+    pub fn for_voxel_type_tag(node: &SDFVoxelTypeTag) -> Self {
+        let mut params = AtomicNodeParams::new();
+        params.push(AtomicUIntParam::new("Tag", u32::from(node.tag)).into());
+        Self::new_unary(AtomicNodeKind::VoxelTypeTag, params, node.child_id)
+    }
 }
 
 impl AtomicNodeKind {
@@ -124,6 +131,7 @@ impl AtomicNodeKind {
             Self::Union => "Union",
             Self::Subtraction => "Subtraction",
             Self::Intersection => "Intersection",
+            Self::VoxelTypeTag => "Voxel type tag",
         }
     }
 
@@ -131,9 +139,11 @@ impl AtomicNodeKind {
         match self {
             Self::Output => AtomicPortConfig::root(),
             Self::Sphere | Self::Capsule | Self::Box => AtomicPortConfig::leaf(),
-            Self::Translation | Self::Rotation | Self::Scaling | Self::MultifractalNoise => {
-                AtomicPortConfig::unary()
-            }
+            Self::Translation
+            | Self::Rotation
+            | Self::Scaling
+            | Self::MultifractalNoise
+            | Self::VoxelTypeTag => AtomicPortConfig::unary(),
             Self::Union | Self::Subtraction | Self::Intersection => AtomicPortConfig::binary(),
         }
     }
@@ -516,6 +516,14 @@ impl MetaPaletteColor {
             darker: Color32::from_rgb(122, 98, 0),     // #7A6200
         }
     }
+
+    pub const fn purple() -> Self {
+        Self {
+            standard: Color32::from_rgb(155, 89, 208), // #9B59D0
+            lighter: Color32::from_rgb(189, 145, 224), // #BD91E0
+            darker: Color32::from_rgb(83, 38, 120),    // #532678
+        }
+    }
 }
 
 impl CollapsedMetaSubgraph {
@@ -11,7 +11,7 @@ use impact_voxel::{
         sdf::{SDFGenerator, SDFGraph, meta::MetaSDFGraph},
         voxel_type::{SameVoxelTypeGenerator, VoxelTypeGenerator},
     },
-    voxel_types::VoxelType,
+    voxel_types::{VoxelType, VoxelTypeRegistry},
 };
 
 #[derive(Clone, Debug)]
@@ -30,14 +30,18 @@ pub fn build_sdf_voxel_generator<A: Allocator, AG: Allocator>(
     alloc: A,
     compiled_graph: &SDFGraphBuildResult<AG>,
     voxel_type_generator: VoxelTypeGenerator,
-) -> SDFVoxelGenerator<A> {
+    voxel_type_registry: &VoxelTypeRegistry,
+) -> Result<SDFVoxelGenerator<A>> {
     let sdf_generator = compiled_graph.graph.build_in(alloc).unwrap();
 
-    SDFVoxelGenerator::new(
+    let voxel_type_generator = voxel_type_generator
+        .with_graph(compiled_graph.graph.voxel_type_graph(), voxel_type_registry)?;
+
+    Ok(SDFVoxelGenerator::new(
         compiled_graph.voxel_extent,
         sdf_generator,
         voxel_type_generator,
-    )
+    ))
 }
 
 pub fn default_sdf_voxel_generator<A: Allocator>(alloc: A) -> SDFVoxelGenerator<A> {
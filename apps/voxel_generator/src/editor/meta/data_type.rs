@@ -10,6 +10,7 @@ pub enum ConcreteEdgeDataType {
     SingleSDF,
     SDFGroup,
     Instances,
+    VoxelTypes,
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
@@ -32,13 +33,16 @@ impl EdgeDataType {
                 MetaPaletteColor::yellow()
             }
             Self::Concrete(ConcreteEdgeDataType::Instances) => MetaPaletteColor::blue(),
+            Self::Concrete(ConcreteEdgeDataType::VoxelTypes) => MetaPaletteColor::purple(),
             Self::Undefined => MetaPaletteColor::green(),
         }
     }
 
     pub const fn port_shape(&self) -> MetaPortShape {
         match self {
-            Self::Concrete(ConcreteEdgeDataType::SingleSDF) => MetaPortShape::Circle,
+            Self::Concrete(ConcreteEdgeDataType::SingleSDF | ConcreteEdgeDataType::VoxelTypes) => {
+                MetaPortShape::Circle
+            }
             Self::Concrete(ConcreteEdgeDataType::SDFGroup | ConcreteEdgeDataType::Instances)
             | Self::Undefined => MetaPortShape::Square,
         }
@@ -49,6 +53,7 @@ impl EdgeDataType {
             Self::Concrete(ConcreteEdgeDataType::SingleSDF) => "SDF",
             Self::Concrete(ConcreteEdgeDataType::SDFGroup) => "SDF group",
             Self::Concrete(ConcreteEdgeDataType::Instances) => "Instances",
+            Self::Concrete(ConcreteEdgeDataType::VoxelTypes) => "Voxel types",
             Self::Undefined => "Not determined",
         }
     }
@@ -64,6 +69,7 @@ impl EdgeDataType {
                     Self::Concrete(SingleSDF | SDFGroup)
                 )
                 | (Self::Concrete(Instances), Self::Concrete(Instances))
+                | (Self::Concrete(VoxelTypes), Self::Concrete(VoxelTypes))
                 | (Self::Undefined, _)
                 | (_, Self::Undefined)
         )
@@ -80,6 +86,9 @@ pub fn input_and_output_types_for_new_node(
             MetaChildPortKind::SingleSDF => EdgeDataType::Concrete(ConcreteEdgeDataType::SingleSDF),
             MetaChildPortKind::SDFGroup => EdgeDataType::Concrete(ConcreteEdgeDataType::SDFGroup),
             MetaChildPortKind::Instances => EdgeDataType::Concrete(ConcreteEdgeDataType::Instances),
+            MetaChildPortKind::VoxelTypes => {
+                EdgeDataType::Concrete(ConcreteEdgeDataType::VoxelTypes)
+            }
             MetaChildPortKind::Any => EdgeDataType::Undefined,
         };
         input_data_types.push(data_type);
@@ -89,6 +98,7 @@ pub fn input_and_output_types_for_new_node(
         MetaParentPortKind::SingleSDF => EdgeDataType::Concrete(ConcreteEdgeDataType::SingleSDF),
         MetaParentPortKind::SDFGroup => EdgeDataType::Concrete(ConcreteEdgeDataType::SDFGroup),
         MetaParentPortKind::Instances => EdgeDataType::Concrete(ConcreteEdgeDataType::Instances),
+        MetaParentPortKind::VoxelTypes => EdgeDataType::Concrete(ConcreteEdgeDataType::VoxelTypes),
         MetaParentPortKind::SameAsInput { .. } => EdgeDataType::Undefined,
     };
 
@@ -135,6 +145,9 @@ pub fn update_edge_data_types(nodes: &mut NoHashMap<MetaNodeID, MetaNode>) {
                         MetaChildPortKind::Instances => {
                             EdgeDataType::Concrete(ConcreteEdgeDataType::Instances)
                         }
+                        MetaChildPortKind::VoxelTypes => {
+                            EdgeDataType::Concrete(ConcreteEdgeDataType::VoxelTypes)
+                        }
                         MetaChildPortKind::Any => {
                             if let Some(link) = node.links_to_children[slot]
                                 && let Some(child_node) = nodes.get(&link.to_node)
@@ -158,6 +171,9 @@ pub fn update_edge_data_types(nodes: &mut NoHashMap<MetaNodeID, MetaNode>) {
                     MetaParentPortKind::Instances => {
                         EdgeDataType::Concrete(ConcreteEdgeDataType::Instances)
                     }
+                    MetaParentPortKind::VoxelTypes => {
+                        EdgeDataType::Concrete(ConcreteEdgeDataType::VoxelTypes)
+                    }
                     MetaParentPortKind::SameAsInput { slot } => {
                         if let Some(link) = node.links_to_children[slot]
                             && let Some(child_node) = nodes.get(&link.to_node)
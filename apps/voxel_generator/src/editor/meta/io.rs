@@ -2,8 +2,8 @@ use super::{
     MetaNode, MetaNodeChildLinks, MetaNodeData, MetaNodeID, MetaNodeParentLinks,
     node_kind::MetaNodeKind,
     param::{
-        MetaDistributedParam, MetaEnumParam, MetaFloatParam, MetaNodeParam, MetaUIntParam,
-        ParamDistribution,
+        MetaDistributedParam, MetaEnumParam, MetaFloatParam, MetaNodeParam, MetaTextParam,
+        MetaUIntParam, ParamDistribution,
     },
 };
 use crate::editor::EditorConfig;
@@ -67,6 +67,7 @@ pub enum IOMetaNodeParam {
     UIntRange { low: u32, high: u32 },
     FloatRange { low: f32, high: f32 },
     Distributed(ParamDistribution),
+    Text(String),
 }
 
 impl IOMetaGraphKind {
@@ -159,6 +160,12 @@ impl TryFrom<IOMetaNode> for MetaNode {
                 ) => {
                     *distribution = io_distribution;
                 }
+                (
+                    MetaNodeParam::Text(MetaTextParam { value, .. }),
+                    IOMetaNodeParam::Text(io_value),
+                ) => {
+                    *value = io_value;
+                }
                 _ => {
                     bail!("Inconsistent parameter types");
                 }
@@ -194,6 +201,7 @@ impl<'a> From<&'a MetaNodeParam> for IOMetaNodeParam {
             MetaNodeParam::Distributed(MetaDistributedParam { distribution, .. }) => {
                 Self::Distributed(distribution.clone())
             }
+            MetaNodeParam::Text(MetaTextParam { value, .. }) => Self::Text(value.clone()),
         }
     }
 }
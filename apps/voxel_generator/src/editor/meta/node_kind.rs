@@ -2,7 +2,7 @@ use super::{
     MetaNodeID, MetaNodeLink,
     param::{
         EnumParamVariants, MetaDistributedParam, MetaEnumParam, MetaFloatParam, MetaNodeParam,
-        MetaNodeParams, MetaUIntParam,
+        MetaNodeParams, MetaTextParam, MetaUIntParam,
     },
 };
 use impact::impact_alloc::Allocator;
@@ -10,12 +10,13 @@ use impact_containers::NoHashMap;
 use impact_dev_ui::option_panels::LabelAndHoverText;
 use impact_voxel::generation::sdf::meta::{
     CompositionMode, MetaBoxes, MetaCapsules, MetaClosestTranslationToSurface,
-    MetaMultifractalNoiseSDFModifier, MetaPoints, MetaRayTranslationToSurface, MetaRotation,
+    MetaCurvatureVoxelTypeSelection, MetaDepthVoxelTypeSelection, MetaMultifractalNoiseSDFModifier,
+    MetaNoiseVoxelTypeSelection, MetaPoints, MetaRayTranslationToSurface, MetaRotation,
     MetaRotationToGradient, MetaSDFGroupUnion, MetaSDFInstantiation, MetaSDFIntersection,
     MetaSDFNode, MetaSDFNodeID, MetaSDFSubtraction, MetaSDFUnion, MetaScaling, MetaSimilarity,
-    MetaSphereSurfaceTransforms, MetaSpheres, MetaStochasticSelection,
-    MetaStratifiedGridTransforms, MetaTransformApplication, MetaTranslation, ParameterSamplingMode,
-    RayTranslationAnchor, SphereSurfaceRotation,
+    MetaSlopeVoxelTypeSelection, MetaSphereSurfaceTransforms, MetaSpheres, MetaStochasticSelection,
+    MetaStratifiedGridTransforms, MetaTransformApplication, MetaTranslation, MetaVoxelType,
+    MetaVoxelTypeAssignment, ParameterSamplingMode, RayTranslationAnchor, SphereSurfaceRotation,
 };
 use serde::{Deserialize, Serialize};
 
@@ -57,6 +58,12 @@ pub enum MetaNodeKind {
     SDFSubtraction,
     SDFIntersection,
     SDFGroupUnion,
+    VoxelType,
+    DepthVoxelTypeSelection,
+    SlopeVoxelTypeSelection,
+    CurvatureVoxelTypeSelection,
+    NoiseVoxelTypeSelection,
+    VoxelTypeAssignment,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -70,6 +77,7 @@ pub enum MetaNodeKindGroup {
     SDFFromInstances,
     SDFModifiers,
     SDFCombination,
+    VoxelTypes,
 }
 
 #[allow(dead_code)]
@@ -79,6 +87,7 @@ pub enum MetaChildPortKind {
     SingleSDF,
     SDFGroup,
     Instances,
+    VoxelTypes,
     Any,
 }
 
@@ -89,6 +98,7 @@ pub enum MetaParentPortKind {
     SingleSDF,
     SDFGroup,
     Instances,
+    VoxelTypes,
     SameAsInput {
         slot: usize,
     },
@@ -1462,8 +1472,252 @@ impl SpecificMetaNodeKind for MetaSDFGroupUnion {
     }
 }
 
+impl SpecificMetaNodeKind for MetaVoxelType {
+    const LABEL: LabelAndHoverText = LabelAndHoverText {
+        label: "Voxel type",
+        hover_text: "A single voxel type.",
+    };
+    const PARENT_PORT_KIND: MetaParentPortKind = MetaParentPortKind::VoxelTypes;
+    const CHILD_PORT_KINDS: MetaChildPortKinds = leaf_child_port_kind();
+
+    fn params() -> MetaNodeParams {
+        let mut params = MetaNodeParams::new();
+        params.push(MetaTextParam::new(
+            LabelAndHoverText {
+                label: "Name",
+                hover_text: "Name of the voxel type.",
+            },
+            "",
+        ));
+        params
+    }
+
+    fn build<A: Allocator>(
+        _id_map: &NoHashMap<MetaNodeID, MetaSDFNodeID, A>,
+        _children: &[Option<MetaNodeLink>],
+        params: &[MetaNodeParam],
+    ) -> Option<MetaSDFNode> {
+        assert_eq!(params.len(), 1);
+        Some(MetaSDFNode::VoxelType(MetaVoxelType {
+            voxel_type_name: (&params[0]).into(),
+        }))
+    }
+}
+
+impl SpecificMetaNodeKind for MetaDepthVoxelTypeSelection {
+    const LABEL: LabelAndHoverText = LabelAndHoverText {
+        label: "Depth selection",
+        hover_text: "Selection between voxel types based on the depth below the surface.",
+    };
+    const PARENT_PORT_KIND: MetaParentPortKind = MetaParentPortKind::VoxelTypes;
+    const CHILD_PORT_KINDS: MetaChildPortKinds =
+        two_child_port_kinds(MetaChildPortKind::VoxelTypes, MetaChildPortKind::VoxelTypes);
+
+    fn params() -> MetaNodeParams {
+        let mut params = MetaNodeParams::new();
+        params.push(
+            MetaFloatParam::new(
+                LabelAndHoverText {
+                    label: "Depth",
+                    hover_text: "Depth below the surface where the voxel types in the second input begin, in voxels.",
+                },
+                4.0,
+            )
+            .with_min_value(0.0),
+        );
+        params
+    }
+
+    fn build<A: Allocator>(
+        id_map: &NoHashMap<MetaNodeID, MetaSDFNodeID, A>,
+        children: &[Option<MetaNodeLink>],
+        params: &[MetaNodeParam],
+    ) -> Option<MetaSDFNode> {
+        assert_eq!(params.len(), 1);
+        let (surface_id, interior_id) = binary_children(id_map, children)?;
+        Some(MetaSDFNode::DepthVoxelTypeSelection(
+            MetaDepthVoxelTypeSelection {
+                surface_id,
+                interior_id,
+                depth: (&params[0]).into(),
+            },
+        ))
+    }
+}
+
+impl SpecificMetaNodeKind for MetaSlopeVoxelTypeSelection {
+    const LABEL: LabelAndHoverText = LabelAndHoverText {
+        label: "Slope selection",
+        hover_text: "Selection between voxel types based on the steepness of the surface.",
+    };
+    const PARENT_PORT_KIND: MetaParentPortKind = MetaParentPortKind::VoxelTypes;
+    const CHILD_PORT_KINDS: MetaChildPortKinds =
+        two_child_port_kinds(MetaChildPortKind::VoxelTypes, MetaChildPortKind::VoxelTypes);
+
+    fn params() -> MetaNodeParams {
+        let mut params = MetaNodeParams::new();
+        params.push(
+            MetaFloatParam::new(
+                LabelAndHoverText {
+                    label: "Max flat angle",
+                    hover_text: "Largest angle between the surface normal and the y-axis for which the voxel types in the first input are used, in degrees.",
+                },
+                45.0,
+            )
+            .with_min_value(0.0)
+            .with_max_value(180.0),
+        );
+        params
+    }
+
+    fn build<A: Allocator>(
+        id_map: &NoHashMap<MetaNodeID, MetaSDFNodeID, A>,
+        children: &[Option<MetaNodeLink>],
+        params: &[MetaNodeParam],
+    ) -> Option<MetaSDFNode> {
+        assert_eq!(params.len(), 1);
+        let (flat_id, steep_id) = binary_children(id_map, children)?;
+        Some(MetaSDFNode::SlopeVoxelTypeSelection(
+            MetaSlopeVoxelTypeSelection {
+                flat_id,
+                steep_id,
+                max_flat_angle: (&params[0]).into(),
+            },
+        ))
+    }
+}
+
+impl SpecificMetaNodeKind for MetaCurvatureVoxelTypeSelection {
+    const LABEL: LabelAndHoverText = LabelAndHoverText {
+        label: "Curvature selection",
+        hover_text: "Selection between voxel types based on the curvature of the surface.",
+    };
+    const PARENT_PORT_KIND: MetaParentPortKind = MetaParentPortKind::VoxelTypes;
+    const CHILD_PORT_KINDS: MetaChildPortKinds =
+        two_child_port_kinds(MetaChildPortKind::VoxelTypes, MetaChildPortKind::VoxelTypes);
+
+    fn params() -> MetaNodeParams {
+        let mut params = MetaNodeParams::new();
+        params.push(
+            MetaFloatParam::new(
+                LabelAndHoverText {
+                    label: "Threshold",
+                    hover_text: "Mean curvature above which the voxel types in the second input are used, in inverse voxels. Positive values are convex.",
+                },
+                0.0,
+            )
+            .with_speed(0.001),
+        );
+        params
+    }
+
+    fn build<A: Allocator>(
+        id_map: &NoHashMap<MetaNodeID, MetaSDFNodeID, A>,
+        children: &[Option<MetaNodeLink>],
+        params: &[MetaNodeParam],
+    ) -> Option<MetaSDFNode> {
+        assert_eq!(params.len(), 1);
+        let (concave_id, convex_id) = binary_children(id_map, children)?;
+        Some(MetaSDFNode::CurvatureVoxelTypeSelection(
+            MetaCurvatureVoxelTypeSelection {
+                concave_id,
+                convex_id,
+                threshold: (&params[0]).into(),
+            },
+        ))
+    }
+}
+
+impl SpecificMetaNodeKind for MetaNoiseVoxelTypeSelection {
+    const LABEL: LabelAndHoverText = LabelAndHoverText {
+        label: "Noise selection",
+        hover_text: "Selection between voxel types based on a gradient noise pattern.",
+    };
+    const PARENT_PORT_KIND: MetaParentPortKind = MetaParentPortKind::VoxelTypes;
+    const CHILD_PORT_KINDS: MetaChildPortKinds =
+        two_child_port_kinds(MetaChildPortKind::VoxelTypes, MetaChildPortKind::VoxelTypes);
+
+    fn params() -> MetaNodeParams {
+        let mut params = MetaNodeParams::new();
+        params.push(
+            MetaFloatParam::new(
+                LabelAndHoverText {
+                    label: "Frequency",
+                    hover_text: "Spatial frequency of the noise pattern, in inverse voxels.",
+                },
+                0.05,
+            )
+            .with_min_value(0.0)
+            .with_speed(0.001),
+        );
+        params.push(
+            MetaFloatParam::new(
+                LabelAndHoverText {
+                    label: "Threshold",
+                    hover_text: "Noise value above which the voxel types in the second input are used.",
+                },
+                0.0,
+            )
+            .with_speed(0.001),
+        );
+        params.push(MetaUIntParam::new(
+            LabelAndHoverText {
+                label: "Seed",
+                hover_text: "Seed for generating noise.",
+            },
+            0,
+        ));
+        params
+    }
+
+    fn build<A: Allocator>(
+        id_map: &NoHashMap<MetaNodeID, MetaSDFNodeID, A>,
+        children: &[Option<MetaNodeLink>],
+        params: &[MetaNodeParam],
+    ) -> Option<MetaSDFNode> {
+        assert_eq!(params.len(), 3);
+        let (low_id, high_id) = binary_children(id_map, children)?;
+        Some(MetaSDFNode::NoiseVoxelTypeSelection(
+            MetaNoiseVoxelTypeSelection {
+                low_id,
+                high_id,
+                frequency: (&params[0]).into(),
+                threshold: (&params[1]).into(),
+                seed: (&params[2]).into(),
+            },
+        ))
+    }
+}
+
+impl SpecificMetaNodeKind for MetaVoxelTypeAssignment {
+    const LABEL: LabelAndHoverText = LabelAndHoverText {
+        label: "Voxel type assignment",
+        hover_text: "Assignment of the voxel types in the second input to the SDFs in the first input.",
+    };
+    const PARENT_PORT_KIND: MetaParentPortKind = MetaParentPortKind::SameAsInput { slot: 0 };
+    const CHILD_PORT_KINDS: MetaChildPortKinds =
+        two_child_port_kinds(MetaChildPortKind::SDFGroup, MetaChildPortKind::VoxelTypes);
+
+    fn params() -> MetaNodeParams {
+        MetaNodeParams::new()
+    }
+
+    fn build<A: Allocator>(
+        id_map: &NoHashMap<MetaNodeID, MetaSDFNodeID, A>,
+        children: &[Option<MetaNodeLink>],
+        params: &[MetaNodeParam],
+    ) -> Option<MetaSDFNode> {
+        assert_eq!(params.len(), 0);
+        let (sdf_id, voxel_type_id) = binary_children(id_map, children)?;
+        Some(MetaSDFNode::VoxelTypeAssignment(MetaVoxelTypeAssignment {
+            sdf_id,
+            voxel_type_id,
+        }))
+    }
+}
+
 impl MetaNodeKind {
-    pub const fn all_non_root() -> [Self; 21] {
+    pub const fn all_non_root() -> [Self; 27] {
         [
             Self::Points,
             Self::Spheres,
@@ -1486,6 +1740,12 @@ impl MetaNodeKind {
             Self::SDFSubtraction,
             Self::SDFIntersection,
             Self::SDFGroupUnion,
+            Self::VoxelType,
+            Self::DepthVoxelTypeSelection,
+            Self::SlopeVoxelTypeSelection,
+            Self::CurvatureVoxelTypeSelection,
+            Self::NoiseVoxelTypeSelection,
+            Self::VoxelTypeAssignment,
         ]
     }
 
@@ -1516,6 +1776,12 @@ impl MetaNodeKind {
             Self::SDFUnion | Self::SDFSubtraction | Self::SDFIntersection | Self::SDFGroupUnion => {
                 MetaNodeKindGroup::SDFCombination
             }
+            Self::VoxelType
+            | Self::DepthVoxelTypeSelection
+            | Self::SlopeVoxelTypeSelection
+            | Self::CurvatureVoxelTypeSelection
+            | Self::NoiseVoxelTypeSelection
+            | Self::VoxelTypeAssignment => MetaNodeKindGroup::VoxelTypes,
         }
     }
 
@@ -1543,6 +1809,12 @@ impl MetaNodeKind {
             Self::SDFSubtraction => MetaSDFSubtraction::LABEL,
             Self::SDFIntersection => MetaSDFIntersection::LABEL,
             Self::SDFGroupUnion => MetaSDFGroupUnion::LABEL,
+            Self::VoxelType => MetaVoxelType::LABEL,
+            Self::DepthVoxelTypeSelection => MetaDepthVoxelTypeSelection::LABEL,
+            Self::SlopeVoxelTypeSelection => MetaSlopeVoxelTypeSelection::LABEL,
+            Self::CurvatureVoxelTypeSelection => MetaCurvatureVoxelTypeSelection::LABEL,
+            Self::NoiseVoxelTypeSelection => MetaNoiseVoxelTypeSelection::LABEL,
+            Self::VoxelTypeAssignment => MetaVoxelTypeAssignment::LABEL,
         }
     }
 
@@ -1572,6 +1844,12 @@ impl MetaNodeKind {
             Self::SDFSubtraction => MetaSDFSubtraction::PARENT_PORT_KIND,
             Self::SDFIntersection => MetaSDFIntersection::PARENT_PORT_KIND,
             Self::SDFGroupUnion => MetaSDFGroupUnion::PARENT_PORT_KIND,
+            Self::VoxelType => MetaVoxelType::PARENT_PORT_KIND,
+            Self::DepthVoxelTypeSelection => MetaDepthVoxelTypeSelection::PARENT_PORT_KIND,
+            Self::SlopeVoxelTypeSelection => MetaSlopeVoxelTypeSelection::PARENT_PORT_KIND,
+            Self::CurvatureVoxelTypeSelection => MetaCurvatureVoxelTypeSelection::PARENT_PORT_KIND,
+            Self::NoiseVoxelTypeSelection => MetaNoiseVoxelTypeSelection::PARENT_PORT_KIND,
+            Self::VoxelTypeAssignment => MetaVoxelTypeAssignment::PARENT_PORT_KIND,
         }
     }
 
@@ -1601,6 +1879,12 @@ impl MetaNodeKind {
             Self::SDFSubtraction => MetaSDFSubtraction::CHILD_PORT_KINDS,
             Self::SDFIntersection => MetaSDFIntersection::CHILD_PORT_KINDS,
             Self::SDFGroupUnion => MetaSDFGroupUnion::CHILD_PORT_KINDS,
+            Self::VoxelType => MetaVoxelType::CHILD_PORT_KINDS,
+            Self::DepthVoxelTypeSelection => MetaDepthVoxelTypeSelection::CHILD_PORT_KINDS,
+            Self::SlopeVoxelTypeSelection => MetaSlopeVoxelTypeSelection::CHILD_PORT_KINDS,
+            Self::CurvatureVoxelTypeSelection => MetaCurvatureVoxelTypeSelection::CHILD_PORT_KINDS,
+            Self::NoiseVoxelTypeSelection => MetaNoiseVoxelTypeSelection::CHILD_PORT_KINDS,
+            Self::VoxelTypeAssignment => MetaVoxelTypeAssignment::CHILD_PORT_KINDS,
         }
     }
 
@@ -1640,6 +1924,12 @@ impl MetaNodeKind {
             Self::SDFSubtraction => MetaSDFSubtraction::params(),
             Self::SDFIntersection => MetaSDFIntersection::params(),
             Self::SDFGroupUnion => MetaSDFGroupUnion::params(),
+            Self::VoxelType => MetaVoxelType::params(),
+            Self::DepthVoxelTypeSelection => MetaDepthVoxelTypeSelection::params(),
+            Self::SlopeVoxelTypeSelection => MetaSlopeVoxelTypeSelection::params(),
+            Self::CurvatureVoxelTypeSelection => MetaCurvatureVoxelTypeSelection::params(),
+            Self::NoiseVoxelTypeSelection => MetaNoiseVoxelTypeSelection::params(),
+            Self::VoxelTypeAssignment => MetaVoxelTypeAssignment::params(),
         }
     }
 
@@ -1682,12 +1972,26 @@ impl MetaNodeKind {
             Self::SDFSubtraction => MetaSDFSubtraction::build(id_map, children, params),
             Self::SDFIntersection => MetaSDFIntersection::build(id_map, children, params),
             Self::SDFGroupUnion => MetaSDFGroupUnion::build(id_map, children, params),
+            Self::VoxelType => MetaVoxelType::build(id_map, children, params),
+            Self::DepthVoxelTypeSelection => {
+                MetaDepthVoxelTypeSelection::build(id_map, children, params)
+            }
+            Self::SlopeVoxelTypeSelection => {
+                MetaSlopeVoxelTypeSelection::build(id_map, children, params)
+            }
+            Self::CurvatureVoxelTypeSelection => {
+                MetaCurvatureVoxelTypeSelection::build(id_map, children, params)
+            }
+            Self::NoiseVoxelTypeSelection => {
+                MetaNoiseVoxelTypeSelection::build(id_map, children, params)
+            }
+            Self::VoxelTypeAssignment => MetaVoxelTypeAssignment::build(id_map, children, params),
         }
     }
 }
 
 impl MetaNodeKindGroup {
-    pub const fn all_non_root() -> [Self; 8] {
+    pub const fn all_non_root() -> [Self; 9] {
         [
             Self::InstancePrimitives,
             Self::BasicInstanceTransforms,
@@ -1697,6 +2001,7 @@ impl MetaNodeKindGroup {
             Self::SDFFromInstances,
             Self::SDFModifiers,
             Self::SDFCombination,
+            Self::VoxelTypes,
         ]
     }
 }
@@ -24,6 +24,7 @@ pub enum MetaNodeParam {
     UInt(MetaUIntParam),
     Float(MetaFloatParam),
     Distributed(MetaDistributedParam),
+    Text(MetaTextParam),
 }
 
 #[derive(Clone, Debug)]
@@ -35,6 +36,12 @@ pub struct MetaEnumParam {
 
 pub type EnumParamVariants = TinyVec<[&'static str; 3]>;
 
+#[derive(Clone, Debug)]
+pub struct MetaTextParam {
+    pub text: LabelAndHoverText,
+    pub value: String,
+}
+
 #[derive(Clone, Debug)]
 pub struct MetaUIntParam {
     pub text: LabelAndHoverText,
@@ -189,6 +196,7 @@ impl MetaNodeParam {
                 current_distr_param_idx,
                 distr_param_names,
             ),
+            Self::Text(param) => param.show_controls(ui).changed(),
         }
     }
 
@@ -198,6 +206,7 @@ impl MetaNodeParam {
             Self::UInt(param) => param.text_to_display(),
             Self::Float(param) => param.text_to_display(),
             Self::Distributed(param) => param.text_to_display(distr_param_names),
+            Self::Text(param) => param.text_to_display(),
         }
     }
 
@@ -207,6 +216,7 @@ impl MetaNodeParam {
             Self::UInt(param) => param.text.label,
             Self::Float(param) => param.text.label,
             Self::Distributed(param) => param.text.label,
+            Self::Text(param) => param.text.label,
         }
     }
 
@@ -246,6 +256,18 @@ impl MetaNodeParam {
         self.as_float().unwrap()
     }
 
+    pub fn as_text(&self) -> Option<&str> {
+        if let Self::Text(param) = self {
+            Some(&param.value)
+        } else {
+            None
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        self.as_text().unwrap()
+    }
+
     pub fn is_distributed(&self) -> bool {
         matches!(self, Self::Distributed(_))
     }
@@ -287,6 +309,12 @@ impl<'a> From<&'a MetaNodeParam> for f32 {
     }
 }
 
+impl<'a> From<&'a MetaNodeParam> for String {
+    fn from(param: &'a MetaNodeParam) -> Self {
+        param.text().to_string()
+    }
+}
+
 impl<'a> From<&'a MetaNodeParam> for core::DiscreteParamSpec {
     fn from(param: &'a MetaNodeParam) -> Self {
         param.discrete_spec()
@@ -323,6 +351,12 @@ impl From<MetaDistributedParam> for MetaNodeParam {
     }
 }
 
+impl From<MetaTextParam> for MetaNodeParam {
+    fn from(param: MetaTextParam) -> Self {
+        Self::Text(param)
+    }
+}
+
 impl Default for MetaNodeParam {
     fn default() -> Self {
         Self::UInt(MetaUIntParam {
@@ -367,6 +401,28 @@ impl MetaEnumParam {
     }
 }
 
+impl MetaTextParam {
+    pub fn new(text: LabelAndHoverText, value: impl Into<String>) -> Self {
+        Self {
+            text,
+            value: value.into(),
+        }
+    }
+
+    fn show_controls(&mut self, ui: &mut Ui) -> Response {
+        configurable_labeled_option(
+            ui,
+            self.text.clone(),
+            |ui| ui.text_edit_singleline(&mut self.value),
+            true,
+        )
+    }
+
+    fn text_to_display(&self) -> String {
+        format!("{} = {}", self.text.label, self.value)
+    }
+}
+
 impl MetaUIntParam {
     pub const fn new(text: LabelAndHoverText, value: u32) -> Self {
         Self {
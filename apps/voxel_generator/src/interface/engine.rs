@@ -45,7 +45,9 @@ impl ApplicationInterface for AppInterfaceForEngine {
     }
 
     fn on_new_frame(&self, _frame_number: u64) -> Result<()> {
-        access_app_mut().update_voxel_object()
+        let mut app = access_app_mut();
+        app.export_voxel_object_mesh_if_requested();
+        app.update_voxel_object()
     }
 
     fn handle_keyboard_event(&self, event: KeyboardEvent) -> Result<()> {
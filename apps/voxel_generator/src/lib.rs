@@ -12,6 +12,7 @@ pub use impact::{component::gather_roc_type_ids_for_all_components, roc_integrat
 use anyhow::Result;
 use editor::EditorConfig;
 use impact::{
+    command::{AdminCommand, export::ExportAdminCommand},
     engine::{Engine, EngineConfig},
     impact_alloc::{Allocator, Global},
     impact_geometry::{ModelTransform, ReferenceFrame},
@@ -65,10 +66,16 @@ impl App {
     }
 
     fn initialize_voxel_object(&mut self) -> Result<()> {
-        let generator = self
-            .user_interface
-            .editor_mut()
-            .build_next_voxel_sdf_generator_or_default(Global);
+        let engine = self
+            .engine
+            .as_ref()
+            .expect("Tried to use engine before initialization");
+
+        let generator = engine.with_voxel_type_registry(|voxel_type_registry| {
+            self.user_interface
+                .editor_mut()
+                .build_next_voxel_sdf_generator_or_default(Global, voxel_type_registry)
+        });
 
         let voxel_object = self.engine().generate_voxel_object(&generator);
         let model_transform = compute_model_transform(&generator);
@@ -87,11 +94,16 @@ impl App {
     }
 
     fn update_voxel_object(&mut self) -> Result<()> {
-        let Some(generator) = self
-            .user_interface
-            .editor_mut()
-            .build_next_voxel_sdf_generator(Global)
-        else {
+        let engine = self
+            .engine
+            .as_ref()
+            .expect("Tried to use engine before initialization");
+
+        let Some(generator) = engine.with_voxel_type_registry(|voxel_type_registry| {
+            self.user_interface
+                .editor_mut()
+                .build_next_voxel_sdf_generator(Global, voxel_type_registry)
+        }) else {
             return Ok(());
         };
 
@@ -112,6 +124,21 @@ impl App {
 
         Ok(())
     }
+
+    fn export_voxel_object_mesh_if_requested(&mut self) {
+        if let Some(output_path) = self
+            .user_interface
+            .editor_mut()
+            .take_pending_mesh_export_path()
+        {
+            self.engine().enqueue_admin_command(AdminCommand::Export(
+                ExportAdminCommand::SaveVoxelObjectMesh {
+                    entity_id: OBJECT_ENTITY_ID,
+                    output_path,
+                },
+            ));
+        }
+    }
 }
 
 impl AppConfig {
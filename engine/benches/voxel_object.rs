@@ -40,6 +40,9 @@ define_criterion_target!(
     voxel_object,
     copy_voronoi_regions_with_inertial_property_transfer
 );
+define_criterion_target!(voxel_object, generate_terrain_tile);
+define_criterion_target!(voxel_object, load_terrain_tiles_around_observer);
+define_criterion_target!(voxel_object, load_terrain_tiles_around_observer_in_parallel);
 
 criterion::criterion_group!(
     name = benches;
@@ -74,5 +77,8 @@ criterion::criterion_group!(
         extract_voronoi_regions_with_inertial_property_transfer,
         copy_voronoi_regions,
         copy_voronoi_regions_with_inertial_property_transfer,
+        generate_terrain_tile,
+        load_terrain_tiles_around_observer,
+        load_terrain_tiles_around_observer_in_parallel,
 );
 criterion::criterion_main!(benches);
@@ -0,0 +1,343 @@
+//! Keyframe animation clips.
+
+use crate::pose::{Pose, lerp};
+use anyhow::{Result, bail};
+use impact_math::{hash64, quaternion::UnitQuaternion, stringhash64_newtype, vector::Vector3};
+use impact_resource::{Resource, ResourceID, registry::ImmutableResourceRegistry};
+
+stringhash64_newtype!(
+    /// Identifier for an [`AnimationClip`].
+    [pub] AnimationClipID
+);
+
+/// A registry of loaded [`AnimationClip`]s.
+pub type AnimationClipRegistry = ImmutableResourceRegistry<AnimationClip>;
+
+/// An animation of the joints of a skeleton, described by keyframes for the
+/// translation, rotation and scaling of individual joints.
+#[derive(Clone, Debug)]
+pub struct AnimationClip {
+    channels: Vec<AnimationChannel>,
+    duration: f32,
+}
+
+/// The keyframes animating a single property of a single joint.
+#[derive(Clone, Debug)]
+pub struct AnimationChannel {
+    /// The index of the animated joint in the skeleton.
+    pub joint: usize,
+    /// The keyframes for the animated property.
+    pub keyframes: ChannelKeyframes,
+}
+
+/// Keyframes for one of the properties of a joint transform.
+#[derive(Clone, Debug)]
+pub enum ChannelKeyframes {
+    Translation(Keyframes<Vector3>),
+    Rotation(Keyframes<UnitQuaternion>),
+    Scaling(Keyframes<Vector3>),
+}
+
+/// A sequence of values at increasing points in time, together with how to
+/// interpolate between them.
+#[derive(Clone, Debug)]
+pub struct Keyframes<T> {
+    times: Vec<f32>,
+    values: Vec<T>,
+    interpolation: Interpolation,
+}
+
+/// How values are interpolated between keyframes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+    /// The value of the previous keyframe is held until the next keyframe.
+    Step,
+    /// Translation and scaling are interpolated linearly, and rotation is
+    /// interpolated spherically.
+    Linear,
+}
+
+/// Values that can be interpolated between keyframes.
+pub trait KeyframeValue: Copy {
+    /// Interpolates between this value (`t = 0`) and the given value
+    /// (`t = 1`).
+    fn interpolated(&self, other: &Self, t: f32) -> Self;
+}
+
+impl AnimationClipID {
+    /// Creates an animation clip ID hashed from the given name.
+    pub fn from_name(name: &str) -> Self {
+        Self(hash64!(name))
+    }
+}
+
+impl ResourceID for AnimationClipID {}
+
+impl AnimationClip {
+    /// Creates a new animation clip with the given channels. The duration of
+    /// the clip is the time of the last keyframe in any channel.
+    pub fn new(channels: Vec<AnimationChannel>) -> Self {
+        let duration = channels
+            .iter()
+            .map(|channel| channel.keyframes.end_time())
+            .fold(0.0, f32::max);
+        Self { channels, duration }
+    }
+
+    /// Returns the channels of the clip.
+    pub fn channels(&self) -> &[AnimationChannel] {
+        &self.channels
+    }
+
+    /// Returns the duration of the clip.
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    /// Returns the largest joint index targeted by any channel, or [`None`]
+    /// if the clip has no channels.
+    pub fn max_joint_index(&self) -> Option<usize> {
+        self.channels.iter().map(|channel| channel.joint).max()
+    }
+
+    /// Samples the clip at the given time and writes the resulting joint
+    /// transforms into the given pose. Joints without channels in the clip
+    /// keep the transforms they have in the pose, so the pose should typically
+    /// be initialized to the rest pose of the skeleton. Channels targeting
+    /// joints outside the pose are ignored.
+    pub fn sample_into(&self, time: f32, pose: &mut Pose) {
+        let joint_transforms = pose.joint_transforms_mut();
+
+        for channel in &self.channels {
+            let Some(transform) = joint_transforms.get_mut(channel.joint) else {
+                continue;
+            };
+            match &channel.keyframes {
+                ChannelKeyframes::Translation(keyframes) => {
+                    transform.translation = keyframes.sample(time);
+                }
+                ChannelKeyframes::Rotation(keyframes) => {
+                    transform.rotation = keyframes.sample(time);
+                }
+                ChannelKeyframes::Scaling(keyframes) => {
+                    transform.scaling = keyframes.sample(time);
+                }
+            }
+        }
+    }
+}
+
+impl Resource for AnimationClip {
+    type ID = AnimationClipID;
+}
+
+impl ChannelKeyframes {
+    /// Returns the time of the last keyframe.
+    pub fn end_time(&self) -> f32 {
+        match self {
+            Self::Translation(keyframes) | Self::Scaling(keyframes) => keyframes.end_time(),
+            Self::Rotation(keyframes) => keyframes.end_time(),
+        }
+    }
+}
+
+impl<T: KeyframeValue> Keyframes<T> {
+    /// Creates a new sequence of keyframes with the given times and values.
+    ///
+    /// # Errors
+    /// Returns an error if there are no keyframes, if the number of times and
+    /// values differ or if the times are not increasing.
+    pub fn new(times: Vec<f32>, values: Vec<T>, interpolation: Interpolation) -> Result<Self> {
+        if times.is_empty() {
+            bail!("Tried to create keyframes without any keyframes");
+        }
+        if times.len() != values.len() {
+            bail!(
+                "Got {} keyframe times but {} keyframe values",
+                times.len(),
+                values.len()
+            );
+        }
+        if times.windows(2).any(|window| window[1] < window[0]) {
+            bail!("Keyframe times are not increasing");
+        }
+        Ok(Self {
+            times,
+            values,
+            interpolation,
+        })
+    }
+
+    /// Returns the time of the last keyframe.
+    pub fn end_time(&self) -> f32 {
+        *self.times.last().unwrap()
+    }
+
+    /// Computes the value at the given time. Times before the first or after
+    /// the last keyframe give the value of that keyframe.
+    pub fn sample(&self, time: f32) -> T {
+        let next_idx = self
+            .times
+            .partition_point(|&keyframe_time| keyframe_time <= time);
+
+        if next_idx == 0 {
+            return self.values[0];
+        }
+        if next_idx == self.times.len() {
+            return self.values[next_idx - 1];
+        }
+
+        let prev_idx = next_idx - 1;
+
+        match self.interpolation {
+            Interpolation::Step => self.values[prev_idx],
+            Interpolation::Linear => {
+                let prev_time = self.times[prev_idx];
+                let interval = self.times[next_idx] - prev_time;
+                let t = if interval > 0.0 {
+                    (time - prev_time) / interval
+                } else {
+                    0.0
+                };
+                self.values[prev_idx].interpolated(&self.values[next_idx], t)
+            }
+        }
+    }
+}
+
+impl KeyframeValue for Vector3 {
+    fn interpolated(&self, other: &Self, t: f32) -> Self {
+        lerp(self, other, t)
+    }
+}
+
+impl KeyframeValue for UnitQuaternion {
+    fn interpolated(&self, other: &Self, t: f32) -> Self {
+        self.slerp(other, t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pose::JointTransform;
+    use approx::assert_abs_diff_eq;
+    use impact_math::{consts::f32::PI, vector::UnitVector3};
+
+    fn translation_keyframes(interpolation: Interpolation) -> Keyframes<Vector3> {
+        Keyframes::new(
+            vec![0.0, 1.0, 3.0],
+            vec![
+                Vector3::zeros(),
+                Vector3::new(2.0, 0.0, 0.0),
+                Vector3::new(2.0, 4.0, 0.0),
+            ],
+            interpolation,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn creating_invalid_keyframes_fails() {
+        assert!(Keyframes::<Vector3>::new(Vec::new(), Vec::new(), Interpolation::Linear).is_err());
+        assert!(
+            Keyframes::new(
+                vec![0.0, 1.0],
+                vec![Vector3::zeros()],
+                Interpolation::Linear
+            )
+            .is_err()
+        );
+        assert!(
+            Keyframes::new(
+                vec![1.0, 0.0],
+                vec![Vector3::zeros(), Vector3::zeros()],
+                Interpolation::Linear
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn linear_sampling_interpolates_between_keyframes() {
+        let keyframes = translation_keyframes(Interpolation::Linear);
+        assert_abs_diff_eq!(keyframes.sample(0.5), Vector3::new(1.0, 0.0, 0.0));
+        assert_abs_diff_eq!(keyframes.sample(2.0), Vector3::new(2.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn step_sampling_holds_previous_keyframe() {
+        let keyframes = translation_keyframes(Interpolation::Step);
+        assert_abs_diff_eq!(keyframes.sample(0.9), Vector3::zeros());
+        assert_abs_diff_eq!(keyframes.sample(2.9), Vector3::new(2.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn sampling_outside_keyframes_clamps_to_ends() {
+        let keyframes = translation_keyframes(Interpolation::Linear);
+        assert_abs_diff_eq!(keyframes.sample(-1.0), Vector3::zeros());
+        assert_abs_diff_eq!(keyframes.sample(10.0), Vector3::new(2.0, 4.0, 0.0));
+    }
+
+    #[test]
+    fn rotation_keyframes_are_interpolated_spherically() {
+        let keyframes = Keyframes::new(
+            vec![0.0, 1.0],
+            vec![
+                UnitQuaternion::identity(),
+                UnitQuaternion::from_axis_angle(&UnitVector3::unit_x(), PI / 2.0),
+            ],
+            Interpolation::Linear,
+        )
+        .unwrap();
+        assert_abs_diff_eq!(keyframes.sample(0.5).angle(), PI / 4.0, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn clip_duration_is_last_keyframe_time() {
+        let clip = AnimationClip::new(vec![
+            AnimationChannel {
+                joint: 0,
+                keyframes: ChannelKeyframes::Translation(translation_keyframes(
+                    Interpolation::Linear,
+                )),
+            },
+            AnimationChannel {
+                joint: 1,
+                keyframes: ChannelKeyframes::Scaling(
+                    Keyframes::new(
+                        vec![0.0, 5.0],
+                        vec![Vector3::same(1.0); 2],
+                        Interpolation::Step,
+                    )
+                    .unwrap(),
+                ),
+            },
+        ]);
+        assert_eq!(clip.duration(), 5.0);
+        assert_eq!(clip.max_joint_index(), Some(1));
+    }
+
+    #[test]
+    fn sampling_clip_only_affects_animated_joints() {
+        let clip = AnimationClip::new(vec![AnimationChannel {
+            joint: 1,
+            keyframes: ChannelKeyframes::Translation(translation_keyframes(Interpolation::Linear)),
+        }]);
+
+        let rest_transform = JointTransform::new(
+            Vector3::new(0.0, 0.0, 7.0),
+            UnitQuaternion::identity(),
+            Vector3::same(1.0),
+        );
+        let mut pose = Pose::new(vec![rest_transform; 2]);
+
+        clip.sample_into(0.5, &mut pose);
+
+        assert_eq!(pose.joint_transforms()[0], rest_transform);
+        assert_abs_diff_eq!(
+            pose.joint_transforms()[1].translation,
+            Vector3::new(1.0, 0.0, 0.0)
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! GPU resources for skeletal animation.
+
+use crate::palette::JointPaletteManager;
+use impact_gpu::{
+    bind_group_layout::BindGroupLayoutRegistry, buffer::GPUBuffer, device::GraphicsDevice, storage,
+    wgpu,
+};
+use impact_math::hash::ConstStringHash64;
+use std::{borrow::Cow, mem};
+
+/// GPU resources for the joint palettes of all animated skeleton instances,
+/// consisting of a storage buffer holding every joint matrix and its bind
+/// group.
+#[derive(Debug)]
+pub struct JointPaletteGPUResource {
+    joint_matrix_buffer: GPUBuffer,
+    bind_group: wgpu::BindGroup,
+    version: u64,
+}
+
+impl JointPaletteGPUResource {
+    const VISIBILITY: wgpu::ShaderStages = wgpu::ShaderStages::VERTEX;
+    const LAYOUT_ID: ConstStringHash64 = ConstStringHash64::new("JointPalette");
+
+    /// The binding location of the joint matrix storage buffer.
+    pub const fn binding() -> u32 {
+        0
+    }
+
+    /// Creates GPU resources initialized with the joint matrices in the given
+    /// manager.
+    pub fn for_joint_palettes(
+        graphics_device: &GraphicsDevice,
+        staging_belt: &mut wgpu::util::StagingBelt,
+        command_encoder: &mut wgpu::CommandEncoder,
+        bind_group_layout_registry: &BindGroupLayoutRegistry,
+        joint_palette_manager: &JointPaletteManager,
+    ) -> Self {
+        let joint_matrix_buffer = Self::create_joint_matrix_buffer(
+            graphics_device,
+            staging_belt,
+            command_encoder,
+            joint_palette_manager,
+        );
+
+        let bind_group_layout =
+            Self::get_or_create_bind_group_layout(graphics_device, bind_group_layout_registry);
+
+        let bind_group = Self::create_bind_group(
+            graphics_device.device(),
+            &joint_matrix_buffer,
+            &bind_group_layout,
+        );
+
+        Self {
+            joint_matrix_buffer,
+            bind_group,
+            version: joint_palette_manager.version(),
+        }
+    }
+
+    /// Returns the layout of the bind group for the joint matrix storage
+    /// buffer, after creating and caching it if it has not already been
+    /// created.
+    pub fn get_or_create_bind_group_layout(
+        graphics_device: &GraphicsDevice,
+        bind_group_layout_registry: &BindGroupLayoutRegistry,
+    ) -> wgpu::BindGroupLayout {
+        bind_group_layout_registry.get_or_create_layout(Self::LAYOUT_ID.hash(), || {
+            Self::create_bind_group_layout(graphics_device.device())
+        })
+    }
+
+    /// Returns the bind group for the joint matrix storage buffer.
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    /// Ensures that the GPU buffer is in sync with the joint matrices in the
+    /// given manager. The buffer and bind group are recreated if the buffer is
+    /// too small.
+    pub fn sync_with_joint_palettes(
+        &mut self,
+        graphics_device: &GraphicsDevice,
+        staging_belt: &mut wgpu::util::StagingBelt,
+        command_encoder: &mut wgpu::CommandEncoder,
+        bind_group_layout_registry: &BindGroupLayoutRegistry,
+        joint_palette_manager: &JointPaletteManager,
+    ) {
+        if self.version == joint_palette_manager.version() {
+            return;
+        }
+
+        let joint_matrices = joint_palette_manager.joint_matrices();
+
+        if mem::size_of_val(joint_matrices) > self.joint_matrix_buffer.buffer_size() {
+            *self = Self::for_joint_palettes(
+                graphics_device,
+                staging_belt,
+                command_encoder,
+                bind_group_layout_registry,
+                joint_palette_manager,
+            );
+        } else {
+            self.joint_matrix_buffer.encode_update_of_valid_bytes(
+                graphics_device,
+                staging_belt,
+                command_encoder,
+                bytemuck::cast_slice(joint_matrices),
+            );
+            self.version = joint_palette_manager.version();
+        }
+    }
+
+    fn create_joint_matrix_buffer(
+        graphics_device: &GraphicsDevice,
+        staging_belt: &mut wgpu::util::StagingBelt,
+        command_encoder: &mut wgpu::CommandEncoder,
+        joint_palette_manager: &JointPaletteManager,
+    ) -> GPUBuffer {
+        let joint_matrices = joint_palette_manager.joint_matrices();
+
+        // Make room for some extra palettes to avoid frequent recreation as
+        // animated instances are added
+        let total_capacity = 2 * joint_matrices.len();
+
+        GPUBuffer::new_storage_buffer_with_spare_capacity_and_encoded_initialization(
+            graphics_device,
+            staging_belt,
+            command_encoder,
+            total_capacity,
+            joint_matrices,
+            Cow::Borrowed("Joint palette"),
+        )
+    }
+
+    fn create_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[storage::create_storage_buffer_bind_group_layout_entry(
+                Self::binding(),
+                Self::VISIBILITY,
+                true,
+            )],
+            label: Some("Joint palette bind group layout"),
+        })
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        joint_matrix_buffer: &GPUBuffer,
+        layout: &wgpu::BindGroupLayout,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[joint_matrix_buffer.create_bind_group_entry(Self::binding())],
+            label: Some("Joint palette bind group"),
+        })
+    }
+}
@@ -0,0 +1,4 @@
+//! Input/output of animation data.
+
+#[cfg(feature = "gltf")]
+pub mod gltf;
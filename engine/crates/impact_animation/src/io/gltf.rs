@@ -0,0 +1,298 @@
+//! Import of skeletons and animation clips from glTF 2.0 files.
+
+use crate::{
+    clip::{AnimationChannel, AnimationClip, ChannelKeyframes, Interpolation, Keyframes},
+    pose::JointTransform,
+    skeleton::{Joint, Skeleton},
+};
+use anyhow::{Context, Result, anyhow, bail};
+use gltf::{
+    Animation, Document, Skin,
+    animation::{self, Property, util::ReadOutputs},
+};
+use impact_math::{
+    matrix::{Matrix4, Matrix4C},
+    quaternion::{Quaternion, UnitQuaternion},
+    vector::Vector3,
+};
+use impact_mesh::io::gltf::GltfFile;
+
+/// The parent and global transform of every node in a glTF document, indexed
+/// by node index.
+#[derive(Clone, Debug)]
+pub struct GltfNodeHierarchy {
+    parents: Vec<Option<usize>>,
+    global_transforms: Vec<Matrix4>,
+}
+
+impl GltfNodeHierarchy {
+    /// Determines the node hierarchy of the given glTF document.
+    pub fn new(document: &Document) -> Self {
+        let n_nodes = document.nodes().len();
+
+        let mut parents = vec![None; n_nodes];
+        for node in document.nodes() {
+            for child in node.children() {
+                parents[child.index()] = Some(node.index());
+            }
+        }
+
+        let local_transforms: Vec<_> = document
+            .nodes()
+            .map(|node| matrix_from_gltf(node.transform().matrix()))
+            .collect();
+
+        let global_transforms = (0..n_nodes)
+            .map(|node_idx| {
+                let mut global_transform = local_transforms[node_idx];
+                let mut current_idx = node_idx;
+                // Bounding the number of steps guards against malformed files
+                // with cyclic hierarchies
+                for _ in 0..n_nodes {
+                    let Some(parent_idx) = parents[current_idx] else {
+                        break;
+                    };
+                    global_transform = local_transforms[parent_idx] * global_transform;
+                    current_idx = parent_idx;
+                }
+                global_transform
+            })
+            .collect();
+
+        Self {
+            parents,
+            global_transforms,
+        }
+    }
+
+    /// Returns the index of the parent of the given node, or [`None`] if it
+    /// is a root node.
+    pub fn parent(&self, node_idx: usize) -> Option<usize> {
+        self.parents[node_idx]
+    }
+
+    /// Returns the transform from the space of the given node to the space of
+    /// the scene.
+    pub fn global_transform(&self, node_idx: usize) -> &Matrix4 {
+        &self.global_transforms[node_idx]
+    }
+}
+
+/// Creates a [`Skeleton`] from the given glTF skin, for skinning a mesh
+/// attached to the node with the given index.
+///
+/// glTF skinning places vertices in the space of the scene and ignores the
+/// transform of the mesh node. To keep skinned vertices in the model space of
+/// the mesh, so that they can be rendered with the transform of their node
+/// like any other model, the root transform of the skeleton maps from the
+/// space of the parent of the root joints to the space of the mesh node. If
+/// the root joints have different parents, the parent of the first is used.
+///
+/// # Errors
+/// Returns an error if the skin has no joints or too many, or if its inverse
+/// bind matrices can not be read.
+pub fn create_skeleton_from_gltf_skin(
+    file: &GltfFile,
+    skin: &Skin<'_>,
+    mesh_node_idx: usize,
+    hierarchy: &GltfNodeHierarchy,
+) -> Result<Skeleton> {
+    let joint_nodes: Vec<_> = skin.joints().collect();
+
+    let inverse_bind_matrices: Vec<Matrix4> = match skin
+        .reader(|buffer| file.buffer_data(&buffer))
+        .read_inverse_bind_matrices()
+    {
+        Some(matrices) => matrices.map(matrix_from_gltf).collect(),
+        None => vec![Matrix4::identity(); joint_nodes.len()],
+    };
+
+    if inverse_bind_matrices.len() != joint_nodes.len() {
+        bail!(
+            "glTF skin {} has {} joints but {} inverse bind matrices",
+            skin.index(),
+            joint_nodes.len(),
+            inverse_bind_matrices.len()
+        );
+    }
+
+    let joint_index_of_node = |node_idx: usize| {
+        joint_nodes
+            .iter()
+            .position(|joint_node| joint_node.index() == node_idx)
+    };
+
+    let mut root_parent_node_idx = None;
+
+    let joints = joint_nodes
+        .iter()
+        .zip(inverse_bind_matrices)
+        .map(|(node, inverse_bind_matrix)| {
+            let parent_node_idx = hierarchy.parent(node.index());
+            let parent = parent_node_idx.and_then(joint_index_of_node);
+
+            if parent.is_none() {
+                match root_parent_node_idx {
+                    None => {
+                        root_parent_node_idx = Some(parent_node_idx);
+                    }
+                    Some(root_parent_node_idx) if root_parent_node_idx != parent_node_idx => {
+                        log::warn!(
+                            "Root joints of glTF skin {} have different parents, \
+                             animation of joint node {} may be offset",
+                            skin.index(),
+                            node.index()
+                        );
+                    }
+                    Some(_) => {}
+                }
+            }
+
+            let (translation, rotation, scale) = node.transform().decomposed();
+
+            Joint {
+                name: node
+                    .name()
+                    .map_or_else(|| format!("node{}", node.index()), str::to_string),
+                parent,
+                rest_transform: JointTransform::new(
+                    Vector3::from(translation),
+                    unit_quaternion_from_gltf(rotation),
+                    Vector3::from(scale),
+                ),
+                inverse_bind_matrix,
+            }
+        })
+        .collect();
+
+    let root_parent_transform = root_parent_node_idx
+        .flatten()
+        .map_or_else(Matrix4::identity, |node_idx| {
+            *hierarchy.global_transform(node_idx)
+        });
+
+    let root_transform =
+        hierarchy.global_transform(mesh_node_idx).inverse() * root_parent_transform;
+
+    Skeleton::new(joints, root_transform)
+        .with_context(|| format!("Invalid joint hierarchy for glTF skin {}", skin.index()))
+}
+
+/// Creates an [`AnimationClip`] from the channels of the given glTF animation
+/// that target joints of the given skin. Channels animating other nodes or
+/// morph target weights are ignored. Cubic spline keyframes are approximated
+/// with linear interpolation between the keyframe values.
+///
+/// # Returns
+/// The clip, or [`None`] if no channel of the animation targets a joint of
+/// the skin.
+///
+/// # Errors
+/// Returns an error if the keyframes of a channel can not be read or are
+/// invalid.
+pub fn create_animation_clip_from_gltf_animation(
+    file: &GltfFile,
+    animation: &Animation<'_>,
+    skin: &Skin<'_>,
+) -> Result<Option<AnimationClip>> {
+    let joint_node_indices: Vec<_> = skin.joints().map(|node| node.index()).collect();
+
+    let mut channels = Vec::new();
+
+    for channel in animation.channels() {
+        let target = channel.target();
+
+        let Some(joint) = joint_node_indices
+            .iter()
+            .position(|&node_idx| node_idx == target.node().index())
+        else {
+            continue;
+        };
+
+        if target.property() == Property::MorphTargetWeights {
+            continue;
+        }
+
+        let sampler = channel.sampler();
+        let reader = channel.reader(|buffer| file.buffer_data(&buffer));
+
+        let times: Vec<f32> = reader
+            .read_inputs()
+            .ok_or_else(|| anyhow!("Missing keyframe times"))
+            .with_context(|| channel_context(animation, &channel))?
+            .collect();
+
+        let outputs = reader
+            .read_outputs()
+            .ok_or_else(|| anyhow!("Missing keyframe values"))
+            .with_context(|| channel_context(animation, &channel))?;
+
+        let (interpolation, is_cubic_spline) = match sampler.interpolation() {
+            animation::Interpolation::Step => (Interpolation::Step, false),
+            animation::Interpolation::Linear => (Interpolation::Linear, false),
+            animation::Interpolation::CubicSpline => (Interpolation::Linear, true),
+        };
+
+        let keyframes = match outputs {
+            ReadOutputs::Translations(translations) => ChannelKeyframes::Translation(
+                Keyframes::new(
+                    times,
+                    keyframe_values(translations.map(Vector3::from), is_cubic_spline),
+                    interpolation,
+                )
+                .with_context(|| channel_context(animation, &channel))?,
+            ),
+            ReadOutputs::Rotations(rotations) => ChannelKeyframes::Rotation(
+                Keyframes::new(
+                    times,
+                    keyframe_values(
+                        rotations.into_f32().map(unit_quaternion_from_gltf),
+                        is_cubic_spline,
+                    ),
+                    interpolation,
+                )
+                .with_context(|| channel_context(animation, &channel))?,
+            ),
+            ReadOutputs::Scales(scales) => ChannelKeyframes::Scaling(
+                Keyframes::new(
+                    times,
+                    keyframe_values(scales.map(Vector3::from), is_cubic_spline),
+                    interpolation,
+                )
+                .with_context(|| channel_context(animation, &channel))?,
+            ),
+            ReadOutputs::MorphTargetWeights(_) => continue,
+        };
+
+        channels.push(AnimationChannel { joint, keyframes });
+    }
+
+    Ok((!channels.is_empty()).then(|| AnimationClip::new(channels)))
+}
+
+/// Collects the given keyframe values. Cubic spline outputs hold an in-tangent,
+/// a value and an out-tangent for each keyframe, and only the values are kept.
+fn keyframe_values<T>(values: impl Iterator<Item = T>, is_cubic_spline: bool) -> Vec<T> {
+    if is_cubic_spline {
+        values.skip(1).step_by(3).collect()
+    } else {
+        values.collect()
+    }
+}
+
+fn channel_context(animation: &Animation<'_>, channel: &animation::Channel<'_>) -> String {
+    format!(
+        "Failed to import channel {} of glTF animation {}",
+        channel.index(),
+        animation.index()
+    )
+}
+
+fn matrix_from_gltf(columns: [[f32; 4]; 4]) -> Matrix4 {
+    let elements: [f32; 16] = bytemuck::cast(columns);
+    Matrix4C::from(elements).aligned()
+}
+
+fn unit_quaternion_from_gltf([x, y, z, w]: [f32; 4]) -> UnitQuaternion {
+    UnitQuaternion::normalized_from(Quaternion::from_parts(Vector3::new(x, y, z), w))
+}
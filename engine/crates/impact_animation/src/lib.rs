@@ -0,0 +1,20 @@
+//! Skeletal animation.
+
+#[macro_use]
+mod macros;
+
+pub mod clip;
+pub mod gpu_resource;
+pub mod io;
+pub mod palette;
+pub mod player;
+pub mod pose;
+pub mod skeleton;
+#[cfg(feature = "ecs")]
+pub mod systems;
+
+pub use clip::{AnimationClip, AnimationClipID, AnimationClipRegistry};
+pub use palette::JointPaletteManager;
+pub use player::{AnimationPlayer, AnimationPlayerFlags};
+pub use pose::{JointTransform, Pose};
+pub use skeleton::{Joint, Skeleton, SkeletonID, SkeletonRegistry};
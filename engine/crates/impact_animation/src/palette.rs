@@ -0,0 +1,144 @@
+//! Collection of the joint matrices of all animated skeleton instances.
+
+use crate::{gpu_resource::JointPaletteGPUResource, skeleton::MAX_SKELETON_JOINTS};
+use impact_gpu::{bind_group_layout::BindGroupLayoutRegistry, device::GraphicsDevice, wgpu};
+use impact_math::matrix::Matrix4C;
+
+/// Holds the skinning matrices of the joints of every animated skeleton
+/// instance for the current frame, laid out contiguously so that they can be
+/// uploaded to a single GPU buffer. Each instance refers to its palette by the
+/// offset of its first joint matrix.
+///
+/// The palette always begins with [`MAX_SKELETON_JOINTS`] identity matrices,
+/// so that offset zero can be used for skinned mesh instances that are not
+/// animated, which leaves them in their bind pose.
+#[derive(Clone, Debug)]
+pub struct JointPaletteManager {
+    joint_matrices: Vec<Matrix4C>,
+    version: u64,
+}
+
+impl JointPaletteManager {
+    /// The palette offset for instances rendered in their bind pose.
+    pub const BIND_POSE_OFFSET: u32 = 0;
+
+    /// Creates a new manager containing only the bind pose palette.
+    pub fn new() -> Self {
+        Self {
+            joint_matrices: vec![Matrix4C::identity(); MAX_SKELETON_JOINTS],
+            version: 0,
+        }
+    }
+
+    /// Returns all joint matrices, including the bind pose palette.
+    pub fn joint_matrices(&self) -> &[Matrix4C] {
+        &self.joint_matrices
+    }
+
+    /// Returns a version number that changes whenever the joint matrices are
+    /// modified.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Whether any palettes have been added since the last call to
+    /// [`Self::clear_palettes`].
+    pub fn has_palettes(&self) -> bool {
+        self.joint_matrices.len() > MAX_SKELETON_JOINTS
+    }
+
+    /// Removes all palettes except the bind pose palette.
+    pub fn clear_palettes(&mut self) {
+        if self.has_palettes() {
+            self.joint_matrices.truncate(MAX_SKELETON_JOINTS);
+            self.version = self.version.wrapping_add(1);
+        }
+    }
+
+    /// Appends the given joint matrices as a new palette.
+    ///
+    /// # Returns
+    /// The offset of the first matrix of the palette.
+    pub fn add_palette(&mut self, joint_matrices: &[Matrix4C]) -> u32 {
+        let offset = u32::try_from(self.joint_matrices.len()).unwrap();
+        self.joint_matrices.extend_from_slice(joint_matrices);
+        self.version = self.version.wrapping_add(1);
+        offset
+    }
+
+    /// Performs any required updates for keeping the joint palette GPU
+    /// resources in sync with the joint matrices.
+    pub fn sync_gpu_resources(
+        &self,
+        graphics_device: &GraphicsDevice,
+        staging_belt: &mut wgpu::util::StagingBelt,
+        command_encoder: &mut wgpu::CommandEncoder,
+        bind_group_layout_registry: &BindGroupLayoutRegistry,
+        joint_palette_gpu_resources: &mut Option<JointPaletteGPUResource>,
+    ) {
+        if let Some(joint_palette_gpu_resources) = joint_palette_gpu_resources {
+            joint_palette_gpu_resources.sync_with_joint_palettes(
+                graphics_device,
+                staging_belt,
+                command_encoder,
+                bind_group_layout_registry,
+                self,
+            );
+        } else {
+            *joint_palette_gpu_resources = Some(JointPaletteGPUResource::for_joint_palettes(
+                graphics_device,
+                staging_belt,
+                command_encoder,
+                bind_group_layout_registry,
+                self,
+            ));
+        }
+    }
+}
+
+impl Default for JointPaletteManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_manager_has_only_bind_pose_palette() {
+        let manager = JointPaletteManager::new();
+        assert!(!manager.has_palettes());
+        assert_eq!(manager.joint_matrices().len(), MAX_SKELETON_JOINTS);
+        assert_eq!(
+            manager.joint_matrices()[JointPaletteManager::BIND_POSE_OFFSET as usize],
+            Matrix4C::identity()
+        );
+    }
+
+    #[test]
+    fn palettes_are_added_after_bind_pose_palette() {
+        let mut manager = JointPaletteManager::new();
+        let palette = [Matrix4C::zeros(); 3];
+
+        let first = manager.add_palette(&palette);
+        let second = manager.add_palette(&palette);
+
+        assert_eq!(first as usize, MAX_SKELETON_JOINTS);
+        assert_eq!(second as usize, MAX_SKELETON_JOINTS + 3);
+    }
+
+    #[test]
+    fn clearing_palettes_keeps_bind_pose_palette() {
+        let mut manager = JointPaletteManager::new();
+        manager.add_palette(&[Matrix4C::zeros(); 2]);
+        let version = manager.version();
+
+        manager.clear_palettes();
+
+        assert!(!manager.has_palettes());
+        assert_ne!(manager.version(), version);
+        assert_eq!(manager.joint_matrices().len(), MAX_SKELETON_JOINTS);
+    }
+}
@@ -0,0 +1,251 @@
+//! Playback of animation clips.
+
+use crate::{
+    clip::{AnimationClip, AnimationClipID},
+    pose::Pose,
+    skeleton::SkeletonID,
+};
+use bitflags::bitflags;
+use bytemuck::{Pod, Zeroable};
+
+define_component_type! {
+    /// Plays an [`AnimationClip`] on the skeleton of the entity's skinned
+    /// mesh, optionally cross-fading into a second clip.
+    #[repr(C)]
+    #[derive(Copy, Clone, Debug, PartialEq, Zeroable, Pod)]
+    pub struct AnimationPlayer {
+        /// The skeleton the clips animate.
+        pub skeleton_id: SkeletonID,
+        /// The clip currently playing.
+        pub clip_id: AnimationClipID,
+        /// The clip being blended in, if the blend weight is non-zero.
+        pub blend_clip_id: AnimationClipID,
+        /// The current playback time in the clip.
+        pub time: f32,
+        /// The current playback time in the clip being blended in.
+        pub blend_time: f32,
+        /// The factor by which the passage of time is multiplied when
+        /// advancing playback.
+        pub speed: f32,
+        /// The weight of the clip being blended in, from zero (only the
+        /// current clip) to one (only the blended clip).
+        pub blend_weight: f32,
+        /// The rate at which the blend weight increases per unit of time. When
+        /// the weight reaches one, the blended clip replaces the current clip.
+        pub blend_rate: f32,
+        /// Flags controlling playback.
+        pub flags: AnimationPlayerFlags,
+    }
+}
+
+bitflags! {
+    /// Flags controlling the playback of an [`AnimationPlayer`].
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Zeroable, Pod)]
+    pub struct AnimationPlayerFlags: u32 {
+        /// Whether playback restarts from the beginning when the end of a clip
+        /// is reached. Otherwise, the last frame is held.
+        const LOOPING = 1 << 0;
+        /// Whether playback is paused.
+        const PAUSED  = 1 << 1;
+    }
+}
+
+impl AnimationPlayer {
+    /// Creates a player that loops the given clip on the given skeleton from
+    /// the beginning at normal speed.
+    pub fn looping(skeleton_id: SkeletonID, clip_id: AnimationClipID) -> Self {
+        Self {
+            skeleton_id,
+            clip_id,
+            blend_clip_id: clip_id,
+            time: 0.0,
+            blend_time: 0.0,
+            speed: 1.0,
+            blend_weight: 0.0,
+            blend_rate: 0.0,
+            flags: AnimationPlayerFlags::LOOPING,
+        }
+    }
+
+    /// Creates a player that plays the given clip on the given skeleton once
+    /// from the beginning at normal speed.
+    pub fn once(skeleton_id: SkeletonID, clip_id: AnimationClipID) -> Self {
+        Self {
+            flags: AnimationPlayerFlags::empty(),
+            ..Self::looping(skeleton_id, clip_id)
+        }
+    }
+
+    /// Whether playback loops.
+    pub fn is_looping(&self) -> bool {
+        self.flags.contains(AnimationPlayerFlags::LOOPING)
+    }
+
+    /// Whether playback is paused.
+    pub fn is_paused(&self) -> bool {
+        self.flags.contains(AnimationPlayerFlags::PAUSED)
+    }
+
+    /// Whether a second clip is currently being blended in.
+    pub fn is_blending(&self) -> bool {
+        self.blend_weight > 0.0 || self.blend_rate > 0.0
+    }
+
+    /// Starts cross-fading from the current clip to the given clip over the
+    /// given duration. The new clip starts from the beginning. A non-positive
+    /// duration switches to the new clip immediately.
+    pub fn cross_fade_to(&mut self, clip_id: AnimationClipID, duration: f32) {
+        if duration <= 0.0 {
+            self.clip_id = clip_id;
+            self.time = 0.0;
+            self.blend_weight = 0.0;
+            self.blend_rate = 0.0;
+        } else {
+            self.blend_clip_id = clip_id;
+            self.blend_time = 0.0;
+            self.blend_weight = 0.0;
+            self.blend_rate = duration.recip();
+        }
+    }
+
+    /// Advances playback by the given duration. The durations of the current
+    /// and blended clips are needed to wrap or clamp the playback times. When
+    /// a cross-fade completes, the blended clip becomes the current clip.
+    pub fn advance(&mut self, time_step: f32, clip_duration: f32, blend_clip_duration: f32) {
+        if self.is_paused() {
+            return;
+        }
+
+        let scaled_time_step = self.speed * time_step;
+        let looping = self.is_looping();
+
+        self.time = advance_time(self.time, scaled_time_step, clip_duration, looping);
+
+        if self.is_blending() {
+            self.blend_time = advance_time(
+                self.blend_time,
+                scaled_time_step,
+                blend_clip_duration,
+                looping,
+            );
+            self.blend_weight = (self.blend_weight + self.blend_rate * time_step).min(1.0);
+
+            if self.blend_weight >= 1.0 {
+                self.clip_id = self.blend_clip_id;
+                self.time = self.blend_time;
+                self.blend_weight = 0.0;
+                self.blend_rate = 0.0;
+            }
+        }
+    }
+
+    /// Computes the current pose of the skeleton by sampling the current clip
+    /// and blending in the sampled pose of the blended clip. Both poses start
+    /// out as the given rest pose. The pose is written to `pose`, and
+    /// `blend_pose` is used as scratch space.
+    pub fn compute_pose(
+        &self,
+        rest_pose: &Pose,
+        clip: &AnimationClip,
+        blend_clip: Option<&AnimationClip>,
+        pose: &mut Pose,
+        blend_pose: &mut Pose,
+    ) {
+        pose.copy_from(rest_pose);
+        clip.sample_into(self.time, pose);
+
+        if let Some(blend_clip) = blend_clip
+            && self.blend_weight > 0.0
+        {
+            blend_pose.copy_from(rest_pose);
+            blend_clip.sample_into(self.blend_time, blend_pose);
+            pose.blend_with(blend_pose, self.blend_weight);
+        }
+    }
+}
+
+fn advance_time(time: f32, time_step: f32, duration: f32, looping: bool) -> f32 {
+    let time = time + time_step;
+    if duration <= 0.0 {
+        0.0
+    } else if looping {
+        time.rem_euclid(duration)
+    } else {
+        time.clamp(0.0, duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    fn player() -> AnimationPlayer {
+        AnimationPlayer::looping(
+            SkeletonID::from_name("skeleton"),
+            AnimationClipID::from_name("walk"),
+        )
+    }
+
+    #[test]
+    fn looping_playback_wraps_around() {
+        let mut player = player();
+        player.advance(2.5, 2.0, 2.0);
+        assert_abs_diff_eq!(player.time, 0.5);
+    }
+
+    #[test]
+    fn playing_once_holds_last_frame() {
+        let mut player = AnimationPlayer::once(
+            SkeletonID::from_name("skeleton"),
+            AnimationClipID::from_name("walk"),
+        );
+        player.advance(2.5, 2.0, 2.0);
+        assert_abs_diff_eq!(player.time, 2.0);
+    }
+
+    #[test]
+    fn paused_playback_does_not_advance() {
+        let mut player = player();
+        player.flags |= AnimationPlayerFlags::PAUSED;
+        player.advance(1.0, 2.0, 2.0);
+        assert_eq!(player.time, 0.0);
+    }
+
+    #[test]
+    fn playback_speed_scales_time_step() {
+        let mut player = player();
+        player.speed = -0.5;
+        player.advance(1.0, 2.0, 2.0);
+        assert_abs_diff_eq!(player.time, 1.5);
+    }
+
+    #[test]
+    fn completed_cross_fade_switches_clip() {
+        let walk = AnimationClipID::from_name("walk");
+        let run = AnimationClipID::from_name("run");
+        let mut player = player();
+        player.cross_fade_to(run, 1.0);
+
+        player.advance(0.5, 2.0, 3.0);
+        assert_eq!(player.clip_id, walk);
+        assert_abs_diff_eq!(player.blend_weight, 0.5);
+
+        player.advance(0.6, 2.0, 3.0);
+        assert_eq!(player.clip_id, run);
+        assert_abs_diff_eq!(player.time, 1.1);
+        assert!(!player.is_blending());
+    }
+
+    #[test]
+    fn cross_fade_without_duration_switches_immediately() {
+        let run = AnimationClipID::from_name("run");
+        let mut player = player();
+        player.time = 1.0;
+        player.cross_fade_to(run, 0.0);
+        assert_eq!(player.clip_id, run);
+        assert_eq!(player.time, 0.0);
+        assert!(!player.is_blending());
+    }
+}
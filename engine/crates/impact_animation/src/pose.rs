@@ -0,0 +1,167 @@
+//! Poses of skeletons.
+
+use impact_math::{matrix::Matrix4, quaternion::UnitQuaternion, vector::Vector3};
+
+/// The transform of a joint relative to its parent joint, decomposed into
+/// translation, rotation and (non-uniform) scaling so that it can be
+/// interpolated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JointTransform {
+    pub translation: Vector3,
+    pub rotation: UnitQuaternion,
+    pub scaling: Vector3,
+}
+
+/// The local transform of every joint in a skeleton, ordered like the joints
+/// of the skeleton.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pose {
+    joint_transforms: Vec<JointTransform>,
+}
+
+impl JointTransform {
+    /// The transform that leaves a joint at its parent's origin and
+    /// orientation.
+    pub const IDENTITY: Self = Self {
+        translation: Vector3::zeros(),
+        rotation: UnitQuaternion::identity(),
+        scaling: Vector3::same(1.0),
+    };
+
+    /// Creates a new joint transform with the given components.
+    pub fn new(translation: Vector3, rotation: UnitQuaternion, scaling: Vector3) -> Self {
+        Self {
+            translation,
+            rotation,
+            scaling,
+        }
+    }
+
+    /// Interpolates between this transform (`weight = 0`) and the given
+    /// transform (`weight = 1`). Translation and scaling are interpolated
+    /// linearly and rotation spherically.
+    pub fn interpolated(&self, other: &Self, weight: f32) -> Self {
+        Self {
+            translation: lerp(&self.translation, &other.translation, weight),
+            rotation: self.rotation.slerp(&other.rotation, weight),
+            scaling: lerp(&self.scaling, &other.scaling, weight),
+        }
+    }
+
+    /// Computes the homogeneous matrix applying the scaling, rotation and
+    /// translation, in that order.
+    pub fn to_matrix(&self) -> Matrix4 {
+        Matrix4::from_scale_rotation_translation(&self.scaling, &self.rotation, &self.translation)
+    }
+}
+
+impl Default for JointTransform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Pose {
+    /// Creates a pose with the given joint transforms.
+    pub fn new(joint_transforms: Vec<JointTransform>) -> Self {
+        Self { joint_transforms }
+    }
+
+    /// Returns the number of joints in the pose.
+    pub fn n_joints(&self) -> usize {
+        self.joint_transforms.len()
+    }
+
+    /// Returns the local transform of each joint.
+    pub fn joint_transforms(&self) -> &[JointTransform] {
+        &self.joint_transforms
+    }
+
+    /// Returns a mutable reference to the local transform of each joint.
+    pub fn joint_transforms_mut(&mut self) -> &mut [JointTransform] {
+        &mut self.joint_transforms
+    }
+
+    /// Blends this pose (`weight = 0`) with the given pose (`weight = 1`)
+    /// in place by interpolating the transform of each joint.
+    ///
+    /// # Panics
+    /// If the poses do not have the same number of joints.
+    pub fn blend_with(&mut self, other: &Self, weight: f32) {
+        assert_eq!(
+            self.n_joints(),
+            other.n_joints(),
+            "Tried to blend poses with different numbers of joints"
+        );
+        for (transform, other_transform) in self
+            .joint_transforms
+            .iter_mut()
+            .zip(&other.joint_transforms)
+        {
+            *transform = transform.interpolated(other_transform, weight);
+        }
+    }
+
+    /// Overwrites this pose with the given pose, reusing the allocation.
+    pub fn copy_from(&mut self, other: &Self) {
+        self.joint_transforms.clear();
+        self.joint_transforms
+            .extend_from_slice(&other.joint_transforms);
+    }
+}
+
+pub(crate) fn lerp(a: &Vector3, b: &Vector3, t: f32) -> Vector3 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use impact_math::{consts::f32::PI, point::Point3, vector::UnitVector3};
+
+    #[test]
+    fn interpolating_joint_transforms_halfway_gives_midpoint() {
+        let a = JointTransform::IDENTITY;
+        let b = JointTransform::new(
+            Vector3::new(2.0, 0.0, 0.0),
+            UnitQuaternion::from_axis_angle(&UnitVector3::unit_y(), PI / 2.0),
+            Vector3::same(3.0),
+        );
+        let mid = a.interpolated(&b, 0.5);
+
+        assert_abs_diff_eq!(mid.translation, Vector3::new(1.0, 0.0, 0.0));
+        assert_abs_diff_eq!(mid.scaling, Vector3::same(2.0));
+        assert_abs_diff_eq!(mid.rotation.angle(), PI / 4.0, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn blending_poses_with_zero_weight_keeps_original() {
+        let original = Pose::new(vec![JointTransform::new(
+            Vector3::new(1.0, 2.0, 3.0),
+            UnitQuaternion::identity(),
+            Vector3::same(1.0),
+        )]);
+        let other = Pose::new(vec![JointTransform::IDENTITY]);
+
+        let mut blended = original.clone();
+        blended.blend_with(&other, 0.0);
+        assert_eq!(blended, original);
+
+        blended.blend_with(&other, 1.0);
+        assert_abs_diff_eq!(blended.joint_transforms()[0].translation, Vector3::zeros());
+    }
+
+    #[test]
+    fn joint_transform_matrix_scales_then_rotates_then_translates() {
+        let transform = JointTransform::new(
+            Vector3::new(0.0, 0.0, 1.0),
+            UnitQuaternion::from_axis_angle(&UnitVector3::unit_z(), PI / 2.0),
+            Vector3::same(2.0),
+        );
+        let point = transform
+            .to_matrix()
+            .transform_point(&Point3::new(1.0, 0.0, 0.0));
+        assert_abs_diff_eq!(point, Point3::new(0.0, 2.0, 1.0), epsilon = 1e-6);
+    }
+}
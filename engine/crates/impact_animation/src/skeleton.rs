@@ -0,0 +1,320 @@
+//! Skeletons made up of hierarchies of joints.
+
+use crate::pose::{JointTransform, Pose};
+use anyhow::{Result, bail};
+use impact_math::{
+    hash64,
+    matrix::{Matrix4, Matrix4C},
+    stringhash64_newtype,
+};
+use impact_resource::{Resource, ResourceID, registry::ImmutableResourceRegistry};
+
+/// The maximum number of joints a skeleton can have. This bounds the joint
+/// indices that skinned mesh vertices can refer to.
+pub const MAX_SKELETON_JOINTS: usize = 256;
+
+stringhash64_newtype!(
+    /// Identifier for a [`Skeleton`].
+    [pub] SkeletonID
+);
+
+/// A registry of loaded [`Skeleton`]s.
+pub type SkeletonRegistry = ImmutableResourceRegistry<Skeleton>;
+
+/// A hierarchy of joints that the vertices of a skinned mesh are bound to.
+///
+/// The joints are stored in the order that the joint indices in the
+/// [`VertexSkinWeights`](impact_mesh::VertexSkinWeights) of the mesh refer to.
+#[derive(Clone, Debug)]
+pub struct Skeleton {
+    joints: Vec<Joint>,
+    root_transform: Matrix4,
+    evaluation_order: Vec<usize>,
+}
+
+/// A joint in a [`Skeleton`].
+#[derive(Clone, Debug)]
+pub struct Joint {
+    /// The name of the joint, used for looking up joints when attaching
+    /// animation channels.
+    pub name: String,
+    /// The index of the parent joint, or [`None`] for root joints.
+    pub parent: Option<usize>,
+    /// The transform of the joint relative to its parent when no animation is
+    /// applied.
+    pub rest_transform: JointTransform,
+    /// The transform from the model space of the bound mesh to the space of
+    /// the joint in the bind pose.
+    pub inverse_bind_matrix: Matrix4,
+}
+
+impl SkeletonID {
+    /// Creates a skeleton ID hashed from the given name.
+    pub fn from_name(name: &str) -> Self {
+        Self(hash64!(name))
+    }
+}
+
+impl ResourceID for SkeletonID {}
+
+impl Skeleton {
+    /// Creates a new skeleton with the given joints. The transform of each
+    /// root joint is taken to be relative to the given root transform, which
+    /// maps from the space the root joints are defined in to the model space
+    /// of the bound mesh.
+    ///
+    /// # Errors
+    /// Returns an error if there are no joints or more than
+    /// [`MAX_SKELETON_JOINTS`], if a joint refers to a parent that does not
+    /// exist, or if the joint hierarchy has cycles.
+    pub fn new(joints: Vec<Joint>, root_transform: Matrix4) -> Result<Self> {
+        if joints.is_empty() {
+            bail!("Tried to create skeleton with no joints");
+        }
+        if joints.len() > MAX_SKELETON_JOINTS {
+            bail!(
+                "Skeleton has {} joints, but at most {MAX_SKELETON_JOINTS} are supported",
+                joints.len()
+            );
+        }
+
+        let evaluation_order = Self::determine_evaluation_order(&joints)?;
+
+        Ok(Self {
+            joints,
+            root_transform,
+            evaluation_order,
+        })
+    }
+
+    /// Returns the number of joints in the skeleton.
+    pub fn n_joints(&self) -> usize {
+        self.joints.len()
+    }
+
+    /// Returns the joints of the skeleton.
+    pub fn joints(&self) -> &[Joint] {
+        &self.joints
+    }
+
+    /// Returns the index of the joint with the given name, if present.
+    pub fn find_joint(&self, name: &str) -> Option<usize> {
+        self.joints.iter().position(|joint| joint.name == name)
+    }
+
+    /// Returns the transform from the space the root joints are defined in to
+    /// the model space of the bound mesh.
+    pub fn root_transform(&self) -> &Matrix4 {
+        &self.root_transform
+    }
+
+    /// Creates the pose where every joint has its rest transform.
+    pub fn rest_pose(&self) -> Pose {
+        Pose::new(
+            self.joints
+                .iter()
+                .map(|joint| joint.rest_transform)
+                .collect(),
+        )
+    }
+
+    /// Computes the skinning matrix of each joint for the given pose and
+    /// writes them to the given vector, replacing its previous contents. The
+    /// skinning matrix of a joint transforms a vertex from its bind pose
+    /// position in model space to its posed position in model space.
+    ///
+    /// # Panics
+    /// If the pose does not have the same number of joints as the skeleton.
+    pub fn compute_joint_matrices(&self, pose: &Pose, joint_matrices: &mut Vec<Matrix4C>) {
+        assert_eq!(
+            pose.n_joints(),
+            self.n_joints(),
+            "Tried to compute joint matrices for pose with wrong number of joints"
+        );
+
+        let mut global_transforms = vec![Matrix4::identity(); self.n_joints()];
+
+        for &joint_idx in &self.evaluation_order {
+            let local_transform = pose.joint_transforms()[joint_idx].to_matrix();
+            let parent_transform = match self.joints[joint_idx].parent {
+                Some(parent_idx) => &global_transforms[parent_idx],
+                None => &self.root_transform,
+            };
+            let global_transform = parent_transform * local_transform;
+            global_transforms[joint_idx] = global_transform;
+        }
+
+        joint_matrices.clear();
+        joint_matrices.extend(global_transforms.iter().zip(&self.joints).map(
+            |(global_transform, joint)| (global_transform * joint.inverse_bind_matrix).compact(),
+        ));
+    }
+
+    /// Orders the joints so that every joint comes after its parent.
+    fn determine_evaluation_order(joints: &[Joint]) -> Result<Vec<usize>> {
+        let mut depths = vec![None; joints.len()];
+
+        for start_idx in 0..joints.len() {
+            let mut chain = Vec::new();
+            let mut idx = start_idx;
+
+            let base_depth = loop {
+                if let Some(depth) = depths[idx] {
+                    break depth;
+                }
+                if chain.len() > joints.len() {
+                    bail!("Skeleton joint hierarchy contains a cycle");
+                }
+                chain.push(idx);
+
+                match joints[idx].parent {
+                    Some(parent_idx) if parent_idx >= joints.len() => {
+                        bail!(
+                            "Skeleton joint {idx} has invalid parent index {parent_idx} ({} joints)",
+                            joints.len()
+                        );
+                    }
+                    Some(parent_idx) => {
+                        idx = parent_idx;
+                    }
+                    None => break 0,
+                }
+            };
+
+            for (offset, &chain_idx) in chain.iter().rev().enumerate() {
+                depths[chain_idx] = Some(base_depth + offset + 1);
+            }
+        }
+
+        let mut evaluation_order: Vec<_> = (0..joints.len()).collect();
+        evaluation_order.sort_by_key(|&idx| depths[idx]);
+
+        Ok(evaluation_order)
+    }
+}
+
+impl Resource for Skeleton {
+    type ID = SkeletonID;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use impact_math::{
+        point::Point3,
+        quaternion::UnitQuaternion,
+        vector::{Vector3, Vector4},
+    };
+
+    fn joint(name: &str, parent: Option<usize>, translation: Vector3) -> Joint {
+        Joint {
+            name: name.to_string(),
+            parent,
+            rest_transform: JointTransform::new(
+                translation,
+                UnitQuaternion::identity(),
+                Vector3::same(1.0),
+            ),
+            inverse_bind_matrix: Matrix4::identity(),
+        }
+    }
+
+    fn translation_matrix(translation: Vector3) -> Matrix4 {
+        let mut matrix = Matrix4::identity();
+        matrix.translate_transform(&translation);
+        matrix
+    }
+
+    fn transform_point(matrix: &Matrix4C, point: Point3) -> Point3 {
+        let transformed = matrix.aligned() * Vector4::new(point.x(), point.y(), point.z(), 1.0);
+        Point3::new(transformed.x(), transformed.y(), transformed.z())
+    }
+
+    #[test]
+    fn creating_skeleton_without_joints_fails() {
+        assert!(Skeleton::new(Vec::new(), Matrix4::identity()).is_err());
+    }
+
+    #[test]
+    fn creating_skeleton_with_invalid_parent_fails() {
+        let joints = vec![joint("root", Some(3), Vector3::zeros())];
+        assert!(Skeleton::new(joints, Matrix4::identity()).is_err());
+    }
+
+    #[test]
+    fn creating_skeleton_with_cyclic_hierarchy_fails() {
+        let joints = vec![
+            joint("a", Some(1), Vector3::zeros()),
+            joint("b", Some(0), Vector3::zeros()),
+        ];
+        assert!(Skeleton::new(joints, Matrix4::identity()).is_err());
+    }
+
+    #[test]
+    fn joint_matrices_are_identity_in_bind_pose() {
+        let offsets = [Vector3::new(0.0, 1.0, 0.0), Vector3::new(0.0, 2.0, 0.0)];
+        let mut joints = vec![
+            joint("root", None, offsets[0]),
+            joint("child", Some(0), offsets[1]),
+        ];
+        joints[0].inverse_bind_matrix = translation_matrix(-offsets[0]);
+        joints[1].inverse_bind_matrix = translation_matrix(-(offsets[0] + offsets[1]));
+
+        let skeleton = Skeleton::new(joints, Matrix4::identity()).unwrap();
+
+        let mut joint_matrices = Vec::new();
+        skeleton.compute_joint_matrices(&skeleton.rest_pose(), &mut joint_matrices);
+
+        assert_eq!(joint_matrices.len(), 2);
+        for matrix in &joint_matrices {
+            assert_abs_diff_eq!(matrix.aligned(), Matrix4::identity(), epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn child_joints_are_evaluated_after_parents_regardless_of_order() {
+        // The child is listed before its parent
+        let joints = vec![
+            joint("child", Some(1), Vector3::new(1.0, 0.0, 0.0)),
+            joint("root", None, Vector3::new(0.0, 0.0, 5.0)),
+        ];
+        let skeleton = Skeleton::new(joints, Matrix4::identity()).unwrap();
+
+        let mut joint_matrices = Vec::new();
+        skeleton.compute_joint_matrices(&skeleton.rest_pose(), &mut joint_matrices);
+
+        assert_abs_diff_eq!(
+            transform_point(&joint_matrices[0], Point3::origin()),
+            Point3::new(1.0, 0.0, 5.0),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn root_transform_is_applied_to_root_joints() {
+        let joints = vec![joint("root", None, Vector3::zeros())];
+        let skeleton =
+            Skeleton::new(joints, translation_matrix(Vector3::new(0.0, 3.0, 0.0))).unwrap();
+
+        let mut joint_matrices = Vec::new();
+        skeleton.compute_joint_matrices(&skeleton.rest_pose(), &mut joint_matrices);
+
+        assert_abs_diff_eq!(
+            transform_point(&joint_matrices[0], Point3::origin()),
+            Point3::new(0.0, 3.0, 0.0),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn joints_can_be_found_by_name() {
+        let joints = vec![
+            joint("root", None, Vector3::zeros()),
+            joint("hand", Some(0), Vector3::zeros()),
+        ];
+        let skeleton = Skeleton::new(joints, Matrix4::identity()).unwrap();
+        assert_eq!(skeleton.find_joint("hand"), Some(1));
+        assert_eq!(skeleton.find_joint("foot"), None);
+    }
+}
@@ -0,0 +1,71 @@
+//! ECS systems for skeletal animation.
+
+use crate::{
+    clip::AnimationClipRegistry, palette::JointPaletteManager, player::AnimationPlayer, pose::Pose,
+    skeleton::SkeletonRegistry,
+};
+use impact_ecs::{query, world::World as ECSWorld};
+use impact_id::EntityID;
+
+/// Advances the playback of every [`AnimationPlayer`] by the given time step,
+/// computes the resulting joint matrices and adds them as palettes to the
+/// given [`JointPaletteManager`] after clearing the palettes of the previous
+/// frame. The given closure is called with each entity and the offset of its
+/// palette. Entities whose skeleton or clips are not loaded are given the bind
+/// pose palette.
+pub fn advance_animation_players(
+    ecs_world: &ECSWorld,
+    skeleton_registry: &SkeletonRegistry,
+    clip_registry: &AnimationClipRegistry,
+    joint_palette_manager: &mut JointPaletteManager,
+    time_step_duration: f32,
+    mut set_joint_palette_offset: impl FnMut(EntityID, u32),
+) {
+    joint_palette_manager.clear_palettes();
+
+    let mut pose = Pose::new(Vec::new());
+    let mut blend_pose = Pose::new(Vec::new());
+    let mut joint_matrices = Vec::new();
+
+    query!(
+        ecs_world,
+        |entity_id: EntityID, player: &mut AnimationPlayer| {
+            let (Some(skeleton), Some(clip)) = (
+                skeleton_registry.get(player.skeleton_id),
+                clip_registry.get(player.clip_id),
+            ) else {
+                set_joint_palette_offset(entity_id, JointPaletteManager::BIND_POSE_OFFSET);
+                return;
+            };
+
+            let blend_clip = if player.is_blending() {
+                clip_registry.get(player.blend_clip_id)
+            } else {
+                None
+            };
+
+            player.advance(
+                time_step_duration,
+                clip.duration(),
+                blend_clip.map_or(0.0, |blend_clip| blend_clip.duration()),
+            );
+
+            // The blended clip may have replaced the current clip
+            let clip = clip_registry.get(player.clip_id).unwrap_or(clip);
+            let blend_clip = blend_clip.filter(|_| player.is_blending());
+
+            player.compute_pose(
+                &skeleton.rest_pose(),
+                clip,
+                blend_clip,
+                &mut pose,
+                &mut blend_pose,
+            );
+
+            skeleton.compute_joint_matrices(&pose, &mut joint_matrices);
+
+            let offset = joint_palette_manager.add_palette(&joint_matrices);
+            set_joint_palette_offset(entity_id, offset);
+        }
+    );
+}
@@ -76,7 +76,8 @@ static JITTER_OFFSETS: LazyLock<[Vector4C; JITTER_COUNT]> =
     LazyLock::new(CameraProjectionUniform::generate_jitter_offsets);
 
 impl CameraGPUResource {
-    const VISIBILITY: wgpu::ShaderStages = wgpu::ShaderStages::VERTEX_FRAGMENT;
+    const VISIBILITY: wgpu::ShaderStages =
+        wgpu::ShaderStages::VERTEX_FRAGMENT.union(wgpu::ShaderStages::COMPUTE);
     const LAYOUT_ID: ConstStringHash64 = ConstStringHash64::new("CameraProjectionUniform");
 
     /// Creates a new manager with GPU resources initialized from the given
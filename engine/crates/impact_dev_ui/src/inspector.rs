@@ -0,0 +1,103 @@
+use crate::UserInterfaceConfig;
+use impact::{
+    egui::{
+        Color32, Context, Frame, Grid, ScrollArea, SidePanel, Ui, ecolor::linear_u8_from_linear_f32,
+    },
+    engine::Engine,
+    rendering::picking::PickedEntity,
+};
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct InspectorPanel;
+
+const DEFAULT_PANEL_WIDTH: f32 = 240.0;
+
+impl InspectorPanel {
+    pub fn run(&mut self, ctx: &Context, config: &UserInterfaceConfig, engine: &Engine) {
+        let picking_enabled = engine.entity_picking_enabled();
+
+        if picking_enabled {
+            request_pick_if_clicked_outside_ui(ctx, engine);
+        }
+
+        let frame = Frame::side_top_panel(&ctx.style());
+        let fill = Color32::from_black_alpha(linear_u8_from_linear_f32(config.alpha).max(1));
+
+        SidePanel::right("inspector_panel")
+            .frame(Frame { fill, ..frame })
+            .default_width(DEFAULT_PANEL_WIDTH)
+            .show(ctx, |ui| {
+                ui.strong("Inspector");
+                ui.separator();
+
+                if !picking_enabled {
+                    ui.label("Entity picking is disabled");
+                    return;
+                }
+
+                match engine.picked_entity() {
+                    Some(picked_entity) => picked_entity_info(ui, &picked_entity),
+                    None => {
+                        ui.label("Click on an entity to inspect it");
+                    }
+                }
+            });
+    }
+}
+
+fn request_pick_if_clicked_outside_ui(ctx: &Context, engine: &Engine) {
+    if ctx.is_pointer_over_area() {
+        return;
+    }
+    let Some(position) = ctx.input(|input| {
+        input
+            .pointer
+            .primary_clicked()
+            .then(|| input.pointer.interact_pos())
+            .flatten()
+    }) else {
+        return;
+    };
+
+    let pixels_per_point = ctx.pixels_per_point();
+    let x = (position.x * pixels_per_point).max(0.0) as u32;
+    let y = (position.y * pixels_per_point).max(0.0) as u32;
+
+    engine.request_entity_pick(x, y);
+}
+
+fn picked_entity_info(ui: &mut Ui, picked_entity: &PickedEntity) {
+    Grid::new("picked_entity_info").show(ui, |ui| {
+        ui.label("Entity");
+        ui.monospace(picked_entity.entity_id.to_string());
+        ui.end_row();
+
+        if let Some(position) = &picked_entity.world_position {
+            ui.label("Position");
+            ui.monospace(format!(
+                "({:.2}, {:.2}, {:.2})",
+                position.x(),
+                position.y(),
+                position.z()
+            ));
+            ui.end_row();
+        }
+
+        if let Some([i, j, k]) = picked_entity.voxel_indices {
+            ui.label("Voxel");
+            ui.monospace(format!("[{i}, {j}, {k}]"));
+            ui.end_row();
+        }
+    });
+
+    ui.separator();
+    ui.strong("Components");
+
+    ScrollArea::vertical()
+        .auto_shrink([false; 2])
+        .show(ui, |ui| {
+            for name in &picked_entity.component_names {
+                ui.label(*name);
+            }
+        });
+}
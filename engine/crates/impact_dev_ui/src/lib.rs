@@ -3,8 +3,10 @@
 #![allow(clippy::unused_self)]
 
 mod command;
+mod inspector;
 pub mod option_panels;
 pub mod overlay;
+mod resource_reload_overlay;
 mod time_overlay;
 mod timing_panels;
 mod toolbar;
@@ -13,6 +15,7 @@ pub use command::{UICommand, UICommandQueue};
 
 use anyhow::Result;
 use impact::{
+    command::rendering::RenderingAdminCommand,
     command::{
         AdminCommand, capture::CaptureAdminCommand, controller::ControlAdminCommand,
         instrumentation::InstrumentationAdminCommand, physics::PhysicsAdminCommand,
@@ -22,10 +25,12 @@ use impact::{
     engine::Engine,
     ui,
 };
+use inspector::InspectorPanel;
 use option_panels::{
     gizmo::GizmoOptionPanel, physics::PhysicsOptionPanel, rendering::RenderingOptionPanel,
     ui::UIOptionPanel,
 };
+use resource_reload_overlay::ResourceReloadOverlay;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 use time_overlay::TimeOverlay;
@@ -42,7 +47,9 @@ pub struct UserInterface {
     gizmo_option_panel: GizmoOptionPanel,
     task_timing_panel: TaskTimingPanel,
     render_pass_timing_panel: RenderPassTimingPanel,
+    inspector_panel: InspectorPanel,
     time_overlay: TimeOverlay,
+    resource_reload_overlay: ResourceReloadOverlay,
     config: UserInterfaceConfig,
     screenshot_requested: bool,
     single_step_requested: bool,
@@ -60,6 +67,7 @@ pub struct UserInterfaceConfig {
     pub show_gizmo_options: bool,
     pub show_task_timings: bool,
     pub show_render_pass_timings: bool,
+    pub show_inspector: bool,
     pub show_time_overlay: bool,
     pub disable_cursor_capture: bool,
     pub hide_ui_during_screenshots: bool,
@@ -108,6 +116,11 @@ impl UserInterface {
                 self.config.show_render_pass_timings,
             )),
         ));
+        if self.config.show_inspector {
+            engine.enqueue_admin_command(AdminCommand::Rendering(
+                RenderingAdminCommand::SetEntityPicking(ToActiveState::Enabled),
+            ));
+        }
     }
 
     pub fn run(
@@ -168,6 +181,9 @@ impl UserInterface {
                 if self.config.show_render_pass_timings {
                     self.render_pass_timing_panel.run(ctx, &self.config, engine);
                 }
+                if self.config.show_inspector {
+                    self.inspector_panel.run(ctx, &self.config, engine);
+                }
                 custom_elements.run_option_panels(ctx, self.config.alpha);
             }
 
@@ -175,6 +191,10 @@ impl UserInterface {
                 self.time_overlay.run(ctx, engine);
             }
 
+            // Reload failures are always shown, since they would otherwise
+            // go unnoticed
+            self.resource_reload_overlay.run(ctx, engine);
+
             custom_elements.run_overlays(ctx);
 
             // Re-disable the simulation if we requested a single step in the
@@ -241,6 +261,7 @@ impl Default for UserInterfaceConfig {
             show_gizmo_options: false,
             show_task_timings: false,
             show_render_pass_timings: false,
+            show_inspector: false,
             show_time_overlay: true,
             disable_cursor_capture: false,
             hide_ui_during_screenshots: true,
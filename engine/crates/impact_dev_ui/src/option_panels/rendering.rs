@@ -16,7 +16,7 @@ use impact::{
 };
 use impact_math::bounds::{Bounds, UpperExclusiveBounds};
 use impact_rendering::postprocessing::{
-    capturing::{SensorSensitivity, dynamic_range_compression::ToneMappingMethod},
+    capturing::{FocusDistance, SensorSensitivity, dynamic_range_compression::ToneMappingMethod},
     render_attachment_visualization::RenderAttachmentVisualizationPasses,
 };
 
@@ -98,6 +98,92 @@ mod ambient_occlusion {
     }
 }
 
+mod screen_space_reflection {
+    pub mod docs {
+        use crate::option_panels::LabelAndHoverText;
+
+        pub const ENABLED: LabelAndHoverText = LabelAndHoverText {
+            label: "Screen-space reflections",
+            hover_text: "Whether screen-space reflections are enabled.",
+        };
+        pub const MAX_STEP_COUNT: LabelAndHoverText = LabelAndHoverText {
+            label: "Max steps",
+            hover_text: "\
+                The maximum number of steps to take through the hierarchical depth \
+                pyramid along each reflected ray.",
+        };
+        pub const MAX_DISTANCE: LabelAndHoverText = LabelAndHoverText {
+            label: "Max distance",
+            hover_text: "\
+                The maximum distance a reflected ray will be marched before it is \
+                considered to have missed.",
+        };
+        pub const THICKNESS: LabelAndHoverText = LabelAndHoverText {
+            label: "Thickness",
+            hover_text: "\
+                How far behind the depth buffer surface a ray can be while still being \
+                considered to intersect the surface.",
+        };
+        pub const ROUGHNESS_CUTOFF: LabelAndHoverText = LabelAndHoverText {
+            label: "Roughness cutoff",
+            hover_text: "\
+                The roughness above which surfaces receive no screen-space reflections. \
+                Reflections are faded out smoothly as the roughness approaches this value.",
+        };
+        pub const CURRENT_FRAME_WEIGHT: LabelAndHoverText = LabelAndHoverText {
+            label: "Current frame weight",
+            hover_text: "\
+                How much the reflections traced in the current frame should be weighted \
+                compared to the reflections accumulated over previous frames.",
+        };
+        pub const VARIANCE_CLIPPING_THRESHOLD: LabelAndHoverText = LabelAndHoverText {
+            label: "Variance clipping",
+            hover_text: "\
+                The maximum variance allowed between the current and accumulated \
+                reflections when performing temporal blending.",
+        };
+    }
+    pub mod ranges {
+        use std::ops::RangeInclusive;
+
+        pub const MAX_STEP_COUNT: RangeInclusive<u32> = 1..=256;
+        pub const MAX_DISTANCE: RangeInclusive<f32> = 1.0..=200.0;
+        pub const THICKNESS: RangeInclusive<f32> = 0.01..=2.0;
+        pub const ROUGHNESS_CUTOFF: RangeInclusive<f32> = 0.0..=1.0;
+        pub const CURRENT_FRAME_WEIGHT: RangeInclusive<f32> = 0.0..=1.0;
+        pub const VARIANCE_CLIPPING_THRESHOLD: RangeInclusive<f32> = 0.1..=2.0;
+    }
+}
+
+mod volumetric_fog {
+    pub mod docs {
+        use crate::option_panels::LabelAndHoverText;
+
+        pub const ENABLED: LabelAndHoverText = LabelAndHoverText {
+            label: "Volumetric fog",
+            hover_text: "\
+                Whether volumetric fog is enabled. The fog is only visible when the \
+                medium the scene is immersed in scatters or absorbs light.",
+        };
+        pub const MAX_DISTANCE: LabelAndHoverText = LabelAndHoverText {
+            label: "Max distance",
+            hover_text: "\
+                The distance from the camera out to which the fog is evaluated. Beyond \
+                this distance, no additional scattering or extinction is accumulated.",
+        };
+        pub const DENSITY_SCALE: LabelAndHoverText = LabelAndHoverText {
+            label: "Density scale",
+            hover_text: "Factor by which to scale the extinction coefficient of the medium.",
+        };
+    }
+    pub mod ranges {
+        use std::ops::RangeInclusive;
+
+        pub const MAX_DISTANCE: RangeInclusive<f32> = 1.0..=1000.0;
+        pub const DENSITY_SCALE: RangeInclusive<f32> = 0.0..=100.0;
+    }
+}
+
 mod temporal_anti_aliasing {
     pub mod docs {
         use crate::option_panels::LabelAndHoverText;
@@ -213,6 +299,89 @@ mod camera {
     pub const DEFAULT_ISO: f32 = 100.0;
 }
 
+mod depth_of_field {
+    pub mod docs {
+        use crate::option_panels::LabelAndHoverText;
+
+        pub const ENABLED: LabelAndHoverText = LabelAndHoverText {
+            label: "Depth of field",
+            hover_text: "Whether depth of field is enabled.",
+        };
+        pub const FOCAL_LENGTH: LabelAndHoverText = LabelAndHoverText {
+            label: "Focal length",
+            hover_text: "\
+                The focal length of the lens. Together with the field of view, this \
+                determines the size of the sensor.",
+        };
+        pub const AUTOFOCUS: LabelAndHoverText = LabelAndHoverText {
+            label: "Autofocus",
+            hover_text: "\
+                Whether to focus on the closest surface at the center of the view \
+                rather than at a fixed distance.",
+        };
+        pub const FOCUS_DISTANCE: LabelAndHoverText = LabelAndHoverText {
+            label: "Focus distance",
+            hover_text: "The distance from the camera to the plane in focus.",
+        };
+        pub const N_SAMPLES: LabelAndHoverText = LabelAndHoverText {
+            label: "Samples",
+            hover_text: "\
+                The number of luminance samples to gather for each pixel. More samples \
+                give smoother blur at a higher cost.",
+        };
+        pub const MAX_BLUR_RADIUS: LabelAndHoverText = LabelAndHoverText {
+            label: "Max blur radius",
+            hover_text: "The maximum radius of the blur, as a fraction of the image height.",
+        };
+    }
+    pub mod ranges {
+        use std::{num::NonZeroU32, ops::RangeInclusive};
+
+        pub const FOCAL_LENGTH: RangeInclusive<f32> = 0.008..=0.6;
+        pub const FOCUS_DISTANCE: RangeInclusive<f32> = 0.1..=1e4;
+        pub const N_SAMPLES: RangeInclusive<NonZeroU32> =
+            NonZeroU32::new(1).unwrap()..=NonZeroU32::new(128).unwrap();
+        pub const MAX_BLUR_RADIUS: RangeInclusive<f32> = 1e-3..=5e-2;
+    }
+
+    pub const DEFAULT_FOCUS_DISTANCE: f32 = 10.0;
+}
+
+mod motion_blur {
+    pub mod docs {
+        use crate::option_panels::LabelAndHoverText;
+
+        pub const ENABLED: LabelAndHoverText = LabelAndHoverText {
+            label: "Motion blur",
+            hover_text: "Whether motion blur is enabled.",
+        };
+        pub const SHUTTER_ANGLE: LabelAndHoverText = LabelAndHoverText {
+            label: "Shutter angle",
+            hover_text: "\
+                The fraction of the frame duration that the shutter is open, expressed \
+                as the angle of a rotary disc shutter.",
+        };
+        pub const N_SAMPLES: LabelAndHoverText = LabelAndHoverText {
+            label: "Samples",
+            hover_text: "\
+                The number of luminance samples to gather along the motion path of each \
+                pixel.",
+        };
+        pub const MAX_BLUR_LENGTH: LabelAndHoverText = LabelAndHoverText {
+            label: "Max blur length",
+            hover_text: "The maximum length of the blur, as a fraction of the image height.",
+        };
+    }
+    pub mod ranges {
+        use std::{num::NonZeroU32, ops::RangeInclusive};
+
+        pub const SHUTTER_ANGLE: RangeInclusive<f32> = 0.0..=360.0;
+        pub const N_SAMPLES: RangeInclusive<NonZeroU32> =
+            NonZeroU32::new(1).unwrap()..=NonZeroU32::new(64).unwrap();
+        pub const MAX_BLUR_LENGTH: RangeInclusive<f32> = 1e-3..=2e-1;
+    }
+}
+
 mod bloom {
     pub mod docs {
         use crate::option_panels::LabelAndHoverText;
@@ -274,6 +443,19 @@ mod wireframe {
     }
 }
 
+mod entity_picking {
+    pub mod docs {
+        use crate::option_panels::LabelAndHoverText;
+
+        pub const ENABLED: LabelAndHoverText = LabelAndHoverText {
+            label: "Entity picking",
+            hover_text: "\
+                Whether the ID of the entity covering each pixel should be rendered so that \
+                entities can be picked with the cursor.",
+        };
+    }
+}
+
 mod render_attachment {
     pub mod docs {
         use crate::option_panels::LabelAndHoverText;
@@ -310,6 +492,12 @@ impl RenderingOptionPanel {
             option_group(ui, "ambient_occlusion_options", |ui| {
                 ambient_occlusion_options(ui, engine);
             });
+            option_group(ui, "screen_space_reflection_options", |ui| {
+                screen_space_reflection_options(ui, engine);
+            });
+            option_group(ui, "volumetric_fog_options", |ui| {
+                volumetric_fog_options(ui, engine);
+            });
             option_group(ui, "temporal_anti_aliasing_options", |ui| {
                 temporal_anti_aliasing_options(ui, engine);
             });
@@ -317,6 +505,12 @@ impl RenderingOptionPanel {
             option_group(ui, "camera_options", |ui| {
                 camera_options(ui, engine);
             });
+            option_group(ui, "depth_of_field_options", |ui| {
+                depth_of_field_options(ui, engine);
+            });
+            option_group(ui, "motion_blur_options", |ui| {
+                motion_blur_options(ui, engine);
+            });
             option_group(ui, "bloom_options", |ui| {
                 bloom_options(ui, engine);
             });
@@ -326,6 +520,9 @@ impl RenderingOptionPanel {
             option_group(ui, "wireframe_options", |ui| {
                 wireframe_options(ui, engine);
             });
+            option_group(ui, "entity_picking_options", |ui| {
+                entity_picking_options(ui, engine);
+            });
             option_group(ui, "render_attachment_options", |ui| {
                 render_attachment_options(ui, engine);
             });
@@ -478,6 +675,149 @@ fn ambient_occlusion_options(ui: &mut Ui, engine: &Engine) {
     }
 }
 
+fn screen_space_reflection_options(ui: &mut Ui, engine: &Engine) {
+    let mut config = engine.screen_space_reflection_config();
+    let mut config_changed = false;
+
+    if option_checkbox(
+        ui,
+        &mut config.enabled,
+        screen_space_reflection::docs::ENABLED,
+    )
+    .changed()
+    {
+        config_changed = true;
+    }
+
+    if option_slider(
+        ui,
+        screen_space_reflection::docs::MAX_STEP_COUNT,
+        Slider::new(
+            &mut config.max_step_count,
+            screen_space_reflection::ranges::MAX_STEP_COUNT,
+        ),
+    )
+    .changed()
+    {
+        config_changed = true;
+    }
+
+    if option_slider(
+        ui,
+        screen_space_reflection::docs::MAX_DISTANCE,
+        Slider::new(
+            &mut config.max_distance,
+            screen_space_reflection::ranges::MAX_DISTANCE,
+        )
+        .logarithmic(true),
+    )
+    .changed()
+    {
+        config_changed = true;
+    }
+
+    if option_slider(
+        ui,
+        screen_space_reflection::docs::THICKNESS,
+        Slider::new(
+            &mut config.thickness,
+            screen_space_reflection::ranges::THICKNESS,
+        ),
+    )
+    .changed()
+    {
+        config_changed = true;
+    }
+
+    if option_slider(
+        ui,
+        screen_space_reflection::docs::ROUGHNESS_CUTOFF,
+        Slider::new(
+            &mut config.roughness_cutoff,
+            screen_space_reflection::ranges::ROUGHNESS_CUTOFF,
+        ),
+    )
+    .changed()
+    {
+        config_changed = true;
+    }
+
+    if option_slider(
+        ui,
+        screen_space_reflection::docs::CURRENT_FRAME_WEIGHT,
+        Slider::new(
+            &mut config.current_frame_weight,
+            screen_space_reflection::ranges::CURRENT_FRAME_WEIGHT,
+        ),
+    )
+    .changed()
+    {
+        config_changed = true;
+    }
+
+    if option_slider(
+        ui,
+        screen_space_reflection::docs::VARIANCE_CLIPPING_THRESHOLD,
+        Slider::new(
+            &mut config.variance_clipping_threshold,
+            screen_space_reflection::ranges::VARIANCE_CLIPPING_THRESHOLD,
+        ),
+    )
+    .changed()
+    {
+        config_changed = true;
+    }
+
+    if config_changed {
+        engine.enqueue_admin_command(AdminCommand::Rendering(
+            RenderingAdminCommand::SetScreenSpaceReflectionConfig(config),
+        ));
+    }
+}
+
+fn volumetric_fog_options(ui: &mut Ui, engine: &Engine) {
+    let mut config = engine.volumetric_fog_config();
+    let mut config_changed = false;
+
+    if option_checkbox(ui, &mut config.enabled, volumetric_fog::docs::ENABLED).changed() {
+        config_changed = true;
+    }
+
+    if option_slider(
+        ui,
+        volumetric_fog::docs::MAX_DISTANCE,
+        Slider::new(
+            &mut config.max_distance,
+            volumetric_fog::ranges::MAX_DISTANCE,
+        )
+        .logarithmic(true),
+    )
+    .changed()
+    {
+        config_changed = true;
+    }
+
+    if option_slider(
+        ui,
+        volumetric_fog::docs::DENSITY_SCALE,
+        Slider::new(
+            &mut config.density_scale,
+            volumetric_fog::ranges::DENSITY_SCALE,
+        )
+        .logarithmic(true),
+    )
+    .changed()
+    {
+        config_changed = true;
+    }
+
+    if config_changed {
+        engine.enqueue_admin_command(AdminCommand::Rendering(
+            RenderingAdminCommand::SetVolumetricFogConfig(config),
+        ));
+    }
+}
+
 fn temporal_anti_aliasing_options(ui: &mut Ui, engine: &Engine) {
     let mut config = engine.temporal_anti_aliasing_config();
     let mut config_changed = false;
@@ -736,6 +1076,165 @@ fn camera_options(ui: &mut Ui, engine: &Engine) {
     }
 }
 
+fn depth_of_field_options(ui: &mut Ui, engine: &Engine) {
+    let mut config = engine.depth_of_field_config();
+    let mut config_changed = false;
+
+    if option_checkbox(ui, &mut config.enabled, depth_of_field::docs::ENABLED).changed() {
+        config_changed = true;
+    }
+
+    let mut settings = engine.camera_settings();
+    let mut settings_changed = false;
+
+    if option_slider(
+        ui,
+        depth_of_field::docs::FOCAL_LENGTH,
+        Slider::new(
+            &mut settings.focal_length,
+            depth_of_field::ranges::FOCAL_LENGTH,
+        )
+        .logarithmic(true)
+        .suffix(" m"),
+    )
+    .changed()
+    {
+        settings_changed = true;
+    }
+
+    if option_slider(
+        ui,
+        camera::docs::RELATIVE_APERTURE,
+        Slider::new(
+            &mut settings.relative_aperture,
+            camera::ranges::RELATIVE_APERTURE,
+        ),
+    )
+    .changed()
+    {
+        settings_changed = true;
+    }
+
+    let mut autofocus = settings.focus_distance == FocusDistance::Auto;
+
+    if option_checkbox(ui, &mut autofocus, depth_of_field::docs::AUTOFOCUS).changed() {
+        settings.focus_distance = if autofocus {
+            FocusDistance::Auto
+        } else {
+            FocusDistance::Manual {
+                distance: depth_of_field::DEFAULT_FOCUS_DISTANCE,
+            }
+        };
+        settings_changed = true;
+    }
+
+    if let FocusDistance::Manual { distance } = &mut settings.focus_distance
+        && option_slider(
+            ui,
+            depth_of_field::docs::FOCUS_DISTANCE,
+            Slider::new(distance, depth_of_field::ranges::FOCUS_DISTANCE)
+                .logarithmic(true)
+                .suffix(" m"),
+        )
+        .changed()
+    {
+        settings_changed = true;
+    }
+
+    if option_slider(
+        ui,
+        depth_of_field::docs::N_SAMPLES,
+        Slider::new(&mut config.n_samples, depth_of_field::ranges::N_SAMPLES),
+    )
+    .changed()
+    {
+        config_changed = true;
+    }
+
+    if option_slider(
+        ui,
+        depth_of_field::docs::MAX_BLUR_RADIUS,
+        Slider::new(
+            &mut config.max_blur_radius,
+            depth_of_field::ranges::MAX_BLUR_RADIUS,
+        )
+        .logarithmic(true),
+    )
+    .changed()
+    {
+        config_changed = true;
+    }
+
+    if settings_changed {
+        engine.enqueue_admin_command(AdminCommand::Rendering(
+            RenderingAdminCommand::SetCameraSettings(settings),
+        ));
+    }
+
+    if config_changed {
+        engine.enqueue_admin_command(AdminCommand::Rendering(
+            RenderingAdminCommand::SetDepthOfFieldConfig(config),
+        ));
+    }
+}
+
+fn motion_blur_options(ui: &mut Ui, engine: &Engine) {
+    let mut config = engine.motion_blur_config();
+    let mut config_changed = false;
+
+    if option_checkbox(ui, &mut config.enabled, motion_blur::docs::ENABLED).changed() {
+        config_changed = true;
+    }
+
+    let mut settings = engine.camera_settings();
+
+    if option_slider(
+        ui,
+        motion_blur::docs::SHUTTER_ANGLE,
+        Slider::new(
+            &mut settings.shutter_angle,
+            motion_blur::ranges::SHUTTER_ANGLE,
+        )
+        .suffix("°"),
+    )
+    .changed()
+    {
+        engine.enqueue_admin_command(AdminCommand::Rendering(
+            RenderingAdminCommand::SetCameraSettings(settings),
+        ));
+    }
+
+    if option_slider(
+        ui,
+        motion_blur::docs::N_SAMPLES,
+        Slider::new(&mut config.n_samples, motion_blur::ranges::N_SAMPLES),
+    )
+    .changed()
+    {
+        config_changed = true;
+    }
+
+    if option_slider(
+        ui,
+        motion_blur::docs::MAX_BLUR_LENGTH,
+        Slider::new(
+            &mut config.max_blur_length,
+            motion_blur::ranges::MAX_BLUR_LENGTH,
+        )
+        .logarithmic(true),
+    )
+    .changed()
+    {
+        config_changed = true;
+    }
+
+    if config_changed {
+        engine.enqueue_admin_command(AdminCommand::Rendering(
+            RenderingAdminCommand::SetMotionBlurConfig(config),
+        ));
+    }
+}
+
 fn bloom_options(ui: &mut Ui, engine: &Engine) {
     let mut config = engine.bloom_config();
     let mut config_changed = false;
@@ -849,6 +1348,15 @@ fn wireframe_options(ui: &mut Ui, engine: &Engine) {
     }
 }
 
+fn entity_picking_options(ui: &mut Ui, engine: &Engine) {
+    let mut enabled = engine.entity_picking_enabled();
+    if option_checkbox(ui, &mut enabled, entity_picking::docs::ENABLED).changed() {
+        engine.enqueue_admin_command(AdminCommand::Rendering(
+            RenderingAdminCommand::SetEntityPicking(ToActiveState::from_enabled(enabled)),
+        ));
+    }
+}
+
 fn render_attachment_options(ui: &mut Ui, engine: &Engine) {
     let mut quantity = engine.visualized_render_attachment_quantity();
     let original_quantity = quantity;
@@ -0,0 +1,27 @@
+use crate::overlay::{Corner, TextOverlay};
+use impact::{
+    egui::{Context, Id, Vec2, vec2},
+    engine::Engine,
+};
+
+#[derive(Clone, Copy, Debug, Default)]
+pub(super) struct ResourceReloadOverlay;
+
+const OFFSET_FROM_CORNER: Vec2 = vec2(10.0, 3.0);
+
+impl ResourceReloadOverlay {
+    pub(super) fn run(&mut self, ctx: &Context, engine: &Engine) {
+        let failures = engine.resource_reload_failures();
+        if failures.is_empty() {
+            return;
+        }
+
+        let lines: Vec<_> = failures.iter().map(ToString::to_string).collect();
+        let lines: Vec<_> = lines.iter().map(String::as_str).collect();
+
+        TextOverlay::new(Id::new("resource_reload_overlay"))
+            .corner(Corner::BottomLeft)
+            .offset(OFFSET_FROM_CORNER)
+            .show_lines(ctx, &lines);
+    }
+}
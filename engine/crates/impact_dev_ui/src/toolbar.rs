@@ -1,6 +1,9 @@
 use crate::{CustomElements, UserInterfaceConfig};
 use impact::{
-    command::{AdminCommand, instrumentation::InstrumentationAdminCommand, utils::ToActiveState},
+    command::{
+        AdminCommand, instrumentation::InstrumentationAdminCommand,
+        rendering::RenderingAdminCommand, utils::ToActiveState,
+    },
     egui::{Context, Frame, TopBottomPanel},
     engine::Engine,
 };
@@ -52,6 +55,17 @@ impl Toolbar {
                         ));
                     }
 
+                    if ui
+                        .toggle_value(&mut config.show_inspector, "Inspector")
+                        .changed()
+                    {
+                        engine.enqueue_admin_command(AdminCommand::Rendering(
+                            RenderingAdminCommand::SetEntityPicking(ToActiveState::from_enabled(
+                                config.show_inspector,
+                            )),
+                        ));
+                    }
+
                     custom_panels.run_toolbar_buttons(ui);
                 });
             });
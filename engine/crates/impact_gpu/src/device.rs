@@ -1,6 +1,6 @@
 //! Graphics device.
 
-use anyhow::Result;
+use anyhow::{Result, bail};
 
 /// Interface to a connected graphics device.
 #[derive(Debug)]
@@ -21,6 +21,7 @@ impl GraphicsDevice {
     /// # Errors
     /// Returns an error if:
     /// - A compatible graphics device can not be found.
+    /// - The device does not support the required limits.
     /// - The connection request fails.
     pub async fn connect(
         wgpu_instance: &wgpu::Instance,
@@ -48,6 +49,27 @@ impl GraphicsDevice {
             backend = adapter_info.backend,
         );
 
+        // Requesting the device would also fail for unsupported limits, but
+        // we check them first to report all offending limits by name
+        let adapter_limits = adapter.limits();
+        let mut unsupported_limits = Vec::new();
+        required_limits.check_limits_with_fail_fn(
+            &adapter_limits,
+            false,
+            |name, required, supported| {
+                unsupported_limits.push(format!(
+                    "{name} (required: {required}, supported: {supported})"
+                ));
+            },
+        );
+        if !unsupported_limits.is_empty() {
+            bail!(
+                "Graphics adapter {} does not support the required limits: {}",
+                adapter_info.name,
+                unsupported_limits.join(", ")
+            );
+        }
+
         let features = required_features | (desired_features & adapter.features());
 
         let (device, queue) = adapter
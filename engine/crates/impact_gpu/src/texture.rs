@@ -23,6 +23,7 @@ pub trait TexelType: Pod {
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum TexelDescription {
     Rgba8(ColorSpace),
+    Rgba16Float,
     Grayscale8,
     Float32,
 }
@@ -36,6 +37,23 @@ pub enum ColorSpace {
     Srgb,
 }
 
+/// A block compression format for texel data. Each format stores 4x4 blocks
+/// of texels in a fixed number of bytes.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BlockCompression {
+    /// RGB with 1-bit alpha, 8 bytes per block.
+    Bc1,
+    /// RGBA with interpolated alpha, 16 bytes per block.
+    Bc3,
+    /// Single channel, 8 bytes per block.
+    Bc4,
+    /// Two channels, 16 bytes per block.
+    Bc5,
+    /// High quality RGBA, 16 bytes per block.
+    Bc7,
+}
+
 /// A texture holding multidimensional data.
 #[derive(Debug)]
 pub struct Texture {
@@ -179,6 +197,7 @@ impl TexelDescription {
     /// The number of bytes needed to represent a texel of this type.
     pub fn n_bytes(&self) -> u32 {
         match self {
+            Self::Rgba16Float => 8,
             Self::Rgba8(_) | Self::Float32 => 4,
             Self::Grayscale8 => 1,
         }
@@ -189,12 +208,58 @@ impl TexelDescription {
         match self {
             Self::Rgba8(ColorSpace::Linear) => wgpu::TextureFormat::Rgba8Unorm,
             Self::Rgba8(ColorSpace::Srgb) => wgpu::TextureFormat::Rgba8UnormSrgb,
+            Self::Rgba16Float => wgpu::TextureFormat::Rgba16Float,
             Self::Grayscale8 => wgpu::TextureFormat::R8Unorm,
             Self::Float32 => wgpu::TextureFormat::R32Float,
         }
     }
 }
 
+impl BlockCompression {
+    /// The width and height of a block in texels.
+    pub const BLOCK_SIZE: u32 = 4;
+
+    /// The number of bytes used to represent a block.
+    pub fn block_n_bytes(&self) -> u32 {
+        match self {
+            Self::Bc1 | Self::Bc4 => 8,
+            Self::Bc3 | Self::Bc5 | Self::Bc7 => 16,
+        }
+    }
+
+    /// The number of bytes needed to represent an image with the given
+    /// dimensions, including padding of partial blocks.
+    pub fn n_bytes_for_image(&self, width: u32, height: u32) -> u32 {
+        width.div_ceil(Self::BLOCK_SIZE) * height.div_ceil(Self::BLOCK_SIZE) * self.block_n_bytes()
+    }
+
+    /// Whether the format can represent data in the given color space.
+    pub fn supports_color_space(&self, color_space: ColorSpace) -> bool {
+        color_space == ColorSpace::Linear || !matches!(self, Self::Bc4 | Self::Bc5)
+    }
+
+    /// Returns the texture format that will be used for this compression
+    /// format and color space.
+    ///
+    /// # Panics
+    /// If the format does not support the color space.
+    pub fn texture_format(&self, color_space: ColorSpace) -> wgpu::TextureFormat {
+        match (self, color_space) {
+            (Self::Bc1, ColorSpace::Linear) => wgpu::TextureFormat::Bc1RgbaUnorm,
+            (Self::Bc1, ColorSpace::Srgb) => wgpu::TextureFormat::Bc1RgbaUnormSrgb,
+            (Self::Bc3, ColorSpace::Linear) => wgpu::TextureFormat::Bc3RgbaUnorm,
+            (Self::Bc3, ColorSpace::Srgb) => wgpu::TextureFormat::Bc3RgbaUnormSrgb,
+            (Self::Bc4, ColorSpace::Linear) => wgpu::TextureFormat::Bc4RUnorm,
+            (Self::Bc5, ColorSpace::Linear) => wgpu::TextureFormat::Bc5RgUnorm,
+            (Self::Bc7, ColorSpace::Linear) => wgpu::TextureFormat::Bc7RgbaUnorm,
+            (Self::Bc7, ColorSpace::Srgb) => wgpu::TextureFormat::Bc7RgbaUnormSrgb,
+            (Self::Bc4 | Self::Bc5, ColorSpace::Srgb) => {
+                panic!("Block compression {self:?} does not support the sRGB color space")
+            }
+        }
+    }
+}
+
 impl TexelType for f32 {
     const DESCRIPTION: TexelDescription = TexelDescription::Float32;
 }
@@ -259,18 +324,11 @@ impl Texture {
 
         let dimension = Self::determine_texture_dimension(height, depth_or_array_layers);
 
-        let view_dimension = if is_cubemap {
-            if let DepthOrArrayLayers::ArrayLayers(array_layers) = depth_or_array_layers {
-                if array_layers.get() != 6 {
-                    bail!("Tried to create cubemap with {} array layers", array_layers);
-                }
-            } else {
-                bail!("Tried to create cubemap with no array layers");
-            }
-            wgpu::TextureViewDimension::Cube
-        } else {
-            Self::determine_texture_view_dimension(height, depth_or_array_layers)
-        };
+        let view_dimension = Self::determine_valid_texture_view_dimension(
+            height,
+            depth_or_array_layers,
+            is_cubemap,
+        )?;
 
         let device = graphics_device.device();
         let queue = graphics_device.queue();
@@ -318,6 +376,261 @@ impl Texture {
         Ok(Self::new(texture, view, view_dimension))
     }
 
+    /// Creates a texture for the data contained in the given byte buffer, which
+    /// holds the given number of mip levels, with the given dimensions for the
+    /// base level and the given texel description. The levels must be stored
+    /// consecutively, starting with the base level, and each level must hold
+    /// all depth slices or array layers.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The mip level count is zero or exceeds the length of the full mip
+    ///   chain.
+    /// - The texture shape, mip level count and texel size are inconsistent
+    ///   with the size of the byte buffer.
+    pub fn create_with_mip_levels(
+        graphics_device: &GraphicsDevice,
+        byte_buffer: &[u8],
+        width: NonZeroU32,
+        height: NonZeroU32,
+        depth_or_array_layers: DepthOrArrayLayers,
+        texel_description: TexelDescription,
+        is_cubemap: bool,
+        mip_level_count: u32,
+        label: &str,
+    ) -> Result<Self> {
+        let texture_size = wgpu::Extent3d {
+            width: u32::from(width),
+            height: u32::from(height),
+            depth_or_array_layers: u32::from(depth_or_array_layers.unwrap()),
+        };
+
+        let dimension = Self::determine_texture_dimension(height, depth_or_array_layers);
+
+        let full_mip_chain_level_count = texture_size.max_mips(dimension);
+
+        if mip_level_count == 0 || mip_level_count > full_mip_chain_level_count {
+            bail!(
+                "Invalid mip level count {mip_level_count} for texture {label} (full mip chain has {full_mip_chain_level_count} levels)"
+            );
+        }
+
+        let level_sizes: Vec<_> = (0..mip_level_count)
+            .map(|mip_level| texture_size.mip_level_size(mip_level, dimension))
+            .collect();
+
+        let n_bytes: u32 = level_sizes
+            .iter()
+            .map(|size| {
+                texel_description.n_bytes() * size.width * size.height * size.depth_or_array_layers
+            })
+            .sum();
+
+        if n_bytes as usize != byte_buffer.len() {
+            bail!(
+                "Texture {} shape ({}, {}, {:?}) with {} mip levels and texel size ({} bytes) not consistent with number bytes of data ({})",
+                label,
+                width,
+                height,
+                depth_or_array_layers,
+                mip_level_count,
+                texel_description.n_bytes(),
+                byte_buffer.len()
+            )
+        }
+
+        let view_dimension = Self::determine_valid_texture_view_dimension(
+            height,
+            depth_or_array_layers,
+            is_cubemap,
+        )?;
+
+        let texture = Self::create_empty_texture(
+            graphics_device.device(),
+            texel_description.texture_format(),
+            texture_size,
+            dimension,
+            mip_level_count,
+            label,
+        );
+
+        let mut offset = 0;
+        for (mip_level, level_size) in (0..).zip(level_sizes) {
+            let level_n_bytes = (texel_description.n_bytes()
+                * level_size.width
+                * level_size.height
+                * level_size.depth_or_array_layers) as usize;
+
+            graphics_device.queue().write_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: &texture,
+                    mip_level,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &byte_buffer[offset..offset + level_n_bytes],
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(texel_description.n_bytes() * level_size.width),
+                    rows_per_image: Some(level_size.height),
+                },
+                level_size,
+            );
+
+            offset += level_n_bytes;
+        }
+
+        let view = Self::create_view(&texture, view_dimension);
+
+        Ok(Self::new(texture, view, view_dimension))
+    }
+
+    /// Creates a texture for the block compressed data contained in the given
+    /// byte buffer, which holds the given number of mip levels, with the given
+    /// dimensions for the base level. The levels must be stored consecutively,
+    /// starting with the base level, and each level must hold all array
+    /// layers. Partial blocks in small mip levels are stored as full blocks.
+    ///
+    /// The graphics device must support
+    /// [`wgpu::Features::TEXTURE_COMPRESSION_BC`].
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The width or height is not a multiple of the block size.
+    /// - The compression format does not support the color space.
+    /// - The mip level count is zero or exceeds the length of the full mip
+    ///   chain.
+    /// - The texture shape, mip level count and block size are inconsistent
+    ///   with the size of the byte buffer.
+    pub fn create_block_compressed_with_mip_levels(
+        graphics_device: &GraphicsDevice,
+        byte_buffer: &[u8],
+        width: NonZeroU32,
+        height: NonZeroU32,
+        depth_or_array_layers: DepthOrArrayLayers,
+        compression: BlockCompression,
+        color_space: ColorSpace,
+        is_cubemap: bool,
+        mip_level_count: u32,
+        label: &str,
+    ) -> Result<Self> {
+        let block_size = BlockCompression::BLOCK_SIZE;
+
+        if !u32::from(width).is_multiple_of(block_size)
+            || !u32::from(height).is_multiple_of(block_size)
+        {
+            bail!(
+                "Dimensions ({width}, {height}) of block compressed texture {label} are not multiples of {block_size}"
+            );
+        }
+
+        if !compression.supports_color_space(color_space) {
+            bail!(
+                "Block compression {compression:?} for texture {label} does not support color space {color_space:?}"
+            );
+        }
+
+        let texture_size = wgpu::Extent3d {
+            width: u32::from(width),
+            height: u32::from(height),
+            depth_or_array_layers: u32::from(depth_or_array_layers.unwrap()),
+        };
+
+        let dimension = Self::determine_texture_dimension(height, depth_or_array_layers);
+
+        if dimension != wgpu::TextureDimension::D2 {
+            bail!("Block compressed texture {label} must be two-dimensional");
+        }
+
+        let full_mip_chain_level_count = texture_size.max_mips(dimension);
+
+        if mip_level_count == 0 || mip_level_count > full_mip_chain_level_count {
+            bail!(
+                "Invalid mip level count {mip_level_count} for texture {label} (full mip chain has {full_mip_chain_level_count} levels)"
+            );
+        }
+
+        let format = compression.texture_format(color_space);
+
+        // Small mip levels are stored as full blocks, so we copy the physical
+        // (block aligned) size of each level
+        let level_sizes: Vec<_> = (0..mip_level_count)
+            .map(|mip_level| {
+                texture_size
+                    .mip_level_size(mip_level, dimension)
+                    .physical_size(format)
+            })
+            .collect();
+
+        let level_n_bytes = |size: &wgpu::Extent3d| {
+            compression.n_bytes_for_image(size.width, size.height) * size.depth_or_array_layers
+        };
+
+        let n_bytes: u32 = level_sizes.iter().map(level_n_bytes).sum();
+
+        if n_bytes as usize != byte_buffer.len() {
+            bail!(
+                "Block compressed texture {} shape ({}, {}, {:?}) with {} mip levels and compression {:?} not consistent with number bytes of data ({})",
+                label,
+                width,
+                height,
+                depth_or_array_layers,
+                mip_level_count,
+                compression,
+                byte_buffer.len()
+            )
+        }
+
+        let view_dimension = Self::determine_valid_texture_view_dimension(
+            height,
+            depth_or_array_layers,
+            is_cubemap,
+        )?;
+
+        // Compressed formats can not be used as render attachments
+        let texture = graphics_device
+            .device()
+            .create_texture(&wgpu::TextureDescriptor {
+                size: texture_size,
+                mip_level_count,
+                sample_count: 1,
+                dimension,
+                format,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                label: Some(label),
+                view_formats: &[],
+            });
+
+        let mut offset = 0;
+        for (mip_level, level_size) in (0..).zip(level_sizes) {
+            let n_bytes = level_n_bytes(&level_size) as usize;
+
+            graphics_device.queue().write_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: &texture,
+                    mip_level,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &byte_buffer[offset..offset + n_bytes],
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(
+                        level_size.width.div_ceil(block_size) * compression.block_n_bytes(),
+                    ),
+                    rows_per_image: Some(level_size.height.div_ceil(block_size)),
+                },
+                level_size,
+            );
+
+            offset += n_bytes;
+        }
+
+        let view = Self::create_view(&texture, view_dimension);
+
+        Ok(Self::new(texture, view, view_dimension))
+    }
+
     /// Creates a new [`Texture`] comprised of the given `wgpu` texture and
     /// sampler data.
     pub fn new(
@@ -332,6 +645,48 @@ impl Texture {
         }
     }
 
+    /// Overwrites the base mip level of the texture with the data contained in
+    /// the given byte buffer, which must be laid out in the same way as the
+    /// buffer used for creating the texture.
+    ///
+    /// # Errors
+    /// Returns an error if the texture shape and texel size are inconsistent
+    /// with the size of the byte buffer.
+    pub fn write_data(
+        &self,
+        graphics_device: &GraphicsDevice,
+        byte_buffer: &[u8],
+        texel_description: TexelDescription,
+    ) -> Result<()> {
+        let texture_size = self.texture.size();
+
+        if (texel_description.n_bytes()
+            * texture_size.width
+            * texture_size.height
+            * texture_size.depth_or_array_layers) as usize
+            != byte_buffer.len()
+        {
+            bail!(
+                "Texture shape ({}, {}, {}) and texel size ({} bytes) not consistent with number bytes of data ({})",
+                texture_size.width,
+                texture_size.height,
+                texture_size.depth_or_array_layers,
+                texel_description.n_bytes(),
+                byte_buffer.len()
+            )
+        }
+
+        Self::write_data_to_texture(
+            graphics_device.queue(),
+            &self.texture,
+            byte_buffer,
+            texel_description,
+            texture_size,
+        );
+
+        Ok(())
+    }
+
     /// Returns a reference to the underlying [`wgpu::Texture`].
     pub fn texture(&self) -> &wgpu::Texture {
         &self.texture
@@ -406,6 +761,33 @@ impl Texture {
         }
     }
 
+    /// Determines the [`wgpu::TextureViewDimension`] for a texture with the
+    /// given dimensions, which may be a cubemap.
+    ///
+    /// # Errors
+    /// Returns an error if the texture is a cubemap and does not have six array
+    /// layers.
+    fn determine_valid_texture_view_dimension(
+        height: NonZeroU32,
+        depth_or_array_layers: DepthOrArrayLayers,
+        is_cubemap: bool,
+    ) -> Result<wgpu::TextureViewDimension> {
+        if !is_cubemap {
+            return Ok(Self::determine_texture_view_dimension(
+                height,
+                depth_or_array_layers,
+            ));
+        }
+        if let DepthOrArrayLayers::ArrayLayers(array_layers) = depth_or_array_layers {
+            if array_layers.get() != 6 {
+                bail!("Tried to create cubemap with {} array layers", array_layers);
+            }
+        } else {
+            bail!("Tried to create cubemap with no array layers");
+        }
+        Ok(wgpu::TextureViewDimension::Cube)
+    }
+
     /// Creates a new [`wgpu::Texture`] configured to hold 2D image data.
     fn create_empty_texture(
         device: &wgpu::Device,
@@ -1,13 +1,13 @@
 //! Vertex attribute location range constants.
 
 /// Size of the instance vertex attribute range.
-pub const INSTANCE_RANGE_SIZE: u32 = 4;
+pub const INSTANCE_RANGE_SIZE: u32 = 6;
 
 /// Size of the mesh vertex attribute range.
-pub const MESH_RANGE_SIZE: u32 = 5;
+pub const MESH_RANGE_SIZE: u32 = 7;
 
 /// Size of the material vertex attribute range.
-pub const MATERIAL_RANGE_SIZE: u32 = 7;
+pub const MATERIAL_RANGE_SIZE: u32 = 9;
 
 /// Starting location for instance vertex attributes.
 pub const INSTANCE_START: u32 = 0;
@@ -18,6 +18,7 @@ pub struct Image<A: Allocator = Global> {
 }
 
 /// Metadata for an image.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ImageMetadata {
     /// Width of the image in pixels.
@@ -29,6 +30,7 @@ pub struct ImageMetadata {
 }
 
 /// Supported image formats for pixel data.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PixelFormat {
     /// RGBA format with 8 bits per channel.
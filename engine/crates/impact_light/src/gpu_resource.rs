@@ -111,11 +111,13 @@ impl LightGPUResources {
     const AMBIENT_LIGHT_VISIBILITY: wgpu::ShaderStages = wgpu::ShaderStages::FRAGMENT;
     const OMNIDIRECTIONAL_LIGHT_VISIBILITY: wgpu::ShaderStages =
         wgpu::ShaderStages::VERTEX_FRAGMENT;
+    // Shadowable lights are also accessed from compute shaders when computing
+    // volumetric lighting
     const SHADOWABLE_OMNIDIRECTIONAL_LIGHT_VISIBILITY: wgpu::ShaderStages =
-        wgpu::ShaderStages::VERTEX_FRAGMENT;
+        wgpu::ShaderStages::VERTEX_FRAGMENT.union(wgpu::ShaderStages::COMPUTE);
     const UNIDIRECTIONAL_LIGHT_VISIBILITY: wgpu::ShaderStages = wgpu::ShaderStages::VERTEX_FRAGMENT;
     const SHADOWABLE_UNIDIRECTIONAL_LIGHT_VISIBILITY: wgpu::ShaderStages =
-        wgpu::ShaderStages::VERTEX_FRAGMENT;
+        wgpu::ShaderStages::VERTEX_FRAGMENT.union(wgpu::ShaderStages::COMPUTE);
 
     const AMBIENT_LIGHT_LAYOUT_ID: ConstStringHash64 = ConstStringHash64::new("AmbientLight");
     const OMNIDIRECTIONAL_LIGHT_LAYOUT_ID: ConstStringHash64 =
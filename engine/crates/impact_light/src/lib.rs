@@ -449,6 +449,10 @@ impl LightManager {
     /// avoid excessive buffer reallocation when the first few lights are added.
     pub const INITIAL_LIGHT_CAPACITY: usize = 5;
 
+    /// The ID reserved for the ambient light representing the illumination
+    /// from a procedural sky (see [`Self::set_sky_ambient_illuminance`]).
+    pub const SKY_AMBIENT_LIGHT_ID: AmbientLightID = AmbientLightID::from_u64(u64::MAX);
+
     /// Creates a new light manager with no lights.
     pub fn new() -> Self {
         Self {
@@ -688,6 +692,33 @@ impl LightManager {
         self.update_max_reach_for_omnidirectional_lights();
     }
 
+    /// Sets the uniform illuminance of the ambient light representing the
+    /// illumination from the sky to the given value, adding the light if it
+    /// does not exist. If [`None`] is given, the light is removed if it
+    /// exists.
+    pub fn set_sky_ambient_illuminance(&mut self, illuminance: Option<Illumninance>) {
+        let light_id = Self::SKY_AMBIENT_LIGHT_ID;
+        let light_exists = self.ambient_light_buffer.has_uniform(light_id);
+
+        match illuminance {
+            Some(illuminance) if light_exists => {
+                let luminance = compute_luminance_for_uniform_illuminance(&illuminance);
+                if self.ambient_light_buffer.uniform(light_id).luminance != luminance {
+                    self.set_ambient_light_illuminance(light_id, illuminance);
+                }
+            }
+            Some(illuminance) => {
+                let luminance = compute_luminance_for_uniform_illuminance(&illuminance);
+                self.add_ambient_light(light_id, AmbientLight::new(luminance))
+                    .unwrap();
+            }
+            None if light_exists => {
+                self.remove_ambient_light(light_id);
+            }
+            None => {}
+        }
+    }
+
     /// Returns a reference to the [`OmnidirectionalLight`] with the given
     /// ID, or [`None`] if it does not exist.
     pub fn get_omnidirectional_light(
@@ -1357,6 +1388,17 @@ impl UnidirectionalLight {
         self.flags = flags;
     }
 
+    /// Returns the camera space direction the light is traveling in.
+    pub fn camera_space_direction(&self) -> &UnitVector3C {
+        &self.camera_space_direction
+    }
+
+    /// Returns the illuminance of the light on a surface perpendicular to the
+    /// light direction.
+    pub fn perpendicular_illuminance(&self) -> &Illumninance {
+        &self.perpendicular_illuminance
+    }
+
     /// Sets the camera space direction of the light to the given direction.
     pub fn set_camera_space_direction(&mut self, camera_space_direction: UnitVector3C) {
         self.camera_space_direction = camera_space_direction;
@@ -1421,6 +1463,17 @@ impl ShadowableUnidirectionalLight {
         self.flags = flags;
     }
 
+    /// Returns the camera space direction the light is traveling in.
+    pub fn camera_space_direction(&self) -> &UnitVector3C {
+        &self.camera_space_direction
+    }
+
+    /// Returns the illuminance of the light on a surface perpendicular to the
+    /// light direction.
+    pub fn perpendicular_illuminance(&self) -> &Illumninance {
+        &self.perpendicular_illuminance
+    }
+
     /// Returns a reference to the quaternion that rotates camera space to light
     /// space.
     pub fn camera_to_light_space_rotation(&self) -> &UnitQuaternionC {
@@ -3,6 +3,11 @@
 use impact_geometry::projection::CubemapFace;
 use impact_gpu::{device::GraphicsDevice, wgpu};
 
+// Shadow maps are sampled from compute shaders when computing volumetric
+// lighting in addition to the fragment shaders for the light passes
+const SHADOW_MAP_VISIBILITY: wgpu::ShaderStages =
+    wgpu::ShaderStages::FRAGMENT.union(wgpu::ShaderStages::COMPUTE);
+
 /// Configuration options for shadow mapping.
 #[cfg_attr(
     feature = "serde",
@@ -227,7 +232,7 @@ impl ShadowCubemapTexture {
     const fn create_texture_bind_group_layout_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
         wgpu::BindGroupLayoutEntry {
             binding,
-            visibility: wgpu::ShaderStages::FRAGMENT,
+            visibility: SHADOW_MAP_VISIBILITY,
             ty: wgpu::BindingType::Texture {
                 sample_type: wgpu::TextureSampleType::Float { filterable: false },
                 view_dimension: wgpu::TextureViewDimension::Cube,
@@ -240,7 +245,7 @@ impl ShadowCubemapTexture {
     const fn create_sampler_bind_group_layout_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
         wgpu::BindGroupLayoutEntry {
             binding,
-            visibility: wgpu::ShaderStages::FRAGMENT,
+            visibility: SHADOW_MAP_VISIBILITY,
             ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::NonFiltering),
             count: None,
         }
@@ -418,7 +423,7 @@ impl CascadedShadowMapTexture {
     const fn create_texture_bind_group_layout_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
         wgpu::BindGroupLayoutEntry {
             binding,
-            visibility: wgpu::ShaderStages::FRAGMENT,
+            visibility: SHADOW_MAP_VISIBILITY,
             ty: wgpu::BindingType::Texture {
                 sample_type: wgpu::TextureSampleType::Float { filterable: false },
                 view_dimension: wgpu::TextureViewDimension::D2Array,
@@ -431,7 +436,7 @@ impl CascadedShadowMapTexture {
     const fn create_sampler_bind_group_layout_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
         wgpu::BindGroupLayoutEntry {
             binding,
-            visibility: wgpu::ShaderStages::FRAGMENT,
+            visibility: SHADOW_MAP_VISIBILITY,
             ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::NonFiltering),
             count: None,
         }
@@ -33,7 +33,7 @@ use std::{
     fmt,
     hash::{Hash, Hasher},
 };
-use values::{MaterialPropertyFlags, MaterialPropertyValues};
+use values::{MaterialPropertyFlags, MaterialPropertyValues, TextureCoordTransform};
 
 /// A color with RGB components.
 pub type RGBColor = Vector3C;
@@ -91,6 +91,9 @@ pub struct Material {
     /// The material's values for all uniform material properties (properties
     /// that are not sampled from a texture).
     pub property_values: MaterialPropertyValues,
+    /// The initial transform of the texture coordinates for each instance, if
+    /// the material samples its textures from a region of texture atlases.
+    pub texture_coord_transform: Option<TextureCoordTransform>,
 }
 
 /// A template defining the overall structure and requirements of a class of
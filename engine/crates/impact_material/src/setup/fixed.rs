@@ -94,6 +94,7 @@ pub fn setup_fixed_material(
                 template_id,
                 texture_group_id: MaterialTextureGroupID::empty(),
                 property_values,
+                texture_coord_transform: None,
             };
 
             material_registry.insert(material_id, material);
@@ -146,6 +147,7 @@ pub fn setup_fixed_material(
                 template_id,
                 texture_group_id,
                 property_values,
+                texture_coord_transform: None,
             };
 
             material_registry.insert(material_id, material);
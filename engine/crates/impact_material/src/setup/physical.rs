@@ -4,7 +4,8 @@ use crate::{
     Material, MaterialBindGroupSlot, MaterialBindGroupTemplate, MaterialID, MaterialRegistry,
     MaterialTemplate, MaterialTemplateID, MaterialTemplateRegistry,
     MaterialTextureBindingLocations, MaterialTextureGroup, MaterialTextureGroupID,
-    MaterialTextureGroupRegistry, RGBColor, values::MaterialPropertyValues,
+    MaterialTextureGroupRegistry, RGBColor,
+    values::{MaterialPropertyFlags, MaterialPropertyValues, TextureCoordTransform},
 };
 use anyhow::{Result, anyhow, bail};
 use approx::abs_diff_eq;
@@ -14,7 +15,7 @@ use impact_math::{
     vector::{Vector2, Vector3C},
 };
 use impact_mesh::VertexAttributeSet;
-use impact_texture::{SamplerRegistry, TextureID, TextureRegistry};
+use impact_texture::{SamplerRegistry, TextureID, TextureRegistry, atlas::TextureAtlasRegionID};
 use roc_integration::roc;
 use std::hash::Hash;
 
@@ -223,6 +224,11 @@ pub struct PhysicalMaterialProperties {
     pub emissive_luminance: EmissiveLuminance,
     #[cfg_attr(feature = "serde", serde(default))]
     pub bump_map: Option<BumpMap>,
+    /// The region of the texture atlases to sample all textures of the
+    /// material from. Every texture of the material must then be a texture
+    /// atlas with this region in the same place.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub atlas_region: Option<TextureAtlasRegionID>,
 }
 
 /// A uniform or textured base color.
@@ -541,6 +547,7 @@ impl PhysicalMaterialProperties {
             metalness,
             emissive_luminance,
             bump_map,
+            atlas_region: None,
         })
     }
 }
@@ -810,6 +817,13 @@ pub fn setup_physical_material(
         vertex_attribute_requirements |= VertexAttributeSet::NORMAL_VECTOR;
     }
 
+    let texture_coord_transform = properties
+        .atlas_region
+        .map(|region_id| {
+            TextureCoordTransform::for_atlas_region(texture_registry, &texture_ids, region_id)
+        })
+        .transpose()?;
+
     let property_values = MaterialPropertyValues::from_physical_material_properties(
         uniform_color.as_ref(),
         specular_reflectance_value,
@@ -819,13 +833,18 @@ pub fn setup_physical_material(
         parallax_map.as_ref(),
     );
 
+    let mut property_flags = property_values.flags();
+    if texture_coord_transform.is_some() {
+        property_flags |= MaterialPropertyFlags::USES_TEXTURE_COORD_TRANSFORM;
+    }
+
     let template = MaterialTemplate {
         vertex_attribute_requirements,
         bind_group_template: MaterialBindGroupTemplate {
             slots: bind_group_slots,
         },
         texture_binding_locations: MaterialTextureBindingLocations::Physical(bindings),
-        property_flags: property_values.flags(),
+        property_flags,
         instance_feature_type_id: property_values.instance_feature_type_id(),
     };
 
@@ -836,6 +855,7 @@ pub fn setup_physical_material(
         template_id,
         texture_group_id,
         property_values,
+        texture_coord_transform,
     };
 
     material_registry.insert(material_id, material);
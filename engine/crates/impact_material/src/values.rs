@@ -4,6 +4,7 @@ use crate::{
     RGBColor,
     setup::physical::{ParallaxMap, UniformColor},
 };
+use anyhow::{Result, anyhow, bail};
 use bitflags::bitflags;
 use bytemuck::{Pod, Zeroable};
 use impact_gpu::vertex_attribute_ranges::MATERIAL_START;
@@ -11,6 +12,7 @@ use impact_gpu::wgpu;
 use impact_math::vector::Vector2;
 use impact_model::{InstanceFeature, InstanceFeatureTypeID, ModelInstanceManager};
 use impact_model::{InstanceFeatureID, impl_InstanceFeatureForGPU};
+use impact_texture::{TextureID, TextureRegistry, atlas::TextureAtlasRegionID};
 use std::hash::Hash;
 
 bitflags! {
@@ -19,6 +21,7 @@ bitflags! {
     pub struct MaterialPropertyFlags: u8 {
         const HAS_COLOR = 1 << 0;
         const USES_PARALLAX_MAPPING = 1 << 1;
+        const USES_TEXTURE_COORD_TRANSFORM = 1 << 2;
     }
 }
 
@@ -137,6 +140,23 @@ pub struct TexturedColorParallaxMappedPhysicalMaterialValues {
     pub parallax_uv_per_distance: Vector2,
 }
 
+/// Offset and scale applied to the texture coordinates of a model instance
+/// before the textures of its material are sampled. This is used to map the
+/// unit square of texture coordinates to a region of a texture atlas.
+///
+/// Unlike the other material property values, the transform is always stored
+/// per model instance, so that instances sharing a material can show
+/// different regions of the same atlas.
+///
+/// This type implements [`InstanceFeature`], and can thus be buffered in a
+/// [`DynamicInstanceFeatureBuffer`](impact_model::DynamicInstanceFeatureBuffer).
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Zeroable, Pod)]
+pub struct TextureCoordTransform {
+    pub offset: Vector2,
+    pub scale: Vector2,
+}
+
 /// Vertex attribute location of a specific type of material instance feature.
 #[repr(u32)]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -148,6 +168,8 @@ pub enum MaterialInstanceFeatureLocation {
     Color = MATERIAL_START + 4,
     ParallaxDisplacementScale = MATERIAL_START + 5,
     ParallaxUVPerDistance = MATERIAL_START + 6,
+    TextureCoordOffset = MATERIAL_START + 7,
+    TextureCoordScale = MATERIAL_START + 8,
 }
 
 impl MaterialPropertyValues {
@@ -464,6 +486,70 @@ impl PhysicalMaterialPropertyValues {
     }
 }
 
+impl TextureCoordTransform {
+    /// Returns the transform that leaves texture coordinates unchanged.
+    pub fn identity() -> Self {
+        Self {
+            offset: Vector2::zeros(),
+            scale: Vector2::new(1.0, 1.0),
+        }
+    }
+
+    /// Computes the transform mapping texture coordinates in the unit square
+    /// to the region with the given ID in the atlases of the given textures.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - No textures are given.
+    /// - Any of the textures is missing or is not a texture atlas.
+    /// - Any of the atlases has no region with the given ID.
+    /// - The region covers different parts of the different atlases.
+    pub fn for_atlas_region(
+        texture_registry: &TextureRegistry,
+        texture_ids: &[TextureID],
+        region_id: TextureAtlasRegionID,
+    ) -> Result<Self> {
+        let mut transform: Option<Self> = None;
+
+        for &texture_id in texture_ids {
+            let atlas = texture_registry
+                .get(texture_id)
+                .ok_or_else(|| anyhow!("Missing texture {texture_id}"))?
+                .atlas()
+                .ok_or_else(|| anyhow!("Texture {texture_id} is not a texture atlas"))?;
+
+            let (offset, scale) = atlas
+                .region_uv_offset_and_scale(region_id)
+                .ok_or_else(|| anyhow!("Texture atlas {texture_id} has no region {region_id}"))?;
+
+            let region_transform = Self { offset, scale };
+
+            match &transform {
+                Some(transform) if *transform != region_transform => {
+                    bail!(
+                        "Texture atlas region {region_id} differs between the atlases \
+                         of the material textures"
+                    );
+                }
+                Some(_) => {}
+                None => {
+                    transform = Some(region_transform);
+                }
+            }
+        }
+
+        transform.ok_or_else(|| {
+            anyhow!("Tried to use texture atlas region {region_id} for material without textures")
+        })
+    }
+}
+
+impl Default for TextureCoordTransform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
 impl_InstanceFeatureForGPU!(
     FixedColorMaterialValues,
     wgpu::vertex_attr_array![MaterialInstanceFeatureLocation::Color as u32 => Float32x3]
@@ -515,6 +601,14 @@ impl_InstanceFeatureForGPU!(
     ]
 );
 
+impl_InstanceFeatureForGPU!(
+    TextureCoordTransform,
+    wgpu::vertex_attr_array![
+        MaterialInstanceFeatureLocation::TextureCoordOffset as u32 => Float32x2,
+        MaterialInstanceFeatureLocation::TextureCoordScale as u32 => Float32x2,
+    ]
+);
+
 pub fn register_material_feature_types<MID: Copy + Eq + Hash>(
     model_instance_manager: &mut ModelInstanceManager<MID>,
 ) {
@@ -525,4 +619,5 @@ pub fn register_material_feature_types<MID: Copy + Eq + Hash>(
         .register_feature_type::<UniformColorParallaxMappedPhysicalMaterialValues>();
     model_instance_manager
         .register_feature_type::<TexturedColorParallaxMappedPhysicalMaterialValues>();
+    model_instance_manager.register_feature_type::<TextureCoordTransform>();
 }
@@ -493,6 +493,21 @@ impl Matrix4 {
         ))
     }
 
+    /// Creates the homogeneous transform that applies the given non-uniform
+    /// scaling, then the given rotation and finally the given translation.
+    #[inline]
+    pub fn from_scale_rotation_translation(
+        scaling: &Vector3,
+        rotation: &UnitQuaternion,
+        translation: &Vector3,
+    ) -> Self {
+        Self::wrap(glam::Mat4::from_scale_rotation_translation(
+            scaling.unwrap().to_vec3(),
+            rotation.unwrap(),
+            translation.unwrap().to_vec3(),
+        ))
+    }
+
     /// The first column of the matrix.
     #[inline]
     pub fn column_1(&self) -> &Vector4 {
@@ -1430,6 +1445,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn matrix4_from_scale_rotation_translation_applies_in_order() {
+        let matrix = Matrix4::from_scale_rotation_translation(
+            &Vector3::new(2.0, 1.0, 1.0),
+            &UnitQuaternion::from_axis_angle(
+                &crate::vector::UnitVector3::unit_z(),
+                crate::consts::f32::PI / 2.0,
+            ),
+            &Vector3::new(0.0, 0.0, 3.0),
+        );
+        let point = matrix.transform_point(&Point3::new(1.0, 0.0, 0.0));
+        assert_abs_diff_eq!(point, Point3::new(0.0, 2.0, 3.0), epsilon = 1e-6);
+    }
+
     #[test]
     fn converting_matrix4c_to_aligned_and_back_preserves_data() {
         let matrix = Matrix4C::from_diagonal(&Vector4C::new(1.0, 2.0, 3.0, 4.0));
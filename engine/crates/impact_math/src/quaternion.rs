@@ -424,6 +424,13 @@ impl UnitQuaternion {
         self.inner.to_euler(glam::EulerRot::YXZEx)
     }
 
+    /// Computes the spherical linear interpolation between this rotation
+    /// (`t = 0`) and the given rotation (`t = 1`), taking the shortest path.
+    #[inline]
+    pub fn slerp(&self, other: &Self, t: f32) -> Self {
+        Self::wrap(self.inner.slerp(other.inner, t))
+    }
+
     /// Converts the quaternion to a 3x3 rotation matrix.
     #[inline]
     pub fn to_rotation_matrix(&self) -> Matrix3 {
@@ -474,6 +481,11 @@ impl UnitQuaternion {
     pub(crate) const fn wrap(inner: glam::Quat) -> Self {
         Self { inner }
     }
+
+    #[inline]
+    pub(crate) const fn unwrap(self) -> glam::Quat {
+        self.inner
+    }
 }
 
 impl Default for UnitQuaternion {
@@ -1057,6 +1069,17 @@ mod tests {
         assert_abs_diff_eq!(right_mult.real(), test_quat.real(), epsilon = EPSILON);
     }
 
+    #[test]
+    fn unit_quaternion_slerp_interpolates_rotation_angle() {
+        let axis = UnitVector3::unit_z();
+        let from = UnitQuaternion::identity();
+        let to = UnitQuaternion::from_axis_angle(&axis, PI / 2.0);
+
+        assert_abs_diff_eq!(from.slerp(&to, 0.0).angle(), 0.0, epsilon = 1e-3);
+        assert_abs_diff_eq!(from.slerp(&to, 0.5).angle(), PI / 4.0, epsilon = 1e-5);
+        assert_abs_diff_eq!(from.slerp(&to, 1.0).angle(), PI / 2.0, epsilon = 1e-5);
+    }
+
     #[test]
     fn converting_unit_quaternion_to_aligned_and_back_preserves_data() {
         let unit = UnitQuaternion::identity();
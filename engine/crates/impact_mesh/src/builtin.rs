@@ -41,6 +41,7 @@ macro_rules! declare_builtin_triangle_mesh {
                     id: *[<$id_fn_name:upper>],
                     source: $crate::import::TriangleMeshSource::Template($template),
                     texture_projection: None,
+                    lods: None,
                 }
             }
         }
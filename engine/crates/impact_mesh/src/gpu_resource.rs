@@ -3,7 +3,7 @@
 use crate::{
     LineSegmentMesh, LineSegmentMeshDirtyMask, LineSegmentMeshID, N_VERTEX_ATTRIBUTES,
     TriangleMesh, TriangleMeshDirtyMask, TriangleMeshID, VERTEX_ATTRIBUTE_FLAGS, VertexAttribute,
-    VertexAttributeSet, VertexColor, VertexNormalVector, VertexPosition,
+    VertexAttributeSet, VertexColor, VertexNormalVector, VertexPosition, VertexSkinWeights,
     VertexTangentSpaceQuaternion, VertexTextureCoords,
 };
 use anyhow::{Result, anyhow};
@@ -65,6 +65,8 @@ pub enum MeshVertexAttributeLocation {
     TextureCoords = MESH_START + 2,
     TangentSpaceQuaternion = MESH_START + 3,
     Color = MESH_START + 4,
+    JointIndices = MESH_START + 5,
+    JointWeights = MESH_START + 6,
 }
 
 impl MeshGPUResource {
@@ -80,8 +82,8 @@ impl MeshGPUResource {
         );
 
         let mut available_attributes = VertexAttributeSet::empty();
-        let mut vertex_buffers = [None, None, None, None, None];
-        let mut vertex_buffer_layouts = [None, None, None, None, None];
+        let mut vertex_buffers = [const { None }; N_VERTEX_ATTRIBUTES];
+        let mut vertex_buffer_layouts = [const { None }; N_VERTEX_ATTRIBUTES];
 
         let indices = mesh.indices();
         let (index_format, index_buffer) =
@@ -130,6 +132,14 @@ impl MeshGPUResource {
             mesh.colors(),
             &label,
         );
+        Self::add_vertex_attribute_if_available(
+            graphics_device,
+            &mut available_attributes,
+            &mut vertex_buffers,
+            &mut vertex_buffer_layouts,
+            mesh.skin_weights(),
+            &label,
+        );
 
         Self {
             available_attributes,
@@ -150,8 +160,8 @@ impl MeshGPUResource {
         label: String,
     ) -> Self {
         let mut available_attributes = VertexAttributeSet::empty();
-        let mut vertex_buffers = [None, None, None, None, None];
-        let mut vertex_buffer_layouts = [None, None, None, None, None];
+        let mut vertex_buffers = [const { None }; N_VERTEX_ATTRIBUTES];
+        let mut vertex_buffer_layouts = [const { None }; N_VERTEX_ATTRIBUTES];
 
         Self::add_vertex_attribute_if_available(
             graphics_device,
@@ -204,6 +214,9 @@ impl MeshGPUResource {
         if dirty_mask.contains(TriangleMeshDirtyMask::COLORS) {
             self.sync_vertex_buffer(graphics_device, mesh.colors());
         }
+        if dirty_mask.contains(TriangleMeshDirtyMask::SKIN_WEIGHTS) {
+            self.sync_vertex_buffer(graphics_device, mesh.skin_weights());
+        }
 
         if dirty_mask.contains(TriangleMeshDirtyMask::INDICES) {
             self.sync_index_buffer(graphics_device, mesh.indices());
@@ -560,6 +573,14 @@ impl VertexBufferable for VertexColor {
         ]);
 }
 
+impl VertexBufferable for VertexSkinWeights {
+    const BUFFER_LAYOUT: wgpu::VertexBufferLayout<'static> =
+        create_vertex_buffer_layout_for_vertex::<Self>(&wgpu::vertex_attr_array![
+            MeshVertexAttributeLocation::JointIndices as u32 => Uint16x4,
+            MeshVertexAttributeLocation::JointWeights as u32 => Float32x4,
+        ]);
+}
+
 /// Creates a vertex GPU buffer initialized with the given vertex data,
 /// with the first `n_valid_vertices` considered valid data.
 ///
@@ -3,6 +3,7 @@
 use crate::{
     TriangleMesh, TriangleMeshDirtyMask, TriangleMeshID, TriangleMeshRegistry,
     io::TriangleMeshFileFormat,
+    lod::{self, TriangleMeshLODDeclaration},
     setup::{TriangleMeshTemplate, setup_triangle_mesh_from_template},
     texture_projection::{PlanarTextureProjection, TextureProjectionDeclaration},
 };
@@ -15,6 +16,10 @@ pub struct TriangleMeshDeclaration {
     pub id: TriangleMeshID,
     pub source: TriangleMeshSource,
     pub texture_projection: Option<TextureProjectionDeclaration>,
+    /// The chain of simplified meshes to generate for rendering the mesh at
+    /// lower levels of detail, if any.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub lods: Option<TriangleMeshLODDeclaration>,
 }
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -52,7 +57,9 @@ pub fn load_declared_meshes(
 }
 
 /// Loads the triangle mesh in the given declaration and stores it in the
-/// registry.
+/// registry. If the declaration specifies levels of detail, the simplified
+/// meshes are generated and stored under the IDs given by
+/// [`lod::lod_mesh_id`].
 ///
 /// # Errors
 /// Returns an error if:
@@ -64,7 +71,7 @@ pub fn load_declared_triangle_mesh(
     registry: &mut TriangleMeshRegistry,
     declaration: &TriangleMeshDeclaration,
 ) -> Result<TriangleMeshID> {
-    match &declaration.source {
+    let mesh_id = match &declaration.source {
         TriangleMeshSource::File(file_path) => load_triangle_mesh_from_file(
             registry,
             declaration.id,
@@ -77,7 +84,16 @@ pub fn load_declared_triangle_mesh(
             template,
             declaration.texture_projection.as_ref(),
         ),
+    }?;
+
+    if let Some(lod_declaration) = &declaration.lods
+        && let Some(n_levels) =
+            lod::generate_and_insert_lod_meshes(registry, mesh_id, lod_declaration)
+    {
+        log::debug!("Generated {n_levels} levels of detail for triangle mesh `{mesh_id}`");
     }
+
+    Ok(mesh_id)
 }
 
 fn load_triangle_mesh_from_file(
@@ -98,6 +114,12 @@ fn load_triangle_mesh_from_file(
     }
 
     let mut mesh: TriangleMesh = match file_format {
+        #[cfg(feature = "gltf")]
+        TriangleMeshFileFormat::Gltf => crate::io::gltf::read_mesh_from_gltf_file(file_path),
+        #[cfg(not(feature = "gltf"))]
+        TriangleMeshFileFormat::Gltf => Err(anyhow::anyhow!(
+            "Please enable the `gltf` feature in order to read .gltf and .glb files"
+        )),
         #[cfg(feature = "obj")]
         TriangleMeshFileFormat::Obj => crate::io::obj::read_mesh_from_obj_file(file_path),
         #[cfg(not(feature = "obj"))]
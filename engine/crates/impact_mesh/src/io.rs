@@ -1,5 +1,8 @@
 //! Input/output of mesh data.
 
+pub mod export;
+#[cfg(feature = "gltf")]
+pub mod gltf;
 #[cfg(feature = "obj")]
 pub mod obj;
 #[cfg(feature = "ply")]
@@ -10,6 +13,7 @@ use std::path::Path;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TriangleMeshFileFormat {
+    Gltf,
     Obj,
     Ply,
 }
@@ -24,6 +28,7 @@ impl TriangleMeshFileFormat {
             );
         };
         match &*extension.to_string_lossy().to_lowercase() {
+            "gltf" | "glb" => Ok(Self::Gltf),
             "obj" => Ok(Self::Obj),
             "ply" => Ok(Self::Ply),
             other => Err(anyhow!(
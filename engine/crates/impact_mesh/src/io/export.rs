@@ -0,0 +1,363 @@
+//! Writing of triangle meshes to common 3D file formats.
+
+use crate::{TriangleMesh, io::TriangleMeshFileFormat};
+use anyhow::{Context, Result};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+/// Writes the given mesh to the file at the given path, using the file format
+/// implied by the extension of the path (`.obj`, `.ply`, `.gltf` or `.glb`).
+/// Positions, normal vectors, texture coordinates and colors are written if
+/// present in the mesh.
+///
+/// # Errors
+/// Returns an error if the file format is not supported or the file can not be
+/// written.
+pub fn write_mesh_to_file(mesh: &TriangleMesh, file_path: impl AsRef<Path>) -> Result<()> {
+    let file_path = file_path.as_ref();
+
+    match TriangleMeshFileFormat::from_path(file_path)? {
+        TriangleMeshFileFormat::Obj => write_mesh_to_obj_file(mesh, file_path),
+        TriangleMeshFileFormat::Ply => write_mesh_to_ply_file(mesh, file_path),
+        TriangleMeshFileFormat::Gltf => write_mesh_to_gltf_file(mesh, file_path),
+    }
+}
+
+/// Writes the given mesh to a Wavefront OBJ file at the given path. Vertex
+/// colors are written as the widely supported `v x y z r g b` extension.
+///
+/// # Errors
+/// Returns an error if the file can not be written.
+pub fn write_mesh_to_obj_file(mesh: &TriangleMesh, file_path: impl AsRef<Path>) -> Result<()> {
+    let file_path = file_path.as_ref();
+    write_to_file(file_path, |writer| write_mesh_as_obj(mesh, writer))
+        .with_context(|| format!("Failed to write OBJ file {}", file_path.display()))
+}
+
+/// Writes the given mesh in binary little-endian PLY (Polygon File Format)
+/// to a file at the given path. Vertex colors are quantized to 8 bits per
+/// channel, and alpha is omitted.
+///
+/// # Errors
+/// Returns an error if the file can not be written.
+pub fn write_mesh_to_ply_file(mesh: &TriangleMesh, file_path: impl AsRef<Path>) -> Result<()> {
+    let file_path = file_path.as_ref();
+    write_to_file(file_path, |writer| write_mesh_as_ply(mesh, writer))
+        .with_context(|| format!("Failed to write PLY file {}", file_path.display()))
+}
+
+/// Writes the given mesh as Wavefront OBJ to the given writer.
+///
+/// # Errors
+/// Returns an error if writing fails.
+pub fn write_mesh_as_obj(mesh: &TriangleMesh, writer: &mut impl Write) -> Result<()> {
+    writeln!(writer, "# Exported from the Impact engine")?;
+
+    if mesh.has_colors() {
+        for (position, color) in mesh.positions().iter().zip(mesh.colors()) {
+            writeln!(
+                writer,
+                "v {} {} {} {} {} {}",
+                position.0.x(),
+                position.0.y(),
+                position.0.z(),
+                color.0.x(),
+                color.0.y(),
+                color.0.z()
+            )?;
+        }
+    } else {
+        for position in mesh.positions() {
+            writeln!(
+                writer,
+                "v {} {} {}",
+                position.0.x(),
+                position.0.y(),
+                position.0.z()
+            )?;
+        }
+    }
+
+    for texture_coords in mesh.texture_coords() {
+        writeln!(
+            writer,
+            "vt {} {}",
+            texture_coords.0.x(),
+            texture_coords.0.y()
+        )?;
+    }
+
+    for normal_vector in mesh.normal_vectors() {
+        writeln!(
+            writer,
+            "vn {} {} {}",
+            normal_vector.0.x(),
+            normal_vector.0.y(),
+            normal_vector.0.z()
+        )?;
+    }
+
+    let has_texture_coords = mesh.has_texture_coords();
+    let has_normal_vectors = mesh.has_normal_vectors();
+
+    for triangle_indices in mesh.triangle_indices() {
+        write!(writer, "f")?;
+        for index in triangle_indices {
+            // OBJ indices are one-based
+            let index = index + 1;
+            match (has_texture_coords, has_normal_vectors) {
+                (true, true) => write!(writer, " {index}/{index}/{index}")?,
+                (true, false) => write!(writer, " {index}/{index}")?,
+                (false, true) => write!(writer, " {index}//{index}")?,
+                (false, false) => write!(writer, " {index}")?,
+            }
+        }
+        writeln!(writer)?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes the given mesh as binary little-endian PLY to the given writer.
+///
+/// The vertex properties are laid out in the order expected by the PLY reader
+/// in this crate: position, color, normal vector and texture coordinates.
+///
+/// # Errors
+/// Returns an error if writing fails.
+pub fn write_mesh_as_ply(mesh: &TriangleMesh, writer: &mut impl Write) -> Result<()> {
+    let has_colors = mesh.has_colors();
+    let has_normal_vectors = mesh.has_normal_vectors();
+    let has_texture_coords = mesh.has_texture_coords();
+
+    writeln!(writer, "ply")?;
+    writeln!(writer, "format binary_little_endian 1.0")?;
+    writeln!(writer, "comment Exported from the Impact engine")?;
+    writeln!(writer, "element vertex {}", mesh.n_vertices())?;
+    writeln!(writer, "property float x")?;
+    writeln!(writer, "property float y")?;
+    writeln!(writer, "property float z")?;
+    if has_colors {
+        writeln!(writer, "property uchar red")?;
+        writeln!(writer, "property uchar green")?;
+        writeln!(writer, "property uchar blue")?;
+    }
+    if has_normal_vectors {
+        writeln!(writer, "property float nx")?;
+        writeln!(writer, "property float ny")?;
+        writeln!(writer, "property float nz")?;
+    }
+    if has_texture_coords {
+        writeln!(writer, "property float u")?;
+        writeln!(writer, "property float v")?;
+    }
+    writeln!(writer, "element face {}", mesh.n_triangles())?;
+    writeln!(writer, "property list uchar uint vertex_indices")?;
+    writeln!(writer, "end_header")?;
+
+    for idx in 0..mesh.n_vertices() {
+        let position = &mesh.positions()[idx].0;
+        for value in [position.x(), position.y(), position.z()] {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        if has_colors {
+            let color = &mesh.colors()[idx].0;
+            writer.write_all(&[
+                float_to_u8(color.x()),
+                float_to_u8(color.y()),
+                float_to_u8(color.z()),
+            ])?;
+        }
+        if has_normal_vectors {
+            let normal_vector = &mesh.normal_vectors()[idx].0;
+            for value in [normal_vector.x(), normal_vector.y(), normal_vector.z()] {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+        }
+        if has_texture_coords {
+            let texture_coords = &mesh.texture_coords()[idx].0;
+            for value in [texture_coords.x(), texture_coords.y()] {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+        }
+    }
+
+    for triangle_indices in mesh.indices().chunks_exact(3) {
+        writer.write_all(&[3])?;
+        for &index in triangle_indices {
+            writer.write_all(&index.to_le_bytes())?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(feature = "gltf")]
+fn write_mesh_to_gltf_file(mesh: &TriangleMesh, file_path: &Path) -> Result<()> {
+    crate::io::gltf::write_mesh_to_gltf_file(mesh, file_path)
+}
+
+#[cfg(not(feature = "gltf"))]
+fn write_mesh_to_gltf_file(_mesh: &TriangleMesh, _file_path: &Path) -> Result<()> {
+    anyhow::bail!("Please enable the `gltf` feature in order to write .gltf and .glb files")
+}
+
+fn write_to_file(
+    file_path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<()>,
+) -> Result<()> {
+    if let Some(parent) = file_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut writer = BufWriter::new(File::create(file_path)?);
+    write(&mut writer)
+}
+
+fn float_to_u8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{VertexColor, VertexNormalVector, VertexPosition, VertexTextureCoords};
+    use impact_math::{
+        point::Point3C,
+        vector::{UnitVector3C, Vector2, Vector4C},
+    };
+
+    fn create_test_mesh(with_attributes: bool) -> TriangleMesh {
+        let positions = vec![
+            VertexPosition(Point3C::new(0.0, 0.0, 0.0)),
+            VertexPosition(Point3C::new(1.0, 0.0, 0.0)),
+            VertexPosition(Point3C::new(0.0, 1.0, 0.0)),
+            VertexPosition(Point3C::new(1.0, 1.0, 0.5)),
+        ];
+        let (normal_vectors, texture_coords, colors) = if with_attributes {
+            (
+                vec![VertexNormalVector(UnitVector3C::unit_z()); 4],
+                vec![
+                    VertexTextureCoords(Vector2::new(0.0, 0.0)),
+                    VertexTextureCoords(Vector2::new(1.0, 0.0)),
+                    VertexTextureCoords(Vector2::new(0.0, 1.0)),
+                    VertexTextureCoords(Vector2::new(1.0, 1.0)),
+                ],
+                vec![
+                    VertexColor::RED,
+                    VertexColor::GREEN,
+                    VertexColor::BLUE,
+                    VertexColor(Vector4C::new(0.5, 0.5, 0.5, 1.0)),
+                ],
+            )
+        } else {
+            (Vec::new(), Vec::new(), Vec::new())
+        };
+        TriangleMesh::new(
+            positions,
+            normal_vectors,
+            texture_coords,
+            Vec::new(),
+            colors,
+            vec![0, 1, 2, 2, 1, 3],
+        )
+    }
+
+    #[test]
+    fn writing_obj_without_attributes_gives_plain_vertices_and_faces() {
+        let mut bytes = Vec::new();
+        write_mesh_as_obj(&create_test_mesh(false), &mut bytes).unwrap();
+        let text = String::from_utf8(bytes).unwrap();
+        let lines: Vec<_> = text.lines().skip(1).collect();
+        assert_eq!(
+            lines,
+            [
+                "v 0 0 0",
+                "v 1 0 0",
+                "v 0 1 0",
+                "v 1 1 0.5",
+                "f 1 2 3",
+                "f 3 2 4"
+            ]
+        );
+    }
+
+    #[test]
+    fn writing_obj_with_attributes_references_all_attributes_in_faces() {
+        let mut bytes = Vec::new();
+        write_mesh_as_obj(&create_test_mesh(true), &mut bytes).unwrap();
+        let text = String::from_utf8(bytes).unwrap();
+        assert!(text.contains("v 0 0 0 1 0 0\n"));
+        assert!(text.contains("v 1 1 0.5 0.5 0.5 0.5\n"));
+        assert_eq!(
+            text.lines().filter(|line| line.starts_with("vt ")).count(),
+            4
+        );
+        assert_eq!(
+            text.lines().filter(|line| line.starts_with("vn ")).count(),
+            4
+        );
+        assert!(text.contains("f 3/3/3 2/2/2 4/4/4\n"));
+    }
+
+    #[test]
+    fn writing_ply_gives_header_and_correctly_sized_binary_payload() {
+        let mesh = create_test_mesh(true);
+        let mut bytes = Vec::new();
+        write_mesh_as_ply(&mesh, &mut bytes).unwrap();
+
+        let header_end = b"end_header\n";
+        let payload_start = bytes
+            .windows(header_end.len())
+            .position(|window| window == header_end)
+            .unwrap()
+            + header_end.len();
+
+        let header = std::str::from_utf8(&bytes[..payload_start]).unwrap();
+        assert!(header.starts_with("ply\nformat binary_little_endian 1.0\n"));
+        assert!(header.contains("element vertex 4\n"));
+        assert!(header.contains("element face 2\n"));
+
+        // Position (12) + color (3) + normal vector (12) + texture coords (8)
+        let vertex_size = 35;
+        // Count (1) + three indices (12)
+        let face_size = 13;
+        assert_eq!(bytes.len() - payload_start, 4 * vertex_size + 2 * face_size);
+
+        let second_vertex = &bytes[payload_start + vertex_size..];
+        assert_eq!(
+            f32::from_le_bytes(second_vertex[0..4].try_into().unwrap()),
+            1.0
+        );
+        assert_eq!(&second_vertex[12..15], &[0, 255, 0]);
+
+        let last_face = &bytes[bytes.len() - face_size..];
+        assert_eq!(last_face[0], 3);
+        assert_eq!(u32::from_le_bytes(last_face[9..13].try_into().unwrap()), 3);
+    }
+
+    #[cfg(feature = "gltf")]
+    #[test]
+    fn writing_and_reading_gltf_and_glb_preserves_mesh() {
+        let mesh = create_test_mesh(true);
+        let dir = std::env::temp_dir().join("impact_mesh_gltf_export_test");
+
+        for file_name in ["mesh.gltf", "mesh.glb"] {
+            let file_path = dir.join(file_name);
+            write_mesh_to_file(&mesh, &file_path).unwrap();
+            let read_mesh = crate::io::gltf::read_mesh_from_gltf_file(&file_path).unwrap();
+
+            assert_eq!(read_mesh.positions(), mesh.positions());
+            assert_eq!(read_mesh.normal_vectors(), mesh.normal_vectors());
+            assert_eq!(read_mesh.texture_coords(), mesh.texture_coords());
+            assert_eq!(read_mesh.colors(), mesh.colors());
+            assert_eq!(read_mesh.indices(), mesh.indices());
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
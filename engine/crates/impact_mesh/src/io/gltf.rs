@@ -0,0 +1,558 @@
+//! Input/output of mesh data in glTF 2.0 format.
+
+use crate::{
+    TriangleMesh, TriangleMeshDirtyMask, VertexColor, VertexNormalVector, VertexPosition,
+    VertexSkinWeights, VertexTangentSpaceQuaternion, VertexTextureCoords,
+};
+use anyhow::{Context, Result, anyhow, bail};
+use base64::Engine as _;
+use gltf::{
+    Document, Gltf, Primitive, binary, buffer,
+    json::{
+        self, accessor,
+        validation::{Checked::Valid, USize64},
+    },
+    mesh::Mode,
+};
+use impact_math::{
+    point::Point3C,
+    vector::{UnitVector3C, Vector2, Vector3, Vector3C, Vector4C},
+};
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// A parsed glTF document together with the binary data of all its buffers.
+#[derive(Debug)]
+pub struct GltfFile {
+    /// The parsed glTF document.
+    pub document: Document,
+    buffers: Vec<Vec<u8>>,
+    root_path: PathBuf,
+}
+
+impl GltfFile {
+    /// Reads the glTF (`.gltf`) or binary glTF (`.glb`) file at the given path
+    /// along with all buffers it references.
+    ///
+    /// # Errors
+    /// Returns an error if the file can not be read or parsed, or if any of
+    /// the referenced buffers can not be loaded.
+    pub fn read(file_path: impl AsRef<Path>) -> Result<Self> {
+        let file_path = file_path.as_ref();
+
+        let bytes = fs::read(file_path)
+            .with_context(|| format!("Failed to read glTF file {}", file_path.display()))?;
+
+        let Gltf { document, mut blob } = Gltf::from_slice(&bytes)
+            .with_context(|| format!("Failed to parse glTF file {}", file_path.display()))?;
+
+        let root_path = file_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+
+        let mut buffers = Vec::with_capacity(document.buffers().len());
+
+        for buffer in document.buffers() {
+            let mut data = match buffer.source() {
+                buffer::Source::Bin => blob
+                    .take()
+                    .ok_or_else(|| anyhow!("Missing binary chunk in glTF file"))?,
+                buffer::Source::Uri(uri) => read_uri(&root_path, uri)
+                    .with_context(|| format!("Failed to load glTF buffer {}", buffer.index()))?,
+            };
+
+            if data.len() < buffer.length() {
+                bail!(
+                    "glTF buffer {} has {} bytes, expected at least {}",
+                    buffer.index(),
+                    data.len(),
+                    buffer.length()
+                );
+            }
+            data.truncate(buffer.length());
+
+            buffers.push(data);
+        }
+
+        Ok(Self {
+            document,
+            buffers,
+            root_path,
+        })
+    }
+
+    /// Returns the binary data of the given buffer, or [`None`] if the buffer
+    /// does not belong to this file. Suitable as the buffer lookup for the
+    /// accessor readers of the `gltf` crate.
+    pub fn buffer_data(&self, buffer: &buffer::Buffer<'_>) -> Option<&[u8]> {
+        self.buffers.get(buffer.index()).map(Vec::as_slice)
+    }
+
+    /// Returns the binary data of the given buffer view.
+    pub fn buffer_view_data(&self, view: &buffer::View<'_>) -> &[u8] {
+        let buffer = &self.buffers[view.buffer().index()];
+        &buffer[view.offset()..view.offset() + view.length()]
+    }
+
+    /// Returns the data referenced by the given URI, which is either a data
+    /// URI with base64 encoded data or a path relative to the glTF file.
+    ///
+    /// # Errors
+    /// Returns an error if the URI is invalid or the file can not be read.
+    pub fn read_uri(&self, uri: &str) -> Result<Vec<u8>> {
+        read_uri(&self.root_path, uri)
+    }
+}
+
+/// Reads the glTF 2.0 file (`.gltf` or `.glb`) at the given path and creates a
+/// corresponding `TriangleMesh`. If there are multiple meshes or mesh
+/// primitives in the file, they are merged into a single mesh. Node transforms
+/// and materials are ignored.
+///
+/// # Errors
+/// Returns an error if the file can not be found or loaded as a mesh.
+pub fn read_mesh_from_gltf_file(file_path: impl AsRef<Path>) -> Result<TriangleMesh> {
+    let file_path = file_path.as_ref();
+
+    let file = GltfFile::read(file_path)?;
+
+    let mut merged_mesh: Option<TriangleMesh> = None;
+    let mut dirty_mask = TriangleMeshDirtyMask::empty();
+
+    for mesh in file.document.meshes() {
+        for primitive in mesh.primitives() {
+            let Some(primitive_mesh) = create_mesh_from_gltf_primitive(&file, &primitive)? else {
+                continue;
+            };
+            match &mut merged_mesh {
+                Some(merged_mesh) => merged_mesh.merge_with(&primitive_mesh, &mut dirty_mask),
+                None => {
+                    merged_mesh = Some(primitive_mesh);
+                }
+            }
+        }
+    }
+
+    merged_mesh.ok_or_else(|| {
+        anyhow!(
+            "File {} does not contain any triangle meshes",
+            file_path.display()
+        )
+    })
+}
+
+/// Creates a `TriangleMesh` from the given primitive of a mesh in the given
+/// glTF file. Positions, normal vectors, the first set of texture
+/// coordinates, tangents, the first set of vertex colors and the first set of
+/// joint indices and weights are imported.
+///
+/// # Returns
+/// The created mesh, or [`None`] if the primitive does not consist of
+/// triangles.
+///
+/// # Errors
+/// Returns an error if the primitive has no positions or if the number of
+/// values differs between the vertex attributes.
+pub fn create_mesh_from_gltf_primitive(
+    file: &GltfFile,
+    primitive: &Primitive<'_>,
+) -> Result<Option<TriangleMesh>> {
+    if primitive.mode() != Mode::Triangles {
+        log::warn!(
+            "Skipping glTF mesh primitive {} with unsupported mode {:?}",
+            primitive.index(),
+            primitive.mode()
+        );
+        return Ok(None);
+    }
+
+    let reader = primitive.reader(|buffer| file.buffer_data(&buffer));
+
+    let positions: Vec<_> = reader
+        .read_positions()
+        .ok_or_else(|| anyhow!("Missing positions for glTF mesh primitive"))?
+        .map(|[x, y, z]| VertexPosition(Point3C::new(x, y, z)))
+        .collect();
+
+    let n_vertices = positions.len();
+
+    let normal_vectors: Vec<_> = reader
+        .read_normals()
+        .map(|normals| {
+            normals
+                .map(|[x, y, z]| {
+                    VertexNormalVector(UnitVector3C::normalized_from(Vector3C::new(x, y, z)))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let texture_coords: Vec<_> = reader
+        .read_tex_coords(0)
+        .map(|texture_coords| {
+            texture_coords
+                .into_f32()
+                .map(|[u, v]| VertexTextureCoords(Vector2::new(u, v)))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Tangents are meaningless without normal vectors
+    let tangent_space_quaternions: Vec<_> = match reader.read_tangents() {
+        Some(tangents) if normal_vectors.len() == n_vertices => tangents
+            .zip(&normal_vectors)
+            .map(|([x, y, z, w], normal)| {
+                // glTF bitangents point toward decreasing v-coordinates, while
+                // ours point toward increasing v, so a basis that is
+                // right-handed in glTF is left-handed for us
+                VertexTangentSpaceQuaternion::from_normal_and_tangent(
+                    &normal.0.aligned(),
+                    &Vector3::new(x, y, z),
+                    w > 0.0,
+                )
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let colors: Vec<_> = reader
+        .read_colors(0)
+        .map(|colors| {
+            colors
+                .into_rgba_f32()
+                .map(|[r, g, b, a]| VertexColor(Vector4C::new(r, g, b, a)))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let skin_weights: Vec<_> = match (reader.read_joints(0), reader.read_weights(0)) {
+        (Some(joints), Some(weights)) => joints
+            .into_u16()
+            .zip(weights.into_f32())
+            .map(|(joint_indices, weights)| VertexSkinWeights::new(joint_indices, weights))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let indices: Vec<u32> = match reader.read_indices() {
+        Some(indices) => indices.into_u32().collect(),
+        None => (0..u32::try_from(n_vertices)?).collect(),
+    };
+
+    for (n_values, name) in [
+        (normal_vectors.len(), "normal vectors"),
+        (texture_coords.len(), "texture coordinates"),
+        (tangent_space_quaternions.len(), "tangents"),
+        (colors.len(), "colors"),
+        (skin_weights.len(), "skin weights"),
+    ] {
+        if n_values != 0 && n_values != n_vertices {
+            bail!("glTF mesh primitive has {n_values} {name} but {n_vertices} positions");
+        }
+    }
+
+    if let Some(&index) = indices.iter().find(|&&index| index as usize >= n_vertices) {
+        bail!("glTF mesh primitive has out-of-bounds vertex index {index}");
+    }
+
+    let mut mesh = TriangleMesh::new(
+        positions,
+        normal_vectors,
+        texture_coords,
+        tangent_space_quaternions,
+        colors,
+        indices,
+    );
+    mesh.set_skin_weights(skin_weights, &mut TriangleMeshDirtyMask::empty());
+
+    Ok(Some(mesh))
+}
+
+/// Writes the given mesh to a glTF 2.0 file at the given path. If the path has
+/// a `.glb` extension, a binary glTF file is written. Otherwise, a `.gltf` JSON
+/// file is written with the vertex data embedded as a base64 data URI, so that
+/// the mesh is always contained in a single file. Positions, normal vectors,
+/// the texture coordinates and the colors of the mesh are written if present.
+///
+/// # Errors
+/// Returns an error if the mesh has no vertices or the file can not be
+/// written.
+pub fn write_mesh_to_gltf_file(mesh: &TriangleMesh, file_path: impl AsRef<Path>) -> Result<()> {
+    let file_path = file_path.as_ref();
+
+    let is_binary = file_path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("glb"));
+
+    let bytes = if is_binary {
+        create_glb_bytes(mesh)
+    } else {
+        create_gltf_json_bytes(mesh)
+    }
+    .with_context(|| format!("Failed to create glTF data for {}", file_path.display()))?;
+
+    if let Some(parent) = file_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(file_path, bytes)
+        .with_context(|| format!("Failed to write glTF file {}", file_path.display()))
+}
+
+fn create_gltf_json_bytes(mesh: &TriangleMesh) -> Result<Vec<u8>> {
+    let (mut root, buffer_data) = create_gltf_document_for_mesh(mesh)?;
+
+    root.buffers[0].uri = Some(format!(
+        "data:application/octet-stream;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(&buffer_data)
+    ));
+
+    Ok(json::serialize::to_vec_pretty(&root)?)
+}
+
+fn create_glb_bytes(mesh: &TriangleMesh) -> Result<Vec<u8>> {
+    let (root, buffer_data) = create_gltf_document_for_mesh(mesh)?;
+
+    let json = json::serialize::to_vec(&root)?;
+
+    let glb = binary::Glb {
+        header: binary::Header {
+            magic: *b"glTF",
+            version: 2,
+            // Computed when writing
+            length: 0,
+        },
+        json: Cow::Owned(json),
+        bin: Some(Cow::Owned(buffer_data)),
+    };
+
+    Ok(glb.to_vec()?)
+}
+
+/// Creates a glTF document with a single scene containing a single node with
+/// the given mesh, together with the data for the single buffer referenced by
+/// the document. The URI of the buffer is left unspecified.
+fn create_gltf_document_for_mesh(mesh: &TriangleMesh) -> Result<(json::Root, Vec<u8>)> {
+    if !mesh.has_positions() {
+        bail!("Tried to write glTF for mesh without vertices");
+    }
+
+    let mut root = json::Root::default();
+    root.asset.generator = Some("Impact".to_string());
+
+    let buffer = root.push(json::Buffer {
+        byte_length: USize64(0),
+        name: None,
+        uri: None,
+        extensions: None,
+        extras: Default::default(),
+    });
+
+    let mut buffer_data = Vec::new();
+    let mut attributes = BTreeMap::new();
+
+    let mut min_position = [f32::INFINITY; 3];
+    let mut max_position = [f32::NEG_INFINITY; 3];
+    for position in mesh.positions() {
+        let position = [position.0.x(), position.0.y(), position.0.z()];
+        for dim in 0..3 {
+            min_position[dim] = min_position[dim].min(position[dim]);
+            max_position[dim] = max_position[dim].max(position[dim]);
+        }
+    }
+
+    let positions = push_accessor(
+        &mut root,
+        buffer,
+        &mut buffer_data,
+        bytemuck::cast_slice(mesh.positions()),
+        mesh.n_vertices(),
+        accessor::ComponentType::F32,
+        accessor::Type::Vec3,
+        json::buffer::Target::ArrayBuffer,
+        Some((min_position, max_position)),
+    );
+    attributes.insert(Valid(json::mesh::Semantic::Positions), positions);
+
+    if mesh.has_normal_vectors() {
+        let normal_vectors = push_accessor(
+            &mut root,
+            buffer,
+            &mut buffer_data,
+            bytemuck::cast_slice(mesh.normal_vectors()),
+            mesh.n_vertices(),
+            accessor::ComponentType::F32,
+            accessor::Type::Vec3,
+            json::buffer::Target::ArrayBuffer,
+            None,
+        );
+        attributes.insert(Valid(json::mesh::Semantic::Normals), normal_vectors);
+    }
+
+    if mesh.has_texture_coords() {
+        let texture_coords = push_accessor(
+            &mut root,
+            buffer,
+            &mut buffer_data,
+            bytemuck::cast_slice(mesh.texture_coords()),
+            mesh.n_vertices(),
+            accessor::ComponentType::F32,
+            accessor::Type::Vec2,
+            json::buffer::Target::ArrayBuffer,
+            None,
+        );
+        attributes.insert(Valid(json::mesh::Semantic::TexCoords(0)), texture_coords);
+    }
+
+    if mesh.has_colors() {
+        let colors = push_accessor(
+            &mut root,
+            buffer,
+            &mut buffer_data,
+            bytemuck::cast_slice(mesh.colors()),
+            mesh.n_vertices(),
+            accessor::ComponentType::F32,
+            accessor::Type::Vec4,
+            json::buffer::Target::ArrayBuffer,
+            None,
+        );
+        attributes.insert(Valid(json::mesh::Semantic::Colors(0)), colors);
+    }
+
+    let indices = mesh.has_indices().then(|| {
+        push_accessor(
+            &mut root,
+            buffer,
+            &mut buffer_data,
+            bytemuck::cast_slice(mesh.indices()),
+            mesh.n_indices(),
+            accessor::ComponentType::U32,
+            accessor::Type::Scalar,
+            json::buffer::Target::ElementArrayBuffer,
+            None,
+        )
+    });
+
+    root.buffers[buffer.value()].byte_length = USize64::from(buffer_data.len());
+
+    let mesh = root.push(json::Mesh {
+        name: None,
+        primitives: vec![json::mesh::Primitive {
+            attributes,
+            indices,
+            material: None,
+            mode: Valid(Mode::Triangles),
+            targets: None,
+            extensions: None,
+            extras: Default::default(),
+        }],
+        weights: None,
+        extensions: None,
+        extras: Default::default(),
+    });
+
+    let node = root.push(json::Node {
+        mesh: Some(mesh),
+        ..Default::default()
+    });
+
+    let scene = root.push(json::Scene {
+        name: None,
+        nodes: vec![node],
+        extensions: None,
+        extras: Default::default(),
+    });
+    root.scene = Some(scene);
+
+    Ok((root, buffer_data))
+}
+
+/// Appends the given data to the buffer data and adds a buffer view and an
+/// accessor for it to the document.
+fn push_accessor(
+    root: &mut json::Root,
+    buffer: json::Index<json::Buffer>,
+    buffer_data: &mut Vec<u8>,
+    data: &[u8],
+    count: usize,
+    component_type: accessor::ComponentType,
+    type_: accessor::Type,
+    target: json::buffer::Target,
+    bounds: Option<([f32; 3], [f32; 3])>,
+) -> json::Index<json::Accessor> {
+    // All our component types are four bytes, so the data is always aligned
+    let byte_offset = buffer_data.len();
+    buffer_data.extend_from_slice(data);
+
+    let buffer_view = root.push(json::buffer::View {
+        buffer,
+        byte_length: USize64::from(data.len()),
+        byte_offset: Some(USize64::from(byte_offset)),
+        byte_stride: None,
+        name: None,
+        target: Some(Valid(target)),
+        extensions: None,
+        extras: Default::default(),
+    });
+
+    let (min, max) = bounds.map_or((None, None), |(min, max)| {
+        (
+            Some(json::Value::from(Vec::from(min))),
+            Some(json::Value::from(Vec::from(max))),
+        )
+    });
+
+    root.push(json::Accessor {
+        buffer_view: Some(buffer_view),
+        byte_offset: None,
+        count: USize64::from(count),
+        component_type: Valid(accessor::GenericComponentType(component_type)),
+        type_: Valid(type_),
+        min,
+        max,
+        name: None,
+        normalized: false,
+        sparse: None,
+        extensions: None,
+        extras: Default::default(),
+    })
+}
+
+fn read_uri(root_path: &Path, uri: &str) -> Result<Vec<u8>> {
+    if let Some(data_uri) = uri.strip_prefix("data:") {
+        let Some((_media_type, data)) = data_uri.split_once(";base64,") else {
+            bail!("Unsupported data URI in glTF file (only base64 encoding is supported)");
+        };
+        return base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .context("Invalid base64 data in glTF data URI");
+    }
+
+    let path = root_path.join(decode_percent_encoding(uri));
+    fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+/// Decodes `%XX` escape sequences in a relative URI reference.
+fn decode_percent_encoding(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == b'%'
+            && let Some(byte) = uri
+                .get(idx + 1..idx + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            decoded.push(byte);
+            idx += 3;
+        } else {
+            decoded.push(bytes[idx]);
+            idx += 1;
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
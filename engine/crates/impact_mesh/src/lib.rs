@@ -9,20 +9,23 @@ pub mod gpu_resource;
 pub mod import;
 pub mod io;
 mod line_segment;
+pub mod lod;
 pub mod setup;
+pub mod simplification;
 pub mod texture_projection;
 mod triangle;
 
 pub use line_segment::*;
 pub use triangle::*;
 
+use approx::abs_diff_ne;
 use bitflags::bitflags;
 use bytemuck::{Pod, Zeroable};
 use impact_math::{
     point::Point3C,
     quaternion::{UnitQuaternion, UnitQuaternionC},
     transform::Similarity3,
-    vector::{UnitVector3C, Vector2, Vector3, Vector4C},
+    vector::{UnitVector3, UnitVector3C, Vector2, Vector3, Vector4C},
 };
 use roc_integration::roc;
 use std::fmt::{self, Debug};
@@ -83,6 +86,18 @@ pub struct VertexTangentSpaceQuaternion(pub UnitQuaternionC);
 #[derive(Copy, Clone, Debug, PartialEq, Zeroable, Pod)]
 pub struct VertexColor(pub Vector4C);
 
+/// The skeleton joints influencing a mesh vertex together with the weight of
+/// each joint's influence. Unused slots should have zero weight. The weights
+/// of a vertex are expected to sum to one.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Zeroable, Pod)]
+pub struct VertexSkinWeights {
+    /// Indices of the influencing joints in the skeleton's joint list.
+    pub joint_indices: [u16; 4],
+    /// Weight of each influencing joint.
+    pub weights: [f32; 4],
+}
+
 bitflags! {
     /// Bitflag encoding a set of [`VertexAttribute`]s.
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -92,6 +107,7 @@ bitflags! {
         const TEXTURE_COORDS           = 1 << 2;
         const TANGENT_SPACE_QUATERNION = 1 << 3;
         const COLOR                    = 1 << 4;
+        const SKIN_WEIGHTS             = 1 << 5;
     }
 }
 
@@ -105,7 +121,7 @@ pub enum FrontFaceSide {
 }
 
 /// The total number of available vertex attribute types.
-pub const N_VERTEX_ATTRIBUTES: usize = 5;
+pub const N_VERTEX_ATTRIBUTES: usize = 6;
 
 /// The bitflag of each individual vertex attribute, ordered according to
 /// [`VertexAttribute::GLOBAL_INDEX`].
@@ -115,6 +131,7 @@ pub const VERTEX_ATTRIBUTE_FLAGS: [VertexAttributeSet; N_VERTEX_ATTRIBUTES] = [
     VertexAttributeSet::TEXTURE_COORDS,
     VertexAttributeSet::TANGENT_SPACE_QUATERNION,
     VertexAttributeSet::COLOR,
+    VertexAttributeSet::SKIN_WEIGHTS,
 ];
 
 /// The name of each individual vertex attribute, ordered according to
@@ -125,6 +142,7 @@ pub const VERTEX_ATTRIBUTE_NAMES: [&str; N_VERTEX_ATTRIBUTES] = [
     "texture coords",
     "tangent space quaternion",
     "color",
+    "skin weights",
 ];
 
 impl fmt::Display for MeshID {
@@ -176,6 +194,45 @@ impl VertexNormalVector {
 }
 
 impl VertexTangentSpaceQuaternion {
+    /// Creates the tangent space quaternion for the given unit normal vector
+    /// and tangent vector. The tangent vector does not have to be normalized
+    /// or orthogonal to the normal vector. The bitangent vector is taken to be
+    /// the cross product of the normal and tangent vectors, or its negation if
+    /// `is_lefthanded` is `true`.
+    pub fn from_normal_and_tangent(
+        normal: &UnitVector3,
+        tangent: &Vector3,
+        is_lefthanded: bool,
+    ) -> Self {
+        let orthogonal_tangent = tangent - normal * normal.dot(tangent);
+
+        let tangent = UnitVector3::normalized_from_if_above(orthogonal_tangent, 1e-8)
+            .unwrap_or_else(|| {
+                if abs_diff_ne!(normal.x().abs(), 1.0) {
+                    UnitVector3::normalized_from(Vector3::unit_x() - normal * normal.x())
+                } else {
+                    UnitVector3::normalized_from(Vector3::unit_y() - normal * normal.y())
+                }
+            });
+
+        let bitangent = normal.cross(&tangent);
+
+        let mut tangent_space_quaternion = UnitQuaternion::from_basis_unchecked(&[
+            *tangent.as_vector(),
+            bitangent,
+            *normal.as_vector(),
+        ]);
+
+        // Make sure real component is positive for a right-handed basis and
+        // negative for a left-handed basis (negating a quaternion gives the
+        // same rotation)
+        if (tangent_space_quaternion.real() < 0.0) != is_lefthanded {
+            tangent_space_quaternion = -tangent_space_quaternion;
+        }
+
+        Self(tangent_space_quaternion.compact())
+    }
+
     /// Returns the tangent space quaternion rotated by the given unit
     /// quaternion.
     pub fn rotated(&self, rotation: &UnitQuaternion) -> Self {
@@ -214,6 +271,32 @@ impl VertexColor {
     }
 }
 
+impl VertexSkinWeights {
+    /// Creates skin weights from the given joint indices and weights. The
+    /// weights are normalized to sum to one unless they are all zero, in
+    /// which case the vertex is bound fully to the first joint.
+    pub fn new(joint_indices: [u16; 4], weights: [f32; 4]) -> Self {
+        let weight_sum: f32 = weights.iter().sum();
+        let weights = if weight_sum > 0.0 {
+            weights.map(|weight| weight / weight_sum)
+        } else {
+            [1.0, 0.0, 0.0, 0.0]
+        };
+        Self {
+            joint_indices,
+            weights,
+        }
+    }
+
+    /// Returns skin weights binding the vertex fully to the given joint.
+    pub const fn single_joint(joint_index: u16) -> Self {
+        Self {
+            joint_indices: [joint_index, 0, 0, 0],
+            weights: [1.0, 0.0, 0.0, 0.0],
+        }
+    }
+}
+
 impl VertexAttribute for VertexPosition {
     const GLOBAL_INDEX: usize = 0;
 }
@@ -234,6 +317,10 @@ impl VertexAttribute for VertexColor {
     const GLOBAL_INDEX: usize = 4;
 }
 
+impl VertexAttribute for VertexSkinWeights {
+    const GLOBAL_INDEX: usize = 5;
+}
+
 impl fmt::Display for VertexAttributeSet {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{{ ")?;
@@ -0,0 +1,163 @@
+//! Level-of-detail chains for triangle meshes.
+
+use crate::{TriangleMesh, TriangleMeshID, TriangleMeshRegistry};
+
+/// The maximum number of simplified levels of detail a triangle mesh can have
+/// in addition to the full-detail mesh.
+pub const MAX_LOD_LEVELS: usize = 8;
+
+/// The smallest number of triangles we will simplify a mesh down to.
+const MIN_LOD_TRIANGLES: usize = 4;
+
+/// Declaration of the chain of simplified meshes to generate for a triangle
+/// mesh when it is imported.
+///
+/// Level `i` of the chain is produced by simplifying level `i - 1` (level 0
+/// being the original mesh) to the given fraction of its triangle count.
+/// Generation stops early if a level can not be simplified further.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TriangleMeshLODDeclaration {
+    /// The number of simplified levels to generate (at most
+    /// [`MAX_LOD_LEVELS`]).
+    pub n_levels: usize,
+    /// The fraction of the triangles in the previous level to keep in each
+    /// level.
+    pub triangle_fraction: f32,
+}
+
+impl Default for TriangleMeshLODDeclaration {
+    fn default() -> Self {
+        Self {
+            n_levels: 3,
+            triangle_fraction: 0.5,
+        }
+    }
+}
+
+/// Returns the ID under which the simplified mesh for the given level of
+/// detail (starting from 1) for the mesh with the given ID is stored.
+pub fn lod_mesh_id(mesh_id: TriangleMeshID, level: usize) -> TriangleMeshID {
+    TriangleMeshID::from_name(&format!("{mesh_id}@lod{level}"))
+}
+
+/// Returns the IDs of the simplified meshes present in the registry for the
+/// mesh with the given ID, ordered from the most to the least detailed.
+pub fn lod_mesh_ids(
+    registry: &TriangleMeshRegistry,
+    mesh_id: TriangleMeshID,
+) -> Vec<TriangleMeshID> {
+    (1..=MAX_LOD_LEVELS)
+        .map(|level| lod_mesh_id(mesh_id, level))
+        .take_while(|&id| registry.contains(id))
+        .collect()
+}
+
+/// Generates the chain of simplified meshes for the given mesh according to
+/// the given declaration. The returned meshes are ordered from the most to the
+/// least detailed, and do not include the original mesh.
+pub fn generate_lod_meshes(
+    mesh: &TriangleMesh,
+    declaration: &TriangleMeshLODDeclaration,
+) -> Vec<TriangleMesh> {
+    let n_levels = declaration.n_levels.min(MAX_LOD_LEVELS);
+    let triangle_fraction = declaration.triangle_fraction.clamp(0.0, 1.0);
+
+    let mut lod_meshes: Vec<TriangleMesh> = Vec::with_capacity(n_levels);
+
+    for _ in 0..n_levels {
+        let previous = lod_meshes.last().unwrap_or(mesh);
+        let n_previous_triangles = previous.n_triangles();
+
+        let target_n_triangles =
+            ((n_previous_triangles as f32 * triangle_fraction) as usize).max(MIN_LOD_TRIANGLES);
+
+        if target_n_triangles >= n_previous_triangles {
+            break;
+        }
+
+        let simplified = previous.simplified(target_n_triangles);
+
+        // Stop if the simplifier is unable to make meaningful progress
+        if simplified.n_triangles() * 10 > n_previous_triangles * 9 {
+            break;
+        }
+
+        lod_meshes.push(simplified);
+    }
+
+    lod_meshes
+}
+
+/// Generates the chain of simplified meshes for the mesh with the given ID in
+/// the registry according to the given declaration and inserts them into the
+/// registry under the IDs given by [`lod_mesh_id`]. Any previous simplified
+/// meshes for the mesh are removed.
+///
+/// # Returns
+/// The number of generated levels, or [`None`] if the mesh is not present.
+pub fn generate_and_insert_lod_meshes(
+    registry: &mut TriangleMeshRegistry,
+    mesh_id: TriangleMeshID,
+    declaration: &TriangleMeshLODDeclaration,
+) -> Option<usize> {
+    let mesh = registry.get(mesh_id)?;
+
+    if !mesh.has_indices() {
+        return Some(0);
+    }
+
+    let lod_meshes = generate_lod_meshes(mesh, declaration);
+    let n_levels = lod_meshes.len();
+
+    for id in lod_mesh_ids(registry, mesh_id) {
+        registry.remove(id);
+    }
+
+    for (level, lod_mesh) in (1..).zip(lod_meshes) {
+        registry.insert(lod_mesh_id(mesh_id, level), lod_mesh);
+    }
+
+    Some(n_levels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lod_meshes_have_decreasing_triangle_counts() {
+        let mesh = TriangleMesh::create_sphere(16);
+        let lod_meshes = generate_lod_meshes(&mesh, &TriangleMeshLODDeclaration::default());
+
+        assert!(!lod_meshes.is_empty());
+
+        let mut n_previous_triangles = mesh.n_triangles();
+        for lod_mesh in &lod_meshes {
+            assert!(lod_mesh.n_triangles() < n_previous_triangles);
+            n_previous_triangles = lod_mesh.n_triangles();
+        }
+    }
+
+    #[test]
+    fn inserted_lod_meshes_are_found_in_order() {
+        let mut registry = TriangleMeshRegistry::new();
+        let mesh_id = TriangleMeshID::from_name("LODTestSphere");
+        registry.insert(mesh_id, TriangleMesh::create_sphere(16));
+
+        let n_levels = generate_and_insert_lod_meshes(
+            &mut registry,
+            mesh_id,
+            &TriangleMeshLODDeclaration::default(),
+        )
+        .unwrap();
+
+        let ids = lod_mesh_ids(&registry, mesh_id);
+        assert_eq!(ids.len(), n_levels);
+        assert_eq!(ids.first(), Some(&lod_mesh_id(mesh_id, 1)));
+    }
+}
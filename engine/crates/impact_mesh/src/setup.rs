@@ -2,12 +2,12 @@
 
 use crate::{
     FrontFaceSide, TriangleMesh, TriangleMeshDirtyMask, TriangleMeshID, TriangleMeshRegistry,
-    VertexAttributeSet, texture_projection::TextureProjection,
+    VertexAttributeSet, lod, texture_projection::TextureProjection,
 };
 use bytemuck::{Pod, Zeroable};
 use impact_math::{hash64, point::Point3C, vector::Vector3C};
 use roc_integration::roc;
-use std::fmt;
+use std::{fmt, iter};
 
 /// Template specifying how to generate a [`TriangleMesh`].
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -631,7 +631,7 @@ pub fn setup_triangle_mesh_from_template(
 }
 
 /// Generates the vertex attributes missing from the giving requirements for the
-/// specified mesh, if possible.
+/// specified mesh and its simplified levels of detail, if possible.
 pub fn generate_missing_vertex_properties_for_mesh(
     registry: &mut TriangleMeshRegistry,
     mesh_id: TriangleMeshID,
@@ -643,28 +643,34 @@ pub fn generate_missing_vertex_properties_for_mesh(
         return;
     }
 
-    let Some(mut mesh) = registry.get_mut(mesh_id) else {
+    if !registry.contains(mesh_id) {
         log::warn!("Tried to generate missing vertex properties for missing mesh: {mesh_id}");
         return;
-    };
+    }
 
-    let mut dirty_mask = TriangleMeshDirtyMask::empty();
+    for id in iter::once(mesh_id).chain(lod::lod_mesh_ids(registry, mesh_id)) {
+        let Some(mut mesh) = registry.get_mut(id) else {
+            continue;
+        };
 
-    if vertex_attribute_requirements.contains(VertexAttributeSet::NORMAL_VECTOR)
-        && !mesh.has_normal_vectors()
-    {
-        log::info!("Generating normal vectors for mesh: {mesh_id}");
-        mesh.generate_smooth_normal_vectors(&mut dirty_mask);
-    }
+        let mut dirty_mask = TriangleMeshDirtyMask::empty();
 
-    if vertex_attribute_requirements.contains(VertexAttributeSet::TANGENT_SPACE_QUATERNION)
-        && !mesh.has_tangent_space_quaternions()
-    {
-        log::info!("Generating tangent space quaternions for mesh: {mesh_id}");
-        mesh.generate_smooth_tangent_space_quaternions(&mut dirty_mask);
-    }
+        if vertex_attribute_requirements.contains(VertexAttributeSet::NORMAL_VECTOR)
+            && !mesh.has_normal_vectors()
+        {
+            log::info!("Generating normal vectors for mesh: {id}");
+            mesh.generate_smooth_normal_vectors(&mut dirty_mask);
+        }
 
-    mesh.set_dirty_mask(dirty_mask);
+        if vertex_attribute_requirements.contains(VertexAttributeSet::TANGENT_SPACE_QUATERNION)
+            && !mesh.has_tangent_space_quaternions()
+        {
+            log::info!("Generating tangent space quaternions for mesh: {id}");
+            mesh.generate_smooth_tangent_space_quaternions(&mut dirty_mask);
+        }
+
+        mesh.set_dirty_mask(dirty_mask);
+    }
 }
 
 fn create_projection_label(projection: Option<&impl TextureProjection>) -> String {
@@ -0,0 +1,594 @@
+//! Simplification of triangle meshes.
+
+use crate::{TriangleMesh, TriangleMeshDirtyMask};
+use impact_containers::HashMap;
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+/// The weight of the planes constraining boundary edges relative to the
+/// weight of the triangle planes. A high weight makes sure that the boundary
+/// of an open mesh (including seams where vertices are duplicated) is
+/// preserved until no other collapses remain.
+const BOUNDARY_PLANE_WEIGHT: f64 = 1e3;
+
+/// A symmetric 4x4 matrix representing the sum of squared distances to a set
+/// of planes, stored as its 10 unique coefficients.
+#[derive(Clone, Copy, Debug, Default)]
+struct Quadric([f64; 10]);
+
+/// A candidate collapse of the edge between two vertices, where the `from`
+/// vertex is merged into the `to` vertex.
+#[derive(Clone, Copy, Debug)]
+struct EdgeCollapse {
+    cost: f64,
+    from: u32,
+    to: u32,
+    from_version: u32,
+    to_version: u32,
+}
+
+#[derive(Debug)]
+struct MeshSimplifier {
+    positions: Vec<[f64; 3]>,
+    quadrics: Vec<Quadric>,
+    vertex_versions: Vec<u32>,
+    vertex_is_removed: Vec<bool>,
+    vertex_is_on_boundary: Vec<bool>,
+    vertex_triangles: Vec<Vec<usize>>,
+    triangles: Vec<[u32; 3]>,
+    triangle_is_removed: Vec<bool>,
+    n_remaining_triangles: usize,
+    candidates: BinaryHeap<EdgeCollapse>,
+}
+
+impl TriangleMesh {
+    /// Creates a simplified version of this mesh with at most the given number
+    /// of triangles, if possible.
+    ///
+    /// The mesh is simplified by repeatedly collapsing the edge whose removal
+    /// introduces the smallest geometric error, as measured by the quadric
+    /// error metric (the sum of squared distances to the planes of the
+    /// original triangles around each vertex). Every collapse merges one
+    /// vertex into the other, so the remaining vertices keep all their
+    /// original attributes. Collapses that would flip a triangle, make the
+    /// mesh non-manifold or move its boundary are rejected, so the returned
+    /// mesh may have more triangles than requested.
+    ///
+    /// # Panics
+    /// If the mesh misses positions or indices.
+    pub fn simplified(&self, target_n_triangles: usize) -> Self {
+        assert!(self.has_positions());
+        assert!(self.has_indices());
+
+        let mut simplifier = MeshSimplifier::new(self);
+        simplifier.simplify(target_n_triangles);
+        simplifier.create_simplified_mesh(self)
+    }
+}
+
+impl Quadric {
+    fn for_plane(normal: [f64; 3], point: [f64; 3], weight: f64) -> Self {
+        let [a, b, c] = normal;
+        let d = -dot(normal, point);
+        Self([
+            a * a,
+            a * b,
+            a * c,
+            a * d,
+            b * b,
+            b * c,
+            b * d,
+            c * c,
+            c * d,
+            d * d,
+        ])
+        .scaled(weight)
+    }
+
+    fn scaled(mut self, factor: f64) -> Self {
+        for coef in &mut self.0 {
+            *coef *= factor;
+        }
+        self
+    }
+
+    fn add(&mut self, other: &Self) {
+        for (coef, other_coef) in self.0.iter_mut().zip(other.0) {
+            *coef += other_coef;
+        }
+    }
+
+    fn evaluate(&self, [x, y, z]: [f64; 3]) -> f64 {
+        let [aa, ab, ac, ad, bb, bc, bd, cc, cd, dd] = self.0;
+        aa * x * x
+            + 2.0 * ab * x * y
+            + 2.0 * ac * x * z
+            + 2.0 * ad * x
+            + bb * y * y
+            + 2.0 * bc * y * z
+            + 2.0 * bd * y
+            + cc * z * z
+            + 2.0 * cd * z
+            + dd
+    }
+}
+
+impl PartialEq for EdgeCollapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for EdgeCollapse {}
+
+impl Ord for EdgeCollapse {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed so that the binary heap pops the cheapest collapse first
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.from.cmp(&self.from))
+            .then_with(|| other.to.cmp(&self.to))
+    }
+}
+
+impl PartialOrd for EdgeCollapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl MeshSimplifier {
+    fn new(mesh: &TriangleMesh) -> Self {
+        let n_vertices = mesh.n_vertices();
+
+        let positions: Vec<_> = mesh
+            .positions()
+            .iter()
+            .map(|position| {
+                [
+                    f64::from(position.0.x()),
+                    f64::from(position.0.y()),
+                    f64::from(position.0.z()),
+                ]
+            })
+            .collect();
+
+        let triangles: Vec<[u32; 3]> = mesh
+            .indices()
+            .chunks_exact(3)
+            .map(|indices| [indices[0], indices[1], indices[2]])
+            .filter(|&[i, j, k]| i != j && j != k && k != i)
+            .collect();
+
+        let mut quadrics = vec![Quadric::default(); n_vertices];
+        let mut vertex_triangles = vec![Vec::new(); n_vertices];
+
+        let mut edge_triangle_counts: HashMap<(u32, u32), (usize, usize)> = HashMap::default();
+
+        for (triangle_idx, triangle) in triangles.iter().enumerate() {
+            let normal = triangle_normal(&positions, *triangle);
+            let double_area = length(normal);
+
+            if double_area > 0.0 {
+                let quadric = Quadric::for_plane(
+                    scale(normal, 1.0 / double_area),
+                    positions[triangle[0] as usize],
+                    0.5 * double_area,
+                );
+                for &vertex in triangle {
+                    quadrics[vertex as usize].add(&quadric);
+                }
+            }
+
+            for &vertex in triangle {
+                vertex_triangles[vertex as usize].push(triangle_idx);
+            }
+
+            for (a, b) in triangle_edges(*triangle) {
+                edge_triangle_counts
+                    .entry((a.min(b), a.max(b)))
+                    .or_insert((0, triangle_idx))
+                    .0 += 1;
+            }
+        }
+
+        let mut vertex_is_on_boundary = vec![false; n_vertices];
+
+        for (&(a, b), &(count, triangle_idx)) in &edge_triangle_counts {
+            if count != 1 {
+                continue;
+            }
+            vertex_is_on_boundary[a as usize] = true;
+            vertex_is_on_boundary[b as usize] = true;
+
+            // Constrain the boundary edge with a plane perpendicular to its
+            // triangle
+            let edge = sub(positions[b as usize], positions[a as usize]);
+            let boundary_normal = cross(edge, triangle_normal(&positions, triangles[triangle_idx]));
+            let boundary_normal_length = length(boundary_normal);
+
+            if boundary_normal_length > 0.0 {
+                let quadric = Quadric::for_plane(
+                    scale(boundary_normal, 1.0 / boundary_normal_length),
+                    positions[a as usize],
+                    BOUNDARY_PLANE_WEIGHT * dot(edge, edge),
+                );
+                quadrics[a as usize].add(&quadric);
+                quadrics[b as usize].add(&quadric);
+            }
+        }
+
+        let mut simplifier = Self {
+            positions,
+            quadrics,
+            vertex_versions: vec![0; n_vertices],
+            vertex_is_removed: vec![false; n_vertices],
+            vertex_is_on_boundary,
+            vertex_triangles,
+            n_remaining_triangles: triangles.len(),
+            triangle_is_removed: vec![false; triangles.len()],
+            triangles,
+            candidates: BinaryHeap::new(),
+        };
+
+        for &(a, b) in edge_triangle_counts.keys() {
+            simplifier.push_candidates_for_edge(a, b);
+        }
+
+        simplifier
+    }
+
+    fn simplify(&mut self, target_n_triangles: usize) {
+        while self.n_remaining_triangles > target_n_triangles {
+            let Some(candidate) = self.candidates.pop() else {
+                break;
+            };
+
+            let from = candidate.from as usize;
+            let to = candidate.to as usize;
+
+            if self.vertex_is_removed[from]
+                || self.vertex_is_removed[to]
+                || self.vertex_versions[from] != candidate.from_version
+                || self.vertex_versions[to] != candidate.to_version
+            {
+                // The candidate is outdated
+                continue;
+            }
+
+            if self.collapse_is_valid(candidate.from, candidate.to) {
+                self.collapse(candidate.from, candidate.to);
+            }
+        }
+    }
+
+    fn push_candidates_for_edge(&mut self, a: u32, b: u32) {
+        for (from, to) in [(a, b), (b, a)] {
+            let mut quadric = self.quadrics[from as usize];
+            quadric.add(&self.quadrics[to as usize]);
+
+            self.candidates.push(EdgeCollapse {
+                cost: quadric.evaluate(self.positions[to as usize]),
+                from,
+                to,
+                from_version: self.vertex_versions[from as usize],
+                to_version: self.vertex_versions[to as usize],
+            });
+        }
+    }
+
+    fn collapse_is_valid(&self, from: u32, to: u32) -> bool {
+        let from_triangles = &self.vertex_triangles[from as usize];
+
+        let n_shared_triangles = from_triangles
+            .iter()
+            .filter(|&&triangle_idx| self.triangles[triangle_idx].contains(&to))
+            .count();
+
+        if n_shared_triangles == 0 {
+            return false;
+        }
+
+        let is_boundary_edge = n_shared_triangles == 1;
+
+        // Collapsing an interior edge between two boundary vertices would pinch
+        // the mesh, and moving a boundary vertex into the interior would change
+        // the boundary
+        if !is_boundary_edge && self.vertex_is_on_boundary[from as usize] {
+            return false;
+        }
+
+        // Link condition: the only vertices adjacent to both endpoints must be
+        // the opposite vertices of the triangles sharing the edge
+        let to_neighbors = self.neighbors(to);
+        let n_shared_neighbors = self
+            .neighbors(from)
+            .into_iter()
+            .filter(|neighbor| to_neighbors.contains(neighbor))
+            .count();
+
+        if n_shared_neighbors != n_shared_triangles {
+            return false;
+        }
+
+        // Reject collapses that would flip or degenerate any of the triangles
+        // that are kept
+        let to_position = self.positions[to as usize];
+
+        from_triangles
+            .iter()
+            .map(|&triangle_idx| self.triangles[triangle_idx])
+            .filter(|triangle| !triangle.contains(&to))
+            .all(|triangle| {
+                let old_normal = triangle_normal(&self.positions, triangle);
+
+                let new_positions = triangle.map(|vertex| {
+                    if vertex == from {
+                        to_position
+                    } else {
+                        self.positions[vertex as usize]
+                    }
+                });
+                let new_normal = cross(
+                    sub(new_positions[1], new_positions[0]),
+                    sub(new_positions[2], new_positions[0]),
+                );
+
+                dot(old_normal, new_normal) > 1e-3 * length(old_normal) * length(new_normal)
+            })
+    }
+
+    fn collapse(&mut self, from: u32, to: u32) {
+        let from_triangles = std::mem::take(&mut self.vertex_triangles[from as usize]);
+
+        for triangle_idx in from_triangles {
+            let triangle = &mut self.triangles[triangle_idx];
+
+            if triangle.contains(&to) {
+                self.triangle_is_removed[triangle_idx] = true;
+                self.n_remaining_triangles -= 1;
+
+                for vertex in *triangle {
+                    if vertex != from {
+                        self.vertex_triangles[vertex as usize].retain(|&idx| idx != triangle_idx);
+                    }
+                }
+            } else {
+                for vertex in triangle.iter_mut() {
+                    if *vertex == from {
+                        *vertex = to;
+                    }
+                }
+                self.vertex_triangles[to as usize].push(triangle_idx);
+            }
+        }
+
+        self.vertex_is_removed[from as usize] = true;
+
+        let from_quadric = self.quadrics[from as usize];
+        self.quadrics[to as usize].add(&from_quadric);
+
+        self.vertex_versions[to as usize] += 1;
+
+        for neighbor in self.neighbors(to) {
+            self.push_candidates_for_edge(to, neighbor);
+        }
+    }
+
+    fn neighbors(&self, vertex: u32) -> Vec<u32> {
+        let mut neighbors = Vec::with_capacity(8);
+        for &triangle_idx in &self.vertex_triangles[vertex as usize] {
+            for other in self.triangles[triangle_idx] {
+                if other != vertex && !neighbors.contains(&other) {
+                    neighbors.push(other);
+                }
+            }
+        }
+        neighbors
+    }
+
+    fn create_simplified_mesh(&self, original_mesh: &TriangleMesh) -> TriangleMesh {
+        let mut new_indices_of_original_vertices = vec![u32::MAX; original_mesh.n_vertices()];
+        let mut kept_vertices = Vec::new();
+
+        let mut indices = Vec::with_capacity(3 * self.n_remaining_triangles);
+
+        for (triangle, &is_removed) in self.triangles.iter().zip(&self.triangle_is_removed) {
+            if is_removed {
+                continue;
+            }
+            for &vertex in triangle {
+                let new_index = &mut new_indices_of_original_vertices[vertex as usize];
+                if *new_index == u32::MAX {
+                    *new_index = u32::try_from(kept_vertices.len()).unwrap();
+                    kept_vertices.push(vertex as usize);
+                }
+                indices.push(*new_index);
+            }
+        }
+
+        fn gather<T: Copy>(values: &[T], kept_vertices: &[usize]) -> Vec<T> {
+            if values.is_empty() {
+                Vec::new()
+            } else {
+                kept_vertices.iter().map(|&idx| values[idx]).collect()
+            }
+        }
+
+        let mut mesh = TriangleMesh::new(
+            gather(original_mesh.positions(), &kept_vertices),
+            gather(original_mesh.normal_vectors(), &kept_vertices),
+            gather(original_mesh.texture_coords(), &kept_vertices),
+            gather(original_mesh.tangent_space_quaternions(), &kept_vertices),
+            gather(original_mesh.colors(), &kept_vertices),
+            indices,
+        );
+        mesh.set_skin_weights(
+            gather(original_mesh.skin_weights(), &kept_vertices),
+            &mut TriangleMeshDirtyMask::empty(),
+        );
+        mesh
+    }
+}
+
+fn triangle_edges([i, j, k]: [u32; 3]) -> [(u32, u32); 3] {
+    [(i, j), (j, k), (k, i)]
+}
+
+fn triangle_normal(positions: &[[f64; 3]], [i, j, k]: [u32; 3]) -> [f64; 3] {
+    let p0 = positions[i as usize];
+    cross(
+        sub(positions[j as usize], p0),
+        sub(positions[k as usize], p0),
+    )
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn scale(a: [f64; 3], factor: f64) -> [f64; 3] {
+    [a[0] * factor, a[1] * factor, a[2] * factor]
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn length(a: [f64; 3]) -> f64 {
+    dot(a, a).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge_triangle_counts(mesh: &TriangleMesh) -> HashMap<(u32, u32), usize> {
+        let mut counts: HashMap<(u32, u32), usize> = HashMap::default();
+        for triangle in mesh.indices().chunks_exact(3) {
+            for (a, b) in triangle_edges([triangle[0], triangle[1], triangle[2]]) {
+                *counts.entry((a.min(b), a.max(b))).or_default() += 1;
+            }
+        }
+        counts
+    }
+
+    fn create_welded_sphere(n_rings: usize) -> TriangleMesh {
+        // The generated sphere duplicates some vertices, so we merge them to
+        // get a closed mesh
+        let sphere = TriangleMesh::create_sphere(n_rings);
+
+        let mut positions = Vec::new();
+        let mut new_indices: HashMap<[u32; 3], u32> = HashMap::default();
+        let mut index_map = Vec::with_capacity(sphere.n_vertices());
+
+        for position in sphere.positions() {
+            let key = [position.0.x(), position.0.y(), position.0.z()]
+                .map(|coord| ((coord * 1e4).round() as i32).cast_unsigned());
+            let index = *new_indices.entry(key).or_insert_with(|| {
+                positions.push(*position);
+                u32::try_from(positions.len() - 1).unwrap()
+            });
+            index_map.push(index);
+        }
+
+        let indices: Vec<u32> = sphere
+            .indices()
+            .chunks_exact(3)
+            .map(|triangle| {
+                triangle
+                    .iter()
+                    .map(|&idx| index_map[idx as usize])
+                    .collect::<Vec<_>>()
+            })
+            .filter(|triangle| {
+                triangle[0] != triangle[1]
+                    && triangle[1] != triangle[2]
+                    && triangle[2] != triangle[0]
+            })
+            .flatten()
+            .collect();
+
+        TriangleMesh::new(
+            positions,
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            indices,
+        )
+    }
+
+    #[test]
+    fn simplifying_to_more_triangles_than_present_keeps_mesh() {
+        let mesh = TriangleMesh::create_box(1.0, 1.0, 1.0, crate::FrontFaceSide::Outside);
+        let simplified = mesh.simplified(mesh.n_triangles() + 10);
+        assert_eq!(simplified.n_triangles(), mesh.n_triangles());
+        assert_eq!(simplified.n_vertices(), mesh.n_vertices());
+    }
+
+    #[test]
+    fn simplifying_sphere_reaches_target_triangle_count() {
+        let mesh = create_welded_sphere(16);
+        let target = mesh.n_triangles() / 4;
+        let simplified = mesh.simplified(target);
+        assert!(simplified.n_triangles() <= target);
+        assert!(simplified.n_triangles() > 0);
+    }
+
+    #[test]
+    fn simplified_closed_mesh_stays_closed() {
+        let mesh = create_welded_sphere(12);
+        assert!(
+            edge_triangle_counts(&mesh)
+                .values()
+                .all(|&count| count == 2)
+        );
+
+        let simplified = mesh.simplified(mesh.n_triangles() / 5);
+        assert!(
+            edge_triangle_counts(&simplified)
+                .values()
+                .all(|&count| count == 2)
+        );
+    }
+
+    #[test]
+    fn simplified_sphere_stays_close_to_original_surface() {
+        let mesh = create_welded_sphere(16);
+        let simplified = mesh.simplified(mesh.n_triangles() / 4);
+
+        for position in simplified.positions() {
+            let radius = position.0.as_vector().norm();
+            assert!((radius - 1.0).abs() < 1e-3);
+        }
+        let aabb = simplified.compute_aabb().unwrap();
+        assert!(aabb.lower_corner().x() < -0.9);
+        assert!(aabb.upper_corner().x() > 0.9);
+    }
+
+    #[test]
+    fn simplifying_keeps_vertex_attributes() {
+        let mut mesh = create_welded_sphere(10);
+        let n_vertices = mesh.n_vertices();
+        mesh.generate_smooth_normal_vectors(&mut crate::TriangleMeshDirtyMask::empty());
+        mesh.set_same_color(
+            crate::VertexColor::RED,
+            &mut crate::TriangleMeshDirtyMask::empty(),
+        );
+        assert_eq!(mesh.normal_vectors().len(), n_vertices);
+
+        let simplified = mesh.simplified(mesh.n_triangles() / 3);
+        assert_eq!(simplified.normal_vectors().len(), simplified.n_vertices());
+        assert_eq!(simplified.colors().len(), simplified.n_vertices());
+        assert!(simplified.texture_coords().is_empty());
+    }
+}
@@ -1,8 +1,8 @@
 //! Triangle meshes.
 
 use crate::{
-    VertexColor, VertexNormalVector, VertexPosition, VertexTangentSpaceQuaternion,
-    VertexTextureCoords, texture_projection::TextureProjection,
+    VertexColor, VertexNormalVector, VertexPosition, VertexSkinWeights,
+    VertexTangentSpaceQuaternion, VertexTextureCoords, texture_projection::TextureProjection,
 };
 use approx::{abs_diff_eq, abs_diff_ne};
 use bitflags::bitflags;
@@ -46,6 +46,7 @@ pub struct TriangleMesh {
     texture_coords: Vec<VertexTextureCoords>,
     tangent_space_quaternions: Vec<VertexTangentSpaceQuaternion>,
     colors: Vec<VertexColor>,
+    skin_weights: Vec<VertexSkinWeights>,
     indices: Vec<u32>,
 }
 
@@ -59,6 +60,7 @@ bitflags! {
         const TANGENT_SPACE_QUATERNIONS = 1 << 3;
         const COLORS                    = 1 << 4;
         const INDICES                   = 1 << 5;
+        const SKIN_WEIGHTS              = 1 << 6;
     }
 }
 
@@ -146,6 +148,7 @@ impl TriangleMesh {
             texture_coords,
             tangent_space_quaternions,
             colors,
+            skin_weights: Vec::new(),
             indices,
         }
     }
@@ -190,6 +193,11 @@ impl TriangleMesh {
         &self.colors
     }
 
+    /// Returns a slice with the skin weights of the mesh vertices.
+    pub fn skin_weights(&self) -> &[VertexSkinWeights] {
+        &self.skin_weights
+    }
+
     /// Returns a slice with the vertex indices describing the faces of the
     /// mesh.
     pub fn indices(&self) -> &[u32] {
@@ -226,6 +234,11 @@ impl TriangleMesh {
         !self.colors.is_empty()
     }
 
+    /// Whether the vertices have associated skin weights.
+    pub fn has_skin_weights(&self) -> bool {
+        !self.skin_weights.is_empty()
+    }
+
     /// Returns an iterator over the mesh triangles, each item containing the
     /// three triangle indices.
     pub fn triangle_indices(&self) -> impl Iterator<Item = [usize; 3]> {
@@ -592,6 +605,25 @@ impl TriangleMesh {
         self.set_colors(vec![color; self.positions.len()], dirty_mask);
     }
 
+    /// Assigns the given skin weights to the mesh vertices, binding them to
+    /// the joints of a skeleton.
+    ///
+    /// # Panics
+    /// If the number of skin weights is neither zero nor equal to the number
+    /// of vertices.
+    pub fn set_skin_weights(
+        &mut self,
+        skin_weights: Vec<VertexSkinWeights>,
+        dirty_mask: &mut TriangleMeshDirtyMask,
+    ) {
+        assert!(
+            skin_weights.is_empty() || skin_weights.len() == self.n_vertices(),
+            "Mismatching number of skin weights and positions in triangle mesh"
+        );
+        self.skin_weights = skin_weights;
+        *dirty_mask |= TriangleMeshDirtyMask::SKIN_WEIGHTS;
+    }
+
     /// Merges the given mesh into this mesh.
     ///
     /// # Panics
@@ -631,6 +663,12 @@ impl TriangleMesh {
             *dirty_mask |= TriangleMeshDirtyMask::COLORS;
         }
 
+        if self.has_skin_weights() {
+            assert!(other.has_skin_weights());
+            self.skin_weights.extend_from_slice(&other.skin_weights);
+            *dirty_mask |= TriangleMeshDirtyMask::SKIN_WEIGHTS;
+        }
+
         self.indices.extend_from_slice(&other.indices);
 
         let offset = u32::try_from(original_n_vertices).unwrap();
@@ -0,0 +1,47 @@
+//! Entity IDs of model instances.
+
+use bytemuck::{Pod, Zeroable};
+use impact_gpu::vertex_attribute_ranges::INSTANCE_START;
+use impact_gpu::wgpu;
+use impact_id::EntityID;
+
+/// The ID of the entity a specific model instance belongs to, split into its
+/// lower and upper 32 bits.
+///
+/// This struct is intended to be passed to the GPU in a vertex buffer so that
+/// the entity ID can be written to a render attachment for picking. The order
+/// of the fields is assumed in the shaders.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, Zeroable, Pod)]
+pub struct InstanceEntityID {
+    pub lower: u32,
+    pub upper: u32,
+}
+
+impl InstanceEntityID {
+    /// Returns the binding location of the entity ID in the instance buffer.
+    pub const fn location() -> u32 {
+        INSTANCE_START + 4
+    }
+
+    /// Creates the instance feature for the given entity ID.
+    pub const fn new(entity_id: EntityID) -> Self {
+        let value = entity_id.as_u64();
+        Self {
+            lower: value as u32,
+            upper: (value >> 32) as u32,
+        }
+    }
+
+    /// Returns the entity ID represented by this feature.
+    pub fn entity_id(&self) -> EntityID {
+        EntityID::from_u64((u64::from(self.upper) << 32) | u64::from(self.lower))
+    }
+}
+
+impl_InstanceFeatureForGPU!(
+    InstanceEntityID,
+    wgpu::vertex_attr_array![
+        INSTANCE_START + 4 => Uint32x2,
+    ]
+);
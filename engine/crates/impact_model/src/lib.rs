@@ -3,7 +3,9 @@
 #[macro_use]
 pub mod macros;
 
+pub mod entity;
 pub mod gpu_resource;
+pub mod skinning;
 pub mod transform;
 
 use impact_id::define_entity_id_newtype;
@@ -0,0 +1,38 @@
+//! Joint palettes of skinned model instances.
+
+use bytemuck::{Pod, Zeroable};
+use impact_gpu::vertex_attribute_ranges::INSTANCE_START;
+use impact_gpu::wgpu;
+
+/// The offset of the first joint matrix of a skinned model instance's joint
+/// palette in the buffer holding the joint matrices of all skinned instances.
+///
+/// This struct is intended to be passed to the GPU in a vertex buffer so that
+/// the vertex shader can look up the joint matrices for skinning. An offset of
+/// zero refers to a palette of identity matrices, leaving the instance in its
+/// bind pose.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, Zeroable, Pod)]
+pub struct InstanceJointPaletteOffset {
+    pub offset: u32,
+}
+
+impl InstanceJointPaletteOffset {
+    /// Returns the binding location of the joint palette offset in the
+    /// instance buffer.
+    pub const fn location() -> u32 {
+        INSTANCE_START + 5
+    }
+
+    /// Creates the instance feature for the given palette offset.
+    pub const fn new(offset: u32) -> Self {
+        Self { offset }
+    }
+}
+
+impl_InstanceFeatureForGPU!(
+    InstanceJointPaletteOffset,
+    wgpu::vertex_attr_array![
+        INSTANCE_START + 5 => Uint32,
+    ]
+);
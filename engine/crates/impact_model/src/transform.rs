@@ -1,6 +1,6 @@
 //! Model instance transforms.
 
-use crate::ModelInstanceManager;
+use crate::{ModelInstanceManager, entity::InstanceEntityID, skinning::InstanceJointPaletteOffset};
 use bytemuck::{Pod, Zeroable};
 use impact_gpu::vertex_attribute_ranges::INSTANCE_START;
 use impact_gpu::wgpu;
@@ -223,4 +223,6 @@ pub fn register_model_feature_types<MID: Copy + Eq + Hash>(
     model_instance_manager.register_feature_type::<InstanceModelViewTransform>();
     model_instance_manager.register_feature_type::<InstanceModelViewTransformWithPrevious>();
     model_instance_manager.register_feature_type::<InstanceModelLightTransform>();
+    model_instance_manager.register_feature_type::<InstanceEntityID>();
+    model_instance_manager.register_feature_type::<InstanceJointPaletteOffset>();
 }
@@ -93,6 +93,11 @@ impl ConstantAcceleration {
         Self::downward(Self::EARTH_DOWNWARD_ACCELERATION)
     }
 
+    /// Returns the acceleration vector.
+    pub fn vector(&self) -> &Vector3C {
+        &self.0
+    }
+
     /// Applies the acceleration to the given dynamic rigid body.
     pub fn apply(&self, rigid_body: &mut DynamicRigidBody) {
         let acceleration = self.0.aligned();
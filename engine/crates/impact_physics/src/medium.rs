@@ -1,6 +1,7 @@
 //! Physical media objects can interact with.
 
 use crate::quantities::VelocityC;
+use impact_math::vector::Vector3C;
 use roc_integration::roc;
 
 /// A physical medium with the same properties and state everywhere.
@@ -13,6 +14,30 @@ pub struct UniformMedium {
     pub mass_density: f32,
     /// The velocity of the medium.
     pub velocity: VelocityC,
+    /// How the medium absorbs and scatters light.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub optical_properties: MediumOpticalProperties,
+}
+
+/// Properties determining how light is absorbed and scattered when travelling
+/// through a medium.
+#[roc(parents = "Physics")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MediumOpticalProperties {
+    /// The fraction of the light in a beam that is absorbed or scattered out
+    /// of the beam per distance travelled through the medium [1/m].
+    pub extinction_coefficient: f32,
+    /// The fraction of the extinguished light that is scattered rather than
+    /// absorbed, for each RGB channel. This determines the color of the
+    /// medium.
+    pub scattering_albedo: Vector3C,
+    /// The asymmetry parameter of the Henyey-Greenstein phase function
+    /// describing the directional distribution of scattered light. Ranges
+    /// from -1 (all light is scattered backward) to 1 (all light is scattered
+    /// forward), with 0 corresponding to isotropic scattering.
+    pub anisotropy: f32,
 }
 
 #[roc]
@@ -25,19 +50,30 @@ impl UniformMedium {
     #[roc(expr = "1e3")]
     pub const WATER_MASS_DENSITY: f32 = 1e3;
 
-    /// Creates a new uniform medium with the given mass density and velocity.
-    #[roc(body = "{ mass_density, velocity }")]
-    pub fn new(mass_density: f32, velocity: VelocityC) -> Self {
+    /// Creates a new uniform medium with the given mass density, velocity and
+    /// optical properties.
+    #[roc(body = "{ mass_density, velocity, optical_properties }")]
+    pub fn new(
+        mass_density: f32,
+        velocity: VelocityC,
+        optical_properties: MediumOpticalProperties,
+    ) -> Self {
         Self {
             mass_density,
             velocity,
+            optical_properties,
         }
     }
 
-    /// Creates a new vacuum medium (zero mass density and velocity).
-    #[roc(body = "new(0.0, Vector3.zeros)")]
+    /// Creates a new vacuum medium (zero mass density and velocity, fully
+    /// transparent).
+    #[roc(body = "new(0.0, Vector3.zeros, Physics.MediumOpticalProperties.transparent({}))")]
     pub fn vacuum() -> Self {
-        Self::new(0.0, VelocityC::zeros())
+        Self::new(
+            0.0,
+            VelocityC::zeros(),
+            MediumOpticalProperties::transparent(),
+        )
     }
 
     /// Creates a new medium of Earth air at sea level and room temperature with
@@ -49,9 +85,15 @@ impl UniformMedium {
 
     /// Creates a new medium of Earth air at sea level and room temperature with
     /// the given wind velocity.
-    #[roc(body = "new(sea_level_air_mass_density, velocity)")]
+    #[roc(
+        body = "new(sea_level_air_mass_density, velocity, Physics.MediumOpticalProperties.hazy_air({}))"
+    )]
     pub fn moving_air(velocity: VelocityC) -> Self {
-        Self::new(Self::SEA_LEVEL_AIR_MASS_DENSITY, velocity)
+        Self::new(
+            Self::SEA_LEVEL_AIR_MASS_DENSITY,
+            velocity,
+            MediumOpticalProperties::hazy_air(),
+        )
     }
 
     /// Creates a new medium of water with no flow.
@@ -61,9 +103,15 @@ impl UniformMedium {
     }
 
     /// Creates a new medium of water with the given flow velocity.
-    #[roc(body = "new(water_mass_density, velocity)")]
+    #[roc(
+        body = "new(water_mass_density, velocity, Physics.MediumOpticalProperties.murky_water({}))"
+    )]
     pub fn moving_water(velocity: VelocityC) -> Self {
-        Self::new(Self::WATER_MASS_DENSITY, velocity)
+        Self::new(
+            Self::WATER_MASS_DENSITY,
+            velocity,
+            MediumOpticalProperties::murky_water(),
+        )
     }
 }
 
@@ -72,3 +120,51 @@ impl Default for UniformMedium {
         Self::vacuum()
     }
 }
+
+#[roc]
+impl MediumOpticalProperties {
+    /// Creates a new set of optical properties with the given extinction
+    /// coefficient, scattering albedo and scattering anisotropy.
+    #[roc(body = "{ extinction_coefficient, scattering_albedo, anisotropy }")]
+    pub fn new(extinction_coefficient: f32, scattering_albedo: Vector3C, anisotropy: f32) -> Self {
+        Self {
+            extinction_coefficient,
+            scattering_albedo,
+            anisotropy,
+        }
+    }
+
+    /// Creates optical properties for a medium that does not interact with
+    /// light.
+    #[roc(body = "new(0.0, (1.0, 1.0, 1.0), 0.0)")]
+    pub fn transparent() -> Self {
+        Self::new(0.0, Vector3C::new(1.0, 1.0, 1.0), 0.0)
+    }
+
+    /// Creates optical properties for slightly hazy Earth air, which scatters
+    /// light mostly forward without absorbing it.
+    #[roc(body = "new(1e-4, (1.0, 1.0, 1.0), 0.7)")]
+    pub fn hazy_air() -> Self {
+        Self::new(1e-4, Vector3C::new(1.0, 1.0, 1.0), 0.7)
+    }
+
+    /// Creates optical properties for murky water, which absorbs red light
+    /// more strongly than green and blue light and scatters strongly forward.
+    #[roc(body = "new(0.1, (0.3, 0.7, 0.8), 0.8)")]
+    pub fn murky_water() -> Self {
+        Self::new(0.1, Vector3C::new(0.3, 0.7, 0.8), 0.8)
+    }
+}
+
+impl MediumOpticalProperties {
+    /// Whether the medium has no effect on light travelling through it.
+    pub fn is_transparent(&self) -> bool {
+        self.extinction_coefficient <= 0.0
+    }
+}
+
+impl Default for MediumOpticalProperties {
+    fn default() -> Self {
+        Self::transparent()
+    }
+}
@@ -0,0 +1,267 @@
+const GRID_WIDTH: u32 = {{grid_width}};
+const GRID_HEIGHT: u32 = {{grid_height}};
+const GRID_DEPTH: u32 = {{grid_depth}};
+
+const WORKGROUP_SIZE: u32 = {{workgroup_size}};
+
+const INVERSE_FOUR_PI: f32 = 0.07957747154;
+
+struct PushConstants {
+    activeLightIdx: u32,
+    exposure: f32,
+}
+
+struct ProjectionUniform {
+    projectionMatrix: mat4x4f,
+    frustumFarPlaneCorners: array<vec4f, 4>,
+}
+
+struct Parameters {
+    scatteringCoefficient: vec3f,
+    extinctionCoefficient: f32,
+    anisotropy: f32,
+    maxDistance: f32,
+}
+
+#if (unidirectional)
+struct UnidirectionalLights {
+    numLights: u32,
+    lights: array<UnidirectionalLight, {{max_light_count}}>,
+}
+
+struct UnidirectionalLight {
+    cameraToLightRotationQuaternion: vec4f,
+    cameraSpaceDirection: vec3f,
+    perpendicularIlluminanceAndTanAngularRadius: vec4f,
+    orthographicTransforms: array<OrthographicTransform, {{cascade_count}}>,
+    partitionDepths: vec4f,
+    padding: vec4f,
+}
+
+struct OrthographicTransform {
+    translation: vec3f,
+    scaling: vec3f,
+}
+#else
+struct OmnidirectionalLights {
+    numLights: u32,
+    lights: array<OmnidirectionalLight, {{max_light_count}}>,
+}
+
+struct OmnidirectionalLight {
+    cameraToLightRotationQuaternion: vec4f,
+    cameraSpacePosition: vec3f,
+    luminousIntensityAndEmissiveRadius: vec4f,
+    lightSphere: LightSphere,
+}
+
+struct LightSphere {
+    minShadowShellRadius: f32,
+    inverseShadowShellRadialSpan: f32,
+    maxShadowShellRadius: f32,
+    maxReach: f32,
+}
+#endif
+
+var<push_constant> pushConstants: PushConstants;
+
+@group({{projection_uniform_group}}) @binding({{projection_uniform_binding}})
+var<uniform> projectionUniform: ProjectionUniform;
+
+@group({{params_group}}) @binding({{params_binding}}) var<uniform> params: Parameters;
+@group({{scattering_group}}) @binding({{scattering_binding}})
+var<storage, read_write> scattering: array<vec4f>;
+
+#if (unidirectional)
+@group({{light_uniform_group}}) @binding({{light_uniform_binding}})
+var<uniform> unidirectionalLights: UnidirectionalLights;
+
+@group({{shadow_map_texture_group}}) @binding({{shadow_map_texture_binding}})
+var cascadedShadowMapTexture: texture_2d_array<f32>;
+@group({{shadow_map_texture_group}}) @binding({{shadow_map_sampler_binding}})
+var cascadedShadowMapSampler: sampler;
+#else
+@group({{light_uniform_group}}) @binding({{light_uniform_binding}})
+var<uniform> omnidirectionalLights: OmnidirectionalLights;
+
+@group({{shadow_map_texture_group}}) @binding({{shadow_map_texture_binding}})
+var shadowMapTexture: texture_cube<f32>;
+@group({{shadow_map_texture_group}}) @binding({{shadow_map_sampler_binding}})
+var shadowMapSampler: sampler;
+#endif
+
+fn rotateVectorWithQuaternion(quaternion: vec4f, vector: vec3f) -> vec3f {
+    let tmp = 2.0 * cross(quaternion.xyz, vector);
+    return vector + quaternion.w * tmp + cross(quaternion.xyz, tmp);
+}
+
+// Interpolates between the corners of the far plane of the view frustum to
+// find the point on the far plane corresponding to the given screen texture
+// coordinates
+fn computeFrustumFarPlanePoint(textureCoords: vec2f) -> vec3f {
+    let corners = projectionUniform.frustumFarPlaneCorners;
+    let lowerPoint = mix(corners[0].xyz, corners[1].xyz, textureCoords.x);
+    let upperPoint = mix(corners[3].xyz, corners[2].xyz, textureCoords.x);
+    return mix(upperPoint, lowerPoint, textureCoords.y);
+}
+
+// The depth slices of the froxel grid are distributed quadratically with
+// distance from the camera, giving higher resolution close to the camera
+fn computeSliceDistance(normalizedSliceCoord: f32) -> f32 {
+    return params.maxDistance * normalizedSliceCoord * normalizedSliceCoord;
+}
+
+fn computeFroxelIdx(froxelCoords: vec3u) -> u32 {
+    return (froxelCoords.z * GRID_HEIGHT + froxelCoords.y) * GRID_WIDTH + froxelCoords.x;
+}
+
+// Henyey-Greenstein phase function, where `cosScatteringAngle` is the cosine of
+// the angle between the incoming and outgoing light directions
+fn computeHenyeyGreensteinPhase(cosScatteringAngle: f32, anisotropy: f32) -> f32 {
+    let anisotropySquared = anisotropy * anisotropy;
+    let denom = max(1.0 + anisotropySquared - 2.0 * anisotropy * cosScatteringAngle, 1e-6);
+    return INVERSE_FOUR_PI * (1.0 - anisotropySquared) / (denom * sqrt(denom));
+}
+
+#if (unidirectional)
+fn determineCascadeIdxMax1(partitionDepths: vec4f, depth: f32) -> i32 {
+    return 0;
+}
+
+fn determineCascadeIdxMax2(partitionDepths: vec4f, depth: f32) -> i32 {
+    var cascadeIdx: i32;
+    if depth < partitionDepths.x {
+        cascadeIdx = 0;
+    } else {
+        cascadeIdx = 1;
+    }
+    return cascadeIdx;
+}
+
+fn determineCascadeIdxMax3(partitionDepths: vec4f, depth: f32) -> i32 {
+    var cascadeIdx: i32;
+    if depth < partitionDepths.x {
+        cascadeIdx = 0;
+    } else if depth < partitionDepths.y {
+        cascadeIdx = 1;
+    } else {
+        cascadeIdx = 2;
+    }
+    return cascadeIdx;
+}
+
+fn determineCascadeIdxMax4(partitionDepths: vec4f, depth: f32) -> i32 {
+    var cascadeIdx: i32;
+    if depth < partitionDepths.x {
+        cascadeIdx = 0;
+    } else if depth < partitionDepths.y {
+        cascadeIdx = 1;
+    } else if depth < partitionDepths.z {
+        cascadeIdx = 2;
+    } else {
+        cascadeIdx = 3;
+    }
+    return cascadeIdx;
+}
+
+fn computeLightAccessFactor(cameraSpacePosition: vec3f, normalizedDepth: f32) -> f32 {
+    // Note: `var` is required here instead of `let` to make the
+    // `orthographicTransforms` array indexable with a dynamic index
+    var unidirectionalLight = unidirectionalLights.lights[pushConstants.activeLightIdx];
+
+    let cascadeIdx = determineCascadeIdxMax{{cascade_count}}(unidirectionalLight.partitionDepths, normalizedDepth);
+    let orthographicTransform = unidirectionalLight.orthographicTransforms[cascadeIdx];
+
+    let lightSpacePosition = rotateVectorWithQuaternion(unidirectionalLight.cameraToLightRotationQuaternion, cameraSpacePosition);
+    let lightNDCSpacePosition = (lightSpacePosition + orthographicTransform.translation) * orthographicTransform.scaling;
+
+    let shadowMapTextureCoords = vec2f(0.5 * (1.0 + lightNDCSpacePosition.x), 0.5 * (1.0 - lightNDCSpacePosition.y));
+
+    // Positions outside the region covered by the shadow map are treated as
+    // unoccluded
+    if any(shadowMapTextureCoords != saturate(shadowMapTextureCoords)) || lightNDCSpacePosition.z > 1.0 {
+        return 1.0;
+    }
+
+    let sampledDepth = textureSampleLevel(
+        cascadedShadowMapTexture,
+        cascadedShadowMapSampler,
+        shadowMapTextureCoords,
+        cascadeIdx,
+        0.0,
+    ).r;
+
+    return select(0.0, 1.0, sampledDepth >= lightNDCSpacePosition.z);
+}
+
+fn computePreExposedPhaseWeightedIlluminance(cameraSpacePosition: vec3f, viewDirection: vec3f, normalizedDepth: f32) -> vec3f {
+    let unidirectionalLight = unidirectionalLights.lights[pushConstants.activeLightIdx];
+
+    let lightPerpendicularIlluminance = unidirectionalLight.perpendicularIlluminanceAndTanAngularRadius.xyz;
+
+    // The light travels along its direction and is scattered back towards
+    // the camera
+    let cosScatteringAngle = -dot(unidirectionalLight.cameraSpaceDirection, viewDirection);
+    let phase = computeHenyeyGreensteinPhase(cosScatteringAngle, params.anisotropy);
+
+    let lightAccessFactor = computeLightAccessFactor(cameraSpacePosition, normalizedDepth);
+
+    return (pushConstants.exposure * phase * lightAccessFactor) * lightPerpendicularIlluminance;
+}
+#else
+fn computePreExposedPhaseWeightedIlluminance(cameraSpacePosition: vec3f, viewDirection: vec3f, normalizedDepth: f32) -> vec3f {
+    let omnidirectionalLight = omnidirectionalLights.lights[pushConstants.activeLightIdx];
+
+    let lightDisplacement = cameraSpacePosition - omnidirectionalLight.cameraSpacePosition;
+    let squaredDistance = dot(lightDisplacement, lightDisplacement);
+
+    let maxReach = omnidirectionalLight.lightSphere.maxReach;
+    if squaredDistance > maxReach * maxReach {
+        return vec3f(0.0);
+    }
+
+    let distance = sqrt(squaredDistance);
+    let lightTravelDirection = lightDisplacement / max(distance, 1e-6);
+
+    let cosScatteringAngle = -dot(lightTravelDirection, viewDirection);
+    let phase = computeHenyeyGreensteinPhase(cosScatteringAngle, params.anisotropy);
+
+    let lightSpaceDisplacement = rotateVectorWithQuaternion(omnidirectionalLight.cameraToLightRotationQuaternion, lightDisplacement);
+    let normalizedDepthInShadowShell = (distance - omnidirectionalLight.lightSphere.minShadowShellRadius) * omnidirectionalLight.lightSphere.inverseShadowShellRadialSpan;
+
+    let sampledDepth = textureSampleLevel(shadowMapTexture, shadowMapSampler, lightSpaceDisplacement, 0.0).r;
+    let lightAccessFactor = select(0.0, 1.0, sampledDepth >= normalizedDepthInShadowShell);
+
+    let lightLuminousIntensity = omnidirectionalLight.luminousIntensityAndEmissiveRadius.xyz;
+    let illuminance = lightLuminousIntensity / (squaredDistance + 1e-4);
+
+    return (pushConstants.exposure * phase * lightAccessFactor) * illuminance;
+}
+#endif
+
+// Adds the light scattered towards the camera by each froxel from the active
+// light to the scattering buffer
+@compute @workgroup_size(WORKGROUP_SIZE, WORKGROUP_SIZE, WORKGROUP_SIZE)
+fn main(@builtin(global_invocation_id) globalID: vec3u) {
+    if globalID.x >= GRID_WIDTH || globalID.y >= GRID_HEIGHT || globalID.z >= GRID_DEPTH {
+        return;
+    }
+
+    let textureCoords = (vec2f(globalID.xy) + 0.5) / vec2f(f32(GRID_WIDTH), f32(GRID_HEIGHT));
+    let frustumFarPlanePoint = computeFrustumFarPlanePoint(textureCoords);
+    let farDistance = -frustumFarPlanePoint.z;
+
+    let sliceCenterDistance = computeSliceDistance((f32(globalID.z) + 0.5) / f32(GRID_DEPTH));
+    let cameraSpacePosition = (sliceCenterDistance / farDistance) * frustumFarPlanePoint;
+    let viewDirection = normalize(frustumFarPlanePoint);
+    let normalizedDepth = sliceCenterDistance / farDistance;
+
+    let preExposedPhaseWeightedIlluminance = computePreExposedPhaseWeightedIlluminance(
+        cameraSpacePosition,
+        viewDirection,
+        normalizedDepth,
+    );
+
+    let froxelIdx = computeFroxelIdx(globalID);
+    scattering[froxelIdx] += vec4f(preExposedPhaseWeightedIlluminance, 0.0);
+}
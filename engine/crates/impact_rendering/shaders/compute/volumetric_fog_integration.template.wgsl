@@ -0,0 +1,87 @@
+const GRID_WIDTH: u32 = {{grid_width}};
+const GRID_HEIGHT: u32 = {{grid_height}};
+const GRID_DEPTH: u32 = {{grid_depth}};
+
+const WORKGROUP_SIZE: u32 = {{workgroup_size}};
+
+struct ProjectionUniform {
+    projectionMatrix: mat4x4f,
+    frustumFarPlaneCorners: array<vec4f, 4>,
+}
+
+struct Parameters {
+    scatteringCoefficient: vec3f,
+    extinctionCoefficient: f32,
+    anisotropy: f32,
+    maxDistance: f32,
+}
+
+@group({{projection_uniform_group}}) @binding({{projection_uniform_binding}})
+var<uniform> projectionUniform: ProjectionUniform;
+
+@group({{params_group}}) @binding({{params_binding}}) var<uniform> params: Parameters;
+@group({{scattering_group}}) @binding({{scattering_binding}})
+var<storage, read_write> scattering: array<vec4f>;
+@group({{integrated_scattering_group}}) @binding({{integrated_scattering_binding}})
+var integratedScatteringTexture: texture_storage_3d<rgba16float, write>;
+
+fn computeFrustumFarPlanePoint(textureCoords: vec2f) -> vec3f {
+    let corners = projectionUniform.frustumFarPlaneCorners;
+    let lowerPoint = mix(corners[0].xyz, corners[1].xyz, textureCoords.x);
+    let upperPoint = mix(corners[3].xyz, corners[2].xyz, textureCoords.x);
+    return mix(upperPoint, lowerPoint, textureCoords.y);
+}
+
+fn computeSliceDistance(normalizedSliceCoord: f32) -> f32 {
+    return params.maxDistance * normalizedSliceCoord * normalizedSliceCoord;
+}
+
+fn computeFroxelIdx(froxelCoords: vec3u) -> u32 {
+    return (froxelCoords.z * GRID_HEIGHT + froxelCoords.y) * GRID_WIDTH + froxelCoords.x;
+}
+
+// Marches front-to-back through each column of the froxel grid, accumulating
+// the in-scattered luminance and the transmittance from the camera to the far
+// side of each froxel. The scattering buffer is reset as it is consumed so that
+// it is ready for light injection in the next frame.
+@compute @workgroup_size(WORKGROUP_SIZE, WORKGROUP_SIZE, 1)
+fn main(@builtin(global_invocation_id) globalID: vec3u) {
+    if globalID.x >= GRID_WIDTH || globalID.y >= GRID_HEIGHT {
+        return;
+    }
+
+    let textureCoords = (vec2f(globalID.xy) + 0.5) / vec2f(f32(GRID_WIDTH), f32(GRID_HEIGHT));
+    let frustumFarPlanePoint = computeFrustumFarPlanePoint(textureCoords);
+
+    // The distance travelled along the view ray per unit distance along the
+    // view direction
+    let rayLengthPerDepth = length(frustumFarPlanePoint) / (-frustumFarPlanePoint.z);
+
+    let extinctionCoefficient = max(params.extinctionCoefficient, 1e-7);
+
+    var accumulatedPreExposedLuminance = vec3f(0.0);
+    var transmittance = 1.0;
+
+    for (var sliceIdx: u32 = 0u; sliceIdx < GRID_DEPTH; sliceIdx++) {
+        let froxelCoords = vec3u(globalID.xy, sliceIdx);
+        let froxelIdx = computeFroxelIdx(froxelCoords);
+
+        let nearDistance = computeSliceDistance(f32(sliceIdx) / f32(GRID_DEPTH));
+        let farDistance = computeSliceDistance(f32(sliceIdx + 1u) / f32(GRID_DEPTH));
+        let sliceRayLength = (farDistance - nearDistance) * rayLengthPerDepth;
+
+        let sliceTransmittance = exp(-extinctionCoefficient * sliceRayLength);
+
+        let preExposedSourceLuminance = params.scatteringCoefficient * scattering[froxelIdx].rgb;
+        scattering[froxelIdx] = vec4f(0.0);
+
+        // Integrate the in-scattered luminance analytically over the slice,
+        // accounting for extinction within the slice
+        let integratedSliceLuminance = preExposedSourceLuminance * ((1.0 - sliceTransmittance) / extinctionCoefficient);
+
+        accumulatedPreExposedLuminance += transmittance * integratedSliceLuminance;
+        transmittance *= sliceTransmittance;
+
+        textureStore(integratedScatteringTexture, froxelCoords, vec4f(accumulatedPreExposedLuminance, transmittance));
+    }
+}
@@ -0,0 +1,159 @@
+struct ProjectionUniform {
+    projectionMatrix: mat4x4f,
+    frustumFarPlaneCorners: array<vec4f, 4>,
+}
+
+struct Parameters {
+    apertureDiameter: f32,
+    focalLength: f32,
+    // Non-positive if the focus distance should be determined automatically
+    focusDistance: f32,
+    maxBlurRadius: f32,
+    sampleCount: u32,
+}
+
+struct VertexOutput {
+    @builtin(position) projectedPosition: vec4f,
+}
+
+struct FragmentOutput {
+    @location(0) luminance: vec4f,
+}
+
+const GOLDEN_ANGLE: f32 = 2.39996323;
+
+// The minimum allowed focus distance, in units of the focal length
+const MIN_FOCUS_DISTANCE_FOCAL_LENGTHS: f32 = 2.0;
+
+var<push_constant> inverseWindowDimensions: vec2f;
+
+@group({{projection_uniform_group}}) @binding({{projection_uniform_binding}})
+var<uniform> projectionUniform: ProjectionUniform;
+
+@group({{linear_depth_texture_group}}) @binding({{linear_depth_texture_binding}})
+var linearDepthTexture: texture_2d<f32>;
+@group({{linear_depth_texture_group}}) @binding({{linear_depth_sampler_binding}})
+var linearDepthSampler: sampler;
+
+@group({{luminance_texture_group}}) @binding({{luminance_texture_binding}})
+var luminanceTexture: texture_2d<f32>;
+@group({{luminance_texture_group}}) @binding({{luminance_sampler_binding}})
+var luminanceSampler: sampler;
+
+@group({{params_group}}) @binding({{params_binding}}) var<uniform> params: Parameters;
+
+fn convertFramebufferPositionToPixelIndices(framebufferPosition: vec4f) -> vec2i {
+    return vec2i(trunc(framebufferPosition.xy));
+}
+
+fn convertFramebufferPositionToScreenTextureCoords(framebufferPosition: vec4f) -> vec2f {
+    return (framebufferPosition.xy * inverseWindowDimensions);
+}
+
+fn loadDistance(pixelIndices: vec2i, farDistance: f32) -> f32 {
+    return textureLoad(linearDepthTexture, pixelIndices, 0).r * farDistance;
+}
+
+// Determines the distance to the plane in focus, either from the parameters or
+// by autofocusing on the closest surface in a small region at the center of
+// the view.
+fn determineFocusDistance(textureDims: vec2i, farDistance: f32) -> f32 {
+    var focusDistance = params.focusDistance;
+
+    if (focusDistance <= 0.0) {
+        let centerPixelIndices = textureDims / 2;
+        focusDistance = farDistance;
+        for (var i: i32 = -1; i <= 1; i++) {
+            for (var j: i32 = -1; j <= 1; j++) {
+                let pixelIndices = clamp(centerPixelIndices + 4 * vec2i(i, j), vec2i(0), textureDims - 1);
+                focusDistance = min(focusDistance, loadDistance(pixelIndices, farDistance));
+            }
+        }
+    }
+
+    return max(focusDistance, MIN_FOCUS_DISTANCE_FOCAL_LENGTHS * params.focalLength);
+}
+
+// Computes the radius in pixels of the circle of confusion for a point at the
+// given distance. The diameter of the circle of confusion on the sensor of a
+// thin lens is `A * f * |d - S| / (d * (S - f))`, where `A` is the aperture
+// diameter, `f` the focal length, `S` the focus distance and `d` the distance
+// to the point. The `cocScale` argument is `A * f / (S - f)` converted from
+// a diameter on the sensor to a radius in pixels.
+fn computeBlurRadius(distance: f32, focusDistance: f32, cocScale: f32, maxBlurRadius: f32) -> f32 {
+    return min(cocScale * abs(distance - focusDistance) / max(distance, 1e-6), maxBlurRadius);
+}
+
+@vertex
+fn mainVS(@location({{position_location}}) modelSpacePosition: vec3f) -> VertexOutput {
+    var output: VertexOutput;
+    output.projectedPosition = vec4f(modelSpacePosition, 1.0);
+    return output;
+}
+
+@fragment
+fn mainFS(input: VertexOutput) -> FragmentOutput {
+    var output: FragmentOutput;
+
+    let textureDims = vec2i(textureDimensions(luminanceTexture));
+    let pixelIndices = convertFramebufferPositionToPixelIndices(input.projectedPosition);
+    let textureCoords = convertFramebufferPositionToScreenTextureCoords(input.projectedPosition);
+
+    let farDistance = -projectionUniform.frustumFarPlaneCorners[0].z;
+
+    // The projection matrix element [1][1] is the reciprocal of the tangent of
+    // half the vertical field of view, so the sensor height for the given
+    // focal length is `2 * f * tan(fov / 2)`
+    let tanHalfVerticalFOV = 1.0 / projectionUniform.projectionMatrix[1][1];
+    let sensorHeight = 2.0 * params.focalLength * tanHalfVerticalFOV;
+
+    let focusDistance = determineFocusDistance(textureDims, farDistance);
+
+    let cocScale = 0.5 * f32(textureDims.y) * params.apertureDiameter * params.focalLength
+        / ((focusDistance - params.focalLength) * sensorHeight);
+
+    let maxBlurRadius = params.maxBlurRadius * f32(textureDims.y);
+
+    let centerDistance = loadDistance(pixelIndices, farDistance);
+    let centerBlurRadius = computeBlurRadius(centerDistance, focusDistance, cocScale, maxBlurRadius);
+
+    var luminance = textureLoad(luminanceTexture, pixelIndices, 0).rgb;
+    var totalWeight = 1.0;
+
+    // Gather samples distributed evenly over a disk with the maximum blur
+    // radius. Each sample contributes if its own circle of confusion reaches
+    // the current pixel, so that blurred foreground surfaces spread over
+    // sharper surfaces behind them. To avoid blurred background surfaces
+    // bleeding over sharper foreground surfaces, the blur radius of samples
+    // behind the current pixel is limited by the blur radius of the pixel.
+    let inverseSampleCount = 1.0 / f32(params.sampleCount);
+
+    for (var i: u32 = 0u; i < params.sampleCount; i++) {
+        let sampleRadius = maxBlurRadius * sqrt((f32(i) + 0.5) * inverseSampleCount);
+        let angle = f32(i) * GOLDEN_ANGLE;
+        let offset = sampleRadius * vec2f(cos(angle), sin(angle));
+
+        let samplePixelIndices = clamp(pixelIndices + vec2i(round(offset)), vec2i(0), textureDims - 1);
+        let sampleDistance = loadDistance(samplePixelIndices, farDistance);
+
+        var sampleBlurRadius = computeBlurRadius(sampleDistance, focusDistance, cocScale, maxBlurRadius);
+        if (sampleDistance > centerDistance) {
+            sampleBlurRadius = min(sampleBlurRadius, 2.0 * centerBlurRadius);
+        }
+
+        let weight = smoothstep(sampleRadius - 0.5, sampleRadius + 0.5, sampleBlurRadius);
+
+        let sampleLuminance = textureSampleLevel(
+            luminanceTexture,
+            luminanceSampler,
+            textureCoords + offset * inverseWindowDimensions,
+            0.0,
+        ).rgb;
+
+        luminance += weight * sampleLuminance;
+        totalWeight += weight;
+    }
+
+    output.luminance = vec4f(luminance / totalWeight, 1.0);
+    return output;
+}
@@ -0,0 +1,68 @@
+struct VertexOutput {
+    @builtin(position) projectedPosition: vec4f,
+}
+
+struct FragmentOutput {
+    @location(0) depth: f32,
+}
+
+@group(0) @binding({{input_texture_binding}}) var inputTexture: texture_2d<f32>;
+@group(0) @binding({{input_sampler_binding}}) var inputSampler: sampler;
+
+fn convertFramebufferPositionToPixelIndices(framebufferPosition: vec4f) -> vec2i {
+    return vec2i(trunc(framebufferPosition.xy));
+}
+
+@vertex
+fn mainVS(@location({{position_location}}) modelSpacePosition: vec3f) -> VertexOutput {
+    var output: VertexOutput;
+    output.projectedPosition = vec4f(modelSpacePosition, 1.0);
+    return output;
+}
+
+#if (base_level)
+// Copies the linear depth attachment into the base level of the pyramid. The
+// linear depth of background pixels is zero, which would make every pyramid
+// cell covering background appear to be at the camera, so it is replaced with
+// the far plane depth.
+@fragment
+fn mainFS(input: VertexOutput) -> FragmentOutput {
+    var output: FragmentOutput;
+
+    let pixelIndices = convertFramebufferPositionToPixelIndices(input.projectedPosition);
+    let linearDepth = textureLoad(inputTexture, pixelIndices, 0).r;
+
+    output.depth = select(linearDepth, 1.0, linearDepth <= 0.0);
+    return output;
+}
+#else
+// Computes the minimum depth over the texels in the previous (twice as large)
+// level covered by the output texel. When the previous level has an odd
+// dimension, the last output texel along that dimension also covers the extra
+// row or column of input texels.
+@fragment
+fn mainFS(input: VertexOutput) -> FragmentOutput {
+    var output: FragmentOutput;
+
+    let inputTextureDims = vec2i(textureDimensions(inputTexture));
+    let outputPixelIndices = convertFramebufferPositionToPixelIndices(input.projectedPosition);
+    let inputPixelIndices = 2 * outputPixelIndices;
+
+    let includesExtraColumn = (inputTextureDims.x & 1) == 1 && inputPixelIndices.x + 3 == inputTextureDims.x;
+    let includesExtraRow = (inputTextureDims.y & 1) == 1 && inputPixelIndices.y + 3 == inputTextureDims.y;
+
+    let footprintSize = vec2i(select(2, 3, includesExtraColumn), select(2, 3, includesExtraRow));
+
+    var minDepth = 1.0;
+
+    for (var i: i32 = 0; i < footprintSize.x; i++) {
+        for (var j: i32 = 0; j < footprintSize.y; j++) {
+            let samplePixelIndices = min(inputPixelIndices + vec2i(i, j), inputTextureDims - 1);
+            minDepth = min(minDepth, textureLoad(inputTexture, samplePixelIndices, 0).r);
+        }
+    }
+
+    output.depth = minDepth;
+    return output;
+}
+#endif
@@ -20,6 +20,17 @@ struct PreviousModelViewTransform {
     @location({{previous_model_view_transform_translation_location}}) translationAndScaling: vec4f,
 }
 
+#if (is_skinned)
+struct JointPaletteOffset {
+    @location({{joint_palette_offset_location}}) offset: u32,
+}
+
+struct SkinWeights {
+    @location({{joint_indices_location}}) jointIndices: vec4u,
+    @location({{joint_weights_location}}) jointWeights: vec4f,
+}
+#endif
+
 struct VertexOutput {
     @builtin(position) clipSpacePosition: vec4f,
 }
@@ -31,6 +42,11 @@ var<push_constant> pushConstants: PushConstants;
 @group({{projection_uniform_group}}) @binding({{projection_uniform_binding}})
 var<uniform> projectionUniform: ProjectionUniform;
 
+#if (is_skinned)
+@group({{joint_palette_group}}) @binding({{joint_palette_binding}})
+var<storage, read> jointMatrices: array<mat4x4f>;
+#endif
+
 fn transformPosition(
     rotationQuaternion: vec4f,
     translation: vec3f,
@@ -40,6 +56,18 @@ fn transformPosition(
     return rotateVectorWithQuaternion(rotationQuaternion, scaling * position) + translation;
 }
 
+#if (is_skinned)
+fn skinPosition(paletteOffset: u32, skinWeights: SkinWeights, position: vec3f) -> vec3f {
+    let indices = skinWeights.jointIndices;
+    let weights = skinWeights.jointWeights;
+    let skinningMatrix = weights.x * jointMatrices[paletteOffset + indices.x]
+        + weights.y * jointMatrices[paletteOffset + indices.y]
+        + weights.z * jointMatrices[paletteOffset + indices.z]
+        + weights.w * jointMatrices[paletteOffset + indices.w];
+    return (skinningMatrix * vec4f(position, 1.0)).xyz;
+}
+#endif
+
 fn rotateVectorWithQuaternion(quaternion: vec4<f32>, vector: vec3<f32>) -> vec3<f32> {
     let tmp = 2.0 * cross(quaternion.xyz, vector);
     return vector + quaternion.w * tmp + cross(quaternion.xyz, tmp);
@@ -56,12 +84,22 @@ fn obtainProjectionMatrix() -> mat4x4f {
 
 @vertex
 fn mainVS(
-    @location({{position_location}}) modelSpacePosition: vec3f,
+    @location({{position_location}}) vertexPosition: vec3f,
     modelViewTransform: ModelViewTransform,
     previousModelViewTransform: PreviousModelViewTransform,
+#if (is_skinned)
+    jointPaletteOffset: JointPaletteOffset,
+    skinWeights: SkinWeights,
+#endif
 ) -> VertexOutput {
     var output: VertexOutput;
 
+#if (is_skinned)
+    let modelSpacePosition = skinPosition(jointPaletteOffset.offset, skinWeights, vertexPosition);
+#else
+    let modelSpacePosition = vertexPosition;
+#endif
+
     let projectionMatrix = obtainProjectionMatrix();
 
     let cameraSpacePosition = transformPosition(
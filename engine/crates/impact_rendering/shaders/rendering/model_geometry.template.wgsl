@@ -23,6 +23,25 @@ struct PreviousModelViewTransform {
     @location({{previous_model_view_transform_translation_location}}) translationAndScaling: vec4f,
 }
 
+#if (writes_entity_id)
+struct EntityID {
+    @location({{entity_id_location}}) lowerAndUpper: vec2u,
+}
+#endif
+
+#if (is_skinned)
+struct JointPaletteOffset {
+    @location({{joint_palette_offset_location}}) offset: u32,
+}
+#endif
+
+#if (uses_texture_coord_transform)
+struct TextureCoordTransform {
+    @location({{texture_coord_offset_location}}) offset: vec2f,
+    @location({{texture_coord_scale_location}}) scale: vec2f,
+}
+#endif
+
 struct MaterialProperties {
     @location({{specular_reflectance_location}}) specularReflectance: f32,
     @location({{roughness_location}}) roughness: f32,
@@ -48,6 +67,10 @@ struct VertexInput {
 #if (has_tangent_space_quaternion)
     @location({{tangent_space_quaternion_location}}) tangentToModelSpaceRotationQuaternion: vec4f,
 #endif
+#if (is_skinned)
+    @location({{joint_indices_location}}) jointIndices: vec4u,
+    @location({{joint_weights_location}}) jointWeights: vec4f,
+#endif
 }
 
 struct FragmentInput {
@@ -74,6 +97,9 @@ struct FragmentInput {
     @location(10) parallaxDisplacementScale: f32,
     @location(11) parallaxUVPerDistance: vec2f,
 #endif
+#if (writes_entity_id)
+    @location(12) @interpolate(flat) entityID: vec2u,
+#endif
 }
 
 struct FragmentOutput {
@@ -82,15 +108,28 @@ struct FragmentOutput {
     @location(2) motionVector: vec2f,
     @location(3) materialColor: vec4f,
     @location(4) materialProperties: vec4f,
+#if (writes_entity_id)
+    @location(5) entityID: vec4u,
+#endif
 }
 
 const JITTER_COUNT: u32 = {{jitter_count}};
 
+#if (writes_entity_id)
+// Identifies the kind of entity in the last component of the entity ID output
+const MODEL_INSTANCE_ENTITY_KIND: u32 = 1;
+#endif
+
 var<push_constant> pushConstants: PushConstants;
 
 @group({{projection_uniform_group}}) @binding({{projection_uniform_binding}})
 var<uniform> projectionUniform: ProjectionUniform;
 
+#if (is_skinned)
+@group({{joint_palette_group}}) @binding({{joint_palette_binding}})
+var<storage, read> jointMatrices: array<mat4x4f>;
+#endif
+
 #if (uses_parallax_mapping)
 @group({{material_texture_group}}) @binding({{height_map_texture_binding}})
 var heightMapTexture: texture_2d<f32>;
@@ -171,6 +210,62 @@ fn applyRotationToTangentSpaceQuaternion(
     return rotated;
 }
 
+#if (is_skinned)
+fn computeSkinningMatrix(paletteOffset: u32, jointIndices: vec4u, jointWeights: vec4f) -> mat4x4f {
+    return jointWeights.x * jointMatrices[paletteOffset + jointIndices.x]
+        + jointWeights.y * jointMatrices[paletteOffset + jointIndices.y]
+        + jointWeights.z * jointMatrices[paletteOffset + jointIndices.z]
+        + jointWeights.w * jointMatrices[paletteOffset + jointIndices.w];
+}
+
+fn extractLinearPart(matrix: mat4x4f) -> mat3x3f {
+    return mat3x3f(matrix[0].xyz, matrix[1].xyz, matrix[2].xyz);
+}
+#endif
+
+#if (skins_tangent_space_quaternion)
+fn applySkinningToTangentSpaceQuaternion(
+    skinningLinearPart: mat3x3f,
+    tangentToModelSpaceRotationQuaternion: vec4f,
+) -> vec4f {
+    let q = tangentToModelSpaceRotationQuaternion;
+
+    // Skin the normal and tangent directions and rebuild an orthonormal
+    // tangent frame from them
+    let normal = normalize(skinningLinearPart * rotateVectorWithQuaternion(q, vec3f(0.0, 0.0, 1.0)));
+    let skinnedTangent = skinningLinearPart * rotateVectorWithQuaternion(q, vec3f(1.0, 0.0, 0.0));
+    let tangent = normalize(skinnedTangent - dot(skinnedTangent, normal) * normal);
+    let bitangent = cross(normal, tangent);
+
+    var skinned = convertRotationMatrixToQuaternion(mat3x3f(tangent, bitangent, normal));
+
+    // Preserve encoding of tangent space handedness in real component of
+    // tangent space quaternion
+    if (skinned.w < 0.0) != (q.w < 0.0) {
+        skinned = -skinned;
+    }
+
+    return skinned;
+}
+
+fn convertRotationMatrixToQuaternion(m: mat3x3f) -> vec4f {
+    let trace = m[0][0] + m[1][1] + m[2][2];
+    if trace > 0.0 {
+        let s = 2.0 * sqrt(trace + 1.0);
+        return vec4f((m[1][2] - m[2][1]) / s, (m[2][0] - m[0][2]) / s, (m[0][1] - m[1][0]) / s, 0.25 * s);
+    } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+        let s = 2.0 * sqrt(1.0 + m[0][0] - m[1][1] - m[2][2]);
+        return vec4f(0.25 * s, (m[1][0] + m[0][1]) / s, (m[2][0] + m[0][2]) / s, (m[1][2] - m[2][1]) / s);
+    } else if m[1][1] > m[2][2] {
+        let s = 2.0 * sqrt(1.0 + m[1][1] - m[0][0] - m[2][2]);
+        return vec4f((m[1][0] + m[0][1]) / s, 0.25 * s, (m[2][1] + m[1][2]) / s, (m[2][0] - m[0][2]) / s);
+    } else {
+        let s = 2.0 * sqrt(1.0 + m[2][2] - m[0][0] - m[1][1]);
+        return vec4f((m[2][0] + m[0][2]) / s, (m[2][1] + m[1][2]) / s, 0.25 * s, (m[0][1] - m[1][0]) / s);
+    }
+}
+#endif
+
 fn obtainProjectionMatrix() -> mat4x4f {
     var matrix = projectionUniform.projection;
     let jitterIndex = pushConstants.frameCounter % JITTER_COUNT;
@@ -361,43 +456,86 @@ fn mainVS(
     modelViewTransform: ModelViewTransform,
     previousModelViewTransform: PreviousModelViewTransform,
     material: MaterialProperties,
+#if (writes_entity_id)
+    entityID: EntityID,
+#endif
+#if (is_skinned)
+    jointPaletteOffset: JointPaletteOffset,
+#endif
+#if (uses_texture_coord_transform)
+    textureCoordTransform: TextureCoordTransform,
+#endif
 ) -> FragmentInput {
     var output: FragmentInput;
 
     let projectionMatrix = obtainProjectionMatrix();
 
+    var modelSpacePosition = vertex.modelSpacePosition;
+
+#if (is_skinned)
+    let skinningMatrix = computeSkinningMatrix(
+        jointPaletteOffset.offset,
+        vertex.jointIndices,
+        vertex.jointWeights,
+    );
+    let skinningLinearPart = extractLinearPart(skinningMatrix);
+    modelSpacePosition = (skinningMatrix * vec4f(modelSpacePosition, 1.0)).xyz;
+#endif
+
     let cameraSpacePosition = transformPosition(
         modelViewTransform.rotationQuaternion,
         modelViewTransform.translationAndScaling.xyz,
         modelViewTransform.translationAndScaling.w,
-        vertex.modelSpacePosition,
+        modelSpacePosition,
     );
     output.projectedPosition = projectionMatrix * vec4f(cameraSpacePosition, 1.0);
     output.cameraSpacePosition = cameraSpacePosition;
 
+    // The motion vector does not account for the motion of skinned vertices
+    // relative to the model, since the previous joint matrices are not kept
     let previousCameraSpacePosition = transformPosition(
         previousModelViewTransform.rotationQuaternion,
         previousModelViewTransform.translationAndScaling.xyz,
         previousModelViewTransform.translationAndScaling.w,
-        vertex.modelSpacePosition,
+        modelSpacePosition,
     );
     output.previousClipSpacePosition = projectionMatrix * vec4f(previousCameraSpacePosition, 1.0);
 
+#if (has_normal_vector)
+    var modelSpaceNormalVector = vertex.modelSpaceNormalVector;
+#endif
+#if (skins_normal_vector)
+    // Non-uniform scaling by the joints is not accounted for
+    modelSpaceNormalVector = normalize(skinningLinearPart * modelSpaceNormalVector);
+#endif
 #if (has_normal_vector)
     output.cameraSpaceNormalVector = rotateVectorWithQuaternion(
         modelViewTransform.rotationQuaternion,
-        vertex.modelSpaceNormalVector,
+        modelSpaceNormalVector,
     );
 #endif
 
 #if (has_texture_coords)
     output.textureCoords = vertex.textureCoords;
 #endif
+#if (uses_texture_coord_transform)
+    // Map the texture coordinates to the region of the texture atlas
+    output.textureCoords = textureCoordTransform.offset + textureCoordTransform.scale * output.textureCoords;
+#endif
 
+#if (has_tangent_space_quaternion)
+    var tangentToModelSpaceRotationQuaternion = vertex.tangentToModelSpaceRotationQuaternion;
+#endif
+#if (skins_tangent_space_quaternion)
+    tangentToModelSpaceRotationQuaternion = applySkinningToTangentSpaceQuaternion(
+        skinningLinearPart,
+        tangentToModelSpaceRotationQuaternion,
+    );
+#endif
 #if (has_tangent_space_quaternion)
     output.tangentToCameraSpaceRotationQuaternion = applyRotationToTangentSpaceQuaternion(
         modelViewTransform.rotationQuaternion,
-        vertex.tangentToModelSpaceRotationQuaternion,
+        tangentToModelSpaceRotationQuaternion,
     );
 #endif
 
@@ -413,6 +551,10 @@ fn mainVS(
     output.parallaxUVPerDistance = material.parallaxUVPerDistance;
 #endif
 
+#if (writes_entity_id)
+    output.entityID = entityID.lowerAndUpper;
+#endif
+
     return output;
 }
 
@@ -508,5 +650,9 @@ fn mainFS(fragment: FragmentInput) -> FragmentOutput {
 
     output.materialProperties = vec4f(specularReflectance, roughness, metalness, preExposedEmissiveLuminance);
 
+#if (writes_entity_id)
+    output.entityID = vec4u(fragment.entityID, 0u, MODEL_INSTANCE_ENTITY_KIND << 16u);
+#endif
+
     return output;
 }
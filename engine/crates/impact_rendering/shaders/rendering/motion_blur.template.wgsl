@@ -0,0 +1,120 @@
+struct Parameters {
+    exposedFrameFraction: f32,
+    maxBlurLength: f32,
+    sampleCount: u32,
+}
+
+struct VertexOutput {
+    @builtin(position) projectedPosition: vec4f,
+}
+
+struct FragmentOutput {
+    @location(0) luminance: vec4f,
+}
+
+var<push_constant> inverseWindowDimensions: vec2f;
+
+@group({{linear_depth_texture_group}}) @binding({{linear_depth_texture_binding}})
+var linearDepthTexture: texture_2d<f32>;
+@group({{linear_depth_texture_group}}) @binding({{linear_depth_sampler_binding}})
+var linearDepthSampler: sampler;
+
+@group({{motion_vector_texture_group}}) @binding({{motion_vector_texture_binding}})
+var motionVectorTexture: texture_2d<f32>;
+@group({{motion_vector_texture_group}}) @binding({{motion_vector_sampler_binding}})
+var motionVectorSampler: sampler;
+
+@group({{luminance_texture_group}}) @binding({{luminance_texture_binding}})
+var luminanceTexture: texture_2d<f32>;
+@group({{luminance_texture_group}}) @binding({{luminance_sampler_binding}})
+var luminanceSampler: sampler;
+
+@group({{params_group}}) @binding({{params_binding}}) var<uniform> params: Parameters;
+
+fn convertFramebufferPositionToPixelIndices(framebufferPosition: vec4f) -> vec2i {
+    return vec2i(trunc(framebufferPosition.xy));
+}
+
+fn convertFramebufferPositionToScreenTextureCoords(framebufferPosition: vec4f) -> vec2f {
+    return (framebufferPosition.xy * inverseWindowDimensions);
+}
+
+// Computes the displacement in pixels of the given pixel while the shutter is
+// open, limited to the maximum blur length.
+fn computeBlurVector(pixelIndices: vec2i, textureDims: vec2f, maxBlurLength: f32) -> vec2f {
+    let motionVector = textureLoad(motionVectorTexture, pixelIndices, 0).xy;
+    let blurVector = params.exposedFrameFraction * motionVector * textureDims;
+    let blurLength = length(blurVector);
+    if (blurLength > maxBlurLength) {
+        return blurVector * (maxBlurLength / blurLength);
+    } else {
+        return blurVector;
+    }
+}
+
+@vertex
+fn mainVS(@location({{position_location}}) modelSpacePosition: vec3f) -> VertexOutput {
+    var output: VertexOutput;
+    output.projectedPosition = vec4f(modelSpacePosition, 1.0);
+    return output;
+}
+
+@fragment
+fn mainFS(input: VertexOutput) -> FragmentOutput {
+    var output: FragmentOutput;
+
+    let textureDims = vec2i(textureDimensions(luminanceTexture));
+    let pixelIndices = convertFramebufferPositionToPixelIndices(input.projectedPosition);
+    let textureCoords = convertFramebufferPositionToScreenTextureCoords(input.projectedPosition);
+
+    let maxBlurLength = params.maxBlurLength * f32(textureDims.y);
+
+    let blurVector = computeBlurVector(pixelIndices, vec2f(textureDims), maxBlurLength);
+    let blurLength = length(blurVector);
+
+    let centerLuminance = textureLoad(luminanceTexture, pixelIndices, 0).rgb;
+
+    // Skip blurring if the pixel moves less than half a pixel
+    if (blurLength < 0.5) {
+        output.luminance = vec4f(centerLuminance, 1.0);
+        return output;
+    }
+
+    let centerDepth = textureLoad(linearDepthTexture, pixelIndices, 0).r;
+
+    var luminance = centerLuminance;
+    var totalWeight = 1.0;
+
+    // Gather samples along the path traversed by the pixel while the shutter
+    // was open, centered on the current position. Samples in front of the
+    // pixel are weighted by how much they moved relative to the pixel, so that
+    // slower foreground surfaces are not smeared over the pixel.
+    let inverseSampleCount = 1.0 / f32(params.sampleCount);
+
+    for (var i: u32 = 0u; i < params.sampleCount; i++) {
+        let t = (f32(i) + 0.5) * inverseSampleCount - 0.5;
+        let offset = t * blurVector;
+
+        let samplePixelIndices = clamp(pixelIndices + vec2i(round(offset)), vec2i(0), textureDims - 1);
+        let sampleDepth = textureLoad(linearDepthTexture, samplePixelIndices, 0).r;
+
+        var weight = 1.0;
+        if (sampleDepth < centerDepth) {
+            let sampleBlurLength = length(computeBlurVector(samplePixelIndices, vec2f(textureDims), maxBlurLength));
+            weight = saturate(sampleBlurLength / blurLength);
+        }
+
+        let sampleLuminance = textureSampleLevel(
+            luminanceTexture,
+            luminanceSampler,
+            textureCoords + offset * inverseWindowDimensions,
+            0.0,
+        ).rgb;
+
+        luminance += weight * sampleLuminance;
+        totalWeight += weight;
+    }
+
+    output.luminance = vec4f(luminance / totalWeight, 1.0);
+    return output;
+}
@@ -0,0 +1,145 @@
+struct ProjectionUniform {
+    projectionMatrix: mat4x4f,
+    frustumFarPlaneCorners: array<vec4f, 4>,
+}
+
+struct Parameters {
+    maxStepCount: u32,
+    maxDistance: f32,
+    thickness: f32,
+    roughnessCutoff: f32,
+    currentFrameWeight: f32,
+    varianceClippingThreshold: f32,
+}
+
+struct VertexOutput {
+    @builtin(position) projectedPosition: vec4f,
+    @location(0) frustumFarPlanePoint: vec3f,
+}
+
+struct FragmentOutput {
+    @location(0) luminance: vec4f,
+}
+
+var<push_constant> inverseWindowDimensions: vec2f;
+
+@group({{projection_uniform_group}}) @binding({{projection_uniform_binding}})
+var<uniform> projectionUniform: ProjectionUniform;
+
+@group({{linear_depth_texture_group}}) @binding({{linear_depth_texture_binding}})
+var linearDepthTexture: texture_2d<f32>;
+@group({{linear_depth_texture_group}}) @binding({{linear_depth_sampler_binding}})
+var linearDepthSampler: sampler;
+
+@group({{normal_vector_texture_group}}) @binding({{normal_vector_texture_binding}})
+var normalVectorTexture: texture_2d<f32>;
+@group({{normal_vector_texture_group}}) @binding({{normal_vector_sampler_binding}})
+var normalVectorSampler: sampler;
+
+@group({{material_color_texture_group}}) @binding({{material_color_texture_binding}})
+var materialColorTexture: texture_2d<f32>;
+@group({{material_color_texture_group}}) @binding({{material_color_sampler_binding}})
+var materialColorSampler: sampler;
+
+@group({{material_properties_texture_group}}) @binding({{material_properties_texture_binding}})
+var materialPropertiesTexture: texture_2d<f32>;
+@group({{material_properties_texture_group}}) @binding({{material_properties_sampler_binding}})
+var materialPropertiesSampler: sampler;
+
+@group({{reflected_luminance_texture_group}}) @binding({{reflected_luminance_texture_binding}})
+var reflectedLuminanceTexture: texture_2d<f32>;
+@group({{reflected_luminance_texture_group}}) @binding({{reflected_luminance_sampler_binding}})
+var reflectedLuminanceSampler: sampler;
+
+@group({{params_group}}) @binding({{params_binding}}) var<uniform> params: Parameters;
+
+fn convertFramebufferPositionToScreenTextureCoords(framebufferPosition: vec4f) -> vec2f {
+    return (framebufferPosition.xy * inverseWindowDimensions);
+}
+
+fn computePositionFromLinearDepth(linearDepth: f32, frustumFarPlanePoint: vec3f) -> vec3f {
+    return linearDepth * frustumFarPlanePoint;
+}
+
+// From [0, 1] to [-1, 1]
+fn convertNormalColorToNormalizedNormalVector(color: vec3f) -> vec3f {
+    return normalize(convertNormalColorToNormalVector(color));
+}
+
+// From [0, 1] to [-1, 1]
+fn convertNormalColorToNormalVector(color: vec3f) -> vec3f {
+    // May require normalization depending on filtering
+    return 2.0 * (color - 0.5);
+}
+
+fn computeFresnelReflectanceIncidenceFactor(cosineOfIncidenceAngle: f32) -> f32 {
+    let oneMinusCosine = 1.0 - cosineOfIncidenceAngle;
+    let oneMinusCosineSquared = oneMinusCosine * oneMinusCosine;
+    return oneMinusCosineSquared * oneMinusCosineSquared * oneMinusCosine;
+}
+
+// Schlick's approximation of the Fresnel reflectance, with an additional
+// reduction of the grazing angle reflectance for rough surfaces
+fn computeFresnelReflectance(
+    normalIncidenceReflectance: vec3f,
+    roughness: f32,
+    cosineOfIncidenceAngle: f32,
+) -> vec3f {
+    let grazingReflectance = max(vec3f(1.0 - roughness), normalIncidenceReflectance);
+    return normalIncidenceReflectance
+        + (grazingReflectance - normalIncidenceReflectance) * computeFresnelReflectanceIncidenceFactor(cosineOfIncidenceAngle);
+}
+
+// Fades reflections out smoothly as the roughness approaches the cutoff, since
+// a single mirror-direction ray is a poor approximation for rough surfaces
+fn computeRoughnessFade(roughness: f32) -> f32 {
+    return 1.0 - smoothstep(0.5 * params.roughnessCutoff, params.roughnessCutoff, roughness);
+}
+
+@vertex
+fn mainVS(
+    @builtin(vertex_index) vertexIndex: u32,
+    @location({{position_location}}) modelSpacePosition: vec3f
+) -> VertexOutput {
+    var output: VertexOutput;
+    output.projectedPosition = vec4f(modelSpacePosition, 1.0);
+    output.frustumFarPlanePoint = projectionUniform.frustumFarPlaneCorners[vertexIndex].xyz;
+    return output;
+}
+
+@fragment
+fn mainFS(input: VertexOutput) -> FragmentOutput {
+    var output: FragmentOutput;
+
+    let textureCoords = convertFramebufferPositionToScreenTextureCoords(input.projectedPosition);
+
+    let materialProperties = textureSampleLevel(materialPropertiesTexture, materialPropertiesSampler, textureCoords, 0.0);
+    let specularReflectance = materialProperties.x;
+    let roughness = materialProperties.y;
+    let metalness = materialProperties.z;
+
+    if (roughness >= params.roughnessCutoff) {
+        output.luminance = vec4f(0.0);
+        return output;
+    }
+
+    let depth = textureSampleLevel(linearDepthTexture, linearDepthSampler, textureCoords, 0.0).r;
+    let position = computePositionFromLinearDepth(depth, input.frustumFarPlanePoint);
+    let viewDirection = normalize(-position);
+
+    let normalColor = textureSampleLevel(normalVectorTexture, normalVectorSampler, textureCoords, 0.0).rgb;
+    let normalVector = convertNormalColorToNormalizedNormalVector(normalColor);
+
+    let materialColor = textureSampleLevel(materialColorTexture, materialColorSampler, textureCoords, 0.0).rgb;
+
+    let normalIncidenceReflectance = mix(vec3f(specularReflectance), materialColor * specularReflectance, metalness);
+
+    let cosineOfIncidenceAngle = saturate(dot(normalVector, viewDirection));
+
+    let fresnelReflectance = computeFresnelReflectance(normalIncidenceReflectance, roughness, cosineOfIncidenceAngle);
+
+    let reflectedLuminance = textureSampleLevel(reflectedLuminanceTexture, reflectedLuminanceSampler, textureCoords, 0.0).rgb;
+
+    output.luminance = vec4f(computeRoughnessFade(roughness) * fresnelReflectance * reflectedLuminance, 1.0);
+    return output;
+}
@@ -0,0 +1,140 @@
+struct VertexOutput {
+    @builtin(position) projectedPosition: vec4f,
+}
+
+struct FragmentOutput {
+    @location(0) reflectedLuminance: vec4f,
+}
+
+struct Parameters {
+    maxStepCount: u32,
+    maxDistance: f32,
+    thickness: f32,
+    roughnessCutoff: f32,
+    currentFrameWeight: f32,
+    varianceClippingThreshold: f32,
+}
+
+var<push_constant> inverseWindowDimensions: vec2f;
+
+@group({{motion_vector_texture_group}}) @binding({{motion_vector_texture_binding}})
+var motionVectorTexture: texture_2d<f32>;
+@group({{motion_vector_texture_group}}) @binding({{motion_vector_sampler_binding}})
+var motionVectorSampler: sampler;
+
+@group({{reflected_luminance_texture_group}}) @binding({{reflected_luminance_texture_binding}})
+var reflectedLuminanceTexture: texture_2d<f32>;
+@group({{reflected_luminance_texture_group}}) @binding({{reflected_luminance_sampler_binding}})
+var reflectedLuminanceSampler: sampler;
+
+@group({{previous_reflected_luminance_texture_group}}) @binding({{previous_reflected_luminance_texture_binding}})
+var previousReflectedLuminanceTexture: texture_2d<f32>;
+@group({{previous_reflected_luminance_texture_group}}) @binding({{previous_reflected_luminance_sampler_binding}})
+var previousReflectedLuminanceSampler: sampler;
+
+@group({{params_group}}) @binding({{params_binding}}) var<uniform> params: Parameters;
+
+const ONE_OVER_NEIGHBORHOOD_SAMPLE_COUNT: f32 = 1.0 / 9.0;
+
+const SRGB_TO_LUMINANCE: vec3f = vec3f(0.2125, 0.7154, 0.0721);
+
+fn convertFramebufferPositionToPixelIndices(framebufferPosition: vec4f) -> vec2i {
+    return vec2i(trunc(framebufferPosition.xy));
+}
+
+fn convertFramebufferPositionToScreenTextureCoords(framebufferPosition: vec4f) -> vec2f {
+    return (framebufferPosition.xy * inverseWindowDimensions);
+}
+
+// Finds the intersection point between the given AABB and the ray from the AABB
+// center to the given point. Returns the point itself if it is inside the AABB.
+fn clipWithAABBTowardCenter(minCorner: vec3f, maxCorner: vec3f, point: vec3f) -> vec3f {
+    let center = 0.5 * (maxCorner + minCorner);
+    let halfWidths = 0.5 * (maxCorner - minCorner) + 1e-6;
+
+    let displacement = point - center;
+    let relativeDisplacement = abs(displacement / halfWidths);
+    let maxRelativeDisplacement = max(relativeDisplacement.x, max(relativeDisplacement.y, relativeDisplacement.z));
+
+    if (maxRelativeDisplacement > 1.0) {
+        return center + displacement / maxRelativeDisplacement;
+    } else {
+        return point;
+    }
+}
+
+fn computeScalarLuminanceFromColor(color: vec3f) -> f32 {
+    return dot(SRGB_TO_LUMINANCE, color);
+}
+
+@vertex
+fn mainVS(@location({{position_location}}) modelSpacePosition: vec3f) -> VertexOutput {
+    var output: VertexOutput;
+    output.projectedPosition = vec4f(modelSpacePosition, 1.0);
+    return output;
+}
+
+// Blends the reflected luminance traced this frame with the accumulated
+// reflected luminance from the previous frames. The history is reprojected with
+// the motion of the reflecting surface, which is exact for reflections of
+// static scenes seen by a translating camera and a reasonable approximation
+// otherwise. To suppress ghosting where the approximation breaks down, the
+// reprojected history is clipped to the color distribution of the current
+// reflections in the 3x3 neighborhood.
+@fragment
+fn mainFS(input: VertexOutput) -> FragmentOutput {
+    var output: FragmentOutput;
+
+    let textureDims = vec2i(textureDimensions(reflectedLuminanceTexture));
+    let pixelIndices = convertFramebufferPositionToPixelIndices(input.projectedPosition);
+
+    let reflectedLuminance = textureLoad(reflectedLuminanceTexture, pixelIndices, 0).rgb;
+
+    var luminanceFirstMoment = vec3f(0.0);
+    var luminanceSecondMoment = vec3f(0.0);
+
+    for (var i: i32 = -1; i <= 1; i++) {
+        for (var j: i32 = -1; j <= 1; j++) {
+            let neighborPixelIndices = clamp(pixelIndices + vec2i(i, j), vec2i(0), textureDims - 1);
+            let neighborLuminance = textureLoad(reflectedLuminanceTexture, neighborPixelIndices, 0).rgb;
+
+            luminanceFirstMoment += neighborLuminance;
+            luminanceSecondMoment += neighborLuminance * neighborLuminance;
+        }
+    }
+
+    let motionVector = textureLoad(motionVectorTexture, pixelIndices, 0).xy;
+
+    let textureCoords = convertFramebufferPositionToScreenTextureCoords(input.projectedPosition);
+    let previousTextureCoords = textureCoords - motionVector;
+
+    // Without valid history we simply use the reflections from this frame
+    if (any(previousTextureCoords != saturate(previousTextureCoords))) {
+        output.reflectedLuminance = vec4f(reflectedLuminance, 1.0);
+        return output;
+    }
+
+    var previousReflectedLuminance = textureSampleLevel(
+        previousReflectedLuminanceTexture,
+        previousReflectedLuminanceSampler,
+        previousTextureCoords,
+        0.0,
+    ).rgb;
+
+    let luminanceMean = luminanceFirstMoment * ONE_OVER_NEIGHBORHOOD_SAMPLE_COUNT;
+    let luminanceStdDev = sqrt(abs(luminanceSecondMoment * ONE_OVER_NEIGHBORHOOD_SAMPLE_COUNT - luminanceMean * luminanceMean));
+    let lowerClippingLuminance = luminanceMean - params.varianceClippingThreshold * luminanceStdDev;
+    let upperClippingLuminance = luminanceMean + params.varianceClippingThreshold * luminanceStdDev;
+    previousReflectedLuminance = clipWithAABBTowardCenter(lowerClippingLuminance, upperClippingLuminance, previousReflectedLuminance);
+
+    // Weight the frames with the inverse of their tone mapped luminance so that
+    // single bright outliers do not dominate the accumulated result
+    let previousFrameWeight = (1.0 - params.currentFrameWeight) / (1.0 + computeScalarLuminanceFromColor(previousReflectedLuminance));
+    let currentFrameWeight = params.currentFrameWeight / (1.0 + computeScalarLuminanceFromColor(reflectedLuminance));
+    let inverseWeightSum = 1.0 / max(previousFrameWeight + currentFrameWeight, 1e-6);
+
+    let blendedLuminance = (previousFrameWeight * previousReflectedLuminance + currentFrameWeight * reflectedLuminance) * inverseWeightSum;
+
+    output.reflectedLuminance = vec4f(blendedLuminance, 1.0);
+    return output;
+}
@@ -0,0 +1,393 @@
+struct PushConstants {
+    cameraRotationQuaternion: vec4f,
+    inverseWindowDimensions: vec2f,
+    frameCounter: u32,
+    exposure: f32,
+}
+
+struct ProjectionUniform {
+    projectionMatrix: mat4x4f,
+    frustumFarPlaneCorners: array<vec4f, 4>,
+}
+
+struct Parameters {
+    maxStepCount: u32,
+    maxDistance: f32,
+    thickness: f32,
+    roughnessCutoff: f32,
+    currentFrameWeight: f32,
+    varianceClippingThreshold: f32,
+}
+
+#if (uses_skybox)
+struct SkyboxProperties {
+    maxLuminance: f32,
+}
+#endif
+
+struct VertexOutput {
+    @builtin(position) projectedPosition: vec4f,
+    @location(0) frustumFarPlanePoint: vec3f,
+}
+
+struct FragmentOutput {
+    @location(0) preExposedReflectedLuminance: vec4f,
+}
+
+struct RayHit {
+    textureCoords: vec2f,
+    confidence: f32,
+}
+
+// A ray in screen space, parameterized by `t` going from 0 at the ray origin to
+// 1 at the ray end point. Since the inverse of the depth (rather than the depth
+// itself) varies linearly across the screen under perspective projection, the
+// ray is represented by its texture coordinates and inverse linear depth.
+struct ScreenSpaceRay {
+    startTextureCoords: vec2f,
+    textureCoordsDelta: vec2f,
+    startInverseDepth: f32,
+    inverseDepthDelta: f32,
+}
+
+var<push_constant> pushConstants: PushConstants;
+
+@group({{projection_uniform_group}}) @binding({{projection_uniform_binding}})
+var<uniform> projectionUniform: ProjectionUniform;
+
+@group({{normal_vector_texture_group}}) @binding({{normal_vector_texture_binding}})
+var normalVectorTexture: texture_2d<f32>;
+@group({{normal_vector_texture_group}}) @binding({{normal_vector_sampler_binding}})
+var normalVectorSampler: sampler;
+
+@group({{motion_vector_texture_group}}) @binding({{motion_vector_texture_binding}})
+var motionVectorTexture: texture_2d<f32>;
+@group({{motion_vector_texture_group}}) @binding({{motion_vector_sampler_binding}})
+var motionVectorSampler: sampler;
+
+@group({{previous_luminance_texture_group}}) @binding({{previous_luminance_texture_binding}})
+var previousLuminanceTexture: texture_2d<f32>;
+@group({{previous_luminance_texture_group}}) @binding({{previous_luminance_sampler_binding}})
+var previousLuminanceSampler: sampler;
+
+// Pyramid of minimum linear depths, with the linear depth attachment as the
+// base level
+@group({{hierarchical_depth_texture_group}}) @binding({{hierarchical_depth_texture_binding}})
+var hierarchicalDepthTexture: texture_2d<f32>;
+@group({{hierarchical_depth_texture_group}}) @binding({{hierarchical_depth_sampler_binding}})
+var hierarchicalDepthSampler: sampler;
+
+@group({{params_group}}) @binding({{params_binding}}) var<uniform> params: Parameters;
+
+#if (uses_skybox)
+@group({{skybox_properties_group}}) @binding({{skybox_properties_binding}})
+var<uniform> skyboxProperties: SkyboxProperties;
+
+@group({{skybox_texture_group}}) @binding({{skybox_texture_binding}})
+var skyboxTexture: texture_cube<f32>;
+@group({{skybox_texture_group}}) @binding({{skybox_sampler_binding}})
+var skyboxSampler: sampler;
+#endif
+
+// Ray parameter used to represent a point that the ray never reaches
+const UNREACHABLE_RAY_PARAMETER: f32 = 1e30;
+
+// Fraction of the ray parameter length of a base level texel to advance the ray
+// by after moving it to the depth of the closest surface in a cell, so that it
+// does not get stuck there due to rounding errors
+const DEPTH_PLANE_CROSSING_FRACTION: f32 = 1e-3;
+
+// Camera space z-coordinate that the ray end point is not allowed to exceed,
+// since the projection of points at or behind the camera is undefined
+const MAX_CAMERA_SPACE_RAY_Z: f32 = -1e-3;
+
+// Width of the region near the edges of the screen where reflections are faded
+// out, in texture coordinates
+const SCREEN_EDGE_FADE_WIDTH: f32 = 0.1;
+
+fn convertFramebufferPositionToScreenTextureCoords(framebufferPosition: vec4f) -> vec2f {
+    return (framebufferPosition.xy * pushConstants.inverseWindowDimensions);
+}
+
+fn computePositionFromLinearDepth(linearDepth: f32, frustumFarPlanePoint: vec3f) -> vec3f {
+    return linearDepth * frustumFarPlanePoint;
+}
+
+// From [0, 1] to [-1, 1]
+fn convertNormalColorToNormalizedNormalVector(color: vec3f) -> vec3f {
+    return normalize(convertNormalColorToNormalVector(color));
+}
+
+// From [0, 1] to [-1, 1]
+fn convertNormalColorToNormalVector(color: vec3f) -> vec3f {
+    // May require normalization depending on filtering
+    return 2.0 * (color - 0.5);
+}
+
+fn rotateVectorWithQuaternion(quaternion: vec4f, vector: vec3f) -> vec3f {
+    let tmp = 2.0 * cross(quaternion.xyz, vector);
+    return vector + quaternion.w * tmp + cross(quaternion.xyz, tmp);
+}
+
+// Returns a random number between 0 and 1 based on the pixel coordinates and
+// the current frame counter
+fn generateInterleavedGradientNoiseFactor(cameraFramebufferPosition: vec4f) -> f32 {
+    let timeOffset = pushConstants.frameCounter % 8u;
+    let timeOffsetPosition = vec2f(cameraFramebufferPosition.x + f32(timeOffset), cameraFramebufferPosition.y);
+    let magic = vec3f(0.06711056, 0.00583715, 52.9829189);
+    return fract(magic.z * fract(dot(magic.xy, timeOffsetPosition)));
+}
+
+fn computeTextureCoordsForCameraSpacePosition(cameraSpacePosition: vec3f) -> vec2f {
+    let undividedClipSpacePosition = projectionUniform.projectionMatrix * vec4f(cameraSpacePosition, 1.0);
+    let horizontalClipSpacePosition = undividedClipSpacePosition.xy / max(undividedClipSpacePosition.w, 1e-6);
+    var textureCoords = 0.5 * (horizontalClipSpacePosition + 1.0);
+    textureCoords.y = 1.0 - textureCoords.y;
+    return textureCoords;
+}
+
+fn computeScreenEdgeFade(textureCoords: vec2f) -> f32 {
+    let distanceToEdge = min(textureCoords, 1.0 - textureCoords);
+    let fade = saturate(distanceToEdge / SCREEN_EDGE_FADE_WIDTH);
+    return fade.x * fade.y;
+}
+
+fn computeScreenSpaceRay(origin: vec3f, end: vec3f, farDistance: f32) -> ScreenSpaceRay {
+    var ray: ScreenSpaceRay;
+
+    let startTextureCoords = computeTextureCoordsForCameraSpacePosition(origin);
+    let endTextureCoords = computeTextureCoordsForCameraSpacePosition(end);
+
+    ray.startTextureCoords = startTextureCoords;
+    ray.textureCoordsDelta = endTextureCoords - startTextureCoords;
+
+    ray.startInverseDepth = -farDistance / origin.z;
+    ray.inverseDepthDelta = -farDistance / end.z - ray.startInverseDepth;
+
+    return ray;
+}
+
+fn computeRayTextureCoords(ray: ScreenSpaceRay, t: f32) -> vec2f {
+    return ray.startTextureCoords + t * ray.textureCoordsDelta;
+}
+
+fn computeRayLinearDepth(ray: ScreenSpaceRay, t: f32) -> f32 {
+    return 1.0 / (ray.startInverseDepth + t * ray.inverseDepthDelta);
+}
+
+// Returns the ray parameter where the ray leaves the screen, or 1 if the ray
+// ends on screen
+fn computeMaxOnScreenRayParameter(ray: ScreenSpaceRay) -> f32 {
+    let safeDelta = select(vec2f(1e-9), ray.textureCoordsDelta, abs(ray.textureCoordsDelta) > vec2f(1e-9));
+    let boundary = select(vec2f(0.0), vec2f(1.0), ray.textureCoordsDelta > vec2f(0.0));
+    let tBoundary = select(
+        vec2f(UNREACHABLE_RAY_PARAMETER),
+        (boundary - ray.startTextureCoords) / safeDelta,
+        abs(ray.textureCoordsDelta) > vec2f(1e-9),
+    );
+    return min(1.0, min(tBoundary.x, tBoundary.y));
+}
+
+// Returns the ray parameter where the ray crosses into the next cell after the
+// given cell at a level of the depth pyramid with the given dimensions. The
+// crossing offset moves the crossing point slightly into the next cell so that
+// it is not assigned back to the given cell due to rounding.
+fn computeCellExitRayParameter(
+    ray: ScreenSpaceRay,
+    cell: vec2i,
+    levelDims: vec2f,
+    crossingOffset: vec2f,
+) -> f32 {
+    let isIncreasing = ray.textureCoordsDelta > vec2f(0.0);
+    let boundary = (vec2f(cell) + select(vec2f(0.0), vec2f(1.0), isIncreasing)) / levelDims + crossingOffset;
+
+    let hasExtent = abs(ray.textureCoordsDelta) > vec2f(1e-9);
+    let safeDelta = select(vec2f(1e-9), ray.textureCoordsDelta, hasExtent);
+    let tBoundary = select(
+        vec2f(UNREACHABLE_RAY_PARAMETER),
+        (boundary - ray.startTextureCoords) / safeDelta,
+        hasExtent,
+    );
+    return min(tBoundary.x, tBoundary.y);
+}
+
+// Returns the ray parameter where the ray reaches the given linear depth while
+// moving away from the camera, or a value that is never reached if the ray
+// does not move away from the camera
+fn computeDepthPlaneRayParameter(ray: ScreenSpaceRay, linearDepth: f32) -> f32 {
+    if (ray.inverseDepthDelta >= 0.0) {
+        return UNREACHABLE_RAY_PARAMETER;
+    }
+    return (1.0 / linearDepth - ray.startInverseDepth) / ray.inverseDepthDelta;
+}
+
+// Traverses the hierarchical depth pyramid along the screen space ray to find
+// the first intersection with the depth buffer surface.
+//
+// The traversal starts at the base level. As long as the ray stays in front of
+// the closest surface in its current cell, it skips to the boundary of the cell
+// and moves up a level, so that empty regions are crossed in large steps. When
+// the ray reaches the depth of the closest surface within the cell, it moves
+// down a level to resolve the intersection in finer detail. An intersection is
+// found when the ray is behind the surface at the base level by no more than
+// the thickness.
+//
+// The starting point is jittered by up to one base level texel per pixel and
+// frame, which breaks up the stair-stepping of the hit points into noise that
+// is resolved by the temporal accumulation of the reflections.
+fn traceReflectedRay(ray: ScreenSpaceRay, rayLength: f32, farDistance: f32, jitter: f32) -> RayHit {
+    var hit: RayHit;
+    hit.confidence = 0.0;
+
+    let baseDims = vec2f(textureDimensions(hierarchicalDepthTexture, 0));
+    let maxLevel = i32(textureNumLevels(hierarchicalDepthTexture)) - 1;
+
+    let thickness = params.thickness / farDistance;
+
+    let crossingOffset = select(vec2f(-0.5), vec2f(0.5), ray.textureCoordsDelta > vec2f(0.0)) / baseDims;
+
+    let rayTexelExtent = abs(ray.textureCoordsDelta) * baseDims;
+    let texelRayParameterLength = 1.0 / max(max(rayTexelExtent.x, rayTexelExtent.y), 1.0);
+
+    let maxRayParameter = computeMaxOnScreenRayParameter(ray);
+
+    // Start from where the ray leaves the pixel it originates from
+    let originPixel = vec2i(floor(ray.startTextureCoords * baseDims));
+    var t = computeCellExitRayParameter(ray, originPixel, baseDims, crossingOffset)
+        + jitter * texelRayParameterLength;
+
+    var level = 0;
+
+    for (var stepIdx: u32 = 0u; stepIdx < params.maxStepCount; stepIdx++) {
+        if (t >= maxRayParameter) {
+            break;
+        }
+
+        let textureCoords = computeRayTextureCoords(ray, t);
+        let rayDepth = computeRayLinearDepth(ray, t);
+
+        let levelDims = vec2f(textureDimensions(hierarchicalDepthTexture, level));
+        let cell = clamp(vec2i(floor(textureCoords * levelDims)), vec2i(0), vec2i(levelDims) - 1);
+        let cellMinDepth = textureLoad(hierarchicalDepthTexture, cell, level).r;
+
+        let tCellExit = computeCellExitRayParameter(ray, cell, levelDims, crossingOffset);
+
+        if (rayDepth < cellMinDepth) {
+            let tDepthPlane = computeDepthPlaneRayParameter(ray, cellMinDepth);
+
+            if (tDepthPlane < tCellExit) {
+                // The ray reaches the closest surface in the cell before
+                // leaving it, so we need a finer level to tell whether it
+                // actually hits anything
+                t = max(t, tDepthPlane) + DEPTH_PLANE_CROSSING_FRACTION * texelRayParameterLength;
+                level = max(level - 1, 0);
+            } else {
+                t = tCellExit;
+                level = min(level + 1, maxLevel);
+            }
+        } else if (level > 0) {
+            level -= 1;
+        } else if (rayDepth - cellMinDepth <= thickness) {
+            // Compute how far along the ray the hit is in camera space. Since
+            // the linear depth varies linearly with distance along the ray,
+            // this can be obtained from the depths.
+            let startDepth = 1.0 / ray.startInverseDepth;
+            let endDepth = 1.0 / (ray.startInverseDepth + ray.inverseDepthDelta);
+            let depthDelta = endDepth - startDepth;
+            let hitRayFraction = select(t, (rayDepth - startDepth) / depthDelta, abs(depthDelta) > 1e-9);
+            let distanceFade = 1.0 - saturate(hitRayFraction * rayLength / params.maxDistance);
+
+            hit.textureCoords = textureCoords;
+            hit.confidence = computeScreenEdgeFade(textureCoords) * distanceFade;
+            return hit;
+        } else {
+            // The ray passed behind an object that is too thick for us to
+            // assume that the ray actually intersects it
+            t = tCellExit;
+        }
+    }
+
+    return hit;
+}
+
+fn samplePreviousLuminanceAtHit(textureCoords: vec2f) -> vec3f {
+    // Reproject the hit point to where it was in the previous frame, since the
+    // luminance we sample is from the previous frame
+    let motionVector = textureSampleLevel(motionVectorTexture, motionVectorSampler, textureCoords, 0.0).xy;
+    let previousTextureCoords = saturate(textureCoords - motionVector);
+    return textureSampleLevel(previousLuminanceTexture, previousLuminanceSampler, previousTextureCoords, 0.0).rgb;
+}
+
+fn computePreExposedFallbackLuminance(cameraSpaceDirection: vec3f) -> vec3f {
+#if (uses_skybox)
+    // The camera rotation quaternion rotates from world space to camera space,
+    // so we rotate with its conjugate to get the world space direction
+    let inverseCameraRotation = vec4f(-pushConstants.cameraRotationQuaternion.xyz, pushConstants.cameraRotationQuaternion.w);
+    let worldSpaceDirection = rotateVectorWithQuaternion(inverseCameraRotation, cameraSpaceDirection);
+    let sample = textureSampleLevel(skyboxTexture, skyboxSampler, worldSpaceDirection, 0.0).rgb;
+    return (pushConstants.exposure * skyboxProperties.maxLuminance) * sample;
+#else
+    return vec3f(0.0);
+#endif
+}
+
+@vertex
+fn mainVS(
+    @builtin(vertex_index) vertexIndex: u32,
+    @location({{position_location}}) modelSpacePosition: vec3f
+) -> VertexOutput {
+    var output: VertexOutput;
+    output.projectedPosition = vec4f(modelSpacePosition, 1.0);
+    output.frustumFarPlanePoint = projectionUniform.frustumFarPlaneCorners[vertexIndex].xyz;
+    return output;
+}
+
+@fragment
+fn mainFS(input: VertexOutput) -> FragmentOutput {
+    var output: FragmentOutput;
+
+    let textureCoords = convertFramebufferPositionToScreenTextureCoords(input.projectedPosition);
+
+    // Only physical model fragments are traced, so the base level of the depth
+    // pyramid holds their unmodified linear depth
+    let depth = textureSampleLevel(hierarchicalDepthTexture, hierarchicalDepthSampler, textureCoords, 0.0).r;
+    let position = computePositionFromLinearDepth(depth, input.frustumFarPlanePoint);
+
+    let normalColor = textureSampleLevel(normalVectorTexture, normalVectorSampler, textureCoords, 0.0).rgb;
+    let normalVector = convertNormalColorToNormalizedNormalVector(normalColor);
+
+    let farDistance = -input.frustumFarPlanePoint.z;
+
+    let incidentDirection = normalize(position);
+    let reflectedDirection = reflect(incidentDirection, normalVector);
+
+    let jitter = generateInterleavedGradientNoiseFactor(input.projectedPosition);
+
+    // Offset the origin slightly along the normal to avoid self-intersection
+    let origin = position + (1e-3 * farDistance * depth) * normalVector;
+
+    // Shorten the ray if needed to keep its end point in front of the camera
+    var rayLength = params.maxDistance;
+    if (origin.z + rayLength * reflectedDirection.z > MAX_CAMERA_SPACE_RAY_Z) {
+        rayLength = (MAX_CAMERA_SPACE_RAY_Z - origin.z) / reflectedDirection.z;
+    }
+
+    var hit: RayHit;
+    hit.confidence = 0.0;
+
+    if (origin.z < MAX_CAMERA_SPACE_RAY_Z) {
+        let ray = computeScreenSpaceRay(origin, origin + rayLength * reflectedDirection, farDistance);
+        hit = traceReflectedRay(ray, rayLength, farDistance, jitter);
+    }
+
+    let fallbackLuminance = computePreExposedFallbackLuminance(reflectedDirection);
+
+    var reflectedLuminance = fallbackLuminance;
+    if (hit.confidence > 0.0) {
+        let hitLuminance = samplePreviousLuminanceAtHit(hit.textureCoords);
+        reflectedLuminance = mix(fallbackLuminance, hitLuminance, hit.confidence);
+    }
+
+    output.preExposedReflectedLuminance = vec4f(reflectedLuminance, 1.0);
+    return output;
+}
@@ -26,6 +26,17 @@ struct OrthographicTransform {
     scaling: vec3f,
 }
 
+#if (is_skinned)
+struct JointPaletteOffset {
+    @location({{joint_palette_offset_location}}) offset: u32,
+}
+
+struct SkinWeights {
+    @location({{joint_indices_location}}) jointIndices: vec4u,
+    @location({{joint_weights_location}}) jointWeights: vec4f,
+}
+#endif
+
 struct VertexOutput {
     @builtin(position) projectedPosition: vec4f,
 }
@@ -39,6 +50,11 @@ var<push_constant> pushConstants: PushConstants;
 @group({{light_uniform_group}}) @binding({{light_uniform_binding}})
 var<uniform> unidirectionalLights: UnidirectionalLights;
 
+#if (is_skinned)
+@group({{joint_palette_group}}) @binding({{joint_palette_binding}})
+var<storage, read> jointMatrices: array<mat4x4f>;
+#endif
+
 fn transformPosition(
     rotationQuaternion: vec4f,
     translation: vec3f,
@@ -48,6 +64,18 @@ fn transformPosition(
     return rotateVectorWithQuaternion(rotationQuaternion, scaling * position) + translation;
 }
 
+#if (is_skinned)
+fn skinPosition(paletteOffset: u32, skinWeights: SkinWeights, position: vec3f) -> vec3f {
+    let indices = skinWeights.jointIndices;
+    let weights = skinWeights.jointWeights;
+    let skinningMatrix = weights.x * jointMatrices[paletteOffset + indices.x]
+        + weights.y * jointMatrices[paletteOffset + indices.y]
+        + weights.z * jointMatrices[paletteOffset + indices.z]
+        + weights.w * jointMatrices[paletteOffset + indices.w];
+    return (skinningMatrix * vec4f(position, 1.0)).xyz;
+}
+#endif
+
 fn rotateVectorWithQuaternion(quaternion: vec4f, vector: vec3f) -> vec3f {
     let tmp = 2.0 * cross(quaternion.xyz, vector);
     return vector + quaternion.w * tmp + cross(quaternion.xyz, tmp);
@@ -63,11 +91,21 @@ fn applyOrthographicProjectionToPosition(
 
 @vertex
 fn mainVS(
-    @location({{position_location}}) modelSpacePosition: vec3f,
+    @location({{position_location}}) vertexPosition: vec3f,
     modelToLightTransform: ModelToLightTransform,
+#if (is_skinned)
+    jointPaletteOffset: JointPaletteOffset,
+    skinWeights: SkinWeights,
+#endif
 ) -> VertexOutput {
     var output: VertexOutput;
 
+#if (is_skinned)
+    let modelSpacePosition = skinPosition(jointPaletteOffset.offset, skinWeights, vertexPosition);
+#else
+    let modelSpacePosition = vertexPosition;
+#endif
+
     let lightSpacePosition = transformPosition(
         modelToLightTransform.rotationQuaternion,
         modelToLightTransform.translationAndScaling.xyz,
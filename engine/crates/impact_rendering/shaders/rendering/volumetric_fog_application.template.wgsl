@@ -0,0 +1,87 @@
+struct ProjectionUniform {
+    projectionMatrix: mat4x4f,
+    frustumFarPlaneCorners: array<vec4f, 4>,
+}
+
+struct Parameters {
+    scatteringCoefficient: vec3f,
+    extinctionCoefficient: f32,
+    anisotropy: f32,
+    maxDistance: f32,
+}
+
+struct VertexOutput {
+    @builtin(position) projectedPosition: vec4f,
+    @location(0) frustumFarPlanePoint: vec3f,
+}
+
+struct FragmentOutput {
+    // The RGB components hold the pre-exposed luminance scattered towards the
+    // camera and the alpha component holds the transmittance from the surface
+    // to the camera
+    @location(0) luminanceAndTransmittance: vec4f,
+}
+
+const GRID_DEPTH: f32 = {{grid_depth}};
+
+var<push_constant> inverseWindowDimensions: vec2f;
+
+@group({{projection_uniform_group}}) @binding({{projection_uniform_binding}})
+var<uniform> projectionUniform: ProjectionUniform;
+
+@group({{linear_depth_texture_group}}) @binding({{linear_depth_texture_binding}})
+var linearDepthTexture: texture_2d<f32>;
+@group({{linear_depth_texture_group}}) @binding({{linear_depth_sampler_binding}})
+var linearDepthSampler: sampler;
+
+@group({{params_group}}) @binding({{params_binding}}) var<uniform> params: Parameters;
+
+@group({{integrated_scattering_group}}) @binding({{integrated_scattering_texture_binding}})
+var integratedScatteringTexture: texture_3d<f32>;
+@group({{integrated_scattering_group}}) @binding({{integrated_scattering_sampler_binding}})
+var integratedScatteringSampler: sampler;
+
+fn convertFramebufferPositionToScreenTextureCoords(framebufferPosition: vec4f) -> vec2f {
+    return (framebufferPosition.xy * inverseWindowDimensions);
+}
+
+@vertex
+fn mainVS(
+    @builtin(vertex_index) vertexIndex: u32,
+    @location({{position_location}}) modelSpacePosition: vec3f
+) -> VertexOutput {
+    var output: VertexOutput;
+    output.projectedPosition = vec4f(modelSpacePosition, 1.0);
+    output.frustumFarPlanePoint = projectionUniform.frustumFarPlaneCorners[vertexIndex].xyz;
+    return output;
+}
+
+@fragment
+fn mainFS(input: VertexOutput) -> FragmentOutput {
+    var output: FragmentOutput;
+
+    let textureCoords = convertFramebufferPositionToScreenTextureCoords(input.projectedPosition);
+
+    let depth = textureSampleLevel(linearDepthTexture, linearDepthSampler, textureCoords, 0.0).r;
+    let distance = min(depth * (-input.frustumFarPlanePoint.z), params.maxDistance);
+
+    // Invert the quadratic slice distribution. Each froxel holds the
+    // integrated values at the far side of its slice, so we shift by half a
+    // slice to interpolate between the far sides of adjacent slices.
+    let normalizedSliceCoord = sqrt(distance / params.maxDistance);
+    let textureDepthCoord = normalizedSliceCoord - 0.5 / GRID_DEPTH;
+
+    let integrated = textureSampleLevel(
+        integratedScatteringTexture,
+        integratedScatteringSampler,
+        vec3f(textureCoords, textureDepthCoord),
+        0.0,
+    );
+
+    // Within the first slice, interpolate towards no scattering and full
+    // transmittance at the camera
+    let firstSliceFraction = saturate(normalizedSliceCoord * GRID_DEPTH);
+
+    output.luminanceAndTransmittance = mix(vec4f(0.0, 0.0, 0.0, 1.0), integrated, firstSliceFraction);
+    return output;
+}
@@ -16,17 +16,21 @@ bitflags! {
     /// render attachment textures.
     #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
     pub struct RenderAttachmentQuantitySet: u16 {
-        const DEPTH_STENCIL              = 1 << 0;
-        const LINEAR_DEPTH               = 1 << 1;
-        const NORMAL_VECTOR              = 1 << 2;
-        const MOTION_VECTOR              = 1 << 3;
-        const MATERIAL_COLOR             = 1 << 4;
-        const MATERIAL_PROPERTIES        = 1 << 5;
-        const LUMINANCE                  = 1 << 6;
-        const LUMINANCE_AUX              = 1 << 7;
-        const LUMINANCE_HISTORY          = 1 << 8;
-        const PREVIOUS_LUMINANCE_HISTORY = 1 << 9;
-        const OCCLUSION                  = 1 << 10;
+        const DEPTH_STENCIL                        = 1 << 0;
+        const LINEAR_DEPTH                         = 1 << 1;
+        const NORMAL_VECTOR                        = 1 << 2;
+        const MOTION_VECTOR                        = 1 << 3;
+        const MATERIAL_COLOR                       = 1 << 4;
+        const MATERIAL_PROPERTIES                  = 1 << 5;
+        const LUMINANCE                            = 1 << 6;
+        const LUMINANCE_AUX                        = 1 << 7;
+        const LUMINANCE_HISTORY                    = 1 << 8;
+        const PREVIOUS_LUMINANCE_HISTORY           = 1 << 9;
+        const OCCLUSION                            = 1 << 10;
+        const ENTITY_ID                            = 1 << 11;
+        const HIERARCHICAL_DEPTH                   = 1 << 12;
+        const REFLECTED_LUMINANCE_HISTORY          = 1 << 13;
+        const PREVIOUS_REFLECTED_LUMINANCE_HISTORY = 1 << 14;
     }
 }
 
@@ -46,6 +50,10 @@ pub enum RenderAttachmentQuantity {
     LuminanceHistory = 8,
     PreviousLuminanceHistory = 9,
     Occlusion = 10,
+    EntityID = 11,
+    HierarchicalDepth = 12,
+    ReflectedLuminanceHistory = 13,
+    PreviousReflectedLuminanceHistory = 14,
 }
 
 /// A sampler variant for render attachment textures.
@@ -62,6 +70,7 @@ pub struct RenderAttachmentInputDescription {
     sampler: RenderAttachmentSampler,
     visibility: wgpu::ShaderStages,
     mip_level: u32,
+    full_mip_chain: bool,
 }
 
 /// Specifies how a render attachment should be used when bound as an output.
@@ -78,6 +87,10 @@ pub struct RenderAttachmentOutputDescription {
 pub enum Blending {
     Replace,
     Additive,
+    /// The existing color is scaled by the output alpha before the output
+    /// color is added to it, so that the output can represent light that is
+    /// added along with a transmittance for the existing light.
+    Transmissive,
 }
 
 /// A set of descriptions for render attachments.
@@ -99,6 +112,7 @@ pub type RenderAttachmentOutputDescriptionSet =
 #[derive(Debug)]
 pub struct RenderAttachmentTextureManager {
     quantity_textures: [RenderAttachmentTexture; N_RENDER_ATTACHMENT_QUANTITIES],
+    disabled_quantities: RenderAttachmentQuantitySet,
     samplers: [Sampler; 2],
     bind_groups_and_layouts:
         HashMap<RenderAttachmentInputDescription, (wgpu::BindGroupLayout, wgpu::BindGroup)>,
@@ -111,6 +125,7 @@ pub struct RenderAttachmentTexture {
     quantity: RenderAttachmentQuantity,
     texture: Texture,
     mip_texture_views: Vec<wgpu::TextureView>,
+    full_mip_chain_texture_view: Option<wgpu::TextureView>,
 }
 
 pub trait RenderAttachmentDescription {
@@ -123,7 +138,7 @@ pub trait RenderAttachmentDescription {
 }
 
 /// The total number of separate render attachment quantities.
-const N_RENDER_ATTACHMENT_QUANTITIES: usize = 11;
+const N_RENDER_ATTACHMENT_QUANTITIES: usize = 15;
 
 /// Each individual render attachment quantity.
 ///
@@ -141,6 +156,10 @@ const RENDER_ATTACHMENT_QUANTITIES: [RenderAttachmentQuantity; N_RENDER_ATTACHME
     RenderAttachmentQuantity::LuminanceHistory,
     RenderAttachmentQuantity::PreviousLuminanceHistory,
     RenderAttachmentQuantity::Occlusion,
+    RenderAttachmentQuantity::EntityID,
+    RenderAttachmentQuantity::HierarchicalDepth,
+    RenderAttachmentQuantity::ReflectedLuminanceHistory,
+    RenderAttachmentQuantity::PreviousReflectedLuminanceHistory,
 ];
 
 /// The bitflag of each individual render attachment quantity.
@@ -156,6 +175,10 @@ const RENDER_ATTACHMENT_FLAGS: [RenderAttachmentQuantitySet; N_RENDER_ATTACHMENT
     RenderAttachmentQuantitySet::LUMINANCE_HISTORY,
     RenderAttachmentQuantitySet::PREVIOUS_LUMINANCE_HISTORY,
     RenderAttachmentQuantitySet::OCCLUSION,
+    RenderAttachmentQuantitySet::ENTITY_ID,
+    RenderAttachmentQuantitySet::HIERARCHICAL_DEPTH,
+    RenderAttachmentQuantitySet::REFLECTED_LUMINANCE_HISTORY,
+    RenderAttachmentQuantitySet::PREVIOUS_REFLECTED_LUMINANCE_HISTORY,
 ];
 
 /// The name of each individual render attachment quantity.
@@ -173,6 +196,11 @@ const RENDER_ATTACHMENT_NAMES: [&str; N_RENDER_ATTACHMENT_QUANTITIES] = [
     // that their `BindGroupLayout`s can be used interchangeably
     "luminance_history",
     "occlusion",
+    "entity_id",
+    "hierarchical_depth",
+    "reflected_luminance_history",
+    // Same reasoning as for the previous luminance history attachment
+    "reflected_luminance_history",
 ];
 
 /// The texture format used for each render attachment quantity.
@@ -188,6 +216,10 @@ const RENDER_ATTACHMENT_FORMATS: [wgpu::TextureFormat; N_RENDER_ATTACHMENT_QUANT
     wgpu::TextureFormat::Rgba16Float,          // Luminance history
     wgpu::TextureFormat::Rgba16Float,          // Previous luminance history
     wgpu::TextureFormat::R16Float,             // Occlusion
+    wgpu::TextureFormat::Rgba32Uint,           // Entity ID
+    wgpu::TextureFormat::R32Float,             // Hierarchical depth
+    wgpu::TextureFormat::Rgba16Float,          // Reflected luminance history
+    wgpu::TextureFormat::Rgba16Float,          // Previous reflected luminance history
 ];
 
 /// The maximum mip level for each render attachment quantity.
@@ -203,6 +235,10 @@ const RENDER_ATTACHMENT_MAX_MIP_LEVEL: [u32; N_RENDER_ATTACHMENT_QUANTITIES] = [
     0, // Luminance history
     0, // Previous luminance history
     0, // Occlusion
+    0, // Entity ID
+    8, // Hierarchical depth
+    0, // Reflected luminance history
+    0, // Previous reflected luminance history
 ];
 
 /// The clear color used for each render attachment quantity, or [`None`] if the
@@ -219,6 +255,10 @@ const RENDER_ATTACHMENT_CLEAR_COLORS: [Option<wgpu::Color>; N_RENDER_ATTACHMENT_
     Some(wgpu::Color::BLACK), // Luminance history
     None,                     // Previous luminance history
     Some(wgpu::Color::WHITE), // Occlusion
+    Some(wgpu::Color::BLACK), // Entity ID
+    None,                     // Hierarchical depth
+    Some(wgpu::Color::BLACK), // Reflected luminance history
+    None,                     // Previous reflected luminance history
 ];
 
 /// The texture and sampler bind group bindings used for each render attachment
@@ -235,6 +275,10 @@ const RENDER_ATTACHMENT_BINDINGS: [(u32, u32); N_RENDER_ATTACHMENT_QUANTITIES] =
     (0, 1), // Luminance history
     (0, 1), // Previous luminance history
     (0, 1), // Occlusion
+    (0, 1), // Entity ID
+    (0, 1), // Hierarchical depth
+    (0, 1), // Reflected luminance history
+    (0, 1), // Previous reflected luminance history
 ];
 
 impl RenderAttachmentQuantity {
@@ -403,6 +447,7 @@ impl RenderAttachmentInputDescription {
             sampler,
             visibility,
             mip_level,
+            full_mip_chain: false,
         }
     }
 
@@ -436,6 +481,17 @@ impl RenderAttachmentInputDescription {
         self
     }
 
+    /// Makes the full mip chain of the render attachment available to the
+    /// shader rather than a single mip level.
+    ///
+    /// # Panics
+    /// If the render attachment quantity does not have multiple mip levels.
+    pub fn with_full_mip_chain(mut self) -> Self {
+        assert!(self.quantity.max_mip_level() > 0);
+        self.full_mip_chain = true;
+        self
+    }
+
     /// Returns how the render attachment should be sampled.
     pub fn sampler(&self) -> RenderAttachmentSampler {
         self.sampler
@@ -451,6 +507,12 @@ impl RenderAttachmentInputDescription {
     pub fn mip_level(&self) -> u32 {
         self.mip_level
     }
+
+    /// Whether the full mip chain of the render attachment should be used
+    /// rather than a single mip level.
+    pub fn uses_full_mip_chain(&self) -> bool {
+        self.full_mip_chain
+    }
 }
 
 impl RenderAttachmentDescription for RenderAttachmentInputDescription {
@@ -460,6 +522,7 @@ impl RenderAttachmentDescription for RenderAttachmentInputDescription {
             sampler: RenderAttachmentSampler::NonFiltering,
             visibility: wgpu::ShaderStages::FRAGMENT,
             mip_level: 0,
+            full_mip_chain: false,
         }
     }
 
@@ -650,8 +713,14 @@ where
 
 impl RenderAttachmentTextureManager {
     /// Creates a new manager for render attachment textures, initializing
-    /// all render attachment textures.
-    pub fn new(graphics_device: &GraphicsDevice, rendering_surface: &RenderingSurface) -> Self {
+    /// all render attachment textures. The textures for the given disabled
+    /// quantities are only single-texel placeholders until the quantities are
+    /// enabled with [`Self::set_quantity_enabled`].
+    pub fn new(
+        graphics_device: &GraphicsDevice,
+        rendering_surface: &RenderingSurface,
+        disabled_quantities: RenderAttachmentQuantitySet,
+    ) -> Self {
         let samplers = [
             Sampler::create(
                 graphics_device,
@@ -661,11 +730,17 @@ impl RenderAttachmentTextureManager {
         ];
 
         let quantity_textures = RenderAttachmentQuantity::all().map(|quantity| {
-            RenderAttachmentTexture::new(graphics_device, rendering_surface, quantity)
+            Self::create_texture(
+                graphics_device,
+                rendering_surface,
+                disabled_quantities,
+                quantity,
+            )
         });
 
         let mut manager = Self {
             quantity_textures,
+            disabled_quantities,
             samplers,
             bind_groups_and_layouts: HashMap::default(),
         };
@@ -812,6 +887,35 @@ impl RenderAttachmentTextureManager {
             .map(|(_, bind_group)| bind_group)
     }
 
+    /// Whether the texture for the given quantity has its full size.
+    pub fn quantity_is_enabled(&self, quantity: RenderAttachmentQuantity) -> bool {
+        !self.disabled_quantities.contains(quantity.flag())
+    }
+
+    /// Enables or disables the texture for the given quantity. A disabled
+    /// quantity only has a single-texel placeholder texture, so it must not be
+    /// rendered to or read from while disabled.
+    pub fn set_quantity_enabled(
+        &mut self,
+        graphics_device: &GraphicsDevice,
+        rendering_surface: &RenderingSurface,
+        quantity: RenderAttachmentQuantity,
+        enabled: bool,
+    ) {
+        if enabled == self.quantity_is_enabled(quantity) {
+            return;
+        }
+        self.disabled_quantities.set(quantity.flag(), !enabled);
+
+        self.quantity_textures[quantity.index()] = Self::create_texture(
+            graphics_device,
+            rendering_surface,
+            self.disabled_quantities,
+            quantity,
+        );
+        self.recreate_bind_groups(graphics_device);
+    }
+
     /// Recreates all render attachment textures for the current state of the
     /// core system.
     pub fn recreate_textures(
@@ -820,9 +924,10 @@ impl RenderAttachmentTextureManager {
         rendering_surface: &RenderingSurface,
     ) {
         for quantity_texture in self.quantity_textures.iter_mut() {
-            *quantity_texture = RenderAttachmentTexture::new(
+            *quantity_texture = Self::create_texture(
                 graphics_device,
                 rendering_surface,
+                self.disabled_quantities,
                 quantity_texture.quantity(),
             );
         }
@@ -841,6 +946,11 @@ impl RenderAttachmentTextureManager {
             RenderAttachmentQuantity::LuminanceHistory,
             RenderAttachmentQuantity::PreviousLuminanceHistory,
         );
+        self.swap_two_attachments(
+            graphics_device,
+            RenderAttachmentQuantity::ReflectedLuminanceHistory,
+            RenderAttachmentQuantity::PreviousReflectedLuminanceHistory,
+        );
     }
 
     /// Creates any bind group layouts and bind groups represented in the given
@@ -864,14 +974,25 @@ impl RenderAttachmentTextureManager {
         }
     }
 
+    fn create_texture(
+        graphics_device: &GraphicsDevice,
+        rendering_surface: &RenderingSurface,
+        disabled_quantities: RenderAttachmentQuantitySet,
+        quantity: RenderAttachmentQuantity,
+    ) -> RenderAttachmentTexture {
+        if disabled_quantities.contains(quantity.flag()) {
+            RenderAttachmentTexture::new_placeholder(graphics_device, quantity)
+        } else {
+            RenderAttachmentTexture::new(graphics_device, rendering_surface, quantity)
+        }
+    }
+
     fn recreate_bind_groups(&mut self, graphics_device: &GraphicsDevice) {
         for (description, (bind_group_layout, bind_group)) in &mut self.bind_groups_and_layouts {
             let quantity = description.quantity;
 
             let quantity_texture = &self.quantity_textures[quantity.index()];
-            let texture_view = quantity_texture
-                .texture_view(description.mip_level())
-                .unwrap();
+            let texture_view = quantity_texture.input_texture_view(description).unwrap();
 
             let sampler = &self.samplers[description.sampler as usize];
 
@@ -993,9 +1114,7 @@ impl RenderAttachmentTextureManager {
         description: &RenderAttachmentInputDescription,
     ) -> (wgpu::BindGroupLayout, wgpu::BindGroup) {
         let quantity_texture = &quantity_textures[description.quantity.index()];
-        let texture_view = quantity_texture
-            .texture_view(description.mip_level())
-            .unwrap();
+        let texture_view = quantity_texture.input_texture_view(description).unwrap();
 
         let sampler = &samplers[description.sampler() as usize];
 
@@ -1084,14 +1203,32 @@ impl RenderAttachmentTexture {
         rendering_surface: &RenderingSurface,
         quantity: RenderAttachmentQuantity,
     ) -> Self {
-        let device = graphics_device.device();
         let (width, height) = rendering_surface.surface_dimensions();
+        Self::new_with_size(graphics_device, quantity, width.into(), height.into())
+    }
+
+    /// Creates a new single-texel render attachment texture for the given
+    /// render attachment quantity, for use in place of a disabled attachment.
+    pub fn new_placeholder(
+        graphics_device: &GraphicsDevice,
+        quantity: RenderAttachmentQuantity,
+    ) -> Self {
+        Self::new_with_size(graphics_device, quantity, 1, 1)
+    }
+
+    fn new_with_size(
+        graphics_device: &GraphicsDevice,
+        quantity: RenderAttachmentQuantity,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let device = graphics_device.device();
 
         let format = quantity.texture_format();
 
         let texture_size = wgpu::Extent3d {
-            width: width.into(),
-            height: height.into(),
+            width,
+            height,
             depth_or_array_layers: 1,
         };
 
@@ -1125,12 +1262,16 @@ impl RenderAttachmentTexture {
             })
             .collect();
 
+        let full_mip_chain_texture_view = (mip_level_count > 1)
+            .then(|| texture.create_view(&wgpu::TextureViewDescriptor::default()));
+
         let texture = Texture::new(texture, base_texture_view, wgpu::TextureViewDimension::D2);
 
         Self {
             quantity,
             texture,
             mip_texture_views,
+            full_mip_chain_texture_view,
         }
     }
 
@@ -1160,6 +1301,31 @@ impl RenderAttachmentTexture {
         }
     }
 
+    /// Returns a view into the full mip chain of the render attachment
+    /// texture, or [`None`] if the texture only has a single mip level.
+    pub fn full_mip_chain_texture_view(&self) -> Option<&wgpu::TextureView> {
+        self.full_mip_chain_texture_view.as_ref()
+    }
+
+    /// Returns the view into the render attachment texture that should be
+    /// bound for the given input description, or [`None`] if the texture does
+    /// not have the requested mip level. If the full mip chain is requested
+    /// for a texture that only has a single mip level (which happens for tiny
+    /// windows), the base texture view is returned.
+    pub fn input_texture_view(
+        &self,
+        description: &RenderAttachmentInputDescription,
+    ) -> Option<&wgpu::TextureView> {
+        if description.uses_full_mip_chain() {
+            Some(
+                self.full_mip_chain_texture_view()
+                    .unwrap_or_else(|| self.base_texture_view()),
+            )
+        } else {
+            self.texture_view(description.mip_level())
+        }
+    }
+
     /// Returns a view into the base (mip level 0) render attachment texture.
     pub fn base_texture_view(&self) -> &wgpu::TextureView {
         self.texture.view()
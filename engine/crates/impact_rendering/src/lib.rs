@@ -23,6 +23,10 @@ use impact_gpu::{device::GraphicsDevice, wgpu};
 pub struct BasicRenderingConfig {
     pub wireframe_mode_on: bool,
     pub timings_enabled: bool,
+    /// Whether to write the ID of the entity covering each pixel into the
+    /// [`EntityID`](attachment::RenderAttachmentQuantity::EntityID) render
+    /// attachment so that entities can be picked on screen.
+    pub entity_picking_enabled: bool,
 }
 
 impl BasicRenderingConfig {
@@ -50,6 +54,7 @@ impl Default for BasicRenderingConfig {
         Self {
             wireframe_mode_on: false,
             timings_enabled: false,
+            entity_picking_enabled: false,
         }
     }
 }
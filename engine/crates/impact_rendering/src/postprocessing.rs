@@ -4,7 +4,9 @@ pub mod ambient_occlusion;
 pub mod capturing;
 pub mod gaussian_blur;
 pub mod render_attachment_visualization;
+pub mod screen_space_reflection;
 pub mod temporal_anti_aliasing;
+pub mod volumetric_fog;
 
 use crate::{
     attachment::{
@@ -29,7 +31,9 @@ use impact_gpu::{
     wgpu,
 };
 use render_attachment_visualization::RenderAttachmentVisualizationPasses;
+use screen_space_reflection::{SSRConfig, ScreenSpaceReflectionRenderCommands};
 use temporal_anti_aliasing::{TemporalAntiAliasingConfig, TemporalAntiAliasingRenderCommands};
+use volumetric_fog::{VolumetricFogConfig, VolumetricFogRenderCommands};
 
 /// Specific shader template that can be resolved to generate a postprocessing
 /// shader.
@@ -73,6 +77,8 @@ pub trait PostprocessingShaderTemplate: SpecificShaderTemplate {
 #[derive(Debug)]
 pub struct Postprocessor {
     ambient_occlusion_commands: AmbientOcclusionRenderCommands,
+    screen_space_reflection_commands: ScreenSpaceReflectionRenderCommands,
+    volumetric_fog_commands: VolumetricFogRenderCommands,
     temporal_anti_aliasing_commands: TemporalAntiAliasingRenderCommands,
     capturing_camera: CapturingCamera,
     render_attachment_visualization_passes: RenderAttachmentVisualizationPasses,
@@ -83,6 +89,8 @@ impl Postprocessor {
     /// according to the given configuration.
     pub fn new(
         ambient_occlusion_config: AmbientOcclusionConfig,
+        screen_space_reflection_config: SSRConfig,
+        volumetric_fog_config: VolumetricFogConfig,
         temporal_anti_aliasing_config: TemporalAntiAliasingConfig,
         capturing_camera_config: CapturingCameraConfig,
         graphics_device: &GraphicsDevice,
@@ -103,6 +111,26 @@ impl Postprocessor {
             bind_group_layout_registry,
         )?;
 
+        let screen_space_reflection_commands = ScreenSpaceReflectionRenderCommands::new(
+            screen_space_reflection_config,
+            graphics_device,
+            rendering_surface,
+            shader_manager,
+            render_attachment_texture_manager,
+            gpu_resource_group_manager,
+            bind_group_layout_registry,
+        )?;
+
+        let volumetric_fog_commands = VolumetricFogRenderCommands::new(
+            volumetric_fog_config,
+            graphics_device,
+            rendering_surface,
+            shader_manager,
+            render_attachment_texture_manager,
+            gpu_resource_group_manager,
+            bind_group_layout_registry,
+        )?;
+
         let temporal_anti_aliasing_commands = TemporalAntiAliasingRenderCommands::new(
             temporal_anti_aliasing_config,
             graphics_device,
@@ -135,12 +163,47 @@ impl Postprocessor {
 
         Ok(Self {
             ambient_occlusion_commands,
+            screen_space_reflection_commands,
+            volumetric_fog_commands,
             temporal_anti_aliasing_commands,
             capturing_camera,
             render_attachment_visualization_passes,
         })
     }
 
+    /// Makes sure the postprocessing render commands are up to date with the
+    /// given render resources.
+    ///
+    /// # Errors
+    /// Returns an error if any of the required GPU resources are missing.
+    pub fn sync_with_render_resources(
+        &mut self,
+        graphics_device: &GraphicsDevice,
+        shader_manager: &mut ShaderManager,
+        render_attachment_texture_manager: &RenderAttachmentTextureManager,
+        gpu_resource_group_manager: &GPUResourceGroupManager,
+        bind_group_layout_registry: &BindGroupLayoutRegistry,
+        gpu_resources: &impl BasicGPUResources,
+    ) -> Result<()> {
+        self.screen_space_reflection_commands
+            .sync_with_render_resources(
+                graphics_device,
+                shader_manager,
+                render_attachment_texture_manager,
+                gpu_resource_group_manager,
+                bind_group_layout_registry,
+                gpu_resources,
+            )?;
+        self.volumetric_fog_commands.sync_with_render_resources(
+            graphics_device,
+            shader_manager,
+            gpu_resources,
+        );
+        self.capturing_camera
+            .sync_with_settings(graphics_device, gpu_resource_group_manager);
+        Ok(())
+    }
+
     /// Records all postprocessing render commands that do not write directly
     /// into the surface texture into the given command encoder.
     ///
@@ -167,6 +230,26 @@ impl Postprocessor {
             timestamp_recorder,
             command_encoder,
         )?;
+        self.screen_space_reflection_commands.record(
+            rendering_surface,
+            gpu_resources,
+            render_attachment_texture_manager,
+            gpu_resource_group_manager,
+            self,
+            frame_counter,
+            timestamp_recorder,
+            command_encoder,
+        )?;
+        self.volumetric_fog_commands.record(
+            rendering_surface,
+            gpu_resources,
+            render_attachment_texture_manager,
+            gpu_resource_group_manager,
+            self,
+            frame_counter,
+            timestamp_recorder,
+            command_encoder,
+        )?;
         self.capturing_camera
             .record_commands_before_dynamic_range_compression(
                 rendering_surface,
@@ -175,6 +258,7 @@ impl Postprocessor {
                 gpu_resource_group_manager,
                 storage_gpu_buffer_manager,
                 self,
+                frame_counter,
                 timestamp_recorder,
                 command_encoder,
             )?;
@@ -259,6 +343,72 @@ impl Postprocessor {
         self.ambient_occlusion_commands.enabled_mut()
     }
 
+    pub fn screen_space_reflection_config(&self) -> &SSRConfig {
+        self.screen_space_reflection_commands.config()
+    }
+
+    /// Sets the given screen-space reflection configuration parameters and
+    /// updates the appropriate render resources.
+    pub fn set_screen_space_reflection_config(
+        &mut self,
+        graphics_device: &GraphicsDevice,
+        gpu_resource_group_manager: &GPUResourceGroupManager,
+        config: SSRConfig,
+    ) {
+        self.screen_space_reflection_commands.set_config(
+            graphics_device,
+            gpu_resource_group_manager,
+            config,
+        );
+    }
+
+    pub fn screen_space_reflection_enabled_mut(&mut self) -> &mut bool {
+        self.screen_space_reflection_commands.enabled_mut()
+    }
+
+    pub fn volumetric_fog_config(&self) -> &VolumetricFogConfig {
+        self.volumetric_fog_commands.config()
+    }
+
+    /// Sets the given volumetric fog configuration parameters and updates the
+    /// appropriate render resources.
+    pub fn set_volumetric_fog_config(
+        &mut self,
+        graphics_device: &GraphicsDevice,
+        gpu_resource_group_manager: &GPUResourceGroupManager,
+        config: VolumetricFogConfig,
+    ) {
+        self.volumetric_fog_commands.set_config(
+            graphics_device,
+            gpu_resource_group_manager,
+            config,
+        );
+    }
+
+    /// Sets the optical properties of the medium the volumetric fog is made of
+    /// and updates the appropriate render resources. The scattering albedo is
+    /// given per RGB channel.
+    pub fn set_volumetric_fog_medium(
+        &mut self,
+        graphics_device: &GraphicsDevice,
+        gpu_resource_group_manager: &GPUResourceGroupManager,
+        extinction_coefficient: f32,
+        scattering_albedo: [f32; 3],
+        anisotropy: f32,
+    ) {
+        self.volumetric_fog_commands.set_medium(
+            graphics_device,
+            gpu_resource_group_manager,
+            extinction_coefficient,
+            scattering_albedo,
+            anisotropy,
+        );
+    }
+
+    pub fn volumetric_fog_enabled_mut(&mut self) -> &mut bool {
+        self.volumetric_fog_commands.enabled_mut()
+    }
+
     pub fn temporal_anti_aliasing_config(&self) -> &TemporalAntiAliasingConfig {
         self.temporal_anti_aliasing_commands.config()
     }
@@ -286,6 +436,16 @@ impl Postprocessor {
         &mut self.ambient_occlusion_commands
     }
 
+    pub fn screen_space_reflection_commands_mut(
+        &mut self,
+    ) -> &mut ScreenSpaceReflectionRenderCommands {
+        &mut self.screen_space_reflection_commands
+    }
+
+    pub fn volumetric_fog_commands_mut(&mut self) -> &mut VolumetricFogRenderCommands {
+        &mut self.volumetric_fog_commands
+    }
+
     pub fn temporal_anti_aliasing_commands_mut(
         &mut self,
     ) -> &mut TemporalAntiAliasingRenderCommands {
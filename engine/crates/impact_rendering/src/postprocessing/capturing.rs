@@ -2,7 +2,9 @@
 
 pub mod average_luminance;
 pub mod bloom;
+pub mod depth_of_field;
 pub mod dynamic_range_compression;
+pub mod motion_blur;
 
 use crate::{
     attachment::RenderAttachmentTextureManager, postprocessing::Postprocessor,
@@ -11,6 +13,7 @@ use crate::{
 use anyhow::Result;
 use average_luminance::{AverageLuminanceComputationConfig, AverageLuminanceComputeCommands};
 use bloom::{BloomConfig, BloomRenderCommands};
+use depth_of_field::{DepthOfFieldConfig, DepthOfFieldRenderCommands};
 use dynamic_range_compression::{
     DynamicRangeCompressionConfig, DynamicRangeCompressionRenderCommands,
 };
@@ -20,6 +23,7 @@ use impact_gpu::{
     storage::StorageGPUBufferManager, timestamp_query::TimestampQueryRegistry, wgpu,
 };
 use impact_math::bounds::{Bounds, UpperExclusiveBounds};
+use motion_blur::{MotionBlurConfig, MotionBlurRenderCommands};
 use roc_integration::roc;
 
 /// Configuration options for a capturing camera.
@@ -32,6 +36,10 @@ use roc_integration::roc;
 pub struct CapturingCameraConfig {
     /// The settings for the camera.
     pub settings: CameraSettings,
+    /// Configuration options for depth of field.
+    pub depth_of_field: DepthOfFieldConfig,
+    /// Configuration options for motion blur.
+    pub motion_blur: MotionBlurConfig,
     /// Configuration options for bloom.
     pub bloom: BloomConfig,
     /// Configuration options for the computation of the average luminance for
@@ -60,6 +68,23 @@ pub struct CameraSettings {
     /// # Unit
     /// Seconds.
     pub shutter_duration: f32,
+    /// The focal length of the lens. Together with the vertical field of view
+    /// of the camera projection, this determines the size of the sensor.
+    ///
+    /// # Unit
+    /// Meters.
+    pub focal_length: f32,
+    /// The distance from the camera to the plane in focus.
+    pub focus_distance: FocusDistance,
+    /// The fraction of the frame duration that the shutter is open, expressed
+    /// as the angle of a rotary disc shutter. An angle of 360 degrees blurs
+    /// moving objects over their full motion during the frame. This only
+    /// affects motion blur and is independent of the shutter duration used
+    /// for exposure.
+    ///
+    /// # Unit
+    /// Degrees.
+    pub shutter_angle: f32,
     /// The sensitivity of the camera sensor.
     pub sensitivity: SensorSensitivity,
     /// The minimum and maximum exposure of the camera sensor. These correspond
@@ -80,11 +105,23 @@ pub enum SensorSensitivity {
     Auto { ev_compensation: f32 },
 }
 
+/// The distance from the camera to the plane in focus, which may be set
+/// manually or determined automatically from the depth of the surfaces at the
+/// center of the view.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FocusDistance {
+    Manual { distance: f32 },
+    Auto,
+}
+
 /// A camera capturing the incident scene luminance.
 #[derive(Debug)]
 pub struct CapturingCamera {
     settings: CameraSettings,
     exposure: f32,
+    depth_of_field_commands: DepthOfFieldRenderCommands,
+    motion_blur_commands: MotionBlurRenderCommands,
     average_luminance_commands: AverageLuminanceComputeCommands,
     bloom_commands: BloomRenderCommands,
     dynamic_range_compression_commands: DynamicRangeCompressionRenderCommands,
@@ -95,6 +132,9 @@ impl Default for CameraSettings {
         Self {
             relative_aperture: 4.0,
             shutter_duration: 1.0 / 200.0,
+            focal_length: 0.05,
+            focus_distance: FocusDistance::Auto,
+            shutter_angle: 180.0,
             sensitivity: SensorSensitivity::Auto {
                 ev_compensation: 0.0,
             },
@@ -114,12 +154,18 @@ impl CameraSettings {
     pub fn new(
         relative_aperture: f32,
         shutter_duration: f32,
+        focal_length: f32,
+        focus_distance: FocusDistance,
+        shutter_angle: f32,
         sensitivity: SensorSensitivity,
         exposure_bounds: UpperExclusiveBounds<f32>,
     ) -> Self {
         Self {
             relative_aperture,
             shutter_duration,
+            focal_length,
+            focus_distance,
+            shutter_angle,
             sensitivity,
             exposure_bounds,
         }
@@ -154,6 +200,18 @@ impl CameraSettings {
         Ok(exposure.clamp(self.exposure_bounds.lower(), self.exposure_bounds.upper()))
     }
 
+    /// Computes the diameter of the aperture from the focal length and
+    /// relative aperture.
+    pub fn aperture_diameter(&self) -> f32 {
+        self.focal_length / self.relative_aperture
+    }
+
+    /// Computes the fraction of the frame duration that the shutter is open
+    /// from the shutter angle.
+    pub fn exposed_frame_fraction(&self) -> f32 {
+        self.shutter_angle.clamp(0.0, 360.0) / 360.0
+    }
+
     fn compute_exposure_value_at_100_iso(&self, iso: f32) -> f32 {
         f32::log2(self.relative_aperture.powi(2) * 100.0 / (self.shutter_duration * iso))
     }
@@ -211,6 +269,28 @@ impl CapturingCamera {
             storage_gpu_buffer_manager,
         )?;
 
+        let depth_of_field_commands = DepthOfFieldRenderCommands::new(
+            config.depth_of_field,
+            &config.settings,
+            graphics_device,
+            rendering_surface,
+            shader_manager,
+            render_attachment_texture_manager,
+            gpu_resource_group_manager,
+            bind_group_layout_registry,
+        )?;
+
+        let motion_blur_commands = MotionBlurRenderCommands::new(
+            config.motion_blur,
+            &config.settings,
+            graphics_device,
+            rendering_surface,
+            shader_manager,
+            render_attachment_texture_manager,
+            gpu_resource_group_manager,
+            bind_group_layout_registry,
+        )?;
+
         let bloom_commands = BloomRenderCommands::new(
             config.bloom,
             graphics_device,
@@ -241,6 +321,8 @@ impl CapturingCamera {
         Ok(Self {
             settings: config.settings,
             exposure: initial_exposure,
+            depth_of_field_commands,
+            motion_blur_commands,
             average_luminance_commands,
             bloom_commands,
             dynamic_range_compression_commands,
@@ -271,9 +353,30 @@ impl CapturingCamera {
         gpu_resource_group_manager: &GPUResourceGroupManager,
         storage_gpu_buffer_manager: &StorageGPUBufferManager,
         postprocessor: &Postprocessor,
+        frame_counter: u32,
         timestamp_recorder: &mut TimestampQueryRegistry<'_>,
         command_encoder: &mut wgpu::CommandEncoder,
     ) -> Result<()> {
+        self.depth_of_field_commands.record(
+            rendering_surface,
+            gpu_resources,
+            render_attachment_texture_manager,
+            gpu_resource_group_manager,
+            postprocessor,
+            frame_counter,
+            timestamp_recorder,
+            command_encoder,
+        )?;
+        self.motion_blur_commands.record(
+            rendering_surface,
+            gpu_resources,
+            render_attachment_texture_manager,
+            gpu_resource_group_manager,
+            postprocessor,
+            frame_counter,
+            timestamp_recorder,
+            command_encoder,
+        )?;
         self.average_luminance_commands.record(
             rendering_surface,
             gpu_resource_group_manager,
@@ -322,6 +425,25 @@ impl CapturingCamera {
         )
     }
 
+    /// Updates the lens and shutter parameters used for depth of field and
+    /// motion blur if the camera settings have changed.
+    pub fn sync_with_settings(
+        &mut self,
+        graphics_device: &GraphicsDevice,
+        gpu_resource_group_manager: &GPUResourceGroupManager,
+    ) {
+        self.depth_of_field_commands.sync_with_settings(
+            graphics_device,
+            gpu_resource_group_manager,
+            &self.settings,
+        );
+        self.motion_blur_commands.sync_with_settings(
+            graphics_device,
+            gpu_resource_group_manager,
+            &self.settings,
+        );
+    }
+
     /// Updates the exposure based on the current settings and potentially the
     /// average incident luminance.
     pub fn update_exposure(
@@ -358,6 +480,54 @@ impl CapturingCamera {
         );
     }
 
+    pub fn produces_depth_of_field_mut(&mut self) -> &mut bool {
+        self.depth_of_field_commands.enabled_mut()
+    }
+
+    pub fn depth_of_field_config(&self) -> &DepthOfFieldConfig {
+        self.depth_of_field_commands.config()
+    }
+
+    /// Sets the given depth of field configuration parameters and updates the
+    /// appropriate render resources.
+    pub fn set_depth_of_field_config(
+        &mut self,
+        graphics_device: &GraphicsDevice,
+        gpu_resource_group_manager: &GPUResourceGroupManager,
+        config: DepthOfFieldConfig,
+    ) {
+        self.depth_of_field_commands.set_config(
+            graphics_device,
+            gpu_resource_group_manager,
+            &self.settings,
+            config,
+        );
+    }
+
+    pub fn produces_motion_blur_mut(&mut self) -> &mut bool {
+        self.motion_blur_commands.enabled_mut()
+    }
+
+    pub fn motion_blur_config(&self) -> &MotionBlurConfig {
+        self.motion_blur_commands.config()
+    }
+
+    /// Sets the given motion blur configuration parameters and updates the
+    /// appropriate render resources.
+    pub fn set_motion_blur_config(
+        &mut self,
+        graphics_device: &GraphicsDevice,
+        gpu_resource_group_manager: &GPUResourceGroupManager,
+        config: MotionBlurConfig,
+    ) {
+        self.motion_blur_commands.set_config(
+            graphics_device,
+            gpu_resource_group_manager,
+            &self.settings,
+            config,
+        );
+    }
+
     pub fn produces_bloom_mut(&mut self) -> &mut bool {
         self.bloom_commands.enabled_mut()
     }
@@ -0,0 +1,292 @@
+//! Render passes for simulating depth of field.
+
+use crate::{
+    attachment::{RenderAttachmentQuantity, RenderAttachmentTextureManager},
+    postprocessing::{
+        Postprocessor,
+        capturing::{CameraSettings, FocusDistance},
+    },
+    render_command::{
+        postprocessing_pass::PostprocessingRenderPass,
+        render_attachment_texture_copy_command::RenderAttachmentTextureCopyCommand,
+    },
+    resource::BasicGPUResources,
+    shader_templates::depth_of_field::DepthOfFieldShaderTemplate,
+    surface::RenderingSurface,
+};
+use anyhow::Result;
+use bytemuck::{Pod, Zeroable};
+use impact_gpu::{
+    assert_uniform_valid,
+    bind_group_layout::BindGroupLayoutRegistry,
+    device::GraphicsDevice,
+    resource_group::{GPUResourceGroup, GPUResourceGroupID, GPUResourceGroupManager},
+    shader::ShaderManager,
+    timestamp_query::TimestampQueryRegistry,
+    uniform::{self, SingleUniformGPUBuffer, UniformBufferable},
+    wgpu,
+};
+use impact_math::{hash::ConstStringHash64, hash64};
+use std::{borrow::Cow, num::NonZeroU32};
+
+/// Configuration options for depth of field. The physical lens parameters
+/// determining the amount of blur are given by the [`CameraSettings`].
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+#[derive(Clone, Debug)]
+pub struct DepthOfFieldConfig {
+    /// Whether depth of field is enabled.
+    pub enabled: bool,
+    /// The number of luminance samples to gather for each pixel. More samples
+    /// give smoother blur at a higher cost.
+    pub n_samples: NonZeroU32,
+    /// The maximum radius of the blur, as a fraction of the image height.
+    /// Circles of confusion larger than this are clamped to this radius.
+    pub max_blur_radius: f32,
+}
+
+#[derive(Debug)]
+pub(super) struct DepthOfFieldRenderCommands {
+    blurring_pass: PostprocessingRenderPass,
+    copy_command: RenderAttachmentTextureCopyCommand,
+    parameters: DepthOfFieldParameters,
+    config: DepthOfFieldConfig,
+}
+
+/// Uniform holding parameters needed in the shader for simulating depth of
+/// field.
+///
+/// The size of this struct has to be a multiple of 16 bytes as required for
+/// uniforms.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Zeroable, Pod)]
+struct DepthOfFieldParameters {
+    aperture_diameter: f32,
+    focal_length: f32,
+    focus_distance: f32,
+    max_blur_radius: f32,
+    n_samples: u32,
+    _pad: [u8; 12],
+}
+
+impl Default for DepthOfFieldConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            n_samples: NonZeroU32::new(48).unwrap(),
+            max_blur_radius: 0.01,
+        }
+    }
+}
+
+impl DepthOfFieldRenderCommands {
+    pub(super) fn new(
+        config: DepthOfFieldConfig,
+        settings: &CameraSettings,
+        graphics_device: &GraphicsDevice,
+        rendering_surface: &RenderingSurface,
+        shader_manager: &mut ShaderManager,
+        render_attachment_texture_manager: &mut RenderAttachmentTextureManager,
+        gpu_resource_group_manager: &mut GPUResourceGroupManager,
+        bind_group_layout_registry: &BindGroupLayoutRegistry,
+    ) -> Result<Self> {
+        let parameters = DepthOfFieldParameters::new(&config, settings);
+
+        let blurring_pass = create_depth_of_field_render_pass(
+            graphics_device,
+            rendering_surface,
+            shader_manager,
+            render_attachment_texture_manager,
+            gpu_resource_group_manager,
+            bind_group_layout_registry,
+            &parameters,
+        )?;
+
+        let copy_command = RenderAttachmentTextureCopyCommand::new(
+            RenderAttachmentQuantity::LuminanceAux,
+            RenderAttachmentQuantity::Luminance,
+        );
+
+        Ok(Self {
+            blurring_pass,
+            copy_command,
+            parameters,
+            config,
+        })
+    }
+
+    pub(super) fn enabled_mut(&mut self) -> &mut bool {
+        &mut self.config.enabled
+    }
+
+    pub(super) fn config(&self) -> &DepthOfFieldConfig {
+        &self.config
+    }
+
+    /// Sets the given configuration parameters and updates the parameter
+    /// uniform if required.
+    pub(super) fn set_config(
+        &mut self,
+        graphics_device: &GraphicsDevice,
+        gpu_resource_group_manager: &GPUResourceGroupManager,
+        settings: &CameraSettings,
+        config: DepthOfFieldConfig,
+    ) {
+        self.config = config;
+        self.sync_with_settings(graphics_device, gpu_resource_group_manager, settings);
+    }
+
+    /// Updates the parameter uniform if the given camera settings imply
+    /// different lens parameters than the current ones.
+    pub(super) fn sync_with_settings(
+        &mut self,
+        graphics_device: &GraphicsDevice,
+        gpu_resource_group_manager: &GPUResourceGroupManager,
+        settings: &CameraSettings,
+    ) {
+        let parameters = DepthOfFieldParameters::new(&self.config, settings);
+        if parameters != self.parameters {
+            update_depth_of_field_parameters_uniform(
+                graphics_device,
+                gpu_resource_group_manager,
+                &parameters,
+            );
+            self.parameters = parameters;
+        }
+    }
+
+    pub(super) fn record(
+        &self,
+        rendering_surface: &RenderingSurface,
+        gpu_resources: &impl BasicGPUResources,
+        render_attachment_texture_manager: &RenderAttachmentTextureManager,
+        gpu_resource_group_manager: &GPUResourceGroupManager,
+        postprocessor: &Postprocessor,
+        frame_counter: u32,
+        timestamp_recorder: &mut TimestampQueryRegistry<'_>,
+        command_encoder: &mut wgpu::CommandEncoder,
+    ) -> Result<()> {
+        // The pass is skipped without a camera, in which case we must not copy
+        // the stale auxiliary luminance back
+        if !self.config.enabled || gpu_resources.camera().is_none() {
+            return Ok(());
+        }
+
+        self.blurring_pass.record(
+            rendering_surface,
+            None,
+            gpu_resources,
+            render_attachment_texture_manager,
+            gpu_resource_group_manager,
+            postprocessor,
+            frame_counter,
+            timestamp_recorder,
+            command_encoder,
+        )?;
+
+        self.copy_command
+            .record(render_attachment_texture_manager, command_encoder);
+
+        Ok(())
+    }
+}
+
+impl DepthOfFieldParameters {
+    fn new(config: &DepthOfFieldConfig, settings: &CameraSettings) -> Self {
+        let focus_distance = match settings.focus_distance {
+            FocusDistance::Manual { distance } => distance,
+            FocusDistance::Auto => 0.0,
+        };
+        Self {
+            aperture_diameter: settings.aperture_diameter(),
+            focal_length: settings.focal_length,
+            focus_distance,
+            max_blur_radius: config.max_blur_radius,
+            n_samples: config.n_samples.get(),
+            _pad: [0; 12],
+        }
+    }
+}
+
+impl UniformBufferable for DepthOfFieldParameters {
+    const ID: ConstStringHash64 = ConstStringHash64::new("Depth of field parameters");
+
+    fn create_bind_group_layout_entry(
+        binding: u32,
+        visibility: wgpu::ShaderStages,
+    ) -> wgpu::BindGroupLayoutEntry {
+        uniform::create_uniform_buffer_bind_group_layout_entry(binding, visibility)
+    }
+}
+assert_uniform_valid!(DepthOfFieldParameters);
+
+/// Creates a [`PostprocessingRenderPass`] that blurs the luminance attachment
+/// according to the circle of confusion of each pixel, writing the result to
+/// the auxiliary luminance attachment.
+fn create_depth_of_field_render_pass(
+    graphics_device: &GraphicsDevice,
+    rendering_surface: &RenderingSurface,
+    shader_manager: &mut ShaderManager,
+    render_attachment_texture_manager: &mut RenderAttachmentTextureManager,
+    gpu_resource_group_manager: &mut GPUResourceGroupManager,
+    bind_group_layout_registry: &BindGroupLayoutRegistry,
+    parameters: &DepthOfFieldParameters,
+) -> Result<PostprocessingRenderPass> {
+    let resource_group_id = depth_of_field_parameters_resource_group_id();
+
+    gpu_resource_group_manager
+        .resource_group_entry(resource_group_id)
+        .or_insert_with(|| {
+            let parameter_uniform_buffer = SingleUniformGPUBuffer::for_uniform(
+                graphics_device,
+                parameters,
+                wgpu::ShaderStages::FRAGMENT,
+                Cow::Borrowed("Depth of field parameters"),
+            );
+
+            GPUResourceGroup::new(
+                graphics_device,
+                vec![parameter_uniform_buffer],
+                &[],
+                &[],
+                &[],
+                wgpu::ShaderStages::FRAGMENT,
+                "Depth of field resources",
+            )
+        });
+
+    let shader_template = DepthOfFieldShaderTemplate::new(resource_group_id);
+
+    PostprocessingRenderPass::new(
+        graphics_device,
+        rendering_surface,
+        shader_manager,
+        render_attachment_texture_manager,
+        gpu_resource_group_manager,
+        bind_group_layout_registry,
+        &shader_template,
+        Cow::Borrowed("Depth of field pass"),
+    )
+}
+
+fn update_depth_of_field_parameters_uniform(
+    graphics_device: &GraphicsDevice,
+    gpu_resource_group_manager: &GPUResourceGroupManager,
+    uniform: &DepthOfFieldParameters,
+) {
+    let resource_group_id = depth_of_field_parameters_resource_group_id();
+    let resource_group = gpu_resource_group_manager
+        .get_resource_group(resource_group_id)
+        .expect("Depth of field parameters resource group should not be missing during update");
+    let buffer = resource_group
+        .single_uniform_buffer(0)
+        .expect("Depth of field parameters resource group should have single uniform buffer");
+    buffer.update_uniform(graphics_device, uniform);
+}
+
+fn depth_of_field_parameters_resource_group_id() -> GPUResourceGroupID {
+    GPUResourceGroupID(hash64!("DepthOfFieldParameters"))
+}
@@ -0,0 +1,279 @@
+//! Render passes for simulating motion blur.
+
+use crate::{
+    attachment::{RenderAttachmentQuantity, RenderAttachmentTextureManager},
+    postprocessing::{Postprocessor, capturing::CameraSettings},
+    render_command::{
+        postprocessing_pass::PostprocessingRenderPass,
+        render_attachment_texture_copy_command::RenderAttachmentTextureCopyCommand,
+    },
+    resource::BasicGPUResources,
+    shader_templates::motion_blur::MotionBlurShaderTemplate,
+    surface::RenderingSurface,
+};
+use anyhow::Result;
+use bytemuck::{Pod, Zeroable};
+use impact_gpu::{
+    assert_uniform_valid,
+    bind_group_layout::BindGroupLayoutRegistry,
+    device::GraphicsDevice,
+    resource_group::{GPUResourceGroup, GPUResourceGroupID, GPUResourceGroupManager},
+    shader::ShaderManager,
+    timestamp_query::TimestampQueryRegistry,
+    uniform::{self, SingleUniformGPUBuffer, UniformBufferable},
+    wgpu,
+};
+use impact_math::{hash::ConstStringHash64, hash64};
+use std::{borrow::Cow, num::NonZeroU32};
+
+/// Configuration options for motion blur. The length of the blur relative to
+/// the motion over a frame is determined by the shutter angle in the
+/// [`CameraSettings`].
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+#[derive(Clone, Debug)]
+pub struct MotionBlurConfig {
+    /// Whether motion blur is enabled.
+    pub enabled: bool,
+    /// The number of luminance samples to gather along the motion path of each
+    /// pixel.
+    pub n_samples: NonZeroU32,
+    /// The maximum length of the blur, as a fraction of the image height.
+    pub max_blur_length: f32,
+}
+
+#[derive(Debug)]
+pub(super) struct MotionBlurRenderCommands {
+    blurring_pass: PostprocessingRenderPass,
+    copy_command: RenderAttachmentTextureCopyCommand,
+    parameters: MotionBlurParameters,
+    config: MotionBlurConfig,
+}
+
+/// Uniform holding parameters needed in the shader for simulating motion
+/// blur.
+///
+/// The size of this struct has to be a multiple of 16 bytes as required for
+/// uniforms.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Zeroable, Pod)]
+struct MotionBlurParameters {
+    exposed_frame_fraction: f32,
+    max_blur_length: f32,
+    n_samples: u32,
+    _pad: [u8; 4],
+}
+
+impl Default for MotionBlurConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            n_samples: NonZeroU32::new(16).unwrap(),
+            max_blur_length: 0.05,
+        }
+    }
+}
+
+impl MotionBlurRenderCommands {
+    pub(super) fn new(
+        config: MotionBlurConfig,
+        settings: &CameraSettings,
+        graphics_device: &GraphicsDevice,
+        rendering_surface: &RenderingSurface,
+        shader_manager: &mut ShaderManager,
+        render_attachment_texture_manager: &mut RenderAttachmentTextureManager,
+        gpu_resource_group_manager: &mut GPUResourceGroupManager,
+        bind_group_layout_registry: &BindGroupLayoutRegistry,
+    ) -> Result<Self> {
+        let parameters = MotionBlurParameters::new(&config, settings);
+
+        let blurring_pass = create_motion_blur_render_pass(
+            graphics_device,
+            rendering_surface,
+            shader_manager,
+            render_attachment_texture_manager,
+            gpu_resource_group_manager,
+            bind_group_layout_registry,
+            &parameters,
+        )?;
+
+        let copy_command = RenderAttachmentTextureCopyCommand::new(
+            RenderAttachmentQuantity::LuminanceAux,
+            RenderAttachmentQuantity::Luminance,
+        );
+
+        Ok(Self {
+            blurring_pass,
+            copy_command,
+            parameters,
+            config,
+        })
+    }
+
+    pub(super) fn enabled_mut(&mut self) -> &mut bool {
+        &mut self.config.enabled
+    }
+
+    pub(super) fn config(&self) -> &MotionBlurConfig {
+        &self.config
+    }
+
+    /// Sets the given configuration parameters and updates the parameter
+    /// uniform if required.
+    pub(super) fn set_config(
+        &mut self,
+        graphics_device: &GraphicsDevice,
+        gpu_resource_group_manager: &GPUResourceGroupManager,
+        settings: &CameraSettings,
+        config: MotionBlurConfig,
+    ) {
+        self.config = config;
+        self.sync_with_settings(graphics_device, gpu_resource_group_manager, settings);
+    }
+
+    /// Updates the parameter uniform if the given camera settings imply a
+    /// different shutter angle than the current one.
+    pub(super) fn sync_with_settings(
+        &mut self,
+        graphics_device: &GraphicsDevice,
+        gpu_resource_group_manager: &GPUResourceGroupManager,
+        settings: &CameraSettings,
+    ) {
+        let parameters = MotionBlurParameters::new(&self.config, settings);
+        if parameters != self.parameters {
+            update_motion_blur_parameters_uniform(
+                graphics_device,
+                gpu_resource_group_manager,
+                &parameters,
+            );
+            self.parameters = parameters;
+        }
+    }
+
+    pub(super) fn record(
+        &self,
+        rendering_surface: &RenderingSurface,
+        gpu_resources: &impl BasicGPUResources,
+        render_attachment_texture_manager: &RenderAttachmentTextureManager,
+        gpu_resource_group_manager: &GPUResourceGroupManager,
+        postprocessor: &Postprocessor,
+        frame_counter: u32,
+        timestamp_recorder: &mut TimestampQueryRegistry<'_>,
+        command_encoder: &mut wgpu::CommandEncoder,
+    ) -> Result<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        self.blurring_pass.record(
+            rendering_surface,
+            None,
+            gpu_resources,
+            render_attachment_texture_manager,
+            gpu_resource_group_manager,
+            postprocessor,
+            frame_counter,
+            timestamp_recorder,
+            command_encoder,
+        )?;
+
+        self.copy_command
+            .record(render_attachment_texture_manager, command_encoder);
+
+        Ok(())
+    }
+}
+
+impl MotionBlurParameters {
+    fn new(config: &MotionBlurConfig, settings: &CameraSettings) -> Self {
+        Self {
+            exposed_frame_fraction: settings.exposed_frame_fraction(),
+            max_blur_length: config.max_blur_length,
+            n_samples: config.n_samples.get(),
+            _pad: [0; 4],
+        }
+    }
+}
+
+impl UniformBufferable for MotionBlurParameters {
+    const ID: ConstStringHash64 = ConstStringHash64::new("Motion blur parameters");
+
+    fn create_bind_group_layout_entry(
+        binding: u32,
+        visibility: wgpu::ShaderStages,
+    ) -> wgpu::BindGroupLayoutEntry {
+        uniform::create_uniform_buffer_bind_group_layout_entry(binding, visibility)
+    }
+}
+assert_uniform_valid!(MotionBlurParameters);
+
+/// Creates a [`PostprocessingRenderPass`] that blurs the luminance attachment
+/// along the motion vector of each pixel, writing the result to the auxiliary
+/// luminance attachment.
+fn create_motion_blur_render_pass(
+    graphics_device: &GraphicsDevice,
+    rendering_surface: &RenderingSurface,
+    shader_manager: &mut ShaderManager,
+    render_attachment_texture_manager: &mut RenderAttachmentTextureManager,
+    gpu_resource_group_manager: &mut GPUResourceGroupManager,
+    bind_group_layout_registry: &BindGroupLayoutRegistry,
+    parameters: &MotionBlurParameters,
+) -> Result<PostprocessingRenderPass> {
+    let resource_group_id = motion_blur_parameters_resource_group_id();
+
+    gpu_resource_group_manager
+        .resource_group_entry(resource_group_id)
+        .or_insert_with(|| {
+            let parameter_uniform_buffer = SingleUniformGPUBuffer::for_uniform(
+                graphics_device,
+                parameters,
+                wgpu::ShaderStages::FRAGMENT,
+                Cow::Borrowed("Motion blur parameters"),
+            );
+
+            GPUResourceGroup::new(
+                graphics_device,
+                vec![parameter_uniform_buffer],
+                &[],
+                &[],
+                &[],
+                wgpu::ShaderStages::FRAGMENT,
+                "Motion blur resources",
+            )
+        });
+
+    let shader_template = MotionBlurShaderTemplate::new(resource_group_id);
+
+    PostprocessingRenderPass::new(
+        graphics_device,
+        rendering_surface,
+        shader_manager,
+        render_attachment_texture_manager,
+        gpu_resource_group_manager,
+        bind_group_layout_registry,
+        &shader_template,
+        Cow::Borrowed("Motion blur pass"),
+    )
+}
+
+fn update_motion_blur_parameters_uniform(
+    graphics_device: &GraphicsDevice,
+    gpu_resource_group_manager: &GPUResourceGroupManager,
+    uniform: &MotionBlurParameters,
+) {
+    let resource_group_id = motion_blur_parameters_resource_group_id();
+    let resource_group = gpu_resource_group_manager
+        .get_resource_group(resource_group_id)
+        .expect("Motion blur parameters resource group should not be missing during update");
+    let buffer = resource_group
+        .single_uniform_buffer(0)
+        .expect("Motion blur parameters resource group should have single uniform buffer");
+    buffer.update_uniform(graphics_device, uniform);
+}
+
+fn motion_blur_parameters_resource_group_id() -> GPUResourceGroupID {
+    GPUResourceGroupID(hash64!("MotionBlurParameters"))
+}
@@ -0,0 +1,953 @@
+//! Render passes for computing and applying screen-space reflections.
+
+use crate::{
+    attachment::{
+        RenderAttachmentDescription, RenderAttachmentInputDescription,
+        RenderAttachmentInputDescriptionSet, RenderAttachmentOutputDescriptionSet,
+        RenderAttachmentQuantity::{self, HierarchicalDepth, LinearDepth},
+        RenderAttachmentTextureManager,
+    },
+    postprocessing::Postprocessor,
+    push_constant::{BasicPushConstantGroup, BasicPushConstantVariant},
+    render_command::{
+        self, STANDARD_FRONT_FACE, StencilValue, begin_single_render_pass,
+        postprocessing_pass::{
+            PostprocessingRenderPass, create_postprocessing_render_pipeline,
+            create_postprocessing_render_pipeline_layout,
+        },
+    },
+    resource::BasicGPUResources,
+    shader_templates::{
+        hierarchical_depth::HierarchicalDepthShaderTemplate,
+        screen_space_reflection_application::ScreenSpaceReflectionApplicationShaderTemplate,
+        screen_space_reflection_temporal_resolve::ScreenSpaceReflectionTemporalResolveShaderTemplate,
+        screen_space_reflection_tracing::ScreenSpaceReflectionTracingShaderTemplate,
+    },
+    surface::RenderingSurface,
+};
+use anyhow::{Result, anyhow};
+use approx::abs_diff_ne;
+use bytemuck::{Pod, Zeroable};
+use impact_camera::gpu_resource::CameraGPUResource;
+use impact_gpu::{
+    assert_uniform_valid,
+    bind_group_layout::BindGroupLayoutRegistry,
+    device::GraphicsDevice,
+    resource_group::{GPUResourceGroup, GPUResourceGroupID, GPUResourceGroupManager},
+    shader::ShaderManager,
+    timestamp_query::TimestampQueryRegistry,
+    uniform::{self, SingleUniformGPUBuffer, UniformBufferable},
+    wgpu,
+};
+use impact_math::{hash::ConstStringHash64, hash64};
+use impact_mesh::{self, VertexAttributeSet, VertexPosition, gpu_resource::VertexBufferable};
+use impact_scene::skybox::Skybox;
+use std::borrow::Cow;
+
+/// Configuration options for screen-space reflections.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+#[derive(Clone, Debug)]
+pub struct SSRConfig {
+    /// Whether screen-space reflections are enabled.
+    pub enabled: bool,
+    /// The maximum number of steps to take through the hierarchical depth
+    /// pyramid along each reflected ray.
+    pub max_step_count: u32,
+    /// The maximum distance a reflected ray will be marched before it is
+    /// considered to have missed.
+    pub max_distance: f32,
+    /// How far behind the depth buffer surface a ray can be while still being
+    /// considered to intersect the surface.
+    pub thickness: f32,
+    /// The roughness above which surfaces receive no screen-space reflections.
+    /// Reflections are faded out smoothly as the roughness approaches this
+    /// value.
+    pub roughness_cutoff: f32,
+    /// How much the reflections traced in the current frame should be
+    /// weighted compared to the reflections accumulated over previous frames.
+    pub current_frame_weight: f32,
+    /// The maximum variance allowed between the current and accumulated
+    /// reflections when performing temporal blending.
+    pub variance_clipping_threshold: f32,
+}
+
+#[derive(Debug)]
+pub struct ScreenSpaceReflectionRenderCommands {
+    hierarchical_depth_passes: HierarchicalDepthPasses,
+    tracing_pass: ScreenSpaceReflectionTracingPass,
+    temporal_resolve_pass: PostprocessingRenderPass,
+    application_pass: PostprocessingRenderPass,
+    config: SSRConfig,
+}
+
+/// Passes for building the hierarchical depth pyramid traversed by the
+/// tracing pass. The first pass copies the linear depth into the base level of
+/// the pyramid, and each subsequent pass writes the minimum of the depths in
+/// the level below to the next level.
+#[derive(Debug)]
+struct HierarchicalDepthPasses {
+    input_descriptions: RenderAttachmentInputDescriptionSet,
+    base_level_pipeline: wgpu::RenderPipeline,
+    downsampling_pipeline: wgpu::RenderPipeline,
+}
+
+/// Pass for tracing reflected rays through the hierarchical depth pyramid and
+/// writing the reflected luminance to the auxiliary luminance attachment.
+///
+/// Unlike the generic [`PostprocessingRenderPass`], this pass has to bind the
+/// skybox resources when a skybox is present so that rays missing all on-screen
+/// geometry can fall back to the skybox, so it keeps a separate pipeline for
+/// that case.
+#[derive(Debug)]
+struct ScreenSpaceReflectionTracingPass {
+    push_constants: BasicPushConstantGroup,
+    input_render_attachments: RenderAttachmentInputDescriptionSet,
+    output_render_attachments: RenderAttachmentOutputDescriptionSet,
+    push_constant_ranges: Vec<wgpu::PushConstantRange>,
+    color_target_state: wgpu::ColorTargetState,
+    depth_stencil_state: wgpu::DepthStencilState,
+    pipeline: wgpu::RenderPipeline,
+    skybox_pipeline: Option<wgpu::RenderPipeline>,
+    skybox: Option<Skybox>,
+}
+
+/// Uniform holding parameters needed in the shaders for computing and applying
+/// screen-space reflections.
+///
+/// The size of this struct has to be a multiple of 16 bytes as required for
+/// uniforms.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Zeroable, Pod)]
+struct ScreenSpaceReflectionParameters {
+    max_step_count: u32,
+    max_distance: f32,
+    thickness: f32,
+    roughness_cutoff: f32,
+    current_frame_weight: f32,
+    variance_clipping_threshold: f32,
+    _pad: [u8; 8],
+}
+
+impl SSRConfig {
+    fn new_config_requires_resource_update(&self, other: &Self) -> bool {
+        self.max_step_count != other.max_step_count
+            || abs_diff_ne!(self.max_distance, other.max_distance, epsilon = 1e-6)
+            || abs_diff_ne!(self.thickness, other.thickness, epsilon = 1e-6)
+            || abs_diff_ne!(
+                self.roughness_cutoff,
+                other.roughness_cutoff,
+                epsilon = 1e-6
+            )
+            || abs_diff_ne!(
+                self.current_frame_weight,
+                other.current_frame_weight,
+                epsilon = 1e-6
+            )
+            || abs_diff_ne!(
+                self.variance_clipping_threshold,
+                other.variance_clipping_threshold,
+                epsilon = 1e-6
+            )
+    }
+}
+
+impl Default for SSRConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_step_count: 64,
+            max_distance: 20.0,
+            thickness: 0.2,
+            roughness_cutoff: 0.6,
+            current_frame_weight: 0.1,
+            variance_clipping_threshold: 1.0,
+        }
+    }
+}
+
+impl ScreenSpaceReflectionRenderCommands {
+    pub(super) fn new(
+        config: SSRConfig,
+        graphics_device: &GraphicsDevice,
+        rendering_surface: &RenderingSurface,
+        shader_manager: &mut ShaderManager,
+        render_attachment_texture_manager: &mut RenderAttachmentTextureManager,
+        gpu_resource_group_manager: &mut GPUResourceGroupManager,
+        bind_group_layout_registry: &BindGroupLayoutRegistry,
+    ) -> Result<Self> {
+        let resource_group_id = screen_space_reflection_parameters_resource_group_id();
+
+        gpu_resource_group_manager
+            .resource_group_entry(resource_group_id)
+            .or_insert_with(|| {
+                let parameter_uniform = ScreenSpaceReflectionParameters::new(&config);
+
+                let parameter_uniform_buffer = SingleUniformGPUBuffer::for_uniform(
+                    graphics_device,
+                    &parameter_uniform,
+                    wgpu::ShaderStages::FRAGMENT,
+                    Cow::Borrowed("Screen-space reflection parameters"),
+                );
+
+                GPUResourceGroup::new(
+                    graphics_device,
+                    vec![parameter_uniform_buffer],
+                    &[],
+                    &[],
+                    &[],
+                    wgpu::ShaderStages::FRAGMENT,
+                    "Screen-space reflection resources",
+                )
+            });
+
+        let hierarchical_depth_passes = HierarchicalDepthPasses::new(
+            graphics_device,
+            shader_manager,
+            render_attachment_texture_manager,
+        );
+
+        let tracing_pass = ScreenSpaceReflectionTracingPass::new(
+            graphics_device,
+            shader_manager,
+            render_attachment_texture_manager,
+            gpu_resource_group_manager,
+            bind_group_layout_registry,
+        )?;
+
+        let temporal_resolve_pass = PostprocessingRenderPass::new(
+            graphics_device,
+            rendering_surface,
+            shader_manager,
+            render_attachment_texture_manager,
+            gpu_resource_group_manager,
+            bind_group_layout_registry,
+            &ScreenSpaceReflectionTemporalResolveShaderTemplate::new(resource_group_id),
+            Cow::Borrowed("Screen-space reflection temporal resolve pass"),
+        )?;
+
+        let application_pass = PostprocessingRenderPass::new(
+            graphics_device,
+            rendering_surface,
+            shader_manager,
+            render_attachment_texture_manager,
+            gpu_resource_group_manager,
+            bind_group_layout_registry,
+            &ScreenSpaceReflectionApplicationShaderTemplate::new(resource_group_id),
+            Cow::Borrowed("Screen-space reflection application pass"),
+        )?;
+
+        Ok(Self {
+            hierarchical_depth_passes,
+            tracing_pass,
+            temporal_resolve_pass,
+            application_pass,
+            config,
+        })
+    }
+
+    pub fn enabled_mut(&mut self) -> &mut bool {
+        &mut self.config.enabled
+    }
+
+    pub(super) fn config(&self) -> &SSRConfig {
+        &self.config
+    }
+
+    pub(super) fn set_config(
+        &mut self,
+        graphics_device: &GraphicsDevice,
+        gpu_resource_group_manager: &GPUResourceGroupManager,
+        config: SSRConfig,
+    ) {
+        if self.config.new_config_requires_resource_update(&config) {
+            update_screen_space_reflection_parameters_uniform(
+                graphics_device,
+                gpu_resource_group_manager,
+                &ScreenSpaceReflectionParameters::new(&config),
+            );
+        }
+        self.config = config;
+    }
+
+    pub(super) fn sync_with_render_resources(
+        &mut self,
+        graphics_device: &GraphicsDevice,
+        shader_manager: &mut ShaderManager,
+        render_attachment_texture_manager: &RenderAttachmentTextureManager,
+        gpu_resource_group_manager: &GPUResourceGroupManager,
+        bind_group_layout_registry: &BindGroupLayoutRegistry,
+        gpu_resources: &impl BasicGPUResources,
+    ) -> Result<()> {
+        self.tracing_pass.sync_with_render_resources(
+            graphics_device,
+            shader_manager,
+            render_attachment_texture_manager,
+            gpu_resource_group_manager,
+            bind_group_layout_registry,
+            gpu_resources,
+        )
+    }
+
+    pub(super) fn record(
+        &self,
+        rendering_surface: &RenderingSurface,
+        gpu_resources: &impl BasicGPUResources,
+        render_attachment_texture_manager: &RenderAttachmentTextureManager,
+        gpu_resource_group_manager: &GPUResourceGroupManager,
+        postprocessor: &Postprocessor,
+        frame_counter: u32,
+        timestamp_recorder: &mut TimestampQueryRegistry<'_>,
+        command_encoder: &mut wgpu::CommandEncoder,
+    ) -> Result<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        self.hierarchical_depth_passes.record(
+            gpu_resources,
+            render_attachment_texture_manager,
+            timestamp_recorder,
+            command_encoder,
+        )?;
+
+        self.tracing_pass.record(
+            rendering_surface,
+            gpu_resources,
+            render_attachment_texture_manager,
+            gpu_resource_group_manager,
+            postprocessor,
+            frame_counter,
+            timestamp_recorder,
+            command_encoder,
+        )?;
+
+        self.temporal_resolve_pass.record(
+            rendering_surface,
+            None,
+            gpu_resources,
+            render_attachment_texture_manager,
+            gpu_resource_group_manager,
+            postprocessor,
+            frame_counter,
+            timestamp_recorder,
+            command_encoder,
+        )?;
+
+        self.application_pass.record(
+            rendering_surface,
+            None,
+            gpu_resources,
+            render_attachment_texture_manager,
+            gpu_resource_group_manager,
+            postprocessor,
+            frame_counter,
+            timestamp_recorder,
+            command_encoder,
+        )
+    }
+}
+
+impl HierarchicalDepthPasses {
+    fn new(
+        graphics_device: &GraphicsDevice,
+        shader_manager: &mut ShaderManager,
+        render_attachment_texture_manager: &mut RenderAttachmentTextureManager,
+    ) -> Self {
+        let n_levels = HierarchicalDepth.max_mip_level() + 1;
+
+        let mut input_descriptions =
+            RenderAttachmentInputDescriptionSet::with_capacity(n_levels as usize);
+
+        // The input for the base level is the linear depth attachment
+        input_descriptions
+            .insert_description(RenderAttachmentInputDescription::default_for(LinearDepth));
+
+        // The input for every other level is the level below it
+        for mip_level in 0..(n_levels - 1) {
+            input_descriptions.insert_description(
+                RenderAttachmentInputDescription::default_for(HierarchicalDepth)
+                    .with_mip_level(mip_level),
+            );
+        }
+
+        render_attachment_texture_manager
+            .create_missing_bind_groups_and_layouts(graphics_device, &input_descriptions);
+
+        let color_target_state = wgpu::ColorTargetState {
+            format: HierarchicalDepth.texture_format(),
+            blend: None,
+            write_mask: wgpu::ColorWrites::ALL,
+        };
+
+        let base_level_pipeline = Self::create_pipeline(
+            graphics_device,
+            shader_manager,
+            render_attachment_texture_manager,
+            &input_descriptions.descriptions()[0],
+            &HierarchicalDepthShaderTemplate::base_level(),
+            &color_target_state,
+            "hierarchical depth base level",
+        );
+
+        // Mip level does not affect the layout, so all downsampling passes can
+        // use the same pipeline
+        let downsampling_pipeline = Self::create_pipeline(
+            graphics_device,
+            shader_manager,
+            render_attachment_texture_manager,
+            &input_descriptions.descriptions()[1],
+            &HierarchicalDepthShaderTemplate::downsampling(),
+            &color_target_state,
+            "hierarchical depth downsampling",
+        );
+
+        Self {
+            input_descriptions,
+            base_level_pipeline,
+            downsampling_pipeline,
+        }
+    }
+
+    fn create_pipeline(
+        graphics_device: &GraphicsDevice,
+        shader_manager: &mut ShaderManager,
+        render_attachment_texture_manager: &RenderAttachmentTextureManager,
+        input_description: &RenderAttachmentInputDescription,
+        shader_template: &HierarchicalDepthShaderTemplate,
+        color_target_state: &wgpu::ColorTargetState,
+        label: &str,
+    ) -> wgpu::RenderPipeline {
+        let bind_group_layout = render_attachment_texture_manager
+            .get_render_attachment_texture_bind_group_layout(input_description)
+            .unwrap();
+
+        let pipeline_layout = create_postprocessing_render_pipeline_layout(
+            graphics_device.device(),
+            &[bind_group_layout],
+            &[],
+            label,
+        );
+
+        let (_, shader) = shader_manager
+            .get_or_create_rendering_shader_from_template(graphics_device, shader_template);
+
+        create_postprocessing_render_pipeline(
+            graphics_device,
+            &pipeline_layout,
+            shader,
+            &[Some(color_target_state.clone())],
+            None,
+            label,
+        )
+    }
+
+    fn record(
+        &self,
+        gpu_resources: &impl BasicGPUResources,
+        render_attachment_texture_manager: &RenderAttachmentTextureManager,
+        timestamp_recorder: &mut TimestampQueryRegistry<'_>,
+        command_encoder: &mut wgpu::CommandEncoder,
+    ) -> Result<()> {
+        let hierarchical_depth_texture =
+            render_attachment_texture_manager.render_attachment_texture(HierarchicalDepth);
+
+        // The texture may have fewer mip levels than the maximum if the window
+        // is small
+        let n_levels = hierarchical_depth_texture
+            .texture()
+            .texture()
+            .mip_level_count() as usize;
+
+        let mesh_id = impact_mesh::builtin::screen_filling_quad_mesh_id();
+
+        let mesh_gpu_resources = gpu_resources
+            .triangle_mesh()
+            .get(mesh_id)
+            .ok_or_else(|| anyhow!("Missing GPU resources for mesh {}", mesh_id))?;
+
+        let position_buffer = mesh_gpu_resources
+            .request_vertex_gpu_buffers(VertexAttributeSet::POSITION)?
+            .next()
+            .unwrap();
+
+        let ([mut first_timestamp_writes, mut last_timestamp_writes], _timestamp_span_guard) =
+            timestamp_recorder.register_timestamp_writes_for_first_and_last_of_render_passes(
+                n_levels,
+                Cow::Borrowed("Hierarchical depth passes"),
+            );
+
+        for (output_mip_level, input_description) in self
+            .input_descriptions
+            .descriptions()
+            .iter()
+            .take(n_levels)
+            .enumerate()
+        {
+            let color_attachment = wgpu::RenderPassColorAttachment {
+                view: hierarchical_depth_texture
+                    .texture_view(output_mip_level as u32)
+                    .unwrap(),
+                depth_slice: None,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::WHITE),
+                    store: wgpu::StoreOp::Store,
+                },
+            };
+
+            let timestamp_writes = if output_mip_level == 0 {
+                first_timestamp_writes.take()
+            } else if output_mip_level + 1 == n_levels {
+                last_timestamp_writes.take()
+            } else {
+                None
+            };
+
+            let mut render_pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                color_attachments: &[Some(color_attachment)],
+                depth_stencil_attachment: None,
+                timestamp_writes,
+                occlusion_query_set: None,
+                label: Some(&format!(
+                    "Hierarchical depth pass for mip level {output_mip_level}"
+                )),
+            });
+
+            if output_mip_level == 0 {
+                render_pass.set_pipeline(&self.base_level_pipeline);
+            } else {
+                render_pass.set_pipeline(&self.downsampling_pipeline);
+            }
+
+            let bind_group = render_attachment_texture_manager
+                .get_render_attachment_texture_bind_group(input_description)
+                .unwrap();
+            render_pass.set_bind_group(0, bind_group, &[]);
+
+            render_pass.set_vertex_buffer(0, position_buffer.valid_buffer_slice());
+
+            render_pass.set_index_buffer(
+                mesh_gpu_resources
+                    .triangle_mesh_index_gpu_buffer()
+                    .valid_buffer_slice(),
+                mesh_gpu_resources.triangle_mesh_index_format(),
+            );
+
+            render_pass.draw_indexed(
+                0..u32::try_from(mesh_gpu_resources.n_indices()).unwrap(),
+                0,
+                0..1,
+            );
+
+            log::trace!("Recorded hierarchical depth pass for mip level {output_mip_level}");
+        }
+
+        Ok(())
+    }
+}
+
+impl ScreenSpaceReflectionTracingPass {
+    fn new(
+        graphics_device: &GraphicsDevice,
+        shader_manager: &mut ShaderManager,
+        render_attachment_texture_manager: &mut RenderAttachmentTextureManager,
+        gpu_resource_group_manager: &GPUResourceGroupManager,
+        bind_group_layout_registry: &BindGroupLayoutRegistry,
+    ) -> Result<Self> {
+        let push_constants = ScreenSpaceReflectionTracingShaderTemplate::push_constants();
+        let input_render_attachments =
+            ScreenSpaceReflectionTracingShaderTemplate::input_render_attachments();
+        let output_render_attachments =
+            ScreenSpaceReflectionTracingShaderTemplate::output_render_attachments();
+
+        let push_constant_ranges = push_constants.create_ranges();
+
+        let output_description = output_render_attachments
+            .only_description_for_quantity(RenderAttachmentQuantity::LuminanceAux)
+            .unwrap();
+
+        let color_target_state = wgpu::ColorTargetState {
+            format: RenderAttachmentQuantity::LuminanceAux.texture_format(),
+            blend: Some(wgpu::BlendState::REPLACE),
+            write_mask: output_description.write_mask(),
+        };
+
+        let depth_stencil_state =
+            render_command::depth_stencil_state_for_stencil_testing(wgpu::CompareFunction::Equal);
+
+        // Make sure the bind group layouts for the input attachments exist
+        render_attachment_texture_manager
+            .create_and_get_render_attachment_texture_bind_group_layouts(
+                graphics_device,
+                &input_render_attachments,
+            )
+            .for_each(drop);
+
+        let pipeline = Self::create_pipeline(
+            graphics_device,
+            shader_manager,
+            render_attachment_texture_manager,
+            gpu_resource_group_manager,
+            bind_group_layout_registry,
+            &input_render_attachments,
+            &push_constant_ranges,
+            &color_target_state,
+            &depth_stencil_state,
+            None,
+        )?;
+
+        Ok(Self {
+            push_constants,
+            input_render_attachments,
+            output_render_attachments,
+            push_constant_ranges,
+            color_target_state,
+            depth_stencil_state,
+            pipeline,
+            skybox_pipeline: None,
+            skybox: None,
+        })
+    }
+
+    fn sync_with_render_resources(
+        &mut self,
+        graphics_device: &GraphicsDevice,
+        shader_manager: &mut ShaderManager,
+        render_attachment_texture_manager: &RenderAttachmentTextureManager,
+        gpu_resource_group_manager: &GPUResourceGroupManager,
+        bind_group_layout_registry: &BindGroupLayoutRegistry,
+        gpu_resources: &impl BasicGPUResources,
+    ) -> Result<()> {
+        match (self.skybox.as_ref(), gpu_resources.skybox()) {
+            (Some(&skybox), Some(skybox_gpu_resources))
+                if skybox == skybox_gpu_resources.skybox() => {}
+            (_, None) => {
+                self.skybox_pipeline = None;
+                self.skybox = None;
+            }
+            (_, Some(skybox_gpu_resources)) => {
+                self.skybox_pipeline = Some(Self::create_pipeline(
+                    graphics_device,
+                    shader_manager,
+                    render_attachment_texture_manager,
+                    gpu_resource_group_manager,
+                    bind_group_layout_registry,
+                    &self.input_render_attachments,
+                    &self.push_constant_ranges,
+                    &self.color_target_state,
+                    &self.depth_stencil_state,
+                    Some(skybox_gpu_resources.bind_group_layout()),
+                )?);
+                self.skybox = Some(skybox_gpu_resources.skybox());
+            }
+        }
+        Ok(())
+    }
+
+    fn create_pipeline(
+        graphics_device: &GraphicsDevice,
+        shader_manager: &mut ShaderManager,
+        render_attachment_texture_manager: &RenderAttachmentTextureManager,
+        gpu_resource_group_manager: &GPUResourceGroupManager,
+        bind_group_layout_registry: &BindGroupLayoutRegistry,
+        input_render_attachments: &RenderAttachmentInputDescriptionSet,
+        push_constant_ranges: &[wgpu::PushConstantRange],
+        color_target_state: &wgpu::ColorTargetState,
+        depth_stencil_state: &wgpu::DepthStencilState,
+        skybox_bind_group_layout: Option<&wgpu::BindGroupLayout>,
+    ) -> Result<wgpu::RenderPipeline> {
+        let uses_skybox = skybox_bind_group_layout.is_some();
+
+        let (_, shader) = shader_manager.get_or_create_rendering_shader_from_template(
+            graphics_device,
+            &ScreenSpaceReflectionTracingShaderTemplate::new(uses_skybox),
+        );
+
+        let camera_bind_group_layout = CameraGPUResource::get_or_create_bind_group_layout(
+            graphics_device,
+            bind_group_layout_registry,
+        );
+
+        let resource_group_id = screen_space_reflection_parameters_resource_group_id();
+        let parameters_resource_group = gpu_resource_group_manager
+            .get_resource_group(resource_group_id)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Missing GPU resource group for screen-space reflection pass: {}",
+                    resource_group_id
+                )
+            })?;
+
+        let mut bind_group_layouts = Vec::with_capacity(7);
+
+        bind_group_layouts.push(&camera_bind_group_layout);
+
+        bind_group_layouts.extend(
+            render_attachment_texture_manager
+                .get_render_attachment_texture_bind_group_layouts(input_render_attachments),
+        );
+
+        bind_group_layouts.push(parameters_resource_group.bind_group_layout());
+
+        if let Some(skybox_bind_group_layout) = skybox_bind_group_layout {
+            bind_group_layouts.push(skybox_bind_group_layout);
+        }
+
+        let pipeline_layout = render_command::create_render_pipeline_layout(
+            graphics_device.device(),
+            &bind_group_layouts,
+            push_constant_ranges,
+            "Screen-space reflection tracing pass render pipeline layout",
+        );
+
+        Ok(render_command::create_render_pipeline(
+            graphics_device.device(),
+            &pipeline_layout,
+            shader,
+            &[VertexPosition::BUFFER_LAYOUT],
+            &[Some(color_target_state.clone())],
+            STANDARD_FRONT_FACE,
+            Some(wgpu::Face::Back),
+            wgpu::PolygonMode::Fill,
+            false,
+            Some(depth_stencil_state.clone()),
+            "Screen-space reflection tracing pass render pipeline",
+        ))
+    }
+
+    fn color_attachment<'a, 'b: 'a>(
+        &self,
+        render_attachment_texture_manager: &'b RenderAttachmentTextureManager,
+    ) -> wgpu::RenderPassColorAttachment<'a> {
+        let texture = render_attachment_texture_manager
+            .request_render_attachment_textures(self.output_render_attachments.quantities())
+            .next()
+            .unwrap();
+        wgpu::RenderPassColorAttachment {
+            view: texture.base_texture_view(),
+            depth_slice: None,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Load,
+                store: wgpu::StoreOp::Store,
+            },
+        }
+    }
+
+    fn depth_stencil_attachment(
+        render_attachment_texture_manager: &RenderAttachmentTextureManager,
+    ) -> wgpu::RenderPassDepthStencilAttachment<'_> {
+        wgpu::RenderPassDepthStencilAttachment {
+            view: render_attachment_texture_manager
+                .render_attachment_texture(RenderAttachmentQuantity::DepthStencil)
+                .base_texture_view(),
+            depth_ops: Some(wgpu::Operations {
+                load: wgpu::LoadOp::Load,
+                store: wgpu::StoreOp::Store,
+            }),
+            stencil_ops: Some(wgpu::Operations {
+                load: wgpu::LoadOp::Load,
+                store: wgpu::StoreOp::Store,
+            }),
+        }
+    }
+
+    fn set_push_constants(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        rendering_surface: &RenderingSurface,
+        postprocessor: &Postprocessor,
+        camera_gpu_resources: &CameraGPUResource,
+        frame_counter: u32,
+    ) {
+        self.push_constants
+            .set_push_constant_for_render_pass_if_present(
+                render_pass,
+                BasicPushConstantVariant::CameraRotationQuaternion,
+                || camera_gpu_resources.camera_rotation_quaternion_push_constant(),
+            );
+
+        self.push_constants
+            .set_push_constant_for_render_pass_if_present(
+                render_pass,
+                BasicPushConstantVariant::InverseWindowDimensions,
+                || rendering_surface.inverse_window_dimensions_push_constant(),
+            );
+
+        self.push_constants
+            .set_push_constant_for_render_pass_if_present(
+                render_pass,
+                BasicPushConstantVariant::FrameCounter,
+                || frame_counter,
+            );
+
+        self.push_constants
+            .set_push_constant_for_render_pass_if_present(
+                render_pass,
+                BasicPushConstantVariant::Exposure,
+                || postprocessor.capturing_camera().exposure_push_constant(),
+            );
+    }
+
+    fn record(
+        &self,
+        rendering_surface: &RenderingSurface,
+        gpu_resources: &impl BasicGPUResources,
+        render_attachment_texture_manager: &RenderAttachmentTextureManager,
+        gpu_resource_group_manager: &GPUResourceGroupManager,
+        postprocessor: &Postprocessor,
+        frame_counter: u32,
+        timestamp_recorder: &mut TimestampQueryRegistry<'_>,
+        command_encoder: &mut wgpu::CommandEncoder,
+    ) -> Result<()> {
+        let Some(camera_gpu_resources) = gpu_resources.camera() else {
+            return Ok(());
+        };
+
+        let skybox_pipeline_and_resources =
+            self.skybox_pipeline.as_ref().zip(gpu_resources.skybox());
+
+        let color_attachment = self.color_attachment(render_attachment_texture_manager);
+
+        let depth_stencil_attachment =
+            Self::depth_stencil_attachment(render_attachment_texture_manager);
+
+        let (mut render_pass, _timestamp_span_guard) = begin_single_render_pass(
+            command_encoder,
+            timestamp_recorder,
+            &[Some(color_attachment)],
+            Some(depth_stencil_attachment),
+            Cow::Borrowed("Screen-space reflection tracing pass"),
+        );
+
+        if let Some((skybox_pipeline, _)) = skybox_pipeline_and_resources {
+            render_pass.set_pipeline(skybox_pipeline);
+        } else {
+            render_pass.set_pipeline(&self.pipeline);
+        }
+
+        render_pass.set_stencil_reference(StencilValue::PhysicalModel as u32);
+
+        self.set_push_constants(
+            &mut render_pass,
+            rendering_surface,
+            postprocessor,
+            camera_gpu_resources,
+            frame_counter,
+        );
+
+        let mut bind_group_index = 0;
+
+        render_pass.set_bind_group(bind_group_index, camera_gpu_resources.bind_group(), &[]);
+        bind_group_index += 1;
+
+        for bind_group in render_attachment_texture_manager
+            .get_render_attachment_texture_bind_groups(&self.input_render_attachments)
+        {
+            render_pass.set_bind_group(bind_group_index, bind_group, &[]);
+            bind_group_index += 1;
+        }
+
+        let resource_group_id = screen_space_reflection_parameters_resource_group_id();
+        let parameters_resource_group = gpu_resource_group_manager
+            .get_resource_group(resource_group_id)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Missing GPU resource group for screen-space reflection pass: {}",
+                    resource_group_id
+                )
+            })?;
+        render_pass.set_bind_group(
+            bind_group_index,
+            parameters_resource_group.bind_group(),
+            &[],
+        );
+        bind_group_index += 1;
+
+        if let Some((_, skybox_gpu_resources)) = skybox_pipeline_and_resources {
+            render_pass.set_bind_group(bind_group_index, skybox_gpu_resources.bind_group(), &[]);
+        }
+
+        let mesh_id = impact_mesh::builtin::screen_filling_quad_mesh_id();
+
+        let mesh_gpu_resources = gpu_resources
+            .triangle_mesh()
+            .get(mesh_id)
+            .ok_or_else(|| anyhow!("Missing GPU resources for mesh {}", mesh_id))?;
+
+        let position_buffer = mesh_gpu_resources
+            .request_vertex_gpu_buffers(VertexAttributeSet::POSITION)?
+            .next()
+            .unwrap();
+
+        render_pass.set_vertex_buffer(0, position_buffer.valid_buffer_slice());
+
+        render_pass.set_index_buffer(
+            mesh_gpu_resources
+                .triangle_mesh_index_gpu_buffer()
+                .valid_buffer_slice(),
+            mesh_gpu_resources.triangle_mesh_index_format(),
+        );
+
+        render_pass.draw_indexed(
+            0..u32::try_from(mesh_gpu_resources.n_indices()).unwrap(),
+            0,
+            0..1,
+        );
+
+        log::trace!("Recorded screen-space reflection tracing pass");
+
+        Ok(())
+    }
+}
+
+impl ScreenSpaceReflectionParameters {
+    fn new(config: &SSRConfig) -> Self {
+        Self {
+            max_step_count: config.max_step_count,
+            max_distance: config.max_distance,
+            thickness: config.thickness,
+            roughness_cutoff: config.roughness_cutoff,
+            current_frame_weight: config.current_frame_weight,
+            variance_clipping_threshold: config.variance_clipping_threshold,
+            _pad: [0; 8],
+        }
+    }
+}
+
+impl UniformBufferable for ScreenSpaceReflectionParameters {
+    const ID: ConstStringHash64 = ConstStringHash64::new("Screen-space reflection parameters");
+
+    fn create_bind_group_layout_entry(
+        binding: u32,
+        visibility: wgpu::ShaderStages,
+    ) -> wgpu::BindGroupLayoutEntry {
+        uniform::create_uniform_buffer_bind_group_layout_entry(binding, visibility)
+    }
+}
+assert_uniform_valid!(ScreenSpaceReflectionParameters);
+
+fn update_screen_space_reflection_parameters_uniform(
+    graphics_device: &GraphicsDevice,
+    gpu_resource_group_manager: &GPUResourceGroupManager,
+    uniform: &ScreenSpaceReflectionParameters,
+) {
+    let resource_group_id = screen_space_reflection_parameters_resource_group_id();
+    let resource_group = gpu_resource_group_manager
+        .get_resource_group(resource_group_id)
+        .expect(
+            "Screen-space reflection parameters resource group should not be missing during update",
+        );
+    let buffer = resource_group.single_uniform_buffer(0).expect(
+        "Screen-space reflection parameters resource group should have single uniform buffer",
+    );
+    buffer.update_uniform(graphics_device, uniform);
+}
+
+fn screen_space_reflection_parameters_resource_group_id() -> GPUResourceGroupID {
+    GPUResourceGroupID(hash64!("ScreenSpaceReflectionParameters"))
+}
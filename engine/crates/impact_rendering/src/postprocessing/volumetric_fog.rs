@@ -0,0 +1,922 @@
+//! Passes for computing and applying volumetric fog.
+//!
+//! The fog is evaluated on a grid of frustum-aligned voxels (froxels) covering
+//! the view frustum out to a configurable distance. Each frame, the light
+//! scattered towards the camera by each froxel is injected from every enabled
+//! shadowable light, after which the scattered light and transmittance are
+//! integrated front-to-back along each column of the grid. The integrated
+//! result is finally looked up for every pixel and blended into the luminance
+//! attachment.
+
+use crate::{
+    attachment::RenderAttachmentTextureManager,
+    compute,
+    postprocessing::Postprocessor,
+    push_constant::{BasicPushConstantGroup, BasicPushConstantVariant},
+    render_command::postprocessing_pass::PostprocessingRenderPass,
+    resource::BasicGPUResources,
+    shader_templates::{
+        volumetric_fog_application::VolumetricFogApplicationShaderTemplate,
+        volumetric_fog_injection::VolumetricFogInjectionShaderTemplate,
+        volumetric_fog_integration::VolumetricFogIntegrationShaderTemplate,
+    },
+    surface::RenderingSurface,
+};
+use anyhow::Result;
+use approx::abs_diff_ne;
+use bytemuck::{Pod, Zeroable};
+use impact_camera::gpu_resource::CameraGPUResource;
+use impact_gpu::{
+    assert_uniform_valid,
+    bind_group_layout::BindGroupLayoutRegistry,
+    device::GraphicsDevice,
+    resource_group::{GPUResourceGroup, GPUResourceGroupID, GPUResourceGroupManager},
+    shader::ShaderManager,
+    texture::{Sampler, SamplerConfig, Texture},
+    timestamp_query::TimestampQueryRegistry,
+    uniform::{self, SingleUniformGPUBuffer, UniformBufferable},
+    wgpu,
+};
+use impact_light::{
+    LightFlags, LightManager,
+    gpu_resource::{
+        LightGPUResources, OmnidirectionalLightShadowMapManager,
+        UnidirectionalLightShadowMapManager,
+    },
+};
+use impact_math::{hash::ConstStringHash64, hash64};
+use std::{borrow::Cow, mem};
+
+/// The number of froxels along the horizontal, vertical and depth axis of the
+/// view frustum.
+const FROXEL_GRID_DIMENSIONS: [u32; 3] = [160, 90, 64];
+
+const INTEGRATED_SCATTERING_TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// Configuration options for volumetric fog.
+///
+/// The optical properties of the fog are taken from the medium the scene is
+/// immersed in, so no fog is rendered when the medium is transparent.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+#[derive(Clone, Debug)]
+pub struct VolumetricFogConfig {
+    /// Whether volumetric fog is enabled.
+    pub enabled: bool,
+    /// The distance from the camera out to which the fog is evaluated. Beyond
+    /// this distance, no additional scattering or extinction is accumulated.
+    pub max_distance: f32,
+    /// Factor by which to scale the extinction coefficient of the medium.
+    pub density_scale: f32,
+}
+
+#[derive(Debug)]
+pub struct VolumetricFogRenderCommands {
+    injection_pass: VolumetricFogInjectionPass,
+    integration_pass: VolumetricFogIntegrationPass,
+    application_pass: PostprocessingRenderPass,
+    compute_bind_group: wgpu::BindGroup,
+    // The bind group keeps the underlying GPU objects alive, but we hold on
+    // to them to make the ownership explicit
+    _scattering_buffer: wgpu::Buffer,
+    _integrated_scattering_texture: Texture,
+    medium: FogMedium,
+    config: VolumetricFogConfig,
+}
+
+/// Compute pass for injecting the light scattered towards the camera by each
+/// froxel from every enabled shadowable light.
+#[derive(Debug)]
+struct VolumetricFogInjectionPass {
+    push_constants: BasicPushConstantGroup,
+    unidirectional_light_pipeline: InjectionPipeline,
+    omnidirectional_light_pipeline: InjectionPipeline,
+}
+
+#[derive(Debug)]
+struct InjectionPipeline {
+    layout: wgpu::PipelineLayout,
+    pipeline: wgpu::ComputePipeline,
+    max_light_count: usize,
+}
+
+/// Compute pass for integrating the injected scattered light and the
+/// transmittance along each column of the froxel grid.
+#[derive(Debug)]
+struct VolumetricFogIntegrationPass {
+    pipeline: wgpu::ComputePipeline,
+}
+
+/// The optical properties of the medium the fog is made of.
+#[derive(Clone, Debug)]
+struct FogMedium {
+    extinction_coefficient: f32,
+    scattering_albedo: [f32; 3],
+    anisotropy: f32,
+}
+
+/// Uniform holding parameters needed in the shaders for computing and applying
+/// volumetric fog.
+///
+/// The size of this struct has to be a multiple of 16 bytes as required for
+/// uniforms.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Zeroable, Pod)]
+struct VolumetricFogParameters {
+    scattering_coefficient: [f32; 3],
+    extinction_coefficient: f32,
+    anisotropy: f32,
+    max_distance: f32,
+    _pad: [f32; 2],
+}
+
+impl VolumetricFogConfig {
+    fn new_config_requires_resource_update(&self, other: &Self) -> bool {
+        abs_diff_ne!(self.max_distance, other.max_distance, epsilon = 1e-6)
+            || abs_diff_ne!(self.density_scale, other.density_scale, epsilon = 1e-6)
+    }
+}
+
+impl Default for VolumetricFogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_distance: 100.0,
+            density_scale: 1.0,
+        }
+    }
+}
+
+impl VolumetricFogRenderCommands {
+    pub(super) fn new(
+        config: VolumetricFogConfig,
+        graphics_device: &GraphicsDevice,
+        rendering_surface: &RenderingSurface,
+        shader_manager: &mut ShaderManager,
+        render_attachment_texture_manager: &mut RenderAttachmentTextureManager,
+        gpu_resource_group_manager: &mut GPUResourceGroupManager,
+        bind_group_layout_registry: &BindGroupLayoutRegistry,
+    ) -> Result<Self> {
+        let medium = FogMedium::transparent();
+
+        let scattering_buffer = create_scattering_buffer(graphics_device);
+        let integrated_scattering_texture = create_integrated_scattering_texture(graphics_device);
+
+        let resource_group_id = volumetric_fog_resource_group_id();
+
+        let parameter_uniform = VolumetricFogParameters::new(&config, &medium);
+
+        let parameter_uniform_buffer = SingleUniformGPUBuffer::for_uniform(
+            graphics_device,
+            &parameter_uniform,
+            wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
+            Cow::Borrowed("Volumetric fog parameters"),
+        );
+
+        let sampler = Sampler::create(graphics_device, SamplerConfig::default());
+
+        // The compute bind group must refer to the same texture as the
+        // resource group, so we always replace any existing group
+        let resource_group = GPUResourceGroup::new(
+            graphics_device,
+            vec![parameter_uniform_buffer],
+            &[],
+            &[&integrated_scattering_texture],
+            &[&sampler],
+            wgpu::ShaderStages::FRAGMENT,
+            "Volumetric fog resources",
+        );
+
+        let compute_bind_group_layout =
+            create_compute_bind_group_layout(graphics_device, &resource_group);
+
+        let compute_bind_group = create_compute_bind_group(
+            graphics_device,
+            &compute_bind_group_layout,
+            &resource_group,
+            &scattering_buffer,
+            &integrated_scattering_texture,
+        );
+
+        gpu_resource_group_manager.add_resource_group(resource_group_id, resource_group);
+
+        let injection_pass = VolumetricFogInjectionPass::new(
+            graphics_device,
+            shader_manager,
+            bind_group_layout_registry,
+            &compute_bind_group_layout,
+        );
+
+        let integration_pass = VolumetricFogIntegrationPass::new(
+            graphics_device,
+            shader_manager,
+            bind_group_layout_registry,
+            &compute_bind_group_layout,
+        );
+
+        let application_pass = PostprocessingRenderPass::new(
+            graphics_device,
+            rendering_surface,
+            shader_manager,
+            render_attachment_texture_manager,
+            gpu_resource_group_manager,
+            bind_group_layout_registry,
+            &VolumetricFogApplicationShaderTemplate::new(
+                resource_group_id,
+                FROXEL_GRID_DIMENSIONS[2],
+            ),
+            Cow::Borrowed("Volumetric fog application pass"),
+        )?;
+
+        Ok(Self {
+            injection_pass,
+            integration_pass,
+            application_pass,
+            compute_bind_group,
+            _scattering_buffer: scattering_buffer,
+            _integrated_scattering_texture: integrated_scattering_texture,
+            medium,
+            config,
+        })
+    }
+
+    pub fn enabled_mut(&mut self) -> &mut bool {
+        &mut self.config.enabled
+    }
+
+    pub(super) fn config(&self) -> &VolumetricFogConfig {
+        &self.config
+    }
+
+    pub(super) fn set_config(
+        &mut self,
+        graphics_device: &GraphicsDevice,
+        gpu_resource_group_manager: &GPUResourceGroupManager,
+        config: VolumetricFogConfig,
+    ) {
+        if self.config.new_config_requires_resource_update(&config) {
+            update_volumetric_fog_parameters_uniform(
+                graphics_device,
+                gpu_resource_group_manager,
+                &VolumetricFogParameters::new(&config, &self.medium),
+            );
+        }
+        self.config = config;
+    }
+
+    pub(super) fn set_medium(
+        &mut self,
+        graphics_device: &GraphicsDevice,
+        gpu_resource_group_manager: &GPUResourceGroupManager,
+        extinction_coefficient: f32,
+        scattering_albedo: [f32; 3],
+        anisotropy: f32,
+    ) {
+        self.medium = FogMedium {
+            extinction_coefficient,
+            scattering_albedo,
+            anisotropy,
+        };
+        update_volumetric_fog_parameters_uniform(
+            graphics_device,
+            gpu_resource_group_manager,
+            &VolumetricFogParameters::new(&self.config, &self.medium),
+        );
+    }
+
+    pub(super) fn sync_with_render_resources(
+        &mut self,
+        graphics_device: &GraphicsDevice,
+        shader_manager: &mut ShaderManager,
+        gpu_resources: &impl BasicGPUResources,
+    ) {
+        self.injection_pass.sync_with_render_resources(
+            graphics_device,
+            shader_manager,
+            gpu_resources,
+        );
+    }
+
+    fn is_active(&self) -> bool {
+        self.config.enabled && self.medium.extinction_coefficient > 0.0
+    }
+
+    pub(super) fn record(
+        &self,
+        rendering_surface: &RenderingSurface,
+        gpu_resources: &impl BasicGPUResources,
+        render_attachment_texture_manager: &RenderAttachmentTextureManager,
+        gpu_resource_group_manager: &GPUResourceGroupManager,
+        postprocessor: &Postprocessor,
+        frame_counter: u32,
+        timestamp_recorder: &mut TimestampQueryRegistry<'_>,
+        command_encoder: &mut wgpu::CommandEncoder,
+    ) -> Result<()> {
+        if !self.is_active() {
+            return Ok(());
+        }
+
+        let Some(camera_gpu_resources) = gpu_resources.camera() else {
+            return Ok(());
+        };
+
+        if let Some(light_gpu_resources) = gpu_resources.light() {
+            self.injection_pass.record(
+                camera_gpu_resources,
+                light_gpu_resources,
+                &self.compute_bind_group,
+                postprocessor,
+                timestamp_recorder,
+                command_encoder,
+            );
+        }
+
+        self.integration_pass.record(
+            camera_gpu_resources,
+            &self.compute_bind_group,
+            timestamp_recorder,
+            command_encoder,
+        );
+
+        self.application_pass.record(
+            rendering_surface,
+            None,
+            gpu_resources,
+            render_attachment_texture_manager,
+            gpu_resource_group_manager,
+            postprocessor,
+            frame_counter,
+            timestamp_recorder,
+            command_encoder,
+        )
+    }
+}
+
+impl VolumetricFogInjectionPass {
+    fn new(
+        graphics_device: &GraphicsDevice,
+        shader_manager: &mut ShaderManager,
+        bind_group_layout_registry: &BindGroupLayoutRegistry,
+        compute_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let push_constants = VolumetricFogInjectionShaderTemplate::push_constants();
+        let push_constant_ranges = push_constants.create_ranges();
+
+        let camera_bind_group_layout = CameraGPUResource::get_or_create_bind_group_layout(
+            graphics_device,
+            bind_group_layout_registry,
+        );
+
+        let unidirectional_light_bind_group_layout =
+            LightGPUResources::get_or_create_shadowable_unidirectional_light_bind_group_layout(
+                graphics_device,
+                bind_group_layout_registry,
+            );
+        let unidirectional_light_shadow_map_bind_group_layout =
+            UnidirectionalLightShadowMapManager::get_or_create_bind_group_layout(
+                graphics_device,
+                bind_group_layout_registry,
+            );
+
+        let omnidirectional_light_bind_group_layout =
+            LightGPUResources::get_or_create_shadowable_omnidirectional_light_bind_group_layout(
+                graphics_device,
+                bind_group_layout_registry,
+            );
+        let omnidirectional_light_shadow_map_bind_group_layout =
+            OmnidirectionalLightShadowMapManager::get_or_create_bind_group_layout(
+                graphics_device,
+                bind_group_layout_registry,
+            );
+
+        let unidirectional_light_pipeline = InjectionPipeline::new(
+            graphics_device,
+            shader_manager,
+            &[
+                &camera_bind_group_layout,
+                compute_bind_group_layout,
+                &unidirectional_light_bind_group_layout,
+                &unidirectional_light_shadow_map_bind_group_layout,
+            ],
+            &push_constant_ranges,
+            true,
+        );
+
+        let omnidirectional_light_pipeline = InjectionPipeline::new(
+            graphics_device,
+            shader_manager,
+            &[
+                &camera_bind_group_layout,
+                compute_bind_group_layout,
+                &omnidirectional_light_bind_group_layout,
+                &omnidirectional_light_shadow_map_bind_group_layout,
+            ],
+            &push_constant_ranges,
+            false,
+        );
+
+        Self {
+            push_constants,
+            unidirectional_light_pipeline,
+            omnidirectional_light_pipeline,
+        }
+    }
+
+    fn sync_with_render_resources(
+        &mut self,
+        graphics_device: &GraphicsDevice,
+        shader_manager: &mut ShaderManager,
+        gpu_resources: &impl BasicGPUResources,
+    ) {
+        let Some(light_gpu_resources) = gpu_resources.light() else {
+            return;
+        };
+
+        if light_gpu_resources.max_shadowable_unidirectional_light_count()
+            != self.unidirectional_light_pipeline.max_light_count
+        {
+            self.unidirectional_light_pipeline
+                .update_shader_with_new_max_light_count(
+                    graphics_device,
+                    shader_manager,
+                    true,
+                    light_gpu_resources.max_shadowable_unidirectional_light_count(),
+                );
+        }
+
+        if light_gpu_resources.max_shadowable_omnidirectional_light_count()
+            != self.omnidirectional_light_pipeline.max_light_count
+        {
+            self.omnidirectional_light_pipeline
+                .update_shader_with_new_max_light_count(
+                    graphics_device,
+                    shader_manager,
+                    false,
+                    light_gpu_resources.max_shadowable_omnidirectional_light_count(),
+                );
+        }
+    }
+
+    fn set_push_constants(
+        &self,
+        compute_pass: &mut wgpu::ComputePass<'_>,
+        postprocessor: &Postprocessor,
+        light_idx: u32,
+    ) {
+        self.push_constants
+            .set_push_constant_for_compute_pass_if_present(
+                compute_pass,
+                BasicPushConstantVariant::LightIdx,
+                || light_idx,
+            );
+
+        self.push_constants
+            .set_push_constant_for_compute_pass_if_present(
+                compute_pass,
+                BasicPushConstantVariant::Exposure,
+                || postprocessor.capturing_camera().exposure_push_constant(),
+            );
+    }
+
+    fn record(
+        &self,
+        camera_gpu_resources: &CameraGPUResource,
+        light_gpu_resources: &LightGPUResources,
+        compute_bind_group: &wgpu::BindGroup,
+        postprocessor: &Postprocessor,
+        timestamp_recorder: &mut TimestampQueryRegistry<'_>,
+        command_encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let n_shadowable_unidirectional_lights =
+            light_gpu_resources.shadowable_unidirectional_light_count();
+        let n_shadowable_omnidirectional_lights =
+            light_gpu_resources.shadowable_omnidirectional_light_count();
+
+        if n_shadowable_unidirectional_lights == 0 && n_shadowable_omnidirectional_lights == 0 {
+            return;
+        }
+
+        let (timestamp_writes, _timestamp_span_guard) = timestamp_recorder
+            .register_timestamp_writes_for_single_compute_pass(Cow::Borrowed(
+                "Volumetric fog injection pass",
+            ));
+
+        let mut compute_pass = command_encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            timestamp_writes,
+            label: Some("Volumetric fog injection pass"),
+        });
+
+        compute_pass.set_bind_group(0, camera_gpu_resources.bind_group(), &[]);
+        compute_pass.set_bind_group(1, compute_bind_group, &[]);
+
+        let [x, y, z] = VolumetricFogInjectionShaderTemplate::determine_workgroup_counts(
+            FROXEL_GRID_DIMENSIONS,
+        );
+
+        let mut dispatch_count = 0;
+
+        if n_shadowable_unidirectional_lights > 0 {
+            compute_pass.set_pipeline(&self.unidirectional_light_pipeline.pipeline);
+
+            compute_pass.set_bind_group(
+                2,
+                light_gpu_resources.shadowable_unidirectional_light_bind_group(),
+                &[],
+            );
+
+            let shadow_map_textures = light_gpu_resources
+                .unidirectional_light_shadow_map_manager()
+                .textures();
+
+            assert_eq!(
+                shadow_map_textures.len(),
+                n_shadowable_unidirectional_lights
+            );
+
+            for (light_idx, (light, shadow_map_texture)) in light_gpu_resources
+                .shadowable_unidirectional_light_metadata()
+                .iter()
+                .zip(shadow_map_textures)
+                .enumerate()
+            {
+                if light.flags.contains(LightFlags::IS_DISABLED) {
+                    continue;
+                }
+
+                self.set_push_constants(
+                    &mut compute_pass,
+                    postprocessor,
+                    u32::try_from(light_idx).unwrap(),
+                );
+
+                compute_pass.set_bind_group(3, shadow_map_texture.bind_group(), &[]);
+
+                compute_pass.dispatch_workgroups(x, y, z);
+                dispatch_count += 1;
+            }
+        }
+
+        if n_shadowable_omnidirectional_lights > 0 {
+            compute_pass.set_pipeline(&self.omnidirectional_light_pipeline.pipeline);
+
+            compute_pass.set_bind_group(
+                2,
+                light_gpu_resources.shadowable_omnidirectional_light_bind_group(),
+                &[],
+            );
+
+            let shadow_map_textures = light_gpu_resources
+                .omnidirectional_light_shadow_map_manager()
+                .textures();
+
+            assert_eq!(
+                shadow_map_textures.len(),
+                n_shadowable_omnidirectional_lights
+            );
+
+            for (light_idx, (light, shadow_map_texture)) in light_gpu_resources
+                .shadowable_omnidirectional_light_metadata()
+                .iter()
+                .zip(shadow_map_textures)
+                .enumerate()
+            {
+                if light.flags.contains(LightFlags::IS_DISABLED) {
+                    continue;
+                }
+
+                self.set_push_constants(
+                    &mut compute_pass,
+                    postprocessor,
+                    u32::try_from(light_idx).unwrap(),
+                );
+
+                compute_pass.set_bind_group(3, shadow_map_texture.bind_group(), &[]);
+
+                compute_pass.dispatch_workgroups(x, y, z);
+                dispatch_count += 1;
+            }
+        }
+
+        log::trace!("Recorded volumetric fog injection pass ({dispatch_count} dispatches)");
+    }
+}
+
+impl InjectionPipeline {
+    fn new(
+        graphics_device: &GraphicsDevice,
+        shader_manager: &mut ShaderManager,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        push_constant_ranges: &[wgpu::PushConstantRange],
+        is_unidirectional: bool,
+    ) -> Self {
+        let max_light_count = LightManager::INITIAL_LIGHT_CAPACITY;
+
+        let layout = compute::create_compute_pipeline_layout(
+            graphics_device.device(),
+            bind_group_layouts,
+            push_constant_ranges,
+            "Volumetric fog injection pass compute pipeline layout",
+        );
+
+        let pipeline = Self::create_pipeline(
+            graphics_device,
+            shader_manager,
+            &layout,
+            is_unidirectional,
+            max_light_count,
+        );
+
+        Self {
+            layout,
+            pipeline,
+            max_light_count,
+        }
+    }
+
+    fn update_shader_with_new_max_light_count(
+        &mut self,
+        graphics_device: &GraphicsDevice,
+        shader_manager: &mut ShaderManager,
+        is_unidirectional: bool,
+        new_max_light_count: usize,
+    ) {
+        self.pipeline = Self::create_pipeline(
+            graphics_device,
+            shader_manager,
+            &self.layout,
+            is_unidirectional,
+            new_max_light_count,
+        );
+        self.max_light_count = new_max_light_count;
+    }
+
+    fn create_pipeline(
+        graphics_device: &GraphicsDevice,
+        shader_manager: &mut ShaderManager,
+        layout: &wgpu::PipelineLayout,
+        is_unidirectional: bool,
+        max_light_count: usize,
+    ) -> wgpu::ComputePipeline {
+        let shader_template = if is_unidirectional {
+            VolumetricFogInjectionShaderTemplate::for_unidirectional_lights(
+                FROXEL_GRID_DIMENSIONS,
+                max_light_count,
+            )
+        } else {
+            VolumetricFogInjectionShaderTemplate::for_omnidirectional_lights(
+                FROXEL_GRID_DIMENSIONS,
+                max_light_count,
+            )
+        };
+
+        let (_, shader) = shader_manager
+            .get_or_create_compute_shader_from_template(graphics_device, &shader_template);
+
+        compute::create_compute_pipeline(
+            graphics_device.device(),
+            layout,
+            shader,
+            "Volumetric fog injection pass compute pipeline",
+        )
+    }
+}
+
+impl VolumetricFogIntegrationPass {
+    fn new(
+        graphics_device: &GraphicsDevice,
+        shader_manager: &mut ShaderManager,
+        bind_group_layout_registry: &BindGroupLayoutRegistry,
+        compute_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let camera_bind_group_layout = CameraGPUResource::get_or_create_bind_group_layout(
+            graphics_device,
+            bind_group_layout_registry,
+        );
+
+        let layout = compute::create_compute_pipeline_layout(
+            graphics_device.device(),
+            &[&camera_bind_group_layout, compute_bind_group_layout],
+            &[],
+            "Volumetric fog integration pass compute pipeline layout",
+        );
+
+        let (_, shader) = shader_manager.get_or_create_compute_shader_from_template(
+            graphics_device,
+            &VolumetricFogIntegrationShaderTemplate::new(FROXEL_GRID_DIMENSIONS),
+        );
+
+        let pipeline = compute::create_compute_pipeline(
+            graphics_device.device(),
+            &layout,
+            shader,
+            "Volumetric fog integration pass compute pipeline",
+        );
+
+        Self { pipeline }
+    }
+
+    fn record(
+        &self,
+        camera_gpu_resources: &CameraGPUResource,
+        compute_bind_group: &wgpu::BindGroup,
+        timestamp_recorder: &mut TimestampQueryRegistry<'_>,
+        command_encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let (timestamp_writes, _timestamp_span_guard) = timestamp_recorder
+            .register_timestamp_writes_for_single_compute_pass(Cow::Borrowed(
+                "Volumetric fog integration pass",
+            ));
+
+        let mut compute_pass = command_encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            timestamp_writes,
+            label: Some("Volumetric fog integration pass"),
+        });
+
+        compute_pass.set_pipeline(&self.pipeline);
+
+        compute_pass.set_bind_group(0, camera_gpu_resources.bind_group(), &[]);
+        compute_pass.set_bind_group(1, compute_bind_group, &[]);
+
+        let [x, y, z] = VolumetricFogIntegrationShaderTemplate::determine_workgroup_counts(
+            FROXEL_GRID_DIMENSIONS,
+        );
+        compute_pass.dispatch_workgroups(x, y, z);
+
+        log::trace!("Recorded volumetric fog integration pass");
+    }
+}
+
+impl FogMedium {
+    fn transparent() -> Self {
+        Self {
+            extinction_coefficient: 0.0,
+            scattering_albedo: [1.0; 3],
+            anisotropy: 0.0,
+        }
+    }
+}
+
+impl VolumetricFogParameters {
+    fn new(config: &VolumetricFogConfig, medium: &FogMedium) -> Self {
+        let extinction_coefficient = medium.extinction_coefficient * config.density_scale;
+        Self {
+            scattering_coefficient: medium
+                .scattering_albedo
+                .map(|albedo| albedo * extinction_coefficient),
+            extinction_coefficient,
+            anisotropy: medium.anisotropy,
+            max_distance: config.max_distance,
+            _pad: [0.0; 2],
+        }
+    }
+}
+
+impl UniformBufferable for VolumetricFogParameters {
+    const ID: ConstStringHash64 = ConstStringHash64::new("Volumetric fog parameters");
+
+    fn create_bind_group_layout_entry(
+        binding: u32,
+        visibility: wgpu::ShaderStages,
+    ) -> wgpu::BindGroupLayoutEntry {
+        uniform::create_uniform_buffer_bind_group_layout_entry(binding, visibility)
+    }
+}
+assert_uniform_valid!(VolumetricFogParameters);
+
+/// Creates the storage buffer holding the pre-exposed, phase-weighted
+/// illuminance injected into each froxel.
+fn create_scattering_buffer(graphics_device: &GraphicsDevice) -> wgpu::Buffer {
+    let [width, height, depth] = FROXEL_GRID_DIMENSIONS;
+    let froxel_count = u64::from(width) * u64::from(height) * u64::from(depth);
+
+    graphics_device
+        .device()
+        .create_buffer(&wgpu::BufferDescriptor {
+            size: froxel_count * mem::size_of::<[f32; 4]>() as u64,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+            label: Some("Volumetric fog scattering buffer"),
+        })
+}
+
+/// Creates the 3D texture holding the integrated scattered luminance and
+/// transmittance from the camera to the far side of each froxel.
+fn create_integrated_scattering_texture(graphics_device: &GraphicsDevice) -> Texture {
+    let [width, height, depth] = FROXEL_GRID_DIMENSIONS;
+
+    let texture = graphics_device
+        .device()
+        .create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: depth,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D3,
+            format: INTEGRATED_SCATTERING_TEXTURE_FORMAT,
+            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+            label: Some("Volumetric fog integrated scattering texture"),
+        });
+
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    Texture::new(texture, view, wgpu::TextureViewDimension::D3)
+}
+
+/// Creates the layout for the bind group used by the compute passes, which
+/// holds the fog parameters, the scattering buffer and the integrated
+/// scattering texture as a storage texture.
+fn create_compute_bind_group_layout(
+    graphics_device: &GraphicsDevice,
+    resource_group: &GPUResourceGroup,
+) -> wgpu::BindGroupLayout {
+    let parameter_uniform_buffer = resource_group
+        .single_uniform_buffer(0)
+        .expect("Volumetric fog resource group should have single uniform buffer");
+
+    graphics_device
+        .device()
+        .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                parameter_uniform_buffer.create_bind_group_layout_entry(0),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format: INTEGRATED_SCATTERING_TEXTURE_FORMAT,
+                        view_dimension: wgpu::TextureViewDimension::D3,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("Volumetric fog compute bind group layout"),
+        })
+}
+
+fn create_compute_bind_group(
+    graphics_device: &GraphicsDevice,
+    layout: &wgpu::BindGroupLayout,
+    resource_group: &GPUResourceGroup,
+    scattering_buffer: &wgpu::Buffer,
+    integrated_scattering_texture: &Texture,
+) -> wgpu::BindGroup {
+    let parameter_uniform_buffer = resource_group
+        .single_uniform_buffer(0)
+        .expect("Volumetric fog resource group should have single uniform buffer");
+
+    graphics_device
+        .device()
+        .create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                parameter_uniform_buffer.create_bind_group_entry(0),
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: scattering_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(
+                        integrated_scattering_texture.view(),
+                    ),
+                },
+            ],
+            label: Some("Volumetric fog compute bind group"),
+        })
+}
+
+fn update_volumetric_fog_parameters_uniform(
+    graphics_device: &GraphicsDevice,
+    gpu_resource_group_manager: &GPUResourceGroupManager,
+    uniform: &VolumetricFogParameters,
+) {
+    let resource_group_id = volumetric_fog_resource_group_id();
+    let resource_group = gpu_resource_group_manager
+        .get_resource_group(resource_group_id)
+        .expect("Volumetric fog resource group should not be missing during update");
+    let buffer = resource_group
+        .single_uniform_buffer(0)
+        .expect("Volumetric fog resource group should have single uniform buffer");
+    buffer.update_uniform(graphics_device, uniform);
+}
+
+fn volumetric_fog_resource_group_id() -> GPUResourceGroupID {
+    GPUResourceGroupID(hash64!("VolumetricFogResources"))
+}
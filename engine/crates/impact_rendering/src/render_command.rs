@@ -213,6 +213,21 @@ pub fn additive_blend_state() -> wgpu::BlendState {
     }
 }
 
+pub fn transmissive_blend_state() -> wgpu::BlendState {
+    wgpu::BlendState {
+        color: wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::One,
+            dst_factor: wgpu::BlendFactor::SrcAlpha,
+            operation: wgpu::BlendOperation::Add,
+        },
+        alpha: wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::Zero,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        },
+    }
+}
+
 /// Returns the render pass as well as a span guard for timestamp writes. The
 /// span guard should be dropped immediately after the render pass.
 #[track_caller]
@@ -10,6 +10,7 @@ use crate::{
     surface::RenderingSurface,
 };
 use anyhow::{Result, anyhow};
+use impact_animation::gpu_resource::JointPaletteGPUResource;
 use impact_camera::gpu_resource::CameraGPUResource;
 use impact_containers::HashSet;
 use impact_gpu::{
@@ -20,8 +21,11 @@ use impact_gpu::{
     wgpu,
 };
 use impact_material::Material;
-use impact_mesh::{VertexAttributeSet, VertexPosition, gpu_resource::VertexBufferable};
-use impact_model::{InstanceFeature, transform::InstanceModelViewTransformWithPrevious};
+use impact_mesh::{VertexPosition, VertexSkinWeights, gpu_resource::VertexBufferable};
+use impact_model::{
+    InstanceFeature, skinning::InstanceJointPaletteOffset,
+    transform::InstanceModelViewTransformWithPrevious,
+};
 use impact_scene::model::ModelID;
 use std::borrow::Cow;
 
@@ -29,10 +33,17 @@ use std::borrow::Cow;
 #[derive(Debug)]
 pub struct DepthPrepass {
     push_constants: BasicPushConstantGroup,
+    unskinned: DepthPrepassPipeline,
+    skinned: DepthPrepassPipeline,
+    write_stencil_value: StencilValue,
+}
+
+#[derive(Debug)]
+struct DepthPrepassPipeline {
+    shader_template: ModelDepthPrepassShaderTemplate,
     pipeline_layout: wgpu::PipelineLayout,
     pipeline: wgpu::RenderPipeline,
     models: HashSet<ModelID>,
-    write_stencil_value: StencilValue,
 }
 
 impl DepthPrepass {
@@ -43,80 +54,44 @@ impl DepthPrepass {
         write_stencil_value: StencilValue,
         config: &BasicRenderingConfig,
     ) -> Self {
-        shader_manager.get_or_create_rendering_shader_from_template(
-            graphics_device,
-            &ModelDepthPrepassShaderTemplate,
-        );
-
         let push_constants = ModelDepthPrepassShaderTemplate::push_constants();
 
-        let camera_bind_group_layout = CameraGPUResource::get_or_create_bind_group_layout(
+        let unskinned = DepthPrepassPipeline::new(
             graphics_device,
+            shader_manager,
             bind_group_layout_registry,
+            &push_constants,
+            config,
+            false,
         );
 
-        let pipeline_layout = render_command::create_render_pipeline_layout(
-            graphics_device.device(),
-            &[&camera_bind_group_layout],
-            &push_constants.create_ranges(),
-            "Depth prepass render pipeline layout",
+        let skinned = DepthPrepassPipeline::new(
+            graphics_device,
+            shader_manager,
+            bind_group_layout_registry,
+            &push_constants,
+            config,
+            true,
         );
 
-        let pipeline =
-            Self::create_pipeline(graphics_device, shader_manager, &pipeline_layout, config);
-
         Self {
             push_constants,
-            pipeline_layout,
-            pipeline,
-            models: HashSet::default(),
+            unskinned,
+            skinned,
             write_stencil_value,
         }
     }
 
-    fn create_pipeline(
-        graphics_device: &GraphicsDevice,
-        shader_manager: &ShaderManager,
-        pipeline_layout: &wgpu::PipelineLayout,
-        config: &BasicRenderingConfig,
-    ) -> wgpu::RenderPipeline {
-        let shader =
-            &shader_manager.rendering_shaders[&ModelDepthPrepassShaderTemplate.shader_id()];
-
-        render_command::create_render_pipeline(
-            graphics_device.device(),
-            pipeline_layout,
-            shader,
-            &[
-                InstanceModelViewTransformWithPrevious::BUFFER_LAYOUT.unwrap(),
-                VertexPosition::BUFFER_LAYOUT,
-            ],
-            &[],
-            STANDARD_FRONT_FACE,
-            Some(wgpu::Face::Back),
-            if config.wireframe_mode_on {
-                wgpu::PolygonMode::Line
-            } else {
-                wgpu::PolygonMode::Fill
-            },
-            false,
-            Some(render_command::depth_stencil_state_for_depth_stencil_write()),
-            "Depth prepass render pipeline",
-        )
-    }
-
     pub fn sync_with_config(
         &mut self,
         graphics_device: &GraphicsDevice,
         shader_manager: &ShaderManager,
         config: &BasicRenderingConfig,
     ) {
-        self.pipeline = Self::create_pipeline(
-            graphics_device,
-            shader_manager,
-            &self.pipeline_layout,
-            config,
-        );
+        self.unskinned
+            .sync_with_config(graphics_device, shader_manager, config);
+        self.skinned
+            .sync_with_config(graphics_device, shader_manager, config);
     }
 
     pub fn sync_with_render_resources_for_non_physical_models(
@@ -125,11 +100,15 @@ impl DepthPrepass {
     ) {
         let model_instance_buffers = gpu_resources.model_instance_buffer();
 
-        self.models
+        self.unskinned
+            .models
+            .retain(|model_id| model_instance_buffers.contains(model_id));
+        self.skinned
+            .models
             .retain(|model_id| model_instance_buffers.contains(model_id));
 
         for model_id in model_instance_buffers.model_ids() {
-            if self.models.contains(model_id) {
+            if self.unskinned.models.contains(model_id) || self.skinned.models.contains(model_id) {
                 continue;
             }
             if gpu_resources
@@ -137,7 +116,18 @@ impl DepthPrepass {
                 .get(model_id.material_id())
                 .is_some_and(Material::is_fixed)
             {
-                self.models.insert(*model_id);
+                // Only instances set up for skinning have a joint palette
+                // offset buffer
+                if model_instance_buffers
+                    .get_model_buffer_for_feature_feature_type::<InstanceJointPaletteOffset>(
+                        model_id,
+                    )
+                    .is_some()
+                {
+                    self.skinned.models.insert(*model_id);
+                } else {
+                    self.unskinned.models.insert(*model_id);
+                }
             }
         }
     }
@@ -190,7 +180,7 @@ impl DepthPrepass {
         timestamp_recorder: &mut TimestampQueryRegistry<'_>,
         command_encoder: &mut wgpu::CommandEncoder,
     ) -> Result<()> {
-        if self.models.is_empty() {
+        if self.unskinned.models.is_empty() && self.skinned.models.is_empty() {
             return Ok(());
         }
 
@@ -209,67 +199,228 @@ impl DepthPrepass {
             Cow::Borrowed("Depth prepass"),
         );
 
-        render_pass.set_pipeline(&self.pipeline);
-
         render_pass.set_stencil_reference(self.write_stencil_value as u32);
 
-        self.set_push_constants(&mut render_pass, rendering_surface, frame_counter);
+        for pipeline in [&self.unskinned, &self.skinned] {
+            if pipeline.models.is_empty() {
+                continue;
+            }
 
-        render_pass.set_bind_group(0, camera_gpu_resources.bind_group(), &[]);
+            let is_skinned = pipeline.shader_template.is_skinned();
 
-        for model_id in &self.models {
-            let transform_buffer = gpu_resources.model_instance_buffer()
-                .get_model_buffer_for_feature_feature_type::<InstanceModelViewTransformWithPrevious>(model_id)
-                .ok_or_else(|| {
-                    anyhow!(
-                        "Missing model-view transform GPU buffer for model {}",
-                        model_id
-                    )
-                })?;
+            render_pass.set_pipeline(&pipeline.pipeline);
 
-            let transform_range = transform_buffer.initial_feature_range();
+            self.set_push_constants(&mut render_pass, rendering_surface, frame_counter);
 
-            if transform_range.is_empty() {
-                continue;
+            render_pass.set_bind_group(0, camera_gpu_resources.bind_group(), &[]);
+
+            if is_skinned {
+                let Some(joint_palette_gpu_resources) = gpu_resources.joint_palette() else {
+                    continue;
+                };
+                render_pass.set_bind_group(
+                    ModelDepthPrepassShaderTemplate::JOINT_PALETTE_GROUP,
+                    joint_palette_gpu_resources.bind_group(),
+                    &[],
+                );
             }
 
-            render_pass
-                .set_vertex_buffer(0, transform_buffer.vertex_gpu_buffer().valid_buffer_slice());
+            for model_id in &pipeline.models {
+                let transform_buffer = gpu_resources.model_instance_buffer()
+                    .get_model_buffer_for_feature_feature_type::<InstanceModelViewTransformWithPrevious>(model_id)
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "Missing model-view transform GPU buffer for model {}",
+                            model_id
+                        )
+                    })?;
+
+                let transform_range = transform_buffer.initial_feature_range();
+
+                if transform_range.is_empty() {
+                    continue;
+                }
+
+                render_pass.set_vertex_buffer(
+                    0,
+                    transform_buffer.vertex_gpu_buffer().valid_buffer_slice(),
+                );
+
+                let mut vertex_buffer_slot = 1;
+
+                if is_skinned {
+                    let joint_palette_offset_buffer = gpu_resources
+                        .model_instance_buffer()
+                        .get_model_buffer_for_feature_feature_type::<InstanceJointPaletteOffset>(
+                            model_id,
+                        )
+                        .ok_or_else(|| {
+                            anyhow!(
+                                "Missing joint palette offset GPU buffer for model {}",
+                                model_id
+                            )
+                        })?;
+
+                    render_pass.set_vertex_buffer(
+                        vertex_buffer_slot,
+                        joint_palette_offset_buffer
+                            .vertex_gpu_buffer()
+                            .valid_buffer_slice(),
+                    );
+                    vertex_buffer_slot += 1;
+                }
+
+                let mesh_id = model_id.triangle_mesh_id();
+
+                let mesh_gpu_resources = gpu_resources
+                    .triangle_mesh()
+                    .get(mesh_id)
+                    .ok_or_else(|| anyhow!("Missing GPU resources for mesh {}", mesh_id))?;
+
+                for vertex_buffer in mesh_gpu_resources
+                    .request_vertex_gpu_buffers(pipeline.shader_template.vertex_attributes())?
+                {
+                    render_pass
+                        .set_vertex_buffer(vertex_buffer_slot, vertex_buffer.valid_buffer_slice());
+                    vertex_buffer_slot += 1;
+                }
+
+                render_pass.set_index_buffer(
+                    mesh_gpu_resources
+                        .triangle_mesh_index_gpu_buffer()
+                        .valid_buffer_slice(),
+                    mesh_gpu_resources.triangle_mesh_index_format(),
+                );
+
+                render_pass.draw_indexed(
+                    0..u32::try_from(mesh_gpu_resources.n_indices()).unwrap(),
+                    0,
+                    transform_range,
+                );
+            }
+        }
 
-            let mesh_id = model_id.triangle_mesh_id();
+        let n_models = self.unskinned.models.len() + self.skinned.models.len();
 
-            let mesh_gpu_resources = gpu_resources
-                .triangle_mesh()
-                .get(mesh_id)
-                .ok_or_else(|| anyhow!("Missing GPU resources for mesh {}", mesh_id))?;
+        log::trace!(
+            "Recorded depth prepass for {} models ({} draw calls)",
+            n_models,
+            n_models
+        );
 
-            let position_buffer = mesh_gpu_resources
-                .request_vertex_gpu_buffers(VertexAttributeSet::POSITION)?
-                .next()
-                .unwrap();
+        Ok(())
+    }
+}
 
-            render_pass.set_vertex_buffer(1, position_buffer.valid_buffer_slice());
+impl DepthPrepassPipeline {
+    fn new(
+        graphics_device: &GraphicsDevice,
+        shader_manager: &mut ShaderManager,
+        bind_group_layout_registry: &BindGroupLayoutRegistry,
+        push_constants: &BasicPushConstantGroup,
+        config: &BasicRenderingConfig,
+        is_skinned: bool,
+    ) -> Self {
+        let shader_template = ModelDepthPrepassShaderTemplate::new(is_skinned);
 
-            render_pass.set_index_buffer(
-                mesh_gpu_resources
-                    .triangle_mesh_index_gpu_buffer()
-                    .valid_buffer_slice(),
-                mesh_gpu_resources.triangle_mesh_index_format(),
-            );
+        shader_manager
+            .get_or_create_rendering_shader_from_template(graphics_device, &shader_template);
 
-            render_pass.draw_indexed(
-                0..u32::try_from(mesh_gpu_resources.n_indices()).unwrap(),
-                0,
-                transform_range,
-            );
+        let camera_bind_group_layout = CameraGPUResource::get_or_create_bind_group_layout(
+            graphics_device,
+            bind_group_layout_registry,
+        );
+
+        let joint_palette_bind_group_layout;
+        let mut bind_group_layouts = vec![&camera_bind_group_layout];
+
+        if is_skinned {
+            joint_palette_bind_group_layout =
+                JointPaletteGPUResource::get_or_create_bind_group_layout(
+                    graphics_device,
+                    bind_group_layout_registry,
+                );
+            bind_group_layouts.push(&joint_palette_bind_group_layout);
         }
 
-        log::trace!(
-            "Recorded depth prepass for {} models ({} draw calls)",
-            self.models.len(),
-            self.models.len()
+        let pipeline_layout = render_command::create_render_pipeline_layout(
+            graphics_device.device(),
+            &bind_group_layouts,
+            &push_constants.create_ranges(),
+            "Depth prepass render pipeline layout",
         );
 
-        Ok(())
+        let pipeline = Self::create_pipeline(
+            graphics_device,
+            shader_manager,
+            &shader_template,
+            &pipeline_layout,
+            config,
+        );
+
+        Self {
+            shader_template,
+            pipeline_layout,
+            pipeline,
+            models: HashSet::default(),
+        }
+    }
+
+    fn sync_with_config(
+        &mut self,
+        graphics_device: &GraphicsDevice,
+        shader_manager: &ShaderManager,
+        config: &BasicRenderingConfig,
+    ) {
+        self.pipeline = Self::create_pipeline(
+            graphics_device,
+            shader_manager,
+            &self.shader_template,
+            &self.pipeline_layout,
+            config,
+        );
+    }
+
+    fn create_pipeline(
+        graphics_device: &GraphicsDevice,
+        shader_manager: &ShaderManager,
+        shader_template: &ModelDepthPrepassShaderTemplate,
+        pipeline_layout: &wgpu::PipelineLayout,
+        config: &BasicRenderingConfig,
+    ) -> wgpu::RenderPipeline {
+        let shader = &shader_manager.rendering_shaders[&shader_template.shader_id()];
+
+        let vertex_buffer_layouts: &[wgpu::VertexBufferLayout<'_>] = if shader_template.is_skinned()
+        {
+            &[
+                InstanceModelViewTransformWithPrevious::BUFFER_LAYOUT.unwrap(),
+                InstanceJointPaletteOffset::BUFFER_LAYOUT.unwrap(),
+                VertexPosition::BUFFER_LAYOUT,
+                VertexSkinWeights::BUFFER_LAYOUT,
+            ]
+        } else {
+            &[
+                InstanceModelViewTransformWithPrevious::BUFFER_LAYOUT.unwrap(),
+                VertexPosition::BUFFER_LAYOUT,
+            ]
+        };
+
+        render_command::create_render_pipeline(
+            graphics_device.device(),
+            pipeline_layout,
+            shader,
+            vertex_buffer_layouts,
+            &[],
+            STANDARD_FRONT_FACE,
+            Some(wgpu::Face::Back),
+            if config.wireframe_mode_on {
+                wgpu::PolygonMode::Line
+            } else {
+                wgpu::PolygonMode::Fill
+            },
+            false,
+            Some(render_command::depth_stencil_state_for_depth_stencil_write()),
+            "Depth prepass render pipeline",
+        )
     }
 }
@@ -14,6 +14,7 @@ use crate::{
     surface::RenderingSurface,
 };
 use anyhow::{Result, anyhow};
+use impact_animation::gpu_resource::JointPaletteGPUResource;
 use impact_camera::gpu_resource::CameraGPUResource;
 use impact_containers::{HashMap, HashSet, hash_map::Entry};
 use impact_gpu::{
@@ -23,9 +24,12 @@ use impact_gpu::{
     timestamp_query::{TimestampQueryRegistry, external::ExternalGPUSpanGuard},
     wgpu,
 };
-use impact_material::{Material, gpu_resource::GPUMaterialTemplate};
+use impact_material::{Material, gpu_resource::GPUMaterialTemplate, values::TextureCoordTransform};
 use impact_mesh::VertexAttributeSet;
-use impact_model::{InstanceFeature, transform::InstanceModelViewTransformWithPrevious};
+use impact_model::{
+    InstanceFeature, entity::InstanceEntityID, skinning::InstanceJointPaletteOffset,
+    transform::InstanceModelViewTransformWithPrevious,
+};
 use impact_scene::model::ModelID;
 use std::borrow::Cow;
 
@@ -38,6 +42,7 @@ pub struct GeometryPass {
     color_target_states: Vec<Option<wgpu::ColorTargetState>>,
     depth_stencil_state: wgpu::DepthStencilState,
     polygon_mode: wgpu::PolygonMode,
+    writes_entity_id: bool,
     model_pipelines: HashMap<ModelGeometryShaderInput, GeometryPassPipeline>,
 }
 
@@ -53,8 +58,11 @@ struct GeometryPassPipeline {
 
 impl GeometryPass {
     pub fn new(config: &BasicRenderingConfig) -> Self {
+        let writes_entity_id = config.entity_picking_enabled;
+
         let push_constants = ModelGeometryShaderTemplate::push_constants();
-        let output_render_attachments = ModelGeometryShaderTemplate::output_render_attachments();
+        let output_render_attachments =
+            ModelGeometryShaderTemplate::output_render_attachments(writes_entity_id);
 
         let push_constant_ranges = push_constants.create_ranges();
 
@@ -75,6 +83,7 @@ impl GeometryPass {
             color_target_states,
             depth_stencil_state,
             polygon_mode,
+            writes_entity_id,
             model_pipelines: HashMap::default(),
         }
     }
@@ -99,6 +108,21 @@ impl GeometryPass {
             wgpu::PolygonMode::Fill
         };
 
+        if config.entity_picking_enabled != self.writes_entity_id {
+            self.writes_entity_id = config.entity_picking_enabled;
+
+            self.output_render_attachments =
+                ModelGeometryShaderTemplate::output_render_attachments(self.writes_entity_id);
+
+            self.color_target_states =
+                Self::create_color_target_states(&self.output_render_attachments);
+
+            // The shaders and vertex buffer layouts depend on whether we write
+            // entity IDs, so we remove all pipelines and let them be recreated
+            // the next time we sync with the render resources
+            self.model_pipelines.clear();
+        }
+
         for pipeline in self.model_pipelines.values_mut() {
             pipeline.pipeline = Self::create_pipeline(
                 graphics_device,
@@ -184,9 +208,18 @@ impl GeometryPass {
             else {
                 continue;
             };
-            let Some(input) =
-                ModelGeometryShaderInput::for_material_template(&material_template.template)
-            else {
+
+            // Only instances set up for skinning have a joint palette offset
+            // buffer, so its presence determines whether the model is skinned
+            let is_skinned = gpu_resources
+                .model_instance_buffer()
+                .get_model_buffer_for_feature_feature_type::<InstanceJointPaletteOffset>(model_id)
+                .is_some();
+
+            let Some(input) = ModelGeometryShaderInput::for_material_template(
+                &material_template.template,
+                is_skinned,
+            ) else {
                 continue;
             };
 
@@ -195,7 +228,8 @@ impl GeometryPass {
                     entry.get_mut().models.insert(*model_id);
                 }
                 Entry::Vacant(entry) => {
-                    let shader_template = ModelGeometryShaderTemplate::new(input);
+                    let shader_template =
+                        ModelGeometryShaderTemplate::new(input, self.writes_entity_id);
 
                     shader_manager.get_or_create_rendering_shader_from_template(
                         graphics_device,
@@ -214,6 +248,16 @@ impl GeometryPass {
                         bind_group_layouts.push(bind_group_layout);
                     }
 
+                    let joint_palette_bind_group_layout;
+                    if is_skinned {
+                        joint_palette_bind_group_layout =
+                            JointPaletteGPUResource::get_or_create_bind_group_layout(
+                                graphics_device,
+                                bind_group_layout_registry,
+                            );
+                        bind_group_layouts.push(&joint_palette_bind_group_layout);
+                    }
+
                     let pipeline_layout = render_command::create_render_pipeline_layout(
                         graphics_device.device(),
                         &bind_group_layouts,
@@ -228,6 +272,9 @@ impl GeometryPass {
                         gpu_resources,
                         model_id,
                         vertex_attributes,
+                        self.writes_entity_id,
+                        is_skinned,
+                        shader_template.input().uses_texture_coord_transform(),
                     )?;
 
                     let pipeline = Self::create_pipeline(
@@ -289,6 +336,9 @@ impl GeometryPass {
         gpu_resources: &impl BasicGPUResources,
         model_id: &ModelID,
         vertex_attributes: VertexAttributeSet,
+        writes_entity_id: bool,
+        is_skinned: bool,
+        uses_texture_coord_transform: bool,
     ) -> Result<Vec<wgpu::VertexBufferLayout<'static>>> {
         let mut layouts = Vec::with_capacity(8);
 
@@ -299,6 +349,10 @@ impl GeometryPass {
 
         layouts.push(InstanceModelViewTransformWithPrevious::BUFFER_LAYOUT.unwrap());
 
+        if writes_entity_id {
+            layouts.push(InstanceEntityID::BUFFER_LAYOUT.unwrap());
+        }
+
         if let Some(material_property_values_feature_type_id) = gpu_resources
             .material()
             .get(model_id.material_id())
@@ -318,6 +372,14 @@ impl GeometryPass {
             layouts.push(material_property_buffer.vertex_buffer_layout().clone());
         }
 
+        if is_skinned {
+            layouts.push(InstanceJointPaletteOffset::BUFFER_LAYOUT.unwrap());
+        }
+
+        if uses_texture_coord_transform {
+            layouts.push(TextureCoordTransform::BUFFER_LAYOUT.unwrap());
+        }
+
         let mesh_id = model_id.triangle_mesh_id();
 
         let mesh_gpu_resources = gpu_resources
@@ -344,14 +406,19 @@ impl GeometryPass {
                         .only_description_for_quantity(*quantity)
                         .unwrap();
 
-                    let blend_state = match description.blending() {
-                        Blending::Replace => wgpu::BlendState::REPLACE,
-                        Blending::Additive => render_command::additive_blend_state(),
+                    let blend_state = match quantity.texture_format().sample_type(None, None) {
+                        // Integer formats do not support blending
+                        Some(wgpu::TextureSampleType::Uint | wgpu::TextureSampleType::Sint) => None,
+                        _ => Some(match description.blending() {
+                            Blending::Replace => wgpu::BlendState::REPLACE,
+                            Blending::Additive => render_command::additive_blend_state(),
+                            Blending::Transmissive => render_command::transmissive_blend_state(),
+                        }),
                     };
 
                     Some(Some(wgpu::ColorTargetState {
                         format: quantity.texture_format(),
-                        blend: Some(blend_state),
+                        blend: blend_state,
                         write_mask: description.write_mask(),
                     }))
                 } else {
@@ -498,6 +565,19 @@ impl GeometryPass {
                     continue;
                 }
 
+                let is_skinned = pipeline.shader_template.input().is_skinned();
+
+                if is_skinned {
+                    let Some(joint_palette_gpu_resources) = gpu_resources.joint_palette() else {
+                        continue;
+                    };
+                    render_pass.set_bind_group(
+                        pipeline.shader_template.joint_palette_group(),
+                        joint_palette_gpu_resources.bind_group(),
+                        &[],
+                    );
+                }
+
                 let material = gpu_resources.material().get(model_id.material_id());
 
                 if let Some(material_texture_bind_group) = material
@@ -516,6 +596,21 @@ impl GeometryPass {
 
                 let mut vertex_buffer_slot = 1;
 
+                if self.writes_entity_id {
+                    let entity_id_buffer = instance_feature_buffers
+                        .iter()
+                        .find(|buffer| buffer.is_for_feature_type::<InstanceEntityID>())
+                        .ok_or_else(|| {
+                            anyhow!("Missing entity ID GPU buffer for model {}", model_id)
+                        })?;
+
+                    render_pass.set_vertex_buffer(
+                        vertex_buffer_slot,
+                        entity_id_buffer.vertex_gpu_buffer().valid_buffer_slice(),
+                    );
+                    vertex_buffer_slot += 1;
+                }
+
                 if let Some(material_property_values_feature_type_id) =
                     material.and_then(|material| {
                         material
@@ -543,6 +638,50 @@ impl GeometryPass {
                     vertex_buffer_slot += 1;
                 }
 
+                if is_skinned {
+                    let joint_palette_offset_buffer = instance_feature_buffers
+                        .iter()
+                        .find(|buffer| buffer.is_for_feature_type::<InstanceJointPaletteOffset>())
+                        .ok_or_else(|| {
+                            anyhow!(
+                                "Missing joint palette offset GPU buffer for model {}",
+                                model_id
+                            )
+                        })?;
+
+                    render_pass.set_vertex_buffer(
+                        vertex_buffer_slot,
+                        joint_palette_offset_buffer
+                            .vertex_gpu_buffer()
+                            .valid_buffer_slice(),
+                    );
+                    vertex_buffer_slot += 1;
+                }
+
+                if pipeline
+                    .shader_template
+                    .input()
+                    .uses_texture_coord_transform()
+                {
+                    let texture_coord_transform_buffer = instance_feature_buffers
+                        .iter()
+                        .find(|buffer| buffer.is_for_feature_type::<TextureCoordTransform>())
+                        .ok_or_else(|| {
+                            anyhow!(
+                                "Missing texture coordinate transform GPU buffer for model {}",
+                                model_id
+                            )
+                        })?;
+
+                    render_pass.set_vertex_buffer(
+                        vertex_buffer_slot,
+                        texture_coord_transform_buffer
+                            .vertex_gpu_buffer()
+                            .valid_buffer_slice(),
+                    );
+                    vertex_buffer_slot += 1;
+                }
+
                 let mesh_id = model_id.triangle_mesh_id();
 
                 let mesh_gpu_resources = gpu_resources
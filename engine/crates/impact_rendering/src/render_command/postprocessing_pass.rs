@@ -163,6 +163,7 @@ impl PostprocessingRenderPass {
                     let blend_state = match description.blending() {
                         Blending::Replace => wgpu::BlendState::REPLACE,
                         Blending::Additive => render_command::additive_blend_state(),
+                        Blending::Transmissive => render_command::transmissive_blend_state(),
                     };
 
                     Some(Some(wgpu::ColorTargetState {
@@ -10,6 +10,7 @@ use crate::{
     },
 };
 use anyhow::{Result, anyhow};
+use impact_animation::gpu_resource::JointPaletteGPUResource;
 use impact_containers::HashSet;
 use impact_geometry::{Frustum, OrientedBox, projection::CubemapFace};
 use impact_gpu::{
@@ -21,10 +22,12 @@ use impact_light::{
     gpu_resource::LightGPUResources,
     shadow_map::{CascadeIdx, SHADOW_MAP_FORMAT},
 };
-use impact_mesh::{VertexAttributeSet, VertexPosition, gpu_resource::VertexBufferable};
+use impact_mesh::{
+    VertexAttributeSet, VertexPosition, VertexSkinWeights, gpu_resource::VertexBufferable,
+};
 use impact_model::{
     InstanceFeature, InstanceFeatureBufferRangeID, gpu_resource::InstanceFeatureGPUBuffer,
-    transform::InstanceModelLightTransform,
+    skinning::InstanceJointPaletteOffset, transform::InstanceModelLightTransform,
 };
 use impact_scene::model::ModelID;
 use std::borrow::Cow;
@@ -36,8 +39,11 @@ pub struct OmnidirectionalLightShadowMapUpdatePasses {
     color_target_states: Vec<Option<wgpu::ColorTargetState>>,
     pipeline_layout: wgpu::PipelineLayout,
     pipeline: wgpu::RenderPipeline,
+    skinned_pipeline_layout: wgpu::PipelineLayout,
+    skinned_pipeline: wgpu::RenderPipeline,
     max_light_count: usize,
     models: HashSet<ModelID>,
+    skinned_models: HashSet<ModelID>,
 }
 
 /// Passes for filling the cascades of each unidirectional light shadow map.
@@ -47,8 +53,11 @@ pub struct UnidirectionalLightShadowMapUpdatePasses {
     color_target_states: Vec<Option<wgpu::ColorTargetState>>,
     pipeline_layout: wgpu::PipelineLayout,
     pipeline: wgpu::RenderPipeline,
+    skinned_pipeline_layout: wgpu::PipelineLayout,
+    skinned_pipeline: wgpu::RenderPipeline,
     max_light_count: usize,
     models: HashSet<ModelID>,
+    skinned_models: HashSet<ModelID>,
 }
 
 impl OmnidirectionalLightShadowMapUpdatePasses {
@@ -69,36 +78,104 @@ impl OmnidirectionalLightShadowMapUpdatePasses {
     ) -> Self {
         let max_light_count = LightManager::INITIAL_LIGHT_CAPACITY;
 
-        let shader_template = OmnidirectionalLightShadowMapShaderTemplate::new(max_light_count);
-        let (_, shader) = shader_manager
-            .get_or_create_rendering_shader_from_template(graphics_device, &shader_template);
-
         let push_constants = OmnidirectionalLightShadowMapShaderTemplate::push_constants();
 
+        let pipeline_layout = Self::create_pipeline_layout(
+            graphics_device,
+            bind_group_layout_registry,
+            &push_constants,
+            false,
+        );
+        let skinned_pipeline_layout = Self::create_pipeline_layout(
+            graphics_device,
+            bind_group_layout_registry,
+            &push_constants,
+            true,
+        );
+
+        let color_target_states = Self::color_target_states();
+
+        let pipeline = Self::create_pipeline(
+            graphics_device,
+            shader_manager,
+            &pipeline_layout,
+            &color_target_states,
+            max_light_count,
+            false,
+        );
+        let skinned_pipeline = Self::create_pipeline(
+            graphics_device,
+            shader_manager,
+            &skinned_pipeline_layout,
+            &color_target_states,
+            max_light_count,
+            true,
+        );
+
+        Self {
+            push_constants,
+            color_target_states,
+            pipeline_layout,
+            pipeline,
+            skinned_pipeline_layout,
+            skinned_pipeline,
+            max_light_count,
+            models: HashSet::default(),
+            skinned_models: HashSet::default(),
+        }
+    }
+
+    fn create_pipeline_layout(
+        graphics_device: &GraphicsDevice,
+        bind_group_layout_registry: &BindGroupLayoutRegistry,
+        push_constants: &BasicPushConstantGroup,
+        is_skinned: bool,
+    ) -> wgpu::PipelineLayout {
         let omnidirectional_light_bind_group_layout =
             LightGPUResources::get_or_create_shadowable_omnidirectional_light_bind_group_layout(
                 graphics_device,
                 bind_group_layout_registry,
             );
 
-        let pipeline_layout = render_command::create_render_pipeline_layout(
+        let joint_palette_bind_group_layout;
+        let mut bind_group_layouts = vec![&omnidirectional_light_bind_group_layout];
+
+        if is_skinned {
+            joint_palette_bind_group_layout =
+                JointPaletteGPUResource::get_or_create_bind_group_layout(
+                    graphics_device,
+                    bind_group_layout_registry,
+                );
+            bind_group_layouts.push(&joint_palette_bind_group_layout);
+        }
+
+        render_command::create_render_pipeline_layout(
             graphics_device.device(),
-            &[&omnidirectional_light_bind_group_layout],
+            &bind_group_layouts,
             &push_constants.create_ranges(),
             "Omnidirectional light shadow map update render pipeline layout",
-        );
+        )
+    }
 
-        let color_target_states = Self::color_target_states();
+    fn create_pipeline(
+        graphics_device: &GraphicsDevice,
+        shader_manager: &mut ShaderManager,
+        pipeline_layout: &wgpu::PipelineLayout,
+        color_target_states: &[Option<wgpu::ColorTargetState>],
+        max_light_count: usize,
+        is_skinned: bool,
+    ) -> wgpu::RenderPipeline {
+        let shader_template =
+            OmnidirectionalLightShadowMapShaderTemplate::new(max_light_count, is_skinned);
+        let (_, shader) = shader_manager
+            .get_or_create_rendering_shader_from_template(graphics_device, &shader_template);
 
-        let pipeline = render_command::create_render_pipeline(
+        render_command::create_render_pipeline(
             graphics_device.device(),
-            &pipeline_layout,
+            pipeline_layout,
             shader,
-            &[
-                InstanceModelLightTransform::BUFFER_LAYOUT.unwrap(),
-                VertexPosition::BUFFER_LAYOUT,
-            ],
-            &color_target_states,
+            &shadow_map_vertex_buffer_layouts(is_skinned),
+            color_target_states,
             // The cubemap projection does not flip the z-axis, so the front
             // faces will have the opposite winding order compared to normal
             INVERTED_FRONT_FACE,
@@ -107,16 +184,7 @@ impl OmnidirectionalLightShadowMapUpdatePasses {
             false,
             None,
             "Omnidirectional light shadow map update render pipeline",
-        );
-
-        Self {
-            push_constants,
-            color_target_states,
-            pipeline_layout,
-            pipeline,
-            max_light_count,
-            models: HashSet::default(),
-        }
+        )
     }
 
     pub fn sync_with_render_resources(
@@ -141,14 +209,16 @@ impl OmnidirectionalLightShadowMapUpdatePasses {
 
         let model_instance_buffers = gpu_resources.model_instance_buffer();
 
-        self.models.retain(|model_id| {
-            model_instance_buffers
-                .get_model_buffers(model_id)
-                .is_some_and(has_features)
-        });
+        for models in [&mut self.models, &mut self.skinned_models] {
+            models.retain(|model_id| {
+                model_instance_buffers
+                    .get_model_buffers(model_id)
+                    .is_some_and(has_features)
+            });
+        }
 
         for (model_id, instance_feature_buffers) in model_instance_buffers.iter() {
-            if self.models.contains(model_id) {
+            if self.models.contains(model_id) || self.skinned_models.contains(model_id) {
                 continue;
             }
             if !has_features(instance_feature_buffers) {
@@ -162,7 +232,11 @@ impl OmnidirectionalLightShadowMapUpdatePasses {
                     .material_template()
                     .contains(material.template_id)
             {
-                self.models.insert(*model_id);
+                if is_skinned(instance_feature_buffers) {
+                    self.skinned_models.insert(*model_id);
+                } else {
+                    self.models.insert(*model_id);
+                }
             }
         }
     }
@@ -180,25 +254,21 @@ impl OmnidirectionalLightShadowMapUpdatePasses {
         let max_light_count = light_gpu_resources.max_shadowable_omnidirectional_light_count();
 
         if max_light_count != self.max_light_count {
-            let shader_template = OmnidirectionalLightShadowMapShaderTemplate::new(max_light_count);
-            let (_, shader) = shader_manager
-                .get_or_create_rendering_shader_from_template(graphics_device, &shader_template);
-
-            self.pipeline = render_command::create_render_pipeline(
-                graphics_device.device(),
+            self.pipeline = Self::create_pipeline(
+                graphics_device,
+                shader_manager,
                 &self.pipeline_layout,
-                shader,
-                &[
-                    InstanceModelLightTransform::BUFFER_LAYOUT.unwrap(),
-                    VertexPosition::BUFFER_LAYOUT,
-                ],
                 &self.color_target_states,
-                INVERTED_FRONT_FACE,
-                Some(wgpu::Face::Back),
-                wgpu::PolygonMode::Fill,
+                max_light_count,
                 false,
-                None,
-                "Omnidirectional light shadow map update render pipeline",
+            );
+            self.skinned_pipeline = Self::create_pipeline(
+                graphics_device,
+                shader_manager,
+                &self.skinned_pipeline_layout,
+                &self.color_target_states,
+                max_light_count,
+                true,
             );
             self.max_light_count = max_light_count;
         }
@@ -335,74 +405,46 @@ impl OmnidirectionalLightShadowMapUpdatePasses {
                     continue;
                 }
 
-                render_pass.set_pipeline(&self.pipeline);
-
-                self.set_light_idx_push_constant(
-                    &mut render_pass,
-                    u32::try_from(light_idx).unwrap(),
-                );
-
-                render_pass.set_bind_group(
-                    0,
-                    light_gpu_resources.shadowable_omnidirectional_light_bind_group(),
-                    &[],
-                );
-
-                for model_id in &self.models {
-                    let transform_buffer = gpu_resources
-                        .model_instance_buffer()
-                        .get_model_buffer_for_feature_feature_type::<InstanceModelLightTransform>(
-                            model_id,
-                        )
-                        .ok_or_else(|| {
-                            anyhow!(
-                                "Missing model-light transform GPU buffer for model {}",
-                                model_id
-                            )
-                        })?;
-
-                    let Some(transform_range) =
-                        transform_buffer.get_feature_range(instance_range_id)
-                    else {
-                        continue;
-                    };
-
-                    if transform_range.is_empty() {
+                for (pipeline, models, is_skinned) in [
+                    (&self.pipeline, &self.models, false),
+                    (&self.skinned_pipeline, &self.skinned_models, true),
+                ] {
+                    if models.is_empty() {
                         continue;
                     }
 
-                    render_pass.set_vertex_buffer(
-                        0,
-                        transform_buffer.vertex_gpu_buffer().valid_buffer_slice(),
-                    );
-
-                    let mesh_id = model_id.triangle_mesh_id();
-
-                    let mesh_gpu_resources = gpu_resources
-                        .triangle_mesh()
-                        .get(mesh_id)
-                        .ok_or_else(|| anyhow!("Missing GPU resources for mesh {}", mesh_id))?;
-
-                    let position_buffer = mesh_gpu_resources
-                        .request_vertex_gpu_buffers(VertexAttributeSet::POSITION)?
-                        .next()
-                        .unwrap();
+                    render_pass.set_pipeline(pipeline);
 
-                    render_pass.set_vertex_buffer(1, position_buffer.valid_buffer_slice());
-
-                    render_pass.set_index_buffer(
-                        mesh_gpu_resources
-                            .triangle_mesh_index_gpu_buffer()
-                            .valid_buffer_slice(),
-                        mesh_gpu_resources.triangle_mesh_index_format(),
+                    self.set_light_idx_push_constant(
+                        &mut render_pass,
+                        u32::try_from(light_idx).unwrap(),
                     );
 
-                    render_pass.draw_indexed(
-                        0..u32::try_from(mesh_gpu_resources.n_indices()).unwrap(),
+                    render_pass.set_bind_group(
                         0,
-                        transform_range,
+                        light_gpu_resources.shadowable_omnidirectional_light_bind_group(),
+                        &[],
                     );
-                    draw_call_count += 1;
+
+                    if is_skinned {
+                        let Some(joint_palette_gpu_resources) = gpu_resources.joint_palette()
+                        else {
+                            continue;
+                        };
+                        render_pass.set_bind_group(
+                            1,
+                            joint_palette_gpu_resources.bind_group(),
+                            &[],
+                        );
+                    }
+
+                    draw_call_count += record_shadow_map_draw_calls(
+                        gpu_resources,
+                        &mut render_pass,
+                        models,
+                        instance_range_id,
+                        is_skinned,
+                    )?;
                 }
 
                 perform_additional_draw_calls_after_face_update(
@@ -415,7 +457,7 @@ impl OmnidirectionalLightShadowMapUpdatePasses {
         log::trace!(
             "Recorded shadow map update passes for {} omnidirectional lights and {} models ({} passes, {} draw calls)",
             shadow_map_textures.len(),
-            self.models.len(),
+            self.models.len() + self.skinned_models.len(),
             pass_count,
             draw_call_count
         );
@@ -439,52 +481,111 @@ impl UnidirectionalLightShadowMapUpdatePasses {
     ) -> Self {
         let max_light_count = LightManager::INITIAL_LIGHT_CAPACITY;
 
-        let shader_template = UnidirectionalLightShadowMapShaderTemplate::new(max_light_count);
-        let (_, shader) = shader_manager
-            .get_or_create_rendering_shader_from_template(graphics_device, &shader_template);
-
         let push_constants = UnidirectionalLightShadowMapShaderTemplate::push_constants();
 
+        let pipeline_layout = Self::create_pipeline_layout(
+            graphics_device,
+            bind_group_layout_registry,
+            &push_constants,
+            false,
+        );
+        let skinned_pipeline_layout = Self::create_pipeline_layout(
+            graphics_device,
+            bind_group_layout_registry,
+            &push_constants,
+            true,
+        );
+
+        let color_target_states = Self::color_target_states();
+
+        let pipeline = Self::create_pipeline(
+            graphics_device,
+            shader_manager,
+            &pipeline_layout,
+            &color_target_states,
+            max_light_count,
+            false,
+        );
+        let skinned_pipeline = Self::create_pipeline(
+            graphics_device,
+            shader_manager,
+            &skinned_pipeline_layout,
+            &color_target_states,
+            max_light_count,
+            true,
+        );
+
+        Self {
+            push_constants,
+            color_target_states,
+            pipeline_layout,
+            pipeline,
+            skinned_pipeline_layout,
+            skinned_pipeline,
+            max_light_count,
+            models: HashSet::default(),
+            skinned_models: HashSet::default(),
+        }
+    }
+
+    fn create_pipeline_layout(
+        graphics_device: &GraphicsDevice,
+        bind_group_layout_registry: &BindGroupLayoutRegistry,
+        push_constants: &BasicPushConstantGroup,
+        is_skinned: bool,
+    ) -> wgpu::PipelineLayout {
         let unidirectional_light_bind_group_layout =
             LightGPUResources::get_or_create_shadowable_unidirectional_light_bind_group_layout(
                 graphics_device,
                 bind_group_layout_registry,
             );
 
-        let pipeline_layout = render_command::create_render_pipeline_layout(
+        let joint_palette_bind_group_layout;
+        let mut bind_group_layouts = vec![&unidirectional_light_bind_group_layout];
+
+        if is_skinned {
+            joint_palette_bind_group_layout =
+                JointPaletteGPUResource::get_or_create_bind_group_layout(
+                    graphics_device,
+                    bind_group_layout_registry,
+                );
+            bind_group_layouts.push(&joint_palette_bind_group_layout);
+        }
+
+        render_command::create_render_pipeline_layout(
             graphics_device.device(),
-            &[&unidirectional_light_bind_group_layout],
+            &bind_group_layouts,
             &push_constants.create_ranges(),
             "Unidirectional light shadow map update render pipeline layout",
-        );
+        )
+    }
 
-        let color_target_states = Self::color_target_states();
+    fn create_pipeline(
+        graphics_device: &GraphicsDevice,
+        shader_manager: &mut ShaderManager,
+        pipeline_layout: &wgpu::PipelineLayout,
+        color_target_states: &[Option<wgpu::ColorTargetState>],
+        max_light_count: usize,
+        is_skinned: bool,
+    ) -> wgpu::RenderPipeline {
+        let shader_template =
+            UnidirectionalLightShadowMapShaderTemplate::new(max_light_count, is_skinned);
+        let (_, shader) = shader_manager
+            .get_or_create_rendering_shader_from_template(graphics_device, &shader_template);
 
-        let pipeline = render_command::create_render_pipeline(
+        render_command::create_render_pipeline(
             graphics_device.device(),
-            &pipeline_layout,
+            pipeline_layout,
             shader,
-            &[
-                InstanceModelLightTransform::BUFFER_LAYOUT.unwrap(),
-                VertexPosition::BUFFER_LAYOUT,
-            ],
-            &color_target_states,
+            &shadow_map_vertex_buffer_layouts(is_skinned),
+            color_target_states,
             STANDARD_FRONT_FACE,
             Some(wgpu::Face::Back),
             wgpu::PolygonMode::Fill,
             true,
             None,
             "Unidirectional light shadow map update render pipeline",
-        );
-
-        Self {
-            push_constants,
-            color_target_states,
-            pipeline_layout,
-            pipeline,
-            max_light_count,
-            models: HashSet::default(),
-        }
+        )
     }
 
     pub fn sync_with_render_resources(
@@ -509,14 +610,16 @@ impl UnidirectionalLightShadowMapUpdatePasses {
 
         let model_instance_buffers = gpu_resources.model_instance_buffer();
 
-        self.models.retain(|model_id| {
-            model_instance_buffers
-                .get_model_buffers(model_id)
-                .is_some_and(has_features)
-        });
+        for models in [&mut self.models, &mut self.skinned_models] {
+            models.retain(|model_id| {
+                model_instance_buffers
+                    .get_model_buffers(model_id)
+                    .is_some_and(has_features)
+            });
+        }
 
         for (model_id, instance_feature_buffers) in model_instance_buffers.iter() {
-            if self.models.contains(model_id) {
+            if self.models.contains(model_id) || self.skinned_models.contains(model_id) {
                 continue;
             }
             if !has_features(instance_feature_buffers) {
@@ -530,7 +633,11 @@ impl UnidirectionalLightShadowMapUpdatePasses {
                     .material_template()
                     .contains(material.template_id)
             {
-                self.models.insert(*model_id);
+                if is_skinned(instance_feature_buffers) {
+                    self.skinned_models.insert(*model_id);
+                } else {
+                    self.models.insert(*model_id);
+                }
             }
         }
     }
@@ -548,25 +655,21 @@ impl UnidirectionalLightShadowMapUpdatePasses {
         let max_light_count = light_gpu_resources.max_shadowable_unidirectional_light_count();
 
         if max_light_count != self.max_light_count {
-            let shader_template = UnidirectionalLightShadowMapShaderTemplate::new(max_light_count);
-            let (_, shader) = shader_manager
-                .get_or_create_rendering_shader_from_template(graphics_device, &shader_template);
-
-            self.pipeline = render_command::create_render_pipeline(
-                graphics_device.device(),
+            self.pipeline = Self::create_pipeline(
+                graphics_device,
+                shader_manager,
                 &self.pipeline_layout,
-                shader,
-                &[
-                    InstanceModelLightTransform::BUFFER_LAYOUT.unwrap(),
-                    VertexPosition::BUFFER_LAYOUT,
-                ],
                 &self.color_target_states,
-                STANDARD_FRONT_FACE,
-                Some(wgpu::Face::Back),
-                wgpu::PolygonMode::Fill,
+                max_light_count,
+                false,
+            );
+            self.skinned_pipeline = Self::create_pipeline(
+                graphics_device,
+                shader_manager,
+                &self.skinned_pipeline_layout,
+                &self.color_target_states,
+                max_light_count,
                 true,
-                None,
-                "Unidirectional light shadow map update render pipeline",
             );
             self.max_light_count = max_light_count;
         }
@@ -712,75 +815,47 @@ impl UnidirectionalLightShadowMapUpdatePasses {
                     continue;
                 }
 
-                render_pass.set_pipeline(&self.pipeline);
-
-                self.set_light_and_cascade_idx_push_constants(
-                    &mut render_pass,
-                    u32::try_from(light_idx).unwrap(),
-                    cascade_idx,
-                );
-
-                render_pass.set_bind_group(
-                    0,
-                    light_gpu_resources.shadowable_unidirectional_light_bind_group(),
-                    &[],
-                );
-
-                for model_id in &self.models {
-                    let transform_buffer = gpu_resources
-                        .model_instance_buffer()
-                        .get_model_buffer_for_feature_feature_type::<InstanceModelLightTransform>(
-                            model_id,
-                        )
-                        .ok_or_else(|| {
-                            anyhow!(
-                                "Missing model-light transform GPU buffer for model {}",
-                                model_id
-                            )
-                        })?;
-
-                    let Some(transform_range) =
-                        transform_buffer.get_feature_range(instance_range_id)
-                    else {
-                        continue;
-                    };
-
-                    if transform_range.is_empty() {
+                for (pipeline, models, is_skinned) in [
+                    (&self.pipeline, &self.models, false),
+                    (&self.skinned_pipeline, &self.skinned_models, true),
+                ] {
+                    if models.is_empty() {
                         continue;
                     }
 
-                    render_pass.set_vertex_buffer(
-                        0,
-                        transform_buffer.vertex_gpu_buffer().valid_buffer_slice(),
-                    );
-
-                    let mesh_id = model_id.triangle_mesh_id();
-
-                    let mesh_gpu_resources = gpu_resources
-                        .triangle_mesh()
-                        .get(mesh_id)
-                        .ok_or_else(|| anyhow!("Missing GPU resources for mesh {}", mesh_id))?;
-
-                    let position_buffer = mesh_gpu_resources
-                        .request_vertex_gpu_buffers(VertexAttributeSet::POSITION)?
-                        .next()
-                        .unwrap();
-
-                    render_pass.set_vertex_buffer(1, position_buffer.valid_buffer_slice());
+                    render_pass.set_pipeline(pipeline);
 
-                    render_pass.set_index_buffer(
-                        mesh_gpu_resources
-                            .triangle_mesh_index_gpu_buffer()
-                            .valid_buffer_slice(),
-                        mesh_gpu_resources.triangle_mesh_index_format(),
+                    self.set_light_and_cascade_idx_push_constants(
+                        &mut render_pass,
+                        u32::try_from(light_idx).unwrap(),
+                        cascade_idx,
                     );
 
-                    render_pass.draw_indexed(
-                        0..u32::try_from(mesh_gpu_resources.n_indices()).unwrap(),
+                    render_pass.set_bind_group(
                         0,
-                        transform_range,
+                        light_gpu_resources.shadowable_unidirectional_light_bind_group(),
+                        &[],
                     );
-                    draw_call_count += 1;
+
+                    if is_skinned {
+                        let Some(joint_palette_gpu_resources) = gpu_resources.joint_palette()
+                        else {
+                            continue;
+                        };
+                        render_pass.set_bind_group(
+                            1,
+                            joint_palette_gpu_resources.bind_group(),
+                            &[],
+                        );
+                    }
+
+                    draw_call_count += record_shadow_map_draw_calls(
+                        gpu_resources,
+                        &mut render_pass,
+                        models,
+                        instance_range_id,
+                        is_skinned,
+                    )?;
                 }
 
                 perform_additional_draw_calls_after_cascade_update(
@@ -793,7 +868,7 @@ impl UnidirectionalLightShadowMapUpdatePasses {
         log::trace!(
             "Recorded shadow map update passes for {} unidirectional lights and {} models ({} passes, {} draw calls)",
             shadow_map_textures.len(),
-            self.models.len(),
+            self.models.len() + self.skinned_models.len(),
             pass_count,
             draw_call_count
         );
@@ -812,3 +887,120 @@ pub fn depth_stencil_state_for_shadow_map_update() -> wgpu::DepthStencilState {
         bias: wgpu::DepthBiasState::default(),
     }
 }
+
+/// Only instances set up for skinning have a joint palette offset buffer, so
+/// its presence determines whether a model is skinned.
+fn is_skinned(instance_feature_buffers: &[InstanceFeatureGPUBuffer]) -> bool {
+    instance_feature_buffers
+        .iter()
+        .any(|buffer| buffer.is_for_feature_type::<InstanceJointPaletteOffset>())
+}
+
+fn shadow_map_vertex_buffer_layouts(is_skinned: bool) -> Vec<wgpu::VertexBufferLayout<'static>> {
+    if is_skinned {
+        vec![
+            InstanceModelLightTransform::BUFFER_LAYOUT.unwrap(),
+            InstanceJointPaletteOffset::BUFFER_LAYOUT.unwrap(),
+            VertexPosition::BUFFER_LAYOUT,
+            VertexSkinWeights::BUFFER_LAYOUT,
+        ]
+    } else {
+        vec![
+            InstanceModelLightTransform::BUFFER_LAYOUT.unwrap(),
+            VertexPosition::BUFFER_LAYOUT,
+        ]
+    }
+}
+
+/// Draws the instances of the given models whose model-to-light transforms
+/// are in the given instance range.
+///
+/// # Returns
+/// The number of draw calls.
+fn record_shadow_map_draw_calls(
+    gpu_resources: &impl BasicGPUResources,
+    render_pass: &mut wgpu::RenderPass<'_>,
+    models: &HashSet<ModelID>,
+    instance_range_id: InstanceFeatureBufferRangeID,
+    is_skinned: bool,
+) -> Result<usize> {
+    let mut draw_call_count = 0;
+
+    let vertex_attributes = if is_skinned {
+        VertexAttributeSet::POSITION | VertexAttributeSet::SKIN_WEIGHTS
+    } else {
+        VertexAttributeSet::POSITION
+    };
+
+    for model_id in models {
+        let transform_buffer = gpu_resources
+            .model_instance_buffer()
+            .get_model_buffer_for_feature_feature_type::<InstanceModelLightTransform>(model_id)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Missing model-light transform GPU buffer for model {}",
+                    model_id
+                )
+            })?;
+
+        let Some(transform_range) = transform_buffer.get_feature_range(instance_range_id) else {
+            continue;
+        };
+
+        if transform_range.is_empty() {
+            continue;
+        }
+
+        render_pass.set_vertex_buffer(0, transform_buffer.vertex_gpu_buffer().valid_buffer_slice());
+
+        let mut vertex_buffer_slot = 1;
+
+        if is_skinned {
+            let joint_palette_offset_buffer = gpu_resources
+                .model_instance_buffer()
+                .get_model_buffer_for_feature_feature_type::<InstanceJointPaletteOffset>(model_id)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Missing joint palette offset GPU buffer for model {}",
+                        model_id
+                    )
+                })?;
+
+            render_pass.set_vertex_buffer(
+                vertex_buffer_slot,
+                joint_palette_offset_buffer
+                    .vertex_gpu_buffer()
+                    .valid_buffer_slice(),
+            );
+            vertex_buffer_slot += 1;
+        }
+
+        let mesh_id = model_id.triangle_mesh_id();
+
+        let mesh_gpu_resources = gpu_resources
+            .triangle_mesh()
+            .get(mesh_id)
+            .ok_or_else(|| anyhow!("Missing GPU resources for mesh {}", mesh_id))?;
+
+        for vertex_buffer in mesh_gpu_resources.request_vertex_gpu_buffers(vertex_attributes)? {
+            render_pass.set_vertex_buffer(vertex_buffer_slot, vertex_buffer.valid_buffer_slice());
+            vertex_buffer_slot += 1;
+        }
+
+        render_pass.set_index_buffer(
+            mesh_gpu_resources
+                .triangle_mesh_index_gpu_buffer()
+                .valid_buffer_slice(),
+            mesh_gpu_resources.triangle_mesh_index_format(),
+        );
+
+        render_pass.draw_indexed(
+            0..u32::try_from(mesh_gpu_resources.n_indices()).unwrap(),
+            0,
+            transform_range,
+        );
+        draw_call_count += 1;
+    }
+
+    Ok(draw_call_count)
+}
@@ -1,5 +1,6 @@
 //! Synchronization of GPU buffers with geometrical data.
 
+use impact_animation::gpu_resource::JointPaletteGPUResource;
 use impact_camera::gpu_resource::CameraGPUResource;
 use impact_light::gpu_resource::LightGPUResources;
 use impact_material::{
@@ -52,6 +53,10 @@ pub trait BasicGPUResources {
     /// created.
     fn light(&self) -> Option<&LightGPUResources>;
 
+    /// Returns the GPU resource for the joint palettes used for skinning, or
+    /// [`None`] if it has not been created.
+    fn joint_palette(&self) -> Option<&JointPaletteGPUResource>;
+
     /// Returns the GPU resource map for triangle mesh data.
     fn triangle_mesh(&self) -> &TriangleMeshGPUResourceMap;
 
@@ -4,23 +4,60 @@ pub mod ambient_occlusion_computation;
 pub mod bloom_blending;
 pub mod bloom_downsampling;
 pub mod bloom_upsampling_blur;
+pub mod depth_of_field;
 pub mod dynamic_range_compression;
 pub mod fixed_color;
 pub mod gaussian_blur;
+pub mod hierarchical_depth;
 pub mod luminance_histogram;
 pub mod luminance_histogram_average;
 pub mod model_depth_prepass;
 pub mod model_geometry;
+pub mod motion_blur;
 pub mod omnidirectional_light;
 pub mod omnidirectional_light_shadow_map;
 pub mod passthrough;
 pub mod render_attachment_visualization;
+pub mod screen_space_reflection_application;
+pub mod screen_space_reflection_temporal_resolve;
+pub mod screen_space_reflection_tracing;
 pub mod shadowable_omnidirectional_light;
 pub mod shadowable_unidirectional_light;
 pub mod skybox;
 pub mod temporal_anti_aliasing;
 pub mod unidirectional_light;
 pub mod unidirectional_light_shadow_map;
+pub mod volumetric_fog_application;
+pub mod volumetric_fog_injection;
+pub mod volumetric_fog_integration;
+
+use impact_animation::gpu_resource::JointPaletteGPUResource;
+use impact_mesh::gpu_resource::MeshVertexAttributeLocation;
+use impact_model::skinning::InstanceJointPaletteOffset;
+
+/// Returns the replacements for the skinning inputs of model vertex shaders,
+/// with the joint palette bound in the given bind group.
+fn skinning_replacements(joint_palette_group: u32) -> [(&'static str, String); 5] {
+    [
+        (
+            "joint_palette_offset_location",
+            InstanceJointPaletteOffset::location().to_string(),
+        ),
+        (
+            "joint_indices_location",
+            (MeshVertexAttributeLocation::JointIndices as u32).to_string(),
+        ),
+        (
+            "joint_weights_location",
+            (MeshVertexAttributeLocation::JointWeights as u32).to_string(),
+        ),
+        ("joint_palette_group", joint_palette_group.to_string()),
+        (
+            "joint_palette_binding",
+            JointPaletteGPUResource::binding().to_string(),
+        ),
+    ]
+}
 
 #[macro_export]
 macro_rules! compute_template_source {
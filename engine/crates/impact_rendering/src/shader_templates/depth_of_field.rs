@@ -0,0 +1,129 @@
+//! Shader template for the depth of field pass.
+
+use crate::{
+    attachment::{
+        RenderAttachmentDescription, RenderAttachmentInputDescription,
+        RenderAttachmentInputDescriptionSet, RenderAttachmentOutputDescriptionSet,
+        RenderAttachmentQuantity::{LinearDepth, Luminance},
+        RenderAttachmentQuantitySet, RenderAttachmentSampler,
+    },
+    postprocessing::PostprocessingShaderTemplate,
+    push_constant::{BasicPushConstantGroup, BasicPushConstantVariant},
+    rendering_template_source,
+};
+use impact_camera::gpu_resource::CameraProjectionUniform;
+use impact_gpu::{
+    resource_group::GPUResourceGroupID,
+    shader::{
+        ShaderID,
+        template::{ShaderTemplate, SpecificShaderTemplate},
+    },
+    shader_template_replacements,
+};
+use impact_mesh::gpu_resource::MeshVertexAttributeLocation;
+use std::sync::LazyLock;
+
+/// Shader template for the depth of field pass, which blurs the luminance of
+/// each pixel according to the circle of confusion of a thin lens and writes
+/// the result to the auxiliary luminance attachment.
+#[derive(Clone, Debug)]
+pub struct DepthOfFieldShaderTemplate {
+    params_resource_group_id: GPUResourceGroupID,
+    push_constants: BasicPushConstantGroup,
+    input_render_attachments: RenderAttachmentInputDescriptionSet,
+    output_render_attachments: RenderAttachmentOutputDescriptionSet,
+}
+
+static TEMPLATE: LazyLock<ShaderTemplate<'static>> =
+    LazyLock::new(|| ShaderTemplate::new(rendering_template_source!("depth_of_field")).unwrap());
+
+impl DepthOfFieldShaderTemplate {
+    /// Creates a new depth of field shader template using the given resource
+    /// group ID for the depth of field parameters.
+    pub fn new(params_resource_group_id: GPUResourceGroupID) -> Self {
+        let push_constants = BasicPushConstantGroup::for_fragment([
+            BasicPushConstantVariant::InverseWindowDimensions,
+        ]);
+
+        let input_render_attachments = RenderAttachmentInputDescriptionSet::new(vec![
+            RenderAttachmentInputDescription::default_for(LinearDepth),
+            RenderAttachmentInputDescription::default_for(Luminance)
+                .with_sampler(RenderAttachmentSampler::Filtering),
+        ]);
+
+        let output_render_attachments = RenderAttachmentOutputDescriptionSet::with_defaults(
+            RenderAttachmentQuantitySet::LUMINANCE_AUX,
+        );
+
+        Self {
+            params_resource_group_id,
+            push_constants,
+            input_render_attachments,
+            output_render_attachments,
+        }
+    }
+}
+
+impl SpecificShaderTemplate for DepthOfFieldShaderTemplate {
+    fn resolve(&self) -> String {
+        TEMPLATE
+            .resolve(
+                &[],
+                shader_template_replacements!(
+                    "projection_uniform_group" => 0,
+                    "projection_uniform_binding" => CameraProjectionUniform::binding(),
+                    "linear_depth_texture_group" => 1,
+                    "linear_depth_texture_binding" => LinearDepth.texture_binding(),
+                    "linear_depth_sampler_binding" => LinearDepth.sampler_binding(),
+                    "luminance_texture_group" => 2,
+                    "luminance_texture_binding" => Luminance.texture_binding(),
+                    "luminance_sampler_binding" => Luminance.sampler_binding(),
+                    "params_group" => 3,
+                    "params_binding" => 0,
+                    "position_location" => MeshVertexAttributeLocation::Position as u32,
+                ),
+            )
+            .expect("Shader template resolution failed")
+    }
+
+    fn shader_id(&self) -> ShaderID {
+        ShaderID::from_identifier("DepthOfFieldShaderTemplate")
+    }
+}
+
+impl PostprocessingShaderTemplate for DepthOfFieldShaderTemplate {
+    fn push_constants(&self) -> BasicPushConstantGroup {
+        self.push_constants.clone()
+    }
+
+    fn input_render_attachments(&self) -> RenderAttachmentInputDescriptionSet {
+        self.input_render_attachments.clone()
+    }
+
+    fn output_render_attachments(&self) -> RenderAttachmentOutputDescriptionSet {
+        self.output_render_attachments.clone()
+    }
+
+    fn uses_camera(&self) -> bool {
+        true
+    }
+
+    fn gpu_resource_group_id(&self) -> Option<GPUResourceGroupID> {
+        Some(self.params_resource_group_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use impact_gpu::shader::template::validate_template;
+    use impact_math::hash64;
+
+    #[test]
+    fn should_resolve_to_valid_wgsl() {
+        validate_template(&DepthOfFieldShaderTemplate::new(GPUResourceGroupID(
+            hash64!("test".to_string()),
+        )));
+    }
+}
@@ -0,0 +1,92 @@
+//! Shader template for the passes building the hierarchical depth pyramid.
+
+use crate::attachment::RenderAttachmentQuantity;
+use crate::rendering_template_source;
+use impact_gpu::{
+    shader::{
+        ShaderID,
+        template::{ShaderTemplate, SpecificShaderTemplate},
+    },
+    shader_template_replacements,
+};
+use impact_mesh::gpu_resource::MeshVertexAttributeLocation;
+use std::sync::LazyLock;
+
+/// Shader template for the passes building the hierarchical depth pyramid,
+/// where each mip level holds the minimum linear depth over the texels it
+/// covers in the level below. The base level pass copies the linear depth
+/// attachment into mip level 0, while the remaining passes successively
+/// downsample the previous mip level.
+#[derive(Clone, Debug)]
+pub struct HierarchicalDepthShaderTemplate {
+    input_quantity: RenderAttachmentQuantity,
+    base_level: bool,
+}
+
+static TEMPLATE: LazyLock<ShaderTemplate<'static>> = LazyLock::new(|| {
+    ShaderTemplate::new(rendering_template_source!("hierarchical_depth")).unwrap()
+});
+
+impl HierarchicalDepthShaderTemplate {
+    /// Creates a new shader template for the pass writing the base level of
+    /// the pyramid from the linear depth attachment.
+    pub fn base_level() -> Self {
+        Self {
+            input_quantity: RenderAttachmentQuantity::LinearDepth,
+            base_level: true,
+        }
+    }
+
+    /// Creates a new shader template for the passes computing each mip level
+    /// of the pyramid from the level below.
+    pub fn downsampling() -> Self {
+        Self {
+            input_quantity: RenderAttachmentQuantity::HierarchicalDepth,
+            base_level: false,
+        }
+    }
+}
+
+impl SpecificShaderTemplate for HierarchicalDepthShaderTemplate {
+    fn resolve(&self) -> String {
+        let flags: &[&str] = if self.base_level {
+            &["base_level"]
+        } else {
+            &[]
+        };
+        TEMPLATE
+            .resolve(
+                flags,
+                shader_template_replacements!(
+                    "input_texture_binding" => self.input_quantity.texture_binding(),
+                    "input_sampler_binding" => self.input_quantity.sampler_binding(),
+                    "position_location" => MeshVertexAttributeLocation::Position as u32,
+                ),
+            )
+            .expect("Shader template resolution failed")
+    }
+
+    fn shader_id(&self) -> ShaderID {
+        ShaderID::from_identifier(&format!(
+            "HierarchicalDepthShaderTemplate{{ base_level = {} }}",
+            self.base_level
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use impact_gpu::shader::template::validate_template;
+
+    #[test]
+    fn should_resolve_to_valid_wgsl_for_base_level() {
+        validate_template(&HierarchicalDepthShaderTemplate::base_level());
+    }
+
+    #[test]
+    fn should_resolve_to_valid_wgsl_for_downsampling() {
+        validate_template(&HierarchicalDepthShaderTemplate::downsampling());
+    }
+}
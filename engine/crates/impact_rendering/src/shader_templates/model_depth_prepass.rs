@@ -14,13 +14,29 @@ use std::sync::LazyLock;
 /// Shader template for the model depth prepass, which writes the depth
 /// of the rendered model instances to the depth-stencil attachment.
 #[derive(Clone, Copy, Debug)]
-pub struct ModelDepthPrepassShaderTemplate;
+pub struct ModelDepthPrepassShaderTemplate {
+    is_skinned: bool,
+}
 
 static TEMPLATE: LazyLock<ShaderTemplate<'static>> = LazyLock::new(|| {
     ShaderTemplate::new(rendering_template_source!("model_depth_prepass")).unwrap()
 });
 
 impl ModelDepthPrepassShaderTemplate {
+    /// The bind group index of the joint palette used for skinning.
+    pub const JOINT_PALETTE_GROUP: u32 = 1;
+
+    /// Creates a new depth prepass shader template for either skinned or
+    /// unskinned models.
+    pub fn new(is_skinned: bool) -> Self {
+        Self { is_skinned }
+    }
+
+    /// Whether the shader skins the vertex positions.
+    pub fn is_skinned(&self) -> bool {
+        self.is_skinned
+    }
+
     /// Returns the group of push constants used by the shader.
     pub fn push_constants() -> BasicPushConstantGroup {
         BasicPushConstantGroup::for_vertex([
@@ -30,17 +46,20 @@ impl ModelDepthPrepassShaderTemplate {
     }
 
     /// Returns the set of vertex attributes used by the shader.
-    pub fn vertex_attributes() -> VertexAttributeSet {
-        VertexAttributeSet::POSITION
+    pub fn vertex_attributes(&self) -> VertexAttributeSet {
+        if self.is_skinned {
+            VertexAttributeSet::POSITION | VertexAttributeSet::SKIN_WEIGHTS
+        } else {
+            VertexAttributeSet::POSITION
+        }
     }
 }
 
 impl SpecificShaderTemplate for ModelDepthPrepassShaderTemplate {
     fn resolve(&self) -> String {
-        TEMPLATE
-            .resolve(
-                &[],
-                shader_template_replacements!(
+        let mut flags_to_set = Vec::new();
+
+        let mut replacements = shader_template_replacements!(
                     "jitter_count" => CameraProjectionUniform::jitter_count(),
                     "model_view_transform_rotation_location" => InstanceModelViewTransformWithPrevious::current_rotation_location(),
                     "model_view_transform_translation_location" => InstanceModelViewTransformWithPrevious::current_translation_and_scaling_location(),
@@ -49,8 +68,15 @@ impl SpecificShaderTemplate for ModelDepthPrepassShaderTemplate {
                     "projection_uniform_group" => 0,
                     "projection_uniform_binding" => CameraProjectionUniform::binding(),
                     "position_location" => MeshVertexAttributeLocation::Position as u32,
-                )
-            )
+                ).to_vec();
+
+        if self.is_skinned {
+            flags_to_set.push("is_skinned");
+            replacements.extend(super::skinning_replacements(Self::JOINT_PALETTE_GROUP));
+        }
+
+        TEMPLATE
+            .resolve(&flags_to_set, &replacements)
             .expect("Shader template resolution failed")
     }
 }
@@ -63,6 +89,11 @@ mod tests {
 
     #[test]
     fn should_resolve_to_valid_wgsl() {
-        validate_template(&ModelDepthPrepassShaderTemplate);
+        validate_template(&ModelDepthPrepassShaderTemplate::new(false));
+    }
+
+    #[test]
+    fn should_resolve_to_valid_wgsl_for_skinned_models() {
+        validate_template(&ModelDepthPrepassShaderTemplate::new(true));
     }
 }
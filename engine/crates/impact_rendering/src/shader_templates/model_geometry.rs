@@ -18,13 +18,14 @@ use impact_material::{
     values::{MaterialInstanceFeatureLocation, MaterialPropertyFlags},
 };
 use impact_mesh::{VertexAttributeSet, gpu_resource::MeshVertexAttributeLocation};
-use impact_model::transform::InstanceModelViewTransformWithPrevious;
+use impact_model::{entity::InstanceEntityID, transform::InstanceModelViewTransformWithPrevious};
 use std::sync::LazyLock;
 
 /// Input for a specific instance of the model geometry shader template.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct ModelGeometryShaderInput {
-    /// The set of vertex attributes the model has.
+    /// The set of vertex attributes the model has. The model is skinned if
+    /// this includes [`VertexAttributeSet::SKIN_WEIGHTS`].
     pub vertex_attributes: VertexAttributeSet,
     /// The instance feature flags for the model's material.
     pub material_instance_feature_flags: MaterialPropertyFlags,
@@ -40,6 +41,7 @@ pub struct ModelGeometryShaderInput {
 #[derive(Clone, Debug)]
 pub struct ModelGeometryShaderTemplate {
     input: ModelGeometryShaderInput,
+    writes_entity_id: bool,
 }
 
 static TEMPLATE: LazyLock<ShaderTemplate<'static>> =
@@ -47,14 +49,19 @@ static TEMPLATE: LazyLock<ShaderTemplate<'static>> =
 
 impl ModelGeometryShaderInput {
     /// Returns the model geometry shader input corresponding to the given
-    /// material template, or [`None`] if the material template is not
-    /// compatible with the geometry shader (it is not physically based).
-    pub fn for_material_template(template: &MaterialTemplate) -> Option<Self> {
+    /// material template and whether the model's mesh is skinned, or [`None`]
+    /// if the material template is not compatible with the geometry shader
+    /// (it is not physically based).
+    pub fn for_material_template(template: &MaterialTemplate, is_skinned: bool) -> Option<Self> {
         if let MaterialTextureBindingLocations::Physical(material_texture_bindings) =
             &template.texture_binding_locations
         {
+            let mut vertex_attributes = template.vertex_attribute_requirements;
+            if is_skinned {
+                vertex_attributes |= VertexAttributeSet::SKIN_WEIGHTS;
+            }
             Some(Self {
-                vertex_attributes: template.vertex_attribute_requirements,
+                vertex_attributes,
                 material_instance_feature_flags: template.property_flags,
                 material_texture_bindings: material_texture_bindings.clone(),
             })
@@ -62,12 +69,47 @@ impl ModelGeometryShaderInput {
             None
         }
     }
+
+    /// Whether the model's mesh is skinned.
+    pub fn is_skinned(&self) -> bool {
+        self.vertex_attributes
+            .contains(VertexAttributeSet::SKIN_WEIGHTS)
+    }
+
+    /// Whether the texture coordinates of each instance are transformed by a
+    /// [`TextureCoordTransform`](impact_material::values::TextureCoordTransform)
+    /// instance feature.
+    pub fn uses_texture_coord_transform(&self) -> bool {
+        self.material_instance_feature_flags
+            .contains(MaterialPropertyFlags::USES_TEXTURE_COORD_TRANSFORM)
+    }
+
+    /// Whether the model's material has any textures, which are bound in a
+    /// separate bind group.
+    pub fn has_material_textures(&self) -> bool {
+        let bindings = &self.material_texture_bindings;
+        bindings.color_texture_and_sampler_bindings.is_some()
+            || bindings
+                .specular_reflectance_texture_and_sampler_bindings
+                .is_some()
+            || bindings.roughness_texture_and_sampler_bindings.is_some()
+            || bindings.metalness_texture_and_sampler_bindings.is_some()
+            || bindings
+                .emissive_luminance_texture_and_sampler_bindings
+                .is_some()
+            || bindings.bump_mapping.is_some()
+    }
 }
 
 impl ModelGeometryShaderTemplate {
-    /// Creates a new model geometry shader template for the given input.
-    pub fn new(input: ModelGeometryShaderInput) -> Self {
-        Self { input }
+    /// Creates a new model geometry shader template for the given input. If
+    /// `writes_entity_id` is `true`, the shader will also write the entity ID
+    /// of each model instance to the entity ID render attachment.
+    pub fn new(input: ModelGeometryShaderInput, writes_entity_id: bool) -> Self {
+        Self {
+            input,
+            writes_entity_id,
+        }
     }
 
     /// Returns the group of push constants used by the shader.
@@ -80,15 +122,36 @@ impl ModelGeometryShaderTemplate {
     }
 
     /// Returns the descriptions of the render attachments that the shader will
-    /// write to.
-    pub fn output_render_attachments() -> RenderAttachmentOutputDescriptionSet {
-        RenderAttachmentOutputDescriptionSet::with_defaults(RenderAttachmentQuantitySet::g_buffer())
+    /// write to, depending on whether it writes entity IDs.
+    pub fn output_render_attachments(
+        writes_entity_id: bool,
+    ) -> RenderAttachmentOutputDescriptionSet {
+        let mut quantities = RenderAttachmentQuantitySet::g_buffer();
+        if writes_entity_id {
+            quantities |= RenderAttachmentQuantitySet::ENTITY_ID;
+        }
+        RenderAttachmentOutputDescriptionSet::with_defaults(quantities)
     }
 
     /// Returns the input used for this instance of the shader template.
     pub fn input(&self) -> &ModelGeometryShaderInput {
         &self.input
     }
+
+    /// Whether this instance of the shader template writes entity IDs.
+    pub fn writes_entity_id(&self) -> bool {
+        self.writes_entity_id
+    }
+
+    /// Returns the index of the bind group for the joint matrices used for
+    /// skinning, which comes after the camera and material texture groups.
+    pub fn joint_palette_group(&self) -> u32 {
+        if self.input.has_material_textures() {
+            2
+        } else {
+            1
+        }
+    }
 }
 
 impl SpecificShaderTemplate for ModelGeometryShaderTemplate {
@@ -109,6 +172,8 @@ impl SpecificShaderTemplate for ModelGeometryShaderTemplate {
                 "color_location" => MaterialInstanceFeatureLocation::Color as u32,
                 "parallax_displacement_scale_location" => MaterialInstanceFeatureLocation::ParallaxDisplacementScale as u32,
                 "parallax_uv_per_distance_location" => MaterialInstanceFeatureLocation::ParallaxUVPerDistance as u32,
+                "texture_coord_offset_location" => MaterialInstanceFeatureLocation::TextureCoordOffset as u32,
+                "texture_coord_scale_location" => MaterialInstanceFeatureLocation::TextureCoordScale as u32,
                 "position_location" => MeshVertexAttributeLocation::Position as u32,
                 "normal_vector_location" => MeshVertexAttributeLocation::NormalVector as u32,
                 "texture_coords_location" => MeshVertexAttributeLocation::TextureCoords as u32,
@@ -158,6 +223,10 @@ impl SpecificShaderTemplate for ModelGeometryShaderTemplate {
             flags_to_set.push("uses_parallax_mapping");
         }
 
+        if self.input.uses_texture_coord_transform() {
+            flags_to_set.push("uses_texture_coord_transform");
+        }
+
         if let Some((texture_binding, sampler_binding)) = self
             .input
             .material_texture_bindings
@@ -264,6 +333,34 @@ impl SpecificShaderTemplate for ModelGeometryShaderTemplate {
             None => {}
         }
 
+        if self.input.is_skinned() {
+            flags_to_set.push("is_skinned");
+            replacements.extend(super::skinning_replacements(self.joint_palette_group()));
+
+            if self
+                .input
+                .vertex_attributes
+                .contains(VertexAttributeSet::NORMAL_VECTOR)
+            {
+                flags_to_set.push("skins_normal_vector");
+            }
+            if self
+                .input
+                .vertex_attributes
+                .contains(VertexAttributeSet::TANGENT_SPACE_QUATERNION)
+            {
+                flags_to_set.push("skins_tangent_space_quaternion");
+            }
+        }
+
+        if self.writes_entity_id {
+            flags_to_set.push("writes_entity_id");
+            replacements.push((
+                "entity_id_location",
+                InstanceEntityID::location().to_string(),
+            ));
+        }
+
         TEMPLATE
             .resolve(&flags_to_set, &replacements)
             .expect("Shader template resolution failed")
@@ -295,6 +392,7 @@ mod tests {
                     bump_mapping: None,
                 },
             },
+            false,
         ));
     }
 
@@ -319,6 +417,7 @@ mod tests {
                     )),
                 },
             },
+            false,
         ));
     }
 
@@ -345,6 +444,95 @@ mod tests {
                     ),
                 },
             },
+            false,
+        ));
+    }
+
+    #[test]
+    fn should_resolve_to_valid_wgsl_for_skinned_input_with_normal_vectors() {
+        validate_template(&ModelGeometryShaderTemplate::new(
+            ModelGeometryShaderInput {
+                vertex_attributes: VertexAttributeSet::POSITION
+                    | VertexAttributeSet::NORMAL_VECTOR
+                    | VertexAttributeSet::SKIN_WEIGHTS,
+                material_instance_feature_flags: MaterialPropertyFlags::HAS_COLOR,
+                material_texture_bindings: PhysicalMaterialTextureBindingLocations {
+                    color_texture_and_sampler_bindings: None,
+                    specular_reflectance_texture_and_sampler_bindings: None,
+                    roughness_texture_and_sampler_bindings: None,
+                    metalness_texture_and_sampler_bindings: None,
+                    emissive_luminance_texture_and_sampler_bindings: None,
+                    bump_mapping: None,
+                },
+            },
+            false,
+        ));
+    }
+
+    #[test]
+    fn should_resolve_to_valid_wgsl_for_skinned_fully_textured_input_with_normal_mapping() {
+        validate_template(&ModelGeometryShaderTemplate::new(
+            ModelGeometryShaderInput {
+                vertex_attributes: VertexAttributeSet::POSITION
+                    | VertexAttributeSet::TEXTURE_COORDS
+                    | VertexAttributeSet::TANGENT_SPACE_QUATERNION
+                    | VertexAttributeSet::SKIN_WEIGHTS,
+                material_instance_feature_flags: MaterialPropertyFlags::empty(),
+                material_texture_bindings: PhysicalMaterialTextureBindingLocations {
+                    color_texture_and_sampler_bindings: Some((0, 1)),
+                    specular_reflectance_texture_and_sampler_bindings: Some((2, 3)),
+                    roughness_texture_and_sampler_bindings: Some((4, 5)),
+                    metalness_texture_and_sampler_bindings: Some((6, 7)),
+                    emissive_luminance_texture_and_sampler_bindings: Some((8, 9)),
+                    bump_mapping: Some(PhysicalMaterialBumpMappingTextureBindings::NormalMapping(
+                        PhysicalMaterialNormalMappingTextureBindings {
+                            normal_map_texture_and_sampler_bindings: (10, 11),
+                        },
+                    )),
+                },
+            },
+            true,
+        ));
+    }
+
+    #[test]
+    fn should_resolve_to_valid_wgsl_for_textured_input_with_texture_coord_transform() {
+        validate_template(&ModelGeometryShaderTemplate::new(
+            ModelGeometryShaderInput {
+                vertex_attributes: VertexAttributeSet::POSITION
+                    | VertexAttributeSet::NORMAL_VECTOR
+                    | VertexAttributeSet::TEXTURE_COORDS,
+                material_instance_feature_flags:
+                    MaterialPropertyFlags::USES_TEXTURE_COORD_TRANSFORM,
+                material_texture_bindings: PhysicalMaterialTextureBindingLocations {
+                    color_texture_and_sampler_bindings: Some((0, 1)),
+                    specular_reflectance_texture_and_sampler_bindings: None,
+                    roughness_texture_and_sampler_bindings: None,
+                    metalness_texture_and_sampler_bindings: None,
+                    emissive_luminance_texture_and_sampler_bindings: None,
+                    bump_mapping: None,
+                },
+            },
+            false,
+        ));
+    }
+
+    #[test]
+    fn should_resolve_to_valid_wgsl_for_basic_input_writing_entity_id() {
+        validate_template(&ModelGeometryShaderTemplate::new(
+            ModelGeometryShaderInput {
+                vertex_attributes: VertexAttributeSet::POSITION | VertexAttributeSet::NORMAL_VECTOR,
+                material_instance_feature_flags: MaterialPropertyFlags::HAS_COLOR,
+                material_texture_bindings: PhysicalMaterialTextureBindingLocations {
+                    color_texture_and_sampler_bindings: None,
+                    specular_reflectance_texture_and_sampler_bindings: None,
+                    roughness_texture_and_sampler_bindings: None,
+                    metalness_texture_and_sampler_bindings: None,
+                    emissive_luminance_texture_and_sampler_bindings: None,
+                    bump_mapping: None,
+                },
+            },
+            true,
         ));
     }
 }
@@ -18,6 +18,7 @@ use std::sync::LazyLock;
 #[derive(Clone, Debug)]
 pub struct OmnidirectionalLightShadowMapShaderTemplate {
     max_light_count: usize,
+    is_skinned: bool,
 }
 
 static TEMPLATE: LazyLock<ShaderTemplate<'static>> = LazyLock::new(|| {
@@ -28,10 +29,22 @@ static TEMPLATE: LazyLock<ShaderTemplate<'static>> = LazyLock::new(|| {
 });
 
 impl OmnidirectionalLightShadowMapShaderTemplate {
+    /// The bind group index of the joint palette used for skinning.
+    pub const JOINT_PALETTE_GROUP: u32 = 1;
+
     /// Creates a new omnidirectional light shadow map shader template for the
-    /// given maximum number of omnidirectional lights.
-    pub fn new(max_light_count: usize) -> Self {
-        Self { max_light_count }
+    /// given maximum number of omnidirectional lights, for either skinned or
+    /// unskinned models.
+    pub fn new(max_light_count: usize, is_skinned: bool) -> Self {
+        Self {
+            max_light_count,
+            is_skinned,
+        }
+    }
+
+    /// Whether the shader skins the vertex positions.
+    pub fn is_skinned(&self) -> bool {
+        self.is_skinned
     }
 
     /// Returns the group of push constants used by the shader.
@@ -42,18 +55,24 @@ impl OmnidirectionalLightShadowMapShaderTemplate {
 
 impl SpecificShaderTemplate for OmnidirectionalLightShadowMapShaderTemplate {
     fn resolve(&self) -> String {
-        TEMPLATE
-            .resolve(
-                &[],
-                shader_template_replacements!(
+        let mut flags_to_set = Vec::new();
+
+        let mut replacements = shader_template_replacements!(
                     "max_light_count" => self.max_light_count,
                     "model_light_transform_rotation_location" => InstanceModelLightTransform::rotation_location(),
                     "model_light_transform_translation_location" => InstanceModelLightTransform::translation_and_scaling_location(),
                     "light_uniform_group" => 0,
                     "light_uniform_binding" => LightGPUResources::light_binding(),
                     "position_location" => MeshVertexAttributeLocation::Position as u32,
-                )
-            )
+                ).to_vec();
+
+        if self.is_skinned {
+            flags_to_set.push("is_skinned");
+            replacements.extend(super::skinning_replacements(Self::JOINT_PALETTE_GROUP));
+        }
+
+        TEMPLATE
+            .resolve(&flags_to_set, &replacements)
             .expect("Shader template resolution failed")
     }
 }
@@ -66,6 +85,11 @@ mod tests {
 
     #[test]
     fn should_resolve_to_valid_wgsl() {
-        validate_template(&OmnidirectionalLightShadowMapShaderTemplate::new(5));
+        validate_template(&OmnidirectionalLightShadowMapShaderTemplate::new(5, false));
+    }
+
+    #[test]
+    fn should_resolve_to_valid_wgsl_for_skinned_models() {
+        validate_template(&OmnidirectionalLightShadowMapShaderTemplate::new(5, true));
     }
 }
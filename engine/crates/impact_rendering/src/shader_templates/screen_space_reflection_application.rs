@@ -0,0 +1,154 @@
+//! Shader template for the screen-space reflection application pass.
+
+use crate::{
+    attachment::{
+        Blending, RenderAttachmentDescription, RenderAttachmentInputDescription,
+        RenderAttachmentInputDescriptionSet, RenderAttachmentOutputDescription,
+        RenderAttachmentOutputDescriptionSet,
+        RenderAttachmentQuantity::{
+            LinearDepth, Luminance, MaterialColor, MaterialProperties, NormalVector,
+            ReflectedLuminanceHistory,
+        },
+    },
+    postprocessing::PostprocessingShaderTemplate,
+    push_constant::{BasicPushConstantGroup, BasicPushConstantVariant},
+    render_command::StencilValue,
+    rendering_template_source,
+};
+use impact_camera::gpu_resource::CameraProjectionUniform;
+use impact_gpu::{
+    resource_group::GPUResourceGroupID,
+    shader::{
+        ShaderID,
+        template::{ShaderTemplate, SpecificShaderTemplate},
+    },
+    shader_template_replacements, wgpu,
+};
+use impact_mesh::gpu_resource::MeshVertexAttributeLocation;
+use std::sync::LazyLock;
+
+/// Shader template for the screen-space reflection application pass, which
+/// weights the temporally accumulated reflected luminance from the
+/// screen-space reflection temporal resolve pass with the Fresnel reflectance of each surface and adds it to
+/// the luminance attachment.
+#[derive(Clone, Debug)]
+pub struct ScreenSpaceReflectionApplicationShaderTemplate {
+    params_resource_group_id: GPUResourceGroupID,
+    push_constants: BasicPushConstantGroup,
+    input_render_attachments: RenderAttachmentInputDescriptionSet,
+    output_render_attachments: RenderAttachmentOutputDescriptionSet,
+}
+
+static TEMPLATE: LazyLock<ShaderTemplate<'static>> = LazyLock::new(|| {
+    ShaderTemplate::new(rendering_template_source!(
+        "screen_space_reflection_application"
+    ))
+    .unwrap()
+});
+
+impl ScreenSpaceReflectionApplicationShaderTemplate {
+    /// Creates a new screen-space reflection application shader template using
+    /// the given resource group ID for the screen-space reflection parameters.
+    pub fn new(params_resource_group_id: GPUResourceGroupID) -> Self {
+        let push_constants = BasicPushConstantGroup::for_fragment([
+            BasicPushConstantVariant::InverseWindowDimensions,
+        ]);
+
+        let input_render_attachments = RenderAttachmentInputDescriptionSet::new(vec![
+            RenderAttachmentInputDescription::default_for(LinearDepth),
+            RenderAttachmentInputDescription::default_for(NormalVector),
+            RenderAttachmentInputDescription::default_for(MaterialColor),
+            RenderAttachmentInputDescription::default_for(MaterialProperties),
+            RenderAttachmentInputDescription::default_for(ReflectedLuminanceHistory),
+        ]);
+
+        let output_render_attachments = RenderAttachmentOutputDescriptionSet::single(
+            RenderAttachmentOutputDescription::default_for(Luminance)
+                .with_blending(Blending::Additive),
+        );
+
+        Self {
+            params_resource_group_id,
+            push_constants,
+            input_render_attachments,
+            output_render_attachments,
+        }
+    }
+}
+
+impl SpecificShaderTemplate for ScreenSpaceReflectionApplicationShaderTemplate {
+    fn resolve(&self) -> String {
+        TEMPLATE
+            .resolve(
+                &[],
+                shader_template_replacements!(
+                    "projection_uniform_group" => 0,
+                    "projection_uniform_binding" => CameraProjectionUniform::binding(),
+                    "linear_depth_texture_group" => 1,
+                    "linear_depth_texture_binding" => LinearDepth.texture_binding(),
+                    "linear_depth_sampler_binding" => LinearDepth.sampler_binding(),
+                    "normal_vector_texture_group" => 2,
+                    "normal_vector_texture_binding" => NormalVector.texture_binding(),
+                    "normal_vector_sampler_binding" => NormalVector.sampler_binding(),
+                    "material_color_texture_group" => 3,
+                    "material_color_texture_binding" => MaterialColor.texture_binding(),
+                    "material_color_sampler_binding" => MaterialColor.sampler_binding(),
+                    "material_properties_texture_group" => 4,
+                    "material_properties_texture_binding" => MaterialProperties.texture_binding(),
+                    "material_properties_sampler_binding" => MaterialProperties.sampler_binding(),
+                    "reflected_luminance_texture_group" => 5,
+                    "reflected_luminance_texture_binding" => ReflectedLuminanceHistory.texture_binding(),
+                    "reflected_luminance_sampler_binding" => ReflectedLuminanceHistory.sampler_binding(),
+                    "params_group" => 6,
+                    "params_binding" => 0,
+                    "position_location" => MeshVertexAttributeLocation::Position as u32,
+                ),
+            )
+            .expect("Shader template resolution failed")
+    }
+
+    fn shader_id(&self) -> ShaderID {
+        ShaderID::from_identifier("ScreenSpaceReflectionApplicationShaderTemplate")
+    }
+}
+
+impl PostprocessingShaderTemplate for ScreenSpaceReflectionApplicationShaderTemplate {
+    fn push_constants(&self) -> BasicPushConstantGroup {
+        self.push_constants.clone()
+    }
+
+    fn input_render_attachments(&self) -> RenderAttachmentInputDescriptionSet {
+        self.input_render_attachments.clone()
+    }
+
+    fn output_render_attachments(&self) -> RenderAttachmentOutputDescriptionSet {
+        self.output_render_attachments.clone()
+    }
+
+    fn uses_camera(&self) -> bool {
+        true
+    }
+
+    fn gpu_resource_group_id(&self) -> Option<GPUResourceGroupID> {
+        Some(self.params_resource_group_id)
+    }
+
+    fn stencil_test(&self) -> Option<(wgpu::CompareFunction, StencilValue)> {
+        Some((wgpu::CompareFunction::Equal, StencilValue::PhysicalModel))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use impact_gpu::shader::template::validate_template;
+    use impact_math::hash64;
+
+    #[test]
+    fn should_resolve_to_valid_wgsl() {
+        validate_template(&ScreenSpaceReflectionApplicationShaderTemplate::new(
+            GPUResourceGroupID(hash64!("test".to_string())),
+        ));
+    }
+}
@@ -0,0 +1,139 @@
+//! Shader template for the screen-space reflection temporal resolve pass.
+
+use crate::{
+    attachment::{
+        RenderAttachmentDescription, RenderAttachmentInputDescription,
+        RenderAttachmentInputDescriptionSet, RenderAttachmentOutputDescriptionSet,
+        RenderAttachmentQuantity::{LuminanceAux, MotionVector, PreviousReflectedLuminanceHistory},
+        RenderAttachmentQuantitySet, RenderAttachmentSampler,
+    },
+    postprocessing::PostprocessingShaderTemplate,
+    push_constant::{BasicPushConstantGroup, BasicPushConstantVariant},
+    render_command::StencilValue,
+    rendering_template_source,
+};
+use impact_gpu::{
+    resource_group::GPUResourceGroupID,
+    shader::{
+        ShaderID,
+        template::{ShaderTemplate, SpecificShaderTemplate},
+    },
+    shader_template_replacements, wgpu,
+};
+use impact_mesh::gpu_resource::MeshVertexAttributeLocation;
+use std::sync::LazyLock;
+
+/// Shader template for the screen-space reflection temporal resolve pass,
+/// which blends the reflected luminance traced in the current frame with the
+/// reprojected reflected luminance history and writes the result to the
+/// reflected luminance history attachment.
+#[derive(Clone, Debug)]
+pub struct ScreenSpaceReflectionTemporalResolveShaderTemplate {
+    params_resource_group_id: GPUResourceGroupID,
+    push_constants: BasicPushConstantGroup,
+    input_render_attachments: RenderAttachmentInputDescriptionSet,
+    output_render_attachments: RenderAttachmentOutputDescriptionSet,
+}
+
+static TEMPLATE: LazyLock<ShaderTemplate<'static>> = LazyLock::new(|| {
+    ShaderTemplate::new(rendering_template_source!(
+        "screen_space_reflection_temporal_resolve"
+    ))
+    .unwrap()
+});
+
+impl ScreenSpaceReflectionTemporalResolveShaderTemplate {
+    /// Creates a new screen-space reflection temporal resolve shader template
+    /// using the given resource group ID for the screen-space reflection
+    /// parameters.
+    pub fn new(params_resource_group_id: GPUResourceGroupID) -> Self {
+        let push_constants = BasicPushConstantGroup::for_fragment([
+            BasicPushConstantVariant::InverseWindowDimensions,
+        ]);
+
+        let input_render_attachments = RenderAttachmentInputDescriptionSet::new(vec![
+            RenderAttachmentInputDescription::default_for(MotionVector),
+            // The tracing pass writes the reflected luminance to this
+            // attachment
+            RenderAttachmentInputDescription::default_for(LuminanceAux),
+            RenderAttachmentInputDescription::default_for(PreviousReflectedLuminanceHistory)
+                .with_sampler(RenderAttachmentSampler::Filtering),
+        ]);
+
+        let output_render_attachments = RenderAttachmentOutputDescriptionSet::with_defaults(
+            RenderAttachmentQuantitySet::REFLECTED_LUMINANCE_HISTORY,
+        );
+
+        Self {
+            params_resource_group_id,
+            push_constants,
+            input_render_attachments,
+            output_render_attachments,
+        }
+    }
+}
+
+impl SpecificShaderTemplate for ScreenSpaceReflectionTemporalResolveShaderTemplate {
+    fn resolve(&self) -> String {
+        TEMPLATE
+            .resolve(
+                &[],
+                shader_template_replacements!(
+                    "motion_vector_texture_group" => 0,
+                    "motion_vector_texture_binding" => MotionVector.texture_binding(),
+                    "motion_vector_sampler_binding" => MotionVector.sampler_binding(),
+                    "reflected_luminance_texture_group" => 1,
+                    "reflected_luminance_texture_binding" => LuminanceAux.texture_binding(),
+                    "reflected_luminance_sampler_binding" => LuminanceAux.sampler_binding(),
+                    "previous_reflected_luminance_texture_group" => 2,
+                    "previous_reflected_luminance_texture_binding" => PreviousReflectedLuminanceHistory.texture_binding(),
+                    "previous_reflected_luminance_sampler_binding" => PreviousReflectedLuminanceHistory.sampler_binding(),
+                    "params_group" => 3,
+                    "params_binding" => 0,
+                    "position_location" => MeshVertexAttributeLocation::Position as u32,
+                ),
+            )
+            .expect("Shader template resolution failed")
+    }
+
+    fn shader_id(&self) -> ShaderID {
+        ShaderID::from_identifier("ScreenSpaceReflectionTemporalResolveShaderTemplate")
+    }
+}
+
+impl PostprocessingShaderTemplate for ScreenSpaceReflectionTemporalResolveShaderTemplate {
+    fn push_constants(&self) -> BasicPushConstantGroup {
+        self.push_constants.clone()
+    }
+
+    fn input_render_attachments(&self) -> RenderAttachmentInputDescriptionSet {
+        self.input_render_attachments.clone()
+    }
+
+    fn output_render_attachments(&self) -> RenderAttachmentOutputDescriptionSet {
+        self.output_render_attachments.clone()
+    }
+
+    fn gpu_resource_group_id(&self) -> Option<GPUResourceGroupID> {
+        Some(self.params_resource_group_id)
+    }
+
+    fn stencil_test(&self) -> Option<(wgpu::CompareFunction, StencilValue)> {
+        Some((wgpu::CompareFunction::Equal, StencilValue::PhysicalModel))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use impact_gpu::shader::template::validate_template;
+    use impact_math::hash64;
+
+    #[test]
+    fn should_resolve_to_valid_wgsl() {
+        validate_template(&ScreenSpaceReflectionTemporalResolveShaderTemplate::new(
+            GPUResourceGroupID(hash64!("test".to_string())),
+        ));
+    }
+}
@@ -0,0 +1,148 @@
+//! Shader template for the screen-space reflection tracing pass.
+
+use crate::{
+    attachment::{
+        RenderAttachmentDescription, RenderAttachmentInputDescription,
+        RenderAttachmentInputDescriptionSet, RenderAttachmentOutputDescription,
+        RenderAttachmentOutputDescriptionSet,
+        RenderAttachmentQuantity::{
+            HierarchicalDepth, LuminanceAux, MotionVector, NormalVector, PreviousLuminanceHistory,
+        },
+        RenderAttachmentSampler,
+    },
+    push_constant::{BasicPushConstantGroup, BasicPushConstantVariant},
+    rendering_template_source,
+};
+use impact_camera::gpu_resource::CameraProjectionUniform;
+use impact_gpu::{
+    shader::{
+        ShaderID,
+        template::{ShaderTemplate, SpecificShaderTemplate},
+    },
+    shader_template_replacements,
+};
+use impact_mesh::gpu_resource::MeshVertexAttributeLocation;
+use impact_scene::skybox::gpu_resource::SkyboxGPUResource;
+use std::sync::LazyLock;
+
+/// Shader template for the screen-space reflection tracing pass, which
+/// traverses the hierarchical depth pyramid along the reflected view ray of each
+/// physical model fragment and writes the luminance found at the intersection (taken
+/// from the previous frame's luminance history) to the auxiliary luminance
+/// attachment. Rays that miss fall back to the skybox when one is available.
+#[derive(Clone, Debug)]
+pub struct ScreenSpaceReflectionTracingShaderTemplate {
+    uses_skybox: bool,
+}
+
+static TEMPLATE: LazyLock<ShaderTemplate<'static>> = LazyLock::new(|| {
+    ShaderTemplate::new(rendering_template_source!(
+        "screen_space_reflection_tracing"
+    ))
+    .unwrap()
+});
+
+impl ScreenSpaceReflectionTracingShaderTemplate {
+    /// Creates a new screen-space reflection tracing shader template. If
+    /// `uses_skybox` is `true`, rays that do not hit anything on screen will
+    /// sample the skybox, whose resources must then be bound after the
+    /// parameters resource group.
+    pub fn new(uses_skybox: bool) -> Self {
+        Self { uses_skybox }
+    }
+
+    /// Returns the group of push constants used by the shader.
+    pub fn push_constants() -> BasicPushConstantGroup {
+        BasicPushConstantGroup::for_fragment([
+            BasicPushConstantVariant::CameraRotationQuaternion,
+            BasicPushConstantVariant::InverseWindowDimensions,
+            BasicPushConstantVariant::FrameCounter,
+            BasicPushConstantVariant::Exposure,
+        ])
+    }
+
+    /// Returns the set of render attachments used as input by the shader.
+    pub fn input_render_attachments() -> RenderAttachmentInputDescriptionSet {
+        RenderAttachmentInputDescriptionSet::new(vec![
+            RenderAttachmentInputDescription::default_for(NormalVector),
+            RenderAttachmentInputDescription::default_for(MotionVector),
+            RenderAttachmentInputDescription::default_for(PreviousLuminanceHistory)
+                .with_sampler(RenderAttachmentSampler::Filtering),
+            RenderAttachmentInputDescription::default_for(HierarchicalDepth).with_full_mip_chain(),
+        ])
+    }
+
+    /// Returns the descriptions of the render attachments that the shader will
+    /// write to.
+    pub fn output_render_attachments() -> RenderAttachmentOutputDescriptionSet {
+        RenderAttachmentOutputDescriptionSet::single(
+            RenderAttachmentOutputDescription::default_for(LuminanceAux),
+        )
+    }
+}
+
+impl SpecificShaderTemplate for ScreenSpaceReflectionTracingShaderTemplate {
+    fn resolve(&self) -> String {
+        let mut flags_to_set = Vec::with_capacity(1);
+
+        let mut replacements = shader_template_replacements!(
+            "projection_uniform_group" => 0,
+            "projection_uniform_binding" => CameraProjectionUniform::binding(),
+            "normal_vector_texture_group" => 1,
+            "normal_vector_texture_binding" => NormalVector.texture_binding(),
+            "normal_vector_sampler_binding" => NormalVector.sampler_binding(),
+            "motion_vector_texture_group" => 2,
+            "motion_vector_texture_binding" => MotionVector.texture_binding(),
+            "motion_vector_sampler_binding" => MotionVector.sampler_binding(),
+            "previous_luminance_texture_group" => 3,
+            "previous_luminance_texture_binding" => PreviousLuminanceHistory.texture_binding(),
+            "previous_luminance_sampler_binding" => PreviousLuminanceHistory.sampler_binding(),
+            "hierarchical_depth_texture_group" => 4,
+            "hierarchical_depth_texture_binding" => HierarchicalDepth.texture_binding(),
+            "hierarchical_depth_sampler_binding" => HierarchicalDepth.sampler_binding(),
+            "params_group" => 5,
+            "params_binding" => 0,
+            "position_location" => MeshVertexAttributeLocation::Position as u32,
+        )
+        .to_vec();
+
+        if self.uses_skybox {
+            flags_to_set.push("uses_skybox");
+            replacements.extend_from_slice(shader_template_replacements!(
+                "skybox_properties_group" => 6,
+                "skybox_properties_binding" => SkyboxGPUResource::properties_uniform_binding(),
+                "skybox_texture_group" => 6,
+                "skybox_texture_binding" => SkyboxGPUResource::texture_binding(),
+                "skybox_sampler_binding" => SkyboxGPUResource::sampler_binding(),
+            ));
+        }
+
+        TEMPLATE
+            .resolve(&flags_to_set, &replacements)
+            .expect("Shader template resolution failed")
+    }
+
+    fn shader_id(&self) -> ShaderID {
+        ShaderID::from_identifier(&format!(
+            "ScreenSpaceReflectionTracingShaderTemplate{{ uses_skybox = {} }}",
+            self.uses_skybox
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use impact_gpu::shader::template::validate_template;
+
+    #[test]
+    fn should_resolve_to_valid_wgsl_without_skybox() {
+        validate_template(&ScreenSpaceReflectionTracingShaderTemplate::new(false));
+    }
+
+    #[test]
+    fn should_resolve_to_valid_wgsl_with_skybox() {
+        validate_template(&ScreenSpaceReflectionTracingShaderTemplate::new(true));
+    }
+}
@@ -0,0 +1,138 @@
+//! Shader template for the volumetric fog application pass.
+
+use crate::{
+    attachment::{
+        Blending, RenderAttachmentDescription, RenderAttachmentInputDescription,
+        RenderAttachmentInputDescriptionSet, RenderAttachmentOutputDescription,
+        RenderAttachmentOutputDescriptionSet,
+        RenderAttachmentQuantity::{LinearDepth, Luminance},
+    },
+    postprocessing::PostprocessingShaderTemplate,
+    push_constant::{BasicPushConstantGroup, BasicPushConstantVariant},
+    rendering_template_source,
+};
+use impact_camera::gpu_resource::CameraProjectionUniform;
+use impact_gpu::{
+    resource_group::GPUResourceGroupID,
+    shader::{
+        ShaderID,
+        template::{ShaderTemplate, SpecificShaderTemplate},
+    },
+    shader_template_replacements,
+};
+use impact_mesh::gpu_resource::MeshVertexAttributeLocation;
+use std::sync::LazyLock;
+
+/// Shader template for the volumetric fog application pass, which looks up the
+/// integrated scattered luminance and transmittance between the camera and
+/// each surface in the froxel grid and blends it into the luminance
+/// attachment.
+#[derive(Clone, Debug)]
+pub struct VolumetricFogApplicationShaderTemplate {
+    resource_group_id: GPUResourceGroupID,
+    grid_depth: u32,
+    push_constants: BasicPushConstantGroup,
+    input_render_attachments: RenderAttachmentInputDescriptionSet,
+    output_render_attachments: RenderAttachmentOutputDescriptionSet,
+}
+
+static TEMPLATE: LazyLock<ShaderTemplate<'static>> = LazyLock::new(|| {
+    ShaderTemplate::new(rendering_template_source!("volumetric_fog_application")).unwrap()
+});
+
+impl VolumetricFogApplicationShaderTemplate {
+    /// Creates a new volumetric fog application shader template using the
+    /// given resource group ID for the fog parameters and integrated
+    /// scattering texture, which has the given number of depth slices.
+    pub fn new(resource_group_id: GPUResourceGroupID, grid_depth: u32) -> Self {
+        let push_constants = BasicPushConstantGroup::for_fragment([
+            BasicPushConstantVariant::InverseWindowDimensions,
+        ]);
+
+        let input_render_attachments = RenderAttachmentInputDescriptionSet::new(vec![
+            RenderAttachmentInputDescription::default_for(LinearDepth),
+        ]);
+
+        let output_render_attachments = RenderAttachmentOutputDescriptionSet::single(
+            RenderAttachmentOutputDescription::default_for(Luminance)
+                .with_blending(Blending::Transmissive),
+        );
+
+        Self {
+            resource_group_id,
+            grid_depth,
+            push_constants,
+            input_render_attachments,
+            output_render_attachments,
+        }
+    }
+}
+
+impl SpecificShaderTemplate for VolumetricFogApplicationShaderTemplate {
+    fn resolve(&self) -> String {
+        TEMPLATE
+            .resolve(
+                &[],
+                shader_template_replacements!(
+                    "grid_depth" => format!("{}.0", self.grid_depth),
+                    "projection_uniform_group" => 0,
+                    "projection_uniform_binding" => CameraProjectionUniform::binding(),
+                    "linear_depth_texture_group" => 1,
+                    "linear_depth_texture_binding" => LinearDepth.texture_binding(),
+                    "linear_depth_sampler_binding" => LinearDepth.sampler_binding(),
+                    "params_group" => 2,
+                    "params_binding" => 0,
+                    "integrated_scattering_group" => 2,
+                    "integrated_scattering_texture_binding" => 1,
+                    "integrated_scattering_sampler_binding" => 2,
+                    "position_location" => MeshVertexAttributeLocation::Position as u32,
+                ),
+            )
+            .expect("Shader template resolution failed")
+    }
+
+    fn shader_id(&self) -> ShaderID {
+        ShaderID::from_identifier(&format!(
+            "VolumetricFogApplicationShaderTemplate{{ grid_depth = {} }}",
+            self.grid_depth,
+        ))
+    }
+}
+
+impl PostprocessingShaderTemplate for VolumetricFogApplicationShaderTemplate {
+    fn push_constants(&self) -> BasicPushConstantGroup {
+        self.push_constants.clone()
+    }
+
+    fn input_render_attachments(&self) -> RenderAttachmentInputDescriptionSet {
+        self.input_render_attachments.clone()
+    }
+
+    fn output_render_attachments(&self) -> RenderAttachmentOutputDescriptionSet {
+        self.output_render_attachments.clone()
+    }
+
+    fn uses_camera(&self) -> bool {
+        true
+    }
+
+    fn gpu_resource_group_id(&self) -> Option<GPUResourceGroupID> {
+        Some(self.resource_group_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use impact_gpu::shader::template::validate_template;
+    use impact_math::hash64;
+
+    #[test]
+    fn should_resolve_to_valid_wgsl() {
+        validate_template(&VolumetricFogApplicationShaderTemplate::new(
+            GPUResourceGroupID(hash64!("test".to_string())),
+            8,
+        ));
+    }
+}
@@ -0,0 +1,149 @@
+//! Shader template for the volumetric fog light injection pass.
+
+use crate::{
+    compute_template_source,
+    push_constant::{BasicPushConstantGroup, BasicPushConstantVariant},
+};
+use impact_camera::gpu_resource::CameraProjectionUniform;
+use impact_gpu::{
+    shader::{
+        ShaderID,
+        template::{ShaderTemplate, SpecificShaderTemplate},
+    },
+    shader_template_replacements,
+};
+use impact_light::{
+    MAX_SHADOW_MAP_CASCADES,
+    gpu_resource::LightGPUResources,
+    shadow_map::{CascadedShadowMapTexture, ShadowCubemapTexture},
+};
+use std::sync::LazyLock;
+
+/// Shader template for the volumetric fog light injection pass, which computes
+/// the light from a single shadowable light that is scattered towards the
+/// camera in each froxel (frustum-aligned voxel) and adds it to the scattering
+/// buffer.
+#[derive(Clone, Debug)]
+pub struct VolumetricFogInjectionShaderTemplate {
+    grid_dimensions: [u32; 3],
+    is_unidirectional: bool,
+    max_light_count: usize,
+}
+
+static TEMPLATE: LazyLock<ShaderTemplate<'static>> = LazyLock::new(|| {
+    ShaderTemplate::new(compute_template_source!("volumetric_fog_injection")).unwrap()
+});
+
+impl VolumetricFogInjectionShaderTemplate {
+    /// The number of threads along each axis of a workgroup.
+    pub const WORKGROUP_SIZE: u32 = 4;
+
+    /// Creates a new shader template for injecting light from shadowable
+    /// unidirectional lights into a froxel grid with the given dimensions.
+    pub fn for_unidirectional_lights(grid_dimensions: [u32; 3], max_light_count: usize) -> Self {
+        Self {
+            grid_dimensions,
+            is_unidirectional: true,
+            max_light_count,
+        }
+    }
+
+    /// Creates a new shader template for injecting light from shadowable
+    /// omnidirectional lights into a froxel grid with the given dimensions.
+    pub fn for_omnidirectional_lights(grid_dimensions: [u32; 3], max_light_count: usize) -> Self {
+        Self {
+            grid_dimensions,
+            is_unidirectional: false,
+            max_light_count,
+        }
+    }
+
+    /// Returns the group of push constants used by the shader.
+    pub fn push_constants() -> BasicPushConstantGroup {
+        BasicPushConstantGroup::for_compute([
+            BasicPushConstantVariant::LightIdx,
+            BasicPushConstantVariant::Exposure,
+        ])
+    }
+
+    /// Returns the workgroup counts to use when invoking the shader for a
+    /// froxel grid with the given dimensions.
+    pub fn determine_workgroup_counts(grid_dimensions: [u32; 3]) -> [u32; 3] {
+        grid_dimensions.map(|extent| extent.div_ceil(Self::WORKGROUP_SIZE))
+    }
+}
+
+impl SpecificShaderTemplate for VolumetricFogInjectionShaderTemplate {
+    fn resolve(&self) -> String {
+        let [grid_width, grid_height, grid_depth] = self.grid_dimensions;
+
+        let mut replacements = shader_template_replacements!(
+            "grid_width" => grid_width,
+            "grid_height" => grid_height,
+            "grid_depth" => grid_depth,
+            "workgroup_size" => Self::WORKGROUP_SIZE,
+            "max_light_count" => self.max_light_count,
+            "projection_uniform_group" => 0,
+            "projection_uniform_binding" => CameraProjectionUniform::binding(),
+            "params_group" => 1,
+            "params_binding" => 0,
+            "scattering_group" => 1,
+            "scattering_binding" => 1,
+            "light_uniform_group" => 2,
+            "light_uniform_binding" => LightGPUResources::light_binding(),
+            "shadow_map_texture_group" => 3,
+        )
+        .to_vec();
+
+        if self.is_unidirectional {
+            replacements.extend_from_slice(shader_template_replacements!(
+                "cascade_count" => MAX_SHADOW_MAP_CASCADES,
+                "shadow_map_texture_binding" => CascadedShadowMapTexture::texture_binding(),
+                "shadow_map_sampler_binding" => CascadedShadowMapTexture::sampler_binding(),
+            ));
+        } else {
+            replacements.extend_from_slice(shader_template_replacements!(
+                "shadow_map_texture_binding" => ShadowCubemapTexture::texture_binding(),
+                "shadow_map_sampler_binding" => ShadowCubemapTexture::sampler_binding(),
+            ));
+        }
+
+        let flags_to_set: &[_] = if self.is_unidirectional {
+            &["unidirectional"]
+        } else {
+            &[]
+        };
+
+        TEMPLATE
+            .resolve(flags_to_set, &replacements)
+            .expect("Shader template resolution failed")
+    }
+
+    fn shader_id(&self) -> ShaderID {
+        ShaderID::from_identifier(&format!(
+            "VolumetricFogInjectionShaderTemplate{{ grid_dimensions = {:?}, is_unidirectional = {}, max_light_count = {} }}",
+            self.grid_dimensions, self.is_unidirectional, self.max_light_count,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use impact_gpu::shader::template::validate_template;
+
+    #[test]
+    fn should_resolve_to_valid_wgsl_for_unidirectional_lights() {
+        validate_template(
+            &VolumetricFogInjectionShaderTemplate::for_unidirectional_lights([16, 9, 8], 5),
+        );
+    }
+
+    #[test]
+    fn should_resolve_to_valid_wgsl_for_omnidirectional_lights() {
+        validate_template(
+            &VolumetricFogInjectionShaderTemplate::for_omnidirectional_lights([16, 9, 8], 5),
+        );
+    }
+}
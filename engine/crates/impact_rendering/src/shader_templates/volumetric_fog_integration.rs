@@ -0,0 +1,90 @@
+//! Shader template for the volumetric fog integration pass.
+
+use crate::compute_template_source;
+use impact_camera::gpu_resource::CameraProjectionUniform;
+use impact_gpu::{
+    shader::{
+        ShaderID,
+        template::{ShaderTemplate, SpecificShaderTemplate},
+    },
+    shader_template_replacements,
+};
+use std::sync::LazyLock;
+
+/// Shader template for the volumetric fog integration pass, which accumulates
+/// the scattered light and transmittance along each column of the froxel grid
+/// and writes the result to the integrated scattering texture.
+#[derive(Clone, Debug)]
+pub struct VolumetricFogIntegrationShaderTemplate {
+    grid_dimensions: [u32; 3],
+}
+
+static TEMPLATE: LazyLock<ShaderTemplate<'static>> = LazyLock::new(|| {
+    ShaderTemplate::new(compute_template_source!("volumetric_fog_integration")).unwrap()
+});
+
+impl VolumetricFogIntegrationShaderTemplate {
+    /// The number of threads along the x- and y-axis of a workgroup.
+    pub const WORKGROUP_SIZE: u32 = 4;
+
+    /// Creates a new shader template for integrating a froxel grid with the
+    /// given dimensions.
+    pub fn new(grid_dimensions: [u32; 3]) -> Self {
+        Self { grid_dimensions }
+    }
+
+    /// Returns the workgroup counts to use when invoking the shader for a
+    /// froxel grid with the given dimensions.
+    pub fn determine_workgroup_counts(grid_dimensions: [u32; 3]) -> [u32; 3] {
+        [
+            grid_dimensions[0].div_ceil(Self::WORKGROUP_SIZE),
+            grid_dimensions[1].div_ceil(Self::WORKGROUP_SIZE),
+            1,
+        ]
+    }
+}
+
+impl SpecificShaderTemplate for VolumetricFogIntegrationShaderTemplate {
+    fn resolve(&self) -> String {
+        let [grid_width, grid_height, grid_depth] = self.grid_dimensions;
+
+        TEMPLATE
+            .resolve(
+                &[],
+                shader_template_replacements!(
+                    "grid_width" => grid_width,
+                    "grid_height" => grid_height,
+                    "grid_depth" => grid_depth,
+                    "workgroup_size" => Self::WORKGROUP_SIZE,
+                    "projection_uniform_group" => 0,
+                    "projection_uniform_binding" => CameraProjectionUniform::binding(),
+                    "params_group" => 1,
+                    "params_binding" => 0,
+                    "scattering_group" => 1,
+                    "scattering_binding" => 1,
+                    "integrated_scattering_group" => 1,
+                    "integrated_scattering_binding" => 2,
+                ),
+            )
+            .expect("Shader template resolution failed")
+    }
+
+    fn shader_id(&self) -> ShaderID {
+        ShaderID::from_identifier(&format!(
+            "VolumetricFogIntegrationShaderTemplate{{ grid_dimensions = {:?} }}",
+            self.grid_dimensions,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use impact_gpu::shader::template::validate_template;
+
+    #[test]
+    fn should_resolve_to_valid_wgsl() {
+        validate_template(&VolumetricFogIntegrationShaderTemplate::new([16, 9, 8]));
+    }
+}
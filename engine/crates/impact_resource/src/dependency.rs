@@ -0,0 +1,281 @@
+//! Tracking of references held by resources to other resources.
+
+use crate::{
+    Resource,
+    registry::{ResourceChangeKind, ResourceRegistry},
+};
+use impact_containers::RandomState;
+use std::{collections::HashMap, fmt, hash::BuildHasher};
+
+/// Manages the references that the resources in one registry hold to the
+/// resources they depend on in another registry.
+///
+/// The references are kept in sync with the dependent registry by applying the
+/// changes recorded in its changelog, so a resource holds references to its
+/// dependencies for as long as it is present in the registry, regardless of
+/// how it was inserted.
+pub struct ResourceDependencyReferences<R: Resource, DR: Resource, S = RandomState> {
+    held_references: HashMap<R::ID, Vec<DR::ID>, S>,
+    last_synced_revision: u64,
+}
+
+impl<R, DR, S> ResourceDependencyReferences<R, DR, S>
+where
+    R: Resource,
+    DR: Resource,
+    S: Default,
+{
+    /// Creates a new tracker holding no references.
+    pub fn new() -> Self {
+        Self {
+            held_references: HashMap::default(),
+            last_synced_revision: 0,
+        }
+    }
+}
+
+impl<R, DR, S> ResourceDependencyReferences<R, DR, S>
+where
+    R: Resource,
+    DR: Resource,
+    S: BuildHasher,
+{
+    /// Returns the IDs of the dependencies that the resource with the given ID
+    /// currently holds references to.
+    pub fn held_references(&self, id: R::ID) -> &[DR::ID] {
+        self.held_references.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Updates the held references to account for all changes to the given
+    /// registry since the last synchronization. Resources that were inserted
+    /// or changed acquire references to the dependencies returned by
+    /// `get_dependencies`, and resources that were removed or changed release
+    /// their previously held references.
+    ///
+    /// Dependencies that are missing from the dependency registry are not
+    /// referenced.
+    pub fn sync<D, DD, DS, I>(
+        &mut self,
+        registry: &ResourceRegistry<R, D, S>,
+        dependency_registry: &mut ResourceRegistry<DR, DD, DS>,
+        get_dependencies: impl Fn(&R) -> I,
+    ) where
+        DS: BuildHasher,
+        I: IntoIterator<Item = DR::ID>,
+    {
+        for change in registry.changes_since(self.last_synced_revision) {
+            let id = change.id();
+
+            if let Some(dependency_ids) = self.held_references.remove(&id) {
+                for dependency_id in dependency_ids {
+                    dependency_registry.remove_reference(dependency_id);
+                }
+            }
+
+            if matches!(change.kind(), ResourceChangeKind::Removed) {
+                continue;
+            }
+
+            // The resource may have been removed after this change, in which
+            // case a later change will account for the removal
+            if let Some(resource) = registry.get(id) {
+                let dependency_ids: Vec<_> = get_dependencies(resource)
+                    .into_iter()
+                    .filter(|&dependency_id| dependency_registry.add_reference(dependency_id))
+                    .collect();
+
+                if !dependency_ids.is_empty() {
+                    self.held_references.insert(id, dependency_ids);
+                }
+            }
+        }
+        self.last_synced_revision = registry.revision();
+    }
+}
+
+impl<R: Resource, DR: Resource, S> fmt::Debug for ResourceDependencyReferences<R, DR, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResourceDependencyReferences")
+            .field("held_references", &self.held_references)
+            .field("last_synced_revision", &self.last_synced_revision)
+            .finish()
+    }
+}
+
+impl<R, DR, S> Default for ResourceDependencyReferences<R, DR, S>
+where
+    R: Resource,
+    DR: Resource,
+    S: Default,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ResourceID, registry::ImmutableResourceRegistry};
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    struct TestResourceID(u32);
+
+    impl ResourceID for TestResourceID {}
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct Dependent {
+        dependencies: Vec<TestResourceID>,
+    }
+
+    impl Resource for Dependent {
+        type ID = TestResourceID;
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct Dependency;
+
+    impl Resource for Dependency {
+        type ID = TestResourceID;
+    }
+
+    const ID_1: TestResourceID = TestResourceID(1);
+    const ID_2: TestResourceID = TestResourceID(2);
+
+    type References = ResourceDependencyReferences<Dependent, Dependency>;
+
+    fn registries() -> (
+        ImmutableResourceRegistry<Dependent>,
+        ImmutableResourceRegistry<Dependency>,
+    ) {
+        let mut dependencies = ImmutableResourceRegistry::new();
+        dependencies.insert(ID_1, Dependency);
+        dependencies.insert(ID_2, Dependency);
+        (ImmutableResourceRegistry::new(), dependencies)
+    }
+
+    fn sync(
+        references: &mut References,
+        dependents: &ImmutableResourceRegistry<Dependent>,
+        dependencies: &mut ImmutableResourceRegistry<Dependency>,
+    ) {
+        references.sync(dependents, dependencies, |dependent| {
+            dependent.dependencies.clone()
+        });
+    }
+
+    #[test]
+    fn inserted_resource_references_its_dependencies() {
+        let (mut dependents, mut dependencies) = registries();
+        let mut references = References::new();
+
+        dependents.insert(
+            ID_1,
+            Dependent {
+                dependencies: vec![ID_1, ID_2],
+            },
+        );
+        sync(&mut references, &dependents, &mut dependencies);
+
+        assert_eq!(references.held_references(ID_1), &[ID_1, ID_2]);
+        assert_eq!(dependencies.reference_count(ID_1), 1);
+        assert_eq!(dependencies.reference_count(ID_2), 1);
+    }
+
+    #[test]
+    fn syncing_twice_does_not_duplicate_references() {
+        let (mut dependents, mut dependencies) = registries();
+        let mut references = References::new();
+
+        dependents.insert(
+            ID_1,
+            Dependent {
+                dependencies: vec![ID_1],
+            },
+        );
+        sync(&mut references, &dependents, &mut dependencies);
+        sync(&mut references, &dependents, &mut dependencies);
+
+        assert_eq!(dependencies.reference_count(ID_1), 1);
+    }
+
+    #[test]
+    fn removed_resource_releases_its_dependencies() {
+        let (mut dependents, mut dependencies) = registries();
+        let mut references = References::new();
+
+        dependents.insert(
+            ID_1,
+            Dependent {
+                dependencies: vec![ID_1],
+            },
+        );
+        sync(&mut references, &dependents, &mut dependencies);
+
+        dependents.remove(ID_1);
+        sync(&mut references, &dependents, &mut dependencies);
+
+        assert!(references.held_references(ID_1).is_empty());
+        assert_eq!(dependencies.reference_count(ID_1), 0);
+        assert_eq!(dependencies.evict_unreferenced(0).len(), 1);
+    }
+
+    #[test]
+    fn replaced_resource_references_new_dependencies() {
+        let (mut dependents, mut dependencies) = registries();
+        let mut references = References::new();
+
+        dependents.insert(
+            ID_1,
+            Dependent {
+                dependencies: vec![ID_1],
+            },
+        );
+        sync(&mut references, &dependents, &mut dependencies);
+
+        dependents.insert(
+            ID_1,
+            Dependent {
+                dependencies: vec![ID_2],
+            },
+        );
+        sync(&mut references, &dependents, &mut dependencies);
+
+        assert_eq!(references.held_references(ID_1), &[ID_2]);
+        assert_eq!(dependencies.reference_count(ID_1), 0);
+        assert_eq!(dependencies.reference_count(ID_2), 1);
+    }
+
+    #[test]
+    fn missing_dependencies_are_not_referenced() {
+        let (mut dependents, mut dependencies) = registries();
+        let mut references = References::new();
+
+        dependents.insert(
+            ID_1,
+            Dependent {
+                dependencies: vec![ID_1, TestResourceID(3)],
+            },
+        );
+        sync(&mut references, &dependents, &mut dependencies);
+
+        assert_eq!(references.held_references(ID_1), &[ID_1]);
+    }
+
+    #[test]
+    fn resource_inserted_and_removed_between_syncs_holds_no_references() {
+        let (mut dependents, mut dependencies) = registries();
+        let mut references = References::new();
+
+        dependents.insert(
+            ID_1,
+            Dependent {
+                dependencies: vec![ID_1],
+            },
+        );
+        dependents.remove(ID_1);
+        sync(&mut references, &dependents, &mut dependencies);
+
+        assert_eq!(dependencies.reference_count(ID_1), 0);
+    }
+}
@@ -1,5 +1,6 @@
 //! Resource management.
 
+pub mod dependency;
 pub mod gpu;
 pub mod registry;
 
@@ -18,11 +18,19 @@ pub type MutableResourceRegistry<R, S = RandomState> =
     ResourceRegistry<R, <R as MutableResource>::DirtyMask, S>;
 
 /// A change tracking registry for storing resources.
+///
+/// The registry also keeps count of the references held to each resource (by
+/// model instances or other resources). Resources whose reference count has
+/// dropped to zero can be evicted with [`Self::evict_unreferenced`]. Resources
+/// that have never been referenced are never evicted.
 pub struct ResourceRegistry<R: Resource, D, S = RandomState> {
     resources: HashMap<R::ID, R, S>,
+    reference_counts: HashMap<R::ID, ReferenceCount, S>,
     changelog: Vec<ResourceChange<R, D>>,
     /// How many times the registry has changed since it was created.
     revision: u64,
+    /// How many times [`Self::evict_unreferenced`] has been called.
+    eviction_pass: u64,
 }
 
 /// A change that occurred to a resource in a [`ResourceRegistry`].
@@ -47,6 +55,14 @@ pub enum ResourceChangeKind<D> {
     Modified(D),
 }
 
+/// The number of references held to a resource, along with the eviction pass
+/// at which the count last dropped to zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ReferenceCount {
+    count: u32,
+    released_at_pass: u64,
+}
+
 /// A mutable reference to a resource that tracks modifications.
 ///
 /// When this reference is dropped, it will automatically record a change in the
@@ -70,8 +86,10 @@ where
     pub fn new() -> Self {
         Self {
             resources: HashMap::default(),
+            reference_counts: HashMap::default(),
             changelog: Vec::new(),
             revision: 0,
+            eviction_pass: 0,
         }
     }
 }
@@ -143,9 +161,11 @@ where
 
     /// Removes the resource with the given ID from the registry, returning the
     /// removed resource if it existed. If the resource was not present,
-    /// [`None`] is returned.
+    /// [`None`] is returned. Any references held to the resource are
+    /// forgotten.
     pub fn remove(&mut self, id: R::ID) -> Option<R> {
         let removed_resource = self.resources.remove(&id);
+        self.reference_counts.remove(&id);
 
         if removed_resource.is_some() {
             self.changelog.push(ResourceChange {
@@ -159,6 +179,72 @@ where
         removed_resource
     }
 
+    /// Returns the number of references currently held to the resource with
+    /// the given ID.
+    pub fn reference_count(&self, id: R::ID) -> u32 {
+        self.reference_counts
+            .get(&id)
+            .map_or(0, |reference_count| reference_count.count)
+    }
+
+    /// Registers a new reference to the resource with the given ID. Returns
+    /// `false` if the resource is not present, in which case no reference is
+    /// registered.
+    pub fn add_reference(&mut self, id: R::ID) -> bool {
+        if !self.resources.contains_key(&id) {
+            return false;
+        }
+        self.reference_counts
+            .entry(id)
+            .or_insert(ReferenceCount {
+                count: 0,
+                released_at_pass: self.eviction_pass,
+            })
+            .count += 1;
+        true
+    }
+
+    /// Unregisters a reference to the resource with the given ID. When the
+    /// last reference is removed, the resource becomes eligible for eviction.
+    /// Does nothing if no references are registered for the resource.
+    pub fn remove_reference(&mut self, id: R::ID) {
+        if let Some(reference_count) = self.reference_counts.get_mut(&id)
+            && reference_count.count > 0
+        {
+            reference_count.count -= 1;
+            if reference_count.count == 0 {
+                reference_count.released_at_pass = self.eviction_pass;
+            }
+        }
+    }
+
+    /// Removes all resources whose last reference was removed more than
+    /// `grace_period` calls to this method ago, and returns the IDs and
+    /// contents of the removed resources. A removal change is recorded for
+    /// each evicted resource. Resources that have never been referenced are
+    /// not evicted.
+    ///
+    /// The returned resources can be used to release any references the
+    /// evicted resources held to other resources.
+    pub fn evict_unreferenced(&mut self, grace_period: u64) -> Vec<(R::ID, R)> {
+        self.eviction_pass += 1;
+
+        let ids_to_evict: Vec<_> = self
+            .reference_counts
+            .iter()
+            .filter(|(_, reference_count)| {
+                reference_count.count == 0
+                    && self.eviction_pass - reference_count.released_at_pass > grace_period
+            })
+            .map(|(id, _)| *id)
+            .collect();
+
+        ids_to_evict
+            .into_iter()
+            .filter_map(|id| self.remove(id).map(|resource| (id, resource)))
+            .collect()
+    }
+
     /// Returns the current revision number.
     ///
     /// The revision increments each time the registry changes.
@@ -207,8 +293,10 @@ where
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("ResourceRegistry")
             .field("resources", &self.resources)
+            .field("reference_counts", &self.reference_counts)
             .field("changelog", &self.changelog)
             .field("revision", &self.revision)
+            .field("eviction_pass", &self.eviction_pass)
             .finish()
     }
 }
@@ -722,4 +810,148 @@ mod tests {
         assert!(closure_called_for_new);
         assert!(registry.contains(ID_2));
     }
+
+    #[test]
+    fn adding_reference_to_missing_resource_fails() {
+        let mut registry: ImmutableResourceRegistry<TestResource> = ResourceRegistry::new();
+
+        assert!(!registry.add_reference(ID_1));
+        assert_eq!(registry.reference_count(ID_1), 0);
+    }
+
+    #[test]
+    fn adding_and_removing_references_updates_count() {
+        let mut registry: ImmutableResourceRegistry<TestResource> = ResourceRegistry::new();
+        registry.insert(
+            ID_1,
+            TestResource {
+                data: "test".to_string(),
+            },
+        );
+
+        assert!(registry.add_reference(ID_1));
+        assert!(registry.add_reference(ID_1));
+        assert_eq!(registry.reference_count(ID_1), 2);
+
+        registry.remove_reference(ID_1);
+        assert_eq!(registry.reference_count(ID_1), 1);
+
+        registry.remove_reference(ID_1);
+        registry.remove_reference(ID_1);
+        assert_eq!(registry.reference_count(ID_1), 0);
+    }
+
+    #[test]
+    fn never_referenced_resources_are_not_evicted() {
+        let mut registry: ImmutableResourceRegistry<TestResource> = ResourceRegistry::new();
+        registry.insert(
+            ID_1,
+            TestResource {
+                data: "test".to_string(),
+            },
+        );
+
+        assert!(registry.evict_unreferenced(0).is_empty());
+        assert!(registry.contains(ID_1));
+    }
+
+    #[test]
+    fn referenced_resources_are_not_evicted() {
+        let mut registry: ImmutableResourceRegistry<TestResource> = ResourceRegistry::new();
+        registry.insert(
+            ID_1,
+            TestResource {
+                data: "test".to_string(),
+            },
+        );
+        registry.add_reference(ID_1);
+
+        assert!(registry.evict_unreferenced(0).is_empty());
+        assert!(registry.contains(ID_1));
+    }
+
+    #[test]
+    fn released_resources_are_evicted_after_grace_period() {
+        let mut registry: ImmutableResourceRegistry<TestResource> = ResourceRegistry::new();
+        let resource = TestResource {
+            data: "test".to_string(),
+        };
+        registry.insert(ID_1, resource.clone());
+        registry.add_reference(ID_1);
+        registry.remove_reference(ID_1);
+
+        let revision_before_eviction = registry.revision();
+
+        assert!(registry.evict_unreferenced(2).is_empty());
+        assert!(registry.evict_unreferenced(2).is_empty());
+        assert_eq!(registry.evict_unreferenced(2), vec![(ID_1, resource)]);
+
+        assert!(!registry.contains(ID_1));
+
+        let changes = registry.changes_since(revision_before_eviction);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].id(), ID_1);
+        assert_eq!(changes[0].kind(), &ResourceChangeKind::Removed);
+    }
+
+    #[test]
+    fn referencing_released_resource_during_grace_period_prevents_eviction() {
+        let mut registry: ImmutableResourceRegistry<TestResource> = ResourceRegistry::new();
+        registry.insert(
+            ID_1,
+            TestResource {
+                data: "test".to_string(),
+            },
+        );
+        registry.add_reference(ID_1);
+        registry.remove_reference(ID_1);
+
+        assert!(registry.evict_unreferenced(1).is_empty());
+        registry.add_reference(ID_1);
+
+        for _ in 0..3 {
+            assert!(registry.evict_unreferenced(1).is_empty());
+        }
+        assert!(registry.contains(ID_1));
+    }
+
+    #[test]
+    fn only_released_resources_are_evicted() {
+        let mut registry: ImmutableResourceRegistry<TestResource> = ResourceRegistry::new();
+        for id in [ID_1, ID_2, ID_3] {
+            registry.insert(
+                id,
+                TestResource {
+                    data: "test".to_string(),
+                },
+            );
+        }
+        registry.add_reference(ID_1);
+        registry.add_reference(ID_2);
+        registry.remove_reference(ID_2);
+
+        let evicted = registry.evict_unreferenced(0);
+
+        assert_eq!(evicted.len(), 1);
+        assert_eq!(evicted[0].0, ID_2);
+        assert!(registry.contains(ID_1));
+        assert!(registry.contains(ID_3));
+    }
+
+    #[test]
+    fn removing_resource_forgets_references() {
+        let mut registry: ImmutableResourceRegistry<TestResource> = ResourceRegistry::new();
+        let resource = TestResource {
+            data: "test".to_string(),
+        };
+        registry.insert(ID_1, resource.clone());
+        registry.add_reference(ID_1);
+
+        registry.remove(ID_1);
+        assert_eq!(registry.reference_count(ID_1), 0);
+
+        registry.insert(ID_1, resource);
+        assert_eq!(registry.reference_count(ID_1), 0);
+        assert!(registry.evict_unreferenced(0).is_empty());
+    }
 }
@@ -1,6 +1,6 @@
 //! Scene graph implementation.
 
-use crate::{SceneEntityFlags, model::ModelID};
+use crate::{SceneEntityFlags, lod::LODSelectionConfig, model::ModelID};
 use anyhow::{Result, anyhow, bail};
 use bitflags::bitflags;
 use bytemuck::{Pod, Zeroable};
@@ -17,7 +17,7 @@ use std::{
     fmt,
     hash::Hash,
     mem,
-    sync::atomic::{AtomicU32, Ordering},
+    sync::atomic::{AtomicU8, AtomicU32, Ordering},
 };
 use tinyvec::TinyVec;
 
@@ -80,12 +80,16 @@ pub struct GroupNode {
 
 /// A [`SceneGraph`] leaf node representing a model instance. It holds a
 /// transform representing the instance's spatial relationship with its parent
-/// group, as well as a list of instance feature IDs.
+/// group, as well as a list of instance feature IDs. If the instance's mesh has
+/// simplified levels of detail, the node also holds the models for these and
+/// the levels currently selected for rendering and shadow mapping.
 #[derive(Debug)]
 pub struct ModelInstanceNode {
     parent_group_id: SceneGroupID,
     model_to_parent_transform: Similarity3C,
     model_id: ModelID,
+    lod_model_ids: Vec<ModelID>,
+    lod_for_rendering: AtomicU8,
     feature_ids_for_rendering: FeatureIDSet,
     feature_ids_for_shadow_mapping: FeatureIDSet,
     flags: ModelInstanceFlags,
@@ -195,7 +199,9 @@ impl SceneGraph {
     }
 
     /// Creates a new [`ModelInstanceNode`] under the given parent group, using
-    /// the given node ID and model instance information.
+    /// the given node ID and model instance information. The given LOD model
+    /// IDs are the models for the simplified levels of detail of the model,
+    /// ordered from the most to the least detailed.
     ///
     /// # Errors
     /// Returns an error if:
@@ -211,6 +217,7 @@ impl SceneGraph {
         model_instance_id: ModelInstanceID,
         model_to_parent_transform: Similarity3C,
         model_id: ModelID,
+        lod_model_ids: Vec<ModelID>,
         feature_ids_for_rendering: FeatureIDSet,
         feature_ids_for_shadow_mapping: FeatureIDSet,
         flags: ModelInstanceFlags,
@@ -249,6 +256,7 @@ impl SceneGraph {
             parent_group_id,
             model_to_parent_transform,
             model_id,
+            lod_model_ids,
             feature_ids_for_rendering,
             feature_ids_for_shadow_mapping,
             flags,
@@ -556,6 +564,11 @@ impl<N: SceneGraphNode> NodeStorage<N> {
         self.nodes.get(&node_id)
     }
 
+    /// Returns an iterator over all nodes in the storage.
+    pub fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.nodes.values()
+    }
+
     /// Returns a reference to the node with the given ID.
     pub fn node(&self, node_id: N::ID) -> &N {
         self.get_node(node_id).expect("Tried to get missing node")
@@ -720,6 +733,7 @@ impl ModelInstanceNode {
         parent_group_id: SceneGroupID,
         model_to_parent_transform: Similarity3C,
         model_id: ModelID,
+        lod_model_ids: Vec<ModelID>,
         feature_ids_for_rendering: FeatureIDSet,
         feature_ids_for_shadow_mapping: FeatureIDSet,
         flags: ModelInstanceFlags,
@@ -728,6 +742,8 @@ impl ModelInstanceNode {
             parent_group_id,
             model_to_parent_transform,
             model_id,
+            lod_model_ids,
+            lod_for_rendering: AtomicU8::new(0),
             feature_ids_for_rendering,
             feature_ids_for_shadow_mapping,
             flags,
@@ -756,6 +772,49 @@ impl ModelInstanceNode {
         &self.model_id
     }
 
+    /// Returns the IDs of the models for the simplified levels of detail of
+    /// the instance, ordered from the most to the least detailed.
+    pub fn lod_model_ids(&self) -> &[ModelID] {
+        &self.lod_model_ids
+    }
+
+    /// Returns an iterator over the IDs of the models for all levels of detail
+    /// of the instance, starting with the full-detail model.
+    pub fn model_ids_for_all_lods(&self) -> impl Iterator<Item = &ModelID> {
+        std::iter::once(&self.model_id).chain(&self.lod_model_ids)
+    }
+
+    /// Selects the level of detail to render the instance with based on its
+    /// projected screen size and the previously selected level, and returns
+    /// the ID of the model for the selected level.
+    pub fn select_model_id_for_rendering(
+        &self,
+        screen_size: f32,
+        config: &LODSelectionConfig,
+    ) -> &ModelID {
+        let lod = self.select_lod(&self.lod_for_rendering, screen_size, config);
+        self.model_id_for_lod(lod)
+    }
+
+    /// Selects the level of detail to render the instance into shadow maps
+    /// with based on its projected screen size, and returns the ID of the
+    /// model for the selected level. The shadow mapping LOD bias in the
+    /// configuration is applied to the selected level. No hysteresis is
+    /// applied, since the instance may be rendered into many shadow maps.
+    pub fn select_model_id_for_shadow_mapping(
+        &self,
+        screen_size: f32,
+        config: &LODSelectionConfig,
+    ) -> &ModelID {
+        let lod = config.select_lod_without_hysteresis(screen_size, self.lod_model_ids.len());
+        let biased_lod = if config.enabled {
+            lod + config.shadow_mapping_lod_bias as usize
+        } else {
+            lod
+        };
+        self.model_id_for_lod(biased_lod)
+    }
+
     /// Returns the ID of the instance's rendering feature of the specified
     /// type, or [`None`] if it does not exist.
     pub fn get_rendering_feature_id_of_type(
@@ -800,6 +859,27 @@ impl ModelInstanceNode {
             .store(current_frame_number, Ordering::Relaxed);
     }
 
+    fn select_lod(&self, state: &AtomicU8, screen_size: f32, config: &LODSelectionConfig) -> usize {
+        if self.lod_model_ids.is_empty() {
+            return 0;
+        }
+        let current_lod = usize::from(state.load(Ordering::Relaxed));
+        let lod = config.select_lod(screen_size, current_lod, self.lod_model_ids.len());
+        state.store(lod as u8, Ordering::Relaxed);
+        lod
+    }
+
+    fn model_id_for_lod(&self, lod: usize) -> &ModelID {
+        match lod {
+            0 => &self.model_id,
+            _ => self
+                .lod_model_ids
+                .get(lod - 1)
+                .or(self.lod_model_ids.last())
+                .unwrap_or(&self.model_id),
+        }
+    }
+
     fn set_model_to_parent_transform(&mut self, transform: Similarity3C) {
         self.model_to_parent_transform = transform;
     }
@@ -948,6 +1028,7 @@ mod tests {
             model_instance_id,
             model_to_parent_transform,
             create_dummy_model_id(""),
+            Vec::new(),
             create_dummy_model_instance_rendering_feature_ids(),
             FeatureIDSet::new(),
             ModelInstanceFlags::empty(),
@@ -1297,4 +1378,55 @@ mod tests {
             epsilon = 1e-7
         );
     }
+
+    #[test]
+    fn model_instance_node_selects_lod_model_ids() {
+        let root_id = SceneGroupID::from_u64(0);
+        let mut scene_graph = SceneGraph::new(root_id);
+        let id = ModelInstanceID::from_u64(1);
+
+        let lod_model_ids = vec![create_dummy_model_id("lod1"), create_dummy_model_id("lod2")];
+
+        scene_graph
+            .create_model_instance_node(
+                root_id,
+                id,
+                Similarity3C::identity(),
+                create_dummy_model_id(""),
+                lod_model_ids.clone(),
+                create_dummy_model_instance_rendering_feature_ids(),
+                FeatureIDSet::new(),
+                ModelInstanceFlags::empty(),
+            )
+            .unwrap();
+
+        let node = scene_graph.model_instance_nodes().node(id);
+
+        assert_eq!(node.model_ids_for_all_lods().count(), 3);
+
+        let config = LODSelectionConfig {
+            shadow_mapping_lod_bias: 1,
+            ..Default::default()
+        };
+        let large = 10.0;
+        let tiny = 1e-6;
+
+        assert_eq!(
+            node.select_model_id_for_rendering(large, &config),
+            node.model_id()
+        );
+        assert_eq!(
+            node.select_model_id_for_rendering(tiny, &config),
+            &lod_model_ids[1]
+        );
+        assert_eq!(
+            node.select_model_id_for_shadow_mapping(large, &config),
+            &lod_model_ids[0]
+        );
+        // The bias can not go beyond the coarsest level
+        assert_eq!(
+            node.select_model_id_for_shadow_mapping(tiny, &config),
+            &lod_model_ids[1]
+        );
+    }
 }
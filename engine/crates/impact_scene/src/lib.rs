@@ -5,6 +5,7 @@ mod macros;
 
 pub mod graph;
 pub mod light;
+pub mod lod;
 pub mod model;
 pub mod setup;
 pub mod skybox;
@@ -21,6 +22,7 @@ use impact_intersection::IntersectionManager;
 use impact_light::{LightManager, shadow_map::ShadowMappingConfig};
 use impact_material::MaterialRegistry;
 use impact_profiling::{TaskTimer, instrument_task};
+use lod::LODSelectionConfig;
 use model::ModelInstanceManager;
 use roc_integration::roc;
 
@@ -167,6 +169,8 @@ pub fn buffer_model_instances_and_bound_lights(
     camera: &Camera,
     current_frame_number: u32,
     shadow_mapping_config: &ShadowMappingConfig,
+    lod_selection_config: &LODSelectionConfig,
+    entity_picking_enabled: bool,
 ) {
     let Some(ModelInstanceBufferingResult {
         world_space_aabb_for_visible_models,
@@ -179,6 +183,8 @@ pub fn buffer_model_instances_and_bound_lights(
             scene_graph,
             camera,
             current_frame_number,
+            lod_selection_config,
+            entity_picking_enabled,
         )
     })
     else {
@@ -198,6 +204,7 @@ pub fn buffer_model_instances_and_bound_lights(
                 camera,
                 &world_space_aabb_for_visible_models,
                 shadow_mapping_config,
+                lod_selection_config,
             );
         }
     );
@@ -214,6 +221,7 @@ pub fn buffer_model_instances_and_bound_lights(
                 camera,
                 &camera_space_aabb_for_visible_models,
                 shadow_mapping_config,
+                lod_selection_config,
             );
         }
     );
@@ -3,7 +3,8 @@
 use crate::{
     SceneEntityFlags,
     graph::{ModelInstanceFlags, ModelInstanceNode, SceneGraph},
-    model::ModelInstanceManager,
+    lod::{self, LODSelectionConfig},
+    model::{ModelID, ModelInstanceManager},
 };
 use impact_alloc::{AVec, Allocator, arena::ArenaPool};
 use impact_camera::Camera;
@@ -39,6 +40,7 @@ use impact_model::{
 
 #[derive(Debug, Clone)]
 struct ShadowingModel {
+    model_id: ModelID,
     model_to_camera_transform: Similarity3C,
 }
 
@@ -90,6 +92,7 @@ pub fn bound_omnidirectional_lights_and_buffer_shadow_casting_model_instances(
     camera: &Camera,
     world_space_aabb_for_visible_models: &AxisAlignedBox,
     shadow_mapping_config: &ShadowMappingConfig,
+    lod_selection_config: &LODSelectionConfig,
 ) {
     let arena = ArenaPool::get_arena();
     let mut shadowing_models =
@@ -174,6 +177,8 @@ pub fn bound_omnidirectional_lights_and_buffer_shadow_casting_model_instances(
                 |id, aabb| {
                     register_primitive_in_omnidirectional_light_culling_frustum(
                         scene_graph,
+                        camera,
+                        lod_selection_config,
                         world_to_camera_transform,
                         light_id,
                         &world_space_light_position,
@@ -201,6 +206,8 @@ pub fn bound_omnidirectional_lights_and_buffer_shadow_casting_model_instances(
                 |id, aabb| {
                     register_primitive_in_omnidirectional_light_culling_frustum(
                         scene_graph,
+                        camera,
+                        lod_selection_config,
                         world_to_camera_transform,
                         light_id,
                         &world_space_light_position,
@@ -267,6 +274,7 @@ pub fn bound_omnidirectional_lights_and_buffer_shadow_casting_model_instances(
                         model_instance_manager,
                         range_id,
                         model_instance_node,
+                        &model.model_id,
                     );
 
                     let instance_model_light_transform = camera_to_cubemap_face_space_transform
@@ -275,6 +283,7 @@ pub fn bound_omnidirectional_lights_and_buffer_shadow_casting_model_instances(
                     buffer_features_for_model(
                         model_instance_manager,
                         model_instance_node,
+                        &model.model_id,
                         &instance_model_light_transform,
                     );
                 },
@@ -382,6 +391,8 @@ fn determine_perspective_transform_encompassing_box_in_negative_z_halfspace(
 
 fn register_primitive_in_omnidirectional_light_culling_frustum<A: Allocator>(
     scene_graph: &SceneGraph,
+    camera: &Camera,
+    lod_selection_config: &LODSelectionConfig,
     world_to_camera_transform: &Isometry3,
     light_id: ShadowableOmnidirectionalLightID,
     world_space_light_position: &Point3,
@@ -441,9 +452,17 @@ fn register_primitive_in_omnidirectional_light_culling_frustum<A: Allocator>(
         model_instance_node,
     );
 
+    let model_id = select_model_id_for_shadow_mapping(
+        camera,
+        lod_selection_config,
+        model_instance_node,
+        &aabb,
+    );
+
     shadowing_models.insert(
         model_instance_id,
         ShadowingModel {
+            model_id,
             model_to_camera_transform: model_to_camera_transform.compact(),
         },
     );
@@ -470,6 +489,7 @@ pub fn bound_unidirectional_lights_and_buffer_shadow_casting_model_instances(
     camera: &Camera,
     camera_space_aabb_for_visible_models: &AxisAlignedBox,
     shadow_mapping_config: &ShadowMappingConfig,
+    lod_selection_config: &LODSelectionConfig,
 ) {
     if light_manager
         .shadowable_unidirectional_light_buffer()
@@ -480,7 +500,7 @@ pub fn bound_unidirectional_lights_and_buffer_shadow_casting_model_instances(
     }
 
     let arena = ArenaPool::get_arena();
-    let mut shadowing_model_ids = AVec::new_in(&arena);
+    let mut shadowing_models = AVec::new_in(&arena);
 
     let world_to_camera_transform = camera.view_transform();
     let camera_to_world_transform = world_to_camera_transform.inverted();
@@ -535,7 +555,7 @@ pub fn bound_unidirectional_lights_and_buffer_shadow_casting_model_instances(
         {
             let cascade_idx = cascade_idx as CascadeIdx;
 
-            shadowing_model_ids.clear();
+            shadowing_models.clear();
 
             let mut light_space_aabb_for_shadowing_models =
                 AxisAlignedBox::new(Point3::same(f32::INFINITY), Point3::same(f32::NEG_INFINITY));
@@ -545,9 +565,11 @@ pub fn bound_unidirectional_lights_and_buffer_shadow_casting_model_instances(
                 |id, aabb| {
                     register_primitive_in_unidirectional_light_culling_box(
                         scene_graph,
+                        camera,
+                        lod_selection_config,
                         &world_to_light_transform_matrix,
                         light_id,
-                        &mut shadowing_model_ids,
+                        &mut shadowing_models,
                         &mut light_space_aabb_for_shadowing_models,
                         id,
                         aabb,
@@ -555,7 +577,7 @@ pub fn bound_unidirectional_lights_and_buffer_shadow_casting_model_instances(
                 },
             );
 
-            let tight_light_space_orthographic_aabb = if shadowing_model_ids.is_empty() {
+            let tight_light_space_orthographic_aabb = if shadowing_models.is_empty() {
                 // We have no models to bound the cascade, so we just use the
                 // original box
                 light_space_culling_aabb
@@ -598,7 +620,7 @@ pub fn bound_unidirectional_lights_and_buffer_shadow_casting_model_instances(
                 &light_space_orthographic_aabb,
             );
 
-            if !shadow_mapping_config.enabled || shadowing_model_ids.is_empty() {
+            if !shadow_mapping_config.enabled || shadowing_models.is_empty() {
                 continue;
             }
 
@@ -611,14 +633,15 @@ pub fn bound_unidirectional_lights_and_buffer_shadow_casting_model_instances(
                 u64::from(cascade_idx),
             );
 
-            for &model_instance_id in &shadowing_model_ids {
+            for (model_instance_id, model_id) in &shadowing_models {
                 let model_instance_node =
-                    scene_graph.model_instance_nodes().node(model_instance_id);
+                    scene_graph.model_instance_nodes().node(*model_instance_id);
 
                 ensure_ranges_in_feature_buffers_for_model(
                     model_instance_manager,
                     range_id,
                     model_instance_node,
+                    model_id,
                 );
 
                 let model_to_camera_transform = compute_model_to_camera_transform(
@@ -633,6 +656,7 @@ pub fn bound_unidirectional_lights_and_buffer_shadow_casting_model_instances(
                 buffer_features_for_model(
                     model_instance_manager,
                     model_instance_node,
+                    model_id,
                     &instance_model_light_transform,
                 );
             }
@@ -746,9 +770,11 @@ fn compute_light_space_cascade_aabbs_and_bounding_spheres(
 
 fn register_primitive_in_unidirectional_light_culling_box<A: Allocator>(
     scene_graph: &SceneGraph,
+    camera: &Camera,
+    lod_selection_config: &LODSelectionConfig,
     world_to_light_transform_matrix: &Matrix4,
     light_id: ShadowableUnidirectionalLightID,
-    shadowing_model_ids: &mut AVec<ModelInstanceID, A>,
+    shadowing_models: &mut AVec<(ModelInstanceID, ModelID), A>,
     light_space_aabb_for_shadowing_models: &mut AxisAlignedBox,
     id: BoundingVolumeID,
     aabb: &AxisAlignedBoxC,
@@ -777,13 +803,20 @@ fn register_primitive_in_unidirectional_light_culling_box<A: Allocator>(
         return;
     }
 
-    let light_space_aabb = aabb
-        .aligned()
-        .aabb_of_transformed(world_to_light_transform_matrix);
+    let aabb = aabb.aligned();
+
+    let light_space_aabb = aabb.aabb_of_transformed(world_to_light_transform_matrix);
 
     light_space_aabb_for_shadowing_models.merge_with(&light_space_aabb);
 
-    shadowing_model_ids.push(model_instance_id);
+    let model_id = select_model_id_for_shadow_mapping(
+        camera,
+        lod_selection_config,
+        model_instance_node,
+        &aabb,
+    );
+
+    shadowing_models.push((model_instance_id, model_id));
 }
 
 fn snap_light_space_orthographic_aabb_extent_to_texels(
@@ -847,10 +880,26 @@ fn compute_model_to_camera_transform(
     world_to_camera_transform * model_to_world_transform
 }
 
+fn select_model_id_for_shadow_mapping(
+    camera: &Camera,
+    lod_selection_config: &LODSelectionConfig,
+    model_instance_node: &ModelInstanceNode,
+    world_space_aabb: &AxisAlignedBox,
+) -> ModelID {
+    if model_instance_node.lod_model_ids().is_empty() {
+        return *model_instance_node.model_id();
+    }
+    // The level of detail is based on the size of the model as seen from the
+    // camera, since that determines the size of its shadow on screen
+    let screen_size = lod::compute_projected_screen_size(camera, world_space_aabb);
+    *model_instance_node.select_model_id_for_shadow_mapping(screen_size, lod_selection_config)
+}
+
 fn ensure_ranges_in_feature_buffers_for_model(
     model_instance_manager: &mut ModelInstanceManager,
     range_id: u64,
     model_instance_node: &ModelInstanceNode,
+    model_id: &ModelID,
 ) {
     let feature_type_ids_for_shadow_mapping = model_instance_node
         .feature_ids_for_shadow_mapping()
@@ -858,7 +907,7 @@ fn ensure_ranges_in_feature_buffers_for_model(
         .map(|feature_id| feature_id.feature_type_id());
 
     model_instance_manager.ensure_ranges_in_feature_buffers_for_model(
-        model_instance_node.model_id(),
+        model_id,
         feature_type_ids_for_shadow_mapping,
         range_id,
     );
@@ -867,22 +916,18 @@ fn ensure_ranges_in_feature_buffers_for_model(
 fn buffer_features_for_model(
     model_instance_manager: &mut ModelInstanceManager,
     model_instance_node: &ModelInstanceNode,
+    model_id: &ModelID,
     instance_model_light_transform: &Similarity3,
 ) {
     let instance_model_light_transform =
         InstanceModelLightTransform::from(instance_model_light_transform);
 
-    model_instance_manager.buffer_instance_feature(
-        model_instance_node.model_id(),
-        &instance_model_light_transform,
-    );
+    model_instance_manager.buffer_instance_feature(model_id, &instance_model_light_transform);
 
     let feature_ids_for_shadow_mapping = model_instance_node.feature_ids_for_shadow_mapping();
 
     if feature_ids_for_shadow_mapping.len() > 1 {
-        model_instance_manager.buffer_instance_features_from_storages(
-            model_instance_node.model_id(),
-            &feature_ids_for_shadow_mapping[1..],
-        );
+        model_instance_manager
+            .buffer_instance_features_from_storages(model_id, &feature_ids_for_shadow_mapping[1..]);
     }
 }
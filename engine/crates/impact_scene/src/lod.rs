@@ -0,0 +1,215 @@
+//! Selection of levels of detail for model instances.
+
+use impact_camera::Camera;
+use impact_geometry::AxisAlignedBox;
+use impact_math::point::Point3;
+
+/// Configuration options for selecting the level of detail model instances
+/// are rendered with.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LODSelectionConfig {
+    /// Whether model instances with simplified meshes should be rendered with
+    /// a level of detail based on their projected size. If disabled, the
+    /// full-detail mesh is always used.
+    pub enabled: bool,
+    /// The projected size of a model instance, as a fraction of the view
+    /// height, below which the first simplified level of detail is used.
+    pub first_lod_screen_size: f32,
+    /// The factor by which the projected size threshold is multiplied for each
+    /// subsequent level of detail.
+    pub screen_size_factor_per_level: f32,
+    /// The relative margin the projected size must cross a threshold by before
+    /// the level of detail changes, preventing flickering between levels for
+    /// instances close to a threshold. Hysteresis does not apply to the level
+    /// of detail used for shadow mapping, since an instance may be rendered
+    /// into many shadow maps each frame.
+    pub hysteresis: f32,
+    /// The number of levels coarser than the one selected from the projected
+    /// size to use when rendering model instances into shadow maps.
+    pub shadow_mapping_lod_bias: u32,
+    /// The projected size of a single voxel, as a fraction of the view height,
+    /// below which voxel chunks are rendered with the first downsampled level
+    /// of detail. The threshold for each subsequent level is obtained by
+    /// multiplying with [`Self::screen_size_factor_per_level`]. Hysteresis does
+    /// not apply to voxel chunks, since their level of detail is selected on
+    /// the GPU.
+    pub voxel_first_lod_screen_size: f32,
+}
+
+impl LODSelectionConfig {
+    /// Returns the projected size threshold below which the given level of
+    /// detail (starting from 1) is used.
+    fn screen_size_threshold(&self, level: usize) -> f32 {
+        self.first_lod_screen_size
+            * self
+                .screen_size_factor_per_level
+                .powi(level.saturating_sub(1) as i32)
+    }
+
+    /// Selects the level of detail (with 0 being the full-detail level) for a
+    /// model with the given number of simplified levels based only on its
+    /// projected screen size, without hysteresis.
+    pub fn select_lod_without_hysteresis(&self, screen_size: f32, n_lod_levels: usize) -> usize {
+        if !self.enabled {
+            return 0;
+        }
+        (1..=n_lod_levels)
+            .take_while(|&level| screen_size < self.screen_size_threshold(level))
+            .last()
+            .unwrap_or(0)
+    }
+
+    /// Selects the level of detail (with 0 being the full-detail level) for a
+    /// model with the given number of simplified levels, based on its
+    /// projected screen size and currently selected level.
+    ///
+    /// The level only changes if the projected size is beyond the threshold
+    /// between the levels by the configured hysteresis margin. Selecting again
+    /// with the returned level and the same size yields the same level.
+    pub fn select_lod(&self, screen_size: f32, current_lod: usize, n_lod_levels: usize) -> usize {
+        if !self.enabled || n_lod_levels == 0 {
+            return 0;
+        }
+
+        let current_lod = current_lod.min(n_lod_levels);
+
+        let target_lod = self.select_lod_without_hysteresis(screen_size, n_lod_levels);
+
+        if target_lod > current_lod {
+            // Move to the coarsest level whose threshold is crossed by the
+            // margin
+            (current_lod + 1..=target_lod)
+                .rev()
+                .find(|&level| {
+                    screen_size < self.screen_size_threshold(level) * (1.0 - self.hysteresis)
+                })
+                .unwrap_or(current_lod)
+        } else if target_lod < current_lod {
+            // Move to the finest level whose threshold to the next coarser
+            // level is crossed by the margin
+            (target_lod..current_lod)
+                .find(|&level| {
+                    screen_size > self.screen_size_threshold(level + 1) * (1.0 + self.hysteresis)
+                })
+                .unwrap_or(current_lod)
+        } else {
+            current_lod
+        }
+    }
+}
+
+impl Default for LODSelectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            first_lod_screen_size: 0.25,
+            screen_size_factor_per_level: 0.5,
+            hysteresis: 0.1,
+            shadow_mapping_lod_bias: 1,
+            voxel_first_lod_screen_size: 0.002,
+        }
+    }
+}
+
+/// Computes the size of the sphere enclosing the given world space AABB when
+/// projected onto the view plane of the given camera, as a fraction of the
+/// view height.
+pub fn compute_projected_screen_size(camera: &Camera, world_space_aabb: &AxisAlignedBox) -> f32 {
+    let camera_position = camera.compute_world_space_position();
+
+    let center = world_space_aabb.center();
+    let radius = 0.5 * (world_space_aabb.upper_corner() - world_space_aabb.lower_corner()).norm();
+
+    let distance = Point3::distance_between(&camera_position, &center);
+
+    if distance <= radius {
+        return f32::INFINITY;
+    }
+
+    let view_height = camera.projection().view_height_at_distance(distance);
+
+    2.0 * radius / view_height
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> LODSelectionConfig {
+        LODSelectionConfig {
+            enabled: true,
+            first_lod_screen_size: 0.4,
+            screen_size_factor_per_level: 0.5,
+            hysteresis: 0.1,
+            shadow_mapping_lod_bias: 1,
+            voxel_first_lod_screen_size: 0.002,
+        }
+    }
+
+    #[test]
+    fn large_instances_use_full_detail() {
+        assert_eq!(config().select_lod(1.0, 0, 3), 0);
+        assert_eq!(config().select_lod(1.0, 2, 3), 0);
+    }
+
+    #[test]
+    fn small_instances_use_coarsest_level() {
+        assert_eq!(config().select_lod(0.001, 0, 3), 3);
+    }
+
+    #[test]
+    fn selection_without_levels_or_when_disabled_gives_full_detail() {
+        assert_eq!(config().select_lod(0.001, 0, 0), 0);
+        let config = LODSelectionConfig {
+            enabled: false,
+            ..config()
+        };
+        assert_eq!(config.select_lod(0.001, 0, 3), 0);
+    }
+
+    #[test]
+    fn selection_without_hysteresis_ignores_margin() {
+        assert_eq!(config().select_lod_without_hysteresis(0.41, 3), 0);
+        assert_eq!(config().select_lod_without_hysteresis(0.39, 3), 1);
+        assert_eq!(config().select_lod_without_hysteresis(0.001, 3), 3);
+    }
+
+    #[test]
+    fn selection_without_hysteresis_without_levels_or_when_disabled_gives_full_detail() {
+        assert_eq!(config().select_lod_without_hysteresis(0.001, 0), 0);
+        let config = LODSelectionConfig {
+            enabled: false,
+            ..config()
+        };
+        assert_eq!(config.select_lod_without_hysteresis(0.001, 3), 0);
+    }
+
+    #[test]
+    fn level_is_kept_within_hysteresis_margin() {
+        let config = config();
+        // Just below the threshold for level 1, but within the margin
+        assert_eq!(config.select_lod(0.39, 0, 3), 0);
+        // Well below the threshold
+        assert_eq!(config.select_lod(0.35, 0, 3), 1);
+        // Just above the threshold, but within the margin
+        assert_eq!(config.select_lod(0.41, 1, 3), 1);
+        // Well above the threshold
+        assert_eq!(config.select_lod(0.45, 1, 3), 0);
+    }
+
+    #[test]
+    fn selection_is_stable_for_unchanged_size() {
+        let config = config();
+        for size in [0.5, 0.39, 0.21, 0.19, 0.11, 0.09, 0.01] {
+            for current in 0..=3 {
+                let selected = config.select_lod(size, current, 3);
+                assert_eq!(config.select_lod(size, selected, 3), selected);
+            }
+        }
+    }
+}
@@ -1,6 +1,9 @@
 //! Models defined by a mesh and material.
 
-use crate::graph::{ModelInstanceFlags, ModelInstanceNode, SceneGraph};
+use crate::{
+    graph::{ModelInstanceFlags, ModelInstanceNode, SceneGraph},
+    lod::{self, LODSelectionConfig},
+};
 use bytemuck::Zeroable;
 use impact_camera::Camera;
 use impact_geometry::AxisAlignedBox;
@@ -14,6 +17,7 @@ use impact_math::{
 use impact_mesh::{LineSegmentMeshID, MeshID, TriangleMeshID};
 use impact_model::{
     InstanceFeature, ModelInstanceID,
+    entity::InstanceEntityID,
     transform::{InstanceModelViewTransform, InstanceModelViewTransformWithPrevious},
 };
 use std::{
@@ -159,7 +163,10 @@ impl Hash for ModelID {
 
 /// Computes the model-to-camera space transforms of all the model instances
 /// in the scene graph that are visible with the specified camera and adds
-/// them to the model instance manager.
+/// them to the model instance manager. Instances with simplified levels of
+/// detail are added to the model for the level selected based on their
+/// projected size. The entity ID of each instance is only buffered if entity
+/// picking is enabled.
 ///
 /// # Returns
 /// The world- and camera-space AABBs encompassing all the buffered model
@@ -175,6 +182,8 @@ pub fn buffer_model_instances_for_rendering(
     scene_graph: &SceneGraph,
     camera: &Camera,
     current_frame_number: u32,
+    lod_selection_config: &LODSelectionConfig,
+    entity_picking_enabled: bool,
 ) -> Option<ModelInstanceBufferingResult> {
     let world_space_view_frustum = camera.compute_world_space_view_frustum();
 
@@ -209,6 +218,13 @@ pub fn buffer_model_instances_for_rendering(
 
             found_visible_instances = true;
 
+            let model_id = if model_instance_node.lod_model_ids().is_empty() {
+                model_instance_node.model_id()
+            } else {
+                let screen_size = lod::compute_projected_screen_size(camera, &aabb);
+                model_instance_node.select_model_id_for_rendering(screen_size, lod_selection_config)
+            };
+
             buffer_model_instance_for_rendering(
                 material_registry,
                 model_instance_manager,
@@ -216,6 +232,8 @@ pub fn buffer_model_instances_for_rendering(
                 camera,
                 current_frame_number,
                 model_instance_node,
+                model_id,
+                entity_picking_enabled,
             );
         },
     );
@@ -237,6 +255,8 @@ fn buffer_model_instance_for_rendering(
     camera: &Camera,
     current_frame_number: u32,
     model_instance_node: &ModelInstanceNode,
+    model_id: &ModelID,
+    entity_picking_enabled: bool,
 ) {
     let model_view_transform =
         compute_model_view_transform_for_node(scene_graph, camera, model_instance_node);
@@ -253,12 +273,19 @@ fn buffer_model_instance_for_rendering(
         )
         .set_transform_for_new_frame(instance_model_view_transform);
 
-    let model_id = model_instance_node.model_id();
-
-    model_instance_manager.buffer_instance_features_from_storages(
-        model_id,
-        model_instance_node.feature_ids_for_rendering(),
-    );
+    if entity_picking_enabled {
+        model_instance_manager.buffer_instance_features_from_storages(
+            model_id,
+            model_instance_node.feature_ids_for_rendering(),
+        );
+    } else {
+        // The entity ID buffer is left empty, since nothing reads it
+        for &feature_id in model_instance_node.feature_ids_for_rendering() {
+            if feature_id.feature_type_id() != InstanceEntityID::FEATURE_TYPE_ID {
+                model_instance_manager.buffer_instance_feature_from_storage(model_id, feature_id);
+            }
+        }
+    }
 
     if !model_instance_node
         .flags()
@@ -9,7 +9,7 @@ use anyhow::{Result, anyhow};
 use impact_geometry::AxisAlignedBox;
 use impact_id::EntityID;
 use impact_intersection::bounding_volume::{BoundingVolumeID, BoundingVolumeManager};
-use impact_material::{MaterialID, MaterialRegistry};
+use impact_material::{MaterialID, MaterialRegistry, values::TextureCoordTransform};
 use impact_math::{
     point::Point3,
     transform::{Isometry3C, Similarity3C},
@@ -17,6 +17,8 @@ use impact_math::{
 use impact_mesh::{TriangleMeshID, TriangleMeshRegistry};
 use impact_model::{
     ModelInstanceID,
+    entity::InstanceEntityID,
+    skinning::InstanceJointPaletteOffset,
     transform::{InstanceModelLightTransform, InstanceModelViewTransformWithPrevious},
 };
 use tinyvec::TinyVec;
@@ -46,6 +48,14 @@ pub fn setup_scene_graph_group_node(
     scene_graph.create_group_node(parent_group_id, scene_group_id, group_to_parent_transform)
 }
 
+/// Creates a model instance node for the given entity in the scene graph and
+/// registers the instance with the model instance manager. The given LOD mesh
+/// IDs are the simplified levels of detail of the mesh, ordered from the most
+/// to the least detailed, and an instance is registered for the model of each
+/// level so that any of them can be selected for rendering. Instances of
+/// skinned meshes also get an [`InstanceJointPaletteOffset`] feature, and
+/// instances whose material samples a texture atlas region get a
+/// [`TextureCoordTransform`] feature.
 pub fn setup_scene_graph_model_instance_node(
     material_registry: &MaterialRegistry,
     model_instance_manager: &mut ModelInstanceManager,
@@ -53,10 +63,12 @@ pub fn setup_scene_graph_model_instance_node(
     entity_id: EntityID,
     model_to_parent_transform: Similarity3C,
     mesh_id: TriangleMeshID,
+    lod_mesh_ids: &[TriangleMeshID],
     material_id: MaterialID,
     parent_entity_id: Option<EntityID>,
     flags: Option<&SceneEntityFlags>,
     has_independent_material_values: bool,
+    is_skinned: bool,
 ) -> Result<SceneEntityFlags> {
     let flags = flags.copied().unwrap_or_default();
 
@@ -85,10 +97,37 @@ pub fn setup_scene_graph_model_instance_node(
     feature_type_ids.push(model_light_transform_feature_id.feature_type_id());
     feature_ids_for_shadow_mapping.push(model_light_transform_feature_id);
 
-    let material_property_values = material_registry
+    // Add an entry for the entity ID so that it can be written to the entity
+    // ID render attachment when picking is enabled. The feature is always
+    // stored so that picking can be enabled at any time, but it is only
+    // buffered for rendering while picking is enabled
+    let entity_id_feature_id = model_instance_manager
+        .get_storage_mut::<InstanceEntityID>()
+        .expect("Missing storage for InstanceEntityID feature")
+        .add_feature(&InstanceEntityID::new(entity_id));
+
+    feature_type_ids.push(entity_id_feature_id.feature_type_id());
+    feature_ids_for_rendering.push(entity_id_feature_id);
+
+    // Skinned meshes need the offset of their joint palette both when rendered
+    // from the camera and when rendered into shadow maps. The offset starts
+    // out referring to the bind pose palette until the instance is animated.
+    if is_skinned {
+        let joint_palette_offset_feature_id = model_instance_manager
+            .get_storage_mut::<InstanceJointPaletteOffset>()
+            .expect("Missing storage for InstanceJointPaletteOffset feature")
+            .add_feature(&InstanceJointPaletteOffset::default());
+
+        feature_type_ids.push(joint_palette_offset_feature_id.feature_type_id());
+        feature_ids_for_rendering.push(joint_palette_offset_feature_id);
+        feature_ids_for_shadow_mapping.push(joint_palette_offset_feature_id);
+    }
+
+    let material = material_registry
         .get(model_id.material_id())
-        .ok_or_else(|| anyhow!("Missing material {} for model", model_id.material_id()))?
-        .property_values;
+        .ok_or_else(|| anyhow!("Missing material {} for model", model_id.material_id()))?;
+
+    let material_property_values = material.property_values;
 
     if let Some(material_property_values_feature_type_id) =
         material_property_values.instance_feature_type_id_if_applicable()
@@ -103,8 +142,29 @@ pub fn setup_scene_graph_model_instance_node(
         }
     }
 
+    // The texture coordinate transform starts out as the one for the atlas
+    // region of the material, but can be changed for each instance
+    if let Some(texture_coord_transform) = &material.texture_coord_transform {
+        let texture_coord_transform_feature_id = model_instance_manager
+            .get_storage_mut::<TextureCoordTransform>()
+            .expect("Missing storage for TextureCoordTransform feature")
+            .add_feature(texture_coord_transform);
+
+        feature_type_ids.push(texture_coord_transform_feature_id.feature_type_id());
+        feature_ids_for_rendering.push(texture_coord_transform_feature_id);
+    }
+
     model_instance_manager.register_instance(model_id, &feature_type_ids);
 
+    let lod_model_ids: Vec<_> = lod_mesh_ids
+        .iter()
+        .map(|&lod_mesh_id| ModelID::for_triangle_mesh_and_material(lod_mesh_id, material_id))
+        .collect();
+
+    for lod_model_id in &lod_model_ids {
+        model_instance_manager.register_instance(*lod_model_id, &feature_type_ids);
+    }
+
     let model_instance_id = ModelInstanceID::from_entity_id(entity_id);
     let parent_group_id =
         parent_entity_id.map_or_else(|| scene_graph.root_node_id(), SceneGroupID::from_entity_id);
@@ -120,6 +180,7 @@ pub fn setup_scene_graph_model_instance_node(
         model_instance_id,
         model_to_parent_transform,
         model_id,
+        lod_model_ids,
         feature_ids_for_rendering,
         feature_ids_for_shadow_mapping,
         model_instance_flags,
@@ -147,14 +208,32 @@ pub fn setup_bounding_volume_for_mesh(
     bounding_volume_manager.insert_bounding_volume(bounding_volume_id, aabb)
 }
 
+/// Removes the model instance node for the given entity from the scene graph
+/// and unregisters the instance from the model instance manager, including the
+/// instances for the models of any simplified levels of detail.
+///
+/// # Returns
+/// The [`ModelID`]s for all levels of detail of the removed instance, starting
+/// with the full-detail model, if the node existed.
 pub fn remove_scene_graph_model_instance_node(
     model_instance_manager: &mut ModelInstanceManager,
     scene_graph: &mut SceneGraph,
     entity_id: EntityID,
-) {
-    if let Some(model_id) =
-        scene_graph.remove_model_instance_node(ModelInstanceID::from_entity_id(entity_id))
-    {
-        model_instance_manager.unregister_instance(&model_id);
+) -> Option<Vec<ModelID>> {
+    let model_instance_id = ModelInstanceID::from_entity_id(entity_id);
+
+    let model_ids: Vec<_> = scene_graph
+        .model_instance_nodes()
+        .get_node(model_instance_id)?
+        .model_ids_for_all_lods()
+        .copied()
+        .collect();
+
+    scene_graph.remove_model_instance_node(model_instance_id);
+
+    for model_id in &model_ids {
+        model_instance_manager.unregister_instance(model_id);
     }
+
+    Some(model_ids)
 }
@@ -1,9 +1,10 @@
 //! Skybox.
 
+pub mod atmosphere;
 pub mod gpu_resource;
 
 use anyhow::Result;
-use bytemuck::{Pod, Zeroable};
+use atmosphere::{Atmosphere, Sun};
 use gpu_resource::SkyboxGPUResource;
 use impact_gpu::device::GraphicsDevice;
 use impact_texture::{
@@ -12,45 +13,53 @@ use impact_texture::{
 };
 use roc_integration::roc;
 
-/// A skybox specified by a cubemap texture and a maximum luminance (the
-/// luminance that a texel value of unity should be mapped to).
+/// A skybox, which is either specified by a cubemap texture or computed
+/// procedurally from scattering of sunlight in an atmosphere.
 #[roc]
-#[repr(C)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
-#[derive(Clone, Copy, Debug, PartialEq, Zeroable, Pod)]
-pub struct Skybox {
-    cubemap_texture_id: TextureID,
-    max_luminance: f64,
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Skybox {
+    /// A skybox specified by a cubemap texture and a maximum luminance (the
+    /// luminance that a texel value of unity should be mapped to).
+    Cubemap {
+        cubemap_texture_id: TextureID,
+        max_luminance: f64,
+    },
+    /// A sky computed from the scattering of light from the scene's sun (its
+    /// brightest unidirectional light) in the given atmosphere. The sky is
+    /// recomputed whenever the sun moves, and its illuminance is added to the
+    /// scene's ambient lighting.
+    Atmospheric(Atmosphere),
 }
 
 #[roc]
 impl Skybox {
     /// Creates a new skybox with the given cubemap texture and maximum
     /// luminance.
-    #[roc(body = "{ cubemap_texture_id, max_luminance }")]
-    pub fn new(cubemap_texture_id: TextureID, max_luminance: f64) -> Self {
-        Self {
+    #[roc(body = "Cubemap { cubemap_texture_id, max_luminance }")]
+    pub fn cubemap(cubemap_texture_id: TextureID, max_luminance: f64) -> Self {
+        Self::Cubemap {
             cubemap_texture_id,
             max_luminance,
         }
     }
 
-    pub fn cubemap_texture_id(&self) -> &TextureID {
-        &self.cubemap_texture_id
-    }
-
-    pub fn max_luminance(&self) -> f64 {
-        self.max_luminance
+    /// Creates a new skybox computed from the scattering of sunlight in the
+    /// given atmosphere.
+    #[roc(body = "Atmospheric(atmosphere)")]
+    pub fn atmospheric(atmosphere: Atmosphere) -> Self {
+        Self::Atmospheric(atmosphere)
     }
 }
 
 /// Performs any required updates for keeping the skybox GPU resources in sync
-/// with the given scene skybox.
+/// with the given scene skybox and sun.
 ///
 /// # Errors
 /// Returns an error if the skybox cubemap texture or sampler is missing.
 pub fn sync_gpu_resources_for_skybox(
     skybox: Option<&Skybox>,
+    sun: Option<&Sun>,
     graphics_device: &GraphicsDevice,
     textures: &TextureMap,
     samplers: &SamplerMap,
@@ -58,13 +67,20 @@ pub fn sync_gpu_resources_for_skybox(
 ) -> Result<()> {
     if let Some(&skybox) = skybox {
         if let Some(skybox_gpu_resources) = skybox_gpu_resources {
-            skybox_gpu_resources.sync_with_skybox(graphics_device, textures, samplers, skybox)?;
+            skybox_gpu_resources.sync_with_skybox(
+                graphics_device,
+                textures,
+                samplers,
+                skybox,
+                sun,
+            )?;
         } else {
             *skybox_gpu_resources = Some(SkyboxGPUResource::for_skybox(
                 graphics_device,
                 textures,
                 samplers,
                 skybox,
+                sun,
             )?);
         }
     } else {
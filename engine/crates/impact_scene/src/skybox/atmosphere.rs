@@ -0,0 +1,689 @@
+//! Procedural sky computed from physically based scattering of sunlight in a
+//! planetary atmosphere.
+
+use impact_light::{Illumninance, LightFlags, LightManager, Luminance};
+use impact_math::{
+    consts::f32::PI,
+    transform::Isometry3,
+    vector::{UnitVector3, Vector3, Vector3C},
+};
+use roc_integration::roc;
+
+/// A planetary atmosphere that scatters and absorbs light from the sun. The
+/// atmosphere is modeled as a spherical shell around the planet containing air
+/// molecules (causing Rayleigh scattering) and aerosols (causing Mie
+/// scattering) whose densities decrease exponentially with altitude, as well
+/// as a layer of ozone (causing absorption).
+///
+/// The local vertical direction at the observer is taken to be the world
+/// y-axis.
+#[roc]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Atmosphere {
+    /// The radius of the planet's surface [m].
+    pub planet_radius: f32,
+    /// The thickness of the atmosphere above the surface [m].
+    pub atmosphere_height: f32,
+    /// The altitude of the observer above the surface [m].
+    pub observer_altitude: f32,
+    /// The Rayleigh scattering coefficient at the surface for each RGB
+    /// channel [1/m].
+    pub rayleigh_scattering_coefficients: Vector3C,
+    /// The increase in altitude over which the density of Rayleigh scatterers
+    /// decreases by a factor of e [m].
+    pub rayleigh_scale_height: f32,
+    /// The Mie scattering coefficient at the surface [1/m].
+    pub mie_scattering_coefficient: f32,
+    /// The Mie extinction (scattering plus absorption) coefficient at the
+    /// surface [1/m].
+    pub mie_extinction_coefficient: f32,
+    /// The increase in altitude over which the density of Mie scatterers
+    /// decreases by a factor of e [m].
+    pub mie_scale_height: f32,
+    /// The asymmetry parameter of the Mie phase function. Ranges from -1 (all
+    /// light is scattered backward) to 1 (all light is scattered forward).
+    pub mie_anisotropy: f32,
+    /// The ozone absorption coefficient at the peak of the ozone layer for
+    /// each RGB channel [1/m].
+    pub ozone_absorption_coefficients: Vector3C,
+    /// The altitude of the peak of the ozone layer [m].
+    pub ozone_peak_altitude: f32,
+    /// The distance above and below the peak of the ozone layer at which the
+    /// ozone density reaches zero [m].
+    pub ozone_half_thickness: f32,
+}
+
+/// The sun illuminating an [`Atmosphere`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sun {
+    /// The world space direction towards the sun.
+    pub direction: UnitVector3,
+    /// The illuminance from the sun on a surface perpendicular to the sun
+    /// direction outside the atmosphere.
+    pub perpendicular_illuminance: Illumninance,
+}
+
+/// A cubemap of the sky luminance seen by an observer in an [`Atmosphere`]
+/// illuminated by a [`Sun`].
+///
+/// The texels are stored face by face in the order +x, -x, +y, -y, +z, -z,
+/// with each face stored row by row, matching the layout expected for cubemap
+/// textures.
+#[derive(Clone, Debug)]
+pub struct SkyCubemap {
+    face_resolution: usize,
+    luminance: Vec<Luminance>,
+    max_luminance: f32,
+    horizontal_illuminance: Illumninance,
+}
+
+/// Precomputed transmittance from points in the atmosphere to the top of the
+/// atmosphere, parameterized by the altitude of the point and the cosine of the
+/// zenith angle of the direction.
+#[derive(Clone, Debug)]
+struct TransmittanceLookupTable {
+    values: Vec<Vector3>,
+}
+
+#[roc]
+impl Atmosphere {
+    /// Creates the atmosphere of the Earth, observed from the given altitude
+    /// above the surface.
+    #[roc(body = r#"{
+        planet_radius: 6.36e6,
+        atmosphere_height: 1e5,
+        observer_altitude,
+        rayleigh_scattering_coefficients: (5.802e-6, 13.558e-6, 33.1e-6),
+        rayleigh_scale_height: 8e3,
+        mie_scattering_coefficient: 3.996e-6,
+        mie_extinction_coefficient: 4.4e-6,
+        mie_scale_height: 1.2e3,
+        mie_anisotropy: 0.8,
+        ozone_absorption_coefficients: (0.65e-6, 1.881e-6, 0.085e-6),
+        ozone_peak_altitude: 2.5e4,
+        ozone_half_thickness: 1.5e4,
+    }
+    "#)]
+    pub fn earth(observer_altitude: f32) -> Self {
+        Self {
+            planet_radius: 6.36e6,
+            atmosphere_height: 1e5,
+            observer_altitude,
+            rayleigh_scattering_coefficients: Vector3C::new(5.802e-6, 13.558e-6, 33.1e-6),
+            rayleigh_scale_height: 8e3,
+            mie_scattering_coefficient: 3.996e-6,
+            mie_extinction_coefficient: 4.4e-6,
+            mie_scale_height: 1.2e3,
+            mie_anisotropy: 0.8,
+            ozone_absorption_coefficients: Vector3C::new(0.65e-6, 1.881e-6, 0.085e-6),
+            ozone_peak_altitude: 2.5e4,
+            ozone_half_thickness: 1.5e4,
+        }
+    }
+
+    fn top_radius(&self) -> f32 {
+        self.planet_radius + self.atmosphere_height
+    }
+
+    fn observer_radius(&self) -> f32 {
+        self.planet_radius + self.observer_altitude.max(0.0)
+    }
+
+    fn rayleigh_density(&self, altitude: f32) -> f32 {
+        f32::exp(-altitude / self.rayleigh_scale_height)
+    }
+
+    fn mie_density(&self, altitude: f32) -> f32 {
+        f32::exp(-altitude / self.mie_scale_height)
+    }
+
+    fn ozone_density(&self, altitude: f32) -> f32 {
+        (1.0 - (altitude - self.ozone_peak_altitude).abs() / self.ozone_half_thickness).max(0.0)
+    }
+
+    fn extinction_coefficients(&self, altitude: f32) -> Vector3 {
+        let rayleigh = self.rayleigh_scattering_coefficients.aligned();
+        let ozone = self.ozone_absorption_coefficients.aligned();
+
+        rayleigh * self.rayleigh_density(altitude)
+            + Vector3::same(self.mie_extinction_coefficient * self.mie_density(altitude))
+            + ozone * self.ozone_density(altitude)
+    }
+
+    /// Computes the distance along the ray from the given point (relative to
+    /// the planet center) in the given direction to where it exits the
+    /// atmosphere or hits the ground, and whether it hits the ground. Returns
+    /// [`None`] if the ray never passes through the atmosphere.
+    fn ray_extent(&self, origin: &Vector3, direction: &Vector3) -> Option<(f32, f32, bool)> {
+        let b = origin.dot(direction);
+        let origin_radius_squared = origin.norm_squared();
+
+        let top_discriminant = b.powi(2) - (origin_radius_squared - self.top_radius().powi(2));
+        if top_discriminant < 0.0 {
+            return None;
+        }
+        let sqrt_top_discriminant = top_discriminant.sqrt();
+
+        let start = (-b - sqrt_top_discriminant).max(0.0);
+        let mut end = -b + sqrt_top_discriminant;
+        if end <= 0.0 {
+            return None;
+        }
+
+        let ground_discriminant = b.powi(2) - (origin_radius_squared - self.planet_radius.powi(2));
+        let mut hits_ground = false;
+        // The ray can only hit the ground if it is heading downward
+        if ground_discriminant >= 0.0 && b < 0.0 {
+            let ground_distance = (-b - ground_discriminant.sqrt()).max(0.0);
+            if ground_distance < end {
+                end = ground_distance;
+                hits_ground = true;
+            }
+        }
+
+        Some((start, end, hits_ground))
+    }
+}
+
+impl Sun {
+    /// The minimum change in the angle of the sun direction, in radians, for
+    /// the sky to be considered noticeably different.
+    const MIN_NOTICEABLE_ANGLE_CHANGE: f32 = 2.5e-3;
+
+    /// The minimum fractional change in the illuminance for the sky to be
+    /// considered noticeably different.
+    const MIN_NOTICEABLE_ILLUMINANCE_CHANGE: f32 = 1e-2;
+
+    /// Finds the brightest enabled unidirectional light (shadowable or not) in
+    /// the given light manager and returns it as the sun, or returns [`None`]
+    /// if there are no enabled unidirectional lights. The given view transform
+    /// is used to convert the camera space light direction to world space.
+    pub fn find_brightest(
+        light_manager: &LightManager,
+        view_transform: &Isometry3,
+    ) -> Option<Self> {
+        let unidirectional_lights = light_manager
+            .unidirectional_lights()
+            .iter()
+            .filter(|light| !light.flags().contains(LightFlags::IS_DISABLED))
+            .map(|light| {
+                (
+                    light.camera_space_direction(),
+                    light.perpendicular_illuminance(),
+                )
+            });
+
+        let shadowable_unidirectional_lights = light_manager
+            .shadowable_unidirectional_lights()
+            .iter()
+            .filter(|light| !light.flags().contains(LightFlags::IS_DISABLED))
+            .map(|light| {
+                (
+                    light.camera_space_direction(),
+                    light.perpendicular_illuminance(),
+                )
+            });
+
+        unidirectional_lights
+            .chain(shadowable_unidirectional_lights)
+            .max_by(|(_, illuminance_a), (_, illuminance_b)| {
+                illuminance_a
+                    .aligned()
+                    .max_component()
+                    .total_cmp(&illuminance_b.aligned().max_component())
+            })
+            .map(|(camera_space_direction, perpendicular_illuminance)| {
+                // The light travels away from the sun
+                let light_direction =
+                    view_transform.inverse_transform_unit_vector(&camera_space_direction.aligned());
+                Self {
+                    direction: -light_direction,
+                    perpendicular_illuminance: *perpendicular_illuminance,
+                }
+            })
+    }
+
+    /// Whether the direction or illuminance of this sun differs from that of
+    /// the given sun enough to noticeably affect the appearance of the sky.
+    pub fn differs_noticeably_from(&self, other: &Self) -> bool {
+        let cos_angle = self.direction.dot(&other.direction);
+        if cos_angle < f32::cos(Self::MIN_NOTICEABLE_ANGLE_CHANGE) {
+            return true;
+        }
+
+        let illuminance = self.perpendicular_illuminance.aligned();
+        let other_illuminance = other.perpendicular_illuminance.aligned();
+
+        let max_illuminance = illuminance
+            .component_max(&other_illuminance)
+            .max_component();
+
+        (illuminance - other_illuminance)
+            .component_abs()
+            .max_component()
+            > Self::MIN_NOTICEABLE_ILLUMINANCE_CHANGE * max_illuminance
+    }
+}
+
+impl SkyCubemap {
+    const VIEW_RAY_STEPS: usize = 32;
+
+    /// Computes the sky luminance seen in the given atmosphere illuminated by
+    /// the given sun, using single scattering, for each texel of a cubemap
+    /// with the given number of texels along each edge of a face.
+    pub fn generate(atmosphere: &Atmosphere, sun: &Sun, face_resolution: usize) -> Self {
+        let transmittance_lookup_table = TransmittanceLookupTable::compute(atmosphere);
+
+        let texel_solid_angle_scale = (2.0 / face_resolution as f32).powi(2);
+
+        let mut luminance = Vec::with_capacity(6 * face_resolution * face_resolution);
+        let mut max_luminance: f32 = 0.0;
+        let mut horizontal_illuminance = Vector3::zeros();
+
+        for face_idx in 0..6 {
+            for row in 0..face_resolution {
+                for col in 0..face_resolution {
+                    let unnormalized_direction =
+                        texel_direction(face_idx, row, col, face_resolution);
+                    let norm = unnormalized_direction.norm();
+                    let direction = unnormalized_direction / norm;
+
+                    let texel_luminance = compute_single_scattering_luminance(
+                        atmosphere,
+                        &transmittance_lookup_table,
+                        &direction,
+                        sun,
+                    );
+
+                    // The cosine-weighted solid angle of the texel contributes
+                    // to the illuminance on a horizontal surface
+                    if direction.y() > 0.0 {
+                        let solid_angle = texel_solid_angle_scale / norm.powi(3);
+                        horizontal_illuminance += texel_luminance * (direction.y() * solid_angle);
+                    }
+
+                    max_luminance = max_luminance.max(texel_luminance.max_component());
+                    luminance.push(texel_luminance.compact());
+                }
+            }
+        }
+
+        Self {
+            face_resolution,
+            luminance,
+            max_luminance,
+            horizontal_illuminance: horizontal_illuminance.compact(),
+        }
+    }
+
+    /// Returns the number of texels along each edge of a cubemap face.
+    pub fn face_resolution(&self) -> usize {
+        self.face_resolution
+    }
+
+    /// Returns the sky luminance for each texel in the cubemap.
+    pub fn luminance(&self) -> &[Luminance] {
+        &self.luminance
+    }
+
+    /// Returns the largest luminance component of any texel.
+    pub fn max_luminance(&self) -> f32 {
+        self.max_luminance
+    }
+
+    /// Returns the illuminance from the sky on an upward-facing horizontal
+    /// surface.
+    pub fn horizontal_illuminance(&self) -> &Illumninance {
+        &self.horizontal_illuminance
+    }
+
+    /// Returns the luminance of each texel divided by the maximum luminance,
+    /// encoded as RGBA 16-bit floats with unit alpha.
+    pub fn to_normalized_rgba16_float_bytes(&self) -> Vec<u8> {
+        let scale = if self.max_luminance > 0.0 {
+            self.max_luminance.recip()
+        } else {
+            0.0
+        };
+
+        let texels: Vec<half::f16> = self
+            .luminance
+            .iter()
+            .flat_map(|luminance| {
+                [
+                    luminance.x() * scale,
+                    luminance.y() * scale,
+                    luminance.z() * scale,
+                    1.0,
+                ]
+            })
+            .map(half::f16::from_f32)
+            .collect();
+
+        bytemuck::cast_slice(&texels).to_vec()
+    }
+}
+
+impl TransmittanceLookupTable {
+    const ALTITUDE_COUNT: usize = 32;
+    const COS_ZENITH_ANGLE_COUNT: usize = 128;
+    const RAY_STEPS: usize = 40;
+
+    fn compute(atmosphere: &Atmosphere) -> Self {
+        let mut values = Vec::with_capacity(Self::ALTITUDE_COUNT * Self::COS_ZENITH_ANGLE_COUNT);
+
+        for altitude_idx in 0..Self::ALTITUDE_COUNT {
+            let altitude = Self::altitude_for_idx(atmosphere, altitude_idx);
+            let origin = Vector3::new(0.0, atmosphere.planet_radius + altitude, 0.0);
+
+            for cos_zenith_angle_idx in 0..Self::COS_ZENITH_ANGLE_COUNT {
+                let cos_zenith_angle = Self::cos_zenith_angle_for_idx(cos_zenith_angle_idx);
+                let sin_zenith_angle = (1.0 - cos_zenith_angle.powi(2)).max(0.0).sqrt();
+                let direction = Vector3::new(sin_zenith_angle, cos_zenith_angle, 0.0);
+
+                values.push(compute_transmittance_to_top(
+                    atmosphere, &origin, &direction,
+                ));
+            }
+        }
+
+        Self { values }
+    }
+
+    /// Altitudes are distributed quadratically to resolve the dense lower
+    /// atmosphere better.
+    fn altitude_for_idx(atmosphere: &Atmosphere, idx: usize) -> f32 {
+        let fraction = idx as f32 / (Self::ALTITUDE_COUNT - 1) as f32;
+        atmosphere.atmosphere_height * fraction.powi(2)
+    }
+
+    fn cos_zenith_angle_for_idx(idx: usize) -> f32 {
+        2.0 * idx as f32 / (Self::COS_ZENITH_ANGLE_COUNT - 1) as f32 - 1.0
+    }
+
+    /// Returns the bilinearly interpolated transmittance from a point at the
+    /// given altitude to the top of the atmosphere along a direction with the
+    /// given cosine of the zenith angle.
+    fn transmittance(
+        &self,
+        atmosphere: &Atmosphere,
+        altitude: f32,
+        cos_zenith_angle: f32,
+    ) -> Vector3 {
+        let altitude_coord = (altitude.max(0.0) / atmosphere.atmosphere_height)
+            .min(1.0)
+            .sqrt()
+            * (Self::ALTITUDE_COUNT - 1) as f32;
+
+        let cos_zenith_angle_coord = 0.5
+            * (cos_zenith_angle.clamp(-1.0, 1.0) + 1.0)
+            * (Self::COS_ZENITH_ANGLE_COUNT - 1) as f32;
+
+        let (altitude_idx, altitude_weight) =
+            Self::lower_idx_and_weight(altitude_coord, Self::ALTITUDE_COUNT);
+        let (cos_zenith_angle_idx, cos_zenith_angle_weight) =
+            Self::lower_idx_and_weight(cos_zenith_angle_coord, Self::COS_ZENITH_ANGLE_COUNT);
+
+        let value = |altitude_idx: usize, cos_zenith_angle_idx: usize| {
+            self.values[altitude_idx * Self::COS_ZENITH_ANGLE_COUNT + cos_zenith_angle_idx]
+        };
+
+        let lower = value(altitude_idx, cos_zenith_angle_idx) * (1.0 - cos_zenith_angle_weight)
+            + value(altitude_idx, cos_zenith_angle_idx + 1) * cos_zenith_angle_weight;
+        let upper = value(altitude_idx + 1, cos_zenith_angle_idx) * (1.0 - cos_zenith_angle_weight)
+            + value(altitude_idx + 1, cos_zenith_angle_idx + 1) * cos_zenith_angle_weight;
+
+        lower * (1.0 - altitude_weight) + upper * altitude_weight
+    }
+
+    fn lower_idx_and_weight(coord: f32, count: usize) -> (usize, f32) {
+        let lower_idx = (coord.floor() as usize).min(count - 2);
+        (lower_idx, coord - lower_idx as f32)
+    }
+}
+
+/// Computes the transmittance along the ray from the given point (relative to
+/// the planet center) in the given direction to the top of the atmosphere,
+/// which is zero if the ray hits the ground.
+fn compute_transmittance_to_top(
+    atmosphere: &Atmosphere,
+    origin: &Vector3,
+    direction: &Vector3,
+) -> Vector3 {
+    let Some((start, end, hits_ground)) = atmosphere.ray_extent(origin, direction) else {
+        return Vector3::same(1.0);
+    };
+    if hits_ground {
+        return Vector3::zeros();
+    }
+
+    let step_length = (end - start) / TransmittanceLookupTable::RAY_STEPS as f32;
+
+    let mut optical_depth = Vector3::zeros();
+    for step in 0..TransmittanceLookupTable::RAY_STEPS {
+        let distance = start + (step as f32 + 0.5) * step_length;
+        let altitude = (origin + direction * distance).norm() - atmosphere.planet_radius;
+        optical_depth += atmosphere.extinction_coefficients(altitude) * step_length;
+    }
+
+    (-optical_depth).mapped(f32::exp)
+}
+
+/// Computes the luminance of sunlight scattered once towards the observer
+/// from the given view direction.
+fn compute_single_scattering_luminance(
+    atmosphere: &Atmosphere,
+    transmittance_lookup_table: &TransmittanceLookupTable,
+    view_direction: &Vector3,
+    sun: &Sun,
+) -> Vector3 {
+    let observer_position = Vector3::new(0.0, atmosphere.observer_radius(), 0.0);
+
+    let Some((start, end, _)) = atmosphere.ray_extent(&observer_position, view_direction) else {
+        return Vector3::zeros();
+    };
+
+    let sun_direction = sun.direction.as_vector();
+    let cos_scattering_angle = view_direction.dot(sun_direction);
+
+    let rayleigh_phase = rayleigh_phase_function(cos_scattering_angle);
+    let mie_phase = mie_phase_function(cos_scattering_angle, atmosphere.mie_anisotropy);
+
+    let rayleigh_scattering = atmosphere.rayleigh_scattering_coefficients.aligned();
+
+    let step_length = (end - start) / SkyCubemap::VIEW_RAY_STEPS as f32;
+
+    let mut optical_depth = Vector3::zeros();
+    let mut luminance = Vector3::zeros();
+
+    for step in 0..SkyCubemap::VIEW_RAY_STEPS {
+        let distance = start + (step as f32 + 0.5) * step_length;
+        let position = observer_position + view_direction * distance;
+        let radius = position.norm();
+        let altitude = radius - atmosphere.planet_radius;
+
+        let extinction = atmosphere.extinction_coefficients(altitude) * step_length;
+        let view_transmittance = (-(optical_depth + extinction * 0.5)).mapped(f32::exp);
+        optical_depth += extinction;
+
+        let cos_sun_zenith_angle = position.dot(sun_direction) / radius;
+        let sun_transmittance =
+            transmittance_lookup_table.transmittance(atmosphere, altitude, cos_sun_zenith_angle);
+
+        let scattering = rayleigh_scattering
+            * (atmosphere.rayleigh_density(altitude) * rayleigh_phase)
+            + Vector3::same(
+                atmosphere.mie_scattering_coefficient
+                    * atmosphere.mie_density(altitude)
+                    * mie_phase,
+            );
+
+        luminance += view_transmittance
+            .component_mul(&sun_transmittance)
+            .component_mul(&scattering)
+            * step_length;
+    }
+
+    luminance.component_mul(&sun.perpendicular_illuminance.aligned())
+}
+
+fn rayleigh_phase_function(cos_angle: f32) -> f32 {
+    3.0 / (16.0 * PI) * (1.0 + cos_angle.powi(2))
+}
+
+/// The Cornette-Shanks phase function, which is a variant of the
+/// Henyey-Greenstein phase function that better approximates Mie scattering.
+fn mie_phase_function(cos_angle: f32, anisotropy: f32) -> f32 {
+    let g_squared = anisotropy.powi(2);
+    3.0 / (8.0 * PI) * (1.0 - g_squared) * (1.0 + cos_angle.powi(2))
+        / ((2.0 + g_squared) * (1.0 + g_squared - 2.0 * anisotropy * cos_angle).powf(1.5))
+}
+
+/// Returns the (unnormalized) direction through the center of the given texel
+/// in the given cubemap face, following the standard cubemap convention.
+fn texel_direction(face_idx: usize, row: usize, col: usize, face_resolution: usize) -> Vector3 {
+    let u = 2.0 * (col as f32 + 0.5) / face_resolution as f32 - 1.0;
+    let v = 2.0 * (row as f32 + 0.5) / face_resolution as f32 - 1.0;
+    match face_idx {
+        0 => Vector3::new(1.0, -v, -u),
+        1 => Vector3::new(-1.0, -v, u),
+        2 => Vector3::new(u, 1.0, v),
+        3 => Vector3::new(u, -1.0, -v),
+        4 => Vector3::new(u, -v, 1.0),
+        _ => Vector3::new(-u, -v, -1.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    fn sun_at_elevation(elevation_degrees: f32) -> Sun {
+        let elevation = elevation_degrees.to_radians();
+        Sun {
+            direction: UnitVector3::normalized_from(Vector3::new(
+                elevation.cos(),
+                elevation.sin(),
+                0.0,
+            )),
+            perpendicular_illuminance: Vector3C::same(1e5),
+        }
+    }
+
+    fn sky_luminance(sun: &Sun, view_direction: Vector3) -> Vector3 {
+        let atmosphere = Atmosphere::earth(0.0);
+        let lookup_table = TransmittanceLookupTable::compute(&atmosphere);
+        compute_single_scattering_luminance(
+            &atmosphere,
+            &lookup_table,
+            &view_direction.normalized(),
+            sun,
+        )
+    }
+
+    #[test]
+    fn transmittance_is_unity_at_top_of_atmosphere_looking_up() {
+        let atmosphere = Atmosphere::earth(0.0);
+        let lookup_table = TransmittanceLookupTable::compute(&atmosphere);
+        let transmittance =
+            lookup_table.transmittance(&atmosphere, atmosphere.atmosphere_height, 1.0);
+        assert_abs_diff_eq!(transmittance.min_component(), 1.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn transmittance_is_zero_for_rays_hitting_ground() {
+        let atmosphere = Atmosphere::earth(0.0);
+        let lookup_table = TransmittanceLookupTable::compute(&atmosphere);
+        for altitude in [0.0, 1e3] {
+            let transmittance = lookup_table.transmittance(&atmosphere, altitude, -0.5);
+            assert_abs_diff_eq!(transmittance.max_component(), 0.0);
+        }
+    }
+
+    #[test]
+    fn transmittance_is_lowest_for_blue_light() {
+        let atmosphere = Atmosphere::earth(0.0);
+        let lookup_table = TransmittanceLookupTable::compute(&atmosphere);
+        let transmittance = lookup_table.transmittance(&atmosphere, 0.0, 1.0);
+        assert!(transmittance.z() < transmittance.y());
+        assert!(transmittance.y() < transmittance.x());
+    }
+
+    #[test]
+    fn daytime_zenith_sky_is_blue() {
+        let luminance = sky_luminance(&sun_at_elevation(60.0), Vector3::unit_y());
+        assert!(luminance.z() > luminance.y());
+        assert!(luminance.y() > luminance.x());
+    }
+
+    #[test]
+    fn sky_is_much_darker_when_sun_is_below_horizon() {
+        let day_luminance = sky_luminance(&sun_at_elevation(45.0), Vector3::unit_y());
+        let night_luminance = sky_luminance(&sun_at_elevation(-30.0), Vector3::unit_y());
+        assert!(night_luminance.max_component() < 1e-3 * day_luminance.max_component());
+    }
+
+    #[test]
+    fn horizon_towards_setting_sun_is_redder_than_zenith() {
+        let sun = sun_at_elevation(1.0);
+        let horizon_luminance = sky_luminance(&sun, Vector3::new(1.0, 0.02, 0.0));
+        let zenith_luminance = sky_luminance(&sun, Vector3::unit_y());
+        assert!(
+            horizon_luminance.x() / horizon_luminance.z()
+                > zenith_luminance.x() / zenith_luminance.z()
+        );
+    }
+
+    #[test]
+    fn texel_directions_point_along_face_axes_at_face_centers() {
+        let expected = [
+            Vector3::unit_x(),
+            -Vector3::unit_x(),
+            Vector3::unit_y(),
+            -Vector3::unit_y(),
+            Vector3::unit_z(),
+            -Vector3::unit_z(),
+        ];
+        for (face_idx, expected) in expected.iter().enumerate() {
+            // With an odd resolution, the center texel lies at the face center
+            let direction = texel_direction(face_idx, 1, 1, 3);
+            assert_abs_diff_eq!(direction, *expected);
+        }
+    }
+
+    #[test]
+    fn horizontal_sky_illuminance_is_positive_and_below_sun_illuminance() {
+        let sun = sun_at_elevation(45.0);
+        let cubemap = SkyCubemap::generate(&Atmosphere::earth(0.0), &sun, 8);
+        let illuminance = cubemap.horizontal_illuminance().aligned();
+        assert!(illuminance.min_component() > 0.0);
+        assert!(illuminance.max_component() < sun.perpendicular_illuminance.x());
+    }
+
+    #[test]
+    fn normalized_cubemap_bytes_have_expected_size() {
+        let cubemap = SkyCubemap::generate(&Atmosphere::earth(0.0), &sun_at_elevation(30.0), 4);
+        assert_eq!(cubemap.luminance().len(), 6 * 4 * 4);
+        assert_eq!(
+            cubemap.to_normalized_rgba16_float_bytes().len(),
+            6 * 4 * 4 * 8
+        );
+    }
+
+    #[test]
+    fn slightly_moved_sun_does_not_differ_noticeably() {
+        let sun = sun_at_elevation(30.0);
+        assert!(!sun.differs_noticeably_from(&sun_at_elevation(30.01)));
+        assert!(sun.differs_noticeably_from(&sun_at_elevation(31.0)));
+    }
+
+    #[test]
+    fn sun_with_changed_illuminance_differs_noticeably() {
+        let sun = sun_at_elevation(30.0);
+        let mut brighter_sun = sun;
+        brighter_sun.perpendicular_illuminance = Vector3C::same(1.1e5);
+        assert!(sun.differs_noticeably_from(&brighter_sun));
+    }
+}
@@ -1,27 +1,70 @@
 //! GPU resources for skyboxes.
 
-use crate::skybox::Skybox;
+use crate::skybox::{
+    Skybox,
+    atmosphere::{Atmosphere, SkyCubemap, Sun},
+};
 use anyhow::{Result, anyhow};
 use bytemuck::{Pod, Zeroable};
 use impact_gpu::{
     assert_uniform_valid,
     device::GraphicsDevice,
     resource_group::GPUResourceGroup,
+    texture::{
+        DepthOrArrayLayers, Sampler, SamplerConfig, TexelDescription, Texture, TextureConfig,
+    },
     uniform::{self, SingleUniformGPUBuffer, UniformBufferable},
     wgpu,
 };
+use impact_light::Illumninance;
 use impact_math::hash::ConstStringHash64;
-use impact_texture::gpu_resource::{SamplerMap, TextureMap};
-use std::borrow::Cow;
+use impact_texture::{
+    TextureID,
+    gpu_resource::{SamplerMap, TextureMap},
+};
+use std::{
+    borrow::Cow,
+    num::NonZeroU32,
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
 
 /// GPU resources for a skybox.
 #[derive(Debug)]
 pub struct SkyboxGPUResource {
     skybox: Skybox,
+    atmospheric_sky: Option<AtmosphericSkyState>,
     gpu_resource_group: GPUResourceGroup,
 }
 
+/// The generated cubemap texture for an atmospheric skybox along with the
+/// state it was generated for.
+#[derive(Debug)]
+struct AtmosphericSkyState {
+    cubemap_texture: Texture,
+    sun: Option<Sun>,
+    ambient_illuminance: Option<Illumninance>,
+    pending_generation: Option<PendingSkyGeneration>,
+    last_generation_start: Option<Instant>,
+}
+
+/// A sky cubemap being generated on a background thread for the given sun.
+#[derive(Debug)]
+struct PendingSkyGeneration {
+    sun: Sun,
+    handle: JoinHandle<SkyCubemap>,
+}
+
 impl SkyboxGPUResource {
+    /// The number of texels along each edge of a face of the cubemap generated
+    /// for atmospheric skyboxes. The row size in bytes must be a multiple of
+    /// 256.
+    pub const ATMOSPHERIC_SKY_FACE_RESOLUTION: u32 = 64;
+
+    /// The minimum time between starting consecutive regenerations of the
+    /// cubemap for an atmospheric skybox.
+    pub const MIN_ATMOSPHERIC_SKY_REGENERATION_INTERVAL: Duration = Duration::from_millis(250);
+
     /// Returns the binding location of the uniform of skybox properties.
     pub const fn properties_uniform_binding() -> u32 {
         0
@@ -35,7 +78,9 @@ impl SkyboxGPUResource {
         2
     }
 
-    /// Creates a new GPU resource manager for the given skybox.
+    /// Creates a new GPU resource manager for the given skybox. For an
+    /// atmospheric skybox, the initial sky cubemap is generated immediately
+    /// using the given sun.
     ///
     /// # Errors
     /// Returns an error if the skybox cubemap texture or sampler is missing.
@@ -44,19 +89,118 @@ impl SkyboxGPUResource {
         textures: &TextureMap,
         samplers: &SamplerMap,
         skybox: Skybox,
+        sun: Option<&Sun>,
+    ) -> Result<Self> {
+        match skybox {
+            Skybox::Cubemap {
+                cubemap_texture_id,
+                max_luminance,
+            } => Self::for_cubemap_skybox(
+                graphics_device,
+                textures,
+                samplers,
+                skybox,
+                cubemap_texture_id,
+                max_luminance as f32,
+            ),
+            Skybox::Atmospheric(atmosphere) => {
+                Self::for_atmospheric_skybox(graphics_device, skybox, &atmosphere, sun)
+            }
+        }
+    }
+
+    fn for_cubemap_skybox(
+        graphics_device: &GraphicsDevice,
+        textures: &TextureMap,
+        samplers: &SamplerMap,
+        skybox: Skybox,
+        cubemap_texture_id: TextureID,
+        max_luminance: f32,
     ) -> Result<Self> {
         let sampled_texture = textures
-            .get(skybox.cubemap_texture_id)
+            .get(cubemap_texture_id)
             .ok_or_else(|| anyhow!("Missing texture for skybox"))?;
 
-        let cubemap_texture = &sampled_texture.texture;
-
         let sampler = sampled_texture
             .sampler_id
             .and_then(|sampler_id| samplers.get(sampler_id))
             .ok_or_else(|| anyhow!("Missing sampler for skybox"))?;
 
-        let properties_uniform = SkyboxProperties::new(skybox.max_luminance as f32);
+        let gpu_resource_group = Self::create_resource_group(
+            graphics_device,
+            &sampled_texture.texture,
+            sampler,
+            max_luminance,
+        );
+
+        Ok(Self {
+            skybox,
+            atmospheric_sky: None,
+            gpu_resource_group,
+        })
+    }
+
+    fn for_atmospheric_skybox(
+        graphics_device: &GraphicsDevice,
+        skybox: Skybox,
+        atmosphere: &Atmosphere,
+        sun: Option<&Sun>,
+    ) -> Result<Self> {
+        let face_resolution = NonZeroU32::new(Self::ATMOSPHERIC_SKY_FACE_RESOLUTION).unwrap();
+        let texel_description = TexelDescription::Rgba16Float;
+
+        let n_bytes =
+            6 * Self::ATMOSPHERIC_SKY_FACE_RESOLUTION.pow(2) * texel_description.n_bytes();
+
+        let cubemap_texture = Texture::create(
+            graphics_device,
+            None,
+            &vec![0; n_bytes as usize],
+            face_resolution,
+            face_resolution,
+            DepthOrArrayLayers::ArrayLayers(NonZeroU32::new(6).unwrap()),
+            texel_description,
+            true,
+            TextureConfig::default(),
+            "Atmospheric sky cubemap",
+        )?;
+
+        let sampler = Sampler::create(graphics_device, SamplerConfig::default());
+
+        let gpu_resource_group =
+            Self::create_resource_group(graphics_device, &cubemap_texture, &sampler, 0.0);
+
+        let mut resource = Self {
+            skybox,
+            atmospheric_sky: Some(AtmosphericSkyState {
+                cubemap_texture,
+                sun: None,
+                ambient_illuminance: None,
+                pending_generation: None,
+                last_generation_start: None,
+            }),
+            gpu_resource_group,
+        };
+
+        if let Some(sun) = sun {
+            let cubemap = SkyCubemap::generate(
+                atmosphere,
+                sun,
+                Self::ATMOSPHERIC_SKY_FACE_RESOLUTION as usize,
+            );
+            resource.apply_atmospheric_sky(graphics_device, sun, &cubemap)?;
+        }
+
+        Ok(resource)
+    }
+
+    fn create_resource_group(
+        graphics_device: &GraphicsDevice,
+        cubemap_texture: &Texture,
+        sampler: &Sampler,
+        max_luminance: f32,
+    ) -> GPUResourceGroup {
+        let properties_uniform = SkyboxProperties::new(max_luminance);
 
         let properties_uniform_buffer = SingleUniformGPUBuffer::for_uniform(
             graphics_device,
@@ -65,7 +209,7 @@ impl SkyboxGPUResource {
             Cow::Borrowed("Skybox properties"),
         );
 
-        let gpu_resource_group = GPUResourceGroup::new(
+        GPUResourceGroup::new(
             graphics_device,
             vec![properties_uniform_buffer],
             &[],
@@ -73,12 +217,7 @@ impl SkyboxGPUResource {
             &[sampler],
             wgpu::ShaderStages::FRAGMENT,
             "Skybox properties",
-        );
-
-        Ok(Self {
-            skybox,
-            gpu_resource_group,
-        })
+        )
     }
 
     /// Returns the skybox whose GPU resources are managed by this manager.
@@ -86,6 +225,15 @@ impl SkyboxGPUResource {
         self.skybox
     }
 
+    /// Returns the illuminance from the sky on an upward-facing horizontal
+    /// surface if the skybox is atmospheric and lit by a sun, or [`None`]
+    /// otherwise.
+    pub fn sky_ambient_illuminance(&self) -> Option<&Illumninance> {
+        self.atmospheric_sky
+            .as_ref()
+            .and_then(|sky| sky.ambient_illuminance.as_ref())
+    }
+
     /// Returns the bind group layout for the GPU resource group comprised of
     /// the properties uniform and the cubemap texture and sampler for the
     /// skybox.
@@ -100,16 +248,132 @@ impl SkyboxGPUResource {
     }
 
     /// Synchronizes the skybox GPU resources with the given skybox.
+    ///
+    /// For an atmospheric skybox, the sky cubemap is regenerated on a
+    /// background thread when the given sun differs noticeably from the sun it
+    /// was last generated for. The current cubemap is kept until the new one
+    /// is ready, and is replaced in a later call once generation completes.
+    /// At most one generation is in flight at a time, and generations are
+    /// started no more often than
+    /// [`Self::MIN_ATMOSPHERIC_SKY_REGENERATION_INTERVAL`].
+    ///
+    /// # Errors
+    /// Returns an error if the skybox cubemap texture or sampler is missing or
+    /// the sky cubemap generation thread could not be spawned or panicked.
     pub fn sync_with_skybox(
         &mut self,
         graphics_device: &GraphicsDevice,
         textures: &TextureMap,
         samplers: &SamplerMap,
         skybox: Skybox,
+        sun: Option<&Sun>,
     ) -> Result<()> {
         if skybox != self.skybox {
-            *self = Self::for_skybox(graphics_device, textures, samplers, skybox)?;
+            *self = Self::for_skybox(graphics_device, textures, samplers, skybox, sun)?;
+            return Ok(());
         }
+
+        let Skybox::Atmospheric(atmosphere) = skybox else {
+            return Ok(());
+        };
+        let Some(sky) = &mut self.atmospheric_sky else {
+            return Ok(());
+        };
+
+        if let Some(generation) = sky
+            .pending_generation
+            .take_if(|generation| generation.handle.is_finished())
+        {
+            let cubemap = generation
+                .handle
+                .join()
+                .map_err(|_| anyhow!("Atmospheric sky generation thread panicked"))?;
+
+            // Only apply the result if the sun has not disappeared meanwhile
+            if sun.is_some() {
+                self.apply_atmospheric_sky(graphics_device, &generation.sun, &cubemap)?;
+            }
+        }
+
+        let Some(sky) = &mut self.atmospheric_sky else {
+            return Ok(());
+        };
+
+        match (sky.sun, sun) {
+            (Some(current_sun), Some(sun)) if !sun.differs_noticeably_from(&current_sun) => {}
+            (None, None) => {}
+            (_, Some(sun)) => {
+                sky.start_generation_if_ready(atmosphere, *sun)?;
+            }
+            (Some(_), None) => {
+                sky.sun = None;
+                sky.ambient_illuminance = None;
+                sky.pending_generation = None;
+                self.set_max_luminance(graphics_device, 0.0);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn apply_atmospheric_sky(
+        &mut self,
+        graphics_device: &GraphicsDevice,
+        sun: &Sun,
+        cubemap: &SkyCubemap,
+    ) -> Result<()> {
+        let Some(sky) = &mut self.atmospheric_sky else {
+            return Ok(());
+        };
+
+        sky.cubemap_texture.write_data(
+            graphics_device,
+            &cubemap.to_normalized_rgba16_float_bytes(),
+            TexelDescription::Rgba16Float,
+        )?;
+        sky.sun = Some(*sun);
+        sky.ambient_illuminance = Some(*cubemap.horizontal_illuminance());
+
+        self.set_max_luminance(graphics_device, cubemap.max_luminance());
+
+        Ok(())
+    }
+
+    fn set_max_luminance(&self, graphics_device: &GraphicsDevice, max_luminance: f32) {
+        self.gpu_resource_group
+            .single_uniform_buffer(0)
+            .unwrap()
+            .update_uniform(graphics_device, &SkyboxProperties::new(max_luminance));
+    }
+}
+
+impl AtmosphericSkyState {
+    /// Starts generating the sky cubemap for the given sun on a background
+    /// thread, unless a generation is already in flight or the previous one
+    /// was started too recently.
+    fn start_generation_if_ready(&mut self, atmosphere: Atmosphere, sun: Sun) -> Result<()> {
+        if self.pending_generation.is_some() {
+            return Ok(());
+        }
+        if self.last_generation_start.is_some_and(|start| {
+            start.elapsed() < SkyboxGPUResource::MIN_ATMOSPHERIC_SKY_REGENERATION_INTERVAL
+        }) {
+            return Ok(());
+        }
+
+        let handle = thread::Builder::new()
+            .name("Atmospheric sky generation".to_string())
+            .spawn(move || {
+                SkyCubemap::generate(
+                    &atmosphere,
+                    &sun,
+                    SkyboxGPUResource::ATMOSPHERIC_SKY_FACE_RESOLUTION as usize,
+                )
+            })?;
+
+        self.pending_generation = Some(PendingSkyGeneration { sun, handle });
+        self.last_generation_start = Some(Instant::now());
+
         Ok(())
     }
 }
@@ -0,0 +1,615 @@
+//! Texture atlases holding many small images in a single texture.
+
+use anyhow::{Result, anyhow, bail};
+use impact_alloc::{AVec, Global};
+use impact_io::image::{Image, ImageMetadata};
+use impact_math::{hash64, stringhash64_newtype, vector::Vector2};
+
+stringhash64_newtype!(
+    /// Identifier for a region in a [`TextureAtlas`].
+    [pub] TextureAtlasRegionID
+);
+
+/// The layout of a texture atlas, specifying the rectangle of the atlas
+/// texture holding the image for each region.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextureAtlas {
+    width: u32,
+    height: u32,
+    regions: Vec<(TextureAtlasRegionID, AtlasRect)>,
+}
+
+/// A rectangle of texels in a texture atlas, with the origin in the top left
+/// corner of the atlas.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AtlasRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Configuration for packing images into a texture atlas.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TextureAtlasPackingConfig {
+    /// The number of gutter texels around each image. Gutters are filled by
+    /// extending the edge texels of the image outward, so that filtering near
+    /// the edge of a region does not pick up texels from neighboring images.
+    pub padding: u32,
+    /// The number of mip levels below the base level that should be free of
+    /// bleeding between images. Every padded image is placed at and padded to
+    /// a multiple of `2^mip_safe_levels` texels, so that no texel at these
+    /// levels is averaged from texels of different images.
+    pub mip_safe_levels: u32,
+    /// The maximum width and height of the atlas texture.
+    pub max_size: u32,
+}
+
+/// The placement of a set of rectangles packed into a larger rectangle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RectanglePacking {
+    /// The width of the rectangle holding the packed rectangles.
+    pub width: u32,
+    /// The height of the rectangle holding the packed rectangles.
+    pub height: u32,
+    /// The position of the top left corner of each packed rectangle, in the
+    /// order the rectangles were given.
+    pub positions: Vec<[u32; 2]>,
+}
+
+/// The top edge of the packed area over a horizontal span, used by the skyline
+/// packing algorithm.
+#[derive(Clone, Copy, Debug)]
+struct SkylineSegment {
+    x: u32,
+    y: u32,
+    width: u32,
+}
+
+impl TextureAtlasRegionID {
+    /// Creates a region ID hashed from the given name.
+    pub fn from_name(name: &str) -> Self {
+        Self(hash64!(name))
+    }
+
+    /// Returns the ID of the region holding the frame with the given index in
+    /// a sprite sheet atlas (see [`TextureAtlas::for_sprite_sheet`]).
+    pub fn sprite_sheet_frame(frame_idx: usize) -> Self {
+        Self::from_name(&format!("frame{frame_idx}"))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for TextureAtlasRegionID {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TextureAtlasRegionID {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Ok(TextureAtlasRegionID::from_name(&s))
+    }
+}
+
+impl TextureAtlas {
+    /// Creates the layout of a sprite sheet with the given dimensions, divided
+    /// into a grid with the given number of equally sized frames along each
+    /// axis. The frames are numbered row by row from the top left, and the
+    /// region for each frame is identified by
+    /// [`TextureAtlasRegionID::sprite_sheet_frame`].
+    ///
+    /// # Errors
+    /// Returns an error if the number of columns or rows is zero or does not
+    /// evenly divide the corresponding dimension.
+    pub fn for_sprite_sheet(width: u32, height: u32, columns: u32, rows: u32) -> Result<Self> {
+        if columns == 0 || rows == 0 {
+            bail!("Got zero columns or rows for sprite sheet");
+        }
+        if !width.is_multiple_of(columns) || !height.is_multiple_of(rows) {
+            bail!(
+                "Sprite sheet dimensions {width}x{height} are not divisible \
+                 into {columns}x{rows} frames"
+            );
+        }
+
+        let frame_width = width / columns;
+        let frame_height = height / rows;
+
+        let regions = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (row, column)))
+            .enumerate()
+            .map(|(frame_idx, (row, column))| {
+                (
+                    TextureAtlasRegionID::sprite_sheet_frame(frame_idx),
+                    AtlasRect {
+                        x: column * frame_width,
+                        y: row * frame_height,
+                        width: frame_width,
+                        height: frame_height,
+                    },
+                )
+            })
+            .collect();
+
+        Ok(Self {
+            width,
+            height,
+            regions,
+        })
+    }
+
+    /// Returns the width of the atlas texture.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of the atlas texture.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns an iterator over the IDs and rectangles of all regions.
+    pub fn regions(&self) -> impl Iterator<Item = (TextureAtlasRegionID, &AtlasRect)> {
+        self.regions.iter().map(|(id, rect)| (*id, rect))
+    }
+
+    /// Returns the rectangle of the region with the given ID, or [`None`] if
+    /// the atlas has no such region.
+    pub fn region(&self, region_id: TextureAtlasRegionID) -> Option<&AtlasRect> {
+        self.regions
+            .iter()
+            .find_map(|(id, rect)| (*id == region_id).then_some(rect))
+    }
+
+    /// Returns the offset and scale mapping texture coordinates in the unit
+    /// square to the region with the given ID, or [`None`] if the atlas has no
+    /// such region.
+    pub fn region_uv_offset_and_scale(
+        &self,
+        region_id: TextureAtlasRegionID,
+    ) -> Option<(Vector2, Vector2)> {
+        let rect = self.region(region_id)?;
+        let width = self.width as f32;
+        let height = self.height as f32;
+        Some((
+            Vector2::new(rect.x as f32 / width, rect.y as f32 / height),
+            Vector2::new(rect.width as f32 / width, rect.height as f32 / height),
+        ))
+    }
+}
+
+impl Default for TextureAtlasPackingConfig {
+    fn default() -> Self {
+        Self {
+            padding: 2,
+            mip_safe_levels: 2,
+            max_size: 4096,
+        }
+    }
+}
+
+/// Packs the given images into a single atlas image, where each image
+/// occupies the region with the given ID. The atlas dimensions are the
+/// smallest powers of two that fit all images.
+///
+/// # Errors
+/// Returns an error if:
+/// - There are no images.
+/// - Two images have the same region ID.
+/// - The images have different pixel formats or zero width or height.
+/// - The images do not fit within the maximum atlas size.
+pub fn pack_images_into_atlas(
+    images: &[(TextureAtlasRegionID, Image)],
+    config: &TextureAtlasPackingConfig,
+) -> Result<(Image, TextureAtlas)> {
+    let (_, first_image) = images
+        .first()
+        .ok_or_else(|| anyhow!("Got no images to pack into texture atlas"))?;
+
+    let pixel_format = first_image.meta.pixel_format;
+    let bytes_per_pixel = pixel_format.bytes_per_pixel();
+
+    for (idx, (region_id, image)) in images.iter().enumerate() {
+        if image.meta.pixel_format != pixel_format {
+            bail!(
+                "Inconsistent pixel formats for texture atlas images: {:?} != {pixel_format:?}",
+                image.meta.pixel_format
+            );
+        }
+        if image.meta.width == 0 || image.meta.height == 0 {
+            bail!("Got zero width or height for texture atlas image {region_id}");
+        }
+        if images[..idx].iter().any(|(id, _)| id == region_id) {
+            bail!("Got multiple texture atlas images for region {region_id}");
+        }
+    }
+
+    if config.mip_safe_levels >= 16 {
+        bail!(
+            "Too many mip-safe levels for texture atlas: {}",
+            config.mip_safe_levels
+        );
+    }
+    let alignment = 1 << config.mip_safe_levels;
+
+    let cell_sizes: Vec<_> = images
+        .iter()
+        .map(|(_, image)| {
+            [
+                (image.meta.width + 2 * config.padding).next_multiple_of(alignment),
+                (image.meta.height + 2 * config.padding).next_multiple_of(alignment),
+            ]
+        })
+        .collect();
+
+    let packing = pack_rectangles(&cell_sizes, config.max_size)?;
+
+    let atlas_width = packing.width as usize;
+    let mut data = AVec::new_in(Global);
+    data.resize(
+        atlas_width * packing.height as usize * bytes_per_pixel,
+        0_u8,
+    );
+
+    let mut regions = Vec::with_capacity(images.len());
+
+    for (((region_id, image), &[cell_width, cell_height]), &[cell_x, cell_y]) in
+        images.iter().zip(&cell_sizes).zip(&packing.positions)
+    {
+        let width = image.meta.width;
+        let height = image.meta.height;
+
+        // Fill the whole cell, extending the edge texels of the image into
+        // the gutter and alignment space
+        for cell_row in 0..cell_height {
+            let source_row = cell_row.saturating_sub(config.padding).min(height - 1) as usize;
+            let atlas_row = (cell_y + cell_row) as usize;
+
+            for cell_column in 0..cell_width {
+                let source_column =
+                    cell_column.saturating_sub(config.padding).min(width - 1) as usize;
+                let atlas_column = (cell_x + cell_column) as usize;
+
+                let source_start = (source_row * width as usize + source_column) * bytes_per_pixel;
+                let atlas_start = (atlas_row * atlas_width + atlas_column) * bytes_per_pixel;
+
+                data[atlas_start..atlas_start + bytes_per_pixel]
+                    .copy_from_slice(&image.data[source_start..source_start + bytes_per_pixel]);
+            }
+        }
+
+        regions.push((
+            *region_id,
+            AtlasRect {
+                x: cell_x + config.padding,
+                y: cell_y + config.padding,
+                width,
+                height,
+            },
+        ));
+    }
+
+    let atlas_image = Image {
+        meta: ImageMetadata {
+            width: packing.width,
+            height: packing.height,
+            pixel_format,
+        },
+        data,
+    };
+
+    let atlas = TextureAtlas {
+        width: packing.width,
+        height: packing.height,
+        regions,
+    };
+
+    Ok((atlas_image, atlas))
+}
+
+/// Packs rectangles with the given sizes into the smallest rectangle with
+/// power-of-two dimensions no larger than the given maximum that can hold
+/// them, using the skyline bottom-left heuristic. Among rectangles of equal
+/// area, the squarest is preferred.
+///
+/// # Errors
+/// Returns an error if any rectangle has zero width or height, or if the
+/// rectangles do not fit within the maximum size.
+pub fn pack_rectangles(sizes: &[[u32; 2]], max_size: u32) -> Result<RectanglePacking> {
+    if sizes
+        .iter()
+        .any(|&[width, height]| width == 0 || height == 0)
+    {
+        bail!("Got rectangle with zero width or height for packing");
+    }
+
+    let min_width = sizes.iter().map(|&[width, _]| width).max().unwrap_or(1);
+    let min_height = sizes.iter().map(|&[_, height]| height).max().unwrap_or(1);
+
+    if min_width > max_size || min_height > max_size {
+        bail!("Rectangle of size {min_width}x{min_height} exceeds maximum packing size {max_size}");
+    }
+
+    let total_area: u64 = sizes
+        .iter()
+        .map(|&[width, height]| u64::from(width) * u64::from(height))
+        .sum();
+
+    let candidate_sides = |min_side: u32| {
+        let mut side = min_side.next_power_of_two();
+        std::iter::from_fn(move || {
+            let current = side;
+            side = side.saturating_mul(2);
+            (current <= max_size).then_some(current)
+        })
+    };
+
+    let mut candidate_dimensions: Vec<[u32; 2]> = candidate_sides(min_width)
+        .flat_map(|width| candidate_sides(min_height).map(move |height| [width, height]))
+        .filter(|&[width, height]| u64::from(width) * u64::from(height) >= total_area)
+        .collect();
+
+    candidate_dimensions.sort_by_key(|&[width, height]| {
+        (
+            u64::from(width) * u64::from(height),
+            width.abs_diff(height),
+            height,
+        )
+    });
+
+    // Placing the tallest rectangles first keeps the skyline flat
+    let mut order: Vec<_> = (0..sizes.len()).collect();
+    order.sort_by_key(|&idx| {
+        let [width, height] = sizes[idx];
+        (std::cmp::Reverse(height), std::cmp::Reverse(width))
+    });
+
+    for [width, height] in candidate_dimensions {
+        if let Some(positions) = pack_rectangles_with_skyline(sizes, &order, width, height) {
+            return Ok(RectanglePacking {
+                width,
+                height,
+                positions,
+            });
+        }
+    }
+
+    bail!(
+        "Could not pack {} rectangles within maximum size {max_size}",
+        sizes.len()
+    )
+}
+
+fn pack_rectangles_with_skyline(
+    sizes: &[[u32; 2]],
+    order: &[usize],
+    width: u32,
+    height: u32,
+) -> Option<Vec<[u32; 2]>> {
+    let mut skyline = vec![SkylineSegment { x: 0, y: 0, width }];
+    let mut positions = vec![[0; 2]; sizes.len()];
+
+    for &idx in order {
+        let [rect_width, rect_height] = sizes[idx];
+
+        let mut best: Option<(u32, u32, usize)> = None;
+
+        for (segment_idx, segment) in skyline.iter().enumerate() {
+            let x = segment.x;
+            if x + rect_width > width {
+                break;
+            }
+
+            let y = skyline[segment_idx..]
+                .iter()
+                .take_while(|other| other.x < x + rect_width)
+                .map(|other| other.y)
+                .max()
+                .unwrap();
+
+            if y + rect_height > height {
+                continue;
+            }
+
+            let top = y + rect_height;
+            if best.is_none_or(|(best_top, best_x, _)| (top, x) < (best_top, best_x)) {
+                best = Some((top, x, segment_idx));
+            }
+        }
+
+        let (top, x, segment_idx) = best?;
+        positions[idx] = [x, top - rect_height];
+
+        place_on_skyline(&mut skyline, segment_idx, x, top, rect_width);
+    }
+
+    Some(positions)
+}
+
+fn place_on_skyline(
+    skyline: &mut Vec<SkylineSegment>,
+    segment_idx: usize,
+    x: u32,
+    top: u32,
+    width: u32,
+) {
+    let end = x + width;
+
+    skyline.insert(segment_idx, SkylineSegment { x, y: top, width });
+
+    // Remove or shorten the segments now covered by the new segment
+    let next_idx = segment_idx + 1;
+    while next_idx < skyline.len() && skyline[next_idx].x < end {
+        let segment = &mut skyline[next_idx];
+        let segment_end = segment.x + segment.width;
+        if segment_end <= end {
+            skyline.remove(next_idx);
+        } else {
+            segment.width = segment_end - end;
+            segment.x = end;
+            break;
+        }
+    }
+
+    // Merge adjacent segments at the same height
+    let mut idx = 1;
+    while idx < skyline.len() {
+        if skyline[idx - 1].y == skyline[idx].y {
+            skyline[idx - 1].width += skyline[idx].width;
+            skyline.remove(idx);
+        } else {
+            idx += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use impact_io::image::PixelFormat;
+
+    fn luma_image(width: u32, height: u32, value: u8) -> Image {
+        let mut data = AVec::new_in(Global);
+        data.resize((width * height) as usize, value);
+        Image {
+            meta: ImageMetadata {
+                width,
+                height,
+                pixel_format: PixelFormat::Luma8,
+            },
+            data,
+        }
+    }
+
+    fn rects_overlap(a: [u32; 4], b: [u32; 4]) -> bool {
+        a[0] < b[0] + b[2] && b[0] < a[0] + a[2] && a[1] < b[1] + b[3] && b[1] < a[1] + a[3]
+    }
+
+    #[test]
+    fn packed_rectangles_do_not_overlap_and_stay_in_bounds() {
+        let sizes = [[16, 8], [8, 8], [32, 4], [4, 20], [12, 12], [8, 8], [1, 1]];
+        let packing = pack_rectangles(&sizes, 256).unwrap();
+
+        assert!(packing.width.is_power_of_two());
+        assert!(packing.height.is_power_of_two());
+
+        let rects: Vec<_> = sizes
+            .iter()
+            .zip(&packing.positions)
+            .map(|(&[width, height], &[x, y])| [x, y, width, height])
+            .collect();
+
+        for (idx, rect) in rects.iter().enumerate() {
+            assert!(rect[0] + rect[2] <= packing.width);
+            assert!(rect[1] + rect[3] <= packing.height);
+            for other in &rects[..idx] {
+                assert!(!rects_overlap(*rect, *other));
+            }
+        }
+    }
+
+    #[test]
+    fn identical_squares_pack_tightly() {
+        let packing = pack_rectangles(&[[8, 8]; 4], 256).unwrap();
+        assert_eq!((packing.width, packing.height), (16, 16));
+    }
+
+    #[test]
+    fn packing_beyond_maximum_size_fails() {
+        assert!(pack_rectangles(&[[8, 8]; 5], 16).is_err());
+        assert!(pack_rectangles(&[[32, 8]], 16).is_err());
+    }
+
+    #[test]
+    fn atlas_regions_are_aligned_and_gutters_extend_edges() {
+        let images = [
+            (TextureAtlasRegionID::from_name("a"), luma_image(3, 5, 10)),
+            (TextureAtlasRegionID::from_name("b"), luma_image(6, 2, 20)),
+        ];
+        let config = TextureAtlasPackingConfig {
+            padding: 1,
+            mip_safe_levels: 2,
+            max_size: 64,
+        };
+        let (image, atlas) = pack_images_into_atlas(&images, &config).unwrap();
+
+        assert_eq!(image.meta.width, atlas.width());
+        assert_eq!(image.meta.height, atlas.height());
+
+        for ((region_id, source), (id, rect)) in images.iter().zip(atlas.regions()) {
+            assert_eq!(*region_id, id);
+            assert_eq!((rect.width, rect.height), source.dimensions());
+            assert_eq!((rect.x - config.padding) % 4, 0);
+            assert_eq!((rect.y - config.padding) % 4, 0);
+
+            let value = source.data[0];
+            let texel = |x: u32, y: u32| image.data[(y * image.meta.width + x) as usize];
+
+            for y in rect.y..rect.y + rect.height {
+                for x in rect.x..rect.x + rect.width {
+                    assert_eq!(texel(x, y), value);
+                }
+            }
+            // Gutter texels around the corners of the region
+            assert_eq!(texel(rect.x - 1, rect.y - 1), value);
+            assert_eq!(texel(rect.x + rect.width, rect.y + rect.height), value);
+        }
+    }
+
+    #[test]
+    fn packing_images_with_duplicate_regions_fails() {
+        let images = [
+            (TextureAtlasRegionID::from_name("a"), luma_image(2, 2, 0)),
+            (TextureAtlasRegionID::from_name("a"), luma_image(2, 2, 0)),
+        ];
+        assert!(pack_images_into_atlas(&images, &TextureAtlasPackingConfig::default()).is_err());
+    }
+
+    #[test]
+    fn sprite_sheet_frames_are_numbered_row_by_row() {
+        let atlas = TextureAtlas::for_sprite_sheet(64, 32, 4, 2).unwrap();
+
+        assert_eq!(
+            atlas.region(TextureAtlasRegionID::sprite_sheet_frame(5)),
+            Some(&AtlasRect {
+                x: 16,
+                y: 16,
+                width: 16,
+                height: 16
+            })
+        );
+        assert!(
+            atlas
+                .region(TextureAtlasRegionID::sprite_sheet_frame(8))
+                .is_none()
+        );
+
+        let (offset, scale) = atlas
+            .region_uv_offset_and_scale(TextureAtlasRegionID::sprite_sheet_frame(6))
+            .unwrap();
+        assert_eq!(offset, Vector2::new(0.5, 0.5));
+        assert_eq!(scale, Vector2::new(0.25, 0.5));
+    }
+
+    #[test]
+    fn sprite_sheet_with_indivisible_dimensions_is_rejected() {
+        assert!(TextureAtlas::for_sprite_sheet(10, 10, 3, 1).is_err());
+        assert!(TextureAtlas::for_sprite_sheet(10, 10, 0, 1).is_err());
+    }
+}
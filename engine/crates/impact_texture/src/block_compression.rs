@@ -0,0 +1,1157 @@
+//! Encoding and decoding of block compressed (`BCn`) texel data.
+//!
+//! The encoders favor simplicity and speed over the best attainable quality.
+//! Endpoints are found along the principal axis of the texel values in each
+//! block and refined once with a least squares fit. BC7 is always encoded with
+//! mode 6, while the decoder handles all BC7 modes so that data produced by
+//! external tools can be decoded as well.
+
+use impact_gpu::texture::BlockCompression;
+use impact_io::image::{ImageMetadata, PixelFormat};
+
+/// The RGBA values of the 16 texels in a 4x4 block, in row-major order.
+type Block = [[u8; 4]; 16];
+
+const BLOCK_SIZE: u32 = BlockCompression::BLOCK_SIZE;
+
+/// Returns the pixel format of the data produced when decompressing data with
+/// the given compression format.
+pub fn decompressed_pixel_format(compression: BlockCompression) -> PixelFormat {
+    match compression {
+        BlockCompression::Bc4 => PixelFormat::Luma8,
+        BlockCompression::Bc1
+        | BlockCompression::Bc3
+        | BlockCompression::Bc5
+        | BlockCompression::Bc7 => PixelFormat::Rgba8,
+    }
+}
+
+/// Compresses the given image data with the given compression format. The
+/// blocks are stored in row-major order, with partial blocks at the right and
+/// bottom edges padded by repeating the edge texels.
+///
+/// Single-channel formats use the red (or luma) channel and two-channel
+/// formats use the red and green channels.
+///
+/// # Panics
+/// If the size of the data is inconsistent with the metadata.
+pub fn compress_image(compression: BlockCompression, meta: &ImageMetadata, data: &[u8]) -> Vec<u8> {
+    assert_eq!(
+        data.len(),
+        meta.width as usize * meta.height as usize * meta.pixel_format.bytes_per_pixel()
+    );
+
+    let blocks_x = meta.width.div_ceil(BLOCK_SIZE);
+    let blocks_y = meta.height.div_ceil(BLOCK_SIZE);
+
+    let mut compressed =
+        Vec::with_capacity(compression.n_bytes_for_image(meta.width, meta.height) as usize);
+
+    for block_y in 0..blocks_y {
+        for block_x in 0..blocks_x {
+            let block = extract_block(meta, data, block_x, block_y);
+            match compression {
+                BlockCompression::Bc1 => compressed.extend_from_slice(&encode_bc1_block(&block)),
+                BlockCompression::Bc3 => compressed.extend_from_slice(&encode_bc3_block(&block)),
+                BlockCompression::Bc4 => compressed.extend_from_slice(&encode_bc4_block(&block)),
+                BlockCompression::Bc5 => compressed.extend_from_slice(&encode_bc5_block(&block)),
+                BlockCompression::Bc7 => compressed.extend_from_slice(&encode_bc7_block(&block)),
+            }
+        }
+    }
+
+    compressed
+}
+
+/// Decompresses the given block compressed data for an image with the given
+/// dimensions. The returned data has the pixel format given by
+/// [`decompressed_pixel_format`].
+///
+/// # Panics
+/// If the size of the data is inconsistent with the dimensions.
+pub fn decompress_image(
+    compression: BlockCompression,
+    width: u32,
+    height: u32,
+    data: &[u8],
+) -> Vec<u8> {
+    assert_eq!(
+        data.len(),
+        compression.n_bytes_for_image(width, height) as usize
+    );
+
+    let n_channels = decompressed_pixel_format(compression).bytes_per_pixel();
+    let blocks_x = width.div_ceil(BLOCK_SIZE);
+    let block_n_bytes = compression.block_n_bytes() as usize;
+
+    let mut decompressed = vec![0; width as usize * height as usize * n_channels];
+
+    for (block_idx, block_bytes) in data.chunks_exact(block_n_bytes).enumerate() {
+        let block = match compression {
+            BlockCompression::Bc1 => decode_bc1_block(block_bytes),
+            BlockCompression::Bc3 => decode_bc3_block(block_bytes),
+            BlockCompression::Bc4 => decode_bc4_block(block_bytes),
+            BlockCompression::Bc5 => decode_bc5_block(block_bytes),
+            BlockCompression::Bc7 => decode_bc7_block(block_bytes),
+        };
+
+        let block_x = block_idx as u32 % blocks_x;
+        let block_y = block_idx as u32 / blocks_x;
+
+        for (texel_idx, texel) in block.iter().enumerate() {
+            let x = block_x * BLOCK_SIZE + texel_idx as u32 % BLOCK_SIZE;
+            let y = block_y * BLOCK_SIZE + texel_idx as u32 / BLOCK_SIZE;
+            if x >= width || y >= height {
+                continue;
+            }
+            let start = (y as usize * width as usize + x as usize) * n_channels;
+            decompressed[start..start + n_channels].copy_from_slice(&texel[..n_channels]);
+        }
+    }
+
+    decompressed
+}
+
+fn extract_block(meta: &ImageMetadata, data: &[u8], block_x: u32, block_y: u32) -> Block {
+    let mut block = [[0; 4]; 16];
+    for (texel_idx, texel) in block.iter_mut().enumerate() {
+        // Clamp to the edge for partial blocks
+        let x = (block_x * BLOCK_SIZE + texel_idx as u32 % BLOCK_SIZE).min(meta.width - 1);
+        let y = (block_y * BLOCK_SIZE + texel_idx as u32 / BLOCK_SIZE).min(meta.height - 1);
+        let idx = y as usize * meta.width as usize + x as usize;
+        *texel = match meta.pixel_format {
+            PixelFormat::Rgba8 => data[4 * idx..4 * idx + 4].try_into().unwrap(),
+            PixelFormat::Luma8 => [data[idx], data[idx], data[idx], 255],
+        };
+    }
+    block
+}
+
+// ---------------------------------------------------------------------------
+// Shared endpoint fitting
+// ---------------------------------------------------------------------------
+
+/// Finds endpoints enclosing the given points along their principal axis.
+fn principal_axis_endpoints<const N: usize>(points: &[[f32; N]]) -> ([f32; N], [f32; N]) {
+    let n_points = points.len() as f32;
+
+    let mut mean = [0.0; N];
+    for point in points {
+        for c in 0..N {
+            mean[c] += point[c] / n_points;
+        }
+    }
+
+    let mut covariance = [[0.0; N]; N];
+    for point in points {
+        for i in 0..N {
+            for j in 0..N {
+                covariance[i][j] += (point[i] - mean[i]) * (point[j] - mean[j]);
+            }
+        }
+    }
+
+    // Power iteration for the dominant eigenvector, starting from the
+    // covariance row of the channel with the largest variance (starting from a
+    // fixed vector could make us miss axes orthogonal to it)
+    let max_variance_channel = (0..N)
+        .max_by(|&i, &j| covariance[i][i].total_cmp(&covariance[j][j]))
+        .unwrap();
+    if covariance[max_variance_channel][max_variance_channel] < 1e-6 {
+        return (mean, mean);
+    }
+    let mut axis = covariance[max_variance_channel];
+    for _ in 0..8 {
+        let mut next = [0.0; N];
+        for i in 0..N {
+            for j in 0..N {
+                next[i] += covariance[i][j] * axis[j];
+            }
+        }
+        let norm = next.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm < 1e-6 {
+            return (mean, mean);
+        }
+        for c in 0..N {
+            axis[c] = next[c] / norm;
+        }
+    }
+
+    let (mut min_t, mut max_t) = (f32::INFINITY, f32::NEG_INFINITY);
+    for point in points {
+        let t: f32 = (0..N).map(|c| (point[c] - mean[c]) * axis[c]).sum();
+        min_t = min_t.min(t);
+        max_t = max_t.max(t);
+    }
+
+    let mut low = [0.0; N];
+    let mut high = [0.0; N];
+    for c in 0..N {
+        low[c] = (mean[c] + min_t * axis[c]).clamp(0.0, 255.0);
+        high[c] = (mean[c] + max_t * axis[c]).clamp(0.0, 255.0);
+    }
+    (low, high)
+}
+
+/// Finds the endpoints minimizing the squared error of the given points when
+/// interpolated with the given weights (between 0 and 1) of the second
+/// endpoint, or returns [`None`] if the system is degenerate.
+fn least_squares_endpoints<const N: usize>(
+    points: &[[f32; N]],
+    weights: &[f32],
+) -> Option<([f32; N], [f32; N])> {
+    let (mut aa, mut ab, mut bb) = (0.0, 0.0, 0.0);
+    let mut ax = [0.0; N];
+    let mut bx = [0.0; N];
+
+    for (point, &t) in points.iter().zip(weights) {
+        let s = 1.0 - t;
+        aa += s * s;
+        ab += s * t;
+        bb += t * t;
+        for c in 0..N {
+            ax[c] += s * point[c];
+            bx[c] += t * point[c];
+        }
+    }
+
+    let det = aa * bb - ab * ab;
+    if det.abs() < 1e-6 {
+        return None;
+    }
+
+    let mut first = [0.0; N];
+    let mut second = [0.0; N];
+    for c in 0..N {
+        first[c] = ((bb * ax[c] - ab * bx[c]) / det).clamp(0.0, 255.0);
+        second[c] = ((aa * bx[c] - ab * ax[c]) / det).clamp(0.0, 255.0);
+    }
+    Some((first, second))
+}
+
+fn squared_distance<const N: usize>(a: &[u8], b: &[u8; N]) -> u32 {
+    (0..N)
+        .map(|c| {
+            let d = i32::from(a[c]) - i32::from(b[c]);
+            (d * d) as u32
+        })
+        .sum()
+}
+
+/// Returns the index of the palette entry closest to the given value and the
+/// squared distance to it.
+fn closest_palette_entry<const N: usize>(value: &[u8], palette: &[[u8; N]]) -> (u32, u32) {
+    let mut best = (0, u32::MAX);
+    for (idx, entry) in palette.iter().enumerate() {
+        let error = squared_distance(value, entry);
+        if error < best.1 {
+            best = (idx as u32, error);
+        }
+    }
+    best
+}
+
+// ---------------------------------------------------------------------------
+// BC1 and the color part of BC3
+// ---------------------------------------------------------------------------
+
+fn quantize_565(color: [f32; 3]) -> u16 {
+    let r = (color[0] * 31.0 / 255.0).round() as u16;
+    let g = (color[1] * 63.0 / 255.0).round() as u16;
+    let b = (color[2] * 31.0 / 255.0).round() as u16;
+    (r << 11) | (g << 5) | b
+}
+
+fn expand_565(color: u16) -> [u8; 3] {
+    let r = ((color >> 11) & 31) as u8;
+    let g = ((color >> 5) & 63) as u8;
+    let b = (color & 31) as u8;
+    [
+        (r << 3) | (r >> 2),
+        (g << 2) | (g >> 4),
+        (b << 3) | (b >> 2),
+    ]
+}
+
+fn color_palette(color_0: u16, color_1: u16, four_color: bool) -> [[u8; 4]; 4] {
+    let [r0, g0, b0] = expand_565(color_0).map(u32::from);
+    let [r1, g1, b1] = expand_565(color_1).map(u32::from);
+
+    let mix =
+        |a: u32, b: u32, wa: u32, wb: u32| ((wa * a + wb * b + (wa + wb) / 2) / (wa + wb)) as u8;
+
+    let first = [r0 as u8, g0 as u8, b0 as u8, 255];
+    let second = [r1 as u8, g1 as u8, b1 as u8, 255];
+
+    if four_color {
+        [
+            first,
+            second,
+            [mix(r0, r1, 2, 1), mix(g0, g1, 2, 1), mix(b0, b1, 2, 1), 255],
+            [mix(r0, r1, 1, 2), mix(g0, g1, 1, 2), mix(b0, b1, 1, 2), 255],
+        ]
+    } else {
+        [
+            first,
+            second,
+            [mix(r0, r1, 1, 1), mix(g0, g1, 1, 1), mix(b0, b1, 1, 1), 255],
+            [0, 0, 0, 0],
+        ]
+    }
+}
+
+fn write_color_block(color_0: u16, color_1: u16, indices: &[u32; 16]) -> [u8; 8] {
+    let mut bytes = [0; 8];
+    bytes[0..2].copy_from_slice(&color_0.to_le_bytes());
+    bytes[2..4].copy_from_slice(&color_1.to_le_bytes());
+    let packed = indices
+        .iter()
+        .enumerate()
+        .fold(0_u32, |packed, (texel_idx, &idx)| {
+            packed | (idx << (2 * texel_idx))
+        });
+    bytes[4..8].copy_from_slice(&packed.to_le_bytes());
+    bytes
+}
+
+/// Chooses the closest palette entry for each texel, returning the indices
+/// and the total squared error.
+fn assign_color_indices(
+    block: &Block,
+    palette: &[[u8; 4]; 4],
+    n_candidates: usize,
+) -> ([u32; 16], u32) {
+    let rgb_palette = palette.map(|entry| [entry[0], entry[1], entry[2]]);
+
+    let mut indices = [0; 16];
+    let mut total_error = 0;
+    for (texel, idx) in block.iter().zip(&mut indices) {
+        let (closest, error) = closest_palette_entry(texel, &rgb_palette[..n_candidates]);
+        *idx = closest;
+        total_error += error;
+    }
+    (indices, total_error)
+}
+
+/// Encodes the colors of the given block in four-color mode, which is the
+/// only mode available for the color part of BC3 blocks.
+fn encode_four_color_block(block: &Block) -> [u8; 8] {
+    let points: Vec<[f32; 3]> = block
+        .iter()
+        .map(|texel| [texel[0], texel[1], texel[2]].map(f32::from))
+        .collect();
+
+    let (low, high) = principal_axis_endpoints(&points);
+
+    let encode = |first: [f32; 3], second: [f32; 3]| {
+        let (mut color_0, mut color_1) = (quantize_565(first), quantize_565(second));
+        if color_0 < color_1 {
+            std::mem::swap(&mut color_0, &mut color_1);
+        }
+        let palette = color_palette(color_0, color_1, true);
+        // Identical endpoints would be interpreted as three-color mode in BC1
+        // blocks, so we only use the first entry in that case
+        let n_candidates = if color_0 == color_1 { 1 } else { 4 };
+        let (indices, error) = assign_color_indices(block, &palette, n_candidates);
+        (color_0, color_1, indices, error)
+    };
+
+    let mut best = encode(high, low);
+
+    // Refine the endpoints for the chosen indices
+    if best.3 > 0 {
+        const WEIGHTS: [f32; 4] = [0.0, 1.0, 1.0 / 3.0, 2.0 / 3.0];
+        let weights: Vec<f32> = best.2.iter().map(|&idx| WEIGHTS[idx as usize]).collect();
+        if let Some((first, second)) = least_squares_endpoints(&points, &weights) {
+            let refined = encode(first, second);
+            if refined.3 < best.3 {
+                best = refined;
+            }
+        }
+    }
+
+    write_color_block(best.0, best.1, &best.2)
+}
+
+/// Encodes the colors of the given block in three-color mode, where texels
+/// with alpha below one half become transparent black.
+fn encode_three_color_block(block: &Block) -> [u8; 8] {
+    let points: Vec<[f32; 3]> = block
+        .iter()
+        .filter(|texel| texel[3] >= 128)
+        .map(|texel| [texel[0], texel[1], texel[2]].map(f32::from))
+        .collect();
+
+    if points.is_empty() {
+        return write_color_block(0, 0, &[3; 16]);
+    }
+
+    let (low, high) = principal_axis_endpoints(&points);
+    let (mut color_0, mut color_1) = (quantize_565(low), quantize_565(high));
+    if color_0 > color_1 {
+        std::mem::swap(&mut color_0, &mut color_1);
+    }
+
+    let palette = color_palette(color_0, color_1, false);
+    let (mut indices, _) = assign_color_indices(block, &palette, 3);
+    for (texel, idx) in block.iter().zip(&mut indices) {
+        if texel[3] < 128 {
+            *idx = 3;
+        }
+    }
+
+    write_color_block(color_0, color_1, &indices)
+}
+
+fn decode_color_block(bytes: &[u8], always_four_color: bool) -> Block {
+    let color_0 = u16::from_le_bytes([bytes[0], bytes[1]]);
+    let color_1 = u16::from_le_bytes([bytes[2], bytes[3]]);
+    let packed = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
+
+    let palette = color_palette(color_0, color_1, always_four_color || color_0 > color_1);
+
+    let mut block = [[0; 4]; 16];
+    for (texel_idx, texel) in block.iter_mut().enumerate() {
+        *texel = palette[((packed >> (2 * texel_idx)) & 3) as usize];
+    }
+    block
+}
+
+fn encode_bc1_block(block: &Block) -> [u8; 8] {
+    if block.iter().any(|texel| texel[3] < 128) {
+        encode_three_color_block(block)
+    } else {
+        encode_four_color_block(block)
+    }
+}
+
+fn decode_bc1_block(bytes: &[u8]) -> Block {
+    decode_color_block(bytes, false)
+}
+
+// ---------------------------------------------------------------------------
+// BC4, BC5 and the alpha part of BC3
+// ---------------------------------------------------------------------------
+
+fn single_channel_palette(value_0: u8, value_1: u8) -> [u8; 8] {
+    let (a, b) = (u32::from(value_0), u32::from(value_1));
+    let mut palette = [value_0, value_1, 0, 0, 0, 0, 0, 255];
+    if value_0 > value_1 {
+        for (i, entry) in (1..).zip(&mut palette[2..]) {
+            *entry = (((7 - i) * a + i * b + 3) / 7) as u8;
+        }
+    } else {
+        for (i, entry) in (1..).zip(&mut palette[2..6]) {
+            *entry = (((5 - i) * a + i * b + 2) / 5) as u8;
+        }
+    }
+    palette
+}
+
+fn encode_single_channel_block(values: &[u8; 16]) -> [u8; 8] {
+    let encode = |value_0: u8, value_1: u8| {
+        let palette = single_channel_palette(value_0, value_1).map(|entry| [entry]);
+        let mut indices = [0; 16];
+        let mut total_error = 0;
+        for (value, idx) in values.iter().zip(&mut indices) {
+            let (closest, error) = closest_palette_entry(&[*value], &palette);
+            *idx = closest;
+            total_error += error;
+        }
+        (value_0, value_1, indices, total_error)
+    };
+
+    let min = *values.iter().min().unwrap();
+    let max = *values.iter().max().unwrap();
+
+    // Eight interpolated values spanning the full range
+    let mut best = encode(max, min);
+
+    // Six interpolated values spanning the range of non-extreme values, with
+    // explicit entries for 0 and 255
+    if best.3 > 0 {
+        let inner = values.iter().filter(|&&value| value != 0 && value != 255);
+        let inner_min = inner.clone().min().copied().unwrap_or(0);
+        let inner_max = inner.max().copied().unwrap_or(255);
+        let candidate = encode(inner_min, inner_max);
+        if candidate.3 < best.3 {
+            best = candidate;
+        }
+    }
+
+    let (value_0, value_1, indices, _) = best;
+
+    let packed = indices
+        .iter()
+        .enumerate()
+        .fold(0_u64, |packed, (texel_idx, &idx)| {
+            packed | (u64::from(idx) << (3 * texel_idx))
+        });
+
+    let mut bytes = [0; 8];
+    bytes[0] = value_0;
+    bytes[1] = value_1;
+    bytes[2..8].copy_from_slice(&packed.to_le_bytes()[..6]);
+    bytes
+}
+
+fn decode_single_channel_block(bytes: &[u8]) -> [u8; 16] {
+    let palette = single_channel_palette(bytes[0], bytes[1]);
+
+    let mut packed_bytes = [0; 8];
+    packed_bytes[..6].copy_from_slice(&bytes[2..8]);
+    let packed = u64::from_le_bytes(packed_bytes);
+
+    let mut values = [0; 16];
+    for (texel_idx, value) in values.iter_mut().enumerate() {
+        *value = palette[((packed >> (3 * texel_idx)) & 7) as usize];
+    }
+    values
+}
+
+fn channel_values(block: &Block, channel: usize) -> [u8; 16] {
+    let mut values = [0; 16];
+    for (texel, value) in block.iter().zip(&mut values) {
+        *value = texel[channel];
+    }
+    values
+}
+
+fn encode_bc3_block(block: &Block) -> [u8; 16] {
+    let mut bytes = [0; 16];
+    bytes[..8].copy_from_slice(&encode_single_channel_block(&channel_values(block, 3)));
+    bytes[8..].copy_from_slice(&encode_four_color_block(block));
+    bytes
+}
+
+fn decode_bc3_block(bytes: &[u8]) -> Block {
+    let alpha = decode_single_channel_block(&bytes[..8]);
+    let mut block = decode_color_block(&bytes[8..], true);
+    for (texel, alpha) in block.iter_mut().zip(alpha) {
+        texel[3] = alpha;
+    }
+    block
+}
+
+fn encode_bc4_block(block: &Block) -> [u8; 8] {
+    encode_single_channel_block(&channel_values(block, 0))
+}
+
+fn decode_bc4_block(bytes: &[u8]) -> Block {
+    decode_single_channel_block(bytes).map(|value| [value, value, value, 255])
+}
+
+fn encode_bc5_block(block: &Block) -> [u8; 16] {
+    let mut bytes = [0; 16];
+    bytes[..8].copy_from_slice(&encode_single_channel_block(&channel_values(block, 0)));
+    bytes[8..].copy_from_slice(&encode_single_channel_block(&channel_values(block, 1)));
+    bytes
+}
+
+fn decode_bc5_block(bytes: &[u8]) -> Block {
+    let red = decode_single_channel_block(&bytes[..8]);
+    let green = decode_single_channel_block(&bytes[8..]);
+    let mut block = [[0, 0, 0, 255]; 16];
+    for ((texel, red), green) in block.iter_mut().zip(red).zip(green) {
+        texel[0] = red;
+        texel[1] = green;
+    }
+    block
+}
+
+// ---------------------------------------------------------------------------
+// BC7
+// ---------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug)]
+struct Bc7Mode {
+    n_subsets: usize,
+    partition_bits: u32,
+    rotation_bits: u32,
+    index_selection_bits: u32,
+    color_bits: u32,
+    alpha_bits: u32,
+    has_endpoint_pbits: bool,
+    has_shared_pbits: bool,
+    index_bits: u32,
+    secondary_index_bits: u32,
+}
+
+const BC7_MODES: [Bc7Mode; 8] = [
+    Bc7Mode::new(3, 4, 0, 0, 4, 0, true, false, 3, 0),
+    Bc7Mode::new(2, 6, 0, 0, 6, 0, false, true, 3, 0),
+    Bc7Mode::new(3, 6, 0, 0, 5, 0, false, false, 2, 0),
+    Bc7Mode::new(2, 6, 0, 0, 7, 0, true, false, 2, 0),
+    Bc7Mode::new(1, 0, 2, 1, 5, 6, false, false, 2, 3),
+    Bc7Mode::new(1, 0, 2, 0, 7, 8, false, false, 2, 2),
+    Bc7Mode::new(1, 0, 0, 0, 7, 7, true, false, 4, 0),
+    Bc7Mode::new(2, 6, 0, 0, 5, 5, true, false, 2, 0),
+];
+
+/// Subset membership masks for two-subset partitions, with one bit per
+/// texel.
+const BC7_PARTITIONS_2: [u16; 64] = [
+    0xCCCC, 0x8888, 0xEEEE, 0xECC8, 0xC880, 0xFEEC, 0xFEC8, 0xEC80, 0xC800, 0xFFEC, 0xFE80, 0xE800,
+    0xFFE8, 0xFF00, 0xFFF0, 0xF000, 0xF710, 0x008E, 0x7100, 0x08CE, 0x008C, 0x7310, 0x3100, 0x8CCE,
+    0x088C, 0x3110, 0x6666, 0x366C, 0x17E8, 0x0FF0, 0x718E, 0x399C, 0xAAAA, 0xF0F0, 0x5A5A, 0x33CC,
+    0x3C3C, 0x55AA, 0x9696, 0xA55A, 0x73CE, 0x13C8, 0x324C, 0x3BDC, 0x6996, 0xC33C, 0x9966, 0x0660,
+    0x0272, 0x04E4, 0x4E40, 0x2720, 0xC936, 0x936C, 0x39C6, 0x639C, 0x9336, 0x9CC9, 0x817E, 0xE718,
+    0xCCF0, 0x0FCC, 0x7744, 0xEE22,
+];
+
+/// Subset indices for three-subset partitions, with two bits per texel.
+const BC7_PARTITIONS_3: [u32; 64] = [
+    0xAA685050, 0x6A5A5040, 0x5A5A4200, 0x5450A0A8, 0xA5A50000, 0xA0A05050, 0x5555A0A0, 0x5A5A5050,
+    0xAA550000, 0xAA555500, 0xAAAA5500, 0x90909090, 0x94949494, 0xA4A4A4A4, 0xA9A59450, 0x2A0A4250,
+    0xA5945040, 0x0A425054, 0xA5A5A500, 0x55A0A0A0, 0xA8A85454, 0x6A6A4040, 0xA4A45000, 0x1A1A0500,
+    0x0050A4A4, 0xAAA59090, 0x14696914, 0x69691400, 0xA08585A0, 0xAA821414, 0x50A4A450, 0x6A5A0200,
+    0xA9A58000, 0x5090A0A8, 0xA8A09050, 0x24242424, 0x00AA5500, 0x24924924, 0x24499224, 0x50A50A50,
+    0x500AA550, 0xAAAA4444, 0x66660000, 0xA5A0A5A0, 0x50A050A0, 0x69286928, 0x44AAAA44, 0x66666600,
+    0xAA444444, 0x54A854A8, 0x95809580, 0x96969600, 0xA85454A8, 0x80959580, 0xAA141414, 0x96960000,
+    0xAAAA1414, 0xA05050A0, 0xA0A5A5A0, 0x96000000, 0x40804080, 0xA9A8A9A8, 0xAAAAAA44, 0x2A4A5254,
+];
+
+/// Anchor texel of the second subset in two-subset partitions.
+const BC7_ANCHORS_2_OF_2: [u8; 64] = [
+    15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 2, 8, 2, 2, 8, 8, 15, 2, 8,
+    2, 2, 8, 8, 2, 2, 15, 15, 6, 8, 2, 8, 15, 15, 2, 8, 2, 2, 2, 15, 15, 6, 6, 2, 6, 8, 15, 15, 2,
+    2, 15, 15, 15, 15, 15, 2, 2, 15,
+];
+
+/// Anchor texel of the second subset in three-subset partitions.
+const BC7_ANCHORS_2_OF_3: [u8; 64] = [
+    3, 3, 15, 15, 8, 3, 15, 15, 8, 8, 6, 6, 6, 5, 3, 3, 3, 3, 8, 15, 3, 3, 6, 10, 5, 8, 8, 6, 8, 5,
+    15, 15, 8, 15, 3, 5, 6, 10, 8, 15, 15, 3, 15, 5, 15, 15, 15, 15, 3, 15, 5, 5, 5, 8, 5, 10, 5,
+    10, 8, 13, 15, 12, 3, 3,
+];
+
+/// Anchor texel of the third subset in three-subset partitions.
+const BC7_ANCHORS_3_OF_3: [u8; 64] = [
+    15, 8, 8, 3, 15, 15, 3, 8, 15, 15, 15, 15, 15, 15, 15, 8, 15, 8, 15, 3, 15, 8, 15, 8, 3, 15, 6,
+    10, 15, 15, 10, 8, 15, 3, 15, 10, 10, 8, 9, 10, 6, 15, 8, 15, 3, 6, 6, 8, 15, 3, 15, 15, 15,
+    15, 15, 15, 15, 15, 15, 15, 3, 15, 15, 8,
+];
+
+const BC7_WEIGHTS_2: [u32; 4] = [0, 21, 43, 64];
+const BC7_WEIGHTS_3: [u32; 8] = [0, 9, 18, 27, 37, 46, 55, 64];
+const BC7_WEIGHTS_4: [u32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+
+impl Bc7Mode {
+    #[allow(clippy::too_many_arguments)]
+    const fn new(
+        n_subsets: usize,
+        partition_bits: u32,
+        rotation_bits: u32,
+        index_selection_bits: u32,
+        color_bits: u32,
+        alpha_bits: u32,
+        has_endpoint_pbits: bool,
+        has_shared_pbits: bool,
+        index_bits: u32,
+        secondary_index_bits: u32,
+    ) -> Self {
+        Self {
+            n_subsets,
+            partition_bits,
+            rotation_bits,
+            index_selection_bits,
+            color_bits,
+            alpha_bits,
+            has_endpoint_pbits,
+            has_shared_pbits,
+            index_bits,
+            secondary_index_bits,
+        }
+    }
+}
+
+/// Reads and writes bit fields of a 128-bit block, starting from the least
+/// significant bit.
+#[derive(Clone, Debug, Default)]
+struct BlockBits {
+    value: u128,
+    position: u32,
+}
+
+impl BlockBits {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        Self {
+            value: u128::from_le_bytes(bytes[..16].try_into().unwrap()),
+            position: 0,
+        }
+    }
+
+    fn read(&mut self, n_bits: u32) -> u32 {
+        let value = ((self.value >> self.position) & ((1 << n_bits) - 1)) as u32;
+        self.position += n_bits;
+        value
+    }
+
+    fn write(&mut self, value: u32, n_bits: u32) {
+        self.value |= (u128::from(value) & ((1 << n_bits) - 1)) << self.position;
+        self.position += n_bits;
+    }
+
+    fn to_bytes(&self) -> [u8; 16] {
+        self.value.to_le_bytes()
+    }
+}
+
+fn bc7_interpolate(value_0: u8, value_1: u8, weight: u32) -> u8 {
+    (((64 - weight) * u32::from(value_0) + weight * u32::from(value_1) + 32) >> 6) as u8
+}
+
+fn bc7_weights(index_bits: u32) -> &'static [u32] {
+    match index_bits {
+        2 => &BC7_WEIGHTS_2,
+        3 => &BC7_WEIGHTS_3,
+        _ => &BC7_WEIGHTS_4,
+    }
+}
+
+fn expand_bits(value: u32, n_bits: u32) -> u8 {
+    let value = value << (8 - n_bits);
+    (value | (value >> n_bits)) as u8
+}
+
+fn bc7_subset(mode: &Bc7Mode, partition: usize, texel_idx: usize) -> usize {
+    match mode.n_subsets {
+        2 => usize::from((BC7_PARTITIONS_2[partition] >> texel_idx) & 1),
+        3 => ((BC7_PARTITIONS_3[partition] >> (2 * texel_idx)) & 3) as usize,
+        _ => 0,
+    }
+}
+
+fn bc7_is_anchor(mode: &Bc7Mode, partition: usize, texel_idx: usize) -> bool {
+    texel_idx == 0
+        || match mode.n_subsets {
+            2 => texel_idx == usize::from(BC7_ANCHORS_2_OF_2[partition]),
+            3 => {
+                texel_idx == usize::from(BC7_ANCHORS_2_OF_3[partition])
+                    || texel_idx == usize::from(BC7_ANCHORS_3_OF_3[partition])
+            }
+            _ => false,
+        }
+}
+
+fn decode_bc7_block(bytes: &[u8]) -> Block {
+    let mode_idx = bytes[0].trailing_zeros();
+    if mode_idx >= 8 {
+        // Reserved mode, which decodes to transparent black
+        return [[0; 4]; 16];
+    }
+    let mode = &BC7_MODES[mode_idx as usize];
+
+    let mut bits = BlockBits::from_bytes(bytes);
+    bits.read(mode_idx + 1);
+
+    let partition = bits.read(mode.partition_bits) as usize;
+    let rotation = bits.read(mode.rotation_bits);
+    let index_selection = bits.read(mode.index_selection_bits);
+
+    let n_endpoints = 2 * mode.n_subsets;
+    let mut endpoints = [[0_u32; 4]; 6];
+
+    for channel in 0..3 {
+        for endpoint in &mut endpoints[..n_endpoints] {
+            endpoint[channel] = bits.read(mode.color_bits);
+        }
+    }
+    if mode.alpha_bits > 0 {
+        for endpoint in &mut endpoints[..n_endpoints] {
+            endpoint[3] = bits.read(mode.alpha_bits);
+        }
+    }
+
+    let has_pbits = mode.has_endpoint_pbits || mode.has_shared_pbits;
+    if mode.has_endpoint_pbits {
+        for endpoint in &mut endpoints[..n_endpoints] {
+            let pbit = bits.read(1);
+            for value in endpoint.iter_mut() {
+                *value = (*value << 1) | pbit;
+            }
+        }
+    } else if mode.has_shared_pbits {
+        for subset in 0..mode.n_subsets {
+            let pbit = bits.read(1);
+            for endpoint in &mut endpoints[2 * subset..2 * subset + 2] {
+                for value in endpoint.iter_mut() {
+                    *value = (*value << 1) | pbit;
+                }
+            }
+        }
+    }
+
+    let color_bits = mode.color_bits + u32::from(has_pbits);
+    let alpha_bits = mode.alpha_bits + u32::from(has_pbits);
+
+    let endpoints: Vec<[u8; 4]> = endpoints[..n_endpoints]
+        .iter()
+        .map(|endpoint| {
+            [
+                expand_bits(endpoint[0], color_bits),
+                expand_bits(endpoint[1], color_bits),
+                expand_bits(endpoint[2], color_bits),
+                if mode.alpha_bits > 0 {
+                    expand_bits(endpoint[3], alpha_bits)
+                } else {
+                    255
+                },
+            ]
+        })
+        .collect();
+
+    let mut primary_indices = [0; 16];
+    for (texel_idx, idx) in primary_indices.iter_mut().enumerate() {
+        let is_anchor = bc7_is_anchor(mode, partition, texel_idx);
+        *idx = bits.read(mode.index_bits - u32::from(is_anchor));
+    }
+
+    let mut secondary_indices = [0; 16];
+    if mode.secondary_index_bits > 0 {
+        for (texel_idx, idx) in secondary_indices.iter_mut().enumerate() {
+            *idx = bits.read(mode.secondary_index_bits - u32::from(texel_idx == 0));
+        }
+    }
+
+    let mut block = [[0; 4]; 16];
+    for (texel_idx, texel) in block.iter_mut().enumerate() {
+        let subset = bc7_subset(mode, partition, texel_idx);
+        let (first, second) = (endpoints[2 * subset], endpoints[2 * subset + 1]);
+
+        let (color_weight, alpha_weight) = if mode.secondary_index_bits == 0 {
+            let weight = bc7_weights(mode.index_bits)[primary_indices[texel_idx] as usize];
+            (weight, weight)
+        } else {
+            let primary = bc7_weights(mode.index_bits)[primary_indices[texel_idx] as usize];
+            let secondary =
+                bc7_weights(mode.secondary_index_bits)[secondary_indices[texel_idx] as usize];
+            if index_selection == 0 {
+                (primary, secondary)
+            } else {
+                (secondary, primary)
+            }
+        };
+
+        for channel in 0..3 {
+            texel[channel] = bc7_interpolate(first[channel], second[channel], color_weight);
+        }
+        texel[3] = bc7_interpolate(first[3], second[3], alpha_weight);
+
+        match rotation {
+            1 => texel.swap(0, 3),
+            2 => texel.swap(1, 3),
+            3 => texel.swap(2, 3),
+            _ => {}
+        }
+    }
+    block
+}
+
+/// Quantizes the given endpoint to seven bits per channel plus a shared
+/// p-bit, returning the quantized channels and the p-bit.
+fn quantize_bc7_mode_6_endpoint(endpoint: [f32; 4]) -> ([u32; 4], u32) {
+    let mut best = ([0; 4], 0, f32::INFINITY);
+    for pbit in 0..2 {
+        let mut quantized = [0; 4];
+        let mut error = 0.0;
+        for (value, q) in endpoint.iter().zip(&mut quantized) {
+            *q = ((value - pbit as f32) / 2.0).round().clamp(0.0, 127.0) as u32;
+            let reconstructed = ((*q << 1) | pbit) as f32;
+            error += (reconstructed - value).powi(2);
+        }
+        if error < best.2 {
+            best = (quantized, pbit, error);
+        }
+    }
+    (best.0, best.1)
+}
+
+fn encode_bc7_block(block: &Block) -> [u8; 16] {
+    struct Candidate {
+        endpoints: [([u32; 4], u32); 2],
+        indices: [u32; 16],
+        error: u32,
+    }
+
+    let points: Vec<[f32; 4]> = block.iter().map(|texel| texel.map(f32::from)).collect();
+
+    let evaluate = |first: [f32; 4], second: [f32; 4]| {
+        let endpoints = [
+            quantize_bc7_mode_6_endpoint(first),
+            quantize_bc7_mode_6_endpoint(second),
+        ];
+        let [value_0, value_1] =
+            endpoints.map(|(quantized, pbit)| quantized.map(|q| ((q << 1) | pbit) as u8));
+
+        let palette: Vec<[u8; 4]> = BC7_WEIGHTS_4
+            .iter()
+            .map(|&weight| {
+                [0, 1, 2, 3]
+                    .map(|channel| bc7_interpolate(value_0[channel], value_1[channel], weight))
+            })
+            .collect();
+
+        let mut indices = [0; 16];
+        let mut error = 0;
+        for (texel, idx) in block.iter().zip(&mut indices) {
+            let (closest, texel_error) = closest_palette_entry(texel, &palette);
+            *idx = closest;
+            error += texel_error;
+        }
+        Candidate {
+            endpoints,
+            indices,
+            error,
+        }
+    };
+
+    let (low, high) = principal_axis_endpoints(&points);
+    let mut best = evaluate(low, high);
+
+    // Refine the endpoints for the chosen indices
+    if best.error > 0 {
+        let weights: Vec<f32> = best
+            .indices
+            .iter()
+            .map(|&idx| BC7_WEIGHTS_4[idx as usize] as f32 / 64.0)
+            .collect();
+        if let Some((first, second)) = least_squares_endpoints(&points, &weights) {
+            let refined = evaluate(first, second);
+            if refined.error < best.error {
+                best = refined;
+            }
+        }
+    }
+
+    // The most significant index bit of the anchor texel is implicitly zero,
+    // so we swap the endpoints if required (the weights are symmetric)
+    if best.indices[0] >= 8 {
+        best.endpoints.swap(0, 1);
+        for idx in &mut best.indices {
+            *idx = 15 - *idx;
+        }
+    }
+
+    let mut bits = BlockBits::default();
+    bits.write(1 << 6, 7);
+    for channel in 0..4 {
+        for (quantized, _) in &best.endpoints {
+            bits.write(quantized[channel], 7);
+        }
+    }
+    for (_, pbit) in &best.endpoints {
+        bits.write(*pbit, 1);
+    }
+    for (texel_idx, &idx) in best.indices.iter().enumerate() {
+        bits.write(idx, if texel_idx == 0 { 3 } else { 4 });
+    }
+    bits.to_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgba_meta(width: u32, height: u32) -> ImageMetadata {
+        ImageMetadata {
+            width,
+            height,
+            pixel_format: PixelFormat::Rgba8,
+        }
+    }
+
+    /// Creates an image whose texel values vary along a line in RGBA space,
+    /// which block compression can represent well.
+    fn gradient_image(width: u32, height: u32) -> Vec<u8> {
+        let mut data = Vec::new();
+        for idx in 0..width * height {
+            let t = idx as f32 / (width * height - 1) as f32;
+            data.extend_from_slice(&[
+                (255.0 * t) as u8,
+                (64.0 + 128.0 * t) as u8,
+                (200.0 - 150.0 * t) as u8,
+                (255.0 - 100.0 * t) as u8,
+            ]);
+        }
+        data
+    }
+
+    fn max_channel_error(a: &[u8], b: &[u8], n_channels: usize, channels: &[usize]) -> u8 {
+        a.chunks_exact(n_channels)
+            .zip(b.chunks_exact(n_channels))
+            .flat_map(|(a, b)| channels.iter().map(move |&c| a[c].abs_diff(b[c])))
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn compressed_image_has_expected_size() {
+        let meta = rgba_meta(10, 6);
+        let data = gradient_image(10, 6);
+        for compression in [
+            BlockCompression::Bc1,
+            BlockCompression::Bc3,
+            BlockCompression::Bc4,
+            BlockCompression::Bc5,
+            BlockCompression::Bc7,
+        ] {
+            let compressed = compress_image(compression, &meta, &data);
+            assert_eq!(
+                compressed.len(),
+                (3 * 2 * compression.block_n_bytes()) as usize
+            );
+        }
+    }
+
+    #[test]
+    fn bc1_round_trip_is_close_for_opaque_gradient() {
+        let meta = rgba_meta(8, 8);
+        let mut data = gradient_image(8, 8);
+        data.chunks_exact_mut(4).for_each(|texel| texel[3] = 255);
+
+        let compressed = compress_image(BlockCompression::Bc1, &meta, &data);
+        let decompressed = decompress_image(BlockCompression::Bc1, 8, 8, &compressed);
+
+        assert!(max_channel_error(&data, &decompressed, 4, &[0, 1, 2]) <= 24);
+        assert!(decompressed.chunks_exact(4).all(|texel| texel[3] == 255));
+    }
+
+    #[test]
+    fn bc1_preserves_single_color_blocks() {
+        let meta = rgba_meta(4, 4);
+        let data = [255, 0, 0, 255].repeat(16);
+        let compressed = compress_image(BlockCompression::Bc1, &meta, &data);
+        let decompressed = decompress_image(BlockCompression::Bc1, 4, 4, &compressed);
+        assert_eq!(decompressed, data);
+    }
+
+    #[test]
+    fn bc1_encodes_transparent_texels_as_transparent_black() {
+        let meta = rgba_meta(4, 4);
+        let mut data = [200, 100, 50, 255].repeat(16);
+        data[3] = 0;
+        let compressed = compress_image(BlockCompression::Bc1, &meta, &data);
+        let decompressed = decompress_image(BlockCompression::Bc1, 4, 4, &compressed);
+        assert_eq!(&decompressed[..4], &[0, 0, 0, 0]);
+        assert!(
+            decompressed[4..]
+                .chunks_exact(4)
+                .all(|texel| texel[3] == 255)
+        );
+    }
+
+    #[test]
+    fn bc3_round_trip_is_close() {
+        let meta = rgba_meta(8, 8);
+        let data = gradient_image(8, 8);
+        let compressed = compress_image(BlockCompression::Bc3, &meta, &data);
+        let decompressed = decompress_image(BlockCompression::Bc3, 8, 8, &compressed);
+        assert!(max_channel_error(&data, &decompressed, 4, &[0, 1, 2]) <= 24);
+        assert!(max_channel_error(&data, &decompressed, 4, &[3]) <= 8);
+    }
+
+    #[test]
+    fn bc4_round_trip_is_close_for_luma_image() {
+        let meta = ImageMetadata {
+            width: 8,
+            height: 4,
+            pixel_format: PixelFormat::Luma8,
+        };
+        let data: Vec<u8> = (0..32).map(|i| (i * 8) as u8).collect();
+        let compressed = compress_image(BlockCompression::Bc4, &meta, &data);
+        let decompressed = decompress_image(BlockCompression::Bc4, 8, 4, &compressed);
+        // Each block spans 216 values, interpolated in 7 steps
+        assert!(max_channel_error(&data, &decompressed, 1, &[0]) <= 16);
+    }
+
+    #[test]
+    fn bc4_preserves_extreme_values() {
+        let meta = ImageMetadata {
+            width: 4,
+            height: 4,
+            pixel_format: PixelFormat::Luma8,
+        };
+        let mut data = vec![100; 16];
+        data[0] = 0;
+        data[1] = 255;
+        data[2] = 120;
+        let compressed = compress_image(BlockCompression::Bc4, &meta, &data);
+        let decompressed = decompress_image(BlockCompression::Bc4, 4, 4, &compressed);
+        assert_eq!(decompressed[0], 0);
+        assert_eq!(decompressed[1], 255);
+        assert!(max_channel_error(&data, &decompressed, 1, &[0]) <= 4);
+    }
+
+    #[test]
+    fn bc5_round_trip_is_close_for_red_and_green() {
+        let meta = rgba_meta(8, 8);
+        let data = gradient_image(8, 8);
+        let compressed = compress_image(BlockCompression::Bc5, &meta, &data);
+        let decompressed = decompress_image(BlockCompression::Bc5, 8, 8, &compressed);
+        assert!(max_channel_error(&data, &decompressed, 4, &[0, 1]) <= 10);
+        assert!(
+            decompressed
+                .chunks_exact(4)
+                .all(|texel| texel[2] == 0 && texel[3] == 255)
+        );
+    }
+
+    #[test]
+    fn bc7_round_trip_is_close() {
+        let meta = rgba_meta(8, 8);
+        let data = gradient_image(8, 8);
+        let compressed = compress_image(BlockCompression::Bc7, &meta, &data);
+        let decompressed = decompress_image(BlockCompression::Bc7, 8, 8, &compressed);
+        assert!(max_channel_error(&data, &decompressed, 4, &[0, 1, 2, 3]) <= 8);
+    }
+
+    #[test]
+    fn bc7_nearly_preserves_single_color_blocks() {
+        let meta = rgba_meta(4, 4);
+        let data = [10, 200, 77, 128].repeat(16);
+        let compressed = compress_image(BlockCompression::Bc7, &meta, &data);
+        let decompressed = decompress_image(BlockCompression::Bc7, 4, 4, &compressed);
+        // Mode 6 endpoints share the lowest bit across channels
+        assert!(max_channel_error(&data, &decompressed, 4, &[0, 1, 2, 3]) <= 1);
+    }
+
+    #[test]
+    fn bc7_reserved_mode_decodes_to_transparent_black() {
+        assert_eq!(decode_bc7_block(&[0; 16]), [[0; 4]; 16]);
+    }
+
+    #[test]
+    fn bc7_mode_5_block_with_rotation_decodes_correctly() {
+        let mut bits = BlockBits::default();
+        bits.write(1 << 5, 6);
+        // Swap red and alpha
+        bits.write(1, 2);
+        for value in [100_u32, 100, 50, 50, 25, 25] {
+            bits.write(value, 7);
+        }
+        for value in [200_u32, 200] {
+            bits.write(value, 8);
+        }
+        bits.write(0, 31);
+        bits.write(0, 31);
+        assert_eq!(bits.position, 128);
+
+        let block = decode_bc7_block(&bits.to_bytes());
+        let red = expand_bits(100, 7);
+        let green = expand_bits(50, 7);
+        let blue = expand_bits(25, 7);
+        assert_eq!(block, [[200, green, blue, red]; 16]);
+    }
+
+    #[test]
+    fn partial_blocks_are_cropped_when_decompressing() {
+        let meta = rgba_meta(5, 3);
+        let data = [40, 80, 120, 255].repeat(15);
+        let compressed = compress_image(BlockCompression::Bc7, &meta, &data);
+        assert_eq!(compressed.len(), 32);
+        let decompressed = decompress_image(BlockCompression::Bc7, 5, 3, &compressed);
+        assert_eq!(decompressed.len(), data.len());
+        assert!(max_channel_error(&data, &decompressed, 4, &[0, 1, 2, 3]) <= 1);
+    }
+}
@@ -3,10 +3,13 @@
 use crate::{
     ImageSource, ImageTextureCreateInfo, ImageTextureSource, SamplerCreateInfo, SamplerID,
     SamplerRegistry, TextureArrayUsage, TextureCreateInfo, TextureID, TextureRegistry,
+    atlas::{TextureAtlas, TextureAtlasPackingConfig, TextureAtlasRegionID},
     processing::ImageProcessing,
 };
 use anyhow::{Context, Result, bail};
+use impact_alloc::Global;
 use impact_gpu::texture::{SamplerConfig, TextureConfig};
+use impact_io::image::Image;
 use std::path::{Path, PathBuf};
 
 /// Declaration of an image-based texture.
@@ -42,6 +45,31 @@ pub enum DeclaredImageTextureSource {
         front: PathBuf,
         back: PathBuf,
     },
+    /// Images packed into a texture atlas when the texture is loaded, with
+    /// each image occupying its own region of the atlas.
+    AtlasImages {
+        images: Vec<DeclaredAtlasImage>,
+        #[cfg_attr(feature = "serde", serde(default))]
+        packing: TextureAtlasPackingConfig,
+    },
+    /// A sprite sheet image divided into a grid of equally sized frames, with
+    /// each frame forming a region of the atlas. See
+    /// [`TextureAtlas::for_sprite_sheet`].
+    SpriteSheet {
+        image: PathBuf,
+        columns: u32,
+        rows: u32,
+    },
+}
+
+/// An image to pack into a texture atlas.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct DeclaredAtlasImage {
+    /// The ID of the atlas region holding the image.
+    pub id: TextureAtlasRegionID,
+    /// The path to the image file.
+    pub path: PathBuf,
 }
 
 impl ImageTextureDeclaration {
@@ -81,33 +109,110 @@ impl DeclaredImageTextureSource {
                 *front = root_path.join(&front);
                 *back = root_path.join(&back);
             }
+            Self::AtlasImages { images, .. } => {
+                for image in images {
+                    image.path = root_path.join(&image.path);
+                }
+            }
+            Self::SpriteSheet { image, .. } => {
+                *image = root_path.join(&image);
+            }
         }
     }
-}
 
-impl From<DeclaredImageTextureSource> for ImageTextureSource {
-    fn from(source: DeclaredImageTextureSource) -> Self {
-        match source {
-            DeclaredImageTextureSource::Image(image_path) => {
-                ImageTextureSource::Single(ImageSource::File(image_path))
-            }
-            DeclaredImageTextureSource::ArrayImages(image_paths) => ImageTextureSource::Array {
-                sources: image_paths.into_iter().map(ImageSource::File).collect(),
-                usage: TextureArrayUsage::Generic,
-            },
-            DeclaredImageTextureSource::CubemapImages {
+    /// Returns the paths of all image files in the source.
+    pub fn image_paths(&self) -> Vec<&PathBuf> {
+        match self {
+            Self::Image(path) | Self::SpriteSheet { image: path, .. } => vec![path],
+            Self::ArrayImages(paths) => paths.iter().collect(),
+            Self::CubemapImages {
                 right,
                 left,
                 top,
                 bottom,
                 front,
                 back,
-            } => ImageTextureSource::Array {
-                sources: [right, left, top, bottom, front, back]
-                    .map(ImageSource::File)
-                    .to_vec(),
-                usage: TextureArrayUsage::Cubemap,
-            },
+            } => vec![right, left, top, bottom, front, back],
+            Self::AtlasImages { images, .. } => images.iter().map(|image| &image.path).collect(),
+        }
+    }
+
+    /// Loads the texture source, packing the images into a single atlas
+    /// image for atlas sources.
+    ///
+    /// # Returns
+    /// The source along with the layout of the texture if it is an atlas.
+    ///
+    /// # Errors
+    /// Returns an error if the images of an atlas source can not be loaded or
+    /// packed.
+    pub fn load(self) -> Result<(ImageTextureSource, Option<TextureAtlas>)> {
+        Ok(match self {
+            Self::Image(image_path) => (
+                ImageTextureSource::Single(ImageSource::File(image_path)),
+                None,
+            ),
+            Self::ArrayImages(image_paths) => (
+                ImageTextureSource::Array {
+                    sources: image_paths.into_iter().map(ImageSource::File).collect(),
+                    usage: TextureArrayUsage::Generic,
+                },
+                None,
+            ),
+            Self::CubemapImages {
+                right,
+                left,
+                top,
+                bottom,
+                front,
+                back,
+            } => (
+                ImageTextureSource::Array {
+                    sources: [right, left, top, bottom, front, back]
+                        .map(ImageSource::File)
+                        .to_vec(),
+                    usage: TextureArrayUsage::Cubemap,
+                },
+                None,
+            ),
+            Self::AtlasImages { images, packing } => {
+                let (image, atlas) = pack_declared_atlas_images(&images, &packing)?;
+                (
+                    ImageTextureSource::Single(ImageSource::Bytes(image)),
+                    Some(atlas),
+                )
+            }
+            Self::SpriteSheet {
+                image,
+                columns,
+                rows,
+            } => {
+                let (image, atlas) = load_declared_sprite_sheet(&image, columns, rows)?;
+                (
+                    ImageTextureSource::Single(ImageSource::Bytes(image)),
+                    Some(atlas),
+                )
+            }
+        })
+    }
+
+    /// Loads the atlas image and layout for an atlas source, or returns
+    /// [`None`] for other sources.
+    ///
+    /// # Errors
+    /// Returns an error if the images of an atlas source can not be loaded or
+    /// packed.
+    pub fn load_atlas(&self) -> Result<Option<(Image, TextureAtlas)>> {
+        match self {
+            Self::AtlasImages { images, packing } => {
+                pack_declared_atlas_images(images, packing).map(Some)
+            }
+            Self::SpriteSheet {
+                image,
+                columns,
+                rows,
+            } => load_declared_sprite_sheet(image, *columns, *rows).map(Some),
+            Self::Image(_) | Self::ArrayImages(_) | Self::CubemapImages { .. } => Ok(None),
         }
     }
 }
@@ -148,11 +253,21 @@ pub fn load_declared_image_texture(
     sampler_registry: &mut SamplerRegistry,
     declaration: ImageTextureDeclaration,
 ) -> Result<TextureID> {
-    load_image_texture(
+    if texture_registry.contains(declaration.id) {
+        bail!(
+            "Tried to load texture under already existing ID: {}",
+            declaration.id
+        );
+    }
+
+    let (source, atlas) = declaration.source.load()?;
+
+    load_image_texture_with_atlas(
         texture_registry,
         sampler_registry,
         declaration.id,
-        declaration.source.into(),
+        source,
+        atlas,
         declaration.texture_config,
         declaration.sampler_config,
         declaration.processing,
@@ -179,11 +294,58 @@ pub fn load_image_texture(
     texture_config: TextureConfig,
     sampler_config: Option<SamplerConfig>,
     processing: ImageProcessing,
+) -> Result<()> {
+    load_image_texture_with_atlas(
+        texture_registry,
+        sampler_registry,
+        texture_id,
+        source,
+        None,
+        texture_config,
+        sampler_config,
+        processing,
+    )
+}
+
+/// Like [`load_image_texture`], but marks the texture as a texture atlas with
+/// the given layout if one is specified.
+///
+/// # Errors
+/// See [`load_image_texture`]. An error is also returned if the atlas
+/// dimensions differ from the texture dimensions.
+pub fn load_image_texture_with_atlas(
+    texture_registry: &mut TextureRegistry,
+    sampler_registry: &mut SamplerRegistry,
+    texture_id: TextureID,
+    source: ImageTextureSource,
+    atlas: Option<TextureAtlas>,
+    texture_config: TextureConfig,
+    sampler_config: Option<SamplerConfig>,
+    processing: ImageProcessing,
 ) -> Result<()> {
     if texture_registry.contains(texture_id) {
         bail!("Tried to load texture under already existing ID: {texture_id}");
     }
 
+    // Block compressed textures come with their own mip chain, so we read them
+    // in full up front
+    let source = match source {
+        ImageTextureSource::Single(ImageSource::File(path))
+            if crate::io::is_compressed_texture_path(&path) =>
+        {
+            log::debug!(
+                "Reading compressed texture `{texture_id}` from {}",
+                path.display(),
+            );
+            let (mip_chain, array_usage) = crate::io::read_compressed_texture_from_path(&path)?;
+            ImageTextureSource::CompressedMipChain {
+                mip_chain,
+                array_usage,
+            }
+        }
+        source => source,
+    };
+
     let metadata = match &source {
         ImageTextureSource::Single(source) => match source {
             ImageSource::File(path) => {
@@ -218,9 +380,11 @@ pub fn load_image_texture(
                 ImageSource::Bytes(image) => image.meta.clone(),
             }
         }
+        ImageTextureSource::MipChain { mip_chain, .. } => mip_chain.meta().clone(),
+        ImageTextureSource::CompressedMipChain { mip_chain, .. } => mip_chain.decompressed_meta(),
     };
 
-    let image_texture_info = ImageTextureCreateInfo::new(
+    let mut image_texture_info = ImageTextureCreateInfo::new(
         source,
         metadata,
         texture_config,
@@ -228,6 +392,10 @@ pub fn load_image_texture(
         processing,
     )?;
 
+    if let Some(atlas) = atlas {
+        image_texture_info = image_texture_info.with_atlas(atlas)?;
+    }
+
     texture_registry.insert(texture_id, TextureCreateInfo::Image(image_texture_info));
 
     if let Some(sampler_config) = sampler_config {
@@ -240,3 +408,44 @@ pub fn load_image_texture(
 
     Ok(())
 }
+
+fn pack_declared_atlas_images(
+    images: &[DeclaredAtlasImage],
+    packing: &TextureAtlasPackingConfig,
+) -> Result<(Image, TextureAtlas)> {
+    let images = images
+        .iter()
+        .map(|image| {
+            log::debug!(
+                "Loading texture atlas image `{}` from {}",
+                image.id,
+                image.path.display()
+            );
+            let loaded =
+                impact_io::image::load_image_from_path(Global, &image.path).with_context(|| {
+                    format!(
+                        "Failed to load texture atlas image from {}",
+                        image.path.display()
+                    )
+                })?;
+            Ok((image.id, loaded))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    crate::atlas::pack_images_into_atlas(&images, packing)
+}
+
+fn load_declared_sprite_sheet(
+    path: &Path,
+    columns: u32,
+    rows: u32,
+) -> Result<(Image, TextureAtlas)> {
+    log::debug!("Loading sprite sheet from {}", path.display());
+
+    let image = impact_io::image::load_image_from_path(Global, path)
+        .with_context(|| format!("Failed to load sprite sheet from {}", path.display()))?;
+
+    let atlas = TextureAtlas::for_sprite_sheet(image.meta.width, image.meta.height, columns, rows)?;
+
+    Ok((image, atlas))
+}
@@ -1,7 +1,7 @@
 //! Input/output of texture data.
 
-use crate::processing::ImageProcessing;
-use anyhow::Result;
+use crate::{TextureArrayUsage, mip_chain::CompressedMipChain, processing::ImageProcessing};
+use anyhow::{Context, Result, bail};
 use impact_alloc::arena::ArenaPool;
 use impact_gpu::{
     device::GraphicsDevice,
@@ -68,6 +68,239 @@ pub fn create_texture_from_image_path(
     )
 }
 
+/// Whether the file at the given path is a container for block compressed
+/// texture data (KTX2 or DDS), based on its extension.
+pub fn is_compressed_texture_path(path: impl AsRef<Path>) -> bool {
+    path.as_ref()
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            extension.eq_ignore_ascii_case("ktx2") || extension.eq_ignore_ascii_case("dds")
+        })
+}
+
+/// Reads the block compressed texture in the KTX2 or DDS file at the given
+/// path, returning its mip chain and, if it has multiple layers, how the
+/// layers should be used. The container format is determined from the file
+/// extension.
+///
+/// The color space of the texture is not taken from the file, but from the
+/// texture configuration it is created with.
+///
+/// # Errors
+/// Returns an error if:
+/// - The file can not be read.
+/// - The file extension is not `ktx2` or `dds`.
+/// - The feature for the container format is not enabled.
+/// - The file is invalid or holds an unsupported format.
+pub fn read_compressed_texture_from_path(
+    path: impl AsRef<Path>,
+) -> Result<(CompressedMipChain, Option<TextureArrayUsage>)> {
+    let path = path.as_ref();
+
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+
+    let bytes =
+        std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+
+    let result = match extension.as_deref() {
+        Some("ktx2") => read_ktx2_texture_from_bytes(&bytes),
+        Some("dds") => read_dds_texture_from_bytes(&bytes),
+        _ => bail!(
+            "Unsupported compressed texture file extension for {}",
+            path.display()
+        ),
+    };
+
+    result.with_context(|| format!("Failed to read compressed texture from {}", path.display()))
+}
+
+/// Reads the block compressed texture in the given KTX2 file bytes.
+///
+/// Supercompressed, 3D and cubemap array textures are not supported.
+///
+/// # Errors
+/// Returns an error if the data is invalid or holds an unsupported format.
+#[cfg(feature = "ktx2")]
+pub fn read_ktx2_texture_from_bytes(
+    bytes: &[u8],
+) -> Result<(CompressedMipChain, Option<TextureArrayUsage>)> {
+    use impact_gpu::texture::BlockCompression;
+    use ktx2::Format;
+
+    let reader =
+        ktx2::Reader::new(bytes).map_err(|error| anyhow::anyhow!("Invalid KTX2 data: {error}"))?;
+    let header = reader.header();
+
+    if let Some(scheme) = header.supercompression_scheme {
+        bail!("Unsupported KTX2 supercompression scheme {scheme:?}");
+    }
+    if header.pixel_depth > 1 {
+        bail!("Unsupported 3D KTX2 texture");
+    }
+
+    let compression = match header.format {
+        Some(
+            Format::BC1_RGB_UNORM_BLOCK
+            | Format::BC1_RGB_SRGB_BLOCK
+            | Format::BC1_RGBA_UNORM_BLOCK
+            | Format::BC1_RGBA_SRGB_BLOCK,
+        ) => BlockCompression::Bc1,
+        Some(Format::BC3_UNORM_BLOCK | Format::BC3_SRGB_BLOCK) => BlockCompression::Bc3,
+        Some(Format::BC4_UNORM_BLOCK) => BlockCompression::Bc4,
+        Some(Format::BC5_UNORM_BLOCK) => BlockCompression::Bc5,
+        Some(Format::BC7_UNORM_BLOCK | Format::BC7_SRGB_BLOCK) => BlockCompression::Bc7,
+        format => bail!("Unsupported KTX2 texture format {format:?}"),
+    };
+
+    let (n_layers, array_usage) = match (header.face_count, header.layer_count) {
+        (1, 0) => (1, None),
+        (1, layer_count) => (layer_count, Some(TextureArrayUsage::Generic)),
+        (6, 0 | 1) => (6, Some(TextureArrayUsage::Cubemap)),
+        (face_count, layer_count) => {
+            bail!("Unsupported KTX2 texture with {face_count} faces and {layer_count} layers")
+        }
+    };
+
+    // Each level holds all layers or faces
+    let data = reader
+        .levels()
+        .flat_map(|level| level.data)
+        .copied()
+        .collect();
+
+    let mip_chain = CompressedMipChain::from_parts(
+        compression,
+        header.pixel_width,
+        header.pixel_height.max(1),
+        n_layers,
+        header.level_count.max(1),
+        data,
+    )?;
+
+    Ok((mip_chain, array_usage))
+}
+
+#[cfg(not(feature = "ktx2"))]
+fn read_ktx2_texture_from_bytes(
+    _bytes: &[u8],
+) -> Result<(CompressedMipChain, Option<TextureArrayUsage>)> {
+    bail!("Reading KTX2 textures requires the `ktx2` feature")
+}
+
+/// Reads the block compressed texture in the given DDS file bytes.
+///
+/// 3D and cubemap array textures are not supported.
+///
+/// # Errors
+/// Returns an error if the data is invalid or holds an unsupported format.
+#[cfg(feature = "dds")]
+pub fn read_dds_texture_from_bytes(
+    bytes: &[u8],
+) -> Result<(CompressedMipChain, Option<TextureArrayUsage>)> {
+    use crate::mip_chain::mip_level_dimensions;
+    use ddsfile::{Caps2, Dds, DxgiFormat, FourCC, MiscFlag};
+    use impact_gpu::texture::BlockCompression;
+
+    // Four-character codes not recognized by `ddsfile`
+    const BC4U: u32 = u32::from_le_bytes(*b"BC4U");
+    const BC5U: u32 = u32::from_le_bytes(*b"BC5U");
+
+    let dds = Dds::read(bytes).map_err(|error| anyhow::anyhow!("Invalid DDS data: {error}"))?;
+
+    if dds.get_depth() > 1 {
+        bail!("Unsupported 3D DDS texture");
+    }
+
+    let compression = match dds.get_dxgi_format() {
+        Some(DxgiFormat::BC1_Typeless | DxgiFormat::BC1_UNorm | DxgiFormat::BC1_UNorm_sRGB) => {
+            BlockCompression::Bc1
+        }
+        Some(DxgiFormat::BC3_Typeless | DxgiFormat::BC3_UNorm | DxgiFormat::BC3_UNorm_sRGB) => {
+            BlockCompression::Bc3
+        }
+        Some(DxgiFormat::BC4_Typeless | DxgiFormat::BC4_UNorm) => BlockCompression::Bc4,
+        Some(DxgiFormat::BC5_Typeless | DxgiFormat::BC5_UNorm) => BlockCompression::Bc5,
+        Some(DxgiFormat::BC7_Typeless | DxgiFormat::BC7_UNorm | DxgiFormat::BC7_UNorm_sRGB) => {
+            BlockCompression::Bc7
+        }
+        None => match dds.header.spf.fourcc {
+            Some(FourCC(BC4U)) => BlockCompression::Bc4,
+            Some(FourCC(BC5U)) => BlockCompression::Bc5,
+            fourcc => bail!("Unsupported DDS texture format {fourcc:?}"),
+        },
+        format => bail!("Unsupported DDS texture format {format:?}"),
+    };
+
+    let is_cubemap = dds.header.caps2.contains(Caps2::CUBEMAP)
+        || dds
+            .header10
+            .as_ref()
+            .is_some_and(|header10| header10.misc_flag.contains(MiscFlag::TEXTURECUBE));
+
+    let (n_layers, array_usage) = if is_cubemap {
+        if dds
+            .header10
+            .as_ref()
+            .is_some_and(|header10| header10.array_size > 1)
+        {
+            bail!("Unsupported DDS cubemap array texture");
+        }
+        (6, Some(TextureArrayUsage::Cubemap))
+    } else {
+        match dds.get_num_array_layers() {
+            0 | 1 => (1, None),
+            n_layers => (n_layers, Some(TextureArrayUsage::Generic)),
+        }
+    };
+
+    let (width, height) = (dds.get_width(), dds.get_height().max(1));
+    let level_count = dds.get_num_mipmap_levels().max(1);
+
+    let level_sizes: Vec<usize> = (0..level_count)
+        .map(|level| {
+            let (width, height) = mip_level_dimensions(width, height, level);
+            compression.n_bytes_for_image(width, height) as usize
+        })
+        .collect();
+    let layer_size: usize = level_sizes.iter().sum();
+
+    if dds.data.len() < n_layers as usize * layer_size {
+        bail!(
+            "DDS texture has {} bytes of data, expected {}",
+            dds.data.len(),
+            n_layers as usize * layer_size
+        );
+    }
+
+    // DDS stores all levels for each layer consecutively, so we reorder the
+    // data to hold all layers for each level consecutively
+    let mut data = Vec::with_capacity(n_layers as usize * layer_size);
+    let mut level_offset = 0;
+    for level_size in level_sizes {
+        for layer in 0..n_layers as usize {
+            let start = layer * layer_size + level_offset;
+            data.extend_from_slice(&dds.data[start..start + level_size]);
+        }
+        level_offset += level_size;
+    }
+
+    let mip_chain =
+        CompressedMipChain::from_parts(compression, width, height, n_layers, level_count, data)?;
+
+    Ok((mip_chain, array_usage))
+}
+
+#[cfg(not(feature = "dds"))]
+fn read_dds_texture_from_bytes(
+    _bytes: &[u8],
+) -> Result<(CompressedMipChain, Option<TextureArrayUsage>)> {
+    bail!("Reading DDS textures requires the `dds` feature")
+}
+
 /// Saves the texture at the given index of the given texture array as a color
 /// or grayscale PNG image at the given output path.
 ///
@@ -554,3 +787,179 @@ fn convert_bgra8_to_rgba8(bgra_bytes: &mut [u8]) {
         bgra.swap(0, 2);
     }
 }
+
+#[cfg(test)]
+#[cfg(any(feature = "ktx2", feature = "dds"))]
+mod tests {
+    use super::*;
+    use crate::mip_chain::MipChain;
+    use impact_gpu::texture::{BlockCompression, ColorSpace};
+    use impact_io::image::{ImageMetadata, PixelFormat};
+
+    fn compressed_test_mip_chain(
+        compression: BlockCompression,
+        n_layers: u32,
+    ) -> CompressedMipChain {
+        let meta = ImageMetadata {
+            width: 8,
+            height: 4,
+            pixel_format: PixelFormat::Rgba8,
+        };
+        let data: Vec<u8> = (0..n_layers * 8 * 4 * 4).map(|i| (i * 7) as u8).collect();
+        let mip_chain =
+            MipChain::generate(&meta, &data, n_layers, ColorSpace::Linear, None).unwrap();
+        CompressedMipChain::compress(&mip_chain, compression)
+    }
+
+    #[cfg(feature = "ktx2")]
+    fn ktx2_bytes(
+        format: ktx2::Format,
+        face_count: u32,
+        layer_count: u32,
+        mip_chain: &CompressedMipChain,
+    ) -> Vec<u8> {
+        let n_layers = mip_chain.n_layers() as usize;
+        let level_count = mip_chain.level_count();
+
+        let level_sizes: Vec<usize> = (0..level_count)
+            .map(|level| {
+                let (width, height) = crate::mip_chain::mip_level_dimensions(8, 4, level);
+                n_layers * mip_chain.compression().n_bytes_for_image(width, height) as usize
+            })
+            .collect();
+
+        let dfd_offset = ktx2::Header::LENGTH + level_count as usize * ktx2::LevelIndex::LENGTH;
+        let data_offset = dfd_offset + 4;
+
+        let header = ktx2::Header {
+            format: Some(format),
+            type_size: 1,
+            pixel_width: 8,
+            pixel_height: 4,
+            pixel_depth: 0,
+            layer_count,
+            face_count,
+            level_count,
+            supercompression_scheme: None,
+            index: ktx2::Index {
+                dfd_byte_offset: dfd_offset as u32,
+                dfd_byte_length: 4,
+                kvd_byte_offset: data_offset as u32,
+                kvd_byte_length: 0,
+                sgd_byte_offset: 0,
+                sgd_byte_length: 0,
+            },
+        };
+
+        let mut bytes = header.as_bytes().to_vec();
+        let mut level_offset = data_offset;
+        for &size in &level_sizes {
+            let index = ktx2::LevelIndex {
+                byte_offset: level_offset as u64,
+                byte_length: size as u64,
+                uncompressed_byte_length: size as u64,
+            };
+            bytes.extend_from_slice(&index.as_bytes());
+            level_offset += size;
+        }
+        bytes.extend_from_slice(&4_u32.to_le_bytes());
+        bytes.extend_from_slice(mip_chain.data());
+        bytes
+    }
+
+    #[cfg(feature = "ktx2")]
+    #[test]
+    fn reading_ktx2_texture_gives_original_mip_chain() {
+        let mip_chain = compressed_test_mip_chain(BlockCompression::Bc7, 1);
+        let bytes = ktx2_bytes(ktx2::Format::BC7_SRGB_BLOCK, 1, 0, &mip_chain);
+
+        let (read_mip_chain, array_usage) = read_ktx2_texture_from_bytes(&bytes).unwrap();
+
+        assert_eq!(array_usage, None);
+        assert_eq!(read_mip_chain.compression(), BlockCompression::Bc7);
+        assert_eq!(read_mip_chain.level_count(), mip_chain.level_count());
+        assert_eq!(read_mip_chain.data(), mip_chain.data());
+    }
+
+    #[cfg(feature = "ktx2")]
+    #[test]
+    fn reading_ktx2_texture_array_gives_generic_array() {
+        let mip_chain = compressed_test_mip_chain(BlockCompression::Bc1, 3);
+        let bytes = ktx2_bytes(ktx2::Format::BC1_RGBA_UNORM_BLOCK, 1, 3, &mip_chain);
+
+        let (read_mip_chain, array_usage) = read_ktx2_texture_from_bytes(&bytes).unwrap();
+
+        assert_eq!(array_usage, Some(TextureArrayUsage::Generic));
+        assert_eq!(read_mip_chain.n_layers(), 3);
+        assert_eq!(read_mip_chain.data(), mip_chain.data());
+    }
+
+    #[cfg(feature = "ktx2")]
+    #[test]
+    fn reading_ktx2_texture_with_unsupported_format_fails() {
+        let mip_chain = compressed_test_mip_chain(BlockCompression::Bc1, 1);
+        let bytes = ktx2_bytes(ktx2::Format::BC2_UNORM_BLOCK, 1, 0, &mip_chain);
+        assert!(read_ktx2_texture_from_bytes(&bytes).is_err());
+    }
+
+    #[cfg(feature = "dds")]
+    #[test]
+    fn reading_dds_texture_array_reorders_data_by_level() {
+        use ddsfile::{AlphaMode, D3D10ResourceDimension, Dds, DxgiFormat, NewDxgiParams};
+
+        let n_layers = 2;
+        let mip_chain = compressed_test_mip_chain(BlockCompression::Bc3, n_layers);
+        let level_count = mip_chain.level_count();
+
+        let mut dds = Dds::new_dxgi(NewDxgiParams {
+            height: 4,
+            width: 8,
+            depth: None,
+            format: DxgiFormat::BC3_UNorm,
+            mipmap_levels: Some(level_count),
+            array_layers: Some(n_layers),
+            caps2: None,
+            is_cubemap: false,
+            resource_dimension: D3D10ResourceDimension::Texture2D,
+            alpha_mode: AlphaMode::Unknown,
+        })
+        .unwrap();
+
+        // Reorder from level-major to layer-major
+        let level_sizes: Vec<usize> = (0..level_count)
+            .map(|level| {
+                let (width, height) = crate::mip_chain::mip_level_dimensions(8, 4, level);
+                BlockCompression::Bc3.n_bytes_for_image(width, height) as usize
+            })
+            .collect();
+        dds.data.clear();
+        for layer in 0..n_layers as usize {
+            let mut level_offset = 0;
+            for &size in &level_sizes {
+                let start = level_offset + layer * size;
+                dds.data
+                    .extend_from_slice(&mip_chain.data()[start..start + size]);
+                level_offset += n_layers as usize * size;
+            }
+        }
+
+        let mut bytes = Vec::new();
+        dds.write(&mut bytes).unwrap();
+
+        let (read_mip_chain, array_usage) = read_dds_texture_from_bytes(&bytes).unwrap();
+
+        assert_eq!(array_usage, Some(TextureArrayUsage::Generic));
+        assert_eq!(read_mip_chain.compression(), BlockCompression::Bc3);
+        assert_eq!(read_mip_chain.n_layers(), n_layers);
+        assert_eq!(read_mip_chain.level_count(), level_count);
+        assert_eq!(read_mip_chain.data(), mip_chain.data());
+    }
+
+    #[test]
+    fn compressed_texture_paths_are_recognized_by_extension() {
+        assert!(is_compressed_texture_path("textures/albedo.ktx2"));
+        assert!(is_compressed_texture_path("textures/albedo.DDS"));
+        assert!(!is_compressed_texture_path("textures/albedo.png"));
+        assert!(!is_compressed_texture_path("textures/albedo"));
+    }
+}
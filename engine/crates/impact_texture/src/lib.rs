@@ -1,26 +1,32 @@
 //! Texture management.
 
+pub mod atlas;
+pub mod block_compression;
 pub mod gpu_resource;
 pub mod import;
 pub mod io;
 pub mod lookup_table;
+pub mod mip_chain;
 pub mod processing;
 
 use anyhow::{Context, Result, anyhow, bail};
+use atlas::TextureAtlas;
 use gpu_resource::SamplingTexture;
 use impact_alloc::{AVec, Allocator, arena::ArenaPool};
 use impact_containers::DefaultHasher;
 use impact_gpu::{
     device::GraphicsDevice,
     texture::{
-        ColorSpace, DepthOrArrayLayers, SamplerConfig, TexelDescription, Texture, TextureConfig,
-        mipmap::MipmapperGenerator,
+        BlockCompression, ColorSpace, DepthOrArrayLayers, SamplerConfig, TexelDescription, Texture,
+        TextureConfig, mipmap::MipmapperGenerator,
     },
+    wgpu,
 };
 use impact_io::image::{Image, ImageMetadata, PixelFormat};
 use impact_math::{hash64, stringhash64_newtype};
 use impact_resource::{Resource, ResourceID, registry::ImmutableResourceRegistry};
 use lookup_table::LookupTableTextureCreateInfo;
+use mip_chain::{CompressedMipChain, MipChain};
 use processing::ImageProcessing;
 use roc_integration::roc;
 use std::{
@@ -67,6 +73,7 @@ pub struct ImageTextureCreateInfo {
     texture_config: TextureConfig,
     sampler_config: Option<SamplerConfig>,
     processing: ImageProcessing,
+    atlas: Option<TextureAtlas>,
 }
 
 /// Source for image-based texture data.
@@ -77,6 +84,22 @@ pub enum ImageTextureSource {
         sources: Vec<ImageSource>,
         usage: TextureArrayUsage,
     },
+    /// Texel data with a mip chain generated ahead of time. Any image
+    /// processing is assumed to have been performed already. If an array usage
+    /// is specified, each layer of the mip chain is an element in a texture
+    /// array, otherwise the mip chain must have a single layer.
+    MipChain {
+        mip_chain: MipChain,
+        array_usage: Option<TextureArrayUsage>,
+    },
+    /// Block compressed texel data with a mip chain generated ahead of time.
+    /// Layers are interpreted like for [`Self::MipChain`]. The data is
+    /// decompressed on the CPU if the graphics device does not support the
+    /// compression format.
+    CompressedMipChain {
+        mip_chain: CompressedMipChain,
+        array_usage: Option<TextureArrayUsage>,
+    },
 }
 
 /// Source for an image.
@@ -87,6 +110,7 @@ pub enum ImageSource {
 }
 
 /// Intended usage for a texture array.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TextureArrayUsage {
     Generic,
@@ -160,6 +184,14 @@ impl TextureCreateInfo {
             }
         }
     }
+
+    /// Returns the layout of the texture if it is a texture atlas.
+    pub fn atlas(&self) -> Option<&TextureAtlas> {
+        match self {
+            Self::Image(image_texture_info) => image_texture_info.atlas(),
+            Self::LookupTable(_) => None,
+        }
+    }
 }
 
 impl Resource for TextureCreateInfo {
@@ -199,9 +231,34 @@ impl ImageTextureCreateInfo {
             texture_config,
             sampler_config,
             processing,
+            atlas: None,
         })
     }
 
+    /// Marks the texture as a texture atlas with the given layout.
+    ///
+    /// # Errors
+    /// Returns an error if the atlas dimensions differ from the texture
+    /// dimensions.
+    pub fn with_atlas(mut self, atlas: TextureAtlas) -> Result<Self> {
+        if atlas.width() != self.metadata.width || atlas.height() != self.metadata.height {
+            bail!(
+                "Texture atlas dimensions {}x{} do not match texture dimensions {}x{}",
+                atlas.width(),
+                atlas.height(),
+                self.metadata.width,
+                self.metadata.height
+            );
+        }
+        self.atlas = Some(atlas);
+        Ok(self)
+    }
+
+    /// Returns the layout of the texture if it is a texture atlas.
+    pub fn atlas(&self) -> Option<&TextureAtlas> {
+        self.atlas.as_ref()
+    }
+
     /// Returns the texel description for this texture.
     pub fn texel_description(&self) -> TexelDescription {
         determine_valid_texel_description(self.metadata.pixel_format, &self.texture_config).unwrap()
@@ -243,6 +300,20 @@ impl ImageTextureSource {
                     NonZeroU32::new(u32::try_from(sources.len()).unwrap()).unwrap(),
                 )
             }),
+            Self::MipChain {
+                mip_chain,
+                array_usage,
+            } => Some(mip_chain_depth_or_array_layers(
+                mip_chain.n_layers(),
+                *array_usage,
+            )),
+            Self::CompressedMipChain {
+                mip_chain,
+                array_usage,
+            } => Some(mip_chain_depth_or_array_layers(
+                mip_chain.n_layers(),
+                *array_usage,
+            )),
         }
     }
 
@@ -253,11 +324,28 @@ impl ImageTextureSource {
             Self::Array {
                 usage: TextureArrayUsage::Cubemap,
                 ..
+            } | Self::MipChain {
+                array_usage: Some(TextureArrayUsage::Cubemap),
+                ..
+            } | Self::CompressedMipChain {
+                array_usage: Some(TextureArrayUsage::Cubemap),
+                ..
             }
         )
     }
 }
 
+fn mip_chain_depth_or_array_layers(
+    n_layers: u32,
+    array_usage: Option<TextureArrayUsage>,
+) -> DepthOrArrayLayers {
+    if array_usage.is_some() {
+        DepthOrArrayLayers::ArrayLayers(NonZeroU32::new(n_layers).unwrap())
+    } else {
+        DepthOrArrayLayers::Depth(NonZeroU32::new(1).unwrap())
+    }
+}
+
 /// Creates a texture for the image file represented by the given raw byte
 /// buffer, using the given configuration parameters. Mipmaps will be
 /// generated automatically.
@@ -485,6 +573,125 @@ where
     )
 }
 
+/// Creates a texture for the given mip chain, using the given configuration
+/// parameters. If an array usage is specified, each layer of the mip chain
+/// becomes an element in a texture array.
+///
+/// # Errors
+/// Returns an error if:
+/// - The mip chain has multiple layers but no array usage is specified.
+/// - The array usage is `Cubemap` and the number of layers is not six.
+/// - The image is grayscale and the color space in the configuration is not
+///   linear.
+pub fn create_texture_from_mip_chain(
+    graphics_device: &GraphicsDevice,
+    mip_chain: &MipChain,
+    array_usage: Option<TextureArrayUsage>,
+    texture_config: &TextureConfig,
+    label: &str,
+) -> Result<Texture> {
+    let meta = mip_chain.meta();
+
+    let width = NonZeroU32::new(meta.width).ok_or_else(|| anyhow!("Image width is zero"))?;
+    let height = NonZeroU32::new(meta.height).ok_or_else(|| anyhow!("Image height is zero"))?;
+    let n_layers = NonZeroU32::new(mip_chain.n_layers()).unwrap();
+
+    let depth_or_array_layers = if array_usage.is_some() {
+        DepthOrArrayLayers::ArrayLayers(n_layers)
+    } else if n_layers.get() == 1 {
+        DepthOrArrayLayers::Depth(n_layers)
+    } else {
+        bail!("Got mip chain with multiple layers for non-array texture");
+    };
+
+    let texel_description = determine_valid_texel_description(meta.pixel_format, texture_config)?;
+
+    Texture::create_with_mip_levels(
+        graphics_device,
+        mip_chain.data(),
+        width,
+        height,
+        depth_or_array_layers,
+        texel_description,
+        array_usage == Some(TextureArrayUsage::Cubemap),
+        mip_chain.level_count(),
+        label,
+    )
+}
+
+/// Creates a texture for the given block compressed mip chain, using the given
+/// configuration parameters. If an array usage is specified, each layer of the
+/// mip chain becomes an element in a texture array.
+///
+/// If the graphics device does not support BC texture compression, or the
+/// base level dimensions are not multiples of the block size, the mip chain is
+/// decompressed and uploaded uncompressed instead.
+///
+/// # Errors
+/// Returns an error if:
+/// - The mip chain has multiple layers but no array usage is specified.
+/// - The array usage is `Cubemap` and the number of layers is not six.
+/// - The compression format does not support the color space in the
+///   configuration.
+pub fn create_texture_from_compressed_mip_chain(
+    graphics_device: &GraphicsDevice,
+    mip_chain: &CompressedMipChain,
+    array_usage: Option<TextureArrayUsage>,
+    texture_config: &TextureConfig,
+    label: &str,
+) -> Result<Texture> {
+    let compression = mip_chain.compression();
+
+    if !compression.supports_color_space(texture_config.color_space) {
+        bail!(
+            "Unsupported color space {:?} for {compression:?} compressed texture",
+            texture_config.color_space
+        );
+    }
+
+    let meta = mip_chain.decompressed_meta();
+
+    let is_block_aligned = meta.width.is_multiple_of(BlockCompression::BLOCK_SIZE)
+        && meta.height.is_multiple_of(BlockCompression::BLOCK_SIZE);
+
+    if !is_block_aligned
+        || !graphics_device.supports_features(wgpu::Features::TEXTURE_COMPRESSION_BC)
+    {
+        log::debug!(
+            "Decompressing {compression:?} texture {label} on the CPU \
+             (BC compression unsupported for texture or device)"
+        );
+        return create_texture_from_mip_chain(
+            graphics_device,
+            &mip_chain.decompress(),
+            array_usage,
+            texture_config,
+            label,
+        );
+    }
+
+    let width = NonZeroU32::new(meta.width).unwrap();
+    let height = NonZeroU32::new(meta.height).unwrap();
+    let n_layers = mip_chain.n_layers();
+
+    if array_usage.is_none() && n_layers > 1 {
+        bail!("Got mip chain with multiple layers for non-array texture");
+    }
+
+    Texture::create_block_compressed_with_mip_levels(
+        graphics_device,
+        mip_chain.data(),
+        width,
+        height,
+        mip_chain_depth_or_array_layers(n_layers, array_usage),
+        compression,
+        texture_config.color_space,
+        array_usage == Some(TextureArrayUsage::Cubemap),
+        mip_chain.level_count(),
+        label,
+    )
+}
+
 /// Creates a texture from the given [`TextureCreateInfo`].
 ///
 /// Loads image or lookup table data from files specified in the info and
@@ -596,6 +803,34 @@ pub(crate) fn create_texture_from_info(
                         label,
                     )?
                 }
+                ImageTextureSource::MipChain {
+                    mip_chain,
+                    array_usage,
+                } => {
+                    verify_image_metadata(image_metadata, mip_chain.meta())?;
+
+                    create_texture_from_mip_chain(
+                        graphics_device,
+                        mip_chain,
+                        *array_usage,
+                        &texture_config,
+                        label,
+                    )?
+                }
+                ImageTextureSource::CompressedMipChain {
+                    mip_chain,
+                    array_usage,
+                } => {
+                    verify_image_metadata(image_metadata, &mip_chain.decompressed_meta())?;
+
+                    create_texture_from_compressed_mip_chain(
+                        graphics_device,
+                        mip_chain,
+                        *array_usage,
+                        &texture_config,
+                        label,
+                    )?
+                }
             };
             (texture, sampler_id)
         }
@@ -0,0 +1,579 @@
+//! Mip chains generated ahead of time on the CPU.
+
+use crate::block_compression;
+use anyhow::{Result, bail};
+use impact_gpu::texture::{BlockCompression, ColorSpace};
+use impact_io::image::{ImageMetadata, PixelFormat};
+
+/// Texel data for all mip levels of an image texture with one or more
+/// layers.
+///
+/// The levels are stored consecutively starting with the base level, and each
+/// level holds the data for all layers. This matches the layout expected by
+/// [`Texture::create_with_mip_levels`](impact_gpu::texture::Texture::create_with_mip_levels).
+#[derive(Clone, Debug)]
+pub struct MipChain {
+    meta: ImageMetadata,
+    n_layers: u32,
+    level_count: u32,
+    data: Vec<u8>,
+}
+
+impl MipChain {
+    /// Generates a mip chain for the image with the given base level metadata
+    /// and the given texel data for each layer (stored consecutively). Each
+    /// level is computed by averaging 2x2 blocks of texels in the previous
+    /// level. For sRGB images, the color channels are averaged in linear
+    /// space.
+    ///
+    /// The chain is generated down to a 1x1 level, unless a maximum number of
+    /// levels is specified.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The image width or height is zero.
+    /// - The number of layers is zero.
+    /// - The size of the texel data is inconsistent with the metadata and
+    ///   number of layers.
+    pub fn generate(
+        meta: &ImageMetadata,
+        layer_data: &[u8],
+        n_layers: u32,
+        color_space: ColorSpace,
+        max_level_count: Option<u32>,
+    ) -> Result<Self> {
+        if meta.width == 0 || meta.height == 0 {
+            bail!("Got zero width or height for mip chain base level");
+        }
+        if n_layers == 0 {
+            bail!("Got zero layers for mip chain");
+        }
+
+        let n_channels = meta.pixel_format.bytes_per_pixel();
+        let layer_size = meta.width as usize * meta.height as usize * n_channels;
+
+        if layer_data.len() != n_layers as usize * layer_size {
+            bail!(
+                "Mip chain base level data has {} bytes, expected {}",
+                layer_data.len(),
+                n_layers as usize * layer_size
+            );
+        }
+
+        let full_level_count = full_mip_chain_level_count(meta.width, meta.height);
+        let level_count = max_level_count
+            .unwrap_or(full_level_count)
+            .clamp(1, full_level_count);
+
+        let is_srgb = color_space == ColorSpace::Srgb && meta.pixel_format == PixelFormat::Rgba8;
+
+        let mut data = Vec::with_capacity(
+            (0..level_count)
+                .map(|level| {
+                    let (width, height) = mip_level_dimensions(meta.width, meta.height, level);
+                    n_layers as usize * width as usize * height as usize * n_channels
+                })
+                .sum(),
+        );
+        data.extend_from_slice(layer_data);
+
+        let mut previous_level_start = 0;
+        let (mut previous_width, mut previous_height) = (meta.width, meta.height);
+
+        for level in 1..level_count {
+            let (width, height) = mip_level_dimensions(meta.width, meta.height, level);
+
+            let previous_layer_size =
+                previous_width as usize * previous_height as usize * n_channels;
+            let level_start = data.len();
+
+            for layer in 0..n_layers as usize {
+                let previous_layer_start = previous_level_start + layer * previous_layer_size;
+
+                for j in 0..height {
+                    for i in 0..width {
+                        for channel in 0..n_channels {
+                            let is_color = is_srgb && channel < 3;
+
+                            let mut sum = 0.0;
+                            let mut count = 0.0;
+
+                            for (di, dj) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                                // Clamp to the edge for odd dimensions
+                                let pi = (2 * i + di).min(previous_width - 1) as usize;
+                                let pj = (2 * j + dj).min(previous_height - 1) as usize;
+
+                                let value = data[previous_layer_start
+                                    + (pj * previous_width as usize + pi) * n_channels
+                                    + channel];
+
+                                let value = f32::from(value) / 255.0;
+                                sum += if is_color {
+                                    srgb_to_linear(value)
+                                } else {
+                                    value
+                                };
+                                count += 1.0;
+                            }
+
+                            let mean = sum / count;
+                            let value = if is_color { linear_to_srgb(mean) } else { mean };
+
+                            data.push((value.clamp(0.0, 1.0) * 255.0).round() as u8);
+                        }
+                    }
+                }
+            }
+
+            previous_level_start = level_start;
+            previous_width = width;
+            previous_height = height;
+        }
+
+        Ok(Self {
+            meta: meta.clone(),
+            n_layers,
+            level_count,
+            data,
+        })
+    }
+
+    /// Creates a mip chain from the given previously generated texel data.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The image width or height is zero.
+    /// - The number of layers is zero.
+    /// - The number of levels is zero or exceeds the length of the full chain.
+    /// - The size of the texel data is inconsistent with the other
+    ///   parameters.
+    pub fn from_parts(
+        meta: ImageMetadata,
+        n_layers: u32,
+        level_count: u32,
+        data: Vec<u8>,
+    ) -> Result<Self> {
+        if meta.width == 0 || meta.height == 0 {
+            bail!("Got zero width or height for mip chain base level");
+        }
+        if n_layers == 0 {
+            bail!("Got zero layers for mip chain");
+        }
+
+        let full_level_count = full_mip_chain_level_count(meta.width, meta.height);
+        if level_count == 0 || level_count > full_level_count {
+            bail!(
+                "Invalid mip level count {level_count} (full mip chain has {full_level_count} levels)"
+            );
+        }
+
+        let expected_size: usize = (0..level_count)
+            .map(|level| {
+                let (width, height) = mip_level_dimensions(meta.width, meta.height, level);
+                n_layers as usize
+                    * width as usize
+                    * height as usize
+                    * meta.pixel_format.bytes_per_pixel()
+            })
+            .sum();
+
+        if data.len() != expected_size {
+            bail!(
+                "Mip chain data has {} bytes, expected {expected_size}",
+                data.len()
+            );
+        }
+
+        Ok(Self {
+            meta,
+            n_layers,
+            level_count,
+            data,
+        })
+    }
+
+    /// Returns the metadata for the base level.
+    pub fn meta(&self) -> &ImageMetadata {
+        &self.meta
+    }
+
+    /// Returns the number of layers.
+    pub fn n_layers(&self) -> u32 {
+        self.n_layers
+    }
+
+    /// Returns the number of mip levels.
+    pub fn level_count(&self) -> u32 {
+        self.level_count
+    }
+
+    /// Returns the texel data for all levels.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the texel data for the given level.
+    ///
+    /// # Panics
+    /// If the level does not exist.
+    pub fn level_data(&self, level: u32) -> &[u8] {
+        assert!(level < self.level_count);
+        let level_size = |level| {
+            let (width, height) = mip_level_dimensions(self.meta.width, self.meta.height, level);
+            self.n_layers as usize
+                * width as usize
+                * height as usize
+                * self.meta.pixel_format.bytes_per_pixel()
+        };
+        let start: usize = (0..level).map(level_size).sum();
+        &self.data[start..start + level_size(level)]
+    }
+}
+
+/// Block compressed texel data for all mip levels of an image texture with one
+/// or more layers.
+///
+/// The levels and layers are ordered like for [`MipChain`]. Each level holds
+/// whole blocks, so levels smaller than a block are padded.
+#[derive(Clone, Debug)]
+pub struct CompressedMipChain {
+    compression: BlockCompression,
+    width: u32,
+    height: u32,
+    n_layers: u32,
+    level_count: u32,
+    data: Vec<u8>,
+}
+
+impl CompressedMipChain {
+    /// Compresses each level and layer of the given mip chain with the given
+    /// compression format.
+    pub fn compress(mip_chain: &MipChain, compression: BlockCompression) -> Self {
+        let meta = mip_chain.meta();
+        let n_layers = mip_chain.n_layers();
+
+        let mut data = Vec::with_capacity(compressed_mip_chain_size(
+            compression,
+            meta.width,
+            meta.height,
+            n_layers,
+            mip_chain.level_count(),
+        ));
+
+        for level in 0..mip_chain.level_count() {
+            let (width, height) = mip_level_dimensions(meta.width, meta.height, level);
+            let level_meta = ImageMetadata {
+                width,
+                height,
+                pixel_format: meta.pixel_format,
+            };
+            let layer_size = width as usize * height as usize * meta.pixel_format.bytes_per_pixel();
+
+            for layer_data in mip_chain.level_data(level).chunks_exact(layer_size) {
+                data.extend(block_compression::compress_image(
+                    compression,
+                    &level_meta,
+                    layer_data,
+                ));
+            }
+        }
+
+        Self {
+            compression,
+            width: meta.width,
+            height: meta.height,
+            n_layers,
+            level_count: mip_chain.level_count(),
+            data,
+        }
+    }
+
+    /// Creates a compressed mip chain from the given previously compressed
+    /// data.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The base level width or height is zero.
+    /// - The number of layers is zero.
+    /// - The number of levels is zero or exceeds the length of the full chain.
+    /// - The size of the data is inconsistent with the other parameters.
+    pub fn from_parts(
+        compression: BlockCompression,
+        width: u32,
+        height: u32,
+        n_layers: u32,
+        level_count: u32,
+        data: Vec<u8>,
+    ) -> Result<Self> {
+        if width == 0 || height == 0 {
+            bail!("Got zero width or height for compressed mip chain base level");
+        }
+        if n_layers == 0 {
+            bail!("Got zero layers for compressed mip chain");
+        }
+
+        let full_level_count = full_mip_chain_level_count(width, height);
+        if level_count == 0 || level_count > full_level_count {
+            bail!(
+                "Invalid mip level count {level_count} (full mip chain has {full_level_count} levels)"
+            );
+        }
+
+        let expected_size =
+            compressed_mip_chain_size(compression, width, height, n_layers, level_count);
+
+        if data.len() != expected_size {
+            bail!(
+                "Compressed mip chain data has {} bytes, expected {expected_size}",
+                data.len()
+            );
+        }
+
+        Ok(Self {
+            compression,
+            width,
+            height,
+            n_layers,
+            level_count,
+            data,
+        })
+    }
+
+    /// Returns the compression format.
+    pub fn compression(&self) -> BlockCompression {
+        self.compression
+    }
+
+    /// Returns the metadata the base level will have after decompression.
+    pub fn decompressed_meta(&self) -> ImageMetadata {
+        ImageMetadata {
+            width: self.width,
+            height: self.height,
+            pixel_format: block_compression::decompressed_pixel_format(self.compression),
+        }
+    }
+
+    /// Returns the number of layers.
+    pub fn n_layers(&self) -> u32 {
+        self.n_layers
+    }
+
+    /// Returns the number of mip levels.
+    pub fn level_count(&self) -> u32 {
+        self.level_count
+    }
+
+    /// Returns the compressed data for all levels.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Decompresses all levels and layers into an uncompressed mip chain.
+    pub fn decompress(&self) -> MipChain {
+        let meta = self.decompressed_meta();
+
+        let mut data = Vec::with_capacity(
+            (0..self.level_count)
+                .map(|level| {
+                    let (width, height) = mip_level_dimensions(self.width, self.height, level);
+                    self.n_layers as usize
+                        * width as usize
+                        * height as usize
+                        * meta.pixel_format.bytes_per_pixel()
+                })
+                .sum(),
+        );
+
+        let mut offset = 0;
+        for level in 0..self.level_count {
+            let (width, height) = mip_level_dimensions(self.width, self.height, level);
+            let layer_size = self.compression.n_bytes_for_image(width, height) as usize;
+
+            for _ in 0..self.n_layers {
+                data.extend(block_compression::decompress_image(
+                    self.compression,
+                    width,
+                    height,
+                    &self.data[offset..offset + layer_size],
+                ));
+                offset += layer_size;
+            }
+        }
+
+        MipChain {
+            meta,
+            n_layers: self.n_layers,
+            level_count: self.level_count,
+            data,
+        }
+    }
+}
+
+/// Returns the number of levels in a full mip chain for an image with the
+/// given dimensions.
+pub fn full_mip_chain_level_count(width: u32, height: u32) -> u32 {
+    u32::BITS - width.max(height).max(1).leading_zeros()
+}
+
+/// Returns the dimensions of the given mip level for an image with the given
+/// base level dimensions.
+pub fn mip_level_dimensions(width: u32, height: u32, level: u32) -> (u32, u32) {
+    ((width >> level).max(1), (height >> level).max(1))
+}
+
+fn compressed_mip_chain_size(
+    compression: BlockCompression,
+    width: u32,
+    height: u32,
+    n_layers: u32,
+    level_count: u32,
+) -> usize {
+    (0..level_count)
+        .map(|level| {
+            let (width, height) = mip_level_dimensions(width, height, level);
+            n_layers as usize * compression.n_bytes_for_image(width, height) as usize
+        })
+        .sum()
+}
+
+fn srgb_to_linear(srgb_value: f32) -> f32 {
+    if srgb_value <= 0.04045 {
+        srgb_value / 12.92
+    } else {
+        ((srgb_value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(linear_value: f32) -> f32 {
+    if linear_value <= 0.0031308 {
+        linear_value * 12.92
+    } else {
+        linear_value.powf(1.0 / 2.4) * 1.055 - 0.055
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn luma_meta(width: u32, height: u32) -> ImageMetadata {
+        ImageMetadata {
+            width,
+            height,
+            pixel_format: PixelFormat::Luma8,
+        }
+    }
+
+    #[test]
+    fn full_mip_chain_level_count_is_correct() {
+        assert_eq!(full_mip_chain_level_count(1, 1), 1);
+        assert_eq!(full_mip_chain_level_count(2, 1), 2);
+        assert_eq!(full_mip_chain_level_count(256, 64), 9);
+        assert_eq!(full_mip_chain_level_count(5, 3), 3);
+    }
+
+    #[test]
+    fn mip_chain_for_luma_image_averages_blocks() {
+        let data = [
+            0, 100, 50, 150, 200, 200, 200, 200, 10, 10, 30, 30, 10, 10, 30, 30,
+        ];
+        let mip_chain =
+            MipChain::generate(&luma_meta(4, 4), &data, 1, ColorSpace::Linear, None).unwrap();
+
+        assert_eq!(mip_chain.level_count(), 3);
+        assert_eq!(mip_chain.level_data(0), &data);
+        assert_eq!(mip_chain.level_data(1), &[125, 150, 10, 30]);
+        assert_eq!(mip_chain.level_data(2), &[79]);
+    }
+
+    #[test]
+    fn mip_chain_respects_max_level_count() {
+        let mip_chain =
+            MipChain::generate(&luma_meta(4, 4), &[0; 16], 1, ColorSpace::Linear, Some(2)).unwrap();
+        assert_eq!(mip_chain.level_count(), 2);
+        assert_eq!(mip_chain.data().len(), 16 + 4);
+    }
+
+    #[test]
+    fn mip_chain_keeps_layers_separate() {
+        let data = [10, 10, 10, 10, 90, 90, 90, 90];
+        let mip_chain =
+            MipChain::generate(&luma_meta(2, 2), &data, 2, ColorSpace::Linear, None).unwrap();
+        assert_eq!(mip_chain.level_data(1), &[10, 90]);
+    }
+
+    #[test]
+    fn srgb_mip_chain_averages_colors_in_linear_space() {
+        let meta = ImageMetadata {
+            width: 2,
+            height: 1,
+            pixel_format: PixelFormat::Rgba8,
+        };
+        let data = [0, 0, 0, 0, 255, 255, 255, 255];
+        let mip_chain = MipChain::generate(&meta, &data, 1, ColorSpace::Srgb, None).unwrap();
+
+        let level = mip_chain.level_data(1);
+        let expected_color = (linear_to_srgb(0.5) * 255.0).round() as u8;
+        assert_eq!(
+            level,
+            &[expected_color, expected_color, expected_color, 128]
+        );
+    }
+
+    #[test]
+    fn mip_chain_from_parts_validates_size() {
+        assert!(MipChain::from_parts(luma_meta(4, 4), 1, 3, vec![0; 21]).is_ok());
+        assert!(MipChain::from_parts(luma_meta(4, 4), 1, 3, vec![0; 20]).is_err());
+        assert!(MipChain::from_parts(luma_meta(4, 4), 1, 4, vec![0; 22]).is_err());
+    }
+
+    #[test]
+    fn compressed_mip_chain_pads_small_levels_to_whole_blocks() {
+        let data: Vec<u8> = (0..2 * 8 * 8).map(|i| (i % 256) as u8).collect();
+        let mip_chain =
+            MipChain::generate(&luma_meta(8, 8), &data, 2, ColorSpace::Linear, None).unwrap();
+        let compressed = CompressedMipChain::compress(&mip_chain, BlockCompression::Bc4);
+
+        assert_eq!(compressed.level_count(), 4);
+        // 4 + 1 + 1 + 1 blocks of 8 bytes per layer
+        assert_eq!(compressed.data().len(), 2 * 7 * 8);
+        assert!(
+            CompressedMipChain::from_parts(
+                BlockCompression::Bc4,
+                8,
+                8,
+                2,
+                4,
+                compressed.data().to_vec()
+            )
+            .is_ok()
+        );
+        assert!(
+            CompressedMipChain::from_parts(BlockCompression::Bc4, 8, 8, 2, 4, vec![0; 100])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn decompressed_mip_chain_approximates_original() {
+        let data: Vec<u8> = (0..8 * 8).map(|i| (i * 4) as u8).collect();
+        let mip_chain =
+            MipChain::generate(&luma_meta(8, 8), &data, 1, ColorSpace::Linear, None).unwrap();
+        let decompressed =
+            CompressedMipChain::compress(&mip_chain, BlockCompression::Bc4).decompress();
+
+        assert_eq!(decompressed.meta(), mip_chain.meta());
+        assert_eq!(decompressed.level_count(), mip_chain.level_count());
+        // The blocks span up to 216 values, interpolated in 7 steps
+        for (original, decompressed) in mip_chain.data().iter().zip(decompressed.data()) {
+            assert!(original.abs_diff(*decompressed) <= 16);
+        }
+    }
+
+    #[test]
+    fn generating_mip_chain_with_wrong_data_size_fails() {
+        assert!(
+            MipChain::generate(&luma_meta(4, 4), &[0; 15], 1, ColorSpace::Linear, None).is_err()
+        );
+    }
+}
@@ -1,6 +1,8 @@
 //! Processing of imported textures.
 
+use crate::mip_chain::{CompressedMipChain, MipChain};
 use impact_alloc::{AVec, Allocator};
+use impact_gpu::texture::{BlockCompression, ColorSpace};
 use impact_io::image::{Image, ImageMetadata, PixelFormat};
 
 /// Processing operations for a texture image.
@@ -13,6 +15,10 @@ use impact_io::image::{Image, ImageMetadata, PixelFormat};
 pub struct ImageProcessing {
     /// Format conversion operations.
     pub format_conversions: Vec<FormatConversion>,
+    /// Block compression to encode the texture with when its mip chain is
+    /// generated ahead of time (when importing into a resource database).
+    /// Textures loaded directly from images are uploaded uncompressed.
+    pub compression: Option<BlockCompression>,
 }
 
 /// Conversion between texture representations.
@@ -37,9 +43,32 @@ impl ImageProcessing {
     pub fn none() -> Self {
         Self {
             format_conversions: Vec::new(),
+            compression: None,
         }
     }
 
+    /// Encodes the given mip chain with the block compression specified for
+    /// this processing, or returns [`None`] if no compression is specified.
+    ///
+    /// # Errors
+    /// Returns an error if the compression format does not support the given
+    /// color space.
+    pub fn compress(
+        &self,
+        mip_chain: &MipChain,
+        color_space: ColorSpace,
+    ) -> anyhow::Result<Option<CompressedMipChain>> {
+        let Some(compression) = self.compression else {
+            return Ok(None);
+        };
+        if !compression.supports_color_space(color_space) {
+            anyhow::bail!(
+                "Block compression {compression:?} does not support color space {color_space:?}"
+            );
+        }
+        Ok(Some(CompressedMipChain::compress(mip_chain, compression)))
+    }
+
     /// Executes the processing operations on the given image, returning the
     /// processed image or [`None`] if there were no operations to perform.
     pub fn execute<A, IA>(&self, arena: A, image: &Image<IA>) -> Option<Image<A>>
@@ -0,0 +1,12 @@
+#![no_main]
+
+use impact_voxel::{
+    generation::SDFVoxelGenerator,
+    interaction::deposition::fuzzing::fuzz_test_depositing_voxels_within_capsule,
+    object::intersection::fuzzing::ArbitraryCapsule,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (SDFVoxelGenerator, Vec<ArbitraryCapsule>, u8)| {
+    fuzz_test_depositing_voxels_within_capsule(input);
+});
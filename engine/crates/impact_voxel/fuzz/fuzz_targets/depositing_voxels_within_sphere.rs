@@ -0,0 +1,12 @@
+#![no_main]
+
+use impact_voxel::{
+    generation::SDFVoxelGenerator,
+    interaction::deposition::fuzzing::fuzz_test_depositing_voxels_within_sphere,
+    object::intersection::fuzzing::ArbitrarySphere,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (SDFVoxelGenerator, ArbitrarySphere, u8)| {
+    fuzz_test_depositing_voxels_within_sphere(input);
+});
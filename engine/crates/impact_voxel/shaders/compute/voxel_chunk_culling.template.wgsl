@@ -7,6 +7,9 @@ struct PushConstants {
     frustumPlane5: array<f32, 4>,
     mostInsideCorners: array<u32, 6>,
     apexPosition: array<f32, 3>,
+    lodDistanceScale: f32,
+    lodDistanceOffset: f32,
+    inverseLogLODLevelFactor: f32,
     chunkCount: u32,
     instanceIdx: u32,
 }
@@ -18,6 +21,7 @@ struct ChunkSubmesh {
     indexOffset: u32,
     indexCount: u32,
     isObscuredFromDirection: array<array<array<u32, 2>, 2>, 2>,
+    lodIndexCounts: array<u32, 4>,
 }
 
 #if (for_indexed_draw_calls)
@@ -99,6 +103,26 @@ fn chunkIsObscured(chunkSubmesh: ChunkSubmesh, lowerCorner: vec3f) -> bool {
     return isObscuredFromDirection[indices.x][indices.y][indices.z] > 0u;
 }
 
+const MAX_LOD: u32 = 3u;
+
+fn selectLOD(lowerCorner: vec3f) -> u32 {
+    // Find the distance from the frustum apex to the closest point in the chunk
+    let apexPosition = vec3f(pcs.apexPosition[0], pcs.apexPosition[1], pcs.apexPosition[2]);
+    let closestPoint = clamp(apexPosition, lowerCorner, lowerCorner + vec3f(1.0));
+    let distance = length(closestPoint - apexPosition);
+
+    // This is the inverse of the ratio between the projected size of a voxel
+    // and the threshold size for the first downsampled level of detail
+    let inverseRelativeSize = pcs.lodDistanceScale * distance + pcs.lodDistanceOffset;
+
+    if (inverseRelativeSize <= 1.0) {
+        return 0u;
+    }
+
+    let lod = u32(floor(log(inverseRelativeSize) * pcs.inverseLogLODLevelFactor)) + 1u;
+    return min(lod, MAX_LOD);
+}
+
 const WORKGROUP_SIZE: u32 = {{workgroup_size}};
 
 @compute @workgroup_size(WORKGROUP_SIZE, 1, 1)
@@ -120,9 +144,18 @@ fn main(
             indirectDrawArgs[globalIdx].indexCount = 0u;
             indirectDrawArgs[globalIdx].instanceCount = 0u;
         } else {
-            indirectDrawArgs[globalIdx].indexCount = chunkSubmesh.indexCount;
+            let lod = selectLOD(chunkIndices);
+
+            // The indices for each level of detail follow directly after those
+            // for the previous level
+            var firstIndex = chunkSubmesh.indexOffset;
+            for (var level = 0u; level < lod; level++) {
+                firstIndex += chunkSubmesh.lodIndexCounts[level];
+            }
+
+            indirectDrawArgs[globalIdx].indexCount = chunkSubmesh.lodIndexCounts[lod];
             indirectDrawArgs[globalIdx].instanceCount = 1u;
-            indirectDrawArgs[globalIdx].firstIndex = chunkSubmesh.indexOffset;
+            indirectDrawArgs[globalIdx].firstIndex = firstIndex;
             indirectDrawArgs[globalIdx].firstInstance = instanceIdx;
         }
     }
@@ -7,6 +7,9 @@ struct PushConstants {
     originOffsetInRootX: f32,
     originOffsetInRootY: f32,
     originOffsetInRootZ: f32,
+    entityIDLower: u32,
+    entityIDUpper: u32,
+    inverseVoxelExtent: f32,
 }
 
 struct ProjectionUniform {
@@ -42,6 +45,10 @@ struct FragmentInput {
     @location(5) @interpolate(flat) materialIndices: vec4u,
     @location(6) materialWeights: vec4f,
     @location(7) uniformMaterialProperties: vec4f,
+    @location(8) temperature: f32,
+#if (writes_entity_id)
+    @location(9) modelSpacePosition: vec3f,
+#endif
 }
 
 struct FragmentOutput {
@@ -50,16 +57,36 @@ struct FragmentOutput {
     @location(2) motionVector: vec2f,
     @location(3) materialColor: vec4f,
     @location(4) materialProperties: vec4f,
+#if (writes_entity_id)
+    @location(5) entityID: vec4u,
+#endif
 }
 
 const JITTER_COUNT: u32 = {{jitter_count}};
 
+#if (writes_entity_id)
+// Identifies the kind of entity in the last component of the entity ID output
+const VOXEL_OBJECT_ENTITY_KIND: u32 = 2;
+#endif
+
 const TEXTURE_FREQUENCY: f32 = {{texture_frequency}};
 
 const FACE_NORMAL_TRANSITION_START_ALIGNMENT: f32 = 0.8;
 const FACE_NORMAL_TRANSITION_END_ALIGNMENT: f32 = 0.6;
 const FACE_NORMAL_TRANSITION_NORM: f32 = 1.0 / (FACE_NORMAL_TRANSITION_START_ALIGNMENT - FACE_NORMAL_TRANSITION_END_ALIGNMENT);
 
+// Temperature (in K) at which hot surfaces start to visibly glow (the Draper
+// point), and the temperature range over which the glow fades in
+const INCANDESCENCE_START_TEMPERATURE: f32 = 798.0;
+const INCANDESCENCE_FADE_IN_TEMPERATURE_RANGE: f32 = 200.0;
+
+// The emissive luminance (in cd/m²) of a glowing surface at the reference
+// temperature. The luminance is assumed to scale with a high power of the
+// temperature, roughly matching the steep rise in visible blackbody radiation.
+const INCANDESCENCE_REFERENCE_TEMPERATURE: f32 = 1500.0;
+const INCANDESCENCE_REFERENCE_LUMINANCE: f32 = 1e4;
+const INCANDESCENCE_LUMINANCE_EXPONENT: f32 = 8.0;
+
 var<push_constant> pushConstants: PushConstants;
 
 @group({{projection_uniform_group}}) @binding({{projection_uniform_binding}})
@@ -89,6 +116,9 @@ var<storage, read> modelSpaceVertexPositions: array<f32>;
 @group({{position_and_normal_group}}) @binding({{normal_buffer_binding}})
 var<storage, read> modelSpaceVertexNormalVectors: array<f32>;
 
+@group({{position_and_normal_group}}) @binding({{temperature_buffer_binding}})
+var<storage, read> vertexTemperatures: array<f32>;
+
 fn transformPosition(
     rotationQuaternion: vec4f,
     translation: vec3f,
@@ -236,6 +266,58 @@ fn computeGGXRoughnessFromPerceptuallyLinearRoughness(linearRoughness: f32) -> f
     return linearRoughness * linearRoughness;
 }
 
+#if (writes_entity_id)
+// Computes the indices of the voxel whose surface the fragment lies on and
+// packs them together with the entity kind into two 32-bit integers
+fn computePackedVoxelIndices(modelSpacePosition: vec3f, modelSpaceNormalVector: vec3f) -> vec2u {
+    // Step half a voxel into the surface so that we end up inside the voxel
+    let voxelSpacePosition = modelSpacePosition * pushConstants.inverseVoxelExtent - 0.5 * modelSpaceNormalVector;
+    let indices = vec3u(clamp(floor(voxelSpacePosition), vec3f(0.0), vec3f(65535.0)));
+    return vec2u(indices.x | (indices.y << 16u), indices.z | (VOXEL_OBJECT_ENTITY_KIND << 16u));
+}
+#endif
+
+// Approximates the linear RGB color of blackbody radiation at the given
+// temperature, normalized so that the largest component is one. Based on the
+// curve fit by Tanner Helland.
+fn computeBlackbodyColor(temperature: f32) -> vec3f {
+    let scaledTemperature = 0.01 * temperature;
+
+    var sRGBColor: vec3f;
+    if (scaledTemperature <= 66.0) {
+        sRGBColor.r = 1.0;
+        sRGBColor.g = 0.3900815788 * log(scaledTemperature) - 0.6318414438;
+    } else {
+        sRGBColor.r = 1.292936186 * pow(scaledTemperature - 60.0, -0.1332047592);
+        sRGBColor.g = 1.129890861 * pow(scaledTemperature - 60.0, -0.0755148492);
+    }
+    if (scaledTemperature >= 66.0) {
+        sRGBColor.b = 1.0;
+    } else if (scaledTemperature <= 19.0) {
+        sRGBColor.b = 0.0;
+    } else {
+        sRGBColor.b = 0.5432067891 * log(scaledTemperature - 10.0) - 1.19625408914;
+    }
+
+    return pow(clamp(sRGBColor, vec3f(0.0), vec3f(1.0)), vec3f(2.2));
+}
+
+// Computes the weight for blending the surface color towards the blackbody
+// color, and the emissive luminance (in cd/m²) due to incandescence, for a
+// surface at the given temperature
+fn computeIncandescence(temperature: f32) -> vec2f {
+    let glowWeight = smoothstep(
+        INCANDESCENCE_START_TEMPERATURE,
+        INCANDESCENCE_START_TEMPERATURE + INCANDESCENCE_FADE_IN_TEMPERATURE_RANGE,
+        temperature,
+    );
+    let luminance = INCANDESCENCE_REFERENCE_LUMINANCE * pow(
+        temperature / INCANDESCENCE_REFERENCE_TEMPERATURE,
+        INCANDESCENCE_LUMINANCE_EXPONENT,
+    );
+    return vec2f(glowWeight, glowWeight * luminance);
+}
+
 fn computeFaceNormal(position: vec3f) -> vec3f {
     let dx = dpdx(position);
     let dy = dpdy(position);
@@ -264,6 +346,7 @@ fn mainVS(
         modelSpaceVertexNormalVectors[yCompIdx],
         modelSpaceVertexNormalVectors[zCompIdx],
     );
+    output.temperature = vertexTemperatures[vertex.vertexIndex];
 
     let projectionMatrix = obtainProjectionMatrix();
 
@@ -300,6 +383,10 @@ fn mainVS(
         pushConstants.originOffsetInRootZ,
     ) + modelSpacePosition;
 
+#if (writes_entity_id)
+    output.modelSpacePosition = modelSpacePosition;
+#endif
+
     return output;
 }
 
@@ -356,7 +443,13 @@ fn mainFS(fragment: FragmentInput) -> FragmentOutput {
         fragment.materialIndices,
         materialWeights,
     ).rgb;
-    output.materialColor = vec4f(color, 1.0);
+
+    // Hot voxels glow with the color of blackbody radiation. Since the
+    // emissive color is the material color scaled by the emissive luminance,
+    // we blend the material color towards the blackbody color.
+    let incandescence = computeIncandescence(fragment.temperature);
+    let glowColor = computeBlackbodyColor(fragment.temperature);
+    output.materialColor = vec4f(mix(color, glowColor, incandescence.x), 1.0);
 
     var roughness = triplanarSampleAndBlendTextures(
         materialRoughnessTextures,
@@ -386,9 +479,17 @@ fn mainFS(fragment: FragmentInput) -> FragmentOutput {
     roughness = computeGGXRoughnessFromPerceptuallyLinearRoughness(roughness);
 
     let metalness = materialProperties.z;
-    let preExposedEmissiveLuminance = pushConstants.exposure * materialProperties.w;
+    let preExposedEmissiveLuminance = pushConstants.exposure * (materialProperties.w + incandescence.y);
 
     output.materialProperties = vec4f(specularReflectance, roughness, metalness, preExposedEmissiveLuminance);
 
+#if (writes_entity_id)
+    output.entityID = vec4u(
+        pushConstants.entityIDLower,
+        pushConstants.entityIDUpper,
+        computePackedVoxelIndices(fragment.modelSpacePosition, modelSpaceNormalVector),
+    );
+#endif
+
     return output;
 }
@@ -1,16 +1,20 @@
 //! Generation of spatial voxel distributions.
 
 pub mod import;
+pub mod pregenerated;
 pub mod sdf;
+pub mod vox;
 pub mod voxel_type;
+pub mod voxelization;
 
 use crate::{
     Voxel, VoxelSignedDistance,
     generation::sdf::meta::MetaSDFGraph,
     object::{ChunkSparseness, LoopForChunkVoxels, VoxelObject},
-    voxel_types::VoxelType,
+    voxel_types::{VoxelType, VoxelTypeRegistry},
 };
-use impact_alloc::{Allocator, Global};
+use anyhow::Context;
+use impact_alloc::{Allocator, Global, arena::ArenaPool};
 use impact_geometry::AxisAlignedBox;
 use impact_math::{hash64, point::Point3, stringhash64_newtype, vector::Vector3};
 use impact_resource::{Resource, ResourceID, registry::ImmutableResourceRegistry};
@@ -118,6 +122,45 @@ impl Resource for VoxelGenerator {
     type ID = VoxelGeneratorID;
 }
 
+impl VoxelGenerator {
+    /// Creates a voxel generator with the given voxel extent by compiling the
+    /// meta SDF graph with the given scale factor and seed. The given voxel
+    /// type generator is used for all voxels not assigned a type by the graph.
+    ///
+    /// # Errors
+    /// Returns an error if the graph can not be compiled or references voxel
+    /// types that are not in the registry.
+    pub fn create_sdf_voxel_generator(
+        &self,
+        voxel_extent: f32,
+        scale_factor: f32,
+        seed: u64,
+        voxel_type_generator: VoxelTypeGenerator,
+        voxel_type_registry: &VoxelTypeRegistry,
+    ) -> anyhow::Result<SDFVoxelGenerator> {
+        let arena = ArenaPool::get_arena();
+
+        let graph = self
+            .sdf_graph
+            .build_in(&arena, scale_factor, seed)
+            .context("Failed to compile meta SDF graph into atomic graph")?;
+
+        let sdf_generator = graph
+            .build_in(Global)
+            .context("Failed to build SDF generator from atomic graph")?;
+
+        let voxel_type_generator = voxel_type_generator
+            .with_graph(graph.voxel_type_graph(), voxel_type_registry)
+            .context("Failed to create voxel type generator")?;
+
+        Ok(SDFVoxelGenerator::new(
+            voxel_extent,
+            sdf_generator,
+            voxel_type_generator,
+        ))
+    }
+}
+
 #[cfg(feature = "serde")]
 impl<A: Allocator> serde::Serialize for VoxelGenerator<A> {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
@@ -262,6 +305,100 @@ impl<A: Allocator> SDFVoxelGenerator<A> {
     pub fn grid_center(&self) -> Point3 {
         self.shifted_grid_center + Vector3::same(0.5) // Unshift
     }
+
+    /// Returns the axis-aligned box outside of which the signed distance
+    /// field is guaranteed to be positive, in the root SDF coordinate space.
+    /// The coordinates are in whole voxels.
+    pub fn sdf_domain(&self) -> &AxisAlignedBox {
+        self.sdf_generator.domain()
+    }
+
+    /// Generates voxels for a single chunk whose lower corner voxel has its
+    /// center at the given position in the root SDF coordinate space, without
+    /// restricting the voxels to the bounded grid of this generator. This is
+    /// used for generating voxels in an unbounded grid, like for terrain.
+    pub fn generate_unbounded_chunk<AB: Allocator>(
+        &self,
+        buffers: &mut SDFVoxelGeneratorChunkBuffers<AB>,
+        voxels: &mut [Voxel],
+        chunk_origin_in_root_space: &Point3,
+    ) -> ChunkSparseness {
+        assert_eq!(voxels.len(), VoxelObject::chunk_voxel_count());
+
+        if self.sdf_generator.is_empty() {
+            voxels.fill(Voxel::maximally_outside());
+            return ChunkSparseness {
+                has_only_empty_voxels: true,
+                is_void: true,
+            };
+        }
+
+        self.generate_chunk_at_root_space_origin(
+            buffers,
+            voxels,
+            chunk_origin_in_root_space,
+            |_| false,
+        )
+    }
+
+    fn generate_chunk_at_root_space_origin<AB: Allocator>(
+        &self,
+        buffers: &mut SDFVoxelGeneratorChunkBuffers<AB>,
+        voxels: &mut [Voxel],
+        chunk_origin_in_root_space: &Point3,
+        is_outside_grid: impl Fn([usize; 3]) -> bool,
+    ) -> ChunkSparseness {
+        let chunk_aabb_in_root_space = AxisAlignedBox::new(
+            *chunk_origin_in_root_space,
+            chunk_origin_in_root_space + Vector3::same(VoxelObject::chunk_size() as f32),
+        );
+
+        self.sdf_generator
+            .compute_signed_distances_for_chunk(&mut buffers.sdf, &chunk_aabb_in_root_space);
+
+        let signed_distances = buffers.sdf.final_signed_distances();
+
+        let mut chunk_has_only_empty_voxels = true;
+        let mut chunk_is_void = true;
+
+        LoopForChunkVoxels::over_all().execute_with_linear_idx(&mut |&indices_in_chunk, idx| {
+            voxels[idx] = if is_outside_grid(indices_in_chunk) {
+                Voxel::maximally_outside()
+            } else {
+                let signed_distance = signed_distances[idx];
+                let voxel_signed_distance = VoxelSignedDistance::from_f32(signed_distance);
+
+                if voxel_signed_distance.is_negative() {
+                    chunk_has_only_empty_voxels = false;
+                    chunk_is_void = false;
+                    Voxel::non_empty(VoxelType::dummy(), voxel_signed_distance)
+                } else {
+                    if !voxel_signed_distance.is_void() {
+                        chunk_is_void = false;
+                    }
+                    Voxel::empty(voxel_signed_distance)
+                }
+            };
+        });
+
+        if !chunk_has_only_empty_voxels {
+            self.voxel_type_generator.set_voxel_types_for_chunk(
+                voxels,
+                &mut buffers.voxel_type,
+                chunk_origin_in_root_space,
+                signed_distances,
+                buffers
+                    .sdf
+                    .final_voxel_type_tags()
+                    .map(|tags| tags.as_slice()),
+            );
+        }
+
+        ChunkSparseness {
+            has_only_empty_voxels: chunk_has_only_empty_voxels,
+            is_void: chunk_is_void,
+        }
+    }
 }
 
 impl<A: Allocator> ChunkedVoxelGenerator for SDFVoxelGenerator<A> {
@@ -314,60 +451,16 @@ impl<A: Allocator> ChunkedVoxelGenerator for SDFVoxelGenerator<A> {
         let chunk_origin_in_root_space =
             Point3::from(chunk_origin.map(|idx| idx as f32)) - self.shifted_grid_center.as_vector();
 
-        let chunk_aabb_in_root_space = AxisAlignedBox::new(
-            chunk_origin_in_root_space,
-            chunk_origin_in_root_space + Vector3::same(VoxelObject::chunk_size() as f32),
-        );
-
-        self.sdf_generator
-            .compute_signed_distances_for_chunk(&mut buffers.sdf, &chunk_aabb_in_root_space);
-
-        let signed_distances = buffers.sdf.final_signed_distances();
-
-        let mut chunk_has_only_empty_voxels = true;
-        let mut chunk_is_void = true;
-
-        LoopForChunkVoxels::over_all().execute_with_linear_idx(
-            &mut |&[i_in_chunk, j_in_chunk, k_in_chunk], idx| {
-                let i = chunk_origin[0] + i_in_chunk;
-                let j = chunk_origin[1] + j_in_chunk;
-                let k = chunk_origin[2] + k_in_chunk;
-
-                voxels[idx] = if i >= self.grid_shape[0]
-                    || j >= self.grid_shape[1]
-                    || k >= self.grid_shape[2]
-                {
-                    Voxel::maximally_outside()
-                } else {
-                    let signed_distance = signed_distances[idx];
-                    let voxel_signed_distance = VoxelSignedDistance::from_f32(signed_distance);
-
-                    if voxel_signed_distance.is_negative() {
-                        chunk_has_only_empty_voxels = false;
-                        chunk_is_void = false;
-                        Voxel::non_empty(VoxelType::dummy(), voxel_signed_distance)
-                    } else {
-                        if !voxel_signed_distance.is_void() {
-                            chunk_is_void = false;
-                        }
-                        Voxel::empty(voxel_signed_distance)
-                    }
-                };
+        self.generate_chunk_at_root_space_origin(
+            buffers,
+            voxels,
+            &chunk_origin_in_root_space,
+            |[i_in_chunk, j_in_chunk, k_in_chunk]| {
+                chunk_origin[0] + i_in_chunk >= self.grid_shape[0]
+                    || chunk_origin[1] + j_in_chunk >= self.grid_shape[1]
+                    || chunk_origin[2] + k_in_chunk >= self.grid_shape[2]
             },
-        );
-
-        if !chunk_has_only_empty_voxels {
-            self.voxel_type_generator.set_voxel_types_for_chunk(
-                voxels,
-                &mut buffers.voxel_type,
-                &chunk_origin_in_root_space,
-            );
-        }
-
-        ChunkSparseness {
-            has_only_empty_voxels: chunk_has_only_empty_voxels,
-            is_void: chunk_is_void,
-        }
+        )
     }
 }
 
@@ -377,7 +470,13 @@ pub mod fuzzing {
     use crate::{
         generation::{
             sdf::{BoxSDF, CapsuleSDF, SDFNode, SphereSDF},
-            voxel_type::{GradientNoiseVoxelTypeGenerator, SameVoxelTypeGenerator},
+            voxel_type::{
+                GradientNoiseVoxelTypeGenerator, SameVoxelTypeGenerator,
+                graph::{
+                    GraphVoxelTypeGenerator, VoxelTypeNode, VoxelTypeSelection,
+                    VoxelTypeSelectionQuantity,
+                },
+            },
         },
         voxel_types::VoxelTypeRegistry,
     };
@@ -517,6 +616,31 @@ pub mod fuzzing {
         }
     }
 
+    impl Arbitrary<'_> for GraphVoxelTypeGenerator {
+        fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
+            let fallback = SameVoxelTypeGenerator::arbitrary(u)?;
+            let surface_voxel_type = VoxelType::from_idx_u8(u.arbitrary()?);
+            let interior_voxel_type = VoxelType::from_idx_u8(u.arbitrary()?);
+            let depth = 10.0 * arbitrary_norm_f32(u)?;
+            let nodes = vec![
+                VoxelTypeNode::VoxelType(surface_voxel_type),
+                VoxelTypeNode::VoxelType(interior_voxel_type),
+                VoxelTypeNode::Selection(VoxelTypeSelection {
+                    quantity: VoxelTypeSelectionQuantity::Depth,
+                    threshold: depth,
+                    below_id: 0,
+                    above_id: 1,
+                }),
+            ];
+            Ok(Self::from_resolved_nodes(nodes, vec![2], fallback.into()))
+        }
+
+        fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+            let size = 3 * mem::size_of::<u8>() + mem::size_of::<i32>();
+            (size, Some(size))
+        }
+    }
+
     fn arbitrary_norm_f64(u: &mut Unstructured<'_>) -> Result<f64> {
         Ok(f64::from(u.int_in_range(0..=1000000)?) / 1000000.0)
     }
@@ -1,6 +1,10 @@
 //! Importing voxel object generators from declarations.
 
-use crate::generation::VoxelGeneratorID;
+use crate::generation::{
+    VoxelGeneratorID,
+    vox::{VoxModel, VoxModelID, VoxModelRegistry},
+    voxelization::{VoxelizedMesh, VoxelizedMeshID, VoxelizedMeshPart, VoxelizedMeshRegistry},
+};
 use std::path::{Path, PathBuf};
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -8,6 +12,43 @@ use std::path::{Path, PathBuf};
 pub struct VoxelGeneratorDeclaration {
     pub id: VoxelGeneratorID,
     pub path: PathBuf,
+    /// Voxel objects to generate with the generator when importing it into a
+    /// resource database, so that they can be loaded instead of generated at
+    /// runtime. Ignored when loading the generator directly.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pregenerated_objects: Vec<PregeneratedVoxelObjectDeclaration>,
+}
+
+/// A declaration of a voxel object to generate ahead of time with a voxel
+/// generator. The parameters correspond to those of a
+/// [`GeneratedVoxelObject`](crate::setup::GeneratedVoxelObject) combined with a
+/// [`SameVoxelType`](crate::setup::SameVoxelType).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct PregeneratedVoxelObjectDeclaration {
+    pub voxel_extent: f32,
+    pub scale_factor: f32,
+    pub seed: u64,
+    /// The name of the voxel type for all voxels not assigned a type by the
+    /// generator.
+    pub voxel_type: String,
+}
+
+/// A declaration of a voxel object generated from triangle meshes. The meshes
+/// are referenced by ID and must be declared separately.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct VoxelizedMeshDeclaration {
+    pub id: VoxelizedMeshID,
+    pub parts: Vec<VoxelizedMeshPart>,
+}
+
+/// A declaration of a voxel model in the MagicaVoxel `.vox` format.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct VoxModelDeclaration {
+    pub id: VoxModelID,
+    pub path: PathBuf,
 }
 
 impl VoxelGeneratorDeclaration {
@@ -18,6 +59,14 @@ impl VoxelGeneratorDeclaration {
     }
 }
 
+impl VoxModelDeclaration {
+    /// Resolves all paths in the declaration by prepending the given root path
+    /// to all paths.
+    pub fn resolve_paths(&mut self, root_path: &Path) {
+        self.path = root_path.join(&self.path);
+    }
+}
+
 /// Loads all voxel generators in the given declarations and stores them in the
 /// voxel generator registry.
 ///
@@ -70,3 +119,102 @@ pub fn load_declared_voxel_generator(
 
     Ok(id)
 }
+
+/// Stores the voxelized meshes in the given declarations in the voxelized mesh
+/// registry.
+///
+/// # Errors
+/// See [`load_declared_voxelized_mesh`].
+pub fn load_declared_voxelized_meshes(
+    registry: &mut VoxelizedMeshRegistry,
+    declarations: &[VoxelizedMeshDeclaration],
+) -> anyhow::Result<()> {
+    for declaration in declarations {
+        if let Err(error) = load_declared_voxelized_mesh(registry, declaration) {
+            // Failing to load a voxelized mesh is not fatal, since we might not need it
+            log::error!(
+                "Failed to load voxelized mesh {}: {error:#}",
+                declaration.id
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Stores the voxelized mesh in the given declaration in the voxelized mesh
+/// registry. The referenced triangle meshes are not resolved until a voxel
+/// object is generated from the voxelized mesh.
+///
+/// # Errors
+/// Returns an error if:
+/// - Another voxelized mesh with the same name is already loaded.
+/// - The declaration has no parts.
+pub fn load_declared_voxelized_mesh(
+    registry: &mut VoxelizedMeshRegistry,
+    declaration: &VoxelizedMeshDeclaration,
+) -> anyhow::Result<VoxelizedMeshID> {
+    let id = declaration.id;
+
+    log::debug!("Loading voxelized mesh `{id}`");
+
+    if registry.contains(id) {
+        anyhow::bail!("Tried to load voxelized mesh under already existing ID: {id}");
+    }
+    if declaration.parts.is_empty() {
+        anyhow::bail!("Voxelized mesh {id} has no parts");
+    }
+
+    registry.insert(
+        id,
+        VoxelizedMesh {
+            parts: declaration.parts.clone(),
+        },
+    );
+
+    Ok(id)
+}
+
+/// Loads all MagicaVoxel models in the given declarations and stores them in
+/// the voxel model registry.
+///
+/// # Errors
+/// See [`load_declared_vox_model`].
+pub fn load_declared_vox_models(
+    registry: &mut VoxModelRegistry,
+    declarations: &[VoxModelDeclaration],
+) -> anyhow::Result<()> {
+    for declaration in declarations {
+        if let Err(error) = load_declared_vox_model(registry, declaration) {
+            // Failing to load a voxel model is not fatal, since we might not need it
+            log::error!("Failed to load voxel model {}: {error:#}", declaration.id);
+        }
+    }
+    Ok(())
+}
+
+/// Loads the MagicaVoxel model in the given declaration and stores it in the
+/// voxel model registry.
+///
+/// # Errors
+/// Returns an error if:
+/// - Another voxel model with the same name is already loaded.
+/// - The `.vox` file can not be found or is invalid.
+pub fn load_declared_vox_model(
+    registry: &mut VoxModelRegistry,
+    declaration: &VoxModelDeclaration,
+) -> anyhow::Result<VoxModelID> {
+    let id = declaration.id;
+    let path = &declaration.path;
+
+    log::debug!("Loading voxel model `{id}` from {}", path.display());
+
+    if registry.contains(id) {
+        anyhow::bail!("Tried to load voxel model under already existing ID: {id}");
+    }
+
+    let model = VoxModel::from_file(path)?;
+
+    registry.insert(id, model);
+
+    Ok(id)
+}
@@ -0,0 +1,81 @@
+//! Voxel objects generated ahead of time.
+
+use crate::generation::VoxelGeneratorID;
+use impact_containers::HashMap;
+use impact_math::hash::Hash32;
+
+/// Voxel objects generated ahead of time from voxel generators, stored in the
+/// voxel object file format (see [`crate::object::io`]). A generated voxel
+/// object whose parameters match a stored object can be read from the stored
+/// data instead of being generated.
+#[derive(Clone, Debug, Default)]
+pub struct PregeneratedVoxelObjectRegistry {
+    objects: HashMap<PregeneratedVoxelObjectKey, Vec<u8>>,
+}
+
+/// The parameters a voxel object was generated with, which identify the
+/// object in a [`PregeneratedVoxelObjectRegistry`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PregeneratedVoxelObjectKey {
+    generator_id: VoxelGeneratorID,
+    voxel_extent_bits: u32,
+    scale_factor_bits: u32,
+    seed: u64,
+    voxel_type_name_hash: Hash32,
+}
+
+impl PregeneratedVoxelObjectRegistry {
+    /// Creates a new empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the encoded voxel object generated with the given parameters,
+    /// if present.
+    pub fn get(&self, key: &PregeneratedVoxelObjectKey) -> Option<&[u8]> {
+        self.objects.get(key).map(Vec::as_slice)
+    }
+
+    /// Whether an object generated with the given parameters is present.
+    pub fn contains(&self, key: &PregeneratedVoxelObjectKey) -> bool {
+        self.objects.contains_key(key)
+    }
+
+    /// Stores the given encoded voxel object under the given parameters,
+    /// replacing any existing object.
+    pub fn insert(&mut self, key: PregeneratedVoxelObjectKey, encoded_object: Vec<u8>) {
+        self.objects.insert(key, encoded_object);
+    }
+
+    /// Returns the number of stored objects.
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// Whether the registry is empty.
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+}
+
+impl PregeneratedVoxelObjectKey {
+    /// Creates the key for a voxel object generated by the given generator
+    /// with the given voxel extent, scale factor and seed, using the voxel
+    /// type with the given name hash for all voxels not assigned a type by the
+    /// generator.
+    pub fn new(
+        generator_id: VoxelGeneratorID,
+        voxel_extent: f32,
+        scale_factor: f32,
+        seed: u64,
+        voxel_type_name_hash: Hash32,
+    ) -> Self {
+        Self {
+            generator_id,
+            voxel_extent_bits: voxel_extent.to_bits(),
+            scale_factor_bits: scale_factor.to_bits(),
+            seed,
+            voxel_type_name_hash,
+        }
+    }
+}
@@ -1,7 +1,14 @@
 //! Generation of signed distance fields. This module implements the graph of
 //! simple "atomic" SDF nodes that is traversed during generation.
 
-use crate::{VoxelSignedDistance, generation::sdf::Smoothness, object::VoxelObject};
+use crate::{
+    VoxelSignedDistance,
+    generation::{
+        sdf::Smoothness,
+        voxel_type::graph::{UNTAGGED, VoxelTypeGraph, VoxelTypeTag},
+    },
+    object::VoxelObject,
+};
 use anyhow::{Result, anyhow, bail};
 use approx::abs_diff_ne;
 use impact_alloc::{
@@ -37,6 +44,9 @@ pub struct SDFGenerator<A: Allocator> {
     nodes: AVec<ProcessedSDFNode, A>,
     required_forward_stack_size: usize,
     domain: AxisAlignedBox,
+    /// Whether any node assigns voxel type tags, in which case the tags are
+    /// propagated alongside the signed distances during evaluation.
+    tracks_voxel_type_tags: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -44,6 +54,9 @@ pub struct SDFGeneratorBlockBuffers<const COUNT: usize, A: Allocator> {
     /// Contains `required_forward_stack_size + 1` arrays, where the last one is scratch
     /// space.
     pub signed_distance_stack: AVec<[f32; COUNT], A>,
+    /// Contains `required_forward_stack_size` arrays if the generator tracks
+    /// voxel type tags, otherwise it is empty.
+    pub voxel_type_tag_stack: AVec<[VoxelTypeTag; COUNT], A>,
 }
 
 const CHUNK_SIZE: usize = VoxelObject::chunk_size();
@@ -55,6 +68,7 @@ pub type SDFGeneratorChunkBuffers<A> = SDFGeneratorBlockBuffers<CHUNK_VOXEL_COUN
 pub struct SDFGraph<A: Allocator> {
     nodes: AVec<SDFNode, A>,
     root_node_id: SDFNodeID,
+    voxel_type_graph: VoxelTypeGraph<A>,
 }
 
 pub type SDFNodeID = u32;
@@ -74,6 +88,9 @@ pub enum SDFNode {
     // Modifiers
     MultifractalNoise(MultifractalNoiseSDFModifier),
 
+    // Voxel types
+    VoxelTypeTag(SDFVoxelTypeTag),
+
     // Combination
     Union(SDFUnion),
     Subtraction(SDFSubtraction),
@@ -162,6 +179,16 @@ pub struct SDFIntersection {
     pub smoothness: Smoothness,
 }
 
+/// Assigns a voxel type tag to the entire signed distance field of the child.
+/// Combination nodes propagate the tags of their children, so that the final
+/// tag at each point identifies the voxel type graph root that determines the
+/// voxel type there.
+#[derive(Clone, Debug)]
+pub struct SDFVoxelTypeTag {
+    pub child_id: SDFNodeID,
+    pub tag: VoxelTypeTag,
+}
+
 /// Modifier for a signed distance field that adds a multifractal noise term to
 /// the signed distance.
 ///
@@ -222,10 +249,23 @@ impl<A: Allocator> SDFGenerator<A> {
             nodes: AVec::new_in(alloc),
             required_forward_stack_size: 0,
             domain: AxisAlignedBox::new(Point3::origin(), Point3::origin()),
+            tracks_voxel_type_tags: false,
         }
     }
 
     pub fn new_in(alloc: A, nodes: &[SDFNode], root_node_id: SDFNodeID) -> Result<Self> {
+        Self::new_with_root_margin_in(alloc, nodes, root_node_id, VoxelSignedDistance::MAX_F32)
+    }
+
+    /// Like [`Self::new_in`], but keeps signed distances accurate out to the
+    /// given distance (in voxels) from the surface rather than only out to the
+    /// largest distance representable by a [`VoxelSignedDistance`].
+    pub fn new_with_root_margin_in(
+        alloc: A,
+        nodes: &[SDFNode],
+        root_node_id: SDFNodeID,
+        root_margin: f32,
+    ) -> Result<Self> {
         let mut processed_nodes = AVec::with_capacity_in(nodes.len(), alloc);
 
         // Estimate capacity based on node count for domain calculations and processing
@@ -287,7 +327,8 @@ impl<A: Allocator> SDFGenerator<A> {
                                 | SDFNode::MultifractalNoise(MultifractalNoiseSDFModifier {
                                     child_id,
                                     ..
-                                }) => {
+                                })
+                                | SDFNode::VoxelTypeTag(SDFVoxelTypeTag { child_id, .. }) => {
                                     operation_stack.push(BuildOperation::VisitChildren(*child_id));
                                 }
                                 SDFNode::Union(SDFUnion {
@@ -381,6 +422,12 @@ impl<A: Allocator> SDFGenerator<A> {
                                 leaf_counts[node_idx] = leaf_counts[*child_id as usize];
                                 required_padding[node_idx] = required_padding[*child_id as usize];
                             }
+                            &SDFNode::VoxelTypeTag(SDFVoxelTypeTag { child_id, .. }) => {
+                                domains[node_idx] = domains[child_id as usize].clone();
+
+                                leaf_counts[node_idx] = leaf_counts[child_id as usize];
+                                required_padding[node_idx] = required_padding[child_id as usize];
+                            }
                             &SDFNode::Union(SDFUnion {
                                 child_1_id,
                                 child_2_id,
@@ -472,7 +519,8 @@ impl<A: Allocator> SDFGenerator<A> {
                         SDFNode::Translation(_)
                         | SDFNode::Rotation(_)
                         | SDFNode::Scaling(_)
-                        | SDFNode::MultifractalNoise(_) => {}
+                        | SDFNode::MultifractalNoise(_)
+                        | SDFNode::VoxelTypeTag(_) => {}
                     }
                 }
             }
@@ -480,7 +528,11 @@ impl<A: Allocator> SDFGenerator<A> {
 
         debug_assert_eq!(stack_top, 1);
 
-        Self::determine_transforms_and_margins(&arena, &mut processed_nodes);
+        Self::determine_transforms_and_margins(&arena, &mut processed_nodes, root_margin);
+
+        let tracks_voxel_type_tags = processed_nodes
+            .iter()
+            .any(|node| matches!(node.node, SDFNode::VoxelTypeTag(_)));
 
         let root_domain = domains[root_node_id as usize]
             .expanded_about_center(required_padding[root_node_id as usize]);
@@ -489,10 +541,15 @@ impl<A: Allocator> SDFGenerator<A> {
             nodes: processed_nodes,
             required_forward_stack_size: max_stack_top,
             domain: root_domain,
+            tracks_voxel_type_tags,
         })
     }
 
-    fn determine_transforms_and_margins(arena: &PoolArena, nodes: &mut [ProcessedSDFNode]) {
+    fn determine_transforms_and_margins(
+        arena: &PoolArena,
+        nodes: &mut [ProcessedSDFNode],
+        root_margin: f32,
+    ) {
         // We determine the transforms to node space by walking the graph from
         // parent to children, taking the parent transform and either
         // propagating it unchanged to the child or, if the child is a transform
@@ -510,7 +567,7 @@ impl<A: Allocator> SDFGenerator<A> {
 
         let mut stack_top = 0;
         transform_stack[stack_top] = Matrix4::identity();
-        margin_stack[stack_top] = VoxelSignedDistance::MAX_F32;
+        margin_stack[stack_top] = root_margin;
 
         for node in nodes.iter_mut().rev() {
             let transform = transform_stack[stack_top];
@@ -566,6 +623,10 @@ impl<A: Allocator> SDFGenerator<A> {
                     let margin_for_child = margin + modifier.amplitude;
                     margin_stack[stack_top] = margin_for_child;
                 }
+                SDFNode::VoxelTypeTag(_) => {
+                    // Transform and margin: A tag node should have the same
+                    // transform and margin as its child
+                }
                 &SDFNode::Union(SDFUnion { smoothness, .. })
                 | &SDFNode::Subtraction(SDFSubtraction { smoothness, .. })
                 | &SDFNode::Intersection(SDFIntersection { smoothness, .. }) => {
@@ -607,8 +668,15 @@ impl<A: Allocator> SDFGenerator<A> {
         &self.domain
     }
 
+    /// Whether any node assigns voxel type tags, meaning that the final voxel
+    /// type tags are available in the buffers after evaluation.
+    pub fn tracks_voxel_type_tags(&self) -> bool {
+        self.tracks_voxel_type_tags
+    }
+
     pub fn total_buffer_size_for_block<const COUNT: usize>(&self) -> usize {
         mem::size_of::<f32>() * COUNT * (self.required_forward_stack_size + 1)
+            + mem::size_of::<VoxelTypeTag>() * COUNT * self.voxel_type_tag_stack_size()
     }
 
     pub fn create_buffers_for_block_in<const COUNT: usize, AB: Allocator>(
@@ -621,8 +689,20 @@ impl<A: Allocator> SDFGenerator<A> {
         let signed_distance_stack =
             avec![in alloc; [0.0; COUNT]; self.required_forward_stack_size + 1];
 
+        let voxel_type_tag_stack =
+            avec![in alloc; [UNTAGGED; COUNT]; self.voxel_type_tag_stack_size()];
+
         SDFGeneratorBlockBuffers {
             signed_distance_stack,
+            voxel_type_tag_stack,
+        }
+    }
+
+    fn voxel_type_tag_stack_size(&self) -> usize {
+        if self.tracks_voxel_type_tags {
+            self.required_forward_stack_size
+        } else {
+            0
         }
     }
 
@@ -649,6 +729,10 @@ impl<A: Allocator> SDFGenerator<A> {
         let mut stack_top: usize = 0;
 
         for node in &self.nodes {
+            if self.tracks_voxel_type_tags {
+                update_voxel_type_tags_for_node(&node.node, stack_top, buffers);
+            }
+
             match &node.node {
                 SDFNode::Sphere(sphere_generator) => {
                     let block_aabb_in_node_space =
@@ -742,7 +826,7 @@ impl<A: Allocator> SDFGenerator<A> {
 
                     stack_top += 1;
                 }
-                SDFNode::Translation(_) | SDFNode::Rotation(_) => {}
+                SDFNode::Translation(_) | SDFNode::Rotation(_) | SDFNode::VoxelTypeTag(_) => {}
                 SDFNode::Scaling(SDFScaling { scaling, .. }) => {
                     debug_assert!(stack_top >= 1);
 
@@ -936,7 +1020,7 @@ impl<A: Allocator> SDFGenerator<A> {
 
                     stack_top += 1;
                 }
-                SDFNode::Translation(_) | SDFNode::Rotation(_) => {}
+                SDFNode::Translation(_) | SDFNode::Rotation(_) | SDFNode::VoxelTypeTag(_) => {}
                 SDFNode::Scaling(SDFScaling { scaling, .. }) => {
                     debug_assert!(stack_top >= 1);
 
@@ -1014,6 +1098,12 @@ impl<const COUNT: usize, A: Allocator> SDFGeneratorBlockBuffers<COUNT, A> {
     pub fn final_signed_distances(&self) -> &[f32; COUNT] {
         &self.signed_distance_stack[0]
     }
+
+    /// Returns the final voxel type tags, or [`None`] if the generator does
+    /// not track voxel type tags.
+    pub fn final_voxel_type_tags(&self) -> Option<&[VoxelTypeTag; COUNT]> {
+        self.voxel_type_tag_stack.first()
+    }
 }
 
 impl<A: Allocator> SDFGraph<A> {
@@ -1025,6 +1115,7 @@ impl<A: Allocator> SDFGraph<A> {
         Self {
             nodes: AVec::<_, A>::with_capacity_in(capacity, alloc),
             root_node_id: 0,
+            voxel_type_graph: VoxelTypeGraph::new_in(alloc),
         }
     }
 
@@ -1032,7 +1123,19 @@ impl<A: Allocator> SDFGraph<A> {
         if self.nodes.is_empty() {
             Ok(SDFGenerator::empty_in(alloc))
         } else {
-            SDFGenerator::new_in(alloc, &self.nodes, self.root_node_id)
+            // Depth-based voxel type selection needs correct signed distances
+            // down to the deepest selection depth
+            let root_margin = self
+                .voxel_type_graph
+                .max_selection_depth()
+                .max(VoxelSignedDistance::MAX_F32);
+
+            SDFGenerator::new_with_root_margin_in(
+                alloc,
+                &self.nodes,
+                self.root_node_id,
+                root_margin,
+            )
         }
     }
 
@@ -1055,6 +1158,16 @@ impl<A: Allocator> SDFGraph<A> {
         assert!((node_id as usize) < self.nodes.len());
         self.root_node_id = node_id;
     }
+
+    /// Returns the graph determining the voxel types for the voxel type tags
+    /// assigned by the nodes of this graph.
+    pub fn voxel_type_graph(&self) -> &VoxelTypeGraph<A> {
+        &self.voxel_type_graph
+    }
+
+    pub fn voxel_type_graph_mut(&mut self) -> &mut VoxelTypeGraph<A> {
+        &mut self.voxel_type_graph
+    }
 }
 
 impl SDFNode {
@@ -1127,6 +1240,11 @@ impl SDFNode {
         Self::Intersection(SDFIntersection::new(child_1_id, child_2_id, smoothness))
     }
 
+    #[inline]
+    pub fn new_voxel_type_tag(child_id: SDFNodeID, tag: VoxelTypeTag) -> Self {
+        Self::VoxelTypeTag(SDFVoxelTypeTag { child_id, tag })
+    }
+
     #[inline]
     pub fn node_to_parent_translation(&self) -> Vector3 {
         match self {
@@ -1573,6 +1691,67 @@ impl MultifractalNoiseSDFModifier {
 }
 
 #[inline]
+/// Updates the voxel type tags on the tag stack for the given node. This must
+/// be called before the node updates the signed distances, since combination
+/// nodes select tags based on the signed distances of their children.
+fn update_voxel_type_tags_for_node<const COUNT: usize, A: Allocator>(
+    node: &SDFNode,
+    stack_top: usize,
+    buffers: &mut SDFGeneratorBlockBuffers<COUNT, A>,
+) {
+    let SDFGeneratorBlockBuffers {
+        signed_distance_stack,
+        voxel_type_tag_stack,
+    } = buffers;
+
+    match node {
+        SDFNode::Sphere(_) | SDFNode::Capsule(_) | SDFNode::Box(_) => {
+            voxel_type_tag_stack[stack_top].fill(UNTAGGED);
+        }
+        SDFNode::Translation(_)
+        | SDFNode::Rotation(_)
+        | SDFNode::Scaling(_)
+        | SDFNode::MultifractalNoise(_) => {}
+        &SDFNode::VoxelTypeTag(SDFVoxelTypeTag { tag, .. }) => {
+            voxel_type_tag_stack[stack_top - 1].fill(tag);
+        }
+        SDFNode::Union(_) | SDFNode::Subtraction(_) | SDFNode::Intersection(_) => {
+            debug_assert!(stack_top >= 2);
+
+            let distances_1 = &signed_distance_stack[stack_top - 2];
+            let distances_2 = &signed_distance_stack[stack_top - 1];
+
+            let [tags_1, tags_2] = voxel_type_tag_stack
+                .get_disjoint_mut([stack_top - 2, stack_top - 1])
+                .unwrap();
+
+            let selects_second: fn(f32, f32, VoxelTypeTag) -> bool = match node {
+                // The closest surface determines the voxel type
+                SDFNode::Union(_) => |distance_1, distance_2, _| distance_2 < distance_1,
+                // Surfaces carved out by the subtracted field get its voxel
+                // type, unless it is untagged
+                SDFNode::Subtraction(_) => {
+                    |distance_1, distance_2, tag_2| tag_2 != UNTAGGED && -distance_2 > distance_1
+                }
+                // The farthest surface determines the voxel type, unless it is
+                // untagged
+                _ => |distance_1, distance_2, tag_2| tag_2 != UNTAGGED && distance_2 > distance_1,
+            };
+
+            for (((tag_1, &tag_2), &distance_1), &distance_2) in tags_1
+                .iter_mut()
+                .zip(tags_2.iter())
+                .zip(distances_1)
+                .zip(distances_2)
+            {
+                if selects_second(distance_1, distance_2, tag_2) {
+                    *tag_1 = tag_2;
+                }
+            }
+        }
+    }
+}
+
 fn zero_domain() -> AxisAlignedBox {
     AxisAlignedBox::new(Point3::origin(), Point3::origin())
 }
@@ -6,7 +6,10 @@ pub mod params;
 
 use crate::{
     define_meta_node_params,
-    generation::sdf::{SDFGenerator, SDFGeneratorBlockBuffers, SDFGraph, SDFNode, SDFNodeID},
+    generation::{
+        sdf::{SDFGenerator, SDFGeneratorBlockBuffers, SDFGraph, SDFNode, SDFNodeID},
+        voxel_type::graph::{VoxelTypeNodeID, VoxelTypeSelection, VoxelTypeSelectionQuantity},
+    },
 };
 use anyhow::{Context, Result, anyhow, bail};
 use approx::{abs_diff_eq, abs_diff_ne};
@@ -73,6 +76,14 @@ pub enum MetaSDFNode {
     SDFSubtraction(MetaSDFSubtraction),
     SDFIntersection(MetaSDFIntersection),
     SDFGroupUnion(MetaSDFGroupUnion),
+
+    // Voxel types
+    VoxelType(MetaVoxelType),
+    DepthVoxelTypeSelection(MetaDepthVoxelTypeSelection),
+    SlopeVoxelTypeSelection(MetaSlopeVoxelTypeSelection),
+    CurvatureVoxelTypeSelection(MetaCurvatureVoxelTypeSelection),
+    NoiseVoxelTypeSelection(MetaNoiseVoxelTypeSelection),
+    VoxelTypeAssignment(MetaVoxelTypeAssignment),
 }
 
 pub type MetaSDFNodeID = u32;
@@ -95,6 +106,7 @@ enum MetaSDFNodeOutput<A: Allocator> {
     SingleSDF(Option<SDFNodeID>),
     SDFGroup(AVec<SDFNodeID, A>),
     Instances(AVec<Instance, A>),
+    VoxelTypes(VoxelTypeNodeID),
 }
 
 #[derive(Clone, Debug, Default)]
@@ -668,6 +680,109 @@ pub struct MetaSDFGroupUnion {
     pub smoothness: f32,
 }
 
+/// A single voxel type.
+///
+/// Output: `VoxelTypes`
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct MetaVoxelType {
+    /// Name of the voxel type.
+    pub voxel_type_name: String,
+}
+
+/// Selection between voxel types based on the depth below the surface of the
+/// SDF the voxel types are assigned to.
+///
+/// Input 1: `VoxelTypes`
+/// Input 2: `VoxelTypes`
+/// Output: `VoxelTypes`
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct MetaDepthVoxelTypeSelection {
+    /// ID of the voxel type node to use near the surface.
+    pub surface_id: MetaSDFNodeID,
+    /// ID of the voxel type node to use further inside.
+    pub interior_id: MetaSDFNodeID,
+    /// Depth below the surface where the interior voxel types begin, in
+    /// voxels.
+    pub depth: f32,
+}
+
+/// Selection between voxel types based on the steepness of the surface of the
+/// SDF the voxel types are assigned to.
+///
+/// Input 1: `VoxelTypes`
+/// Input 2: `VoxelTypes`
+/// Output: `VoxelTypes`
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct MetaSlopeVoxelTypeSelection {
+    /// ID of the voxel type node to use where the surface is flat.
+    pub flat_id: MetaSDFNodeID,
+    /// ID of the voxel type node to use where the surface is steep.
+    pub steep_id: MetaSDFNodeID,
+    /// Largest angle between the surface normal and the y-axis for which the
+    /// surface is considered flat, in degrees.
+    pub max_flat_angle: f32,
+}
+
+/// Selection between voxel types based on the curvature of the surface of the
+/// SDF the voxel types are assigned to.
+///
+/// Input 1: `VoxelTypes`
+/// Input 2: `VoxelTypes`
+/// Output: `VoxelTypes`
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct MetaCurvatureVoxelTypeSelection {
+    /// ID of the voxel type node to use where the curvature is below the
+    /// threshold.
+    pub concave_id: MetaSDFNodeID,
+    /// ID of the voxel type node to use where the curvature is above the
+    /// threshold.
+    pub convex_id: MetaSDFNodeID,
+    /// Mean curvature separating the two voxel type nodes, in inverse voxels.
+    /// Positive values are convex.
+    pub threshold: f32,
+}
+
+/// Selection between voxel types based on a gradient noise pattern.
+///
+/// Input 1: `VoxelTypes`
+/// Input 2: `VoxelTypes`
+/// Output: `VoxelTypes`
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct MetaNoiseVoxelTypeSelection {
+    /// ID of the voxel type node to use where the noise is below the
+    /// threshold.
+    pub low_id: MetaSDFNodeID,
+    /// ID of the voxel type node to use where the noise is above the
+    /// threshold.
+    pub high_id: MetaSDFNodeID,
+    /// Spatial frequency of the noise pattern, in inverse voxels.
+    pub frequency: f32,
+    /// Noise value separating the two voxel type nodes.
+    pub threshold: f32,
+    /// Seed for generating noise.
+    pub seed: u32,
+}
+
+/// Assignment of voxel types to one or more SDFs. The voxel types are used
+/// wherever the surface of the combined SDF comes from the assigned SDFs.
+///
+/// Input 1: `SDFGroup` or `SingleSDF`
+/// Input 2: `VoxelTypes`
+/// Output: Same as input 1
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct MetaVoxelTypeAssignment {
+    /// ID of the SDF or SDF group node to assign voxel types to.
+    pub sdf_id: MetaSDFNodeID,
+    /// ID of the voxel type node with the voxel types to assign.
+    pub voxel_type_id: MetaSDFNodeID,
+}
+
 /// How to combine the current transformation with the input transformation.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -792,7 +907,8 @@ impl<A: Allocator> MetaSDFGraph<A> {
                                 MetaSDFNode::Points(_)
                                 | MetaSDFNode::Spheres(_)
                                 | MetaSDFNode::Capsules(_)
-                                | MetaSDFNode::Boxes(_) => {}
+                                | MetaSDFNode::Boxes(_)
+                                | MetaSDFNode::VoxelType(_) => {}
                                 MetaSDFNode::Translation(MetaTranslation { child_id, .. })
                                 | MetaSDFNode::Rotation(MetaRotation { child_id, .. })
                                 | MetaSDFNode::Scaling(MetaScaling { child_id, .. })
@@ -854,6 +970,38 @@ impl<A: Allocator> MetaSDFGraph<A> {
                                     child_1_id,
                                     child_2_id,
                                     ..
+                                })
+                                | MetaSDFNode::DepthVoxelTypeSelection(
+                                    MetaDepthVoxelTypeSelection {
+                                        surface_id: child_1_id,
+                                        interior_id: child_2_id,
+                                        ..
+                                    },
+                                )
+                                | MetaSDFNode::SlopeVoxelTypeSelection(
+                                    MetaSlopeVoxelTypeSelection {
+                                        flat_id: child_1_id,
+                                        steep_id: child_2_id,
+                                        ..
+                                    },
+                                )
+                                | MetaSDFNode::CurvatureVoxelTypeSelection(
+                                    MetaCurvatureVoxelTypeSelection {
+                                        concave_id: child_1_id,
+                                        convex_id: child_2_id,
+                                        ..
+                                    },
+                                )
+                                | MetaSDFNode::NoiseVoxelTypeSelection(
+                                    MetaNoiseVoxelTypeSelection {
+                                        low_id: child_1_id,
+                                        high_id: child_2_id,
+                                        ..
+                                    },
+                                )
+                                | MetaSDFNode::VoxelTypeAssignment(MetaVoxelTypeAssignment {
+                                    sdf_id: child_1_id,
+                                    voxel_type_id: child_2_id,
                                 }) => {
                                     operation_stack
                                         .push(BuildOperation::VisitChildren(*child_2_id));
@@ -981,6 +1129,7 @@ impl<A: Allocator> MetaSDFNodeOutput<A> {
             Self::SingleSDF(_) => "SingleSDF",
             Self::SDFGroup(_) => "SDFGroup",
             Self::Instances(_) => "Instances",
+            Self::VoxelTypes(_) => "VoxelTypes",
         }
     }
 }
@@ -1093,6 +1242,35 @@ impl MetaSDFNode {
             Self::SDFGroupUnion(MetaSDFGroupUnion { child_id, .. }) => {
                 combine_unary(0x63, child_id)
             }
+            Self::VoxelType(MetaVoxelType { .. }) => leaf(0x70),
+            Self::DepthVoxelTypeSelection(MetaDepthVoxelTypeSelection {
+                surface_id,
+                interior_id,
+                ..
+            }) => combine_binary(0x71, surface_id, interior_id),
+            Self::SlopeVoxelTypeSelection(MetaSlopeVoxelTypeSelection {
+                flat_id,
+                steep_id,
+                ..
+            }) => combine_binary(0x72, flat_id, steep_id),
+            Self::CurvatureVoxelTypeSelection(MetaCurvatureVoxelTypeSelection {
+                concave_id,
+                convex_id,
+                ..
+            }) => combine_binary(0x73, concave_id, convex_id),
+            Self::NoiseVoxelTypeSelection(MetaNoiseVoxelTypeSelection {
+                low_id,
+                high_id,
+                seed,
+                ..
+            }) => splitmix::random_u64_from_two_states(
+                combine_binary(0x74, low_id, high_id),
+                (*seed).into(),
+            ),
+            Self::VoxelTypeAssignment(MetaVoxelTypeAssignment {
+                sdf_id,
+                voxel_type_id,
+            }) => combine_binary(0x75, sdf_id, voxel_type_id),
         }
     }
 
@@ -1164,6 +1342,22 @@ impl MetaSDFNode {
             Self::SDFGroupUnion(node) => node
                 .resolve(arena, graph, outputs, scale_factor)
                 .context("Failed to resolve SDFGroupUnion node"),
+            Self::VoxelType(node) => Ok(node.resolve(graph)),
+            Self::DepthVoxelTypeSelection(node) => node
+                .resolve(graph, outputs, scale_factor)
+                .context("Failed to resolve DepthVoxelTypeSelection node"),
+            Self::SlopeVoxelTypeSelection(node) => node
+                .resolve(graph, outputs)
+                .context("Failed to resolve SlopeVoxelTypeSelection node"),
+            Self::CurvatureVoxelTypeSelection(node) => node
+                .resolve(graph, outputs, scale_factor)
+                .context("Failed to resolve CurvatureVoxelTypeSelection node"),
+            Self::NoiseVoxelTypeSelection(node) => node
+                .resolve(graph, outputs, scale_factor, seed)
+                .context("Failed to resolve NoiseVoxelTypeSelection node"),
+            Self::VoxelTypeAssignment(node) => node
+                .resolve(arena, graph, outputs)
+                .context("Failed to resolve VoxelTypeAssignment node"),
         }
     }
 }
@@ -1917,6 +2111,9 @@ impl MetaStochasticSelection {
                 }
                 MetaSDFNodeOutput::Instances(output_instances)
             }
+            MetaSDFNodeOutput::VoxelTypes(voxel_type_node_id) => {
+                MetaSDFNodeOutput::VoxelTypes(*voxel_type_node_id)
+            }
         }
     }
 }
@@ -2025,9 +2222,10 @@ impl MetaTransformApplication {
                 Cow::Owned(sdf_node_ids)
             }
             MetaSDFNodeOutput::SDFGroup(sdf_node_ids) => Cow::Borrowed(sdf_node_ids),
-            MetaSDFNodeOutput::Instances(_) => {
+            child_output => {
                 bail!(
-                    "TransformApplication node expects SingleSDF or GroupSDF as input 1, got Instances"
+                    "TransformApplication node expects SingleSDF or GroupSDF as input 1, got {}",
+                    child_output.label()
                 );
             }
         };
@@ -2252,13 +2450,137 @@ impl MetaSDFGroupUnion {
                 );
                 Ok(MetaSDFNodeOutput::SingleSDF(output_node_id))
             }
-            MetaSDFNodeOutput::Instances(_) => {
-                bail!("SDFGroupUnion node expects SDFGroup or SingleSDF input, got Instances");
+            child_output => {
+                bail!(
+                    "SDFGroupUnion node expects SDFGroup or SingleSDF input, got {}",
+                    child_output.label()
+                );
             }
         }
     }
 }
 
+impl MetaVoxelType {
+    fn resolve<AR: Allocator, AG: Allocator>(
+        &self,
+        graph: &mut SDFGraph<AG>,
+    ) -> MetaSDFNodeOutput<AR> {
+        let voxel_type_node_id = graph
+            .voxel_type_graph_mut()
+            .add_voxel_type_node(self.voxel_type_name.clone());
+        MetaSDFNodeOutput::VoxelTypes(voxel_type_node_id)
+    }
+}
+
+impl MetaDepthVoxelTypeSelection {
+    fn resolve<AR: Allocator, AG: Allocator>(
+        &self,
+        graph: &mut SDFGraph<AG>,
+        outputs: &[MetaSDFNodeOutput<AR>],
+        scale_factor: f32,
+    ) -> Result<MetaSDFNodeOutput<AR>> {
+        resolve_voxel_type_selection(
+            graph,
+            "DepthVoxelTypeSelection",
+            &outputs[self.surface_id as usize],
+            &outputs[self.interior_id as usize],
+            VoxelTypeSelectionQuantity::Depth,
+            (self.depth * scale_factor).max(0.0),
+        )
+    }
+}
+
+impl MetaSlopeVoxelTypeSelection {
+    fn resolve<AR: Allocator, AG: Allocator>(
+        &self,
+        graph: &mut SDFGraph<AG>,
+        outputs: &[MetaSDFNodeOutput<AR>],
+    ) -> Result<MetaSDFNodeOutput<AR>> {
+        resolve_voxel_type_selection(
+            graph,
+            "SlopeVoxelTypeSelection",
+            &outputs[self.flat_id as usize],
+            &outputs[self.steep_id as usize],
+            VoxelTypeSelectionQuantity::Slope,
+            self.max_flat_angle,
+        )
+    }
+}
+
+impl MetaCurvatureVoxelTypeSelection {
+    fn resolve<AR: Allocator, AG: Allocator>(
+        &self,
+        graph: &mut SDFGraph<AG>,
+        outputs: &[MetaSDFNodeOutput<AR>],
+        scale_factor: f32,
+    ) -> Result<MetaSDFNodeOutput<AR>> {
+        // The curvature quantity is the sum of the two principal curvatures
+        resolve_voxel_type_selection(
+            graph,
+            "CurvatureVoxelTypeSelection",
+            &outputs[self.concave_id as usize],
+            &outputs[self.convex_id as usize],
+            VoxelTypeSelectionQuantity::Curvature,
+            2.0 * self.threshold / scale_factor,
+        )
+    }
+}
+
+impl MetaNoiseVoxelTypeSelection {
+    fn resolve<AR: Allocator, AG: Allocator>(
+        &self,
+        graph: &mut SDFGraph<AG>,
+        outputs: &[MetaSDFNodeOutput<AR>],
+        scale_factor: f32,
+        seed: u64,
+    ) -> Result<MetaSDFNodeOutput<AR>> {
+        let mut rng = create_param_rng(seed);
+        resolve_voxel_type_selection(
+            graph,
+            "NoiseVoxelTypeSelection",
+            &outputs[self.low_id as usize],
+            &outputs[self.high_id as usize],
+            VoxelTypeSelectionQuantity::Noise {
+                frequency: self.frequency / scale_factor,
+                seed: rng.random_u32_in_range(..),
+            },
+            self.threshold,
+        )
+    }
+}
+
+impl MetaVoxelTypeAssignment {
+    fn resolve<AR: Allocator, AG: Allocator>(
+        &self,
+        arena: AR,
+        graph: &mut SDFGraph<AG>,
+        outputs: &[MetaSDFNodeOutput<AR>],
+    ) -> Result<MetaSDFNodeOutput<AR>> {
+        let voxel_type_node_id = match &outputs[self.voxel_type_id as usize] {
+            MetaSDFNodeOutput::VoxelTypes(voxel_type_node_id) => *voxel_type_node_id,
+            child_output => {
+                bail!(
+                    "VoxelTypeAssignment node expects VoxelTypes as input 2, got {}",
+                    child_output.label()
+                );
+            }
+        };
+
+        let tag = graph.voxel_type_graph_mut().add_tag(voxel_type_node_id)?;
+
+        resolve_unary_sdf_op(
+            arena,
+            graph,
+            "VoxelTypeAssignment",
+            0,
+            &outputs[self.sdf_id as usize],
+            ParameterSamplingMode::OnlyOnce,
+            |_| Ok(()),
+            |_, input_node_id| SDFNode::new_voxel_type_tag(input_node_id, tag),
+        )
+    }
+}
+
 impl CompositionMode {
     pub fn try_from_str(variant: &str) -> Result<Self> {
         match variant {
@@ -2387,6 +2709,39 @@ fn resolve_unary_sdf_op<AR: Allocator, AG: Allocator, P>(
     }
 }
 
+fn resolve_voxel_type_selection<AR: Allocator, AG: Allocator>(
+    graph: &mut SDFGraph<AG>,
+    name: &str,
+    child_1_output: &MetaSDFNodeOutput<AR>,
+    child_2_output: &MetaSDFNodeOutput<AR>,
+    quantity: VoxelTypeSelectionQuantity,
+    threshold: f32,
+) -> Result<MetaSDFNodeOutput<AR>> {
+    let (below_id, above_id) = match (child_1_output, child_2_output) {
+        (MetaSDFNodeOutput::VoxelTypes(below_id), MetaSDFNodeOutput::VoxelTypes(above_id)) => {
+            (*below_id, *above_id)
+        }
+        (child_1_output, child_2_output) => {
+            bail!(
+                "{name} node expects two VoxelTypes inputs, got {} and {}",
+                child_1_output.label(),
+                child_2_output.label()
+            );
+        }
+    };
+
+    let voxel_type_node_id = graph
+        .voxel_type_graph_mut()
+        .add_selection_node(VoxelTypeSelection {
+            quantity,
+            threshold,
+            below_id,
+            above_id,
+        });
+
+    Ok(MetaSDFNodeOutput::VoxelTypes(voxel_type_node_id))
+}
+
 fn emit_balanced_binary_tree<A, N>(
     arena: A,
     leaf_nodes: &[N],
@@ -0,0 +1,1213 @@
+//! Import of voxel models in the MagicaVoxel `.vox` format.
+
+use crate::{
+    Voxel, VoxelSignedDistance,
+    generation::ChunkedVoxelGenerator,
+    object::{ChunkSparseness, LoopForChunkVoxels, VoxelObject},
+    voxel_types::{VoxelType, VoxelTypeRegistry},
+};
+use anyhow::{Context, Result, anyhow, bail};
+use impact_alloc::Allocator;
+use impact_containers::HashMap;
+use impact_math::{hash64, stringhash64_newtype};
+use impact_resource::{Resource, ResourceID, registry::ImmutableResourceRegistry};
+use roc_integration::roc;
+use std::{fs, path::Path};
+
+pub type VoxModelRegistry = ImmutableResourceRegistry<VoxModel>;
+
+stringhash64_newtype!(
+    /// Identifier for a MagicaVoxel model.
+    #[roc(parents = "Voxel")]
+    [pub] VoxModelID
+);
+
+/// A voxel model imported from a MagicaVoxel `.vox` file, with all the models
+/// in the file placed according to the transforms in the file's scene graph and
+/// merged into a single grid.
+///
+/// The grid uses the coordinate system of the engine, where the y-axis points
+/// up, rather than the z-up coordinate system of MagicaVoxel.
+#[derive(Clone, Debug)]
+pub struct VoxModel {
+    shape: [usize; 3],
+    palette_indices: Vec<u8>,
+    palette: Option<Box<[[u8; 4]; 256]>>,
+}
+
+/// Maps each MagicaVoxel palette index to the voxel type that voxels with
+/// that palette index should get.
+#[derive(Clone, Debug)]
+pub struct VoxPaletteVoxelTypes(pub [VoxelType; 256]);
+
+/// Generator for a voxel object from a [`VoxModel`].
+///
+/// Since the model only specifies which voxels are occupied, signed distances
+/// are synthesized from the Euclidean distance between each voxel and the
+/// closest voxel with the opposite occupancy, placing the surface halfway
+/// between occupied and unoccupied voxels. The signed distance field can
+/// optionally be smoothed to give a less blocky surface.
+#[derive(Clone, Debug)]
+pub struct VoxVoxelGenerator {
+    voxel_extent: f32,
+    grid_shape: [usize; 3],
+    signed_distances: Vec<VoxelSignedDistance>,
+    voxel_types: Vec<VoxelType>,
+}
+
+/// A rotation and translation in the integer coordinates of a MagicaVoxel
+/// scene.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct VoxTransform {
+    rotation: [[i32; 3]; 3],
+    translation: [i32; 3],
+}
+
+#[derive(Clone, Debug)]
+enum VoxSceneNode {
+    Transform {
+        child_id: i32,
+        transform: VoxTransform,
+        is_hidden: bool,
+    },
+    Group {
+        child_ids: Vec<i32>,
+    },
+    Shape {
+        model_ids: Vec<i32>,
+    },
+}
+
+#[derive(Clone, Debug)]
+struct VoxModelData {
+    size: [usize; 3],
+    /// The coordinates and palette index of each occupied voxel.
+    voxels: Vec<[u8; 4]>,
+}
+
+/// Reads the little-endian values of a `.vox` file.
+struct VoxReader<'a> {
+    bytes: &'a [u8],
+}
+
+/// The squared distance used for voxels with no site within the grid.
+const FAR_SQUARED_DISTANCE: f32 = 1e10;
+
+/// The maximum number of voxels along each axis of the merged grid. This
+/// matches the extent of the MagicaVoxel world, and prevents malformed files
+/// from placing models so far apart that the grid becomes impossibly large.
+const MAX_GRID_EXTENT: usize = 2048;
+
+impl ResourceID for VoxModelID {}
+
+#[roc(dependencies = [impact_math::hash::Hash64])]
+impl VoxModelID {
+    #[roc(body = "Hashing.hash_str_64(name)")]
+    /// Creates a MagicaVoxel model ID hashed from the given name.
+    pub fn from_name(name: &str) -> Self {
+        Self(hash64!(name))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for VoxModelID {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for VoxModelID {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::from_name(&s))
+    }
+}
+
+impl Resource for VoxModel {
+    type ID = VoxModelID;
+}
+
+impl VoxModel {
+    /// Reads the MagicaVoxel `.vox` file at the given path.
+    ///
+    /// # Errors
+    /// Returns an error if the file can not be read or is not a valid `.vox`
+    /// file.
+    pub fn from_file(file_path: impl AsRef<Path>) -> Result<Self> {
+        let file_path = file_path.as_ref();
+        let bytes = fs::read(file_path)
+            .with_context(|| format!("Failed to read {}", file_path.display()))?;
+        Self::from_bytes(&bytes)
+            .with_context(|| format!("Invalid MagicaVoxel file {}", file_path.display()))
+    }
+
+    /// Parses the given contents of a MagicaVoxel `.vox` file.
+    ///
+    /// # Errors
+    /// Returns an error if the bytes are not a valid `.vox` file, or if the
+    /// placed models span more than 2048 voxels along any axis.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = VoxReader { bytes };
+
+        if reader.read_bytes(4)? != b"VOX " {
+            bail!("Missing `VOX ` header");
+        }
+        let _version = reader.read_i32()?;
+
+        let (main_id, _, main_children) = reader.read_chunk()?;
+        if main_id != *b"MAIN" {
+            bail!("Missing `MAIN` chunk");
+        }
+
+        let mut models = Vec::new();
+        let mut pending_size = None;
+        let mut palette = None;
+        let mut nodes = HashMap::default();
+
+        let mut children = VoxReader {
+            bytes: main_children,
+        };
+
+        while !children.is_empty() {
+            let (id, content, _) = children.read_chunk()?;
+            let mut content = VoxReader { bytes: content };
+
+            match &id {
+                b"SIZE" => {
+                    let size = [
+                        content.read_i32()?,
+                        content.read_i32()?,
+                        content.read_i32()?,
+                    ];
+                    if size.iter().any(|&extent| extent <= 0 || extent > 256) {
+                        bail!("Invalid model size {size:?}");
+                    }
+                    pending_size = Some(size.map(|extent| extent as usize));
+                }
+                b"XYZI" => {
+                    let size = pending_size
+                        .take()
+                        .ok_or_else(|| anyhow!("`XYZI` chunk without preceding `SIZE` chunk"))?;
+                    let voxel_count = content.read_count()?;
+                    let voxels = (0..voxel_count)
+                        .map(|_| {
+                            let voxel: [u8; 4] = content.read_bytes(4)?.try_into().unwrap();
+                            if (0..3).any(|dim| usize::from(voxel[dim]) >= size[dim]) {
+                                bail!("Voxel {voxel:?} outside model of size {size:?}");
+                            }
+                            Ok(voxel)
+                        })
+                        .collect::<Result<_>>()?;
+                    models.push(VoxModelData { size, voxels });
+                }
+                b"RGBA" => {
+                    // The color at position `i` in the chunk belongs to palette
+                    // index `i + 1`
+                    let mut colors = Box::new([[0; 4]; 256]);
+                    for color in &mut colors[1..] {
+                        *color = content.read_bytes(4)?.try_into().unwrap();
+                    }
+                    palette = Some(colors);
+                }
+                b"nTRN" => {
+                    let node_id = content.read_i32()?;
+                    let attributes = content.read_dict()?;
+                    let child_id = content.read_i32()?;
+                    let _reserved_id = content.read_i32()?;
+                    let _layer_id = content.read_i32()?;
+                    let frame_count = content.read_count()?;
+
+                    // Only the first frame of animated transforms is used
+                    let transform = if frame_count > 0 {
+                        VoxTransform::from_frame_attributes(&content.read_dict()?)?
+                    } else {
+                        VoxTransform::identity()
+                    };
+
+                    let is_hidden = attributes.get("_hidden").is_some_and(|value| value == "1");
+
+                    nodes.insert(
+                        node_id,
+                        VoxSceneNode::Transform {
+                            child_id,
+                            transform,
+                            is_hidden,
+                        },
+                    );
+                }
+                b"nGRP" => {
+                    let node_id = content.read_i32()?;
+                    let _attributes = content.read_dict()?;
+                    let child_count = content.read_count()?;
+                    let child_ids = (0..child_count)
+                        .map(|_| content.read_i32())
+                        .collect::<Result<_>>()?;
+                    nodes.insert(node_id, VoxSceneNode::Group { child_ids });
+                }
+                b"nSHP" => {
+                    let node_id = content.read_i32()?;
+                    let _attributes = content.read_dict()?;
+                    let model_count = content.read_count()?;
+                    let model_ids = (0..model_count)
+                        .map(|_| {
+                            let model_id = content.read_i32()?;
+                            let _model_attributes = content.read_dict()?;
+                            Ok(model_id)
+                        })
+                        .collect::<Result<_>>()?;
+                    nodes.insert(node_id, VoxSceneNode::Shape { model_ids });
+                }
+                // Materials, layers, cameras, render settings and notes do not
+                // affect the voxels
+                _ => {}
+            }
+        }
+
+        let placed_models = if nodes.is_empty() {
+            // Files without a scene graph place all models at the origin
+            models
+                .iter()
+                .map(|model| (model, VoxTransform::identity()))
+                .collect()
+        } else {
+            let mut placed_models = Vec::new();
+            place_models_in_scene_graph(
+                &nodes,
+                &models,
+                0,
+                VoxTransform::identity(),
+                &mut placed_models,
+                0,
+            )?;
+            placed_models
+        };
+
+        Self::merge_placed_models(&placed_models, palette)
+    }
+
+    /// Returns the number of voxels along the x-, y- and z-axis of the model's
+    /// grid, respectively.
+    pub fn shape(&self) -> [usize; 3] {
+        self.shape
+    }
+
+    /// Returns the MagicaVoxel palette index of the voxel at the given indices
+    /// in the model's grid, or zero if the voxel is empty.
+    ///
+    /// # Panics
+    /// If the indices are outside the grid.
+    pub fn palette_idx(&self, i: usize, j: usize, k: usize) -> u8 {
+        self.palette_indices[(i * self.shape[1] + j) * self.shape[2] + k]
+    }
+
+    /// Returns the RGBA color of each palette index, or [`None`] if the file
+    /// uses the default MagicaVoxel palette.
+    pub fn palette(&self) -> Option<&[[u8; 4]; 256]> {
+        self.palette.as_deref()
+    }
+
+    /// Returns the number of occupied voxels in the model.
+    pub fn occupied_voxel_count(&self) -> usize {
+        self.palette_indices.iter().filter(|&&idx| idx != 0).count()
+    }
+
+    fn merge_placed_models(
+        placed_models: &[(&VoxModelData, VoxTransform)],
+        palette: Option<Box<[[u8; 4]; 256]>>,
+    ) -> Result<Self> {
+        let mut placed_voxels = Vec::new();
+
+        for (model, transform) in placed_models {
+            for &[x, y, z, palette_idx] in &model.voxels {
+                // Transform the doubled voxel center coordinates relative to
+                // the model center to stay in integers
+                let doubled_local_center = [
+                    2 * i32::from(x) + 1 - model.size[0] as i32,
+                    2 * i32::from(y) + 1 - model.size[1] as i32,
+                    2 * i32::from(z) + 1 - model.size[2] as i32,
+                ];
+                let doubled_center = transform
+                    .apply_to_doubled(doubled_local_center)
+                    .ok_or_else(|| anyhow!("Placed voxel position out of range"))?;
+                let [cx, cy, cz] = doubled_center.map(|coord| coord.div_euclid(2));
+
+                // Convert from z-up to y-up while keeping the coordinate
+                // system right-handed
+                placed_voxels.push(([cx, cz, -cy - 1], palette_idx));
+            }
+        }
+
+        if placed_voxels.is_empty() {
+            return Ok(Self {
+                shape: [0; 3],
+                palette_indices: Vec::new(),
+                palette,
+            });
+        }
+
+        let mut lower = [i32::MAX; 3];
+        let mut upper = [i32::MIN; 3];
+        for (indices, _) in &placed_voxels {
+            for dim in 0..3 {
+                lower[dim] = lower[dim].min(indices[dim]);
+                upper[dim] = upper[dim].max(indices[dim]);
+            }
+        }
+
+        // The difference can not overflow in 64 bits
+        let shape =
+            [0, 1, 2].map(|dim| (i64::from(upper[dim]) - i64::from(lower[dim]) + 1) as usize);
+        if shape.iter().any(|&extent| extent > MAX_GRID_EXTENT) {
+            bail!(
+                "Placed models span {shape:?} voxels, exceeding the maximum of \
+                 {MAX_GRID_EXTENT} along each axis"
+            );
+        }
+
+        let mut palette_indices = vec![0; shape.iter().product()];
+
+        // Voxels of later models overwrite those of earlier models
+        for ([i, j, k], palette_idx) in placed_voxels {
+            let i = (i - lower[0]) as usize;
+            let j = (j - lower[1]) as usize;
+            let k = (k - lower[2]) as usize;
+            palette_indices[(i * shape[1] + j) * shape[2] + k] = palette_idx;
+        }
+
+        Ok(Self {
+            shape,
+            palette_indices,
+            palette,
+        })
+    }
+}
+
+impl VoxPaletteVoxelTypes {
+    /// Creates the palette voxel type mapping specified in the given voxel
+    /// type registry. Palette indices without an assigned voxel type are
+    /// mapped to the first voxel type.
+    pub fn from_registry(voxel_type_registry: &VoxelTypeRegistry) -> Self {
+        Self(std::array::from_fn(|idx| {
+            voxel_type_registry
+                .voxel_type_for_vox_palette_index(idx as u8)
+                .unwrap_or_default()
+        }))
+    }
+
+    /// Maps all palette indices to the given voxel type.
+    pub fn same(voxel_type: VoxelType) -> Self {
+        Self([voxel_type; 256])
+    }
+}
+
+impl VoxVoxelGenerator {
+    /// Creates a new generator for a voxel object with the given voxel extent
+    /// from the given MagicaVoxel model, assigning voxel types to palette
+    /// indices according to the given mapping. The synthesized signed distance
+    /// field is smoothed the given number of times.
+    pub fn new(
+        voxel_extent: f32,
+        model: &VoxModel,
+        palette_voxel_types: &VoxPaletteVoxelTypes,
+        smoothing_iterations: u32,
+    ) -> Self {
+        assert!(voxel_extent > 0.0);
+
+        if model.shape.contains(&0) {
+            return Self {
+                voxel_extent,
+                grid_shape: [0; 3],
+                signed_distances: Vec::new(),
+                voxel_types: Vec::new(),
+            };
+        }
+
+        // Make room for a border of empty voxels around the object so that the
+        // surface nets meshing algorithm can correctly interpolate distances at
+        // the boundaries
+        let grid_shape = model.shape.map(|n| n + 2);
+        let voxel_count: usize = grid_shape.iter().product();
+
+        let mut palette_indices = vec![0; voxel_count];
+        for i in 0..model.shape[0] {
+            for j in 0..model.shape[1] {
+                for k in 0..model.shape[2] {
+                    palette_indices[linear_idx(&grid_shape, i + 1, j + 1, k + 1)] =
+                        model.palette_idx(i, j, k);
+                }
+            }
+        }
+
+        // The palette index of the closest occupied voxel is propagated along
+        // with the distance so that every voxel that may end up inside the
+        // smoothed surface has a voxel type
+        let (squared_distances_to_occupied, closest_palette_indices) =
+            compute_squared_distances_to_sites(&grid_shape, &palette_indices, |palette_idx| {
+                palette_idx != 0
+            });
+
+        let (squared_distances_to_empty, _) =
+            compute_squared_distances_to_sites(&grid_shape, &palette_indices, |palette_idx| {
+                palette_idx == 0
+            });
+
+        let mut signed_distances: Vec<f32> = palette_indices
+            .iter()
+            .zip(squared_distances_to_occupied)
+            .zip(squared_distances_to_empty)
+            .map(
+                |((&palette_idx, squared_distance_to_occupied), squared_distance_to_empty)| {
+                    let signed_distance = if palette_idx == 0 {
+                        squared_distance_to_occupied.sqrt() - 0.5
+                    } else {
+                        0.5 - squared_distance_to_empty.sqrt()
+                    };
+                    signed_distance.clamp(
+                        VoxelSignedDistance::min_f32(),
+                        VoxelSignedDistance::max_f32(),
+                    )
+                },
+            )
+            .collect();
+
+        for _ in 0..smoothing_iterations {
+            smooth_signed_distances(&grid_shape, &mut signed_distances);
+        }
+
+        let voxel_types = closest_palette_indices
+            .into_iter()
+            .map(|palette_idx| palette_voxel_types.0[usize::from(palette_idx)])
+            .collect();
+
+        Self {
+            voxel_extent,
+            grid_shape,
+            signed_distances: signed_distances
+                .into_iter()
+                .map(VoxelSignedDistance::from_f32)
+                .collect(),
+            voxel_types,
+        }
+    }
+}
+
+impl ChunkedVoxelGenerator for VoxVoxelGenerator {
+    type ChunkGenerationBuffers<AB: Allocator> = ();
+
+    #[inline]
+    fn voxel_extent(&self) -> f32 {
+        self.voxel_extent
+    }
+
+    #[inline]
+    fn grid_shape(&self) -> [usize; 3] {
+        self.grid_shape
+    }
+
+    #[inline]
+    fn total_buffer_size(&self) -> usize {
+        0
+    }
+
+    fn create_buffers_in<AB: Allocator>(&self, _alloc: AB) -> Self::ChunkGenerationBuffers<AB> {}
+
+    fn generate_chunk<AB: Allocator>(
+        &self,
+        _buffers: &mut Self::ChunkGenerationBuffers<AB>,
+        voxels: &mut [Voxel],
+        chunk_origin: &[usize; 3],
+    ) -> ChunkSparseness {
+        assert_eq!(voxels.len(), VoxelObject::chunk_voxel_count());
+
+        if chunk_origin
+            .iter()
+            .zip(self.grid_shape)
+            .any(|(&origin, size)| origin >= size)
+        {
+            voxels.fill(Voxel::maximally_outside());
+            return ChunkSparseness {
+                has_only_empty_voxels: true,
+                is_void: true,
+            };
+        }
+
+        let mut chunk_has_only_empty_voxels = true;
+        let mut chunk_is_void = true;
+
+        LoopForChunkVoxels::over_all().execute_with_linear_idx(
+            &mut |&[i_in_chunk, j_in_chunk, k_in_chunk], idx| {
+                let i = chunk_origin[0] + i_in_chunk;
+                let j = chunk_origin[1] + j_in_chunk;
+                let k = chunk_origin[2] + k_in_chunk;
+
+                voxels[idx] = if i >= self.grid_shape[0]
+                    || j >= self.grid_shape[1]
+                    || k >= self.grid_shape[2]
+                {
+                    Voxel::maximally_outside()
+                } else {
+                    let grid_idx = linear_idx(&self.grid_shape, i, j, k);
+                    let signed_distance = self.signed_distances[grid_idx];
+
+                    if signed_distance.is_negative() {
+                        chunk_has_only_empty_voxels = false;
+                        chunk_is_void = false;
+                        Voxel::non_empty(self.voxel_types[grid_idx], signed_distance)
+                    } else {
+                        if !signed_distance.is_void() {
+                            chunk_is_void = false;
+                        }
+                        Voxel::empty(signed_distance)
+                    }
+                };
+            },
+        );
+
+        ChunkSparseness {
+            has_only_empty_voxels: chunk_has_only_empty_voxels,
+            is_void: chunk_is_void,
+        }
+    }
+}
+
+impl VoxTransform {
+    const fn identity() -> Self {
+        Self {
+            rotation: [[1, 0, 0], [0, 1, 0], [0, 0, 1]],
+            translation: [0; 3],
+        }
+    }
+
+    fn from_frame_attributes(attributes: &HashMap<String, String>) -> Result<Self> {
+        let mut transform = Self::identity();
+
+        if let Some(rotation) = attributes.get("_r") {
+            let rotation: u8 = rotation
+                .parse()
+                .with_context(|| format!("Invalid rotation `{rotation}`"))?;
+            transform.rotation = decode_rotation(rotation)?;
+        }
+
+        if let Some(translation) = attributes.get("_t") {
+            let components = translation
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<Vec<i32>, _>>()
+                .with_context(|| format!("Invalid translation `{translation}`"))?;
+            transform.translation = components
+                .try_into()
+                .map_err(|_| anyhow!("Invalid translation `{translation}`"))?;
+        }
+
+        Ok(transform)
+    }
+
+    /// Returns the transform that applies `self` after `child`, or [`None`]
+    /// if the combined translation overflows.
+    fn then_applied_after(&self, child: &Self) -> Option<Self> {
+        let mut rotation = [[0; 3]; 3];
+        for (row_idx, row) in rotation.iter_mut().enumerate() {
+            for (col_idx, element) in row.iter_mut().enumerate() {
+                *element = (0..3)
+                    .map(|k| self.rotation[row_idx][k] * child.rotation[k][col_idx])
+                    .sum();
+            }
+        }
+        Some(Self {
+            rotation,
+            translation: self.apply_to(child.translation)?,
+        })
+    }
+
+    // Translations come straight from the file, so arithmetic involving them
+    // is checked
+    fn rotate(&self, point: [i32; 3]) -> Option<[i32; 3]> {
+        let mut rotated = [0; 3];
+        for (rotated_coord, row) in rotated.iter_mut().zip(&self.rotation) {
+            for (&element, &coord) in row.iter().zip(&point) {
+                *rotated_coord = rotated_coord.checked_add(element.checked_mul(coord)?)?;
+            }
+        }
+        Some(rotated)
+    }
+
+    fn apply_to(&self, point: [i32; 3]) -> Option<[i32; 3]> {
+        let mut transformed = self.rotate(point)?;
+        for (coord, &translation) in transformed.iter_mut().zip(&self.translation) {
+            *coord = coord.checked_add(translation)?;
+        }
+        Some(transformed)
+    }
+
+    /// Applies the transform to a point whose coordinates have been doubled.
+    fn apply_to_doubled(&self, doubled_point: [i32; 3]) -> Option<[i32; 3]> {
+        let mut transformed = self.rotate(doubled_point)?;
+        for (coord, &translation) in transformed.iter_mut().zip(&self.translation) {
+            *coord = coord.checked_add(translation.checked_mul(2)?)?;
+        }
+        Some(transformed)
+    }
+}
+
+impl<'a> VoxReader<'a> {
+    fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn read_bytes(&mut self, count: usize) -> Result<&'a [u8]> {
+        if self.bytes.len() < count {
+            bail!("Unexpected end of data");
+        }
+        let (bytes, remaining) = self.bytes.split_at(count);
+        self.bytes = remaining;
+        Ok(bytes)
+    }
+
+    fn read_i32(&mut self) -> Result<i32> {
+        Ok(i32::from_le_bytes(self.read_bytes(4)?.try_into().unwrap()))
+    }
+
+    fn read_count(&mut self) -> Result<usize> {
+        let count = self.read_i32()?;
+        usize::try_from(count).map_err(|_| anyhow!("Invalid count {count}"))
+    }
+
+    fn read_string(&mut self) -> Result<String> {
+        let len = self.read_count()?;
+        Ok(String::from_utf8_lossy(self.read_bytes(len)?).into_owned())
+    }
+
+    fn read_dict(&mut self) -> Result<HashMap<String, String>> {
+        let pair_count = self.read_count()?;
+        (0..pair_count)
+            .map(|_| Ok((self.read_string()?, self.read_string()?)))
+            .collect()
+    }
+
+    /// Reads a chunk and returns its ID, content and children.
+    fn read_chunk(&mut self) -> Result<([u8; 4], &'a [u8], &'a [u8])> {
+        let id: [u8; 4] = self.read_bytes(4)?.try_into().unwrap();
+        let content_size = self.read_count()?;
+        let children_size = self.read_count()?;
+        let content = self.read_bytes(content_size)?;
+        let children = self.read_bytes(children_size)?;
+        Ok((id, content, children))
+    }
+}
+
+fn place_models_in_scene_graph<'a>(
+    nodes: &HashMap<i32, VoxSceneNode>,
+    models: &'a [VoxModelData],
+    node_id: i32,
+    parent_transform: VoxTransform,
+    placed_models: &mut Vec<(&'a VoxModelData, VoxTransform)>,
+    depth: usize,
+) -> Result<()> {
+    // Guard against cycles in malformed files
+    if depth > nodes.len() {
+        bail!("Cyclic scene graph");
+    }
+
+    let node = nodes
+        .get(&node_id)
+        .ok_or_else(|| anyhow!("Missing scene graph node {node_id}"))?;
+
+    match node {
+        VoxSceneNode::Transform {
+            child_id,
+            transform,
+            is_hidden,
+        } => {
+            if !is_hidden {
+                let transform = parent_transform
+                    .then_applied_after(transform)
+                    .ok_or_else(|| anyhow!("Node {node_id} translation out of range"))?;
+                place_models_in_scene_graph(
+                    nodes,
+                    models,
+                    *child_id,
+                    transform,
+                    placed_models,
+                    depth + 1,
+                )?;
+            }
+        }
+        VoxSceneNode::Group { child_ids } => {
+            for &child_id in child_ids {
+                place_models_in_scene_graph(
+                    nodes,
+                    models,
+                    child_id,
+                    parent_transform,
+                    placed_models,
+                    depth + 1,
+                )?;
+            }
+        }
+        VoxSceneNode::Shape { model_ids } => {
+            for &model_id in model_ids {
+                let model = usize::try_from(model_id)
+                    .ok()
+                    .and_then(|idx| models.get(idx))
+                    .ok_or_else(|| anyhow!("Missing model {model_id}"))?;
+                placed_models.push((model, parent_transform));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Decodes a MagicaVoxel rotation byte. Bits 0-1 and 2-3 give the column of
+/// the non-zero entry in the first and second row, respectively, and bits 4, 5
+/// and 6 give whether the entries in the three rows are negative.
+fn decode_rotation(rotation: u8) -> Result<[[i32; 3]; 3]> {
+    let first_col = usize::from(rotation & 3);
+    let second_col = usize::from((rotation >> 2) & 3);
+    if first_col > 2 || second_col > 2 || first_col == second_col {
+        bail!("Invalid rotation {rotation}");
+    }
+    let third_col = 3 - first_col - second_col;
+
+    let mut matrix = [[0; 3]; 3];
+    for (row, col) in [first_col, second_col, third_col].into_iter().enumerate() {
+        matrix[row][col] = if rotation & (1 << (4 + row)) != 0 {
+            -1
+        } else {
+            1
+        };
+    }
+    Ok(matrix)
+}
+
+fn linear_idx(shape: &[usize; 3], i: usize, j: usize, k: usize) -> usize {
+    (i * shape[1] + j) * shape[2] + k
+}
+
+/// Computes the squared Euclidean distance from the center of each voxel to
+/// the center of the closest voxel whose palette index satisfies the given
+/// site predicate, together with the palette index of that voxel, using the
+/// separable algorithm of Felzenszwalb and Huttenlocher (2012).
+fn compute_squared_distances_to_sites(
+    shape: &[usize; 3],
+    palette_indices: &[u8],
+    is_site: impl Fn(u8) -> bool,
+) -> (Vec<f32>, Vec<u8>) {
+    let mut squared_distances: Vec<f32> = palette_indices
+        .iter()
+        .map(|&palette_idx| {
+            if is_site(palette_idx) {
+                0.0
+            } else {
+                FAR_SQUARED_DISTANCE
+            }
+        })
+        .collect();
+    let mut features = palette_indices.to_vec();
+
+    let max_len = shape.iter().copied().max().unwrap_or(0);
+    let mut line_distances = vec![0.0; max_len];
+    let mut line_features = vec![0; max_len];
+    let mut transformed_distances = vec![0.0; max_len];
+    let mut transformed_features = vec![0; max_len];
+    let mut parabola_locations = vec![0; max_len];
+    let mut parabola_boundaries = vec![0.0; max_len + 1];
+
+    for_each_grid_line(shape, |len, stride, start| {
+        for q in 0..len {
+            line_distances[q] = squared_distances[start + q * stride];
+            line_features[q] = features[start + q * stride];
+        }
+
+        compute_1d_squared_distance_transform(
+            &line_distances[..len],
+            &line_features[..len],
+            &mut transformed_distances[..len],
+            &mut transformed_features[..len],
+            &mut parabola_locations,
+            &mut parabola_boundaries,
+        );
+
+        for q in 0..len {
+            squared_distances[start + q * stride] =
+                transformed_distances[q].min(FAR_SQUARED_DISTANCE);
+            features[start + q * stride] = transformed_features[q];
+        }
+    });
+
+    (squared_distances, features)
+}
+
+fn compute_1d_squared_distance_transform(
+    distances: &[f32],
+    features: &[u8],
+    transformed_distances: &mut [f32],
+    transformed_features: &mut [u8],
+    parabola_locations: &mut [usize],
+    parabola_boundaries: &mut [f32],
+) {
+    let len = distances.len();
+    let parabola = |q: usize| distances[q] + (q * q) as f32;
+
+    let mut k = 0;
+    parabola_locations[0] = 0;
+    parabola_boundaries[0] = f32::NEG_INFINITY;
+    parabola_boundaries[1] = f32::INFINITY;
+
+    for q in 1..len {
+        let mut s;
+        loop {
+            let v = parabola_locations[k];
+            s = (parabola(q) - parabola(v)) / (2 * (q - v)) as f32;
+            if s <= parabola_boundaries[k] && k > 0 {
+                k -= 1;
+            } else {
+                break;
+            }
+        }
+        k += 1;
+        parabola_locations[k] = q;
+        parabola_boundaries[k] = s;
+        parabola_boundaries[k + 1] = f32::INFINITY;
+    }
+
+    k = 0;
+    for q in 0..len {
+        while parabola_boundaries[k + 1] < q as f32 {
+            k += 1;
+        }
+        let v = parabola_locations[k];
+        transformed_distances[q] = (q.abs_diff(v) * q.abs_diff(v)) as f32 + distances[v];
+        transformed_features[q] = features[v];
+    }
+}
+
+/// Applies a [1, 2, 1] / 4 blur along each axis of the given grid of signed
+/// distances. Values outside the grid are treated as maximally outside.
+fn smooth_signed_distances(shape: &[usize; 3], signed_distances: &mut [f32]) {
+    let max_len = shape.iter().copied().max().unwrap_or(0);
+    let mut line = vec![0.0; max_len];
+
+    for_each_grid_line(shape, |len, stride, start| {
+        for q in 0..len {
+            line[q] = signed_distances[start + q * stride];
+        }
+        for q in 0..len {
+            let previous = if q > 0 {
+                line[q - 1]
+            } else {
+                VoxelSignedDistance::max_f32()
+            };
+            let next = if q + 1 < len {
+                line[q + 1]
+            } else {
+                VoxelSignedDistance::max_f32()
+            };
+            signed_distances[start + q * stride] = 0.25 * (previous + 2.0 * line[q] + next);
+        }
+    });
+}
+
+/// Calls the given closure with the length, stride and start index of every
+/// line of voxels along each axis of a grid with the given shape, one axis at
+/// a time.
+fn for_each_grid_line(shape: &[usize; 3], mut f: impl FnMut(usize, usize, usize)) {
+    let strides = [shape[1] * shape[2], shape[2], 1];
+    for axis in 0..3 {
+        let other_axis_1 = (axis + 1) % 3;
+        let other_axis_2 = (axis + 2) % 3;
+        for idx_1 in 0..shape[other_axis_1] {
+            for idx_2 in 0..shape[other_axis_2] {
+                let start = idx_1 * strides[other_axis_1] + idx_2 * strides[other_axis_2];
+                f(shape[axis], strides[axis], start);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object::VoxelObjectBuffers;
+
+    fn chunk(id: &[u8; 4], content: &[u8], children: &[u8]) -> Vec<u8> {
+        let mut bytes = id.to_vec();
+        bytes.extend_from_slice(&(content.len() as i32).to_le_bytes());
+        bytes.extend_from_slice(&(children.len() as i32).to_le_bytes());
+        bytes.extend_from_slice(content);
+        bytes.extend_from_slice(children);
+        bytes
+    }
+
+    fn string(value: &str) -> Vec<u8> {
+        let mut bytes = (value.len() as i32).to_le_bytes().to_vec();
+        bytes.extend_from_slice(value.as_bytes());
+        bytes
+    }
+
+    fn dict(pairs: &[(&str, &str)]) -> Vec<u8> {
+        let mut bytes = (pairs.len() as i32).to_le_bytes().to_vec();
+        for (key, value) in pairs {
+            bytes.extend(string(key));
+            bytes.extend(string(value));
+        }
+        bytes
+    }
+
+    fn ints(values: &[i32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+
+    fn model_chunks(size: [i32; 3], voxels: &[[u8; 4]]) -> Vec<u8> {
+        let mut xyzi = ints(&[voxels.len() as i32]);
+        xyzi.extend(voxels.iter().flatten());
+        let mut bytes = chunk(b"SIZE", &ints(&size), &[]);
+        bytes.extend(chunk(b"XYZI", &xyzi, &[]));
+        bytes
+    }
+
+    fn vox_file(children: &[u8]) -> Vec<u8> {
+        let mut bytes = b"VOX ".to_vec();
+        bytes.extend(ints(&[150]));
+        bytes.extend(chunk(b"MAIN", &[], children));
+        bytes
+    }
+
+    fn filled_box_voxels(size: [u8; 3], palette_idx: u8) -> Vec<[u8; 4]> {
+        let mut voxels = Vec::new();
+        for x in 0..size[0] {
+            for y in 0..size[1] {
+                for z in 0..size[2] {
+                    voxels.push([x, y, z, palette_idx]);
+                }
+            }
+        }
+        voxels
+    }
+
+    fn transform_node(node_id: i32, child_id: i32, frame: &[(&str, &str)]) -> Vec<u8> {
+        let mut content = ints(&[node_id]);
+        content.extend(dict(&[]));
+        content.extend(ints(&[child_id, -1, 0, 1]));
+        content.extend(dict(frame));
+        chunk(b"nTRN", &content, &[])
+    }
+
+    fn group_node(node_id: i32, child_ids: &[i32]) -> Vec<u8> {
+        let mut content = ints(&[node_id]);
+        content.extend(dict(&[]));
+        content.extend(ints(&[child_ids.len() as i32]));
+        content.extend(ints(child_ids));
+        chunk(b"nGRP", &content, &[])
+    }
+
+    fn shape_node(node_id: i32, model_id: i32) -> Vec<u8> {
+        let mut content = ints(&[node_id]);
+        content.extend(dict(&[]));
+        content.extend(ints(&[1, model_id]));
+        content.extend(dict(&[]));
+        chunk(b"nSHP", &content, &[])
+    }
+
+    fn count_non_empty_voxels(object: &VoxelObject) -> usize {
+        let [nx, ny, nz] = object
+            .chunk_counts()
+            .map(|count| count * VoxelObject::chunk_size());
+        let mut count = 0;
+        for i in 0..nx {
+            for j in 0..ny {
+                for k in 0..nz {
+                    if let Some(voxel) = object.get_voxel_if_occupied(i, j, k)
+                        && !voxel.is_empty()
+                    {
+                        count += 1;
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn parsing_file_with_single_model_gives_correct_voxels() {
+        let bytes = vox_file(&model_chunks([2, 3, 4], &[[0, 0, 0, 7], [1, 2, 3, 9]]));
+
+        let model = VoxModel::from_bytes(&bytes).unwrap();
+
+        // The z-axis of the file becomes the y-axis of the model
+        assert_eq!(model.shape(), [2, 4, 3]);
+        assert_eq!(model.occupied_voxel_count(), 2);
+        assert_eq!(model.palette_idx(0, 0, 2), 7);
+        assert_eq!(model.palette_idx(1, 3, 0), 9);
+        assert!(model.palette().is_none());
+    }
+
+    #[test]
+    fn parsing_file_with_palette_gives_correct_colors() {
+        let mut palette = Vec::new();
+        for idx in 0..256_u32 {
+            palette.extend_from_slice(&[idx as u8, 0, 0, 255]);
+        }
+        let mut children = model_chunks([1, 1, 1], &[[0, 0, 0, 1]]);
+        children.extend(chunk(b"RGBA", &palette, &[]));
+
+        let model = VoxModel::from_bytes(&vox_file(&children)).unwrap();
+
+        let palette = model.palette().unwrap();
+        assert_eq!(palette[1], [0, 0, 0, 255]);
+        assert_eq!(palette[255], [254, 0, 0, 255]);
+    }
+
+    #[test]
+    fn parsing_file_with_scene_graph_applies_transforms() {
+        let mut children = model_chunks([2, 2, 2], &filled_box_voxels([2, 2, 2], 1));
+        children.extend(model_chunks([1, 1, 1], &[[0, 0, 0, 2]]));
+        children.extend(transform_node(0, 1, &[]));
+        children.extend(group_node(1, &[2, 4]));
+        children.extend(transform_node(2, 3, &[("_t", "10 0 0")]));
+        children.extend(shape_node(3, 0));
+        // Rotate the second model by 90 degrees about the z-axis
+        children.extend(transform_node(4, 5, &[("_t", "0 0 0"), ("_r", "17")]));
+        children.extend(shape_node(5, 1));
+
+        let model = VoxModel::from_bytes(&vox_file(&children)).unwrap();
+
+        assert_eq!(model.occupied_voxel_count(), 9);
+        assert_eq!(model.shape(), [11, 2, 2]);
+        assert_eq!(model.palette_idx(0, 1, 0), 2);
+        assert_eq!(model.palette_idx(9, 0, 0), 1);
+        assert_eq!(model.palette_idx(10, 1, 1), 1);
+    }
+
+    #[test]
+    fn parsing_file_skips_hidden_nodes() {
+        let mut children = model_chunks([1, 1, 1], &[[0, 0, 0, 1]]);
+        let mut content = ints(&[0]);
+        content.extend(dict(&[("_hidden", "1")]));
+        content.extend(ints(&[1, -1, 0, 1]));
+        content.extend(dict(&[]));
+        children.extend(chunk(b"nTRN", &content, &[]));
+        children.extend(shape_node(1, 0));
+
+        let model = VoxModel::from_bytes(&vox_file(&children)).unwrap();
+
+        assert_eq!(model.occupied_voxel_count(), 0);
+    }
+
+    #[test]
+    fn parsing_invalid_files_fails() {
+        assert!(VoxModel::from_bytes(b"NOPE").is_err());
+        assert!(VoxModel::from_bytes(&vox_file(&chunk(b"XYZI", &ints(&[0]), &[]))).is_err());
+        assert!(
+            VoxModel::from_bytes(&vox_file(&model_chunks([1, 1, 1], &[[1, 0, 0, 1]]))).is_err()
+        );
+    }
+
+    #[test]
+    fn parsing_file_with_overflowing_translation_fails() {
+        let mut children = model_chunks([1, 1, 1], &[[0, 0, 0, 1]]);
+        children.extend(transform_node(0, 1, &[]));
+        children.extend(group_node(1, &[2]));
+        children.extend(transform_node(2, 3, &[("_t", "2147483647 0 0")]));
+        children.extend(transform_node(3, 4, &[("_t", "1 0 0")]));
+        children.extend(shape_node(4, 0));
+
+        assert!(VoxModel::from_bytes(&vox_file(&children)).is_err());
+
+        let mut children = model_chunks([1, 1, 1], &[[0, 0, 0, 1]]);
+        children.extend(transform_node(0, 1, &[("_t", "2000000000 0 0")]));
+        children.extend(shape_node(1, 0));
+
+        assert!(VoxModel::from_bytes(&vox_file(&children)).is_err());
+    }
+
+    #[test]
+    fn parsing_file_with_models_spanning_too_large_grid_fails() {
+        let mut children = model_chunks([1, 1, 1], &[[0, 0, 0, 1]]);
+        children.extend(transform_node(0, 1, &[]));
+        children.extend(group_node(1, &[2, 4]));
+        children.extend(transform_node(2, 3, &[("_t", "-5000 0 0")]));
+        children.extend(shape_node(3, 0));
+        children.extend(transform_node(4, 5, &[("_t", "5000 0 0")]));
+        children.extend(shape_node(5, 0));
+
+        assert!(VoxModel::from_bytes(&vox_file(&children)).is_err());
+    }
+
+    #[test]
+    fn decoding_identity_rotation_works() {
+        assert_eq!(
+            decode_rotation(4).unwrap(),
+            [[1, 0, 0], [0, 1, 0], [0, 0, 1]]
+        );
+        assert!(decode_rotation(0).is_err());
+    }
+
+    #[test]
+    fn decoding_rotation_with_permutation_and_sign_works() {
+        assert_eq!(
+            decode_rotation(17).unwrap(),
+            [[0, -1, 0], [1, 0, 0], [0, 0, 1]]
+        );
+    }
+
+    #[test]
+    fn squared_distances_to_sites_are_exact() {
+        let shape = [1, 1, 7];
+        let palette_indices = [0, 0, 3, 0, 0, 0, 5];
+        let (squared_distances, features) =
+            compute_squared_distances_to_sites(&shape, &palette_indices, |idx| idx != 0);
+        assert_eq!(squared_distances, [4.0, 1.0, 0.0, 1.0, 4.0, 1.0, 0.0]);
+        assert_eq!(features, [3, 3, 3, 3, 3, 5, 5]);
+    }
+
+    #[test]
+    fn generating_object_from_box_model_gives_box_with_mapped_voxel_types() {
+        let bytes = vox_file(&model_chunks([6, 6, 6], &filled_box_voxels([6, 6, 6], 4)));
+        let model = VoxModel::from_bytes(&bytes).unwrap();
+
+        let mut palette_voxel_types = VoxPaletteVoxelTypes::same(VoxelType::from_idx(0));
+        palette_voxel_types.0[4] = VoxelType::from_idx(3);
+
+        let generator = VoxVoxelGenerator::new(0.25, &model, &palette_voxel_types, 0);
+        let object = VoxelObject::generate(VoxelObjectBuffers::new(), &generator);
+
+        assert_eq!(count_non_empty_voxels(&object), 216);
+        assert_eq!(
+            object.get_voxel_if_occupied(3, 3, 3).unwrap().voxel_type(),
+            VoxelType::from_idx(3)
+        );
+
+        object.validate_adjacencies();
+        object.validate_chunk_obscuredness();
+        object.validate_region_count();
+    }
+
+    #[test]
+    fn smoothing_preserves_interior_of_large_box() {
+        let bytes = vox_file(&model_chunks([8, 8, 8], &filled_box_voxels([8, 8, 8], 1)));
+        let model = VoxModel::from_bytes(&bytes).unwrap();
+
+        let generator = VoxVoxelGenerator::new(
+            0.25,
+            &model,
+            &VoxPaletteVoxelTypes::same(VoxelType::default()),
+            2,
+        );
+        let object = VoxelObject::generate(VoxelObjectBuffers::new(), &generator);
+
+        let count = count_non_empty_voxels(&object);
+        assert!(count > 6 * 6 * 6);
+        assert!(count <= 8 * 8 * 8);
+    }
+}
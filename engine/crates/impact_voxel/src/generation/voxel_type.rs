@@ -1,6 +1,14 @@
 //! Generation of voxel type distributions.
 
-use crate::{Voxel, object::VoxelObject, voxel_types::VoxelType};
+pub mod graph;
+
+use crate::{
+    Voxel,
+    object::VoxelObject,
+    voxel_types::{VoxelType, VoxelTypeRegistry},
+};
+use anyhow::Result;
+use graph::{GraphVoxelTypeGenerator, VoxelTypeGraph, VoxelTypeTag};
 use impact_alloc::{AVec, Allocator, avec};
 use impact_math::point::Point3;
 use simdnoise::{NoiseBuilder, Settings};
@@ -11,11 +19,13 @@ use std::mem;
 pub enum VoxelTypeGenerator {
     Same(SameVoxelTypeGenerator),
     GradientNoise(GradientNoiseVoxelTypeGenerator),
+    Graph(GraphVoxelTypeGenerator),
 }
 
 #[derive(Clone, Debug)]
 pub struct VoxelTypeGeneratorChunkBuffers<A: Allocator> {
     gradient_noise: AVec<f32, A>,
+    selection_noise: AVec<f32, A>,
 }
 
 /// Voxel type generator that always returns the same voxel type.
@@ -36,26 +46,63 @@ pub struct GradientNoiseVoxelTypeGenerator {
 }
 
 impl VoxelTypeGenerator {
+    /// Wraps this generator in a [`GraphVoxelTypeGenerator`] for the given
+    /// voxel type graph, so that this generator is only used for voxels
+    /// without a voxel type tag. If the graph has no tags, this generator is
+    /// returned unchanged.
+    ///
+    /// # Errors
+    /// Returns an error if any of the voxel types in the graph is not present
+    /// in the registry.
+    pub fn with_graph<A: Allocator>(
+        self,
+        graph: &VoxelTypeGraph<A>,
+        voxel_type_registry: &VoxelTypeRegistry,
+    ) -> Result<Self> {
+        if graph.is_empty() {
+            return Ok(self);
+        }
+        Ok(GraphVoxelTypeGenerator::new(graph, voxel_type_registry, self)?.into())
+    }
+
     pub fn total_buffer_size(&self) -> usize {
         match self {
             Self::Same(_) => 0,
             Self::GradientNoise(generator) => generator.noise_buffer_size(),
+            Self::Graph(generator) => {
+                generator.noise_buffer_size() + generator.fallback().total_buffer_size()
+            }
         }
     }
 
     pub fn create_buffers_in<A: Allocator>(&self, alloc: A) -> VoxelTypeGeneratorChunkBuffers<A> {
-        let gradient_noise = match self {
-            Self::Same(_) => AVec::new_in(alloc),
-            Self::GradientNoise(generator) => generator.create_noise_buffer_in(alloc),
-        };
-        VoxelTypeGeneratorChunkBuffers { gradient_noise }
+        match self {
+            Self::Same(_) => VoxelTypeGeneratorChunkBuffers {
+                gradient_noise: AVec::new_in(alloc),
+                selection_noise: AVec::new_in(alloc),
+            },
+            Self::GradientNoise(generator) => VoxelTypeGeneratorChunkBuffers {
+                gradient_noise: generator.create_noise_buffer_in(alloc),
+                selection_noise: AVec::new_in(alloc),
+            },
+            Self::Graph(generator) => VoxelTypeGeneratorChunkBuffers {
+                selection_noise: generator.create_noise_buffer_in(alloc),
+                ..generator.fallback().create_buffers_in(alloc)
+            },
+        }
     }
 
+    /// Sets the types of the given voxels in the chunk with the given origin.
+    /// The signed distances and voxel type tags of the voxels, as computed
+    /// by the SDF generator, are used by generators that select voxel types
+    /// based on the local properties of the signed distance field.
     pub fn set_voxel_types_for_chunk<A: Allocator>(
         &self,
         voxels: &mut [Voxel],
         buffers: &mut VoxelTypeGeneratorChunkBuffers<A>,
         chunk_origin: &Point3,
+        signed_distances: &[f32],
+        voxel_type_tags: Option<&[VoxelTypeTag]>,
     ) {
         match self {
             Self::Same(generator) => {
@@ -64,6 +111,15 @@ impl VoxelTypeGenerator {
             Self::GradientNoise(generator) => {
                 generator.set_voxel_types_for_chunk(voxels, buffers, chunk_origin);
             }
+            Self::Graph(generator) => {
+                generator.set_voxel_types_for_chunk(
+                    voxels,
+                    buffers,
+                    chunk_origin,
+                    signed_distances,
+                    voxel_type_tags,
+                );
+            }
         }
     }
 }
@@ -80,6 +136,12 @@ impl From<GradientNoiseVoxelTypeGenerator> for VoxelTypeGenerator {
     }
 }
 
+impl From<GraphVoxelTypeGenerator> for VoxelTypeGenerator {
+    fn from(generator: GraphVoxelTypeGenerator) -> Self {
+        Self::Graph(generator)
+    }
+}
+
 impl SameVoxelTypeGenerator {
     pub fn new(voxel_type: VoxelType) -> Self {
         Self { voxel_type }
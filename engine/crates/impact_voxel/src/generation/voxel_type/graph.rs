@@ -0,0 +1,684 @@
+//! Graph-based generation of voxel types.
+
+use crate::{
+    Voxel,
+    generation::voxel_type::{VoxelTypeGenerator, VoxelTypeGeneratorChunkBuffers},
+    object::VoxelObject,
+    voxel_types::{VoxelType, VoxelTypeRegistry},
+};
+use anyhow::{Result, anyhow, bail};
+use impact_alloc::{AVec, Allocator, avec};
+use impact_math::point::Point3;
+use simdnoise::{NoiseBuilder, Settings};
+use std::{array, mem};
+
+/// Tag assigned to parts of a signed distance field to identify the root node
+/// in a [`VoxelTypeGraph`] that determines the voxel types there.
+pub type VoxelTypeTag = u16;
+
+/// The voxel type tag of parts of a signed distance field that have not been
+/// assigned a tag.
+pub const UNTAGGED: VoxelTypeTag = 0;
+
+pub type VoxelTypeNodeID = u32;
+
+/// A graph of nodes selecting voxel types based on local properties of a
+/// signed distance field, such as the depth below the surface. Each voxel type
+/// tag assigned in the SDF graph corresponds to a root node in this graph.
+///
+/// The voxel types are specified by name and must be resolved against a
+/// [`VoxelTypeRegistry`] to create a [`GraphVoxelTypeGenerator`].
+#[derive(Clone, Debug)]
+pub struct VoxelTypeGraph<A: Allocator> {
+    nodes: AVec<VoxelTypeNode<String>, A>,
+    /// The root node for each voxel type tag, offset by one since
+    /// [`UNTAGGED`] has no root.
+    tag_roots: AVec<VoxelTypeNodeID, A>,
+    max_selection_depth: f32,
+}
+
+/// A node in a voxel type graph, with voxel types specified as `T`.
+#[derive(Clone, Debug)]
+pub enum VoxelTypeNode<T> {
+    VoxelType(T),
+    Selection(VoxelTypeSelection),
+}
+
+/// Selects between two voxel type nodes based on whether a local property of
+/// the signed distance field lies below or above a threshold.
+#[derive(Clone, Debug)]
+pub struct VoxelTypeSelection {
+    pub quantity: VoxelTypeSelectionQuantity,
+    pub threshold: f32,
+    /// ID of the node to use where the quantity is below the threshold.
+    pub below_id: VoxelTypeNodeID,
+    /// ID of the node to use where the quantity is at or above the threshold.
+    pub above_id: VoxelTypeNodeID,
+}
+
+/// A local property of a signed distance field that voxel types can be
+/// selected on.
+#[derive(Clone, Copy, Debug)]
+pub enum VoxelTypeSelectionQuantity {
+    /// The depth below the surface, in voxels.
+    Depth,
+    /// The angle between the surface normal and the y-axis, in degrees.
+    Slope,
+    /// The Laplacian of the signed distance field, which near the surface
+    /// equals the sum of its principal curvatures in inverse voxels. It is
+    /// positive for convex and negative for concave surfaces.
+    Curvature,
+    /// A gradient noise pattern with the given spatial frequency (in inverse
+    /// voxels) and seed.
+    Noise { frequency: f32, seed: u32 },
+}
+
+/// Voxel type generator that determines the types of voxels assigned a voxel
+/// type tag by evaluating the root node of a voxel type graph for the tag.
+/// Untagged voxels get their types from a fallback generator.
+#[derive(Clone, Debug)]
+pub struct GraphVoxelTypeGenerator {
+    nodes: Vec<VoxelTypeNode<VoxelType>>,
+    tag_roots: Vec<VoxelTypeNodeID>,
+    /// For each node that selects on noise, the index of its noise pattern in
+    /// the selection noise buffer.
+    noise_indices: Vec<Option<usize>>,
+    noise_count: usize,
+    fallback: Box<VoxelTypeGenerator>,
+}
+
+const CHUNK_SIZE: usize = VoxelObject::chunk_size();
+
+/// The linear index offset between adjacent voxels in a chunk along each axis.
+const CHUNK_AXIS_STRIDES: [usize; 3] = [CHUNK_SIZE * CHUNK_SIZE, CHUNK_SIZE, 1];
+
+impl<A: Allocator> VoxelTypeGraph<A> {
+    pub fn new_in(alloc: A) -> Self {
+        Self {
+            nodes: AVec::new_in(alloc),
+            tag_roots: AVec::new_in(alloc),
+            max_selection_depth: 0.0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tag_roots.is_empty()
+    }
+
+    pub fn nodes(&self) -> &[VoxelTypeNode<String>] {
+        &self.nodes
+    }
+
+    /// Returns the largest depth below the surface that any node selects
+    /// voxel types on, or zero if no node selects on depth.
+    pub fn max_selection_depth(&self) -> f32 {
+        self.max_selection_depth
+    }
+
+    /// Returns the ID of the root node for the given voxel type tag.
+    ///
+    /// # Panics
+    /// If the tag is [`UNTAGGED`] or has not been added to the graph.
+    pub fn root_node_id_for_tag(&self, tag: VoxelTypeTag) -> VoxelTypeNodeID {
+        assert_ne!(tag, UNTAGGED);
+        self.tag_roots[usize::from(tag - 1)]
+    }
+
+    pub fn add_voxel_type_node(&mut self, voxel_type_name: String) -> VoxelTypeNodeID {
+        self.add_node(VoxelTypeNode::VoxelType(voxel_type_name))
+    }
+
+    /// # Panics
+    /// If any of the child nodes of the selection is not present in the graph.
+    pub fn add_selection_node(&mut self, selection: VoxelTypeSelection) -> VoxelTypeNodeID {
+        assert!((selection.below_id as usize) < self.nodes.len());
+        assert!((selection.above_id as usize) < self.nodes.len());
+
+        if let VoxelTypeSelectionQuantity::Depth = selection.quantity {
+            self.max_selection_depth = self.max_selection_depth.max(selection.threshold);
+        }
+
+        self.add_node(VoxelTypeNode::Selection(selection))
+    }
+
+    /// Creates a new voxel type tag whose voxel types will be determined by
+    /// the given root node.
+    ///
+    /// # Errors
+    /// Returns an error if there are no more available tags.
+    ///
+    /// # Panics
+    /// If the node is not present in the graph.
+    pub fn add_tag(&mut self, root_node_id: VoxelTypeNodeID) -> Result<VoxelTypeTag> {
+        assert!((root_node_id as usize) < self.nodes.len());
+
+        let tag = VoxelTypeTag::try_from(self.tag_roots.len() + 1)
+            .map_err(|_| anyhow!("Exceeded maximum number of voxel type tags"))?;
+
+        self.tag_roots.push(root_node_id);
+
+        Ok(tag)
+    }
+
+    fn add_node(&mut self, node: VoxelTypeNode<String>) -> VoxelTypeNodeID {
+        let node_id = self.nodes.len().try_into().unwrap();
+        self.nodes.push(node);
+        node_id
+    }
+}
+
+impl GraphVoxelTypeGenerator {
+    /// Creates a new generator for the given voxel type graph, looking up the
+    /// voxel types by name in the given registry. Voxels without a voxel type
+    /// tag will get their types from the given fallback generator.
+    ///
+    /// # Errors
+    /// Returns an error if any of the voxel types in the graph is not present
+    /// in the registry.
+    pub fn new<A: Allocator>(
+        graph: &VoxelTypeGraph<A>,
+        voxel_type_registry: &VoxelTypeRegistry,
+        fallback: VoxelTypeGenerator,
+    ) -> Result<Self> {
+        let mut nodes = Vec::with_capacity(graph.nodes.len());
+        let mut noise_indices = Vec::with_capacity(graph.nodes.len());
+        let mut noise_count = 0;
+
+        for node in &graph.nodes {
+            let mut noise_idx = None;
+
+            nodes.push(match node {
+                VoxelTypeNode::VoxelType(name) => {
+                    let Some(voxel_type) = voxel_type_registry.voxel_type_for_name(name) else {
+                        bail!("Missing voxel type `{name}` in voxel type graph");
+                    };
+                    VoxelTypeNode::VoxelType(voxel_type)
+                }
+                VoxelTypeNode::Selection(selection) => {
+                    if let VoxelTypeSelectionQuantity::Noise { .. } = selection.quantity {
+                        noise_idx = Some(noise_count);
+                        noise_count += 1;
+                    }
+                    VoxelTypeNode::Selection(selection.clone())
+                }
+            });
+
+            noise_indices.push(noise_idx);
+        }
+
+        Ok(Self {
+            nodes,
+            tag_roots: graph.tag_roots.to_vec(),
+            noise_indices,
+            noise_count,
+            fallback: Box::new(fallback),
+        })
+    }
+
+    /// Creates a new generator from already resolved voxel type nodes.
+    ///
+    /// # Panics
+    /// If any node refers to a node that does not precede it, or if any tag
+    /// root is not present.
+    pub fn from_resolved_nodes(
+        nodes: Vec<VoxelTypeNode<VoxelType>>,
+        tag_roots: Vec<VoxelTypeNodeID>,
+        fallback: VoxelTypeGenerator,
+    ) -> Self {
+        let mut noise_indices = Vec::with_capacity(nodes.len());
+        let mut noise_count = 0;
+
+        for (node_idx, node) in nodes.iter().enumerate() {
+            let mut noise_idx = None;
+
+            if let VoxelTypeNode::Selection(selection) = node {
+                assert!((selection.below_id as usize) < node_idx);
+                assert!((selection.above_id as usize) < node_idx);
+
+                if let VoxelTypeSelectionQuantity::Noise { .. } = selection.quantity {
+                    noise_idx = Some(noise_count);
+                    noise_count += 1;
+                }
+            }
+
+            noise_indices.push(noise_idx);
+        }
+
+        assert!(
+            tag_roots
+                .iter()
+                .all(|&root_node_id| (root_node_id as usize) < nodes.len())
+        );
+
+        Self {
+            nodes,
+            tag_roots,
+            noise_indices,
+            noise_count,
+            fallback: Box::new(fallback),
+        }
+    }
+
+    pub fn fallback(&self) -> &VoxelTypeGenerator {
+        &self.fallback
+    }
+
+    pub(super) fn noise_buffer_size(&self) -> usize {
+        self.noise_buffer_len() * mem::size_of::<f32>()
+    }
+
+    fn noise_buffer_len(&self) -> usize {
+        VoxelObject::chunk_voxel_count() * self.noise_count
+    }
+
+    pub(super) fn create_noise_buffer_in<A: Allocator>(&self, alloc: A) -> AVec<f32, A> {
+        avec![in alloc; 0.0; self.noise_buffer_len()]
+    }
+
+    pub(super) fn set_voxel_types_for_chunk<A: Allocator>(
+        &self,
+        voxels: &mut [Voxel],
+        buffers: &mut VoxelTypeGeneratorChunkBuffers<A>,
+        chunk_origin: &Point3,
+        signed_distances: &[f32],
+        voxel_type_tags: Option<&[VoxelTypeTag]>,
+    ) {
+        assert_eq!(voxels.len(), VoxelObject::chunk_voxel_count());
+        assert_eq!(signed_distances.len(), VoxelObject::chunk_voxel_count());
+
+        self.fallback.set_voxel_types_for_chunk(
+            voxels,
+            buffers,
+            chunk_origin,
+            signed_distances,
+            voxel_type_tags,
+        );
+
+        let Some(voxel_type_tags) = voxel_type_tags else {
+            return;
+        };
+
+        if voxel_type_tags.iter().all(|&tag| tag == UNTAGGED) {
+            return;
+        }
+
+        self.generate_selection_noise(&mut buffers.selection_noise, chunk_origin);
+
+        for (idx, (voxel, &tag)) in voxels.iter_mut().zip(voxel_type_tags).enumerate() {
+            if tag == UNTAGGED || voxel.is_empty() {
+                continue;
+            }
+            let voxel_type =
+                self.evaluate_for_voxel(tag, idx, signed_distances, &buffers.selection_noise);
+            voxel.set_voxel_type(voxel_type);
+        }
+    }
+
+    fn generate_selection_noise(&self, selection_noise: &mut [f32], chunk_origin: &Point3) {
+        for (node, noise_idx) in self.nodes.iter().zip(&self.noise_indices) {
+            let (
+                Some(noise_idx),
+                VoxelTypeNode::Selection(VoxelTypeSelection {
+                    quantity: VoxelTypeSelectionQuantity::Noise { frequency, seed },
+                    ..
+                }),
+            ) = (noise_idx, node)
+            else {
+                continue;
+            };
+
+            let chunk_voxel_count = VoxelObject::chunk_voxel_count();
+            let noise = &mut selection_noise
+                [noise_idx * chunk_voxel_count..(noise_idx + 1) * chunk_voxel_count];
+
+            NoiseBuilder::gradient_3d_offset(
+                // Warning: We reverse the order of dimensions here because the
+                // generated noise is laid out in row-major order
+                chunk_origin.z(),
+                CHUNK_SIZE,
+                chunk_origin.y(),
+                CHUNK_SIZE,
+                chunk_origin.x(),
+                CHUNK_SIZE,
+            )
+            .with_freq(*frequency)
+            .with_seed(*seed as i32)
+            .generate(noise);
+        }
+    }
+
+    fn evaluate_for_voxel(
+        &self,
+        tag: VoxelTypeTag,
+        idx: usize,
+        signed_distances: &[f32],
+        selection_noise: &[f32],
+    ) -> VoxelType {
+        let mut node_id = self.tag_roots[usize::from(tag - 1)];
+        loop {
+            let node_idx = node_id as usize;
+            match &self.nodes[node_idx] {
+                VoxelTypeNode::VoxelType(voxel_type) => {
+                    return *voxel_type;
+                }
+                VoxelTypeNode::Selection(selection) => {
+                    let value = match selection.quantity {
+                        VoxelTypeSelectionQuantity::Depth => -signed_distances[idx],
+                        VoxelTypeSelectionQuantity::Slope => {
+                            compute_slope_angle(signed_distances, idx)
+                        }
+                        VoxelTypeSelectionQuantity::Curvature => {
+                            compute_curvature(signed_distances, idx)
+                        }
+                        VoxelTypeSelectionQuantity::Noise { .. } => {
+                            let noise_idx = self.noise_indices[node_idx].unwrap();
+                            selection_noise[noise_idx * VoxelObject::chunk_voxel_count() + idx]
+                        }
+                    };
+                    node_id = if value < selection.threshold {
+                        selection.below_id
+                    } else {
+                        selection.above_id
+                    };
+                }
+            }
+        }
+    }
+}
+
+/// Returns the signed distances of the two neighbors of the voxel at the given
+/// linear index in a chunk along the given axis, together with the number of
+/// voxels between them. Neighbors outside the chunk are replaced with the
+/// voxel itself.
+fn neighbor_signed_distances(signed_distances: &[f32], idx: usize, axis: usize) -> (f32, f32, f32) {
+    let stride = CHUNK_AXIS_STRIDES[axis];
+    let coord = (idx / stride) % CHUNK_SIZE;
+
+    let (lower, lower_steps) = if coord > 0 {
+        (signed_distances[idx - stride], 1.0)
+    } else {
+        (signed_distances[idx], 0.0)
+    };
+    let (upper, upper_steps) = if coord + 1 < CHUNK_SIZE {
+        (signed_distances[idx + stride], 1.0)
+    } else {
+        (signed_distances[idx], 0.0)
+    };
+
+    (lower, upper, lower_steps + upper_steps)
+}
+
+fn compute_slope_angle(signed_distances: &[f32], idx: usize) -> f32 {
+    let [gradient_x, gradient_y, gradient_z] = array::from_fn(|axis| {
+        let (lower, upper, steps) = neighbor_signed_distances(signed_distances, idx, axis);
+        (upper - lower) / steps
+    });
+
+    let gradient_norm =
+        (gradient_x * gradient_x + gradient_y * gradient_y + gradient_z * gradient_z).sqrt();
+
+    if gradient_norm == 0.0 {
+        return 0.0;
+    }
+
+    (gradient_y / gradient_norm)
+        .clamp(-1.0, 1.0)
+        .acos()
+        .to_degrees()
+}
+
+fn compute_curvature(signed_distances: &[f32], idx: usize) -> f32 {
+    let center = signed_distances[idx];
+    (0..3)
+        .map(|axis| {
+            let (lower, upper, _) = neighbor_signed_distances(signed_distances, idx, axis);
+            lower + upper - 2.0 * center
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        VoxelSignedDistance,
+        generation::{
+            sdf::{SDFGraph, SDFNode, SDFNodeID},
+            voxel_type::{SameVoxelTypeGenerator, VoxelTypeGenerator},
+        },
+        object::LoopForChunkVoxels,
+    };
+    use impact_alloc::Global;
+    use impact_geometry::AxisAlignedBox;
+    use impact_math::vector::Vector3;
+
+    fn voxel_type(idx: usize) -> VoxelType {
+        VoxelType::from_idx(idx)
+    }
+
+    fn create_generator(
+        nodes: Vec<VoxelTypeNode<VoxelType>>,
+        tag_roots: Vec<VoxelTypeNodeID>,
+    ) -> GraphVoxelTypeGenerator {
+        GraphVoxelTypeGenerator::from_resolved_nodes(
+            nodes,
+            tag_roots,
+            SameVoxelTypeGenerator::new(voxel_type(0)).into(),
+        )
+    }
+
+    fn generate_chunk(
+        generator: &VoxelTypeGenerator,
+        signed_distance: impl Fn([usize; 3]) -> f32,
+        tag: impl Fn([usize; 3]) -> VoxelTypeTag,
+    ) -> Vec<Voxel> {
+        let count = VoxelObject::chunk_voxel_count();
+        let mut signed_distances = vec![0.0; count];
+        let mut tags = vec![UNTAGGED; count];
+        let mut voxels = vec![Voxel::maximally_outside(); count];
+
+        LoopForChunkVoxels::over_all().execute_with_linear_idx(&mut |&indices, idx| {
+            let distance = signed_distance(indices);
+            signed_distances[idx] = distance;
+            tags[idx] = tag(indices);
+            voxels[idx] = if distance < 0.0 {
+                Voxel::non_empty(VoxelType::dummy(), VoxelSignedDistance::from_f32(distance))
+            } else {
+                Voxel::empty(VoxelSignedDistance::from_f32(distance))
+            };
+        });
+
+        let mut buffers = generator.create_buffers_in(Global);
+        generator.set_voxel_types_for_chunk(
+            &mut voxels,
+            &mut buffers,
+            &Point3::origin(),
+            &signed_distances,
+            Some(&tags),
+        );
+        voxels
+    }
+
+    fn linear_idx(i: usize, j: usize, k: usize) -> usize {
+        (i * CHUNK_SIZE + j) * CHUNK_SIZE + k
+    }
+
+    fn add_tagged_sphere(
+        graph: &mut SDFGraph<Global>,
+        radius: f32,
+        center_x: f32,
+        tag: VoxelTypeTag,
+    ) -> SDFNodeID {
+        let sphere_id = graph.add_node(SDFNode::new_sphere(radius));
+        let translation_id = graph.add_node(SDFNode::new_translation(
+            sphere_id,
+            Vector3::new(center_x, 0.0, 0.0),
+        ));
+        graph.add_node(SDFNode::new_voxel_type_tag(translation_id, tag))
+    }
+
+    fn compute_voxel_type_tags_for_centered_chunk(graph: &SDFGraph<Global>) -> Vec<VoxelTypeTag> {
+        let generator = graph.build_in(Global).unwrap();
+        assert!(generator.tracks_voxel_type_tags());
+
+        let half_size = (CHUNK_SIZE / 2) as f32;
+        let chunk_aabb = AxisAlignedBox::new(Point3::same(-half_size), Point3::same(half_size));
+
+        let mut buffers = generator.create_buffers_for_chunk_in(Global);
+        generator.compute_signed_distances_for_chunk(&mut buffers, &chunk_aabb);
+        buffers.final_voxel_type_tags().unwrap().to_vec()
+    }
+
+    #[test]
+    fn sdf_generator_without_tags_does_not_track_voxel_type_tags() {
+        let mut graph = SDFGraph::new_in(Global);
+        graph.add_node(SDFNode::new_sphere(4.0));
+        let generator = graph.build_in(Global).unwrap();
+
+        assert!(!generator.tracks_voxel_type_tags());
+        assert!(
+            generator
+                .create_buffers_for_chunk_in(Global)
+                .final_voxel_type_tags()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn union_gives_voxel_type_tag_of_closest_sdf() {
+        let mut graph = SDFGraph::new_in(Global);
+        let sphere_1_id = add_tagged_sphere(&mut graph, 4.0, -4.0, 1);
+        let sphere_2_id = add_tagged_sphere(&mut graph, 4.0, 4.0, 2);
+        graph.add_node(SDFNode::new_union(sphere_1_id, sphere_2_id, 0.0));
+
+        let tags = compute_voxel_type_tags_for_centered_chunk(&graph);
+
+        assert_eq!(tags[linear_idx(3, 8, 8)], 1);
+        assert_eq!(tags[linear_idx(12, 8, 8)], 2);
+    }
+
+    #[test]
+    fn subtraction_gives_voxel_type_tag_of_subtracted_sdf_on_carved_surface() {
+        let mut graph = SDFGraph::new_in(Global);
+        let sphere_1_id = add_tagged_sphere(&mut graph, 6.0, 0.0, 1);
+        let sphere_2_id = add_tagged_sphere(&mut graph, 4.0, 6.0, 2);
+        graph.add_node(SDFNode::new_subtraction(sphere_1_id, sphere_2_id, 0.0));
+
+        let tags = compute_voxel_type_tags_for_centered_chunk(&graph);
+
+        assert_eq!(tags[linear_idx(4, 8, 8)], 1);
+        assert_eq!(tags[linear_idx(9, 8, 8)], 2);
+    }
+
+    #[test]
+    fn untagged_voxels_get_fallback_voxel_type() {
+        let generator: VoxelTypeGenerator =
+            create_generator(vec![VoxelTypeNode::VoxelType(voxel_type(1))], vec![0]).into();
+
+        let voxels = generate_chunk(&generator, |_| -1.0, |_| UNTAGGED);
+
+        assert!(
+            voxels
+                .iter()
+                .all(|voxel| voxel.voxel_type() == voxel_type(0))
+        );
+    }
+
+    #[test]
+    fn tagged_voxels_get_voxel_type_of_tag_root() {
+        let generator: VoxelTypeGenerator = create_generator(
+            vec![
+                VoxelTypeNode::VoxelType(voxel_type(1)),
+                VoxelTypeNode::VoxelType(voxel_type(2)),
+            ],
+            vec![0, 1],
+        )
+        .into();
+
+        let voxels = generate_chunk(&generator, |_| -1.0, |[i, _, _]| if i < 8 { 1 } else { 2 });
+
+        assert_eq!(voxels[linear_idx(0, 0, 0)].voxel_type(), voxel_type(1));
+        assert_eq!(voxels[linear_idx(15, 0, 0)].voxel_type(), voxel_type(2));
+    }
+
+    #[test]
+    fn depth_selection_separates_surface_and_interior() {
+        let generator: VoxelTypeGenerator = create_generator(
+            vec![
+                VoxelTypeNode::VoxelType(voxel_type(1)),
+                VoxelTypeNode::VoxelType(voxel_type(2)),
+                VoxelTypeNode::Selection(VoxelTypeSelection {
+                    quantity: VoxelTypeSelectionQuantity::Depth,
+                    threshold: 3.0,
+                    below_id: 0,
+                    above_id: 1,
+                }),
+            ],
+            vec![2],
+        )
+        .into();
+
+        // Surface at the top of the chunk, with depth increasing downwards
+        let voxels = generate_chunk(&generator, |[_, j, _]| j as f32 - 15.5, |_| 1);
+
+        assert_eq!(voxels[linear_idx(4, 15, 4)].voxel_type(), voxel_type(1));
+        assert_eq!(voxels[linear_idx(4, 13, 4)].voxel_type(), voxel_type(1));
+        assert_eq!(voxels[linear_idx(4, 12, 4)].voxel_type(), voxel_type(2));
+        assert_eq!(voxels[linear_idx(4, 0, 4)].voxel_type(), voxel_type(2));
+    }
+
+    #[test]
+    fn slope_selection_separates_flat_and_steep_surfaces() {
+        let generator: VoxelTypeGenerator = create_generator(
+            vec![
+                VoxelTypeNode::VoxelType(voxel_type(1)),
+                VoxelTypeNode::VoxelType(voxel_type(2)),
+                VoxelTypeNode::Selection(VoxelTypeSelection {
+                    quantity: VoxelTypeSelectionQuantity::Slope,
+                    threshold: 45.0,
+                    below_id: 0,
+                    above_id: 1,
+                }),
+            ],
+            vec![2],
+        )
+        .into();
+
+        // Horizontal surface with upward normal
+        let voxels = generate_chunk(&generator, |[_, j, _]| j as f32 - 8.5, |_| 1);
+        assert_eq!(voxels[linear_idx(4, 6, 4)].voxel_type(), voxel_type(1));
+
+        // Vertical surface with normal along the x-axis
+        let voxels = generate_chunk(&generator, |[i, _, _]| i as f32 - 8.5, |_| 1);
+        assert_eq!(voxels[linear_idx(6, 4, 4)].voxel_type(), voxel_type(2));
+    }
+
+    #[test]
+    fn curvature_selection_separates_convex_and_flat_surfaces() {
+        let generator: VoxelTypeGenerator = create_generator(
+            vec![
+                VoxelTypeNode::VoxelType(voxel_type(1)),
+                VoxelTypeNode::VoxelType(voxel_type(2)),
+                VoxelTypeNode::Selection(VoxelTypeSelection {
+                    quantity: VoxelTypeSelectionQuantity::Curvature,
+                    threshold: 0.1,
+                    below_id: 0,
+                    above_id: 1,
+                }),
+            ],
+            vec![2],
+        )
+        .into();
+
+        let sphere_distance = |[i, j, k]: [usize; 3]| {
+            let [x, y, z] = [i, j, k].map(|idx| idx as f32 - 7.5);
+            (x * x + y * y + z * z).sqrt() - 6.0
+        };
+
+        let voxels = generate_chunk(&generator, sphere_distance, |_| 1);
+        assert_eq!(voxels[linear_idx(7, 2, 7)].voxel_type(), voxel_type(2));
+
+        let voxels = generate_chunk(&generator, |[_, j, _]| j as f32 - 8.5, |_| 1);
+        assert_eq!(voxels[linear_idx(7, 7, 7)].voxel_type(), voxel_type(1));
+    }
+}
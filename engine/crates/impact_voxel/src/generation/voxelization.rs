@@ -0,0 +1,918 @@
+//! Generation of voxel objects from triangle meshes.
+
+use crate::{
+    Voxel, VoxelSignedDistance,
+    generation::ChunkedVoxelGenerator,
+    object::{ChunkSparseness, LoopForChunkVoxels, VoxelObject},
+    voxel_types::{VoxelType, VoxelTypeRegistry},
+};
+use anyhow::{Result, anyhow, bail};
+use impact_alloc::Allocator;
+use impact_geometry::AxisAlignedBox;
+use impact_math::{consts::f32::PI, hash64, point::Point3, stringhash64_newtype, vector::Vector3};
+use impact_mesh::{TriangleMesh, TriangleMeshID, TriangleMeshRegistry};
+use impact_resource::{Resource, ResourceID, registry::ImmutableResourceRegistry};
+use roc_integration::roc;
+use tinyvec::ArrayVec;
+
+pub type VoxelizedMeshRegistry = ImmutableResourceRegistry<VoxelizedMesh>;
+
+stringhash64_newtype!(
+    /// Identifier for a voxelized mesh.
+    #[roc(parents = "Voxel")]
+    [pub] VoxelizedMeshID
+);
+
+/// A description of a voxel object to generate from one or more triangle
+/// meshes.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct VoxelizedMesh {
+    pub parts: Vec<VoxelizedMeshPart>,
+}
+
+/// A triangle mesh to include in a voxelized mesh, along with how to assign
+/// voxel types to the voxels inside it.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct VoxelizedMeshPart {
+    pub mesh_id: TriangleMeshID,
+    pub voxel_types: MeshVoxelTypeAssignment,
+}
+
+/// How to assign voxel types to the voxels inside a triangle mesh, with voxel
+/// types specified by name.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub enum MeshVoxelTypeAssignment {
+    /// All voxels get the voxel type with the given name.
+    Same(String),
+    /// Each voxel gets the voxel type whose color is closest to the average
+    /// vertex color of the nearest triangle.
+    VertexColors(Vec<VertexColorVoxelType>),
+}
+
+/// A voxel type to assign to voxels near triangles with the given RGB vertex
+/// color.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct VertexColorVoxelType {
+    pub color: [f32; 3],
+    pub voxel_type: String,
+}
+
+/// How to assign voxel types to the voxels inside a triangle mesh.
+#[derive(Clone, Debug)]
+pub enum MeshVoxelTypes {
+    /// All voxels get the given voxel type.
+    Same(VoxelType),
+    /// Each voxel gets the voxel type whose associated RGB color is closest to
+    /// the average vertex color of the nearest triangle.
+    VertexColors(Vec<([f32; 3], VoxelType)>),
+}
+
+/// Generator for a voxel object from a set of closed triangle meshes.
+///
+/// The signed distance of each voxel is the distance to the closest triangle,
+/// found using a bounding volume hierarchy over the triangles, with a sign
+/// determined by the generalized winding number of the meshes at the voxel
+/// center. The winding number is robust to small holes and self-intersections
+/// in the meshes. Each voxel inside the meshes gets the voxel type associated
+/// with its closest triangle.
+#[derive(Clone, Debug)]
+pub struct MeshVoxelGenerator {
+    voxel_extent: f32,
+    grid_shape: [usize; 3],
+    triangles: Vec<GridTriangle>,
+    nodes: Vec<TriangleBVHNode>,
+}
+
+/// A triangle with vertices in the coordinate space of the voxel grid, where
+/// the unit of length is the voxel extent.
+#[derive(Clone, Debug)]
+struct GridTriangle {
+    vertices: [Point3; 3],
+    voxel_type: VoxelType,
+}
+
+#[derive(Clone, Debug)]
+struct TriangleBVHNode {
+    aabb: AxisAlignedBox,
+    /// The sum of the area-weighted normal vectors of the triangles in the
+    /// node.
+    area_vector: Vector3,
+    /// The area-weighted average of the centroids of the triangles in the node.
+    center: Point3,
+    /// The radius of the sphere about `center` containing all the triangles in
+    /// the node.
+    radius: f32,
+    content: TriangleBVHNodeContent,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum TriangleBVHNodeContent {
+    Children { left_idx: u32, right_idx: u32 },
+    Triangles { start: u32, end: u32 },
+}
+
+/// The maximum number of triangles in a leaf node of the BVH.
+const MAX_TRIANGLES_PER_LEAF: usize = 4;
+
+/// Nodes whose triangles all lie within a sphere that is this many times
+/// smaller than the distance to the query point have their contribution to the
+/// winding number approximated by that of a single dipole.
+const WINDING_NUMBER_ACCURACY: f32 = 2.0;
+
+const MAX_BVH_DEPTH: usize = 64;
+
+impl ResourceID for VoxelizedMeshID {}
+
+#[roc(dependencies = [impact_math::hash::Hash64])]
+impl VoxelizedMeshID {
+    #[roc(body = "Hashing.hash_str_64(name)")]
+    /// Creates a voxelized mesh ID hashed from the given name.
+    pub fn from_name(name: &str) -> Self {
+        Self(hash64!(name))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for VoxelizedMeshID {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for VoxelizedMeshID {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::from_name(&s))
+    }
+}
+
+impl Resource for VoxelizedMesh {
+    type ID = VoxelizedMeshID;
+}
+
+impl MeshVoxelTypeAssignment {
+    /// Resolves the voxel type names in the assignment using the given voxel
+    /// type registry.
+    ///
+    /// # Errors
+    /// Returns an error if any of the voxel types is not present in the
+    /// registry.
+    pub fn resolve(&self, voxel_type_registry: &VoxelTypeRegistry) -> Result<MeshVoxelTypes> {
+        let lookup = |name: &str| {
+            voxel_type_registry
+                .voxel_type_for_name(name)
+                .ok_or_else(|| anyhow!("Missing voxel type `{name}` for voxelized mesh"))
+        };
+        Ok(match self {
+            Self::Same(name) => MeshVoxelTypes::Same(lookup(name)?),
+            Self::VertexColors(color_voxel_types) => MeshVoxelTypes::VertexColors(
+                color_voxel_types
+                    .iter()
+                    .map(|entry| Ok((entry.color, lookup(&entry.voxel_type)?)))
+                    .collect::<Result<_>>()?,
+            ),
+        })
+    }
+}
+
+impl MeshVoxelTypes {
+    fn voxel_type_for_triangle(
+        &self,
+        mesh: &TriangleMesh,
+        vertex_indices: [usize; 3],
+    ) -> VoxelType {
+        match self {
+            Self::Same(voxel_type) => *voxel_type,
+            Self::VertexColors(color_voxel_types) => {
+                let colors = mesh.colors();
+                let mut average_color = [0.0; 3];
+                for idx in vertex_indices {
+                    let color = &colors[idx].0;
+                    average_color[0] += color.x() / 3.0;
+                    average_color[1] += color.y() / 3.0;
+                    average_color[2] += color.z() / 3.0;
+                }
+
+                color_voxel_types
+                    .iter()
+                    .min_by(|(color_a, _), (color_b, _)| {
+                        squared_color_distance(color_a, &average_color)
+                            .total_cmp(&squared_color_distance(color_b, &average_color))
+                    })
+                    .map(|(_, voxel_type)| *voxel_type)
+                    .unwrap()
+            }
+        }
+    }
+}
+
+impl MeshVoxelGenerator {
+    /// Creates a new generator for a voxel object with the given voxel extent
+    /// containing the given triangle meshes, with voxel types assigned as
+    /// specified for each mesh. The meshes should be closed and have
+    /// counterclockwise winding when viewed from the outside.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - Vertex color based voxel types are specified for a mesh without vertex
+    ///   colors.
+    /// - No voxel types are specified for vertex color based assignment.
+    pub fn new(voxel_extent: f32, meshes: &[(&TriangleMesh, MeshVoxelTypes)]) -> Result<Self> {
+        assert!(voxel_extent > 0.0);
+
+        for (mesh, voxel_types) in meshes {
+            if let MeshVoxelTypes::VertexColors(color_voxel_types) = voxel_types {
+                if !mesh.has_colors() {
+                    bail!("Tried to assign voxel types from vertex colors for mesh without colors");
+                }
+                if color_voxel_types.is_empty() {
+                    bail!("Missing voxel types for vertex color based voxel type assignment");
+                }
+            }
+        }
+
+        let Some(mesh_aabb) = meshes
+            .iter()
+            .filter_map(|(mesh, _)| mesh.compute_aabb())
+            .reduce(|aabb_1, aabb_2| AxisAlignedBox::aabb_from_pair(&aabb_1, &aabb_2))
+        else {
+            return Ok(Self {
+                voxel_extent,
+                grid_shape: [0; 3],
+                triangles: Vec::new(),
+                nodes: Vec::new(),
+            });
+        };
+
+        let inverse_voxel_extent = voxel_extent.recip();
+        let mesh_extents: [f32; 3] = (mesh_aabb.extents() * inverse_voxel_extent).into();
+
+        // Make room for a border of empty voxels around the object so that the
+        // surface nets meshing algorithm can correctly interpolate distances at
+        // the boundaries
+        let grid_shape = mesh_extents.map(|extent| extent.ceil() as usize + 2);
+
+        // Place the lower corner of the mesh bounding box one voxel into the
+        // grid to account for the border
+        let to_grid_space = |position: Point3| {
+            Point3::same(1.0) + (position - mesh_aabb.lower_corner()) * inverse_voxel_extent
+        };
+
+        let mut triangles = Vec::new();
+
+        for (mesh, voxel_types) in meshes {
+            let positions = mesh.positions();
+            for indices in mesh.indices().chunks_exact(3) {
+                let vertex_indices = [
+                    indices[0] as usize,
+                    indices[1] as usize,
+                    indices[2] as usize,
+                ];
+                triangles.push(GridTriangle {
+                    vertices: vertex_indices.map(|idx| to_grid_space(positions[idx].0.aligned())),
+                    voxel_type: voxel_types.voxel_type_for_triangle(mesh, vertex_indices),
+                });
+            }
+        }
+
+        let nodes = build_bvh(&mut triangles);
+
+        Ok(Self {
+            voxel_extent,
+            grid_shape,
+            triangles,
+            nodes,
+        })
+    }
+
+    /// Creates a new generator for the given voxelized mesh with the given
+    /// voxel extent, uniformly scaling the meshes by the given factor.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - Any of the meshes is not present in the triangle mesh registry.
+    /// - Any of the voxel types is not present in the voxel type registry.
+    /// - See [`Self::new`].
+    pub fn for_voxelized_mesh(
+        voxel_extent: f32,
+        scale_factor: f32,
+        voxelized_mesh: &VoxelizedMesh,
+        triangle_mesh_registry: &TriangleMeshRegistry,
+        voxel_type_registry: &VoxelTypeRegistry,
+    ) -> Result<Self> {
+        let meshes = voxelized_mesh
+            .parts
+            .iter()
+            .map(|part| {
+                let mesh = triangle_mesh_registry.get(part.mesh_id).ok_or_else(|| {
+                    anyhow!("Missing triangle mesh {} for voxelized mesh", part.mesh_id)
+                })?;
+                Ok((mesh, part.voxel_types.resolve(voxel_type_registry)?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Scaling the meshes up is equivalent to scaling the voxels down
+        Self::new(voxel_extent / scale_factor, &meshes)
+            .map(|generator| generator.with_voxel_extent(voxel_extent))
+    }
+
+    fn with_voxel_extent(mut self, voxel_extent: f32) -> Self {
+        self.voxel_extent = voxel_extent;
+        self
+    }
+
+    /// Computes the generalized winding number of the meshes at the given
+    /// point in grid space. The winding number is close to one inside the
+    /// meshes and close to zero outside.
+    fn compute_winding_number(&self, point: &Point3) -> f32 {
+        let mut solid_angle = 0.0;
+
+        let mut stack = ArrayVec::<[u32; MAX_BVH_DEPTH]>::new();
+        stack.push(0);
+
+        while let Some(node_idx) = stack.pop() {
+            let node = &self.nodes[node_idx as usize];
+
+            let displacement = node.center - point;
+            let distance = displacement.norm();
+
+            if distance > WINDING_NUMBER_ACCURACY * node.radius {
+                // Far-field approximation treating all triangles in the node as
+                // a single dipole
+                solid_angle += node.area_vector.dot(&displacement) / distance.powi(3);
+                continue;
+            }
+
+            match node.content {
+                TriangleBVHNodeContent::Children {
+                    left_idx,
+                    right_idx,
+                } => {
+                    stack.push(left_idx);
+                    stack.push(right_idx);
+                }
+                TriangleBVHNodeContent::Triangles { start, end } => {
+                    for triangle in &self.triangles[start as usize..end as usize] {
+                        solid_angle += triangle.compute_solid_angle(point);
+                    }
+                }
+            }
+        }
+
+        solid_angle / (4.0 * PI)
+    }
+
+    /// Finds the triangle closest to the given point in grid space, if any
+    /// triangle is closer than the given maximum distance. Returns the triangle
+    /// together with the squared distance to it.
+    fn find_closest_triangle(
+        &self,
+        point: &Point3,
+        max_squared_distance: f32,
+    ) -> Option<(&GridTriangle, f32)> {
+        let mut closest = None;
+        let mut closest_squared_distance = max_squared_distance;
+
+        let mut stack = ArrayVec::<[u32; MAX_BVH_DEPTH]>::new();
+        stack.push(0);
+
+        while let Some(node_idx) = stack.pop() {
+            let node = &self.nodes[node_idx as usize];
+
+            if squared_distance_to_aabb(&node.aabb, point) >= closest_squared_distance {
+                continue;
+            }
+
+            match node.content {
+                TriangleBVHNodeContent::Children {
+                    left_idx,
+                    right_idx,
+                } => {
+                    let left_squared_distance =
+                        squared_distance_to_aabb(&self.nodes[left_idx as usize].aabb, point);
+                    let right_squared_distance =
+                        squared_distance_to_aabb(&self.nodes[right_idx as usize].aabb, point);
+
+                    // Visit the closest child first
+                    if left_squared_distance < right_squared_distance {
+                        stack.push(right_idx);
+                        stack.push(left_idx);
+                    } else {
+                        stack.push(left_idx);
+                        stack.push(right_idx);
+                    }
+                }
+                TriangleBVHNodeContent::Triangles { start, end } => {
+                    for triangle in &self.triangles[start as usize..end as usize] {
+                        let squared_distance = triangle.compute_squared_distance(point);
+                        if squared_distance < closest_squared_distance {
+                            closest_squared_distance = squared_distance;
+                            closest = Some(triangle);
+                        }
+                    }
+                }
+            }
+        }
+
+        closest.map(|triangle| (triangle, closest_squared_distance))
+    }
+}
+
+impl ChunkedVoxelGenerator for MeshVoxelGenerator {
+    type ChunkGenerationBuffers<AB: Allocator> = ();
+
+    #[inline]
+    fn voxel_extent(&self) -> f32 {
+        self.voxel_extent
+    }
+
+    #[inline]
+    fn grid_shape(&self) -> [usize; 3] {
+        self.grid_shape
+    }
+
+    #[inline]
+    fn total_buffer_size(&self) -> usize {
+        0
+    }
+
+    fn create_buffers_in<AB: Allocator>(&self, _alloc: AB) -> Self::ChunkGenerationBuffers<AB> {}
+
+    fn generate_chunk<AB: Allocator>(
+        &self,
+        _buffers: &mut Self::ChunkGenerationBuffers<AB>,
+        voxels: &mut [Voxel],
+        chunk_origin: &[usize; 3],
+    ) -> ChunkSparseness {
+        assert_eq!(voxels.len(), VoxelObject::chunk_voxel_count());
+
+        let max_distance = VoxelSignedDistance::max_f32();
+
+        let chunk_voxel_center_aabb = AxisAlignedBox::new(
+            Point3::from(chunk_origin.map(|idx| idx as f32 + 0.5)),
+            Point3::from(chunk_origin.map(|idx| (idx + VoxelObject::chunk_size()) as f32 - 0.5)),
+        );
+
+        // Voxels too far outside the mesh bounding box to be influenced by the
+        // surface are all outside the meshes
+        if self.nodes.is_empty()
+            || chunk_origin
+                .iter()
+                .zip(self.grid_shape)
+                .any(|(&origin, size)| origin >= size)
+            || squared_distance_between_aabbs(&self.nodes[0].aabb, &chunk_voxel_center_aabb)
+                >= max_distance.powi(2)
+        {
+            voxels.fill(Voxel::maximally_outside());
+            return ChunkSparseness {
+                has_only_empty_voxels: true,
+                is_void: true,
+            };
+        }
+
+        let mut chunk_has_only_empty_voxels = true;
+        let mut chunk_is_void = true;
+
+        LoopForChunkVoxels::over_all().execute_with_linear_idx(
+            &mut |&[i_in_chunk, j_in_chunk, k_in_chunk], idx| {
+                let i = chunk_origin[0] + i_in_chunk;
+                let j = chunk_origin[1] + j_in_chunk;
+                let k = chunk_origin[2] + k_in_chunk;
+
+                if i >= self.grid_shape[0] || j >= self.grid_shape[1] || k >= self.grid_shape[2] {
+                    voxels[idx] = Voxel::maximally_outside();
+                    return;
+                }
+
+                let voxel_center = Point3::new(i as f32 + 0.5, j as f32 + 0.5, k as f32 + 0.5);
+
+                let is_inside = self.compute_winding_number(&voxel_center) > 0.5;
+
+                // Inside voxels need their closest triangle regardless of
+                // distance to determine their voxel type
+                let max_squared_distance = if is_inside {
+                    f32::INFINITY
+                } else {
+                    max_distance.powi(2)
+                };
+
+                let closest_triangle =
+                    self.find_closest_triangle(&voxel_center, max_squared_distance);
+
+                let distance = closest_triangle.map_or(max_distance, |(_, squared_distance)| {
+                    squared_distance.sqrt()
+                });
+
+                let voxel_signed_distance =
+                    VoxelSignedDistance::from_f32(if is_inside { -distance } else { distance });
+
+                voxels[idx] = if voxel_signed_distance.is_negative() {
+                    chunk_has_only_empty_voxels = false;
+                    chunk_is_void = false;
+                    let voxel_type = closest_triangle
+                        .map_or_else(VoxelType::default, |(triangle, _)| triangle.voxel_type);
+                    Voxel::non_empty(voxel_type, voxel_signed_distance)
+                } else {
+                    if !voxel_signed_distance.is_void() {
+                        chunk_is_void = false;
+                    }
+                    Voxel::empty(voxel_signed_distance)
+                };
+            },
+        );
+
+        ChunkSparseness {
+            has_only_empty_voxels: chunk_has_only_empty_voxels,
+            is_void: chunk_is_void,
+        }
+    }
+}
+
+impl GridTriangle {
+    fn centroid(&self) -> Point3 {
+        let [a, b, c] = &self.vertices;
+        Point3::from((a.as_vector() + b.as_vector() + c.as_vector()) / 3.0)
+    }
+
+    /// Returns the normal vector of the triangle scaled by its area.
+    fn area_vector(&self) -> Vector3 {
+        let [a, b, c] = &self.vertices;
+        0.5 * (b - a).cross(&(c - a))
+    }
+
+    /// Computes the signed solid angle subtended by the triangle at the given
+    /// point, using the formula of Van Oosterom and Strackee.
+    fn compute_solid_angle(&self, point: &Point3) -> f32 {
+        let [a, b, c] = self.vertices.each_ref().map(|vertex| vertex - point);
+
+        let a_norm = a.norm();
+        let b_norm = b.norm();
+        let c_norm = c.norm();
+
+        let numerator = a.dot(&b.cross(&c));
+        let denominator =
+            a_norm * b_norm * c_norm + a.dot(&b) * c_norm + b.dot(&c) * a_norm + c.dot(&a) * b_norm;
+
+        2.0 * numerator.atan2(denominator)
+    }
+
+    /// Computes the squared distance from the given point to the closest point
+    /// on the triangle.
+    fn compute_squared_distance(&self, point: &Point3) -> f32 {
+        let closest_point = self.find_closest_point(point);
+        Point3::squared_distance_between(&closest_point, point)
+    }
+
+    /// Finds the point on the triangle closest to the given point, following
+    /// Ericson (2005), "Real-Time Collision Detection", section 5.1.5.
+    fn find_closest_point(&self, point: &Point3) -> Point3 {
+        let [a, b, c] = &self.vertices;
+
+        let ab = b - a;
+        let ac = c - a;
+        let ap = point - a;
+
+        let d1 = ab.dot(&ap);
+        let d2 = ac.dot(&ap);
+        if d1 <= 0.0 && d2 <= 0.0 {
+            return *a;
+        }
+
+        let bp = point - b;
+        let d3 = ab.dot(&bp);
+        let d4 = ac.dot(&bp);
+        if d3 >= 0.0 && d4 <= d3 {
+            return *b;
+        }
+
+        let vc = d1 * d4 - d3 * d2;
+        if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+            let v = d1 / (d1 - d3);
+            return a + ab * v;
+        }
+
+        let cp = point - c;
+        let d5 = ab.dot(&cp);
+        let d6 = ac.dot(&cp);
+        if d6 >= 0.0 && d5 <= d6 {
+            return *c;
+        }
+
+        let vb = d5 * d2 - d1 * d6;
+        if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+            let w = d2 / (d2 - d6);
+            return a + ac * w;
+        }
+
+        let va = d3 * d6 - d5 * d4;
+        if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+            let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+            return b + (c - b) * w;
+        }
+
+        let denominator = va + vb + vc;
+        if denominator == 0.0 {
+            // Degenerate triangle where all the above tests failed due to
+            // round-off, fall back to the closest vertex
+            return *[a, b, c]
+                .into_iter()
+                .min_by(|v1, v2| {
+                    Point3::squared_distance_between(v1, point)
+                        .total_cmp(&Point3::squared_distance_between(v2, point))
+                })
+                .unwrap();
+        }
+        let v = vb / denominator;
+        let w = vc / denominator;
+        a + ab * v + ac * w
+    }
+}
+
+/// Builds a bounding volume hierarchy over the given triangles by recursively
+/// splitting them at the median centroid along the longest axis. The triangles
+/// are reordered so that the triangles in each leaf are contiguous. The root
+/// is the first node.
+fn build_bvh(triangles: &mut [GridTriangle]) -> Vec<TriangleBVHNode> {
+    let mut nodes = Vec::with_capacity(2 * triangles.len().div_ceil(MAX_TRIANGLES_PER_LEAF));
+    if !triangles.is_empty() {
+        build_bvh_node(&mut nodes, triangles, 0);
+    }
+    nodes
+}
+
+fn build_bvh_node(
+    nodes: &mut Vec<TriangleBVHNode>,
+    triangles: &mut [GridTriangle],
+    offset: usize,
+) -> u32 {
+    let node_idx = nodes.len();
+
+    let aabb = AxisAlignedBox::aabb_for_point_array(&triangles[0].vertices);
+    let aabb = triangles[1..].iter().fold(aabb, |aabb, triangle| {
+        AxisAlignedBox::aabb_from_pair(
+            &aabb,
+            &AxisAlignedBox::aabb_for_point_array(&triangle.vertices),
+        )
+    });
+
+    let mut area_vector = Vector3::zeros();
+    let mut weighted_centroid_sum = Vector3::zeros();
+    let mut total_area = 0.0;
+    for triangle in triangles.iter() {
+        let triangle_area_vector = triangle.area_vector();
+        let area = triangle_area_vector.norm();
+        area_vector += triangle_area_vector;
+        weighted_centroid_sum += triangle.centroid().as_vector() * area;
+        total_area += area;
+    }
+
+    let center = if total_area > 0.0 {
+        Point3::from(weighted_centroid_sum / total_area)
+    } else {
+        aabb.center()
+    };
+
+    let radius = triangles
+        .iter()
+        .flat_map(|triangle| &triangle.vertices)
+        .map(|vertex| Point3::distance_between(vertex, &center))
+        .fold(0.0, f32::max);
+
+    nodes.push(TriangleBVHNode {
+        aabb,
+        area_vector,
+        center,
+        radius,
+        content: TriangleBVHNodeContent::Triangles {
+            start: offset as u32,
+            end: (offset + triangles.len()) as u32,
+        },
+    });
+
+    if triangles.len() <= MAX_TRIANGLES_PER_LEAF {
+        return node_idx as u32;
+    }
+
+    let centroid_extents = triangles
+        .iter()
+        .fold(
+            AxisAlignedBox::new(triangles[0].centroid(), triangles[0].centroid()),
+            |mut aabb, triangle| {
+                aabb.expand_to_point(&triangle.centroid());
+                aabb
+            },
+        )
+        .extents();
+
+    let split_axis = if centroid_extents.x() >= centroid_extents.y()
+        && centroid_extents.x() >= centroid_extents.z()
+    {
+        0
+    } else if centroid_extents.y() >= centroid_extents.z() {
+        1
+    } else {
+        2
+    };
+
+    let mid = triangles.len() / 2;
+    triangles.select_nth_unstable_by(mid, |triangle_a, triangle_b| {
+        triangle_a.centroid()[split_axis].total_cmp(&triangle_b.centroid()[split_axis])
+    });
+
+    let (left_triangles, right_triangles) = triangles.split_at_mut(mid);
+    let left_idx = build_bvh_node(nodes, left_triangles, offset);
+    let right_idx = build_bvh_node(nodes, right_triangles, offset + mid);
+
+    nodes[node_idx].content = TriangleBVHNodeContent::Children {
+        left_idx,
+        right_idx,
+    };
+
+    node_idx as u32
+}
+
+fn squared_distance_to_aabb(aabb: &AxisAlignedBox, point: &Point3) -> f32 {
+    Point3::squared_distance_between(&aabb.closest_interior_point_to(point), point)
+}
+
+fn squared_distance_between_aabbs(aabb_a: &AxisAlignedBox, aabb_b: &AxisAlignedBox) -> f32 {
+    let gap = (aabb_a.lower_corner() - aabb_b.upper_corner())
+        .component_max(&(aabb_b.lower_corner() - aabb_a.upper_corner()))
+        .component_max(&Vector3::zeros());
+    gap.norm_squared()
+}
+
+fn squared_color_distance(color_a: &[f32; 3], color_b: &[f32; 3]) -> f32 {
+    (color_a[0] - color_b[0]).powi(2)
+        + (color_a[1] - color_b[1]).powi(2)
+        + (color_a[2] - color_b[2]).powi(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        generation::{
+            SDFVoxelGenerator,
+            sdf::{SDFGraph, SDFNode},
+            voxel_type::SameVoxelTypeGenerator,
+        },
+        object::VoxelObjectBuffers,
+    };
+    use impact_alloc::Global;
+    use impact_mesh::VertexColor;
+
+    fn for_each_non_empty_voxel(object: &VoxelObject, f: &mut impl FnMut(Voxel)) {
+        let [nx, ny, nz] = object
+            .chunk_counts()
+            .map(|count| count * VoxelObject::chunk_size());
+        for i in 0..nx {
+            for j in 0..ny {
+                for k in 0..nz {
+                    if let Some(voxel) = object.get_voxel_if_occupied(i, j, k)
+                        && !voxel.is_empty()
+                    {
+                        f(voxel);
+                    }
+                }
+            }
+        }
+    }
+
+    fn unit_box_mesh() -> TriangleMesh {
+        TriangleMesh::create_box(1.0, 1.0, 1.0, impact_mesh::FrontFaceSide::Outside)
+    }
+
+    #[test]
+    fn winding_number_of_box_is_one_inside_and_zero_outside() {
+        let mesh = unit_box_mesh();
+        let generator =
+            MeshVoxelGenerator::new(0.1, &[(&mesh, MeshVoxelTypes::Same(VoxelType::default()))])
+                .unwrap();
+
+        let center = Point3::same(6.0);
+        assert!((generator.compute_winding_number(&center) - 1.0).abs() < 1e-3);
+
+        let outside = Point3::new(-3.0, 6.0, 6.0);
+        assert!(generator.compute_winding_number(&outside).abs() < 1e-3);
+    }
+
+    #[test]
+    fn closest_triangle_distance_for_box_is_correct() {
+        let mesh = unit_box_mesh();
+        let generator =
+            MeshVoxelGenerator::new(0.1, &[(&mesh, MeshVoxelTypes::Same(VoxelType::default()))])
+                .unwrap();
+
+        // The box spans 1 to 11 in grid space
+        let (_, squared_distance) = generator
+            .find_closest_triangle(&Point3::new(6.0, 6.0, 3.0), f32::INFINITY)
+            .unwrap();
+        assert!((squared_distance - 4.0).abs() < 1e-4);
+
+        assert!(
+            generator
+                .find_closest_triangle(&Point3::new(6.0, 6.0, -3.0), 1.0)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn voxelizing_sphere_mesh_gives_object_similar_to_sdf_sphere() {
+        let mesh = TriangleMesh::create_sphere(64);
+        let generator =
+            MeshVoxelGenerator::new(0.1, &[(&mesh, MeshVoxelTypes::Same(VoxelType::default()))])
+                .unwrap();
+        let object = VoxelObject::generate(VoxelObjectBuffers::new(), &generator);
+
+        // The unit radius mesh has a radius of 10 voxels
+        let mut graph = SDFGraph::new_in(Global);
+        graph.add_node(SDFNode::new_sphere(10.0));
+        let sdf_generator = SDFVoxelGenerator::new(
+            0.1,
+            graph.build_in(Global).unwrap(),
+            SameVoxelTypeGenerator::new(VoxelType::default()).into(),
+        );
+        let sdf_object = VoxelObject::generate(VoxelObjectBuffers::new(), &sdf_generator);
+
+        let count_non_empty = |object: &VoxelObject| {
+            let mut count = 0;
+            for_each_non_empty_voxel(object, &mut |_| count += 1);
+            count as f32
+        };
+
+        let voxelized_count = count_non_empty(&object);
+        let sdf_count = count_non_empty(&sdf_object);
+        assert!((voxelized_count - sdf_count).abs() / sdf_count < 0.05);
+
+        object.validate_adjacencies();
+        object.validate_chunk_obscuredness();
+        object.validate_region_count();
+    }
+
+    #[test]
+    fn voxel_types_are_assigned_from_closest_vertex_colors() {
+        let red = VertexColor(impact_math::vector::Vector4C::new(1.0, 0.0, 0.0, 1.0));
+        let blue = VertexColor(impact_math::vector::Vector4C::new(0.0, 0.0, 1.0, 1.0));
+        let mesh =
+            TriangleMesh::create_cube_with_face_colors(1.0, &[red, red, red, blue, blue, blue]);
+
+        let red_type = VoxelType::from_idx(1);
+        let blue_type = VoxelType::from_idx(2);
+
+        let generator = MeshVoxelGenerator::new(
+            0.1,
+            &[(
+                &mesh,
+                MeshVoxelTypes::VertexColors(vec![
+                    ([0.9, 0.1, 0.0], red_type),
+                    ([0.0, 0.1, 0.9], blue_type),
+                ]),
+            )],
+        )
+        .unwrap();
+
+        let object = VoxelObject::generate(VoxelObjectBuffers::new(), &generator);
+
+        let mut has_red = false;
+        let mut has_blue = false;
+        for_each_non_empty_voxel(&object, &mut |voxel| {
+            has_red |= voxel.voxel_type() == red_type;
+            has_blue |= voxel.voxel_type() == blue_type;
+        });
+        assert!(has_red);
+        assert!(has_blue);
+    }
+
+    #[test]
+    fn vertex_color_assignment_fails_for_mesh_without_colors() {
+        let mesh = unit_box_mesh();
+        assert!(
+            MeshVoxelGenerator::new(
+                0.1,
+                &[(
+                    &mesh,
+                    MeshVoxelTypes::VertexColors(vec![([1.0; 3], VoxelType::default())])
+                )]
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn voxelizing_no_meshes_gives_empty_object() {
+        let generator = MeshVoxelGenerator::new(0.1, &[]).unwrap();
+        assert_eq!(generator.grid_shape(), [0; 3]);
+    }
+}
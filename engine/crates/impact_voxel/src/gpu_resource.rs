@@ -3,8 +3,9 @@
 use crate::{
     VoxelObjectID, VoxelObjectManager,
     mesh::{
-        ChunkSubmesh, CullingFrustum, MeshedVoxelObject, VoxelMeshIndex, VoxelMeshIndexMaterials,
-        VoxelMeshModifications, VoxelMeshVertexNormalVector, VoxelMeshVertexPosition,
+        ChunkLODSelectionParameters, ChunkSubmesh, CullingFrustum, MeshedVoxelObject,
+        VoxelMeshIndex, VoxelMeshIndexMaterials, VoxelMeshModifications,
+        VoxelMeshVertexNormalVector, VoxelMeshVertexPosition, VoxelMeshVertexTemperature,
     },
     voxel_types::{FixedVoxelMaterialProperties, VoxelTypeRegistry},
 };
@@ -75,10 +76,12 @@ pub struct VoxelObjectGPUResources {
 /// GPU buffers for a [`VoxelObject`](crate::chunks::VoxelObject).
 #[derive(Debug)]
 pub struct VoxelObjectGPUBuffers {
+    voxel_extent: f32,
     chunk_extent: f32,
     origin_offset_in_root: [f32; 3],
     position_buffer: GPUBuffer,
     normal_vector_buffer: GPUBuffer,
+    temperature_buffer: GPUBuffer,
     index_material_buffer: GPUBuffer,
     index_buffer: GPUBuffer,
     chunk_submesh_buffer: GPUBuffer,
@@ -109,7 +112,10 @@ pub type VoxelPushConstantGroup = PushConstantGroup<VoxelPushConstantVariant>;
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum VoxelPushConstantVariant {
     CullingFrustum,
+    ChunkLODSelection,
     ChunkCount,
+    EntityID,
+    InverseVoxelExtent,
     Rendering(BasicPushConstantVariant),
 }
 
@@ -507,6 +513,14 @@ impl VoxelObjectGPUBuffers {
             Cow::Owned(format!("{voxel_object_id} normal vector")),
         );
 
+        let temperature_buffer = Self::create_temperature_buffer(
+            graphics_device,
+            staging_belt,
+            command_encoder,
+            mesh.temperatures(),
+            Cow::Owned(format!("{voxel_object_id} vertex temperature")),
+        );
+
         let index_material_buffer = Self::create_index_material_buffer(
             graphics_device,
             staging_belt,
@@ -564,6 +578,7 @@ impl VoxelObjectGPUBuffers {
                 graphics_device.device(),
                 &position_buffer,
                 &normal_vector_buffer,
+                &temperature_buffer,
                 &position_and_normal_buffer_bind_group_layout,
             );
 
@@ -584,10 +599,12 @@ impl VoxelObjectGPUBuffers {
             );
 
         Self {
+            voxel_extent: voxel_object.object().voxel_extent(),
             chunk_extent: voxel_object.object().chunk_extent(),
             origin_offset_in_root: voxel_object.object().origin_offset_in_root(),
             position_buffer,
             normal_vector_buffer,
+            temperature_buffer,
             index_material_buffer,
             index_buffer,
             chunk_submesh_buffer,
@@ -599,6 +616,11 @@ impl VoxelObjectGPUBuffers {
         }
     }
 
+    /// Returns the extent of a single voxel in the object.
+    pub fn voxel_extent(&self) -> f32 {
+        self.voxel_extent
+    }
+
     /// Returns the extent of a single voxel chunk in the object.
     pub fn chunk_extent(&self) -> f32 {
         self.chunk_extent
@@ -633,6 +655,12 @@ impl VoxelObjectGPUBuffers {
         &self.normal_vector_buffer
     }
 
+    /// Return a reference to the [`GPUBuffer`] holding all the vertex
+    /// temperatures in the object's mesh.
+    pub fn vertex_temperature_gpu_buffer(&self) -> &GPUBuffer {
+        &self.temperature_buffer
+    }
+
     /// Return a reference to the [`GPUBuffer`] holding all the index materials
     /// in the object's mesh.
     pub fn index_material_gpu_buffer(&self) -> &GPUBuffer {
@@ -667,9 +695,9 @@ impl VoxelObjectGPUBuffers {
         &self.indexed_indirect_argument_buffer
     }
 
-    /// Returns the layout of the bind group for the position and normal
-    /// buffers, after creating and caching it if it has not already been
-    /// created.
+    /// Returns the layout of the bind group for the position, normal and
+    /// temperature buffers, after creating and caching it if it has not already
+    /// been created.
     pub fn get_or_create_position_and_normal_buffer_bind_group_layout(
         graphics_device: &GraphicsDevice,
         bind_group_layout_registry: &BindGroupLayoutRegistry,
@@ -693,8 +721,8 @@ impl VoxelObjectGPUBuffers {
         )
     }
 
-    /// Returns a reference to the bind group for the position and normal
-    /// buffers.
+    /// Returns a reference to the bind group for the position, normal and
+    /// temperature buffers.
     pub fn position_and_normal_buffer_bind_group(&self) -> &wgpu::BindGroup {
         &self.position_and_normal_buffer_bind_group
     }
@@ -721,6 +749,9 @@ impl VoxelObjectGPUBuffers {
         voxel_object: &mut MeshedVoxelObject,
         bind_group_layout_registry: &BindGroupLayoutRegistry,
     ) {
+        // The origin offset changes if the chunk grid of the object grows
+        self.origin_offset_in_root = voxel_object.object().origin_offset_in_root();
+
         let mesh = voxel_object.mesh();
 
         let VoxelMeshModifications {
@@ -749,9 +780,9 @@ impl VoxelObjectGPUBuffers {
 
         if mem::size_of_val(mesh.positions()) > self.position_buffer.buffer_size() {
             // If the current size of the mesh's position slice exceeds the
-            // current buffer size, we have to recreate the position and normal
-            // vector buffers (each of these have room for the same number of
-            // elements) and their bind group
+            // current buffer size, we have to recreate the position, normal
+            // vector and temperature buffers (each of these have room for the
+            // same number of elements) and their bind group
 
             self.position_buffer = Self::create_position_buffer(
                 graphics_device,
@@ -767,6 +798,13 @@ impl VoxelObjectGPUBuffers {
                 mesh.normal_vectors(),
                 self.normal_vector_buffer.label().clone(),
             );
+            self.temperature_buffer = Self::create_temperature_buffer(
+                graphics_device,
+                staging_belt,
+                command_encoder,
+                mesh.temperatures(),
+                self.temperature_buffer.label().clone(),
+            );
 
             let position_and_normal_buffer_bind_group_layout =
                 Self::get_or_create_position_and_normal_buffer_bind_group_layout(
@@ -779,6 +817,7 @@ impl VoxelObjectGPUBuffers {
                     graphics_device.device(),
                     &self.position_buffer,
                     &self.normal_vector_buffer,
+                    &self.temperature_buffer,
                     &position_and_normal_buffer_bind_group_layout,
                 );
         } else {
@@ -800,6 +839,14 @@ impl VoxelObjectGPUBuffers {
                     command_encoder,
                     &self.normal_vector_buffer,
                     mesh.normal_vectors(),
+                    vertex_range.clone(),
+                );
+                Self::update_buffer_range(
+                    graphics_device,
+                    staging_belt,
+                    command_encoder,
+                    &self.temperature_buffer,
+                    mesh.temperatures(),
                     vertex_range,
                 );
             }
@@ -957,6 +1004,27 @@ impl VoxelObjectGPUBuffers {
         )
     }
 
+    fn create_temperature_buffer(
+        graphics_device: &GraphicsDevice,
+        staging_belt: &mut wgpu::util::StagingBelt,
+        command_encoder: &mut wgpu::CommandEncoder,
+        temperatures: &[VoxelMeshVertexTemperature],
+        label: Cow<'static, str>,
+    ) -> GPUBuffer {
+        let total_capacity = Self::add_spare_buffer_capacity(temperatures.len());
+
+        // The temperature buffer is bound as a storage buffer for the geometry
+        // pass
+        GPUBuffer::new_storage_buffer_with_spare_capacity_and_encoded_initialization(
+            graphics_device,
+            staging_belt,
+            command_encoder,
+            total_capacity,
+            temperatures,
+            label,
+        )
+    }
+
     fn create_index_material_buffer(
         graphics_device: &GraphicsDevice,
         staging_belt: &mut wgpu::util::StagingBelt,
@@ -1086,8 +1154,18 @@ impl VoxelObjectGPUBuffers {
             true,
         );
 
+        let temperature_buffer_layout = storage::create_storage_buffer_bind_group_layout_entry(
+            2,
+            wgpu::ShaderStages::VERTEX,
+            true,
+        );
+
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            entries: &[position_buffer_layout, normal_buffer_layout],
+            entries: &[
+                position_buffer_layout,
+                normal_buffer_layout,
+                temperature_buffer_layout,
+            ],
             label: Some("Voxel object position and normal buffer bind group layout"),
         })
     }
@@ -1118,6 +1196,7 @@ impl VoxelObjectGPUBuffers {
         device: &wgpu::Device,
         position_buffer: &GPUBuffer,
         normal_buffer: &GPUBuffer,
+        temperature_buffer: &GPUBuffer,
         layout: &wgpu::BindGroupLayout,
     ) -> wgpu::BindGroup {
         device.create_bind_group(&wgpu::BindGroupDescriptor {
@@ -1125,6 +1204,7 @@ impl VoxelObjectGPUBuffers {
             entries: &[
                 position_buffer.create_bind_group_entry(0),
                 normal_buffer.create_bind_group_entry(1),
+                temperature_buffer.create_bind_group_entry(2),
             ],
             label: Some("Voxel object position and normal buffer bind group"),
         })
@@ -1217,7 +1297,10 @@ impl PushConstantVariant for VoxelPushConstantVariant {
     fn size(&self) -> u32 {
         match self {
             Self::CullingFrustum => mem::size_of::<CullingFrustum>() as u32,
+            Self::ChunkLODSelection => mem::size_of::<ChunkLODSelectionParameters>() as u32,
             Self::ChunkCount => mem::size_of::<u32>() as u32,
+            Self::EntityID => mem::size_of::<[u32; 2]>() as u32,
+            Self::InverseVoxelExtent => mem::size_of::<f32>() as u32,
             Self::Rendering(variant) => variant.size(),
         }
     }
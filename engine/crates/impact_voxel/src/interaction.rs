@@ -1,14 +1,21 @@
 //! Interactions with voxel objects.
 
 pub mod absorption;
+pub mod deposition;
 pub mod fracturing;
+pub mod integrity;
+pub mod thermal;
+pub mod welding;
 
 #[cfg(feature = "ecs")]
 pub mod systems;
 
 use crate::{
     VoxelObjectBufferPool, VoxelObjectID, VoxelObjectManager, VoxelObjectPhysicsContext,
-    interaction::fracturing::VoxelFracturingConfig,
+    interaction::{
+        fracturing::VoxelFracturingConfig, integrity::VoxelIntegrityConfig,
+        thermal::VoxelThermalConfig,
+    },
     mesh::{MeshedVoxelObject, MeshedVoxelObjectBuffers},
     object::{
         VoxelObject, extraction::ExtractionResult, inertia::VoxelObjectInertialPropertyManager,
@@ -32,7 +39,10 @@ use impact_physics::{
     quantities::{AngularVelocity, Orientation, Position, Velocity},
     rigid_body::{DynamicRigidBody, DynamicRigidBodyID, RigidBodyManager},
 };
+use integrity::VoxelObjectIntegrityManager;
+use thermal::VoxelObjectThermalManager;
 use tinyvec::TinyVec;
+use welding::VoxelObjectWeldingManager;
 
 /// Context trait for handling voxel object interactions in a generic way.
 ///
@@ -78,6 +88,9 @@ pub trait VoxelObjectInteractionContext {
 pub struct VoxelInteractionManager {
     pub absorption_manager: VoxelAbsorptionManager,
     pub fracturing_manager: VoxelObjectFracturingManager,
+    pub welding_manager: VoxelObjectWeldingManager,
+    pub integrity_manager: VoxelObjectIntegrityManager,
+    pub thermal_manager: VoxelObjectThermalManager,
 }
 
 #[cfg_attr(
@@ -88,6 +101,8 @@ pub struct VoxelInteractionManager {
 #[derive(Clone, Debug, Default)]
 pub struct VoxelInteractionConfig {
     pub fracturing: VoxelFracturingConfig,
+    pub integrity: VoxelIntegrityConfig,
+    pub thermal: VoxelThermalConfig,
 }
 
 #[derive(Debug, Default)]
@@ -146,6 +161,9 @@ impl VoxelInteractionManager {
         Ok(Self {
             absorption_manager: VoxelAbsorptionManager::new(),
             fracturing_manager: VoxelObjectFracturingManager::new(config.fracturing)?,
+            welding_manager: VoxelObjectWeldingManager::new(),
+            integrity_manager: VoxelObjectIntegrityManager::new(config.integrity)?,
+            thermal_manager: VoxelObjectThermalManager::new(config.thermal)?,
         })
     }
 
@@ -169,10 +187,43 @@ impl VoxelInteractionManager {
         &mut self.fracturing_manager
     }
 
+    /// Returns a reference to the [`VoxelObjectWeldingManager`].
+    pub fn welding_manager(&self) -> &VoxelObjectWeldingManager {
+        &self.welding_manager
+    }
+
+    /// Returns a mutable reference to the [`VoxelObjectWeldingManager`].
+    pub fn welding_manager_mut(&mut self) -> &mut VoxelObjectWeldingManager {
+        &mut self.welding_manager
+    }
+
+    /// Returns a reference to the [`VoxelObjectIntegrityManager`].
+    pub fn integrity_manager(&self) -> &VoxelObjectIntegrityManager {
+        &self.integrity_manager
+    }
+
+    /// Returns a mutable reference to the [`VoxelObjectIntegrityManager`].
+    pub fn integrity_manager_mut(&mut self) -> &mut VoxelObjectIntegrityManager {
+        &mut self.integrity_manager
+    }
+
+    /// Returns a reference to the [`VoxelObjectThermalManager`].
+    pub fn thermal_manager(&self) -> &VoxelObjectThermalManager {
+        &self.thermal_manager
+    }
+
+    /// Returns a mutable reference to the [`VoxelObjectThermalManager`].
+    pub fn thermal_manager_mut(&mut self) -> &mut VoxelObjectThermalManager {
+        &mut self.thermal_manager
+    }
+
     /// Removes all voxel interaction state and frees up all allocated memory.
     pub fn reset_and_free(&mut self) {
         self.absorption_manager.reset_and_free();
         self.fracturing_manager.reset_and_free();
+        self.welding_manager.reset_and_free();
+        self.integrity_manager.reset_and_free();
+        self.thermal_manager.reset_and_free();
     }
 }
 
@@ -679,6 +730,25 @@ fn update_anchors_for_voxel_object_after_removing_voxels(
     lost_anchors
 }
 
+fn update_anchors_for_voxel_object_after_adding_voxels(
+    anchor_manager: &mut AnchorManager,
+    rigid_body_id: DynamicRigidBodyID,
+    original_local_center_of_mass: &Vector3,
+    new_local_center_of_mass: &Vector3,
+) {
+    // Adding voxels can not detach any anchors, so we only have to make the
+    // anchor points relative to the new center of mass
+    let anchor_offset = original_local_center_of_mass - new_local_center_of_mass;
+
+    anchor_manager.dynamic_mut().for_each_body_anchor_mut(
+        rigid_body_id,
+        &mut |_, compact_anchor_point| {
+            let anchor_point = compact_anchor_point.aligned() + anchor_offset;
+            *compact_anchor_point = anchor_point.compact();
+        },
+    );
+}
+
 fn transfer_anchors_to_extracted_voxel_object(
     remaining_lost_anchors: &mut Anchors,
     extracted_object: &VoxelObject,
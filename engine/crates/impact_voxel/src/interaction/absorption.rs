@@ -11,7 +11,7 @@ use anyhow::{Result, bail};
 use bytemuck::{Pod, Zeroable};
 use impact_alloc::{arena::ArenaPool, avec};
 use impact_containers::HashMap;
-use impact_geometry::{CapsuleC, SphereC};
+use impact_geometry::{CapsuleC, Sphere, SphereC};
 use impact_id::{EntityID, EntityIDManager, define_entity_id_newtype};
 use impact_intersection::{IntersectionManager, bounding_volume::BoundingVolumeID};
 use impact_math::{
@@ -444,7 +444,8 @@ pub fn apply_absorption<C>(
 {
     let voxel_object_manager = &mut voxel_manager.object_manager;
     let voxel_object_buffer_pool = &mut voxel_manager.object_buffer_pool;
-    let voxel_absorption_manager = voxel_manager.interaction_manager.absorption_manager_mut();
+    let voxel_absorption_manager = &mut voxel_manager.interaction_manager.absorption_manager;
+    let voxel_thermal_manager = &mut voxel_manager.interaction_manager.thermal_manager;
 
     let absorbing_sphere_entities = context.gather_voxel_absorbing_sphere_entities();
     let absorbing_capsule_entities = context.gather_voxel_absorbing_capsule_entities();
@@ -484,13 +485,18 @@ pub fn apply_absorption<C>(
                      voxel_object,
                      local_center_of_mass,
                      world_to_voxel_object_transform| {
-                        apply_sphere_absorption(
+                        let (absorbed_volume, absorbing_region) = apply_sphere_absorption(
                             inertial_property_updater,
                             voxel_object,
                             &world_to_voxel_object_transform,
                             absorbing_sphere,
                             sphere_to_world_transform,
                         );
+                        voxel_thermal_manager.stage_absorption_heat_deposit(
+                            VoxelObjectID::from_entity_id(object_entity_id),
+                            &absorbing_region,
+                            absorbed_volume,
+                        );
                         if !affected_voxel_objects
                             .iter()
                             .any(|(id, _)| *id == object_entity_id)
@@ -537,13 +543,18 @@ pub fn apply_absorption<C>(
                      voxel_object,
                      local_center_of_mass,
                      world_to_voxel_object_transform| {
-                        apply_capsule_absorption(
+                        let (absorbed_volume, absorbing_region) = apply_capsule_absorption(
                             inertial_property_updater,
                             voxel_object,
                             &world_to_voxel_object_transform,
                             absorbing_capsule,
                             capsule_to_world_transform,
                         );
+                        voxel_thermal_manager.stage_absorption_heat_deposit(
+                            VoxelObjectID::from_entity_id(object_entity_id),
+                            &absorbing_region,
+                            absorbed_volume,
+                        );
                         if !affected_voxel_objects
                             .iter()
                             .any(|(id, _)| *id == object_entity_id)
@@ -677,6 +688,13 @@ pub fn apply_absorption<C>(
             }
 
             context.create_extracted_voxel_object_entities(disconnected_entity_ids, entity_id);
+
+            if !original_object_empty {
+                voxel_manager
+                    .interaction_manager
+                    .integrity_manager
+                    .stage_integrity_check_after_edit(voxel_object_id);
+            }
         }
     }
 }
@@ -798,13 +816,16 @@ fn with_potential_voxel_object_pair(
     );
 }
 
+/// Applies the absorbing sphere to the voxel object and returns the absorbed
+/// volume together with a sphere bounding the absorbing region in the
+/// normalized model space of the voxel object.
 fn apply_sphere_absorption(
     inertial_property_updater: &mut VoxelObjectInertialPropertyUpdater<'_, '_>,
     voxel_object: &mut VoxelObject,
     world_to_voxel_object_transform: &Isometry3,
     tracking_absorbing_sphere: &mut TrackingVoxelAbsorbingSphere,
     sphere_to_world_transform: &Isometry3,
-) {
+) -> (f32, Sphere) {
     let absorbing_sphere = &tracking_absorbing_sphere.sphere;
     let tracker = &mut tracking_absorbing_sphere.tracker;
 
@@ -821,6 +842,8 @@ fn apply_sphere_absorption(
 
     let sphere_radius_in_voxels = absorbing_sphere.radius * voxel_object.inverse_voxel_extent();
 
+    let mut absorbed_volume = 0.0;
+
     voxel_object.modify_voxels_within_sphere(
         &influence_sphere_in_norm_voxel_object_space,
         &mut |object_voxel_indices, squared_distance_from_center_in_voxels, voxel| {
@@ -837,19 +860,30 @@ fn apply_sphere_absorption(
                     inertial_property_updater
                         .remove_voxel(&object_voxel_indices, voxel.voxel_type());
                     tracker.register_absorbed_voxel(voxel_volume, *voxel);
+                    absorbed_volume += voxel_volume;
                 }
             });
         },
     );
+
+    let absorbing_region = Sphere::new(
+        *influence_sphere_in_norm_voxel_object_space.center(),
+        sphere_radius_in_voxels,
+    );
+
+    (absorbed_volume, absorbing_region)
 }
 
+/// Applies the absorbing capsule to the voxel object and returns the absorbed
+/// volume together with a sphere bounding the absorbing region in the
+/// normalized model space of the voxel object.
 fn apply_capsule_absorption(
     inertial_property_updater: &mut VoxelObjectInertialPropertyUpdater<'_, '_>,
     voxel_object: &mut VoxelObject,
     world_to_voxel_object_transform: &Isometry3,
     tracking_absorbing_capsule: &mut TrackingVoxelAbsorbingCapsule,
     capsule_to_world_transform: &Isometry3,
-) {
+) -> (f32, Sphere) {
     let absorbing_capsule = &tracking_absorbing_capsule.capsule;
     let tracker = &mut tracking_absorbing_capsule.tracker;
 
@@ -866,6 +900,8 @@ fn apply_capsule_absorption(
 
     let capsule_radius_in_voxels = absorbing_capsule.radius * voxel_object.inverse_voxel_extent();
 
+    let mut absorbed_volume = 0.0;
+
     voxel_object.modify_voxels_within_capsule(
         &influence_capsule_in_norm_voxel_object_space,
         &mut |object_voxel_indices, squared_distance_from_segment_in_voxels, voxel| {
@@ -882,10 +918,20 @@ fn apply_capsule_absorption(
                     inertial_property_updater
                         .remove_voxel(&object_voxel_indices, voxel.voxel_type());
                     tracker.register_absorbed_voxel(voxel_volume, *voxel);
+                    absorbed_volume += voxel_volume;
                 }
             });
         },
     );
+
+    let half_segment_vector = *influence_capsule_in_norm_voxel_object_space.segment_vector() * 0.5;
+
+    let absorbing_region = Sphere::new(
+        *influence_capsule_in_norm_voxel_object_space.segment_start() + half_segment_vector,
+        half_segment_vector.norm() + capsule_radius_in_voxels,
+    );
+
+    (absorbed_volume, absorbing_region)
 }
 
 pub fn apply_mutual_absorption(
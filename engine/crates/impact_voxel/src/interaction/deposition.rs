@@ -0,0 +1,1083 @@
+//! Voxel deposition.
+
+use crate::{
+    Voxel, VoxelManager, VoxelObjectID, VoxelSignedDistance,
+    generation::sdf::{Smoothness, sdf_union},
+    interaction,
+    object::{VoxelObject, inertia::VoxelObjectInertialPropertyManager},
+    voxel_types::{VoxelType, VoxelTypeRegistry},
+};
+use impact_geometry::{AxisAlignedBox, CapsuleC, OrientedBox, OrientedBoxC, SphereC};
+use impact_id::EntityID;
+use impact_math::{
+    point::{Point3, Point3C},
+    quaternion::UnitQuaternionC,
+    transform::Isometry3,
+    vector::{Vector3, Vector3C},
+};
+use impact_physics::{
+    anchor::AnchorManager,
+    rigid_body::{DynamicRigidBodyID, RigidBodyManager},
+};
+
+/// A sphere that deposits voxels of a given type inside it.
+#[derive(Clone, Copy, Debug)]
+pub struct VoxelDepositingSphere {
+    /// The offset of the sphere in the reference frame of the brush.
+    offset: Vector3C,
+    /// The radius of the sphere.
+    radius: f32,
+    /// The type of the deposited voxels.
+    voxel_type: VoxelType,
+    /// The distance over which the deposited volume is blended with the
+    /// existing surface.
+    smoothness: f32,
+}
+
+/// A capsule that deposits voxels of a given type inside it.
+#[derive(Clone, Copy, Debug)]
+pub struct VoxelDepositingCapsule {
+    /// The offset of the starting point of the capsule's central line segment
+    /// in the reference frame of the brush.
+    offset_to_segment_start: Vector3C,
+    /// The displacement vector from the start to the end of the capsule's
+    /// central line segment in the reference frame of the brush.
+    segment_vector: Vector3C,
+    /// The radius of the capsule.
+    radius: f32,
+    /// The type of the deposited voxels.
+    voxel_type: VoxelType,
+    /// The distance over which the deposited volume is blended with the
+    /// existing surface.
+    smoothness: f32,
+}
+
+/// A box that deposits voxels of a given type inside it. The box is aligned
+/// with the axes of the brush's reference frame.
+#[derive(Clone, Copy, Debug)]
+pub struct VoxelDepositingBox {
+    /// The offset of the center of the box in the reference frame of the brush.
+    offset: Vector3C,
+    /// The half extents of the box along each axis.
+    half_extents: Vector3C,
+    /// The type of the deposited voxels.
+    voxel_type: VoxelType,
+    /// The distance over which the deposited volume is blended with the
+    /// existing surface.
+    smoothness: f32,
+}
+
+/// The result of depositing voxels into a voxel object.
+#[derive(Clone, Debug)]
+pub struct VoxelDepositionOutcome {
+    /// The displacement of the origin of the object's voxel grid due to the
+    /// grid being grown, in the model space of the object prior to the
+    /// deposition. Points in the old model space are converted to the new one
+    /// by subtracting this displacement.
+    pub origin_displacement: Vector3,
+    /// The number of previously empty voxels that were filled.
+    pub filled_voxel_count: usize,
+}
+
+impl VoxelDepositingSphere {
+    /// Creates a new [`VoxelDepositingSphere`] with the given offset and radius
+    /// in the reference frame of the brush, depositing voxels of the given type
+    /// blended with the existing surface over the given smoothness distance.
+    pub fn new(offset: Vector3C, radius: f32, voxel_type: VoxelType, smoothness: f32) -> Self {
+        assert!(radius >= 0.0);
+        assert!(smoothness >= 0.0);
+        Self {
+            offset,
+            radius,
+            voxel_type,
+            smoothness,
+        }
+    }
+
+    /// Returns the sphere in the reference frame of the brush.
+    pub fn sphere(&self) -> SphereC {
+        SphereC::new(Point3C::from(self.offset), self.radius)
+    }
+
+    /// Returns the type of the deposited voxels.
+    pub fn voxel_type(&self) -> VoxelType {
+        self.voxel_type
+    }
+
+    /// Returns the smoothness distance of the deposition.
+    pub fn smoothness(&self) -> f32 {
+        self.smoothness
+    }
+}
+
+impl VoxelDepositingCapsule {
+    /// Creates a new [`VoxelDepositingCapsule`] with the given offset to the
+    /// start of the capsule's central line segment, displacement from the start
+    /// to the end of the line segment and radius, all in the reference frame of
+    /// the brush, depositing voxels of the given type blended with the existing
+    /// surface over the given smoothness distance.
+    pub fn new(
+        offset_to_segment_start: Vector3C,
+        segment_vector: Vector3C,
+        radius: f32,
+        voxel_type: VoxelType,
+        smoothness: f32,
+    ) -> Self {
+        assert!(radius >= 0.0);
+        assert!(smoothness >= 0.0);
+        Self {
+            offset_to_segment_start,
+            segment_vector,
+            radius,
+            voxel_type,
+            smoothness,
+        }
+    }
+
+    /// Returns the capsule in the reference frame of the brush.
+    pub fn capsule(&self) -> CapsuleC {
+        CapsuleC::new(
+            Point3C::from(self.offset_to_segment_start),
+            self.segment_vector,
+            self.radius,
+        )
+    }
+
+    /// Returns the type of the deposited voxels.
+    pub fn voxel_type(&self) -> VoxelType {
+        self.voxel_type
+    }
+
+    /// Returns the smoothness distance of the deposition.
+    pub fn smoothness(&self) -> f32 {
+        self.smoothness
+    }
+}
+
+impl VoxelDepositingBox {
+    /// Creates a new [`VoxelDepositingBox`] with the given center offset and
+    /// half extents in the reference frame of the brush, depositing voxels of
+    /// the given type blended with the existing surface over the given
+    /// smoothness distance.
+    pub fn new(
+        offset: Vector3C,
+        half_extents: Vector3C,
+        voxel_type: VoxelType,
+        smoothness: f32,
+    ) -> Self {
+        assert!(half_extents.x() >= 0.0 && half_extents.y() >= 0.0 && half_extents.z() >= 0.0);
+        assert!(smoothness >= 0.0);
+        Self {
+            offset,
+            half_extents,
+            voxel_type,
+            smoothness,
+        }
+    }
+
+    /// Returns the box in the reference frame of the brush.
+    pub fn oriented_box(&self) -> OrientedBoxC {
+        OrientedBoxC::new(
+            Point3C::from(self.offset),
+            UnitQuaternionC::identity(),
+            self.half_extents,
+        )
+    }
+
+    /// Returns the type of the deposited voxels.
+    pub fn voxel_type(&self) -> VoxelType {
+        self.voxel_type
+    }
+
+    /// Returns the smoothness distance of the deposition.
+    pub fn smoothness(&self) -> f32 {
+        self.smoothness
+    }
+}
+
+/// Deposits the given sphere into the voxel object with the given entity ID,
+/// which must have a dynamic rigid body. The chunk grid of the object is grown
+/// if the sphere extends beyond it, and the inertial properties of the rigid
+/// body and its anchors are updated to account for the added mass.
+///
+/// Deposited material that does not touch the object still becomes part of
+/// the same rigid body.
+///
+/// Returns [`None`] if the entity has no voxel object or rigid body.
+pub fn apply_sphere_deposition(
+    voxel_manager: &mut VoxelManager,
+    voxel_type_registry: &VoxelTypeRegistry,
+    rigid_body_manager: &mut RigidBodyManager,
+    anchor_manager: &mut AnchorManager,
+    entity_id: EntityID,
+    depositing_sphere: &VoxelDepositingSphere,
+    sphere_to_world_transform: &Isometry3,
+) -> Option<VoxelDepositionOutcome> {
+    apply_deposition(
+        voxel_manager,
+        voxel_type_registry,
+        rigid_body_manager,
+        anchor_manager,
+        entity_id,
+        |voxel_object, inertial_property_manager, voxel_type_densities, world_to_voxel_object| {
+            deposit_voxels_within_sphere(
+                voxel_object,
+                inertial_property_manager,
+                voxel_type_densities,
+                depositing_sphere,
+                &(world_to_voxel_object * sphere_to_world_transform),
+            )
+        },
+    )
+}
+
+/// Deposits the given capsule into the voxel object with the given entity ID,
+/// which must have a dynamic rigid body. See [`apply_sphere_deposition`] for
+/// details.
+pub fn apply_capsule_deposition(
+    voxel_manager: &mut VoxelManager,
+    voxel_type_registry: &VoxelTypeRegistry,
+    rigid_body_manager: &mut RigidBodyManager,
+    anchor_manager: &mut AnchorManager,
+    entity_id: EntityID,
+    depositing_capsule: &VoxelDepositingCapsule,
+    capsule_to_world_transform: &Isometry3,
+) -> Option<VoxelDepositionOutcome> {
+    apply_deposition(
+        voxel_manager,
+        voxel_type_registry,
+        rigid_body_manager,
+        anchor_manager,
+        entity_id,
+        |voxel_object, inertial_property_manager, voxel_type_densities, world_to_voxel_object| {
+            deposit_voxels_within_capsule(
+                voxel_object,
+                inertial_property_manager,
+                voxel_type_densities,
+                depositing_capsule,
+                &(world_to_voxel_object * capsule_to_world_transform),
+            )
+        },
+    )
+}
+
+/// Deposits the given box into the voxel object with the given entity ID,
+/// which must have a dynamic rigid body. See [`apply_sphere_deposition`] for
+/// details.
+pub fn apply_box_deposition(
+    voxel_manager: &mut VoxelManager,
+    voxel_type_registry: &VoxelTypeRegistry,
+    rigid_body_manager: &mut RigidBodyManager,
+    anchor_manager: &mut AnchorManager,
+    entity_id: EntityID,
+    depositing_box: &VoxelDepositingBox,
+    box_to_world_transform: &Isometry3,
+) -> Option<VoxelDepositionOutcome> {
+    apply_deposition(
+        voxel_manager,
+        voxel_type_registry,
+        rigid_body_manager,
+        anchor_manager,
+        entity_id,
+        |voxel_object, inertial_property_manager, voxel_type_densities, world_to_voxel_object| {
+            deposit_voxels_within_box(
+                voxel_object,
+                inertial_property_manager,
+                voxel_type_densities,
+                depositing_box,
+                &(world_to_voxel_object * box_to_world_transform),
+            )
+        },
+    )
+}
+
+/// Deposits the given sphere into the given voxel object, growing its chunk
+/// grid if required and updating the given inertial property manager
+/// accordingly. The transform maps from the reference frame of the brush to
+/// the model space of the voxel object.
+///
+/// Even though modifying the object will invalidate the connected region
+/// information, this function does not call
+/// [`VoxelObject::resolve_connected_regions_between_all_chunks`]. Make sure to
+/// call it once all modifications have been made.
+pub fn deposit_voxels_within_sphere(
+    voxel_object: &mut VoxelObject,
+    inertial_property_manager: &mut VoxelObjectInertialPropertyManager,
+    voxel_type_densities: &[f32],
+    depositing_sphere: &VoxelDepositingSphere,
+    sphere_to_voxel_object_transform: &Isometry3,
+) -> VoxelDepositionOutcome {
+    let sphere = depositing_sphere
+        .sphere()
+        .aligned()
+        .iso_transformed(sphere_to_voxel_object_transform)
+        .scaled(voxel_object.inverse_voxel_extent());
+
+    let smoothness_in_voxels = depositing_sphere.smoothness * voxel_object.inverse_voxel_extent();
+
+    let influence_aabb = sphere
+        .compute_aabb()
+        .expanded_about_center(influence_padding(smoothness_in_voxels));
+
+    deposit_voxels(
+        voxel_object,
+        inertial_property_manager,
+        voxel_type_densities,
+        depositing_sphere.voxel_type,
+        smoothness_in_voxels,
+        &influence_aabb,
+        |position| (position - sphere.center()).norm() - sphere.radius(),
+    )
+}
+
+/// Deposits the given capsule into the given voxel object. See
+/// [`deposit_voxels_within_sphere`] for details.
+pub fn deposit_voxels_within_capsule(
+    voxel_object: &mut VoxelObject,
+    inertial_property_manager: &mut VoxelObjectInertialPropertyManager,
+    voxel_type_densities: &[f32],
+    depositing_capsule: &VoxelDepositingCapsule,
+    capsule_to_voxel_object_transform: &Isometry3,
+) -> VoxelDepositionOutcome {
+    let capsule = depositing_capsule
+        .capsule()
+        .aligned()
+        .iso_transformed(capsule_to_voxel_object_transform)
+        .scaled(voxel_object.inverse_voxel_extent());
+
+    let smoothness_in_voxels = depositing_capsule.smoothness * voxel_object.inverse_voxel_extent();
+
+    let influence_aabb = capsule
+        .compute_aabb()
+        .expanded_about_center(influence_padding(smoothness_in_voxels));
+
+    let containment_tester = capsule.create_point_containment_tester();
+
+    deposit_voxels(
+        voxel_object,
+        inertial_property_manager,
+        voxel_type_densities,
+        depositing_capsule.voxel_type,
+        smoothness_in_voxels,
+        &influence_aabb,
+        |position| {
+            containment_tester
+                .shortest_squared_distance_from_point_to_segment(position)
+                .sqrt()
+                - capsule.radius()
+        },
+    )
+}
+
+/// Deposits the given box into the given voxel object. See
+/// [`deposit_voxels_within_sphere`] for details.
+pub fn deposit_voxels_within_box(
+    voxel_object: &mut VoxelObject,
+    inertial_property_manager: &mut VoxelObjectInertialPropertyManager,
+    voxel_type_densities: &[f32],
+    depositing_box: &VoxelDepositingBox,
+    box_to_voxel_object_transform: &Isometry3,
+) -> VoxelDepositionOutcome {
+    let transformed_box = depositing_box
+        .oriented_box()
+        .aligned()
+        .iso_transformed(box_to_voxel_object_transform);
+
+    let oriented_box = OrientedBox::new(
+        Point3::from(transformed_box.center().as_vector() * voxel_object.inverse_voxel_extent()),
+        *transformed_box.orientation(),
+        transformed_box.half_extents() * voxel_object.inverse_voxel_extent(),
+    );
+
+    let smoothness_in_voxels = depositing_box.smoothness * voxel_object.inverse_voxel_extent();
+
+    let influence_aabb = AxisAlignedBox::aabb_for_point_array(&oriented_box.compute_corners())
+        .expanded_about_center(influence_padding(smoothness_in_voxels));
+
+    let half_extents = *oriented_box.half_extents();
+
+    deposit_voxels(
+        voxel_object,
+        inertial_property_manager,
+        voxel_type_densities,
+        depositing_box.voxel_type,
+        smoothness_in_voxels,
+        &influence_aabb,
+        |position| {
+            let position_in_box_frame = oriented_box.transform_point_to_box_frame(position);
+            let q = position_in_box_frame.as_vector().component_abs() - half_extents;
+            q.component_max(&Vector3::zeros()).norm() + q.max_component().min(0.0)
+        },
+    )
+}
+
+/// Computes the new signed distance for the given voxel when depositing a
+/// brush with the given signed distance at the voxel. Distances and
+/// smoothness should be specified in voxels.
+pub fn compute_deposited_signed_distance(
+    voxel: &Voxel,
+    brush_signed_distance: f32,
+    smoothness: Smoothness,
+) -> f32 {
+    sdf_union(
+        voxel.signed_distance().to_f32(),
+        brush_signed_distance,
+        smoothness,
+    )
+}
+
+/// Voxels farther outside the brush than this (in voxels) can not be affected
+/// by the deposition.
+fn influence_padding(smoothness_in_voxels: f32) -> f32 {
+    VoxelSignedDistance::max_f32() + smoothness_in_voxels
+}
+
+fn deposit_voxels(
+    voxel_object: &mut VoxelObject,
+    inertial_property_manager: &mut VoxelObjectInertialPropertyManager,
+    voxel_type_densities: &[f32],
+    voxel_type: VoxelType,
+    smoothness_in_voxels: f32,
+    normalized_influence_aabb: &AxisAlignedBox,
+    compute_brush_signed_distance: impl Fn(&Point3) -> f32,
+) -> VoxelDepositionOutcome {
+    let padding = voxel_object.chunk_grid_padding_to_include_aab(normalized_influence_aabb);
+    voxel_object.grow_chunk_grid(&padding);
+
+    // All brush positions are in the normalized model space of the object
+    // prior to growing the grid, so we shift voxel positions back into it
+    let voxel_shift = Vector3::from(padding.lower_voxel_shift().map(|shift| shift as f32));
+
+    let origin_displacement = -voxel_object.voxel_extent() * voxel_shift;
+
+    // The inertial properties are defined relative to the origin of the grid
+    if !padding.is_zero() {
+        inertial_property_manager.offset_reference_point_by(&origin_displacement);
+    }
+
+    let smoothness = Smoothness::new(smoothness_in_voxels);
+
+    let included_voxel_ranges = voxel_object
+        .voxel_ranges_in_grid_touching_aab(&normalized_influence_aabb.translated(&voxel_shift));
+
+    let mut inertial_property_updater =
+        inertial_property_manager.begin_update(voxel_object.voxel_extent(), voxel_type_densities);
+
+    let mut filled_voxel_count = 0;
+
+    voxel_object.deposit_into_voxels_within_ranges(
+        included_voxel_ranges,
+        &mut |object_voxel_indices, voxel| {
+            let position = Point3::new(
+                object_voxel_indices[0] as f32 + 0.5,
+                object_voxel_indices[1] as f32 + 0.5,
+                object_voxel_indices[2] as f32 + 0.5,
+            ) - voxel_shift;
+
+            let original_signed_distance = voxel.signed_distance();
+
+            let new_signed_distance = compute_deposited_signed_distance(
+                voxel,
+                compute_brush_signed_distance(&position),
+                smoothness,
+            );
+
+            voxel.set_signed_distance_filling_with(new_signed_distance, voxel_type, &mut |_| {
+                inertial_property_updater.add_voxel(&object_voxel_indices, voxel_type);
+                filled_voxel_count += 1;
+            });
+
+            voxel.signed_distance() != original_signed_distance
+        },
+    );
+
+    VoxelDepositionOutcome {
+        origin_displacement,
+        filled_voxel_count,
+    }
+}
+
+fn apply_deposition(
+    voxel_manager: &mut VoxelManager,
+    voxel_type_registry: &VoxelTypeRegistry,
+    rigid_body_manager: &mut RigidBodyManager,
+    anchor_manager: &mut AnchorManager,
+    entity_id: EntityID,
+    deposit: impl FnOnce(
+        &mut VoxelObject,
+        &mut VoxelObjectInertialPropertyManager,
+        &[f32],
+        &Isometry3,
+    ) -> VoxelDepositionOutcome,
+) -> Option<VoxelDepositionOutcome> {
+    let voxel_object_id = VoxelObjectID::from_entity_id(entity_id);
+
+    let (voxel_object, physics_context) = voxel_manager
+        .object_manager
+        .get_voxel_object_with_physics_context_mut(voxel_object_id)?;
+
+    let voxel_object = voxel_object.object_mut();
+    let inertial_property_manager = &mut physics_context.inertial_property_manager;
+
+    let rigid_body_id = DynamicRigidBodyID::from_entity_id(entity_id);
+    let Some(rigid_body) = rigid_body_manager.get_dynamic_rigid_body_mut(rigid_body_id) else {
+        log::warn!("Voxel object physics context points to missing dynamic rigid body");
+        return None;
+    };
+
+    let original_local_center_of_mass = inertial_property_manager.derive_center_of_mass();
+
+    let world_to_voxel_object_transform = rigid_body
+        .reference_frame()
+        .create_transform_to_parent_space()
+        .applied_to_translation(&(-original_local_center_of_mass))
+        .inverted();
+
+    let outcome = deposit(
+        voxel_object,
+        inertial_property_manager,
+        voxel_type_registry.mass_densities(),
+        &world_to_voxel_object_transform,
+    );
+
+    if voxel_object.invalidated_mesh_chunk_indices().len() == 0 {
+        return Some(outcome);
+    }
+
+    // The global connected region information has not been resolved after the
+    // voxels were deposited
+    voxel_object.resolve_connected_regions_between_all_chunks();
+
+    // The original center of mass in the model space of the possibly grown
+    // object
+    let original_local_center_of_mass = original_local_center_of_mass - outcome.origin_displacement;
+
+    let new_local_center_of_mass =
+        interaction::apply_updated_inertial_properties_to_rigid_body_preserving_momentum(
+            rigid_body,
+            inertial_property_manager,
+            original_local_center_of_mass,
+        );
+
+    interaction::update_anchors_for_voxel_object_after_adding_voxels(
+        anchor_manager,
+        rigid_body_id,
+        &original_local_center_of_mass,
+        &new_local_center_of_mass,
+    );
+
+    voxel_manager
+        .interaction_manager
+        .integrity_manager
+        .stage_integrity_check_after_edit(voxel_object_id);
+
+    Some(outcome)
+}
+
+#[cfg(not(miri))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        generation::{
+            SDFVoxelGenerator,
+            sdf::{SDFGraph, SDFNode},
+            voxel_type::SameVoxelTypeGenerator,
+        },
+        object::{CHUNK_SIZE, VoxelObjectBuffers},
+    };
+    use impact_alloc::Global;
+
+    fn generate_sphere_object(radius: f32) -> VoxelObject {
+        let mut graph = SDFGraph::new_in(Global);
+        graph.add_node(SDFNode::new_sphere(radius));
+        let sdf_generator = graph.build_in(Global).unwrap();
+
+        let generator = SDFVoxelGenerator::new(
+            0.5,
+            sdf_generator,
+            SameVoxelTypeGenerator::new(VoxelType::default()).into(),
+        );
+        VoxelObject::generate(VoxelObjectBuffers::new(), &generator)
+    }
+
+    fn setup() -> (VoxelObject, VoxelObjectInertialPropertyManager, Vec<f32>) {
+        let object = generate_sphere_object(10.0);
+        let voxel_type_densities = vec![1.0; 256];
+        let inertial_property_manager =
+            VoxelObjectInertialPropertyManager::initialized_from(&object, &voxel_type_densities);
+        (object, inertial_property_manager, voxel_type_densities)
+    }
+
+    /// Returns the center and radius of the occupied part of the object in its
+    /// model space.
+    fn occupied_center_and_radius(object: &VoxelObject) -> (Vector3, f32) {
+        let ranges = object.determine_tight_occupied_voxel_ranges();
+        let [x, y, z] = ranges
+            .clone()
+            .map(|range| 0.5 * (range.start + range.end) as f32 * object.voxel_extent());
+        let radius = 0.5 * (ranges[0].end - ranges[0].start) as f32 * object.voxel_extent();
+        (Vector3::new(x, y, z), radius)
+    }
+
+    /// Whether the voxel at the given position in the model space of the
+    /// object prior to the deposition is occupied.
+    fn is_occupied_at(
+        object: &VoxelObject,
+        outcome: &VoxelDepositionOutcome,
+        original_position: Vector3,
+    ) -> bool {
+        let grid_coords =
+            object.inverse_voxel_extent() * (original_position - outcome.origin_displacement);
+        object
+            .get_voxel_at_grid_coords(&grid_coords)
+            .is_some_and(|voxel| !voxel.is_empty())
+    }
+
+    fn validate_object_after_deposition(
+        object: &mut VoxelObject,
+        inertial_property_manager: &VoxelObjectInertialPropertyManager,
+        voxel_type_densities: &[f32],
+    ) {
+        object.resolve_connected_regions_between_all_chunks();
+
+        object.validate_adjacencies();
+        object.validate_chunk_obscuredness();
+        object.validate_occupied_voxel_ranges();
+        object.validate_sdf();
+        object.validate_region_count();
+
+        inertial_property_manager.validate_for_object(object, voxel_type_densities, 1e-3);
+    }
+
+    #[test]
+    fn depositing_sphere_on_surface_fills_voxels_in_same_region() {
+        let (mut object, mut inertial_property_manager, voxel_type_densities) = setup();
+        let (center, radius) = occupied_center_and_radius(&object);
+
+        let sphere_center = center + Vector3::new(radius, 0.0, 0.0);
+
+        let outcome = deposit_voxels_within_sphere(
+            &mut object,
+            &mut inertial_property_manager,
+            &voxel_type_densities,
+            &VoxelDepositingSphere::new(sphere_center.compact(), 1.5, VoxelType::default(), 0.0),
+            &Isometry3::identity(),
+        );
+
+        assert!(outcome.filled_voxel_count > 0);
+        assert!(is_occupied_at(
+            &object,
+            &outcome,
+            sphere_center + Vector3::new(0.75, 0.0, 0.0)
+        ));
+
+        validate_object_after_deposition(
+            &mut object,
+            &inertial_property_manager,
+            &voxel_type_densities,
+        );
+        assert_eq!(object.count_regions(), 1);
+    }
+
+    #[test]
+    fn depositing_capsule_on_surface_fills_voxels_along_segment() {
+        let (mut object, mut inertial_property_manager, voxel_type_densities) = setup();
+        let (center, radius) = occupied_center_and_radius(&object);
+
+        let segment_start = center + Vector3::new(0.0, radius - 1.0, 0.0);
+        let segment_vector = Vector3::new(0.0, 4.0, 0.0);
+
+        let outcome = deposit_voxels_within_capsule(
+            &mut object,
+            &mut inertial_property_manager,
+            &voxel_type_densities,
+            &VoxelDepositingCapsule::new(
+                segment_start.compact(),
+                segment_vector.compact(),
+                1.0,
+                VoxelType::default(),
+                0.0,
+            ),
+            &Isometry3::identity(),
+        );
+
+        assert!(outcome.filled_voxel_count > 0);
+        for distance_along_segment in [1.5, 2.5, 3.5] {
+            assert!(is_occupied_at(
+                &object,
+                &outcome,
+                segment_start + Vector3::new(0.0, distance_along_segment, 0.0)
+            ));
+        }
+        // Beyond the radius of the capsule
+        assert!(!is_occupied_at(
+            &object,
+            &outcome,
+            segment_start + Vector3::new(2.0, 3.0, 0.0)
+        ));
+
+        validate_object_after_deposition(
+            &mut object,
+            &inertial_property_manager,
+            &voxel_type_densities,
+        );
+        assert_eq!(object.count_regions(), 1);
+    }
+
+    #[test]
+    fn depositing_box_on_surface_fills_voxels_inside_box() {
+        let (mut object, mut inertial_property_manager, voxel_type_densities) = setup();
+        let (center, radius) = occupied_center_and_radius(&object);
+
+        let box_center = center + Vector3::new(0.0, 0.0, radius + 1.0);
+        let half_extents = Vector3::new(1.5, 1.0, 1.5);
+
+        let outcome = deposit_voxels_within_box(
+            &mut object,
+            &mut inertial_property_manager,
+            &voxel_type_densities,
+            &VoxelDepositingBox::new(
+                box_center.compact(),
+                half_extents.compact(),
+                VoxelType::default(),
+                0.0,
+            ),
+            &Isometry3::identity(),
+        );
+
+        assert!(outcome.filled_voxel_count > 0);
+        // Towards the corners of the box
+        for offset in [
+            Vector3::new(1.0, 0.5, 1.0),
+            Vector3::new(-1.0, -0.5, 1.0),
+            Vector3::new(1.0, 0.5, 0.0),
+        ] {
+            assert!(is_occupied_at(&object, &outcome, box_center + offset));
+        }
+        // Outside the box along its shortest axis
+        assert!(!is_occupied_at(
+            &object,
+            &outcome,
+            box_center + Vector3::new(0.0, 2.0, 1.0)
+        ));
+
+        validate_object_after_deposition(
+            &mut object,
+            &inertial_property_manager,
+            &voxel_type_densities,
+        );
+        assert_eq!(object.count_regions(), 1);
+    }
+
+    #[test]
+    fn depositing_below_grid_grows_grid_downward_and_shifts_voxels() {
+        let (mut object, mut inertial_property_manager, voxel_type_densities) = setup();
+        let original_object = object.clone();
+        let (center, radius) = occupied_center_and_radius(&object);
+
+        // A capsule from inside the object to below the lower x-side of the
+        // grid
+        let segment_start = center - Vector3::new(radius - 1.0, 0.0, 0.0);
+        let segment_end = Vector3::new(-2.0, center.y(), center.z());
+
+        let outcome = deposit_voxels_within_capsule(
+            &mut object,
+            &mut inertial_property_manager,
+            &voxel_type_densities,
+            &VoxelDepositingCapsule::new(
+                segment_start.compact(),
+                (segment_end - segment_start).compact(),
+                1.0,
+                VoxelType::default(),
+                0.0,
+            ),
+            &Isometry3::identity(),
+        );
+
+        let chunk_extent = CHUNK_SIZE as f32 * object.voxel_extent();
+        let chunks_added_below = (-outcome.origin_displacement.x() / chunk_extent) as usize;
+
+        assert!(chunks_added_below > 0);
+        assert_eq!(outcome.origin_displacement.y(), 0.0);
+        assert_eq!(outcome.origin_displacement.z(), 0.0);
+        assert_eq!(
+            object.chunk_counts()[0],
+            original_object.chunk_counts()[0] + chunks_added_below
+        );
+        assert_eq!(
+            object.origin_offset_in_root(),
+            [outcome.origin_displacement.x(), 0.0, 0.0]
+        );
+
+        // The original voxels have moved up in the grid
+        let voxel_shift = chunks_added_below * CHUNK_SIZE;
+        let [_, nj, nk] = original_object
+            .chunk_counts()
+            .map(|count| count * CHUNK_SIZE);
+        let (j, k) = (nj / 2, nk / 2);
+        for i in 0..original_object.chunk_counts()[0] * CHUNK_SIZE {
+            if original_object.get_voxel_if_occupied(i, j, k).is_some() {
+                assert!(
+                    object
+                        .get_voxel_if_occupied(i + voxel_shift, j, k)
+                        .is_some()
+                );
+            }
+        }
+
+        assert!(is_occupied_at(&object, &outcome, segment_end));
+        assert!(is_occupied_at(
+            &object,
+            &outcome,
+            Vector3::new(0.0, center.y(), center.z())
+        ));
+
+        validate_object_after_deposition(
+            &mut object,
+            &inertial_property_manager,
+            &voxel_type_densities,
+        );
+        assert_eq!(object.count_regions(), 1);
+    }
+
+    #[test]
+    fn inertial_properties_after_depositing_match_recomputed_ones() {
+        let (mut object, mut inertial_property_manager, voxel_type_densities) = setup();
+        let (center, radius) = occupied_center_and_radius(&object);
+
+        // Deposit a sequence of overlapping brushes, including ones that grow
+        // the grid on the lower and upper side, converting the brush positions
+        // to the current model space after each deposition
+        let mut total_origin_displacement = Vector3::zeros();
+
+        let outcome = deposit_voxels_within_sphere(
+            &mut object,
+            &mut inertial_property_manager,
+            &voxel_type_densities,
+            &VoxelDepositingSphere::new(
+                (center + Vector3::new(radius, 0.0, 0.0)).compact(),
+                2.0,
+                VoxelType::default(),
+                1.0,
+            ),
+            &Isometry3::identity(),
+        );
+        total_origin_displacement += outcome.origin_displacement;
+        inertial_property_manager.validate_for_object(&object, &voxel_type_densities, 1e-3);
+
+        let outcome = deposit_voxels_within_capsule(
+            &mut object,
+            &mut inertial_property_manager,
+            &voxel_type_densities,
+            &VoxelDepositingCapsule::new(
+                (center - total_origin_displacement).compact(),
+                Vector3::new(0.0, -(radius + 6.0), 0.0).compact(),
+                1.5,
+                VoxelType::default(),
+                0.5,
+            ),
+            &Isometry3::identity(),
+        );
+        total_origin_displacement += outcome.origin_displacement;
+        inertial_property_manager.validate_for_object(&object, &voxel_type_densities, 1e-3);
+
+        let outcome = deposit_voxels_within_box(
+            &mut object,
+            &mut inertial_property_manager,
+            &voxel_type_densities,
+            &VoxelDepositingBox::new(
+                (center + Vector3::new(0.0, 0.0, radius + 4.0) - total_origin_displacement)
+                    .compact(),
+                Vector3::new(2.0, 2.0, 5.0).compact(),
+                VoxelType::default(),
+                0.0,
+            ),
+            &Isometry3::identity(),
+        );
+        total_origin_displacement += outcome.origin_displacement;
+
+        assert!(total_origin_displacement.y() < 0.0);
+
+        validate_object_after_deposition(
+            &mut object,
+            &inertial_property_manager,
+            &voxel_type_densities,
+        );
+    }
+}
+
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing {
+    use super::*;
+    use crate::{
+        generation::SDFVoxelGenerator,
+        mesh::VoxelObjectMesh,
+        object::{
+            CHUNK_SIZE, VoxelObjectBuffers,
+            intersection::fuzzing::{ArbitraryCapsule, ArbitrarySphere},
+        },
+    };
+    use impact_alloc::Global;
+    use impact_containers::HashSet;
+
+    pub fn fuzz_test_depositing_voxels_within_sphere(
+        (generator, sphere, smoothness): (SDFVoxelGenerator<Global>, ArbitrarySphere, u8),
+    ) {
+        let mut object = VoxelObject::generate(VoxelObjectBuffers::new(), &generator);
+
+        if object.is_effectively_empty() {
+            return;
+        }
+
+        let voxel_type_densities = vec![1.0; 256];
+
+        let mut inertial_property_manager =
+            VoxelObjectInertialPropertyManager::initialized_from(&object, &voxel_type_densities);
+
+        let mut mesh = VoxelObjectMesh::create(&object);
+
+        let (center, radius) = confine_brush_to_grid_neighborhood(
+            &object,
+            sphere.0.center().as_vector(),
+            sphere.0.radius(),
+        );
+
+        let depositing_sphere = VoxelDepositingSphere::new(
+            center.compact(),
+            radius,
+            VoxelType::default(),
+            arbitrary_smoothness(&object, smoothness),
+        );
+
+        deposit_voxels_within_sphere(
+            &mut object,
+            &mut inertial_property_manager,
+            &voxel_type_densities,
+            &depositing_sphere,
+            &Isometry3::identity(),
+        );
+
+        validate_object_after_deposition(
+            &mut object,
+            &inertial_property_manager,
+            &voxel_type_densities,
+            &mut mesh,
+        );
+    }
+
+    pub fn fuzz_test_depositing_voxels_within_capsule(
+        (generator, capsules, smoothness): (SDFVoxelGenerator<Global>, Vec<ArbitraryCapsule>, u8),
+    ) {
+        let mut object = VoxelObject::generate(VoxelObjectBuffers::new(), &generator);
+
+        if object.is_effectively_empty() {
+            return;
+        }
+
+        let voxel_type_densities = vec![1.0; 256];
+
+        let mut inertial_property_manager =
+            VoxelObjectInertialPropertyManager::initialized_from(&object, &voxel_type_densities);
+
+        let mut mesh = VoxelObjectMesh::create(&object);
+
+        for capsule in capsules {
+            let (segment_start, radius) = confine_brush_to_grid_neighborhood(
+                &object,
+                capsule.0.segment_start().as_vector(),
+                capsule.0.radius(),
+            );
+
+            let max_segment_length = (CHUNK_SIZE as f32) * object.voxel_extent();
+            let segment_vector = if capsule.0.segment_vector().norm() > max_segment_length {
+                capsule.0.segment_vector().normalized() * max_segment_length
+            } else {
+                *capsule.0.segment_vector()
+            };
+
+            let depositing_capsule = VoxelDepositingCapsule::new(
+                segment_start.compact(),
+                segment_vector.compact(),
+                radius,
+                VoxelType::default(),
+                arbitrary_smoothness(&object, smoothness),
+            );
+
+            deposit_voxels_within_capsule(
+                &mut object,
+                &mut inertial_property_manager,
+                &voxel_type_densities,
+                &depositing_capsule,
+                &Isometry3::identity(),
+            );
+        }
+
+        validate_object_after_deposition(
+            &mut object,
+            &inertial_property_manager,
+            &voxel_type_densities,
+            &mut mesh,
+        );
+    }
+
+    /// Keeps the brush within one chunk of the object's current grid and limits
+    /// its radius to one chunk, so that the grid does not grow arbitrarily.
+    fn confine_brush_to_grid_neighborhood(
+        object: &VoxelObject,
+        position: &Vector3,
+        radius: f32,
+    ) -> (Vector3, f32) {
+        let chunk_extent = (CHUNK_SIZE as f32) * object.voxel_extent();
+
+        let grid_extents = Vector3::from(
+            object
+                .chunk_counts()
+                .map(|count| count as f32 * chunk_extent),
+        );
+
+        let position = Vector3::new(
+            position.x() % (grid_extents.x() + 2.0 * chunk_extent) - chunk_extent,
+            position.y() % (grid_extents.y() + 2.0 * chunk_extent) - chunk_extent,
+            position.z() % (grid_extents.z() + 2.0 * chunk_extent) - chunk_extent,
+        );
+
+        (position, radius.min(chunk_extent))
+    }
+
+    fn arbitrary_smoothness(object: &VoxelObject, smoothness: u8) -> f32 {
+        f32::from(smoothness % 32) * 0.1 * object.voxel_extent()
+    }
+
+    fn validate_object_after_deposition(
+        object: &mut VoxelObject,
+        inertial_property_manager: &VoxelObjectInertialPropertyManager,
+        voxel_type_densities: &[f32],
+        mesh: &mut VoxelObjectMesh,
+    ) {
+        object.resolve_connected_regions_between_all_chunks();
+
+        object.validate_adjacencies();
+        object.validate_chunk_obscuredness();
+        object.validate_occupied_voxel_ranges();
+        object.validate_sdf();
+        object.validate_region_count();
+
+        inertial_property_manager.validate_for_object(object, voxel_type_densities, 1e-3);
+
+        mesh.sync_with_voxel_object(object);
+        let mesh_from_scratch = VoxelObjectMesh::create(object);
+
+        assert_eq!(
+            mesh.chunk_submeshes()
+                .iter()
+                .map(|submesh| *submesh.chunk_indices())
+                .collect::<HashSet<_>>(),
+            mesh_from_scratch
+                .chunk_submeshes()
+                .iter()
+                .map(|submesh| *submesh.chunk_indices())
+                .collect::<HashSet<_>>()
+        );
+    }
+}
@@ -0,0 +1,413 @@
+//! Structural integrity of voxel objects.
+
+use crate::{
+    VoxelObjectBufferPool, VoxelObjectID, VoxelObjectManager,
+    collidable::CollisionWorld,
+    interaction::{self, RemovedMassFate, VoxelObjectInteractionContext, VoxelRemovalOutcome},
+    voxel_types::VoxelTypeRegistry,
+};
+use anyhow::{Result, bail};
+use impact_alloc::{AVec, arena::ArenaPool};
+use impact_id::{EntityID, EntityIDManager};
+use impact_math::{point::Point3, vector::Vector3};
+use impact_physics::{
+    anchor::AnchorManager,
+    force::{ForceGeneratorManager, constant_acceleration::ConstantAccelerationGeneratorID},
+    rigid_body::{DynamicRigidBodyID, RigidBodyManager},
+};
+
+/// Manages structural integrity checks for voxel objects.
+///
+/// An integrity check estimates how the weight of a voxel object is carried
+/// through its voxels to the points where it is supported, which are its
+/// anchors and its contacts that push against gravity. Voxels whose
+/// connections are loaded beyond the strength of their voxel types are
+/// broken, and any resulting disconnected regions are extracted into separate
+/// objects.
+#[derive(Debug)]
+pub struct VoxelObjectIntegrityManager {
+    staged_checks: Vec<VoxelObjectID>,
+    config: VoxelIntegrityConfig,
+}
+
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+#[derive(Clone, Debug)]
+pub struct VoxelIntegrityConfig {
+    /// Whether to automatically stage an integrity check for voxel objects
+    /// whose voxels have been absorbed or deposited.
+    pub check_after_edits: bool,
+    /// The maximum number of integrity checks to execute in a single
+    /// simulation step. Remaining staged checks are postponed to later steps.
+    pub max_checks_per_step: usize,
+    /// Voxels within this distance (in voxels) of an anchor or a supporting
+    /// contact point are considered supported.
+    pub support_radius: f32,
+}
+
+impl VoxelObjectIntegrityManager {
+    /// Creates a new integrity manager with the given configuration.
+    pub fn new(config: VoxelIntegrityConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            staged_checks: Vec::new(),
+            config,
+        })
+    }
+
+    /// Whether an integrity check is currently staged for the given voxel
+    /// object.
+    pub fn object_has_staged_integrity_check(&self, voxel_object_id: VoxelObjectID) -> bool {
+        self.staged_checks.contains(&voxel_object_id)
+    }
+
+    /// Stages an integrity check for the given voxel object, unless one is
+    /// already staged.
+    ///
+    /// Call [`Self::execute_integrity_checks`] to execute the staged checks.
+    pub fn stage_integrity_check(&mut self, voxel_object_id: VoxelObjectID) {
+        if !self.object_has_staged_integrity_check(voxel_object_id) {
+            self.staged_checks.push(voxel_object_id);
+        }
+    }
+
+    /// Stages an integrity check for the given voxel object if the
+    /// configuration specifies that objects should be checked after their
+    /// voxels have been modified.
+    pub fn stage_integrity_check_after_edit(&mut self, voxel_object_id: VoxelObjectID) {
+        if self.config.check_after_edits {
+            self.stage_integrity_check(voxel_object_id);
+        }
+    }
+
+    /// Executes integrity checks staged with [`Self::stage_integrity_check`],
+    /// up to the configured maximum number of checks per step.
+    ///
+    /// Since breaking overloaded voxels shifts their load onto the remaining
+    /// connections, objects that had voxels broken are checked again in a
+    /// later step, along with any objects that were split off from them.
+    pub fn execute_integrity_checks<C>(
+        &mut self,
+        context: &mut C,
+        entity_id_manager: &mut EntityIDManager,
+        voxel_type_registry: &VoxelTypeRegistry,
+        voxel_object_manager: &mut VoxelObjectManager,
+        voxel_object_buffer_pool: &mut VoxelObjectBufferPool,
+        rigid_body_manager: &mut RigidBodyManager,
+        anchor_manager: &mut AnchorManager,
+        force_generator_manager: &ForceGeneratorManager,
+        collision_world: &CollisionWorld,
+    ) where
+        C: VoxelObjectInteractionContext,
+    {
+        if self.staged_checks.is_empty() {
+            return;
+        }
+
+        // Nothing can break if all voxel types are infinitely strong
+        if voxel_type_registry
+            .strengths()
+            .iter()
+            .all(|strength| strength.is_infinite())
+        {
+            self.staged_checks.clear();
+            return;
+        }
+
+        let check_count = self
+            .staged_checks
+            .len()
+            .min(self.config.max_checks_per_step);
+
+        let checked_object_ids: Vec<_> = self.staged_checks.drain(..check_count).collect();
+
+        for voxel_object_id in checked_object_ids {
+            let Some(outcome) = check_integrity_of_object(
+                &self.config,
+                context,
+                entity_id_manager,
+                voxel_type_registry,
+                voxel_object_manager,
+                voxel_object_buffer_pool,
+                rigid_body_manager,
+                anchor_manager,
+                force_generator_manager,
+                collision_world,
+                voxel_object_id,
+            ) else {
+                continue;
+            };
+
+            if !outcome.original_object_empty {
+                self.stage_integrity_check(voxel_object_id);
+            }
+            for entity_id in outcome.disconnected_entity_ids {
+                self.stage_integrity_check(VoxelObjectID::from_entity_id(entity_id));
+            }
+        }
+    }
+
+    /// Removes all staged integrity checks and frees up all allocated memory.
+    pub fn reset_and_free(&mut self) {
+        self.staged_checks = Vec::new();
+    }
+}
+
+impl VoxelIntegrityConfig {
+    fn validate(&self) -> Result<()> {
+        if self.max_checks_per_step == 0 {
+            bail!("Maximum number of integrity checks per step must be at least 1");
+        }
+        if self.support_radius <= 0.0 {
+            bail!(
+                "Support radius for integrity checks must be positive: {}",
+                self.support_radius
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Default for VoxelIntegrityConfig {
+    fn default() -> Self {
+        Self {
+            check_after_edits: true,
+            max_checks_per_step: 4,
+            support_radius: 1.5,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct IntegrityCheckOutcome {
+    original_object_empty: bool,
+    disconnected_entity_ids: Vec<EntityID>,
+}
+
+/// Checks the integrity of the given voxel object and breaks its overloaded
+/// voxels.
+///
+/// # Returns
+/// [`None`] if no voxels were broken.
+fn check_integrity_of_object<C>(
+    config: &VoxelIntegrityConfig,
+    context: &mut C,
+    entity_id_manager: &mut EntityIDManager,
+    voxel_type_registry: &VoxelTypeRegistry,
+    voxel_object_manager: &mut VoxelObjectManager,
+    voxel_object_buffer_pool: &mut VoxelObjectBufferPool,
+    rigid_body_manager: &mut RigidBodyManager,
+    anchor_manager: &mut AnchorManager,
+    force_generator_manager: &ForceGeneratorManager,
+    collision_world: &CollisionWorld,
+    voxel_object_id: VoxelObjectID,
+) -> Option<IntegrityCheckOutcome>
+where
+    C: VoxelObjectInteractionContext,
+{
+    let entity_id = voxel_object_id.as_entity_id();
+
+    let (voxel_object, physics_context) =
+        voxel_object_manager.get_voxel_object_with_physics_context_mut(voxel_object_id)?;
+
+    let voxel_object = voxel_object.object_mut();
+
+    let rigid_body_id = DynamicRigidBodyID::from_entity_id(entity_id);
+    let Some(rigid_body) = rigid_body_manager.get_dynamic_rigid_body_mut(rigid_body_id) else {
+        log::warn!("Voxel object physics context points to missing dynamic rigid body");
+        return None;
+    };
+
+    let gravitational_acceleration = compute_gravitational_acceleration(
+        force_generator_manager,
+        entity_id,
+        rigid_body_id,
+        rigid_body.mass(),
+    );
+
+    let gravitational_acceleration_magnitude = gravitational_acceleration.norm();
+    if gravitational_acceleration_magnitude == 0.0 {
+        return None;
+    }
+
+    let original_local_center_of_mass = physics_context
+        .inertial_property_manager
+        .derive_center_of_mass();
+
+    let world_to_voxel_object_transform = rigid_body
+        .reference_frame()
+        .create_transform_to_parent_space()
+        .applied_to_translation(&(-original_local_center_of_mass))
+        .inverted();
+
+    let inverse_voxel_extent = voxel_object.inverse_voxel_extent();
+
+    let arena = ArenaPool::get_arena();
+    let mut support_voxel_indices = AVec::new_in(&arena);
+
+    // Anchor points are specified relative to the center of mass
+    for (_, anchor_point) in anchor_manager.dynamic().anchors_for_body(rigid_body_id) {
+        let normalized_anchor_point = Point3::from(
+            (*anchor_point.aligned().as_vector() + original_local_center_of_mass)
+                * inverse_voxel_extent,
+        );
+        voxel_object.gather_non_empty_voxels_near_point(
+            &normalized_anchor_point,
+            config.support_radius,
+            &mut support_voxel_indices,
+        );
+    }
+
+    if let Some(collisions) = collision_world.cached_collisions() {
+        for collision in collisions {
+            // The contact force on body A points along the surface normal of
+            // body B, and the force on body B points the opposite way
+            let normal_sign = if collision.collidable_a_id.as_entity_id() == entity_id {
+                1.0
+            } else if collision.collidable_b_id.as_entity_id() == entity_id {
+                -1.0
+            } else {
+                continue;
+            };
+
+            for contact in collision.contact_manifold.contacts() {
+                let geometry = &contact.contact.geometry;
+
+                // Only contacts pushing against gravity support the object
+                if normal_sign
+                    * geometry
+                        .surface_normal
+                        .as_vector()
+                        .dot(&gravitational_acceleration)
+                    >= 0.0
+                {
+                    continue;
+                }
+
+                let contact_point =
+                    world_to_voxel_object_transform.transform_point(&geometry.position);
+                let normalized_contact_point =
+                    Point3::from(*contact_point.as_vector() * inverse_voxel_extent);
+
+                voxel_object.gather_non_empty_voxels_near_point(
+                    &normalized_contact_point,
+                    config.support_radius,
+                    &mut support_voxel_indices,
+                );
+            }
+        }
+    }
+
+    let overloaded_voxel_indices = voxel_object.find_overloaded_voxels(
+        &arena,
+        &support_voxel_indices,
+        gravitational_acceleration_magnitude,
+        voxel_type_registry.mass_densities(),
+        voxel_type_registry.strengths(),
+    );
+
+    if overloaded_voxel_indices.is_empty() {
+        return None;
+    }
+
+    log::debug!(
+        "Breaking {} overloaded voxels in voxel object {voxel_object_id}",
+        overloaded_voxel_indices.len()
+    );
+
+    let mut inertial_property_updater = physics_context.inertial_property_manager.begin_update(
+        voxel_object.voxel_extent(),
+        voxel_type_registry.mass_densities(),
+    );
+
+    voxel_object.break_voxels(
+        &overloaded_voxel_indices,
+        &mut |voxel_indices, voxel_type| {
+            inertial_property_updater.remove_voxel(&voxel_indices, voxel_type);
+        },
+    );
+
+    voxel_object.resolve_connected_regions_between_all_chunks();
+
+    let VoxelRemovalOutcome {
+        original_object_empty,
+        extracted_components_for_disconnected_objects,
+        lost_anchors,
+    } = interaction::handle_voxel_object_after_removing_voxels(
+        &arena,
+        anchor_manager,
+        voxel_type_registry,
+        voxel_object_buffer_pool,
+        voxel_object,
+        &mut physics_context.inertial_property_manager,
+        rigid_body_id,
+        rigid_body,
+        original_local_center_of_mass,
+        RemovedMassFate::Destroyed,
+    );
+
+    // All lost anchors not inherited by a disconnected object should be
+    // deleted
+    for (anchor_id, _) in lost_anchors {
+        anchor_manager.dynamic_mut().remove(anchor_id);
+    }
+
+    if original_object_empty {
+        context.remove_voxel_object_entity(entity_id);
+    }
+
+    let disconnected_entity_ids =
+        entity_id_manager.provide_id_vec(extracted_components_for_disconnected_objects.len());
+
+    for (disconnected_entity_id, extracted_components) in disconnected_entity_ids
+        .iter()
+        .copied()
+        .zip(extracted_components_for_disconnected_objects)
+    {
+        interaction::spawn_extracted_voxel_object(
+            voxel_object_manager,
+            rigid_body_manager,
+            anchor_manager,
+            extracted_components,
+            disconnected_entity_id,
+        );
+    }
+
+    context.create_extracted_voxel_object_entities(disconnected_entity_ids.clone(), entity_id);
+
+    Some(IntegrityCheckOutcome {
+        original_object_empty,
+        disconnected_entity_ids,
+    })
+}
+
+/// Computes the world-space gravitational acceleration of the given body from
+/// its constant acceleration generator and the dynamic gravity acting on it.
+fn compute_gravitational_acceleration(
+    force_generator_manager: &ForceGeneratorManager,
+    entity_id: EntityID,
+    rigid_body_id: DynamicRigidBodyID,
+    mass: f32,
+) -> Vector3 {
+    let mut acceleration = Vector3::zeros();
+
+    if let Some(generator) = force_generator_manager
+        .constant_accelerations()
+        .get_generator(&ConstantAccelerationGeneratorID::from_entity_id(entity_id))
+    {
+        acceleration += generator.acceleration.vector().aligned();
+    }
+
+    if mass > 0.0
+        && let Some(load) = force_generator_manager
+            .dynamic_gravity_manager()
+            .get_load_on_body(rigid_body_id)
+    {
+        acceleration += load.force.aligned() / mass;
+    }
+
+    acceleration
+}
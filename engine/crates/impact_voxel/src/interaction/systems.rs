@@ -8,6 +8,7 @@ use crate::{
         VoxelObjectInteractionContext,
         absorption::{self, HasVoxelAbsorbingCapsule, HasVoxelAbsorbingSphere},
         fracturing::{FracturingProperties, VoxelObjectFracturingContext},
+        welding::{VoxelObjectWeldingContext, Weldable},
     },
     voxel_types::VoxelTypeRegistry,
 };
@@ -61,6 +62,12 @@ pub struct ECSVoxelObjectFracturingContext<'a> {
     pub ecs_world: &'a ECSWorld,
 }
 
+/// ECS-based implementation of a voxel object welding context.
+#[derive(Debug)]
+pub struct ECSVoxelObjectWeldingContext<'a> {
+    pub ecs_world: &'a ECSWorld,
+}
+
 impl<'a> VoxelObjectInteractionContext for ECSVoxelObjectInteractionContext<'a> {
     fn entity_exists(&self, entity_id: EntityID) -> bool {
         self.ecs_world.has_entity(entity_id)
@@ -319,6 +326,14 @@ impl<'a> VoxelObjectFracturingContext for ECSVoxelObjectFracturingContext<'a> {
     }
 }
 
+impl<'a> VoxelObjectWeldingContext for ECSVoxelObjectWeldingContext<'a> {
+    fn get_weldable_properties_for_entity(&self, entity_id: EntityID) -> Option<Weldable> {
+        self.ecs_world
+            .get_entity(entity_id)
+            .and_then(|entity| entity.get_component().map(|props| *props.access()))
+    }
+}
+
 /// Synchronizes model transforms for all voxel objects entities with their
 /// inertial properties.
 pub fn sync_voxel_object_model_transforms(
@@ -445,3 +460,125 @@ pub fn execute_fracturing_processes(
         );
     }
 }
+
+/// Welds staged pairs of voxel objects in resting contact.
+pub fn execute_welds(
+    component_metadata_registry: &ComponentMetadataRegistry,
+    entity_stager: &mut EntityStager,
+    ecs_world: &ECSWorld,
+    scene_graph: &SceneGraph,
+    voxel_manager: &mut VoxelManager,
+    voxel_type_registry: &VoxelTypeRegistry,
+    rigid_body_manager: &mut RigidBodyManager,
+    anchor_manager: &mut AnchorManager,
+    force_generator_manager: &ForceGeneratorManager,
+    collision_world: &CollisionWorld,
+) {
+    let mut interaction_context = ECSVoxelObjectInteractionContext {
+        component_metadata_registry,
+        entity_stager,
+        ecs_world,
+        scene_graph,
+        force_generator_manager,
+        collision_world,
+    };
+
+    let voxel_object_manager = &mut voxel_manager.object_manager;
+    let voxel_object_buffer_pool = &mut voxel_manager.object_buffer_pool;
+    let interaction_manager = &mut voxel_manager.interaction_manager;
+    let welding_manager = interaction_manager.welding_manager_mut();
+
+    welding_manager.execute_staged_welds(
+        &mut interaction_context,
+        voxel_type_registry,
+        voxel_object_manager,
+        voxel_object_buffer_pool,
+        rigid_body_manager,
+        anchor_manager,
+    );
+}
+
+/// Executes staged structural integrity checks, breaking overloaded voxels and
+/// extracting any resulting disconnected regions.
+pub fn execute_integrity_checks(
+    component_metadata_registry: &ComponentMetadataRegistry,
+    entity_id_manager: &mut EntityIDManager,
+    entity_stager: &mut EntityStager,
+    ecs_world: &ECSWorld,
+    scene_graph: &SceneGraph,
+    voxel_manager: &mut VoxelManager,
+    voxel_type_registry: &VoxelTypeRegistry,
+    rigid_body_manager: &mut RigidBodyManager,
+    anchor_manager: &mut AnchorManager,
+    force_generator_manager: &ForceGeneratorManager,
+    collision_world: &CollisionWorld,
+) {
+    let mut interaction_context = ECSVoxelObjectInteractionContext {
+        component_metadata_registry,
+        entity_stager,
+        ecs_world,
+        scene_graph,
+        force_generator_manager,
+        collision_world,
+    };
+
+    let voxel_object_manager = &mut voxel_manager.object_manager;
+    let voxel_object_buffer_pool = &mut voxel_manager.object_buffer_pool;
+    let interaction_manager = &mut voxel_manager.interaction_manager;
+    let integrity_manager = interaction_manager.integrity_manager_mut();
+
+    integrity_manager.execute_integrity_checks(
+        &mut interaction_context,
+        entity_id_manager,
+        voxel_type_registry,
+        voxel_object_manager,
+        voxel_object_buffer_pool,
+        rigid_body_manager,
+        anchor_manager,
+        force_generator_manager,
+        collision_world,
+    );
+}
+
+/// Applies staged heat deposits, diffuses heat through voxel objects that are
+/// not at ambient temperature and melts away voxels that have reached their
+/// melting temperature, extracting any resulting disconnected regions.
+pub fn update_thermal_states(
+    component_metadata_registry: &ComponentMetadataRegistry,
+    entity_id_manager: &mut EntityIDManager,
+    entity_stager: &mut EntityStager,
+    ecs_world: &ECSWorld,
+    scene_graph: &SceneGraph,
+    voxel_manager: &mut VoxelManager,
+    voxel_type_registry: &VoxelTypeRegistry,
+    rigid_body_manager: &mut RigidBodyManager,
+    anchor_manager: &mut AnchorManager,
+    force_generator_manager: &ForceGeneratorManager,
+    collision_world: &CollisionWorld,
+    time_step_duration: f32,
+) {
+    let mut interaction_context = ECSVoxelObjectInteractionContext {
+        component_metadata_registry,
+        entity_stager,
+        ecs_world,
+        scene_graph,
+        force_generator_manager,
+        collision_world,
+    };
+
+    let voxel_object_manager = &mut voxel_manager.object_manager;
+    let voxel_object_buffer_pool = &mut voxel_manager.object_buffer_pool;
+    let interaction_manager = &mut voxel_manager.interaction_manager;
+
+    interaction_manager.thermal_manager.update_thermal_states(
+        &mut interaction_context,
+        entity_id_manager,
+        voxel_type_registry,
+        voxel_object_manager,
+        voxel_object_buffer_pool,
+        &mut interaction_manager.integrity_manager,
+        rigid_body_manager,
+        anchor_manager,
+        time_step_duration,
+    );
+}
@@ -0,0 +1,556 @@
+//! Heating, heat diffusion and melting of voxel objects.
+
+use crate::{
+    VoxelObjectBufferPool, VoxelObjectID, VoxelObjectManager,
+    collidable::{Collidable, CollisionWorld},
+    interaction::{
+        self, RemovedMassFate, VoxelObjectInteractionContext, VoxelRemovalOutcome,
+        integrity::VoxelObjectIntegrityManager,
+    },
+    voxel_types::VoxelTypeRegistry,
+};
+use anyhow::{Result, bail};
+use impact_alloc::{AVec, arena::ArenaPool};
+use impact_geometry::Sphere;
+use impact_id::{EntityID, EntityIDManager};
+use impact_math::point::{Point3, Point3C};
+use impact_physics::{
+    anchor::AnchorManager,
+    collision::CollidableID,
+    constraint::ConstrainedBodyManager,
+    rigid_body::{DynamicRigidBodyID, RigidBodyManager},
+};
+
+/// Manages the thermal state of voxel objects.
+///
+/// Heat is deposited into voxel objects when their voxels are absorbed and
+/// when they collide with other bodies. The deposited heat is staged and
+/// applied at the start of the next thermal update, which then conducts heat
+/// through each warm object, exchanges heat with the surroundings and melts
+/// away voxels that have reached the melting temperature of their type.
+#[derive(Debug)]
+pub struct VoxelObjectThermalManager {
+    staged_heat_deposits: Vec<StagedHeatDeposit>,
+    config: VoxelThermalConfig,
+}
+
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+#[derive(Clone, Debug)]
+pub struct VoxelThermalConfig {
+    /// The thermal energy (in J) released into the voxels surrounding an
+    /// absorbing sphere or capsule per cubic meter of absorbed voxels.
+    pub absorption_heat_per_volume: f32,
+    /// The fraction of the kinetic energy dissipated in an impact that is
+    /// converted into heat in the colliding voxel objects.
+    pub impact_heating_fraction: f32,
+    /// Impacts dissipating less energy (in J) than this do not heat the
+    /// colliding voxel objects. This prevents resting contacts from slowly
+    /// heating objects.
+    pub min_impact_heat: f32,
+    /// The distance (in voxels) from the contact point within which voxels
+    /// receive heat from an impact.
+    pub impact_heating_radius: f32,
+    /// The coefficient (in W/(m²·K)) for heat exchange between exposed voxel
+    /// faces and the surroundings.
+    pub surface_heat_transfer_coefficient: f32,
+    /// How much the temperature (in K) of a chunk must have changed before its
+    /// mesh is updated to reflect the new temperatures.
+    pub mesh_update_temperature_threshold: f32,
+}
+
+/// Heat waiting to be distributed among the voxels near a point in a voxel
+/// object.
+#[derive(Clone, Copy, Debug)]
+struct StagedHeatDeposit {
+    voxel_object_id: VoxelObjectID,
+    /// The point in the normalized model space of the voxel object.
+    normalized_point: Point3C,
+    /// The distance (in voxels) from the point within which voxels are heated.
+    distance: f32,
+    /// The thermal energy (in J).
+    energy: f32,
+}
+
+#[derive(Debug)]
+struct MeltingOutcome {
+    original_object_empty: bool,
+    disconnected_entity_ids: Vec<EntityID>,
+}
+
+impl VoxelObjectThermalManager {
+    /// Creates a new thermal manager with the given configuration.
+    pub fn new(config: VoxelThermalConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            staged_heat_deposits: Vec::new(),
+            config,
+        })
+    }
+
+    /// Returns the thermal configuration.
+    pub fn config(&self) -> &VoxelThermalConfig {
+        &self.config
+    }
+
+    /// Stages the given amount of thermal energy (in J) to be distributed
+    /// among the voxels within the given distance of the given point in the
+    /// voxel object. The point and distance are specified in the normalized
+    /// model space of the voxel object, where distances are in voxels.
+    ///
+    /// Call [`Self::update_thermal_states`] to apply the staged heat.
+    pub fn stage_heat_deposit(
+        &mut self,
+        voxel_object_id: VoxelObjectID,
+        normalized_point: &Point3,
+        distance: f32,
+        energy: f32,
+    ) {
+        if energy <= 0.0 {
+            return;
+        }
+        self.staged_heat_deposits.push(StagedHeatDeposit {
+            voxel_object_id,
+            normalized_point: normalized_point.compact(),
+            distance,
+            energy,
+        });
+    }
+
+    /// Stages the heat released by absorbing the given volume (in m³) of
+    /// voxels from the given voxel object. The heat goes into the voxels just
+    /// outside the given sphere bounding the absorbing region in the
+    /// normalized model space of the voxel object.
+    pub fn stage_absorption_heat_deposit(
+        &mut self,
+        voxel_object_id: VoxelObjectID,
+        absorbing_region_in_norm_voxel_object_space: &Sphere,
+        absorbed_volume: f32,
+    ) {
+        // Include the layer of voxels surrounding the absorbed region
+        const RIM_THICKNESS: f32 = 1.5;
+
+        self.stage_heat_deposit(
+            voxel_object_id,
+            absorbing_region_in_norm_voxel_object_space.center(),
+            absorbing_region_in_norm_voxel_object_space.radius() + RIM_THICKNESS,
+            absorbed_volume * self.config.absorption_heat_per_volume,
+        );
+    }
+
+    /// Identifies collisions involving voxel objects and stages heat deposits
+    /// at the contact points based on the kinetic energy dissipated in the
+    /// collisions.
+    ///
+    /// For a normal impulse `J` with restitution coefficient `e` and approach
+    /// speed `v`, the dissipated kinetic energy is `J * v * (1 - e) / 2`. The
+    /// configured fraction of this is converted to heat and divided equally
+    /// between the colliding voxel objects.
+    pub fn stage_impact_heat_deposits(
+        &mut self,
+        voxel_object_manager: &VoxelObjectManager,
+        voxel_type_registry: &VoxelTypeRegistry,
+        rigid_body_manager: &RigidBodyManager,
+        collision_world: &CollisionWorld,
+    ) {
+        if self.config.impact_heating_fraction == 0.0
+            || !has_heatable_voxel_types(voxel_type_registry)
+        {
+            return;
+        }
+
+        let Some(collisions) = collision_world.cached_collisions() else {
+            return;
+        };
+
+        let arena = ArenaPool::get_arena();
+
+        // We will cache rigid body state as `ConstrainedBody`s for efficient
+        // impulse computation
+        let mut body_manager = ConstrainedBodyManager::new_in(&arena);
+
+        let is_voxel_object = |id: CollidableID| {
+            voxel_object_manager.has_voxel_object(VoxelObjectID::from_entity_id(id.as_entity_id()))
+        };
+
+        for collision in collisions {
+            let a_is_voxel_object = is_voxel_object(collision.collidable_a_id);
+            let b_is_voxel_object = is_voxel_object(collision.collidable_b_id);
+
+            if !a_is_voxel_object && !b_is_voxel_object {
+                continue;
+            }
+
+            let Some(descriptor_a) =
+                collision_world.get_collidable_descriptor(collision.collidable_a_id)
+            else {
+                continue;
+            };
+            let Some(descriptor_b) =
+                collision_world.get_collidable_descriptor(collision.collidable_b_id)
+            else {
+                continue;
+            };
+
+            let Some((body_a_idx, body_b_idx)) = body_manager.add_body_pair(
+                rigid_body_manager,
+                descriptor_a.rigid_body_id(),
+                descriptor_b.rigid_body_id(),
+            ) else {
+                continue;
+            };
+            let body_a = body_manager.body(body_a_idx);
+            let body_b = body_manager.body(body_b_idx);
+
+            let heated_object_count =
+                usize::from(a_is_voxel_object) + usize::from(b_is_voxel_object);
+
+            for contact in collision.contact_manifold.contacts() {
+                let contact = &contact.contact;
+                let geometry = &contact.geometry;
+
+                let impulse = contact.compute_normal_impulse(body_a, body_b);
+                if impulse <= 0.0 {
+                    continue;
+                }
+
+                let disp_a = geometry.position - body_a.position.aligned();
+                let disp_b = geometry.position - body_b.position.aligned();
+
+                let velocity_a =
+                    body_a.velocity.aligned() + body_a.angular_velocity.aligned().cross(&disp_a);
+                let velocity_b =
+                    body_b.velocity.aligned() + body_b.angular_velocity.aligned().cross(&disp_b);
+
+                let approach_speed = -geometry.surface_normal.dot(&(velocity_a - velocity_b));
+
+                let dissipated_energy = 0.5
+                    * impulse
+                    * approach_speed
+                    * (1.0 - contact.response_params.restitution_coef);
+
+                let heat = self.config.impact_heating_fraction * dissipated_energy;
+
+                if heat < self.config.min_impact_heat {
+                    continue;
+                }
+
+                let heat_per_object = heat / heated_object_count as f32;
+
+                for (is_voxel_object, collidable_id, descriptor) in [
+                    (a_is_voxel_object, collision.collidable_a_id, descriptor_a),
+                    (b_is_voxel_object, collision.collidable_b_id, descriptor_b),
+                ] {
+                    if !is_voxel_object {
+                        continue;
+                    }
+                    let Some(collidable) =
+                        collision_world.get_collidable_with_descriptor(descriptor)
+                    else {
+                        continue;
+                    };
+                    let Collidable::VoxelObject(collidable) = collidable.collidable() else {
+                        continue;
+                    };
+                    let voxel_object_id =
+                        VoxelObjectID::from_entity_id(collidable_id.as_entity_id());
+                    let Some(voxel_object) = voxel_object_manager.get_voxel_object(voxel_object_id)
+                    else {
+                        continue;
+                    };
+
+                    let contact_point = collidable
+                        .transform_to_object_space()
+                        .aligned()
+                        .transform_point(&geometry.position);
+
+                    let normalized_contact_point = Point3::from(
+                        *contact_point.as_vector() * voxel_object.object().inverse_voxel_extent(),
+                    );
+
+                    self.stage_heat_deposit(
+                        voxel_object_id,
+                        &normalized_contact_point,
+                        self.config.impact_heating_radius,
+                        heat_per_object,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Applies the staged heat deposits and advances the thermal state of all
+    /// voxel objects that are not at ambient temperature by the given
+    /// duration (in s).
+    ///
+    /// Voxels that reach the melting temperature of their type are removed,
+    /// and any resulting disconnected regions are extracted into separate
+    /// objects. Only objects with a physics context can melt. Melted objects
+    /// are staged for integrity checks if the integrity manager is configured
+    /// to check objects after edits.
+    pub fn update_thermal_states<C>(
+        &mut self,
+        context: &mut C,
+        entity_id_manager: &mut EntityIDManager,
+        voxel_type_registry: &VoxelTypeRegistry,
+        voxel_object_manager: &mut VoxelObjectManager,
+        voxel_object_buffer_pool: &mut VoxelObjectBufferPool,
+        integrity_manager: &mut VoxelObjectIntegrityManager,
+        rigid_body_manager: &mut RigidBodyManager,
+        anchor_manager: &mut AnchorManager,
+        time_step_duration: f32,
+    ) where
+        C: VoxelObjectInteractionContext,
+    {
+        if !has_heatable_voxel_types(voxel_type_registry) {
+            self.staged_heat_deposits.clear();
+            return;
+        }
+
+        let densities = voxel_type_registry.mass_densities();
+        let thermal_properties = voxel_type_registry.thermal_properties();
+
+        let arena = ArenaPool::get_arena();
+
+        for deposit in self.staged_heat_deposits.drain(..) {
+            let Some(voxel_object) =
+                voxel_object_manager.get_voxel_object_mut(deposit.voxel_object_id)
+            else {
+                continue;
+            };
+            voxel_object.object_mut().add_heat_near_point(
+                &arena,
+                &deposit.normalized_point.aligned(),
+                deposit.distance,
+                deposit.energy,
+                densities,
+                thermal_properties,
+            );
+        }
+
+        let mut melting_object_ids = AVec::new_in(&arena);
+
+        for (&voxel_object_id, voxel_object) in voxel_object_manager.voxel_objects_mut() {
+            let voxel_object = voxel_object.object_mut();
+
+            if !voxel_object.has_thermal_activity() {
+                continue;
+            }
+
+            voxel_object.diffuse_heat(
+                time_step_duration,
+                densities,
+                thermal_properties,
+                self.config.surface_heat_transfer_coefficient,
+            );
+
+            voxel_object.invalidate_meshes_for_changed_temperatures(
+                self.config.mesh_update_temperature_threshold,
+            );
+
+            if voxel_object.has_thermal_activity() {
+                melting_object_ids.push(voxel_object_id);
+            }
+        }
+
+        for voxel_object_id in melting_object_ids {
+            let Some(outcome) = melt_voxels_of_object(
+                context,
+                entity_id_manager,
+                voxel_type_registry,
+                voxel_object_manager,
+                voxel_object_buffer_pool,
+                rigid_body_manager,
+                anchor_manager,
+                voxel_object_id,
+            ) else {
+                continue;
+            };
+
+            if !outcome.original_object_empty {
+                integrity_manager.stage_integrity_check_after_edit(voxel_object_id);
+            }
+            for entity_id in outcome.disconnected_entity_ids {
+                integrity_manager
+                    .stage_integrity_check_after_edit(VoxelObjectID::from_entity_id(entity_id));
+            }
+        }
+    }
+
+    /// Removes all staged heat deposits and frees up all allocated memory.
+    pub fn reset_and_free(&mut self) {
+        self.staged_heat_deposits = Vec::new();
+    }
+}
+
+impl VoxelThermalConfig {
+    fn validate(&self) -> Result<()> {
+        if self.absorption_heat_per_volume < 0.0 {
+            bail!(
+                "Heat released per absorbed volume must be non-negative: {}",
+                self.absorption_heat_per_volume
+            );
+        }
+        if !(0.0..=1.0).contains(&self.impact_heating_fraction) {
+            bail!(
+                "Impact heating fraction must be between 0 and 1: {}",
+                self.impact_heating_fraction
+            );
+        }
+        if self.impact_heating_radius <= 0.0 {
+            bail!(
+                "Impact heating radius must be positive: {}",
+                self.impact_heating_radius
+            );
+        }
+        if self.surface_heat_transfer_coefficient < 0.0 {
+            bail!(
+                "Surface heat transfer coefficient must be non-negative: {}",
+                self.surface_heat_transfer_coefficient
+            );
+        }
+        if self.mesh_update_temperature_threshold <= 0.0 {
+            bail!(
+                "Temperature threshold for mesh updates must be positive: {}",
+                self.mesh_update_temperature_threshold
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Default for VoxelThermalConfig {
+    fn default() -> Self {
+        Self {
+            absorption_heat_per_volume: 5e8,
+            impact_heating_fraction: 0.5,
+            min_impact_heat: 10.0,
+            impact_heating_radius: 1.5,
+            surface_heat_transfer_coefficient: 25.0,
+            mesh_update_temperature_threshold: 10.0,
+        }
+    }
+}
+
+/// Whether any registered voxel type has a finite heat capacity.
+fn has_heatable_voxel_types(voxel_type_registry: &VoxelTypeRegistry) -> bool {
+    voxel_type_registry
+        .thermal_properties()
+        .iter()
+        .any(|properties| properties.specific_heat_capacity.is_finite())
+}
+
+/// Removes the voxels of the given voxel object that have reached their
+/// melting temperature.
+///
+/// # Returns
+/// [`None`] if no voxels were melted.
+fn melt_voxels_of_object<C>(
+    context: &mut C,
+    entity_id_manager: &mut EntityIDManager,
+    voxel_type_registry: &VoxelTypeRegistry,
+    voxel_object_manager: &mut VoxelObjectManager,
+    voxel_object_buffer_pool: &mut VoxelObjectBufferPool,
+    rigid_body_manager: &mut RigidBodyManager,
+    anchor_manager: &mut AnchorManager,
+    voxel_object_id: VoxelObjectID,
+) -> Option<MeltingOutcome>
+where
+    C: VoxelObjectInteractionContext,
+{
+    let entity_id = voxel_object_id.as_entity_id();
+
+    let (voxel_object, physics_context) =
+        voxel_object_manager.get_voxel_object_with_physics_context_mut(voxel_object_id)?;
+
+    let voxel_object = voxel_object.object_mut();
+
+    let arena = ArenaPool::get_arena();
+
+    let melting_voxel_indices =
+        voxel_object.find_melting_voxels(&arena, voxel_type_registry.thermal_properties());
+
+    if melting_voxel_indices.is_empty() {
+        return None;
+    }
+
+    let rigid_body_id = DynamicRigidBodyID::from_entity_id(entity_id);
+    let Some(rigid_body) = rigid_body_manager.get_dynamic_rigid_body_mut(rigid_body_id) else {
+        log::warn!("Voxel object physics context points to missing dynamic rigid body");
+        return None;
+    };
+
+    log::debug!(
+        "Melting {} voxels in voxel object {voxel_object_id}",
+        melting_voxel_indices.len()
+    );
+
+    let original_local_center_of_mass = physics_context
+        .inertial_property_manager
+        .derive_center_of_mass();
+
+    let mut inertial_property_updater = physics_context.inertial_property_manager.begin_update(
+        voxel_object.voxel_extent(),
+        voxel_type_registry.mass_densities(),
+    );
+
+    voxel_object.break_voxels(&melting_voxel_indices, &mut |voxel_indices, voxel_type| {
+        inertial_property_updater.remove_voxel(&voxel_indices, voxel_type);
+    });
+
+    voxel_object.resolve_connected_regions_between_all_chunks();
+
+    let VoxelRemovalOutcome {
+        original_object_empty,
+        extracted_components_for_disconnected_objects,
+        lost_anchors,
+    } = interaction::handle_voxel_object_after_removing_voxels(
+        &arena,
+        anchor_manager,
+        voxel_type_registry,
+        voxel_object_buffer_pool,
+        voxel_object,
+        &mut physics_context.inertial_property_manager,
+        rigid_body_id,
+        rigid_body,
+        original_local_center_of_mass,
+        RemovedMassFate::Destroyed,
+    );
+
+    // All lost anchors not inherited by a disconnected object should be
+    // deleted
+    for (anchor_id, _) in lost_anchors {
+        anchor_manager.dynamic_mut().remove(anchor_id);
+    }
+
+    if original_object_empty {
+        context.remove_voxel_object_entity(entity_id);
+    }
+
+    let disconnected_entity_ids =
+        entity_id_manager.provide_id_vec(extracted_components_for_disconnected_objects.len());
+
+    for (disconnected_entity_id, extracted_components) in disconnected_entity_ids
+        .iter()
+        .copied()
+        .zip(extracted_components_for_disconnected_objects)
+    {
+        interaction::spawn_extracted_voxel_object(
+            voxel_object_manager,
+            rigid_body_manager,
+            anchor_manager,
+            extracted_components,
+            disconnected_entity_id,
+        );
+    }
+
+    context.create_extracted_voxel_object_entities(disconnected_entity_ids.clone(), entity_id);
+
+    Some(MeltingOutcome {
+        original_object_empty,
+        disconnected_entity_ids,
+    })
+}
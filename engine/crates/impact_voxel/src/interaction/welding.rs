@@ -0,0 +1,810 @@
+//! Welding of voxel objects into a single object.
+
+use crate::{
+    Voxel, VoxelObjectBufferPool, VoxelObjectID, VoxelObjectManager, VoxelSignedDistance,
+    collidable::CollisionWorld,
+    interaction::{self, VoxelObjectInteractionContext, fracturing::VoxelObjectFracturingManager},
+    object::{CHUNK_SIZE, VoxelObject, inertia::VoxelObjectInertialPropertyManager},
+    voxel_types::{VoxelType, VoxelTypeRegistry},
+};
+use bytemuck::{Pod, Zeroable};
+use impact_containers::HashMap;
+use impact_geometry::AxisAlignedBox;
+use impact_id::EntityID;
+use impact_math::{point::Point3, transform::Isometry3, vector::Vector3};
+use impact_physics::{
+    anchor::{AnchorManager, DynamicRigidBodyAnchor},
+    collision::CollidableID,
+    rigid_body::{DynamicRigidBodyID, RigidBodyManager},
+};
+use roc_integration::roc;
+use std::mem;
+
+pub trait VoxelObjectWeldingContext {
+    /// Returns the welding properties of the given entity if it is weldable.
+    fn get_weldable_properties_for_entity(&self, entity_id: EntityID) -> Option<Weldable>;
+}
+
+define_component_type! {
+    /// Allows a voxel object to be welded together with other weldable voxel
+    /// objects that it rests in contact with. When two weldable objects have
+    /// been touching with a low relative velocity for long enough, the smaller
+    /// object is merged into the larger one.
+    #[roc(parents = "Comp")]
+    #[repr(C)]
+    #[derive(Copy, Clone, Debug, Zeroable, Pod)]
+    pub struct Weldable {
+        /// The maximum speed of the contact points relative to each other for
+        /// the objects to be considered at rest. The smaller value of the two
+        /// objects is used.
+        pub max_relative_speed: f32,
+        /// The duration the objects must stay in resting contact before they are
+        /// welded. The larger value of the two objects is used.
+        pub rest_duration: f32,
+    }
+}
+
+#[cfg(feature = "ecs")]
+impact_ecs::declare_component_flags! {
+    Weldable => impact_ecs::component::ComponentFlags::INHERITABLE,
+}
+
+/// Manages automatic welding of voxel objects in resting contact.
+#[derive(Debug)]
+pub struct VoxelObjectWeldingManager {
+    resting_contact_durations: HashMap<[VoxelObjectID; 2], f32>,
+    staged_welds: Vec<StagedWeld>,
+}
+
+/// The result of merging the voxels of one voxel object into another.
+#[derive(Clone, Debug)]
+pub struct VoxelWeldingOutcome {
+    /// The displacement of the origin of the target object's voxel grid due to
+    /// the grid being grown, in the model space of the object prior to the
+    /// merge. Points in the old model space are converted to the new one by
+    /// subtracting this displacement.
+    pub origin_displacement: Vector3,
+    /// The number of previously empty voxels in the target object that were
+    /// filled.
+    pub filled_voxel_count: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct StagedWeld {
+    target_entity_id: EntityID,
+    source_entity_id: EntityID,
+}
+
+#[roc]
+impl Weldable {
+    /// Creates a new [`Weldable`] component with the given maximum relative
+    /// contact speed and required resting duration.
+    #[roc(body = r#"
+    # These can be uncommented once https://github.com/roc-lang/roc/issues/5680 is fixed
+    # expect max_relative_speed >= 0.0
+    # expect rest_duration >= 0.0
+    {
+        max_relative_speed,
+        rest_duration,
+    }"#)]
+    pub fn new(max_relative_speed: f32, rest_duration: f32) -> Self {
+        assert!(max_relative_speed >= 0.0);
+        assert!(rest_duration >= 0.0);
+        Self {
+            max_relative_speed,
+            rest_duration,
+        }
+    }
+}
+
+impl VoxelObjectWeldingManager {
+    pub fn new() -> Self {
+        Self {
+            resting_contact_durations: HashMap::default(),
+            staged_welds: Vec::new(),
+        }
+    }
+
+    /// Whether the voxel object with the given ID is part of a staged weld.
+    pub fn object_has_staged_weld(&self, voxel_object_id: VoxelObjectID) -> bool {
+        let entity_id = voxel_object_id.as_entity_id();
+        self.staged_welds
+            .iter()
+            .any(|weld| weld.target_entity_id == entity_id || weld.source_entity_id == entity_id)
+    }
+
+    /// Identifies collisions between weldable voxel objects whose contact
+    /// points move slowly relative to each other, and accumulates how long
+    /// each pair of objects has been in such resting contact. Pairs that have
+    /// rested for long enough are staged for welding, with the less massive
+    /// object being merged into the more massive one. Pairs that are no longer
+    /// in resting contact are forgotten. Objects with a staged fracturing
+    /// process are not welded.
+    pub fn handle_resting_contacts<C>(
+        &mut self,
+        context: &C,
+        voxel_object_manager: &VoxelObjectManager,
+        fracturing_manager: &VoxelObjectFracturingManager,
+        rigid_body_manager: &RigidBodyManager,
+        collision_world: &CollisionWorld,
+        time_step_duration: f32,
+    ) where
+        C: VoxelObjectWeldingContext,
+    {
+        let previous_durations = mem::take(&mut self.resting_contact_durations);
+
+        let Some(collisions) = collision_world.cached_collisions() else {
+            return;
+        };
+
+        let voxel_object_entity_id = |id: CollidableID| {
+            let entity_id = id.as_entity_id();
+            voxel_object_manager
+                .has_voxel_object(VoxelObjectID::from_entity_id(entity_id))
+                .then_some(entity_id)
+        };
+
+        for collision in collisions {
+            let Some(entity_a_id) = voxel_object_entity_id(collision.collidable_a_id) else {
+                continue;
+            };
+            let Some(entity_b_id) = voxel_object_entity_id(collision.collidable_b_id) else {
+                continue;
+            };
+            if entity_a_id == entity_b_id {
+                continue;
+            }
+
+            let Some(properties_a) = context.get_weldable_properties_for_entity(entity_a_id) else {
+                continue;
+            };
+            let Some(properties_b) = context.get_weldable_properties_for_entity(entity_b_id) else {
+                continue;
+            };
+
+            let Some(body_a) = rigid_body_manager
+                .get_dynamic_rigid_body(DynamicRigidBodyID::from_entity_id(entity_a_id))
+            else {
+                continue;
+            };
+            let Some(body_b) = rigid_body_manager
+                .get_dynamic_rigid_body(DynamicRigidBodyID::from_entity_id(entity_b_id))
+            else {
+                continue;
+            };
+
+            let max_relative_speed = properties_a
+                .max_relative_speed
+                .min(properties_b.max_relative_speed);
+
+            let is_resting = collision.contact_manifold.contacts().iter().all(|contact| {
+                let position = &contact.contact.geometry.position;
+                let relative_velocity = body_a
+                    .compute_velocity_of_attached_world_space_point(position)
+                    - body_b.compute_velocity_of_attached_world_space_point(position);
+                relative_velocity.norm_squared() <= max_relative_speed.powi(2)
+            });
+
+            if !is_resting {
+                continue;
+            }
+
+            let object_a_id = VoxelObjectID::from_entity_id(entity_a_id);
+            let object_b_id = VoxelObjectID::from_entity_id(entity_b_id);
+
+            let pair = if object_a_id.as_entity_id() <= object_b_id.as_entity_id() {
+                [object_a_id, object_b_id]
+            } else {
+                [object_b_id, object_a_id]
+            };
+
+            if fracturing_manager.object_has_staged_fracturing_process(object_a_id)
+                || fracturing_manager.object_has_staged_fracturing_process(object_b_id)
+            {
+                continue;
+            }
+
+            let duration =
+                previous_durations.get(&pair).copied().unwrap_or(0.0) + time_step_duration;
+
+            let rest_duration = properties_a.rest_duration.max(properties_b.rest_duration);
+
+            if duration < rest_duration
+                || self.object_has_staged_weld(object_a_id)
+                || self.object_has_staged_weld(object_b_id)
+            {
+                self.resting_contact_durations.insert(pair, duration);
+                continue;
+            }
+
+            let (target_entity_id, source_entity_id) = if body_a.mass() >= body_b.mass() {
+                (entity_a_id, entity_b_id)
+            } else {
+                (entity_b_id, entity_a_id)
+            };
+
+            log::debug!(
+                "Staging welding of voxel object {source_entity_id} into {target_entity_id} \
+                 after {duration:.2} s of resting contact"
+            );
+
+            self.staged_welds.push(StagedWeld {
+                target_entity_id,
+                source_entity_id,
+            });
+        }
+    }
+
+    /// Welds all staged pairs of voxel objects, removing the entities of the
+    /// objects that were merged into other objects.
+    pub fn execute_staged_welds<C>(
+        &mut self,
+        context: &mut C,
+        voxel_type_registry: &VoxelTypeRegistry,
+        voxel_object_manager: &mut VoxelObjectManager,
+        voxel_object_buffer_pool: &mut VoxelObjectBufferPool,
+        rigid_body_manager: &mut RigidBodyManager,
+        anchor_manager: &mut AnchorManager,
+    ) where
+        C: VoxelObjectInteractionContext,
+    {
+        for weld in self.staged_welds.drain(..) {
+            if !context.entity_exists(weld.target_entity_id)
+                || !context.entity_exists(weld.source_entity_id)
+            {
+                continue;
+            }
+
+            if weld_voxel_objects(
+                voxel_type_registry,
+                voxel_object_manager,
+                voxel_object_buffer_pool,
+                rigid_body_manager,
+                anchor_manager,
+                weld.target_entity_id,
+                weld.source_entity_id,
+            )
+            .is_some()
+            {
+                context.remove_voxel_object_entity(weld.source_entity_id);
+            }
+        }
+    }
+
+    /// Removes all welding state and frees up all allocated memory.
+    pub fn reset_and_free(&mut self) {
+        self.resting_contact_durations = HashMap::default();
+        self.staged_welds = Vec::new();
+    }
+}
+
+impl Default for VoxelObjectWeldingManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Merges the voxel object of the source entity into the voxel object of the
+/// target entity, which must both have a dynamic rigid body. The voxels of
+/// the source object are resampled into the voxel grid of the target object,
+/// which is grown if required, and the source object is removed from the
+/// voxel object manager.
+///
+/// The rigid body of the target entity gets the inertial properties of the
+/// merged object, while the total linear and angular momentum of the two
+/// bodies is conserved. Anchors on the source body are moved to the target
+/// body. The caller is responsible for removing the source entity.
+///
+/// Returns [`None`] if either entity has no voxel object, physics context or
+/// dynamic rigid body, or if the entities are the same.
+pub fn weld_voxel_objects(
+    voxel_type_registry: &VoxelTypeRegistry,
+    voxel_object_manager: &mut VoxelObjectManager,
+    voxel_object_buffer_pool: &mut VoxelObjectBufferPool,
+    rigid_body_manager: &mut RigidBodyManager,
+    anchor_manager: &mut AnchorManager,
+    target_entity_id: EntityID,
+    source_entity_id: EntityID,
+) -> Option<VoxelWeldingOutcome> {
+    if target_entity_id == source_entity_id {
+        return None;
+    }
+
+    let target_object_id = VoxelObjectID::from_entity_id(target_entity_id);
+    let source_object_id = VoxelObjectID::from_entity_id(source_entity_id);
+    let target_rigid_body_id = DynamicRigidBodyID::from_entity_id(target_entity_id);
+    let source_rigid_body_id = DynamicRigidBodyID::from_entity_id(source_entity_id);
+
+    let [
+        (target_object, target_physics_context),
+        (source_object, source_physics_context),
+    ] = voxel_object_manager
+        .get_voxel_object_pair_with_physics_contexts_mut(target_object_id, source_object_id)?;
+
+    let [target_rigid_body, source_rigid_body] = rigid_body_manager
+        .get_two_dynamic_rigid_bodies_mut(target_rigid_body_id, source_rigid_body_id)?;
+
+    let target_inertial_property_manager = &mut target_physics_context.inertial_property_manager;
+
+    let original_target_local_center_of_mass =
+        target_inertial_property_manager.derive_center_of_mass();
+    let source_local_center_of_mass = source_physics_context
+        .inertial_property_manager
+        .derive_center_of_mass();
+
+    let target_to_world_transform = target_rigid_body
+        .reference_frame()
+        .create_transform_to_parent_space()
+        .applied_to_translation(&(-original_target_local_center_of_mass));
+
+    let source_to_world_transform = source_rigid_body
+        .reference_frame()
+        .create_transform_to_parent_space()
+        .applied_to_translation(&(-source_local_center_of_mass));
+
+    let source_to_target_transform =
+        target_to_world_transform.inverted() * source_to_world_transform;
+
+    let outcome = merge_voxel_object_into(
+        target_object.object_mut(),
+        target_inertial_property_manager,
+        voxel_type_registry.mass_densities(),
+        source_object.object(),
+        &source_to_target_transform,
+    );
+
+    target_object
+        .object_mut()
+        .resolve_connected_regions_between_all_chunks();
+
+    // The original center of mass in the model space of the possibly grown
+    // object
+    let original_target_local_center_of_mass =
+        original_target_local_center_of_mass - outcome.origin_displacement;
+
+    let original_target_position = target_rigid_body.position().aligned();
+    let source_position = source_rigid_body.position().aligned();
+    let orientation = target_rigid_body.orientation().aligned();
+
+    let target_momentum = target_rigid_body.momentum().aligned();
+    let source_momentum = source_rigid_body.momentum().aligned();
+
+    let new_inertial_properties = target_inertial_property_manager.derive_inertial_properties();
+    let new_local_center_of_mass = *new_inertial_properties.center_of_mass().as_vector();
+
+    let new_position = original_target_position
+        + orientation
+            .rotate_vector(&(new_local_center_of_mass - original_target_local_center_of_mass));
+
+    // The total angular momentum about the new center of mass is the sum of
+    // the spin angular momenta of the two bodies and the orbital angular
+    // momenta of their centers of mass
+    let new_angular_momentum = target_rigid_body.angular_momentum().aligned()
+        + source_rigid_body.angular_momentum().aligned()
+        + (original_target_position - new_position).cross(&target_momentum)
+        + (source_position - new_position).cross(&source_momentum);
+
+    target_rigid_body.set_inertial_properties(
+        new_inertial_properties.mass(),
+        new_inertial_properties.inertia_tensor().compact(),
+    );
+    target_rigid_body.set_position(new_position.compact());
+
+    target_rigid_body.apply_impulse_at_center_of_mass(&source_momentum);
+    target_rigid_body.apply_angular_impulse(
+        &(new_angular_momentum - target_rigid_body.angular_momentum().aligned()),
+    );
+
+    interaction::update_anchors_for_voxel_object_after_adding_voxels(
+        anchor_manager,
+        target_rigid_body_id,
+        &original_target_local_center_of_mass,
+        &new_local_center_of_mass,
+    );
+
+    transfer_anchors_to_welded_voxel_object(
+        anchor_manager,
+        source_rigid_body_id,
+        target_rigid_body_id,
+        &source_local_center_of_mass,
+        &source_to_target_transform,
+        &outcome.origin_displacement,
+        &new_local_center_of_mass,
+    );
+
+    // The source object now lives on in the target object, so we can remove it
+    // right away to avoid collisions between the two before the source entity
+    // is removed
+    if let Some(source_object) = voxel_object_manager.remove_voxel_object(source_object_id) {
+        voxel_object_buffer_pool.add_buffers(source_object.into_buffers());
+    }
+
+    Some(outcome)
+}
+
+/// Merges the voxels of the source object into the target object by sampling
+/// the signed distance field of the source object at the voxel centers of the
+/// target object and taking the union with the existing field. The given
+/// transform maps from the model space of the source object to the model
+/// space of the target object. The chunk grid of the target object is grown
+/// if required, and the given inertial property manager is updated
+/// accordingly.
+///
+/// Each filled voxel gets the type of the closest non-empty source voxel
+/// contributing to its sampled signed distance.
+///
+/// Even though modifying the object will invalidate the connected region
+/// information, this function does not call
+/// [`VoxelObject::resolve_connected_regions_between_all_chunks`]. Make sure to
+/// call it once all modifications have been made.
+pub fn merge_voxel_object_into(
+    target_object: &mut VoxelObject,
+    target_inertial_property_manager: &mut VoxelObjectInertialPropertyManager,
+    voxel_type_densities: &[f32],
+    source_object: &VoxelObject,
+    source_to_target_transform: &Isometry3,
+) -> VoxelWeldingOutcome {
+    if source_object.contains_only_empty_voxels() {
+        return VoxelWeldingOutcome {
+            origin_displacement: Vector3::zeros(),
+            filled_voxel_count: 0,
+        };
+    }
+
+    let target_voxel_extent = target_object.voxel_extent();
+    let source_voxel_extent = source_object.voxel_extent();
+
+    // Converts signed distances in source voxels to target voxels
+    let distance_scale = source_voxel_extent * target_object.inverse_voxel_extent();
+
+    let source_corners_in_target_voxels = source_object
+        .compute_normalized_aabb()
+        .all_corners()
+        .map(|corner| {
+            let corner_in_target_space =
+                source_to_target_transform.transform_point(&(corner * source_voxel_extent));
+            corner_in_target_space * target_object.inverse_voxel_extent()
+        });
+
+    // Voxels farther outside the source object than the maximum signed
+    // distance can not be affected, and we add a voxel of padding to cover the
+    // interpolation footprint
+    let influence_aabb = AxisAlignedBox::aabb_for_point_array(&source_corners_in_target_voxels)
+        .expanded_about_center(VoxelSignedDistance::max_f32() * distance_scale + 1.0);
+
+    let padding = target_object.chunk_grid_padding_to_include_aab(&influence_aabb);
+    target_object.grow_chunk_grid(&padding);
+
+    // Voxel positions are shifted back into the model space of the target
+    // object prior to growing the grid
+    let voxel_shift = Vector3::from(padding.lower_voxel_shift().map(|shift| shift as f32));
+
+    let origin_displacement = -target_voxel_extent * voxel_shift;
+
+    // The inertial properties are defined relative to the origin of the grid
+    if !padding.is_zero() {
+        target_inertial_property_manager.offset_reference_point_by(&origin_displacement);
+    }
+
+    let included_voxel_ranges =
+        target_object.voxel_ranges_in_grid_touching_aab(&influence_aabb.translated(&voxel_shift));
+
+    let target_to_source_transform = source_to_target_transform.inverted();
+
+    let mut inertial_property_updater =
+        target_inertial_property_manager.begin_update(target_voxel_extent, voxel_type_densities);
+
+    let mut filled_voxel_count = 0;
+
+    target_object.deposit_into_voxels_within_ranges(
+        included_voxel_ranges,
+        &mut |object_voxel_indices, voxel| {
+            let position = (Point3::new(
+                object_voxel_indices[0] as f32 + 0.5,
+                object_voxel_indices[1] as f32 + 0.5,
+                object_voxel_indices[2] as f32 + 0.5,
+            ) - voxel_shift)
+                * target_voxel_extent;
+
+            let source_grid_coords = target_to_source_transform.transform_point(&position)
+                * source_object.inverse_voxel_extent();
+
+            let (source_signed_distance, voxel_type) =
+                sample_signed_distance_and_voxel_type(source_object, &source_grid_coords);
+
+            let original_signed_distance = voxel.signed_distance();
+
+            let new_signed_distance = source_signed_distance * distance_scale;
+
+            if new_signed_distance >= original_signed_distance.to_f32() {
+                return false;
+            }
+
+            voxel.set_signed_distance_filling_with(new_signed_distance, voxel_type, &mut |_| {
+                inertial_property_updater.add_voxel(&object_voxel_indices, voxel_type);
+                filled_voxel_count += 1;
+            });
+
+            voxel.signed_distance() != original_signed_distance
+        },
+    );
+
+    VoxelWeldingOutcome {
+        origin_displacement,
+        filled_voxel_count,
+    }
+}
+
+/// Trilinearly interpolates the signed distance (in voxels) of the given
+/// object at the given grid coordinates, and returns it together with the
+/// type of the non-empty voxel with the smallest signed distance among the
+/// eight voxels surrounding the coordinates. Voxels outside the grid are
+/// treated as maximally outside.
+fn sample_signed_distance_and_voxel_type(
+    object: &VoxelObject,
+    grid_coords: &Point3,
+) -> (f32, VoxelType) {
+    let grid_shape = object.chunk_counts().map(|count| count * CHUNK_SIZE);
+
+    // Voxel values are defined at the voxel centers
+    let lower_coords = [
+        grid_coords.x() - 0.5,
+        grid_coords.y() - 0.5,
+        grid_coords.z() - 0.5,
+    ];
+    let lower_indices = lower_coords.map(|coord| coord.floor() as i64);
+    let fractions: [f32; 3] =
+        std::array::from_fn(|dim| lower_coords[dim] - lower_indices[dim] as f32);
+
+    let mut signed_distance = 0.0;
+    let mut closest_voxel_type = None;
+    let mut closest_signed_distance = f32::INFINITY;
+
+    for corner in 0..8 {
+        let offsets = [(corner >> 2) & 1, (corner >> 1) & 1, corner & 1];
+
+        let mut weight = 1.0;
+        for dim in 0..3 {
+            weight *= if offsets[dim] == 1 {
+                fractions[dim]
+            } else {
+                1.0 - fractions[dim]
+            };
+        }
+
+        let indices: [i64; 3] = std::array::from_fn(|dim| lower_indices[dim] + offsets[dim]);
+
+        let voxel = if indices
+            .iter()
+            .zip(grid_shape)
+            .all(|(&idx, size)| idx >= 0 && (idx as usize) < size)
+        {
+            object.voxel(
+                indices[0] as usize,
+                indices[1] as usize,
+                indices[2] as usize,
+            )
+        } else {
+            Voxel::maximally_outside()
+        };
+
+        let voxel_signed_distance = voxel.signed_distance().to_f32();
+
+        signed_distance += weight * voxel_signed_distance;
+
+        if !voxel.is_empty() && voxel_signed_distance < closest_signed_distance {
+            closest_signed_distance = voxel_signed_distance;
+            closest_voxel_type = Some(voxel.voxel_type());
+        }
+    }
+
+    (signed_distance, closest_voxel_type.unwrap_or_default())
+}
+
+fn transfer_anchors_to_welded_voxel_object(
+    anchor_manager: &mut AnchorManager,
+    source_rigid_body_id: DynamicRigidBodyID,
+    target_rigid_body_id: DynamicRigidBodyID,
+    source_local_center_of_mass: &Vector3,
+    source_to_target_transform: &Isometry3,
+    target_origin_displacement: &Vector3,
+    new_target_local_center_of_mass: &Vector3,
+) {
+    let source_anchors: Vec<_> = anchor_manager
+        .dynamic()
+        .anchors_for_body(source_rigid_body_id)
+        .map(|(anchor_id, point)| (anchor_id, point.aligned()))
+        .collect();
+
+    for (anchor_id, anchor_point) in source_anchors {
+        // The anchor point is relative to the center of mass of the source
+        // object, so we make it relative to the origin of the source grid,
+        // transform it to the model space of the target object prior to
+        // growing the grid and make it relative to the new center of mass
+        let local_anchor = anchor_point + source_local_center_of_mass;
+        let anchor_in_target_space = source_to_target_transform.transform_point(&local_anchor)
+            - target_origin_displacement
+            - new_target_local_center_of_mass;
+
+        anchor_manager.dynamic_mut().replace(
+            anchor_id,
+            DynamicRigidBodyAnchor {
+                rigid_body_id: target_rigid_body_id,
+                point: anchor_in_target_space.compact(),
+            },
+        );
+    }
+}
+
+#[cfg(not(miri))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        generation::{
+            SDFVoxelGenerator,
+            sdf::{SDFGraph, SDFNode},
+            voxel_type::SameVoxelTypeGenerator,
+        },
+        object::VoxelObjectBuffers,
+    };
+    use impact_alloc::Global;
+
+    fn generate_sphere_object(radius: f32, voxel_type: VoxelType) -> VoxelObject {
+        let mut graph = SDFGraph::new_in(Global);
+        graph.add_node(SDFNode::new_sphere(radius));
+        let sdf_generator = graph.build_in(Global).unwrap();
+
+        let generator = SDFVoxelGenerator::new(
+            0.5,
+            sdf_generator,
+            SameVoxelTypeGenerator::new(voxel_type).into(),
+        );
+        VoxelObject::generate(VoxelObjectBuffers::new(), &generator)
+    }
+
+    fn count_non_empty_voxels(object: &VoxelObject) -> usize {
+        let [nx, ny, nz] = object.chunk_counts().map(|count| count * CHUNK_SIZE);
+        let mut count = 0;
+        for i in 0..nx {
+            for j in 0..ny {
+                for k in 0..nz {
+                    if object.get_voxel_if_occupied(i, j, k).is_some() {
+                        count += 1;
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    fn validate_merged_object(
+        object: &mut VoxelObject,
+        inertial_property_manager: &VoxelObjectInertialPropertyManager,
+        voxel_type_densities: &[f32],
+    ) {
+        object.resolve_connected_regions_between_all_chunks();
+        object.validate_adjacencies();
+        object.validate_chunk_obscuredness();
+        object.validate_occupied_voxel_ranges();
+        object.validate_sdf();
+        object.validate_region_count();
+        inertial_property_manager.validate_for_object(object, voxel_type_densities, 1e-3);
+    }
+
+    #[test]
+    fn merging_overlapping_spheres_gives_single_region() {
+        let voxel_type_densities = vec![1.0; 256];
+
+        let mut target = generate_sphere_object(8.0, VoxelType::from_idx(0));
+        let source = generate_sphere_object(8.0, VoxelType::from_idx(1));
+
+        let mut inertial_property_manager =
+            VoxelObjectInertialPropertyManager::initialized_from(&target, &voxel_type_densities);
+
+        let original_count = count_non_empty_voxels(&target);
+
+        // Place the source sphere so that it overlaps the upper x-side of the
+        // target sphere and extends beyond its grid
+        let offset = Vector3::new(6.0, 0.0, 0.0);
+        let outcome = merge_voxel_object_into(
+            &mut target,
+            &mut inertial_property_manager,
+            &voxel_type_densities,
+            &source,
+            &Isometry3::from_translation(offset),
+        );
+
+        assert!(outcome.filled_voxel_count > 0);
+        assert_eq!(
+            count_non_empty_voxels(&target),
+            original_count + outcome.filled_voxel_count
+        );
+
+        validate_merged_object(
+            &mut target,
+            &inertial_property_manager,
+            &voxel_type_densities,
+        );
+        assert_eq!(target.count_regions(), 1);
+    }
+
+    #[test]
+    fn merging_separated_object_with_negative_offset_grows_grid_downward() {
+        let voxel_type_densities = vec![1.0; 256];
+
+        let mut target = generate_sphere_object(4.0, VoxelType::from_idx(0));
+        let source = generate_sphere_object(4.0, VoxelType::from_idx(1));
+
+        let mut inertial_property_manager =
+            VoxelObjectInertialPropertyManager::initialized_from(&target, &voxel_type_densities);
+
+        let original_count = count_non_empty_voxels(&target);
+        let source_count = count_non_empty_voxels(&source);
+
+        let offset = Vector3::new(0.0, -20.0, 0.0);
+        let outcome = merge_voxel_object_into(
+            &mut target,
+            &mut inertial_property_manager,
+            &voxel_type_densities,
+            &source,
+            &Isometry3::from_translation(offset),
+        );
+
+        assert!(outcome.origin_displacement.y() < 0.0);
+
+        // Translating by a whole number of voxels samples the source voxels at
+        // their centers, so the filled voxels should closely match the source
+        assert!(outcome.filled_voxel_count.abs_diff(source_count) <= source_count / 50);
+        assert_eq!(
+            count_non_empty_voxels(&target),
+            original_count + outcome.filled_voxel_count
+        );
+
+        validate_merged_object(
+            &mut target,
+            &inertial_property_manager,
+            &voxel_type_densities,
+        );
+        assert_eq!(target.count_regions(), 2);
+    }
+
+    #[test]
+    fn merged_voxels_get_source_voxel_type() {
+        let voxel_type_densities = vec![1.0; 256];
+
+        let mut target = generate_sphere_object(4.0, VoxelType::from_idx(0));
+        let source = generate_sphere_object(4.0, VoxelType::from_idx(1));
+
+        let mut inertial_property_manager =
+            VoxelObjectInertialPropertyManager::initialized_from(&target, &voxel_type_densities);
+
+        merge_voxel_object_into(
+            &mut target,
+            &mut inertial_property_manager,
+            &voxel_type_densities,
+            &source,
+            &Isometry3::from_translation(Vector3::new(10.0, 0.0, 0.0)),
+        );
+
+        let [nx, ny, nz] = target.chunk_counts().map(|count| count * CHUNK_SIZE);
+        let mut voxel_types = Vec::new();
+        for i in 0..nx {
+            for j in 0..ny {
+                for k in 0..nz {
+                    if let Some(voxel) = target.get_voxel_if_occupied(i, j, k)
+                        && !voxel_types.contains(&voxel.voxel_type())
+                    {
+                        voxel_types.push(voxel.voxel_type());
+                    }
+                }
+            }
+        }
+        voxel_types.sort_by_key(VoxelType::idx);
+
+        assert_eq!(
+            voxel_types,
+            [VoxelType::from_idx(0), VoxelType::from_idx(1)]
+        );
+    }
+}
@@ -12,6 +12,7 @@ pub mod object;
 pub mod render_commands;
 pub mod setup;
 pub mod shader_templates;
+pub mod terrain;
 pub mod utils;
 pub mod voxel_types;
 
@@ -34,6 +35,7 @@ use voxel_types::VoxelType;
 use crate::{
     interaction::{VoxelInteractionConfig, VoxelInteractionManager},
     mesh::MeshedVoxelObjectBuffers,
+    terrain::{VoxelTerrainConfig, VoxelTerrainManager},
     voxel_types::VoxelTypeConfig,
 };
 
@@ -118,6 +120,7 @@ pub enum VoxelSurfacePlacement {
 pub struct VoxelManager {
     pub object_manager: VoxelObjectManager,
     pub interaction_manager: VoxelInteractionManager,
+    pub terrain_manager: VoxelTerrainManager,
     pub object_buffer_pool: VoxelObjectBufferPool,
 }
 
@@ -149,6 +152,7 @@ pub struct VoxelObjectPhysicsContext {
 pub struct VoxelConfig {
     pub types: VoxelTypeConfig,
     pub interaction: VoxelInteractionConfig,
+    pub terrain: VoxelTerrainConfig,
 }
 
 impl VoxelSignedDistance {
@@ -460,6 +464,24 @@ impl Voxel {
         }
     }
 
+    /// Sets the signed distance to the given value, and if the voxel was empty
+    /// and the signed distance becomes negative, marks the voxel as non-empty
+    /// with the given type and calls the given closure.
+    #[inline]
+    pub fn set_signed_distance_filling_with(
+        &mut self,
+        new_signed_distance: f32,
+        voxel_type: VoxelType,
+        on_filled: &mut impl FnMut(&Self),
+    ) {
+        self.signed_distance = VoxelSignedDistance::from_f32(new_signed_distance);
+        if self.is_empty() && self.signed_distance.is_negative() {
+            self.voxel_type = voxel_type;
+            self.update_flags(VoxelFlags::new());
+            on_filled(self);
+        }
+    }
+
     /// Increases the signed distance by the given amount, and marks the voxel
     /// as empty and calls the given closure if the signed distance becomes
     /// positive.
@@ -501,6 +523,7 @@ impl VoxelManager {
         Ok(Self {
             object_manager: VoxelObjectManager::new(),
             interaction_manager: VoxelInteractionManager::new(config.interaction)?,
+            terrain_manager: VoxelTerrainManager::new(config.terrain)?,
             object_buffer_pool: VoxelObjectBufferPool::new(),
         })
     }
@@ -529,6 +552,18 @@ impl VoxelManager {
         &mut self.interaction_manager
     }
 
+    /// Returns a reference to the [`VoxelTerrainManager`].
+    #[inline]
+    pub fn terrain_manager(&self) -> &VoxelTerrainManager {
+        &self.terrain_manager
+    }
+
+    /// Returns a mutable reference to the [`VoxelTerrainManager`].
+    #[inline]
+    pub fn terrain_manager_mut(&mut self) -> &mut VoxelTerrainManager {
+        &mut self.terrain_manager
+    }
+
     /// Returns a mutable reference to the [`VoxelObjectBufferPool`].
     #[inline]
     pub fn object_buffer_pool_mut(&mut self) -> &mut VoxelObjectBufferPool {
@@ -539,6 +574,7 @@ impl VoxelManager {
     #[inline]
     pub fn reset_and_free(&mut self) {
         self.interaction_manager.reset_and_free();
+        self.terrain_manager.reset_and_free();
         self.object_manager.reset_and_free();
         self.object_buffer_pool.reset_and_free();
     }
@@ -765,7 +801,8 @@ impl Default for VoxelObjectManager {
 }
 
 impl VoxelObjectBufferPool {
-    fn new() -> Self {
+    /// Creates a new empty buffer pool.
+    pub fn new() -> Self {
         Self {
             buffer_pool: Vec::new(),
         }
@@ -805,6 +842,12 @@ impl VoxelObjectBufferPool {
     }
 }
 
+impl Default for VoxelObjectBufferPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl VoxelConfig {
     /// Resolves all paths in the configuration by prepending the given root
     /// path to all paths.
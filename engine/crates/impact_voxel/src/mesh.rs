@@ -4,18 +4,24 @@ use crate::{
     collidable::VoxelObjectCollisionProbes,
     object::{
         VoxelChunkFlags, VoxelObject, VoxelObjectBuffers,
-        sdf::{VoxelChunkSignedDistanceField, surface_nets::SurfaceNetsBuffer},
+        sdf::{
+            VoxelChunkSignedDistanceField,
+            lod::{VOXEL_CHUNK_LOD_COUNT, VoxelChunkLODRegion},
+            surface_nets::SurfaceNetsBuffer,
+        },
     },
 };
 use bytemuck::{Pod, Zeroable};
-use impact_containers::{KeyIndexMapper, RangeAllocator};
+use impact_containers::{HashMap, KeyIndexMapper, RangeAllocator};
 use impact_geometry::{AxisAlignedBox, Frustum, OrientedBox, Plane};
 use impact_math::{
     point::{Point3, Point3C},
     transform::Similarity3,
     vector::{UnitVector3C, Vector3},
 };
-use std::{array, ops::Range};
+use impact_mesh::{TriangleMesh, VertexNormalVector, VertexPosition};
+use impact_scene::lod::LODSelectionConfig;
+use std::{array, mem, ops::Range};
 
 /// A [`VoxelObject`] with an associated [`VoxelObjectMesh`] and mesh-derived
 /// state.
@@ -46,15 +52,26 @@ pub struct VoxelObjectMeshBuffers {
 /// that the indices defining the triangles for a specific chunk are
 /// contiguous in the buffer. A list of [`ChunkSubmesh`] objects mapping each
 /// chunk to its segment of the index buffer is also stored.
+///
+/// Each chunk is meshed at every level of detail (see
+/// [`VoxelChunkLODRegion`]). The data for a chunk starts with the vertices
+/// and triangles of the full-detail surface, followed by a skirt hanging down
+/// from the open edges of the surface, and then the surface and skirt for
+/// each coarser level of detail in turn. The skirts hide the cracks that
+/// would otherwise appear between adjacent chunks rendered at different levels
+/// of detail.
 #[derive(Debug)]
 pub struct VoxelObjectMesh {
     positions: Vec<VoxelMeshVertexPosition>,
     normal_vectors: Vec<VoxelMeshVertexNormalVector>,
+    temperatures: Vec<VoxelMeshVertexTemperature>,
     index_materials: Vec<VoxelMeshIndexMaterials>,
     indices: Vec<VoxelMeshIndex>,
     sdf_buffer: VoxelChunkSignedDistanceField,
     surface_nets_buffer: SurfaceNetsBuffer,
+    chunk_mesh_buffer: ChunkMeshBuffer,
     chunk_submesh_manager: ChunkSubmeshManager,
+    padding_chunk_layers: usize,
 }
 
 /// A vertex position in a [`VoxelObjectMesh`].
@@ -67,6 +84,12 @@ pub struct VoxelMeshVertexPosition(pub [f32; 3]);
 #[derive(Copy, Clone, Debug, PartialEq, Zeroable, Pod)]
 pub struct VoxelMeshVertexNormalVector(pub [f32; 3]);
 
+/// The temperature (in K) of the voxels around a vertex in a
+/// [`VoxelObjectMesh`], used for making hot voxels glow.
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, PartialEq, Zeroable, Pod)]
+pub struct VoxelMeshVertexTemperature(pub f32);
+
 /// A set of four material indices and corresponding weights for a vertex index
 /// in a [`VoxelObjectMesh`]. The materials must be specificed per index rather
 /// than per vertex to ensure that the four materials to blend are the same for
@@ -94,12 +117,17 @@ pub struct VoxelMeshIndex(pub u32);
 pub struct ChunkSubmesh {
     chunk_indices: [u32; 3],
     index_offset: u32,
+    /// The number of indices for the full-detail surface, excluding its skirt.
     index_count: u32,
     /// Table of booleans (stored as `u32`s to make it directly representable in
     /// WGSL) indicating whether the chunk is obscured from a specific
     /// axis-aligned direction. Used for culling obscured chunks given a view
     /// direction.
     is_obscured_from_direction: [[[u32; 2]; 2]; 2],
+    /// The number of indices (including the skirt) for each level of detail.
+    /// The indices for each level follow directly after those for the previous
+    /// level, starting at the index offset.
+    lod_index_counts: [u32; VOXEL_CHUNK_LOD_COUNT],
 }
 
 /// Ranges for the vertex and index data of a chunk submesh in the mesh data
@@ -138,23 +166,87 @@ pub struct FrustumPlane {
     pub displacement: f32,
 }
 
+/// Parameters for selecting the level of detail to render each voxel chunk
+/// with, gathered in a representation suitable for passing to the GPU.
+///
+/// The level of detail for a chunk is determined from the distance `d`
+/// between the view apex and the closest point in the chunk (in normalized
+/// voxel object space, where chunks have unit extent) through `m = scale * d +
+/// offset`. If `m` does not exceed one, the chunk is rendered at full detail.
+/// Otherwise, each time `m` grows by the inverse of the screen size factor per
+/// level, the level of detail is increased by one.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default, Zeroable, Pod)]
+pub struct ChunkLODSelectionParameters {
+    pub distance_scale: f32,
+    pub distance_offset: f32,
+    pub inverse_log_level_factor: f32,
+}
+
 #[derive(Clone, Debug)]
 struct ChunkSubmeshManager {
     chunk_index_map: KeyIndexMapper<[usize; 3]>,
     chunk_submeshes: Vec<ChunkSubmesh>,
+    /// The range of vertices making up the full-detail surface for each chunk.
     chunk_vertex_ranges: Vec<Range<usize>>,
+    /// The full range of vertices allocated for each chunk, including all
+    /// levels of detail and skirts.
+    chunk_allocated_vertex_ranges: Vec<Range<usize>>,
     vertex_range_allocator: RangeAllocator,
     index_range_allocator: RangeAllocator,
     updated_data_ranges: Vec<ChunkSubmeshDataRanges>,
     chunks_were_removed: bool,
 }
 
+/// Staging buffers for the mesh of a single chunk at every level of detail,
+/// laid out the same way as the chunk's data in the [`VoxelObjectMesh`].
+#[derive(Debug)]
+struct ChunkMeshBuffer {
+    positions: Vec<VoxelMeshVertexPosition>,
+    normal_vectors: Vec<VoxelMeshVertexNormalVector>,
+    index_materials: Vec<VoxelMeshIndexMaterials>,
+    /// Vertex indices relative to the first vertex of the chunk.
+    indices: Vec<u32>,
+    layout: ChunkMeshLayout,
+    lod_region: VoxelChunkLODRegion,
+    lod_sdf: VoxelChunkSignedDistanceField,
+    /// Maps each undirected edge to the number of triangles it belongs to and
+    /// the position in `indices` of the start vertex of the edge in the first
+    /// of these triangles.
+    edge_counts: HashMap<[u32; 2], (u32, usize)>,
+    /// The positions in `indices` of the start vertex of each open edge.
+    open_edges: Vec<usize>,
+    skirt_vertices: HashMap<u32, u32>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct ChunkMeshLayout {
+    vertex_count: usize,
+    surface_vertex_count: usize,
+    surface_index_count: usize,
+    lod_index_counts: [usize; VOXEL_CHUNK_LOD_COUNT],
+}
+
 impl MeshedVoxelObject {
     /// Creates the [`VoxelObjectMesh`] and associated derived state for the
     /// given [`VoxelObject`] using the given memory buffers and returns it as a
     /// [`MeshedVoxelObject`].
     pub fn create(buffers: VoxelObjectMeshBuffers, voxel_object: VoxelObject) -> Self {
+        Self::create_with_padding_chunks(buffers, voxel_object, 0)
+    }
+
+    /// Like [`Self::create`], but treats the given number of chunk layers
+    /// along each face of the object's chunk grid as padding that is only used
+    /// for meshing the interior chunks and does not get a mesh of its own.
+    /// This allows adjacent objects whose padding overlaps each other's
+    /// interior to be meshed without seams.
+    pub fn create_with_padding_chunks(
+        buffers: VoxelObjectMeshBuffers,
+        voxel_object: VoxelObject,
+        padding_chunk_layers: usize,
+    ) -> Self {
         let mut mesh = buffers.mesh;
+        mesh.padding_chunk_layers = padding_chunk_layers;
         mesh.recreate(&voxel_object);
 
         let mut collision_probes = buffers.collision_probes;
@@ -254,6 +346,7 @@ impl VoxelObjectMeshBuffers {
     /// Clears all buffers.
     pub fn clear(&mut self) {
         self.mesh.clear();
+        self.mesh.padding_chunk_layers = 0;
         self.collision_probes.clear();
     }
 }
@@ -269,11 +362,14 @@ impl VoxelObjectMesh {
         Self {
             positions: Vec::new(),
             normal_vectors: Vec::new(),
+            temperatures: Vec::new(),
             index_materials: Vec::new(),
             indices: Vec::new(),
             sdf_buffer: VoxelChunkSignedDistanceField::new(),
             surface_nets_buffer: SurfaceNetsBuffer::new(),
+            chunk_mesh_buffer: ChunkMeshBuffer::new(),
             chunk_submesh_manager: ChunkSubmeshManager::new(),
+            padding_chunk_layers: 0,
         }
     }
 
@@ -295,6 +391,7 @@ impl VoxelObjectMesh {
         // give a significant performace gain
         self.positions.reserve(vertex_count_huristic);
         self.normal_vectors.reserve(vertex_count_huristic);
+        self.temperatures.reserve(vertex_count_huristic);
         self.index_materials.reserve(index_count_huristic);
         self.indices.reserve(index_count_huristic);
 
@@ -306,49 +403,54 @@ impl VoxelObjectMesh {
         self.chunk_submesh_manager
             .clear_with_capacity(chunk_count_heuristic);
 
+        let padding_chunk_layers = self.padding_chunk_layers;
+
         voxel_object.for_each_exposed_chunk_with_sdf(&mut self.sdf_buffer, &mut |chunk, sdf| {
             let chunk_indices = chunk.chunk_indices();
 
-            let vertex_position_offset =
-                Self::vertex_position_offset_for_chunk(voxel_object, chunk_indices);
+            if Self::is_padding_chunk(padding_chunk_layers, voxel_object, chunk_indices) {
+                return;
+            }
 
-            sdf.compute_surface_nets_mesh(
-                voxel_object.voxel_extent(),
-                &vertex_position_offset,
-                &mut self.surface_nets_buffer,
-            );
+            let chunk_mesh = &mut self.chunk_mesh_buffer;
 
-            if self.surface_nets_buffer.is_empty() {
+            if !chunk_mesh.compute_for_chunk(
+                voxel_object,
+                chunk_indices,
+                sdf,
+                &mut self.surface_nets_buffer,
+            ) {
                 return;
             }
 
             let vertex_offset = self.positions.len();
             let index_offset = self.indices.len();
-            let vertex_count = self.surface_nets_buffer.positions.len();
-            let index_count = self.surface_nets_buffer.indices.len();
 
             self.chunk_submesh_manager.push_chunk(
                 *chunk_indices,
                 vertex_offset,
-                vertex_count,
                 index_offset,
-                index_count,
+                &chunk_mesh.layout,
                 chunk.flags(),
             );
 
-            self.positions
-                .extend_from_slice(&self.surface_nets_buffer.positions);
+            self.positions.extend_from_slice(&chunk_mesh.positions);
             self.normal_vectors
-                .extend_from_slice(&self.surface_nets_buffer.normal_vectors);
+                .extend_from_slice(&chunk_mesh.normal_vectors);
+            Self::extend_vertex_temperatures(
+                &mut self.temperatures,
+                voxel_object,
+                &chunk_mesh.positions,
+            );
             self.index_materials
-                .extend_from_slice(&self.surface_nets_buffer.index_materials);
+                .extend_from_slice(&chunk_mesh.index_materials);
 
-            self.indices.reserve(index_count);
+            self.indices.reserve(chunk_mesh.indices.len());
             self.indices.extend(
-                self.surface_nets_buffer
+                chunk_mesh
                     .indices
                     .iter()
-                    .map(|&index| VoxelMeshIndex(vertex_offset as u32 + u32::from(index))),
+                    .map(|&index| VoxelMeshIndex(vertex_offset as u32 + index)),
             );
         });
     }
@@ -361,19 +463,20 @@ impl VoxelObjectMesh {
         let invalidated_mesh_chunk_indices = voxel_object.invalidated_mesh_chunk_indices();
 
         for chunk_indices in invalidated_mesh_chunk_indices {
+            if Self::is_padding_chunk(self.padding_chunk_layers, voxel_object, chunk_indices) {
+                continue;
+            }
             if let Some(chunk_flags) =
                 voxel_object.fill_sdf_for_chunk_if_exposed(&mut self.sdf_buffer, *chunk_indices)
             {
-                let vertex_position_offset =
-                    Self::vertex_position_offset_for_chunk(voxel_object, chunk_indices);
+                let chunk_mesh = &mut self.chunk_mesh_buffer;
 
-                self.sdf_buffer.compute_surface_nets_mesh(
-                    voxel_object.voxel_extent(),
-                    &vertex_position_offset,
+                if !chunk_mesh.compute_for_chunk(
+                    voxel_object,
+                    chunk_indices,
+                    &self.sdf_buffer,
                     &mut self.surface_nets_buffer,
-                );
-
-                if self.surface_nets_buffer.is_empty() {
+                ) {
                     self.chunk_submesh_manager
                         .remove_chunk_if_present(chunk_indices);
                     continue;
@@ -381,8 +484,8 @@ impl VoxelObjectMesh {
 
                 let total_vertex_count = self.positions.len();
                 let total_index_count = self.indices.len();
-                let vertex_count = self.surface_nets_buffer.positions.len();
-                let index_count = self.surface_nets_buffer.indices.len();
+                let vertex_count = chunk_mesh.positions.len();
+                let index_count = chunk_mesh.indices.len();
 
                 let ChunkSubmeshDataRanges {
                     vertex_range,
@@ -391,57 +494,58 @@ impl VoxelObjectMesh {
                     total_vertex_count,
                     total_index_count,
                     *chunk_indices,
-                    vertex_count,
-                    index_count,
+                    &chunk_mesh.layout,
                     chunk_flags,
                 );
 
                 if vertex_range.start == total_vertex_count {
                     // If no free range was found for the vertex data inside the buffers, we push
                     // the data to the end of the buffers
-                    self.positions
-                        .extend_from_slice(&self.surface_nets_buffer.positions);
+                    self.positions.extend_from_slice(&chunk_mesh.positions);
                     self.normal_vectors
-                        .extend_from_slice(&self.surface_nets_buffer.normal_vectors);
+                        .extend_from_slice(&chunk_mesh.normal_vectors);
+                    Self::extend_vertex_temperatures(
+                        &mut self.temperatures,
+                        voxel_object,
+                        &chunk_mesh.positions,
+                    );
                 } else {
                     assert!(vertex_range.end <= total_vertex_count);
                     assert_eq!(vertex_range.len(), vertex_count);
 
                     // If we got a free range inside the buffers, we can use it for the new data,
                     // overwriting any obsolete values
-                    self.positions[vertex_range.clone()]
-                        .copy_from_slice(&self.surface_nets_buffer.positions);
+                    self.positions[vertex_range.clone()].copy_from_slice(&chunk_mesh.positions);
                     self.normal_vectors[vertex_range.clone()]
-                        .copy_from_slice(&self.surface_nets_buffer.normal_vectors);
+                        .copy_from_slice(&chunk_mesh.normal_vectors);
+                    Self::write_vertex_temperatures(
+                        &mut self.temperatures[vertex_range.clone()],
+                        voxel_object,
+                        &chunk_mesh.positions,
+                    );
                 }
 
                 if index_range.start == total_index_count {
                     self.index_materials
-                        .extend_from_slice(&self.surface_nets_buffer.index_materials);
+                        .extend_from_slice(&chunk_mesh.index_materials);
 
                     self.indices.reserve(index_count);
                     self.indices
-                        .extend(self.surface_nets_buffer.indices.iter().map(
-                            |&surface_nets_index| {
-                                VoxelMeshIndex(
-                                    vertex_range.start as u32 + u32::from(surface_nets_index),
-                                )
-                            },
-                        ));
+                        .extend(chunk_mesh.indices.iter().map(|&chunk_index| {
+                            VoxelMeshIndex(vertex_range.start as u32 + chunk_index)
+                        }));
                 } else {
                     assert!(index_range.end <= total_index_count);
                     assert_eq!(index_range.len(), index_count);
 
                     self.index_materials[index_range.clone()]
-                        .copy_from_slice(&self.surface_nets_buffer.index_materials);
+                        .copy_from_slice(&chunk_mesh.index_materials);
 
-                    for (index, &surface_nets_index) in self.indices[index_range]
+                    for (index, &chunk_index) in self.indices[index_range]
                         .iter_mut()
-                        .zip(&self.surface_nets_buffer.indices)
+                        .zip(&chunk_mesh.indices)
                     {
-                        *index = VoxelMeshIndex(
-                            vertex_range.start as u32 + u32::from(surface_nets_index),
-                        );
+                        *index = VoxelMeshIndex(vertex_range.start as u32 + chunk_index);
                     }
                 }
             } else {
@@ -465,6 +569,11 @@ impl VoxelObjectMesh {
         &self.normal_vectors
     }
 
+    /// Returns a slice with the temperatures of all the vertices of the mesh.
+    pub fn temperatures(&self) -> &[VoxelMeshVertexTemperature] {
+        &self.temperatures
+    }
+
     /// Returns a slice with the materials for each vertex index in
     /// [`Self::indices`].
     pub fn index_materials(&self) -> &[VoxelMeshIndexMaterials] {
@@ -482,8 +591,8 @@ impl VoxelObjectMesh {
         self.chunk_submesh_manager.chunk_submeshes()
     }
 
-    /// Returns a slice with the vertex range for each chunk, in the same order
-    /// as [`Self::chunk_submeshes`].
+    /// Returns a slice with the range of vertices making up the full-detail
+    /// surface of each chunk, in the same order as [`Self::chunk_submeshes`].
     pub fn chunk_vertex_ranges(&self) -> &[Range<usize>] {
         self.chunk_submesh_manager.chunk_vertex_ranges()
     }
@@ -499,14 +608,94 @@ impl VoxelObjectMesh {
         self.positions().len()
     }
 
+    /// Creates a [`TriangleMesh`] representing the current surface of the voxel
+    /// object with the given voxel extent, for example for exporting it to a
+    /// file. Only the triangles of chunks that currently have a mesh are
+    /// included. The vertices on the boundary between two chunks are
+    /// duplicated in the meshes for both chunks, so vertices closer than a
+    /// small fraction of the voxel extent are merged to make the resulting mesh
+    /// connected across chunk boundaries. The normal vectors of merged vertices
+    /// are averaged.
+    pub fn create_triangle_mesh(&self, voxel_extent: f32) -> TriangleMesh {
+        const UNMAPPED: u32 = u32::MAX;
+
+        let inverse_merge_distance = 1e3 / voxel_extent;
+
+        let mut vertex_index_map = vec![UNMAPPED; self.positions.len()];
+        let mut welded_vertex_indices: HashMap<[i64; 3], u32> = HashMap::default();
+
+        let mut positions = Vec::with_capacity(self.positions.len());
+        let mut normal_vector_sums: Vec<[f32; 3]> = Vec::with_capacity(self.positions.len());
+        let mut indices = Vec::with_capacity(self.indices.len());
+
+        for submesh in self.chunk_submeshes() {
+            for triangle in self.indices[submesh.index_range()].chunks_exact(3) {
+                let triangle =
+                    [triangle[0], triangle[1], triangle[2]].map(|VoxelMeshIndex(index)| {
+                        let index = index as usize;
+
+                        if vertex_index_map[index] == UNMAPPED {
+                            let VoxelMeshVertexPosition(position) = self.positions[index];
+                            let VoxelMeshVertexNormalVector(normal_vector) =
+                                self.normal_vectors[index];
+
+                            let welded_index =
+                                *welded_vertex_indices
+                                    .entry(position.map(|coord| {
+                                        (coord * inverse_merge_distance).round() as i64
+                                    }))
+                                    .or_insert_with(|| {
+                                        positions.push(VertexPosition(position.into()));
+                                        normal_vector_sums.push([0.0; 3]);
+                                        (positions.len() - 1) as u32
+                                    });
+
+                            let normal_vector_sum = &mut normal_vector_sums[welded_index as usize];
+                            for (sum, component) in normal_vector_sum.iter_mut().zip(normal_vector)
+                            {
+                                *sum += component;
+                            }
+
+                            vertex_index_map[index] = welded_index;
+                        }
+
+                        vertex_index_map[index]
+                    });
+
+                // Skip any triangles that became degenerate from the merging
+                if triangle[0] != triangle[1]
+                    && triangle[1] != triangle[2]
+                    && triangle[2] != triangle[0]
+                {
+                    indices.extend_from_slice(&triangle);
+                }
+            }
+        }
+
+        let normal_vectors = normal_vector_sums
+            .into_iter()
+            .map(|sum| VertexNormalVector(UnitVector3C::normalized_from(sum.into())))
+            .collect();
+
+        TriangleMesh::new(
+            positions,
+            normal_vectors,
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            indices,
+        )
+    }
+
     /// Returns the modifications that were made to the mesh since it was last
     /// synchronized with the GPU.
     pub fn mesh_modifications(&self) -> VoxelMeshModifications<'_> {
         self.chunk_submesh_manager.modifications()
     }
 
-    /// Returns the ranges of vertices and indices, respectively, for the chunk
-    /// at the given indices if that chunk has a mesh.
+    /// Returns the ranges of vertices and indices, respectively, for the
+    /// full-detail surface of the chunk at the given indices if that chunk has
+    /// a mesh.
     pub fn vertex_and_index_range_for_chunk_at_indices(
         &self,
         chunk_indices: [usize; 3],
@@ -534,11 +723,30 @@ impl VoxelObjectMesh {
     pub fn clear(&mut self) {
         self.positions.clear();
         self.normal_vectors.clear();
+        self.temperatures.clear();
         self.index_materials.clear();
         self.indices.clear();
         self.chunk_submesh_manager.clear();
     }
 
+    /// Returns the number of chunk layers along each face of the object's
+    /// chunk grid that are treated as padding and not meshed.
+    pub fn padding_chunk_layers(&self) -> usize {
+        self.padding_chunk_layers
+    }
+
+    fn is_padding_chunk(
+        padding: usize,
+        voxel_object: &VoxelObject,
+        chunk_indices: &[usize; 3],
+    ) -> bool {
+        padding > 0
+            && chunk_indices
+                .iter()
+                .zip(voxel_object.chunk_counts())
+                .any(|(&idx, &count)| idx < padding || idx + padding >= count)
+    }
+
     /// Returns a guess for the typical number of vertices in a chunk mesh.
     const fn vertex_count_per_chunk_heuristic() -> usize {
         // Surface nets tends to produce roughly 1-2x as many vertices as there
@@ -556,11 +764,38 @@ impl VoxelObjectMesh {
         5 * Self::vertex_count_per_chunk_heuristic()
     }
 
+    fn extend_vertex_temperatures(
+        temperatures: &mut Vec<VoxelMeshVertexTemperature>,
+        voxel_object: &VoxelObject,
+        positions: &[VoxelMeshVertexPosition],
+    ) {
+        temperatures.extend(positions.iter().map(|position| {
+            VoxelMeshVertexTemperature(voxel_object.compute_temperature_at_position(&position.0))
+        }));
+    }
+
+    fn write_vertex_temperatures(
+        temperatures: &mut [VoxelMeshVertexTemperature],
+        voxel_object: &VoxelObject,
+        positions: &[VoxelMeshVertexPosition],
+    ) {
+        for (temperature, position) in temperatures.iter_mut().zip(positions) {
+            *temperature = VoxelMeshVertexTemperature(
+                voxel_object.compute_temperature_at_position(&position.0),
+            );
+        }
+    }
+
+    fn lod_voxel_extent(voxel_object: &VoxelObject, lod: usize) -> f32 {
+        voxel_object.voxel_extent() * (1 << lod) as f32
+    }
+
     fn vertex_position_offset_for_chunk(
         voxel_object: &VoxelObject,
         chunk_indices: &[usize; 3],
+        lod: usize,
     ) -> Vector3 {
-        let voxel_extent = voxel_object.voxel_extent();
+        let voxel_extent = Self::lod_voxel_extent(voxel_object, lod);
         let chunk_extent = voxel_object.chunk_extent();
 
         // Since the `VoxelChunkSignedDistanceField` has a 1-voxel padding
@@ -569,6 +804,8 @@ impl VoxelObjectMesh {
         // of the vertices for the surface nets mesh. We also need to add
         // half a voxel extent to account for the SDF values being specified
         // at voxel centers, at half-voxel coordinates in the voxel object.
+        // At coarser levels of detail, the voxel extent is that of the
+        // downsampled voxels.
         Vector3::new(
             chunk_indices[0] as f32 * chunk_extent - 0.5 * voxel_extent,
             chunk_indices[1] as f32 * chunk_extent - 0.5 * voxel_extent,
@@ -584,12 +821,15 @@ impl ChunkSubmesh {
     fn new(
         chunk_indices: [usize; 3],
         index_offset: usize,
-        index_count: usize,
+        layout: &ChunkMeshLayout,
         flags: VoxelChunkFlags,
     ) -> Self {
         let chunk_indices = chunk_indices.map(|index| index as u32);
         let index_offset = u32::try_from(index_offset).unwrap();
-        let index_count = u32::try_from(index_count).unwrap();
+        let index_count = u32::try_from(layout.surface_index_count).unwrap();
+        let lod_index_counts = layout
+            .lod_index_counts
+            .map(|count| u32::try_from(count).unwrap());
         let is_obscured_from_direction = Self::compute_directional_obscuredness_table(flags);
 
         Self {
@@ -597,6 +837,7 @@ impl ChunkSubmesh {
             index_offset,
             index_count,
             is_obscured_from_direction,
+            lod_index_counts,
         }
     }
 
@@ -604,10 +845,25 @@ impl ChunkSubmesh {
         &self.chunk_indices
     }
 
+    /// Returns the range of indices for the full-detail surface of the chunk.
     pub fn index_range(&self) -> Range<usize> {
         (self.index_offset as usize)..(self.index_offset as usize + self.index_count as usize)
     }
 
+    /// Returns the number of indices (including the skirt) for each level of
+    /// detail. The indices for each level follow directly after those for the
+    /// previous level, starting at the start of [`Self::index_range`].
+    pub fn lod_index_counts(&self) -> &[u32; VOXEL_CHUNK_LOD_COUNT] {
+        &self.lod_index_counts
+    }
+
+    /// Returns the full range of indices for the chunk, covering every level
+    /// of detail.
+    fn allocated_index_range(&self) -> Range<usize> {
+        let index_count: u32 = self.lod_index_counts.iter().sum();
+        (self.index_offset as usize)..(self.index_offset as usize + index_count as usize)
+    }
+
     fn compute_directional_obscuredness_table(flags: VoxelChunkFlags) -> [[[u32; 2]; 2]; 2] {
         const OBSCURED_X: [VoxelChunkFlags; 2] = [
             VoxelChunkFlags::IS_OBSCURED_X_DN,
@@ -696,12 +952,76 @@ impl CullingFrustum {
     }
 }
 
+impl ChunkLODSelectionParameters {
+    /// Creates parameters for selecting the level of detail of voxel chunks
+    /// seen through the given perspective frustum, based on the given
+    /// configuration. Since only ratios of lengths are involved, the
+    /// parameters are valid in any space related to the frustum's space by a
+    /// similarity transform.
+    pub fn for_frustum(frustum: &Frustum, config: &LODSelectionConfig) -> Self {
+        if !config.enabled {
+            return Self::full_detail();
+        }
+
+        let corners = frustum.compute_corners();
+
+        // The height of the view plane per unit distance from the apex
+        let view_height_per_distance =
+            (corners[3].y() - corners[1].y()).abs() / frustum.far_distance();
+
+        Self::new(
+            config.voxel_first_lod_screen_size
+                * VoxelObject::chunk_size() as f32
+                * view_height_per_distance,
+            0.0,
+            config,
+        )
+    }
+
+    /// Creates parameters for selecting the level of detail of voxel chunks
+    /// seen through the given orthographic frustum (represented by an oriented
+    /// box) after transforming it with the given similarity transform, based on
+    /// the given configuration. The level of detail will be the same for all
+    /// chunks.
+    pub fn for_transformed_orthographic_frustum(
+        orthographic_frustum: &OrientedBox,
+        transformation: &Similarity3,
+        config: &LODSelectionConfig,
+    ) -> Self {
+        if !config.enabled {
+            return Self::full_detail();
+        }
+
+        let view_height = 2.0 * transformation.scaling() * orthographic_frustum.half_extents().y();
+
+        Self::new(
+            0.0,
+            config.voxel_first_lod_screen_size * VoxelObject::chunk_size() as f32 * view_height,
+            config,
+        )
+    }
+
+    /// Creates parameters that always select the full level of detail.
+    pub fn full_detail() -> Self {
+        Self::default()
+    }
+
+    fn new(distance_scale: f32, distance_offset: f32, config: &LODSelectionConfig) -> Self {
+        Self {
+            distance_scale,
+            distance_offset,
+            inverse_log_level_factor: -1.0 / config.screen_size_factor_per_level.ln(),
+        }
+    }
+}
+
 impl ChunkSubmeshManager {
     fn new() -> Self {
         Self {
             chunk_index_map: KeyIndexMapper::new(),
             chunk_submeshes: Vec::new(),
             chunk_vertex_ranges: Vec::new(),
+            chunk_allocated_vertex_ranges: Vec::new(),
             vertex_range_allocator: RangeAllocator::fully_occupied(),
             index_range_allocator: RangeAllocator::fully_occupied(),
             updated_data_ranges: Vec::new(),
@@ -714,6 +1034,7 @@ impl ChunkSubmeshManager {
         self.chunk_index_map.reserve(chunk_count);
         self.chunk_submeshes.reserve(chunk_count);
         self.chunk_vertex_ranges.reserve(chunk_count);
+        self.chunk_allocated_vertex_ranges.reserve(chunk_count);
     }
 
     fn chunk_submeshes(&self) -> &[ChunkSubmesh] {
@@ -728,9 +1049,8 @@ impl ChunkSubmeshManager {
         &mut self,
         chunk_indices: [usize; 3],
         vertex_offset: usize,
-        vertex_count: usize,
         index_offset: usize,
-        index_count: usize,
+        layout: &ChunkMeshLayout,
         flags: VoxelChunkFlags,
     ) {
         self.chunk_index_map.push_key(chunk_indices);
@@ -738,12 +1058,14 @@ impl ChunkSubmeshManager {
         self.chunk_submeshes.push(ChunkSubmesh::new(
             chunk_indices,
             index_offset,
-            index_count,
+            layout,
             flags,
         ));
 
         self.chunk_vertex_ranges
-            .push(vertex_offset..vertex_offset + vertex_count);
+            .push(vertex_offset..vertex_offset + layout.surface_vertex_count);
+        self.chunk_allocated_vertex_ranges
+            .push(vertex_offset..vertex_offset + layout.vertex_count);
     }
 
     fn write_chunk(
@@ -751,18 +1073,20 @@ impl ChunkSubmeshManager {
         total_vertex_count: usize,
         total_index_count: usize,
         chunk_indices: [usize; 3],
-        vertex_count: usize,
-        index_count: usize,
+        layout: &ChunkMeshLayout,
         flags: VoxelChunkFlags,
     ) -> ChunkSubmeshDataRanges {
+        let vertex_count = layout.vertex_count;
+        let index_count = layout.index_count();
+
         // If the chunk already exists, free its mesh vertex and index ranges
         let chunk_idx = self.chunk_index_map.get(chunk_indices).inspect(|&idx| {
             let old_chunk_submesh = &self.chunk_submeshes[idx];
-            let old_vertex_range = &self.chunk_vertex_ranges[idx];
+            let old_vertex_range = &self.chunk_allocated_vertex_ranges[idx];
 
             self.vertex_range_allocator.free_range(old_vertex_range);
             self.index_range_allocator
-                .free_range(&old_chunk_submesh.index_range());
+                .free_range(&old_chunk_submesh.allocated_index_range());
         });
 
         // Find the smallest free vertex range fitting the vertex count, or append the
@@ -781,17 +1105,23 @@ impl ChunkSubmeshManager {
         // Now that we know where the indices will begin, we can construct the
         // `ChunkSubmesh`
         let new_chunk_submesh =
-            ChunkSubmesh::new(chunk_indices, new_index_range.start, index_count, flags);
+            ChunkSubmesh::new(chunk_indices, new_index_range.start, layout, flags);
+
+        let new_surface_vertex_range =
+            new_vertex_range.start..new_vertex_range.start + layout.surface_vertex_count;
 
         // Overwrite the existing submesh entry or push a new one if the chunk didn't
         // exist
         if let Some(idx) = chunk_idx {
             self.chunk_submeshes[idx] = new_chunk_submesh;
-            self.chunk_vertex_ranges[idx] = new_vertex_range.clone();
+            self.chunk_vertex_ranges[idx] = new_surface_vertex_range;
+            self.chunk_allocated_vertex_ranges[idx] = new_vertex_range.clone();
         } else {
             self.chunk_index_map.push_key(chunk_indices);
             self.chunk_submeshes.push(new_chunk_submesh);
-            self.chunk_vertex_ranges.push(new_vertex_range.clone());
+            self.chunk_vertex_ranges.push(new_surface_vertex_range);
+            self.chunk_allocated_vertex_ranges
+                .push(new_vertex_range.clone());
         }
 
         let data_ranges = ChunkSubmeshDataRanges {
@@ -808,13 +1138,14 @@ impl ChunkSubmeshManager {
 
     fn remove_chunk_if_present(&mut self, chunk_indices: &[usize; 3]) {
         if let Ok(idx) = self.chunk_index_map.try_swap_remove_key(*chunk_indices) {
-            let vertex_range = self.chunk_vertex_ranges.swap_remove(idx);
+            self.chunk_vertex_ranges.swap_remove(idx);
+            let vertex_range = self.chunk_allocated_vertex_ranges.swap_remove(idx);
 
             let chunk_submesh = self.chunk_submeshes.swap_remove(idx);
 
             self.vertex_range_allocator.free_range(&vertex_range);
             self.index_range_allocator
-                .free_range(&chunk_submesh.index_range());
+                .free_range(&chunk_submesh.allocated_index_range());
 
             self.chunks_were_removed = true;
         }
@@ -841,9 +1172,318 @@ impl ChunkSubmeshManager {
         self.chunk_index_map.clear();
         self.chunk_submeshes.clear();
         self.chunk_vertex_ranges.clear();
+        self.chunk_allocated_vertex_ranges.clear();
         self.vertex_range_allocator.mark_all_ranges_occupied();
         self.index_range_allocator.mark_all_ranges_occupied();
         self.updated_data_ranges.clear();
         self.chunks_were_removed = false;
     }
 }
+
+impl ChunkMeshBuffer {
+    fn new() -> Self {
+        Self {
+            positions: Vec::new(),
+            normal_vectors: Vec::new(),
+            index_materials: Vec::new(),
+            indices: Vec::new(),
+            layout: ChunkMeshLayout::default(),
+            lod_region: VoxelChunkLODRegion::new(),
+            lod_sdf: VoxelChunkSignedDistanceField::new(),
+            edge_counts: HashMap::default(),
+            open_edges: Vec::new(),
+            skirt_vertices: HashMap::default(),
+        }
+    }
+
+    /// Computes the mesh for the chunk at the given indices at every level of
+    /// detail, using the given full-detail SDF for the chunk. Returns `false`
+    /// if the full-detail mesh is empty, in which case no other levels are
+    /// computed.
+    fn compute_for_chunk(
+        &mut self,
+        voxel_object: &VoxelObject,
+        chunk_indices: &[usize; 3],
+        sdf: &VoxelChunkSignedDistanceField,
+        surface_nets_buffer: &mut SurfaceNetsBuffer,
+    ) -> bool {
+        self.clear();
+
+        sdf.compute_surface_nets_mesh(
+            voxel_object.voxel_extent(),
+            &VoxelObjectMesh::vertex_position_offset_for_chunk(voxel_object, chunk_indices, 0),
+            surface_nets_buffer,
+        );
+
+        if surface_nets_buffer.is_empty() {
+            return false;
+        }
+
+        self.layout.surface_vertex_count = surface_nets_buffer.positions.len();
+        self.layout.surface_index_count = surface_nets_buffer.indices.len();
+
+        self.push_lod(0, voxel_object.voxel_extent(), surface_nets_buffer);
+
+        self.lod_region.fill_for_chunk(voxel_object, chunk_indices);
+
+        for lod in 1..VOXEL_CHUNK_LOD_COUNT {
+            self.lod_region
+                .fill_sdf_for_lod(lod, sdf, &mut self.lod_sdf);
+
+            let lod_voxel_extent = VoxelObjectMesh::lod_voxel_extent(voxel_object, lod);
+
+            self.lod_sdf.compute_surface_nets_mesh(
+                lod_voxel_extent,
+                &VoxelObjectMesh::vertex_position_offset_for_chunk(
+                    voxel_object,
+                    chunk_indices,
+                    lod,
+                ),
+                surface_nets_buffer,
+            );
+
+            self.push_lod(lod, lod_voxel_extent, surface_nets_buffer);
+        }
+
+        self.layout.vertex_count = self.positions.len();
+
+        true
+    }
+
+    fn push_lod(
+        &mut self,
+        lod: usize,
+        lod_voxel_extent: f32,
+        surface_nets_buffer: &SurfaceNetsBuffer,
+    ) {
+        let vertex_offset = self.positions.len() as u32;
+        let index_offset = self.indices.len();
+
+        self.positions
+            .extend_from_slice(&surface_nets_buffer.positions);
+        self.normal_vectors
+            .extend_from_slice(&surface_nets_buffer.normal_vectors);
+        self.index_materials
+            .extend_from_slice(&surface_nets_buffer.index_materials);
+        self.indices.extend(
+            surface_nets_buffer
+                .indices
+                .iter()
+                .map(|&index| vertex_offset + u32::from(index)),
+        );
+
+        // The skirt must reach deep enough to cover the gap to the surface of
+        // an adjacent chunk at the next coarser level of detail
+        self.push_skirt(index_offset, 2.0 * lod_voxel_extent);
+
+        self.layout.lod_index_counts[lod] = self.indices.len() - index_offset;
+    }
+
+    /// Adds a skirt for the triangles whose indices start at the given offset.
+    /// For every edge belonging to only one of the triangles (which will lie
+    /// on the chunk boundary), a quad is added that extends the given depth
+    /// inwards from the edge, along the negative normal vectors of its
+    /// vertices.
+    fn push_skirt(&mut self, index_offset: usize, depth: f32) {
+        self.edge_counts.clear();
+
+        for (triangle_idx, triangle) in self.indices[index_offset..].chunks_exact(3).enumerate() {
+            for (corner, &start) in triangle.iter().enumerate() {
+                let end = triangle[(corner + 1) % 3];
+                let position = index_offset + 3 * triangle_idx + corner;
+
+                self.edge_counts
+                    .entry([start.min(end), start.max(end)])
+                    .and_modify(|(count, _)| *count += 1)
+                    .or_insert((1, position));
+            }
+        }
+
+        self.open_edges.clear();
+        self.open_edges.extend(
+            self.edge_counts
+                .values()
+                .filter(|(count, _)| *count == 1)
+                .map(|&(_, position)| position),
+        );
+        // Sort to make the result independent of hash map iteration order
+        self.open_edges.sort_unstable();
+
+        self.skirt_vertices.clear();
+
+        let open_edges = mem::take(&mut self.open_edges);
+
+        for &start_position in &open_edges {
+            let end_position = 3 * (start_position / 3) + (start_position + 1) % 3;
+
+            let start = self.indices[start_position];
+            let end = self.indices[end_position];
+            let start_materials = self.index_materials[start_position];
+            let end_materials = self.index_materials[end_position];
+
+            let start_skirt = self.skirt_vertex(start, depth);
+            let end_skirt = self.skirt_vertex(end, depth);
+
+            // The triangle traverses the edge from start to end, so the skirt
+            // must traverse it from end to start to get the same winding
+            self.indices
+                .extend_from_slice(&[end, start, start_skirt, end, start_skirt, end_skirt]);
+            self.index_materials.extend_from_slice(&[
+                end_materials,
+                start_materials,
+                start_materials,
+                end_materials,
+                start_materials,
+                end_materials,
+            ]);
+        }
+
+        self.open_edges = open_edges;
+    }
+
+    fn skirt_vertex(&mut self, vertex: u32, depth: f32) -> u32 {
+        if let Some(&skirt_vertex) = self.skirt_vertices.get(&vertex) {
+            return skirt_vertex;
+        }
+
+        let VoxelMeshVertexPosition(position) = self.positions[vertex as usize];
+        let normal_vector = self.normal_vectors[vertex as usize];
+
+        let skirt_vertex = self.positions.len() as u32;
+
+        self.positions
+            .push(VoxelMeshVertexPosition(array::from_fn(|dim| {
+                position[dim] - depth * normal_vector.0[dim]
+            })));
+        self.normal_vectors.push(normal_vector);
+
+        self.skirt_vertices.insert(vertex, skirt_vertex);
+
+        skirt_vertex
+    }
+
+    fn clear(&mut self) {
+        self.positions.clear();
+        self.normal_vectors.clear();
+        self.index_materials.clear();
+        self.indices.clear();
+        self.layout = ChunkMeshLayout::default();
+    }
+}
+
+impl ChunkMeshLayout {
+    fn index_count(&self) -> usize {
+        self.lod_index_counts.iter().sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        generation::{
+            SDFVoxelGenerator,
+            sdf::{SDFGraph, SDFNode},
+            voxel_type::SameVoxelTypeGenerator,
+        },
+        voxel_types::VoxelType,
+    };
+    use impact_alloc::Global;
+
+    #[test]
+    fn triangle_mesh_for_multi_chunk_sphere_is_closed() {
+        let mut graph = SDFGraph::new_in(Global);
+        graph.add_node(SDFNode::new_sphere(10.0));
+        let sdf_generator = graph.build_in(Global).unwrap();
+
+        let generator = SDFVoxelGenerator::new(
+            0.5,
+            sdf_generator,
+            SameVoxelTypeGenerator::new(VoxelType::default()).into(),
+        );
+        let object = VoxelObject::generate(VoxelObjectBuffers::new(), &generator);
+        let voxel_mesh = VoxelObjectMesh::create(&object);
+        assert!(voxel_mesh.n_chunks() > 1);
+
+        let mesh = voxel_mesh.create_triangle_mesh(object.voxel_extent());
+
+        assert!(mesh.n_triangles() > 0);
+        assert!(mesh.n_vertices() < voxel_mesh.n_vertices());
+        assert_eq!(mesh.normal_vectors().len(), mesh.n_vertices());
+        assert_eq!(voxel_mesh.temperatures().len(), voxel_mesh.n_vertices());
+
+        // In a closed mesh, every edge is shared by exactly two triangles
+        let mut edge_counts: HashMap<[usize; 2], usize> = HashMap::default();
+        for [i, j, k] in mesh.triangle_indices() {
+            for (a, b) in [(i, j), (j, k), (k, i)] {
+                *edge_counts.entry([a.min(b), a.max(b)]).or_default() += 1;
+            }
+        }
+        assert!(edge_counts.values().all(|&count| count == 2));
+    }
+
+    #[test]
+    fn coarser_levels_of_detail_for_sphere_have_fewer_indices() {
+        let object = create_sphere_object(10.0);
+        let voxel_mesh = VoxelObjectMesh::create(&object);
+
+        let mut total_lod_index_counts = [0; VOXEL_CHUNK_LOD_COUNT];
+        for submesh in voxel_mesh.chunk_submeshes() {
+            for (total, &count) in total_lod_index_counts
+                .iter_mut()
+                .zip(submesh.lod_index_counts())
+            {
+                assert_eq!(count % 3, 0);
+                *total += count;
+            }
+        }
+
+        for lod in 1..VOXEL_CHUNK_LOD_COUNT {
+            assert!(total_lod_index_counts[lod] > 0);
+            assert!(total_lod_index_counts[lod] < total_lod_index_counts[lod - 1]);
+        }
+    }
+
+    #[test]
+    fn full_detail_chunk_meshes_of_sphere_have_skirts() {
+        let object = create_sphere_object(10.0);
+        let voxel_mesh = VoxelObjectMesh::create(&object);
+        assert!(voxel_mesh.n_chunks() > 1);
+
+        for submesh in voxel_mesh.chunk_submeshes() {
+            // Every chunk of a multi-chunk sphere has a boundary with open
+            // edges, so there must be a skirt in addition to the surface
+            assert!(submesh.lod_index_counts()[0] as usize > submesh.index_range().len());
+        }
+    }
+
+    #[test]
+    fn full_detail_surface_indices_refer_to_full_detail_surface_vertices() {
+        let object = create_sphere_object(10.0);
+        let voxel_mesh = VoxelObjectMesh::create(&object);
+
+        for (submesh, vertex_range) in voxel_mesh
+            .chunk_submeshes()
+            .iter()
+            .zip(voxel_mesh.chunk_vertex_ranges())
+        {
+            assert!(!submesh.index_range().is_empty());
+            for &VoxelMeshIndex(index) in &voxel_mesh.indices()[submesh.index_range()] {
+                assert!(vertex_range.contains(&(index as usize)));
+            }
+        }
+    }
+
+    fn create_sphere_object(radius: f32) -> VoxelObject {
+        let mut graph = SDFGraph::new_in(Global);
+        graph.add_node(SDFNode::new_sphere(radius));
+        let sdf_generator = graph.build_in(Global).unwrap();
+
+        let generator = SDFVoxelGenerator::new(
+            0.5,
+            sdf_generator,
+            SameVoxelTypeGenerator::new(VoxelType::default()).into(),
+        );
+        VoxelObject::generate(VoxelObjectBuffers::new(), &generator)
+    }
+}
@@ -1,10 +1,14 @@
 //! Voxel objects.
 
+pub mod deposition;
 pub mod extraction;
 pub mod inertia;
+pub mod integrity;
 pub mod intersection;
+pub mod io;
 pub mod sdf;
 pub mod split_detection;
+pub mod thermal;
 
 use crate::{
     Voxel, VoxelFlags,
@@ -30,6 +34,7 @@ use split_detection::{
     UniformChunkSplitDetectionData,
 };
 use std::{array, mem, ops::Range};
+use thermal::VoxelObjectThermalState;
 use tinyvec::TinyVec;
 
 /// An object represented by a grid of voxels.
@@ -49,11 +54,12 @@ pub struct VoxelObject {
     chunk_idx_strides: [usize; 3],
     occupied_chunk_ranges: ChunkRanges,
     occupied_voxel_ranges: VoxelRanges,
-    origin_offset_in_root: [usize; 3],
+    origin_offset_in_root: [isize; 3],
     chunks: Vec<VoxelChunk>,
     voxels: Vec<Voxel>,
     split_detector: SplitDetector,
     invalidated_mesh_chunk_indices: HashSet<[usize; 3]>,
+    thermal_state: VoxelObjectThermalState,
 }
 
 /// The heap memory buffers for a [`VoxelObject`].
@@ -355,6 +361,7 @@ impl VoxelObject {
             voxels,
             split_detector,
             invalidated_mesh_chunk_indices,
+            thermal_state: VoxelObjectThermalState::new(),
         }
     }
 
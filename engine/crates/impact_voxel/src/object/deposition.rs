@@ -0,0 +1,575 @@
+//! Deposition of voxels into voxel objects.
+
+use crate::{
+    Voxel,
+    object::{
+        self, CHUNK_SIZE, CHUNK_VOXEL_COUNT, FaceVoxelDistribution, NonUniformVoxelChunk,
+        VoxelChunk, VoxelChunkFlags, VoxelObject, VoxelRanges, chunk_voxels_mut, intersection,
+        split_detection::NonUniformChunkSplitDetectionData,
+    },
+};
+use impact_alloc::{arena::ArenaPool, avec};
+use impact_geometry::AxisAlignedBox;
+use std::{array, ops::Range};
+
+/// The number of chunks to add on the lower and upper side of a voxel
+/// object's chunk grid along each axis.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkGridPadding {
+    pub lower: [usize; 3],
+    pub upper: [usize; 3],
+}
+
+impl ChunkGridPadding {
+    /// Whether the padding does not add any chunks.
+    pub fn is_zero(&self) -> bool {
+        self.lower == [0; 3] && self.upper == [0; 3]
+    }
+
+    /// Returns the number of voxels the existing grid will be offset by along
+    /// each axis when the padding is applied.
+    pub fn lower_voxel_shift(&self) -> [usize; 3] {
+        self.lower.map(|count| count * CHUNK_SIZE)
+    }
+}
+
+impl VoxelObject {
+    /// Determines how many chunks must be added to each side of the chunk grid
+    /// for it to fully contain the given axis-aligned box.
+    ///
+    /// The box should be specified in the normalized model space of the voxel
+    /// object, where distances are in voxels, the lower corner of the grid is
+    /// at the origin and the cartesian axes are aligned with the grid.
+    pub fn chunk_grid_padding_to_include_aab(
+        &self,
+        normalized_aab: &AxisAlignedBox,
+    ) -> ChunkGridPadding {
+        let lower_corner = normalized_aab.lower_corner();
+        let upper_corner = normalized_aab.upper_corner();
+
+        let lower = array::from_fn(|dim| {
+            if lower_corner[dim] < 0.0 {
+                (-lower_corner[dim] / CHUNK_SIZE as f32).ceil() as usize
+            } else {
+                0
+            }
+        });
+
+        let upper = array::from_fn(|dim| {
+            let required_chunk_count =
+                (upper_corner[dim].max(0.0) / CHUNK_SIZE as f32).ceil() as usize;
+            required_chunk_count.saturating_sub(self.chunk_counts[dim])
+        });
+
+        ChunkGridPadding { lower, upper }
+    }
+
+    /// Computes the ranges of voxel indices along each axis of the full voxel
+    /// grid (not just the occupied part) for the voxels touching the given
+    /// axis-aligned box.
+    ///
+    /// The box should be in normalized voxel object space (where voxel extent
+    /// is 1.0).
+    pub fn voxel_ranges_in_grid_touching_aab(
+        &self,
+        normalized_aab: &AxisAlignedBox,
+    ) -> VoxelRanges {
+        intersection::voxel_ranges_touching_aab(
+            self.chunk_counts.map(|count| 0..count * CHUNK_SIZE),
+            normalized_aab,
+        )
+    }
+
+    /// Grows the chunk grid of the object by adding void chunks on each side
+    /// according to the given padding. The existing voxels are shifted
+    /// upward by [`ChunkGridPadding::lower_voxel_shift`] in the grid, so any
+    /// quantities defined relative to the origin of the grid (like the
+    /// inertial properties) must be updated by the caller. The origin offset
+    /// in the root object is shifted accordingly, so the voxels keep their
+    /// position relative to the root object.
+    ///
+    /// Since all chunk indices change, the mesh data for every chunk is
+    /// invalidated, and the boundary adjacencies and connected regions are
+    /// recomputed.
+    pub fn grow_chunk_grid(&mut self, padding: &ChunkGridPadding) {
+        if padding.is_zero() {
+            return;
+        }
+
+        let new_chunk_counts: [usize; 3] =
+            array::from_fn(|dim| padding.lower[dim] + self.chunk_counts[dim] + padding.upper[dim]);
+
+        let new_chunk_idx_strides = [
+            new_chunk_counts[2] * new_chunk_counts[1],
+            new_chunk_counts[2],
+            1,
+        ];
+
+        let mut new_chunks = vec![VoxelChunk::Void; new_chunk_counts.iter().product()];
+
+        for chunk_i in 0..self.chunk_counts[0] {
+            for chunk_j in 0..self.chunk_counts[1] {
+                for chunk_k in 0..self.chunk_counts[2] {
+                    let chunk_idx = self.linear_chunk_idx(&[chunk_i, chunk_j, chunk_k]);
+
+                    let new_chunk_idx = (chunk_i + padding.lower[0]) * new_chunk_idx_strides[0]
+                        + (chunk_j + padding.lower[1]) * new_chunk_idx_strides[1]
+                        + chunk_k
+                        + padding.lower[2];
+
+                    new_chunks[new_chunk_idx] = self.chunks[chunk_idx];
+                }
+            }
+        }
+
+        self.chunks = new_chunks;
+        self.chunk_counts = new_chunk_counts;
+        self.chunk_idx_strides = new_chunk_idx_strides;
+
+        let voxel_shift = padding.lower_voxel_shift();
+
+        self.thermal_state.shift_chunk_indices(padding.lower);
+
+        if !self.occupied_chunk_ranges.iter().any(Range::is_empty) {
+            self.occupied_chunk_ranges = array::from_fn(|dim| {
+                let range = &self.occupied_chunk_ranges[dim];
+                range.start + padding.lower[dim]..range.end + padding.lower[dim]
+            });
+            self.occupied_voxel_ranges = array::from_fn(|dim| {
+                let range = &self.occupied_voxel_ranges[dim];
+                range.start + voxel_shift[dim]..range.end + voxel_shift[dim]
+            });
+        }
+
+        // The origin of the grid has moved down by the shift, so the offset
+        // must follow it for the existing voxels to keep their position
+        // relative to the root object (which determines their texture
+        // coordinates). The offset becomes negative for a root object.
+        self.origin_offset_in_root =
+            array::from_fn(|dim| self.origin_offset_in_root[dim] - voxel_shift[dim] as isize);
+
+        for chunk_i in 0..self.chunk_counts[0] {
+            for chunk_j in 0..self.chunk_counts[1] {
+                for chunk_k in 0..self.chunk_counts[2] {
+                    self.invalidated_mesh_chunk_indices
+                        .insert([chunk_i, chunk_j, chunk_k]);
+                }
+            }
+        }
+
+        // The global region labels encode linear chunk indices, which have all
+        // changed
+        self.update_local_connected_regions_for_all_chunks();
+        self.update_all_chunk_boundary_adjacencies();
+        self.resolve_connected_regions_between_all_chunks();
+    }
+
+    /// Calls the given closure for each voxel within the given voxel ranges,
+    /// including voxels in void chunks, which are materialized as non-uniform
+    /// chunks if the closure modifies any of their voxels. The closure should
+    /// return whether it modified the voxel. Voxels in void chunks start out as
+    /// maximally outside.
+    ///
+    /// This is intended for operations adding material to the object. The
+    /// ranges are clamped to the current grid, so the grid should be grown
+    /// with [`Self::grow_chunk_grid`] beforehand if required.
+    ///
+    /// Since it is assumed that the given closure will modify the voxels, the
+    /// adjacency information will be updated for all voxels within the ranges,
+    /// and any chunk whose mesh data would be invalidated by changes to these
+    /// voxels will be registered. The invalidated chunks can be obtained by
+    /// calling [`Self::invalidated_mesh_chunk_indices`].
+    ///
+    /// Even though modifying the object will invalidate the connected region
+    /// information, this method does not call
+    /// [`Self::resolve_connected_regions_between_all_chunks`] to avoid
+    /// duplicating work when this method is called multiple times. Make sure to
+    /// call it once all modifications have been made.
+    pub fn deposit_into_voxels_within_ranges(
+        &mut self,
+        included_voxel_ranges: VoxelRanges,
+        modify_voxel: &mut impl FnMut([usize; 3], &mut Voxel) -> bool,
+    ) {
+        let included_voxel_ranges: VoxelRanges = array::from_fn(|dim| {
+            let range = &included_voxel_ranges[dim];
+            let end = range.end.min(self.chunk_counts[dim] * CHUNK_SIZE);
+            range.start.min(end)..end
+        });
+
+        if included_voxel_ranges.iter().any(Range::is_empty) {
+            return;
+        }
+
+        let included_chunk_ranges = included_voxel_ranges
+            .clone()
+            .map(object::chunk_range_encompassing_voxel_range);
+
+        let arena = ArenaPool::get_arena();
+        let mut void_chunk_voxels = avec![in &arena; Voxel::maximally_outside(); CHUNK_VOXEL_COUNT];
+
+        let mut modified_chunks = false;
+        let mut removed_chunks = false;
+
+        for chunk_i in included_chunk_ranges[0].clone() {
+            for chunk_j in included_chunk_ranges[1].clone() {
+                for chunk_k in included_chunk_ranges[2].clone() {
+                    let chunk_indices = [chunk_i, chunk_j, chunk_k];
+                    let chunk_idx = self.linear_chunk_idx(&chunk_indices);
+
+                    let object_voxel_ranges_in_chunk =
+                        chunk_indices.map(|index| index * CHUNK_SIZE..(index + 1) * CHUNK_SIZE);
+
+                    let included_voxel_ranges_in_chunk: [_; 3] = array::from_fn(|dim| {
+                        let range_in_chunk = &object_voxel_ranges_in_chunk[dim];
+                        let included_range = &included_voxel_ranges[dim];
+                        usize::max(range_in_chunk.start, included_range.start)
+                            ..usize::min(range_in_chunk.end, included_range.end)
+                    });
+
+                    let chunk = &mut self.chunks[chunk_idx];
+
+                    let chunk_touched = match chunk {
+                        VoxelChunk::Void => {
+                            void_chunk_voxels.fill(Voxel::maximally_outside());
+
+                            if !modify_voxels_in_chunk(
+                                &mut void_chunk_voxels,
+                                &included_voxel_ranges_in_chunk,
+                                modify_voxel,
+                            ) {
+                                continue;
+                            }
+
+                            // Something was deposited into the void chunk, so
+                            // we give it storage at the end of the voxel buffer
+                            let start_voxel_idx = self.voxels.len();
+                            self.voxels.extend_from_slice(&void_chunk_voxels);
+                            self.split_detector.add_non_uniform_chunk();
+
+                            *chunk = VoxelChunk::NonUniform(NonUniformVoxelChunk {
+                                data_offset: object::chunk_data_offset_from_start_voxel_idx(
+                                    start_voxel_idx,
+                                ),
+                                face_distributions: FaceVoxelDistribution::all_empty(),
+                                flags: VoxelChunkFlags::HAS_ONLY_EMPTY_VOXELS,
+                                split_detection: NonUniformChunkSplitDetectionData::new(),
+                            });
+
+                            true
+                        }
+                        VoxelChunk::Uniform(_) => {
+                            chunk.convert_to_non_uniform_if_uniform(
+                                &mut self.voxels,
+                                &mut self.split_detector,
+                            );
+                            let data_offset = if let VoxelChunk::NonUniform(chunk) = chunk {
+                                chunk.data_offset
+                            } else {
+                                unreachable!()
+                            };
+                            modify_voxels_in_chunk(
+                                chunk_voxels_mut(&mut self.voxels, data_offset),
+                                &included_voxel_ranges_in_chunk,
+                                modify_voxel,
+                            )
+                        }
+                        VoxelChunk::NonUniform(chunk) => modify_voxels_in_chunk(
+                            chunk_voxels_mut(&mut self.voxels, chunk.data_offset),
+                            &included_voxel_ranges_in_chunk,
+                            modify_voxel,
+                        ),
+                    };
+
+                    if chunk_touched {
+                        Self::handle_chunk_voxels_modified(
+                            &mut self.voxels,
+                            &mut self.split_detector,
+                            &self.chunk_counts,
+                            chunk,
+                            chunk_indices,
+                            chunk_idx,
+                            object_voxel_ranges_in_chunk,
+                            included_voxel_ranges_in_chunk,
+                            &mut self.invalidated_mesh_chunk_indices,
+                            &mut removed_chunks,
+                        );
+                        modified_chunks = true;
+                    }
+                }
+            }
+        }
+
+        if modified_chunks {
+            self.update_occupied_ranges();
+        }
+
+        self.update_upper_boundary_adjacencies_for_chunks_in_ranges(
+            included_chunk_ranges.map(|range| range.start.saturating_sub(1)..range.end),
+        );
+    }
+}
+
+fn modify_voxels_in_chunk(
+    chunk_voxels: &mut [Voxel],
+    included_voxel_ranges_in_chunk: &VoxelRanges,
+    modify_voxel: &mut impl FnMut([usize; 3], &mut Voxel) -> bool,
+) -> bool {
+    let mut chunk_touched = false;
+
+    for i in included_voxel_ranges_in_chunk[0].clone() {
+        for j in included_voxel_ranges_in_chunk[1].clone() {
+            for k in included_voxel_ranges_in_chunk[2].clone() {
+                let voxel_idx =
+                    object::linear_voxel_idx_within_chunk_from_object_voxel_indices(i, j, k);
+
+                chunk_touched |= modify_voxel([i, j, k], &mut chunk_voxels[voxel_idx]);
+            }
+        }
+    }
+
+    chunk_touched
+}
+
+#[cfg(not(miri))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        generation::{
+            SDFVoxelGenerator,
+            sdf::{SDFGraph, SDFNode},
+            voxel_type::SameVoxelTypeGenerator,
+        },
+        object::{ChunkRanges, VoxelObjectBuffers},
+        voxel_types::VoxelType,
+    };
+    use impact_alloc::Global;
+
+    fn generate_sphere_object(radius: f32) -> VoxelObject {
+        let mut graph = SDFGraph::new_in(Global);
+        graph.add_node(SDFNode::new_sphere(radius));
+        let sdf_generator = graph.build_in(Global).unwrap();
+
+        let generator = SDFVoxelGenerator::new(
+            0.5,
+            sdf_generator,
+            SameVoxelTypeGenerator::new(VoxelType::default()).into(),
+        );
+        VoxelObject::generate(VoxelObjectBuffers::new(), &generator)
+    }
+
+    #[test]
+    fn growing_chunk_grid_preserves_shifted_voxels() {
+        let mut object = generate_sphere_object(10.0);
+        let original_object = object.clone();
+
+        let padding = ChunkGridPadding {
+            lower: [1, 0, 2],
+            upper: [0, 1, 0],
+        };
+        object.grow_chunk_grid(&padding);
+
+        let original_chunk_counts = *original_object.chunk_counts();
+        assert_eq!(
+            object.chunk_counts(),
+            &[
+                original_chunk_counts[0] + 1,
+                original_chunk_counts[1] + 1,
+                original_chunk_counts[2] + 2
+            ]
+        );
+
+        let [di, dj, dk] = padding.lower_voxel_shift();
+
+        for i in 0..original_chunk_counts[0] * CHUNK_SIZE {
+            for j in 0..original_chunk_counts[1] * CHUNK_SIZE {
+                for k in 0..original_chunk_counts[2] * CHUNK_SIZE {
+                    let original_voxel = original_object.voxel(i, j, k);
+                    let voxel = object.voxel(i + di, j + dj, k + dk);
+                    assert_eq!(voxel.signed_distance(), original_voxel.signed_distance());
+                    assert_eq!(voxel.is_empty(), original_voxel.is_empty());
+                }
+            }
+        }
+
+        object.validate_adjacencies();
+        object.validate_chunk_obscuredness();
+        object.validate_occupied_voxel_ranges();
+        object.validate_region_count();
+        assert_eq!(object.count_regions(), 1);
+    }
+
+    #[test]
+    fn growing_chunk_grid_downward_shifts_occupied_ranges_and_keeps_regions() {
+        let mut object = generate_sphere_object(10.0);
+        let original_object = object.clone();
+
+        let padding = ChunkGridPadding {
+            lower: [2, 1, 1],
+            upper: [0; 3],
+        };
+        object.grow_chunk_grid(&padding);
+
+        let voxel_shift = padding.lower_voxel_shift();
+
+        let original_chunk_ranges = original_object.occupied_chunk_ranges();
+        let expected_chunk_ranges: ChunkRanges = array::from_fn(|dim| {
+            original_chunk_ranges[dim].start + padding.lower[dim]
+                ..original_chunk_ranges[dim].end + padding.lower[dim]
+        });
+        assert_eq!(object.occupied_chunk_ranges(), &expected_chunk_ranges);
+
+        let shift_ranges = |ranges: &VoxelRanges| -> VoxelRanges {
+            array::from_fn(|dim| {
+                ranges[dim].start + voxel_shift[dim]..ranges[dim].end + voxel_shift[dim]
+            })
+        };
+        assert_eq!(
+            object.occupied_voxel_ranges(),
+            &shift_ranges(original_object.occupied_voxel_ranges())
+        );
+        assert_eq!(
+            object.determine_tight_occupied_voxel_ranges(),
+            shift_ranges(&original_object.determine_tight_occupied_voxel_ranges())
+        );
+
+        let [di, dj, dk] = voxel_shift;
+        let original_chunk_counts = *original_object.chunk_counts();
+
+        for i in 0..original_chunk_counts[0] * CHUNK_SIZE {
+            for j in 0..original_chunk_counts[1] * CHUNK_SIZE {
+                for k in 0..original_chunk_counts[2] * CHUNK_SIZE {
+                    assert_eq!(
+                        object
+                            .get_voxel_if_occupied(i + di, j + dj, k + dk)
+                            .is_some(),
+                        original_object.get_voxel_if_occupied(i, j, k).is_some()
+                    );
+                }
+            }
+        }
+
+        // None of the new chunks below the original grid contain voxels
+        for i in 0..di {
+            for j in 0..object.chunk_counts()[1] * CHUNK_SIZE {
+                for k in 0..object.chunk_counts()[2] * CHUNK_SIZE {
+                    assert!(object.voxel(i, j, k).is_empty());
+                }
+            }
+        }
+
+        object.validate_adjacencies();
+        object.validate_chunk_obscuredness();
+        object.validate_occupied_voxel_ranges();
+        object.validate_sdf();
+        object.validate_region_count();
+        assert_eq!(object.count_regions(), 1);
+    }
+
+    #[test]
+    fn growing_chunk_grid_downward_keeps_voxel_positions_relative_to_root() {
+        let mut object = generate_sphere_object(10.0);
+        let original_object = object.clone();
+
+        // Find a surface voxel by scanning inward along the x-axis through the
+        // middle of the grid
+        let [_, nj, nk] = object.chunk_counts().map(|count| count * CHUNK_SIZE);
+        let (j, k) = (nj / 2, nk / 2);
+        let i = (0..object.chunk_counts()[0] * CHUNK_SIZE)
+            .find(|&i| object.get_voxel_if_occupied(i, j, k).is_some())
+            .unwrap();
+
+        let padding = ChunkGridPadding {
+            lower: [1, 2, 0],
+            upper: [1, 0, 0],
+        };
+        object.grow_chunk_grid(&padding);
+
+        let [di, dj, dk] = padding.lower_voxel_shift();
+        assert!(
+            object
+                .get_voxel_if_occupied(i + di, j + dj, k + dk)
+                .is_some()
+        );
+
+        let position_in_root = |object: &VoxelObject, indices: [usize; 3]| -> [f32; 3] {
+            let offset = object.origin_offset_in_root();
+            array::from_fn(|dim| indices[dim] as f32 * object.voxel_extent() + offset[dim])
+        };
+
+        assert_eq!(
+            position_in_root(&object, [i + di, j + dj, k + dk]),
+            position_in_root(&original_object, [i, j, k])
+        );
+    }
+
+    #[test]
+    fn depositing_outside_grid_fills_void_chunks_as_separate_region() {
+        let mut object = generate_sphere_object(10.0);
+
+        let original_voxel_counts = object.chunk_counts().map(|count| count * CHUNK_SIZE);
+        let ball_radius = 4.0;
+        let ball_center = [
+            (original_voxel_counts[0] + CHUNK_SIZE) as f32,
+            0.5 * original_voxel_counts[1] as f32,
+            0.5 * original_voxel_counts[2] as f32,
+        ];
+
+        let ball_aabb = AxisAlignedBox::new(
+            [
+                ball_center[0] - ball_radius - 3.0,
+                ball_center[1] - ball_radius - 3.0,
+                ball_center[2] - ball_radius - 3.0,
+            ]
+            .into(),
+            [
+                ball_center[0] + ball_radius + 3.0,
+                ball_center[1] + ball_radius + 3.0,
+                ball_center[2] + ball_radius + 3.0,
+            ]
+            .into(),
+        );
+
+        let padding = object.chunk_grid_padding_to_include_aab(&ball_aabb);
+        assert_eq!(padding.lower, [0; 3]);
+        assert_eq!(padding.upper, [2, 0, 0]);
+
+        object.grow_chunk_grid(&padding);
+
+        let mut filled_voxel_count = 0;
+
+        object.deposit_into_voxels_within_ranges(
+            object.voxel_ranges_in_grid_touching_aab(&ball_aabb),
+            &mut |[i, j, k], voxel| {
+                let distance = [i, j, k]
+                    .into_iter()
+                    .zip(ball_center)
+                    .map(|(index, center)| (index as f32 + 0.5 - center).powi(2))
+                    .sum::<f32>()
+                    .sqrt();
+
+                let original_signed_distance = voxel.signed_distance();
+
+                voxel.set_signed_distance_filling_with(
+                    voxel.signed_distance().to_f32().min(distance - ball_radius),
+                    VoxelType::default(),
+                    &mut |_| {
+                        filled_voxel_count += 1;
+                    },
+                );
+
+                voxel.signed_distance() != original_signed_distance
+            },
+        );
+
+        object.resolve_connected_regions_between_all_chunks();
+
+        assert!(filled_voxel_count > 0);
+        object.validate_adjacencies();
+        object.validate_chunk_obscuredness();
+        object.validate_occupied_voxel_ranges();
+        object.validate_sdf();
+        object.validate_region_count();
+        assert_eq!(object.count_regions(), 2);
+    }
+}
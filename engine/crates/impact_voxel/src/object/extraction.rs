@@ -14,6 +14,7 @@ use crate::{
             UniformChunkSplitDetectionData, chunk_voxel_region_labels, find_root_for_region,
             non_uniform_chunk_regions, non_uniform_chunk_start_region_idx,
         },
+        thermal::VoxelObjectThermalState,
     },
     utils::{Dimension, Faces},
     voxel_types::VoxelType,
@@ -1901,7 +1902,7 @@ impl VoxelObject {
     #[inline]
     fn complete_extracted_voxel_object(
         voxel_extent: f32,
-        parent_origin_offset_in_root: &[usize; 3],
+        parent_origin_offset_in_root: &[isize; 3],
         chunk_counts: [usize; 3],
         chunk_ranges_in_parent: ChunkRanges,
         uniform_chunk_count: usize,
@@ -1975,7 +1976,7 @@ impl VoxelObject {
     #[inline]
     fn create_extracted_voxel_object_in_single_chunk_if_possible(
         voxel_extent: f32,
-        parent_origin_offset_in_root: &[usize; 3],
+        parent_origin_offset_in_root: &[isize; 3],
         origin_offset_in_parent: [usize; 3],
         chunk_counts: [usize; 3],
         uniform_chunk_count: usize,
@@ -2143,7 +2144,7 @@ impl VoxelObject {
 
     fn create_extracted_voxel_object(
         voxel_extent: f32,
-        parent_origin_offset_in_root: &[usize; 3],
+        parent_origin_offset_in_root: &[isize; 3],
         origin_offset_in_parent: [usize; 3],
         chunk_counts: [usize; 3],
         chunks: Vec<VoxelChunk>,
@@ -2160,8 +2161,9 @@ impl VoxelObject {
             .clone()
             .map(|chunk_range| chunk_range.start * CHUNK_SIZE..chunk_range.end * CHUNK_SIZE);
 
-        let origin_offset_in_root =
-            array::from_fn(|dim| parent_origin_offset_in_root[dim] + origin_offset_in_parent[dim]);
+        let origin_offset_in_root = array::from_fn(|dim| {
+            parent_origin_offset_in_root[dim] + origin_offset_in_parent[dim] as isize
+        });
 
         let voxel_object = Self {
             voxel_extent,
@@ -2175,6 +2177,8 @@ impl VoxelObject {
             voxels,
             split_detector,
             invalidated_mesh_chunk_indices,
+            // The extracted voxels start out at ambient temperature
+            thermal_state: VoxelObjectThermalState::new(),
         };
 
         ExtractedVoxelObject {
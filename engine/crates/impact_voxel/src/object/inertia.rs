@@ -255,6 +255,11 @@ impl VoxelObjectInertialPropertyManager {
     /// reference point at the given offset from the point they are currently
     /// defined with respect to.
     pub fn offset_reference_point_by(&mut self, offset: &Vector3) {
+        // Without mass there is nothing to offset, and the center of mass is
+        // undefined
+        if self.mass == 0.0 {
+            return;
+        }
         let (moment_of_inertia_deltas, product_of_inertia_deltas) =
             InertiaTensor::compute_delta_to_moments_and_products_of_inertia_defined_relative_to_point(
                 self.mass,
@@ -392,6 +397,25 @@ impl VoxelObjectInertialPropertyUpdater<'_, '_> {
         self.parent.moments_of_inertia -= voxel_moments_of_inertia;
         self.parent.products_of_inertia -= voxel_products_of_inertia;
     }
+
+    /// Updates the inertial properties to account for the given voxel being
+    /// added.
+    #[inline]
+    pub fn add_voxel(&mut self, object_voxel_indices: &[usize; 3], voxel_type: VoxelType) {
+        let (voxel_mass, voxel_moments, voxel_moments_of_inertia, voxel_products_of_inertia) =
+            compute_moments_for_voxel(
+                self.voxel_extent,
+                self.voxel_extent_pow_2,
+                self.voxel_extent_pow_3,
+                self.voxel_type_densities,
+                object_voxel_indices,
+                voxel_type,
+            );
+        self.parent.mass += voxel_mass;
+        self.parent.moments += voxel_moments;
+        self.parent.moments_of_inertia += voxel_moments_of_inertia;
+        self.parent.products_of_inertia += voxel_products_of_inertia;
+    }
 }
 
 impl VoxelObjectInertialPropertyTransferrer<'_, '_> {
@@ -0,0 +1,419 @@
+//! Structural integrity analysis of voxel objects.
+
+use crate::{
+    object::{VoxelObject, VoxelRanges},
+    voxel_types::VoxelType,
+};
+use impact_alloc::{AVec, Allocator, avec};
+use impact_math::point::Point3;
+use std::{array, ops::Range};
+
+/// The radius (in voxels) of the sphere carved out around a broken voxel. It
+/// is just large enough to empty the voxel itself while keeping the signed
+/// distance field consistent for its neighbors.
+const BREAK_RADIUS: f32 = 0.5;
+
+const NO_NODE: u32 = u32::MAX;
+const UNREACHED: u32 = u32::MAX;
+
+impl VoxelObject {
+    /// Approximates how the weight of each non-empty voxel is carried through
+    /// the voxel connectivity graph to the given support voxels, and returns
+    /// the indices of the voxels whose connections towards the supports are
+    /// loaded beyond what they can sustain.
+    ///
+    /// The load of each voxel (its own weight plus the load passed on to it)
+    /// is distributed evenly among its face neighbors that are one step closer
+    /// to the nearest support. A voxel is overloaded if its load exceeds the
+    /// combined strength of these connections, where the strength of a
+    /// connection is the smaller strength of the two voxel types times the area
+    /// of a voxel face. Loads are processed from the voxels farthest from the
+    /// supports inwards, and an overloaded voxel does not pass its load on, so
+    /// only the weakest link along each load path is reported.
+    ///
+    /// Voxels that are not connected to any support carry no load. The
+    /// gravitational acceleration is given in m/s², the mass densities in
+    /// kg/m³ and the strengths in Pa.
+    pub fn find_overloaded_voxels<A: Allocator>(
+        &self,
+        alloc: A,
+        support_voxel_indices: &[[usize; 3]],
+        gravitational_acceleration: f32,
+        voxel_type_densities: &[f32],
+        voxel_type_strengths: &[f32],
+    ) -> AVec<[usize; 3], A> {
+        let mut overloaded_voxel_indices = AVec::new_in(alloc);
+
+        let ranges = self.occupied_voxel_ranges().clone();
+        let shape = ranges.clone().map(|range| range.len());
+        let grid_voxel_count = shape.iter().product::<usize>();
+
+        if grid_voxel_count == 0 || support_voxel_indices.is_empty() {
+            return overloaded_voxel_indices;
+        }
+
+        let voxel_extent = self.voxel_extent();
+        let voxel_volume = voxel_extent.powi(3);
+        let face_area = voxel_extent.powi(2);
+
+        let linear_idx = |[i, j, k]: [usize; 3]| (i * shape[1] + j) * shape[2] + k;
+
+        // Assign a node to each non-empty voxel in the occupied ranges
+        let mut node_indices = avec![in alloc; NO_NODE; grid_voxel_count];
+        let mut node_local_voxel_indices = AVec::new_in(alloc);
+        let mut node_voxel_types = AVec::new_in(alloc);
+
+        for i in 0..shape[0] {
+            for j in 0..shape[1] {
+                for k in 0..shape[2] {
+                    let voxel = self.voxel(
+                        ranges[0].start + i,
+                        ranges[1].start + j,
+                        ranges[2].start + k,
+                    );
+                    if !voxel.is_empty() {
+                        node_indices[linear_idx([i, j, k])] = node_local_voxel_indices.len() as u32;
+                        node_local_voxel_indices.push([i, j, k]);
+                        node_voxel_types.push(voxel.voxel_type());
+                    }
+                }
+            }
+        }
+
+        let node_count = node_local_voxel_indices.len();
+
+        let local_node_idx = |global_indices: &[usize; 3]| -> Option<u32> {
+            if (0..3).any(|dim| !ranges[dim].contains(&global_indices[dim])) {
+                return None;
+            }
+            let local_indices = array::from_fn(|dim| global_indices[dim] - ranges[dim].start);
+            let node_idx = node_indices[linear_idx(local_indices)];
+            (node_idx != NO_NODE).then_some(node_idx)
+        };
+
+        let for_each_neighbor_node = |[i, j, k]: [usize; 3], f: &mut dyn FnMut(u32)| {
+            let neighbors = [
+                (i > 0).then(|| [i - 1, j, k]),
+                (i + 1 < shape[0]).then(|| [i + 1, j, k]),
+                (j > 0).then(|| [i, j - 1, k]),
+                (j + 1 < shape[1]).then(|| [i, j + 1, k]),
+                (k > 0).then(|| [i, j, k - 1]),
+                (k + 1 < shape[2]).then(|| [i, j, k + 1]),
+            ];
+            for neighbor in neighbors.into_iter().flatten() {
+                let node_idx = node_indices[linear_idx(neighbor)];
+                if node_idx != NO_NODE {
+                    f(node_idx);
+                }
+            }
+        };
+
+        // Breadth-first search from the supports to find the graph distance
+        // from each voxel to its nearest support
+        let mut depths = avec![in alloc; UNREACHED; node_count];
+        let mut visit_order = AVec::with_capacity_in(node_count, alloc);
+
+        for indices in support_voxel_indices {
+            if let Some(node_idx) = local_node_idx(indices)
+                && depths[node_idx as usize] == UNREACHED
+            {
+                depths[node_idx as usize] = 0;
+                visit_order.push(node_idx);
+            }
+        }
+
+        let mut cursor = 0;
+        while cursor < visit_order.len() {
+            let node_idx = visit_order[cursor];
+            cursor += 1;
+
+            let next_depth = depths[node_idx as usize] + 1;
+
+            for_each_neighbor_node(
+                node_local_voxel_indices[node_idx as usize],
+                &mut |neighbor_idx| {
+                    if depths[neighbor_idx as usize] == UNREACHED {
+                        depths[neighbor_idx as usize] = next_depth;
+                        visit_order.push(neighbor_idx);
+                    }
+                },
+            );
+        }
+
+        let mut loads: AVec<f32, A> = AVec::with_capacity_in(node_count, alloc);
+        loads.extend(node_voxel_types.iter().map(|voxel_type| {
+            voxel_type_densities[voxel_type.idx()] * voxel_volume * gravitational_acceleration
+        }));
+
+        let mut supporting_nodes = AVec::with_capacity_in(6, alloc);
+
+        // Pass loads inwards, starting with the voxels farthest from the
+        // supports
+        for &node_idx in visit_order.iter().rev() {
+            let node_idx = node_idx as usize;
+            let depth = depths[node_idx];
+
+            if depth == 0 {
+                continue;
+            }
+
+            let strength = voxel_type_strengths[node_voxel_types[node_idx].idx()];
+
+            supporting_nodes.clear();
+            let mut capacity = 0.0;
+
+            for_each_neighbor_node(node_local_voxel_indices[node_idx], &mut |neighbor_idx| {
+                if depths[neighbor_idx as usize] + 1 == depth {
+                    let neighbor_strength =
+                        voxel_type_strengths[node_voxel_types[neighbor_idx as usize].idx()];
+                    capacity += strength.min(neighbor_strength) * face_area;
+                    supporting_nodes.push(neighbor_idx);
+                }
+            });
+
+            let load = loads[node_idx];
+
+            if load > capacity {
+                let local_indices = node_local_voxel_indices[node_idx];
+                overloaded_voxel_indices
+                    .push(array::from_fn(|dim| ranges[dim].start + local_indices[dim]));
+                continue;
+            }
+
+            let load_per_connection = load / supporting_nodes.len() as f32;
+            for &supporting_idx in &supporting_nodes {
+                loads[supporting_idx as usize] += load_per_connection;
+            }
+        }
+
+        overloaded_voxel_indices
+    }
+
+    /// Adds the indices of all non-empty voxels whose centers lie within the
+    /// given distance of the given point to the given list.
+    ///
+    /// The point and distance should be specified in the normalized model
+    /// space of the voxel object, where distances are in voxels, the lower
+    /// corner of the grid is at the origin and the cartesian axes are aligned
+    /// with the grid.
+    pub fn gather_non_empty_voxels_near_point<A: Allocator>(
+        &self,
+        normalized_point: &Point3,
+        distance: f32,
+        voxel_indices: &mut AVec<[usize; 3], A>,
+    ) {
+        let occupied_ranges = self.occupied_voxel_ranges();
+        let point = [
+            normalized_point.x(),
+            normalized_point.y(),
+            normalized_point.z(),
+        ];
+
+        let ranges: VoxelRanges = array::from_fn(|dim| {
+            let occupied_range = &occupied_ranges[dim];
+            let start =
+                ((point[dim] - distance - 0.5).floor().max(0.0) as usize).max(occupied_range.start);
+            let end =
+                ((point[dim] + distance + 0.5).ceil().max(0.0) as usize).min(occupied_range.end);
+            start.min(end)..end
+        });
+
+        if ranges.iter().any(Range::is_empty) {
+            return;
+        }
+
+        let squared_distance = distance.powi(2);
+
+        for i in ranges[0].clone() {
+            for j in ranges[1].clone() {
+                for k in ranges[2].clone() {
+                    let squared_distance_to_center = (i as f32 + 0.5 - point[0]).powi(2)
+                        + (j as f32 + 0.5 - point[1]).powi(2)
+                        + (k as f32 + 0.5 - point[2]).powi(2);
+
+                    if squared_distance_to_center <= squared_distance
+                        && !self.voxel(i, j, k).is_empty()
+                    {
+                        voxel_indices.push([i, j, k]);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Empties the voxels with the given indices by carving out a small sphere
+    /// around each of them, and calls the given closure with the indices and
+    /// type of each voxel that became empty.
+    ///
+    /// Even though modifying the object will invalidate the connected region
+    /// information, this method does not call
+    /// [`Self::resolve_connected_regions_between_all_chunks`] to avoid
+    /// duplicating work when this method is called multiple times. Make sure to
+    /// call it once all modifications have been made.
+    pub fn break_voxels(
+        &mut self,
+        voxel_indices: &[[usize; 3]],
+        on_emptied: &mut impl FnMut([usize; 3], VoxelType),
+    ) {
+        let grid_shape = self.chunk_counts().map(|count| count * super::CHUNK_SIZE);
+
+        for &indices in voxel_indices {
+            let ranges: VoxelRanges = array::from_fn(|dim| {
+                indices[dim].saturating_sub(1)..(indices[dim] + 2).min(grid_shape[dim])
+            });
+
+            self.modify_voxels_within_ranges(ranges, &mut |neighbor_indices, voxel| {
+                let squared_distance = (0..3)
+                    .map(|dim| (neighbor_indices[dim] as f32 - indices[dim] as f32).powi(2))
+                    .sum::<f32>();
+
+                let carved_signed_distance = BREAK_RADIUS - squared_distance.sqrt();
+
+                let original_signed_distance = voxel.signed_distance();
+
+                if voxel.is_empty() || carved_signed_distance <= original_signed_distance.to_f32() {
+                    return false;
+                }
+
+                voxel.set_signed_distance(carved_signed_distance, &mut |voxel| {
+                    on_emptied(neighbor_indices, voxel.voxel_type());
+                });
+
+                voxel.signed_distance() != original_signed_distance
+            });
+        }
+    }
+}
+
+#[cfg(not(miri))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        generation::{
+            SDFVoxelGenerator,
+            sdf::{SDFGraph, SDFNode},
+            voxel_type::SameVoxelTypeGenerator,
+        },
+        object::VoxelObjectBuffers,
+    };
+    use impact_alloc::Global;
+    use impact_math::vector::Vector3;
+
+    const GRAVITY: f32 = 10.0;
+
+    /// Creates two 10-voxel cubes along the x-axis connected by a thin bar.
+    fn generate_cantilever_object() -> VoxelObject {
+        let mut graph = SDFGraph::new_in(Global);
+        let base_id = graph.add_node(SDFNode::new_box([10.0, 10.0, 10.0]));
+        let bar_id = graph.add_node(SDFNode::new_box([12.0, 1.5, 1.5]));
+        let bar_id = graph.add_node(SDFNode::new_translation(
+            bar_id,
+            Vector3::new(10.0, 0.0, 0.0),
+        ));
+        let tip_id = graph.add_node(SDFNode::new_box([10.0, 10.0, 10.0]));
+        let tip_id = graph.add_node(SDFNode::new_translation(
+            tip_id,
+            Vector3::new(20.0, 0.0, 0.0),
+        ));
+        let union_id = graph.add_node(SDFNode::new_union(base_id, bar_id, 0.0));
+        graph.add_node(SDFNode::new_union(union_id, tip_id, 0.0));
+        let sdf_generator = graph.build_in(Global).unwrap();
+
+        let generator = SDFVoxelGenerator::new(
+            1.0,
+            sdf_generator,
+            SameVoxelTypeGenerator::new(VoxelType::default()).into(),
+        );
+        VoxelObject::generate(VoxelObjectBuffers::new(), &generator)
+    }
+
+    /// Returns the non-empty voxels in the lowest occupied layer along x.
+    fn find_base_support_voxels(object: &VoxelObject) -> Vec<[usize; 3]> {
+        let ranges = object.occupied_voxel_ranges().clone();
+        let mut supports = Vec::new();
+        for i in ranges[0].clone() {
+            for j in ranges[1].clone() {
+                for k in ranges[2].clone() {
+                    if !object.voxel(i, j, k).is_empty() {
+                        supports.push([i, j, k]);
+                    }
+                }
+            }
+            if !supports.is_empty() {
+                break;
+            }
+        }
+        supports
+    }
+
+    #[test]
+    fn unsupported_object_has_no_overloaded_voxels() {
+        let object = generate_cantilever_object();
+        let overloaded = object.find_overloaded_voxels(Global, &[], GRAVITY, &[1.0], &[1e-3]);
+        assert!(overloaded.is_empty());
+    }
+
+    #[test]
+    fn strong_cantilever_has_no_overloaded_voxels() {
+        let object = generate_cantilever_object();
+        let supports = find_base_support_voxels(&object);
+        let overloaded = object.find_overloaded_voxels(Global, &supports, GRAVITY, &[1.0], &[1e6]);
+        assert!(overloaded.is_empty());
+    }
+
+    #[test]
+    fn weak_cantilever_breaks_between_base_and_tip() {
+        let mut object = generate_cantilever_object();
+        let supports = find_base_support_voxels(&object);
+        let base_end = supports[0][0] + 8;
+
+        // The tip weighs about 10^4 N, which the thin bar can not carry, while
+        // the thick base distributes the load well enough. Breaking the
+        // weakest links can shift the load onto the remaining ones, so we
+        // repeat the check until the structure is stable.
+        let mut iterations = 0;
+        loop {
+            let overloaded =
+                object.find_overloaded_voxels(Global, &supports, GRAVITY, &[1.0], &[1e3]);
+            if overloaded.is_empty() {
+                break;
+            }
+            assert!(overloaded.iter().all(|indices| indices[0] > base_end));
+
+            let mut emptied_count = 0;
+            object.break_voxels(&overloaded, &mut |_, _| emptied_count += 1);
+            assert!(emptied_count >= overloaded.len());
+
+            object.resolve_connected_regions_between_all_chunks();
+
+            iterations += 1;
+            assert!(iterations < 20);
+        }
+        assert!(iterations > 0);
+
+        object.validate_adjacencies();
+        object.validate_chunk_obscuredness();
+        object.validate_occupied_voxel_ranges();
+        object.validate_sdf();
+        object.validate_region_count();
+        assert!(object.count_regions() >= 2);
+    }
+
+    #[test]
+    fn gathering_voxels_near_point_finds_only_nearby_non_empty_voxels() {
+        let object = generate_cantilever_object();
+        let supports = find_base_support_voxels(&object);
+        let [i, j, k] = supports[supports.len() / 2];
+
+        let point = Point3::new(i as f32 + 0.5, j as f32 + 0.5, k as f32 + 0.5);
+        let mut gathered = AVec::new_in(Global);
+        object.gather_non_empty_voxels_near_point(&point, 1.0, &mut gathered);
+
+        assert!(gathered.contains(&[i, j, k]));
+        assert!(gathered.len() <= 7);
+        for indices in &gathered {
+            assert!(!object.voxel(indices[0], indices[1], indices[2]).is_empty());
+        }
+    }
+}
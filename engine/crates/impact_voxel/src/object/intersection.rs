@@ -536,7 +536,7 @@ impl VoxelObject {
         );
     }
 
-    fn handle_chunk_voxels_modified(
+    pub(super) fn handle_chunk_voxels_modified(
         voxels: &mut [Voxel],
         split_detector: &mut SplitDetector,
         chunk_counts: &[usize; 3],
@@ -763,7 +763,7 @@ fn voxel_ranges_within_plane(
 /// The AAB should be in normalized voxel object space (where voxel extent is
 /// 1.0).
 #[inline]
-fn voxel_ranges_touching_aab(
+pub(super) fn voxel_ranges_touching_aab(
     max_voxel_ranges: VoxelRanges,
     normalized_aab: &AxisAlignedBox,
 ) -> VoxelRanges {
@@ -799,10 +799,10 @@ pub mod fuzzing {
     pub struct ArbitraryPlane(Plane);
 
     #[derive(Clone, Debug)]
-    pub struct ArbitrarySphere(Sphere);
+    pub struct ArbitrarySphere(pub Sphere);
 
     #[derive(Clone, Debug)]
-    pub struct ArbitraryCapsule(Capsule);
+    pub struct ArbitraryCapsule(pub Capsule);
 
     impl Arbitrary<'_> for ArbitraryPlane {
         fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
@@ -0,0 +1,698 @@
+//! Reading and writing of voxel objects in a compact binary file format.
+//!
+//! A file starts with a header holding the magic bytes `IVOX`, the format
+//! version, the voxel extent, the number of chunks along each axis and the
+//! signed origin offset of the object in its root object. The header is
+//! followed by one record per chunk, in the same order as the chunks of the
+//! object:
+//!
+//! - Void chunks are stored as a single tag byte.
+//! - Uniform chunks are stored as a tag byte followed by their single voxel.
+//! - Non-uniform chunks are stored as a tag byte followed by a palette of the
+//!   distinct voxels in the chunk and a run-length encoded sequence of palette
+//!   indices for the voxels in the chunk.
+//!
+//! The chunk records are followed by the thermal state of the object: the
+//! number of chunks whose temperatures are tracked, followed by the indices
+//! and voxel temperatures of each such chunk. Files of version 1 have no
+//! thermal state, and all voxels of objects read from them are at ambient
+//! temperature.
+//!
+//! A voxel is stored as its type, its encoded signed distance and whether it
+//! is empty. Adjacency information and all other derived state is recomputed
+//! when the object is read. All numbers are little-endian.
+
+use crate::{
+    Voxel, VoxelFlags, VoxelSignedDistance,
+    generation::ChunkedVoxelGenerator,
+    object::{
+        CHUNK_SIZE, CHUNK_VOXEL_COUNT, ChunkSparseness, UniformVoxelChunk, VoxelChunk, VoxelObject,
+        VoxelObjectBuffers, chunk_voxels, thermal::VoxelObjectThermalState,
+    },
+    voxel_types::VoxelType,
+};
+use anyhow::{Context, Result, bail};
+use impact_alloc::Allocator;
+use impact_containers::HashMap;
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+/// The bytes every voxel object file starts with.
+pub const VOXEL_OBJECT_FILE_MAGIC: [u8; 4] = *b"IVOX";
+
+/// The current version of the voxel object file format.
+pub const VOXEL_OBJECT_FILE_VERSION: u32 = 2;
+
+/// The first version of the voxel object file format that stores the thermal
+/// state of the object.
+const FIRST_VERSION_WITH_THERMAL_STATE: u32 = 2;
+
+const VOID_CHUNK_TAG: u8 = 0;
+const UNIFORM_CHUNK_TAG: u8 = 1;
+const NON_UNIFORM_CHUNK_TAG: u8 = 2;
+
+/// The voxel data of a voxel object read from a file, acting as a generator
+/// for the object.
+#[derive(Clone, Debug)]
+struct StoredVoxelObject {
+    voxel_extent: f32,
+    chunk_counts: [usize; 3],
+    origin_offset_in_root: [isize; 3],
+    chunks: Vec<StoredVoxelChunk>,
+    thermal_state: VoxelObjectThermalState,
+}
+
+#[derive(Clone, Debug)]
+enum StoredVoxelChunk {
+    Void,
+    Uniform(Voxel),
+    NonUniform(Box<[Voxel]>),
+}
+
+/// Writes the given voxel object to a file at the given path, creating any
+/// missing directories.
+///
+/// # Errors
+/// Returns an error if the file can not be written.
+pub fn write_voxel_object_to_file(object: &VoxelObject, file_path: impl AsRef<Path>) -> Result<()> {
+    let file_path = file_path.as_ref();
+
+    let file = impact_io::create_file_and_required_directories(file_path)
+        .with_context(|| format!("Failed to create voxel object file {}", file_path.display()))?;
+
+    let mut writer = BufWriter::new(file);
+
+    write_voxel_object(object, &mut writer)
+        .and_then(|_| Ok(writer.flush()?))
+        .with_context(|| format!("Failed to write voxel object file {}", file_path.display()))
+}
+
+/// Reads the voxel object in the file at the given path, using the given
+/// memory buffers for the object.
+///
+/// # Errors
+/// Returns an error if the file can not be read or is not a valid voxel
+/// object file.
+pub fn read_voxel_object_from_file(
+    buffers: VoxelObjectBuffers,
+    file_path: impl AsRef<Path>,
+) -> Result<VoxelObject> {
+    let file_path = file_path.as_ref();
+
+    let file = File::open(file_path)
+        .with_context(|| format!("Failed to open voxel object file {}", file_path.display()))?;
+
+    read_voxel_object(buffers, &mut BufReader::new(file))
+        .with_context(|| format!("Failed to read voxel object file {}", file_path.display()))
+}
+
+/// Writes the given voxel object in the voxel object file format to the given
+/// writer.
+///
+/// # Errors
+/// Returns an error if writing fails.
+pub fn write_voxel_object(object: &VoxelObject, writer: &mut impl Write) -> Result<()> {
+    writer.write_all(&VOXEL_OBJECT_FILE_MAGIC)?;
+    writer.write_all(&VOXEL_OBJECT_FILE_VERSION.to_le_bytes())?;
+    writer.write_all(&object.voxel_extent.to_le_bytes())?;
+
+    for count in object.chunk_counts {
+        write_u32(writer, count)?;
+    }
+    for offset in object.origin_offset_in_root {
+        write_i32(writer, offset)?;
+    }
+
+    let mut palette = Vec::new();
+    let mut palette_indices = HashMap::default();
+
+    for chunk in &object.chunks {
+        match chunk {
+            VoxelChunk::Void => {
+                writer.write_all(&[VOID_CHUNK_TAG])?;
+            }
+            VoxelChunk::Uniform(UniformVoxelChunk { voxel, .. }) => {
+                writer.write_all(&[UNIFORM_CHUNK_TAG])?;
+                writer.write_all(&encode_voxel(voxel))?;
+            }
+            VoxelChunk::NonUniform(chunk) => {
+                writer.write_all(&[NON_UNIFORM_CHUNK_TAG])?;
+                write_non_uniform_chunk_voxels(
+                    writer,
+                    chunk_voxels(&object.voxels, chunk.data_offset),
+                    &mut palette,
+                    &mut palette_indices,
+                )?;
+            }
+        }
+    }
+
+    write_thermal_state(writer, &object.thermal_state)?;
+
+    Ok(())
+}
+
+/// Reads a voxel object in the voxel object file format from the given
+/// reader, using the given memory buffers for the object.
+///
+/// # Errors
+/// Returns an error if reading fails or the data is not a valid voxel object
+/// file.
+pub fn read_voxel_object(
+    buffers: VoxelObjectBuffers,
+    reader: &mut impl Read,
+) -> Result<VoxelObject> {
+    let stored_object = StoredVoxelObject::read(reader)?;
+
+    let mut object = VoxelObject::generate(buffers, &stored_object);
+
+    object.origin_offset_in_root = stored_object.origin_offset_in_root;
+    object.thermal_state = stored_object.thermal_state;
+
+    Ok(object)
+}
+
+impl StoredVoxelObject {
+    fn read(reader: &mut impl Read) -> Result<Self> {
+        let mut magic = [0; 4];
+        reader
+            .read_exact(&mut magic)
+            .context("Missing voxel object file header")?;
+        if magic != VOXEL_OBJECT_FILE_MAGIC {
+            bail!("Not a voxel object file");
+        }
+
+        let version = read_u32(reader)?;
+        if !(1..=VOXEL_OBJECT_FILE_VERSION).contains(&version) {
+            bail!(
+                "Unsupported voxel object file version {version} (expected {VOXEL_OBJECT_FILE_VERSION})"
+            );
+        }
+
+        let voxel_extent = f32::from_le_bytes(read_array(reader)?);
+        if !(voxel_extent.is_finite() && voxel_extent > 0.0) {
+            bail!("Invalid voxel extent {voxel_extent}");
+        }
+
+        let chunk_counts = [
+            read_u32(reader)? as usize,
+            read_u32(reader)? as usize,
+            read_u32(reader)? as usize,
+        ];
+        let origin_offset_in_root = [
+            read_i32(reader)? as isize,
+            read_i32(reader)? as isize,
+            read_i32(reader)? as isize,
+        ];
+
+        let total_chunk_count = chunk_counts
+            .iter()
+            .try_fold(1_usize, |product, &count| product.checked_mul(count))
+            .context("Chunk counts are too large")?;
+
+        let mut chunks = Vec::new();
+
+        for _ in 0..total_chunk_count {
+            let [tag] = read_array(reader)?;
+            let chunk = match tag {
+                VOID_CHUNK_TAG => StoredVoxelChunk::Void,
+                UNIFORM_CHUNK_TAG => StoredVoxelChunk::Uniform(decode_voxel(read_array(reader)?)),
+                NON_UNIFORM_CHUNK_TAG => {
+                    StoredVoxelChunk::NonUniform(read_non_uniform_chunk_voxels(reader)?)
+                }
+                _ => bail!("Invalid chunk tag {tag}"),
+            };
+            chunks.push(chunk);
+        }
+
+        let thermal_state = if version >= FIRST_VERSION_WITH_THERMAL_STATE {
+            read_thermal_state(reader, &chunk_counts)?
+        } else {
+            VoxelObjectThermalState::new()
+        };
+
+        Ok(Self {
+            voxel_extent,
+            chunk_counts,
+            origin_offset_in_root,
+            chunks,
+            thermal_state,
+        })
+    }
+}
+
+impl ChunkedVoxelGenerator for StoredVoxelObject {
+    type ChunkGenerationBuffers<AB: Allocator> = ();
+
+    fn voxel_extent(&self) -> f32 {
+        self.voxel_extent
+    }
+
+    fn grid_shape(&self) -> [usize; 3] {
+        self.chunk_counts.map(|count| count * CHUNK_SIZE)
+    }
+
+    fn total_buffer_size(&self) -> usize {
+        0
+    }
+
+    fn create_buffers_in<AB: Allocator>(&self, _alloc: AB) -> Self::ChunkGenerationBuffers<AB> {}
+
+    fn generate_chunk<AB: Allocator>(
+        &self,
+        _buffers: &mut Self::ChunkGenerationBuffers<AB>,
+        voxels: &mut [Voxel],
+        chunk_origin: &[usize; 3],
+    ) -> ChunkSparseness {
+        let chunk_idx =
+            (chunk_origin[0] / CHUNK_SIZE) * self.chunk_counts[2] * self.chunk_counts[1]
+                + (chunk_origin[1] / CHUNK_SIZE) * self.chunk_counts[2]
+                + chunk_origin[2] / CHUNK_SIZE;
+
+        match &self.chunks[chunk_idx] {
+            StoredVoxelChunk::Void => {
+                voxels.fill(Voxel::maximally_outside());
+            }
+            StoredVoxelChunk::Uniform(voxel) => {
+                voxels.fill(*voxel);
+            }
+            StoredVoxelChunk::NonUniform(stored_voxels) => {
+                voxels.copy_from_slice(stored_voxels);
+            }
+        }
+
+        ChunkSparseness {
+            has_only_empty_voxels: voxels.iter().all(Voxel::is_empty),
+            is_void: voxels
+                .iter()
+                .all(|voxel| voxel.signed_distance().is_maximally_outside()),
+        }
+    }
+}
+
+fn write_non_uniform_chunk_voxels(
+    writer: &mut impl Write,
+    voxels: &[Voxel],
+    palette: &mut Vec<[u8; 3]>,
+    palette_indices: &mut HashMap<[u8; 3], u16>,
+) -> Result<()> {
+    palette.clear();
+    palette_indices.clear();
+
+    let mut runs: Vec<(u16, u16)> = Vec::new();
+
+    for voxel in voxels {
+        let encoded_voxel = encode_voxel(voxel);
+
+        let palette_idx = *palette_indices.entry(encoded_voxel).or_insert_with(|| {
+            palette.push(encoded_voxel);
+            (palette.len() - 1) as u16
+        });
+
+        match runs.last_mut() {
+            Some((length, idx)) if *idx == palette_idx => {
+                *length += 1;
+            }
+            _ => {
+                runs.push((1, palette_idx));
+            }
+        }
+    }
+
+    write_u16(writer, palette.len())?;
+    for encoded_voxel in palette.iter() {
+        writer.write_all(encoded_voxel)?;
+    }
+
+    write_u16(writer, runs.len())?;
+    for (length, palette_idx) in runs {
+        writer.write_all(&length.to_le_bytes())?;
+        writer.write_all(&palette_idx.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+fn read_non_uniform_chunk_voxels(reader: &mut impl Read) -> Result<Box<[Voxel]>> {
+    let palette_len = usize::from(u16::from_le_bytes(read_array(reader)?));
+
+    let palette = (0..palette_len)
+        .map(|_| Ok(decode_voxel(read_array(reader)?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let run_count = u16::from_le_bytes(read_array(reader)?);
+
+    let mut voxels = Vec::with_capacity(CHUNK_VOXEL_COUNT);
+
+    for _ in 0..run_count {
+        let length = usize::from(u16::from_le_bytes(read_array(reader)?));
+        let palette_idx = usize::from(u16::from_le_bytes(read_array(reader)?));
+
+        let Some(&voxel) = palette.get(palette_idx) else {
+            bail!("Palette index {palette_idx} out of bounds for palette of length {palette_len}");
+        };
+        if voxels.len() + length > CHUNK_VOXEL_COUNT {
+            bail!("Voxel runs exceed chunk size");
+        }
+
+        voxels.resize(voxels.len() + length, voxel);
+    }
+
+    if voxels.len() != CHUNK_VOXEL_COUNT {
+        bail!(
+            "Voxel runs cover {} voxels, expected {CHUNK_VOXEL_COUNT}",
+            voxels.len()
+        );
+    }
+
+    Ok(voxels.into_boxed_slice())
+}
+
+/// Only the emptiness flag is stored, since the remaining flags are derived.
+fn encode_voxel(voxel: &Voxel) -> [u8; 3] {
+    let flags = voxel.flags() & VoxelFlags::IS_EMPTY;
+    [
+        voxel.voxel_type().idx_u8(),
+        voxel.signed_distance().encoded as u8,
+        flags.bits(),
+    ]
+}
+
+fn decode_voxel([voxel_type, signed_distance, flags]: [u8; 3]) -> Voxel {
+    Voxel::new(
+        VoxelType::from_idx_u8(voxel_type),
+        VoxelFlags::from_bits_retain(flags) & VoxelFlags::IS_EMPTY,
+        VoxelSignedDistance::from_encoded(signed_distance as i8),
+    )
+}
+
+fn write_u16(writer: &mut impl Write, value: usize) -> Result<()> {
+    let value = u16::try_from(value).context("Value does not fit in 16 bits")?;
+    writer.write_all(&value.to_le_bytes())?;
+    Ok(())
+}
+
+fn write_thermal_state(
+    writer: &mut impl Write,
+    thermal_state: &VoxelObjectThermalState,
+) -> Result<()> {
+    // Sort the chunks so that the same state always gives the same bytes
+    let mut tracked_chunks: Vec<_> = thermal_state.tracked_chunk_temperatures().collect();
+    tracked_chunks.sort_unstable_by_key(|(chunk_indices, _)| **chunk_indices);
+
+    write_u32(writer, tracked_chunks.len())?;
+
+    for (chunk_indices, temperatures) in tracked_chunks {
+        for &index in chunk_indices {
+            write_u32(writer, index)?;
+        }
+        for temperature in temperatures {
+            writer.write_all(&temperature.to_le_bytes())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn read_thermal_state(
+    reader: &mut impl Read,
+    chunk_counts: &[usize; 3],
+) -> Result<VoxelObjectThermalState> {
+    let total_chunk_count: usize = chunk_counts.iter().product();
+
+    let tracked_chunk_count = read_u32(reader)? as usize;
+    if tracked_chunk_count > total_chunk_count {
+        bail!(
+            "Thermal state tracks {tracked_chunk_count} chunks, but the object only has \
+             {total_chunk_count}"
+        );
+    }
+
+    let mut thermal_state = VoxelObjectThermalState::new();
+
+    for _ in 0..tracked_chunk_count {
+        let chunk_indices = [
+            read_u32(reader)? as usize,
+            read_u32(reader)? as usize,
+            read_u32(reader)? as usize,
+        ];
+        if chunk_indices
+            .iter()
+            .zip(chunk_counts)
+            .any(|(&index, &count)| index >= count)
+        {
+            bail!("Thermal state chunk indices {chunk_indices:?} are out of bounds");
+        }
+
+        let mut temperatures = Box::new([0.0; CHUNK_VOXEL_COUNT]);
+        for temperature in temperatures.iter_mut() {
+            *temperature = f32::from_le_bytes(read_array(reader)?);
+            if !(temperature.is_finite() && *temperature >= 0.0) {
+                bail!("Invalid voxel temperature {temperature}");
+            }
+        }
+
+        thermal_state.set_chunk_temperatures(chunk_indices, temperatures);
+    }
+
+    Ok(thermal_state)
+}
+
+fn write_u32(writer: &mut impl Write, value: usize) -> Result<()> {
+    let value = u32::try_from(value).context("Value does not fit in 32 bits")?;
+    writer.write_all(&value.to_le_bytes())?;
+    Ok(())
+}
+
+fn write_i32(writer: &mut impl Write, value: isize) -> Result<()> {
+    let value = i32::try_from(value).context("Value does not fit in 32 bits")?;
+    writer.write_all(&value.to_le_bytes())?;
+    Ok(())
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32> {
+    Ok(u32::from_le_bytes(read_array(reader)?))
+}
+
+fn read_i32(reader: &mut impl Read) -> Result<i32> {
+    Ok(i32::from_le_bytes(read_array(reader)?))
+}
+
+fn read_array<const N: usize>(reader: &mut impl Read) -> Result<[u8; N]> {
+    let mut bytes = [0; N];
+    reader
+        .read_exact(&mut bytes)
+        .context("Unexpected end of voxel object data")?;
+    Ok(bytes)
+}
+
+#[cfg(not(miri))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        generation::{
+            SDFVoxelGenerator,
+            sdf::{SDFGraph, SDFNode},
+            voxel_type::SameVoxelTypeGenerator,
+        },
+        mesh::VoxelObjectMesh,
+        object::{
+            chunk_voxel_indices_from_linear_idx, deposition::ChunkGridPadding,
+            inertia::VoxelObjectInertialPropertyManager,
+        },
+        voxel_types::VoxelThermalProperties,
+    };
+    use approx::assert_abs_diff_eq;
+    use impact_alloc::Global;
+    use impact_geometry::Sphere;
+    use impact_math::point::Point3;
+
+    fn generate_sphere_object(radius: f32) -> VoxelObject {
+        let mut graph = SDFGraph::new_in(Global);
+        graph.add_node(SDFNode::new_sphere(radius));
+        let sdf_generator = graph.build_in(Global).unwrap();
+
+        let generator = SDFVoxelGenerator::new(
+            0.5,
+            sdf_generator,
+            SameVoxelTypeGenerator::new(VoxelType::default()).into(),
+        );
+        VoxelObject::generate(VoxelObjectBuffers::new(), &generator)
+    }
+
+    fn round_trip(object: &VoxelObject) -> (VoxelObject, usize) {
+        let mut bytes = Vec::new();
+        write_voxel_object(object, &mut bytes).unwrap();
+        let read_object =
+            read_voxel_object(VoxelObjectBuffers::new(), &mut bytes.as_slice()).unwrap();
+        (read_object, bytes.len())
+    }
+
+    fn assert_objects_have_identical_meshes_and_inertia(
+        object: &VoxelObject,
+        read_object: &VoxelObject,
+    ) {
+        assert_eq!(read_object.voxel_extent(), object.voxel_extent());
+        assert_eq!(read_object.chunk_counts(), object.chunk_counts());
+        assert_eq!(
+            read_object.origin_offset_in_root(),
+            object.origin_offset_in_root()
+        );
+
+        let mesh = VoxelObjectMesh::create(object);
+        let read_mesh = VoxelObjectMesh::create(read_object);
+        assert_eq!(read_mesh.positions(), mesh.positions());
+        assert_eq!(read_mesh.normal_vectors(), mesh.normal_vectors());
+        assert_eq!(read_mesh.indices(), mesh.indices());
+
+        let voxel_type_densities = [1.0; 256];
+        assert_abs_diff_eq!(
+            VoxelObjectInertialPropertyManager::initialized_from(
+                read_object,
+                &voxel_type_densities
+            ),
+            VoxelObjectInertialPropertyManager::initialized_from(object, &voxel_type_densities),
+            epsilon = 1e-6
+        );
+
+        read_object.validate_adjacencies();
+        read_object.validate_chunk_obscuredness();
+        read_object.validate_occupied_voxel_ranges();
+        read_object.validate_region_count();
+    }
+
+    #[test]
+    fn reading_written_generated_object_gives_identical_object() {
+        let object = generate_sphere_object(10.0);
+
+        let (read_object, byte_count) = round_trip(&object);
+
+        assert_objects_have_identical_meshes_and_inertia(&object, &read_object);
+
+        // The palette and run-length encoding should compress the voxels well
+        assert!(byte_count < object.voxels.len() * 3 / 4);
+    }
+
+    #[test]
+    fn reading_written_damaged_object_gives_identical_object() {
+        let mut object = generate_sphere_object(10.0);
+
+        // Carve a hole into the surface of the sphere
+        let center = object.chunk_counts()[0] as f32 * CHUNK_SIZE as f32 * 0.5;
+        let carving_sphere = Sphere::new(Point3::new(center + 18.0, center, center), 8.0);
+        object.modify_voxels_within_sphere(&carving_sphere, &mut |_, squared_distance, voxel| {
+            let new_signed_distance = voxel
+                .signed_distance()
+                .to_f32()
+                .max(8.0 - squared_distance.sqrt());
+            voxel.set_signed_distance(new_signed_distance, &mut |_| {});
+        });
+        object.resolve_connected_regions_between_all_chunks();
+
+        let (read_object, _) = round_trip(&object);
+
+        assert_objects_have_identical_meshes_and_inertia(&object, &read_object);
+    }
+
+    #[test]
+    fn reading_written_object_grown_downward_keeps_negative_origin_offset() {
+        let mut object = generate_sphere_object(10.0);
+        object.grow_chunk_grid(&ChunkGridPadding {
+            lower: [1, 0, 2],
+            upper: [0; 3],
+        });
+        assert!(object.origin_offset_in_root()[0] < 0.0);
+
+        let (read_object, _) = round_trip(&object);
+
+        assert_objects_have_identical_meshes_and_inertia(&object, &read_object);
+    }
+
+    #[test]
+    fn reading_written_heated_object_preserves_temperatures() {
+        let mut object = generate_sphere_object(10.0);
+
+        let center = object.chunk_counts()[0] as f32 * CHUNK_SIZE as f32 * 0.5;
+        let surface_point = Point3::new(center + 10.0, center, center);
+        let thermal_properties = [VoxelThermalProperties {
+            thermal_conductivity: 1.0,
+            specific_heat_capacity: 1000.0,
+            melting_temperature: None,
+        }];
+        object.add_heat_near_point(
+            Global,
+            &surface_point,
+            3.0,
+            1e9,
+            &[1000.0],
+            &thermal_properties,
+        );
+        assert!(object.has_thermal_activity());
+
+        let (read_object, _) = round_trip(&object);
+
+        assert_objects_have_identical_meshes_and_inertia(&object, &read_object);
+
+        let thermal_state = object.thermal_state();
+        let read_thermal_state = read_object.thermal_state();
+        assert_eq!(
+            read_thermal_state.tracked_chunk_count(),
+            thermal_state.tracked_chunk_count()
+        );
+        for (chunk_indices, temperatures) in thermal_state.tracked_chunk_temperatures() {
+            for (voxel_idx, &temperature) in temperatures.iter().enumerate() {
+                let [i, j, k] = chunk_indices.map(|index| index * CHUNK_SIZE);
+                let [di, dj, dk] = chunk_voxel_indices_from_linear_idx(voxel_idx);
+                assert_eq!(
+                    read_thermal_state.temperature(&[i + di, j + dj, k + dk]),
+                    temperature
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn reading_version_1_data_gives_object_at_ambient_temperature() {
+        let object = generate_sphere_object(4.0);
+
+        let mut bytes = Vec::new();
+        write_voxel_object(&object, &mut bytes).unwrap();
+
+        // Version 1 has no thermal state, which is just a zero tracked chunk
+        // count for an object at ambient temperature
+        bytes[4..8].copy_from_slice(&1_u32.to_le_bytes());
+        bytes.truncate(bytes.len() - 4);
+
+        let read_object =
+            read_voxel_object(VoxelObjectBuffers::new(), &mut bytes.as_slice()).unwrap();
+
+        assert_objects_have_identical_meshes_and_inertia(&object, &read_object);
+        assert!(read_object.thermal_state().is_at_ambient_temperature());
+    }
+
+    #[test]
+    fn reading_data_with_wrong_magic_fails() {
+        let object = generate_sphere_object(4.0);
+
+        let mut bytes = Vec::new();
+        write_voxel_object(&object, &mut bytes).unwrap();
+        bytes[0] = b'X';
+
+        assert!(read_voxel_object(VoxelObjectBuffers::new(), &mut bytes.as_slice()).is_err());
+    }
+
+    #[test]
+    fn reading_truncated_data_fails() {
+        let object = generate_sphere_object(4.0);
+
+        let mut bytes = Vec::new();
+        write_voxel_object(&object, &mut bytes).unwrap();
+        bytes.truncate(bytes.len() - 1);
+
+        assert!(read_voxel_object(VoxelObjectBuffers::new(), &mut bytes.as_slice()).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 //! Signed distance field for voxel objects.
 
+pub mod lod;
 pub mod surface_nets;
 
 use crate::{
@@ -27,6 +28,10 @@ pub struct VoxelChunkSignedDistanceField {
     values: [f32; SDF_GRID_CELL_COUNT],
     voxel_types: [VoxelType; SDF_GRID_CELL_COUNT],
     adjacent_is_non_uniform: [[bool; 2]; 3],
+    /// The number of grid cells along each axis that are in use, starting from
+    /// the lower corner. This is smaller than the full grid size when the SDF
+    /// holds a downsampled version of a chunk.
+    active_grid_size: usize,
 }
 
 /// The number of grid cells holding a signed distance in the SDF grid for a
@@ -92,9 +97,19 @@ impl VoxelChunkSignedDistanceField {
             values: [0.0; SDF_GRID_CELL_COUNT],
             voxel_types: [VoxelType::dummy(); SDF_GRID_CELL_COUNT],
             adjacent_is_non_uniform: [[false; 2]; 3],
+            active_grid_size: SDF_GRID_SIZE,
         }
     }
 
+    /// The number of grid cells along each axis that are currently in use,
+    /// starting from the lower corner of the grid. This equals
+    /// [`Self::grid_size`] unless the SDF holds a downsampled version of a
+    /// chunk.
+    #[inline]
+    pub fn active_grid_size(&self) -> usize {
+        self.active_grid_size
+    }
+
     #[inline]
     pub fn get_value(&self, i: usize, j: usize, k: usize) -> Option<f32> {
         self.values.get(Self::linear_idx(&[i, j, k])).copied()
@@ -194,6 +209,8 @@ impl VoxelObject {
 
         match &self.chunks[chunk_idx] {
             VoxelChunk::NonUniform(chunk) if chunk.flags.has_exposed_face() => {
+                sdf.active_grid_size = SDF_GRID_SIZE;
+
                 if chunk_indices[0] > 0
                     && chunk_indices[0] < self.chunk_counts[0] - 1
                     && chunk_indices[1] > 0
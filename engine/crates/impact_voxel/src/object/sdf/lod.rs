@@ -0,0 +1,279 @@
+//! Downsampled signed distance fields for meshing voxel chunks at reduced
+//! levels of detail.
+
+use crate::{
+    Voxel,
+    object::{CHUNK_SIZE, VoxelObject, sdf::VoxelChunkSignedDistanceField},
+    voxel_types::VoxelType,
+};
+
+/// The coarsest level of detail a voxel chunk can be meshed at. At level of
+/// detail `l`, each voxel in the downsampled grid covers `2^l` voxels of the
+/// original grid along each axis.
+pub const MAX_VOXEL_CHUNK_LOD: usize = 3;
+
+/// The number of levels of detail a voxel chunk can be meshed at, including
+/// the full-detail level.
+pub const VOXEL_CHUNK_LOD_COUNT: usize = MAX_VOXEL_CHUNK_LOD + 1;
+
+/// The number of voxels the region sampled for a chunk extends beyond the
+/// chunk on each side. This is the size of a single voxel at the coarsest
+/// level of detail, which is the padding required for its downsampled grid.
+const REGION_PADDING: usize = 1 << MAX_VOXEL_CHUNK_LOD;
+
+/// The number of voxels along each axis of the region sampled for a chunk.
+const REGION_SIZE: usize = CHUNK_SIZE + 2 * REGION_PADDING;
+
+/// Signed distances and voxel types for the region around a single voxel chunk
+/// in a [`VoxelObject`], downsampled to every level of detail.
+///
+/// The region covers the chunk and enough of its neighborhood to fill the
+/// one-voxel padding of the chunk's SDF at every level of detail, so that the
+/// meshes of adjacent chunks at the same level of detail connect seamlessly.
+#[derive(Clone, Debug)]
+pub struct VoxelChunkLODRegion {
+    levels: [DownsampledRegion; VOXEL_CHUNK_LOD_COUNT],
+}
+
+/// The region around a chunk at a single level of detail. The signed distance
+/// of each downsampled voxel is the average of the signed distances of the
+/// voxels it covers, and its type is the most common type among the non-empty
+/// voxels it covers.
+#[derive(Clone, Debug)]
+struct DownsampledRegion {
+    size: usize,
+    values: Vec<f32>,
+    voxel_types: Vec<VoxelType>,
+    /// The number of non-empty voxels covered by each downsampled voxel.
+    non_empty_counts: Vec<u16>,
+}
+
+impl VoxelChunkLODRegion {
+    /// Creates a new region buffer.
+    pub fn new() -> Self {
+        Self {
+            levels: std::array::from_fn(|lod| DownsampledRegion::new(REGION_SIZE >> lod)),
+        }
+    }
+
+    /// Fills the region with the voxels around the chunk at the given indices
+    /// in the given object's chunk grid and downsamples them to every level of
+    /// detail. Voxels outside the object's voxel grid are treated as
+    /// maximally outside the object.
+    pub fn fill_for_chunk(&mut self, object: &VoxelObject, chunk_indices: &[usize; 3]) {
+        let grid_voxel_counts = object.chunk_counts().map(|count| count * CHUNK_SIZE);
+
+        let region_lower_voxel_indices =
+            chunk_indices.map(|idx| (idx * CHUNK_SIZE) as isize - REGION_PADDING as isize);
+
+        let full_detail = &mut self.levels[0];
+
+        let mut idx = 0;
+        for i in 0..REGION_SIZE {
+            let voxel_i = region_lower_voxel_indices[0] + i as isize;
+            for j in 0..REGION_SIZE {
+                let voxel_j = region_lower_voxel_indices[1] + j as isize;
+                for k in 0..REGION_SIZE {
+                    let voxel_k = region_lower_voxel_indices[2] + k as isize;
+
+                    let voxel = if voxel_i < 0
+                        || voxel_j < 0
+                        || voxel_k < 0
+                        || voxel_i as usize >= grid_voxel_counts[0]
+                        || voxel_j as usize >= grid_voxel_counts[1]
+                        || voxel_k as usize >= grid_voxel_counts[2]
+                    {
+                        Voxel::maximally_outside()
+                    } else {
+                        object.voxel(voxel_i as usize, voxel_j as usize, voxel_k as usize)
+                    };
+
+                    full_detail.values[idx] = voxel.signed_distance().to_f32();
+                    full_detail.voxel_types[idx] = voxel.voxel_type();
+                    full_detail.non_empty_counts[idx] = u16::from(!voxel.is_empty());
+
+                    idx += 1;
+                }
+            }
+        }
+
+        for lod in 1..VOXEL_CHUNK_LOD_COUNT {
+            let (finer_levels, coarser_levels) = self.levels.split_at_mut(lod);
+            coarser_levels[0].downsample_from(&finer_levels[lod - 1]);
+        }
+    }
+
+    /// Fills the given SDF with the signed distances and voxel types of the
+    /// chunk at the given level of detail, including the one-voxel padding. The
+    /// signed distances are expressed in units of the downsampled voxel extent.
+    /// The information about adjacent chunks is copied from the given
+    /// full-detail SDF for the same chunk.
+    ///
+    /// # Panics
+    /// If the level of detail is zero or exceeds [`MAX_VOXEL_CHUNK_LOD`].
+    pub fn fill_sdf_for_lod(
+        &self,
+        lod: usize,
+        full_detail_sdf: &VoxelChunkSignedDistanceField,
+        sdf: &mut VoxelChunkSignedDistanceField,
+    ) {
+        assert!((1..=MAX_VOXEL_CHUNK_LOD).contains(&lod));
+
+        let level = &self.levels[lod];
+        let inverse_voxel_scale = 1.0 / (1 << lod) as f32;
+
+        let grid_size = (CHUNK_SIZE >> lod) + 2;
+
+        // The first downsampled voxel in the chunk's padded grid is the one
+        // just below the chunk
+        let offset = (REGION_PADDING >> lod) - 1;
+
+        for i in 0..grid_size {
+            for j in 0..grid_size {
+                for k in 0..grid_size {
+                    let level_idx = level.linear_idx(i + offset, j + offset, k + offset);
+                    let sdf_idx = VoxelChunkSignedDistanceField::linear_idx(&[i, j, k]);
+                    sdf.values[sdf_idx] = level.values[level_idx] * inverse_voxel_scale;
+                    sdf.voxel_types[sdf_idx] = level.voxel_types[level_idx];
+                }
+            }
+        }
+
+        sdf.active_grid_size = grid_size;
+        sdf.adjacent_is_non_uniform = full_detail_sdf.adjacent_is_non_uniform;
+    }
+}
+
+impl Default for VoxelChunkLODRegion {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DownsampledRegion {
+    fn new(size: usize) -> Self {
+        let cell_count = size.pow(3);
+        Self {
+            size,
+            values: vec![0.0; cell_count],
+            voxel_types: vec![VoxelType::dummy(); cell_count],
+            non_empty_counts: vec![0; cell_count],
+        }
+    }
+
+    fn linear_idx(&self, i: usize, j: usize, k: usize) -> usize {
+        (i * self.size + j) * self.size + k
+    }
+
+    fn downsample_from(&mut self, finer: &Self) {
+        debug_assert_eq!(2 * self.size, finer.size);
+
+        for i in 0..self.size {
+            for j in 0..self.size {
+                for k in 0..self.size {
+                    let mut value_sum = 0.0;
+                    let mut non_empty_count = 0;
+
+                    // Voxel types and their accumulated non-empty counts for
+                    // the eight finer voxels
+                    let mut type_counts = [(VoxelType::dummy(), 0_u16); 8];
+                    let mut type_count = 0;
+
+                    for di in 0..2 {
+                        for dj in 0..2 {
+                            for dk in 0..2 {
+                                let finer_idx =
+                                    finer.linear_idx(2 * i + di, 2 * j + dj, 2 * k + dk);
+
+                                value_sum += finer.values[finer_idx];
+
+                                let count = finer.non_empty_counts[finer_idx];
+                                if count == 0 {
+                                    continue;
+                                }
+                                non_empty_count += count;
+
+                                let voxel_type = finer.voxel_types[finer_idx];
+                                if let Some((_, accumulated)) = type_counts[..type_count]
+                                    .iter_mut()
+                                    .find(|(existing, _)| *existing == voxel_type)
+                                {
+                                    *accumulated += count;
+                                } else {
+                                    type_counts[type_count] = (voxel_type, count);
+                                    type_count += 1;
+                                }
+                            }
+                        }
+                    }
+
+                    let voxel_type = type_counts[..type_count]
+                        .iter()
+                        .max_by_key(|(_, count)| *count)
+                        .map_or_else(
+                            || finer.voxel_types[finer.linear_idx(2 * i, 2 * j, 2 * k)],
+                            |(voxel_type, _)| *voxel_type,
+                        );
+
+                    let idx = self.linear_idx(i, j, k);
+                    self.values[idx] = 0.125 * value_sum;
+                    self.voxel_types[idx] = voxel_type;
+                    self.non_empty_counts[idx] = non_empty_count;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(miri))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object::{VoxelObjectBuffers, tests::OffsetBoxVoxelGenerator};
+
+    #[test]
+    fn downsampled_sdf_of_fully_enclosed_chunk_is_negative() {
+        let generator = OffsetBoxVoxelGenerator::with_default([3 * CHUNK_SIZE; 3]);
+        let object = VoxelObject::generate(VoxelObjectBuffers::new(), &generator);
+
+        let mut region = VoxelChunkLODRegion::new();
+        region.fill_for_chunk(&object, &[1, 1, 1]);
+
+        let full_detail_sdf = VoxelChunkSignedDistanceField::new();
+        let mut sdf = VoxelChunkSignedDistanceField::new();
+
+        for lod in 1..=MAX_VOXEL_CHUNK_LOD {
+            region.fill_sdf_for_lod(lod, &full_detail_sdf, &mut sdf);
+
+            let grid_size = sdf.active_grid_size();
+            assert_eq!(grid_size, (CHUNK_SIZE >> lod) + 2);
+
+            for i in 0..grid_size {
+                for j in 0..grid_size {
+                    for k in 0..grid_size {
+                        assert!(sdf.get_value(i, j, k).unwrap() < 0.0);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn downsampled_sdf_outside_object_is_positive() {
+        let generator = OffsetBoxVoxelGenerator::with_default([CHUNK_SIZE; 3]);
+        let object = VoxelObject::generate(VoxelObjectBuffers::new(), &generator);
+
+        let mut region = VoxelChunkLODRegion::new();
+        region.fill_for_chunk(&object, &[0, 0, 0]);
+
+        let full_detail_sdf = VoxelChunkSignedDistanceField::new();
+        let mut sdf = VoxelChunkSignedDistanceField::new();
+
+        for lod in 1..=MAX_VOXEL_CHUNK_LOD {
+            region.fill_sdf_for_lod(lod, &full_detail_sdf, &mut sdf);
+
+            // The lower corner of the padding lies outside the object's grid
+            assert!(sdf.get_value(0, 0, 0).unwrap() > 0.0);
+        }
+    }
+}
@@ -155,9 +155,11 @@ impl VoxelChunkSignedDistanceField {
         position_offset: &Vector3,
         buffer: &mut SurfaceNetsBuffer,
     ) {
-        for i in 0..Self::grid_size_u32() - 1 {
-            for j in 0..Self::grid_size_u32() - 1 {
-                for k in 0..Self::grid_size_u32() - 1 {
+        let cube_count = self.active_grid_size as u32 - 1;
+
+        for i in 0..cube_count {
+            for j in 0..cube_count {
+                for k in 0..cube_count {
                     let linear_idx = Self::linear_idx_u32(&[i, j, k]);
                     if let Some((centroid, normal, vertex_materials)) =
                         self.estimate_surface_net_vertex_attributes_in_cube(linear_idx)
@@ -249,7 +251,7 @@ impl VoxelChunkSignedDistanceField {
     // are facing the right way. See the comments on `maybe_make_quad` to help
     // with understanding the indexing.
     fn make_all_surface_nets_quads(&self, buffer: &mut SurfaceNetsBuffer) {
-        let mut upper_indices = [Self::grid_size_u32() as u8 - 1; 3];
+        let mut upper_indices = [self.active_grid_size as u8 - 1; 3];
 
         // To avoid z-fighting due to triangles duplicated between adjacent chunks,
         // we avoid generating triangles from the upper voxels for every dimension where
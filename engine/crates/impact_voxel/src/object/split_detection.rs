@@ -638,6 +638,23 @@ impl SplitDetector {
             ));
     }
 
+    /// Extends the buffers to account for a new non-uniform chunk having been
+    /// added to the end of the voxel buffer (for example when a void chunk is
+    /// filled with voxels). The chunk's local regions must be updated before
+    /// they are used.
+    pub fn add_non_uniform_chunk(&mut self) {
+        self.voxel_region_labels
+            .extend(iter::repeat_n(EMPTY_VOXEL_LABEL, CHUNK_VOXEL_COUNT));
+
+        self.regions
+            .extend(iter::repeat_n(LocalRegion::zeroed(), CHUNK_MAX_REGIONS));
+
+        self.adjacent_region_connections.extend(iter::repeat_n(
+            AdjacentRegionConnection::zero(),
+            CHUNK_MAX_ADJACENT_REGION_CONNECTIONS,
+        ));
+    }
+
     /// Analyzes the given non-uniform chunk to determine all regions of
     /// connected voxels within the chunk. This should be called whenever any
     /// voxels in the chunk have changed state between present and empty.
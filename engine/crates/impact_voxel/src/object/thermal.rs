@@ -0,0 +1,819 @@
+//! Thermal state of voxel objects.
+
+use crate::{
+    object::{
+        CHUNK_SIZE, CHUNK_VOXEL_COUNT, VoxelObject, chunk_indices_from_object_voxel_indices,
+        chunk_voxel_indices_from_linear_idx, linear_voxel_idx_within_chunk,
+        linear_voxel_idx_within_chunk_from_object_voxel_indices,
+    },
+    voxel_types::VoxelThermalProperties,
+};
+use impact_alloc::{AVec, Allocator};
+use impact_containers::{HashMap, HashSet};
+use impact_math::point::Point3;
+use std::mem;
+
+/// The temperature (in K) of every voxel whose temperature is not explicitly
+/// tracked, and of the surroundings that exposed voxels exchange heat with.
+pub const AMBIENT_TEMPERATURE: f32 = 293.15;
+
+/// When no voxel in a chunk deviates more than this from
+/// [`AMBIENT_TEMPERATURE`] (in K), the temperatures of the chunk are no longer
+/// tracked.
+const TRACKED_TEMPERATURE_DEVIATION: f32 = 0.5;
+
+/// The maximum number of explicit substeps a single heat diffusion step is
+/// divided into. If more substeps would be required for stability, the
+/// simulated duration is shortened instead.
+const MAX_DIFFUSION_SUBSTEPS: usize = 16;
+
+/// Sparse temperature field for the voxels of a [`VoxelObject`].
+///
+/// Temperatures are only stored for chunks containing voxels that deviate
+/// noticeably from [`AMBIENT_TEMPERATURE`], so objects that are not being
+/// heated carry no thermal data at all.
+#[derive(Clone, Debug, Default)]
+pub struct VoxelObjectThermalState {
+    chunks: HashMap<[usize; 3], ChunkThermalState>,
+}
+
+#[derive(Clone, Debug)]
+struct ChunkThermalState {
+    temperatures: Box<[f32; CHUNK_VOXEL_COUNT]>,
+    /// An upper bound for how much the temperature of any voxel in the chunk
+    /// has changed since the chunk's mesh was last invalidated.
+    temperature_change_since_mesh_invalidation: f32,
+}
+
+impl VoxelObjectThermalState {
+    /// Creates a new thermal state where all voxels are at
+    /// [`AMBIENT_TEMPERATURE`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether all voxels are at [`AMBIENT_TEMPERATURE`].
+    pub fn is_at_ambient_temperature(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Returns the number of chunks whose voxel temperatures are currently
+    /// tracked.
+    pub fn tracked_chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Returns the temperature (in K) of the voxel at the given indices in the
+    /// object's voxel grid. The temperature of empty voxels is meaningless.
+    #[inline]
+    pub fn temperature(&self, object_voxel_indices: &[usize; 3]) -> f32 {
+        let [i, j, k] = *object_voxel_indices;
+        self.chunks
+            .get(&chunk_indices_from_object_voxel_indices(i, j, k))
+            .map_or(AMBIENT_TEMPERATURE, |chunk| {
+                chunk.temperatures[linear_voxel_idx_within_chunk_from_object_voxel_indices(i, j, k)]
+            })
+    }
+
+    /// Offsets the chunk indices of all tracked chunks by the given amounts,
+    /// to account for chunks being added at the lower end of the object's
+    /// chunk grid.
+    pub fn shift_chunk_indices(&mut self, chunk_shift: [usize; 3]) {
+        if chunk_shift == [0; 3] {
+            return;
+        }
+        self.chunks = mem::take(&mut self.chunks)
+            .into_iter()
+            .map(|(chunk_indices, chunk)| {
+                (
+                    [
+                        chunk_indices[0] + chunk_shift[0],
+                        chunk_indices[1] + chunk_shift[1],
+                        chunk_indices[2] + chunk_shift[2],
+                    ],
+                    chunk,
+                )
+            })
+            .collect();
+    }
+
+    /// Resets all voxels to [`AMBIENT_TEMPERATURE`].
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+    }
+
+    /// Returns an iterator over the indices and voxel temperatures of all
+    /// chunks whose temperatures are currently tracked, in no particular
+    /// order.
+    pub fn tracked_chunk_temperatures(
+        &self,
+    ) -> impl Iterator<Item = (&[usize; 3], &[f32; CHUNK_VOXEL_COUNT])> {
+        self.chunks
+            .iter()
+            .map(|(chunk_indices, chunk)| (chunk_indices, &*chunk.temperatures))
+    }
+
+    /// Sets the temperatures of the voxels in the chunk with the given indices
+    /// and starts tracking them.
+    pub fn set_chunk_temperatures(
+        &mut self,
+        chunk_indices: [usize; 3],
+        temperatures: Box<[f32; CHUNK_VOXEL_COUNT]>,
+    ) {
+        self.tracked_chunk_mut(chunk_indices).temperatures = temperatures;
+    }
+
+    fn tracked_chunk_mut(&mut self, chunk_indices: [usize; 3]) -> &mut ChunkThermalState {
+        self.chunks
+            .entry(chunk_indices)
+            .or_insert_with(ChunkThermalState::at_ambient_temperature)
+    }
+}
+
+impl ChunkThermalState {
+    fn at_ambient_temperature() -> Self {
+        Self {
+            temperatures: ambient_chunk_temperatures(),
+            temperature_change_since_mesh_invalidation: 0.0,
+        }
+    }
+
+    fn max_deviation_from_ambient(&self) -> f32 {
+        self.temperatures
+            .iter()
+            .map(|&temperature| (temperature - AMBIENT_TEMPERATURE).abs())
+            .fold(0.0, f32::max)
+    }
+}
+
+impl VoxelObject {
+    /// Returns a reference to the object's [`VoxelObjectThermalState`].
+    pub fn thermal_state(&self) -> &VoxelObjectThermalState {
+        &self.thermal_state
+    }
+
+    /// Whether any voxel in the object deviates from
+    /// [`AMBIENT_TEMPERATURE`].
+    pub fn has_thermal_activity(&self) -> bool {
+        !self.thermal_state.is_at_ambient_temperature()
+    }
+
+    /// Computes the temperature (in K) at the given object space position as
+    /// the average temperature of the non-empty voxels among the eight voxels
+    /// whose centers surround the position. This is intended for assigning
+    /// temperatures to mesh vertices.
+    pub fn compute_temperature_at_position(&self, position: &[f32; 3]) -> f32 {
+        if self.thermal_state.is_at_ambient_temperature() {
+            return AMBIENT_TEMPERATURE;
+        }
+
+        let lower_indices =
+            position.map(|coord| (coord * self.inverse_voxel_extent - 0.5).floor() as isize);
+
+        let mut temperature_sum = 0.0;
+        let mut count = 0;
+
+        for di in 0..2 {
+            for dj in 0..2 {
+                for dk in 0..2 {
+                    let indices = [
+                        lower_indices[0] + di,
+                        lower_indices[1] + dj,
+                        lower_indices[2] + dk,
+                    ];
+                    if indices.iter().any(|&idx| idx < 0) {
+                        continue;
+                    }
+                    let indices = indices.map(|idx| idx as usize);
+
+                    if self
+                        .get_voxel_if_occupied(indices[0], indices[1], indices[2])
+                        .is_some()
+                    {
+                        temperature_sum += self.thermal_state.temperature(&indices);
+                        count += 1;
+                    }
+                }
+            }
+        }
+
+        if count == 0 {
+            AMBIENT_TEMPERATURE
+        } else {
+            temperature_sum / count as f32
+        }
+    }
+
+    /// Distributes the given amount of thermal energy (in J) evenly among the
+    /// non-empty voxels whose centers lie within the given distance of the
+    /// given point, and raises their temperatures accordingly. Voxels whose
+    /// type has infinite heat capacity are not heated. Returns whether any
+    /// voxel was heated.
+    ///
+    /// The point and distance should be specified in the normalized model
+    /// space of the voxel object, where distances are in voxels, the lower
+    /// corner of the grid is at the origin and the cartesian axes are aligned
+    /// with the grid. The mass densities are given in kg/m³.
+    pub fn add_heat_near_point<A: Allocator>(
+        &mut self,
+        alloc: A,
+        normalized_point: &Point3,
+        distance: f32,
+        energy: f32,
+        voxel_type_densities: &[f32],
+        voxel_type_thermal_properties: &[VoxelThermalProperties],
+    ) -> bool {
+        if energy <= 0.0 {
+            return false;
+        }
+
+        let mut voxel_indices = AVec::new_in(alloc);
+        self.gather_non_empty_voxels_near_point(normalized_point, distance, &mut voxel_indices);
+
+        let voxel_volume = self.voxel_extent.powi(3);
+
+        let mut heat_capacities = AVec::with_capacity_in(voxel_indices.len(), alloc);
+        heat_capacities.extend(voxel_indices.iter().map(|&[i, j, k]| {
+            let voxel_type = self.voxel(i, j, k).voxel_type();
+            voxel_type_densities[voxel_type.idx()]
+                * voxel_type_thermal_properties[voxel_type.idx()].specific_heat_capacity
+                * voxel_volume
+        }));
+
+        let heatable_voxel_count = heat_capacities
+            .iter()
+            .filter(|heat_capacity| heat_capacity.is_finite())
+            .count();
+
+        if heatable_voxel_count == 0 {
+            return false;
+        }
+
+        let energy_per_voxel = energy / heatable_voxel_count as f32;
+
+        for (&[i, j, k], &heat_capacity) in voxel_indices.iter().zip(&heat_capacities) {
+            if !heat_capacity.is_finite() {
+                continue;
+            }
+            let temperature_change = energy_per_voxel / heat_capacity;
+
+            let chunk = self
+                .thermal_state
+                .tracked_chunk_mut(chunk_indices_from_object_voxel_indices(i, j, k));
+
+            chunk.temperatures[linear_voxel_idx_within_chunk_from_object_voxel_indices(i, j, k)] +=
+                temperature_change;
+            chunk.temperature_change_since_mesh_invalidation += temperature_change;
+        }
+
+        true
+    }
+
+    /// Advances the heat conduction between the object's voxels and the
+    /// exchange of heat between exposed voxels and their surroundings by the
+    /// given duration (in s).
+    ///
+    /// Heat is conducted across the faces shared by adjacent non-empty voxels,
+    /// using the harmonic mean of their thermal conductivities. Faces towards
+    /// empty voxels exchange heat with surroundings at [`AMBIENT_TEMPERATURE`]
+    /// according to Newton's law of cooling with the given heat transfer
+    /// coefficient (in W/(m²·K)). The explicit time integration is divided
+    /// into enough substeps to remain stable, up to a fixed limit beyond which
+    /// the simulated duration is shortened.
+    ///
+    /// Chunks that have returned to ambient temperature stop being tracked,
+    /// and their meshes are invalidated so that they no longer appear hot.
+    pub fn diffuse_heat(
+        &mut self,
+        duration: f32,
+        voxel_type_densities: &[f32],
+        voxel_type_thermal_properties: &[VoxelThermalProperties],
+        surface_heat_transfer_coefficient: f32,
+    ) {
+        if self.thermal_state.is_at_ambient_temperature() || duration <= 0.0 {
+            return;
+        }
+
+        let max_substep_duration = self.compute_max_stable_heat_diffusion_time_step(
+            voxel_type_densities,
+            voxel_type_thermal_properties,
+            surface_heat_transfer_coefficient,
+        );
+
+        let substep_count =
+            ((duration / max_substep_duration).ceil() as usize).clamp(1, MAX_DIFFUSION_SUBSTEPS);
+        let substep_duration = (duration / substep_count as f32).min(max_substep_duration);
+
+        // The temperatures are read through the voxel object while being
+        // updated, so we move them out for the duration of the update
+        let mut thermal_state = mem::take(&mut self.thermal_state);
+
+        let mut updated_chunks = Vec::with_capacity(thermal_state.chunks.len());
+
+        for _ in 0..substep_count {
+            self.track_chunks_receiving_heat(&mut thermal_state);
+
+            for (chunk_indices, chunk) in &thermal_state.chunks {
+                updated_chunks.push((
+                    *chunk_indices,
+                    self.compute_updated_chunk_temperatures(
+                        &thermal_state,
+                        chunk_indices,
+                        chunk,
+                        substep_duration,
+                        voxel_type_densities,
+                        voxel_type_thermal_properties,
+                        surface_heat_transfer_coefficient,
+                    ),
+                ));
+            }
+
+            for (chunk_indices, (temperatures, max_temperature_change)) in updated_chunks.drain(..)
+            {
+                let chunk = thermal_state.chunks.get_mut(&chunk_indices).unwrap();
+                chunk.temperatures = temperatures;
+                chunk.temperature_change_since_mesh_invalidation += max_temperature_change;
+            }
+        }
+
+        let invalidated_mesh_chunk_indices = &mut self.invalidated_mesh_chunk_indices;
+
+        thermal_state.chunks.retain(|chunk_indices, chunk| {
+            if chunk.max_deviation_from_ambient() > TRACKED_TEMPERATURE_DEVIATION {
+                return true;
+            }
+            if chunk.temperature_change_since_mesh_invalidation > 0.0 {
+                invalidated_mesh_chunk_indices.insert(*chunk_indices);
+            }
+            false
+        });
+
+        self.thermal_state = thermal_state;
+    }
+
+    /// Invalidates the meshes of all chunks where the temperature of some voxel
+    /// may have changed by at least the given amount (in K) since the chunk's
+    /// mesh was last invalidated, so that the mesh vertex temperatures will be
+    /// updated.
+    pub fn invalidate_meshes_for_changed_temperatures(&mut self, temperature_threshold: f32) {
+        for (chunk_indices, chunk) in &mut self.thermal_state.chunks {
+            if chunk.temperature_change_since_mesh_invalidation >= temperature_threshold {
+                self.invalidated_mesh_chunk_indices.insert(*chunk_indices);
+                chunk.temperature_change_since_mesh_invalidation = 0.0;
+            }
+        }
+    }
+
+    /// Returns the indices of all non-empty voxels whose temperature has
+    /// reached the melting temperature of their voxel type.
+    pub fn find_melting_voxels<A: Allocator>(
+        &self,
+        alloc: A,
+        voxel_type_thermal_properties: &[VoxelThermalProperties],
+    ) -> AVec<[usize; 3], A> {
+        let mut melting_voxel_indices = AVec::new_in(alloc);
+
+        for (chunk_indices, chunk) in &self.thermal_state.chunks {
+            for (idx, &temperature) in chunk.temperatures.iter().enumerate() {
+                let indices = object_voxel_indices_in_chunk(chunk_indices, idx);
+
+                let Some(voxel) = self.get_voxel_if_occupied(indices[0], indices[1], indices[2])
+                else {
+                    continue;
+                };
+
+                if let Some(melting_temperature) =
+                    voxel_type_thermal_properties[voxel.voxel_type().idx()].melting_temperature
+                    && temperature >= melting_temperature
+                {
+                    melting_voxel_indices.push(indices);
+                }
+            }
+        }
+
+        melting_voxel_indices
+    }
+
+    fn compute_max_stable_heat_diffusion_time_step(
+        &self,
+        voxel_type_densities: &[f32],
+        voxel_type_thermal_properties: &[VoxelThermalProperties],
+        surface_heat_transfer_coefficient: f32,
+    ) -> f32 {
+        // For the explicit scheme to be stable, the total conductance of a
+        // voxel's six faces times the time step must not exceed its heat
+        // capacity. The harmonic mean of two conductivities is at most twice
+        // the smaller one, which gives the additional factor of two.
+        let voxel_extent = self.voxel_extent;
+        voxel_type_densities
+            .iter()
+            .zip(voxel_type_thermal_properties)
+            .map(|(&density, properties)| {
+                let max_conductance_per_extent = (2.0 * properties.thermal_conductivity)
+                    .max(surface_heat_transfer_coefficient * voxel_extent);
+                density * properties.specific_heat_capacity * voxel_extent.powi(2)
+                    / (6.0 * max_conductance_per_extent)
+            })
+            .filter(|time_step| time_step.is_finite() && *time_step > 0.0)
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    /// Starts tracking the temperatures of the chunks that tracked chunks
+    /// conduct noticeable heat into, so that heat is not lost at the
+    /// boundaries of the tracked region.
+    fn track_chunks_receiving_heat(&self, thermal_state: &mut VoxelObjectThermalState) {
+        let mut newly_tracked_chunk_indices = HashSet::default();
+
+        for (chunk_indices, chunk) in &thermal_state.chunks {
+            for dim in 0..3 {
+                for (side, adjacent_chunk_idx) in [
+                    (0, chunk_indices[dim].wrapping_sub(1)),
+                    (CHUNK_SIZE - 1, chunk_indices[dim] + 1),
+                ] {
+                    if adjacent_chunk_idx >= self.chunk_counts[dim] {
+                        continue;
+                    }
+
+                    let mut adjacent_chunk_indices = *chunk_indices;
+                    adjacent_chunk_indices[dim] = adjacent_chunk_idx;
+
+                    if thermal_state.chunks.contains_key(&adjacent_chunk_indices)
+                        || self
+                            .chunks
+                            .get(self.linear_chunk_idx(&adjacent_chunk_indices))
+                            .is_none_or(|chunk| chunk.contains_only_empty_voxels())
+                    {
+                        continue;
+                    }
+
+                    if face_deviates_from_ambient(&chunk.temperatures, dim, side) {
+                        newly_tracked_chunk_indices.insert(adjacent_chunk_indices);
+                    }
+                }
+            }
+        }
+
+        for chunk_indices in newly_tracked_chunk_indices {
+            thermal_state.tracked_chunk_mut(chunk_indices);
+        }
+    }
+
+    fn compute_updated_chunk_temperatures(
+        &self,
+        thermal_state: &VoxelObjectThermalState,
+        chunk_indices: &[usize; 3],
+        chunk: &ChunkThermalState,
+        duration: f32,
+        voxel_type_densities: &[f32],
+        voxel_type_thermal_properties: &[VoxelThermalProperties],
+        surface_heat_transfer_coefficient: f32,
+    ) -> (Box<[f32; CHUNK_VOXEL_COUNT]>, f32) {
+        let voxel_extent = self.voxel_extent;
+        let voxel_volume = voxel_extent.powi(3);
+        let surface_conductance = surface_heat_transfer_coefficient * voxel_extent.powi(2);
+
+        let mut updated_temperatures = ambient_chunk_temperatures();
+        let mut max_temperature_change: f32 = 0.0;
+
+        for (idx, &temperature) in chunk.temperatures.iter().enumerate() {
+            let indices = object_voxel_indices_in_chunk(chunk_indices, idx);
+
+            // Empty voxels are reset to ambient temperature
+            let Some(voxel) = self.get_voxel_if_occupied(indices[0], indices[1], indices[2]) else {
+                continue;
+            };
+
+            let voxel_type_idx = voxel.voxel_type().idx();
+            let properties = &voxel_type_thermal_properties[voxel_type_idx];
+
+            let heat_capacity = voxel_type_densities[voxel_type_idx]
+                * properties.specific_heat_capacity
+                * voxel_volume;
+
+            if !heat_capacity.is_finite() {
+                updated_temperatures[idx] = temperature;
+                continue;
+            }
+
+            let mut heat_flow_rate = 0.0;
+
+            for dim in 0..3 {
+                for offset in [usize::MAX, 1] {
+                    let mut adjacent_indices = indices;
+                    adjacent_indices[dim] = adjacent_indices[dim].wrapping_add(offset);
+
+                    let Some(adjacent_voxel) = self.get_voxel_if_occupied(
+                        adjacent_indices[0],
+                        adjacent_indices[1],
+                        adjacent_indices[2],
+                    ) else {
+                        heat_flow_rate += surface_conductance * (AMBIENT_TEMPERATURE - temperature);
+                        continue;
+                    };
+
+                    let conductivity = harmonic_mean(
+                        properties.thermal_conductivity,
+                        voxel_type_thermal_properties[adjacent_voxel.voxel_type().idx()]
+                            .thermal_conductivity,
+                    );
+                    if conductivity == 0.0 {
+                        continue;
+                    }
+
+                    let adjacent_temperature = if chunk_indices_from_object_voxel_indices(
+                        adjacent_indices[0],
+                        adjacent_indices[1],
+                        adjacent_indices[2],
+                    ) == *chunk_indices
+                    {
+                        chunk.temperatures[linear_voxel_idx_within_chunk_from_object_voxel_indices(
+                            adjacent_indices[0],
+                            adjacent_indices[1],
+                            adjacent_indices[2],
+                        )]
+                    } else {
+                        thermal_state.temperature(&adjacent_indices)
+                    };
+
+                    // The conductance across a face is the conductivity times
+                    // the face area divided by the distance between the voxel
+                    // centers
+                    heat_flow_rate +=
+                        conductivity * voxel_extent * (adjacent_temperature - temperature);
+                }
+            }
+
+            let temperature_change = duration * heat_flow_rate / heat_capacity;
+
+            updated_temperatures[idx] = temperature + temperature_change;
+            max_temperature_change = max_temperature_change.max(temperature_change.abs());
+        }
+
+        (updated_temperatures, max_temperature_change)
+    }
+}
+
+fn ambient_chunk_temperatures() -> Box<[f32; CHUNK_VOXEL_COUNT]> {
+    vec![AMBIENT_TEMPERATURE; CHUNK_VOXEL_COUNT]
+        .into_boxed_slice()
+        .try_into()
+        .unwrap()
+}
+
+fn object_voxel_indices_in_chunk(chunk_indices: &[usize; 3], idx: usize) -> [usize; 3] {
+    let voxel_indices = chunk_voxel_indices_from_linear_idx(idx);
+    [
+        chunk_indices[0] * CHUNK_SIZE + voxel_indices[0],
+        chunk_indices[1] * CHUNK_SIZE + voxel_indices[1],
+        chunk_indices[2] * CHUNK_SIZE + voxel_indices[2],
+    ]
+}
+
+fn face_deviates_from_ambient(
+    temperatures: &[f32; CHUNK_VOXEL_COUNT],
+    dim: usize,
+    face_idx: usize,
+) -> bool {
+    (0..CHUNK_SIZE).any(|a| {
+        (0..CHUNK_SIZE).any(|b| {
+            let mut voxel_indices = [0; 3];
+            voxel_indices[dim] = face_idx;
+            voxel_indices[(dim + 1) % 3] = a;
+            voxel_indices[(dim + 2) % 3] = b;
+            (temperatures[linear_voxel_idx_within_chunk(&voxel_indices)] - AMBIENT_TEMPERATURE)
+                .abs()
+                > TRACKED_TEMPERATURE_DEVIATION
+        })
+    })
+}
+
+fn harmonic_mean(a: f32, b: f32) -> f32 {
+    let sum = a + b;
+    if sum > 0.0 { 2.0 * a * b / sum } else { 0.0 }
+}
+
+#[cfg(not(miri))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        generation::{
+            SDFVoxelGenerator,
+            sdf::{SDFGraph, SDFNode},
+            voxel_type::SameVoxelTypeGenerator,
+        },
+        object::VoxelObjectBuffers,
+        voxel_types::VoxelType,
+    };
+    use approx::assert_abs_diff_eq;
+    use impact_alloc::Global;
+
+    const DENSITY: f32 = 1000.0;
+
+    fn thermal_properties(melting_temperature: Option<f32>) -> VoxelThermalProperties {
+        VoxelThermalProperties {
+            thermal_conductivity: 1.0,
+            specific_heat_capacity: 1000.0,
+            melting_temperature,
+        }
+    }
+
+    fn generate_box_object(size: f32) -> VoxelObject {
+        let mut graph = SDFGraph::new_in(Global);
+        graph.add_node(SDFNode::new_box([size, size, size]));
+        let sdf_generator = graph.build_in(Global).unwrap();
+
+        let generator = SDFVoxelGenerator::new(
+            1.0,
+            sdf_generator,
+            SameVoxelTypeGenerator::new(VoxelType::default()).into(),
+        );
+        VoxelObject::generate(VoxelObjectBuffers::new(), &generator)
+    }
+
+    fn object_center(object: &VoxelObject) -> Point3 {
+        let ranges = object.occupied_voxel_ranges();
+        Point3::new(
+            0.5 * (ranges[0].start + ranges[0].end) as f32,
+            0.5 * (ranges[1].start + ranges[1].end) as f32,
+            0.5 * (ranges[2].start + ranges[2].end) as f32,
+        )
+    }
+
+    fn compute_thermal_energy_above_ambient(object: &VoxelObject) -> f32 {
+        let heat_capacity = DENSITY * thermal_properties(None).specific_heat_capacity;
+        let ranges = object.occupied_voxel_ranges().clone();
+        let mut energy = 0.0;
+        for i in ranges[0].clone() {
+            for j in ranges[1].clone() {
+                for k in ranges[2].clone() {
+                    if !object.voxel(i, j, k).is_empty() {
+                        energy += heat_capacity
+                            * (object.thermal_state().temperature(&[i, j, k])
+                                - AMBIENT_TEMPERATURE);
+                    }
+                }
+            }
+        }
+        energy
+    }
+
+    #[test]
+    fn new_object_is_at_ambient_temperature() {
+        let object = generate_box_object(8.0);
+        assert!(!object.has_thermal_activity());
+        let center = object_center(&object);
+        assert_eq!(
+            object.compute_temperature_at_position(&[center.x(), center.y(), center.z()]),
+            AMBIENT_TEMPERATURE
+        );
+    }
+
+    #[test]
+    fn heating_raises_temperature_of_nearby_voxels_only() {
+        let mut object = generate_box_object(8.0);
+        let center = object_center(&object);
+        let properties = [thermal_properties(None)];
+
+        let energy = 1e6;
+        assert!(object.add_heat_near_point(Global, &center, 1.0, energy, &[DENSITY], &properties));
+        assert!(object.has_thermal_activity());
+
+        let center_indices = [center.x(), center.y(), center.z()].map(|coord| coord as usize);
+        assert!(object.thermal_state().temperature(&center_indices) > AMBIENT_TEMPERATURE);
+
+        let ranges = object.occupied_voxel_ranges();
+        let corner_indices = [ranges[0].start, ranges[1].start, ranges[2].start];
+        assert_eq!(
+            object.thermal_state().temperature(&corner_indices),
+            AMBIENT_TEMPERATURE
+        );
+
+        assert_abs_diff_eq!(
+            compute_thermal_energy_above_ambient(&object),
+            energy,
+            epsilon = 1e-3 * energy
+        );
+    }
+
+    #[test]
+    fn inert_voxels_are_not_heated() {
+        let mut object = generate_box_object(8.0);
+        let center = object_center(&object);
+        assert!(!object.add_heat_near_point(
+            Global,
+            &center,
+            1.0,
+            1e6,
+            &[DENSITY],
+            &[VoxelThermalProperties::INERT]
+        ));
+        assert!(!object.has_thermal_activity());
+    }
+
+    #[test]
+    fn diffusion_without_surface_exchange_spreads_heat_and_conserves_energy() {
+        let mut object = generate_box_object(8.0);
+        let center = object_center(&object);
+        let properties = [thermal_properties(None)];
+
+        let energy = 1e8;
+        object.add_heat_near_point(Global, &center, 1.0, energy, &[DENSITY], &properties);
+
+        let center_indices = [center.x(), center.y(), center.z()].map(|coord| coord as usize);
+        let initial_center_temperature = object.thermal_state().temperature(&center_indices);
+
+        for _ in 0..10 {
+            object.diffuse_heat(1e5, &[DENSITY], &properties, 0.0);
+        }
+
+        let center_temperature = object.thermal_state().temperature(&center_indices);
+        assert!(center_temperature < initial_center_temperature);
+        assert!(center_temperature > AMBIENT_TEMPERATURE);
+
+        assert_abs_diff_eq!(
+            compute_thermal_energy_above_ambient(&object),
+            energy,
+            epsilon = 2e-2 * energy
+        );
+    }
+
+    #[test]
+    fn surface_exchange_cools_object_back_to_ambient_temperature() {
+        let mut object = generate_box_object(4.0);
+        let center = object_center(&object);
+        let properties = [VoxelThermalProperties {
+            thermal_conductivity: 100.0,
+            ..thermal_properties(None)
+        }];
+
+        object.add_heat_near_point(Global, &center, 2.0, 1e8, &[DENSITY], &properties);
+        object.mark_chunk_meshes_synchronized();
+
+        let mut steps = 0;
+        while object.has_thermal_activity() {
+            object.diffuse_heat(1e4, &[DENSITY], &properties, 10.0);
+            steps += 1;
+            assert!(steps < 1000);
+        }
+
+        // The meshes must be updated to no longer appear hot
+        assert!(object.invalidated_mesh_chunk_indices().len() > 0);
+    }
+
+    #[test]
+    fn meshes_are_invalidated_only_for_large_enough_temperature_changes() {
+        let mut object = generate_box_object(8.0);
+        let center = object_center(&object);
+        let properties = [thermal_properties(None)];
+
+        object.add_heat_near_point(Global, &center, 1.0, 1e6, &[DENSITY], &properties);
+        object.mark_chunk_meshes_synchronized();
+
+        object.invalidate_meshes_for_changed_temperatures(f32::INFINITY);
+        assert_eq!(object.invalidated_mesh_chunk_indices().len(), 0);
+
+        object.invalidate_meshes_for_changed_temperatures(1.0);
+        assert!(object.invalidated_mesh_chunk_indices().len() > 0);
+    }
+
+    #[test]
+    fn voxels_at_melting_temperature_are_found() {
+        let mut object = generate_box_object(8.0);
+        let center = object_center(&object);
+        let properties = [thermal_properties(Some(500.0))];
+
+        object.add_heat_near_point(Global, &center, 1.0, 1e5, &[DENSITY], &properties);
+        assert!(object.find_melting_voxels(Global, &properties).is_empty());
+
+        object.add_heat_near_point(Global, &center, 1.0, 1e8, &[DENSITY], &properties);
+        let melting = object.find_melting_voxels(Global, &properties);
+        assert!(!melting.is_empty());
+        for indices in &melting {
+            assert!(object.thermal_state().temperature(indices) >= 500.0);
+        }
+    }
+
+    #[test]
+    fn shifting_chunk_indices_moves_temperatures() {
+        let mut object = generate_box_object(8.0);
+        let center = object_center(&object);
+        let properties = [thermal_properties(None)];
+
+        object.add_heat_near_point(Global, &center, 1.0, 1e6, &[DENSITY], &properties);
+
+        let indices = [center.x(), center.y(), center.z()].map(|coord| coord as usize);
+        let temperature = object.thermal_state().temperature(&indices);
+
+        let mut thermal_state = object.thermal_state().clone();
+        thermal_state.shift_chunk_indices([1, 0, 2]);
+
+        let shifted_indices = [
+            indices[0] + CHUNK_SIZE,
+            indices[1],
+            indices[2] + 2 * CHUNK_SIZE,
+        ];
+        assert_eq!(thermal_state.temperature(&shifted_indices), temperature);
+        assert_eq!(thermal_state.temperature(&indices), AMBIENT_TEMPERATURE);
+    }
+}
@@ -6,7 +6,7 @@ use crate::{
         VOXEL_MODEL_ID, VoxelGPUResources, VoxelMaterialGPUResources, VoxelObjectGPUBuffers,
         VoxelPushConstantGroup, VoxelPushConstantVariant, VoxelResourceRegistries,
     },
-    mesh::{CullingFrustum, VoxelMeshIndex, VoxelMeshIndexMaterials},
+    mesh::{ChunkLODSelectionParameters, CullingFrustum, VoxelMeshIndex, VoxelMeshIndexMaterials},
     shader_templates::{
         voxel_chunk_culling::VoxelChunkCullingShaderTemplate,
         voxel_geometry::VoxelGeometryShaderTemplate,
@@ -26,6 +26,7 @@ use impact_math::transform::Similarity3;
 use impact_mesh::gpu_resource::VertexBufferable;
 use impact_model::{
     InstanceFeature,
+    entity::InstanceEntityID,
     transform::{
         AsInstanceModelViewTransform, InstanceModelLightTransform, InstanceModelViewTransform,
         InstanceModelViewTransformWithPrevious,
@@ -39,6 +40,7 @@ use impact_rendering::{
     resource::BasicGPUResources,
     surface::RenderingSurface,
 };
+use impact_scene::lod::LODSelectionConfig;
 use std::{borrow::Cow, ops::Range};
 
 /// GPU commands that should be executed prior to rendering voxel objects.
@@ -105,15 +107,21 @@ impl VoxelRenderCommands {
     pub fn sync_with_config(
         &mut self,
         graphics_device: &GraphicsDevice,
-        shader_manager: &ShaderManager,
+        shader_manager: &mut ShaderManager,
+        geometry_pass: &GeometryPass,
         config: &BasicRenderingConfig,
     ) {
-        self.geometry_pipeline
-            .sync_with_config(graphics_device, shader_manager, config);
+        self.geometry_pipeline.sync_with_config(
+            graphics_device,
+            shader_manager,
+            geometry_pass.color_target_states(),
+            config,
+        );
     }
 
     pub fn record_before_geometry_pass<GR>(
         &self,
+        lod_selection_config: &LODSelectionConfig,
         gpu_resources: &GR,
         timestamp_recorder: &mut TimestampQueryRegistry<'_>,
         command_encoder: &mut wgpu::CommandEncoder,
@@ -122,6 +130,7 @@ impl VoxelRenderCommands {
         GR: BasicGPUResources + VoxelGPUResources,
     {
         self.chunk_culling_pass.record_for_geometry_pass(
+            lod_selection_config,
             gpu_resources,
             timestamp_recorder,
             command_encoder,
@@ -151,6 +160,7 @@ impl VoxelRenderCommands {
     pub fn record_before_omnidirectional_light_shadow_cubemap_face_update<GR>(
         &self,
         positive_z_cubemap_face_frustum: &Frustum,
+        lod_selection_config: &LODSelectionConfig,
         instance_range_id: u64,
         gpu_resources: &GR,
         timestamp_recorder: &mut TimestampQueryRegistry<'_>,
@@ -162,6 +172,7 @@ impl VoxelRenderCommands {
         self.chunk_culling_pass
             .record_for_shadow_mapping_with_frustum(
                 positive_z_cubemap_face_frustum,
+                lod_selection_config,
                 instance_range_id,
                 gpu_resources,
                 timestamp_recorder,
@@ -172,6 +183,7 @@ impl VoxelRenderCommands {
     pub fn record_before_unidirectional_light_shadow_map_cascade_update<GR>(
         &self,
         cascade_frustum: &OrientedBox,
+        lod_selection_config: &LODSelectionConfig,
         instance_range_id: u64,
         gpu_resources: &GR,
         timestamp_recorder: &mut TimestampQueryRegistry<'_>,
@@ -183,6 +195,7 @@ impl VoxelRenderCommands {
         self.chunk_culling_pass
             .record_for_shadow_mapping_with_orthographic_frustum(
                 cascade_frustum,
+                lod_selection_config,
                 instance_range_id,
                 gpu_resources,
                 timestamp_recorder,
@@ -325,6 +338,7 @@ impl VoxelChunkCullingPass {
         &self,
         compute_pass: &mut wgpu::ComputePass<'_>,
         culling_frustum: CullingFrustum,
+        lod_selection_parameters: ChunkLODSelectionParameters,
         chunk_count: u32,
         instance_idx: u32,
     ) {
@@ -335,6 +349,13 @@ impl VoxelChunkCullingPass {
                 || culling_frustum,
             );
 
+        self.push_constants
+            .set_push_constant_for_compute_pass_if_present(
+                compute_pass,
+                VoxelPushConstantVariant::ChunkLODSelection,
+                || lod_selection_parameters,
+            );
+
         self.push_constants
             .set_push_constant_for_compute_pass_if_present(
                 compute_pass,
@@ -352,6 +373,7 @@ impl VoxelChunkCullingPass {
 
     fn record_for_geometry_pass<GR>(
         &self,
+        lod_selection_config: &LODSelectionConfig,
         gpu_resources: &GR,
         timestamp_recorder: &mut TimestampQueryRegistry<'_>,
         command_encoder: &mut wgpu::CommandEncoder,
@@ -370,6 +392,9 @@ impl VoxelChunkCullingPass {
 
         let frustum = camera.view_frustum();
 
+        let lod_selection_parameters =
+            ChunkLODSelectionParameters::for_frustum(frustum, lod_selection_config);
+
         let visible_voxel_object_ids = gpu_resources
             .voxel_objects()
             .visible_voxel_object_ids_in_initial_range();
@@ -387,7 +412,13 @@ impl VoxelChunkCullingPass {
             visible_voxel_object_to_frustum_transforms,
             instance_range_for_transforms,
             &|frustum_to_voxel_object_transform| {
-                CullingFrustum::for_transformed_frustum(frustum, frustum_to_voxel_object_transform)
+                (
+                    CullingFrustum::for_transformed_frustum(
+                        frustum,
+                        frustum_to_voxel_object_transform,
+                    ),
+                    lod_selection_parameters,
+                )
             },
             // The geometry pass uses non-indexed draw calls
             false,
@@ -395,9 +426,13 @@ impl VoxelChunkCullingPass {
         )
     }
 
+    /// The level of detail of each chunk is selected based on its projected
+    /// size in the shadow map, independently of the level it is rendered with
+    /// in the camera view.
     fn record_for_shadow_mapping_with_frustum<GR>(
         &self,
         frustum: &Frustum,
+        lod_selection_config: &LODSelectionConfig,
         instance_range_id: u64,
         gpu_resources: &GR,
         timestamp_recorder: &mut TimestampQueryRegistry<'_>,
@@ -426,6 +461,9 @@ impl VoxelChunkCullingPass {
             return Ok(());
         };
 
+        let lod_selection_parameters =
+            ChunkLODSelectionParameters::for_frustum(frustum, lod_selection_config);
+
         self.record(
             gpu_resources,
             timestamp_recorder,
@@ -434,7 +472,13 @@ impl VoxelChunkCullingPass {
             visible_voxel_object_to_frustum_transforms,
             instance_range_for_transforms,
             &|frustum_to_voxel_object_transform| {
-                CullingFrustum::for_transformed_frustum(frustum, frustum_to_voxel_object_transform)
+                (
+                    CullingFrustum::for_transformed_frustum(
+                        frustum,
+                        frustum_to_voxel_object_transform,
+                    ),
+                    lod_selection_parameters,
+                )
             },
             // Shadow map update passes use indexed draw calls
             true,
@@ -442,9 +486,12 @@ impl VoxelChunkCullingPass {
         )
     }
 
+    /// All chunks are rendered with the level of detail selected from the
+    /// projected size of a voxel in the shadow map.
     fn record_for_shadow_mapping_with_orthographic_frustum<GR>(
         &self,
         orthographic_frustum: &OrientedBox,
+        lod_selection_config: &LODSelectionConfig,
         instance_range_id: u64,
         gpu_resources: &GR,
         timestamp_recorder: &mut TimestampQueryRegistry<'_>,
@@ -481,10 +528,17 @@ impl VoxelChunkCullingPass {
             visible_voxel_object_to_frustum_transforms,
             instance_range_for_transforms,
             &|frustum_to_voxel_object_transform| {
-                CullingFrustum::for_transformed_orthographic_frustum(
-                    orthographic_frustum,
-                    frustum_to_voxel_object_transform,
-                    10000.0, // Put the apex this many chunks away to emulate infinity
+                (
+                    CullingFrustum::for_transformed_orthographic_frustum(
+                        orthographic_frustum,
+                        frustum_to_voxel_object_transform,
+                        10000.0, // Put the apex this many chunks away to emulate infinity
+                    ),
+                    ChunkLODSelectionParameters::for_transformed_orthographic_frustum(
+                        orthographic_frustum,
+                        frustum_to_voxel_object_transform,
+                        lod_selection_config,
+                    ),
                 )
             },
             // Shadow map update passes use indexed draw calls
@@ -501,7 +555,12 @@ impl VoxelChunkCullingPass {
         visible_voxel_object_ids: &[VoxelObjectID],
         visible_voxel_object_to_frustum_transforms: &[T],
         instance_range_for_transforms: Range<u32>,
-        obtain_frustum_planes_in_voxel_object_space: &impl Fn(&Similarity3) -> CullingFrustum,
+        obtain_frustum_planes_and_lod_selection_in_voxel_object_space: &impl Fn(
+            &Similarity3,
+        ) -> (
+            CullingFrustum,
+            ChunkLODSelectionParameters,
+        ),
         for_indexed_draw_calls: bool,
         tag: Cow<'static, str>,
     ) -> Result<()>
@@ -560,10 +619,18 @@ impl VoxelChunkCullingPass {
                     voxel_object_buffers.chunk_extent(),
                 );
 
-            let frustum_planes =
-                obtain_frustum_planes_in_voxel_object_space(&frustum_to_voxel_object_transform);
+            let (frustum_planes, lod_selection_parameters) =
+                obtain_frustum_planes_and_lod_selection_in_voxel_object_space(
+                    &frustum_to_voxel_object_transform,
+                );
 
-            self.set_push_constants(&mut compute_pass, frustum_planes, chunk_count, instance_idx);
+            self.set_push_constants(
+                &mut compute_pass,
+                frustum_planes,
+                lod_selection_parameters,
+                chunk_count,
+                instance_idx,
+            );
 
             compute_pass.set_bind_group(
                 0,
@@ -610,7 +677,8 @@ impl VoxelGeometryPipeline {
     ) -> Self {
         let n_voxel_types = resource_registries.voxel_type().n_voxel_types();
 
-        let shader_template = VoxelGeometryShaderTemplate::new(n_voxel_types, 0.1);
+        let shader_template =
+            VoxelGeometryShaderTemplate::new(n_voxel_types, 0.1, config.entity_picking_enabled);
 
         shader_manager
             .get_or_create_rendering_shader_from_template(graphics_device, &shader_template);
@@ -703,9 +771,23 @@ impl VoxelGeometryPipeline {
     pub fn sync_with_config(
         &mut self,
         graphics_device: &GraphicsDevice,
-        shader_manager: &ShaderManager,
+        shader_manager: &mut ShaderManager,
+        color_target_states: &[Option<wgpu::ColorTargetState>],
         config: &BasicRenderingConfig,
     ) {
+        if config.entity_picking_enabled != self.shader_template.writes_entity_id() {
+            self.shader_template = self
+                .shader_template
+                .with_writes_entity_id(config.entity_picking_enabled);
+
+            shader_manager.get_or_create_rendering_shader_from_template(
+                graphics_device,
+                &self.shader_template,
+            );
+        }
+
+        self.color_target_states = color_target_states.to_vec();
+
         self.pipeline = Self::create_pipeline(
             graphics_device,
             shader_manager,
@@ -751,6 +833,7 @@ impl VoxelGeometryPipeline {
     fn set_per_object_push_constants(
         &self,
         render_pass: &mut wgpu::RenderPass<'_>,
+        voxel_object_id: VoxelObjectID,
         voxel_object_buffers: &VoxelObjectGPUBuffers,
     ) {
         self.push_constants
@@ -759,6 +842,23 @@ impl VoxelGeometryPipeline {
                 VoxelPushConstantVariant::Rendering(BasicPushConstantVariant::GenericVec3f32),
                 || voxel_object_buffers.origin_offset_in_root(),
             );
+
+        self.push_constants
+            .set_push_constant_for_render_pass_if_present(
+                render_pass,
+                VoxelPushConstantVariant::EntityID,
+                || {
+                    let entity_id = InstanceEntityID::new(voxel_object_id.as_entity_id());
+                    [entity_id.lower, entity_id.upper]
+                },
+            );
+
+        self.push_constants
+            .set_push_constant_for_render_pass_if_present(
+                render_pass,
+                VoxelPushConstantVariant::InverseVoxelExtent,
+                || voxel_object_buffers.voxel_extent().recip(),
+            );
     }
 
     pub fn record<GR>(
@@ -828,7 +928,7 @@ impl VoxelGeometryPipeline {
                 continue;
             };
 
-            self.set_per_object_push_constants(render_pass, voxel_object_buffers);
+            self.set_per_object_push_constants(render_pass, *voxel_object_id, voxel_object_buffers);
 
             let chunk_count = u32::try_from(voxel_object_buffers.n_chunks()).unwrap();
 
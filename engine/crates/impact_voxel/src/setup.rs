@@ -5,11 +5,13 @@ use crate::{
     generation::{
         ChunkedVoxelGenerator, VoxelGeneratorID,
         sdf::{SDFGraph, SDFNode, SDFNodeID},
+        vox::VoxModelID,
         voxel_type::{GradientNoiseVoxelTypeGenerator, SameVoxelTypeGenerator},
+        voxelization::VoxelizedMeshID,
     },
     gpu_resource::VOXEL_MODEL_ID,
     mesh::MeshedVoxelObject,
-    object::{VoxelObject, inertia::VoxelObjectInertialPropertyManager},
+    object::{VoxelObject, inertia::VoxelObjectInertialPropertyManager, io},
     voxel_types::{VoxelType, VoxelTypeRegistry},
 };
 use anyhow::{Result, anyhow, bail};
@@ -49,6 +51,49 @@ define_setup_type! {
     }
 }
 
+define_setup_type! {
+    /// A voxel terrain of unbounded extent that is generated in tiles around
+    /// the camera. The origin of the generator's coordinate space is placed at
+    /// the position of the entity's reference frame, if present.
+    #[roc(parents = "Setup")]
+    #[repr(C)]
+    #[derive(Copy, Clone, Debug, Zeroable, Pod)]
+    pub struct GeneratedVoxelTerrain {
+        pub generator_id: VoxelGeneratorID,
+        pub voxel_extent: f32,
+        pub scale_factor: f32,
+        pub seed: u64,
+    }
+}
+
+define_setup_type! {
+    /// A voxel object generated by voxelizing the triangle meshes of a declared
+    /// voxelized mesh, after scaling the meshes uniformly by the given factor.
+    #[roc(parents = "Setup")]
+    #[repr(C)]
+    #[derive(Copy, Clone, Debug, Zeroable, Pod)]
+    pub struct VoxelizedMeshObject {
+        pub voxelized_mesh_id: VoxelizedMeshID,
+        pub voxel_extent: f32,
+        pub scale_factor: f32,
+    }
+}
+
+define_setup_type! {
+    /// A voxel object generated from a declared MagicaVoxel model, with voxel
+    /// types assigned to palette entries as specified for the registered voxel
+    /// types. The signed distance field synthesized from the model's occupancy
+    /// is smoothed the given number of times.
+    #[roc(parents = "Setup")]
+    #[repr(C)]
+    #[derive(Copy, Clone, Debug, Zeroable, Pod)]
+    pub struct VoxModelObject {
+        pub vox_model_id: VoxModelID,
+        pub voxel_extent: f32,
+        pub smoothing_iterations: u32,
+    }
+}
+
 define_setup_type! {
     /// A voxel type that is the only type present in a voxel object.
     #[roc(parents = "Setup")]
@@ -180,6 +225,49 @@ impl GeneratedVoxelObject {
     }
 }
 
+#[roc(dependencies = [impact_math::hash::Hash64])]
+impl GeneratedVoxelTerrain {
+    #[roc(
+        body = "{ generator_id: Hashing.hash_str_64(generator_name), voxel_extent, scale_factor, seed }"
+    )]
+    pub fn new(generator_name: &str, voxel_extent: f32, scale_factor: f32, seed: u64) -> Self {
+        Self {
+            generator_id: VoxelGeneratorID::from_name(generator_name),
+            voxel_extent,
+            scale_factor,
+            seed,
+        }
+    }
+}
+
+#[roc(dependencies = [impact_math::hash::Hash64])]
+impl VoxelizedMeshObject {
+    #[roc(
+        body = "{ voxelized_mesh_id: Hashing.hash_str_64(voxelized_mesh_name), voxel_extent, scale_factor }"
+    )]
+    pub fn new(voxelized_mesh_name: &str, voxel_extent: f32, scale_factor: f32) -> Self {
+        Self {
+            voxelized_mesh_id: VoxelizedMeshID::from_name(voxelized_mesh_name),
+            voxel_extent,
+            scale_factor,
+        }
+    }
+}
+
+#[roc(dependencies = [impact_math::hash::Hash64])]
+impl VoxModelObject {
+    #[roc(
+        body = "{ vox_model_id: Hashing.hash_str_64(vox_model_name), voxel_extent, smoothing_iterations }"
+    )]
+    pub fn new(vox_model_name: &str, voxel_extent: f32, smoothing_iterations: u32) -> Self {
+        Self {
+            vox_model_id: VoxModelID::from_name(vox_model_name),
+            voxel_extent,
+            smoothing_iterations,
+        }
+    }
+}
+
 #[roc]
 impl SameVoxelType {
     #[roc(body = "{ voxel_type_name_hash: Hashing.hash_str_32(voxel_type_name) }")]
@@ -189,6 +277,10 @@ impl SameVoxelType {
         }
     }
 
+    pub fn voxel_type_name_hash(&self) -> Hash32 {
+        self.voxel_type_name_hash
+    }
+
     pub fn voxel_type(&self, voxel_type_registry: &VoxelTypeRegistry) -> Result<VoxelType> {
         voxel_type_registry
             .voxel_type_for_name_hash(self.voxel_type_name_hash)
@@ -578,6 +670,29 @@ where
     Ok(())
 }
 
+/// Creates a voxel object for the given entity by reading the given data in
+/// the voxel object file format (see [`crate::object::io`]).
+///
+/// # Errors
+/// Returns an error if the data is not a valid voxel object file.
+pub fn setup_stored_voxel_object(
+    voxel_object_manager: &mut VoxelObjectManager,
+    voxel_object_buffer_pool: &mut VoxelObjectBufferPool,
+    encoded_object: &[u8],
+    entity_id: EntityID,
+) -> Result<()> {
+    let buffers = voxel_object_buffer_pool.take_or_create_buffers();
+
+    let voxel_object = io::read_voxel_object(buffers.object_buffers, &mut &encoded_object[..])?;
+
+    let meshed_voxel_object = MeshedVoxelObject::create(buffers.mesh_buffers, voxel_object);
+
+    let voxel_object_id = VoxelObjectID::from_entity_id(entity_id);
+    voxel_object_manager.add_voxel_object(voxel_object_id, meshed_voxel_object)?;
+
+    Ok(())
+}
+
 pub fn setup_dynamic_rigid_body_for_voxel_object(
     rigid_body_manager: &mut RigidBodyManager,
     voxel_object_manager: &mut VoxelObjectManager,
@@ -672,6 +787,7 @@ pub fn create_model_instance_node_for_voxel_object(
         model_instance_id,
         model_to_parent_transform.compact(),
         model_id,
+        Vec::new(),
         FeatureIDSet::from_iter([model_view_transform_feature_id, voxel_object_id_feature_id]),
         FeatureIDSet::from_iter([model_light_transform_feature_id, voxel_object_id_feature_id]),
         flags.into(),
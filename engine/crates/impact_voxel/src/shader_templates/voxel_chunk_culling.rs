@@ -14,7 +14,8 @@ use std::sync::LazyLock;
 /// Shader template for a voxel chunk culling compute pass, which determines
 /// which of the chunks in a voxel object lie outside a frustum and updates an
 /// indirect draw parameter buffer so that those chunks will be excluded in a
-/// subsequent indirect draw call.
+/// subsequent indirect draw call. The remaining chunks are drawn with a level
+/// of detail selected from their distance to the frustum apex.
 #[derive(Clone, Debug)]
 pub struct VoxelChunkCullingShaderTemplate {
     /// Whether the draw call arguments to update are for indexed draw calls.
@@ -40,6 +41,7 @@ impl VoxelChunkCullingShaderTemplate {
     pub fn push_constants() -> VoxelPushConstantGroup {
         VoxelPushConstantGroup::for_compute([
             VoxelPushConstantVariant::CullingFrustum,
+            VoxelPushConstantVariant::ChunkLODSelection,
             VoxelPushConstantVariant::ChunkCount,
             VoxelPushConstantVariant::Rendering(BasicPushConstantVariant::InstanceIdx),
         ])
@@ -80,4 +82,11 @@ mod tests {
             for_indexed_draw_calls: false,
         });
     }
+
+    #[test]
+    fn should_resolve_to_valid_wgsl_for_indexed_draw_calls() {
+        validate_template(&VoxelChunkCullingShaderTemplate {
+            for_indexed_draw_calls: true,
+        });
+    }
 }
@@ -25,6 +25,7 @@ use std::sync::LazyLock;
 pub struct VoxelGeometryShaderTemplate {
     n_voxel_types: usize,
     texture_frequency: f64,
+    writes_entity_id: bool,
 }
 
 static TEMPLATE: LazyLock<ShaderTemplate<'static>> =
@@ -33,31 +34,60 @@ static TEMPLATE: LazyLock<ShaderTemplate<'static>> =
 impl VoxelGeometryShaderTemplate {
     /// Creates a new voxel geometry shader template for the given number of
     /// registered voxel types and the given frequency factor determining the
-    /// spatial extent of features in the textures.
-    pub fn new(n_voxel_types: usize, texture_frequency: f64) -> Self {
+    /// spatial extent of features in the textures. If `writes_entity_id` is
+    /// `true`, the shader will also write the entity ID of each voxel object
+    /// and the indices of the voxel under each fragment to the entity ID
+    /// render attachment.
+    pub fn new(n_voxel_types: usize, texture_frequency: f64, writes_entity_id: bool) -> Self {
         assert!(n_voxel_types > 0);
         Self {
             n_voxel_types,
             texture_frequency,
+            writes_entity_id,
         }
     }
 
     /// Returns the group of push constants used by the shader.
+    ///
+    /// The entity ID and inverse voxel extent are only used by the shader when
+    /// it writes entity IDs, but they are always included so that the same
+    /// pipeline layout can be used for both variants.
     pub fn push_constants() -> VoxelPushConstantGroup {
         PushConstantGroup::for_vertex_fragment([
             VoxelPushConstantVariant::Rendering(BasicPushConstantVariant::InverseWindowDimensions),
             VoxelPushConstantVariant::Rendering(BasicPushConstantVariant::FrameCounter),
             VoxelPushConstantVariant::Rendering(BasicPushConstantVariant::Exposure),
             VoxelPushConstantVariant::Rendering(BasicPushConstantVariant::GenericVec3f32), // Offsets for texture coordinates
+            VoxelPushConstantVariant::EntityID,
+            VoxelPushConstantVariant::InverseVoxelExtent,
         ])
     }
+
+    /// Returns a copy of this shader template that writes entity IDs if
+    /// `writes_entity_id` is `true`.
+    pub fn with_writes_entity_id(self, writes_entity_id: bool) -> Self {
+        Self {
+            writes_entity_id,
+            ..self
+        }
+    }
+
+    /// Whether this instance of the shader template writes entity IDs.
+    pub fn writes_entity_id(&self) -> bool {
+        self.writes_entity_id
+    }
 }
 
 impl SpecificShaderTemplate for VoxelGeometryShaderTemplate {
     fn resolve(&self) -> String {
+        let flags_to_set: &[_] = if self.writes_entity_id {
+            &["writes_entity_id"]
+        } else {
+            &[]
+        };
         TEMPLATE
             .resolve(
-                &[],
+                flags_to_set,
                 shader_template_replacements!(
                     "jitter_count" => CameraProjectionUniform::jitter_count(),
                     "texture_frequency" => self.texture_frequency,
@@ -77,6 +107,7 @@ impl SpecificShaderTemplate for VoxelGeometryShaderTemplate {
                     "position_and_normal_group" => 2,
                     "position_buffer_binding" => 0,
                     "normal_buffer_binding" => 1,
+                    "temperature_buffer_binding" => 2,
                     "index_location" => VoxelMeshVertexAttributeLocation::Indices as u32,
                     "material_indices_location" => VoxelMeshVertexAttributeLocation::MaterialIndices as u32,
                     "material_weights_location" => VoxelMeshVertexAttributeLocation::MaterialWeights as u32,
@@ -94,6 +125,11 @@ mod tests {
 
     #[test]
     fn should_resolve_to_valid_wgsl() {
-        validate_template(&VoxelGeometryShaderTemplate::new(5, 1.0));
+        validate_template(&VoxelGeometryShaderTemplate::new(5, 1.0, false));
+    }
+
+    #[test]
+    fn should_resolve_to_valid_wgsl_writing_entity_id() {
+        validate_template(&VoxelGeometryShaderTemplate::new(5, 1.0, true));
     }
 }
@@ -0,0 +1,1161 @@
+//! Voxel terrain of unbounded extent, generated in tiles around an observer.
+
+#[cfg(feature = "ecs")]
+pub mod systems;
+
+use crate::{
+    Voxel, VoxelObjectBufferPool, VoxelObjectID, VoxelObjectManager,
+    collidable::{
+        CollisionWorld,
+        setup::{self, VoxelCollidable},
+    },
+    generation::{ChunkedVoxelGenerator, SDFVoxelGenerator, SDFVoxelGeneratorChunkBuffers},
+    mesh::{MeshedVoxelObject, MeshedVoxelObjectBuffers},
+    object::{CHUNK_SIZE, ChunkSparseness, VoxelObject},
+};
+use anyhow::{Result, bail};
+use bytemuck::{Pod, Zeroable};
+use impact_alloc::Allocator;
+use impact_containers::{HashMap, NoHashMap};
+use impact_geometry::AxisAlignedBox;
+use impact_id::{EntityID, EntityIDManager, define_entity_id_newtype};
+use impact_math::{point::Point3, vector::Vector3};
+use impact_physics::{collision::CollidableID, rigid_body::RigidBodyType};
+use impact_thread::{
+    channel,
+    pool::{DynamicTask, DynamicThreadPool},
+};
+use roc_integration::roc;
+use std::{num::NonZeroUsize, sync::Arc, time::Instant};
+
+define_entity_id_newtype! {
+    /// Identifier for a [`VoxelTerrain`] in a [`VoxelTerrainManager`].
+    [pub] VoxelTerrainID
+}
+
+define_component_type! {
+    /// Marks that an entity has a voxel terrain identified by a
+    /// [`VoxelTerrainID`].
+    ///
+    /// Use [`VoxelTerrainID::from_entity_id`] to obtain the terrain ID from the
+    /// entity ID.
+    #[roc(parents = "Comp")]
+    #[repr(C)]
+    #[derive(Copy, Clone, Debug, Zeroable, Pod)]
+    pub struct HasVoxelTerrain;
+}
+
+pub trait VoxelTerrainContext {
+    /// Whether the entity with the given ID exists.
+    fn entity_exists(&self, entity_id: EntityID) -> bool;
+
+    /// Creates an entity with the given ID for the terrain tile whose voxel
+    /// object has been added to the voxel object manager under the same ID.
+    /// The lower corner of the tile's voxel grid should be placed at the given
+    /// world space position.
+    fn create_terrain_tile_entity(&mut self, entity_id: EntityID, position: &Point3);
+
+    /// Removes the entity for the terrain tile with the given ID.
+    fn remove_terrain_tile_entity(&mut self, entity_id: EntityID);
+}
+
+/// Manager of all [`VoxelTerrain`]s in a scene.
+#[derive(Debug)]
+pub struct VoxelTerrainManager {
+    terrains: NoHashMap<VoxelTerrainID, VoxelTerrain>,
+    removed_terrains: Vec<VoxelTerrain>,
+    config: VoxelTerrainConfig,
+    tile_generation_thread_pool: Option<DynamicThreadPool>,
+}
+
+/// A voxel terrain of unbounded extent. The terrain is divided into cubic tiles
+/// of voxel chunks that are generated from an [`SDFVoxelGenerator`] when they
+/// come within the load distance of the observer and discarded when they move
+/// beyond the unload distance. Each tile is a separate voxel object entity with
+/// a kinematic rigid body.
+///
+/// Every tile is generated with an additional layer of padding chunks
+/// overlapping the neighbouring tiles. The padding chunks are not meshed, but
+/// they provide the boundary chunks of the tile with the same signed distances
+/// as the neighbouring tiles, so that adjacent tile meshes line up without
+/// seams.
+///
+/// Tiles are never modified after generation, since they are regenerated from
+/// the generator whenever they are reloaded.
+///
+/// When the manager has tile generation threads, tiles are generated in the
+/// background and added to the terrain in the first update after they are
+/// finished.
+#[derive(Debug)]
+pub struct VoxelTerrain {
+    generator: Arc<SDFVoxelGenerator>,
+    origin: Point3,
+    collidable: Option<VoxelCollidable>,
+    tiles: HashMap<[i32; 3], TerrainTile>,
+    pending_tile_count: usize,
+    tile_result_sender: channel::Sender<TileGenerationResult>,
+    tile_result_receiver: channel::Receiver<TileGenerationResult>,
+}
+
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+#[derive(Clone, Debug)]
+pub struct VoxelTerrainConfig {
+    /// The number of voxel chunks along each axis of a terrain tile.
+    pub tile_chunk_count: usize,
+    /// Tiles closer to the observer than this distance are generated.
+    pub load_distance: f32,
+    /// Tiles farther from the observer than this distance are discarded.
+    pub unload_distance: f32,
+    /// Tiles closer to the observer than this distance get a collidable.
+    pub collision_distance: f32,
+    /// The maximum number of tiles to generate for each terrain in a single
+    /// update, or to have in progress at any time when tiles are generated in
+    /// the background. The closest tiles are generated first.
+    pub max_tiles_generated_per_update: usize,
+    /// The number of background threads generating terrain tiles. If zero,
+    /// tiles are generated on the calling thread during each update.
+    pub tile_generation_thread_count: usize,
+}
+
+/// Generator for the voxels of a single tile in a [`VoxelTerrain`], including
+/// the padding chunks overlapping the neighbouring tiles.
+#[derive(Clone, Debug)]
+pub struct VoxelTerrainTileGenerator<'a> {
+    generator: &'a SDFVoxelGenerator,
+    grid_shape: [usize; 3],
+    origin_in_root_space: Point3,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum TerrainTile {
+    /// The tile is being generated in the background.
+    Pending,
+    /// The tile has no surface to mesh, so it has no voxel object.
+    Empty,
+    Loaded {
+        entity_id: EntityID,
+        has_collidable: bool,
+    },
+}
+
+#[derive(Debug)]
+enum TileGenerationResult {
+    Generated([i32; 3], MeshedVoxelObject),
+    NotGenerated([i32; 3], MeshedVoxelObjectBuffers),
+}
+
+impl VoxelTerrainManager {
+    /// The number of padding chunk layers on each face of a terrain tile's
+    /// chunk grid.
+    pub const TILE_PADDING_CHUNK_LAYERS: usize = 1;
+
+    const TILE_GENERATION_QUEUE_CAPACITY: NonZeroUsize = NonZeroUsize::new(256).unwrap();
+
+    /// Creates a new terrain manager with no terrains and the given
+    /// configuration. The configured number of tile generation threads are
+    /// spawned.
+    pub fn new(config: VoxelTerrainConfig) -> Result<Self> {
+        config.validate()?;
+
+        let tile_generation_thread_pool = NonZeroUsize::new(config.tile_generation_thread_count)
+            .map(|n_workers| {
+                DynamicThreadPool::new_dynamic(n_workers, Self::TILE_GENERATION_QUEUE_CAPACITY)
+            });
+
+        Ok(Self {
+            terrains: NoHashMap::default(),
+            removed_terrains: Vec::new(),
+            config,
+            tile_generation_thread_pool,
+        })
+    }
+
+    /// Returns a reference to the terrain configuration.
+    pub fn config(&self) -> &VoxelTerrainConfig {
+        &self.config
+    }
+
+    /// Returns the number of terrains in the manager.
+    pub fn terrain_count(&self) -> usize {
+        self.terrains.len()
+    }
+
+    /// Returns a reference to the [`VoxelTerrain`] with the given ID, or
+    /// [`None`] if the terrain is not present.
+    pub fn get_terrain(&self, terrain_id: VoxelTerrainID) -> Option<&VoxelTerrain> {
+        self.terrains.get(&terrain_id)
+    }
+
+    /// Whether a terrain with the given ID exists in the manager.
+    pub fn has_terrain(&self, terrain_id: VoxelTerrainID) -> bool {
+        self.terrains.contains_key(&terrain_id)
+    }
+
+    /// Returns the total number of tiles being generated in the background
+    /// for all terrains.
+    pub fn pending_tile_count(&self) -> usize {
+        self.terrains
+            .values()
+            .map(VoxelTerrain::pending_tile_count)
+            .sum()
+    }
+
+    /// Adds the given [`VoxelTerrain`] to the manager under the given ID.
+    ///
+    /// # Errors
+    /// Returns an error if a terrain with the given ID already exists.
+    pub fn add_terrain(&mut self, terrain_id: VoxelTerrainID, terrain: VoxelTerrain) -> Result<()> {
+        if self.terrains.contains_key(&terrain_id) {
+            bail!("A voxel terrain with ID {terrain_id} is already present");
+        }
+        self.terrains.insert(terrain_id, terrain);
+        Ok(())
+    }
+
+    /// Removes the terrain with the given ID if it exists. The entities for
+    /// its tiles are removed in the next call to [`Self::update_terrains`].
+    pub fn remove_terrain(&mut self, terrain_id: VoxelTerrainID) {
+        if let Some(terrain) = self.terrains.remove(&terrain_id) {
+            self.removed_terrains.push(terrain);
+        }
+    }
+
+    /// Brings the tiles of all terrains up to date with the given observer
+    /// position (typically the camera position). Tiles beyond the unload
+    /// distance are removed along with their entities, and up to the
+    /// configured number of missing tiles within the load distance are
+    /// generated, closest first, and added as voxel object entities.
+    /// Collidables are added for tiles within the collision distance and
+    /// removed from tiles beyond it.
+    ///
+    /// If the manager has tile generation threads, the missing tiles are only
+    /// submitted for generation, and tiles finished since the previous update
+    /// are added instead.
+    pub fn update_terrains<C>(
+        &mut self,
+        context: &mut C,
+        entity_id_manager: &mut EntityIDManager,
+        voxel_object_manager: &mut VoxelObjectManager,
+        voxel_object_buffer_pool: &mut VoxelObjectBufferPool,
+        collision_world: &mut CollisionWorld,
+        observer_position: &Point3,
+    ) where
+        C: VoxelTerrainContext,
+    {
+        for mut terrain in self.removed_terrains.drain(..) {
+            terrain.unload_all_tiles(context, collision_world);
+        }
+
+        for (terrain_id, terrain) in &mut self.terrains {
+            terrain.unload_distant_tiles(context, collision_world, &self.config, observer_position);
+
+            terrain.load_nearby_tiles(
+                context,
+                self.tile_generation_thread_pool.as_ref(),
+                entity_id_manager,
+                voxel_object_manager,
+                voxel_object_buffer_pool,
+                &self.config,
+                *terrain_id,
+                observer_position,
+            );
+
+            terrain.update_tile_collidables(
+                context,
+                collision_world,
+                &self.config,
+                observer_position,
+            );
+        }
+    }
+
+    /// Removes all terrains without removing the entities for their tiles.
+    pub fn reset_and_free(&mut self) {
+        self.terrains.clear();
+        self.removed_terrains.clear();
+    }
+}
+
+impl VoxelTerrain {
+    /// Creates a new terrain with no tiles, using the given generator for
+    /// generating the voxels of the tiles. The origin of the generator's root
+    /// SDF coordinate space is placed at the given world space position. If a
+    /// collidable is given, it is used for tiles within the collision
+    /// distance of the observer.
+    pub fn new(
+        generator: SDFVoxelGenerator,
+        origin: Point3,
+        collidable: Option<VoxelCollidable>,
+    ) -> Self {
+        let (tile_result_sender, tile_result_receiver) = channel::unbounded();
+        Self {
+            generator: Arc::new(generator),
+            origin,
+            collidable,
+            tiles: HashMap::default(),
+            pending_tile_count: 0,
+            tile_result_sender,
+            tile_result_receiver,
+        }
+    }
+
+    /// Returns a reference to the generator for the terrain voxels.
+    pub fn generator(&self) -> &SDFVoxelGenerator {
+        &self.generator
+    }
+
+    /// Returns the world space position of the origin of the terrain.
+    pub fn origin(&self) -> &Point3 {
+        &self.origin
+    }
+
+    /// Returns the number of tiles currently tracked by the terrain,
+    /// including tiles found to contain no surface and tiles being generated.
+    pub fn tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Returns the number of tiles being generated in the background.
+    pub fn pending_tile_count(&self) -> usize {
+        self.pending_tile_count
+    }
+
+    /// Returns an iterator over the IDs of the entities for the currently
+    /// loaded tiles.
+    pub fn loaded_tile_entity_ids(&self) -> impl Iterator<Item = EntityID> {
+        self.tiles.values().filter_map(|tile| match tile {
+            TerrainTile::Loaded { entity_id, .. } => Some(*entity_id),
+            TerrainTile::Pending | TerrainTile::Empty => None,
+        })
+    }
+
+    /// Returns the extent of a single tile with the given number of chunks
+    /// along each axis.
+    pub fn tile_extent(&self, tile_chunk_count: usize) -> f32 {
+        (tile_chunk_count * CHUNK_SIZE) as f32 * self.generator.voxel_extent()
+    }
+
+    /// Returns the indices of the tile containing the given world space
+    /// position.
+    pub fn tile_indices_containing(&self, position: &Point3, tile_chunk_count: usize) -> [i32; 3] {
+        let tile_extent = self.tile_extent(tile_chunk_count);
+        let relative_position = position - self.origin;
+        [
+            (relative_position.x() / tile_extent).floor() as i32,
+            (relative_position.y() / tile_extent).floor() as i32,
+            (relative_position.z() / tile_extent).floor() as i32,
+        ]
+    }
+
+    /// Returns the world space axis-aligned box covered by the tile with the
+    /// given indices, excluding its padding chunks.
+    pub fn compute_tile_aabb(
+        &self,
+        tile_indices: &[i32; 3],
+        tile_chunk_count: usize,
+    ) -> AxisAlignedBox {
+        compute_tile_aabb(
+            &self.origin,
+            self.tile_extent(tile_chunk_count),
+            tile_indices,
+        )
+    }
+
+    /// Creates a generator for the voxels of the tile with the given indices.
+    pub fn create_tile_generator(
+        &self,
+        tile_indices: &[i32; 3],
+        tile_chunk_count: usize,
+    ) -> VoxelTerrainTileGenerator<'_> {
+        VoxelTerrainTileGenerator::new(&self.generator, tile_indices, tile_chunk_count)
+    }
+
+    /// Generates the voxel object for the tile with the given indices using
+    /// the given buffers and meshes all its non-padding chunks.
+    ///
+    /// # Returns
+    /// [`None`] if the tile has no surface to mesh, in which case the buffers
+    /// are returned instead.
+    pub fn generate_tile(
+        &self,
+        tile_indices: &[i32; 3],
+        tile_chunk_count: usize,
+        buffers: MeshedVoxelObjectBuffers,
+    ) -> Result<MeshedVoxelObject, MeshedVoxelObjectBuffers> {
+        generate_tile(&self.generator, tile_indices, tile_chunk_count, buffers)
+    }
+
+    fn tile_position(&self, tile_indices: &[i32; 3], tile_chunk_count: usize) -> Point3 {
+        let grid_aabb_in_root_space = VoxelTerrainTileGenerator::compute_grid_aabb_in_root_space(
+            tile_indices,
+            tile_chunk_count,
+        );
+        self.origin
+            + grid_aabb_in_root_space.lower_corner().as_vector() * self.generator.voxel_extent()
+    }
+
+    fn unload_all_tiles<C>(&mut self, context: &mut C, collision_world: &mut CollisionWorld)
+    where
+        C: VoxelTerrainContext,
+    {
+        // Tiles still being generated are discarded when they are finished,
+        // since the receiver is dropped along with the terrain
+        self.pending_tile_count = 0;
+
+        for (_, tile) in self.tiles.drain() {
+            if let TerrainTile::Loaded {
+                entity_id,
+                has_collidable,
+            } = tile
+            {
+                if has_collidable {
+                    collision_world.remove_collidable(CollidableID::from_entity_id(entity_id));
+                }
+                context.remove_terrain_tile_entity(entity_id);
+            }
+        }
+    }
+
+    fn unload_distant_tiles<C>(
+        &mut self,
+        context: &mut C,
+        collision_world: &mut CollisionWorld,
+        config: &VoxelTerrainConfig,
+        observer_position: &Point3,
+    ) where
+        C: VoxelTerrainContext,
+    {
+        let tile_extent = self.tile_extent(config.tile_chunk_count);
+
+        self.tiles.retain(|tile_indices, tile| {
+            let aabb = compute_tile_aabb(&self.origin, tile_extent, tile_indices);
+            if compute_distance_to_aabb(&aabb, observer_position) <= config.unload_distance {
+                return true;
+            }
+            match *tile {
+                // We can not cancel the generation, so the tile is discarded
+                // when it is finished instead
+                TerrainTile::Pending => true,
+                TerrainTile::Empty => false,
+                TerrainTile::Loaded {
+                    entity_id,
+                    has_collidable,
+                } => {
+                    // Removing an entity that has not been created yet is not
+                    // possible, so we wait until it exists
+                    if !context.entity_exists(entity_id) {
+                        return true;
+                    }
+                    if has_collidable {
+                        collision_world.remove_collidable(CollidableID::from_entity_id(entity_id));
+                    }
+                    context.remove_terrain_tile_entity(entity_id);
+                    false
+                }
+            }
+        });
+    }
+
+    fn load_nearby_tiles<C>(
+        &mut self,
+        context: &mut C,
+        thread_pool: Option<&DynamicThreadPool>,
+        entity_id_manager: &mut EntityIDManager,
+        voxel_object_manager: &mut VoxelObjectManager,
+        voxel_object_buffer_pool: &mut VoxelObjectBufferPool,
+        config: &VoxelTerrainConfig,
+        terrain_id: VoxelTerrainID,
+        observer_position: &Point3,
+    ) where
+        C: VoxelTerrainContext,
+    {
+        let mut handle_result = |terrain: &mut Self,
+                                 voxel_object_buffer_pool: &mut VoxelObjectBufferPool,
+                                 result: TileGenerationResult| {
+            let (tile_indices, result) = match result {
+                TileGenerationResult::Generated(tile_indices, voxel_object) => {
+                    (tile_indices, Ok(voxel_object))
+                }
+                TileGenerationResult::NotGenerated(tile_indices, buffers) => {
+                    (tile_indices, Err(buffers))
+                }
+            };
+
+            if matches!(terrain.tiles.get(&tile_indices), Some(TerrainTile::Pending)) {
+                terrain.pending_tile_count -= 1;
+
+                // The observer may have moved away while the tile was
+                // generated
+                let aabb = terrain.compute_tile_aabb(&tile_indices, config.tile_chunk_count);
+                if compute_distance_to_aabb(&aabb, observer_position) > config.unload_distance {
+                    terrain.tiles.remove(&tile_indices);
+                    voxel_object_buffer_pool.add_buffers(match result {
+                        Ok(voxel_object) => voxel_object.into_buffers(),
+                        Err(buffers) => buffers,
+                    });
+                    return;
+                }
+            }
+
+            match result {
+                Ok(voxel_object) => {
+                    let entity_id = entity_id_manager.provide_id();
+
+                    voxel_object_manager
+                        .add_voxel_object(VoxelObjectID::from_entity_id(entity_id), voxel_object)
+                        .expect("Voxel object for new terrain tile entity should not exist");
+
+                    let position = terrain.tile_position(&tile_indices, config.tile_chunk_count);
+                    context.create_terrain_tile_entity(entity_id, &position);
+
+                    terrain.tiles.insert(
+                        tile_indices,
+                        TerrainTile::Loaded {
+                            entity_id,
+                            has_collidable: false,
+                        },
+                    );
+                }
+                Err(buffers) => {
+                    voxel_object_buffer_pool.add_buffers(buffers);
+                    terrain.tiles.insert(tile_indices, TerrainTile::Empty);
+                }
+            }
+        };
+
+        while let Ok(result) = self.tile_result_receiver.try_recv() {
+            handle_result(self, voxel_object_buffer_pool, result);
+        }
+
+        let max_tile_count = config
+            .max_tiles_generated_per_update
+            .saturating_sub(self.pending_tile_count);
+
+        let tile_indices_to_load =
+            self.find_tiles_to_load(config, observer_position, max_tile_count);
+
+        if tile_indices_to_load.is_empty() {
+            return;
+        }
+
+        let tile_count = tile_indices_to_load.len();
+
+        if let Some(thread_pool) = thread_pool {
+            for tile_indices in tile_indices_to_load {
+                let generator = Arc::clone(&self.generator);
+                let result_sender = self.tile_result_sender.clone();
+                let tile_chunk_count = config.tile_chunk_count;
+                let buffers = voxel_object_buffer_pool.take_or_create_buffers();
+
+                let task = DynamicTask::new(move |_| {
+                    let result = generate_tile_with_result(
+                        &generator,
+                        tile_indices,
+                        tile_chunk_count,
+                        buffers,
+                    );
+                    // The terrain may have been removed in the meantime, in
+                    // which case the result is not needed
+                    let _ = result_sender.send(result);
+                });
+
+                if let Err(error) = thread_pool.execute([task]) {
+                    log::warn!(
+                        "Failed to submit tile generation for voxel terrain {terrain_id}: {error}"
+                    );
+                    break;
+                }
+
+                self.tiles.insert(tile_indices, TerrainTile::Pending);
+                self.pending_tile_count += 1;
+            }
+
+            log::debug!(
+                "Requested generation of {tile_count} tile(s) for voxel terrain {terrain_id}"
+            );
+        } else {
+            let start_time = log::log_enabled!(log::Level::Debug).then(Instant::now);
+
+            for tile_indices in tile_indices_to_load {
+                let buffers = voxel_object_buffer_pool.take_or_create_buffers();
+                let result = generate_tile_with_result(
+                    &self.generator,
+                    tile_indices,
+                    config.tile_chunk_count,
+                    buffers,
+                );
+                handle_result(self, voxel_object_buffer_pool, result);
+            }
+
+            if let Some(start_time) = start_time {
+                log::debug!(
+                    "Generated {tile_count} tile(s) in {elapsed_ms:.2} ms for voxel terrain {terrain_id}",
+                    elapsed_ms = 1e3 * start_time.elapsed().as_secs_f64()
+                );
+            }
+        }
+    }
+
+    /// Finds the indices of the tiles within the load distance of the
+    /// observer that have not been generated or requested yet, sorted by
+    /// increasing distance and limited to the given maximum number of tiles.
+    fn find_tiles_to_load(
+        &self,
+        config: &VoxelTerrainConfig,
+        observer_position: &Point3,
+        max_tile_count: usize,
+    ) -> Vec<[i32; 3]> {
+        if max_tile_count == 0 {
+            return Vec::new();
+        }
+
+        let tile_chunk_count = config.tile_chunk_count;
+        let tile_extent = self.tile_extent(tile_chunk_count);
+
+        let observer_tile_indices =
+            self.tile_indices_containing(observer_position, tile_chunk_count);
+        let tile_radius = (config.load_distance / tile_extent).ceil() as i32;
+
+        let mut candidates = Vec::new();
+
+        for i in -tile_radius..=tile_radius {
+            for j in -tile_radius..=tile_radius {
+                for k in -tile_radius..=tile_radius {
+                    let tile_indices = [
+                        observer_tile_indices[0] + i,
+                        observer_tile_indices[1] + j,
+                        observer_tile_indices[2] + k,
+                    ];
+                    if self.tiles.contains_key(&tile_indices) {
+                        continue;
+                    }
+                    let aabb = compute_tile_aabb(&self.origin, tile_extent, &tile_indices);
+                    let distance = compute_distance_to_aabb(&aabb, observer_position);
+                    if distance <= config.load_distance {
+                        candidates.push((distance, tile_indices));
+                    }
+                }
+            }
+        }
+
+        candidates
+            .sort_unstable_by(|(distance_a, _), (distance_b, _)| distance_a.total_cmp(distance_b));
+
+        candidates
+            .into_iter()
+            .take(max_tile_count)
+            .map(|(_, tile_indices)| tile_indices)
+            .collect()
+    }
+
+    fn update_tile_collidables<C>(
+        &mut self,
+        context: &C,
+        collision_world: &mut CollisionWorld,
+        config: &VoxelTerrainConfig,
+        observer_position: &Point3,
+    ) where
+        C: VoxelTerrainContext,
+    {
+        let Some(collidable) = self.collidable else {
+            return;
+        };
+
+        let tile_extent = self.tile_extent(config.tile_chunk_count);
+
+        for (tile_indices, tile) in &mut self.tiles {
+            let TerrainTile::Loaded {
+                entity_id,
+                has_collidable,
+            } = tile
+            else {
+                continue;
+            };
+
+            let aabb = compute_tile_aabb(&self.origin, tile_extent, tile_indices);
+            let should_have_collidable =
+                compute_distance_to_aabb(&aabb, observer_position) <= config.collision_distance;
+
+            if should_have_collidable && !*has_collidable {
+                // The collidable can only be added once the tile entity and
+                // its rigid body have been created
+                if !context.entity_exists(*entity_id) {
+                    continue;
+                }
+                if let Err(error) = setup::setup_voxel_collidable(
+                    collision_world,
+                    *entity_id,
+                    RigidBodyType::Kinematic,
+                    &collidable,
+                    None,
+                ) {
+                    log::error!("Failed to add collidable for voxel terrain tile: {error:#}");
+                    continue;
+                }
+                *has_collidable = true;
+            } else if !should_have_collidable && *has_collidable {
+                collision_world.remove_collidable(CollidableID::from_entity_id(*entity_id));
+                *has_collidable = false;
+            }
+        }
+    }
+}
+
+/// Generates the voxel object for the tile with the given indices using the
+/// given buffers and meshes all its non-padding chunks. The buffers are
+/// returned if the tile has no surface to mesh.
+fn generate_tile(
+    generator: &SDFVoxelGenerator,
+    tile_indices: &[i32; 3],
+    tile_chunk_count: usize,
+    buffers: MeshedVoxelObjectBuffers,
+) -> Result<MeshedVoxelObject, MeshedVoxelObjectBuffers> {
+    if !tile_may_have_surface(generator, tile_indices, tile_chunk_count) {
+        return Err(buffers);
+    }
+
+    let tile_generator = VoxelTerrainTileGenerator::new(generator, tile_indices, tile_chunk_count);
+
+    let voxel_object = VoxelObject::generate(buffers.object_buffers, &tile_generator);
+
+    if voxel_object.contains_only_empty_voxels() {
+        let mut buffers = MeshedVoxelObjectBuffers {
+            object_buffers: voxel_object.into_buffers(),
+            mesh_buffers: buffers.mesh_buffers,
+        };
+        buffers.clear();
+        return Err(buffers);
+    }
+
+    let meshed_voxel_object = MeshedVoxelObject::create_with_padding_chunks(
+        buffers.mesh_buffers,
+        voxel_object,
+        VoxelTerrainManager::TILE_PADDING_CHUNK_LAYERS,
+    );
+
+    if meshed_voxel_object.mesh().n_chunks() == 0 {
+        return Err(meshed_voxel_object.into_buffers());
+    }
+
+    Ok(meshed_voxel_object)
+}
+
+fn generate_tile_with_result(
+    generator: &SDFVoxelGenerator,
+    tile_indices: [i32; 3],
+    tile_chunk_count: usize,
+    buffers: MeshedVoxelObjectBuffers,
+) -> TileGenerationResult {
+    match generate_tile(generator, &tile_indices, tile_chunk_count, buffers) {
+        Ok(voxel_object) => TileGenerationResult::Generated(tile_indices, voxel_object),
+        Err(buffers) => TileGenerationResult::NotGenerated(tile_indices, buffers),
+    }
+}
+
+/// Whether the signed distance field may be negative anywhere within the tile
+/// with the given indices, including its padding chunks.
+fn tile_may_have_surface(
+    generator: &SDFVoxelGenerator,
+    tile_indices: &[i32; 3],
+    tile_chunk_count: usize,
+) -> bool {
+    let tile_grid_aabb_in_root_space =
+        VoxelTerrainTileGenerator::compute_grid_aabb_in_root_space(tile_indices, tile_chunk_count);
+    !generator
+        .sdf_domain()
+        .box_lies_outside(&tile_grid_aabb_in_root_space)
+}
+
+fn compute_tile_aabb(
+    terrain_origin: &Point3,
+    tile_extent: f32,
+    tile_indices: &[i32; 3],
+) -> AxisAlignedBox {
+    let lower_corner = terrain_origin
+        + Vector3::new(
+            tile_indices[0] as f32 * tile_extent,
+            tile_indices[1] as f32 * tile_extent,
+            tile_indices[2] as f32 * tile_extent,
+        );
+    AxisAlignedBox::new(lower_corner, lower_corner + Vector3::same(tile_extent))
+}
+
+fn compute_distance_to_aabb(aabb: &AxisAlignedBox, position: &Point3) -> f32 {
+    let closest_point = aabb.closest_interior_point_to(position);
+    Point3::distance_between(&closest_point, position)
+}
+
+impl VoxelTerrainConfig {
+    fn validate(&self) -> Result<()> {
+        if self.tile_chunk_count == 0 {
+            bail!("Number of chunks per terrain tile must be positive");
+        }
+        if self.load_distance < 0.0 {
+            bail!(
+                "Terrain tile load distance must be non-negative: {}",
+                self.load_distance
+            );
+        }
+        if self.unload_distance < self.load_distance {
+            bail!(
+                "Terrain tile unload distance ({}) must not be smaller than load distance ({})",
+                self.unload_distance,
+                self.load_distance
+            );
+        }
+        if self.collision_distance < 0.0 {
+            bail!(
+                "Terrain tile collision distance must be non-negative: {}",
+                self.collision_distance
+            );
+        }
+        if self.max_tiles_generated_per_update == 0 {
+            bail!("Maximum number of terrain tiles generated per update must be positive");
+        }
+        Ok(())
+    }
+}
+
+impl Default for VoxelTerrainConfig {
+    fn default() -> Self {
+        Self {
+            tile_chunk_count: 4,
+            load_distance: 150.0,
+            unload_distance: 200.0,
+            collision_distance: 30.0,
+            max_tiles_generated_per_update: 8,
+            tile_generation_thread_count: 2,
+        }
+    }
+}
+
+impl<'a> VoxelTerrainTileGenerator<'a> {
+    /// Creates a generator for the voxels of the tile with the given indices
+    /// in a terrain using the given generator and number of chunks along each
+    /// axis of a tile.
+    pub fn new(
+        generator: &'a SDFVoxelGenerator,
+        tile_indices: &[i32; 3],
+        tile_chunk_count: usize,
+    ) -> Self {
+        let padded_chunk_count =
+            tile_chunk_count + 2 * VoxelTerrainManager::TILE_PADDING_CHUNK_LAYERS;
+        let grid_shape = [padded_chunk_count * CHUNK_SIZE; 3];
+
+        let grid_aabb_in_root_space =
+            Self::compute_grid_aabb_in_root_space(tile_indices, tile_chunk_count);
+
+        // The signed distance field is evaluated at the center of each voxel
+        let origin_in_root_space = grid_aabb_in_root_space.lower_corner() + Vector3::same(0.5);
+
+        Self {
+            generator,
+            grid_shape,
+            origin_in_root_space,
+        }
+    }
+
+    /// Computes the axis-aligned box covered by the voxel grid of the tile
+    /// with the given indices, including its padding chunks, in the root SDF
+    /// coordinate space of the terrain generator. The coordinates are in whole
+    /// voxels.
+    fn compute_grid_aabb_in_root_space(
+        tile_indices: &[i32; 3],
+        tile_chunk_count: usize,
+    ) -> AxisAlignedBox {
+        let tile_size = (tile_chunk_count * CHUNK_SIZE) as i64;
+        let padding_size = (VoxelTerrainManager::TILE_PADDING_CHUNK_LAYERS * CHUNK_SIZE) as i64;
+        let padded_tile_size = (tile_size + 2 * padding_size) as f32;
+
+        let lower_corner = Point3::new(
+            (i64::from(tile_indices[0]) * tile_size - padding_size) as f32,
+            (i64::from(tile_indices[1]) * tile_size - padding_size) as f32,
+            (i64::from(tile_indices[2]) * tile_size - padding_size) as f32,
+        );
+
+        AxisAlignedBox::new(lower_corner, lower_corner + Vector3::same(padded_tile_size))
+    }
+}
+
+impl ChunkedVoxelGenerator for VoxelTerrainTileGenerator<'_> {
+    type ChunkGenerationBuffers<AB: Allocator> = SDFVoxelGeneratorChunkBuffers<AB>;
+
+    #[inline]
+    fn voxel_extent(&self) -> f32 {
+        self.generator.voxel_extent()
+    }
+
+    #[inline]
+    fn grid_shape(&self) -> [usize; 3] {
+        self.grid_shape
+    }
+
+    #[inline]
+    fn total_buffer_size(&self) -> usize {
+        self.generator.total_buffer_size()
+    }
+
+    fn create_buffers_in<AB: Allocator>(&self, alloc: AB) -> Self::ChunkGenerationBuffers<AB> {
+        self.generator.create_buffers_in(alloc)
+    }
+
+    fn generate_chunk<AB: Allocator>(
+        &self,
+        buffers: &mut Self::ChunkGenerationBuffers<AB>,
+        voxels: &mut [Voxel],
+        chunk_origin: &[usize; 3],
+    ) -> ChunkSparseness {
+        let chunk_origin_in_root_space = self.origin_in_root_space
+            + Vector3::new(
+                chunk_origin[0] as f32,
+                chunk_origin[1] as f32,
+                chunk_origin[2] as f32,
+            );
+        self.generator
+            .generate_unbounded_chunk(buffers, voxels, &chunk_origin_in_root_space)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        generation::{
+            sdf::{SDFGraph, SDFNode},
+            voxel_type::SameVoxelTypeGenerator,
+        },
+        voxel_types::VoxelType,
+    };
+    use impact_alloc::Global;
+
+    const TILE_CHUNK_COUNT: usize = 1;
+
+    fn create_sphere_terrain(radius: f32) -> VoxelTerrain {
+        let mut graph = SDFGraph::new_in(Global);
+        graph.add_node(SDFNode::new_sphere(radius));
+        let sdf_generator = graph.build_in(Global).unwrap();
+
+        let generator = SDFVoxelGenerator::new(
+            1.0,
+            sdf_generator,
+            SameVoxelTypeGenerator::new(VoxelType::default()).into(),
+        );
+
+        VoxelTerrain::new(generator, Point3::origin(), None)
+    }
+
+    fn generate_all_tiles_in_range(
+        terrain: &VoxelTerrain,
+        range: std::ops::RangeInclusive<i32>,
+    ) -> Vec<([i32; 3], MeshedVoxelObject)> {
+        let mut tiles = Vec::new();
+        for i in range.clone() {
+            for j in range.clone() {
+                for k in range.clone() {
+                    let tile_indices = [i, j, k];
+                    if let Ok(voxel_object) = terrain.generate_tile(
+                        &tile_indices,
+                        TILE_CHUNK_COUNT,
+                        MeshedVoxelObjectBuffers::new(),
+                    ) {
+                        tiles.push((tile_indices, voxel_object));
+                    }
+                }
+            }
+        }
+        tiles
+    }
+
+    #[test]
+    fn tile_indices_are_rounded_towards_negative_infinity() {
+        let terrain = create_sphere_terrain(10.0);
+        let tile_extent = terrain.tile_extent(TILE_CHUNK_COUNT);
+
+        assert_eq!(
+            terrain.tile_indices_containing(&Point3::new(0.5, 0.5, 0.5), TILE_CHUNK_COUNT),
+            [0, 0, 0]
+        );
+        assert_eq!(
+            terrain.tile_indices_containing(
+                &Point3::new(-0.5, tile_extent + 0.5, -tile_extent - 0.5),
+                TILE_CHUNK_COUNT
+            ),
+            [-1, 1, -2]
+        );
+    }
+
+    #[test]
+    fn tiles_outside_sdf_domain_are_not_generated() {
+        let terrain = create_sphere_terrain(10.0);
+        assert!(
+            terrain
+                .generate_tile(
+                    &[10, 0, 0],
+                    TILE_CHUNK_COUNT,
+                    MeshedVoxelObjectBuffers::new()
+                )
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn tile_meshes_exclude_padding_chunks() {
+        let terrain = create_sphere_terrain(20.0);
+        let tiles = generate_all_tiles_in_range(&terrain, -3..=2);
+        assert!(!tiles.is_empty());
+
+        let padding = VoxelTerrainManager::TILE_PADDING_CHUNK_LAYERS as u32;
+        let max_owned_chunk_idx = padding + TILE_CHUNK_COUNT as u32 - 1;
+
+        for (_, voxel_object) in &tiles {
+            for submesh in voxel_object.mesh().chunk_submeshes() {
+                assert!(
+                    submesh
+                        .chunk_indices()
+                        .iter()
+                        .all(|&idx| (padding..=max_owned_chunk_idx).contains(&idx))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn tile_meshes_of_sphere_join_into_closed_surface() {
+        let terrain = create_sphere_terrain(20.0);
+        let tiles = generate_all_tiles_in_range(&terrain, -3..=2);
+        assert!(tiles.len() > 1);
+
+        let mut welded_vertex_indices: HashMap<[i64; 3], usize> = HashMap::default();
+        let mut edge_counts: HashMap<[usize; 2], usize> = HashMap::default();
+
+        for (tile_indices, voxel_object) in &tiles {
+            let tile_position = terrain.tile_position(tile_indices, TILE_CHUNK_COUNT);
+            let mesh = voxel_object
+                .mesh()
+                .create_triangle_mesh(voxel_object.object().voxel_extent());
+
+            let welded_indices: Vec<usize> = mesh
+                .positions()
+                .iter()
+                .map(|position| {
+                    let world_position = tile_position + position.0.aligned().as_vector();
+                    let key = [world_position.x(), world_position.y(), world_position.z()]
+                        .map(|coord| (coord * 1e3).round() as i64);
+                    let next_index = welded_vertex_indices.len();
+                    *welded_vertex_indices.entry(key).or_insert(next_index)
+                })
+                .collect();
+
+            for [i, j, k] in mesh.triangle_indices() {
+                let [i, j, k] = [welded_indices[i], welded_indices[j], welded_indices[k]];
+                for (a, b) in [(i, j), (j, k), (k, i)] {
+                    *edge_counts.entry([a.min(b), a.max(b)]).or_default() += 1;
+                }
+            }
+        }
+
+        // If the tiles are stitched without seams, every edge is shared by
+        // exactly two triangles
+        assert!(edge_counts.values().all(|&count| count == 2));
+    }
+
+    #[derive(Debug, Default)]
+    struct HeadlessContext {
+        tile_entity_ids: Vec<EntityID>,
+    }
+
+    impl VoxelTerrainContext for HeadlessContext {
+        fn entity_exists(&self, entity_id: EntityID) -> bool {
+            self.tile_entity_ids.contains(&entity_id)
+        }
+
+        fn create_terrain_tile_entity(&mut self, entity_id: EntityID, _position: &Point3) {
+            self.tile_entity_ids.push(entity_id);
+        }
+
+        fn remove_terrain_tile_entity(&mut self, entity_id: EntityID) {
+            self.tile_entity_ids.retain(|&id| id != entity_id);
+        }
+    }
+
+    #[test]
+    fn tiles_generated_in_background_are_loaded_once_in_later_updates() {
+        let config = VoxelTerrainConfig {
+            tile_chunk_count: TILE_CHUNK_COUNT,
+            load_distance: 40.0,
+            unload_distance: 60.0,
+            collision_distance: 0.0,
+            max_tiles_generated_per_update: 4,
+            tile_generation_thread_count: 2,
+        };
+        let mut manager = VoxelTerrainManager::new(config).unwrap();
+        let mut entity_id_manager = EntityIDManager::new();
+        let mut voxel_object_manager = VoxelObjectManager::new();
+        let mut voxel_object_buffer_pool = VoxelObjectBufferPool::new();
+        let mut collision_world = CollisionWorld::new();
+        let mut context = HeadlessContext::default();
+
+        let terrain_id = VoxelTerrainID::from_entity_id(entity_id_manager.provide_id());
+        manager
+            .add_terrain(terrain_id, create_sphere_terrain(20.0))
+            .unwrap();
+
+        let observer_position = Point3::new(0.0, 0.0, 20.0);
+
+        let mut update = |manager: &mut VoxelTerrainManager, context: &mut HeadlessContext| {
+            manager.update_terrains(
+                context,
+                &mut entity_id_manager,
+                &mut voxel_object_manager,
+                &mut voxel_object_buffer_pool,
+                &mut collision_world,
+                &observer_position,
+            );
+        };
+
+        update(&mut manager, &mut context);
+        assert!(manager.pending_tile_count() > 0);
+        assert!(manager.pending_tile_count() <= 4);
+
+        let start_time = Instant::now();
+        loop {
+            update(&mut manager, &mut context);
+            assert!(manager.pending_tile_count() <= 4);
+
+            // Each update requests any missing tiles, so nothing is pending
+            // after an update once all tiles in range are loaded
+            if manager.pending_tile_count() == 0 {
+                break;
+            }
+            assert!(
+                start_time.elapsed().as_secs() < 60,
+                "Timed out waiting for terrain tiles"
+            );
+            std::thread::yield_now();
+        }
+
+        let terrain = manager.get_terrain(terrain_id).unwrap();
+        let entity_ids: Vec<_> = terrain.loaded_tile_entity_ids().collect();
+        assert!(!entity_ids.is_empty());
+        assert_eq!(entity_ids.len(), context.tile_entity_ids.len());
+
+        let mut unique_entity_ids = entity_ids.clone();
+        unique_entity_ids.sort();
+        unique_entity_ids.dedup();
+        assert_eq!(unique_entity_ids.len(), entity_ids.len());
+    }
+
+    #[test]
+    fn config_with_unload_distance_below_load_distance_is_invalid() {
+        let config = VoxelTerrainConfig {
+            load_distance: 100.0,
+            unload_distance: 50.0,
+            ..Default::default()
+        };
+        assert!(VoxelTerrainManager::new(config).is_err());
+    }
+}
@@ -0,0 +1,66 @@
+//! ECS systems for voxel terrains.
+
+use crate::{
+    HasVoxelObject, VoxelManager, collidable::CollisionWorld, terrain::VoxelTerrainContext,
+};
+use impact_ecs::world::{EntityStager, World as ECSWorld};
+use impact_geometry::ReferenceFrame;
+use impact_id::{EntityID, EntityIDManager};
+use impact_math::point::Point3;
+use impact_physics::quantities::Motion;
+
+/// ECS-based implementation of a voxel terrain context.
+#[derive(Debug)]
+pub struct ECSVoxelTerrainContext<'a> {
+    pub entity_stager: &'a mut EntityStager,
+    pub ecs_world: &'a ECSWorld,
+}
+
+impl<'a> VoxelTerrainContext for ECSVoxelTerrainContext<'a> {
+    fn entity_exists(&self, entity_id: EntityID) -> bool {
+        self.ecs_world.has_entity(entity_id)
+    }
+
+    fn create_terrain_tile_entity(&mut self, entity_id: EntityID, position: &Point3) {
+        // A stationary motion gives the tile a kinematic rigid body
+        self.entity_stager
+            .stage_entity_for_creation_with_id(
+                entity_id,
+                (
+                    &ReferenceFrame::unoriented(position.compact()),
+                    &Motion::stationary(),
+                    &HasVoxelObject,
+                ),
+            )
+            .expect("Failed to stage voxel terrain tile entity for creation");
+    }
+
+    fn remove_terrain_tile_entity(&mut self, entity_id: EntityID) {
+        self.entity_stager.stage_entity_for_removal(entity_id);
+    }
+}
+
+/// Generates and discards terrain tiles based on their distance to the given
+/// observer position and updates the collidables of the loaded tiles.
+pub fn update_terrains(
+    entity_id_manager: &mut EntityIDManager,
+    entity_stager: &mut EntityStager,
+    ecs_world: &ECSWorld,
+    voxel_manager: &mut VoxelManager,
+    collision_world: &mut CollisionWorld,
+    observer_position: &Point3,
+) {
+    let mut context = ECSVoxelTerrainContext {
+        entity_stager,
+        ecs_world,
+    };
+
+    voxel_manager.terrain_manager.update_terrains(
+        &mut context,
+        entity_id_manager,
+        &mut voxel_manager.object_manager,
+        &mut voxel_manager.object_buffer_pool,
+        collision_world,
+        observer_position,
+    );
+}
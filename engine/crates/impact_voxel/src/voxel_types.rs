@@ -49,6 +49,33 @@ pub struct VoxelTypeSpecification {
     pub metalness: f32,
     pub emissive_luminance: f32,
     pub normal_map: Option<VoxelNormalMap>,
+    /// The indices (1-255) of the entries in MagicaVoxel palettes whose voxels
+    /// should get this voxel type when importing `.vox` files.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub vox_palette_indices: Vec<u8>,
+    /// The maximum stress (in Pa) the connection between two voxels of this
+    /// type can sustain before breaking in structural integrity checks. If
+    /// [`None`], the connections never break.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub strength: Option<f32>,
+    /// The thermal properties of the voxel type. If [`None`], voxels of this
+    /// type neither conduct, absorb nor emit heat.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub thermal: Option<VoxelThermalProperties>,
+}
+
+/// Properties governing how voxels of a given type conduct and store heat.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VoxelThermalProperties {
+    /// The thermal conductivity, in W/(m·K).
+    pub thermal_conductivity: f32,
+    /// The specific heat capacity, in J/(kg·K).
+    pub specific_heat_capacity: f32,
+    /// The temperature (in K) at or above which voxels of this type melt and
+    /// are removed. If [`None`], the voxels never melt.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub melting_temperature: Option<f32>,
 }
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -79,8 +106,11 @@ pub struct VoxelNormalMap {
 #[derive(Clone, Debug)]
 pub struct VoxelTypeRegistry {
     name_lookup_table: NoHashMap<u32, VoxelType>,
+    vox_palette_lookup_table: NoHashMap<u8, VoxelType>,
     names: Vec<Cow<'static, str>>,
     mass_densities: Vec<f32>,
+    strengths: Vec<f32>,
+    thermal_properties: Vec<VoxelThermalProperties>,
     fixed_material_properties: Vec<FixedVoxelMaterialProperties>,
     color_texture_array_id: Option<TextureID>,
     roughness_texture_array_id: Option<TextureID>,
@@ -185,8 +215,11 @@ impl VoxelTypeRegistry {
     pub fn empty() -> Self {
         Self {
             name_lookup_table: NoHashMap::default(),
+            vox_palette_lookup_table: NoHashMap::default(),
             names: Vec::new(),
             mass_densities: Vec::new(),
+            strengths: Vec::new(),
+            thermal_properties: Vec::new(),
             fixed_material_properties: Vec::new(),
             color_texture_array_id: None,
             roughness_texture_array_id: None,
@@ -201,6 +234,11 @@ impl VoxelTypeRegistry {
     /// - The number of voxel types is not smaller than
     ///   [`Self::max_n_voxel_types`].
     /// - There are duplicate names.
+    /// - A MagicaVoxel palette index is invalid or assigned to multiple voxel
+    ///   types.
+    /// - A strength is not positive.
+    /// - A thermal conductivity is negative, a specific heat capacity is not
+    ///   positive or a melting temperature is not positive.
     ///
     /// See also
     /// [`load_declared_image_texture`](impact_texture::import::load_declared_image_texture).
@@ -216,6 +254,12 @@ impl VoxelTypeRegistry {
 
         voxel_types.validate()?;
 
+        let vox_palette_lookup_table = voxel_types.create_vox_palette_lookup_table()?;
+
+        let strengths = voxel_types.collect_strengths()?;
+
+        let thermal_properties = voxel_types.collect_thermal_properties()?;
+
         let (
             names,
             mass_densities,
@@ -279,6 +323,7 @@ impl VoxelTypeRegistry {
         let normal_map_processing =
             normal_map_format.map_or_else(ImageProcessing::none, |format| ImageProcessing {
                 format_conversions: vec![FormatConversion::NormalMap { from: format }],
+                compression: None,
             });
 
         impact_texture::import::load_image_texture(
@@ -300,8 +345,11 @@ impl VoxelTypeRegistry {
 
         Ok(Self {
             name_lookup_table,
+            vox_palette_lookup_table,
             names,
             mass_densities,
+            strengths,
+            thermal_properties,
             fixed_material_properties,
             color_texture_array_id: Some(color_texture_array_id),
             roughness_texture_array_id: Some(roughness_texture_array_id),
@@ -326,6 +374,12 @@ impl VoxelTypeRegistry {
         self.name_lookup_table.get(&u32::from(name_hash)).copied()
     }
 
+    /// Returns the voxel type assigned to the given MagicaVoxel palette index,
+    /// or [`None`] if no voxel type has been assigned to the index.
+    pub fn voxel_type_for_vox_palette_index(&self, palette_idx: u8) -> Option<VoxelType> {
+        self.vox_palette_lookup_table.get(&palette_idx).copied()
+    }
+
     /// Returns the name of the given voxel type.
     ///
     /// # Panics
@@ -346,6 +400,19 @@ impl VoxelTypeRegistry {
         &self.mass_densities
     }
 
+    /// Returns the slice of strengths for all registered voxel types. Voxel
+    /// types without a specified strength have an infinite strength.
+    pub fn strengths(&self) -> &[f32] {
+        &self.strengths
+    }
+
+    /// Returns the slice of thermal properties for all registered voxel types.
+    /// Voxel types without specified thermal properties are given
+    /// [`VoxelThermalProperties::INERT`].
+    pub fn thermal_properties(&self) -> &[VoxelThermalProperties] {
+        &self.thermal_properties
+    }
+
     /// Returns the slice of fixed material properties for all registered voxel
     /// types.
     pub fn fixed_material_properties(&self) -> &[FixedVoxelMaterialProperties] {
@@ -405,6 +472,79 @@ impl VoxelTypeSpecifications {
         Ok(())
     }
 
+    fn create_vox_palette_lookup_table(&self) -> Result<NoHashMap<u8, VoxelType>> {
+        let mut lookup_table = NoHashMap::default();
+        for (idx, specification) in self.0.iter().enumerate() {
+            for &palette_idx in &specification.vox_palette_indices {
+                if palette_idx == 0 {
+                    bail!(
+                        "Invalid MagicaVoxel palette index 0 for voxel type {}",
+                        specification.name
+                    );
+                }
+                if lookup_table
+                    .insert(palette_idx, VoxelType::from_idx(idx))
+                    .is_some()
+                {
+                    bail!(
+                        "MagicaVoxel palette index {palette_idx} assigned to multiple voxel types"
+                    );
+                }
+            }
+        }
+        Ok(lookup_table)
+    }
+
+    fn collect_strengths(&self) -> Result<Vec<f32>> {
+        self.0
+            .iter()
+            .map(|specification| match specification.strength {
+                Some(strength) if strength <= 0.0 => {
+                    bail!(
+                        "Non-positive strength {strength} for voxel type {}",
+                        specification.name
+                    );
+                }
+                Some(strength) => Ok(strength),
+                None => Ok(f32::INFINITY),
+            })
+            .collect()
+    }
+
+    fn collect_thermal_properties(&self) -> Result<Vec<VoxelThermalProperties>> {
+        self.0
+            .iter()
+            .map(|specification| {
+                let Some(thermal) = specification.thermal else {
+                    return Ok(VoxelThermalProperties::INERT);
+                };
+                if thermal.thermal_conductivity < 0.0 {
+                    bail!(
+                        "Negative thermal conductivity {} for voxel type {}",
+                        thermal.thermal_conductivity,
+                        specification.name
+                    );
+                }
+                if thermal.specific_heat_capacity <= 0.0 {
+                    bail!(
+                        "Non-positive specific heat capacity {} for voxel type {}",
+                        thermal.specific_heat_capacity,
+                        specification.name
+                    );
+                }
+                if let Some(melting_temperature) = thermal.melting_temperature
+                    && melting_temperature <= 0.0
+                {
+                    bail!(
+                        "Non-positive melting temperature {melting_temperature} for voxel type {}",
+                        specification.name
+                    );
+                }
+                Ok(thermal)
+            })
+            .collect()
+    }
+
     #[allow(clippy::type_complexity)]
     fn resolve(
         self,
@@ -434,6 +574,9 @@ impl VoxelTypeSpecifications {
             metalness,
             emissive_luminance,
             normal_map,
+            vox_palette_indices: _,
+            strength: _,
+            thermal: _,
         } in self.0
         {
             let color_texture_source = match color {
@@ -553,6 +696,9 @@ impl Default for VoxelTypeSpecifications {
             metalness: 0.0,
             emissive_luminance: 0.0,
             normal_map: None,
+            vox_palette_indices: Vec::new(),
+            strength: None,
+            thermal: None,
         }])
     }
 }
@@ -573,6 +719,17 @@ impl VoxelTypeSpecification {
     }
 }
 
+impl VoxelThermalProperties {
+    /// Properties for voxels that take no part in heat transfer: they do not
+    /// conduct heat, their infinite heat capacity keeps their temperature
+    /// fixed and they never melt.
+    pub const INERT: Self = Self {
+        thermal_conductivity: 0.0,
+        specific_heat_capacity: f32::INFINITY,
+        melting_temperature: None,
+    };
+}
+
 impl VoxelTypeConfig {
     /// Resolves all paths in the configuration by prepending the given root
     /// path to all paths.
@@ -93,6 +93,9 @@ impact_profiling::define_target_enum! {
         extract_voronoi_regions_with_inertial_property_transfer,
         copy_voronoi_regions,
         copy_voronoi_regions_with_inertial_property_transfer,
+        generate_terrain_tile,
+        load_terrain_tiles_around_observer,
+        load_terrain_tiles_around_observer_in_parallel,
     },
     model => {
         add_feature_to_dynamic_instance_buffer_from_storage,
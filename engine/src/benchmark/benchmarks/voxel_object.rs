@@ -3,10 +3,10 @@
 use super::tesselation::create_randomized_grid_points;
 use impact_alloc::Global;
 use impact_geometry::{AxisAlignedBox, Plane, ReferenceFrame, Sphere};
-use impact_id::EntityIDManager;
+use impact_id::{EntityID, EntityIDManager};
 use impact_intersection::{IntersectionManager, bounding_volume::BoundingVolumeID};
 use impact_math::{
-    point::Point3C,
+    point::{Point3, Point3C},
     quaternion::UnitQuaternion,
     random::Rng,
     transform::{Isometry3, Similarity3},
@@ -21,7 +21,7 @@ use impact_physics::{
 use impact_profiling::benchmark::Benchmarker;
 use impact_tesselation::{delaunay::DelaunayTetrahedralization, voronoi::VoronoiPolyhedron};
 use impact_voxel::{
-    VoxelObjectID, VoxelObjectManager, VoxelObjectPhysicsContext,
+    VoxelObjectBufferPool, VoxelObjectID, VoxelObjectManager, VoxelObjectPhysicsContext,
     collidable::{
         self, CollisionWorld, VoxelObjectCollisionProbes,
         setup::{VoxelCollidable, setup_voxel_collidable},
@@ -32,15 +32,18 @@ use impact_voxel::{
         voxel_type::SameVoxelTypeGenerator,
     },
     interaction::absorption::{MutualVoxelAbsorptionProcess, apply_mutual_absorption},
-    mesh::{MeshedVoxelObject, VoxelObjectMesh, VoxelObjectMeshBuffers},
+    mesh::{MeshedVoxelObject, MeshedVoxelObjectBuffers, VoxelObjectMesh, VoxelObjectMeshBuffers},
     object::{
         VoxelObject, VoxelObjectBuffers, extraction::ExtractionResult,
         inertia::VoxelObjectInertialPropertyManager, sdf::VoxelChunkSignedDistanceField,
     },
     setup,
+    terrain::{
+        VoxelTerrain, VoxelTerrainConfig, VoxelTerrainContext, VoxelTerrainID, VoxelTerrainManager,
+    },
     voxel_types::VoxelType,
 };
-use std::hint::black_box;
+use std::{hint::black_box, thread};
 
 pub fn update_internal_adjacencies_for_all_chunks(benchmarker: impl Benchmarker) {
     let generator = create_sphere_generator(100.0);
@@ -647,6 +650,110 @@ pub fn copy_voronoi_regions_with_inertial_property_transfer(benchmarker: impl Be
     });
 }
 
+pub fn generate_terrain_tile(benchmarker: impl Benchmarker) {
+    let terrain = create_planet_terrain();
+    let tile_indices = [0, 0, 15];
+    benchmarker.benchmark(&mut || {
+        let result = terrain.generate_tile(
+            &tile_indices,
+            PLANET_TERRAIN_TILE_CHUNK_COUNT,
+            MeshedVoxelObjectBuffers::new(),
+        );
+        black_box(result)
+    });
+}
+
+pub fn load_terrain_tiles_around_observer(benchmarker: impl Benchmarker) {
+    benchmark_loading_of_terrain_tiles_around_observer(benchmarker, 0);
+}
+
+pub fn load_terrain_tiles_around_observer_in_parallel(benchmarker: impl Benchmarker) {
+    benchmark_loading_of_terrain_tiles_around_observer(
+        benchmarker,
+        thread::available_parallelism().unwrap().get(),
+    );
+}
+
+const PLANET_TERRAIN_RADIUS: f32 = 1000.0;
+const PLANET_TERRAIN_TILE_CHUNK_COUNT: usize = 4;
+
+#[derive(Debug, Default)]
+struct HeadlessVoxelTerrainContext {
+    tile_entity_ids: Vec<EntityID>,
+}
+
+impl VoxelTerrainContext for HeadlessVoxelTerrainContext {
+    fn entity_exists(&self, entity_id: EntityID) -> bool {
+        self.tile_entity_ids.contains(&entity_id)
+    }
+
+    fn create_terrain_tile_entity(&mut self, entity_id: EntityID, _position: &Point3) {
+        self.tile_entity_ids.push(entity_id);
+    }
+
+    fn remove_terrain_tile_entity(&mut self, entity_id: EntityID) {
+        self.tile_entity_ids.retain(|&id| id != entity_id);
+    }
+}
+
+fn benchmark_loading_of_terrain_tiles_around_observer(
+    benchmarker: impl Benchmarker,
+    tile_generation_thread_count: usize,
+) {
+    let config = VoxelTerrainConfig {
+        tile_chunk_count: PLANET_TERRAIN_TILE_CHUNK_COUNT,
+        load_distance: 48.0,
+        unload_distance: 64.0,
+        collision_distance: 0.0,
+        max_tiles_generated_per_update: usize::MAX,
+        tile_generation_thread_count,
+    };
+    let observer_position = Point3::new(0.0, 0.0, PLANET_TERRAIN_RADIUS);
+
+    benchmarker.benchmark(&mut || {
+        let mut entity_id_manager = EntityIDManager::new();
+        let mut terrain_manager = VoxelTerrainManager::new(config.clone()).unwrap();
+        let mut voxel_object_manager = VoxelObjectManager::new();
+        let mut voxel_object_buffer_pool = VoxelObjectBufferPool::new();
+        let mut collision_world = CollisionWorld::new();
+        let mut context = HeadlessVoxelTerrainContext::default();
+
+        terrain_manager
+            .add_terrain(
+                VoxelTerrainID::from_entity_id(entity_id_manager.provide_id()),
+                create_planet_terrain(),
+            )
+            .unwrap();
+
+        // With background generation, the first update only requests the
+        // tiles, so we keep updating until all of them have been added
+        loop {
+            terrain_manager.update_terrains(
+                &mut context,
+                &mut entity_id_manager,
+                &mut voxel_object_manager,
+                &mut voxel_object_buffer_pool,
+                &mut collision_world,
+                &observer_position,
+            );
+            if terrain_manager.pending_tile_count() == 0 {
+                break;
+            }
+            thread::yield_now();
+        }
+
+        black_box(voxel_object_manager)
+    });
+}
+
+fn create_planet_terrain() -> VoxelTerrain {
+    VoxelTerrain::new(
+        create_sphere_generator(PLANET_TERRAIN_RADIUS),
+        Point3::origin(),
+        None,
+    )
+}
+
 fn create_sphere_generator(radius: f32) -> SDFVoxelGenerator<Global> {
     let mut graph = SDFGraph::new_in(Global);
     graph.add_node(SDFNode::new_sphere(radius));
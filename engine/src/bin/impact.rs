@@ -5,7 +5,12 @@ mod main {
     use super::*;
     use anyhow::bail;
     use clap::{Parser, Subcommand};
-    use impact::engine::EngineConfig;
+    use impact::{
+        engine::EngineConfig,
+        resource::{database::ResourceDatabase, import::ResourceDeclarations},
+    };
+    use impact_texture::{SamplerRegistry, TextureRegistry};
+    use impact_voxel::voxel_types::VoxelTypeRegistry;
     use std::path::PathBuf;
 
     #[derive(Debug, Parser)]
@@ -26,6 +31,21 @@ mod main {
             #[arg(short, long)]
             force_overwrite: bool,
         },
+        /// Process the resources declared in a RON resource file and store them
+        /// in a resource database
+        ImportResources {
+            /// Path to the resource file
+            #[arg(short, long)]
+            resource_file_path: PathBuf,
+            /// Path to the resource database directory (created if missing)
+            #[arg(short, long)]
+            database_path: PathBuf,
+            /// Path to the engine configuration file whose voxel types are used
+            /// for pregenerated voxel objects (the default voxel types are
+            /// used if not specified)
+            #[arg(short, long)]
+            config_path: Option<PathBuf>,
+        },
         #[cfg(feature = "benchmark")]
         /// Run a benchmarking target
         Benchmark {
@@ -62,6 +82,29 @@ mod main {
                 let config = EngineConfig::default();
                 impact_io::write_ron_file(&config, output_path)
             }
+            Command::ImportResources {
+                resource_file_path,
+                database_path,
+                config_path,
+            } => {
+                let config = match config_path {
+                    Some(config_path) => EngineConfig::from_ron_file(config_path)?,
+                    None => EngineConfig::default(),
+                };
+                let voxel_types = VoxelTypeRegistry::from_config(
+                    &mut TextureRegistry::new(),
+                    &mut SamplerRegistry::new(),
+                    config.voxel.types,
+                )?;
+                let declarations = ResourceDeclarations::from_ron_file(resource_file_path)?;
+                let mut database = ResourceDatabase::open_or_create(database_path);
+                let summary = database.import_declarations(&declarations, &voxel_types)?;
+                println!("Imported resources: {summary}");
+                if summary.n_failed > 0 {
+                    bail!("Failed to import {} resources", summary.n_failed);
+                }
+                Ok(())
+            }
             #[cfg(feature = "benchmark")]
             Command::Benchmark {
                 target,
@@ -2,6 +2,7 @@
 
 pub mod capture;
 pub mod controller;
+pub mod export;
 pub mod game_loop;
 pub mod gizmo;
 pub mod instrumentation;
@@ -19,6 +20,7 @@ use crate::{
 use anyhow::{Context, Result};
 use capture::CaptureAdminCommand;
 use controller::ControlCommand;
+use export::ExportAdminCommand;
 use game_loop::GameLoopAdminCommand;
 use gizmo::GizmoAdminCommand;
 use instrumentation::InstrumentationAdminCommand;
@@ -42,6 +44,7 @@ pub enum AdminCommand {
     Physics(PhysicsAdminCommand),
     Control(ControlAdminCommand),
     Capture(CaptureAdminCommand),
+    Export(ExportAdminCommand),
     Instrumentation(InstrumentationAdminCommand),
     GameLoop(GameLoopAdminCommand),
     Gizmo(GizmoAdminCommand),
@@ -73,6 +76,7 @@ pub struct AdminCommandQueues {
     pub physics: CommandQueue<PhysicsAdminCommand>,
     pub control: CommandQueue<ControlAdminCommand>,
     pub capture: CommandQueue<CaptureAdminCommand>,
+    pub export: CommandQueue<ExportAdminCommand>,
     pub instrumentation: CommandQueue<InstrumentationAdminCommand>,
     pub game_loop: CommandQueue<GameLoopAdminCommand>,
     pub gizmo: CommandQueue<GizmoAdminCommand>,
@@ -100,6 +104,7 @@ impl AdminCommandQueues {
         self.physics.clear_and_free();
         self.control.clear_and_free();
         self.capture.clear_and_free();
+        self.export.clear_and_free();
         self.instrumentation.clear_and_free();
         self.game_loop.clear_and_free();
         self.gizmo.clear_and_free();
@@ -121,6 +126,7 @@ pub fn execute_admin_command(engine: &Engine, command: AdminCommand) -> Result<(
         AdminCommand::Physics(command) => execute_physics_admin_command(engine, command),
         AdminCommand::Control(command) => execute_control_admin_command(engine, command),
         AdminCommand::Capture(command) => execute_capture_admin_command(engine, command),
+        AdminCommand::Export(command) => execute_export_admin_command(engine, command),
         AdminCommand::Instrumentation(command) => {
             execute_instrumentation_admin_command(engine, command)
         }
@@ -224,10 +230,22 @@ pub fn execute_rendering_admin_command(
             rendering::set_render_pass_timings(&mut engine.renderer().owrite(), to);
             Ok(())
         }
+        RenderingAdminCommand::SetEntityPicking(to) => {
+            rendering::set_entity_picking(&mut engine.renderer().owrite(), to);
+            Ok(())
+        }
         RenderingAdminCommand::SetAmbientOcclusionConfig(config) => {
             rendering::set_ambient_occlusion_config(&engine.renderer().oread(), config);
             Ok(())
         }
+        RenderingAdminCommand::SetScreenSpaceReflectionConfig(config) => {
+            rendering::set_screen_space_reflection_config(&engine.renderer().oread(), config);
+            Ok(())
+        }
+        RenderingAdminCommand::SetVolumetricFogConfig(config) => {
+            rendering::set_volumetric_fog_config(&engine.renderer().oread(), config);
+            Ok(())
+        }
         RenderingAdminCommand::SetTemporalAntiAliasingConfig(config) => {
             rendering::set_temporal_anti_aliasing_config(
                 engine.scene(),
@@ -236,6 +254,14 @@ pub fn execute_rendering_admin_command(
             );
             Ok(())
         }
+        RenderingAdminCommand::SetDepthOfFieldConfig(config) => {
+            rendering::set_depth_of_field_config(&engine.renderer().oread(), config);
+            Ok(())
+        }
+        RenderingAdminCommand::SetMotionBlurConfig(config) => {
+            rendering::set_motion_blur_config(&engine.renderer().oread(), config);
+            Ok(())
+        }
         RenderingAdminCommand::SetBloomConfig(config) => {
             rendering::set_bloom_config(&engine.renderer().oread(), config);
             Ok(())
@@ -301,6 +327,20 @@ pub fn execute_capture_admin_command(engine: &Engine, command: CaptureAdminComma
     Ok(())
 }
 
+pub fn execute_export_admin_command(engine: &Engine, command: ExportAdminCommand) -> Result<()> {
+    match command {
+        ExportAdminCommand::SaveVoxelObjectMesh {
+            entity_id,
+            output_path,
+        } => export::save_voxel_object_mesh(engine, entity_id, &output_path),
+        ExportAdminCommand::SaveVoxelObject {
+            entity_id,
+            output_path,
+        } => export::save_voxel_object(engine, entity_id, &output_path),
+    }
+    .context("Failed to execute export admin command")
+}
+
 pub fn execute_instrumentation_admin_command(
     engine: &Engine,
     command: InstrumentationAdminCommand,
@@ -0,0 +1,107 @@
+//! Commands for exporting engine data to files.
+
+use crate::{engine::Engine, lock_order::OrderedRwLock};
+use anyhow::{Context, Result, anyhow};
+use impact_id::EntityID;
+use impact_voxel::VoxelObjectID;
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+#[derive(Clone, Debug)]
+pub enum ExportAdminCommand {
+    /// Writes the current surface mesh of the voxel object of the given entity
+    /// to the given path. The file format is determined by the extension of
+    /// the path (`.obj`, `.ply`, `.gltf` or `.glb`). Positions are in the
+    /// local frame of the voxel object.
+    SaveVoxelObjectMesh {
+        entity_id: EntityID,
+        output_path: PathBuf,
+    },
+    /// Writes the voxels of the voxel object of the given entity to the given
+    /// path in the binary voxel object file format, so that the object can be
+    /// loaded again in its current state.
+    SaveVoxelObject {
+        entity_id: EntityID,
+        output_path: PathBuf,
+    },
+}
+
+pub fn save_voxel_object_mesh(
+    engine: &Engine,
+    entity_id: EntityID,
+    output_path: &Path,
+) -> Result<()> {
+    log::info!(
+        "Saving mesh of voxel object for entity {entity_id} to {}",
+        output_path.display()
+    );
+
+    let voxel_object_id = VoxelObjectID::from_entity_id(entity_id);
+
+    let mesh = {
+        let scene = engine.scene().oread();
+        let voxel_manager = scene.voxel_manager().oread();
+
+        let voxel_object = voxel_manager
+            .object_manager
+            .get_voxel_object(voxel_object_id)
+            .ok_or_else(|| {
+                anyhow!("Tried to export mesh of missing voxel object {voxel_object_id}")
+            })?;
+
+        voxel_object
+            .mesh()
+            .create_triangle_mesh(voxel_object.object().voxel_extent())
+    };
+
+    impact_mesh::io::export::write_mesh_to_file(&mesh, output_path)?;
+
+    log::info!(
+        "Saved voxel object mesh with {} triangles to {}",
+        mesh.n_triangles(),
+        output_path.display()
+    );
+
+    Ok(())
+}
+
+pub fn save_voxel_object(engine: &Engine, entity_id: EntityID, output_path: &Path) -> Result<()> {
+    log::info!(
+        "Saving voxel object for entity {entity_id} to {}",
+        output_path.display()
+    );
+
+    let voxel_object_id = VoxelObjectID::from_entity_id(entity_id);
+
+    let mut bytes = Vec::new();
+    {
+        let scene = engine.scene().oread();
+        let voxel_manager = scene.voxel_manager().oread();
+
+        let voxel_object = voxel_manager
+            .object_manager
+            .get_voxel_object(voxel_object_id)
+            .ok_or_else(|| anyhow!("Tried to save missing voxel object {voxel_object_id}"))?;
+
+        impact_voxel::object::io::write_voxel_object(voxel_object.object(), &mut bytes)?;
+    }
+
+    impact_io::create_file_and_required_directories(output_path)
+        .and_then(|mut file| file.write_all(&bytes))
+        .with_context(|| {
+            format!(
+                "Failed to write voxel object file {}",
+                output_path.display()
+            )
+        })?;
+
+    log::info!(
+        "Saved voxel object ({} bytes) to {}",
+        bytes.len(),
+        output_path.display()
+    );
+
+    Ok(())
+}
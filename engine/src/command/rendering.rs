@@ -10,6 +10,7 @@ use crate::{
 };
 use anyhow::Result;
 use impact_light::shadow_map::ShadowMappingConfig;
+use impact_physics::medium::MediumOpticalProperties;
 use impact_rendering::{
     attachment::RenderAttachmentQuantity,
     postprocessing::{
@@ -18,9 +19,13 @@ use impact_rendering::{
             CameraSettings,
             average_luminance::AverageLuminanceComputationConfig,
             bloom::BloomConfig,
+            depth_of_field::DepthOfFieldConfig,
             dynamic_range_compression::{DynamicRangeCompressionConfig, ToneMappingMethod},
+            motion_blur::MotionBlurConfig,
         },
+        screen_space_reflection::SSRConfig,
         temporal_anti_aliasing::TemporalAntiAliasingConfig,
+        volumetric_fog::VolumetricFogConfig,
     },
 };
 use parking_lot::RwLock;
@@ -29,7 +34,11 @@ use postprocessing::{ToExposure, ToRenderAttachmentQuantity, ToToneMappingMethod
 #[derive(Clone, Debug)]
 pub enum RenderingAdminCommand {
     SetAmbientOcclusionConfig(AmbientOcclusionConfig),
+    SetScreenSpaceReflectionConfig(SSRConfig),
+    SetVolumetricFogConfig(VolumetricFogConfig),
     SetTemporalAntiAliasingConfig(TemporalAntiAliasingConfig),
+    SetDepthOfFieldConfig(DepthOfFieldConfig),
+    SetMotionBlurConfig(MotionBlurConfig),
     SetBloomConfig(BloomConfig),
     SetCameraSettings(CameraSettings),
     SetAverageLuminanceComputationConfig(AverageLuminanceComputationConfig),
@@ -41,6 +50,7 @@ pub enum RenderingAdminCommand {
     SetShadowMappingConfig(ShadowMappingConfig),
     SetWireframeMode(ToActiveState),
     SetRenderPassTimings(ToActiveState),
+    SetEntityPicking(ToActiveState),
 }
 
 pub fn set_ambient_occlusion_config(renderer: &RenderingSystem, config: AmbientOcclusionConfig) {
@@ -54,6 +64,46 @@ pub fn set_ambient_occlusion_config(renderer: &RenderingSystem, config: AmbientO
     );
 }
 
+pub fn set_screen_space_reflection_config(renderer: &RenderingSystem, config: SSRConfig) {
+    log::info!("Setting screen-space reflection config to {config:?}");
+    let gpu_resource_group_manager = renderer.gpu_resource_group_manager().oread();
+    let mut postprocessor = renderer.postprocessor().owrite();
+    postprocessor.set_screen_space_reflection_config(
+        renderer.graphics_device(),
+        &gpu_resource_group_manager,
+        config,
+    );
+}
+
+pub fn set_volumetric_fog_config(renderer: &RenderingSystem, config: VolumetricFogConfig) {
+    log::info!("Setting volumetric fog config to {config:?}");
+    let gpu_resource_group_manager = renderer.gpu_resource_group_manager().oread();
+    let mut postprocessor = renderer.postprocessor().owrite();
+    postprocessor.set_volumetric_fog_config(
+        renderer.graphics_device(),
+        &gpu_resource_group_manager,
+        config,
+    );
+}
+
+/// Makes the volumetric fog use the given optical properties of the medium
+/// the scene is immersed in.
+pub fn set_volumetric_fog_medium(
+    renderer: &RenderingSystem,
+    optical_properties: &MediumOpticalProperties,
+) {
+    let albedo = &optical_properties.scattering_albedo;
+    let gpu_resource_group_manager = renderer.gpu_resource_group_manager().oread();
+    let mut postprocessor = renderer.postprocessor().owrite();
+    postprocessor.set_volumetric_fog_medium(
+        renderer.graphics_device(),
+        &gpu_resource_group_manager,
+        optical_properties.extinction_coefficient,
+        [albedo.x(), albedo.y(), albedo.z()],
+        optical_properties.anisotropy,
+    );
+}
+
 pub fn set_temporal_anti_aliasing_config(
     scene: &RwLock<Scene>,
     renderer: &RenderingSystem,
@@ -80,6 +130,30 @@ pub fn set_temporal_anti_aliasing_config(
     );
 }
 
+pub fn set_depth_of_field_config(renderer: &RenderingSystem, config: DepthOfFieldConfig) {
+    log::info!("Setting depth of field config to {config:?}");
+    let gpu_resource_group_manager = renderer.gpu_resource_group_manager().oread();
+    let mut postprocessor = renderer.postprocessor().owrite();
+    postprocessor
+        .capturing_camera_mut()
+        .set_depth_of_field_config(
+            renderer.graphics_device(),
+            &gpu_resource_group_manager,
+            config,
+        );
+}
+
+pub fn set_motion_blur_config(renderer: &RenderingSystem, config: MotionBlurConfig) {
+    log::info!("Setting motion blur config to {config:?}");
+    let gpu_resource_group_manager = renderer.gpu_resource_group_manager().oread();
+    let mut postprocessor = renderer.postprocessor().owrite();
+    postprocessor.capturing_camera_mut().set_motion_blur_config(
+        renderer.graphics_device(),
+        &gpu_resource_group_manager,
+        config,
+    );
+}
+
 pub fn set_bloom_config(renderer: &RenderingSystem, config: BloomConfig) {
     log::info!("Setting bloom config to {config:?}");
     let mut shader_manager = renderer.shader_manager().owrite();
@@ -206,3 +280,16 @@ pub fn set_render_pass_timings(
     }
     state
 }
+
+pub fn set_entity_picking(
+    renderer: &mut RenderingSystem,
+    to: ToActiveState,
+) -> ModifiedActiveState {
+    log::info!("Setting entity picking to {to:?}");
+    let mut enabled = renderer.basic_config().entity_picking_enabled;
+    let state = to.set(&mut enabled);
+    if state.changed {
+        renderer.set_entity_picking_enabled(enabled);
+    }
+    state
+}
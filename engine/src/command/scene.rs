@@ -1,6 +1,10 @@
 //! Commands for scene manipulation.
 
-use crate::{command::utils::ActiveState, engine::Engine, lock_order::OrderedRwLock};
+use crate::{
+    command::{rendering, utils::ActiveState},
+    engine::Engine,
+    lock_order::OrderedRwLock,
+};
 use anyhow::Result;
 use impact_camera::CameraID;
 use impact_id::EntityID;
@@ -33,12 +37,18 @@ pub fn set_active_camera(engine: &Engine, entity_id: EntityID) -> Result<()> {
 }
 
 pub fn set_skybox(engine: &Engine, skybox: Skybox) {
-    log::info!("Setting skybox to {}", skybox.cubemap_texture_id());
+    match &skybox {
+        Skybox::Cubemap {
+            cubemap_texture_id, ..
+        } => log::info!("Setting skybox to {cubemap_texture_id}"),
+        Skybox::Atmospheric(_) => log::info!("Setting skybox to atmospheric sky"),
+    }
     engine.scene().oread().set_skybox(Some(skybox));
 }
 
 pub fn set_medium(engine: &Engine, medium: UniformMedium) {
     log::info!("Setting medium to {medium:?}");
+    rendering::set_volumetric_fog_medium(&engine.renderer().oread(), &medium.optical_properties);
     engine.simulator().owrite().set_medium(medium);
 }
 
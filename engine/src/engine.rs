@@ -25,7 +25,7 @@ use crate::{
         RenderingConfig, RenderingSystem,
         screen_capture::{ScreenCaptureConfig, ScreenCapturer},
     },
-    resource::{ResourceConfig, ResourceManager},
+    resource::{ResourceConfig, ResourceManager, hot_reloading::ResourceReloader},
     scene::Scene,
 };
 use anyhow::Result;
@@ -69,6 +69,7 @@ pub struct Engine {
     entity_stager: Mutex<EntityStager>,
     ecs_world: RwLock<ECSWorld>,
     resource_manager: RwLock<ResourceManager>,
+    resource_reloader: Option<Mutex<ResourceReloader>>,
     scene: RwLock<Scene>,
     simulator: RwLock<PhysicsSimulator>,
     renderer: RwLock<RenderingSystem>,
@@ -130,6 +131,8 @@ impl Engine {
         resource_manager.load_builtin_resources()?;
         resource_manager.load_resources_declared_in_config()?;
 
+        let resource_reloader = ResourceReloader::for_config(&resource_manager.config)?;
+
         impact_gizmo::mesh::generate_gizmo_meshes(&mut resource_manager);
 
         let mut model_instance_manager = ModelInstanceManager::new();
@@ -161,6 +164,11 @@ impl Engine {
 
         let simulator = PhysicsSimulator::new(config.physics)?;
 
+        command::rendering::set_volumetric_fog_medium(
+            &renderer,
+            &simulator.medium().optical_properties,
+        );
+
         let gizmo_manager = GizmoManager::new(config.gizmo);
 
         let (motion_controller, orientation_controller) =
@@ -181,6 +189,7 @@ impl Engine {
             entity_stager: Mutex::new(EntityStager::new()),
             ecs_world: RwLock::new(ecs_world),
             resource_manager: RwLock::new(resource_manager),
+            resource_reloader: resource_reloader.map(Mutex::new),
             scene: RwLock::new(scene),
             simulator: RwLock::new(simulator),
             renderer: RwLock::new(renderer),
@@ -246,6 +255,12 @@ impl Engine {
         &self.resource_manager
     }
 
+    /// Returns a reference to the [`ResourceReloader`], guarded by a
+    /// [`Mutex`], if hot reloading of resources is enabled.
+    pub(crate) fn resource_reloader(&self) -> Option<&Mutex<ResourceReloader>> {
+        self.resource_reloader.as_ref()
+    }
+
     /// Returns a reference to the [`Scene`], guarded by a [`RwLock`].
     pub(crate) fn scene(&self) -> &RwLock<Scene> {
         &self.scene
@@ -533,6 +548,13 @@ impl Engine {
             .try_execute_commands(|command| command::execute_capture_admin_command(self, command))
     }
 
+    pub(crate) fn execute_enqueued_export_admin_commands(&self) -> Result<()> {
+        self.command_queues
+            .admin
+            .export
+            .try_execute_commands(|command| command::execute_export_admin_command(self, command))
+    }
+
     pub(crate) fn execute_enqueued_instrumentation_admin_commands(&self) -> Result<()> {
         self.command_queues
             .admin
@@ -5,10 +5,13 @@ use crate::{
     command::{AdminCommand, UserCommand},
     lock_order::{OrderedMutex, OrderedRwLock},
     physics::SimulatorConfig,
+    rendering::picking::PickedEntity,
+    resource::{gltf::ImportedSceneID, hot_reloading::ResourceReloadFailure},
     setup,
 };
-use anyhow::{Result, anyhow};
+use anyhow::{Result, anyhow, bail};
 use impact_alloc::{AVec, arena::ArenaPool};
+use impact_animation::AnimationPlayer;
 use impact_ecs::{
     archetype::ArchetypeComponents,
     component::{
@@ -17,15 +20,18 @@ use impact_ecs::{
     },
     world::{PrototypeEntities, QueryableWorld},
 };
+use impact_geometry::ReferenceFrame;
 use impact_gizmo::{GizmoParameters, GizmoType, GizmoVisibilities, GizmoVisibility};
 use impact_id::EntityID;
 use impact_light::shadow_map::ShadowMappingConfig;
-use impact_material::values::UniformColorPhysicalMaterialValues;
+use impact_material::values::{TextureCoordTransform, UniformColorPhysicalMaterialValues};
+use impact_math::transform::Isometry3;
 use impact_model::{InstanceFeature, ModelInstanceID};
 use impact_physics::{
+    anchor::AnchorManager,
     constraint::solver::ConstraintSolverConfig,
     force::alignment_torque::{AlignmentTorqueGenerator, AlignmentTorqueGeneratorID},
-    rigid_body::{DynamicRigidBody, DynamicRigidBodyID},
+    rigid_body::{DynamicRigidBody, DynamicRigidBodyID, RigidBodyManager},
 };
 use impact_profiling::TimedTask;
 use impact_rendering::{
@@ -35,20 +41,32 @@ use impact_rendering::{
         ambient_occlusion::AmbientOcclusionConfig,
         capturing::{
             CameraSettings, average_luminance::AverageLuminanceComputationConfig,
-            bloom::BloomConfig, dynamic_range_compression::DynamicRangeCompressionConfig,
+            bloom::BloomConfig, depth_of_field::DepthOfFieldConfig,
+            dynamic_range_compression::DynamicRangeCompressionConfig,
+            motion_blur::MotionBlurConfig,
         },
+        screen_space_reflection::SSRConfig,
         temporal_anti_aliasing::TemporalAntiAliasingConfig,
+        volumetric_fog::VolumetricFogConfig,
     },
 };
+use impact_scene::{CanBeParent, ParentEntity};
+use impact_texture::atlas::TextureAtlasRegionID;
 use impact_voxel::{
-    VoxelObjectID,
+    VoxelManager, VoxelObjectID,
     generation::ChunkedVoxelGenerator,
     interaction::{
         absorption::{AbsorbedVoxels, VoxelAbsorbingCapsuleID, VoxelAbsorbingSphereID},
+        deposition::{
+            self, VoxelDepositingBox, VoxelDepositingCapsule, VoxelDepositingSphere,
+            VoxelDepositionOutcome,
+        },
         fracturing::FracturePointGenerator,
+        welding,
     },
     mesh::MeshedVoxelObject,
     object::VoxelObject,
+    voxel_types::VoxelTypeRegistry,
 };
 use std::sync::atomic::Ordering;
 use std::time::Duration;
@@ -214,6 +232,73 @@ impl Engine {
         Ok(entity_ids.into_vec())
     }
 
+    /// Creates entities for the nodes and models of the imported scene with
+    /// the given ID. The nodes are placed under a new group entity with the
+    /// given reference frame, which is attached to the given parent entity if
+    /// specified. The ID of the group entity is returned, which can also be
+    /// used to derive the corresponding `SceneGroupID`.
+    ///
+    /// # Errors
+    /// Returns an error if the imported scene is not present or if any of the
+    /// entities can not be created.
+    pub fn spawn_imported_scene(
+        &self,
+        scene_id: ImportedSceneID,
+        frame: ReferenceFrame,
+        parent: Option<EntityID>,
+    ) -> Result<EntityID> {
+        let scene = self
+            .resource_manager()
+            .oread()
+            .imported_scenes
+            .get(scene_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("Missing imported scene {scene_id}"))?;
+
+        let root_entity_id = match parent {
+            Some(parent_entity_id) => {
+                self.create_entity((&CanBeParent, &frame, &ParentEntity(parent_entity_id)))?
+            }
+            None => self.create_entity((&CanBeParent, &frame))?,
+        };
+
+        let mut node_entity_ids = Vec::with_capacity(scene.nodes().len());
+
+        for node in scene.nodes() {
+            let parent = ParentEntity(
+                node.parent
+                    .map_or(root_entity_id, |parent_idx| node_entity_ids[parent_idx]),
+            );
+
+            let node_entity_id = self.create_entity((&CanBeParent, &node.frame, &parent))?;
+
+            for model in &node.models {
+                if let (Some(skeleton_id), Some(clip_id)) =
+                    (model.skeleton_id, model.animation_clip_id)
+                {
+                    self.create_entity((
+                        &model.mesh_id,
+                        &model.material_id,
+                        &model.model_transform,
+                        &ParentEntity(node_entity_id),
+                        &AnimationPlayer::looping(skeleton_id, clip_id),
+                    ))?;
+                } else {
+                    self.create_entity((
+                        &model.mesh_id,
+                        &model.material_id,
+                        &model.model_transform,
+                        &ParentEntity(node_entity_id),
+                    ))?;
+                }
+            }
+
+            node_entity_ids.push(node_entity_id);
+        }
+
+        Ok(root_entity_id)
+    }
+
     pub fn update_entity<A>(
         &self,
         entity_id: EntityID,
@@ -321,6 +406,9 @@ impl Engine {
             AdminCommand::Capture(command) => {
                 self.command_queues.admin.capture.enqueue_command(command);
             }
+            AdminCommand::Export(command) => {
+                self.command_queues.admin.export.enqueue_command(command);
+            }
             AdminCommand::Instrumentation(command) => {
                 self.command_queues
                     .admin
@@ -350,6 +438,19 @@ impl Engine {
 
         self.ecs_world.owrite().reset_and_free();
 
+        {
+            // Entities are not cleaned up individually, so we must release the
+            // resource references held by their model instances here
+            let mut resource_manager = self.resource_manager.owrite();
+            let scene = self.scene.oread();
+            let scene_graph = scene.scene_graph().oread();
+            for model_instance_node in scene_graph.model_instance_nodes().nodes() {
+                for model_id in model_instance_node.model_ids_for_all_lods() {
+                    resource_manager.remove_model_instance_references(model_id);
+                }
+            }
+        }
+
         self.scene.oread().reset_and_free(&mut entity_id_manager);
 
         self.simulator.owrite().reset_and_free();
@@ -418,6 +519,26 @@ impl Engine {
             .enabled
     }
 
+    /// Returns the current screen-space reflection configuration.
+    pub fn screen_space_reflection_config(&self) -> SSRConfig {
+        self.renderer()
+            .oread()
+            .postprocessor()
+            .oread()
+            .screen_space_reflection_config()
+            .clone()
+    }
+
+    /// Returns the current volumetric fog configuration.
+    pub fn volumetric_fog_config(&self) -> VolumetricFogConfig {
+        self.renderer()
+            .oread()
+            .postprocessor()
+            .oread()
+            .volumetric_fog_config()
+            .clone()
+    }
+
     /// Returns the current temporal anti-aliasing configuration.
     pub fn temporal_anti_aliasing_config(&self) -> TemporalAntiAliasingConfig {
         self.renderer()
@@ -449,6 +570,28 @@ impl Engine {
             .clone()
     }
 
+    /// Returns the current depth of field configuration.
+    pub fn depth_of_field_config(&self) -> DepthOfFieldConfig {
+        self.renderer()
+            .oread()
+            .postprocessor()
+            .oread()
+            .capturing_camera()
+            .depth_of_field_config()
+            .clone()
+    }
+
+    /// Returns the current motion blur configuration.
+    pub fn motion_blur_config(&self) -> MotionBlurConfig {
+        self.renderer()
+            .oread()
+            .postprocessor()
+            .oread()
+            .capturing_camera()
+            .motion_blur_config()
+            .clone()
+    }
+
     /// Returns the current bloom configuration.
     pub fn bloom_config(&self) -> BloomConfig {
         self.renderer()
@@ -502,6 +645,14 @@ impl Engine {
             .visualized_render_attachment_quantity()
     }
 
+    /// Returns the failures from reloading resources whose source files have
+    /// changed, or an empty list if hot reloading is disabled.
+    pub fn resource_reload_failures(&self) -> Vec<ResourceReloadFailure> {
+        self.resource_reloader()
+            .map(|reloader| reloader.olock().failures().to_vec())
+            .unwrap_or_default()
+    }
+
     /// Returns the current simulation time.
     pub fn simulation_time(&self) -> f32 {
         self.simulator().oread().current_simulation_time()
@@ -596,6 +747,59 @@ impl Engine {
         self.renderer().oread().basic_config().timings_enabled
     }
 
+    /// Returns whether entity picking is enabled.
+    pub fn entity_picking_enabled(&self) -> bool {
+        self.renderer()
+            .oread()
+            .basic_config()
+            .entity_picking_enabled
+    }
+
+    /// Requests that the entity visible at the given physical pixel
+    /// coordinates (with the origin at the top left corner of the rendering
+    /// surface) be picked. The result becomes available from
+    /// [`Self::picked_entity`] a frame or two later. Has no effect unless
+    /// entity picking is enabled.
+    pub fn request_entity_pick(&self, x: u32, y: u32) {
+        self.renderer()
+            .owrite()
+            .entity_picker_mut()
+            .request_pick(x, y);
+    }
+
+    /// Returns information about the entity found by the most recently
+    /// completed pick, or [`None`] if no entity was found or the entity no
+    /// longer exists.
+    pub fn picked_entity(&self) -> Option<PickedEntity> {
+        let pick = *self.renderer().oread().entity_picker().last_result()?;
+
+        let component_names = {
+            let ecs_world = self.ecs_world.oread();
+            let entity = ecs_world.get_entity(pick.entity_id)?;
+            entity
+                .archetype()
+                .component_ids()
+                .iter()
+                .map(|&component_id| self.component_metadata_registry.metadata(component_id).name)
+                .collect()
+        };
+
+        let world_position = {
+            let scene = self.scene().oread();
+            let camera_manager = scene.camera_manager().oread();
+            camera_manager
+                .active_camera()
+                .map(|camera| pick.compute_world_space_position(camera))
+        };
+
+        Some(PickedEntity {
+            entity_id: pick.entity_id,
+            world_position,
+            voxel_indices: pick.voxel_indices,
+            component_names,
+        })
+    }
+
     pub fn with_uniform_physical_material_property_values_mut<R>(
         &self,
         entity_id: EntityID,
@@ -625,6 +829,58 @@ impl Engine {
         f(model_instance_manager.feature_mut(feature_id))
     }
 
+    /// Makes the model instance of the given entity sample its material
+    /// textures from the texture atlas region with the given ID. The material
+    /// must already sample a region of the same atlases.
+    pub fn set_texture_atlas_region(
+        &self,
+        entity_id: EntityID,
+        region_id: TextureAtlasRegionID,
+    ) -> Result<()> {
+        let resource_manager = self.resource_manager().oread();
+        let scene = self.scene().oread();
+
+        let scene_graph = scene.scene_graph().oread();
+
+        let node = scene_graph
+            .model_instance_nodes()
+            .get_node(ModelInstanceID::from_entity_id(entity_id))
+            .ok_or_else(|| {
+                anyhow!("Tried to set texture atlas region for missing model instance")
+            })?;
+
+        let feature_id = node
+            .get_rendering_feature_id_of_type(TextureCoordTransform::FEATURE_TYPE_ID)
+            .ok_or_else(|| anyhow!("Missing `TextureCoordTransform` feature for model instance"))?;
+
+        let material_id = node.model_id().material_id();
+
+        drop(scene_graph);
+
+        let texture_ids = resource_manager
+            .materials
+            .get(material_id)
+            .and_then(|material| material.texture_group_id_if_non_empty())
+            .and_then(|texture_group_id| {
+                resource_manager
+                    .material_texture_groups
+                    .get(texture_group_id)
+            })
+            .map_or(&[][..], |texture_group| &texture_group.texture_ids);
+
+        let transform = TextureCoordTransform::for_atlas_region(
+            &resource_manager.textures,
+            texture_ids,
+            region_id,
+        )?;
+
+        let mut model_instance_manager = scene.model_instance_manager().owrite();
+
+        *model_instance_manager.feature_mut::<TextureCoordTransform>(feature_id) = transform;
+
+        Ok(())
+    }
+
     pub fn with_dynamic_rigid_body<R>(
         &self,
         entity_id: EntityID,
@@ -750,6 +1006,181 @@ impl Engine {
             .map(|_| ())
     }
 
+    /// Stages a structural integrity check for the voxel object of the given
+    /// entity, which will break any voxels that can not carry their load under
+    /// gravity. The check is executed in the next simulation step.
+    pub fn check_voxel_object_integrity(&self, entity_id: EntityID) -> Result<()> {
+        let voxel_object_id = VoxelObjectID::from_entity_id(entity_id);
+
+        let scene = self.scene().oread();
+        let voxel_manager = &mut **scene.voxel_manager().owrite();
+
+        if !voxel_manager
+            .object_manager
+            .has_voxel_object(voxel_object_id)
+        {
+            bail!("Tried to check integrity of missing voxel object {voxel_object_id}");
+        }
+
+        voxel_manager
+            .interaction_manager
+            .integrity_manager_mut()
+            .stage_integrity_check(voxel_object_id);
+
+        Ok(())
+    }
+
+    /// Merges the voxel object of the source entity into the voxel object of
+    /// the target entity and stages the source entity for removal. Both
+    /// entities must have a voxel object and a dynamic rigid body.
+    pub fn weld_voxel_objects(
+        &self,
+        target_entity_id: EntityID,
+        source_entity_id: EntityID,
+    ) -> Result<()> {
+        // The guards must be released before staging the removal, since the
+        // entity stager is locked before all of them
+        {
+            let resource_manager = self.resource_manager().oread();
+            let scene = self.scene().oread();
+            let voxel_manager = &mut **scene.voxel_manager().owrite();
+            let simulator = self.simulator().oread();
+            let mut rigid_body_manager = simulator.rigid_body_manager().owrite();
+            let mut anchor_manager = simulator.anchor_manager().owrite();
+
+            welding::weld_voxel_objects(
+                &resource_manager.voxel_types,
+                &mut voxel_manager.object_manager,
+                &mut voxel_manager.object_buffer_pool,
+                &mut rigid_body_manager,
+                &mut anchor_manager,
+                target_entity_id,
+                source_entity_id,
+            )
+            .ok_or_else(|| {
+                anyhow!(
+                    "Tried to weld voxel object of entity {source_entity_id} into \
+                     entity {target_entity_id}, but one of them is missing a voxel object \
+                     or dynamic rigid body"
+                )
+            })?;
+        }
+
+        self.stage_entity_for_removal(source_entity_id);
+
+        Ok(())
+    }
+
+    /// Deposits the given sphere into the voxel object of the given entity,
+    /// growing the object if required. The entity must have a voxel object
+    /// and a dynamic rigid body, whose inertial properties are updated to
+    /// account for the added mass.
+    pub fn deposit_voxel_sphere(
+        &self,
+        entity_id: EntityID,
+        depositing_sphere: &VoxelDepositingSphere,
+        sphere_to_world_transform: &Isometry3,
+    ) -> Result<VoxelDepositionOutcome> {
+        self.apply_voxel_deposition(
+            entity_id,
+            |voxel_manager, voxel_type_registry, rigid_body_manager, anchor_manager| {
+                deposition::apply_sphere_deposition(
+                    voxel_manager,
+                    voxel_type_registry,
+                    rigid_body_manager,
+                    anchor_manager,
+                    entity_id,
+                    depositing_sphere,
+                    sphere_to_world_transform,
+                )
+            },
+        )
+    }
+
+    /// Deposits the given capsule into the voxel object of the given entity.
+    /// See [`Self::deposit_voxel_sphere`] for details.
+    pub fn deposit_voxel_capsule(
+        &self,
+        entity_id: EntityID,
+        depositing_capsule: &VoxelDepositingCapsule,
+        capsule_to_world_transform: &Isometry3,
+    ) -> Result<VoxelDepositionOutcome> {
+        self.apply_voxel_deposition(
+            entity_id,
+            |voxel_manager, voxel_type_registry, rigid_body_manager, anchor_manager| {
+                deposition::apply_capsule_deposition(
+                    voxel_manager,
+                    voxel_type_registry,
+                    rigid_body_manager,
+                    anchor_manager,
+                    entity_id,
+                    depositing_capsule,
+                    capsule_to_world_transform,
+                )
+            },
+        )
+    }
+
+    /// Deposits the given box into the voxel object of the given entity. See
+    /// [`Self::deposit_voxel_sphere`] for details.
+    pub fn deposit_voxel_box(
+        &self,
+        entity_id: EntityID,
+        depositing_box: &VoxelDepositingBox,
+        box_to_world_transform: &Isometry3,
+    ) -> Result<VoxelDepositionOutcome> {
+        self.apply_voxel_deposition(
+            entity_id,
+            |voxel_manager, voxel_type_registry, rigid_body_manager, anchor_manager| {
+                deposition::apply_box_deposition(
+                    voxel_manager,
+                    voxel_type_registry,
+                    rigid_body_manager,
+                    anchor_manager,
+                    entity_id,
+                    depositing_box,
+                    box_to_world_transform,
+                )
+            },
+        )
+    }
+
+    fn apply_voxel_deposition(
+        &self,
+        entity_id: EntityID,
+        apply: impl FnOnce(
+            &mut VoxelManager,
+            &VoxelTypeRegistry,
+            &mut RigidBodyManager,
+            &mut AnchorManager,
+        ) -> Option<VoxelDepositionOutcome>,
+    ) -> Result<VoxelDepositionOutcome> {
+        let resource_manager = self.resource_manager().oread();
+        let scene = self.scene().oread();
+        let voxel_manager = &mut **scene.voxel_manager().owrite();
+        let simulator = self.simulator().oread();
+        let mut rigid_body_manager = simulator.rigid_body_manager().owrite();
+        let mut anchor_manager = simulator.anchor_manager().owrite();
+
+        apply(
+            voxel_manager,
+            &resource_manager.voxel_types,
+            &mut rigid_body_manager,
+            &mut anchor_manager,
+        )
+        .ok_or_else(|| {
+            anyhow!(
+                "Tried to deposit voxels into entity {entity_id}, but it is missing a voxel \
+                 object or dynamic rigid body"
+            )
+        })
+    }
+
+    pub fn with_voxel_type_registry<R>(&self, f: impl FnOnce(&VoxelTypeRegistry) -> R) -> R {
+        let resource_manager = self.resource_manager().oread();
+        f(&resource_manager.voxel_types)
+    }
+
     pub fn with_absorbed_voxels_for_sphere<R>(
         &self,
         entity_id: EntityID,
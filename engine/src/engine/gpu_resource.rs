@@ -3,6 +3,7 @@
 use super::Engine;
 use crate::lock_order::OrderedRwLock;
 use anyhow::Result;
+use impact_scene::skybox::{Skybox, atmosphere::Sun};
 
 impl Engine {
     pub(crate) fn sync_all_gpu_resources(&self) -> Result<()> {
@@ -108,26 +109,50 @@ impl Engine {
     pub(crate) fn sync_misc_gpu_resources(&self) -> Result<()> {
         let resource_manager = self.resource_manager().oread();
         let scene = self.scene().oread();
-        let skybox = scene.skybox().oread();
-        let renderer = self.renderer().oread();
-        let mut render_resource_manager = renderer.render_resource_manager().owrite();
-        let render_resource_manager = &mut **render_resource_manager;
 
-        impact_scene::skybox::sync_gpu_resources_for_skybox(
-            skybox.as_ref(),
-            renderer.graphics_device(),
-            &render_resource_manager.textures,
-            &render_resource_manager.samplers,
-            &mut render_resource_manager.skybox,
-        )?;
+        let sky_ambient_illuminance = {
+            let skybox = scene.skybox().oread();
+            let view_transform = scene.camera_manager().oread().active_view_transform();
 
-        resource_manager.voxel_types.sync_material_gpu_resources(
-            renderer.graphics_device(),
-            &render_resource_manager.textures,
-            &render_resource_manager.samplers,
-            renderer.bind_group_layout_registry(),
-            &mut render_resource_manager.voxel_materials,
-        )?;
+            let sun = matches!(skybox.as_ref(), Some(Skybox::Atmospheric(_)))
+                .then(|| Sun::find_brightest(&scene.light_manager().oread(), &view_transform))
+                .flatten();
+
+            let renderer = self.renderer().oread();
+            let mut render_resource_manager = renderer.render_resource_manager().owrite();
+            let render_resource_manager = &mut **render_resource_manager;
+
+            impact_scene::skybox::sync_gpu_resources_for_skybox(
+                skybox.as_ref(),
+                sun.as_ref(),
+                renderer.graphics_device(),
+                &render_resource_manager.textures,
+                &render_resource_manager.samplers,
+                &mut render_resource_manager.skybox,
+            )?;
+
+            resource_manager.voxel_types.sync_material_gpu_resources(
+                renderer.graphics_device(),
+                &render_resource_manager.textures,
+                &render_resource_manager.samplers,
+                renderer.bind_group_layout_registry(),
+                &mut render_resource_manager.voxel_materials,
+            )?;
+
+            render_resource_manager
+                .skybox
+                .as_ref()
+                .and_then(|skybox| skybox.sky_ambient_illuminance())
+                .copied()
+        };
+
+        // The light manager is only write-locked after the skybox and renderer
+        // guards have been released, so that it is never held across the
+        // (potentially slow) skybox synchronization
+        scene
+            .light_manager()
+            .owrite()
+            .set_sky_ambient_illuminance(sky_ambient_illuminance);
 
         Ok(())
     }
@@ -139,6 +164,7 @@ impl Engine {
         let camera_manager = scene.camera_manager().oread();
         let light_manager = scene.light_manager().oread();
         let mut voxel_manager = scene.voxel_manager().owrite();
+        let joint_palette_manager = scene.joint_palette_manager().oread();
         let mut model_instance_manager = scene.model_instance_manager().owrite();
         let mut renderer = self.renderer().owrite();
 
@@ -146,6 +172,7 @@ impl Engine {
             &camera_manager,
             &light_manager,
             voxel_manager.object_manager_mut(),
+            &joint_palette_manager,
             &mut model_instance_manager,
         );
         Ok(())
@@ -68,6 +68,10 @@ pub fn create_wgpu_instance() -> wgpu::Instance {
 /// initializes the surface for presentation through the connected graphics
 /// device.
 ///
+/// The device must support more vertex buffers and vertex attributes than the
+/// `wgpu` defaults, so adapters that only support the defaults are rejected
+/// with an error listing the unsupported limits.
+///
 /// # Errors
 /// See [`GraphicsDevice::connect`].
 pub fn connect_to_graphics_device_for_rendering(
@@ -84,12 +88,18 @@ pub fn connect_to_graphics_device_for_rendering(
             | wgpu::Features::DEPTH_CLIP_CONTROL,
         wgpu::Features::POLYGON_MODE_LINE
             | wgpu::Features::TIMESTAMP_QUERY
-            | wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS,
+            | wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS
+            | wgpu::Features::TEXTURE_COMPRESSION_BC,
         wgpu::Limits {
             max_bind_groups: 7,
             max_push_constant_size: 256,
             max_color_attachment_bytes_per_sample: 64,
+            // Each location in the reserved instance, mesh and material
+            // ranges may be used by a vertex attribute
             max_vertex_attributes: vertex_attribute_ranges::TOTAL_LOCATIONS,
+            // Skinned models add a joint palette offset and a skin weight
+            // buffer on top of the other instance and mesh buffers
+            max_vertex_buffers: 16,
             ..wgpu::Limits::default()
         },
         wgpu::MemoryHints::Performance,
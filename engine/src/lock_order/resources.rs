@@ -27,6 +27,7 @@ declare_lockable_resource!(impact_ecs::world::World, 70);
 // ============================================================================
 
 declare_lockable_resource!(crate::resource::ResourceManager, 100);
+declare_lockable_resource!(crate::resource::hot_reloading::ResourceReloader, 110);
 
 // ============================================================================
 // SCENE (200-299)
@@ -39,6 +40,7 @@ declare_lockable_resource!(Option<impact_scene::skybox::Skybox>, 210);
 declare_lockable_resource!(impact_camera::CameraManager, 220);
 declare_lockable_resource!(impact_light::LightManager, 230);
 declare_lockable_resource!(impact_voxel::VoxelManager, 240);
+declare_lockable_resource!(impact_animation::JointPaletteManager, 245);
 declare_lockable_resource!(impact_scene::model::ModelInstanceManager, 250);
 declare_lockable_resource!(impact_intersection::IntersectionManager, 260);
 declare_lockable_resource!(impact_scene::graph::SceneGraph, 270);
@@ -1,5 +1,6 @@
 //! Graphics rendering.
 
+pub mod picking;
 pub mod render_command;
 pub mod resource;
 pub mod screen_capture;
@@ -8,6 +9,7 @@ use crate::{
     lock_order::OrderedRwLock, resource::ResourceManager, tasks::RenderToSurface, ui::UserInterface,
 };
 use anyhow::Result;
+use impact_animation::JointPaletteManager;
 use impact_camera::CameraManager;
 use impact_gpu::{
     bind_group_layout::BindGroupLayoutRegistry,
@@ -25,17 +27,21 @@ use impact_gpu::{
 use impact_light::{LightManager, shadow_map::ShadowMappingConfig};
 use impact_rendering::{
     BasicRenderingConfig,
-    attachment::RenderAttachmentTextureManager,
+    attachment::{
+        RenderAttachmentQuantity, RenderAttachmentQuantitySet, RenderAttachmentTextureManager,
+    },
     postprocessing::{
         Postprocessor, ambient_occlusion::AmbientOcclusionConfig, capturing::CapturingCameraConfig,
-        temporal_anti_aliasing::TemporalAntiAliasingConfig,
+        screen_space_reflection::SSRConfig, temporal_anti_aliasing::TemporalAntiAliasingConfig,
+        volumetric_fog::VolumetricFogConfig,
     },
     surface::RenderingSurface,
 };
-use impact_scene::model::ModelInstanceManager;
+use impact_scene::{lod::LODSelectionConfig, model::ModelInstanceManager};
 use impact_scheduling::{Task, TaskErrors};
 use impact_voxel::VoxelObjectManager;
 use parking_lot::RwLock;
+use picking::EntityPicker;
 use render_command::RenderCommandManager;
 use resource::RenderResourceManager;
 use serde::{Deserialize, Serialize};
@@ -58,9 +64,11 @@ pub struct RenderingSystem {
     staging_belt: wgpu::util::StagingBelt,
     render_command_manager: RenderCommandManager,
     timestamp_query_manager: TimestampQueryManager,
+    entity_picker: EntityPicker,
     frame_counter: u64,
     basic_config: BasicRenderingConfig,
     shadow_mapping_config: ShadowMappingConfig,
+    lod_selection_config: LODSelectionConfig,
 }
 
 /// Rendering configuration options.
@@ -69,7 +77,10 @@ pub struct RenderingSystem {
 pub struct RenderingConfig {
     pub basic: BasicRenderingConfig,
     pub shadow_mapping: ShadowMappingConfig,
+    pub lod_selection: LODSelectionConfig,
     pub ambient_occlusion: AmbientOcclusionConfig,
+    pub screen_space_reflection: SSRConfig,
+    pub volumetric_fog: VolumetricFogConfig,
     pub temporal_anti_aliasing: TemporalAntiAliasingConfig,
     pub capturing_camera: CapturingCameraConfig,
 }
@@ -92,8 +103,18 @@ impl RenderingSystem {
 
         let mut shader_manager = ShaderManager::new();
 
-        let mut render_attachment_texture_manager =
-            RenderAttachmentTextureManager::new(&graphics_device, &rendering_surface);
+        // The entity ID attachment is only needed for picking
+        let disabled_render_attachment_quantities = if config.basic.entity_picking_enabled {
+            RenderAttachmentQuantitySet::empty()
+        } else {
+            RenderAttachmentQuantitySet::ENTITY_ID
+        };
+
+        let mut render_attachment_texture_manager = RenderAttachmentTextureManager::new(
+            &graphics_device,
+            &rendering_surface,
+            disabled_render_attachment_quantities,
+        );
 
         let bind_group_layout_registry = BindGroupLayoutRegistry::new();
 
@@ -113,6 +134,8 @@ impl RenderingSystem {
 
         let postprocessor = Postprocessor::new(
             config.ambient_occlusion,
+            config.screen_space_reflection,
+            config.volumetric_fog,
             config.temporal_anti_aliasing,
             config.capturing_camera,
             &graphics_device,
@@ -136,6 +159,8 @@ impl RenderingSystem {
             ExternalGPUProfiler::None
         });
 
+        let entity_picker = EntityPicker::new(&graphics_device);
+
         Ok(Self {
             graphics_device,
             rendering_surface,
@@ -151,9 +176,11 @@ impl RenderingSystem {
             staging_belt: wgpu::util::StagingBelt::new(1024 * 1024),
             render_command_manager,
             timestamp_query_manager,
+            entity_picker,
             frame_counter: 0,
             basic_config: config.basic,
             shadow_mapping_config: config.shadow_mapping,
+            lod_selection_config: config.lod_selection,
         })
     }
 
@@ -221,6 +248,16 @@ impl RenderingSystem {
         &self.timestamp_query_manager
     }
 
+    /// Returns a reference to the [`EntityPicker`].
+    pub fn entity_picker(&self) -> &EntityPicker {
+        &self.entity_picker
+    }
+
+    /// Returns a mutable reference to the [`EntityPicker`].
+    pub fn entity_picker_mut(&mut self) -> &mut EntityPicker {
+        &mut self.entity_picker
+    }
+
     pub fn is_initial_frame(&self) -> bool {
         self.frame_counter == 0
     }
@@ -243,6 +280,14 @@ impl RenderingSystem {
         &mut self.shadow_mapping_config
     }
 
+    pub fn lod_selection_config(&self) -> &LODSelectionConfig {
+        &self.lod_selection_config
+    }
+
+    pub fn lod_selection_config_mut(&mut self) -> &mut LODSelectionConfig {
+        &mut self.lod_selection_config
+    }
+
     /// Presents the last surface texture that was rendered to. Does nothing if
     /// there is no texture to present.
     pub fn present(&mut self) {
@@ -258,6 +303,7 @@ impl RenderingSystem {
         camera_manager: &CameraManager,
         light_manager: &LightManager,
         voxel_object_manager: &mut VoxelObjectManager,
+        joint_palette_manager: &JointPaletteManager,
         model_instance_manager: &mut ModelInstanceManager,
     ) {
         let mut command_encoder =
@@ -294,6 +340,14 @@ impl RenderingSystem {
             &mut render_resource_manager.voxel_objects,
         );
 
+        joint_palette_manager.sync_gpu_resources(
+            &self.graphics_device,
+            &mut self.staging_belt,
+            &mut command_encoder,
+            &self.bind_group_layout_registry,
+            &mut render_resource_manager.joint_palettes,
+        );
+
         // TODO: Most instance feature GPU buffers are also updated every frame
         // because the instance feature buffers are cleared and repopulated
         // every frame.
@@ -325,6 +379,17 @@ impl RenderingSystem {
             &mut shader_manager,
             &**render_resource_manager,
             &self.bind_group_layout_registry,
+        )?;
+
+        let render_attachment_texture_manager = self.render_attachment_texture_manager.oread();
+        let gpu_resource_group_manager = self.gpu_resource_group_manager.oread();
+        self.postprocessor.owrite().sync_with_render_resources(
+            &self.graphics_device,
+            &mut shader_manager,
+            &render_attachment_texture_manager,
+            &gpu_resource_group_manager,
+            &self.bind_group_layout_registry,
+            &**render_resource_manager,
         )
     }
 
@@ -353,17 +418,28 @@ impl RenderingSystem {
             &self.storage_gpu_buffer_manager.oread(),
             &self.postprocessor.oread(),
             &self.shadow_mapping_config,
+            &self.lod_selection_config,
             self.frame_counter as u32,
             &mut timestamp_recorder,
             &mut command_encoder,
         )?;
 
+        if self.basic_config.entity_picking_enabled {
+            self.entity_picker.record_readback_if_requested(
+                &self.rendering_surface,
+                &self.render_attachment_texture_manager.oread(),
+                &mut command_encoder,
+            );
+        }
+
         timestamp_recorder.finish(&mut command_encoder);
 
         self.graphics_device
             .queue()
             .submit(std::iter::once(command_encoder.finish()));
 
+        self.entity_picker.begin_mapping_recorded_readback();
+
         Ok(())
     }
 
@@ -442,6 +518,14 @@ impl RenderingSystem {
         Ok(())
     }
 
+    /// Loads the result of the most recent entity pick if the GPU has finished
+    /// rendering the frame it was recorded in. Call after
+    /// [`Self::render_to_surface`]. This method does not wait for the GPU.
+    pub fn load_completed_entity_pick(&mut self) -> Result<()> {
+        self.entity_picker
+            .load_completed_readback(&self.graphics_device)
+    }
+
     /// Updates the exposure based on the current settings and potentially the
     /// average incident luminance. Call after [`Self::render_to_surface`]. This
     /// method will wait for the GPU to finish rendering if it needs the average
@@ -486,6 +570,26 @@ impl RenderingSystem {
         }
     }
 
+    pub fn set_entity_picking_enabled(&mut self, enabled: bool) {
+        let was_enabled = self.basic_config.entity_picking_enabled;
+
+        if enabled != was_enabled {
+            self.basic_config.entity_picking_enabled = enabled;
+            if !enabled {
+                self.entity_picker.clear_last_result();
+            }
+            self.render_attachment_texture_manager
+                .owrite()
+                .set_quantity_enabled(
+                    &self.graphics_device,
+                    &self.rendering_surface,
+                    RenderAttachmentQuantity::EntityID,
+                    enabled,
+                );
+            self.sync_render_command_manager_with_basic_config();
+        }
+    }
+
     pub fn set_render_pass_timings_enabled(&mut self, enabled: bool) {
         if enabled
             && !self
@@ -518,7 +622,7 @@ impl RenderingSystem {
     fn sync_render_command_manager_with_basic_config(&mut self) {
         self.render_command_manager.sync_with_config(
             &self.graphics_device,
-            &self.shader_manager.oread(),
+            &mut self.shader_manager.owrite(),
             &self.basic_config,
         );
     }
@@ -0,0 +1,348 @@
+//! Picking of the entities visible at specific screen positions.
+
+use anyhow::{Result, anyhow};
+use impact_camera::Camera;
+use impact_gpu::{device::GraphicsDevice, wgpu};
+use impact_id::EntityID;
+use impact_math::{point::Point3, vector::Vector3};
+use impact_model::entity::InstanceEntityID;
+use impact_rendering::{
+    attachment::{RenderAttachmentQuantity, RenderAttachmentTextureManager},
+    surface::RenderingSurface,
+};
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// Helper for reading back the entity ID and linear depth render attachment
+/// texels at a requested pixel in order to determine which entity is visible
+/// there.
+///
+/// The readback is asynchronous: a pick requested with [`Self::request_pick`]
+/// is recorded in the next frame rendered with entity picking enabled, and the
+/// result becomes available from [`Self::last_result`] once the GPU has
+/// finished rendering that frame.
+#[derive(Debug)]
+pub struct EntityPicker {
+    readback_buffer: wgpu::Buffer,
+    requested_pixel: Option<[u32; 2]>,
+    pending_readback: Option<PendingReadback>,
+    last_result: Option<EntityPickResult>,
+}
+
+/// The entity found under a picked pixel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EntityPickResult {
+    /// The ID of the picked entity.
+    pub entity_id: EntityID,
+    /// The indices of the picked voxel, if the entity is a voxel object.
+    pub voxel_indices: Option<[usize; 3]>,
+    /// The texture coordinates of the center of the picked pixel, with the
+    /// origin at the top left corner of the surface.
+    pub texture_coords: [f32; 2],
+    /// The linear depth (zero at the camera and one at the far plane) of the
+    /// picked surface.
+    pub linear_depth: f32,
+}
+
+/// Information about a picked entity.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PickedEntity {
+    /// The ID of the picked entity.
+    pub entity_id: EntityID,
+    /// The world space position of the picked surface point, if there is an
+    /// active camera.
+    pub world_position: Option<Point3>,
+    /// The indices of the picked voxel, if the entity is a voxel object.
+    pub voxel_indices: Option<[usize; 3]>,
+    /// The names of the components of the picked entity.
+    pub component_names: Vec<&'static str>,
+}
+
+type MapResult = Arc<Mutex<Option<Result<(), wgpu::BufferAsyncError>>>>;
+
+#[derive(Debug)]
+struct PendingReadback {
+    texture_coords: [f32; 2],
+    map_result: Option<MapResult>,
+}
+
+const ENTITY_ID_TEXEL_SIZE: u64 = 16;
+const LINEAR_DEPTH_TEXEL_SIZE: u64 = 4;
+
+const ENTITY_ID_OFFSET: u64 = 0;
+const LINEAR_DEPTH_OFFSET: u64 = ENTITY_ID_TEXEL_SIZE;
+
+const READBACK_BUFFER_SIZE: u64 = ENTITY_ID_TEXEL_SIZE + LINEAR_DEPTH_TEXEL_SIZE;
+
+/// The entity kinds encoded in the entity ID render attachment. Must match the
+/// kinds written by the geometry shaders.
+const NO_ENTITY_KIND: u32 = 0;
+const VOXEL_OBJECT_ENTITY_KIND: u32 = 2;
+
+impl EntityPicker {
+    /// Creates a new entity picker with no pending picks.
+    pub fn new(graphics_device: &GraphicsDevice) -> Self {
+        let readback_buffer = graphics_device
+            .device()
+            .create_buffer(&wgpu::BufferDescriptor {
+                size: READBACK_BUFFER_SIZE,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                label: Some("Entity picking readback buffer"),
+                mapped_at_creation: false,
+            });
+        Self {
+            readback_buffer,
+            requested_pixel: None,
+            pending_readback: None,
+            last_result: None,
+        }
+    }
+
+    /// Requests that the entity visible at the given physical pixel
+    /// coordinates (with the origin at the top left corner of the surface) be
+    /// picked. Replaces any earlier request that has not been recorded yet.
+    pub fn request_pick(&mut self, x: u32, y: u32) {
+        self.requested_pixel = Some([x, y]);
+    }
+
+    /// Whether a pick has been requested but its result is not available
+    /// yet.
+    pub fn has_pending_pick(&self) -> bool {
+        self.requested_pixel.is_some() || self.pending_readback.is_some()
+    }
+
+    /// Returns the result of the most recently completed pick, or [`None`] if
+    /// no entity was visible at the picked pixel (or nothing has been picked
+    /// yet).
+    pub fn last_result(&self) -> Option<&EntityPickResult> {
+        self.last_result.as_ref()
+    }
+
+    /// Forgets the result of the most recently completed pick.
+    pub fn clear_last_result(&mut self) {
+        self.last_result = None;
+    }
+
+    /// Records commands copying the entity ID and linear depth texels at the
+    /// requested pixel into the readback buffer, if a pick has been requested
+    /// and no earlier readback is still in progress.
+    ///
+    /// Must be called after the geometry pass has been recorded.
+    pub fn record_readback_if_requested(
+        &mut self,
+        rendering_surface: &RenderingSurface,
+        render_attachment_texture_manager: &RenderAttachmentTextureManager,
+        command_encoder: &mut wgpu::CommandEncoder,
+    ) {
+        if self.pending_readback.is_some() {
+            return;
+        }
+        let Some([x, y]) = self.requested_pixel.take() else {
+            return;
+        };
+
+        let (width, height) = rendering_surface.surface_dimensions();
+        let x = x.min(width.get() - 1);
+        let y = y.min(height.get() - 1);
+
+        for (quantity, offset) in [
+            (RenderAttachmentQuantity::EntityID, ENTITY_ID_OFFSET),
+            (RenderAttachmentQuantity::LinearDepth, LINEAR_DEPTH_OFFSET),
+        ] {
+            let texture = render_attachment_texture_manager
+                .render_attachment_texture(quantity)
+                .texture()
+                .texture();
+
+            command_encoder.copy_texture_to_buffer(
+                wgpu::TexelCopyTextureInfo {
+                    texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d { x, y, z: 0 },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::TexelCopyBufferInfo {
+                    buffer: &self.readback_buffer,
+                    layout: wgpu::TexelCopyBufferLayout {
+                        offset,
+                        bytes_per_row: None,
+                        rows_per_image: None,
+                    },
+                },
+                wgpu::Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        self.pending_readback = Some(PendingReadback {
+            texture_coords: [
+                (x as f32 + 0.5) / width.get() as f32,
+                (y as f32 + 0.5) / height.get() as f32,
+            ],
+            map_result: None,
+        });
+    }
+
+    /// Starts mapping the readback buffer if a readback was recorded. Must be
+    /// called after the commands recorded by
+    /// [`Self::record_readback_if_requested`] have been submitted.
+    pub fn begin_mapping_recorded_readback(&mut self) {
+        let Some(pending_readback) = &mut self.pending_readback else {
+            return;
+        };
+        if pending_readback.map_result.is_some() {
+            return;
+        }
+
+        let map_result_sender = Arc::new(Mutex::new(None));
+        let map_result_receiver = Arc::clone(&map_result_sender);
+
+        self.readback_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                *map_result_sender.lock() = Some(result);
+            });
+
+        pending_readback.map_result = Some(map_result_receiver);
+    }
+
+    /// Checks without blocking whether the readback buffer has been mapped,
+    /// and if so, decodes the picked texels into a new pick result.
+    ///
+    /// # Errors
+    /// Returns an error if polling the device or mapping the buffer fails.
+    pub fn load_completed_readback(&mut self, graphics_device: &GraphicsDevice) -> Result<()> {
+        let Some(map_result) = self
+            .pending_readback
+            .as_ref()
+            .and_then(|pending_readback| pending_readback.map_result.as_ref())
+        else {
+            return Ok(());
+        };
+
+        graphics_device.device().poll(wgpu::PollType::Poll)?;
+
+        let Some(map_result) = map_result.lock().take() else {
+            return Ok(());
+        };
+
+        let pending_readback = self.pending_readback.take().unwrap();
+
+        map_result.map_err(|error| anyhow!("Failed to map entity picking buffer: {error}"))?;
+
+        let (texel, linear_depth) = {
+            let buffer_view = self.readback_buffer.slice(..).get_mapped_range();
+            let entity_id_bytes = &buffer_view
+                [ENTITY_ID_OFFSET as usize..(ENTITY_ID_OFFSET + ENTITY_ID_TEXEL_SIZE) as usize];
+            let linear_depth_bytes = &buffer_view[LINEAR_DEPTH_OFFSET as usize
+                ..(LINEAR_DEPTH_OFFSET + LINEAR_DEPTH_TEXEL_SIZE) as usize];
+            (
+                bytemuck::pod_read_unaligned::<[u32; 4]>(entity_id_bytes),
+                bytemuck::pod_read_unaligned::<f32>(linear_depth_bytes),
+            )
+        };
+        self.readback_buffer.unmap();
+
+        self.last_result =
+            EntityPickResult::decode(texel, pending_readback.texture_coords, linear_depth);
+
+        Ok(())
+    }
+}
+
+impl EntityPickResult {
+    /// Decodes the given entity ID texel into a pick result, or returns
+    /// [`None`] if the texel does not contain an entity.
+    fn decode(texel: [u32; 4], texture_coords: [f32; 2], linear_depth: f32) -> Option<Self> {
+        let kind = texel[3] >> 16;
+        if kind == NO_ENTITY_KIND {
+            return None;
+        }
+
+        let entity_id = InstanceEntityID {
+            lower: texel[0],
+            upper: texel[1],
+        }
+        .entity_id();
+
+        let voxel_indices = (kind == VOXEL_OBJECT_ENTITY_KIND).then(|| {
+            [
+                (texel[2] & 0xFFFF) as usize,
+                (texel[2] >> 16) as usize,
+                (texel[3] & 0xFFFF) as usize,
+            ]
+        });
+
+        Some(Self {
+            entity_id,
+            voxel_indices,
+            texture_coords,
+            linear_depth,
+        })
+    }
+
+    /// Computes the world space position of the picked surface point as seen
+    /// from the given camera.
+    pub fn compute_world_space_position(&self, camera: &Camera) -> Point3 {
+        let corners = camera.projection().view_frustum().compute_corners();
+
+        let [u, v] = self.texture_coords;
+
+        let lower_left = *corners[1].as_vector();
+        let lower_right = *corners[5].as_vector();
+        let upper_left = *corners[3].as_vector();
+        let upper_right = *corners[7].as_vector();
+
+        let upper = lerp(&upper_left, &upper_right, u);
+        let lower = lerp(&lower_left, &lower_right, u);
+        let far_plane_point = lerp(&upper, &lower, v);
+
+        let camera_space_position = Point3::from(far_plane_point * self.linear_depth);
+
+        camera
+            .view_transform()
+            .inverse_transform_point(&camera_space_position)
+    }
+}
+
+fn lerp(a: &Vector3, b: &Vector3, t: f32) -> Vector3 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decoding_cleared_texel_gives_no_result() {
+        assert!(EntityPickResult::decode([0; 4], [0.5, 0.5], 1.0).is_none());
+    }
+
+    #[test]
+    fn decoding_model_instance_texel_gives_entity_without_voxel() {
+        let entity_id = EntityID::from_u64(0x1234_5678_9ABC_DEF0);
+        let feature = InstanceEntityID::new(entity_id);
+        let result =
+            EntityPickResult::decode([feature.lower, feature.upper, 0, 1 << 16], [0.5, 0.5], 0.2)
+                .unwrap();
+        assert_eq!(result.entity_id, entity_id);
+        assert!(result.voxel_indices.is_none());
+    }
+
+    #[test]
+    fn decoding_voxel_object_texel_gives_entity_with_voxel() {
+        let entity_id = EntityID::from_u64(42);
+        let feature = InstanceEntityID::new(entity_id);
+        let result = EntityPickResult::decode(
+            [feature.lower, feature.upper, 3 | (7 << 16), 11 | (2 << 16)],
+            [0.5, 0.5],
+            0.2,
+        )
+        .unwrap();
+        assert_eq!(result.entity_id, entity_id);
+        assert_eq!(result.voxel_indices, Some([3, 7, 11]));
+    }
+}
@@ -27,6 +27,7 @@ use impact_rendering::{
     resource::{BasicGPUResources, BasicResourceRegistries},
     surface::RenderingSurface,
 };
+use impact_scene::lod::LODSelectionConfig;
 use impact_voxel::{
     gpu_resource::{VoxelGPUResources, VoxelResourceRegistries},
     render_commands::VoxelRenderCommands,
@@ -63,10 +64,13 @@ impl RenderCommandManager {
     where
         R: BasicResourceRegistries + VoxelResourceRegistries,
     {
+        // The G-buffer and entity ID attachments are cleared by the geometry
+        // pass (the latter only when it is in use)
         let attachment_clearing_pass = AttachmentClearingPass::new(
             (RenderAttachmentQuantitySet::DEPTH_STENCIL
                 | RenderAttachmentQuantitySet::all().with_clear_color_only())
-                - RenderAttachmentQuantitySet::g_buffer(),
+                - RenderAttachmentQuantitySet::g_buffer()
+                - RenderAttachmentQuantitySet::ENTITY_ID,
         );
 
         let non_physical_model_depth_prepass = DepthPrepass::new(
@@ -143,7 +147,7 @@ impl RenderCommandManager {
     pub fn sync_with_config(
         &mut self,
         graphics_device: &GraphicsDevice,
-        shader_manager: &ShaderManager,
+        shader_manager: &mut ShaderManager,
         config: &BasicRenderingConfig,
     ) {
         self.non_physical_model_depth_prepass.sync_with_config(
@@ -156,7 +160,12 @@ impl RenderCommandManager {
             .sync_with_config(graphics_device, shader_manager, config);
 
         if let Some(voxel_render_commands) = &mut self.voxel_render_commands {
-            voxel_render_commands.sync_with_config(graphics_device, shader_manager, config);
+            voxel_render_commands.sync_with_config(
+                graphics_device,
+                shader_manager,
+                &self.geometry_pass,
+                config,
+            );
         }
     }
 
@@ -228,6 +237,7 @@ impl RenderCommandManager {
         storage_gpu_buffer_manager: &StorageGPUBufferManager,
         postprocessor: &Postprocessor,
         shadow_mapping_config: &ShadowMappingConfig,
+        lod_selection_config: &LODSelectionConfig,
         frame_counter: u32,
         timestamp_recorder: &mut TimestampQueryRegistry<'_>,
         command_encoder: &mut wgpu::CommandEncoder,
@@ -243,6 +253,7 @@ impl RenderCommandManager {
 
         if let Some(voxel_render_commands) = &self.voxel_render_commands {
             voxel_render_commands.record_before_geometry_pass(
+                lod_selection_config,
                 gpu_resources,
                 timestamp_recorder,
                 command_encoder,
@@ -295,6 +306,7 @@ impl RenderCommandManager {
                     voxel_render_commands
                         .record_before_omnidirectional_light_shadow_cubemap_face_update(
                             positive_z_cubemap_face_frustum,
+                            lod_selection_config,
                             instance_range_id,
                             gpu_resources,
                             timestamp_recorder,
@@ -327,6 +339,7 @@ impl RenderCommandManager {
                     voxel_render_commands
                         .record_before_unidirectional_light_shadow_map_cascade_update(
                             cascade_frustum,
+                            lod_selection_config,
                             instance_range_id,
                             gpu_resources,
                             timestamp_recorder,
@@ -1,5 +1,6 @@
 //! GPU resource management.
 
+use impact_animation::gpu_resource::JointPaletteGPUResource;
 use impact_camera::gpu_resource::CameraGPUResource;
 use impact_light::gpu_resource::LightGPUResources;
 use impact_material::gpu_resource::{
@@ -18,6 +19,7 @@ pub struct RenderResourceManager {
     pub camera: Option<CameraGPUResource>,
     pub skybox: Option<SkyboxGPUResource>,
     pub lights: Option<LightGPUResources>,
+    pub joint_palettes: Option<JointPaletteGPUResource>,
     pub triangle_meshes: TriangleMeshGPUResourceMap,
     pub line_segment_meshes: LineSegmentMeshGPUResourceMap,
     pub textures: TextureMap,
@@ -37,6 +39,7 @@ impl RenderResourceManager {
             camera: None,
             skybox: None,
             lights: None,
+            joint_palettes: None,
             triangle_meshes: TriangleMeshGPUResourceMap::new(),
             line_segment_meshes: LineSegmentMeshGPUResourceMap::new(),
             textures: TextureMap::new(),
@@ -71,6 +74,10 @@ impl BasicGPUResources for RenderResourceManager {
         self.lights.as_ref()
     }
 
+    fn joint_palette(&self) -> Option<&JointPaletteGPUResource> {
+        self.joint_palettes.as_ref()
+    }
+
     fn triangle_mesh(&self) -> &TriangleMeshGPUResourceMap {
         &self.triangle_meshes
     }
@@ -1,15 +1,33 @@
 //! Resource management.
 
+pub mod database;
+pub mod gltf;
+pub mod hot_reloading;
 pub mod import;
 
-use crate::resource::import::ResourceDeclarations;
+use crate::resource::{
+    database::ResourceDatabase, gltf::ImportedSceneRegistry, import::ResourceDeclarations,
+};
 use anyhow::Result;
-use impact_material::{MaterialRegistry, MaterialTemplateRegistry, MaterialTextureGroupRegistry};
-use impact_mesh::{LineSegmentMeshRegistry, TriangleMeshRegistry};
+use impact_animation::{AnimationClipRegistry, SkeletonRegistry};
+use impact_material::{
+    Material, MaterialRegistry, MaterialTemplate, MaterialTemplateRegistry, MaterialTextureGroup,
+    MaterialTextureGroupRegistry,
+};
+use impact_mesh::{LineSegmentMeshRegistry, MeshID, TriangleMeshRegistry};
 use impact_rendering::resource::BasicResourceRegistries;
-use impact_texture::{SamplerRegistry, TextureRegistry, lookup_table::LookupTableRegistry};
+use impact_resource::dependency::ResourceDependencyReferences;
+use impact_scene::{model::ModelID, skybox::Skybox};
+use impact_texture::{
+    SamplerRegistry, TextureCreateInfo, TextureID, TextureRegistry,
+    lookup_table::LookupTableRegistry,
+};
 use impact_voxel::{
-    generation::VoxelGeneratorRegistry, gpu_resource::VoxelResourceRegistries,
+    generation::{
+        VoxelGeneratorRegistry, pregenerated::PregeneratedVoxelObjectRegistry,
+        vox::VoxModelRegistry, voxelization::VoxelizedMeshRegistry,
+    },
+    gpu_resource::VoxelResourceRegistries,
     voxel_types::VoxelTypeRegistry,
 };
 use serde::{Deserialize, Serialize};
@@ -22,9 +40,25 @@ pub struct ResourceConfig {
     /// as RON (Rusty Object Notation). The resources specified in the file will
     /// be automatically loaded on startup.
     pub resource_file_path: Option<PathBuf>,
+    /// Path to a resource database created from a resource file with the
+    /// `import-resources` command. If specified, resources will be loaded on
+    /// startup from the database instead of from the resource file.
+    pub resource_database_path: Option<PathBuf>,
     /// Path to the folder where automatically computed lookup tables should be
     /// stored.
     pub lookup_table_dir: PathBuf,
+    /// Number of frames to keep a mesh, material or texture after the last
+    /// model instance or other resource referencing it was removed, before
+    /// evicting it. Resources that have never been referenced are never
+    /// evicted. Evicted resources are not reloaded, so this should only be
+    /// enabled if resources are not needed again after their last use. If
+    /// [`None`] (the default), resources are never evicted.
+    pub unreferenced_resource_grace_frames: Option<u64>,
+    /// Whether to watch the source files of the textures, triangle meshes and
+    /// voxel generators declared in the resource file and reload them when
+    /// they change. Requires the `hot_reloading` feature, and has no effect when
+    /// resources are loaded from a resource database.
+    pub hot_reloading: bool,
 }
 
 /// Owner and manager of all resource registries.
@@ -39,8 +73,21 @@ pub struct ResourceManager {
     pub material_templates: MaterialTemplateRegistry,
     pub material_texture_groups: MaterialTextureGroupRegistry,
     pub voxel_generators: VoxelGeneratorRegistry,
+    pub voxelized_meshes: VoxelizedMeshRegistry,
+    pub vox_models: VoxModelRegistry,
+    /// Voxel objects generated when importing a resource database, used in
+    /// place of generating the matching objects.
+    pub pregenerated_voxel_objects: PregeneratedVoxelObjectRegistry,
     pub voxel_types: VoxelTypeRegistry,
+    pub skeletons: SkeletonRegistry,
+    pub animation_clips: AnimationClipRegistry,
+    pub imported_scenes: ImportedSceneRegistry,
     pub config: ResourceConfig,
+    material_template_references: ResourceDependencyReferences<Material, MaterialTemplate>,
+    material_texture_group_references: ResourceDependencyReferences<Material, MaterialTextureGroup>,
+    texture_references: ResourceDependencyReferences<MaterialTextureGroup, TextureCreateInfo>,
+    /// Textures referenced by the skybox and the voxel types.
+    external_texture_references: Vec<TextureID>,
 }
 
 impl ResourceConfig {
@@ -58,6 +105,9 @@ impl ResourceConfig {
         if let Some(resource_file_path) = self.resource_file_path.as_mut() {
             *resource_file_path = root_path.join(&resource_file_path);
         }
+        if let Some(resource_database_path) = self.resource_database_path.as_mut() {
+            *resource_database_path = root_path.join(&resource_database_path);
+        }
         self.lookup_table_dir = root_path.join(&self.lookup_table_dir);
     }
 }
@@ -66,7 +116,10 @@ impl Default for ResourceConfig {
     fn default() -> Self {
         Self {
             resource_file_path: None,
+            resource_database_path: None,
             lookup_table_dir: PathBuf::from("resources/lookup_tables"),
+            unreferenced_resource_grace_frames: None,
+            hot_reloading: false,
         }
     }
 }
@@ -88,21 +141,34 @@ impl ResourceManager {
             material_templates: MaterialTemplateRegistry::new(),
             material_texture_groups: MaterialTextureGroupRegistry::new(),
             voxel_generators: VoxelGeneratorRegistry::new(),
+            voxelized_meshes: VoxelizedMeshRegistry::new(),
+            vox_models: VoxModelRegistry::new(),
+            pregenerated_voxel_objects: PregeneratedVoxelObjectRegistry::new(),
             voxel_types,
+            skeletons: SkeletonRegistry::new(),
+            animation_clips: AnimationClipRegistry::new(),
+            imported_scenes: ImportedSceneRegistry::new(),
             config,
+            material_template_references: ResourceDependencyReferences::new(),
+            material_texture_group_references: ResourceDependencyReferences::new(),
+            texture_references: ResourceDependencyReferences::new(),
+            external_texture_references: Vec::new(),
         }
     }
 
-    /// Parses the resource file pointed to in the [`ResourceConfig`] and loads
-    /// all resources declared in the file.
-    ///
-    /// # Returns
-    /// The parsed [`ResourceDeclarations`].
+    /// Loads all resources in the resource database pointed to in the
+    /// [`ResourceConfig`], or if there is no database, parses the resource
+    /// file pointed to in the config and loads all resources declared in the
+    /// file.
     ///
     /// # Errors
-    /// Returns an error if the resource file does not exist or is invalid. See
-    /// also [`Self::load_declared_resources`].
+    /// Returns an error if the resource database or file does not exist or is
+    /// invalid. See also [`Self::load_declared_resources`].
     pub fn load_resources_declared_in_config(&mut self) -> Result<()> {
+        if let Some(resource_database_path) = &self.config.resource_database_path {
+            let database = ResourceDatabase::open(resource_database_path)?;
+            return database.load_resources(self);
+        }
         let declarations = self.config.read_declarations()?;
         self.load_declared_resources(&declarations)?;
         Ok(())
@@ -148,8 +214,157 @@ impl ResourceManager {
             &mut self.voxel_generators,
             &resource_declarations.voxel_generators,
         )?;
+        impact_voxel::generation::import::load_declared_voxelized_meshes(
+            &mut self.voxelized_meshes,
+            &resource_declarations.voxelized_meshes,
+        )?;
+        impact_voxel::generation::import::load_declared_vox_models(
+            &mut self.vox_models,
+            &resource_declarations.vox_models,
+        )?;
+        gltf::load_declared_gltf_scenes(self, &resource_declarations.gltf_scenes)?;
         Ok(())
     }
+
+    /// Registers references from a model instance of the given model to the
+    /// model's mesh and material.
+    pub fn add_model_instance_references(&mut self, model_id: &ModelID) {
+        match model_id.mesh_id() {
+            MeshID::Triangle(mesh_id) => {
+                self.triangle_meshes.add_reference(mesh_id);
+            }
+            MeshID::LineSegment(mesh_id) => {
+                self.line_segment_meshes.add_reference(mesh_id);
+            }
+        }
+        self.materials.add_reference(model_id.material_id());
+    }
+
+    /// Unregisters references from a model instance of the given model to the
+    /// model's mesh and material.
+    pub fn remove_model_instance_references(&mut self, model_id: &ModelID) {
+        match model_id.mesh_id() {
+            MeshID::Triangle(mesh_id) => {
+                self.triangle_meshes.remove_reference(mesh_id);
+            }
+            MeshID::LineSegment(mesh_id) => {
+                self.line_segment_meshes.remove_reference(mesh_id);
+            }
+        }
+        self.materials.remove_reference(model_id.material_id());
+    }
+
+    /// Evicts all meshes, materials and textures that have not been referenced
+    /// by any model instance or other resource for the number of frames
+    /// specified in the [`ResourceConfig`]. Materials hold references to their
+    /// templates and texture groups, and texture groups hold references to
+    /// their textures. The given skybox and the voxel types also hold
+    /// references to their textures.
+    ///
+    /// Evicted resources are recorded as removed in their registry, so their
+    /// GPU resources are freed on the next synchronization.
+    pub fn evict_unreferenced_resources(&mut self, skybox: Option<&Skybox>) {
+        let Some(grace_period) = self.config.unreferenced_resource_grace_frames else {
+            return;
+        };
+
+        self.sync_external_texture_references(skybox);
+
+        log_evicted(
+            "triangle meshes",
+            self.triangle_meshes.evict_unreferenced(grace_period).len(),
+        );
+        log_evicted(
+            "line segment meshes",
+            self.line_segment_meshes
+                .evict_unreferenced(grace_period)
+                .len(),
+        );
+
+        // Resources are evicted in dependency order, so that resources only
+        // referenced by evicted resources are released in the same pass
+
+        self.sync_material_dependency_references();
+        log_evicted(
+            "materials",
+            self.materials.evict_unreferenced(grace_period).len(),
+        );
+
+        self.sync_material_dependency_references();
+        log_evicted(
+            "material templates",
+            self.material_templates
+                .evict_unreferenced(grace_period)
+                .len(),
+        );
+        log_evicted(
+            "material texture groups",
+            self.material_texture_groups
+                .evict_unreferenced(grace_period)
+                .len(),
+        );
+
+        self.sync_texture_group_dependency_references();
+        log_evicted(
+            "textures",
+            self.textures.evict_unreferenced(grace_period).len(),
+        );
+    }
+
+    fn sync_material_dependency_references(&mut self) {
+        self.material_template_references.sync(
+            &self.materials,
+            &mut self.material_templates,
+            |material| [material.template_id],
+        );
+        self.material_texture_group_references.sync(
+            &self.materials,
+            &mut self.material_texture_groups,
+            |material| [material.texture_group_id],
+        );
+        self.sync_texture_group_dependency_references();
+    }
+
+    fn sync_external_texture_references(&mut self, skybox: Option<&Skybox>) {
+        let mut texture_ids: Vec<_> = [
+            self.voxel_types.color_texture_array_id(),
+            self.voxel_types.roughness_texture_array_id(),
+            self.voxel_types.normal_texture_array_id(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        if let Some(&Skybox::Cubemap {
+            cubemap_texture_id, ..
+        }) = skybox
+        {
+            texture_ids.push(cubemap_texture_id);
+        }
+
+        // New references are added before the old ones are released, so that
+        // textures that remain in use never drop to zero references
+        texture_ids.retain(|&texture_id| self.textures.add_reference(texture_id));
+
+        for texture_id in self.external_texture_references.drain(..) {
+            self.textures.remove_reference(texture_id);
+        }
+        self.external_texture_references = texture_ids;
+    }
+
+    fn sync_texture_group_dependency_references(&mut self) {
+        self.texture_references.sync(
+            &self.material_texture_groups,
+            &mut self.textures,
+            |texture_group| texture_group.texture_ids.clone(),
+        );
+    }
+}
+
+fn log_evicted(resource_kind: &str, n_evicted: usize) {
+    if n_evicted > 0 {
+        log::debug!("Evicted {n_evicted} unreferenced {resource_kind}");
+    }
 }
 
 impl BasicResourceRegistries for ResourceManager {
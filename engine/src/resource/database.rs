@@ -0,0 +1,1651 @@
+//! Database of processed resources.
+//!
+//! Resources declared in [`ResourceDeclarations`] are imported offline into a
+//! content-addressed database on disk, so that the engine can load them at
+//! runtime without decoding images or parsing mesh files. The database is a
+//! directory containing an index file and an `objects` directory holding the
+//! processed data for each resource, named by the hash of the data.
+//!
+//! Each entry in the index records a hash of the declaration and its source
+//! files. When importing again, only resources whose source hash has changed
+//! are processed, and objects that are no longer referenced are deleted.
+//!
+//! Voxel generators can declare voxel objects to generate during import. These
+//! are stored in the voxel object file format and used at runtime in place of
+//! generating the matching objects.
+//!
+//! glTF scenes are not processed into the database. Their declarations are
+//! recorded in the index, and they are loaded from their source files at
+//! runtime.
+
+use crate::resource::{
+    ResourceManager,
+    gltf::{self, GltfSceneDeclaration},
+    import::ResourceDeclarations,
+};
+use anyhow::{Context, Result, anyhow, bail};
+use bytemuck::Pod;
+use impact_alloc::Global;
+use impact_gpu::texture::{BlockCompression, ColorSpace, SamplerConfig, TextureConfig};
+use impact_io::image::{ImageMetadata, PixelFormat};
+use impact_material::import::MaterialDeclaration;
+use impact_math::hash::{Hash32, Hash64, compute_hash_64_of_two_hash_64};
+use impact_mesh::{
+    TriangleMesh, TriangleMeshDirtyMask, TriangleMeshID, TriangleMeshRegistry, VertexSkinWeights,
+    import::{TriangleMeshDeclaration, TriangleMeshSource},
+};
+use impact_texture::{
+    ImageTextureSource, SamplerRegistry, TextureArrayUsage, TextureID, TextureRegistry,
+    atlas::TextureAtlas,
+    import::{DeclaredImageTextureSource, ImageTextureDeclaration},
+    mip_chain::{CompressedMipChain, MipChain},
+    processing::ImageProcessing,
+};
+use impact_voxel::{
+    generation::{
+        VoxelGenerator, VoxelGeneratorID, VoxelGeneratorRegistry,
+        import::{
+            PregeneratedVoxelObjectDeclaration, VoxModelDeclaration, VoxelGeneratorDeclaration,
+            VoxelizedMeshDeclaration,
+        },
+        pregenerated::{PregeneratedVoxelObjectKey, PregeneratedVoxelObjectRegistry},
+        vox::{VoxModel, VoxModelID, VoxModelRegistry},
+        voxel_type::SameVoxelTypeGenerator,
+    },
+    object::{VoxelObject, VoxelObjectBuffers, io},
+    voxel_types::{VoxelType, VoxelTypeRegistry},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fmt, fs, iter,
+    path::{Path, PathBuf},
+};
+
+/// The version of the database format. Incrementing it invalidates all
+/// existing entries, so it must be incremented whenever the processing or the
+/// format of the processed data changes.
+const FORMAT_VERSION: u32 = 5;
+
+const INDEX_FILE_NAME: &str = "index.ron";
+const OBJECT_DIR_NAME: &str = "objects";
+const OBJECT_FILE_EXTENSION: &str = "bin";
+
+/// A database of processed resources stored in a directory on disk.
+#[derive(Debug)]
+pub struct ResourceDatabase {
+    root_path: PathBuf,
+    index: ResourceDatabaseIndex,
+}
+
+/// Summary of an import into a [`ResourceDatabase`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ResourceImportSummary {
+    /// The number of resources that were processed.
+    pub n_processed: usize,
+    /// The number of resources whose existing entry was up to date.
+    pub n_up_to_date: usize,
+    /// The number of resources that could not be processed.
+    pub n_failed: usize,
+    /// The number of resources that were not processed because the database
+    /// does not support their type. They are loaded from their source files
+    /// at runtime instead.
+    pub n_skipped: usize,
+    /// The number of unreferenced objects that were deleted.
+    pub n_pruned_objects: usize,
+}
+
+/// A 64-bit hash identifying either the source of a resource or a processed
+/// object.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+struct ContentHash(u64);
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ResourceDatabaseIndex {
+    format_version: u32,
+    triangle_meshes: Vec<ProcessedResourceEntry>,
+    textures: Vec<ProcessedTextureEntry>,
+    voxel_generators: Vec<ProcessedVoxelGeneratorEntry>,
+    vox_models: Vec<ProcessedResourceEntry>,
+    /// The names of the voxel types, in registry order, that pregenerated
+    /// voxel objects were generated with.
+    voxel_type_names: Vec<String>,
+    /// Materials need no processing, so their declarations are stored
+    /// directly.
+    materials: Vec<MaterialDeclaration>,
+    /// Voxelized meshes only reference other resources, so their declarations
+    /// are stored directly.
+    voxelized_meshes: Vec<VoxelizedMeshDeclaration>,
+    /// glTF scenes are not processed, so their declarations are stored for
+    /// loading them from their source files.
+    gltf_scenes: Vec<GltfSceneDeclaration>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ProcessedResourceEntry {
+    /// The name the resource ID is derived from.
+    name: String,
+    source_hash: ContentHash,
+    object_hash: ContentHash,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ProcessedTextureEntry {
+    resource: ProcessedResourceEntry,
+    meta: ImageMetadata,
+    n_layers: u32,
+    array_usage: Option<TextureArrayUsage>,
+    level_count: u32,
+    texture_config: TextureConfig,
+    sampler_config: Option<SamplerConfig>,
+    /// The block compression of the stored mip chain, if any. The metadata
+    /// then describes the decompressed base level.
+    #[serde(default)]
+    compression: Option<BlockCompression>,
+    /// The layout of the texture if it is a texture atlas.
+    #[serde(default)]
+    atlas: Option<TextureAtlas>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ProcessedVoxelGeneratorEntry {
+    resource: ProcessedResourceEntry,
+    pregenerated_objects: Vec<ProcessedPregeneratedVoxelObject>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ProcessedPregeneratedVoxelObject {
+    declaration: PregeneratedVoxelObjectDeclaration,
+    object_hash: ContentHash,
+}
+
+/// The mip chain of a processed texture, which may be block compressed.
+#[derive(Clone, Debug)]
+enum ProcessedMipChain {
+    Uncompressed(MipChain),
+    Compressed(CompressedMipChain),
+}
+
+/// The vertex attributes and indices of a triangle mesh, stored as the raw
+/// bytes that are uploaded to the GPU. The object for a declared mesh holds the
+/// full-detail mesh followed by any simplified levels of detail.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ProcessedTriangleMesh {
+    positions: Vec<u8>,
+    normal_vectors: Vec<u8>,
+    texture_coords: Vec<u8>,
+    tangent_space_quaternions: Vec<u8>,
+    colors: Vec<u8>,
+    skin_weights: Vec<u8>,
+    indices: Vec<u8>,
+}
+
+impl ResourceDatabase {
+    /// Opens the existing database at the given path.
+    ///
+    /// # Errors
+    /// Returns an error if the database index can not be read or was written
+    /// with a different format version.
+    pub fn open(root_path: impl AsRef<Path>) -> Result<Self> {
+        let root_path = root_path.as_ref().to_path_buf();
+        let index_path = root_path.join(INDEX_FILE_NAME);
+
+        let index: ResourceDatabaseIndex =
+            impact_io::parse_ron_file(&index_path).with_context(|| {
+                format!(
+                    "Failed to read resource database index {}",
+                    index_path.display()
+                )
+            })?;
+
+        if index.format_version != FORMAT_VERSION {
+            bail!(
+                "Resource database {} has format version {}, expected {FORMAT_VERSION} (re-import the resources)",
+                root_path.display(),
+                index.format_version
+            );
+        }
+
+        Ok(Self { root_path, index })
+    }
+
+    /// Opens the database at the given path, or creates an empty database if
+    /// there is no valid database there.
+    pub fn open_or_create(root_path: impl AsRef<Path>) -> Self {
+        let root_path = root_path.as_ref();
+        Self::open(root_path).unwrap_or_else(|error| {
+            log::debug!(
+                "Creating new resource database at {}: {error:#}",
+                root_path.display()
+            );
+            Self {
+                root_path: root_path.to_path_buf(),
+                index: ResourceDatabaseIndex {
+                    format_version: FORMAT_VERSION,
+                    ..Default::default()
+                },
+            }
+        })
+    }
+
+    /// Returns the path to the database directory.
+    pub fn root_path(&self) -> &Path {
+        &self.root_path
+    }
+
+    /// Processes all resources in the given declarations and stores them in
+    /// the database, replacing the previous contents. Resources whose
+    /// declaration and source files are unchanged since the last import are
+    /// not processed again. Failing to process a resource is not fatal, the
+    /// resource is just left out of the database.
+    ///
+    /// Triangle meshes are stored as raw vertex and index buffers, with
+    /// tangent space quaternions generated for meshes that have normal vectors
+    /// and texture coordinates. Image textures are stored with any processing
+    /// applied and a full mip chain (cubemaps are not mipmapped). Voxel
+    /// generators are stored as validated copies of their RON files, along
+    /// with the voxel objects they declare to be pregenerated, which are
+    /// generated using the given voxel type registry. Voxel models are stored
+    /// as validated copies of their `.vox` files. glTF scenes are skipped with
+    /// a warning, and only their declarations are stored so that they can be
+    /// loaded from their source files at runtime.
+    ///
+    /// # Errors
+    /// Returns an error if the database can not be written.
+    pub fn import_declarations(
+        &mut self,
+        declarations: &ResourceDeclarations,
+        voxel_types: &VoxelTypeRegistry,
+    ) -> Result<ResourceImportSummary> {
+        impact_io::create_directory_if_missing(self.object_dir())?;
+
+        let mut summary = ResourceImportSummary::default();
+
+        for declaration in &declarations.gltf_scenes {
+            log::warn!(
+                "glTF scene {} is not supported by the resource database and will be loaded \
+                 from {} at runtime",
+                declaration.name,
+                declaration.file_path.display()
+            );
+            summary.n_skipped += 1;
+        }
+
+        let voxel_type_names = voxel_type_names(voxel_types);
+
+        let mut index = ResourceDatabaseIndex {
+            format_version: FORMAT_VERSION,
+            voxel_type_names: voxel_type_names.clone(),
+            materials: declarations.materials.clone(),
+            voxelized_meshes: declarations.voxelized_meshes.clone(),
+            gltf_scenes: declarations.gltf_scenes.clone(),
+            ..Default::default()
+        };
+
+        for declaration in &declarations.triangle_meshes {
+            let name = declaration.id.to_string();
+            let entry = self.import_resource(
+                &mut summary,
+                &name,
+                &self.index.triangle_meshes,
+                |entry| &entry.name,
+                |entry| entry,
+                || compute_triangle_mesh_source_hash(declaration),
+                |database, source_hash| {
+                    let object = process_triangle_mesh(declaration)?;
+                    let object_hash = database.write_object(&object)?;
+                    Ok(ProcessedResourceEntry {
+                        name: name.clone(),
+                        source_hash,
+                        object_hash,
+                    })
+                },
+            );
+            index.triangle_meshes.extend(entry);
+        }
+
+        for declaration in &declarations.textures {
+            let name = declaration.id.to_string();
+            let entry = self.import_resource(
+                &mut summary,
+                &name,
+                &self.index.textures,
+                |entry| &entry.resource.name,
+                |entry| &entry.resource,
+                || compute_texture_source_hash(declaration),
+                |database, source_hash| {
+                    let (mip_chain, array_usage, atlas) = process_image_texture(declaration)?;
+                    let object_hash = database.write_object(mip_chain.data())?;
+                    Ok(ProcessedTextureEntry {
+                        resource: ProcessedResourceEntry {
+                            name: name.clone(),
+                            source_hash,
+                            object_hash,
+                        },
+                        meta: mip_chain.meta(),
+                        n_layers: mip_chain.n_layers(),
+                        array_usage,
+                        level_count: mip_chain.level_count(),
+                        texture_config: declaration.texture_config.clone(),
+                        sampler_config: declaration.sampler_config.clone(),
+                        compression: mip_chain.compression(),
+                        atlas,
+                    })
+                },
+            );
+            index.textures.extend(entry);
+        }
+
+        for declaration in &declarations.voxel_generators {
+            let name = declaration.id.to_string();
+            let entry = self.import_resource(
+                &mut summary,
+                &name,
+                &self.index.voxel_generators,
+                |entry| &entry.resource.name,
+                |entry| &entry.resource,
+                || compute_voxel_generator_source_hash(declaration, &voxel_type_names),
+                |database, source_hash| {
+                    let (object, generator) = process_voxel_generator(declaration)?;
+                    let object_hash = database.write_object(&object)?;
+
+                    let pregenerated_objects = declaration
+                        .pregenerated_objects
+                        .iter()
+                        .map(|pregenerated| {
+                            let object =
+                                pregenerate_voxel_object(&generator, pregenerated, voxel_types)?;
+                            Ok(ProcessedPregeneratedVoxelObject {
+                                declaration: pregenerated.clone(),
+                                object_hash: database.write_object(&object)?,
+                            })
+                        })
+                        .collect::<Result<_>>()?;
+
+                    Ok(ProcessedVoxelGeneratorEntry {
+                        resource: ProcessedResourceEntry {
+                            name: name.clone(),
+                            source_hash,
+                            object_hash,
+                        },
+                        pregenerated_objects,
+                    })
+                },
+            );
+            index.voxel_generators.extend(entry);
+        }
+
+        for declaration in &declarations.vox_models {
+            let name = declaration.id.to_string();
+            let entry = self.import_resource(
+                &mut summary,
+                &name,
+                &self.index.vox_models,
+                |entry| &entry.name,
+                |entry| entry,
+                || compute_vox_model_source_hash(declaration),
+                |database, source_hash| {
+                    let object = process_vox_model(declaration)?;
+                    let object_hash = database.write_object(&object)?;
+                    Ok(ProcessedResourceEntry {
+                        name: name.clone(),
+                        source_hash,
+                        object_hash,
+                    })
+                },
+            );
+            index.vox_models.extend(entry);
+        }
+
+        self.index = index;
+
+        impact_io::write_ron_file(&self.index, self.root_path.join(INDEX_FILE_NAME))?;
+
+        summary.n_pruned_objects = self.prune_unreferenced_objects()?;
+
+        Ok(summary)
+    }
+
+    /// Loads all resources in the database into the registries of the given
+    /// resource manager. glTF scenes recorded in the database are loaded from
+    /// their source files.
+    ///
+    /// # Errors
+    /// Returns an error if any resource can not be loaded, or if the database
+    /// has pregenerated voxel objects that were generated with different voxel
+    /// types than those in the resource manager.
+    pub fn load_resources(&self, resource_manager: &mut ResourceManager) -> Result<()> {
+        self.load_triangle_meshes(&mut resource_manager.triangle_meshes)?;
+        self.load_textures(
+            &mut resource_manager.textures,
+            &mut resource_manager.samplers,
+        )?;
+        self.load_voxel_generators(&mut resource_manager.voxel_generators)?;
+        self.load_pregenerated_voxel_objects(
+            &mut resource_manager.pregenerated_voxel_objects,
+            &resource_manager.voxel_types,
+        )?;
+        self.load_vox_models(&mut resource_manager.vox_models)?;
+        for declaration in &self.index.materials {
+            impact_material::import::load_declared_material(
+                &resource_manager.textures,
+                &resource_manager.samplers,
+                &mut resource_manager.materials,
+                &mut resource_manager.material_templates,
+                &mut resource_manager.material_texture_groups,
+                declaration,
+            )
+            .with_context(|| format!("Failed to load material {}", declaration.id))?;
+        }
+        for declaration in &self.index.voxelized_meshes {
+            impact_voxel::generation::import::load_declared_voxelized_mesh(
+                &mut resource_manager.voxelized_meshes,
+                declaration,
+            )
+            .with_context(|| format!("Failed to load voxelized mesh {}", declaration.id))?;
+        }
+        gltf::load_declared_gltf_scenes(resource_manager, &self.index.gltf_scenes)?;
+        Ok(())
+    }
+
+    fn load_triangle_meshes(&self, registry: &mut TriangleMeshRegistry) -> Result<()> {
+        for entry in &self.index.triangle_meshes {
+            (|| -> Result<_> {
+                let id = TriangleMeshID::from_name(&entry.name);
+                if registry.contains(id) {
+                    bail!("Tried to load triangle mesh under already existing ID: {id}");
+                }
+                let object = self.read_object(entry.object_hash)?;
+                let mut meshes = decode_triangle_meshes(&object)?.into_iter();
+                registry.insert(id, meshes.next().unwrap());
+                for (level, lod_mesh) in (1..).zip(meshes) {
+                    registry.insert(impact_mesh::lod::lod_mesh_id(id, level), lod_mesh);
+                }
+                Ok(())
+            })()
+            .with_context(|| {
+                format!(
+                    "Failed to load triangle mesh {} from resource database",
+                    entry.name
+                )
+            })?;
+        }
+        Ok(())
+    }
+
+    fn load_textures(
+        &self,
+        texture_registry: &mut TextureRegistry,
+        sampler_registry: &mut SamplerRegistry,
+    ) -> Result<()> {
+        for entry in &self.index.textures {
+            (|| -> Result<_> {
+                let data = self.read_object(entry.resource.object_hash)?;
+                let source = if let Some(compression) = entry.compression {
+                    ImageTextureSource::CompressedMipChain {
+                        mip_chain: CompressedMipChain::from_parts(
+                            compression,
+                            entry.meta.width,
+                            entry.meta.height,
+                            entry.n_layers,
+                            entry.level_count,
+                            data,
+                        )?,
+                        array_usage: entry.array_usage,
+                    }
+                } else {
+                    ImageTextureSource::MipChain {
+                        mip_chain: MipChain::from_parts(
+                            entry.meta.clone(),
+                            entry.n_layers,
+                            entry.level_count,
+                            data,
+                        )?,
+                        array_usage: entry.array_usage,
+                    }
+                };
+                impact_texture::import::load_image_texture_with_atlas(
+                    texture_registry,
+                    sampler_registry,
+                    TextureID::from_name(&entry.resource.name),
+                    source,
+                    entry.atlas.clone(),
+                    entry.texture_config.clone(),
+                    entry.sampler_config.clone(),
+                    ImageProcessing::none(),
+                )
+            })()
+            .with_context(|| {
+                format!(
+                    "Failed to load texture {} from resource database",
+                    entry.resource.name
+                )
+            })?;
+        }
+        Ok(())
+    }
+
+    fn load_voxel_generators(&self, registry: &mut VoxelGeneratorRegistry) -> Result<()> {
+        for entry in &self.index.voxel_generators {
+            let entry = &entry.resource;
+            (|| -> Result<_> {
+                let id = VoxelGeneratorID::from_name(&entry.name);
+                if registry.contains(id) {
+                    bail!("Tried to load voxel generator under already existing ID: {id}");
+                }
+                // Verify the object before parsing it
+                self.read_object(entry.object_hash)?;
+                let generator: VoxelGenerator =
+                    impact_io::parse_ron_file(self.object_path(entry.object_hash))?;
+                registry.insert(id, generator);
+                Ok(())
+            })()
+            .with_context(|| {
+                format!(
+                    "Failed to load voxel generator {} from resource database",
+                    entry.name
+                )
+            })?;
+        }
+        Ok(())
+    }
+
+    fn load_pregenerated_voxel_objects(
+        &self,
+        registry: &mut PregeneratedVoxelObjectRegistry,
+        voxel_types: &VoxelTypeRegistry,
+    ) -> Result<()> {
+        if self
+            .index
+            .voxel_generators
+            .iter()
+            .all(|entry| entry.pregenerated_objects.is_empty())
+        {
+            return Ok(());
+        }
+
+        // The objects store voxel types by their index in the registry
+        if voxel_type_names(voxel_types) != self.index.voxel_type_names {
+            bail!(
+                "The voxel types differ from those the pregenerated voxel objects in the \
+                 resource database were generated with (re-import the resources)"
+            );
+        }
+
+        for entry in &self.index.voxel_generators {
+            let generator_id = VoxelGeneratorID::from_name(&entry.resource.name);
+            for pregenerated in &entry.pregenerated_objects {
+                let key = pregenerated_voxel_object_key(generator_id, &pregenerated.declaration);
+                if registry.contains(&key) {
+                    bail!(
+                        "Got duplicate pregenerated voxel object for voxel generator {}",
+                        entry.resource.name
+                    );
+                }
+                let object = self
+                    .read_object(pregenerated.object_hash)
+                    .with_context(|| {
+                        format!(
+                            "Failed to load pregenerated voxel object for voxel generator {}",
+                            entry.resource.name
+                        )
+                    })?;
+                registry.insert(key, object);
+            }
+        }
+        Ok(())
+    }
+
+    fn load_vox_models(&self, registry: &mut VoxModelRegistry) -> Result<()> {
+        for entry in &self.index.vox_models {
+            (|| -> Result<_> {
+                let id = VoxModelID::from_name(&entry.name);
+                if registry.contains(id) {
+                    bail!("Tried to load voxel model under already existing ID: {id}");
+                }
+                let object = self.read_object(entry.object_hash)?;
+                registry.insert(id, VoxModel::from_bytes(&object)?);
+                Ok(())
+            })()
+            .with_context(|| {
+                format!(
+                    "Failed to load voxel model {} from resource database",
+                    entry.name
+                )
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Returns the entry for the resource with the given name, reusing the
+    /// existing entry if its source hash is unchanged and its object exists,
+    /// and processing the resource otherwise. Returns [`None`] if the
+    /// resource could not be processed.
+    #[allow(clippy::too_many_arguments)]
+    fn import_resource<E: Clone>(
+        &self,
+        summary: &mut ResourceImportSummary,
+        name: &str,
+        existing_entries: &[E],
+        entry_name: impl Fn(&E) -> &String,
+        resource_entry: impl Fn(&E) -> &ProcessedResourceEntry,
+        compute_source_hash: impl FnOnce() -> Result<ContentHash>,
+        process: impl FnOnce(&Self, ContentHash) -> Result<E>,
+    ) -> Option<E> {
+        let result = (|| -> Result<_> {
+            let source_hash = compute_source_hash()?;
+
+            if let Some(existing_entry) = existing_entries
+                .iter()
+                .find(|entry| entry_name(entry) == name)
+            {
+                let existing = resource_entry(existing_entry);
+                if existing.source_hash == source_hash
+                    && self.object_path(existing.object_hash).is_file()
+                {
+                    log::debug!("Resource {name} is up to date in resource database");
+                    return Ok((existing_entry.clone(), false));
+                }
+            }
+
+            log::info!("Processing resource {name} for resource database");
+            Ok((process(self, source_hash)?, true))
+        })();
+
+        match result {
+            Ok((entry, processed)) => {
+                if processed {
+                    summary.n_processed += 1;
+                } else {
+                    summary.n_up_to_date += 1;
+                }
+                Some(entry)
+            }
+            Err(error) => {
+                log::error!("Failed to import resource {name} into resource database: {error:#}");
+                summary.n_failed += 1;
+                None
+            }
+        }
+    }
+
+    fn object_dir(&self) -> PathBuf {
+        self.root_path.join(OBJECT_DIR_NAME)
+    }
+
+    fn object_path(&self, hash: ContentHash) -> PathBuf {
+        self.object_dir()
+            .join(format!("{hash}.{OBJECT_FILE_EXTENSION}"))
+    }
+
+    /// Writes the given object data to the database under its content hash,
+    /// unless an object with the same hash already exists.
+    fn write_object(&self, data: &[u8]) -> Result<ContentHash> {
+        let hash = ContentHash::of_bytes(data);
+        let path = self.object_path(hash);
+        if !path.is_file() {
+            impact_io::save_data_as_binary(&path, data)
+                .with_context(|| format!("Failed to write object {}", path.display()))?;
+        }
+        Ok(hash)
+    }
+
+    /// Reads the object with the given content hash and verifies that its
+    /// data matches the hash.
+    fn read_object(&self, hash: ContentHash) -> Result<Vec<u8>> {
+        let path = self.object_path(hash);
+        let data =
+            fs::read(&path).with_context(|| format!("Failed to read object {}", path.display()))?;
+        if ContentHash::of_bytes(&data) != hash {
+            bail!("Object {} is corrupted", path.display());
+        }
+        Ok(data)
+    }
+
+    fn referenced_object_hashes(&self) -> HashSet<ContentHash> {
+        self.index
+            .triangle_meshes
+            .iter()
+            .chain(self.index.textures.iter().map(|entry| &entry.resource))
+            .chain(
+                self.index
+                    .voxel_generators
+                    .iter()
+                    .map(|entry| &entry.resource),
+            )
+            .chain(&self.index.vox_models)
+            .map(|entry| entry.object_hash)
+            .chain(
+                self.index
+                    .voxel_generators
+                    .iter()
+                    .flat_map(|entry| &entry.pregenerated_objects)
+                    .map(|pregenerated| pregenerated.object_hash),
+            )
+            .collect()
+    }
+
+    fn prune_unreferenced_objects(&self) -> Result<usize> {
+        let referenced_object_hashes = self.referenced_object_hashes();
+        let referenced_file_names: HashSet<_> = referenced_object_hashes
+            .iter()
+            .map(|hash| format!("{hash}.{OBJECT_FILE_EXTENSION}"))
+            .collect();
+
+        let mut n_pruned = 0;
+        for dir_entry in fs::read_dir(self.object_dir())? {
+            let dir_entry = dir_entry?;
+            let file_name = dir_entry.file_name();
+            if !referenced_file_names.contains(file_name.to_string_lossy().as_ref()) {
+                fs::remove_file(dir_entry.path())?;
+                n_pruned += 1;
+            }
+        }
+        Ok(n_pruned)
+    }
+}
+
+impl fmt::Display for ResourceImportSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} processed, {} up to date, {} failed, {} skipped, {} unreferenced objects deleted",
+            self.n_processed,
+            self.n_up_to_date,
+            self.n_failed,
+            self.n_skipped,
+            self.n_pruned_objects
+        )
+    }
+}
+
+impl ContentHash {
+    fn of_bytes(bytes: &[u8]) -> Self {
+        Self(Hash64::from_bytes(bytes).to_u64())
+    }
+
+    fn combined_with(self, other: Self) -> Self {
+        Self(
+            compute_hash_64_of_two_hash_64(
+                Hash64::from_raw_u64(self.0),
+                Hash64::from_raw_u64(other.0),
+            )
+            .to_u64(),
+        )
+    }
+}
+
+impl fmt::Display for ContentHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// Computes a hash of the format version, the given declaration and the
+/// contents of the given source files.
+fn compute_source_hash<'a>(
+    declaration: &impl fmt::Debug,
+    source_file_paths: impl IntoIterator<Item = &'a PathBuf>,
+) -> Result<ContentHash> {
+    let mut hash = ContentHash::of_bytes(&FORMAT_VERSION.to_le_bytes())
+        .combined_with(ContentHash::of_bytes(format!("{declaration:?}").as_bytes()));
+
+    for path in source_file_paths {
+        let data = fs::read(path)
+            .with_context(|| format!("Failed to read source file {}", path.display()))?;
+        hash = hash.combined_with(ContentHash::of_bytes(&data));
+    }
+
+    Ok(hash)
+}
+
+fn compute_triangle_mesh_source_hash(declaration: &TriangleMeshDeclaration) -> Result<ContentHash> {
+    match &declaration.source {
+        TriangleMeshSource::File(path) => compute_source_hash(declaration, [path]),
+        TriangleMeshSource::Template(_) => compute_source_hash(declaration, []),
+    }
+}
+
+fn compute_texture_source_hash(declaration: &ImageTextureDeclaration) -> Result<ContentHash> {
+    compute_source_hash(declaration, declaration.source.image_paths())
+}
+
+fn compute_voxel_generator_source_hash(
+    declaration: &VoxelGeneratorDeclaration,
+    voxel_type_names: &[String],
+) -> Result<ContentHash> {
+    // Pregenerated objects depend on the available voxel types
+    let voxel_type_names: &[String] = if declaration.pregenerated_objects.is_empty() {
+        &[]
+    } else {
+        voxel_type_names
+    };
+    compute_source_hash(&(declaration, voxel_type_names), [&declaration.path])
+}
+
+fn compute_vox_model_source_hash(declaration: &VoxModelDeclaration) -> Result<ContentHash> {
+    compute_source_hash(declaration, [&declaration.path])
+}
+
+fn process_triangle_mesh(declaration: &TriangleMeshDeclaration) -> Result<Vec<u8>> {
+    let mut registry = TriangleMeshRegistry::new();
+    impact_mesh::import::load_declared_triangle_mesh(&mut registry, declaration)?;
+
+    if !registry.contains(declaration.id) {
+        bail!("Missing loaded triangle mesh {}", declaration.id);
+    }
+
+    let lod_mesh_ids = impact_mesh::lod::lod_mesh_ids(&registry, declaration.id);
+
+    let processed: Vec<_> = iter::once(declaration.id)
+        .chain(lod_mesh_ids)
+        .filter_map(|mesh_id| registry.remove(mesh_id))
+        .map(|mut mesh| {
+            if mesh.has_normal_vectors()
+                && mesh.has_texture_coords()
+                && !mesh.has_tangent_space_quaternions()
+            {
+                mesh.generate_smooth_tangent_space_quaternions(&mut TriangleMeshDirtyMask::empty());
+            }
+
+            ProcessedTriangleMesh {
+                positions: bytemuck::cast_slice(mesh.positions()).to_vec(),
+                normal_vectors: bytemuck::cast_slice(mesh.normal_vectors()).to_vec(),
+                texture_coords: bytemuck::cast_slice(mesh.texture_coords()).to_vec(),
+                tangent_space_quaternions: bytemuck::cast_slice(mesh.tangent_space_quaternions())
+                    .to_vec(),
+                colors: bytemuck::cast_slice(mesh.colors()).to_vec(),
+                skin_weights: bytemuck::cast_slice(mesh.skin_weights()).to_vec(),
+                indices: bytemuck::cast_slice(mesh.indices()).to_vec(),
+            }
+        })
+        .collect();
+
+    Ok(postcard::to_allocvec(&processed)?)
+}
+
+/// Decodes the full-detail mesh and any simplified levels of detail stored in
+/// the given object.
+fn decode_triangle_meshes(object: &[u8]) -> Result<Vec<TriangleMesh>> {
+    let processed: Vec<ProcessedTriangleMesh> = postcard::from_bytes(object)?;
+
+    if processed.is_empty() {
+        bail!("Processed triangle mesh object contains no meshes");
+    }
+
+    processed.into_iter().map(decode_triangle_mesh).collect()
+}
+
+fn decode_triangle_mesh(processed: ProcessedTriangleMesh) -> Result<TriangleMesh> {
+    let positions = cast_bytes_to_vec(&processed.positions)?;
+    let normal_vectors = cast_bytes_to_vec(&processed.normal_vectors)?;
+    let texture_coords = cast_bytes_to_vec(&processed.texture_coords)?;
+    let tangent_space_quaternions = cast_bytes_to_vec(&processed.tangent_space_quaternions)?;
+    let colors = cast_bytes_to_vec(&processed.colors)?;
+    let skin_weights: Vec<VertexSkinWeights> = cast_bytes_to_vec(&processed.skin_weights)?;
+    let indices: Vec<u32> = cast_bytes_to_vec(&processed.indices)?;
+
+    let n_vertices = positions.len();
+    for n_values in [
+        normal_vectors.len(),
+        texture_coords.len(),
+        tangent_space_quaternions.len(),
+        colors.len(),
+        skin_weights.len(),
+    ] {
+        if n_values != 0 && n_values != n_vertices {
+            bail!("Inconsistent number of vertex attribute values in processed triangle mesh");
+        }
+    }
+    if indices.iter().any(|&index| index as usize >= n_vertices) {
+        bail!("Out-of-bounds vertex index in processed triangle mesh");
+    }
+
+    let mut mesh = TriangleMesh::new(
+        positions,
+        normal_vectors,
+        texture_coords,
+        tangent_space_quaternions,
+        colors,
+        indices,
+    );
+    mesh.set_skin_weights(skin_weights, &mut TriangleMeshDirtyMask::empty());
+
+    Ok(mesh)
+}
+
+fn cast_bytes_to_vec<T: Pod>(bytes: &[u8]) -> Result<Vec<T>> {
+    if !bytes.len().is_multiple_of(size_of::<T>()) {
+        bail!(
+            "Byte count {} is not a multiple of the value size {}",
+            bytes.len(),
+            size_of::<T>()
+        );
+    }
+    Ok(bytemuck::pod_collect_to_vec(bytes))
+}
+
+fn process_image_texture(
+    declaration: &ImageTextureDeclaration,
+) -> Result<(
+    ProcessedMipChain,
+    Option<TextureArrayUsage>,
+    Option<TextureAtlas>,
+)> {
+    // Block compressed textures are stored as they are
+    if let DeclaredImageTextureSource::Image(path) = &declaration.source
+        && impact_texture::io::is_compressed_texture_path(path)
+    {
+        let (mip_chain, array_usage) = impact_texture::io::read_compressed_texture_from_path(path)?;
+
+        let color_space = declaration.texture_config.color_space;
+        if !mip_chain.compression().supports_color_space(color_space) {
+            bail!(
+                "Unsupported color space {color_space:?} for {:?} compressed texture",
+                mip_chain.compression()
+            );
+        }
+        if !declaration.processing.format_conversions.is_empty()
+            || declaration.processing.compression.is_some()
+        {
+            log::warn!(
+                "Ignoring processing for already compressed texture {}",
+                declaration.id
+            );
+        }
+
+        return Ok((ProcessedMipChain::Compressed(mip_chain), array_usage, None));
+    }
+
+    // Atlases are packed into a single image before processing
+    let (images, array_usage, atlas) = if let Some((image, atlas)) =
+        declaration.source.load_atlas()?
+    {
+        (vec![image], None, Some(atlas))
+    } else {
+        let array_usage = match &declaration.source {
+            DeclaredImageTextureSource::ArrayImages(_) => Some(TextureArrayUsage::Generic),
+            DeclaredImageTextureSource::CubemapImages { .. } => Some(TextureArrayUsage::Cubemap),
+            _ => None,
+        };
+        let images = declaration
+            .source
+            .image_paths()
+            .into_iter()
+            .map(|path| {
+                impact_io::image::load_image_from_path(Global, path).with_context(|| {
+                    format!("Failed to load texture image from {}", path.display())
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        (images, array_usage, None)
+    };
+
+    let mut meta: Option<ImageMetadata> = None;
+    let mut layer_data = Vec::new();
+
+    for image in &images {
+        match &meta {
+            Some(meta) if *meta != image.meta => {
+                bail!(
+                    "Inconsistent metadata for array images: {:?} != {meta:?}",
+                    image.meta
+                );
+            }
+            Some(_) => {}
+            None => {
+                meta = Some(image.meta.clone());
+            }
+        }
+
+        let layer_start = layer_data.len();
+        layer_data.extend_from_slice(&image.data);
+        declaration
+            .processing
+            .execute_in_place(&image.meta, &mut layer_data[layer_start..]);
+    }
+
+    let meta = meta.ok_or_else(|| anyhow!("Got empty list of sources for texture array"))?;
+
+    if meta.pixel_format == PixelFormat::Luma8
+        && declaration.texture_config.color_space != ColorSpace::Linear
+    {
+        bail!(
+            "Unsupported color space {:?} for grayscale image",
+            declaration.texture_config.color_space
+        );
+    }
+
+    // Cubemaps are not mipmapped when loaded directly either
+    let max_level_count = if array_usage == Some(TextureArrayUsage::Cubemap) {
+        Some(1)
+    } else {
+        declaration.texture_config.max_mip_level_count
+    };
+
+    let mip_chain = MipChain::generate(
+        &meta,
+        &layer_data,
+        u32::try_from(images.len())?,
+        declaration.texture_config.color_space,
+        max_level_count,
+    )?;
+
+    let mip_chain = match declaration
+        .processing
+        .compress(&mip_chain, declaration.texture_config.color_space)?
+    {
+        Some(compressed) => {
+            if !meta.width.is_multiple_of(BlockCompression::BLOCK_SIZE)
+                || !meta.height.is_multiple_of(BlockCompression::BLOCK_SIZE)
+            {
+                log::warn!(
+                    "Texture {} has dimensions that are not multiples of {} \
+                     and will be decompressed when loaded",
+                    declaration.id,
+                    BlockCompression::BLOCK_SIZE
+                );
+            }
+            ProcessedMipChain::Compressed(compressed)
+        }
+        None => ProcessedMipChain::Uncompressed(mip_chain),
+    };
+
+    Ok((mip_chain, array_usage, atlas))
+}
+
+impl ProcessedMipChain {
+    fn meta(&self) -> ImageMetadata {
+        match self {
+            Self::Uncompressed(mip_chain) => mip_chain.meta().clone(),
+            Self::Compressed(mip_chain) => mip_chain.decompressed_meta(),
+        }
+    }
+
+    fn n_layers(&self) -> u32 {
+        match self {
+            Self::Uncompressed(mip_chain) => mip_chain.n_layers(),
+            Self::Compressed(mip_chain) => mip_chain.n_layers(),
+        }
+    }
+
+    fn level_count(&self) -> u32 {
+        match self {
+            Self::Uncompressed(mip_chain) => mip_chain.level_count(),
+            Self::Compressed(mip_chain) => mip_chain.level_count(),
+        }
+    }
+
+    fn data(&self) -> &[u8] {
+        match self {
+            Self::Uncompressed(mip_chain) => mip_chain.data(),
+            Self::Compressed(mip_chain) => mip_chain.data(),
+        }
+    }
+
+    fn compression(&self) -> Option<BlockCompression> {
+        match self {
+            Self::Uncompressed(_) => None,
+            Self::Compressed(mip_chain) => Some(mip_chain.compression()),
+        }
+    }
+}
+
+/// Returns the contents of the generator's RON file along with the parsed
+/// generator.
+fn process_voxel_generator(
+    declaration: &VoxelGeneratorDeclaration,
+) -> Result<(Vec<u8>, VoxelGenerator)> {
+    let path = &declaration.path;
+
+    // Make sure the generator is valid before storing it
+    let generator = impact_io::parse_ron_file(path)
+        .with_context(|| format!("Failed to load voxel generator from {}", path.display()))?;
+
+    let object = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+
+    Ok((object, generator))
+}
+
+/// Generates the declared voxel object with the given generator and encodes it
+/// in the voxel object file format.
+fn pregenerate_voxel_object(
+    generator: &VoxelGenerator,
+    declaration: &PregeneratedVoxelObjectDeclaration,
+    voxel_types: &VoxelTypeRegistry,
+) -> Result<Vec<u8>> {
+    if declaration.voxel_extent.is_nan() || declaration.voxel_extent <= 0.0 {
+        bail!(
+            "Invalid voxel extent {} for pregenerated voxel object",
+            declaration.voxel_extent
+        );
+    }
+
+    let voxel_type = voxel_types
+        .voxel_type_for_name(&declaration.voxel_type)
+        .ok_or_else(|| {
+            anyhow!(
+                "Missing voxel type {} for pregenerated voxel object",
+                declaration.voxel_type
+            )
+        })?;
+
+    let generator = generator.create_sdf_voxel_generator(
+        declaration.voxel_extent,
+        declaration.scale_factor,
+        declaration.seed,
+        SameVoxelTypeGenerator::new(voxel_type).into(),
+        voxel_types,
+    )?;
+
+    let object = VoxelObject::generate(VoxelObjectBuffers::new(), &generator);
+
+    let mut data = Vec::new();
+    io::write_voxel_object(&object, &mut data)?;
+    Ok(data)
+}
+
+fn pregenerated_voxel_object_key(
+    generator_id: VoxelGeneratorID,
+    declaration: &PregeneratedVoxelObjectDeclaration,
+) -> PregeneratedVoxelObjectKey {
+    PregeneratedVoxelObjectKey::new(
+        generator_id,
+        declaration.voxel_extent,
+        declaration.scale_factor,
+        declaration.seed,
+        Hash32::from_str(&declaration.voxel_type),
+    )
+}
+
+fn process_vox_model(declaration: &VoxModelDeclaration) -> Result<Vec<u8>> {
+    let path = &declaration.path;
+
+    let object = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+
+    // Make sure the model is valid before storing it
+    VoxModel::from_bytes(&object)
+        .with_context(|| format!("Invalid MagicaVoxel file {}", path.display()))?;
+
+    Ok(object)
+}
+
+fn voxel_type_names(voxel_types: &VoxelTypeRegistry) -> Vec<String> {
+    (0..voxel_types.n_voxel_types())
+        .map(|idx| voxel_types.name(VoxelType::from_idx(idx)).to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resource::gltf::GltfImportOptions;
+    use impact_math::{point::Point3C, vector::Vector3C};
+    use impact_mesh::{
+        lod::TriangleMeshLODDeclaration,
+        setup::{RectangleMesh, SphereMesh, TriangleMeshTemplate},
+        texture_projection::TextureProjectionDeclaration,
+    };
+    use impact_voxel::{
+        object::inertia::VoxelObjectInertialPropertyManager, voxel_types::VoxelTypeConfig,
+    };
+
+    fn rectangle_mesh_declaration(name: &str) -> TriangleMeshDeclaration {
+        TriangleMeshDeclaration {
+            id: TriangleMeshID::from_name(name),
+            source: TriangleMeshSource::Template(TriangleMeshTemplate::Rectangle(RectangleMesh {
+                extent_x: 1.0,
+                extent_z: 2.0,
+            })),
+            texture_projection: Some(TextureProjectionDeclaration::Planar {
+                origin: Point3C::origin(),
+                u_vector: Vector3C::unit_x(),
+                v_vector: Vector3C::unit_z(),
+            }),
+            lods: None,
+        }
+    }
+
+    fn voxel_generator_declaration(name: &str, path: PathBuf) -> VoxelGeneratorDeclaration {
+        VoxelGeneratorDeclaration {
+            id: VoxelGeneratorID::from_name(name),
+            path,
+            pregenerated_objects: Vec::new(),
+        }
+    }
+
+    fn copy_test_voxel_generator(dir: &Path) -> PathBuf {
+        let path = dir.join("generator.vgen.ron");
+        fs::copy(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("benches/data/asteroid.vgen.ron"),
+            &path,
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn opening_missing_database_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(ResourceDatabase::open(dir.path().join("db")).is_err());
+    }
+
+    #[test]
+    fn triangle_mesh_survives_import_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let declaration = rectangle_mesh_declaration("rectangle");
+
+        let mut database = ResourceDatabase::open_or_create(dir.path());
+        let summary = database
+            .import_declarations(
+                &ResourceDeclarations {
+                    triangle_meshes: vec![declaration.clone()],
+                    ..Default::default()
+                },
+                &VoxelTypeRegistry::empty(),
+            )
+            .unwrap();
+        assert_eq!(summary.n_processed, 1);
+        assert_eq!(summary.n_failed, 0);
+
+        let mut original_registry = TriangleMeshRegistry::new();
+        impact_mesh::import::load_declared_triangle_mesh(&mut original_registry, &declaration)
+            .unwrap();
+        let original = original_registry.get(declaration.id).unwrap();
+
+        let database = ResourceDatabase::open(dir.path()).unwrap();
+        let mut registry = TriangleMeshRegistry::new();
+        database.load_triangle_meshes(&mut registry).unwrap();
+        let loaded = registry.get(declaration.id).unwrap();
+
+        assert_eq!(loaded.positions(), original.positions());
+        assert_eq!(loaded.normal_vectors(), original.normal_vectors());
+        assert_eq!(loaded.texture_coords(), original.texture_coords());
+        assert_eq!(loaded.indices(), original.indices());
+        assert!(loaded.has_texture_coords());
+        assert!(loaded.has_tangent_space_quaternions());
+    }
+
+    #[test]
+    fn triangle_mesh_lods_survive_import_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let declaration = TriangleMeshDeclaration {
+            id: TriangleMeshID::from_name("lod sphere"),
+            source: TriangleMeshSource::Template(TriangleMeshTemplate::Sphere(SphereMesh {
+                n_rings: 16,
+            })),
+            texture_projection: None,
+            lods: Some(TriangleMeshLODDeclaration::default()),
+        };
+
+        let mut database = ResourceDatabase::open_or_create(dir.path());
+        database
+            .import_declarations(
+                &ResourceDeclarations {
+                    triangle_meshes: vec![declaration.clone()],
+                    ..Default::default()
+                },
+                &VoxelTypeRegistry::empty(),
+            )
+            .unwrap();
+
+        let mut original_registry = TriangleMeshRegistry::new();
+        impact_mesh::import::load_declared_triangle_mesh(&mut original_registry, &declaration)
+            .unwrap();
+        let original_lod_ids = impact_mesh::lod::lod_mesh_ids(&original_registry, declaration.id);
+        assert!(!original_lod_ids.is_empty());
+
+        let database = ResourceDatabase::open(dir.path()).unwrap();
+        let mut registry = TriangleMeshRegistry::new();
+        database.load_triangle_meshes(&mut registry).unwrap();
+
+        let lod_ids = impact_mesh::lod::lod_mesh_ids(&registry, declaration.id);
+        assert_eq!(lod_ids, original_lod_ids);
+
+        for id in lod_ids {
+            assert_eq!(
+                registry.get(id).unwrap().indices(),
+                original_registry.get(id).unwrap().indices()
+            );
+        }
+    }
+
+    #[test]
+    fn unchanged_resources_are_not_processed_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let generator_path = copy_test_voxel_generator(dir.path());
+
+        let declarations = ResourceDeclarations {
+            triangle_meshes: vec![rectangle_mesh_declaration("rectangle")],
+            voxel_generators: vec![voxel_generator_declaration("asteroid", generator_path)],
+            ..Default::default()
+        };
+
+        let db_path = dir.path().join("db");
+        ResourceDatabase::open_or_create(&db_path)
+            .import_declarations(&declarations, &VoxelTypeRegistry::empty())
+            .unwrap();
+
+        let summary = ResourceDatabase::open_or_create(&db_path)
+            .import_declarations(&declarations, &VoxelTypeRegistry::empty())
+            .unwrap();
+
+        assert_eq!(summary.n_processed, 0);
+        assert_eq!(summary.n_up_to_date, 2);
+    }
+
+    #[test]
+    fn changed_source_file_invalidates_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let generator_path = copy_test_voxel_generator(dir.path());
+
+        let declarations = ResourceDeclarations {
+            triangle_meshes: vec![rectangle_mesh_declaration("rectangle")],
+            voxel_generators: vec![voxel_generator_declaration(
+                "asteroid",
+                generator_path.clone(),
+            )],
+            ..Default::default()
+        };
+
+        let db_path = dir.path().join("db");
+        let mut database = ResourceDatabase::open_or_create(&db_path);
+        database
+            .import_declarations(&declarations, &VoxelTypeRegistry::empty())
+            .unwrap();
+        let original_object_hash = database.index.voxel_generators[0].resource.object_hash;
+
+        let mut text = fs::read_to_string(&generator_path).unwrap();
+        text.push_str("\n// Modified\n");
+        fs::write(&generator_path, text).unwrap();
+
+        let summary = database
+            .import_declarations(&declarations, &VoxelTypeRegistry::empty())
+            .unwrap();
+
+        assert_eq!(summary.n_processed, 1);
+        assert_eq!(summary.n_up_to_date, 1);
+        assert_eq!(summary.n_pruned_objects, 1);
+        assert_ne!(
+            database.index.voxel_generators[0].resource.object_hash,
+            original_object_hash
+        );
+
+        let mut registry = VoxelGeneratorRegistry::new();
+        database.load_voxel_generators(&mut registry).unwrap();
+        assert!(registry.contains(VoxelGeneratorID::from_name("asteroid")));
+    }
+
+    #[test]
+    fn removed_declarations_are_dropped_from_database() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut database = ResourceDatabase::open_or_create(dir.path());
+        database
+            .import_declarations(
+                &ResourceDeclarations {
+                    triangle_meshes: vec![rectangle_mesh_declaration("rectangle")],
+                    ..Default::default()
+                },
+                &VoxelTypeRegistry::empty(),
+            )
+            .unwrap();
+
+        let summary = database
+            .import_declarations(
+                &ResourceDeclarations::default(),
+                &VoxelTypeRegistry::empty(),
+            )
+            .unwrap();
+        assert_eq!(summary.n_pruned_objects, 1);
+
+        let mut registry = TriangleMeshRegistry::new();
+        ResourceDatabase::open(dir.path())
+            .unwrap()
+            .load_triangle_meshes(&mut registry)
+            .unwrap();
+        assert!(!registry.contains(TriangleMeshID::from_name("rectangle")));
+    }
+
+    #[test]
+    fn corrupted_object_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut database = ResourceDatabase::open_or_create(dir.path());
+        database
+            .import_declarations(
+                &ResourceDeclarations {
+                    triangle_meshes: vec![rectangle_mesh_declaration("rectangle")],
+                    ..Default::default()
+                },
+                &VoxelTypeRegistry::empty(),
+            )
+            .unwrap();
+
+        let object_path = database.object_path(database.index.triangle_meshes[0].object_hash);
+        let mut data = fs::read(&object_path).unwrap();
+        data[0] ^= 0xff;
+        fs::write(&object_path, data).unwrap();
+
+        assert!(
+            database
+                .read_object(database.index.triangle_meshes[0].object_hash)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn failing_resource_is_left_out() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut database = ResourceDatabase::open_or_create(dir.path());
+        let summary = database
+            .import_declarations(
+                &ResourceDeclarations {
+                    voxel_generators: vec![voxel_generator_declaration(
+                        "missing",
+                        dir.path().join("missing.vgen.ron"),
+                    )],
+                    ..Default::default()
+                },
+                &VoxelTypeRegistry::empty(),
+            )
+            .unwrap();
+
+        assert_eq!(summary.n_failed, 1);
+        assert!(database.index.voxel_generators.is_empty());
+    }
+
+    fn default_voxel_types() -> VoxelTypeRegistry {
+        VoxelTypeRegistry::from_config(
+            &mut TextureRegistry::new(),
+            &mut SamplerRegistry::new(),
+            VoxelTypeConfig::default(),
+        )
+        .unwrap()
+    }
+
+    fn pregenerated_asteroid_declaration(generator_path: PathBuf) -> VoxelGeneratorDeclaration {
+        VoxelGeneratorDeclaration {
+            pregenerated_objects: vec![PregeneratedVoxelObjectDeclaration {
+                voxel_extent: 1.0,
+                scale_factor: 0.1,
+                seed: 0,
+                voxel_type: "Default".to_string(),
+            }],
+            ..voxel_generator_declaration("asteroid", generator_path)
+        }
+    }
+
+    #[test]
+    fn pregenerated_voxel_object_survives_import_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let generator_path = copy_test_voxel_generator(dir.path());
+        let declaration = pregenerated_asteroid_declaration(generator_path);
+        let voxel_types = default_voxel_types();
+
+        let db_path = dir.path().join("db");
+        let mut database = ResourceDatabase::open_or_create(&db_path);
+        let summary = database
+            .import_declarations(
+                &ResourceDeclarations {
+                    voxel_generators: vec![declaration.clone()],
+                    ..Default::default()
+                },
+                &voxel_types,
+            )
+            .unwrap();
+        assert_eq!(summary.n_failed, 0);
+
+        let mut generators = VoxelGeneratorRegistry::new();
+        impact_voxel::generation::import::load_declared_voxel_generator(
+            &mut generators,
+            &declaration,
+        )
+        .unwrap();
+        let pregenerated = &declaration.pregenerated_objects[0];
+        let original = VoxelObject::generate(
+            VoxelObjectBuffers::new(),
+            &generators
+                .get(declaration.id)
+                .unwrap()
+                .create_sdf_voxel_generator(
+                    pregenerated.voxel_extent,
+                    pregenerated.scale_factor,
+                    pregenerated.seed,
+                    SameVoxelTypeGenerator::new(
+                        voxel_types.voxel_type_for_name("Default").unwrap(),
+                    )
+                    .into(),
+                    &voxel_types,
+                )
+                .unwrap(),
+        );
+
+        let database = ResourceDatabase::open(&db_path).unwrap();
+        let mut registry = PregeneratedVoxelObjectRegistry::new();
+        database
+            .load_pregenerated_voxel_objects(&mut registry, &voxel_types)
+            .unwrap();
+
+        let encoded = registry
+            .get(&pregenerated_voxel_object_key(declaration.id, pregenerated))
+            .unwrap();
+        let loaded = io::read_voxel_object(VoxelObjectBuffers::new(), &mut &encoded[..]).unwrap();
+
+        assert_eq!(loaded.chunk_counts(), original.chunk_counts());
+        assert_eq!(
+            VoxelObjectInertialPropertyManager::initialized_from(
+                &loaded,
+                voxel_types.mass_densities()
+            ),
+            VoxelObjectInertialPropertyManager::initialized_from(
+                &original,
+                voxel_types.mass_densities()
+            )
+        );
+    }
+
+    #[test]
+    fn pregenerated_voxel_objects_are_rejected_for_different_voxel_types() {
+        let dir = tempfile::tempdir().unwrap();
+        let generator_path = copy_test_voxel_generator(dir.path());
+
+        let mut database = ResourceDatabase::open_or_create(dir.path().join("db"));
+        database
+            .import_declarations(
+                &ResourceDeclarations {
+                    voxel_generators: vec![pregenerated_asteroid_declaration(generator_path)],
+                    ..Default::default()
+                },
+                &default_voxel_types(),
+            )
+            .unwrap();
+
+        let mut registry = PregeneratedVoxelObjectRegistry::new();
+        assert!(
+            database
+                .load_pregenerated_voxel_objects(&mut registry, &VoxelTypeRegistry::empty())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn gltf_scene_declarations_are_skipped_without_failing_other_resources() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut database = ResourceDatabase::open_or_create(dir.path());
+        let summary = database
+            .import_declarations(
+                &ResourceDeclarations {
+                    triangle_meshes: vec![rectangle_mesh_declaration("rectangle")],
+                    gltf_scenes: vec![GltfSceneDeclaration {
+                        name: "scene".to_string(),
+                        file_path: dir.path().join("scene.gltf"),
+                        options: GltfImportOptions::default(),
+                    }],
+                    ..Default::default()
+                },
+                &VoxelTypeRegistry::empty(),
+            )
+            .unwrap();
+
+        assert_eq!(summary.n_processed, 1);
+        assert_eq!(summary.n_skipped, 1);
+        assert_eq!(summary.n_failed, 0);
+        assert_eq!(database.index.triangle_meshes.len(), 1);
+        assert_eq!(database.index.gltf_scenes.len(), 1);
+
+        // The declarations are kept in the index on disk
+        let database = ResourceDatabase::open(dir.path()).unwrap();
+        assert_eq!(database.index.gltf_scenes[0].name, "scene");
+    }
+
+    #[test]
+    fn invalid_vox_model_is_left_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("model.vox");
+        fs::write(&path, b"not a vox file").unwrap();
+
+        let mut database = ResourceDatabase::open_or_create(dir.path().join("db"));
+        let summary = database
+            .import_declarations(
+                &ResourceDeclarations {
+                    vox_models: vec![VoxModelDeclaration {
+                        id: VoxModelID::from_name("model"),
+                        path,
+                    }],
+                    ..Default::default()
+                },
+                &VoxelTypeRegistry::empty(),
+            )
+            .unwrap();
+
+        assert_eq!(summary.n_failed, 1);
+        assert!(database.index.vox_models.is_empty());
+    }
+
+    #[test]
+    fn missing_object_fails_loading() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut database = ResourceDatabase::open_or_create(dir.path());
+        database
+            .import_declarations(
+                &ResourceDeclarations {
+                    triangle_meshes: vec![rectangle_mesh_declaration("rectangle")],
+                    ..Default::default()
+                },
+                &VoxelTypeRegistry::empty(),
+            )
+            .unwrap();
+
+        fs::remove_file(database.object_path(database.index.triangle_meshes[0].object_hash))
+            .unwrap();
+
+        let mut registry = TriangleMeshRegistry::new();
+        assert!(database.load_triangle_meshes(&mut registry).is_err());
+    }
+}
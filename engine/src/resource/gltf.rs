@@ -0,0 +1,597 @@
+//! Importing meshes, materials and scene hierarchies from glTF 2.0 files.
+
+// The conversion helpers are only used for reading when the `gltf` feature is
+// enabled, but they are always compiled so that they can be tested
+#![cfg_attr(not(feature = "gltf"), allow(dead_code))]
+
+#[cfg(feature = "gltf")]
+mod reading;
+
+use crate::resource::ResourceManager;
+use anyhow::Result;
+use impact_animation::{AnimationClipID, SkeletonID};
+use impact_geometry::{ModelTransform, ReferenceFrame};
+use impact_material::{
+    MaterialID,
+    setup::physical::{
+        BumpMap, Color, EmissiveLuminance, Metalness, NormalMap, PhysicalMaterialProperties,
+        Roughness, SpecularReflectance, TexturedColor, TexturedEmissiveLuminance,
+        TexturedMetalness, TexturedRoughness, TexturedSpecularReflectance, UniformColor,
+        UniformEmissiveLuminance, UniformMetalness, UniformRoughness, UniformSpecularReflectance,
+    },
+};
+use impact_math::{
+    hash64,
+    point::Point3C,
+    quaternion::{Quaternion, UnitQuaternion},
+    stringhash64_newtype,
+    vector::{Vector3, Vector3C},
+};
+use impact_mesh::TriangleMeshID;
+use impact_resource::{Resource, ResourceID, registry::ImmutableResourceRegistry};
+use impact_texture::TextureID;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub type ImportedSceneRegistry = ImmutableResourceRegistry<ImportedScene>;
+
+stringhash64_newtype!(
+    /// Identifier for an [`ImportedScene`].
+    [pub] ImportedSceneID
+);
+
+/// Declaration of a glTF 2.0 file (`.gltf` or `.glb`) whose meshes, materials
+/// and node hierarchy should be imported.
+///
+/// Each mesh primitive in the file is imported as a triangle mesh with ID
+/// `<name>/mesh<i>/primitive<j>`, and each material as a physical material
+/// with ID `<name>/material<k>`, where the indices are those in the file.
+/// Each skin is imported as a skeleton with ID `<name>/skin<s>`, and each
+/// animation of its joints as an animation clip with ID
+/// `<name>/skin<s>/animation<a>`. The
+/// node hierarchy of the selected glTF scene is stored as an
+/// [`ImportedScene`] with ID [`ImportedSceneID::from_name(name)`], which can
+/// be spawned as entities.
+///
+/// [`ImportedSceneID::from_name(name)`]: ImportedSceneID::from_name
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GltfSceneDeclaration {
+    /// The name that the IDs of the imported resources are derived from.
+    pub name: String,
+    /// The path to the glTF file.
+    pub file_path: PathBuf,
+    /// Options for how to import the file.
+    #[serde(default)]
+    pub options: GltfImportOptions,
+}
+
+/// Options for importing a glTF 2.0 file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GltfImportOptions {
+    /// The index of the glTF scene whose node hierarchy should be imported. If
+    /// [`None`], the default scene of the file is used, or the first scene if
+    /// the file specifies no default.
+    pub scene_index: Option<usize>,
+    /// The emissive luminance (in cd/m²) corresponding to a glTF emissive
+    /// factor of unity. glTF emissive colors are relative, so this sets their
+    /// absolute brightness.
+    pub emissive_luminance_scale: f32,
+}
+
+/// A hierarchy of nodes with attached models, imported from a file, that can
+/// be spawned as entities.
+#[derive(Clone, Debug, Default)]
+pub struct ImportedScene {
+    nodes: Vec<ImportedSceneNode>,
+}
+
+/// A node in an [`ImportedScene`].
+#[derive(Clone, Debug)]
+pub struct ImportedSceneNode {
+    /// The index of the node's parent node, or [`None`] if the node is a root
+    /// node. Parents always come before their children.
+    pub parent: Option<usize>,
+    /// The reference frame of the node in the space of its parent.
+    pub frame: ReferenceFrame,
+    /// The models attached to the node.
+    pub models: Vec<ImportedModel>,
+}
+
+/// A model attached to a node in an [`ImportedScene`].
+#[derive(Clone, Copy, Debug)]
+pub struct ImportedModel {
+    pub mesh_id: TriangleMeshID,
+    pub material_id: MaterialID,
+    /// The transform from the space of the model to the space of its node.
+    pub model_transform: ModelTransform,
+    /// The skeleton deforming the model's mesh, if it is skinned.
+    pub skeleton_id: Option<SkeletonID>,
+    /// The animation clip to play on the skeleton when the model is spawned,
+    /// if any.
+    pub animation_clip_id: Option<AnimationClipID>,
+}
+
+/// Physical material parameters for a glTF material, with any textures
+/// already imported.
+#[derive(Clone, Debug)]
+struct GltfMaterialParameters {
+    base_color: [f32; 3],
+    color_texture_id: Option<TextureID>,
+    roughness: f32,
+    roughness_texture_id: Option<TextureID>,
+    metalness: f32,
+    metalness_texture_id: Option<TextureID>,
+    specular_reflectance_texture_id: Option<TextureID>,
+    normal_map_texture_id: Option<TextureID>,
+    emissive_luminance: f32,
+    emissive_luminance_texture_id: Option<TextureID>,
+}
+
+/// The specular reflectance at normal incidence that glTF assumes for
+/// dielectric materials.
+const DIELECTRIC_SPECULAR_REFLECTANCE: f32 = 0.04;
+
+impl ImportedSceneID {
+    /// Creates an imported scene ID hashed from the given name.
+    pub fn from_name(name: &str) -> Self {
+        Self(hash64!(name))
+    }
+}
+
+impl ResourceID for ImportedSceneID {}
+
+impl Default for GltfImportOptions {
+    fn default() -> Self {
+        Self {
+            scene_index: None,
+            emissive_luminance_scale: 1.0,
+        }
+    }
+}
+
+impl GltfSceneDeclaration {
+    /// Resolves all paths in the declaration by prepending the given root path
+    /// to all paths.
+    pub fn resolve_paths(&mut self, root_path: &Path) {
+        self.file_path = root_path.join(&self.file_path);
+    }
+}
+
+impl ImportedScene {
+    /// Creates a new scene with no nodes.
+    pub fn new() -> Self {
+        Self { nodes: Vec::new() }
+    }
+
+    /// Returns the nodes in the scene. Parents always come before their
+    /// children.
+    pub fn nodes(&self) -> &[ImportedSceneNode] {
+        &self.nodes
+    }
+
+    /// Adds a node with the given parent, reference frame and models to the
+    /// scene and returns its index.
+    ///
+    /// # Panics
+    /// If the parent index does not refer to an existing node.
+    pub fn add_node(
+        &mut self,
+        parent: Option<usize>,
+        frame: ReferenceFrame,
+        models: Vec<ImportedModel>,
+    ) -> usize {
+        if let Some(parent) = parent {
+            assert!(
+                parent < self.nodes.len(),
+                "Parent node {parent} does not exist in imported scene"
+            );
+        }
+        let idx = self.nodes.len();
+        self.nodes.push(ImportedSceneNode {
+            parent,
+            frame,
+            models,
+        });
+        idx
+    }
+}
+
+impl Resource for ImportedScene {
+    type ID = ImportedSceneID;
+}
+
+impl GltfMaterialParameters {
+    fn new(base_color: [f32; 3], roughness: f32, metalness: f32, emissive_luminance: f32) -> Self {
+        Self {
+            base_color,
+            color_texture_id: None,
+            roughness,
+            roughness_texture_id: None,
+            metalness,
+            metalness_texture_id: None,
+            specular_reflectance_texture_id: None,
+            normal_map_texture_id: None,
+            emissive_luminance,
+            emissive_luminance_texture_id: None,
+        }
+    }
+
+    /// Maps the glTF metallic-roughness parameters onto our physical material
+    /// model. Any scale factors for the textured color and specular
+    /// reflectance are assumed to have been baked into the textures.
+    ///
+    /// glTF has a fixed specular reflectance for dielectrics and uses the base
+    /// color as the specular reflectance for metals. We get the same result
+    /// at both extremes of metalness by interpolating the scalar specular
+    /// reflectance from the dielectric value to unity with metalness.
+    fn into_physical_material_properties(self) -> PhysicalMaterialProperties {
+        let color = match self.color_texture_id {
+            Some(texture_id) => Color::Textured(TexturedColor(texture_id)),
+            None => Color::Uniform(UniformColor(Vector3C::from(self.base_color))),
+        };
+
+        let roughness = match self.roughness_texture_id {
+            Some(texture_id) => Roughness::Textured(TexturedRoughness {
+                texture_id,
+                scale_factor: f64::from(self.roughness),
+            }),
+            None => Roughness::Uniform(UniformRoughness(self.roughness)),
+        };
+
+        let metalness = match self.metalness_texture_id {
+            Some(texture_id) => Metalness::Textured(TexturedMetalness {
+                texture_id,
+                scale_factor: f64::from(self.metalness),
+            }),
+            None => Metalness::Uniform(UniformMetalness(self.metalness)),
+        };
+
+        let specular_reflectance = match self.specular_reflectance_texture_id {
+            Some(texture_id) => {
+                SpecularReflectance::Textured(TexturedSpecularReflectance::unscaled(texture_id))
+            }
+            None => SpecularReflectance::Uniform(UniformSpecularReflectance(
+                specular_reflectance_for_metalness(self.metalness),
+            )),
+        };
+
+        let emissive_luminance = match self.emissive_luminance_texture_id {
+            Some(texture_id) => EmissiveLuminance::Textured(TexturedEmissiveLuminance {
+                texture_id,
+                scale_factor: f64::from(self.emissive_luminance),
+            }),
+            None => EmissiveLuminance::Uniform(UniformEmissiveLuminance(self.emissive_luminance)),
+        };
+
+        let bump_map = self
+            .normal_map_texture_id
+            .map(|texture_id| BumpMap::Normal(NormalMap(texture_id)));
+
+        PhysicalMaterialProperties {
+            color,
+            specular_reflectance,
+            roughness,
+            metalness,
+            emissive_luminance,
+            bump_map,
+            atlas_region: None,
+        }
+    }
+}
+
+/// Loads the meshes, materials and scene hierarchies of all glTF files in the
+/// given declarations and stores them in the resource registries.
+///
+/// # Errors
+/// See [`load_declared_gltf_scene`].
+pub fn load_declared_gltf_scenes(
+    resource_manager: &mut ResourceManager,
+    gltf_scene_declarations: &[GltfSceneDeclaration],
+) -> Result<()> {
+    for declaration in gltf_scene_declarations {
+        if let Err(error) = load_declared_gltf_scene(resource_manager, declaration) {
+            // Failing to load a scene is not fatal, since we might not need it
+            log::error!("Failed to load glTF scene {}: {error:#}", declaration.name);
+        }
+    }
+    Ok(())
+}
+
+/// Loads the meshes, materials and scene hierarchy of the glTF file in the
+/// given declaration and stores them in the resource registries.
+///
+/// # Errors
+/// Returns an error if:
+/// - The `gltf` feature is not enabled.
+/// - Another scene with the same name is already loaded.
+/// - The file can not be found or parsed.
+/// - A mesh, texture or material in the file can not be imported.
+#[cfg_attr(not(feature = "gltf"), allow(unused_variables))]
+pub fn load_declared_gltf_scene(
+    resource_manager: &mut ResourceManager,
+    declaration: &GltfSceneDeclaration,
+) -> Result<ImportedSceneID> {
+    #[cfg(feature = "gltf")]
+    return reading::import_gltf_scene(resource_manager, declaration);
+
+    #[cfg(not(feature = "gltf"))]
+    anyhow::bail!("Please enable the `gltf` feature in order to import glTF files");
+}
+
+/// Computes the reference frame of a node in the space of its parent, along
+/// with the node's accumulated scale, from the node's translation, rotation
+/// (as `[x, y, z, w]`) and scale relative to its parent and the accumulated
+/// scale of the parent.
+///
+/// Reference frames can not represent scaling, so scales are instead
+/// accumulated down the hierarchy and applied to the translations of child
+/// nodes and to the model transforms. Non-uniform scales are approximated by
+/// their mean.
+fn compute_node_frame_and_scale(
+    translation: [f32; 3],
+    rotation: [f32; 4],
+    scale: [f32; 3],
+    parent_scale: f32,
+) -> (ReferenceFrame, f32) {
+    let [qx, qy, qz, qw] = rotation;
+    let orientation =
+        UnitQuaternion::normalized_from(Quaternion::from_parts(Vector3::new(qx, qy, qz), qw));
+
+    let position = Point3C::from(translation) * parent_scale;
+
+    let uniform_scale = (scale[0] + scale[1] + scale[2]) / 3.0;
+
+    (
+        ReferenceFrame::new(position, orientation.compact()),
+        parent_scale * uniform_scale,
+    )
+}
+
+/// Computes the scalar specular reflectance that gives glTF's specular
+/// reflectance at normal incidence at both zero and full metalness.
+fn specular_reflectance_for_metalness(metalness: f32) -> f32 {
+    DIELECTRIC_SPECULAR_REFLECTANCE + (1.0 - DIELECTRIC_SPECULAR_REFLECTANCE) * metalness
+}
+
+fn srgb_to_linear(srgb_value: f32) -> f32 {
+    if srgb_value <= 0.04045 {
+        srgb_value / 12.92
+    } else {
+        ((srgb_value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(linear_value: f32) -> f32 {
+    if linear_value <= 0.0031308 {
+        linear_value * 12.92
+    } else {
+        linear_value.powf(1.0 / 2.4) * 1.055 - 0.055
+    }
+}
+
+fn byte_to_float(byte: u8) -> f32 {
+    f32::from(byte) / 255.0
+}
+
+fn float_to_byte(float: f32) -> u8 {
+    (float.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Multiplies the RGB components of the given sRGB-encoded RGBA8 texels with
+/// the given linear factors.
+fn scale_srgb_texel_colors(rgba_texels: &mut [u8], factors: [f32; 3]) {
+    for texel in rgba_texels.chunks_exact_mut(4) {
+        for (value, factor) in texel[..3].iter_mut().zip(factors) {
+            let linear_value = srgb_to_linear(byte_to_float(*value)) * factor;
+            *value = float_to_byte(linear_to_srgb(linear_value));
+        }
+    }
+}
+
+/// Extracts the given channel from the given RGBA8 texels.
+fn extract_texel_channel(rgba_texels: &[u8], channel: usize) -> Vec<u8> {
+    rgba_texels
+        .chunks_exact(4)
+        .map(|texel| texel[channel])
+        .collect()
+}
+
+/// Computes the specular reflectance for each of the given metalness texels
+/// (see [`specular_reflectance_for_metalness`]), after scaling the metalness
+/// by the given factor.
+fn compute_specular_reflectance_texels(metalness_texels: &[u8], metalness_scale: f32) -> Vec<u8> {
+    metalness_texels
+        .iter()
+        .map(|&metalness| {
+            float_to_byte(specular_reflectance_for_metalness(
+                byte_to_float(metalness) * metalness_scale,
+            ))
+        })
+        .collect()
+}
+
+/// Computes a linear monochromatic emissive luminance for each of the given
+/// sRGB-encoded RGBA8 emissive color texels, taken as the largest linear
+/// component.
+fn compute_emissive_luminance_texels(rgba_texels: &[u8]) -> Vec<u8> {
+    rgba_texels
+        .chunks_exact(4)
+        .map(|texel| {
+            let max_value = texel[..3]
+                .iter()
+                .map(|&value| srgb_to_linear(byte_to_float(value)))
+                .fold(0.0, f32::max);
+            float_to_byte(max_value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn node_frame_applies_parent_scale_to_translation() {
+        let (frame, scale) =
+            compute_node_frame_and_scale([1.0, 2.0, 3.0], [0.0, 0.0, 0.0, 1.0], [3.0; 3], 2.0);
+        assert_abs_diff_eq!(frame.position, Point3C::new(2.0, 4.0, 6.0));
+        assert_abs_diff_eq!(scale, 6.0);
+    }
+
+    #[test]
+    fn node_frame_orientation_matches_rotation() {
+        let half_angle = std::f32::consts::FRAC_PI_4;
+        let (frame, _) = compute_node_frame_and_scale(
+            [0.0; 3],
+            [0.0, 0.0, half_angle.sin(), half_angle.cos()],
+            [1.0; 3],
+            1.0,
+        );
+        let rotated = frame
+            .orientation
+            .aligned()
+            .rotate_vector(&Vector3::unit_x());
+        assert_abs_diff_eq!(rotated, Vector3::unit_y(), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn non_uniform_node_scale_is_averaged() {
+        let (_, scale) =
+            compute_node_frame_and_scale([0.0; 3], [0.0, 0.0, 0.0, 1.0], [1.0, 2.0, 3.0], 1.0);
+        assert_abs_diff_eq!(scale, 2.0);
+    }
+
+    #[test]
+    fn specular_reflectance_matches_gltf_at_metalness_extremes() {
+        assert_abs_diff_eq!(
+            specular_reflectance_for_metalness(0.0),
+            DIELECTRIC_SPECULAR_REFLECTANCE
+        );
+        assert_abs_diff_eq!(specular_reflectance_for_metalness(1.0), 1.0);
+    }
+
+    #[test]
+    fn untextured_material_maps_to_uniform_properties() {
+        let properties = GltfMaterialParameters::new([0.5, 0.25, 1.0], 0.3, 0.0, 2.0)
+            .into_physical_material_properties();
+
+        assert!(matches!(
+            properties.color,
+            Color::Uniform(UniformColor(color)) if color == Vector3C::new(0.5, 0.25, 1.0)
+        ));
+        assert!(matches!(
+            properties.roughness,
+            Roughness::Uniform(UniformRoughness(roughness)) if roughness == 0.3
+        ));
+        assert!(matches!(
+            properties.metalness,
+            Metalness::Uniform(UniformMetalness(metalness)) if metalness == 0.0
+        ));
+        assert!(matches!(
+            properties.specular_reflectance,
+            SpecularReflectance::Uniform(UniformSpecularReflectance(reflectance))
+                if (reflectance - DIELECTRIC_SPECULAR_REFLECTANCE).abs() < 1e-6
+        ));
+        assert!(matches!(
+            properties.emissive_luminance,
+            EmissiveLuminance::Uniform(UniformEmissiveLuminance(luminance)) if luminance == 2.0
+        ));
+        assert!(properties.bump_map.is_none());
+    }
+
+    #[test]
+    fn textured_material_maps_factors_to_texture_scales() {
+        let color_texture_id = TextureID::from_name("color");
+        let roughness_texture_id = TextureID::from_name("roughness");
+        let metalness_texture_id = TextureID::from_name("metalness");
+        let specular_texture_id = TextureID::from_name("specular");
+        let normal_texture_id = TextureID::from_name("normal");
+        let emissive_texture_id = TextureID::from_name("emissive");
+
+        let mut parameters = GltfMaterialParameters::new([1.0; 3], 0.5, 0.8, 10.0);
+        parameters.color_texture_id = Some(color_texture_id);
+        parameters.roughness_texture_id = Some(roughness_texture_id);
+        parameters.metalness_texture_id = Some(metalness_texture_id);
+        parameters.specular_reflectance_texture_id = Some(specular_texture_id);
+        parameters.normal_map_texture_id = Some(normal_texture_id);
+        parameters.emissive_luminance_texture_id = Some(emissive_texture_id);
+
+        let properties = parameters.into_physical_material_properties();
+
+        assert!(matches!(
+            properties.color,
+            Color::Textured(TexturedColor(id)) if id == color_texture_id
+        ));
+        assert!(matches!(
+            properties.roughness,
+            Roughness::Textured(TexturedRoughness { texture_id, scale_factor })
+                if texture_id == roughness_texture_id && scale_factor == 0.5
+        ));
+        assert!(matches!(
+            properties.metalness,
+            Metalness::Textured(TexturedMetalness { texture_id, scale_factor })
+                if texture_id == metalness_texture_id && (scale_factor - 0.8).abs() < 1e-6
+        ));
+        assert!(matches!(
+            properties.specular_reflectance,
+            SpecularReflectance::Textured(TexturedSpecularReflectance { texture_id, scale_factor })
+                if texture_id == specular_texture_id && scale_factor == 1.0
+        ));
+        assert!(matches!(
+            properties.emissive_luminance,
+            EmissiveLuminance::Textured(TexturedEmissiveLuminance { texture_id, scale_factor })
+                if texture_id == emissive_texture_id && scale_factor == 10.0
+        ));
+        assert!(matches!(
+            properties.bump_map,
+            Some(BumpMap::Normal(NormalMap(id))) if id == normal_texture_id
+        ));
+    }
+
+    #[test]
+    fn scaling_srgb_texels_by_unity_preserves_them() {
+        let mut texels = vec![0, 64, 128, 7, 200, 255, 13, 255];
+        let original = texels.clone();
+        scale_srgb_texel_colors(&mut texels, [1.0; 3]);
+        assert_eq!(texels, original);
+    }
+
+    #[test]
+    fn scaling_srgb_texels_scales_linear_values_and_keeps_alpha() {
+        let mut texels = vec![255, 255, 255, 100];
+        scale_srgb_texel_colors(&mut texels, [0.0, 1.0, 0.5]);
+        assert_eq!(texels[0], 0);
+        assert_eq!(texels[1], 255);
+        assert_eq!(texels[2], float_to_byte(linear_to_srgb(0.5)));
+        assert_eq!(texels[3], 100);
+    }
+
+    #[test]
+    fn extracting_texel_channel_works() {
+        let texels = [1, 2, 3, 4, 5, 6, 7, 8];
+        assert_eq!(extract_texel_channel(&texels, 1), vec![2, 6]);
+        assert_eq!(extract_texel_channel(&texels, 2), vec![3, 7]);
+    }
+
+    #[test]
+    fn specular_reflectance_texels_follow_scaled_metalness() {
+        let texels = compute_specular_reflectance_texels(&[0, 255, 255], 0.5);
+        assert_eq!(
+            texels,
+            vec![
+                float_to_byte(DIELECTRIC_SPECULAR_REFLECTANCE),
+                float_to_byte(specular_reflectance_for_metalness(0.5)),
+                float_to_byte(specular_reflectance_for_metalness(0.5)),
+            ]
+        );
+    }
+
+    #[test]
+    fn emissive_luminance_texels_use_max_linear_component() {
+        let texels = compute_emissive_luminance_texels(&[0, 255, 128, 0, 10, 20, 0, 255]);
+        assert_eq!(texels[0], 255);
+        assert_eq!(texels[1], float_to_byte(srgb_to_linear(byte_to_float(20))));
+    }
+}
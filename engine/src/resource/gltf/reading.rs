@@ -0,0 +1,516 @@
+//! Reading glTF files into resources.
+
+use super::{
+    GltfMaterialParameters, GltfSceneDeclaration, ImportedModel, ImportedScene, ImportedSceneID,
+    compute_emissive_luminance_texels, compute_node_frame_and_scale,
+    compute_specular_reflectance_texels, extract_texel_channel, scale_srgb_texel_colors,
+};
+use crate::resource::ResourceManager;
+use anyhow::{Context, Result, anyhow, bail};
+use gltf::{Material, Node, image, texture};
+use impact_alloc::Global;
+use impact_animation::{
+    AnimationClipID, SkeletonID,
+    io::gltf::{
+        GltfNodeHierarchy, create_animation_clip_from_gltf_animation,
+        create_skeleton_from_gltf_skin,
+    },
+};
+use impact_containers::HashMap;
+use impact_geometry::ModelTransform;
+use impact_gpu::{
+    texture::{
+        ColorSpace, DetailedTextureAddressingConfig, SamplerConfig, TextureAddressingConfig,
+        TextureConfig, TextureFilteringConfig,
+    },
+    wgpu,
+};
+use impact_io::image::{Image, ImageMetadata, PixelFormat};
+use impact_material::{
+    MaterialID,
+    import::{MaterialDeclaration, MaterialProperties},
+};
+use impact_mesh::{TriangleMeshID, io::gltf::GltfFile};
+use impact_texture::{
+    ImageSource, ImageTextureSource, TextureID,
+    processing::{FormatConversion, ImageProcessing, NormalMapFormat},
+};
+
+/// State for importing the resources in a single glTF file.
+struct GltfImporter<'a> {
+    resource_manager: &'a mut ResourceManager,
+    declaration: &'a GltfSceneDeclaration,
+    file: GltfFile,
+    /// Decoded RGBA8 images, indexed by glTF image index.
+    images: HashMap<usize, Image>,
+    /// Imported materials, indexed by glTF material index ([`None`] for the
+    /// default material).
+    materials: HashMap<Option<usize>, MaterialID>,
+    /// The imported triangle mesh and material for each primitive of each
+    /// glTF mesh, or [`None`] for primitives that could not be imported.
+    meshes: Vec<Vec<Option<(TriangleMeshID, MaterialID)>>>,
+    /// The imported skeleton and the first of its imported animation clips,
+    /// indexed by glTF skin index.
+    skins: HashMap<usize, (SkeletonID, Option<AnimationClipID>)>,
+}
+
+/// Imports the meshes, materials and scene hierarchy of the glTF file in the
+/// given declaration.
+pub(super) fn import_gltf_scene(
+    resource_manager: &mut ResourceManager,
+    declaration: &GltfSceneDeclaration,
+) -> Result<ImportedSceneID> {
+    let scene_id = ImportedSceneID::from_name(&declaration.name);
+
+    if resource_manager.imported_scenes.contains(scene_id) {
+        bail!(
+            "Tried to import glTF scene under already existing name: {}",
+            declaration.name
+        );
+    }
+
+    log::debug!(
+        "Importing glTF scene {} from {}",
+        declaration.name,
+        declaration.file_path.display()
+    );
+
+    let file = GltfFile::read(&declaration.file_path)?;
+
+    let mut importer = GltfImporter {
+        resource_manager,
+        declaration,
+        file,
+        images: HashMap::default(),
+        materials: HashMap::default(),
+        meshes: Vec::new(),
+        skins: HashMap::default(),
+    };
+
+    importer.import_meshes()?;
+    importer.import_skins()?;
+
+    let scene = importer.import_scene_hierarchy()?;
+
+    importer
+        .resource_manager
+        .imported_scenes
+        .insert(scene_id, scene);
+
+    Ok(scene_id)
+}
+
+impl GltfImporter<'_> {
+    fn import_meshes(&mut self) -> Result<()> {
+        let document = self.file.document.clone();
+
+        for mesh in document.meshes() {
+            let mut primitives = Vec::with_capacity(mesh.primitives().len());
+
+            for primitive in mesh.primitives() {
+                let Some(triangle_mesh) =
+                    impact_mesh::io::gltf::create_mesh_from_gltf_primitive(&self.file, &primitive)
+                        .with_context(|| {
+                            format!(
+                                "Failed to import primitive {} of glTF mesh {}",
+                                primitive.index(),
+                                mesh.index()
+                            )
+                        })?
+                else {
+                    primitives.push(None);
+                    continue;
+                };
+
+                let mesh_id = TriangleMeshID::from_name(&format!(
+                    "{}/mesh{}/primitive{}",
+                    self.declaration.name,
+                    mesh.index(),
+                    primitive.index()
+                ));
+
+                if self.resource_manager.triangle_meshes.contains(mesh_id) {
+                    bail!("Tried to load triangle mesh under already existing ID: {mesh_id}");
+                }
+                self.resource_manager
+                    .triangle_meshes
+                    .insert(mesh_id, triangle_mesh);
+
+                let material_id = self.import_material(&primitive.material())?;
+
+                primitives.push(Some((mesh_id, material_id)));
+            }
+
+            self.meshes.push(primitives);
+        }
+
+        Ok(())
+    }
+
+    fn import_skins(&mut self) -> Result<()> {
+        let document = self.file.document.clone();
+        let hierarchy = GltfNodeHierarchy::new(&document);
+
+        for skin in document.skins() {
+            // The skeleton maps the joints into the space of the node holding
+            // the skinned mesh. If several nodes use the skin, the first one
+            // determines this space.
+            let Some(mesh_node) = document.nodes().find(|node| {
+                node.mesh().is_some() && node.skin().is_some_and(|s| s.index() == skin.index())
+            }) else {
+                log::warn!("Skipping glTF skin {} not used by any mesh", skin.index());
+                continue;
+            };
+
+            let skeleton =
+                create_skeleton_from_gltf_skin(&self.file, &skin, mesh_node.index(), &hierarchy)
+                    .with_context(|| format!("Failed to import glTF skin {}", skin.index()))?;
+
+            let skeleton_name = format!("{}/skin{}", self.declaration.name, skin.index());
+            let skeleton_id = SkeletonID::from_name(&skeleton_name);
+
+            if self.resource_manager.skeletons.contains(skeleton_id) {
+                bail!("Tried to load skeleton under already existing ID: {skeleton_id}");
+            }
+            self.resource_manager
+                .skeletons
+                .insert(skeleton_id, skeleton);
+
+            let mut first_clip_id = None;
+
+            for animation in document.animations() {
+                let Some(clip) =
+                    create_animation_clip_from_gltf_animation(&self.file, &animation, &skin)?
+                else {
+                    continue;
+                };
+
+                let clip_id = AnimationClipID::from_name(&format!(
+                    "{skeleton_name}/animation{}",
+                    animation.index()
+                ));
+
+                if self.resource_manager.animation_clips.contains(clip_id) {
+                    bail!("Tried to load animation clip under already existing ID: {clip_id}");
+                }
+                self.resource_manager.animation_clips.insert(clip_id, clip);
+
+                first_clip_id.get_or_insert(clip_id);
+            }
+
+            self.skins
+                .insert(skin.index(), (skeleton_id, first_clip_id));
+        }
+
+        Ok(())
+    }
+
+    fn import_scene_hierarchy(&self) -> Result<ImportedScene> {
+        let document = &self.file.document;
+
+        let gltf_scene = match self.declaration.options.scene_index {
+            Some(scene_index) => document
+                .scenes()
+                .nth(scene_index)
+                .ok_or_else(|| anyhow!("glTF file has no scene with index {scene_index}"))?,
+            None => document
+                .default_scene()
+                .or_else(|| document.scenes().next())
+                .ok_or_else(|| anyhow!("glTF file has no scenes"))?,
+        };
+
+        let mut scene = ImportedScene::new();
+
+        for node in gltf_scene.nodes() {
+            self.add_node_to_scene(&mut scene, &node, None, 1.0);
+        }
+
+        Ok(scene)
+    }
+
+    fn add_node_to_scene(
+        &self,
+        scene: &mut ImportedScene,
+        node: &Node<'_>,
+        parent: Option<usize>,
+        parent_scale: f32,
+    ) {
+        let (translation, rotation, scale) = node.transform().decomposed();
+
+        let (frame, scale) =
+            compute_node_frame_and_scale(translation, rotation, scale, parent_scale);
+
+        let skin = node
+            .skin()
+            .and_then(|skin| self.skins.get(&skin.index()).copied());
+
+        let models = node
+            .mesh()
+            .map(|mesh| {
+                self.meshes[mesh.index()]
+                    .iter()
+                    .flatten()
+                    .map(|&(mesh_id, material_id)| ImportedModel {
+                        mesh_id,
+                        material_id,
+                        model_transform: ModelTransform::with_scale(scale),
+                        skeleton_id: skin.map(|(skeleton_id, _)| skeleton_id),
+                        animation_clip_id: skin.and_then(|(_, clip_id)| clip_id),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let node_idx = scene.add_node(parent, frame, models);
+
+        for child in node.children() {
+            self.add_node_to_scene(scene, &child, Some(node_idx), scale);
+        }
+    }
+
+    fn import_material(&mut self, material: &Material<'_>) -> Result<MaterialID> {
+        if let Some(&material_id) = self.materials.get(&material.index()) {
+            return Ok(material_id);
+        }
+
+        let material_name = match material.index() {
+            Some(material_idx) => format!("{}/material{material_idx}", self.declaration.name),
+            None => format!("{}/default_material", self.declaration.name),
+        };
+
+        let properties = self
+            .create_material_parameters(material, &material_name)
+            .with_context(|| format!("Failed to import glTF material {material_name}"))?
+            .into_physical_material_properties();
+
+        let resource_manager = &mut *self.resource_manager;
+
+        let material_id = impact_material::import::load_declared_material(
+            &resource_manager.textures,
+            &resource_manager.samplers,
+            &mut resource_manager.materials,
+            &mut resource_manager.material_templates,
+            &mut resource_manager.material_texture_groups,
+            &MaterialDeclaration {
+                id: MaterialID::from_name(&material_name),
+                properties: MaterialProperties::Physical(properties),
+            },
+        )?;
+
+        self.materials.insert(material.index(), material_id);
+
+        Ok(material_id)
+    }
+
+    fn create_material_parameters(
+        &mut self,
+        material: &Material<'_>,
+        material_name: &str,
+    ) -> Result<GltfMaterialParameters> {
+        let pbr = material.pbr_metallic_roughness();
+
+        let [r, g, b, _] = pbr.base_color_factor();
+
+        let [er, eg, eb] = material.emissive_factor();
+        let emissive_luminance = er.max(eg).max(eb)
+            * material.emissive_strength().unwrap_or(1.0)
+            * self.declaration.options.emissive_luminance_scale;
+
+        let mut parameters = GltfMaterialParameters::new(
+            [r, g, b],
+            pbr.roughness_factor(),
+            pbr.metallic_factor(),
+            emissive_luminance,
+        );
+
+        if let Some(info) = pbr.base_color_texture() {
+            let texture = info.texture();
+            check_tex_coord_set(info.tex_coord());
+
+            let mut image = self.decode_image(&texture.source())?;
+            if [r, g, b] != [1.0; 3] {
+                scale_srgb_texel_colors(&mut image.data, [r, g, b]);
+            }
+
+            parameters.color_texture_id = Some(self.load_texture(
+                material_name,
+                "color",
+                &texture,
+                image,
+                ColorSpace::Srgb,
+                ImageProcessing::none(),
+            )?);
+        }
+
+        if let Some(info) = pbr.metallic_roughness_texture() {
+            let texture = info.texture();
+            check_tex_coord_set(info.tex_coord());
+
+            let image = self.decode_image(&texture.source())?;
+
+            // glTF stores roughness in the green channel and metalness in the
+            // blue channel, but our material textures are single-channel
+            let roughness_texels = extract_texel_channel(&image.data, 1);
+            let metalness_texels = extract_texel_channel(&image.data, 2);
+            let specular_reflectance_texels =
+                compute_specular_reflectance_texels(&metalness_texels, parameters.metalness);
+
+            parameters.roughness_texture_id = Some(self.load_texture(
+                material_name,
+                "roughness",
+                &texture,
+                luma_image_like(&image, roughness_texels),
+                ColorSpace::Linear,
+                ImageProcessing::none(),
+            )?);
+            parameters.metalness_texture_id = Some(self.load_texture(
+                material_name,
+                "metalness",
+                &texture,
+                luma_image_like(&image, metalness_texels),
+                ColorSpace::Linear,
+                ImageProcessing::none(),
+            )?);
+            parameters.specular_reflectance_texture_id = Some(self.load_texture(
+                material_name,
+                "specular_reflectance",
+                &texture,
+                luma_image_like(&image, specular_reflectance_texels),
+                ColorSpace::Linear,
+                ImageProcessing::none(),
+            )?);
+        }
+
+        if let Some(info) = material.normal_texture() {
+            let texture = info.texture();
+            check_tex_coord_set(info.tex_coord());
+
+            let image = self.decode_image(&texture.source())?;
+
+            parameters.normal_map_texture_id = Some(self.load_texture(
+                material_name,
+                "normal",
+                &texture,
+                image,
+                ColorSpace::Linear,
+                ImageProcessing {
+                    format_conversions: vec![FormatConversion::NormalMap {
+                        from: NormalMapFormat::OpenGL,
+                    }],
+                    compression: None,
+                },
+            )?);
+        }
+
+        if let Some(info) = material.emissive_texture() {
+            let texture = info.texture();
+            check_tex_coord_set(info.tex_coord());
+
+            let image = self.decode_image(&texture.source())?;
+            let emissive_luminance_texels = compute_emissive_luminance_texels(&image.data);
+
+            parameters.emissive_luminance_texture_id = Some(self.load_texture(
+                material_name,
+                "emissive_luminance",
+                &texture,
+                luma_image_like(&image, emissive_luminance_texels),
+                ColorSpace::Linear,
+                ImageProcessing::none(),
+            )?);
+        }
+
+        Ok(parameters)
+    }
+
+    /// Returns the given image decoded to RGBA8.
+    fn decode_image(&mut self, gltf_image: &image::Image<'_>) -> Result<Image> {
+        if let Some(image) = self.images.get(&gltf_image.index()) {
+            return Ok(image.clone());
+        }
+
+        let bytes = match gltf_image.source() {
+            image::Source::View { view, .. } => self.file.buffer_view_data(&view).to_vec(),
+            image::Source::Uri { uri, .. } => self.file.read_uri(uri)?,
+        };
+
+        let image = impact_io::image::load_image_from_bytes(Global, &bytes)
+            .with_context(|| format!("Failed to decode glTF image {}", gltf_image.index()))?;
+
+        let meta = ImageMetadata {
+            pixel_format: PixelFormat::Rgba8,
+            ..image.meta
+        };
+        let image = Image {
+            meta,
+            data: image.into_rgba8(Global),
+        };
+
+        self.images.insert(gltf_image.index(), image.clone());
+
+        Ok(image)
+    }
+
+    fn load_texture(
+        &mut self,
+        material_name: &str,
+        texture_name: &str,
+        texture: &texture::Texture<'_>,
+        image: Image,
+        color_space: ColorSpace,
+        processing: ImageProcessing,
+    ) -> Result<TextureID> {
+        let texture_id = TextureID::from_name(&format!("{material_name}/{texture_name}"));
+
+        let sampler = texture.sampler();
+        let sampler_config = SamplerConfig {
+            addressing: TextureAddressingConfig::Detailed(DetailedTextureAddressingConfig {
+                address_mode_u: convert_wrapping_mode(sampler.wrap_s()),
+                address_mode_v: convert_wrapping_mode(sampler.wrap_t()),
+                address_mode_w: wgpu::AddressMode::ClampToEdge,
+            }),
+            filtering: TextureFilteringConfig::default(),
+        };
+
+        impact_texture::import::load_image_texture(
+            &mut self.resource_manager.textures,
+            &mut self.resource_manager.samplers,
+            texture_id,
+            ImageTextureSource::Single(ImageSource::Bytes(image)),
+            TextureConfig {
+                color_space,
+                max_mip_level_count: None,
+            },
+            Some(sampler_config),
+            processing,
+        )?;
+
+        Ok(texture_id)
+    }
+}
+
+fn luma_image_like(image: &Image, texels: Vec<u8>) -> Image {
+    Image {
+        meta: ImageMetadata {
+            pixel_format: PixelFormat::Luma8,
+            ..image.meta
+        },
+        data: texels.into_iter().collect(),
+    }
+}
+
+fn check_tex_coord_set(tex_coord: u32) {
+    if tex_coord != 0 {
+        log::warn!(
+            "glTF texture uses texture coordinate set {tex_coord}, but only the first set is \
+             imported"
+        );
+    }
+}
+
+fn convert_wrapping_mode(mode: texture::WrappingMode) -> wgpu::AddressMode {
+    match mode {
+        texture::WrappingMode::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+        texture::WrappingMode::MirroredRepeat => wgpu::AddressMode::MirrorRepeat,
+        texture::WrappingMode::Repeat => wgpu::AddressMode::Repeat,
+    }
+}
@@ -0,0 +1,584 @@
+//! Reloading of declared resources when their source files change.
+
+use crate::resource::{ResourceConfig, ResourceManager, import::ResourceDeclarations};
+use anyhow::{Context, Result, anyhow, bail};
+use impact_mesh::{
+    TriangleMeshDirtyMask, TriangleMeshRegistry,
+    import::{TriangleMeshDeclaration, TriangleMeshSource},
+};
+use impact_texture::{
+    TextureRegistry,
+    import::{DeclaredImageTextureSource, ImageTextureDeclaration},
+};
+use impact_voxel::generation::{VoxelGenerator, import::VoxelGeneratorDeclaration};
+use parking_lot::Mutex;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, iter,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Watches the source files of declared textures, triangle meshes and voxel
+/// generators and reloads the resources when their files change.
+///
+/// Reloaded resources replace the existing entries in their registries, so
+/// that their GPU resources are resynchronized. A resource that fails to
+/// reload keeps its previous entry, and the failure is recorded until the
+/// resource is successfully reloaded.
+pub struct ResourceReloader {
+    watched_resources: HashMap<PathBuf, Vec<ReloadableResource>>,
+    changed_paths: Arc<Mutex<HashSet<PathBuf>>>,
+    failures: Vec<ResourceReloadFailure>,
+    #[cfg(feature = "hot_reloading")]
+    watcher: Option<notify::RecommendedWatcher>,
+}
+
+/// A failed attempt to reload a resource whose source file changed.
+#[derive(Clone, Debug)]
+pub struct ResourceReloadFailure {
+    /// Description of the resource, like "texture `name`".
+    pub resource: String,
+    /// The changed file that triggered the reload.
+    pub path: PathBuf,
+    /// The error message.
+    pub message: String,
+}
+
+#[derive(Clone, Debug)]
+enum ReloadableResource {
+    Texture(ImageTextureDeclaration),
+    TriangleMesh(TriangleMeshDeclaration),
+    VoxelGenerator(VoxelGeneratorDeclaration),
+}
+
+impl ResourceReloader {
+    /// Creates a reloader for the file-based resources in the given
+    /// declarations. Changes are only detected automatically after calling
+    /// [`Self::start_watching`].
+    pub fn new(declarations: &ResourceDeclarations) -> Self {
+        let mut watched_resources: HashMap<PathBuf, Vec<ReloadableResource>> = HashMap::new();
+
+        let mut watch = |path: &Path, resource: &ReloadableResource| {
+            watched_resources
+                .entry(normalize_path(path))
+                .or_default()
+                .push(resource.clone());
+        };
+
+        for declaration in &declarations.textures {
+            let resource = ReloadableResource::Texture(declaration.clone());
+            for path in declaration.source.image_paths() {
+                watch(path, &resource);
+            }
+        }
+        for declaration in &declarations.triangle_meshes {
+            if let TriangleMeshSource::File(path) = &declaration.source {
+                watch(path, &ReloadableResource::TriangleMesh(declaration.clone()));
+            }
+        }
+        for declaration in &declarations.voxel_generators {
+            watch(
+                &declaration.path,
+                &ReloadableResource::VoxelGenerator(declaration.clone()),
+            );
+        }
+
+        Self {
+            watched_resources,
+            changed_paths: Arc::new(Mutex::new(HashSet::new())),
+            failures: Vec::new(),
+            #[cfg(feature = "hot_reloading")]
+            watcher: None,
+        }
+    }
+
+    /// Creates a reloader watching the files of the resources declared in the
+    /// resource file pointed to in the given [`ResourceConfig`], if hot
+    /// reloading is enabled in the config.
+    ///
+    /// # Errors
+    /// Returns an error if the resource file is invalid or the files could
+    /// not be watched.
+    pub fn for_config(config: &ResourceConfig) -> Result<Option<Self>> {
+        if !config.hot_reloading {
+            return Ok(None);
+        }
+        if config.resource_database_path.is_some() {
+            log::warn!("Hot reloading is not supported for resources loaded from a database");
+            return Ok(None);
+        }
+
+        #[cfg(feature = "hot_reloading")]
+        {
+            let mut reloader = Self::new(&config.read_declarations()?);
+            reloader.start_watching()?;
+            Ok(Some(reloader))
+        }
+        #[cfg(not(feature = "hot_reloading"))]
+        {
+            log::warn!("Hot reloading of resources requires the `hot_reloading` feature");
+            Ok(None)
+        }
+    }
+
+    /// Returns an iterator over the paths of all watched files.
+    pub fn watched_paths(&self) -> impl Iterator<Item = &Path> {
+        self.watched_resources.keys().map(PathBuf::as_path)
+    }
+
+    /// Returns the failures from the most recent reload attempt of each
+    /// resource that has not been successfully reloaded since.
+    pub fn failures(&self) -> &[ResourceReloadFailure] {
+        &self.failures
+    }
+
+    /// Starts watching the directories containing the watched files for
+    /// changes.
+    ///
+    /// # Errors
+    /// Returns an error if the file watcher could not be created or a
+    /// directory could not be watched.
+    #[cfg(feature = "hot_reloading")]
+    pub fn start_watching(&mut self) -> Result<()> {
+        use notify::{RecursiveMode, Watcher as _};
+
+        let changed_paths = Arc::clone(&self.changed_paths);
+
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(err) => {
+                        log::error!("Watching resource files failed with error: {err}");
+                        return;
+                    }
+                };
+                if event_changed_file_content(event.kind) {
+                    let mut changed_paths = changed_paths.lock();
+                    for path in &event.paths {
+                        changed_paths.insert(normalize_path(path));
+                    }
+                }
+            })
+            .context("Failed to create resource file watcher")?;
+
+        // Editors often save by replacing the file, so we watch the
+        // directories rather than the files themselves
+        let directories: HashSet<_> = self
+            .watched_resources
+            .keys()
+            .filter_map(|path| path.parent())
+            .collect();
+
+        for directory in directories {
+            watcher
+                .watch(directory, RecursiveMode::NonRecursive)
+                .with_context(|| format!("Failed to watch {}", directory.display()))?;
+        }
+
+        self.watcher = Some(watcher);
+
+        Ok(())
+    }
+
+    /// Registers that the file at the given path has changed, so that any
+    /// resources loaded from it are reloaded on the next call to
+    /// [`Self::reload_changed_resources`].
+    pub fn notify_file_changed(&self, path: &Path) {
+        self.changed_paths.lock().insert(normalize_path(path));
+    }
+
+    /// Reloads all resources whose source files have changed since the last
+    /// call and replaces them in the registries of the given resource manager.
+    /// Material texture groups using a reloaded texture are replaced as well,
+    /// so that they pick up the new texture.
+    ///
+    /// Failing to reload a resource is not fatal. The error is logged and
+    /// recorded in [`Self::failures`], and the previous version of the
+    /// resource is kept.
+    ///
+    /// # Returns
+    /// The number of successfully reloaded resources.
+    pub fn reload_changed_resources(&mut self, resource_manager: &mut ResourceManager) -> usize {
+        let changed_paths: Vec<_> = self.changed_paths.lock().drain().collect();
+
+        // A resource may be loaded from multiple changed files, in which case
+        // it should only be reloaded once
+        let mut reloaded = HashSet::new();
+        let mut n_reloaded = 0;
+
+        for path in changed_paths {
+            let Some(resources) = self.watched_resources.get(&path) else {
+                continue;
+            };
+
+            for resource in resources {
+                let description = resource.description();
+
+                if !reloaded.insert(description.clone()) {
+                    continue;
+                }
+
+                log::info!("Reloading {description} after change in {}", path.display());
+
+                self.failures
+                    .retain(|failure| failure.resource != description);
+
+                match resource.reload(resource_manager) {
+                    Ok(()) => {
+                        n_reloaded += 1;
+                    }
+                    Err(error) => {
+                        log::error!("Failed to reload {description}: {error:#}");
+                        self.failures.push(ResourceReloadFailure {
+                            resource: description,
+                            path: path.clone(),
+                            message: format!("{error:#}"),
+                        });
+                    }
+                }
+            }
+        }
+
+        n_reloaded
+    }
+}
+
+impl fmt::Debug for ResourceReloader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("ResourceReloader");
+        debug
+            .field("watched_resources", &self.watched_resources)
+            .field("changed_paths", &self.changed_paths)
+            .field("failures", &self.failures);
+        #[cfg(feature = "hot_reloading")]
+        debug.field("watching", &self.watcher.is_some());
+        debug.finish()
+    }
+}
+
+impl fmt::Display for ResourceReloadFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to reload {} from {}: {}",
+            self.resource,
+            self.path.display(),
+            self.message
+        )
+    }
+}
+
+impl ReloadableResource {
+    fn description(&self) -> String {
+        match self {
+            Self::Texture(declaration) => format!("texture `{}`", declaration.id),
+            Self::TriangleMesh(declaration) => format!("triangle mesh `{}`", declaration.id),
+            Self::VoxelGenerator(declaration) => format!("voxel generator `{}`", declaration.id),
+        }
+    }
+
+    fn reload(&self, resource_manager: &mut ResourceManager) -> Result<()> {
+        match self {
+            Self::Texture(declaration) => reload_texture(resource_manager, declaration),
+            Self::TriangleMesh(declaration) => {
+                reload_triangle_mesh(&mut resource_manager.triangle_meshes, declaration)
+            }
+            Self::VoxelGenerator(declaration) => {
+                let generator: VoxelGenerator = impact_io::parse_ron_file(&declaration.path)
+                    .with_context(|| {
+                        format!(
+                            "Failed to load voxel generator from {}",
+                            declaration.path.display()
+                        )
+                    })?;
+                resource_manager
+                    .voxel_generators
+                    .insert(declaration.id, generator);
+                Ok(())
+            }
+        }
+    }
+}
+
+fn reload_texture(
+    resource_manager: &mut ResourceManager,
+    declaration: &ImageTextureDeclaration,
+) -> Result<()> {
+    let id = declaration.id;
+
+    let mut textures = TextureRegistry::new();
+    impact_texture::import::load_declared_image_texture(
+        &mut textures,
+        &mut resource_manager.samplers,
+        declaration.clone(),
+    )?;
+    let texture = textures
+        .remove(id)
+        .ok_or_else(|| anyhow!("Missing loaded texture {id}"))?;
+
+    resource_manager.textures.insert(id, texture);
+
+    // The GPU resources of texture groups refer to the textures directly, so
+    // the groups must be replaced to be recreated with the new texture
+    let affected_groups: Vec<_> = resource_manager
+        .material_texture_groups
+        .iter()
+        .filter(|(_, group)| group.texture_ids.contains(&id))
+        .map(|(group_id, group)| (group_id, group.clone()))
+        .collect();
+
+    for (group_id, group) in affected_groups {
+        resource_manager
+            .material_texture_groups
+            .insert(group_id, group);
+    }
+
+    Ok(())
+}
+
+fn reload_triangle_mesh(
+    registry: &mut TriangleMeshRegistry,
+    declaration: &TriangleMeshDeclaration,
+) -> Result<()> {
+    let id = declaration.id;
+
+    let mut meshes = TriangleMeshRegistry::new();
+    impact_mesh::import::load_declared_triangle_mesh(&mut meshes, declaration)?;
+
+    if !meshes.contains(id) {
+        bail!("Missing loaded triangle mesh {id}");
+    }
+
+    // Vertex attributes generated for the previous mesh after it was loaded
+    // must also be generated for the new mesh and its levels of detail
+    let (generate_normal_vectors, generate_tangent_space_quaternions) =
+        registry.get(id).map_or((false, false), |previous_mesh| {
+            (
+                previous_mesh.has_normal_vectors(),
+                previous_mesh.has_tangent_space_quaternions(),
+            )
+        });
+
+    // Levels of detail are replaced level by level, so that instances using
+    // them keep valid meshes even if the new chain is shorter
+    let lod_mesh_ids = impact_mesh::lod::lod_mesh_ids(&meshes, id);
+
+    for mesh_id in iter::once(id).chain(lod_mesh_ids) {
+        let Some(mut mesh) = meshes.remove(mesh_id) else {
+            continue;
+        };
+
+        let mut dirty_mask = TriangleMeshDirtyMask::empty();
+
+        if generate_normal_vectors && !mesh.has_normal_vectors() {
+            mesh.generate_smooth_normal_vectors(&mut dirty_mask);
+        }
+        if generate_tangent_space_quaternions
+            && !mesh.has_tangent_space_quaternions()
+            && mesh.has_texture_coords()
+        {
+            mesh.generate_smooth_tangent_space_quaternions(&mut dirty_mask);
+        }
+
+        registry.insert(mesh_id, mesh);
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "hot_reloading")]
+fn event_changed_file_content(event_kind: notify::EventKind) -> bool {
+    use notify::{
+        EventKind,
+        event::{CreateKind, DataChange, ModifyKind},
+    };
+    matches!(
+        event_kind,
+        EventKind::Create(CreateKind::File)
+            | EventKind::Modify(
+                ModifyKind::Data(DataChange::Content | DataChange::Any) | ModifyKind::Name(_)
+            )
+    )
+}
+
+/// Makes the path absolute and resolves symbolic links if possible, so that
+/// declared paths can be compared with the paths in file events.
+fn normalize_path(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use impact_texture::SamplerRegistry;
+    use impact_voxel::{generation::VoxelGeneratorID, voxel_types::VoxelTypeRegistry};
+    use std::fs;
+
+    fn resource_manager() -> ResourceManager {
+        ResourceManager::new(
+            Default::default(),
+            TextureRegistry::new(),
+            SamplerRegistry::new(),
+            VoxelTypeRegistry::empty(),
+        )
+    }
+
+    fn copy_test_voxel_generator(dir: &Path) -> PathBuf {
+        let path = dir.join("generator.vgen.ron");
+        fs::copy(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("benches/data/asteroid.vgen.ron"),
+            &path,
+        )
+        .unwrap();
+        path
+    }
+
+    fn setup_voxel_generator(
+        dir: &Path,
+    ) -> (ResourceReloader, ResourceManager, VoxelGeneratorID, PathBuf) {
+        let path = copy_test_voxel_generator(dir);
+        let declaration = VoxelGeneratorDeclaration {
+            id: VoxelGeneratorID::from_name("asteroid"),
+            path: path.clone(),
+            pregenerated_objects: Vec::new(),
+        };
+
+        let mut resource_manager = resource_manager();
+        impact_voxel::generation::import::load_declared_voxel_generator(
+            &mut resource_manager.voxel_generators,
+            &declaration,
+        )
+        .unwrap();
+
+        let reloader = ResourceReloader::new(&ResourceDeclarations {
+            voxel_generators: vec![declaration.clone()],
+            ..Default::default()
+        });
+
+        (reloader, resource_manager, declaration.id, path)
+    }
+
+    #[test]
+    fn declared_files_are_watched() {
+        let dir = tempfile::tempdir().unwrap();
+        let (reloader, ..) = setup_voxel_generator(dir.path());
+
+        let watched: Vec<_> = reloader.watched_paths().collect();
+        assert_eq!(watched.len(), 1);
+        assert!(watched[0].ends_with("generator.vgen.ron"));
+    }
+
+    #[test]
+    fn nothing_is_reloaded_without_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut reloader, mut resource_manager, ..) = setup_voxel_generator(dir.path());
+        let revision = resource_manager.voxel_generators.revision();
+
+        assert_eq!(reloader.reload_changed_resources(&mut resource_manager), 0);
+        assert_eq!(resource_manager.voxel_generators.revision(), revision);
+    }
+
+    #[test]
+    fn changed_voxel_generator_is_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut reloader, mut resource_manager, id, path) = setup_voxel_generator(dir.path());
+        let revision = resource_manager.voxel_generators.revision();
+
+        reloader.notify_file_changed(&path);
+
+        assert_eq!(reloader.reload_changed_resources(&mut resource_manager), 1);
+        assert!(resource_manager.voxel_generators.contains(id));
+        assert!(resource_manager.voxel_generators.revision() > revision);
+        assert!(reloader.failures().is_empty());
+    }
+
+    #[test]
+    fn changes_to_unwatched_files_are_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut reloader, mut resource_manager, ..) = setup_voxel_generator(dir.path());
+
+        let other_path = dir.path().join("other.ron");
+        fs::write(&other_path, "()").unwrap();
+        reloader.notify_file_changed(&other_path);
+
+        assert_eq!(reloader.reload_changed_resources(&mut resource_manager), 0);
+    }
+
+    #[test]
+    fn invalid_file_records_failure_and_keeps_previous_resource() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut reloader, mut resource_manager, id, path) = setup_voxel_generator(dir.path());
+        let revision = resource_manager.voxel_generators.revision();
+
+        fs::write(&path, "not a voxel generator").unwrap();
+        reloader.notify_file_changed(&path);
+
+        assert_eq!(reloader.reload_changed_resources(&mut resource_manager), 0);
+        assert!(resource_manager.voxel_generators.contains(id));
+        assert_eq!(resource_manager.voxel_generators.revision(), revision);
+
+        let failures = reloader.failures();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].resource, "voxel generator `asteroid`");
+        assert_eq!(failures[0].path, normalize_path(&path));
+    }
+
+    #[test]
+    fn repeated_failures_are_recorded_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut reloader, mut resource_manager, _, path) = setup_voxel_generator(dir.path());
+
+        fs::write(&path, "not a voxel generator").unwrap();
+        reloader.notify_file_changed(&path);
+        reloader.reload_changed_resources(&mut resource_manager);
+        reloader.notify_file_changed(&path);
+        reloader.reload_changed_resources(&mut resource_manager);
+
+        assert_eq!(reloader.failures().len(), 1);
+    }
+
+    #[test]
+    fn successful_reload_clears_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut reloader, mut resource_manager, _, path) = setup_voxel_generator(dir.path());
+        let valid_contents = fs::read_to_string(&path).unwrap();
+
+        fs::write(&path, "not a voxel generator").unwrap();
+        reloader.notify_file_changed(&path);
+        reloader.reload_changed_resources(&mut resource_manager);
+        assert_eq!(reloader.failures().len(), 1);
+
+        fs::write(&path, valid_contents).unwrap();
+        reloader.notify_file_changed(&path);
+
+        assert_eq!(reloader.reload_changed_resources(&mut resource_manager), 1);
+        assert!(reloader.failures().is_empty());
+    }
+
+    #[test]
+    fn texture_reload_failure_keeps_previous_texture() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("texture.png");
+        fs::write(&path, "not an image").unwrap();
+
+        let mut reloader = ResourceReloader::new(&ResourceDeclarations {
+            textures: vec![ImageTextureDeclaration {
+                id: impact_texture::TextureID::from_name("texture"),
+                source: DeclaredImageTextureSource::Image(path.clone()),
+                texture_config: Default::default(),
+                sampler_config: None,
+                processing: Default::default(),
+            }],
+            ..Default::default()
+        });
+        let mut resource_manager = resource_manager();
+
+        reloader.notify_file_changed(&path);
+
+        assert_eq!(reloader.reload_changed_resources(&mut resource_manager), 0);
+        assert_eq!(reloader.failures().len(), 1);
+        assert_eq!(reloader.failures()[0].resource, "texture `texture`");
+    }
+}
@@ -1,10 +1,13 @@
 //! Importing resources.
 
+use crate::resource::gltf::GltfSceneDeclaration;
 use anyhow::Result;
 use impact_material::import::MaterialDeclaration;
 use impact_mesh::import::TriangleMeshDeclaration;
 use impact_texture::import::ImageTextureDeclaration;
-use impact_voxel::generation::import::VoxelGeneratorDeclaration;
+use impact_voxel::generation::import::{
+    VoxModelDeclaration, VoxelGeneratorDeclaration, VoxelizedMeshDeclaration,
+};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
@@ -15,6 +18,9 @@ pub struct ResourceDeclarations {
     pub textures: Vec<ImageTextureDeclaration>,
     pub materials: Vec<MaterialDeclaration>,
     pub voxel_generators: Vec<VoxelGeneratorDeclaration>,
+    pub voxelized_meshes: Vec<VoxelizedMeshDeclaration>,
+    pub vox_models: Vec<VoxModelDeclaration>,
+    pub gltf_scenes: Vec<GltfSceneDeclaration>,
 }
 
 impl ResourceDeclarations {
@@ -41,5 +47,11 @@ impl ResourceDeclarations {
         for declaration in &mut self.voxel_generators {
             declaration.resolve_paths(root_path);
         }
+        for declaration in &mut self.vox_models {
+            declaration.resolve_paths(root_path);
+        }
+        for declaration in &mut self.gltf_scenes {
+            declaration.resolve_paths(root_path);
+        }
     }
 }
@@ -2,6 +2,7 @@
 
 use crate::lock_order::OrderedRwLock;
 use anyhow::Result;
+use impact_animation::JointPaletteManager;
 use impact_camera::{CameraContext, CameraManager};
 use impact_id::EntityIDManager;
 use impact_intersection::IntersectionManager;
@@ -21,6 +22,7 @@ pub struct Scene {
     camera_manager: RwLock<CameraManager>,
     light_manager: RwLock<LightManager>,
     voxel_manager: RwLock<VoxelManager>,
+    joint_palette_manager: RwLock<JointPaletteManager>,
     model_instance_manager: RwLock<ModelInstanceManager>,
     initial_model_instance_manager_state: ModelInstanceManagerState,
     intersection_manager: RwLock<IntersectionManager>,
@@ -42,6 +44,7 @@ impl Scene {
             camera_manager: RwLock::new(CameraManager::new(camera_context)),
             light_manager: RwLock::new(LightManager::new()),
             voxel_manager: RwLock::new(VoxelManager::new(voxel_config)?),
+            joint_palette_manager: RwLock::new(JointPaletteManager::new()),
             model_instance_manager: RwLock::new(model_instance_manager),
             initial_model_instance_manager_state,
             intersection_manager: RwLock::new(IntersectionManager::new()),
@@ -70,6 +73,12 @@ impl Scene {
         &self.voxel_manager
     }
 
+    /// Returns a reference to the [`JointPaletteManager`], guarded by a
+    /// [`RwLock`].
+    pub fn joint_palette_manager(&self) -> &RwLock<JointPaletteManager> {
+        &self.joint_palette_manager
+    }
+
     /// Returns a reference to the [`ModelInstanceManager`], guarded by a
     /// [`RwLock`].
     pub fn model_instance_manager(&self) -> &RwLock<ModelInstanceManager> {
@@ -108,6 +117,8 @@ impl Scene {
 
         self.voxel_manager.owrite().reset_and_free();
 
+        self.joint_palette_manager.owrite().clear_palettes();
+
         self.model_instance_manager
             .owrite()
             .reset_to_state(&self.initial_model_instance_manager_state);
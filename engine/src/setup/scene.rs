@@ -22,9 +22,11 @@ use impact_material::MaterialID;
 use impact_mesh::TriangleMeshID;
 use impact_model::HasModel;
 use impact_scene::{
-    CanBeParent, ParentEntity, SceneEntityFlags, setup::HasIndependentMaterialValues,
+    CanBeParent, ParentEntity, SceneEntityFlags, model::ModelID,
+    setup::HasIndependentMaterialValues,
 };
 use parking_lot::RwLock;
+use std::iter;
 
 /// Performs any modifications to the scene required to accommodate the given
 /// new entities, and adds any additional components to the entities' components
@@ -74,11 +76,12 @@ pub fn add_new_entities_to_scene_graph(
 /// Performs any modifications required to clean up the scene when
 /// the given entity is removed.
 pub fn cleanup_scene_data_for_removed_entity(
+    resource_manager: &RwLock<ResourceManager>,
     scene: &RwLock<Scene>,
     entity_id: EntityID,
     entity: &EntityEntry<'_>,
 ) {
-    remove_scene_graph_model_instance_node_for_entity(scene, entity_id, entity);
+    remove_scene_graph_model_instance_node_for_entity(resource_manager, scene, entity_id, entity);
 
     light::cleanup_light_for_removed_entity(scene, entity_id, entity);
 
@@ -126,7 +129,7 @@ fn setup_scene_graph_model_instance_nodes_for_new_entities(
 ) -> Result<()> {
     setup!(
         {
-            let resource_manager = resource_manager.oread();
+            let mut resource_manager = resource_manager.owrite();
             let scene = scene.oread();
             let mut model_instance_manager = scene.model_instance_manager().owrite();
             let mut scene_graph = scene.scene_graph().owrite();
@@ -151,6 +154,14 @@ fn setup_scene_graph_model_instance_nodes_for_new_entities(
             let has_independent_material_values =
                 entities.has_component_type::<HasIndependentMaterialValues>();
 
+            let lod_mesh_ids =
+                impact_mesh::lod::lod_mesh_ids(&resource_manager.triangle_meshes, *mesh_id);
+
+            let is_skinned = resource_manager
+                .triangle_meshes
+                .get(*mesh_id)
+                .is_some_and(|mesh| mesh.has_skin_weights());
+
             let flags = impact_scene::setup::setup_scene_graph_model_instance_node(
                 &resource_manager.materials,
                 &mut model_instance_manager,
@@ -158,12 +169,20 @@ fn setup_scene_graph_model_instance_nodes_for_new_entities(
                 entity_id,
                 model_to_parent_transform.compact(),
                 *mesh_id,
+                &lod_mesh_ids,
                 *material_id,
                 parent_entity_id,
                 flags,
                 has_independent_material_values,
+                is_skinned,
             )?;
 
+            for mesh_id in iter::once(*mesh_id).chain(lod_mesh_ids) {
+                resource_manager.add_model_instance_references(
+                    &ModelID::for_triangle_mesh_and_material(mesh_id, *material_id),
+                );
+            }
+
             Ok((HasModel, model_transform, flags))
         },
         ![HasModel]
@@ -171,18 +190,24 @@ fn setup_scene_graph_model_instance_nodes_for_new_entities(
 }
 
 fn remove_scene_graph_model_instance_node_for_entity(
+    resource_manager: &RwLock<ResourceManager>,
     scene: &RwLock<Scene>,
     entity_id: EntityID,
     entity: &EntityEntry<'_>,
 ) {
     if entity.has_component::<HasModel>() {
+        let mut resource_manager = resource_manager.owrite();
         let scene = scene.oread();
         let mut model_instance_manager = scene.model_instance_manager().owrite();
         let mut scene_graph = scene.scene_graph().owrite();
-        impact_scene::setup::remove_scene_graph_model_instance_node(
+        if let Some(model_ids) = impact_scene::setup::remove_scene_graph_model_instance_node(
             &mut model_instance_manager,
             &mut scene_graph,
             entity_id,
-        );
+        ) {
+            for model_id in &model_ids {
+                resource_manager.remove_model_instance_references(model_id);
+            }
+        }
     }
 }
@@ -9,6 +9,7 @@ use impact_alloc::{Global, arena::ArenaPool};
 use impact_ecs::{setup, world::PrototypeEntities};
 use impact_geometry::{ModelTransform, ReferenceFrame};
 use impact_id::EntityID;
+use impact_math::point::Point3;
 use impact_model::HasModel;
 use impact_physics::{
     quantities::Motion,
@@ -17,16 +18,25 @@ use impact_physics::{
 use impact_scene::{ParentEntity, SceneEntityFlags};
 use impact_voxel::{
     HasVoxelObject, VoxelObjectID,
-    generation::{SDFVoxelGenerator, sdf::SDFGraph},
+    collidable::setup::VoxelCollidable,
+    generation::{
+        SDFVoxelGenerator,
+        pregenerated::PregeneratedVoxelObjectKey,
+        sdf::SDFGraph,
+        vox::{VoxPaletteVoxelTypes, VoxVoxelGenerator},
+        voxel_type::VoxelTypeGenerator,
+        voxelization::MeshVoxelGenerator,
+    },
     interaction::absorption::{
         HasVoxelAbsorbingCapsule, HasVoxelAbsorbingSphere, VoxelAbsorbingCapsule,
         VoxelAbsorbingCapsuleID, VoxelAbsorbingSphere, VoxelAbsorbingSphereID,
     },
     setup::{
-        self, DynamicVoxels, GeneratedVoxelObject, GradientNoiseVoxelTypes,
-        MultifractalNoiseSDFModification, SameVoxelType, VoxelBox, VoxelCapsule, VoxelSphere,
-        VoxelSphereUnion,
+        self, DynamicVoxels, GeneratedVoxelObject, GeneratedVoxelTerrain, GradientNoiseVoxelTypes,
+        MultifractalNoiseSDFModification, SameVoxelType, VoxModelObject, VoxelBox, VoxelCapsule,
+        VoxelSphere, VoxelSphereUnion, VoxelizedMeshObject,
     },
+    terrain::{HasVoxelTerrain, VoxelTerrain, VoxelTerrainID},
 };
 use parking_lot::RwLock;
 
@@ -73,37 +83,41 @@ pub fn setup_voxel_objects_for_new_entities(
          generated_voxel_object: &GeneratedVoxelObject,
          voxel_type: &SameVoxelType|
          -> Result<HasVoxelObject> {
-            let generator_id = generated_voxel_object.generator_id;
-
-            let generator = resource_manager
-                .voxel_generators
-                .get(generator_id)
-                .ok_or_else(|| {
-                    anyhow!("Tried to setup voxel object using missing generator {generator_id}")
-                })?;
-
-            let arena = ArenaPool::get_arena();
+            let pregenerated_object_key = PregeneratedVoxelObjectKey::new(
+                generated_voxel_object.generator_id,
+                generated_voxel_object.voxel_extent,
+                generated_voxel_object.scale_factor,
+                generated_voxel_object.seed,
+                voxel_type.voxel_type_name_hash(),
+            );
 
-            let graph = generator
-                .sdf_graph
-                .build_in(&arena, generated_voxel_object.scale_factor, generated_voxel_object.seed)
+            if let Some(encoded_object) = resource_manager
+                .pregenerated_voxel_objects
+                .get(&pregenerated_object_key)
+            {
+                setup::setup_stored_voxel_object(
+                    &mut voxel_manager.object_manager,
+                    &mut voxel_manager.object_buffer_pool,
+                    encoded_object,
+                    entity_id,
+                )
                 .with_context(|| {
-                    format!("Failed to compile meta SDF graph into atomic graph for voxel generator {generator_id}")
+                    format!(
+                        "Failed to read pregenerated voxel object for voxel generator {}",
+                        generated_voxel_object.generator_id
+                    )
                 })?;
 
-            let sdf_generator = graph.build_in(Global).with_context(|| {
-                format!("Failed to build SDF generator from atomic graph for voxel generator {generator_id}")
-            })?;
-
-            let voxel_type_generator = voxel_type
-                .create_generator(&resource_manager.voxel_types)?
-                .into();
+                return Ok(HasVoxelObject);
+            }
 
-            let generator = SDFVoxelGenerator::new(
-                generated_voxel_object.voxel_extent,
-                sdf_generator,
-                voxel_type_generator,
-            );
+            let generator = create_generator_for_voxel_object(
+                &resource_manager,
+                generated_voxel_object,
+                voxel_type
+                    .create_generator(&resource_manager.voxel_types)?
+                    .into(),
+            )?;
 
             setup::setup_voxel_object(
                 ctx.thread_pool,
@@ -301,37 +315,13 @@ pub fn setup_voxel_objects_for_new_entities(
          generated_voxel_object: &GeneratedVoxelObject,
          voxel_types: &GradientNoiseVoxelTypes|
          -> Result<HasVoxelObject> {
-            let generator_id = generated_voxel_object.generator_id;
-
-            let generator = resource_manager
-                .voxel_generators
-                .get(generator_id)
-                .ok_or_else(|| {
-                    anyhow!("Tried to setup voxel object using missing generator {generator_id}")
-                })?;
-
-            let arena = ArenaPool::get_arena();
-
-            let graph = generator
-                .sdf_graph
-                .build_in(&arena, generated_voxel_object.scale_factor, generated_voxel_object.seed)
-                .with_context(|| {
-                    format!("Failed to compile meta SDF graph into atomic graph for voxel generator {generator_id}")
-                })?;
-
-            let sdf_generator = graph.build_in(Global).with_context(|| {
-                format!("Failed to build SDF generator from atomic graph for voxel generator {generator_id}")
-            })?;
-
-            let voxel_type_generator = voxel_types
-                .create_generator(&resource_manager.voxel_types)?
-                .into();
-
-            let generator = SDFVoxelGenerator::new(
-                generated_voxel_object.voxel_extent,
-                sdf_generator,
-                voxel_type_generator,
-            );
+            let generator = create_generator_for_voxel_object(
+                &resource_manager,
+                generated_voxel_object,
+                voxel_types
+                    .create_generator(&resource_manager.voxel_types)?
+                    .into(),
+            )?;
 
             setup::setup_voxel_object(
                 ctx.thread_pool,
@@ -518,6 +508,156 @@ pub fn setup_voxel_objects_for_new_entities(
         ![HasVoxelObject]
     )?;
 
+    setup!(
+        {
+            let resource_manager = resource_manager.oread();
+            let scene = scene.oread();
+            let voxel_manager = &mut **scene.voxel_manager().owrite();
+        },
+        entities,
+        |entity_id: EntityID,
+         voxelized_mesh_object: &VoxelizedMeshObject|
+         -> Result<HasVoxelObject> {
+            let voxelized_mesh_id = voxelized_mesh_object.voxelized_mesh_id;
+
+            let voxelized_mesh = resource_manager
+                .voxelized_meshes
+                .get(voxelized_mesh_id)
+                .ok_or_else(|| {
+                    anyhow!("Tried to setup voxel object using missing voxelized mesh {voxelized_mesh_id}")
+                })?;
+
+            let generator = MeshVoxelGenerator::for_voxelized_mesh(
+                voxelized_mesh_object.voxel_extent,
+                voxelized_mesh_object.scale_factor,
+                voxelized_mesh,
+                &resource_manager.triangle_meshes,
+                &resource_manager.voxel_types,
+            )
+            .with_context(|| {
+                format!("Failed to create voxel generator for voxelized mesh {voxelized_mesh_id}")
+            })?;
+
+            setup::setup_voxel_object(
+                ctx.thread_pool,
+                &mut voxel_manager.object_manager,
+                &mut voxel_manager.object_buffer_pool,
+                &generator,
+                entity_id,
+            )?;
+
+            Ok(HasVoxelObject)
+        },
+        ![HasVoxelObject]
+    )?;
+
+    setup!(
+        {
+            let resource_manager = resource_manager.oread();
+            let scene = scene.oread();
+            let voxel_manager = &mut **scene.voxel_manager().owrite();
+        },
+        entities,
+        |entity_id: EntityID, vox_model_object: &VoxModelObject| -> Result<HasVoxelObject> {
+            let vox_model_id = vox_model_object.vox_model_id;
+
+            let vox_model = resource_manager
+                .vox_models
+                .get(vox_model_id)
+                .ok_or_else(|| {
+                    anyhow!("Tried to setup voxel object using missing voxel model {vox_model_id}")
+                })?;
+
+            let generator = VoxVoxelGenerator::new(
+                vox_model_object.voxel_extent,
+                vox_model,
+                &VoxPaletteVoxelTypes::from_registry(&resource_manager.voxel_types),
+                vox_model_object.smoothing_iterations,
+            );
+
+            setup::setup_voxel_object(
+                ctx.thread_pool,
+                &mut voxel_manager.object_manager,
+                &mut voxel_manager.object_buffer_pool,
+                &generator,
+                entity_id,
+            )?;
+
+            Ok(HasVoxelObject)
+        },
+        ![HasVoxelObject]
+    )?;
+
+    setup!(
+        {
+            let resource_manager = resource_manager.oread();
+            let scene = scene.oread();
+            let mut voxel_manager = scene.voxel_manager().owrite();
+        },
+        entities,
+        |entity_id: EntityID,
+         generated_voxel_terrain: &GeneratedVoxelTerrain,
+         voxel_type: &SameVoxelType,
+         frame: Option<&ReferenceFrame>,
+         collidable: Option<&VoxelCollidable>|
+         -> Result<HasVoxelTerrain> {
+            let voxel_type_generator = voxel_type
+                .create_generator(&resource_manager.voxel_types)?
+                .into();
+
+            let generator = create_generator_for_voxel_terrain(
+                &resource_manager,
+                generated_voxel_terrain,
+                voxel_type_generator,
+            )?;
+
+            let origin = frame.map_or_else(Point3::origin, |frame| frame.position.aligned());
+
+            voxel_manager.terrain_manager_mut().add_terrain(
+                VoxelTerrainID::from_entity_id(entity_id),
+                VoxelTerrain::new(generator, origin, collidable.copied()),
+            )?;
+
+            Ok(HasVoxelTerrain)
+        },
+        ![HasVoxelTerrain]
+    )?;
+
+    setup!(
+        {
+            let resource_manager = resource_manager.oread();
+            let scene = scene.oread();
+            let mut voxel_manager = scene.voxel_manager().owrite();
+        },
+        entities,
+        |entity_id: EntityID,
+         generated_voxel_terrain: &GeneratedVoxelTerrain,
+         voxel_types: &GradientNoiseVoxelTypes,
+         frame: Option<&ReferenceFrame>,
+         collidable: Option<&VoxelCollidable>|
+         -> Result<HasVoxelTerrain> {
+            let voxel_type_generator = voxel_types
+                .create_generator(&resource_manager.voxel_types)?
+                .into();
+
+            let generator = create_generator_for_voxel_terrain(
+                &resource_manager,
+                generated_voxel_terrain,
+                voxel_type_generator,
+            )?;
+
+            let origin = frame.map_or_else(Point3::origin, |frame| frame.position.aligned());
+
+            voxel_manager.terrain_manager_mut().add_terrain(
+                VoxelTerrainID::from_entity_id(entity_id),
+                VoxelTerrain::new(generator, origin, collidable.copied()),
+            )?;
+
+            Ok(HasVoxelTerrain)
+        },
+        ![HasVoxelTerrain]
+    )?;
+
     setup!(
         {
             let resource_manager = resource_manager.oread();
@@ -556,6 +696,56 @@ pub fn setup_voxel_objects_for_new_entities(
     Ok(())
 }
 
+fn create_generator_for_voxel_object(
+    resource_manager: &ResourceManager,
+    generated_voxel_object: &GeneratedVoxelObject,
+    voxel_type_generator: VoxelTypeGenerator,
+) -> Result<SDFVoxelGenerator> {
+    let generator_id = generated_voxel_object.generator_id;
+
+    let generator = resource_manager
+        .voxel_generators
+        .get(generator_id)
+        .ok_or_else(|| {
+            anyhow!("Tried to setup voxel object using missing generator {generator_id}")
+        })?;
+
+    generator
+        .create_sdf_voxel_generator(
+            generated_voxel_object.voxel_extent,
+            generated_voxel_object.scale_factor,
+            generated_voxel_object.seed,
+            voxel_type_generator,
+            &resource_manager.voxel_types,
+        )
+        .with_context(|| format!("Failed to create generator for voxel generator {generator_id}"))
+}
+
+fn create_generator_for_voxel_terrain(
+    resource_manager: &ResourceManager,
+    generated_voxel_terrain: &GeneratedVoxelTerrain,
+    voxel_type_generator: VoxelTypeGenerator,
+) -> Result<SDFVoxelGenerator> {
+    let generator_id = generated_voxel_terrain.generator_id;
+
+    let generator = resource_manager
+        .voxel_generators
+        .get(generator_id)
+        .ok_or_else(|| {
+            anyhow!("Tried to setup voxel terrain using missing generator {generator_id}")
+        })?;
+
+    generator
+        .create_sdf_voxel_generator(
+            generated_voxel_terrain.voxel_extent,
+            generated_voxel_terrain.scale_factor,
+            generated_voxel_terrain.seed,
+            voxel_type_generator,
+            &resource_manager.voxel_types,
+        )
+        .with_context(|| format!("Failed to create generator for voxel generator {generator_id}"))
+}
+
 pub fn setup_voxel_interaction_for_new_entities(
     scene: &RwLock<Scene>,
     entities: &mut PrototypeEntities,
@@ -639,6 +829,7 @@ pub fn cleanup_voxel_state_for_removed_entity(
 ) {
     cleanup_voxel_object_for_removed_entity(scene, entity_id, entity);
     cleanup_voxel_interaction_for_removed_entity(scene, entity_id, entity);
+    cleanup_voxel_terrain_for_removed_entity(scene, entity_id, entity);
 }
 
 fn cleanup_voxel_object_for_removed_entity(
@@ -686,3 +877,18 @@ fn cleanup_voxel_interaction_for_removed_entity(
             .remove_mutual_absorption_processes_involving_entity(entity_id);
     }
 }
+
+fn cleanup_voxel_terrain_for_removed_entity(
+    scene: &RwLock<Scene>,
+    entity_id: EntityID,
+    entity: &impact_ecs::world::EntityEntry<'_>,
+) {
+    if entity.has_component::<HasVoxelTerrain>() {
+        let scene = scene.oread();
+        let mut voxel_manager = scene.voxel_manager().owrite();
+        let terrain_id = VoxelTerrainID::from_entity_id(entity_id);
+        voxel_manager
+            .terrain_manager_mut()
+            .remove_terrain(terrain_id);
+    }
+}
@@ -5,10 +5,13 @@ use crate::{
     runtime::tasks::{RuntimeContext, RuntimeTaskScheduler},
 };
 use anyhow::Result;
+use impact_model::{InstanceFeature, ModelInstanceID, skinning::InstanceJointPaletteOffset};
 use impact_profiling::instrument_task;
 use impact_scene::buffer_model_instances_and_bound_lights;
 use impact_scheduling::{define_execution_tag, define_task};
-use impact_voxel::interaction::systems::ECSVoxelObjectFracturingContext;
+use impact_voxel::interaction::systems::{
+    ECSVoxelObjectFracturingContext, ECSVoxelObjectWeldingContext,
+};
 
 // =============================================================================
 // EXECUTION TAGS
@@ -157,6 +160,8 @@ define_task!(
 
             renderer.load_recorded_timing_results()?;
 
+            renderer.load_completed_entity_pick()?;
+
             renderer.downgrade().update_exposure()
         })
     }
@@ -221,6 +226,7 @@ define_task!(
             engine.execute_enqueued_physics_commands()?;
             engine.execute_enqueued_physics_admin_commands()?;
             engine.execute_enqueued_control_admin_commands()?;
+            engine.execute_enqueued_export_admin_commands()?;
             engine.execute_enqueued_instrumentation_admin_commands()?;
             engine.execute_enqueued_game_loop_admin_commands()?;
             engine.execute_enqueued_gizmo_admin_commands()?;
@@ -455,11 +461,11 @@ define_task!(
         let engine = ctx.engine();
         instrument_task!("Running collision triggered logic", engine.task_timer(), {
             let ecs_world = engine.ecs_world().oread();
+            let resource_manager = engine.resource_manager().oread();
             let scene = engine.scene().oread();
             let voxel_manager = &mut **scene.voxel_manager().owrite();
             let voxel_object_manager = &voxel_manager.object_manager;
             let interaction_manager = &mut voxel_manager.interaction_manager;
-            let fracturing_manager = interaction_manager.fracturing_manager_mut();
             let simulator = engine.simulator().oread();
             if !simulator.enabled() {
                 return Ok(());
@@ -473,15 +479,39 @@ define_task!(
                 ecs_world: &ecs_world,
             };
 
-            fracturing_manager.handle_fracturing_impacts(
-                &fracturing_context,
+            interaction_manager
+                .fracturing_manager
+                .handle_fracturing_impacts(
+                    &fracturing_context,
+                    voxel_object_manager,
+                    &rigid_body_manager,
+                    constraint_manager,
+                    &collision_world,
+                    time_step_duration,
+                );
+
+            let welding_context = ECSVoxelObjectWeldingContext {
+                ecs_world: &ecs_world,
+            };
+
+            interaction_manager.welding_manager.handle_resting_contacts(
+                &welding_context,
                 voxel_object_manager,
+                &interaction_manager.fracturing_manager,
                 &rigid_body_manager,
-                constraint_manager,
                 &collision_world,
                 time_step_duration,
             );
 
+            interaction_manager
+                .thermal_manager
+                .stage_impact_heat_deposits(
+                    voxel_object_manager,
+                    &resource_manager.voxel_types,
+                    &rigid_body_manager,
+                    &collision_world,
+                );
+
             Ok(())
         })
     }
@@ -769,6 +799,63 @@ define_task!(
     }
 );
 
+define_task!(
+    /// Advances the playback of all animation players, computes the joint
+    /// palettes of the animated skeletons and updates the palette offsets of
+    /// the associated skinned model instances.
+    [pub] AdvanceAnimations,
+    depends_on = [
+        // We need to include the newly created entities.
+        HandleStagedEntities
+    ],
+    execute_on = [RenderingTag],
+    |ctx: &RuntimeContext| {
+        let engine = ctx.engine();
+        instrument_task!("Advancing animations", engine.task_timer(), {
+            // Animations advance with simulated time, so that they pause along
+            // with the simulation
+            let time_step_duration = {
+                let simulator = engine.simulator().oread();
+                if simulator.enabled() {
+                    simulator.scaled_time_step_duration()
+                } else {
+                    0.0
+                }
+            };
+            let ecs_world = engine.ecs_world().oread();
+            let resource_manager = engine.resource_manager().oread();
+            let scene = engine.scene().oread();
+            let mut joint_palette_manager = scene.joint_palette_manager().owrite();
+            let mut model_instance_manager = scene.model_instance_manager().owrite();
+            let scene_graph = scene.scene_graph().oread();
+
+            impact_animation::systems::advance_animation_players(
+                &ecs_world,
+                &resource_manager.skeletons,
+                &resource_manager.animation_clips,
+                &mut joint_palette_manager,
+                time_step_duration,
+                |entity_id, offset| {
+                    let Some(feature_id) = scene_graph
+                        .model_instance_nodes()
+                        .get_node(ModelInstanceID::from_entity_id(entity_id))
+                        .and_then(|node| {
+                            node.get_rendering_feature_id_of_type(
+                                InstanceJointPaletteOffset::FEATURE_TYPE_ID,
+                            )
+                        })
+                    else {
+                        return;
+                    };
+                    *model_instance_manager.feature_mut::<InstanceJointPaletteOffset>(feature_id) =
+                        InstanceJointPaletteOffset::new(offset);
+                },
+            );
+            Ok(())
+        })
+    }
+);
+
 define_task!(
     /// Determines which model instances are visible, updates their
     /// model-to-camera space transforms and buffer their features for
@@ -788,7 +875,9 @@ define_task!(
         BuildBoundingVolumeHierarchy,
         // The buffers must have been cleared from the previous frame before we
         // write into them.
-        ClearModelInstanceBuffers
+        ClearModelInstanceBuffers,
+        // The joint palette offsets of skinned instances must be up to date.
+        AdvanceAnimations
     ],
     execute_on = [RenderingTag],
     |ctx: &RuntimeContext| {
@@ -802,7 +891,14 @@ define_task!(
             let mut model_instance_manager = scene.model_instance_manager().owrite();
             let intersection_manager = scene.intersection_manager().oread();
             let scene_graph = scene.scene_graph().oread();
-            let shadow_mapping_config = engine.renderer().oread().shadow_mapping_config().clone();
+            let (shadow_mapping_config, lod_selection_config, entity_picking_enabled) = {
+                let renderer = engine.renderer().oread();
+                (
+                    renderer.shadow_mapping_config().clone(),
+                    renderer.lod_selection_config().clone(),
+                    renderer.basic_config().entity_picking_enabled,
+                )
+            };
 
             buffer_model_instances_and_bound_lights(
                 engine.task_timer(),
@@ -814,6 +910,8 @@ define_task!(
                 camera,
                 current_frame_number,
                 &shadow_mapping_config,
+                &lod_selection_config,
+                entity_picking_enabled,
             );
         }
 
@@ -902,10 +1000,60 @@ define_task!(
 // GPU RESOURCE SYNCHRONIZATION (of updates from current frame, rendered next frame)
 // =============================================================================
 
+define_task!(
+    /// Reloads resources whose source files have changed, if hot reloading is
+    /// enabled.
+    [pub] ReloadChangedResources,
+    depends_on = [],
+    execute_on = [RenderingTag],
+    |ctx: &RuntimeContext| {
+        let engine = ctx.engine();
+        instrument_task!("Reloading changed resources", engine.task_timer(), {
+            if let Some(resource_reloader) = engine.resource_reloader() {
+                let mut resource_manager = engine.resource_manager().owrite();
+                resource_reloader
+                    .olock()
+                    .reload_changed_resources(&mut resource_manager);
+            }
+            Ok(())
+        })
+    }
+);
+
+define_task!(
+    /// Evicts meshes, materials and textures that are no longer referenced by
+    /// any model instance or other resource.
+    [pub] EvictUnreferencedResources,
+    depends_on = [
+        // The application may create or remove entities, which can add or
+        // remove resource references. Same for staged entities.
+        CallApp,
+        HandleInputEvents,
+        HandleStagedEntities,
+        // Reloaded resources replace the existing ones, which must be
+        // accounted for when tracking references.
+        ReloadChangedResources
+    ],
+    execute_on = [RenderingTag],
+    |ctx: &RuntimeContext| {
+        let engine = ctx.engine();
+        instrument_task!("Evicting unreferenced resources", engine.task_timer(), {
+            let mut resource_manager = engine.resource_manager().owrite();
+            let scene = engine.scene().oread();
+            let skybox = scene.skybox().oread();
+            resource_manager.evict_unreferenced_resources(skybox.as_ref());
+            Ok(())
+        })
+    }
+);
+
 define_task!(
     /// Synchronizes GPU resources for textures.
     [pub] SyncTextureGPUResources,
-    depends_on = [],
+    depends_on = [
+        // Evicted textures must be removed from the GPU.
+        EvictUnreferencedResources
+    ],
     execute_on = [RenderingTag],
     |ctx: &RuntimeContext| {
         let engine = ctx.engine();
@@ -923,7 +1071,9 @@ define_task!(
         // resources. Same for staged entities.
         CallApp,
         HandleInputEvents,
-        HandleStagedEntities
+        HandleStagedEntities,
+        // Evicted meshes must be removed from the GPU.
+        EvictUnreferencedResources
     ],
     execute_on = [RenderingTag],
     |ctx: &RuntimeContext| {
@@ -943,6 +1093,8 @@ define_task!(
         CallApp,
         HandleInputEvents,
         HandleStagedEntities,
+        // Evicted materials must be removed from the GPU.
+        EvictUnreferencedResources,
         // Some materials need access to the current textures.
         SyncTextureGPUResources
     ],
@@ -961,7 +1113,9 @@ define_task!(
     depends_on = [
         // Both the skybox and voxel materials need access to the current
         // textures.
-        SyncTextureGPUResources
+        SyncTextureGPUResources,
+        // An atmospheric skybox needs the current direction of the sun.
+        SyncLights
     ],
     execute_on = [RenderingTag],
     |ctx: &RuntimeContext| {
@@ -981,6 +1135,9 @@ define_task!(
         SyncSceneCameraViewTransform,
         // Light uniforms must be synced with current light state.
         SyncLights,
+        // The ambient light from an atmospheric skybox is updated when syncing
+        // the skybox.
+        SyncMiscGPUResources,
         // The current voxel meshes must be synced with various GPU buffers.
         UpdateVoxelObjectMeshes,
         // The light uniforms must be updated on the GPU and the current model
@@ -1127,6 +1284,236 @@ define_task!(
     }
 );
 
+define_task!(
+    /// Welds pairs of weldable voxel objects that have been staged for welding
+    /// after resting in contact.
+    ///
+    /// The changes will be visible when the next frame is rendered, not the
+    /// current one.
+    [pub] ExecuteVoxelObjectWelding,
+    depends_on = [
+        // Objects modified or created by fracturing should be welded in their
+        // updated state.
+        ExecuteVoxelObjectFracturing,
+        // We want to operate on the rigid body states for the current frame,
+        // which is after the simulation step.
+        SyncRigidBodyComponents,
+        // Newly added or removed voxel object entities should be included.
+        // (This is in practice covered by the other dependencies).
+        HandleStagedEntities
+    ],
+    execute_on = [PhysicsTag],
+    |ctx: &RuntimeContext| {
+        let engine = ctx.engine();
+        instrument_task!("Executing voxel object welding", engine.task_timer(), {
+            let mut entity_stager = engine.entity_stager().olock();
+            let ecs_world = engine.ecs_world().oread();
+            let resource_manager = engine.resource_manager().oread();
+            let scene = engine.scene().oread();
+            let mut voxel_manager = scene.voxel_manager().owrite();
+            let scene_graph = scene.scene_graph().oread();
+            let simulator = engine.simulator().oread();
+            if !simulator.enabled() {
+                return Ok(());
+            }
+            let mut rigid_body_manager = simulator.rigid_body_manager().owrite();
+            let mut anchor_manager = simulator.anchor_manager().owrite();
+            let force_generator_manager = simulator.force_generator_manager().oread();
+            let collision_world = simulator.collision_world().oread();
+
+            impact_voxel::interaction::systems::execute_welds(
+                engine.component_metadata_registry(),
+                &mut entity_stager,
+                &ecs_world,
+                &scene_graph,
+                &mut voxel_manager,
+                &resource_manager.voxel_types,
+                &mut rigid_body_manager,
+                &mut anchor_manager,
+                &force_generator_manager,
+                &collision_world,
+            );
+
+            Ok(())
+        })
+    }
+);
+
+define_task!(
+    /// Applies heat deposited in voxel objects by absorption and impacts,
+    /// conducts heat through the objects that are not at ambient temperature
+    /// and melts away voxels that have reached their melting temperature,
+    /// spawning any resulting disconnected parts as separate objects.
+    ///
+    /// The changes will be visible when the next frame is rendered, not the
+    /// current one.
+    [pub] UpdateVoxelObjectThermalStates,
+    depends_on = [
+        // Objects modified by welding should be heated in their updated
+        // state. This also ensures that heat deposited by absorption has
+        // been staged.
+        ExecuteVoxelObjectWelding,
+        // We want to operate on the rigid body states for the current frame,
+        // which is after the simulation step.
+        SyncRigidBodyComponents,
+        // Newly added or removed voxel object entities should be included.
+        // (This is in practice covered by the other dependencies).
+        HandleStagedEntities
+    ],
+    execute_on = [PhysicsTag],
+    |ctx: &RuntimeContext| {
+        let engine = ctx.engine();
+        instrument_task!("Updating voxel object thermal states", engine.task_timer(), {
+            let mut entity_id_manager = engine.entity_id_manager().olock();
+            let mut entity_stager = engine.entity_stager().olock();
+            let ecs_world = engine.ecs_world().oread();
+            let resource_manager = engine.resource_manager().oread();
+            let scene = engine.scene().oread();
+            let mut voxel_manager = scene.voxel_manager().owrite();
+            let scene_graph = scene.scene_graph().oread();
+            let simulator = engine.simulator().oread();
+            if !simulator.enabled() {
+                return Ok(());
+            }
+            let mut rigid_body_manager = simulator.rigid_body_manager().owrite();
+            let mut anchor_manager = simulator.anchor_manager().owrite();
+            let force_generator_manager = simulator.force_generator_manager().oread();
+            let collision_world = simulator.collision_world().oread();
+            let time_step_duration = simulator.scaled_time_step_duration();
+
+            impact_voxel::interaction::systems::update_thermal_states(
+                engine.component_metadata_registry(),
+                &mut entity_id_manager,
+                &mut entity_stager,
+                &ecs_world,
+                &scene_graph,
+                &mut voxel_manager,
+                &resource_manager.voxel_types,
+                &mut rigid_body_manager,
+                &mut anchor_manager,
+                &force_generator_manager,
+                &collision_world,
+                time_step_duration,
+            );
+
+            Ok(())
+        })
+    }
+);
+
+define_task!(
+    /// Checks the structural integrity of voxel objects that have been staged
+    /// for it, breaking voxels that can not carry their load under gravity
+    /// and spawning the resulting disconnected parts as separate objects.
+    ///
+    /// The changes will be visible when the next frame is rendered, not the
+    /// current one.
+    [pub] ExecuteVoxelObjectIntegrityChecks,
+    depends_on = [
+        // Objects modified by welding or melting should be checked in their
+        // updated state.
+        UpdateVoxelObjectThermalStates,
+        // We want to use the contacts and rigid body states for the current
+        // frame, which is after the simulation step.
+        SyncRigidBodyComponents,
+        // Newly added or removed voxel object entities should be included.
+        // (This is in practice covered by the other dependencies).
+        HandleStagedEntities
+    ],
+    execute_on = [PhysicsTag],
+    |ctx: &RuntimeContext| {
+        let engine = ctx.engine();
+        instrument_task!("Executing voxel object integrity checks", engine.task_timer(), {
+            let mut entity_id_manager = engine.entity_id_manager().olock();
+            let mut entity_stager = engine.entity_stager().olock();
+            let ecs_world = engine.ecs_world().oread();
+            let resource_manager = engine.resource_manager().oread();
+            let scene = engine.scene().oread();
+            let mut voxel_manager = scene.voxel_manager().owrite();
+            let scene_graph = scene.scene_graph().oread();
+            let simulator = engine.simulator().oread();
+            if !simulator.enabled() {
+                return Ok(());
+            }
+            let mut rigid_body_manager = simulator.rigid_body_manager().owrite();
+            let mut anchor_manager = simulator.anchor_manager().owrite();
+            let force_generator_manager = simulator.force_generator_manager().oread();
+            let collision_world = simulator.collision_world().oread();
+
+            impact_voxel::interaction::systems::execute_integrity_checks(
+                engine.component_metadata_registry(),
+                &mut entity_id_manager,
+                &mut entity_stager,
+                &ecs_world,
+                &scene_graph,
+                &mut voxel_manager,
+                &resource_manager.voxel_types,
+                &mut rigid_body_manager,
+                &mut anchor_manager,
+                &force_generator_manager,
+                &collision_world,
+            );
+
+            Ok(())
+        })
+    }
+);
+
+define_task!(
+    /// Generates voxel terrain tiles that have come within the load distance
+    /// of the camera, discards tiles that have moved beyond the unload distance
+    /// and updates which tiles have collidables.
+    ///
+    /// The changes will be visible when the next frame is rendered, not the
+    /// current one.
+    [pub] UpdateVoxelTerrains,
+    depends_on = [
+        // The generated tiles should be included in the next frame, so the
+        // mesh updates for this frame must be completed before we add new
+        // voxel objects.
+        UpdateVoxelObjectMeshes,
+        // We need the current camera position.
+        SyncSceneCameraViewTransform,
+        // Tile entities staged for creation in the previous update must exist
+        // before we can give them collidables.
+        HandleStagedEntities
+    ],
+    execute_on = [PhysicsTag],
+    |ctx: &RuntimeContext| {
+        let engine = ctx.engine();
+        instrument_task!("Updating voxel terrains", engine.task_timer(), {
+            let mut entity_id_manager = engine.entity_id_manager().olock();
+            let mut entity_stager = engine.entity_stager().olock();
+            let ecs_world = engine.ecs_world().oread();
+            let scene = engine.scene().oread();
+            let observer_position = {
+                let camera_manager = scene.camera_manager().oread();
+                let Some(camera) = camera_manager.active_camera() else {
+                    return Ok(());
+                };
+                camera.compute_world_space_position()
+            };
+            let mut voxel_manager = scene.voxel_manager().owrite();
+            if voxel_manager.terrain_manager().terrain_count() == 0 {
+                return Ok(());
+            }
+            let simulator = engine.simulator().oread();
+            let mut collision_world = simulator.collision_world().owrite();
+
+            impact_voxel::terrain::systems::update_terrains(
+                &mut entity_id_manager,
+                &mut entity_stager,
+                &ecs_world,
+                &mut voxel_manager,
+                &mut collision_world,
+                &observer_position,
+            );
+
+            Ok(())
+        })
+    }
+);
+
 // =============================================================================
 // TASK REGISTRATION
 // =============================================================================
@@ -1193,6 +1580,7 @@ pub fn register_all_tasks(task_scheduler: &mut RuntimeTaskScheduler) -> Result<(
 
     // MODEL INSTANCE BUFFERING (for current frame)
     task_scheduler.register_task(ClearModelInstanceBuffers)?;
+    task_scheduler.register_task(AdvanceAnimations)?;
     task_scheduler.register_task(BufferModelInstancesAndBoundLights)?;
 
     // GIZMO PROCESSING (for current frame)
@@ -1200,6 +1588,8 @@ pub fn register_all_tasks(task_scheduler: &mut RuntimeTaskScheduler) -> Result<(
     task_scheduler.register_task(BufferGizmoInstances)?;
 
     // GPU RESOURCE SYNCHRONIZATION (of updates from current frame, rendered next frame)
+    task_scheduler.register_task(ReloadChangedResources)?;
+    task_scheduler.register_task(EvictUnreferencedResources)?;
     task_scheduler.register_task(SyncTextureGPUResources)?;
     task_scheduler.register_task(SyncMeshGPUResources)?;
     task_scheduler.register_task(SyncMaterialGPUResources)?;
@@ -1208,5 +1598,9 @@ pub fn register_all_tasks(task_scheduler: &mut RuntimeTaskScheduler) -> Result<(
 
     // VOXEL PROCESSING (for next frame)
     task_scheduler.register_task(ApplyVoxelAbsorption)?;
-    task_scheduler.register_task(ExecuteVoxelObjectFracturing)
+    task_scheduler.register_task(ExecuteVoxelObjectFracturing)?;
+    task_scheduler.register_task(ExecuteVoxelObjectWelding)?;
+    task_scheduler.register_task(UpdateVoxelObjectThermalStates)?;
+    task_scheduler.register_task(ExecuteVoxelObjectIntegrityChecks)?;
+    task_scheduler.register_task(UpdateVoxelTerrains)
 }
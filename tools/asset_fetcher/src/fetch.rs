@@ -169,7 +169,7 @@ fn looks_like_zip(content_type: Option<&str>, magic_bytes: &[u8; 4], uri: &str)
 ///
 /// Preserves the directory structure from the archive and creates necessary
 /// parent directories.
-fn extract_zip_archive<R>(archive_file: R, target_dir: &Path) -> Result<()>
+pub fn extract_zip_archive<R>(archive_file: R, target_dir: &Path) -> Result<()>
 where
     R: io::Read + io::Seek,
 {
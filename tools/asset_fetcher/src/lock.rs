@@ -0,0 +1,417 @@
+//! Lockfile recording the exact content of fetched assets.
+
+use crate::asset::Asset;
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Record of the resolved content of a set of fetched assets, used to check
+/// or reproduce an asset directory without network access.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetLockfile {
+    pub assets: Vec<LockedAsset>,
+}
+
+/// The resolved content of a single fetched asset.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedAsset {
+    /// User-specified identifier for the asset, which is also the name of its
+    /// directory.
+    pub name: String,
+    /// Name of the provider the asset was fetched from.
+    pub provider: String,
+    /// The provider's ID for the asset.
+    pub id: String,
+    /// The resolution the asset was fetched in.
+    pub resolution: String,
+    /// SPDX identifier for the license of the asset.
+    pub license: String,
+    /// All files in the asset directory, sorted by path.
+    pub files: Vec<LockedFile>,
+}
+
+/// A file in the directory of a locked asset.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedFile {
+    /// Path of the file relative to the asset directory, with `/` as
+    /// separator.
+    pub path: String,
+    /// Size of the file in bytes.
+    pub size: u64,
+    /// Hex-encoded SHA-256 hash of the file content.
+    pub sha256: String,
+}
+
+/// A discrepancy between a lockfile and an asset directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerificationProblem {
+    /// The directory of a locked asset does not exist.
+    MissingAsset { asset: String },
+    /// A locked file does not exist in the asset directory.
+    MissingFile { asset: String, path: String },
+    /// The asset directory contains a file that is not locked.
+    UnexpectedFile { asset: String, path: String },
+    /// A file has a different size than recorded in the lockfile.
+    SizeMismatch {
+        asset: String,
+        path: String,
+        expected: u64,
+        actual: u64,
+    },
+    /// A file has a different hash than recorded in the lockfile.
+    HashMismatch { asset: String, path: String },
+}
+
+impl AssetLockfile {
+    /// Reads the lockfile at the given path.
+    pub fn read(path: &Path) -> Result<Self> {
+        impact_io::parse_ron_file(path)
+            .with_context(|| format!("Failed to read lockfile {}", path.display()))
+    }
+
+    /// Writes the lockfile to the given path.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        impact_io::write_ron_file(self, path)
+            .with_context(|| format!("Failed to write lockfile {}", path.display()))
+    }
+
+    /// Returns the locked asset with the given name, if present.
+    pub fn get(&self, name: &str) -> Option<&LockedAsset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+
+    /// Inserts the given locked asset, replacing any existing entry with the
+    /// same name. The assets are kept sorted by name.
+    pub fn insert(&mut self, locked_asset: LockedAsset) {
+        match self
+            .assets
+            .binary_search_by(|asset| asset.name.cmp(&locked_asset.name))
+        {
+            Ok(idx) => self.assets[idx] = locked_asset,
+            Err(idx) => self.assets.insert(idx, locked_asset),
+        }
+    }
+
+    /// Checks every locked asset against its directory in `asset_dir`,
+    /// without any network access.
+    ///
+    /// # Returns
+    /// All discrepancies that were found.
+    pub fn verify(&self, asset_dir: &Path) -> Result<Vec<VerificationProblem>> {
+        let mut problems = Vec::new();
+        for locked_asset in &self.assets {
+            problems.extend(locked_asset.verify(&asset_dir.join(&locked_asset.name))?);
+        }
+        Ok(problems)
+    }
+}
+
+impl LockedAsset {
+    /// Records the current content of the directory of the given asset.
+    pub fn from_asset_dir(asset: &Asset, asset_dir: &Path) -> Result<Self> {
+        if !asset_dir.is_dir() {
+            bail!("Missing asset directory: {}", asset_dir.display());
+        }
+
+        let files = list_files(asset_dir)?
+            .into_iter()
+            .map(|path| {
+                let file_path = asset_dir.join(&path);
+                let (size, sha256) = compute_size_and_hash(&file_path)?;
+                Ok(LockedFile { path, size, sha256 })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            name: asset.name.clone(),
+            provider: asset.info.provider_name().to_string(),
+            id: asset.info.id().to_string(),
+            resolution: asset.info.resolution().to_string(),
+            license: asset.info.license().to_string(),
+            files,
+        })
+    }
+
+    /// Checks the given directory against the locked content of the asset.
+    ///
+    /// # Returns
+    /// All discrepancies that were found.
+    pub fn verify(&self, asset_dir: &Path) -> Result<Vec<VerificationProblem>> {
+        if !asset_dir.is_dir() {
+            return Ok(vec![VerificationProblem::MissingAsset {
+                asset: self.name.clone(),
+            }]);
+        }
+
+        let present_files = list_files(asset_dir)?;
+
+        let mut problems = Vec::new();
+
+        for file in &self.files {
+            if present_files.binary_search(&file.path).is_err() {
+                problems.push(VerificationProblem::MissingFile {
+                    asset: self.name.clone(),
+                    path: file.path.clone(),
+                });
+                continue;
+            }
+
+            let (size, sha256) = compute_size_and_hash(&asset_dir.join(&file.path))?;
+
+            if size != file.size {
+                problems.push(VerificationProblem::SizeMismatch {
+                    asset: self.name.clone(),
+                    path: file.path.clone(),
+                    expected: file.size,
+                    actual: size,
+                });
+            } else if sha256 != file.sha256 {
+                problems.push(VerificationProblem::HashMismatch {
+                    asset: self.name.clone(),
+                    path: file.path.clone(),
+                });
+            }
+        }
+
+        for path in present_files {
+            if !self.files.iter().any(|file| file.path == path) {
+                problems.push(VerificationProblem::UnexpectedFile {
+                    asset: self.name.clone(),
+                    path,
+                });
+            }
+        }
+
+        Ok(problems)
+    }
+}
+
+impl fmt::Display for VerificationProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingAsset { asset } => write!(f, "Asset '{asset}' is missing"),
+            Self::MissingFile { asset, path } => {
+                write!(f, "File '{path}' of asset '{asset}' is missing")
+            }
+            Self::UnexpectedFile { asset, path } => {
+                write!(f, "File '{path}' of asset '{asset}' is not in the lockfile")
+            }
+            Self::SizeMismatch {
+                asset,
+                path,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "File '{path}' of asset '{asset}' has size {actual} bytes, expected {expected} bytes"
+            ),
+            Self::HashMismatch { asset, path } => {
+                write!(f, "File '{path}' of asset '{asset}' has a different hash")
+            }
+        }
+    }
+}
+
+/// Lists the paths of all files under the given directory relative to it,
+/// with `/` as separator, in sorted order.
+fn list_files(dir: &Path) -> Result<Vec<String>> {
+    let mut paths = Vec::new();
+    let mut pending_dirs = vec![PathBuf::new()];
+
+    while let Some(relative_dir) = pending_dirs.pop() {
+        for entry in fs::read_dir(dir.join(&relative_dir))? {
+            let entry = entry?;
+            let relative_path = relative_dir.join(entry.file_name());
+
+            if entry.file_type()?.is_dir() {
+                pending_dirs.push(relative_path);
+            } else {
+                let path = relative_path
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                paths.push(path);
+            }
+        }
+    }
+
+    paths.sort();
+    Ok(paths)
+}
+
+/// Computes the size and hex-encoded SHA-256 hash of the file at the given
+/// path.
+fn compute_size_and_hash(path: &Path) -> Result<(u64, String)> {
+    let mut file = io::BufReader::new(
+        fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?,
+    );
+
+    let mut hasher = Sha256::new();
+    let size = io::copy(&mut file, &mut hasher)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+
+    let sha256 = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+
+    Ok((size, sha256))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::{
+        AssetInfo,
+        polyhaven::{self, ImageFormat, TextureAssetInfo, TextureResolution},
+    };
+
+    fn testdata_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("src/providers/testdata")
+    }
+
+    fn testdata_asset() -> Asset {
+        Asset {
+            name: "polyhaven".to_string(),
+            info: AssetInfo::PolyHaven(polyhaven::AssetInfo::Texture(TextureAssetInfo {
+                id: "brick_wall_001".to_string(),
+                resolution: TextureResolution::TwoK,
+                format: ImageFormat::Jpg,
+                components: Vec::new(),
+            })),
+        }
+    }
+
+    fn copy_testdata_asset(target_dir: &Path) -> PathBuf {
+        let source_dir = testdata_dir().join("polyhaven");
+        let asset_dir = target_dir.join("polyhaven");
+        fs::create_dir_all(&asset_dir).unwrap();
+        for path in list_files(&source_dir).unwrap() {
+            fs::copy(source_dir.join(&path), asset_dir.join(&path)).unwrap();
+        }
+        asset_dir
+    }
+
+    #[test]
+    fn test_lock_testdata_asset() {
+        let locked =
+            LockedAsset::from_asset_dir(&testdata_asset(), &testdata_dir().join("polyhaven"))
+                .unwrap();
+
+        assert_eq!(locked.provider, "PolyHaven");
+        assert_eq!(locked.id, "brick_wall_001");
+        assert_eq!(locked.resolution, "2k");
+        assert_eq!(locked.license, "CC0-1.0");
+
+        let paths: Vec<_> = locked.files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(paths, ["brick_wall_001.json", "moon_rock_01.json"]);
+
+        for file in &locked.files {
+            let metadata = fs::metadata(testdata_dir().join("polyhaven").join(&file.path)).unwrap();
+            assert_eq!(file.size, metadata.len());
+            assert_eq!(file.sha256.len(), 64);
+        }
+    }
+
+    #[test]
+    fn test_verify_unmodified_asset() {
+        let mut lockfile = AssetLockfile::default();
+        lockfile.insert(
+            LockedAsset::from_asset_dir(&testdata_asset(), &testdata_dir().join("polyhaven"))
+                .unwrap(),
+        );
+
+        assert!(lockfile.verify(&testdata_dir()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_verify_detects_modified_files() {
+        let target_dir = tempfile::tempdir().unwrap();
+        let asset_dir = copy_testdata_asset(target_dir.path());
+
+        let locked = LockedAsset::from_asset_dir(&testdata_asset(), &asset_dir).unwrap();
+
+        // Change a byte without changing the size
+        let brick_wall_path = asset_dir.join("brick_wall_001.json");
+        let mut content = fs::read(&brick_wall_path).unwrap();
+        content[0] = if content[0] == b'[' { b'{' } else { b'[' };
+        fs::write(&brick_wall_path, content).unwrap();
+
+        // Truncate one file and add another
+        fs::write(asset_dir.join("moon_rock_01.json"), "{}").unwrap();
+        fs::write(asset_dir.join("extra.txt"), "extra").unwrap();
+
+        let problems = locked.verify(&asset_dir).unwrap();
+
+        assert_eq!(
+            problems,
+            [
+                VerificationProblem::HashMismatch {
+                    asset: "polyhaven".to_string(),
+                    path: "brick_wall_001.json".to_string(),
+                },
+                VerificationProblem::SizeMismatch {
+                    asset: "polyhaven".to_string(),
+                    path: "moon_rock_01.json".to_string(),
+                    expected: locked.files[1].size,
+                    actual: 2,
+                },
+                VerificationProblem::UnexpectedFile {
+                    asset: "polyhaven".to_string(),
+                    path: "extra.txt".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_verify_detects_missing_files_and_assets() {
+        let target_dir = tempfile::tempdir().unwrap();
+        let asset_dir = copy_testdata_asset(target_dir.path());
+
+        let mut lockfile = AssetLockfile::default();
+        lockfile.insert(LockedAsset::from_asset_dir(&testdata_asset(), &asset_dir).unwrap());
+
+        let mut missing_asset = lockfile.assets[0].clone();
+        missing_asset.name = "missing".to_string();
+        lockfile.insert(missing_asset);
+
+        fs::remove_file(asset_dir.join("moon_rock_01.json")).unwrap();
+
+        let problems = lockfile.verify(target_dir.path()).unwrap();
+
+        assert_eq!(
+            problems,
+            [
+                VerificationProblem::MissingAsset {
+                    asset: "missing".to_string(),
+                },
+                VerificationProblem::MissingFile {
+                    asset: "polyhaven".to_string(),
+                    path: "moon_rock_01.json".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lockfile_roundtrip() {
+        let target_dir = tempfile::tempdir().unwrap();
+        let lockfile_path = target_dir.path().join("assets.lock.ron");
+
+        let mut lockfile = AssetLockfile::default();
+        lockfile.insert(
+            LockedAsset::from_asset_dir(&testdata_asset(), &testdata_dir().join("polyhaven"))
+                .unwrap(),
+        );
+        lockfile.write(&lockfile_path).unwrap();
+
+        assert_eq!(AssetLockfile::read(&lockfile_path).unwrap(), lockfile);
+    }
+}
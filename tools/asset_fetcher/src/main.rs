@@ -1,11 +1,13 @@
 mod asset;
 mod fetch;
+mod lock;
 mod providers;
 
-use anyhow::Result;
+use anyhow::{Result, bail};
 use asset::AssetList;
 use clap::{Parser, Subcommand};
 use impact_containers::HashSet;
+use lock::{AssetLockfile, LockedAsset};
 use std::{
     fs,
     path::{Path, PathBuf},
@@ -28,6 +30,31 @@ enum Command {
         /// Path to the asset directory that should be synchronized
         #[arg(short, long, value_name = "PATH")]
         target_dir: PathBuf,
+        /// Path to a lockfile that should be updated with the synchronized assets
+        #[arg(short, long, value_name = "PATH")]
+        lockfile: Option<PathBuf>,
+    },
+    /// Check the assets in a directory against a lockfile without network access
+    Verify {
+        /// Path to the lockfile
+        #[arg(short, long, value_name = "PATH")]
+        lockfile: PathBuf,
+        /// Path to the asset directory that should be verified
+        #[arg(short, long, value_name = "PATH")]
+        target_dir: PathBuf,
+    },
+    /// Install the assets in a lockfile from a local mirror directory
+    FromMirror {
+        /// Path to the lockfile
+        #[arg(short, long, value_name = "PATH")]
+        lockfile: PathBuf,
+        /// Path to the directory holding the mirrored asset directories or
+        /// ZIP archives
+        #[arg(short, long, value_name = "PATH")]
+        mirror_dir: PathBuf,
+        /// Path to the asset directory the assets should be installed in
+        #[arg(short, long, value_name = "PATH")]
+        target_dir: PathBuf,
     },
 }
 
@@ -38,6 +65,7 @@ fn main() -> Result<()> {
         Command::Sync {
             asset_list: asset_list_path,
             target_dir,
+            lockfile: lockfile_path,
         } => {
             let asset_list: AssetList = impact_io::parse_ron_file(asset_list_path)?;
 
@@ -49,12 +77,12 @@ fn main() -> Result<()> {
             let mut failed_assets = Vec::new();
 
             for asset in asset_list
-                .into_iter()
+                .iter()
                 .filter(|asset| !present_assets.contains(&asset.name))
             {
-                if let Err(e) = fetch::fetch_asset(&asset, &target_dir) {
+                if let Err(e) = fetch::fetch_asset(asset, &target_dir) {
                     eprintln!("Error fetching asset '{}': {}", asset.name, e);
-                    failed_assets.push(asset.name);
+                    failed_assets.push(asset.name.clone());
                 }
             }
 
@@ -65,6 +93,82 @@ fn main() -> Result<()> {
                     failed_assets.join(", ")
                 );
             }
+
+            if let Some(lockfile_path) = lockfile_path {
+                let mut lockfile = if lockfile_path.exists() {
+                    AssetLockfile::read(&lockfile_path)?
+                } else {
+                    AssetLockfile::default()
+                };
+
+                for asset in asset_list
+                    .iter()
+                    .filter(|asset| !failed_assets.contains(&asset.name))
+                {
+                    lockfile.insert(LockedAsset::from_asset_dir(
+                        asset,
+                        &target_dir.join(&asset.name),
+                    )?);
+                }
+
+                lockfile.write(&lockfile_path)?;
+            }
+        }
+        Command::Verify {
+            lockfile: lockfile_path,
+            target_dir,
+        } => {
+            let lockfile = AssetLockfile::read(&lockfile_path)?;
+
+            let problems = lockfile.verify(&target_dir)?;
+
+            for problem in &problems {
+                eprintln!("{problem}");
+            }
+
+            if !problems.is_empty() {
+                bail!(
+                    "Found {} problem(s) in {}",
+                    problems.len(),
+                    target_dir.display()
+                );
+            }
+
+            println!("All {} asset(s) match the lockfile", lockfile.assets.len());
+        }
+        Command::FromMirror {
+            lockfile: lockfile_path,
+            mirror_dir,
+            target_dir,
+        } => {
+            let lockfile = AssetLockfile::read(&lockfile_path)?;
+
+            std::fs::create_dir_all(&target_dir)?;
+
+            let present_assets = determine_present_assets(&target_dir)?;
+
+            let mut failed_assets = Vec::new();
+
+            for locked_asset in lockfile
+                .assets
+                .iter()
+                .filter(|asset| !present_assets.contains(&asset.name))
+            {
+                if let Err(e) =
+                    providers::mirror::install_asset(locked_asset, &mirror_dir, &target_dir)
+                {
+                    eprintln!("Error installing asset '{}': {}", locked_asset.name, e);
+                    failed_assets.push(locked_asset.name.as_str());
+                }
+            }
+
+            if !failed_assets.is_empty() {
+                bail!(
+                    "Failed to install {} asset(s): {}",
+                    failed_assets.len(),
+                    failed_assets.join(", ")
+                );
+            }
         }
     }
 
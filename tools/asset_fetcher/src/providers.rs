@@ -2,6 +2,7 @@
 
 pub mod ambientcg;
 pub mod cgbookcase;
+pub mod mirror;
 pub mod polyhaven;
 
 use anyhow::Result;
@@ -47,4 +48,41 @@ impl AssetInfo {
             Self::PolyHaven(info) => info.get_downloads(),
         }
     }
+
+    /// Returns the name of the provider of this asset.
+    pub fn provider_name(&self) -> &'static str {
+        match self {
+            Self::AmbientCG(_) => "AmbientCG",
+            Self::CGBookcase(_) => "CGBookcase",
+            Self::PolyHaven(_) => "PolyHaven",
+        }
+    }
+
+    /// Returns the provider's ID for this asset.
+    pub fn id(&self) -> &str {
+        match self {
+            Self::AmbientCG(info) => info.id(),
+            Self::CGBookcase(info) => &info.id,
+            Self::PolyHaven(info) => info.id(),
+        }
+    }
+
+    /// Returns the resolution this asset is fetched in, using the provider's
+    /// naming.
+    pub fn resolution(&self) -> &'static str {
+        match self {
+            Self::AmbientCG(info) => info.resolution(),
+            Self::CGBookcase(info) => info.resolution.as_str(),
+            Self::PolyHaven(info) => info.resolution(),
+        }
+    }
+
+    /// Returns the SPDX identifier for the license of this asset.
+    pub fn license(&self) -> &'static str {
+        match self {
+            Self::AmbientCG(_) => ambientcg::LICENSE,
+            Self::CGBookcase(_) => cgbookcase::LICENSE,
+            Self::PolyHaven(_) => polyhaven::LICENSE,
+        }
+    }
 }
@@ -4,6 +4,9 @@ use crate::providers::AssetDownload;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
+/// SPDX identifier for the license of all AmbientCG assets.
+pub const LICENSE: &str = "CC0-1.0";
+
 /// Asset information specific to AmbientCG provider.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AssetInfo {
@@ -69,6 +72,20 @@ impl SurfaceAssetFormat {
 }
 
 impl AssetInfo {
+    /// Returns the AmbientCG ID of this asset.
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Surface(info) => &info.id,
+        }
+    }
+
+    /// Returns the resolution this asset is fetched in.
+    pub fn resolution(&self) -> &'static str {
+        match self {
+            Self::Surface(info) => info.resolution.as_str(),
+        }
+    }
+
     /// Gets all downloads required for this AmbientCG asset.
     ///
     /// AmbientCG provides assets as ZIP archives containing all texture maps,
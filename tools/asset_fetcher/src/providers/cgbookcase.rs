@@ -4,6 +4,9 @@ use crate::providers::AssetDownload;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
+/// SPDX identifier for the license of all CGBookcase assets.
+pub const LICENSE: &str = "CC0-1.0";
+
 /// Asset information specific to CGBookcase provider.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AssetInfo {
@@ -25,7 +28,7 @@ pub enum Resolution {
 }
 
 impl Resolution {
-    fn as_str(&self) -> &'static str {
+    pub(super) fn as_str(&self) -> &'static str {
         match self {
             Self::OneK => "1K",
             Self::TwoK => "2K",
@@ -0,0 +1,181 @@
+//! Local mirror provider implementation.
+//!
+//! A mirror is a local directory holding a copy of each asset, either as a
+//! directory or as a ZIP archive named after the asset. Assets are installed
+//! from a mirror based on a lockfile, so no network access is required.
+
+use crate::{fetch, lock::LockedAsset};
+use anyhow::{Context, Result, bail};
+use std::{fs, io, path::Path};
+
+/// Installs the given locked asset from the mirror directory into the target
+/// directory.
+///
+/// The asset is taken from `<mirror_dir>/<name>.zip` if it exists, and from
+/// `<mirror_dir>/<name>/` otherwise. The installed files are verified against
+/// the lockfile before being moved into `<target_dir>/<name>/`, so a failed
+/// installation leaves the target directory unchanged.
+pub fn install_asset(
+    locked_asset: &LockedAsset,
+    mirror_dir: &Path,
+    target_dir: &Path,
+) -> Result<()> {
+    let asset_dir = target_dir.join(&locked_asset.name);
+    if asset_dir.exists() {
+        bail!("Asset directory already exists: {}", asset_dir.display());
+    }
+
+    println!("Installing asset '{}' from mirror", locked_asset.name);
+
+    let staging_dir = tempfile::tempdir_in(target_dir)?;
+
+    let archive_path = mirror_dir.join(format!("{}.zip", locked_asset.name));
+    let mirrored_asset_dir = mirror_dir.join(&locked_asset.name);
+
+    if archive_path.is_file() {
+        let archive_file = io::BufReader::new(fs::File::open(&archive_path)?);
+        fetch::extract_zip_archive(archive_file, staging_dir.path())
+            .with_context(|| format!("Failed to extract {}", archive_path.display()))?;
+    } else if mirrored_asset_dir.is_dir() {
+        copy_dir_contents(&mirrored_asset_dir, staging_dir.path())?;
+    } else {
+        bail!(
+            "Asset '{}' not found in mirror {}",
+            locked_asset.name,
+            mirror_dir.display()
+        );
+    }
+
+    let problems = locked_asset.verify(staging_dir.path())?;
+    if !problems.is_empty() {
+        for problem in &problems {
+            eprintln!("{problem}");
+        }
+        bail!(
+            "Mirrored asset '{}' does not match the lockfile",
+            locked_asset.name
+        );
+    }
+
+    fs::rename(staging_dir.keep(), &asset_dir)?;
+
+    println!(
+        "Successfully installed asset '{}' ({} files)",
+        locked_asset.name,
+        locked_asset.files.len()
+    );
+    Ok(())
+}
+
+/// Recursively copies everything in the source directory into the target
+/// directory.
+fn copy_dir_contents(source_dir: &Path, target_dir: &Path) -> Result<()> {
+    for entry in fs::read_dir(source_dir)? {
+        let entry = entry?;
+        let target_path = target_dir.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            fs::create_dir_all(&target_path)?;
+            copy_dir_contents(&entry.path(), &target_path)?;
+        } else {
+            fs::copy(entry.path(), &target_path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        asset::Asset,
+        providers::{
+            AssetInfo,
+            polyhaven::{self, ImageFormat, TextureAssetInfo, TextureResolution},
+        },
+    };
+    use std::{io::Write, path::PathBuf};
+
+    fn testdata_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("src/providers/testdata")
+    }
+
+    fn lock_testdata_asset() -> LockedAsset {
+        let asset = Asset {
+            name: "polyhaven".to_string(),
+            info: AssetInfo::PolyHaven(polyhaven::AssetInfo::Texture(TextureAssetInfo {
+                id: "brick_wall_001".to_string(),
+                resolution: TextureResolution::TwoK,
+                format: ImageFormat::Jpg,
+                components: Vec::new(),
+            })),
+        };
+        LockedAsset::from_asset_dir(&asset, &testdata_dir().join("polyhaven")).unwrap()
+    }
+
+    fn write_testdata_archive(archive_path: &Path) {
+        let mut writer = zip::ZipWriter::new(fs::File::create(archive_path).unwrap());
+        for file_name in ["brick_wall_001.json", "moon_rock_01.json"] {
+            writer
+                .start_file(file_name, zip::write::FileOptions::default())
+                .unwrap();
+            writer
+                .write_all(&fs::read(testdata_dir().join("polyhaven").join(file_name)).unwrap())
+                .unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn test_install_asset_from_mirrored_directory() {
+        let target_dir = tempfile::tempdir().unwrap();
+        let locked_asset = lock_testdata_asset();
+
+        install_asset(&locked_asset, &testdata_dir(), target_dir.path()).unwrap();
+
+        assert!(
+            locked_asset
+                .verify(&target_dir.path().join("polyhaven"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_install_asset_from_mirrored_archive() {
+        let mirror_dir = tempfile::tempdir().unwrap();
+        let target_dir = tempfile::tempdir().unwrap();
+        let locked_asset = lock_testdata_asset();
+
+        write_testdata_archive(&mirror_dir.path().join("polyhaven.zip"));
+
+        install_asset(&locked_asset, mirror_dir.path(), target_dir.path()).unwrap();
+
+        assert!(
+            locked_asset
+                .verify(&target_dir.path().join("polyhaven"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_install_mismatching_asset_fails_without_installing() {
+        let target_dir = tempfile::tempdir().unwrap();
+        let mut locked_asset = lock_testdata_asset();
+        locked_asset.files[0].sha256 = "0".repeat(64);
+
+        assert!(install_asset(&locked_asset, &testdata_dir(), target_dir.path()).is_err());
+        assert_eq!(fs::read_dir(target_dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_install_asset_missing_from_mirror_fails() {
+        let mirror_dir = tempfile::tempdir().unwrap();
+        let target_dir = tempfile::tempdir().unwrap();
+
+        assert!(
+            install_asset(&lock_testdata_asset(), mirror_dir.path(), target_dir.path()).is_err()
+        );
+    }
+}
@@ -3,6 +3,9 @@
 use crate::{fetch, providers::AssetDownload};
 use anyhow::{Context, Result, bail};
 use serde::{Deserialize, Serialize};
+
+/// SPDX identifier for the license of all Poly Haven assets.
+pub const LICENSE: &str = "CC0-1.0";
 use std::io::Read;
 
 /// Asset information specific to Poly Haven provider.
@@ -182,6 +185,22 @@ impl AssetInfo {
         }
     }
 
+    /// Returns the Poly Haven ID of this asset.
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Texture(info) => &info.id,
+            Self::Model(info) => &info.id,
+        }
+    }
+
+    /// Returns the resolution this asset is fetched in.
+    pub fn resolution(&self) -> &'static str {
+        match self {
+            Self::Texture(info) => info.resolution.as_str(),
+            Self::Model(info) => info.resolution.as_str(),
+        }
+    }
+
     /// Helper method to extract TextureAssetInfo from AssetInfo for testing.
     #[cfg(test)]
     fn as_texture_info(&self) -> &TextureAssetInfo {